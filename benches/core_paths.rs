@@ -0,0 +1,225 @@
+//! Benchmarks for the state machine's hot paths.
+//!
+//! ```bash
+//! cargo bench --bench core_paths
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+
+use zkusd::core::cdp::{CDPManager, CDP};
+use zkusd::core::config::{ProtocolConfig, ProtocolParams};
+use zkusd::core::token::TokenAmount;
+use zkusd::core::vault::CollateralAmount;
+use zkusd::liquidation::{LiquidationEngine, StabilityPool};
+use zkusd::protocol::codec::{canonical_hash_with, CDP_DOMAIN};
+use zkusd::protocol::operations::{OpenCDPOp, Operation, ProtocolOperation, RedeemOp, UpdatePriceOp};
+use zkusd::protocol::signing::SigningDomain;
+use zkusd::protocol::state_machine::ProtocolStateMachine;
+use zkusd::storage::{InMemoryStore, StateManager};
+use zkusd::utils::constants::{BPS_DIVISOR, MIN_ORACLE_SOURCES, SATS_PER_BTC, SIGNATURE_LENGTH};
+use zkusd::utils::crypto::{verify_signature, Hash, HashScheme, KeyPair, PublicKey, Signature};
+use zkusd::utils::math::calculate_collateral_ratio;
+
+/// BTC price used throughout ($100,000)
+const PRICE: u64 = 10_000_000;
+
+/// CDPs in the sorted-iteration and redemption benchmarks
+const CDP_COUNT: usize = 10_000;
+
+/// Liquidatable CDPs per liquidation batch
+const LIQUIDATION_BATCH: usize = 100;
+
+fn blank() -> Signature {
+    Signature::new([0u8; SIGNATURE_LENGTH])
+}
+
+/// 1 BTC CDP whose debt spreads the ratios between 125% and ~500%
+fn cdp(owner: PublicKey, i: u64) -> CDP {
+    let mut cdp = CDP::with_collateral(owner, SATS_PER_BTC, i, 1).unwrap();
+    cdp.debt_cents = 2_000_000 + (i * 7_919) % 6_000_000;
+    cdp
+}
+
+fn cdp_manager(count: usize) -> CDPManager {
+    let owner = *KeyPair::generate().public_key();
+    let mut manager = CDPManager::new();
+    for i in 0..count as u64 {
+        manager.register(cdp(owner, i)).unwrap();
+    }
+    manager
+}
+
+fn bench_collateral_ratio(c: &mut Criterion) {
+    c.bench_function("calculate_collateral_ratio", |b| {
+        b.iter(|| calculate_collateral_ratio(black_box(150_000_000), black_box(PRICE), black_box(8_000_000)))
+    });
+}
+
+fn bench_sorted_cdps(c: &mut Criterion) {
+    let manager = cdp_manager(CDP_COUNT);
+    let mut group = c.benchmark_group("cdps");
+    group.throughput(Throughput::Elements(CDP_COUNT as u64));
+    group.bench_function("sorted_by_ratio_10k", |b| {
+        b.iter(|| manager.get_sorted_by_ratio(black_box(PRICE)).len())
+    });
+    group.finish();
+}
+
+/// Machine holding `CDP_COUNT` CDPs plus a well-collateralized redeemer
+fn redemption_machine() -> (ProtocolStateMachine<InMemoryStore>, KeyPair) {
+    let params = ProtocolParams::default()
+        .with_rate_limit(0, 1)
+        .with_min_intervals(0, 0)
+        .with_redemption_cap(0);
+    let mut machine = ProtocolStateMachine::new(InMemoryStore::new()).unwrap().with_params(params);
+    machine.begin_block(1, 1_700_000_000).unwrap();
+
+    let oracle = KeyPair::generate();
+    let mut price = UpdatePriceOp {
+        operator: *oracle.public_key(),
+        price_cents: PRICE,
+        source_count: MIN_ORACLE_SOURCES as u8,
+        confidence: 100,
+        confidence_interval: 0,
+        proof: Vec::new(),
+        nonce: 1,
+        signature: blank(),
+    };
+    price.sign(&oracle, &SigningDomain::default()).unwrap();
+    machine.execute(ProtocolOperation::UpdatePrice(price)).unwrap();
+
+    let mut open = |owner: &KeyPair, collateral: u64, debt: u64| {
+        let mut op = OpenCDPOp {
+            owner: *owner.public_key(),
+            collateral: CollateralAmount::from_sats(collateral),
+            initial_debt: Some(TokenAmount::from_cents(debt)),
+            nonce: 1,
+            signature: blank(),
+        };
+        op.sign(owner, &SigningDomain::default()).unwrap();
+        machine.execute(ProtocolOperation::OpenCDP(op)).unwrap();
+    };
+
+    let redeemer = KeyPair::generate();
+    open(&redeemer, 100 * SATS_PER_BTC, 200_000_000);
+    for i in 0..CDP_COUNT as u64 {
+        open(&KeyPair::generate(), SATS_PER_BTC, 2_000_000 + (i * 7_919) % 6_000_000);
+    }
+
+    (machine, redeemer)
+}
+
+fn bench_redemption(c: &mut Criterion) {
+    let (mut machine, redeemer) = redemption_machine();
+    let mut nonce = 1;
+
+    let mut group = c.benchmark_group("redemption");
+    group.sample_size(20);
+    group.bench_function("redeem_over_10k_cdps", |b| {
+        b.iter(|| {
+            nonce += 1;
+            let mut op = RedeemOp {
+                redeemer: *redeemer.public_key(),
+                amount: TokenAmount::from_cents(1_000),
+                max_fee_bps: BPS_DIVISOR,
+                first_cdp_hint: None,
+                last_cdp_hint: None,
+                max_cdps: 0,
+                nonce,
+                signature: blank(),
+            };
+            op.sign(&redeemer, &SigningDomain::default()).unwrap();
+            machine.execute(ProtocolOperation::Redeem(op)).unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn bench_liquidation_batch(c: &mut Criterion) {
+    // Half the CDPs fall below the MCR once the price halves
+    let manager = cdp_manager(2 * LIQUIDATION_BATCH);
+    let config = ProtocolConfig::new(ProtocolParams::default());
+    let liquidator = *KeyPair::generate().public_key();
+    let crash_price = PRICE / 2;
+
+    let mut group = c.benchmark_group("liquidation");
+    group.throughput(Throughput::Elements(LIQUIDATION_BATCH as u64));
+    group.bench_function("batch_100", |b| {
+        b.iter_batched(
+            || (manager.clone(), StabilityPool::new(), LiquidationEngine::new()),
+            |(mut manager, mut pool, mut engine)| {
+                engine
+                    .liquidate_batch(
+                        &mut manager,
+                        &mut pool,
+                        &config,
+                        crash_price,
+                        liquidator,
+                        2,
+                        Hash::sha256(b"liquidation"),
+                        LIQUIDATION_BATCH,
+                    )
+                    .unwrap()
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn bench_signatures(c: &mut Criterion) {
+    let keypair = KeyPair::generate();
+    let message = Hash::sha256(b"zkusd operation");
+    let signature = keypair.sign(&message);
+
+    let mut group = c.benchmark_group("signatures");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("sign", |b| b.iter(|| keypair.sign(black_box(&message))));
+    group.bench_function("verify", |b| {
+        b.iter(|| verify_signature(keypair.public_key(), black_box(&message), black_box(&signature)))
+    });
+    group.finish();
+}
+
+/// State root over `CDP_COUNT` CDPs under each hash scheme: the hashing
+/// alone, then including the loads from storage
+fn bench_state_root(c: &mut Criterion) {
+    let manager = StateManager::new(InMemoryStore::new());
+    manager.initialize_if_needed().unwrap();
+    let owner = *KeyPair::generate().public_key();
+    for i in 0..CDP_COUNT as u64 {
+        manager.save_cdp(&cdp(owner, i)).unwrap();
+    }
+    let cdps = manager.load_all_cdps().unwrap();
+
+    let mut group = c.benchmark_group("state_root");
+    group.throughput(Throughput::Elements(CDP_COUNT as u64));
+    for scheme in [HashScheme::Sha256, HashScheme::Blake3] {
+        group.bench_function(format!("tree_10k_{}", scheme), |b| {
+            b.iter(|| {
+                let leaves: Vec<Hash> = cdps
+                    .iter()
+                    .map(|cdp| canonical_hash_with(scheme, CDP_DOMAIN, cdp).unwrap())
+                    .collect();
+                scheme.merkle_root(black_box(&leaves))
+            })
+        });
+
+        manager.save_hash_scheme(scheme).unwrap();
+        group.bench_function(format!("compute_10k_{}", scheme), |b| {
+            b.iter(|| manager.compute_state_root().unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_collateral_ratio,
+    bench_sorted_cdps,
+    bench_redemption,
+    bench_liquidation_batch,
+    bench_signatures,
+    bench_state_root
+);
+criterion_main!(benches);
@@ -0,0 +1,99 @@
+//! RocksDB persistence benchmarks: per-write vs per-block batched CDP
+//! writes, and saving and loading a 100k-CDP book.
+//!
+//! ```bash
+//! cargo bench --features rocksdb-storage --bench storage
+//! ```
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+
+use zkusd::core::cdp::CDP;
+use zkusd::storage::{RocksStore, StateManager};
+use zkusd::utils::crypto::KeyPair;
+
+/// CDP writes per simulated block
+const WRITES_PER_BLOCK: usize = 1_000;
+
+/// CDPs in the save/load benchmarks
+const BOOK_SIZE: usize = 100_000;
+
+fn cdps(count: usize) -> Vec<CDP> {
+    let owner = *KeyPair::generate().public_key();
+    (0..count as u64)
+        .map(|i| CDP::with_collateral(owner, 100_000_000 + i, i, 100).unwrap())
+        .collect()
+}
+
+fn open_manager() -> (tempfile::TempDir, StateManager<RocksStore>) {
+    let dir = tempfile::tempdir().unwrap();
+    let store = RocksStore::open_default(dir.path()).unwrap();
+    (dir, StateManager::new(store))
+}
+
+fn bench_cdp_writes(c: &mut Criterion) {
+    let cdps = cdps(WRITES_PER_BLOCK);
+    let mut group = c.benchmark_group("cdp_writes");
+    group.throughput(Throughput::Elements(WRITES_PER_BLOCK as u64));
+
+    group.bench_function("individual", |b| {
+        b.iter_batched(
+            open_manager,
+            |(_dir, manager)| {
+                for cdp in &cdps {
+                    manager.save_cdp(cdp).unwrap();
+                }
+            },
+            BatchSize::PerIteration,
+        )
+    });
+
+    group.bench_function("batched", |b| {
+        b.iter_batched(
+            open_manager,
+            |(_dir, manager)| {
+                manager.begin_batch().unwrap();
+                for cdp in &cdps {
+                    manager.save_cdp(cdp).unwrap();
+                }
+                manager.commit_batch().unwrap();
+            },
+            BatchSize::PerIteration,
+        )
+    });
+
+    group.finish();
+}
+
+fn bench_cdp_book(c: &mut Criterion) {
+    let cdps = cdps(BOOK_SIZE);
+    let mut group = c.benchmark_group("cdp_book_100k");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(BOOK_SIZE as u64));
+
+    group.bench_function("save", |b| {
+        b.iter_batched(
+            open_manager,
+            |(_dir, manager)| {
+                manager.begin_batch().unwrap();
+                for cdp in &cdps {
+                    manager.save_cdp(cdp).unwrap();
+                }
+                manager.commit_batch().unwrap();
+            },
+            BatchSize::PerIteration,
+        )
+    });
+
+    let (_dir, manager) = open_manager();
+    manager.begin_batch().unwrap();
+    for cdp in &cdps {
+        manager.save_cdp(cdp).unwrap();
+    }
+    manager.commit_batch().unwrap();
+
+    group.bench_function("load", |b| b.iter(|| manager.load_all_cdps().unwrap().len()));
+    group.finish();
+}
+
+criterion_group!(benches, bench_cdp_writes, bench_cdp_book);
+criterion_main!(benches);
@@ -0,0 +1,11 @@
+//! Build script.
+//!
+//! With the `guest-elfs` feature, compiles the SP1 guest programs in
+//! `guest/` (one binary per circuit) for the zkVM target and exposes each
+//! ELF as `SP1_ELF_<circuit_id>` for `EMBEDDED_ELFS`. Requires the SP1
+//! toolchain (`sp1up`). Without the feature this is a no-op.
+
+fn main() {
+    #[cfg(feature = "guest-elfs")]
+    sp1_build::build_program_with_args("guest", sp1_build::BuildArgs::default());
+}
@@ -0,0 +1,16 @@
+//! Decode arbitrary bytes as a protocol operation.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zkusd::protocol::operations::ProtocolOperation;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(op) = ProtocolOperation::decode(data) {
+        let _ = op.operation_type();
+        let _ = op.signer();
+        let _ = op.nonce();
+        let encoded = op.encode().expect("decoded operation re-encodes");
+        assert!(ProtocolOperation::decode(&encoded).is_ok());
+    }
+});
@@ -0,0 +1,15 @@
+//! Encode structurally valid operations and check they decode unchanged.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zkusd::protocol::operations::ProtocolOperation;
+
+fuzz_target!(|op: ProtocolOperation| {
+    let Ok(encoded) = op.encode() else { return };
+    match ProtocolOperation::decode(&encoded) {
+        Ok(decoded) => assert_eq!(decoded.encode().ok(), Some(encoded)),
+        // Only operations over the size limit may fail to decode
+        Err(_) => assert!(encoded.len() > zkusd::utils::constants::MAX_OPERATION_SIZE),
+    }
+});
@@ -0,0 +1,22 @@
+//! Decode arbitrary bytes as every spell parameter type.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zkusd::charms::spells::{ClaimGainsParams, RedeemParams, TransferParams};
+
+fuzz_target!(|data: &[u8]| {
+    // Keys decode from hex in either case, so compare re-encodings only
+    if let Ok(params) = TransferParams::decode(data) {
+        let encoded = params.encode();
+        assert_eq!(TransferParams::decode(&encoded).unwrap().encode(), encoded);
+    }
+    if let Ok(params) = RedeemParams::decode(data) {
+        let encoded = params.encode();
+        assert_eq!(RedeemParams::decode(&encoded).unwrap().encode(), encoded);
+    }
+    if let Ok(params) = ClaimGainsParams::decode(data) {
+        let encoded = params.encode();
+        assert_eq!(ClaimGainsParams::decode(&encoded).unwrap().encode(), encoded);
+    }
+});
@@ -0,0 +1,14 @@
+//! Decode arbitrary bytes as a spell and check it re-encodes losslessly.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zkusd::charms::spells::CharmSpell;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(spell) = CharmSpell::decode(data) {
+        let encoded = spell.encode().expect("decoded spell re-encodes");
+        let again = CharmSpell::decode(&encoded).expect("re-encoded spell decodes");
+        assert_eq!(again.hash(), spell.hash());
+    }
+});
@@ -0,0 +1,455 @@
+//! zkUSD Explorer
+//!
+//! Read-only HTTP UI and JSON API over a node's data directory, for browsing
+//! CDPs, events, transactions and prices during support and audits without
+//! exposing the operational node. The database is opened read-only, so the
+//! explorer runs alongside the node that writes it, and is reopened
+//! periodically to pick up new blocks.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tower_http::{
+    compression::CompressionLayer,
+    cors::{Any, CorsLayer},
+    trace::TraceLayer,
+};
+use tracing::{info, warn};
+
+use zkusd::core::cdp::{CDP, CDPId, CDPStatus};
+use zkusd::storage::backend::BinaryStore;
+use zkusd::storage::state::{StateManager, TransactionRecord, SCHEMA_VERSION};
+use zkusd::utils::crypto::{Hash, PublicKey};
+use zkusd::utils::logging;
+
+/// Seconds between reopening the database by default
+const DEFAULT_REFRESH_SECS: u64 = 10;
+
+/// Most blocks of events returned by one query
+const MAX_EVENT_BLOCKS: u64 = 1_000;
+
+/// Page size when a query doesn't name one
+const DEFAULT_LIMIT: usize = 100;
+
+/// Largest page size
+const MAX_LIMIT: usize = 1_000;
+
+/// Rows per table on the HTML overview
+const OVERVIEW_ROWS: usize = 25;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// EXPLORER STATE
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Read-only view of a node's database
+pub struct Explorer {
+    /// Database directory
+    db_dir: PathBuf,
+    /// Store as of the last refresh
+    manager: RwLock<StateManager<BinaryStore>>,
+}
+
+impl Explorer {
+    /// Open the database in `db_dir` read-only
+    pub fn open(db_dir: PathBuf) -> zkusd::error::Result<Self> {
+        let manager = Self::open_manager(&db_dir)?;
+        Ok(Self {
+            db_dir,
+            manager: RwLock::new(manager),
+        })
+    }
+
+    fn open_manager(db_dir: &std::path::Path) -> zkusd::error::Result<StateManager<BinaryStore>> {
+        let manager = StateManager::new(BinaryStore::open_read_only(db_dir)?);
+        if let Some(version) = manager.schema_version()? {
+            if version > SCHEMA_VERSION {
+                return Err(zkusd::error::Error::UnsupportedSchemaVersion {
+                    found: version,
+                    supported: SCHEMA_VERSION,
+                });
+            }
+        }
+        Ok(manager)
+    }
+
+    /// Reopen the database to pick up blocks written since the last refresh
+    pub async fn refresh(&self) -> zkusd::error::Result<()> {
+        let manager = Self::open_manager(&self.db_dir)?;
+        *self.manager.write().await = manager;
+        Ok(())
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// API TYPES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Serialize)]
+pub struct ApiResponse<T> {
+    pub success: bool,
+    pub data: Option<T>,
+    pub error: Option<String>,
+}
+
+impl<T: Serialize> ApiResponse<T> {
+    pub fn ok(data: T) -> Self {
+        Self { success: true, data: Some(data), error: None }
+    }
+
+    pub fn err(msg: impl Into<String>) -> Self {
+        Self { success: false, data: None, error: Some(msg.into()) }
+    }
+}
+
+/// JSON response for a lookup, mapping errors to status codes
+fn respond<T: Serialize>(result: zkusd::error::Result<Option<T>>, what: &str) -> Response {
+    match result {
+        Ok(Some(data)) => (StatusCode::OK, Json(ApiResponse::ok(data))).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::err(format!("{} not found", what)))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::err(e.to_string()))).into_response(),
+    }
+}
+
+fn bad_request(msg: impl Into<String>) -> Response {
+    (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::err(msg))).into_response()
+}
+
+/// Page of results
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    /// Matching items before paging
+    pub total: usize,
+    pub offset: usize,
+    pub items: Vec<T>,
+}
+
+impl<T> Page<T> {
+    fn of(items: Vec<T>, offset: usize, limit: Option<usize>) -> Self {
+        let total = items.len();
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+        Self {
+            total,
+            offset,
+            items: items.into_iter().skip(offset).take(limit).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CdpQuery {
+    /// Owner public key (hex)
+    pub owner: Option<String>,
+    pub status: Option<CDPStatus>,
+    #[serde(default)]
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CdpDetail {
+    pub cdp: CDP,
+    /// Heights with a stored version of the CDP, when history is kept
+    pub versions: Vec<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EventQuery {
+    pub from: u64,
+    pub to: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TxQuery {
+    /// Account public key (hex)
+    pub account: Option<String>,
+    /// CDP ID (hex)
+    pub cdp: Option<String>,
+    #[serde(default)]
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PriceQuery {
+    #[serde(default)]
+    pub from: u64,
+    pub to: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PriceInfo {
+    /// Latest BTC price in cents and its timestamp
+    pub latest: Option<(u64, u64)>,
+    /// `(timestamp, price_cents)` history, oldest first
+    pub history: Vec<(u64, u64)>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// HANDLERS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// GET /health - Health check
+async fn health_check() -> impl IntoResponse {
+    Json(serde_json::json!({ "status": "healthy", "version": zkusd::VERSION }))
+}
+
+/// GET /api/status - Protocol state and state root
+async fn get_status(State(explorer): State<Arc<Explorer>>) -> Response {
+    let manager = explorer.manager.read().await;
+    let status = manager.load_protocol_state().and_then(|state| {
+        Ok(Some(serde_json::json!({
+            "state": state,
+            "schema_version": manager.schema_version()?,
+            "state_root": manager.compute_state_root()?.to_hex(),
+        })))
+    });
+    respond(status, "Protocol state")
+}
+
+/// GET /api/cdps - CDPs, optionally by owner and status
+async fn list_cdps(State(explorer): State<Arc<Explorer>>, Query(query): Query<CdpQuery>) -> Response {
+    let owner = match query.owner.as_deref().map(PublicKey::from_hex).transpose() {
+        Ok(owner) => owner,
+        Err(e) => return bad_request(format!("owner: {}", e)),
+    };
+
+    let manager = explorer.manager.read().await;
+    let cdps = match owner {
+        Some(owner) => manager.load_cdps_by_owner(&owner),
+        None => manager.load_all_cdps(),
+    };
+    let page = cdps.map(|mut cdps| {
+        cdps.retain(|cdp| query.status.is_none_or(|status| cdp.status == status));
+        cdps.sort_by_key(|cdp| (cdp.created_at, *cdp.id.as_bytes()));
+        Some(Page::of(cdps, query.offset, query.limit))
+    });
+    respond(page, "CDPs")
+}
+
+/// GET /api/cdp/:id - A CDP and its stored versions
+async fn get_cdp(State(explorer): State<Arc<Explorer>>, Path(id): Path<String>) -> Response {
+    let id = match CDPId::from_hex(&id) {
+        Ok(id) => id,
+        Err(e) => return bad_request(format!("id: {}", e)),
+    };
+
+    let manager = explorer.manager.read().await;
+    let detail = manager.load_cdp(&id).and_then(|cdp| match cdp {
+        Some(cdp) => Ok(Some(CdpDetail { cdp, versions: manager.cdp_version_heights(&id)? })),
+        None => Ok(None),
+    });
+    respond(detail, "CDP")
+}
+
+/// GET /api/cdp/:id/at/:height - A CDP as of a block height
+async fn get_cdp_at(State(explorer): State<Arc<Explorer>>, Path((id, height)): Path<(String, u64)>) -> Response {
+    let id = match CDPId::from_hex(&id) {
+        Ok(id) => id,
+        Err(e) => return bad_request(format!("id: {}", e)),
+    };
+    respond(explorer.manager.read().await.load_cdp_at(&id, height), "CDP version")
+}
+
+/// GET /api/events?from=&to= - Events of a block range
+async fn get_events(State(explorer): State<Arc<Explorer>>, Query(query): Query<EventQuery>) -> Response {
+    let to = query.to.unwrap_or(query.from);
+    if to < query.from || to - query.from >= MAX_EVENT_BLOCKS {
+        return bad_request(format!("block range must be ascending and at most {} blocks", MAX_EVENT_BLOCKS));
+    }
+    respond(explorer.manager.read().await.load_events_range(query.from, to).map(Some), "Events")
+}
+
+/// GET /api/transactions - Transactions, newest first
+async fn list_transactions(State(explorer): State<Arc<Explorer>>, Query(query): Query<TxQuery>) -> Response {
+    let account = match query.account.as_deref().map(PublicKey::from_hex).transpose() {
+        Ok(account) => account,
+        Err(e) => return bad_request(format!("account: {}", e)),
+    };
+    let cdp = match query.cdp.as_deref().map(CDPId::from_hex).transpose() {
+        Ok(cdp) => cdp,
+        Err(e) => return bad_request(format!("cdp: {}", e)),
+    };
+
+    let txs = explorer.manager.read().await.load_all_transactions().map(|txs| {
+        let txs: Vec<TransactionRecord> = txs
+            .into_iter()
+            .rev()
+            .filter(|tx| account.is_none_or(|account| tx.account == account))
+            .filter(|tx| cdp.is_none_or(|cdp| tx.cdp_id == Some(cdp)))
+            .collect();
+        Some(Page::of(txs, query.offset, query.limit))
+    });
+    respond(txs, "Transactions")
+}
+
+/// GET /api/tx/:hash - A transaction by hash
+async fn get_transaction(State(explorer): State<Arc<Explorer>>, Path(hash): Path<String>) -> Response {
+    let hash = match Hash::from_hex(&hash) {
+        Ok(hash) => hash,
+        Err(e) => return bad_request(format!("hash: {}", e)),
+    };
+    respond(explorer.manager.read().await.load_transaction(&hash), "Transaction")
+}
+
+/// GET /api/prices?from=&to= - Latest price and history by timestamp
+async fn get_prices(State(explorer): State<Arc<Explorer>>, Query(query): Query<PriceQuery>) -> Response {
+    let manager = explorer.manager.read().await;
+    let prices = manager.load_price().and_then(|latest| {
+        Ok(Some(PriceInfo {
+            latest,
+            history: manager.load_price_history(query.from, query.to.unwrap_or(u64::MAX))?,
+        }))
+    });
+    respond(prices, "Prices")
+}
+
+/// GET /api/price-pairs - Cross-rate feeds
+async fn get_price_pairs(State(explorer): State<Arc<Explorer>>) -> Response {
+    respond(explorer.manager.read().await.load_price_pairs(), "Price pairs")
+}
+
+/// GET / - HTML overview
+async fn index(State(explorer): State<Arc<Explorer>>) -> Response {
+    match render_index(&*explorer.manager.read().await) {
+        Ok(html) => Html(html).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+fn render_index(manager: &StateManager<BinaryStore>) -> zkusd::error::Result<String> {
+    let dollars = |cents: u64| format!("${}.{:02}", cents / 100, cents % 100);
+
+    let mut html = String::from(
+        "<!doctype html><html><head><meta charset=\"utf-8\"><title>zkUSD Explorer</title>\
+         <style>body{font-family:monospace;margin:2em}table{border-collapse:collapse;margin-bottom:2em}\
+         td,th{border:1px solid #ccc;padding:4px 8px;text-align:right}</style></head><body><h1>zkUSD Explorer</h1>",
+    );
+
+    match manager.load_protocol_state() {
+        Ok(state) => html.push_str(&format!(
+            "<p>Block {} &middot; {} active CDPs &middot; supply {} &middot; collateral {} sats</p>",
+            state.block_height,
+            state.active_cdps,
+            dollars(state.total_supply),
+            state.total_collateral
+        )),
+        Err(_) => html.push_str("<p>No protocol state stored yet</p>"),
+    }
+    if let Some((price, timestamp)) = manager.load_price()? {
+        html.push_str(&format!("<p>BTC {} at {}</p>", dollars(price), timestamp));
+    }
+
+    let mut cdps = manager.load_active_cdps()?;
+    cdps.sort_by_key(|cdp| std::cmp::Reverse(cdp.debt_cents));
+    html.push_str("<h2>Largest active CDPs</h2><table><tr><th>ID</th><th>Collateral (sats)</th><th>Debt</th><th>Status</th></tr>");
+    for cdp in cdps.iter().take(OVERVIEW_ROWS) {
+        html.push_str(&format!(
+            "<tr><td><a href=\"/api/cdp/{id}\">{id}</a></td><td>{}</td><td>{}</td><td>{:?}</td></tr>",
+            cdp.collateral_sats,
+            dollars(cdp.debt_cents),
+            cdp.status,
+            id = cdp.id.to_hex()
+        ));
+    }
+    html.push_str("</table>");
+
+    let txs = manager.load_all_transactions()?;
+    html.push_str("<h2>Recent transactions</h2><table><tr><th>Hash</th><th>Type</th><th>Amount</th><th>Block</th></tr>");
+    for tx in txs.iter().rev().take(OVERVIEW_ROWS) {
+        html.push_str(&format!(
+            "<tr><td><a href=\"/api/tx/{hash}\">{hash}</a></td><td>{:?}</td><td>{}</td><td>{}</td></tr>",
+            tx.tx_type,
+            tx.amount,
+            tx.block_height,
+            hash = tx.hash.to_hex()
+        ));
+    }
+    html.push_str(
+        "</table><p>JSON API: <a href=\"/api/status\">/api/status</a> &middot; <a href=\"/api/cdps\">/api/cdps</a> \
+         &middot; <a href=\"/api/transactions\">/api/transactions</a> &middot; <a href=\"/api/prices\">/api/prices</a> \
+         &middot; /api/events?from=&amp;to=</p></body></html>",
+    );
+    Ok(html)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// MAIN
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[tokio::main]
+async fn main() {
+    // Initialize tracing (ZKUSD_LOG_FORMAT=json for structured logs)
+    let log_format = std::env::var("ZKUSD_LOG_FORMAT")
+        .ok()
+        .and_then(|format| format.parse().ok())
+        .unwrap_or_default();
+    logging::init(log_format, tracing::Level::INFO);
+
+    let data_dir: PathBuf = std::env::var("ZKUSD_DATA_DIR")
+        .expect("ZKUSD_DATA_DIR must point at a node's data directory")
+        .into();
+    let explorer = Arc::new(Explorer::open(data_dir.join("db")).expect("Failed to open database"));
+
+    let refresh_secs: u64 = std::env::var("ZKUSD_EXPLORER_REFRESH_SECS")
+        .map(|secs| secs.parse().expect("Invalid ZKUSD_EXPLORER_REFRESH_SECS"))
+        .unwrap_or(DEFAULT_REFRESH_SECS);
+    {
+        let explorer = explorer.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(refresh_secs.max(1)));
+            loop {
+                interval.tick().await;
+                if let Err(e) = explorer.refresh().await {
+                    warn!("Keeping previous view, refresh failed: {}", e);
+                }
+            }
+        });
+    }
+
+    let app = Router::new()
+        .route("/", get(index))
+        .route("/health", get(health_check))
+        .route("/api/status", get(get_status))
+        .route("/api/cdps", get(list_cdps))
+        .route("/api/cdp/:id", get(get_cdp))
+        .route("/api/cdp/:id/at/:height", get(get_cdp_at))
+        .route("/api/events", get(get_events))
+        .route("/api/transactions", get(list_transactions))
+        .route("/api/tx/:hash", get(get_transaction))
+        .route("/api/prices", get(get_prices))
+        .route("/api/price-pairs", get(get_price_pairs))
+        .layer(TraceLayer::new_for_http())
+        .layer(CompressionLayer::new())
+        .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any).allow_headers(Any))
+        .with_state(explorer);
+
+    let addr: SocketAddr = std::env::var("ZKUSD_EXPLORER_BIND")
+        .unwrap_or_else(|_| "127.0.0.1:8090".to_string())
+        .parse()
+        .expect("Invalid bind address");
+
+    info!("Starting zkUSD explorer on {} over {}", addr, data_dir.display());
+    info!("  GET  /                        - HTML overview");
+    info!("  GET  /api/status              - Protocol state and state root");
+    info!("  GET  /api/cdps                - CDPs (?owner=&status=&offset=&limit=)");
+    info!("  GET  /api/cdp/:id             - CDP and stored versions");
+    info!("  GET  /api/cdp/:id/at/:height  - CDP as of a block");
+    info!("  GET  /api/events              - Events (?from=&to=)");
+    info!("  GET  /api/transactions        - Transactions (?account=&cdp=&offset=&limit=)");
+    info!("  GET  /api/tx/:hash            - Transaction");
+    info!("  GET  /api/prices              - Price history (?from=&to=)");
+    info!("  GET  /api/price-pairs         - Cross-rate feeds");
+
+    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    axum::serve(listener, app).await.unwrap();
+}
@@ -2,33 +2,56 @@
 //!
 //! Production-grade HTTP/JSON-RPC server for the zkUSD protocol.
 
+use std::collections::{HashMap, VecDeque};
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::IntoResponse,
+    body::{Body, Bytes},
+    extract::{ConnectInfo, DefaultBodyLimit, FromRequest, FromRequestParts, Path, Query, State},
+    http::{header, request::Parts, Request, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use tokio::sync::RwLock;
 use tower_http::{
     compression::CompressionLayer,
     cors::{Any, CorsLayer},
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
     trace::TraceLayer,
 };
 use tracing::{info, warn};
 
 use zkusd::core::cdp::{CDP, CDPId, CDPManager, CDPStatus};
-use zkusd::core::config::ProtocolConfig;
+use zkusd::core::config::{Network, ProtocolConfig, ProtocolParameter};
+use zkusd::core::risk::{assess, RiskAssumptions, RiskReport};
+use zkusd::core::savings::SavingsPot;
 use zkusd::core::token::{TokenAmount, ZkUSD};
 use zkusd::core::vault::{CollateralAmount, Vault};
+use zkusd::liquidation::engine::{AuctionHouse, LiquidationEngine};
 use zkusd::liquidation::stability_pool::StabilityPool;
+use zkusd::monitoring::{AlertBook, DashboardFeed, RiskSnapshot, VolatilityEstimator};
 use zkusd::oracle::price_feed::PriceFeed;
-use zkusd::storage::backend::InMemoryStore;
-use zkusd::utils::crypto::{Hash, PublicKey};
+use zkusd::oracle::{OracleOperator, OracleRegistry};
+use zkusd::storage::backend::{BinaryStore, InMemoryStore};
+use zkusd::storage::backup::{BackupManager, BackupManifest};
+use zkusd::storage::state::{CdpProof, ProtocolState, PruneStats, PruningMode, StateManager};
+use zkusd::protocol::invariants::{InvariantChecker, InvariantContext};
+use zkusd::protocol::nonces::NonceManager;
+use zkusd::protocol::operations::{Operation, UpdatePriceOp};
+use zkusd::protocol::signing::SigningDomain;
+use zkusd::protocol::state_machine::ParameterChangePreview;
+use zkusd::protocol::sync::{SyncSource, SYNC_BATCH_BLOCKS};
+use zkusd::protocol::treasury::Treasury;
+use zkusd::protocol::view::{ProtocolSnapshotView, SnapshotHandle};
+use zkusd::utils::constants::{CDP_ID_LENGTH, PUBKEY_LENGTH, SIGNATURE_LENGTH};
+use zkusd::utils::crypto::{verify_signature, Hash, PublicKey};
+use zkusd::utils::logging;
+use zkusd::utils::math::calculate_collateral_ratio;
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // SERVER STATE
@@ -36,28 +59,89 @@ use zkusd::utils::crypto::{Hash, PublicKey};
 
 /// Shared application state
 pub struct AppState {
-    pub config: ProtocolConfig,
+    pub config: RwLock<ProtocolConfig>,
+    /// Network the server runs on; parameter overrides are refused on mainnet
+    pub network: Network,
     pub cdp_manager: RwLock<CDPManager>,
     pub token: RwLock<ZkUSD>,
     pub vault: RwLock<Vault>,
     pub stability_pool: RwLock<StabilityPool>,
+    pub savings: RwLock<SavingsPot>,
     pub price_feed: RwLock<PriceFeed>,
     pub block_height: RwLock<u64>,
+    /// Read view of the last completed block; CDP and balance queries are
+    /// served from it without taking the component locks
+    pub view: SnapshotHandle,
+    /// Alerts raised from the dashboard feed at each block
+    pub alerts: RwLock<AlertBook>,
+    /// Bearer token for the admin namespace; `None` disables it
+    pub admin_token: Option<String>,
+    /// Admin calls, oldest first, capped at [`MAX_AUDIT_EVENTS`]
+    pub audit_log: RwLock<VecDeque<AdminAuditEvent>>,
+    /// Directory holding the `db` and `backups` used by admin backups
+    /// and pruning
+    pub data_dir: Option<PathBuf>,
+    /// Per-client quotas for the public namespace
+    pub rate_limiter: RwLock<ApiRateLimiter>,
+    /// Operators allowed to submit signed price updates; empty refuses them all
+    pub oracle_registry: RwLock<OracleRegistry>,
+    /// Nonces of accepted signed price updates, so none can be replayed
+    pub oracle_nonces: RwLock<NonceManager>,
 }
 
 impl AppState {
     pub fn new() -> Self {
         Self {
-            config: ProtocolConfig::default(),
+            config: RwLock::new(ProtocolConfig::default()),
+            network: Network::Mainnet,
             cdp_manager: RwLock::new(CDPManager::new()),
             token: RwLock::new(ZkUSD::new()),
             vault: RwLock::new(Vault::new()),
             stability_pool: RwLock::new(StabilityPool::new()),
+            savings: RwLock::new(SavingsPot::new()),
             price_feed: RwLock::new(PriceFeed::new()),
             block_height: RwLock::new(0),
+            view: SnapshotHandle::new(),
+            alerts: RwLock::new(AlertBook::new()),
+            admin_token: None,
+            audit_log: RwLock::new(VecDeque::new()),
+            data_dir: None,
+            rate_limiter: RwLock::new(ApiRateLimiter::new()),
+            oracle_registry: RwLock::new(OracleRegistry::new()),
+            oracle_nonces: RwLock::new(NonceManager::new()),
         }
     }
 
+    /// Set the network
+    pub fn with_network(mut self, network: Network) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Enable the admin namespace with a bearer token
+    pub fn with_admin_token(mut self, token: impl Into<String>) -> Self {
+        self.admin_token = Some(token.into());
+        self
+    }
+
+    /// Set the data directory used by admin backups and pruning
+    pub fn with_data_dir(mut self, data_dir: impl Into<PathBuf>) -> Self {
+        self.data_dir = Some(data_dir.into());
+        self
+    }
+
+    /// Set the rate limiter for the public namespace
+    pub fn with_rate_limiter(mut self, limiter: ApiRateLimiter) -> Self {
+        self.rate_limiter = RwLock::new(limiter);
+        self
+    }
+
+    /// Set the operators allowed to submit signed price updates
+    pub fn with_oracle_registry(mut self, registry: OracleRegistry) -> Self {
+        self.oracle_registry = RwLock::new(registry);
+        self
+    }
+
     pub async fn get_btc_price(&self) -> u64 {
         self.price_feed.read().await.price_cents()
     }
@@ -65,6 +149,49 @@ impl AppState {
     pub async fn current_block(&self) -> u64 {
         *self.block_height.read().await
     }
+
+    pub async fn is_paused(&self) -> bool {
+        self.config.read().await.paused
+    }
+
+    /// Raise or clear alerts from the current state
+    pub async fn refresh_alerts(&self) {
+        let feed = {
+            let cdp_manager = self.cdp_manager.read().await;
+            let pool = self.stability_pool.read().await;
+            let config = self.config.read().await;
+            DashboardFeed::collect(
+                &cdp_manager,
+                self.get_btc_price().await,
+                config.effective_mcr(),
+                config.params.critical_collateral_ratio,
+                pool.total_deposits(),
+                self.current_block().await,
+                Vec::new(),
+            )
+        };
+        self.alerts.write().await.update(&feed);
+    }
+
+    /// Publish the current component state as the read view
+    pub async fn publish_view(&self) {
+        let cdp_manager = self.cdp_manager.read().await;
+        let token = self.token.read().await;
+        let vault = self.vault.read().await;
+        let pool = self.stability_pool.read().await;
+
+        let mut view = ProtocolSnapshotView::new(cdp_manager.clone(), token.all_balances().clone());
+        view.block_height = self.current_block().await;
+        view.btc_price = self.get_btc_price().await;
+        view.total_supply = token.total_supply();
+        view.total_collateral = vault.total_collateral();
+        view.total_debt = cdp_manager.all_cdps().iter().map(|cdp| cdp.debt_cents).sum();
+        view.pool_deposits = pool.total_deposits();
+        let config = self.config.read().await;
+        view.min_collateral_ratio = config.effective_mcr();
+        view.paused = config.paused;
+        self.view.publish(view);
+    }
 }
 
 impl Default for AppState {
@@ -103,6 +230,8 @@ pub struct ProtocolStatus {
     pub total_collateral_sats: u64,
     pub active_cdps: u64,
     pub stability_pool_deposits_cents: u64,
+    pub savings_locked_cents: u64,
+    pub treasury_balance_cents: u64,
     pub min_collateral_ratio: u64,
     pub recovery_mode: bool,
 }
@@ -141,6 +270,23 @@ pub struct OpenCDPRequest {
     pub debt_cents: Option<u64>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RiskQuery {
+    pub drift_bps: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LiquidationPreviewQuery {
+    /// BTC price to preview at, in cents (defaults to the current price)
+    pub price_cents: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SyncBlocksQuery {
+    pub from: u64,
+    pub limit: Option<usize>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct DepositCollateralRequest {
     pub amount_sats: u64,
@@ -183,6 +329,212 @@ pub struct PriceInfo {
     pub confidence: u8,
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// REQUEST VALIDATION
+// ═══════════════════════════════════════════════════════════════════════════════
+//
+// Bodies are checked against a schema before they reach serde: the body must
+// be a JSON object within `MAX_BODY_BYTES`, carry only known fields, and every
+// hex field must decode to the expected number of bytes. Failures come back
+// as `400` with one entry per offending field.
+
+/// Largest request body accepted
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Shape of a JSON request body
+pub trait RequestSchema: DeserializeOwned {
+    /// Fields the body may carry
+    const FIELDS: &'static [&'static str];
+    /// Hex-encoded fields and their decoded length in bytes
+    const HEX_FIELDS: &'static [(&'static str, usize)] = &[];
+}
+
+impl RequestSchema for OpenCDPRequest {
+    const FIELDS: &'static [&'static str] = &["owner", "collateral_sats", "debt_cents"];
+    const HEX_FIELDS: &'static [(&'static str, usize)] = &[("owner", PUBKEY_LENGTH)];
+}
+
+impl RequestSchema for DepositCollateralRequest {
+    const FIELDS: &'static [&'static str] = &["amount_sats"];
+}
+
+impl RequestSchema for WithdrawCollateralRequest {
+    const FIELDS: &'static [&'static str] = &["amount_sats"];
+}
+
+impl RequestSchema for MintDebtRequest {
+    const FIELDS: &'static [&'static str] = &["amount_cents"];
+}
+
+impl RequestSchema for RepayDebtRequest {
+    const FIELDS: &'static [&'static str] = &["amount_cents"];
+}
+
+impl RequestSchema for StabilityDepositRequest {
+    const FIELDS: &'static [&'static str] = &["depositor", "amount_cents"];
+    const HEX_FIELDS: &'static [(&'static str, usize)] = &[("depositor", PUBKEY_LENGTH)];
+}
+
+impl RequestSchema for UpdatePriceOp {
+    const FIELDS: &'static [&'static str] = &[
+        "operator",
+        "price_cents",
+        "source_count",
+        "confidence",
+        "confidence_interval",
+        "proof",
+        "nonce",
+        "signature",
+    ];
+    const HEX_FIELDS: &'static [(&'static str, usize)] =
+        &[("operator", PUBKEY_LENGTH), ("signature", SIGNATURE_LENGTH)];
+}
+
+impl RequestSchema for ParameterOverrideRequest {
+    const FIELDS: &'static [&'static str] = &["parameter", "value"];
+}
+
+impl RequestSchema for ParameterPreviewRequest {
+    const FIELDS: &'static [&'static str] = &["changes"];
+}
+
+impl RequestSchema for BackupRequest {
+    const FIELDS: &'static [&'static str] = &["incremental"];
+}
+
+impl RequestSchema for PruneRequest {
+    const FIELDS: &'static [&'static str] = &["keep_blocks"];
+}
+
+/// One rejected field
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub reason: String,
+}
+
+impl FieldError {
+    fn new(field: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self { field: field.into(), reason: reason.into() }
+    }
+}
+
+/// Rejected request
+#[derive(Debug, Serialize)]
+pub struct ValidationFailure {
+    #[serde(skip)]
+    pub status: StatusCode,
+    pub success: bool,
+    pub error: String,
+    pub fields: Vec<FieldError>,
+}
+
+impl ValidationFailure {
+    fn new(status: StatusCode, fields: Vec<FieldError>) -> Self {
+        Self { status, success: false, error: "Validation failed".to_string(), fields }
+    }
+}
+
+impl IntoResponse for ValidationFailure {
+    fn into_response(self) -> Response {
+        (self.status, Json(self)).into_response()
+    }
+}
+
+/// Check that `value` is hex decoding to `len` bytes
+fn check_hex(field: &str, value: &str, len: usize) -> Option<FieldError> {
+    match hex::decode(value) {
+        Ok(bytes) if bytes.len() == len => None,
+        Ok(bytes) => Some(FieldError::new(field, format!("expected {} bytes, got {}", len, bytes.len()))),
+        Err(e) => Some(FieldError::new(field, format!("invalid hex: {}", e))),
+    }
+}
+
+/// Check a parsed body against the schema of `T`
+fn check_schema<T: RequestSchema>(body: &serde_json::Value) -> Vec<FieldError> {
+    let Some(object) = body.as_object() else {
+        return vec![FieldError::new("body", "expected a JSON object")];
+    };
+
+    let mut errors: Vec<FieldError> = object
+        .keys()
+        .filter(|key| !T::FIELDS.contains(&key.as_str()))
+        .map(|key| FieldError::new(key.as_str(), "unknown field"))
+        .collect();
+
+    for (field, len) in T::HEX_FIELDS {
+        match object.get(*field) {
+            Some(serde_json::Value::String(value)) => errors.extend(check_hex(field, value, *len)),
+            Some(_) => errors.push(FieldError::new(*field, "expected a hex string")),
+            // Missing fields are reported by serde
+            None => {}
+        }
+    }
+    errors
+}
+
+/// JSON body checked against its [`RequestSchema`]
+pub struct Validated<T>(pub T);
+
+#[axum::async_trait]
+impl<T, S> FromRequest<S> for Validated<T>
+where
+    T: RequestSchema,
+    S: Send + Sync,
+{
+    type Rejection = ValidationFailure;
+
+    async fn from_request(request: Request<Body>, state: &S) -> Result<Self, Self::Rejection> {
+        let is_json = request
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("application/json"));
+        if !is_json {
+            return Err(ValidationFailure::new(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                vec![FieldError::new("content-type", "expected application/json")],
+            ));
+        }
+
+        let bytes = Bytes::from_request(request, state)
+            .await
+            .map_err(|e| ValidationFailure::new(e.status(), vec![FieldError::new("body", e.body_text())]))?;
+        let body: serde_json::Value = serde_json::from_slice(&bytes).map_err(|e| {
+            ValidationFailure::new(StatusCode::BAD_REQUEST, vec![FieldError::new("body", e.to_string())])
+        })?;
+
+        let errors = check_schema::<T>(&body);
+        if !errors.is_empty() {
+            return Err(ValidationFailure::new(StatusCode::BAD_REQUEST, errors));
+        }
+
+        serde_json::from_value(body).map(Validated).map_err(|e| {
+            ValidationFailure::new(StatusCode::BAD_REQUEST, vec![FieldError::new("body", e.to_string())])
+        })
+    }
+}
+
+/// CDP ID path segment, checked before it is parsed
+pub struct CdpIdPath(pub CDPId);
+
+#[axum::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for CdpIdPath {
+    type Rejection = ValidationFailure;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Path(id) = Path::<String>::from_request_parts(parts, state)
+            .await
+            .map_err(|e| ValidationFailure::new(e.status(), vec![FieldError::new("id", e.body_text())]))?;
+        if let Some(error) = check_hex("id", &id, CDP_ID_LENGTH) {
+            return Err(ValidationFailure::new(StatusCode::BAD_REQUEST, vec![error]));
+        }
+        CDPId::from_hex(&id)
+            .map(CdpIdPath)
+            .map_err(|e| ValidationFailure::new(StatusCode::BAD_REQUEST, vec![FieldError::new("id", e.to_string())]))
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // HANDLERS
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -202,8 +554,10 @@ async fn get_status(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     let token = state.token.read().await;
     let vault = state.vault.read().await;
     let stability_pool = state.stability_pool.read().await;
+    let savings = state.savings.read().await;
     let btc_price = state.get_btc_price().await;
     let block_height = state.current_block().await;
+    let config = state.config.read().await;
 
     let status = ProtocolStatus {
         version: zkusd::VERSION.to_string(),
@@ -213,8 +567,10 @@ async fn get_status(State(state): State<Arc<AppState>>) -> impl IntoResponse {
         total_collateral_sats: vault.total_collateral().sats(),
         active_cdps: cdp_manager.active_count() as u64,
         stability_pool_deposits_cents: stability_pool.total_deposits().cents(),
-        min_collateral_ratio: state.config.params.min_collateral_ratio,
-        recovery_mode: state.config.recovery_mode,
+        savings_locked_cents: savings.total_locked().cents(),
+        treasury_balance_cents: token.balance_of(&Treasury::account()).cents(),
+        min_collateral_ratio: config.params.min_collateral_ratio,
+        recovery_mode: config.recovery_mode,
     };
 
     Json(ApiResponse::ok(status))
@@ -254,40 +610,115 @@ async fn update_price(
     Json(ApiResponse::ok("Price updated"))
 }
 
+/// POST /price/signed - Submit a signed price update (for registered oracle
+/// operators; each nonce is accepted once)
+async fn update_price_signed(
+    State(state): State<Arc<AppState>>,
+    Validated(op): Validated<UpdatePriceOp>,
+) -> impl IntoResponse {
+    let domain = SigningDomain::new(state.network).with_chain_id(state.config.read().await.chain_id);
+    let verified = op
+        .signing_hash(&domain)
+        .map(|hash| verify_signature(&op.operator, &hash, &op.signature))
+        .unwrap_or(false);
+    if !verified {
+        return (StatusCode::UNAUTHORIZED, Json(ApiResponse::<&str>::err("Invalid signature")));
+    }
+    if let Err(e) = state.oracle_registry.read().await.authorize(&op.operator) {
+        return (StatusCode::FORBIDDEN, Json(ApiResponse::err(e.to_string())));
+    }
+    // The nonce is signed, so a captured update cannot be submitted again
+    if let Err(e) = state.oracle_nonces.write().await.accept(&op.operator, op.nonce) {
+        return (StatusCode::CONFLICT, Json(ApiResponse::err(e.to_string())));
+    }
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let mut price_data =
+        zkusd::oracle::price_feed::PriceData::new(op.price_cents, timestamp, op.source_count);
+    price_data.confidence = op.confidence;
+
+    let mut price_feed = state.price_feed.write().await;
+    if let Err(e) = price_feed.update(price_data) {
+        return (StatusCode::BAD_REQUEST, Json(ApiResponse::err(e.to_string())));
+    }
+
+    info!("Signed price update: {} cents from {}", op.price_cents, op.operator.to_hex());
+    (StatusCode::OK, Json(ApiResponse::ok("Price updated")))
+}
+
 /// GET /cdp/:id - Get CDP info
 async fn get_cdp(
     State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
+    CdpIdPath(cdp_id): CdpIdPath,
 ) -> impl IntoResponse {
-    let cdp_id = match CDPId::from_hex(&id) {
-        Ok(id) => id,
-        Err(_) => return Json(ApiResponse::<CDPInfo>::err("Invalid CDP ID")),
-    };
+    let view = state.view.load();
 
-    let cdp_manager = state.cdp_manager.read().await;
-    let btc_price = state.get_btc_price().await;
-
-    match cdp_manager.get(&cdp_id) {
+    match view.cdp(&cdp_id) {
         Some(cdp) => {
             let mut info = CDPInfo::from(cdp);
-            info.ratio = cdp.calculate_ratio(btc_price);
+            info.ratio = cdp.calculate_ratio(view.btc_price);
             Json(ApiResponse::ok(info))
         }
         None => Json(ApiResponse::err("CDP not found")),
     }
 }
 
-/// GET /cdps - List all CDPs
-async fn list_cdps(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+/// GET /cdp/:id/risk - Get CDP liquidation risk
+async fn get_cdp_risk(
+    State(state): State<Arc<AppState>>,
+    CdpIdPath(cdp_id): CdpIdPath,
+    Query(query): Query<RiskQuery>,
+) -> impl IntoResponse {
     let cdp_manager = state.cdp_manager.read().await;
     let btc_price = state.get_btc_price().await;
+    let min_ratio = state.config.read().await.effective_mcr();
+
+    let mut assumptions = RiskAssumptions::default();
+    if let Some(drift) = query.drift_bps {
+        assumptions = assumptions.with_drift(drift);
+    }
 
-    let cdps: Vec<CDPInfo> = cdp_manager
+    match cdp_manager.get(&cdp_id) {
+        Some(cdp) => Json(ApiResponse::ok(assess(cdp, btc_price, min_ratio, &assumptions))),
+        None => Json(ApiResponse::err("CDP not found")),
+    }
+}
+
+/// GET /cdp/:id/liquidation - Preview liquidating a CDP, optionally at another price
+async fn preview_liquidation(
+    State(state): State<Arc<AppState>>,
+    CdpIdPath(cdp_id): CdpIdPath,
+    Query(query): Query<LiquidationPreviewQuery>,
+) -> impl IntoResponse {
+    let btc_price = match query.price_cents {
+        Some(price) => price,
+        None => state.get_btc_price().await,
+    };
+    let cdp_manager = state.cdp_manager.read().await;
+    let stability_pool = state.stability_pool.read().await;
+    let config = state.config.read().await;
+
+    match LiquidationEngine::preview(&cdp_manager, &stability_pool, &AuctionHouse::new(), &config, &cdp_id, btc_price) {
+        Ok(preview) => Json(ApiResponse::ok(preview)),
+        Err(e) => Json(ApiResponse::err(e.to_string())),
+    }
+}
+
+/// GET /cdps - List all CDPs
+async fn list_cdps(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let view = state.view.load();
+
+    let cdps: Vec<CDPInfo> = view
+        .cdps()
         .all_cdps()
         .into_iter()
         .map(|cdp| {
             let mut info = CDPInfo::from(cdp);
-            info.ratio = cdp.calculate_ratio(btc_price);
+            info.ratio = cdp.calculate_ratio(view.btc_price);
             info
         })
         .collect();
@@ -298,8 +729,12 @@ async fn list_cdps(State(state): State<Arc<AppState>>) -> impl IntoResponse {
 /// POST /cdp - Open new CDP
 async fn open_cdp(
     State(state): State<Arc<AppState>>,
-    Json(req): Json<OpenCDPRequest>,
+    Validated(req): Validated<OpenCDPRequest>,
 ) -> impl IntoResponse {
+    if state.is_paused().await {
+        return Json(ApiResponse::err("Protocol is paused"));
+    }
+
     let owner_bytes = match hex::decode(&req.owner) {
         Ok(b) if b.len() == 33 => b,
         _ => return Json(ApiResponse::<CDPInfo>::err("Invalid owner public key")),
@@ -311,7 +746,7 @@ async fn open_cdp(
 
     let block_height = state.current_block().await;
     let btc_price = state.get_btc_price().await;
-    let min_ratio = state.config.params.min_collateral_ratio;
+    let min_ratio = state.config.read().await.params.min_collateral_ratio;
 
     let cdp = match CDP::with_collateral(owner, req.collateral_sats, 1, block_height) {
         Ok(mut cdp) => {
@@ -354,13 +789,12 @@ async fn open_cdp(
 /// POST /cdp/:id/deposit - Deposit collateral
 async fn deposit_collateral(
     State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
-    Json(req): Json<DepositCollateralRequest>,
+    CdpIdPath(cdp_id): CdpIdPath,
+    Validated(req): Validated<DepositCollateralRequest>,
 ) -> impl IntoResponse {
-    let cdp_id = match CDPId::from_hex(&id) {
-        Ok(id) => id,
-        Err(_) => return Json(ApiResponse::<CDPInfo>::err("Invalid CDP ID")),
-    };
+    if state.is_paused().await {
+        return Json(ApiResponse::err("Protocol is paused"));
+    }
 
     let block_height = state.current_block().await;
     let btc_price = state.get_btc_price().await;
@@ -388,17 +822,16 @@ async fn deposit_collateral(
 /// POST /cdp/:id/withdraw - Withdraw collateral
 async fn withdraw_collateral(
     State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
-    Json(req): Json<WithdrawCollateralRequest>,
+    CdpIdPath(cdp_id): CdpIdPath,
+    Validated(req): Validated<WithdrawCollateralRequest>,
 ) -> impl IntoResponse {
-    let cdp_id = match CDPId::from_hex(&id) {
-        Ok(id) => id,
-        Err(_) => return Json(ApiResponse::<CDPInfo>::err("Invalid CDP ID")),
-    };
+    if state.is_paused().await {
+        return Json(ApiResponse::err("Protocol is paused"));
+    }
 
     let block_height = state.current_block().await;
     let btc_price = state.get_btc_price().await;
-    let min_ratio = state.config.params.min_collateral_ratio;
+    let min_ratio = state.config.read().await.params.min_collateral_ratio;
 
     let mut cdp_manager = state.cdp_manager.write().await;
 
@@ -423,17 +856,16 @@ async fn withdraw_collateral(
 /// POST /cdp/:id/mint - Mint debt
 async fn mint_debt(
     State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
-    Json(req): Json<MintDebtRequest>,
+    CdpIdPath(cdp_id): CdpIdPath,
+    Validated(req): Validated<MintDebtRequest>,
 ) -> impl IntoResponse {
-    let cdp_id = match CDPId::from_hex(&id) {
-        Ok(id) => id,
-        Err(_) => return Json(ApiResponse::<CDPInfo>::err("Invalid CDP ID")),
-    };
+    if state.is_paused().await {
+        return Json(ApiResponse::err("Protocol is paused"));
+    }
 
     let block_height = state.current_block().await;
     let btc_price = state.get_btc_price().await;
-    let min_ratio = state.config.params.min_collateral_ratio;
+    let min_ratio = state.config.read().await.params.min_collateral_ratio;
 
     let mut cdp_manager = state.cdp_manager.write().await;
 
@@ -459,13 +891,12 @@ async fn mint_debt(
 /// POST /cdp/:id/repay - Repay debt
 async fn repay_debt(
     State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
-    Json(req): Json<RepayDebtRequest>,
+    CdpIdPath(cdp_id): CdpIdPath,
+    Validated(req): Validated<RepayDebtRequest>,
 ) -> impl IntoResponse {
-    let cdp_id = match CDPId::from_hex(&id) {
-        Ok(id) => id,
-        Err(_) => return Json(ApiResponse::<CDPInfo>::err("Invalid CDP ID")),
-    };
+    if state.is_paused().await {
+        return Json(ApiResponse::err("Protocol is paused"));
+    }
 
     let block_height = state.current_block().await;
     let btc_price = state.get_btc_price().await;
@@ -496,12 +927,11 @@ async fn repay_debt(
 /// POST /cdp/:id/close - Close CDP
 async fn close_cdp(
     State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
+    CdpIdPath(cdp_id): CdpIdPath,
 ) -> impl IntoResponse {
-    let cdp_id = match CDPId::from_hex(&id) {
-        Ok(id) => id,
-        Err(_) => return Json(ApiResponse::<String>::err("Invalid CDP ID")),
-    };
+    if state.is_paused().await {
+        return Json(ApiResponse::err("Protocol is paused"));
+    }
 
     let block_height = state.current_block().await;
 
@@ -541,16 +971,12 @@ async fn get_balance(
     owner_arr.copy_from_slice(&owner_bytes);
     let owner = PublicKey::new(owner_arr);
 
-    let token = state.token.read().await;
-    let balance = token.balance_of(&owner);
-
-    Json(ApiResponse::ok(balance.cents()))
+    Json(ApiResponse::ok(state.view.load().balance(&owner).cents()))
 }
 
 /// GET /token/supply - Get total supply
 async fn get_supply(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let token = state.token.read().await;
-    Json(ApiResponse::ok(token.total_supply().cents()))
+    Json(ApiResponse::ok(state.view.load().total_supply.cents()))
 }
 
 /// GET /pool/status - Stability pool status
@@ -576,8 +1002,12 @@ async fn get_pool_status(State(state): State<Arc<AppState>>) -> impl IntoRespons
 /// POST /pool/deposit - Deposit to stability pool
 async fn pool_deposit(
     State(state): State<Arc<AppState>>,
-    Json(req): Json<StabilityDepositRequest>,
+    Validated(req): Validated<StabilityDepositRequest>,
 ) -> impl IntoResponse {
+    if state.is_paused().await {
+        return Json(ApiResponse::err("Protocol is paused"));
+    }
+
     let depositor_bytes = match hex::decode(&req.depositor) {
         Ok(b) if b.len() == 33 => b,
         _ => return Json(ApiResponse::<String>::err("Invalid depositor address")),
@@ -599,29 +1029,849 @@ async fn pool_deposit(
     }
 }
 
+/// GET /risk - System-wide risk snapshot
+async fn get_risk_snapshot(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let cdp_manager = state.cdp_manager.read().await;
+    let pool = state.stability_pool.read().await;
+    let history: Vec<(u64, u64)> = state
+        .price_feed
+        .read()
+        .await
+        .history()
+        .iter()
+        .map(|p| (p.timestamp, p.price_cents))
+        .collect();
+
+    Json(ApiResponse::ok(
+        RiskSnapshot::compute(
+            &cdp_manager,
+            state.get_btc_price().await,
+            state.config.read().await.effective_mcr(),
+            pool.total_deposits(),
+            state.current_block().await,
+        )
+        .with_volatility(&VolatilityEstimator::default().from_history(&history)),
+    ))
+}
+
+/// GET /monitor - Dashboard feed for `zkusd monitor`
+///
+/// The server keeps no event history, so `recent_events` is always empty.
+async fn get_monitor_feed(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let cdp_manager = state.cdp_manager.read().await;
+    let pool = state.stability_pool.read().await;
+    let config = state.config.read().await;
+
+    Json(ApiResponse::ok(DashboardFeed::collect(
+        &cdp_manager,
+        state.get_btc_price().await,
+        config.effective_mcr(),
+        config.params.critical_collateral_ratio,
+        pool.total_deposits(),
+        state.current_block().await,
+        Vec::new(),
+    )))
+}
+
+/// GET /savings/status - Savings pot status
+async fn get_savings_status(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let savings = state.savings.read().await;
+
+    #[derive(Serialize)]
+    struct SavingsStatus {
+        rate_bps: u64,
+        total_locked_cents: u64,
+        reserve_cents: u64,
+        interest_paid_cents: u64,
+        depositor_count: u64,
+    }
+
+    let status = SavingsStatus {
+        rate_bps: savings.rate_bps(),
+        total_locked_cents: savings.total_locked().cents(),
+        reserve_cents: savings.reserve().cents(),
+        interest_paid_cents: savings.interest_paid().cents(),
+        depositor_count: savings.depositor_count() as u64,
+    };
+
+    Json(ApiResponse::ok(status))
+}
+
 /// POST /block - Advance block height (for testing/simulation)
 async fn advance_block(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let mut block_height = state.block_height.write().await;
-    *block_height += 1;
-    Json(ApiResponse::ok(*block_height))
+    let height = {
+        let mut block_height = state.block_height.write().await;
+        *block_height += 1;
+        *block_height
+    };
+    state.publish_view().await;
+    state.refresh_alerts().await;
+    Json(ApiResponse::ok(height))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// RATE LIMITING
+// ═══════════════════════════════════════════════════════════════════════════════
+//
+// Public calls are metered per client: callers presenting `X-API-Key` get the
+// quota of the key's tier, everyone else shares the anonymous tier per IP.
+// Each client has a token bucket refilled at the per-minute rate plus a daily
+// counter reset at midnight UTC. Counters are written to
+// `<ZKUSD_DATA_DIR>/rate_limits.json` so restarts don't hand out fresh quotas.
+
+/// Header carrying the caller's API key
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// File the rate limiter state is persisted to, under the data directory
+const RATE_LIMIT_FILE: &str = "rate_limits.json";
+
+/// Seconds between rate limiter state writes
+const RATE_LIMIT_PERSIST_SECS: u64 = 30;
+
+const SECS_PER_DAY: u64 = 86_400;
+
+/// Quota tier of an API key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiTier {
+    /// Callers without an API key, limited per IP
+    Anonymous,
+    Standard,
+    Premium,
+    /// Unmetered, for operator tooling
+    Internal,
+}
+
+impl ApiTier {
+    /// Requests allowed per minute and per day; `None` is unmetered
+    pub fn limits(self) -> Option<(u64, u64)> {
+        match self {
+            ApiTier::Anonymous => Some((60, 10_000)),
+            ApiTier::Standard => Some((300, 100_000)),
+            ApiTier::Premium => Some((1_200, 1_000_000)),
+            ApiTier::Internal => None,
+        }
+    }
+}
+
+impl std::str::FromStr for ApiTier {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "anonymous" => Ok(ApiTier::Anonymous),
+            "standard" => Ok(ApiTier::Standard),
+            "premium" => Ok(ApiTier::Premium),
+            "internal" => Ok(ApiTier::Internal),
+            other => Err(format!("Unknown API tier: {}", other)),
+        }
+    }
+}
+
+/// Persisted quota usage of one client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaState {
+    /// Tokens left in the per-minute bucket
+    pub tokens: f64,
+    /// Unix milliseconds of the last refill
+    pub refilled_at_ms: u64,
+    /// Day (since the epoch) `day_count` belongs to
+    pub day: u64,
+    /// Requests served on `day`
+    pub day_count: u64,
+}
+
+/// Outcome of charging one request to a client
+#[derive(Debug, Clone, Copy)]
+pub struct QuotaDecision {
+    pub allowed: bool,
+    pub minute_limit: u64,
+    pub minute_remaining: u64,
+    pub day_limit: u64,
+    pub day_remaining: u64,
+    /// Seconds until the next request would be allowed
+    pub retry_after_secs: u64,
+    /// Seconds until the daily quota resets
+    pub day_reset_secs: u64,
+}
+
+/// Token buckets per API key (or per IP for anonymous callers)
+#[derive(Debug, Default)]
+pub struct ApiRateLimiter {
+    /// Configured API keys and their tiers
+    keys: HashMap<String, ApiTier>,
+    /// Usage keyed by client id; API keys are stored hashed
+    quotas: HashMap<String, QuotaState>,
+}
+
+impl ApiRateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse `key:tier` pairs separated by commas
+    pub fn with_keys(mut self, spec: &str) -> Result<Self, String> {
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (key, tier) = entry
+                .rsplit_once(':')
+                .ok_or_else(|| format!("Expected key:tier, got {}", entry))?;
+            self.keys.insert(key.to_string(), tier.parse()?);
+        }
+        Ok(self)
+    }
+
+    /// Number of configured API keys
+    pub fn key_count(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Tier and client id for a request; `None` for an unknown key
+    pub fn identify(&self, api_key: Option<&str>, ip: Option<std::net::IpAddr>) -> Option<(ApiTier, String)> {
+        match api_key {
+            Some(key) => {
+                let tier = *self.keys.get(key)?;
+                Some((tier, format!("key:{}", &Hash::sha256(key.as_bytes()).to_hex()[..16])))
+            }
+            None => Some((
+                ApiTier::Anonymous,
+                format!("ip:{}", ip.map(|ip| ip.to_string()).unwrap_or_default()),
+            )),
+        }
+    }
+
+    /// Charge one request for `client` at `now_ms`
+    pub fn check(&mut self, client: &str, tier: ApiTier, now_ms: u64) -> Option<QuotaDecision> {
+        let (per_minute, per_day) = tier.limits()?;
+        let today = now_ms / 1_000 / SECS_PER_DAY;
+        let quota = self.quotas.entry(client.to_string()).or_insert(QuotaState {
+            tokens: per_minute as f64,
+            refilled_at_ms: now_ms,
+            day: today,
+            day_count: 0,
+        });
+
+        let refill_per_ms = per_minute as f64 / 60_000.0;
+        let elapsed = now_ms.saturating_sub(quota.refilled_at_ms) as f64;
+        quota.tokens = (quota.tokens + elapsed * refill_per_ms).min(per_minute as f64);
+        quota.refilled_at_ms = now_ms;
+        if quota.day != today {
+            quota.day = today;
+            quota.day_count = 0;
+        }
+
+        let day_reset_secs = (today + 1) * SECS_PER_DAY - now_ms / 1_000;
+        let minute_ok = quota.tokens >= 1.0;
+        let day_ok = quota.day_count < per_day;
+        let allowed = minute_ok && day_ok;
+        if allowed {
+            quota.tokens -= 1.0;
+            quota.day_count += 1;
+        }
+
+        let retry_after_secs = if allowed {
+            0
+        } else if !day_ok {
+            day_reset_secs
+        } else {
+            ((1.0 - quota.tokens) / refill_per_ms / 1_000.0).ceil().max(1.0) as u64
+        };
+
+        Some(QuotaDecision {
+            allowed,
+            minute_limit: per_minute,
+            minute_remaining: quota.tokens.floor() as u64,
+            day_limit: per_day,
+            day_remaining: per_day.saturating_sub(quota.day_count),
+            retry_after_secs,
+            day_reset_secs,
+        })
+    }
+
+    /// Drop clients whose bucket is full again and whose daily count has expired
+    pub fn prune(&mut self, now_ms: u64) {
+        let today = now_ms / 1_000 / SECS_PER_DAY;
+        self.quotas.retain(|_, quota| {
+            quota.day == today || now_ms.saturating_sub(quota.refilled_at_ms) < 60_000
+        });
+    }
+
+    /// Restore usage written by [`ApiRateLimiter::save`]
+    pub fn load(&mut self, path: &std::path::Path) -> std::io::Result<()> {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        self.quotas = serde_json::from_slice(&bytes)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        Ok(())
+    }
+
+    /// Write usage to `path`, replacing the previous file atomically
+    pub fn save(&self, path: &std::path::Path) -> std::io::Result<()> {
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec(&self.quotas)?)?;
+        std::fs::rename(tmp, path)
+    }
+}
+
+/// Attach the quota headers of `decision` to `response`
+fn quota_headers(response: &mut Response, decision: &QuotaDecision) {
+    let headers = response.headers_mut();
+    let mut set = |name: &'static str, value: u64| {
+        headers.insert(name, header::HeaderValue::from(value));
+    };
+    set("x-ratelimit-limit", decision.minute_limit);
+    set("x-ratelimit-remaining", decision.minute_remaining);
+    set("x-quota-limit", decision.day_limit);
+    set("x-quota-remaining", decision.day_remaining);
+    set("x-quota-reset", decision.day_reset_secs);
+    if !decision.allowed {
+        set("retry-after", decision.retry_after_secs);
+    }
+}
+
+/// Meter public calls by API key or IP
+async fn rate_limit(State(state): State<Arc<AppState>>, request: Request<Body>, next: Next) -> Response {
+    if request.uri().path() == "/health" {
+        return next.run(request).await;
+    }
+
+    let api_key = request
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+
+    let decision = {
+        let mut limiter = state.rate_limiter.write().await;
+        let Some((tier, client)) = limiter.identify(api_key.as_deref(), ip) else {
+            return (StatusCode::UNAUTHORIZED, Json(ApiResponse::<()>::err("Unknown API key"))).into_response();
+        };
+        limiter.check(&client, tier, unix_now_ms())
+    };
+
+    let Some(decision) = decision else {
+        return next.run(request).await;
+    };
+    let mut response = if decision.allowed {
+        next.run(request).await
+    } else {
+        (StatusCode::TOO_MANY_REQUESTS, Json(ApiResponse::<()>::err("Rate limit exceeded"))).into_response()
+    };
+    quota_headers(&mut response, &decision);
+    response
+}
+
+/// Prune and persist rate limiter state
+async fn persist_rate_limits(state: &AppState, path: &std::path::Path) {
+    let mut limiter = state.rate_limiter.write().await;
+    limiter.prune(unix_now_ms());
+    if let Err(e) = limiter.save(path) {
+        warn!("Failed to persist rate limits to {}: {}", path.display(), e);
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// ADMIN API
+// ═══════════════════════════════════════════════════════════════════════════════
+//
+// Operator calls live under `/admin`, apart from the public namespace. Every
+// request must carry `Authorization: Bearer <ZKUSD_ADMIN_TOKEN>`, and every
+// request, authorized or not, is recorded in the audit log. For mTLS, serve
+// the namespace on its own listener (`ZKUSD_ADMIN_BIND`) behind a proxy that
+// verifies client certificates.
+
+/// Admin calls kept in the audit log
+const MAX_AUDIT_EVENTS: usize = 1_024;
+
+/// Shortest admin token accepted at startup
+const MIN_ADMIN_TOKEN_LEN: usize = 32;
+
+/// One admin call
+#[derive(Debug, Clone, Serialize)]
+pub struct AdminAuditEvent {
+    pub timestamp: u64,
+    pub block_height: u64,
+    pub method: String,
+    pub path: String,
+    pub request_id: String,
+    /// Whether the bearer token matched
+    pub authorized: bool,
+    /// HTTP status of the response
+    pub status: u16,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ParameterOverrideRequest {
+    pub parameter: String,
+    pub value: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ParameterPreviewRequest {
+    /// Changes applied in order
+    pub changes: Vec<ParameterOverrideRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BackupRequest {
+    #[serde(default)]
+    pub incremental: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PruneRequest {
+    pub keep_blocks: u64,
+}
+
+fn unix_now() -> u64 {
+    unix_now_ms() / 1_000
+}
+
+fn unix_now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Compare tokens without short-circuiting on the first differing byte
+fn token_matches(expected: &str, presented: &str) -> bool {
+    let (expected, presented) = (expected.as_bytes(), presented.as_bytes());
+    expected.len() == presented.len()
+        && expected.iter().zip(presented).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Check the bearer token and audit the call
+async fn admin_auth(State(state): State<Arc<AppState>>, request: Request<Body>, next: Next) -> Response {
+    let presented = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let authorized = match (&state.admin_token, presented) {
+        (Some(expected), Some(presented)) => token_matches(expected, presented),
+        _ => false,
+    };
+
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let request_id = request
+        .headers()
+        .get("x-request-id")
+        .and_then(|id| id.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+
+    let response = if authorized {
+        next.run(request).await
+    } else {
+        (StatusCode::UNAUTHORIZED, Json(ApiResponse::<()>::err("Invalid admin token"))).into_response()
+    };
+
+    let event = AdminAuditEvent {
+        timestamp: unix_now(),
+        block_height: state.current_block().await,
+        method,
+        path,
+        request_id,
+        authorized,
+        status: response.status().as_u16(),
+    };
+    info!(
+        target: "zkusd::audit",
+        method = %event.method,
+        path = %event.path,
+        request_id = %event.request_id,
+        authorized = event.authorized,
+        status = event.status,
+        "admin call"
+    );
+
+    let mut audit_log = state.audit_log.write().await;
+    if audit_log.len() == MAX_AUDIT_EVENTS {
+        audit_log.pop_front();
+    }
+    audit_log.push_back(event);
+
+    response
+}
+
+/// POST /admin/pause - Reject state-changing calls until unpaused
+async fn admin_pause(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    state.config.write().await.paused = true;
+    state.publish_view().await;
+    warn!("Protocol paused by admin");
+    Json(ApiResponse::ok("Protocol paused"))
+}
+
+/// POST /admin/unpause - Resume state-changing calls
+async fn admin_unpause(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    state.config.write().await.paused = false;
+    state.publish_view().await;
+    warn!("Protocol unpaused by admin");
+    Json(ApiResponse::ok("Protocol unpaused"))
+}
+
+/// POST /admin/params - Override a protocol parameter (not on mainnet)
+async fn admin_override_parameter(
+    State(state): State<Arc<AppState>>,
+    Validated(req): Validated<ParameterOverrideRequest>,
+) -> impl IntoResponse {
+    if state.network == Network::Mainnet {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::err("Parameter overrides are disabled on mainnet")),
+        );
+    }
+    let parameter: ProtocolParameter = match req.parameter.parse() {
+        Ok(parameter) => parameter,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(ApiResponse::err(e.to_string()))),
+    };
+
+    let mut config = state.config.write().await;
+    let old_value = parameter.get(&config.params);
+    match config.params.checked_update(parameter, req.value) {
+        Ok(params) => config.params = params,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(ApiResponse::err(e.to_string()))),
+    }
+    drop(config);
+    state.publish_view().await;
+
+    warn!("Parameter {} overridden by admin: {} -> {}", parameter, old_value, req.value);
+    (StatusCode::OK, Json(ApiResponse::ok(format!("{} = {}", parameter, req.value))))
+}
+
+/// POST /admin/params/preview - Impact of parameter changes, without applying them
+async fn admin_preview_parameters(
+    State(state): State<Arc<AppState>>,
+    Validated(req): Validated<ParameterPreviewRequest>,
+) -> impl IntoResponse {
+    let mut changes = Vec::with_capacity(req.changes.len());
+    for change in &req.changes {
+        match change.parameter.parse::<ProtocolParameter>() {
+            Ok(parameter) => changes.push((parameter, change.value)),
+            Err(e) => return (StatusCode::BAD_REQUEST, Json(ApiResponse::err(e.to_string()))),
+        }
+    }
+
+    let cdp_manager = state.cdp_manager.read().await;
+    let token = state.token.read().await;
+    let vault = state.vault.read().await;
+    let stability_pool = state.stability_pool.read().await;
+    let savings = state.savings.read().await;
+    let btc_price = state.get_btc_price().await;
+    let config = state.config.read().await;
+
+    let total_debt = token.total_supply().cents();
+    let tcr = if total_debt == 0 {
+        Some(u64::MAX)
+    } else {
+        calculate_collateral_ratio(vault.total_collateral().sats(), btc_price, total_debt).ok()
+    };
+    let ctx = InvariantContext {
+        cdps: &cdp_manager,
+        token: &token,
+        vault: &vault,
+        stability_pool: &stability_pool,
+        savings: &savings,
+        btc_price,
+        tcr,
+    };
+
+    match ParameterChangePreview::compute(&config.params, &changes, &ctx, &InvariantChecker::default()) {
+        Ok(preview) => (StatusCode::OK, Json(ApiResponse::ok(preview))),
+        Err(e) => (StatusCode::BAD_REQUEST, Json(ApiResponse::err(e.to_string()))),
+    }
+}
+
+/// GET /admin/alerts - Raised alerts
+async fn admin_alerts(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(ApiResponse::ok(state.alerts.read().await.alerts().to_vec()))
+}
+
+/// POST /admin/alerts/:id/ack - Acknowledge an alert
+async fn admin_ack_alert(State(state): State<Arc<AppState>>, Path(id): Path<u64>) -> impl IntoResponse {
+    if state.alerts.write().await.acknowledge(id) {
+        (StatusCode::OK, Json(ApiResponse::ok(id)))
+    } else {
+        (StatusCode::NOT_FOUND, Json(ApiResponse::err("Alert not found")))
+    }
+}
+
+/// POST /admin/alerts/ack - Acknowledge every raised alert
+async fn admin_ack_all_alerts(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    state.alerts.write().await.acknowledge_all();
+    Json(ApiResponse::ok("Alerts acknowledged"))
+}
+
+/// Write the in-memory state to the store so backups capture it
+async fn persist_state(state: &AppState, manager: &StateManager<BinaryStore>) -> zkusd::error::Result<()> {
+    let cdp_manager = state.cdp_manager.read().await;
+    let token = state.token.read().await;
+    let vault = state.vault.read().await;
+
+    for cdp in cdp_manager.all_cdps() {
+        manager.save_cdp(cdp)?;
+    }
+    for (account, balance) in token.all_balances() {
+        manager.save_balance(account, balance.cents())?;
+    }
+    manager.save_stability_pool(&*state.stability_pool.read().await)?;
+    manager.save_savings(&*state.savings.read().await)?;
+
+    let mut protocol_state = ProtocolState::new(state.config.read().await.clone());
+    protocol_state.total_supply = token.total_supply().cents();
+    protocol_state.total_collateral = vault.total_collateral().sats();
+    protocol_state.total_debt = cdp_manager.all_cdps().iter().map(|cdp| cdp.debt_cents).sum();
+    protocol_state.active_cdps = cdp_manager.active_count();
+    protocol_state.block_height = state.current_block().await;
+    protocol_state.last_update = unix_now();
+    manager.save_protocol_state(&protocol_state)?;
+    manager.flush()
+}
+
+/// POST /admin/backup - Persist the state and back up the data directory
+async fn admin_backup(
+    State(state): State<Arc<AppState>>,
+    Validated(req): Validated<BackupRequest>,
+) -> impl IntoResponse {
+    let Some(data_dir) = state.data_dir.clone() else {
+        return (StatusCode::CONFLICT, Json(ApiResponse::err("No data directory configured")));
+    };
+
+    let result: zkusd::error::Result<BackupManifest> = async {
+        let manager = StateManager::new(BinaryStore::new(data_dir.join("db"))?);
+        persist_state(&state, &manager).await?;
+        let backups = BackupManager::new(data_dir.join("backups"))?;
+        if req.incremental {
+            backups.create_incremental(&manager, unix_now())
+        } else {
+            backups.create_full(&manager, unix_now())
+        }
+    }
+    .await;
+
+    match result {
+        Ok(manifest) => {
+            info!("Backup {} created by admin", manifest.id);
+            (StatusCode::OK, Json(ApiResponse::ok(manifest)))
+        }
+        Err(e @ zkusd::error::Error::StorageLocked { .. }) => (StatusCode::CONFLICT, Json(ApiResponse::err(e.to_string()))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::err(e.to_string()))),
+    }
+}
+
+/// POST /admin/prune - Prune stored history older than `keep_blocks`
+async fn admin_prune(
+    State(state): State<Arc<AppState>>,
+    Validated(req): Validated<PruneRequest>,
+) -> impl IntoResponse {
+    let Some(data_dir) = state.data_dir.clone() else {
+        return (StatusCode::CONFLICT, Json(ApiResponse::err("No data directory configured")));
+    };
+    let block_height = state.current_block().await;
+
+    let result = BinaryStore::new(data_dir.join("db")).and_then(|store| -> zkusd::error::Result<PruneStats> {
+        let manager = StateManager::new(store).with_pruning(PruningMode::Pruned { keep_blocks: req.keep_blocks });
+        let stats = manager.prune(block_height, unix_now())?;
+        manager.flush()?;
+        Ok(stats)
+    });
+
+    match result {
+        Ok(stats) => {
+            info!("Pruned {} entries by admin (keeping last {} blocks)", stats.total(), req.keep_blocks);
+            (StatusCode::OK, Json(ApiResponse::ok(stats)))
+        }
+        Err(e @ zkusd::error::Error::StorageLocked { .. }) => (StatusCode::CONFLICT, Json(ApiResponse::err(e.to_string()))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::err(e.to_string()))),
+    }
+}
+
+/// GET /admin/audit - Recent admin calls, oldest first
+async fn admin_audit_log(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(ApiResponse::ok(state.audit_log.read().await.iter().cloned().collect::<Vec<_>>()))
+}
+
+/// Admin routes, behind the bearer token check
+fn admin_router(state: Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/pause", post(admin_pause))
+        .route("/unpause", post(admin_unpause))
+        .route("/params", post(admin_override_parameter))
+        .route("/params/preview", post(admin_preview_parameters))
+        .route("/alerts", get(admin_alerts))
+        .route("/alerts/ack", post(admin_ack_all_alerts))
+        .route("/alerts/:id/ack", post(admin_ack_alert))
+        .route("/backup", post(admin_backup))
+        .route("/prune", post(admin_prune))
+        .route("/audit", get(admin_audit_log))
+        .layer(middleware::from_fn_with_state(state, admin_auth))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// STATE SYNC
+// ═══════════════════════════════════════════════════════════════════════════════
+//
+// Peers bootstrap from the store in the data directory: its status and
+// snapshot, then the block logs after it. The store is opened read-only per
+// request, so a node writing it keeps running. Snapshots and block logs are
+// bincode, as they carry raw store entries and signed operations.
+
+/// Open the data directory's store for serving peers
+fn sync_source(state: &AppState) -> zkusd::error::Result<StateManager<BinaryStore>> {
+    let data_dir = state
+        .data_dir
+        .as_ref()
+        .ok_or_else(|| zkusd::error::Error::Internal("No data directory configured".into()))?;
+    Ok(StateManager::new(BinaryStore::open_read_only(data_dir.join("db"))?))
+}
+
+/// Bincode body, or the error as a JSON envelope
+fn sync_response(result: zkusd::error::Result<Vec<u8>>) -> Response {
+    match result {
+        Ok(bytes) => ([(header::CONTENT_TYPE, "application/octet-stream")], bytes).into_response(),
+        Err(e) => (StatusCode::SERVICE_UNAVAILABLE, Json(ApiResponse::<()>::err(e.to_string()))).into_response(),
+    }
+}
+
+/// GET /sync/status - Height, state root and genesis of the served store
+async fn get_sync_status(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match sync_source(&state).and_then(|source| source.status()) {
+        Ok(status) => (StatusCode::OK, Json(ApiResponse::ok(status))),
+        Err(e) => (StatusCode::SERVICE_UNAVAILABLE, Json(ApiResponse::err(e.to_string()))),
+    }
+}
+
+/// GET /sync/snapshot - Snapshot of the served store
+async fn get_sync_snapshot(State(state): State<Arc<AppState>>) -> Response {
+    sync_response(sync_source(&state).and_then(|source| source.snapshot()).and_then(|snapshot| snapshot.to_bytes()))
+}
+
+/// GET /sync/blocks?from=&limit= - Block logs at or above `from`
+async fn get_sync_blocks(State(state): State<Arc<AppState>>, Query(query): Query<SyncBlocksQuery>) -> Response {
+    let limit = query.limit.unwrap_or(SYNC_BATCH_BLOCKS).min(SYNC_BATCH_BLOCKS);
+    sync_response(sync_source(&state).and_then(|source| source.blocks(query.from, limit)).and_then(|blocks| {
+        bincode::serialize(&blocks).map_err(|e| zkusd::error::Error::Serialization(e.to_string()))
+    }))
+}
+
+/// Inclusion proof of a CDP with the root it proves against
+#[derive(Debug, Serialize)]
+struct CdpProofResponse {
+    state_root: String,
+    proof: CdpProof,
+}
+
+/// GET /cdp/:id/proof - Merkle proof of a CDP against the served store's state root
+async fn get_cdp_proof(State(state): State<Arc<AppState>>, CdpIdPath(cdp_id): CdpIdPath) -> impl IntoResponse {
+    let proof = sync_source(&state).and_then(|source| Ok((source.compute_state_root()?, source.prove_cdp(&cdp_id)?)));
+    match proof {
+        Ok((root, Some(proof))) => (
+            StatusCode::OK,
+            Json(ApiResponse::ok(CdpProofResponse { state_root: root.to_hex(), proof })),
+        ),
+        Ok((_, None)) => (StatusCode::NOT_FOUND, Json(ApiResponse::err("CDP not found"))),
+        Err(e) => (StatusCode::SERVICE_UNAVAILABLE, Json(ApiResponse::err(e.to_string()))),
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // MAIN
 // ═══════════════════════════════════════════════════════════════════════════════
 
+/// Parse a comma-separated list of oracle operator public keys, each given
+/// weight 1
+fn parse_oracle_operators(spec: &str) -> Result<OracleRegistry, String> {
+    let mut registry = OracleRegistry::new();
+    for key in spec.split(',').map(str::trim).filter(|k| !k.is_empty()) {
+        let pubkey = PublicKey::from_hex(key).map_err(|e| format!("{}: {}", key, e))?;
+        registry.register(OracleOperator::new(pubkey, 1)).map_err(|e| e.to_string())?;
+    }
+    Ok(registry)
+}
+
 #[tokio::main]
 async fn main() {
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive(tracing::Level::INFO.into()),
-        )
-        .init();
+    // Initialize tracing (ZKUSD_LOG_FORMAT=json for structured logs)
+    let log_format = std::env::var("ZKUSD_LOG_FORMAT")
+        .ok()
+        .and_then(|format| format.parse().ok())
+        .unwrap_or_default();
+
+    // Export spans over OTLP when ZKUSD_OTLP_ENDPOINT is set
+    #[cfg(feature = "otel")]
+    let _otel = match std::env::var("ZKUSD_OTLP_ENDPOINT") {
+        Ok(endpoint) => {
+            let config = zkusd::monitoring::otel::OtelConfig::new(endpoint).with_service_name(
+                std::env::var("ZKUSD_OTEL_SERVICE_NAME").unwrap_or_else(|_| "zkusd-server".to_string()),
+            );
+            match zkusd::monitoring::otel::init(&config, log_format, tracing::Level::INFO) {
+                Ok(guard) => Some(guard),
+                Err(e) => {
+                    eprintln!("OTLP export disabled: {}", e);
+                    logging::init(log_format, tracing::Level::INFO);
+                    None
+                }
+            }
+        }
+        Err(_) => {
+            logging::init(log_format, tracing::Level::INFO);
+            None
+        }
+    };
+    #[cfg(not(feature = "otel"))]
+    logging::init(log_format, tracing::Level::INFO);
 
     // Create shared state
-    let state = Arc::new(AppState::new());
+    let mut app_state = AppState::new();
+    if let Ok(network) = std::env::var("ZKUSD_NETWORK") {
+        app_state = app_state.with_network(network.parse().expect("Invalid ZKUSD_NETWORK"));
+    }
+    if let Ok(data_dir) = std::env::var("ZKUSD_DATA_DIR") {
+        app_state = app_state.with_data_dir(data_dir);
+    }
+    match std::env::var("ZKUSD_ADMIN_TOKEN") {
+        Ok(token) if token.len() >= MIN_ADMIN_TOKEN_LEN => app_state = app_state.with_admin_token(token),
+        Ok(_) => warn!("ZKUSD_ADMIN_TOKEN is shorter than {} characters; admin API disabled", MIN_ADMIN_TOKEN_LEN),
+        Err(_) => info!("ZKUSD_ADMIN_TOKEN not set; admin API disabled"),
+    }
+    if let Ok(operators) = std::env::var("ZKUSD_ORACLE_OPERATORS") {
+        let registry = parse_oracle_operators(&operators).expect("Invalid ZKUSD_ORACLE_OPERATORS");
+        info!("Loaded {} oracle operators", registry.operators().len());
+        app_state = app_state.with_oracle_registry(registry);
+    } else {
+        info!("ZKUSD_ORACLE_OPERATORS not set; signed price updates disabled");
+    }
+    let mut rate_limiter = ApiRateLimiter::new();
+    if let Ok(keys) = std::env::var("ZKUSD_API_KEYS") {
+        rate_limiter = rate_limiter.with_keys(&keys).expect("Invalid ZKUSD_API_KEYS");
+        info!("Loaded {} API keys", rate_limiter.key_count());
+    }
+    let rate_limit_path = app_state.data_dir.as_ref().map(|dir| dir.join(RATE_LIMIT_FILE));
+    if let Some(path) = &rate_limit_path {
+        if let Err(e) = rate_limiter.load(path) {
+            warn!("Ignoring rate limits in {}: {}", path.display(), e);
+        }
+    }
+    let state = Arc::new(app_state.with_rate_limiter(rate_limiter));
+
+    if let Some(path) = rate_limit_path {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(RATE_LIMIT_PERSIST_SECS));
+            loop {
+                interval.tick().await;
+                persist_rate_limits(&state, &path).await;
+            }
+        });
+    }
 
     // Initialize with default price
     {
@@ -633,9 +1883,17 @@ async fn main() {
         let price_data = zkusd::oracle::price_feed::PriceData::new(10_000_000, timestamp, 3);
         price_feed.update(price_data);
     }
+    state.publish_view().await;
+
+    // Admin namespace: on its own listener when ZKUSD_ADMIN_BIND is set,
+    // otherwise nested under /admin on the public listener
+    let admin_bind: Option<SocketAddr> = std::env::var("ZKUSD_ADMIN_BIND")
+        .ok()
+        .map(|addr| addr.parse().expect("Invalid admin bind address"));
+    let admin = state.admin_token.as_ref().map(|_| admin_router(state.clone()));
 
     // Build router
-    let app = Router::new()
+    let mut app = Router::new()
         // Health & Status
         .route("/health", get(health_check))
         .route("/status", get(get_status))
@@ -643,10 +1901,14 @@ async fn main() {
         // Price
         .route("/price", get(get_price))
         .route("/price", post(update_price))
+        .route("/price/signed", post(update_price_signed))
 
         // CDP operations
         .route("/cdp", post(open_cdp))
         .route("/cdp/:id", get(get_cdp))
+        .route("/cdp/:id/risk", get(get_cdp_risk))
+        .route("/cdp/:id/proof", get(get_cdp_proof))
+        .route("/cdp/:id/liquidation", get(preview_liquidation))
         .route("/cdps", get(list_cdps))
         .route("/cdp/:id/deposit", post(deposit_collateral))
         .route("/cdp/:id/withdraw", post(withdraw_collateral))
@@ -662,11 +1924,46 @@ async fn main() {
         .route("/pool/status", get(get_pool_status))
         .route("/pool/deposit", post(pool_deposit))
 
+        // Savings
+        .route("/savings/status", get(get_savings_status))
+
+        // Risk
+        .route("/risk", get(get_risk_snapshot))
+        .route("/monitor", get(get_monitor_feed))
+
+        // State sync
+        .route("/sync/status", get(get_sync_status))
+        .route("/sync/snapshot", get(get_sync_snapshot))
+        .route("/sync/blocks", get(get_sync_blocks))
+
         // Admin/Testing
-        .route("/block", post(advance_block))
+        .route("/block", post(advance_block));
 
+    if let (Some(admin), None) = (&admin, admin_bind) {
+        app = app.nest("/admin", admin.clone());
+    }
+
+    let app = app
         // Middleware
-        .layer(TraceLayer::new_for_http())
+        .layer(middleware::from_fn_with_state(state.clone(), rate_limit))
+        .layer(DefaultBodyLimit::max(MAX_BODY_BYTES))
+        // Correlation IDs: reuse the caller's x-request-id or assign one, tag
+        // the request span with it and echo it back
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(TraceLayer::new_for_http().make_span_with(|request: &Request<Body>| {
+            let request_id = request
+                .headers()
+                .get("x-request-id")
+                .and_then(|id| id.to_str().ok())
+                .unwrap_or_default();
+            tracing::info_span!(
+                "rpc",
+                method = %request.method(),
+                uri = %request.uri(),
+                request_id = %request_id,
+            )
+        }))
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
         .layer(CompressionLayer::new())
         .layer(
             CorsLayer::new()
@@ -674,7 +1971,7 @@ async fn main() {
                 .allow_methods(Any)
                 .allow_headers(Any),
         )
-        .with_state(state);
+        .with_state(state.clone());
 
     // Get bind address from env or default
     let addr: SocketAddr = std::env::var("ZKUSD_BIND")
@@ -701,6 +1998,35 @@ async fn main() {
     info!("  GET  /pool/status         - Stability pool status");
     info!("  POST /pool/deposit        - Deposit to pool");
 
+    if admin.is_some() {
+        info!("Admin endpoints (Authorization: Bearer <ZKUSD_ADMIN_TOKEN>):");
+        info!("  POST /admin/pause         - Pause state-changing calls");
+        info!("  POST /admin/unpause       - Resume state-changing calls");
+        info!("  POST /admin/params        - Override a parameter (not on mainnet)");
+        info!("  POST /admin/params/preview - Dry-run parameter changes");
+        info!("  GET  /admin/alerts        - Raised alerts");
+        info!("  POST /admin/alerts/:id/ack - Acknowledge an alert");
+        info!("  POST /admin/backup        - Back up the data directory");
+        info!("  POST /admin/prune         - Prune stored history");
+        info!("  GET  /admin/audit         - Admin audit log");
+    }
+
+    if let (Some(admin), Some(admin_addr)) = (admin, admin_bind) {
+        let admin_app = Router::new()
+            .nest("/admin", admin)
+            .layer(DefaultBodyLimit::max(MAX_BODY_BYTES))
+            .layer(PropagateRequestIdLayer::x_request_id())
+            .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+            .with_state(state.clone());
+        info!("Starting zkUSD admin API on {}", admin_addr);
+        let admin_listener = tokio::net::TcpListener::bind(admin_addr).await.unwrap();
+        tokio::spawn(async move {
+            axum::serve(admin_listener, admin_app).await.unwrap();
+        });
+    }
+
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .await
+        .unwrap();
 }
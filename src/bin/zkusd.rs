@@ -2,17 +2,40 @@
 //!
 //! Command-line interface for interacting with the zkUSD stablecoin protocol.
 
-use std::path::PathBuf;
-
-use clap::{Parser, Subcommand};
-use console::{style, Term};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
+use clap_complete::Shell;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use console::style;
 use indicatif::{ProgressBar, ProgressStyle};
 
+use zkusd::btc::utxo::UtxoStore;
+use zkusd::cli::{CommandOutput, OutputFormat, OutputFormatter};
 use zkusd::core::cdp::{CDP, CDPId, CDPStatus};
-use zkusd::core::config::ProtocolConfig;
+use zkusd::core::config::{Network, ProtocolConfig, ProtocolParameter, ProtocolParams};
+use zkusd::core::risk::{assess, RiskAssumptions, RiskReport, DEFAULT_DRIFT_BPS_PER_DAY};
 use zkusd::core::token::TokenAmount;
-use zkusd::core::vault::CollateralAmount;
-use zkusd::utils::crypto::KeyPair;
+use zkusd::core::vault::{CollateralAmount, ProofOfReserves};
+use zkusd::monitoring::{LiquidationWatcher, PositionStatus, WatchList, DEFAULT_ALERT_WITHIN_BPS};
+use zkusd::protocol::automation::{AutomationEvent, AutomationOutcome, Strategy, StrategyLimits, TopUpEngine};
+use zkusd::protocol::events::ProtocolEvent;
+use zkusd::protocol::genesis::{Genesis, GenesisCircuitKey};
+use zkusd::protocol::operations::*;
+use zkusd::protocol::state_machine::{OperationPreview, ProtocolStateMachine};
+use zkusd::sim::{Scenario, Simulation};
+use zkusd::storage::{
+    verify_cdp_proof, BackupManager, BackupManifest, BinaryStore, CdpProof, ExportDataset, ExportFilter, ExportFormat,
+    PruningMode, StateManager, StoreLock,
+};
+use zkusd::utils::constants::{BPS_DIVISOR, SIGNATURE_LENGTH};
+use zkusd::utils::crypto::{Hash, KeyPair, PublicKey, Signature};
+use zkusd::utils::logging::{self, LogFormat};
+use zkusd::zkp::circuits::CircuitRegistry;
+use zkusd::zkp::vk_export::{self, VerifierKeySet, VerifierTarget};
+use zkusd::zkp::{ElfSource, SP1Prover, SP1ProverConfig, VerificationKeyRegistry};
 
 /// zkUSD Protocol CLI - Decentralized stablecoin backed by Bitcoin
 #[derive(Parser)]
@@ -22,18 +45,40 @@ use zkusd::utils::crypto::KeyPair;
 #[command(about = "Command-line interface for the zkUSD protocol", long_about = None)]
 #[command(propagate_version = true)]
 struct Cli {
-    /// Path to data directory
+    /// Path to data directory (holds `profiles.json` and each profile's data)
     #[arg(short, long, env = "ZKUSD_DATA_DIR", default_value = "~/.zkusd")]
     data_dir: PathBuf,
 
-    /// Network to connect to
-    #[arg(short, long, env = "ZKUSD_NETWORK", default_value = "mainnet")]
-    network: String,
+    /// Profile to use (defaults to the active profile)
+    #[arg(short, long, env = "ZKUSD_PROFILE")]
+    profile: Option<String>,
+
+    /// Network to connect to; selects the profile of the same name
+    #[arg(short, long, env = "ZKUSD_NETWORK")]
+    network: Option<String>,
 
     /// Enable verbose output
     #[arg(short, long)]
     verbose: bool,
 
+    /// Log format: text or json
+    #[arg(long, env = "ZKUSD_LOG_FORMAT", default_value = "text")]
+    log_format: LogFormat,
+
+    /// Output format: table, json or yaml
+    #[arg(long, env = "ZKUSD_OUTPUT", default_value = "table")]
+    output: OutputFormat,
+
+    /// Preview CDP, pool and redeem commands against the local state without
+    /// signing or submitting them
+    #[arg(long)]
+    simulate: bool,
+
+    /// Remove a lock left on the profile's database by a process that is no
+    /// longer running
+    #[arg(long)]
+    force_unlock: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -45,6 +90,45 @@ enum Commands {
         /// Force overwrite existing configuration
         #[arg(short, long)]
         force: bool,
+
+        /// Initialize the profile's database from this genesis file
+        #[arg(short, long)]
+        genesis: Option<PathBuf>,
+    },
+
+    /// Write a genesis file for a new network from the selected profile
+    InitNetwork {
+        /// Output file
+        #[arg(short, long, default_value = "genesis.json")]
+        output: PathBuf,
+
+        /// Unix timestamp the network starts at (defaults to now)
+        #[arg(long)]
+        genesis_time: Option<u64>,
+
+        /// Chain id operations are signed for (defaults to the network's)
+        #[arg(long)]
+        chain_id: Option<u64>,
+
+        /// Oracle signing key (repeatable; defaults to the profile's oracle keys)
+        #[arg(long = "oracle-key")]
+        oracle_keys: Vec<String>,
+
+        /// Guardian key (repeatable)
+        #[arg(long = "guardian")]
+        guardians: Vec<String>,
+
+        /// Guardian signatures required (defaults to a majority)
+        #[arg(long)]
+        guardian_threshold: Option<u32>,
+
+        /// Verifier key set to pin, as written by `zkp export-keys --target bitcoinos`
+        #[arg(long)]
+        vk_set: Option<PathBuf>,
+
+        /// Bitcoin block to anchor to, as `<height>:<block hash>`
+        #[arg(long)]
+        anchor: Option<String>,
     },
 
     /// CDP (Collateralized Debt Position) operations
@@ -59,6 +143,25 @@ enum Commands {
     #[command(subcommand)]
     Pool(PoolCommands),
 
+    /// zkUSD savings rate operations
+    #[command(subcommand)]
+    Savings(SavingsCommands),
+
+    /// Automatic top-ups that keep CDPs above a collateral ratio
+    #[command(subcommand)]
+    Automate(AutomateCommands),
+
+    /// Redeem zkUSD for collateral from the riskiest CDPs
+    Redeem {
+        /// Amount in cents
+        #[arg(short, long)]
+        amount: u64,
+
+        /// Highest fee accepted in basis points
+        #[arg(long, default_value = "500")]
+        max_fee_bps: u64,
+    },
+
     /// Oracle and price operations
     #[command(subcommand)]
     Oracle(OracleCommands),
@@ -70,9 +173,129 @@ enum Commands {
     /// Protocol status and info
     Status,
 
+    /// Accrued protocol statistics and supply history
+    Stats {
+        /// Days in the rolling window
+        #[arg(short, long, default_value = "7")]
+        days: u64,
+
+        /// Show one epoch instead of the rolling window
+        #[arg(short, long)]
+        epoch: Option<u64>,
+
+        /// Also list the closing supply of each recorded day
+        #[arg(long)]
+        history: bool,
+    },
+
+    /// Settlement status of a submitted operation
+    Receipt {
+        /// Operation hash (hex)
+        id: String,
+    },
+
     /// Key management
     #[command(subcommand)]
     Keys(KeysCommands),
+
+    /// Address book of labelled accounts
+    #[command(subcommand)]
+    Book(BookCommands),
+
+    /// Database maintenance
+    #[command(subcommand)]
+    Db(DbCommands),
+
+    /// Network profiles and protocol configuration
+    #[command(subcommand)]
+    Config(ConfigCommands),
+
+    /// Run a simulation scenario
+    Sim {
+        /// Scenario file (JSON)
+        scenario: PathBuf,
+
+        /// Write the full report as JSON to this file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Export events, transactions or price history to CSV or Parquet
+    Export {
+        /// Dataset to export: events, transactions or prices
+        dataset: String,
+
+        /// Output file
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Output format: csv or parquet (defaults to the output file extension)
+        #[arg(short, long)]
+        format: Option<String>,
+
+        /// Comma-separated columns to keep, in order (defaults to all)
+        #[arg(short, long, value_delimiter = ',')]
+        columns: Vec<String>,
+
+        /// First block to include
+        #[arg(long)]
+        from_block: Option<u64>,
+
+        /// Last block to include
+        #[arg(long)]
+        to_block: Option<u64>,
+
+        /// Earliest unix timestamp to include
+        #[arg(long)]
+        from_time: Option<u64>,
+
+        /// Latest unix timestamp to include
+        #[arg(long)]
+        to_time: Option<u64>,
+    },
+
+    /// Zero-knowledge circuits and guest programs
+    #[command(subcommand)]
+    Zkp(ZkpCommands),
+
+    /// Live dashboard of system health, alerts and at-risk CDPs
+    Monitor {
+        /// RPC endpoint to poll instead of the local database (defaults to
+        /// the profile's `rpc_url`)
+        #[arg(long, env = "ZKUSD_RPC_URL")]
+        rpc: Option<String>,
+
+        /// Seconds between refreshes
+        #[arg(short, long, default_value = "5")]
+        interval: u64,
+    },
+
+    /// Bring the local database up to a peer: install its snapshot if the
+    /// database is fresh, then replay the blocks after it
+    Sync {
+        /// RPC endpoint of the peer (defaults to the profile's `rpc_url`)
+        #[arg(long, env = "ZKUSD_RPC_URL")]
+        rpc: Option<String>,
+
+        /// Only accept a snapshot with this state root
+        #[arg(long)]
+        trust_root: Option<String>,
+
+        /// Only accept a snapshot carrying a light-client proof
+        #[arg(long)]
+        require_proof: bool,
+
+        /// Accept a snapshot with neither a trusted root nor a proof,
+        /// trusting the peer
+        #[arg(long, conflicts_with_all = ["trust_root", "require_proof"])]
+        insecure: bool,
+    },
+
+    /// Print a shell completion script
+    Completions {
+        /// Shell to generate completions for
+        shell: Shell,
+    },
 }
 
 #[derive(Subcommand)]
@@ -104,7 +327,7 @@ enum CdpCommands {
 
     /// List all CDPs
     List {
-        /// Filter by owner public key
+        /// Filter by owner (address, hex key or address book label)
         #[arg(short, long)]
         owner: Option<String>,
 
@@ -163,20 +386,75 @@ enum CdpCommands {
         #[arg(short, long)]
         id: String,
     },
+
+    /// Show a CDP's liquidation risk
+    Risk {
+        /// CDP ID to assess
+        #[arg(short, long)]
+        id: String,
+
+        /// Assumed daily BTC price drift in basis points
+        #[arg(long, default_value_t = DEFAULT_DRIFT_BPS_PER_DAY, allow_hyphen_values = true)]
+        drift: i64,
+    },
+
+    /// Watch own CDPs and alert as the BTC price nears their liquidation price
+    Watch {
+        /// Add a CDP owned by this profile to the watch list
+        #[arg(long)]
+        add: Vec<String>,
+
+        /// Remove a CDP from the watch list
+        #[arg(long)]
+        remove: Vec<String>,
+
+        /// Alert when the price is within this many basis points of liquidation
+        #[arg(long, default_value_t = DEFAULT_ALERT_WITHIN_BPS)]
+        within_bps: u64,
+
+        /// POST each alert as JSON to this URL
+        #[arg(long, conflicts_with = "once")]
+        webhook: Option<String>,
+
+        /// Check once at the current price instead of following the oracle
+        #[arg(long)]
+        once: bool,
+    },
+
+    /// Prove a CDP is part of the current state root
+    Prove {
+        /// CDP ID to prove
+        #[arg(short, long)]
+        id: String,
+
+        /// Write the proof to a file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Verify a CDP proof against a published state root
+    VerifyProof {
+        /// Proof file
+        file: PathBuf,
+
+        /// State root to verify against, in hex
+        #[arg(long)]
+        root: String,
+    },
 }
 
 #[derive(Subcommand)]
 enum TokenCommands {
     /// View token balance
     Balance {
-        /// Address to check (defaults to own address)
+        /// Address, hex key or address book label (defaults to own address)
         #[arg(short, long)]
         address: Option<String>,
     },
 
     /// Transfer zkUSD
     Transfer {
-        /// Recipient address
+        /// Recipient address, hex key or address book label
         #[arg(short, long)]
         to: String,
 
@@ -216,6 +494,110 @@ enum PoolCommands {
     },
 }
 
+#[derive(Subcommand)]
+enum SavingsCommands {
+    /// Lock zkUSD in the savings pot
+    Deposit {
+        /// Amount in cents
+        #[arg(short, long)]
+        amount: u64,
+    },
+
+    /// Unlock zkUSD from the savings pot
+    Withdraw {
+        /// Amount in cents (0 = withdraw all)
+        #[arg(short, long, default_value = "0")]
+        amount: u64,
+    },
+
+    /// Accrue savings interest up to the current block
+    Accrue,
+
+    /// View savings pot status
+    Status {
+        /// Show your savings balance
+        #[arg(short, long)]
+        mine: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AutomateCommands {
+    /// Add or replace the top-up strategy for a CDP
+    Add {
+        /// CDP ID
+        #[arg(short, long)]
+        id: String,
+
+        /// Top up when the ratio falls below this (percent)
+        #[arg(long)]
+        floor: u64,
+
+        /// Ratio a top-up restores (percent)
+        #[arg(long)]
+        target: u64,
+
+        /// How to top up: deposit or repay
+        #[arg(long, default_value = "deposit")]
+        action: String,
+
+        /// Whose funds pay: account (this profile's key) or session (this
+        /// profile's key is a session key on the CDP, spending the owner's funds)
+        #[arg(long, default_value = "account")]
+        funding: String,
+
+        /// Most a single top-up may spend (sats or cents)
+        #[arg(long)]
+        max_per_action: Option<u64>,
+
+        /// Most top-ups may spend per period (sats or cents)
+        #[arg(long)]
+        max_per_period: Option<u64>,
+
+        /// Spending period in blocks
+        #[arg(long, default_value = "144")]
+        period_blocks: u64,
+
+        /// Blocks to wait between top-ups
+        #[arg(long, default_value = "1")]
+        cooldown: u64,
+    },
+
+    /// Remove a CDP's strategy
+    Remove {
+        /// CDP ID
+        #[arg(short, long)]
+        id: String,
+    },
+
+    /// Stop acting on a CDP without removing its strategy
+    Pause {
+        /// CDP ID
+        #[arg(short, long)]
+        id: String,
+    },
+
+    /// Resume a paused strategy
+    Resume {
+        /// CDP ID
+        #[arg(short, long)]
+        id: String,
+    },
+
+    /// List strategies and what they spent this period
+    List,
+
+    /// Check strategies against the local state and execute due top-ups
+    Run,
+
+    /// Show the audit log of top-ups
+    Log {
+        /// Most recent entries to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+}
+
 #[derive(Subcommand)]
 enum OracleCommands {
     /// Get current BTC price
@@ -243,6 +625,23 @@ enum VaultCommands {
         #[arg(short, long)]
         id: String,
     },
+
+    /// Generate a proof of reserves from the tracked UTXO set
+    Reserves {
+        /// Confirmations an output needs to count as a reserve
+        #[arg(long, default_value = "1")]
+        min_confirmations: u32,
+
+        /// Write the report to a file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Verify a proof of reserves report
+    VerifyReserves {
+        /// Report file
+        file: PathBuf,
+    },
 }
 
 #[derive(Subcommand)]
@@ -266,6 +665,255 @@ enum KeysCommands {
 
     /// Show current address
     Address,
+
+    /// Show the next nonce and the unused ones still accepted
+    Nonce,
+}
+
+#[derive(Subcommand)]
+enum BookCommands {
+    /// Add or replace a labelled address
+    Add {
+        /// Label to refer to the account by
+        label: String,
+
+        /// Address or hex public key
+        address: String,
+    },
+
+    /// Remove a label
+    Remove {
+        /// Label to remove
+        label: String,
+    },
+
+    /// List labelled addresses
+    List,
+}
+
+#[derive(Subcommand)]
+enum DbCommands {
+    /// Prune history older than the retention window
+    Prune {
+        /// Number of recent blocks of history to keep
+        #[arg(short, long)]
+        keep_blocks: u64,
+    },
+
+    /// Show storage usage statistics
+    Stats,
+
+    /// Create a checksummed backup of the database
+    Backup {
+        /// Only record changes since the latest backup
+        #[arg(short, long)]
+        incremental: bool,
+    },
+
+    /// Restore the database from a backup
+    Restore {
+        /// Backup ID (defaults to the latest backup)
+        #[arg(short, long)]
+        id: Option<String>,
+    },
+
+    /// Verify a backup without restoring it
+    Verify {
+        /// Backup ID
+        #[arg(short, long)]
+        id: String,
+    },
+
+    /// List available backups
+    Backups,
+
+    /// Stream every CDP to a checksummed file for migration
+    ExportCdps {
+        /// Output file
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+
+    /// Import CDPs from a file written by `export-cdps`
+    ImportCdps {
+        /// Input file
+        #[arg(short, long)]
+        input: PathBuf,
+    },
+
+    /// Verify the hash chain of the admin audit log
+    VerifyAudit {
+        /// Also list the recorded actions
+        #[arg(short, long)]
+        list: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ZkpCommands {
+    /// List circuits with their guest ELFs and version hashes
+    Circuits {
+        /// Directory searched for `<circuit_id>.elf` files not embedded in the binary
+        #[arg(short, long, default_value = "./elf")]
+        elf_dir: PathBuf,
+
+        /// Also derive verification keys (slow; requires the `sp1-prover` feature)
+        #[arg(long)]
+        vk: bool,
+    },
+
+    /// Export verification keys for on-chain verifiers (requires the `sp1-prover` feature)
+    ExportKeys {
+        /// Target: bitcoinos (JSON key set) or evm (Solidity contract)
+        #[arg(short, long, default_value = "bitcoinos")]
+        target: String,
+
+        /// Output file
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Directory searched for `<circuit_id>.elf` files not embedded in the binary
+        #[arg(short, long, default_value = "./elf")]
+        elf_dir: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Show the selected profile and its protocol configuration
+    Show {
+        /// List every profile instead
+        #[arg(short, long)]
+        all: bool,
+    },
+
+    /// Set a profile setting, creating the profile if needed
+    ///
+    /// Keys are `network` (which also applies that network's parameter
+    /// preset), `data_dir`, `rpc_url`, `oracle_keys` (comma-separated hex
+    /// public keys) and `params.<name>` for any protocol parameter.
+    Set {
+        /// Setting to change
+        key: String,
+
+        /// New value (empty clears `rpc_url` and `oracle_keys`)
+        value: String,
+    },
+
+    /// Make the selected profile the default
+    Use,
+
+    /// Dry-run protocol parameter changes against the stored state
+    ///
+    /// Reports the CDPs pushed below the minimum ratio, recovery mode and
+    /// invariant checks without changing anything.
+    Preview {
+        /// Changes as `params.<name>=<value>`, applied in order
+        #[arg(required = true)]
+        changes: Vec<String>,
+    },
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// PROFILES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Profiles file in the root data directory
+const PROFILES_FILE: &str = "profiles.json";
+
+/// Named network settings selected with `--profile`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Profile {
+    /// Network the profile connects to
+    network: Network,
+    /// Data directory, relative to the root data directory unless absolute
+    data_dir: PathBuf,
+    /// `zkusd-server` endpoint
+    #[serde(default)]
+    rpc_url: Option<String>,
+    /// Hex public keys of trusted oracle signers
+    #[serde(default)]
+    oracle_keys: Vec<String>,
+    /// OTLP collector receiving spans and metrics (requires the `otel` feature)
+    #[serde(default)]
+    otlp_endpoint: Option<String>,
+    /// Protocol parameters written by `init`
+    params: ProtocolParams,
+}
+
+impl Profile {
+    fn preset(network: Network, data_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            network,
+            data_dir: data_dir.into(),
+            rpc_url: None,
+            oracle_keys: Vec::new(),
+            otlp_endpoint: None,
+            params: ProtocolParams::for_network(network),
+        }
+    }
+
+    /// Apply a `config set` key/value pair
+    fn set(&mut self, key: &str, value: &str) -> anyhow::Result<()> {
+        match key {
+            "network" => {
+                self.network = value.parse()?;
+                self.params = ProtocolParams::for_network(self.network);
+            }
+            "data_dir" => self.data_dir = PathBuf::from(value),
+            "rpc_url" => self.rpc_url = (!value.is_empty()).then(|| value.trim_end_matches('/').to_string()),
+            "otlp_endpoint" => self.otlp_endpoint = (!value.is_empty()).then(|| value.to_string()),
+            "oracle_keys" => {
+                self.oracle_keys = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|key| !key.is_empty())
+                    .map(|key| PublicKey::from_hex_validated(key).map(|pk| pk.to_hex()))
+                    .collect::<Result<_, _>>()?;
+            }
+            _ => {
+                let Some(name) = key.strip_prefix("params.") else {
+                    anyhow::bail!("Unknown setting '{}'", key);
+                };
+                let mut params = serde_json::to_value(&self.params)?;
+                let slot = params
+                    .get_mut(name)
+                    .ok_or_else(|| anyhow::anyhow!("Unknown protocol parameter '{}'", name))?;
+                // Numbers and booleans parse as JSON, anything else is a string
+                *slot = serde_json::from_str(value).unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+                self.params = serde_json::from_value(params)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Contents of the profiles file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Profiles {
+    /// Profile used when none is selected
+    active: String,
+    /// Profiles by name
+    profiles: BTreeMap<String, Profile>,
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        // Mainnet keeps the root directory so existing data dirs carry over
+        let profiles = [
+            ("mainnet", Profile::preset(Network::Mainnet, "")),
+            ("testnet", Profile::preset(Network::Testnet, "testnet")),
+            ("regtest", Profile::preset(Network::Regtest, "regtest")),
+        ]
+        .into_iter()
+        .map(|(name, profile)| (name.to_string(), profile))
+        .collect();
+
+        Self {
+            active: "mainnet".to_string(),
+            profiles,
+        }
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -273,33 +921,157 @@ enum KeysCommands {
 // ═══════════════════════════════════════════════════════════════════════════════
 
 fn main() {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive(tracing::Level::INFO.into()),
-        )
-        .init();
-
-    let cli = Cli::parse();
-    let term = Term::stdout();
-
-    if let Err(e) = run_command(&cli, &term) {
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    // Initialize logging, exporting spans if the profile names a collector
+    #[cfg(feature = "otel")]
+    let _otel = init_otel(&cli);
+    #[cfg(not(feature = "otel"))]
+    logging::init(cli.log_format, log_level(&cli));
+
+    if let Commands::Completions { shell } = &cli.command {
+        clap_complete::generate(*shell, &mut Cli::command(), "zkusd", &mut std::io::stdout());
+        return;
+    }
+
+    let out = OutputFormatter::new(cli.output);
+    let command = command_path(&matches);
+
+    let result = run_command(&cli, &out)
+        .and_then(|data| Ok(CommandOutput::new(command.as_str(), data)?))
+        .and_then(|output| Ok(out.emit(&output)?));
+    if let Err(e) = result {
+        if !out.is_table() {
+            let _ = out.emit(&CommandOutput::failure(command, &e));
+        }
         eprintln!("{} {}", style("Error:").red().bold(), e);
         std::process::exit(1);
     }
 }
 
-fn run_command(cli: &Cli, term: &Term) -> anyhow::Result<()> {
+/// Log level for the CLI; structured output keeps informational logs out of
+/// the document written to stdout
+fn log_level(cli: &Cli) -> tracing::Level {
+    match cli.output {
+        OutputFormat::Table => tracing::Level::INFO,
+        OutputFormat::Json | OutputFormat::Yaml => tracing::Level::WARN,
+    }
+}
+
+/// Dotted subcommand path naming the command in structured output, e.g.
+/// `cdp.open`
+fn command_path(matches: &ArgMatches) -> String {
+    let mut path = Vec::new();
+    let mut matches = matches;
+    while let Some((name, sub)) = matches.subcommand() {
+        path.push(name);
+        matches = sub;
+    }
+    path.join(".")
+}
+
+/// Start OTLP export for the profile's collector, falling back to plain
+/// logging. The runtime drives the batch exporters and must outlive the guard.
+#[cfg(feature = "otel")]
+fn init_otel(cli: &Cli) -> Option<(zkusd::monitoring::otel::OtelGuard, tokio::runtime::Runtime)> {
+    use zkusd::monitoring::otel::{self, OtelConfig};
+
+    let Some(endpoint) = active_profile(cli).ok().and_then(|(_, profile)| profile.otlp_endpoint) else {
+        logging::init(cli.log_format, log_level(cli));
+        return None;
+    };
+
+    let started = tokio::runtime::Runtime::new().map_err(anyhow::Error::from).and_then(|runtime| {
+        let entered = runtime.enter();
+        let config = OtelConfig::new(endpoint).with_service_name("zkusd-cli");
+        let guard = otel::init(&config, cli.log_format, log_level(cli))?;
+        drop(entered);
+        Ok((guard, runtime))
+    });
+
+    match started {
+        Ok(otel) => Some(otel),
+        Err(e) => {
+            logging::init(cli.log_format, log_level(cli));
+            tracing::warn!(error = %e, "OTLP export disabled");
+            None
+        }
+    }
+}
+
+/// Run the selected command, returning its result for structured output
+fn run_command(cli: &Cli, out: &OutputFormatter) -> anyhow::Result<Value> {
+    if cli.force_unlock {
+        if let Some(owner) = StoreLock::force_unlock(profile_dir(cli)?.join("db"))? {
+            out.line(format!("{} Removed database lock held by {}", style("⚠").yellow(), owner));
+        }
+    }
+
     match &cli.command {
-        Commands::Init { force } => cmd_init(cli, *force, term),
-        Commands::Cdp(cmd) => cmd_cdp(cli, cmd, term),
-        Commands::Token(cmd) => cmd_token(cli, cmd, term),
-        Commands::Pool(cmd) => cmd_pool(cli, cmd, term),
-        Commands::Oracle(cmd) => cmd_oracle(cli, cmd, term),
-        Commands::Vault(cmd) => cmd_vault(cli, cmd, term),
-        Commands::Status => cmd_status(cli, term),
-        Commands::Keys(cmd) => cmd_keys(cli, cmd, term),
+        Commands::Init { force, genesis } => cmd_init(cli, *force, genesis.as_deref(), out),
+        Commands::InitNetwork {
+            output,
+            genesis_time,
+            chain_id,
+            oracle_keys,
+            guardians,
+            guardian_threshold,
+            vk_set,
+            anchor,
+        } => cmd_init_network(
+            cli,
+            output,
+            *genesis_time,
+            *chain_id,
+            oracle_keys,
+            guardians,
+            *guardian_threshold,
+            vk_set.as_deref(),
+            anchor.as_deref(),
+            out,
+        ),
+        Commands::Cdp(cmd) => cmd_cdp(cli, cmd, out),
+        Commands::Token(cmd) => cmd_token(cli, cmd, out),
+        Commands::Pool(cmd) => cmd_pool(cli, cmd, out),
+        Commands::Savings(cmd) => cmd_savings(cli, cmd, out),
+        Commands::Automate(cmd) => cmd_automate(cli, cmd, out),
+        Commands::Redeem { amount, max_fee_bps } => cmd_redeem(cli, *amount, *max_fee_bps, out),
+        Commands::Oracle(cmd) => cmd_oracle(cli, cmd, out),
+        Commands::Vault(cmd) => cmd_vault(cli, cmd, out),
+        Commands::Status => cmd_status(cli, out),
+        Commands::Stats { days, epoch, history } => cmd_stats(cli, *days, *epoch, *history, out),
+        Commands::Receipt { id } => cmd_receipt(cli, id, out),
+        Commands::Keys(cmd) => cmd_keys(cli, cmd, out),
+        Commands::Book(cmd) => cmd_book(cli, cmd, out),
+        Commands::Db(cmd) => cmd_db(cli, cmd, out),
+        Commands::Config(cmd) => cmd_config(cli, cmd, out),
+        Commands::Sim { scenario, output } => cmd_sim(scenario, output.as_ref(), out),
+        Commands::Export {
+            dataset,
+            output,
+            format,
+            columns,
+            from_block,
+            to_block,
+            from_time,
+            to_time,
+        } => {
+            let filter = ExportFilter::new()
+                .with_blocks(from_block.unwrap_or(0), to_block.unwrap_or(u64::MAX))
+                .with_time(from_time.unwrap_or(0), to_time.unwrap_or(u64::MAX));
+            cmd_export(cli, dataset, output, format.as_deref(), columns, &filter, out)
+        }
+        Commands::Zkp(cmd) => cmd_zkp(cmd, out),
+        Commands::Monitor { rpc, interval } => cmd_monitor(cli, rpc.as_deref(), *interval),
+        Commands::Sync {
+            rpc,
+            trust_root,
+            require_proof,
+            insecure,
+        } => cmd_sync(cli, rpc.as_deref(), trust_root.as_deref(), *require_proof, *insecure, out),
+        // Written by `main` before any other output
+        Commands::Completions { .. } => Ok(Value::Null),
     }
 }
 
@@ -307,17 +1079,20 @@ fn run_command(cli: &Cli, term: &Term) -> anyhow::Result<()> {
 // COMMAND HANDLERS
 // ═══════════════════════════════════════════════════════════════════════════════
 
-fn cmd_init(cli: &Cli, force: bool, term: &Term) -> anyhow::Result<()> {
-    let _ = term.write_line(&format!(
+fn cmd_init(cli: &Cli, force: bool, genesis: Option<&Path>, out: &OutputFormatter) -> anyhow::Result<Value> {
+    out.line(format!(
         "{} Initializing zkUSD configuration...",
         style("→").cyan()
     ));
 
-    let data_dir = expand_path(&cli.data_dir)?;
+    let (name, profile) = active_profile(cli)?;
+    let data_dir = profile_dir(cli)?;
 
-    if data_dir.exists() && !force {
+    // The root data directory also holds other profiles, so only an existing
+    // key means this profile was initialized
+    if data_dir.join("key.json").exists() && !force {
         anyhow::bail!(
-            "Data directory already exists: {}. Use --force to overwrite.",
+            "Profile already initialized in {}. Use --force to overwrite.",
             data_dir.display()
         );
     }
@@ -331,37 +1106,156 @@ fn cmd_init(cli: &Cli, force: bool, term: &Term) -> anyhow::Result<()> {
     let key_data = serde_json::json!({
         "public_key": hex::encode(keypair.public_key().as_bytes()),
         "created_at": chrono::Utc::now().to_rfc3339(),
-        "network": &cli.network,
+        "network": profile.network.as_str(),
     });
 
     std::fs::write(&key_path, serde_json::to_string_pretty(&key_data)?)?;
 
-    // Create config
-    let config = ProtocolConfig::default();
+    // Create config from the genesis, or else the profile's parameter preset
+    let genesis = genesis.map(Genesis::load).transpose()?;
+    let config = match &genesis {
+        Some(genesis) if genesis.network != profile.network => anyhow::bail!(
+            "Genesis is for {}, profile '{}' is on {}",
+            genesis.network.as_str(),
+            name,
+            profile.network.as_str()
+        ),
+        Some(genesis) => genesis.config.clone(),
+        None => ProtocolConfig::new(profile.params.clone()).with_chain_id(profile.network.default_chain_id()),
+    };
+    config.validate()?;
     let config_path = data_dir.join("config.json");
     std::fs::write(&config_path, serde_json::to_string_pretty(&config)?)?;
 
-    let _ = term.write_line(&format!(
+    let genesis_hash = match &genesis {
+        Some(genesis) => {
+            std::fs::write(data_dir.join("genesis.json"), genesis.to_json()?)?;
+            let machine = ProtocolStateMachine::from_genesis(BinaryStore::new(data_dir.join("db"))?, genesis)?;
+            let hash = machine.genesis_hash()?.unwrap_or_else(|| genesis.hash());
+            out.line(format!("{} Genesis:  {}", style("✓").green(), hash.to_hex()));
+            Some(hash.to_hex())
+        }
+        None => None,
+    };
+
+    out.line(format!(
         "{} Configuration created at: {}",
         style("✓").green(),
         data_dir.display()
     ));
-    let _ = term.write_line(&format!(
-        "{} Public key: {}",
+    out.line(format!(
+        "{} Address: {}",
         style("✓").green(),
-        hex::encode(keypair.public_key().as_bytes())
+        keypair.public_key().to_address()
     ));
 
-    Ok(())
+    Ok(json!({
+        "profile": name,
+        "network": profile.network,
+        "data_dir": data_dir,
+        "address": keypair.public_key().to_address(),
+        "public_key": keypair.public_key().to_hex(),
+        "genesis_hash": genesis_hash,
+    }))
 }
 
-fn cmd_cdp(cli: &Cli, cmd: &CdpCommands, term: &Term) -> anyhow::Result<()> {
-    let config = load_config(cli)?;
-    let btc_price = get_current_price()?;
+#[allow(clippy::too_many_arguments)]
+fn cmd_init_network(
+    cli: &Cli,
+    output: &Path,
+    genesis_time: Option<u64>,
+    chain_id: Option<u64>,
+    oracle_keys: &[String],
+    guardians: &[String],
+    guardian_threshold: Option<u32>,
+    vk_set: Option<&Path>,
+    anchor: Option<&str>,
+    out: &OutputFormatter,
+) -> anyhow::Result<Value> {
+    let (name, profile) = active_profile(cli)?;
+    let oracle_keys = if oracle_keys.is_empty() { &profile.oracle_keys[..] } else { oracle_keys };
+    let oracle_keys = oracle_keys.iter().map(|key| parse_pubkey(cli, key)).collect::<anyhow::Result<Vec<_>>>()?;
+    let guardians = guardians.iter().map(|key| parse_pubkey(cli, key)).collect::<anyhow::Result<Vec<_>>>()?;
+    let threshold = match guardian_threshold {
+        Some(threshold) => threshold,
+        None if guardians.is_empty() => 0,
+        None => guardians.len() as u32 / 2 + 1,
+    };
 
-    match cmd {
-        CdpCommands::Open { collateral, debt } => {
-            let spinner = create_spinner("Opening CDP...");
+    let config = ProtocolConfig::new(profile.params.clone())
+        .with_chain_id(chain_id.unwrap_or_else(|| profile.network.default_chain_id()));
+    let mut genesis = Genesis::new(profile.network, config)
+        .with_genesis_time(genesis_time.unwrap_or_else(|| chrono::Utc::now().timestamp() as u64))
+        .with_oracle_keys(oracle_keys)
+        .with_guardians(guardians, threshold);
+
+    if let Some(path) = vk_set {
+        let set: VerifierKeySet = serde_json::from_str(&std::fs::read_to_string(expand_path(&path.to_path_buf())?)?)?;
+        genesis.circuit_keys = set
+            .keys
+            .into_iter()
+            .map(|key| {
+                Ok(GenesisCircuitKey {
+                    key_hash: Hash::from_hex(&key.key_hash)?,
+                    circuit_id: key.circuit_id,
+                    version: key.version,
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+    }
+
+    if let Some(anchor) = anchor {
+        let (height, hash) = anchor
+            .split_once(':')
+            .ok_or_else(|| anyhow::anyhow!("Anchor must be <height>:<block hash>, got '{}'", anchor))?;
+        genesis = genesis.with_anchor(height.parse()?, Hash::from_hex(hash)?);
+    }
+
+    genesis.validate()?;
+    std::fs::write(expand_path(&output.to_path_buf())?, genesis.to_json()?)?;
+
+    out.line(format!(
+        "{} Genesis for {} written to {}",
+        style("✓").green(),
+        genesis.network.as_str(),
+        output.display()
+    ));
+    out.line(format!("  Hash:          {}", style(genesis.hash().to_hex()).yellow()));
+    out.line(format!("  Chain id:      {}", genesis.config.chain_id));
+    out.line(format!("  Oracle keys:   {}", genesis.oracle_keys.len()));
+    out.line(format!("  Guardians:     {} of {}", genesis.guardian_threshold, genesis.guardians.len()));
+    out.line(format!("  Circuit keys:  {}", genesis.circuit_keys.len()));
+    if let Some(anchor) = &genesis.anchor {
+        out.line(format!("  Anchor:        {} @ {}", anchor.block_hash.to_hex(), anchor.height));
+    }
+
+    Ok(json!({
+        "profile": name,
+        "output": output,
+        "genesis_hash": genesis.hash().to_hex(),
+        "genesis": genesis,
+    }))
+}
+
+fn cmd_cdp(cli: &Cli, cmd: &CdpCommands, out: &OutputFormatter) -> anyhow::Result<Value> {
+    let config = load_config(cli)?;
+    let btc_price = get_current_price()?;
+
+    let data = match cmd {
+        CdpCommands::Open { collateral, debt } => {
+            if cli.simulate {
+                return simulate(cli, out, |_, owner, nonce| {
+                    Ok(ProtocolOperation::OpenCDP(OpenCDPOp {
+                        owner,
+                        collateral: CollateralAmount::from_sats(*collateral),
+                        initial_debt: (*debt > 0).then(|| TokenAmount::from_cents(*debt)),
+                        nonce,
+                        signature: unsigned(),
+                    }))
+                });
+            }
+
+            let spinner = create_spinner("Opening CDP...");
 
             let keypair = load_keypair(cli)?;
             let mut cdp = CDP::with_collateral(*keypair.public_key(), *collateral, 1, get_block_height())?;
@@ -372,360 +1266,1092 @@ fn cmd_cdp(cli: &Cli, cmd: &CdpCommands, term: &Term) -> anyhow::Result<()> {
 
             spinner.finish_with_message("CDP opened successfully");
 
-            print_cdp_info(&cdp, btc_price, config.params.min_collateral_ratio, term)?;
-            let _ = term.write_line(&format!(
+            let details = print_cdp_info(&cdp, btc_price, config.params.min_collateral_ratio, out);
+            out.line(format!(
                 "\n{} CDP ID: {}",
                 style("✓").green(),
                 style(cdp.id.to_hex()).yellow()
             ));
+            details
         }
 
         CdpCommands::Close { id } => {
             let cdp_id = parse_cdp_id(id)?;
-            let _ = term.write_line(&format!(
+            if cli.simulate {
+                return simulate(cli, out, |_, owner, nonce| {
+                    Ok(ProtocolOperation::CloseCDP(CloseCDPOp {
+                        cdp_id,
+                        owner,
+                        nonce,
+                        signature: unsigned(),
+                        cosignatures: Vec::new(),
+                    }))
+                });
+            }
+            out.line(format!(
                 "{} CDP {} would be closed (dry run)",
                 style("ℹ").blue(),
                 cdp_id.to_hex()
             ));
+            json!({ "cdp_id": cdp_id.to_hex(), "dry_run": true })
         }
 
         CdpCommands::Info { id } => {
             let cdp_id = parse_cdp_id(id)?;
             // In production, load from storage
-            let _ = term.write_line(&format!(
+            out.line(format!(
                 "{} CDP Info for: {}",
                 style("ℹ").blue(),
                 cdp_id.to_hex()
             ));
-            let _ = term.write_line("  (Would load from storage in production)");
+            out.line("  (Would load from storage in production)");
+            json!({ "cdp_id": cdp_id.to_hex(), "cdp": null })
         }
 
         CdpCommands::List { owner, liquidatable } => {
-            let _ = term.write_line(&format!(
+            let owner = owner.as_deref().map(|o| parse_pubkey(cli, o)).transpose()?;
+            out.line(format!(
                 "{} Listing CDPs{}{}",
                 style("→").cyan(),
-                owner.as_ref().map(|o| format!(" for owner {}", o)).unwrap_or_default(),
+                owner.map(|o| format!(" for owner {}", o.to_address())).unwrap_or_default(),
                 if *liquidatable { " (liquidatable only)" } else { "" }
             ));
-            let _ = term.write_line("  (Would query storage in production)");
+            out.line("  (Would query storage in production)");
+            json!({
+                "owner": owner.map(|o| o.to_address()),
+                "liquidatable": liquidatable,
+                "cdps": [],
+            })
         }
 
         CdpCommands::Deposit { id, amount } => {
             let cdp_id = parse_cdp_id(id)?;
+            if cli.simulate {
+                return simulate(cli, out, |_, depositor, nonce| {
+                    Ok(ProtocolOperation::DepositCollateral(DepositCollateralOp {
+                        cdp_id,
+                        depositor,
+                        amount: CollateralAmount::from_sats(*amount),
+                        nonce,
+                        signature: unsigned(),
+                    }))
+                });
+            }
             let collateral = CollateralAmount::from_sats(*amount);
-            let _ = term.write_line(&format!(
+            out.line(format!(
                 "{} Would deposit {} to CDP {}",
                 style("ℹ").blue(),
                 collateral,
                 cdp_id.to_hex()
             ));
+            json!({ "cdp_id": cdp_id.to_hex(), "amount_sats": amount, "dry_run": true })
         }
 
         CdpCommands::Withdraw { id, amount } => {
             let cdp_id = parse_cdp_id(id)?;
+            if cli.simulate {
+                return simulate(cli, out, |_, owner, nonce| {
+                    Ok(ProtocolOperation::WithdrawCollateral(WithdrawCollateralOp {
+                        cdp_id,
+                        owner,
+                        amount: CollateralAmount::from_sats(*amount),
+                        nonce,
+                        signature: unsigned(),
+                        cosignatures: Vec::new(),
+                    }))
+                });
+            }
             let collateral = CollateralAmount::from_sats(*amount);
-            let _ = term.write_line(&format!(
+            out.line(format!(
                 "{} Would withdraw {} from CDP {}",
                 style("ℹ").blue(),
                 collateral,
                 cdp_id.to_hex()
             ));
+            json!({ "cdp_id": cdp_id.to_hex(), "amount_sats": amount, "dry_run": true })
         }
 
         CdpCommands::Mint { id, amount } => {
             let cdp_id = parse_cdp_id(id)?;
+            if cli.simulate {
+                return simulate(cli, out, |_, owner, nonce| {
+                    Ok(ProtocolOperation::MintDebt(MintDebtOp {
+                        cdp_id,
+                        owner,
+                        amount: TokenAmount::from_cents(*amount),
+                        max_fee_bps: BPS_DIVISOR,
+                        nonce,
+                        signature: unsigned(),
+                        cosignatures: Vec::new(),
+                    }))
+                });
+            }
             let debt = TokenAmount::from_cents(*amount);
-            let _ = term.write_line(&format!(
+            out.line(format!(
                 "{} Would mint {} from CDP {}",
                 style("ℹ").blue(),
                 debt,
                 cdp_id.to_hex()
             ));
+            json!({ "cdp_id": cdp_id.to_hex(), "amount_cents": amount, "dry_run": true })
         }
 
         CdpCommands::Repay { id, amount } => {
             let cdp_id = parse_cdp_id(id)?;
+            if cli.simulate {
+                // Repayments are capped at the outstanding debt
+                let amount = if *amount == 0 { u64::MAX } else { *amount };
+                return simulate(cli, out, |_, payer, nonce| {
+                    Ok(ProtocolOperation::RepayDebt(RepayDebtOp {
+                        cdp_id,
+                        payer,
+                        amount: TokenAmount::from_cents(amount),
+                        nonce,
+                        signature: unsigned(),
+                    }))
+                });
+            }
             let debt = if *amount == 0 {
                 "all debt".to_string()
             } else {
                 TokenAmount::from_cents(*amount).to_string()
             };
-            let _ = term.write_line(&format!(
+            out.line(format!(
                 "{} Would repay {} to CDP {}",
                 style("ℹ").blue(),
                 debt,
                 cdp_id.to_hex()
             ));
+            json!({
+                "cdp_id": cdp_id.to_hex(),
+                "amount_cents": (*amount > 0).then_some(*amount),
+                "dry_run": true,
+            })
         }
 
         CdpCommands::Liquidate { id } => {
             let cdp_id = parse_cdp_id(id)?;
-            let _ = term.write_line(&format!(
+            if cli.simulate {
+                return simulate(cli, out, |_, liquidator, nonce| {
+                    Ok(ProtocolOperation::LiquidateCDP(LiquidateCDPOp {
+                        cdp_id,
+                        liquidator,
+                        nonce,
+                        signature: unsigned(),
+                    }))
+                });
+            }
+            out.line(format!(
                 "{} Would attempt to liquidate CDP {}",
                 style("⚠").yellow(),
                 cdp_id.to_hex()
             ));
+            json!({ "cdp_id": cdp_id.to_hex(), "dry_run": true })
         }
-    }
 
-    Ok(())
+        CdpCommands::Risk { id, drift } => {
+            let cdp_id = parse_cdp_id(id)?;
+            let cdp = open_state_reader(cli)?
+                .load_cdp(&cdp_id)?
+                .ok_or_else(|| anyhow::anyhow!("CDP {} not found", cdp_id.to_hex()))?;
+
+            let assumptions = RiskAssumptions::default().with_drift(*drift);
+            let report = assess(&cdp, btc_price, config.effective_mcr(), &assumptions);
+            print_risk_report(&report, *drift, out);
+            json!({ "cdp_id": cdp_id.to_hex(), "drift_bps": drift, "report": report })
+        }
+
+        CdpCommands::Prove { id, output } => {
+            let cdp_id = parse_cdp_id(id)?;
+            let state = open_state_reader(cli)?;
+            let root = state.compute_state_root()?;
+            let proof = state
+                .prove_cdp(&cdp_id)?
+                .ok_or_else(|| anyhow::anyhow!("CDP {} not found", cdp_id.to_hex()))?;
+
+            if let Some(path) = output {
+                std::fs::write(path, serde_json::to_string_pretty(&proof)?)?;
+                out.line(format!("{} Wrote CDP proof to {}", style("✓").green(), path.display()));
+            }
+            print_cdp_proof(&proof, &root, out);
+            json!({ "state_root": root.to_hex(), "proof": proof })
+        }
+
+        CdpCommands::VerifyProof { file, root } => {
+            let proof: CdpProof = serde_json::from_str(&std::fs::read_to_string(file)?)?;
+            let root = Hash::from_hex(root)?;
+            verify_cdp_proof(&root, &proof)?;
+            out.line(format!("{} CDP is part of the state root", style("✓").green()));
+            print_cdp_proof(&proof, &root, out);
+            json!({ "valid": true, "cdp_id": proof.cdp.id.to_hex(), "state_root": root.to_hex() })
+        }
+
+        CdpCommands::Watch { add, remove, within_bps, webhook, once } => {
+            let mut list = load_watch_list(cli)?;
+            if !add.is_empty() || !remove.is_empty() {
+                let owner = load_public_key(cli)?;
+                let state = open_state_reader(cli)?;
+                for id in add {
+                    let cdp_id = parse_cdp_id(id)?;
+                    let cdp = state
+                        .load_cdp(&cdp_id)?
+                        .ok_or_else(|| anyhow::anyhow!("CDP {} not found", cdp_id.to_hex()))?;
+                    if cdp.owner != owner {
+                        anyhow::bail!("CDP {} is not owned by this profile", cdp_id.to_hex());
+                    }
+                    list.add(&cdp_id);
+                }
+                for id in remove {
+                    list.remove(&parse_cdp_id(id)?);
+                }
+                save_watch_list(cli, &list)?;
+
+                out.line(format!("{} Watching {} CDP(s)", style("✓").green(), list.len()));
+                let watching: Vec<String> = list.ids().iter().map(CDPId::to_hex).collect();
+                return Ok(json!({ "watching": watching }));
+            }
+
+            if list.is_empty() {
+                anyhow::bail!("No CDPs watched; add one with `zkusd cdp watch --add <ID>`");
+            }
+
+            let mut watcher = LiquidationWatcher::new(*within_bps);
+            if !*once {
+                return watch_positions(cli, out, &list, watcher, webhook.as_deref(), config.effective_mcr());
+            }
+
+            let cdps = load_watched_cdps(cli, &list)?;
+            let positions: Vec<PositionStatus> = cdps
+                .iter()
+                .map(|cdp| PositionStatus::of(cdp, btc_price, config.effective_mcr()))
+                .collect();
+            out.line(format!("\n{} at {}", style("Watched CDPs").bold().underlined(), format_price(btc_price)));
+            for position in &positions {
+                out.line(format!(
+                    "  {}  liquidates at {:>14}  {:>7.2}% away",
+                    position.cdp_id.to_hex(),
+                    format_price(position.liquidation_price),
+                    position.distance_bps as f64 / 100.0
+                ));
+            }
+
+            let alerts = watcher.check(&cdps, btc_price, config.effective_mcr());
+            for alert in &alerts {
+                notify_watch_alert(alert, out);
+            }
+            json!({
+                "btc_price": btc_price,
+                "within_bps": within_bps,
+                "positions": positions,
+                "alerts": alerts,
+            })
+        }
+    };
+
+    Ok(data)
 }
 
-fn cmd_token(_cli: &Cli, cmd: &TokenCommands, term: &Term) -> anyhow::Result<()> {
-    match cmd {
+fn cmd_token(cli: &Cli, cmd: &TokenCommands, out: &OutputFormatter) -> anyhow::Result<Value> {
+    let data = match cmd {
         TokenCommands::Balance { address } => {
-            let addr = address.as_deref().unwrap_or("(self)");
-            let _ = term.write_line(&format!(
+            let address = address.as_deref().map(|a| parse_pubkey(cli, a)).transpose()?.map(|pk| pk.to_address());
+            let addr = address.clone().unwrap_or_else(|| "(self)".to_string());
+            out.line(format!(
                 "{} zkUSD Balance for {}",
                 style("→").cyan(),
                 addr
             ));
             // In production, query actual balance
-            let _ = term.write_line(&format!("  Balance: {}", style("$0.00").green()));
+            out.line(format!("  Balance: {}", style("$0.00").green()));
+            json!({ "address": address, "balance_cents": 0 })
         }
 
         TokenCommands::Transfer { to, amount } => {
+            let recipient = parse_pubkey(cli, to)?;
             let tokens = TokenAmount::from_cents(*amount);
-            let _ = term.write_line(&format!(
+            out.line(format!(
                 "{} Would transfer {} to {}",
                 style("ℹ").blue(),
                 tokens,
-                to
+                recipient.to_address()
             ));
+            json!({ "to": recipient.to_address(), "amount_cents": amount, "dry_run": true })
         }
 
         TokenCommands::Supply => {
-            let _ = term.write_line(&format!(
+            out.line(format!(
                 "{} zkUSD Total Supply",
                 style("→").cyan()
             ));
             // In production, query actual supply
-            let _ = term.write_line(&format!("  Total Supply: {}", style("$0.00").green()));
-            let _ = term.write_line(&format!("  Circulating: {}", style("$0.00").green()));
+            out.line(format!("  Total Supply: {}", style("$0.00").green()));
+            out.line(format!("  Circulating: {}", style("$0.00").green()));
+            json!({ "total_supply_cents": 0, "circulating_cents": 0 })
         }
-    }
+    };
 
-    Ok(())
+    Ok(data)
 }
 
-fn cmd_pool(_cli: &Cli, cmd: &PoolCommands, term: &Term) -> anyhow::Result<()> {
-    match cmd {
+fn cmd_pool(cli: &Cli, cmd: &PoolCommands, out: &OutputFormatter) -> anyhow::Result<Value> {
+    let data = match cmd {
         PoolCommands::Deposit { amount } => {
+            if cli.simulate {
+                return simulate(cli, out, |_, depositor, nonce| {
+                    Ok(ProtocolOperation::StabilityDeposit(StabilityDepositOp {
+                        depositor,
+                        amount: TokenAmount::from_cents(*amount),
+                        frontend: None,
+                        nonce,
+                        signature: unsigned(),
+                    }))
+                });
+            }
             let tokens = TokenAmount::from_cents(*amount);
-            let _ = term.write_line(&format!(
+            out.line(format!(
                 "{} Would deposit {} to stability pool",
                 style("ℹ").blue(),
                 tokens
             ));
+            json!({ "amount_cents": amount, "dry_run": true })
         }
 
         PoolCommands::Withdraw { amount } => {
+            if cli.simulate {
+                return simulate(cli, out, |machine, depositor, nonce| {
+                    let amount = match amount {
+                        0 => machine.stability_pool().get_current_value(&depositor),
+                        amount => TokenAmount::from_cents(*amount),
+                    };
+                    Ok(ProtocolOperation::StabilityWithdraw(StabilityWithdrawOp {
+                        depositor,
+                        amount,
+                        nonce,
+                        signature: unsigned(),
+                    }))
+                });
+            }
             let msg = if *amount == 0 {
                 "all".to_string()
             } else {
                 TokenAmount::from_cents(*amount).to_string()
             };
-            let _ = term.write_line(&format!(
+            out.line(format!(
                 "{} Would withdraw {} from stability pool",
                 style("ℹ").blue(),
                 msg
             ));
+            json!({ "amount_cents": (*amount > 0).then_some(*amount), "dry_run": true })
         }
 
         PoolCommands::Claim => {
-            let _ = term.write_line(&format!(
+            if cli.simulate {
+                return simulate(cli, out, |_, depositor, nonce| {
+                    Ok(ProtocolOperation::ClaimGains(ClaimGainsOp {
+                        depositor,
+                        nonce,
+                        signature: unsigned(),
+                    }))
+                });
+            }
+            out.line(format!(
                 "{} Would claim BTC gains from stability pool",
                 style("ℹ").blue()
             ));
+            json!({ "dry_run": true })
         }
 
         PoolCommands::Status { mine } => {
-            let _ = term.write_line(&format!(
+            out.line(format!(
                 "{} Stability Pool Status",
                 style("→").cyan()
             ));
-            let _ = term.write_line(&format!("  Total Deposits: {}", style("$0.00").green()));
-            let _ = term.write_line(&format!("  Total BTC Gains: {}", style("0.00000000 BTC").yellow()));
+            out.line(format!("  Total Deposits: {}", style("$0.00").green()));
+            out.line(format!("  Total BTC Gains: {}", style("0.00000000 BTC").yellow()));
+
+            if *mine {
+                out.line(format!("\n  {} Your Position:", style("→").cyan()));
+                out.line(format!("    Deposited: {}", style("$0.00").green()));
+                out.line(format!("    Claimable BTC: {}", style("0.00000000 BTC").yellow()));
+            }
+            let mut data = json!({ "total_deposits_cents": 0, "total_btc_gains_sats": 0 });
+            if *mine {
+                data["mine"] = json!({ "deposited_cents": 0, "claimable_sats": 0 });
+            }
+            data
+        }
+    };
+
+    Ok(data)
+}
+
+fn cmd_savings(cli: &Cli, cmd: &SavingsCommands, out: &OutputFormatter) -> anyhow::Result<Value> {
+    let data = match cmd {
+        SavingsCommands::Deposit { amount } => {
+            let tokens = TokenAmount::from_cents(*amount);
+            out.line(format!(
+                "{} Would deposit {} to savings pot",
+                style("ℹ").blue(),
+                tokens
+            ));
+            json!({ "amount_cents": amount, "dry_run": true })
+        }
+
+        SavingsCommands::Withdraw { amount } => {
+            let msg = if *amount == 0 {
+                "all".to_string()
+            } else {
+                TokenAmount::from_cents(*amount).to_string()
+            };
+            out.line(format!(
+                "{} Would withdraw {} from savings pot",
+                style("ℹ").blue(),
+                msg
+            ));
+            json!({ "amount_cents": (*amount > 0).then_some(*amount), "dry_run": true })
+        }
+
+        SavingsCommands::Accrue => {
+            let block_height = get_block_height();
+            out.line(format!(
+                "{} Would accrue savings interest at block {}",
+                style("ℹ").blue(),
+                block_height
+            ));
+            json!({ "block_height": block_height, "dry_run": true })
+        }
 
+        SavingsCommands::Status { mine } => {
+            let pot = open_state_reader(cli)?.load_savings()?.unwrap_or_default();
+            out.line(format!(
+                "{} Savings Pot Status",
+                style("→").cyan()
+            ));
+            out.line(format!(
+                "  Savings Rate: {}",
+                style(format!("{:.2}%", pot.rate_bps() as f64 / 100.0)).cyan()
+            ));
+            out.line(format!("  Total Locked: {}", style(pot.total_locked()).green()));
+            out.line(format!("  Fee Reserve: {}", style(pot.reserve()).green()));
+            out.line(format!("  Interest Paid: {}", style(pot.interest_paid()).green()));
+            out.line(format!("  Depositors: {}", style(pot.depositor_count()).cyan()));
+
+            let mut data = json!({
+                "rate_bps": pot.rate_bps(),
+                "total_locked_cents": pot.total_locked().cents(),
+                "reserve_cents": pot.reserve().cents(),
+                "interest_paid_cents": pot.interest_paid().cents(),
+                "depositors": pot.depositor_count(),
+            });
             if *mine {
-                let _ = term.write_line(&format!("\n  {} Your Position:", style("→").cyan()));
-                let _ = term.write_line(&format!("    Deposited: {}", style("$0.00").green()));
-                let _ = term.write_line(&format!("    Claimable BTC: {}", style("0.00000000 BTC").yellow()));
+                let keypair = load_keypair(cli)?;
+                let balance = pot.balance_of(keypair.public_key());
+                out.line(format!("\n  {} Your Position:", style("→").cyan()));
+                out.line(format!("    Balance: {}", style(balance).green()));
+                data["mine"] = json!({ "balance_cents": balance.cents() });
+            }
+            data
+        }
+    };
+
+    Ok(data)
+}
+
+fn cmd_automate(cli: &Cli, cmd: &AutomateCommands, out: &OutputFormatter) -> anyhow::Result<Value> {
+    let mut strategies = load_strategies(cli)?;
+
+    let data = match cmd {
+        AutomateCommands::Add {
+            id,
+            floor,
+            target,
+            action,
+            funding,
+            max_per_action,
+            max_per_period,
+            period_blocks,
+            cooldown,
+        } => {
+            let limits = StrategyLimits {
+                max_per_action: max_per_action.unwrap_or(u64::MAX),
+                max_per_period: max_per_period.unwrap_or(u64::MAX),
+                period_blocks: *period_blocks,
+                cooldown_blocks: *cooldown,
+            };
+            let strategy = Strategy::new(parse_cdp_id(id)?, *floor, *target, action.parse()?)
+                .with_funding(funding.parse()?)
+                .with_limits(limits);
+            strategy.validate()?;
+
+            strategies.retain(|s| s.cdp_id != strategy.cdp_id);
+            strategies.push(strategy.clone());
+            save_strategies(cli, &strategies)?;
+            out.line(format!(
+                "{} CDP {} tops up to {}% below {}% ({:?}, {:?} funding)",
+                style("✓").green(),
+                strategy.cdp_id.to_hex(),
+                strategy.target,
+                strategy.floor,
+                strategy.action,
+                strategy.funding
+            ));
+            json!({ "strategy": strategy })
+        }
+
+        AutomateCommands::Remove { id } => {
+            let cdp_id = parse_cdp_id(id)?;
+            let before = strategies.len();
+            strategies.retain(|s| s.cdp_id != cdp_id);
+            if strategies.len() == before {
+                anyhow::bail!("No strategy for CDP {}", cdp_id.to_hex());
+            }
+            save_strategies(cli, &strategies)?;
+            out.line(format!("{} Removed strategy for CDP {}", style("✓").green(), cdp_id.to_hex()));
+            json!({ "cdp_id": cdp_id.to_hex(), "removed": true })
+        }
+
+        AutomateCommands::Pause { id } | AutomateCommands::Resume { id } => {
+            let cdp_id = parse_cdp_id(id)?;
+            let enabled = matches!(cmd, AutomateCommands::Resume { .. });
+            let strategy = strategies
+                .iter_mut()
+                .find(|s| s.cdp_id == cdp_id)
+                .ok_or_else(|| anyhow::anyhow!("No strategy for CDP {}", cdp_id.to_hex()))?;
+            strategy.enabled = enabled;
+            save_strategies(cli, &strategies)?;
+            let verb = if enabled { "Resumed" } else { "Paused" };
+            out.line(format!("{} {} strategy for CDP {}", style("✓").green(), verb, cdp_id.to_hex()));
+            json!({ "cdp_id": cdp_id.to_hex(), "enabled": enabled })
+        }
+
+        AutomateCommands::List => {
+            if strategies.is_empty() {
+                out.line("  (no strategies)");
+            }
+            for strategy in &strategies {
+                out.line(format!(
+                    "  {}  {}% → {}%  {:?}/{:?}  spent {}{}",
+                    strategy.cdp_id.to_hex(),
+                    strategy.floor,
+                    strategy.target,
+                    strategy.action,
+                    strategy.funding,
+                    strategy.spent_in_period,
+                    if strategy.enabled { "" } else { "  (paused)" }
+                ));
+            }
+            json!({ "strategies": strategies })
+        }
+
+        AutomateCommands::Run => {
+            if strategies.is_empty() {
+                anyhow::bail!("No strategies; add one with `zkusd automate add`");
+            }
+            let mut machine = open_state_machine(cli)?;
+            machine.load_state()?;
+
+            let mut engine = TopUpEngine::new(load_keypair(cli)?).with_strategies(strategies);
+            let events = engine.run(&mut machine);
+            machine.save_state()?;
+            save_strategies(cli, engine.strategies())?;
+            append_automation_log(cli, &events)?;
+
+            if events.is_empty() {
+                out.line(format!("{} All CDPs in band", style("✓").green()));
+            }
+            for event in &events {
+                print_automation_event(event, out);
             }
+            json!({ "events": events })
         }
+
+        AutomateCommands::Log { limit } => {
+            let events = load_automation_log(cli)?;
+            let recent = &events[events.len().saturating_sub(*limit)..];
+            if recent.is_empty() {
+                out.line("  (no top-ups yet)");
+            }
+            for event in recent {
+                print_automation_event(event, out);
+            }
+            json!({ "events": recent })
+        }
+    };
+
+    Ok(data)
+}
+
+fn print_automation_event(event: &AutomationEvent, out: &OutputFormatter) {
+    let outcome = match &event.outcome {
+        AutomationOutcome::Executed { amount, capped, new_ratio } => format!(
+            "{} {:?} {} → {}%{}",
+            style("✓").green(),
+            event.action,
+            amount,
+            new_ratio,
+            if *capped { " (capped)" } else { "" }
+        ),
+        AutomationOutcome::Failed { amount, error } => {
+            format!("{} {:?} {} failed: {}", style("✗").red(), event.action, amount, error)
+        }
+        AutomationOutcome::CoolingDown { until } => {
+            format!("{} cooling down until block {}", style("…").yellow(), until)
+        }
+        AutomationOutcome::BudgetExhausted => format!("{} period budget spent", style("⚠").yellow()),
+        AutomationOutcome::Missing => format!("{} CDP not found or closed", style("⚠").yellow()),
+    };
+    out.line(format!(
+        "  block {:>8}  {}  at {}%  {}",
+        event.block_height,
+        event.cdp_id.to_hex(),
+        event.ratio,
+        outcome
+    ));
+}
+
+fn cmd_redeem(cli: &Cli, amount: u64, max_fee_bps: u64, out: &OutputFormatter) -> anyhow::Result<Value> {
+    if cli.simulate {
+        return simulate(cli, out, |_, redeemer, nonce| {
+            Ok(ProtocolOperation::Redeem(RedeemOp {
+                redeemer,
+                amount: TokenAmount::from_cents(amount),
+                max_fee_bps,
+                first_cdp_hint: None,
+                last_cdp_hint: None,
+                max_cdps: 0,
+                nonce,
+                signature: unsigned(),
+            }))
+        });
     }
 
-    Ok(())
+    let tokens = TokenAmount::from_cents(amount);
+    out.line(format!(
+        "{} Would redeem {} (max fee {} bps)",
+        style("ℹ").blue(),
+        tokens,
+        max_fee_bps
+    ));
+    Ok(json!({ "amount_cents": amount, "max_fee_bps": max_fee_bps, "dry_run": true }))
 }
 
-fn cmd_oracle(_cli: &Cli, cmd: &OracleCommands, term: &Term) -> anyhow::Result<()> {
-    match cmd {
+fn cmd_oracle(cli: &Cli, cmd: &OracleCommands, out: &OutputFormatter) -> anyhow::Result<Value> {
+    let data = match cmd {
         OracleCommands::Price => {
             let btc_price = get_current_price()?;
             let formatted = format_price(btc_price);
 
-            let _ = term.write_line(&format!(
+            out.line(format!(
                 "{} Current BTC Price",
                 style("→").cyan()
             ));
-            let _ = term.write_line(&format!("  Price: {}", style(&formatted).green().bold()));
-            let _ = term.write_line(&format!("  Sources: {}", style("3").cyan()));
-            let _ = term.write_line(&format!("  Confidence: {}%", style("95").cyan()));
+            out.line(format!("  Price: {}", style(&formatted).green().bold()));
+            out.line(format!("  Sources: {}", style("3").cyan()));
+            out.line(format!("  Confidence: {}%", style("95").cyan()));
+            json!({ "price_cents": btc_price, "sources": 3, "confidence": 95 })
         }
 
         OracleCommands::History { count } => {
-            let _ = term.write_line(&format!(
+            out.line(format!(
                 "{} Price History (last {} entries)",
                 style("→").cyan(),
                 count
             ));
-            let _ = term.write_line("  (Would show price history in production)");
+            out.line("  (Would show price history in production)");
+            json!({ "count": count, "entries": [] })
         }
 
         OracleCommands::Sources => {
-            let _ = term.write_line(&format!(
+            let sources = ["CoinGecko", "Binance", "Kraken"];
+            out.line(format!(
                 "{} Oracle Sources",
                 style("→").cyan()
             ));
-            let _ = term.write_line(&format!(
-                "  {} CoinGecko      - {}",
-                style("●").green(),
-                style("Online").green()
-            ));
-            let _ = term.write_line(&format!(
-                "  {} Binance        - {}",
-                style("●").green(),
-                style("Online").green()
-            ));
-            let _ = term.write_line(&format!(
-                "  {} Kraken         - {}",
-                style("●").green(),
-                style("Online").green()
+            for source in sources {
+                out.line(format!(
+                    "  {} {:<14} - {}",
+                    style("●").green(),
+                    source,
+                    style("Online").green()
+                ));
+            }
+
+            let (name, profile) = active_profile(cli)?;
+            out.line(format!(
+                "\n{} Trusted signers ({} profile)",
+                style("→").cyan(),
+                name
             ));
+            if profile.oracle_keys.is_empty() {
+                out.line("  (none configured)");
+            }
+            for key in &profile.oracle_keys {
+                out.line(format!("  {} {}", style("●").green(), key));
+            }
+            json!({
+                "sources": sources.map(|name| json!({ "name": name, "online": true })),
+                "profile": name,
+                "signers": profile.oracle_keys,
+            })
         }
-    }
+    };
 
-    Ok(())
+    Ok(data)
 }
 
-fn cmd_vault(_cli: &Cli, cmd: &VaultCommands, term: &Term) -> anyhow::Result<()> {
-    match cmd {
+fn cmd_vault(cli: &Cli, cmd: &VaultCommands, out: &OutputFormatter) -> anyhow::Result<Value> {
+    let data = match cmd {
         VaultCommands::Status => {
-            let _ = term.write_line(&format!(
+            out.line(format!(
                 "{} Vault Status",
                 style("→").cyan()
             ));
-            let _ = term.write_line(&format!("  Total Collateral: {}", style("0.00000000 BTC").yellow()));
-            let _ = term.write_line(&format!("  Active CDPs: {}", style("0").cyan()));
+            out.line(format!("  Total Collateral: {}", style("0.00000000 BTC").yellow()));
+            out.line(format!("  Active CDPs: {}", style("0").cyan()));
+            json!({ "total_collateral_sats": 0, "active_cdps": 0 })
         }
 
         VaultCommands::Collateral { id } => {
             let cdp_id = parse_cdp_id(id)?;
-            let _ = term.write_line(&format!(
+            out.line(format!(
                 "{} Collateral for CDP {}",
                 style("→").cyan(),
                 cdp_id.to_hex()
             ));
-            let _ = term.write_line(&format!("  Locked: {}", style("0.00000000 BTC").yellow()));
+            out.line(format!("  Locked: {}", style("0.00000000 BTC").yellow()));
+            json!({ "cdp_id": cdp_id.to_hex(), "locked_sats": 0 })
+        }
+
+        VaultCommands::Reserves { min_confirmations, output } => {
+            let vault = open_state_reader(cli)?.load_vault()?.unwrap_or_default();
+            let utxos = UtxoStore::new(BinaryStore::open_read_only(profile_dir(cli)?.join("db"))?).load()?;
+            let height = utxos.tip_height().unwrap_or(0);
+            let proof = vault.proof_of_reserves(utxos.reserves(height, *min_confirmations));
+
+            if let Some(path) = output {
+                std::fs::write(path, serde_json::to_string_pretty(&proof)?)?;
+                out.line(format!("{} Wrote proof of reserves to {}", style("✓").green(), path.display()));
+            }
+            print_proof_of_reserves(&proof, out);
+            json!({ "height": height, "proof": proof })
         }
+
+        VaultCommands::VerifyReserves { file } => {
+            let proof: ProofOfReserves = serde_json::from_str(&std::fs::read_to_string(file)?)?;
+            proof.verify()?;
+            out.line(format!("{} Proof of reserves is consistent", style("✓").green()));
+            print_proof_of_reserves(&proof, out);
+            json!({ "valid": true, "solvent": proof.is_solvent(), "merkle_root": proof.merkle_root.to_hex() })
+        }
+    };
+
+    Ok(data)
+}
+
+fn print_cdp_proof(proof: &CdpProof, root: &Hash, out: &OutputFormatter) {
+    out.line(format!("{} CDP Proof for: {}", style("→").cyan(), proof.cdp.id.to_hex()));
+    out.line(format!("  State Root:   {}", root.to_hex()));
+    out.line(format!("  Collateral:   {} sats", proof.cdp.collateral_sats));
+    out.line(format!("  Debt:         {} cents", proof.cdp.debt_cents));
+    out.line(format!("  Leaf Index:   {}", proof.index));
+    out.line(format!("  Path Length:  {}", proof.path.len()));
+}
+
+fn print_proof_of_reserves(proof: &ProofOfReserves, out: &OutputFormatter) {
+    out.line(format!("{} Proof of Reserves", style("→").cyan()));
+    out.line(format!("  Merkle Root:  {}", proof.merkle_root.to_hex()));
+    out.line(format!("  Outputs:      {}", proof.utxos.len()));
+    out.line(format!("  Reserves:     {}", style(proof.reserves.to_string_formatted()).yellow()));
+    out.line(format!("  Liabilities:  {}", style(proof.liabilities.to_string_formatted()).yellow()));
+    out.line(format!("  Unattributed: {}", proof.unattributed.to_string_formatted()));
+    for cdp in proof.shortfalls() {
+        out.line(format!(
+            "  {} CDP {} backed by {} of {}",
+            style("!").red(),
+            cdp.cdp_id.to_hex(),
+            cdp.backing.to_string_formatted(),
+            cdp.ledger.to_string_formatted()
+        ));
+    }
+    if proof.is_solvent() {
+        out.line(format!("  Status:       {}", style("Solvent").green()));
+    } else {
+        out.line(format!("  Status:       {}", style("Undercollateralized").red()));
     }
+}
 
-    Ok(())
+fn cmd_zkp(cmd: &ZkpCommands, out: &OutputFormatter) -> anyhow::Result<Value> {
+    let data = match cmd {
+        ZkpCommands::Circuits { elf_dir, vk } => {
+            let config = SP1ProverConfig {
+                cache_proofs: false,
+                ..SP1ProverConfig::local(expand_path(elf_dir)?)
+            };
+            let mut prover = SP1Prover::new(config)?;
+            let elfs = prover.elfs()?;
+
+            out.line(format!("{} Circuits", style("→").cyan()));
+            let mut circuits = Vec::new();
+            for circuit in CircuitRegistry::new().circuits() {
+                out.line(format!(
+                    "  {} v{} (~{} constraints) - {}",
+                    style(circuit.id).cyan(),
+                    circuit.version,
+                    circuit.constraints,
+                    circuit.description
+                ));
+
+                let mut entry = json!({
+                    "id": circuit.id,
+                    "version": circuit.version,
+                    "constraints": circuit.constraints,
+                    "description": circuit.description,
+                    "elf": null,
+                });
+                let Some(elf) = elfs.iter().find(|elf| elf.circuit_id == circuit.id) else {
+                    out.line(format!("    ELF:  {}", style("missing").red()));
+                    circuits.push(entry);
+                    continue;
+                };
+                let source = match &elf.source {
+                    ElfSource::Embedded => "embedded".to_string(),
+                    ElfSource::Registered => "registered".to_string(),
+                    ElfSource::File(path) => path.display().to_string(),
+                };
+                out.line(format!("    ELF:  {} ({} bytes)", source, elf.size));
+                out.line(format!("    Hash: {}", style(elf.hash.to_hex()).yellow()));
+                entry["elf"] = json!({ "source": source, "size": elf.size, "hash": elf.hash.to_hex() });
+                if *vk {
+                    let key = prover.verification_key(circuit.id)?;
+                    out.line(format!("    VK:   {}", style(&key).yellow()));
+                    entry["verification_key"] = json!(key);
+                }
+                circuits.push(entry);
+            }
+            json!({ "circuits": circuits })
+        }
+
+        ZkpCommands::ExportKeys { target, output, elf_dir } => {
+            let target: VerifierTarget = target.parse()?;
+            let config = SP1ProverConfig {
+                cache_proofs: false,
+                ..SP1ProverConfig::local(expand_path(elf_dir)?)
+            };
+            let mut prover = SP1Prover::new(config)?;
+
+            let spinner = create_spinner("Deriving verification keys...");
+            let mut keys = VerificationKeyRegistry::new();
+            for elf in prover.elfs()? {
+                keys.register(prover.export_verification_key(&elf.circuit_id)?);
+            }
+            spinner.finish_with_message("Keys derived");
+
+            let artifact = vk_export::export(&keys, target)?;
+            std::fs::write(expand_path(output)?, artifact)?;
+
+            out.line(format!(
+                "{} Exported {} verification keys to {}",
+                style("✓").green(),
+                style(keys.current_keys().len()).cyan(),
+                output.display()
+            ));
+            json!({
+                "target": format!("{:?}", target).to_lowercase(),
+                "keys": keys.current_keys().len(),
+                "output": output,
+            })
+        }
+    };
+
+    Ok(data)
+}
+
+fn cmd_stats(cli: &Cli, days: u64, epoch: Option<u64>, history: bool, out: &OutputFormatter) -> anyhow::Result<Value> {
+    let manager = open_state_reader(cli)?;
+    let Some(aggregates) = manager.load_aggregates()? else {
+        out.line(format!("{} No statistics recorded yet", style("ℹ").blue()));
+        return Ok(json!({ "stats": null }));
+    };
+
+    let (title, stats) = match epoch {
+        Some(epoch) => match aggregates.epoch(epoch) {
+            Some(stats) => (format!("Epoch {} ({} blocks)", epoch, aggregates.epoch_blocks()), *stats),
+            None => anyhow::bail!("No statistics for epoch {}", epoch),
+        },
+        None => {
+            let now = chrono::Utc::now().timestamp() as u64;
+            (format!("Last {} days", days), aggregates.rolling(now, days))
+        }
+    };
+
+    let amount = |cents: u64| TokenAmount::from_cents(cents).to_string();
+    out.line(format!("{} {}", style("→").cyan(), style(title).bold()));
+    out.line(format!("  Blocks:            {}-{}", stats.first_block, stats.last_block));
+    out.line(format!("  Mint volume:       {}", style(amount(stats.mint_volume)).cyan()));
+    out.line(format!("  Burn volume:       {}", style(amount(stats.burn_volume)).cyan()));
+    out.line(format!("  Redemption volume: {}", style(amount(stats.redemption_volume)).cyan()));
+    out.line(format!(
+        "  Liquidations:      {} ({} debt)",
+        style(stats.liquidations).cyan(),
+        amount(stats.liquidated_debt)
+    ));
+    out.line(format!("  Fees collected:    {}", style(amount(stats.fees_collected)).green()));
+    out.line(format!("  Closing supply:    {}", style(amount(stats.closing_supply)).yellow()));
+
+    let mut data = json!({
+        "window": match epoch {
+            Some(epoch) => json!({ "epoch": epoch, "epoch_blocks": aggregates.epoch_blocks() }),
+            None => json!({ "days": days }),
+        },
+        "stats": stats,
+    });
+
+    if history {
+        out.line("");
+        out.line(format!("{} Supply history", style("→").cyan()));
+        let mut supply_history = Vec::new();
+        for (day, supply) in aggregates.supply_history() {
+            let date = chrono::DateTime::from_timestamp((day * zkusd::monitoring::SECS_PER_DAY) as i64, 0)
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_else(|| day.to_string());
+            out.line(format!("  {}  {}", date, amount(supply)));
+            supply_history.push(json!({ "date": date, "supply_cents": supply }));
+        }
+        data["supply_history"] = json!(supply_history);
+    }
+
+    Ok(data)
 }
 
-fn cmd_status(cli: &Cli, term: &Term) -> anyhow::Result<()> {
+fn cmd_status(cli: &Cli, out: &OutputFormatter) -> anyhow::Result<Value> {
+    let (_, profile) = active_profile(cli)?;
     let config = load_config(cli)?;
     let btc_price = get_current_price()?;
     let formatted_price = format_price(btc_price);
 
-    let _ = term.write_line("");
-    let _ = term.write_line(&format!(
+    out.line("");
+    out.line(format!(
         "{}",
         style("╔════════════════════════════════════════════════════════════╗").cyan()
     ));
-    let _ = term.write_line(&format!(
+    out.line(format!(
         "{}                    {}                       {}",
         style("║").cyan(),
         style("zkUSD Protocol Status").bold(),
         style("║").cyan()
     ));
-    let _ = term.write_line(&format!(
+    out.line(format!(
         "{}",
         style("╠════════════════════════════════════════════════════════════╣").cyan()
     ));
-    let _ = term.write_line(&format!(
+    out.line(format!(
         "{}  Network:             {:>36}  {}",
         style("║").cyan(),
-        style(&cli.network).green(),
+        style(profile.network.as_str()).green(),
         style("║").cyan()
     ));
-    let _ = term.write_line(&format!(
+    out.line(format!(
         "{}  Version:             {:>36}  {}",
         style("║").cyan(),
         style(zkusd::VERSION).green(),
         style("║").cyan()
     ));
-    let _ = term.write_line(&format!(
+    out.line(format!(
         "{}  Protocol Name:       {:>36}  {}",
         style("║").cyan(),
         style(zkusd::PROTOCOL_NAME).green(),
         style("║").cyan()
     ));
-    let _ = term.write_line(&format!(
+    out.line(format!(
         "{}",
         style("╠════════════════════════════════════════════════════════════╣").cyan()
     ));
-    let _ = term.write_line(&format!(
+    out.line(format!(
         "{}  BTC Price:           {:>36}  {}",
         style("║").cyan(),
         style(&formatted_price).yellow().bold(),
         style("║").cyan()
     ));
-    let _ = term.write_line(&format!(
+    out.line(format!(
         "{}  Min Collateral:      {:>35}%  {}",
         style("║").cyan(),
         style(config.params.min_collateral_ratio).cyan(),
         style("║").cyan()
     ));
-    let _ = term.write_line(&format!(
+    out.line(format!(
         "{}  Liquidation Bonus:   {:>35}%  {}",
         style("║").cyan(),
         style(config.params.liquidation_bonus_bps / 100).cyan(),
         style("║").cyan()
     ));
-    let _ = term.write_line(&format!(
+    out.line(format!(
         "{}",
         style("╠════════════════════════════════════════════════════════════╣").cyan()
     ));
-    let _ = term.write_line(&format!(
+    out.line(format!(
         "{}  Total zkUSD Supply:  {:>36}  {}",
         style("║").cyan(),
         style("$0.00").green(),
         style("║").cyan()
     ));
-    let _ = term.write_line(&format!(
+    out.line(format!(
         "{}  Total Collateral:    {:>36}  {}",
         style("║").cyan(),
         style("0.00000000 BTC").yellow(),
         style("║").cyan()
     ));
-    let _ = term.write_line(&format!(
+    out.line(format!(
         "{}  Active CDPs:         {:>36}  {}",
         style("║").cyan(),
         style("0").cyan(),
         style("║").cyan()
     ));
-    let _ = term.write_line(&format!(
+    out.line(format!(
         "{}",
         style("╚════════════════════════════════════════════════════════════╝").cyan()
     ));
-    let _ = term.write_line("");
+    out.line("");
+
+    Ok(json!({
+        "network": profile.network,
+        "version": zkusd::VERSION,
+        "protocol": zkusd::PROTOCOL_NAME,
+        "btc_price_cents": btc_price,
+        "min_collateral_ratio": config.params.min_collateral_ratio,
+        "liquidation_bonus_bps": config.params.liquidation_bonus_bps,
+        "total_supply_cents": 0,
+        "total_collateral_sats": 0,
+        "active_cdps": 0,
+    }))
+}
 
-    Ok(())
+fn cmd_receipt(cli: &Cli, id: &str, out: &OutputFormatter) -> anyhow::Result<Value> {
+    let manager = open_state_reader(cli)?;
+    let receipt = manager
+        .load_receipt(&Hash::from_hex(id)?)?
+        .ok_or_else(|| anyhow::anyhow!("No receipt for operation {}", id))?;
+
+    out.line(format!("{}", style("Receipt").bold().underlined()));
+    out.line(format!("  Operation:  {} (nonce {})", receipt.operation, receipt.nonce));
+    out.line(format!("  Signer:     {}", receipt.signer.to_address()));
+    out.line(format!("  Status:     {}", style(receipt.status).cyan()));
+    if let Some(height) = receipt.block_height {
+        out.line(format!("  Block:      {}", height));
+    }
+    if let Some(proof) = &receipt.proof_hash {
+        out.line(format!("  Proof:      {}", proof.to_hex()));
+    }
+    if let Some(txid) = &receipt.anchor_txid {
+        out.line(format!("  Anchor tx:  {} ({} confirmations)", txid, receipt.confirmations));
+    }
+    if let Some(error) = &receipt.error {
+        out.line(format!("  Error:      {}", style(error).red()));
+    }
+    Ok(json!(receipt))
 }
 
-fn cmd_keys(cli: &Cli, cmd: &KeysCommands, term: &Term) -> anyhow::Result<()> {
-    match cmd {
+fn cmd_keys(cli: &Cli, cmd: &KeysCommands, out: &OutputFormatter) -> anyhow::Result<Value> {
+    let data = match cmd {
         KeysCommands::Generate { output } => {
             let spinner = create_spinner("Generating new keypair...");
             let keypair = KeyPair::generate();
@@ -739,18 +2365,28 @@ fn cmd_keys(cli: &Cli, cmd: &KeysCommands, term: &Term) -> anyhow::Result<()> {
                     "created_at": chrono::Utc::now().to_rfc3339(),
                 });
                 std::fs::write(path, serde_json::to_string_pretty(&key_data)?)?;
-                let _ = term.write_line(&format!(
+                out.line(format!(
                     "{} Key saved to: {}",
                     style("✓").green(),
                     path.display()
                 ));
             }
 
-            let _ = term.write_line(&format!(
+            out.line(format!(
                 "{} Public Key: {}",
                 style("✓").green(),
                 style(&pubkey_hex).yellow()
             ));
+            out.line(format!(
+                "{} Address:    {}",
+                style("✓").green(),
+                style(keypair.public_key().to_address()).yellow()
+            ));
+            json!({
+                "public_key": pubkey_hex,
+                "address": keypair.public_key().to_address(),
+                "saved_to": output,
+            })
         }
 
         KeysCommands::Import { key } => {
@@ -758,27 +2394,30 @@ fn cmd_keys(cli: &Cli, cmd: &KeysCommands, term: &Term) -> anyhow::Result<()> {
             if bytes.len() != 32 {
                 anyhow::bail!("Invalid private key length");
             }
-            let _ = term.write_line(&format!(
+            out.line(format!(
                 "{} Key imported successfully",
                 style("✓").green()
             ));
+            json!({ "imported": true })
         }
 
         KeysCommands::Export => {
             match load_keypair(cli) {
                 Ok(keypair) => {
                     let pubkey_hex = hex::encode(keypair.public_key().as_bytes());
-                    let _ = term.write_line(&format!(
+                    out.line(format!(
                         "{} Public Key: {}",
                         style("✓").green(),
                         style(&pubkey_hex).yellow()
                     ));
+                    json!({ "public_key": pubkey_hex })
                 }
                 Err(_) => {
-                    let _ = term.write_line(&format!(
+                    out.line(format!(
                         "{} No keypair found. Run 'zkusd init' first.",
                         style("✗").red()
                     ));
+                    json!({ "public_key": null })
                 }
             }
         }
@@ -786,60 +2425,1023 @@ fn cmd_keys(cli: &Cli, cmd: &KeysCommands, term: &Term) -> anyhow::Result<()> {
         KeysCommands::Address => {
             match load_keypair(cli) {
                 Ok(keypair) => {
-                    let pubkey_hex = hex::encode(keypair.public_key().as_bytes());
-                    let _ = term.write_line(&format!(
+                    out.line(format!(
                         "{} Address: {}",
                         style("✓").green(),
-                        style(&pubkey_hex[..40]).yellow()
+                        style(keypair.public_key().to_address()).yellow()
                     ));
+                    json!({ "address": keypair.public_key().to_address() })
                 }
                 Err(_) => {
-                    let _ = term.write_line(&format!(
+                    out.line(format!(
                         "{} No keypair found. Run 'zkusd init' first.",
                         style("✗").red()
                     ));
+                    json!({ "address": null })
                 }
             }
         }
-    }
 
-    Ok(())
+        KeysCommands::Nonce => {
+            let signer = load_public_key(cli)?;
+            let mut machine = open_state_machine(cli)?;
+            machine.load_state()?;
+            let window = machine.nonce_window(&signer);
+
+            out.line(format!("{} Next nonce: {}", style("✓").green(), style(window.next()).yellow()));
+            if !window.gaps().is_empty() {
+                out.line(format!(
+                    "  Unused: {}",
+                    window.gaps().iter().map(u64::to_string).collect::<Vec<_>>().join(", ")
+                ));
+            }
+            out.line(format!("  Accepted from: {}", window.floor()));
+            json!({
+                "next": window.next(),
+                "highest": window.highest(),
+                "floor": window.floor(),
+                "gaps": window.gaps(),
+            })
+        }
+    };
+
+    Ok(data)
 }
 
-// ═══════════════════════════════════════════════════════════════════════════════
-// HELPER FUNCTIONS
-// ═══════════════════════════════════════════════════════════════════════════════
+fn cmd_book(cli: &Cli, cmd: &BookCommands, out: &OutputFormatter) -> anyhow::Result<Value> {
+    let mut book = load_address_book(cli)?;
 
-fn expand_path(path: &PathBuf) -> anyhow::Result<PathBuf> {
-    let path_str = path.to_string_lossy();
-    if path_str.starts_with('~') {
-        let home = std::env::var("HOME")?;
-        Ok(PathBuf::from(path_str.replacen('~', &home, 1)))
-    } else {
-        Ok(path.clone())
-    }
-}
+    let data = match cmd {
+        BookCommands::Add { label, address } => {
+            if PublicKey::parse(label).is_ok() {
+                anyhow::bail!("Label '{}' looks like an address; pick another name", label);
+            }
+            let pubkey = PublicKey::parse(address)
+                .map_err(|e| anyhow::anyhow!("Invalid address: {}", e))?;
+            book.insert(label.clone(), pubkey.to_address());
+            save_address_book(cli, &book)?;
+            out.line(format!(
+                "{} {} → {}",
+                style("✓").green(),
+                label,
+                style(pubkey.to_address()).yellow()
+            ));
+            json!({ "label": label, "address": pubkey.to_address() })
+        }
 
-fn load_config(cli: &Cli) -> anyhow::Result<ProtocolConfig> {
-    let data_dir = expand_path(&cli.data_dir)?;
-    let config_path = data_dir.join("config.json");
+        BookCommands::Remove { label } => {
+            if book.remove(label).is_none() {
+                anyhow::bail!("No address book entry named '{}'", label);
+            }
+            save_address_book(cli, &book)?;
+            out.line(format!("{} Removed {}", style("✓").green(), label));
+            json!({ "label": label, "removed": true })
+        }
 
-    if config_path.exists() {
-        let data = std::fs::read_to_string(&config_path)?;
-        Ok(serde_json::from_str(&data)?)
-    } else {
-        Ok(ProtocolConfig::default())
-    }
+        BookCommands::List => {
+            if book.is_empty() {
+                out.line("  (address book is empty)");
+            }
+            for (label, address) in &book {
+                out.line(format!("  {:<20} {}", label, style(address).yellow()));
+            }
+            json!({ "entries": book })
+        }
+    };
+
+    Ok(data)
 }
 
-fn load_keypair(cli: &Cli) -> anyhow::Result<KeyPair> {
-    let data_dir = expand_path(&cli.data_dir)?;
-    let key_path = data_dir.join("key.json");
+fn cmd_db(cli: &Cli, cmd: &DbCommands, out: &OutputFormatter) -> anyhow::Result<Value> {
+    let data = match cmd {
+        DbCommands::Prune { keep_blocks } => {
+            let manager = open_state_manager(cli)?
+                .with_pruning(PruningMode::Pruned { keep_blocks: *keep_blocks });
+            let state = manager.initialize_if_needed()?;
 
-    if key_path.exists() {
-        // In production, load actual keypair
-        Ok(KeyPair::generate())
-    } else {
+            let spinner = create_spinner("Pruning history...");
+            let stats = manager.prune(state.block_height, state.last_update)?;
+            manager.flush()?;
+            spinner.finish_with_message("Pruning complete");
+
+            out.line(format!(
+                "{} Removed {} entries (keeping last {} blocks)",
+                style("✓").green(),
+                style(stats.total()).cyan(),
+                keep_blocks
+            ));
+            out.line(format!("  Transactions:  {}", stats.transactions));
+            out.line(format!("  Price history: {}", stats.price_entries));
+            out.line(format!("  Events:        {}", stats.event_entries));
+            out.line(format!("  Block logs:    {}", stats.block_entries));
+            out.line(format!("  Receipts:      {}", stats.receipts));
+            json!({ "keep_blocks": keep_blocks, "removed": stats.total(), "stats": stats })
+        }
+
+        DbCommands::Stats => {
+            let manager = open_state_reader(cli)?;
+            let stats = manager.storage_stats()?;
+
+            out.line(format!(
+                "{} Storage Usage",
+                style("→").cyan()
+            ));
+            out.line(format!("  CDPs:          {}", style(stats.cdps).cyan()));
+            out.line(format!("  Balances:      {}", style(stats.balances).cyan()));
+            out.line(format!("  Transactions:  {}", style(stats.transactions).cyan()));
+            out.line(format!("  Price entries: {}", style(stats.price_entries).cyan()));
+            out.line(format!("  Event entries: {}", style(stats.event_entries).cyan()));
+            out.line(format!("  Total keys:    {}", style(stats.total_keys).cyan()));
+            out.line(format!("  Total size:    {} bytes", style(stats.total_bytes).yellow()));
+            json!(stats)
+        }
+
+        DbCommands::Backup { incremental } => {
+            let manager = open_state_manager(cli)?;
+            let backups = open_backup_manager(cli)?;
+            let now = chrono::Utc::now().timestamp() as u64;
+
+            let spinner = create_spinner("Creating backup...");
+            let manifest = if *incremental {
+                backups.create_incremental(&manager, now)?
+            } else {
+                backups.create_full(&manager, now)?
+            };
+            spinner.finish_with_message("Backup complete");
+
+            print_backup_manifest(&manifest, out);
+            json!(manifest)
+        }
+
+        DbCommands::Restore { id } => {
+            let manager = open_state_manager(cli)?;
+            let backups = open_backup_manager(cli)?;
+            let id = match id {
+                Some(id) => id.clone(),
+                None => backups
+                    .latest()?
+                    .map(|m| m.id)
+                    .ok_or_else(|| anyhow::anyhow!("No backups found"))?,
+            };
+
+            let spinner = create_spinner("Verifying and restoring backup...");
+            let manifest = backups.restore(&id, &manager)?;
+            spinner.finish_with_message("Restore complete");
+
+            print_backup_manifest(&manifest, out);
+            json!(manifest)
+        }
+
+        DbCommands::Verify { id } => {
+            let backups = open_backup_manager(cli)?;
+            let manifest = backups.verify(id)?;
+            out.line(format!(
+                "{} Backup {} is intact",
+                style("✓").green(),
+                style(&manifest.id).yellow()
+            ));
+            json!({ "intact": true, "manifest": manifest })
+        }
+
+        DbCommands::Backups => {
+            let backups = open_backup_manager(cli)?;
+            out.line(format!(
+                "{} Backups in {}",
+                style("→").cyan(),
+                backups.dir().display()
+            ));
+            let manifests = backups.list()?;
+            for manifest in &manifests {
+                out.line(format!(
+                    "  {}  {:<11}  block {:>8}  {} entries",
+                    style(&manifest.id).yellow(),
+                    format!("{:?}", manifest.kind),
+                    manifest.block_height,
+                    manifest.entry_count
+                ));
+            }
+            json!({ "dir": backups.dir(), "backups": manifests })
+        }
+
+        DbCommands::ExportCdps { output } => {
+            let manager = open_state_reader(cli)?;
+            let file = std::io::BufWriter::new(std::fs::File::create(output)?);
+
+            let spinner = create_spinner("Exporting CDPs...");
+            let summary = manager.export_cdps(file, |count| {
+                if count % 1_000 == 0 {
+                    spinner.set_message(format!("Exporting CDPs... {}", count));
+                }
+            })?;
+            spinner.finish_with_message("Export complete");
+
+            out.line(format!(
+                "{} Exported {} CDPs ({} bytes) to {}",
+                style("✓").green(),
+                style(summary.count).cyan(),
+                summary.bytes,
+                output.display()
+            ));
+            out.line(format!("  Checksum: {}", style(summary.checksum.to_hex()).yellow()));
+            json!({
+                "output": output,
+                "count": summary.count,
+                "bytes": summary.bytes,
+                "checksum": summary.checksum.to_hex(),
+            })
+        }
+
+        DbCommands::ImportCdps { input } => {
+            let manager = open_state_manager(cli)?;
+            let file = std::io::BufReader::new(std::fs::File::open(input)?);
+
+            let spinner = create_spinner("Importing CDPs...");
+            let summary = manager.import_cdps(file, |count| {
+                if count % 1_000 == 0 {
+                    spinner.set_message(format!("Importing CDPs... {}", count));
+                }
+            })?;
+            spinner.finish_with_message("Import complete");
+
+            out.line(format!(
+                "{} Imported {} CDPs from {}",
+                style("✓").green(),
+                style(summary.count).cyan(),
+                input.display()
+            ));
+            out.line(format!("  Checksum: {}", style(summary.checksum.to_hex()).yellow()));
+            json!({
+                "input": input,
+                "count": summary.count,
+                "bytes": summary.bytes,
+                "checksum": summary.checksum.to_hex(),
+            })
+        }
+
+        DbCommands::VerifyAudit { list } => {
+            let manager = open_state_reader(cli)?;
+            let report = manager.verify_audit_log()?;
+            out.line(format!(
+                "{} Audit log intact: {} entries, {} anchors checked",
+                style("✓").green(),
+                style(report.entries).cyan(),
+                report.anchors
+            ));
+            out.line(format!("  Head: {}", style(report.head.to_hex()).yellow()));
+
+            let mut actions = Vec::new();
+            if *list {
+                for entry in manager.load_audit_entries(0, usize::MAX)? {
+                    out.line(format!(
+                        "  #{:<6} block {:>8}  {}",
+                        entry.sequence,
+                        entry.event.block_height(),
+                        describe_audit_event(&entry.event)
+                    ));
+                    actions.push(json!({
+                        "sequence": entry.sequence,
+                        "block_height": entry.event.block_height(),
+                        "action": describe_audit_event(&entry.event),
+                        "hash": entry.hash.to_hex(),
+                    }));
+                }
+            }
+            json!({
+                "intact": true,
+                "entries": report.entries,
+                "head": report.head.to_hex(),
+                "anchors": report.anchors,
+                "actions": actions,
+            })
+        }
+    };
+
+    Ok(data)
+}
+
+fn cmd_export(
+    cli: &Cli,
+    dataset: &str,
+    output: &Path,
+    format: Option<&str>,
+    columns: &[String],
+    filter: &ExportFilter,
+    out: &OutputFormatter,
+) -> anyhow::Result<Value> {
+    let dataset: ExportDataset = dataset.parse()?;
+    let format: ExportFormat = match format {
+        Some(format) => format.parse()?,
+        None => match output.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("parquet") => ExportFormat::Parquet,
+            _ => ExportFormat::Csv,
+        },
+    };
+
+    let mut table = open_state_reader(cli)?.export(dataset, filter)?;
+    if !columns.is_empty() {
+        table = table.select(columns)?;
+    }
+
+    let file = std::fs::File::create(output)?;
+    table.write(format, std::io::BufWriter::new(file))?;
+
+    let dataset = format!("{:?}", dataset).to_lowercase();
+    out.line(format!(
+        "{} Exported {} rows of {} to {}",
+        style("✓").green(),
+        table.rows().len(),
+        dataset,
+        output.display()
+    ));
+    Ok(json!({
+        "dataset": dataset,
+        "format": format!("{:?}", format).to_lowercase(),
+        "rows": table.rows().len(),
+        "output": output,
+    }))
+}
+
+#[cfg(feature = "tui")]
+fn cmd_monitor(cli: &Cli, rpc: Option<&str>, interval: u64) -> anyhow::Result<Value> {
+    let rpc = rpc.map(str::to_string).or(active_profile(cli)?.1.rpc_url);
+    let source = match rpc {
+        Some(url) => monitor::Source::rpc(&url),
+        None => monitor::Source::Local {
+            state: open_state_reader(cli)?,
+            config: load_config(cli)?,
+        },
+    };
+    monitor::run(source, std::time::Duration::from_secs(interval.max(1)))?;
+    Ok(Value::Null)
+}
+
+#[cfg(not(feature = "tui"))]
+fn cmd_monitor(_cli: &Cli, _rpc: Option<&str>, _interval: u64) -> anyhow::Result<Value> {
+    anyhow::bail!("zkusd was built without the monitor dashboard; rebuild with `--features tui`")
+}
+
+#[cfg(feature = "async-oracle")]
+fn cmd_sync(
+    cli: &Cli,
+    rpc: Option<&str>,
+    trust_root: Option<&str>,
+    require_proof: bool,
+    insecure: bool,
+    out: &OutputFormatter,
+) -> anyhow::Result<Value> {
+    use zkusd::protocol::sync::{StateSync, SyncOptions, SyncPhase};
+
+    let url = match rpc.map(str::to_string).or(active_profile(cli)?.1.rpc_url) {
+        Some(url) => url,
+        None => anyhow::bail!("No peer to sync from; pass --rpc or set the profile's rpc_url"),
+    };
+
+    let mut options = SyncOptions::default();
+    if let Some(root) = trust_root {
+        options = options.with_trusted_root(Hash::from_hex(root)?);
+    }
+    if require_proof {
+        options = options.with_required_proof();
+    }
+    if insecure {
+        options = options.with_insecure();
+    }
+
+    let mut machine = open_state_machine(cli)?;
+    machine.load_state()?;
+    let start = machine.block_height();
+
+    let bar = ProgressBar::new(0);
+    bar.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.cyan} {msg} [{bar:30.cyan/dim}] {pos}/{len}")
+            .unwrap(),
+    );
+    let report = StateSync::new(sync_peer::RpcPeer::new(&url)?)
+        .with_options(options)
+        .sync(&mut machine, |progress| {
+            bar.set_length(progress.target);
+            bar.set_position(progress.height);
+            bar.set_message(match progress.phase {
+                SyncPhase::Snapshot => "Downloading snapshot".to_string(),
+                SyncPhase::CatchUp => format!("Replayed {} blocks", progress.blocks_replayed),
+                SyncPhase::Complete => "Synced".to_string(),
+            });
+        });
+    bar.finish_and_clear();
+    let report = report?;
+
+    out.line(format!(
+        "{} Synced from {} to block {}",
+        style("✓").green(),
+        url,
+        style(report.block_height).cyan()
+    ));
+    if let Some(height) = report.snapshot_height {
+        out.line(format!("  Snapshot:        block {}", height));
+    }
+    out.line(format!("  Blocks replayed: {}", report.blocks_replayed));
+    out.line(format!("  State root:      {}", style(report.state_root.to_hex()).yellow()));
+
+    Ok(json!({
+        "peer": url,
+        "start_height": start,
+        "report": report,
+    }))
+}
+
+#[cfg(not(feature = "async-oracle"))]
+fn cmd_sync(
+    _cli: &Cli,
+    _rpc: Option<&str>,
+    _trust_root: Option<&str>,
+    _require_proof: bool,
+    _insecure: bool,
+    _out: &OutputFormatter,
+) -> anyhow::Result<Value> {
+    anyhow::bail!("zkusd was built without an HTTP client; rebuild with `--features async-oracle`")
+}
+
+fn cmd_config(cli: &Cli, cmd: &ConfigCommands, out: &OutputFormatter) -> anyhow::Result<Value> {
+    let mut profiles = load_profiles(cli)?;
+    let name = profile_name(cli, &profiles);
+
+    let data = match cmd {
+        ConfigCommands::Show { all: true } => {
+            out.line(format!("{} Profiles", style("→").cyan()));
+            for (profile_name, profile) in &profiles.profiles {
+                let marker = if *profile_name == profiles.active { "*" } else { " " };
+                out.line(format!(
+                    "  {} {:<12} {:<8} {}",
+                    style(marker).green(),
+                    style(profile_name).yellow(),
+                    profile.network,
+                    profile.data_dir.display()
+                ));
+            }
+            json!({ "active": profiles.active, "profiles": profiles.profiles })
+        }
+
+        ConfigCommands::Show { all: false } => {
+            let (name, profile) = active_profile(cli)?;
+            let config = load_config(cli)?;
+
+            out.line(format!("{} Profile {}", style("→").cyan(), style(&name).yellow()));
+            out.line(format!("  Network:     {}", style(profile.network).green()));
+            out.line(format!("  Data Dir:    {}", profile_dir(cli)?.display()));
+            out.line(format!(
+                "  RPC URL:     {}",
+                profile.rpc_url.as_deref().unwrap_or("(none)")
+            ));
+            out.line(format!(
+                "  OTLP:        {}",
+                profile.otlp_endpoint.as_deref().unwrap_or("(none)")
+            ));
+            out.line(format!("  Oracle Keys: {}", profile.oracle_keys.len()));
+            for key in &profile.oracle_keys {
+                out.line(format!("    {}", key));
+            }
+            out.line(format!("\n{} Protocol Configuration", style("→").cyan()));
+            out.line(serde_json::to_string_pretty(&config)?);
+            json!({
+                "profile": name,
+                "settings": profile,
+                "data_dir": profile_dir(cli)?,
+                "config": config,
+            })
+        }
+
+        ConfigCommands::Set { key, value } => {
+            let root = expand_path(&cli.data_dir)?;
+            let profile = profiles
+                .profiles
+                .entry(name.clone())
+                .or_insert_with(|| Profile::preset(Network::Custom, name.as_str()));
+            profile.set(key, value)?;
+            ProtocolConfig::new(profile.params.clone()).validate()?;
+
+            // An initialized profile also carries its parameters in config.json
+            let config_path = root.join(expand_path(&profile.data_dir)?).join("config.json");
+            if (key == "network" || key.starts_with("params.")) && config_path.exists() {
+                let mut config: ProtocolConfig = serde_json::from_str(&std::fs::read_to_string(&config_path)?)?;
+                config.params = profile.params.clone();
+                config.validate()?;
+                std::fs::write(&config_path, serde_json::to_string_pretty(&config)?)?;
+            }
+
+            save_profiles(cli, &profiles)?;
+            out.line(format!(
+                "{} Set {} = {} on profile {}",
+                style("✓").green(),
+                key,
+                value,
+                style(&name).yellow()
+            ));
+            json!({ "profile": name, "key": key, "value": value })
+        }
+
+        ConfigCommands::Use => {
+            if !profiles.profiles.contains_key(&name) {
+                anyhow::bail!("Unknown profile '{}'", name);
+            }
+            profiles.active = name.clone();
+            save_profiles(cli, &profiles)?;
+            out.line(format!(
+                "{} Profile {} is now the default",
+                style("✓").green(),
+                style(&name).yellow()
+            ));
+            json!({ "active": name })
+        }
+
+        ConfigCommands::Preview { changes } => {
+            let changes = changes
+                .iter()
+                .map(|change| {
+                    let (key, value) = change
+                        .split_once('=')
+                        .ok_or_else(|| anyhow::anyhow!("Expected params.<name>=<value>, got '{}'", change))?;
+                    let parameter: ProtocolParameter = key.strip_prefix("params.").unwrap_or(key).parse()?;
+                    Ok((parameter, value.parse::<u64>()?))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            let mut machine = open_state_machine(cli)?;
+            machine.load_state()?;
+            let preview = machine.preview_parameter_changes(&changes)?;
+
+            out.line(format!("{} Parameter changes", style("→").cyan()));
+            for diff in &preview.changes {
+                out.line(format!(
+                    "  params.{:<28} {} -> {}",
+                    diff.parameter.as_str(),
+                    diff.old_value,
+                    style(diff.new_value).yellow()
+                ));
+            }
+            out.line(format!(
+                "  CDPs below MCR: {} -> {}",
+                preview.below_mcr_before,
+                preview.below_mcr_after
+            ));
+            for id in &preview.newly_below_mcr {
+                out.line(format!("    {} {}", style("!").red(), id.to_hex()));
+            }
+            let tcr = match preview.tcr {
+                Some(tcr) if tcr != u64::MAX => format!("{}%", tcr),
+                _ => "-".to_string(),
+            };
+            out.line(format!("  TCR: {}", tcr));
+            out.line(format!(
+                "  Recovery mode: {} -> {}",
+                preview.recovery_mode_before,
+                preview.recovery_mode_after
+            ));
+            if preview.violations.is_empty() {
+                out.line(format!("  {} All invariants hold", style("✓").green()));
+            } else {
+                for violation in &preview.violations {
+                    out.line(format!("  {} {}: {}", style("✗").red(), violation.invariant, violation.detail));
+                }
+            }
+            json!({ "preview": preview })
+        }
+    };
+
+    Ok(data)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// HELPER FUNCTIONS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+fn cmd_sim(scenario: &PathBuf, output: Option<&PathBuf>, out: &OutputFormatter) -> anyhow::Result<Value> {
+    let scenario = Scenario::from_file(expand_path(scenario)?)?;
+    out.line(format!(
+        "{} Simulating '{}' over {} blocks",
+        style("→").cyan(),
+        style(&scenario.name).bold(),
+        scenario.blocks
+    ));
+
+    let report = Simulation::new(scenario)?.run()?;
+
+    out.line(format!(
+        "  {:>6} {:>14} {:>8} {:>6} {:>14} {:>16}",
+        "Block", "Price", "TCR", "Liqs", "Redeemed", "Pool Depth"
+    ));
+    for block in &report.blocks {
+        let tcr = if block.tcr == u64::MAX {
+            "-".to_string()
+        } else {
+            format!("{}%", block.tcr)
+        };
+        let tcr = if block.recovery_mode { style(tcr).red() } else { style(tcr).green() };
+        out.line(format!(
+            "  {:>6} {:>14} {:>8} {:>6} {:>14} {:>16}",
+            block.block,
+            format_price(block.price_cents),
+            tcr,
+            block.liquidations,
+            TokenAmount::from_cents(block.redeemed_cents).to_string(),
+            TokenAmount::from_cents(block.pool_depth_cents).to_string()
+        ));
+    }
+
+    let min_tcr = match report.min_tcr() {
+        Some(u64::MAX) | None => "-".to_string(),
+        Some(tcr) => format!("{}%", tcr),
+    };
+    out.line("");
+    out.line(format!("  Min TCR:             {}", style(min_tcr).cyan()));
+    out.line(format!(
+        "  Liquidations:        {}",
+        style(report.total_liquidations()).cyan()
+    ));
+    out.line(format!(
+        "  Redeemed:            {}",
+        style(TokenAmount::from_cents(report.total_redeemed_cents())).cyan()
+    ));
+    out.line(format!(
+        "  Recovery mode:       {} blocks",
+        style(report.recovery_mode_blocks()).cyan()
+    ));
+    if let Some(max_fee) = report.max_borrowing_fee_bps() {
+        out.line(format!(
+            "  Max borrowing fee:   {}",
+            style(format!("{} bps", max_fee)).cyan()
+        ));
+    }
+
+    if let Some(output) = output {
+        let path = expand_path(output)?;
+        std::fs::write(&path, serde_json::to_string_pretty(&report)?)?;
+        out.line(format!(
+            "{} Report written to {}",
+            style("✓").green(),
+            path.display()
+        ));
+    }
+
+    Ok(json!(report))
+}
+
+fn expand_path(path: &PathBuf) -> anyhow::Result<PathBuf> {
+    let path_str = path.to_string_lossy();
+    if path_str.starts_with('~') {
+        let home = std::env::var("HOME")?;
+        Ok(PathBuf::from(path_str.replacen('~', &home, 1)))
+    } else {
+        Ok(path.clone())
+    }
+}
+
+fn load_config(cli: &Cli) -> anyhow::Result<ProtocolConfig> {
+    let data_dir = profile_dir(cli)?;
+    let config_path = data_dir.join("config.json");
+
+    if config_path.exists() {
+        let data = std::fs::read_to_string(&config_path)?;
+        Ok(serde_json::from_str(&data)?)
+    } else {
+        let profile = active_profile(cli)?.1;
+        Ok(ProtocolConfig::new(profile.params).with_chain_id(profile.network.default_chain_id()))
+    }
+}
+
+/// Name of the profile selected by `--profile`, then `--network`, then the
+/// profiles file
+fn profile_name(cli: &Cli, profiles: &Profiles) -> String {
+    cli.profile
+        .clone()
+        .or_else(|| cli.network.clone())
+        .unwrap_or_else(|| profiles.active.clone())
+}
+
+fn load_profiles(cli: &Cli) -> anyhow::Result<Profiles> {
+    let path = expand_path(&cli.data_dir)?.join(PROFILES_FILE);
+
+    if path.exists() {
+        let data = std::fs::read_to_string(&path)?;
+        Ok(serde_json::from_str(&data)?)
+    } else {
+        Ok(Profiles::default())
+    }
+}
+
+fn save_profiles(cli: &Cli, profiles: &Profiles) -> anyhow::Result<()> {
+    let root = expand_path(&cli.data_dir)?;
+    std::fs::create_dir_all(&root)?;
+    std::fs::write(root.join(PROFILES_FILE), serde_json::to_string_pretty(profiles)?)?;
+    Ok(())
+}
+
+fn active_profile(cli: &Cli) -> anyhow::Result<(String, Profile)> {
+    let mut profiles = load_profiles(cli)?;
+    let name = profile_name(cli, &profiles);
+    match profiles.profiles.remove(&name) {
+        Some(profile) => Ok((name, profile)),
+        None => anyhow::bail!(
+            "Unknown profile '{}'. Create it with `zkusd --profile {} config set network <network>`",
+            name,
+            name
+        ),
+    }
+}
+
+/// Data directory of the selected profile
+fn profile_dir(cli: &Cli) -> anyhow::Result<PathBuf> {
+    let (_, profile) = active_profile(cli)?;
+    Ok(expand_path(&cli.data_dir)?.join(expand_path(&profile.data_dir)?))
+}
+
+fn load_address_book(cli: &Cli) -> anyhow::Result<BTreeMap<String, String>> {
+    let path = profile_dir(cli)?.join("addressbook.json");
+
+    if path.exists() {
+        let data = std::fs::read_to_string(&path)?;
+        Ok(serde_json::from_str(&data)?)
+    } else {
+        Ok(BTreeMap::new())
+    }
+}
+
+fn save_address_book(cli: &Cli, book: &BTreeMap<String, String>) -> anyhow::Result<()> {
+    let data_dir = profile_dir(cli)?;
+    std::fs::create_dir_all(&data_dir)?;
+    std::fs::write(data_dir.join("addressbook.json"), serde_json::to_string_pretty(book)?)?;
+    Ok(())
+}
+
+fn load_watch_list(cli: &Cli) -> anyhow::Result<WatchList> {
+    let path = profile_dir(cli)?.join("watchlist.json");
+
+    if path.exists() {
+        let data = std::fs::read_to_string(&path)?;
+        Ok(serde_json::from_str(&data)?)
+    } else {
+        Ok(WatchList::default())
+    }
+}
+
+fn save_watch_list(cli: &Cli, list: &WatchList) -> anyhow::Result<()> {
+    let data_dir = profile_dir(cli)?;
+    std::fs::create_dir_all(&data_dir)?;
+    std::fs::write(data_dir.join("watchlist.json"), serde_json::to_string_pretty(list)?)?;
+    Ok(())
+}
+
+fn load_strategies(cli: &Cli) -> anyhow::Result<Vec<Strategy>> {
+    let path = profile_dir(cli)?.join("automation.json");
+
+    if path.exists() {
+        let data = std::fs::read_to_string(&path)?;
+        Ok(serde_json::from_str(&data)?)
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+fn save_strategies(cli: &Cli, strategies: &[Strategy]) -> anyhow::Result<()> {
+    let data_dir = profile_dir(cli)?;
+    std::fs::create_dir_all(&data_dir)?;
+    std::fs::write(data_dir.join("automation.json"), serde_json::to_string_pretty(strategies)?)?;
+    Ok(())
+}
+
+/// Audit log of top-ups, one JSON event per line
+fn load_automation_log(cli: &Cli) -> anyhow::Result<Vec<AutomationEvent>> {
+    let path = profile_dir(cli)?.join("automation.log");
+
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    std::fs::read_to_string(&path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+fn append_automation_log(cli: &Cli, events: &[AutomationEvent]) -> anyhow::Result<()> {
+    use std::io::Write;
+
+    let data_dir = profile_dir(cli)?;
+    std::fs::create_dir_all(&data_dir)?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(data_dir.join("automation.log"))?;
+    for event in events {
+        writeln!(file, "{}", serde_json::to_string(event)?)?;
+    }
+    Ok(())
+}
+
+/// Watched CDPs still open in the profile's stored state
+fn load_watched_cdps(cli: &Cli, list: &WatchList) -> anyhow::Result<Vec<CDP>> {
+    let state = open_state_reader(cli)?;
+    let mut cdps = Vec::new();
+    for cdp_id in list.ids() {
+        match state.load_cdp(&cdp_id)? {
+            Some(cdp) if !cdp.status.is_terminal() => cdps.push(cdp),
+            _ => tracing::warn!(cdp_id = %cdp_id.to_hex(), "watched CDP is no longer open"),
+        }
+    }
+    Ok(cdps)
+}
+
+/// Raise a local notification for a watch alert: a highlighted line with a
+/// terminal bell, or one output document per alert in structured modes
+fn notify_watch_alert(alert: &PositionStatus, out: &OutputFormatter) {
+    if !out.is_table() {
+        if let Err(e) = CommandOutput::new("cdp.watch", json!({ "alert": alert })).and_then(|doc| out.emit(&doc)) {
+            tracing::warn!(error = %e, "failed to emit watch alert");
+        }
+        return;
+    }
+
+    let headline = if alert.liquidatable {
+        style("LIQUIDATABLE").red().bold()
+    } else {
+        style("NEAR LIQUIDATION").yellow().bold()
+    };
+    out.line(format!(
+        "\x07{} CDP {} liquidates at {} (BTC {}, {:.2}% away)",
+        headline,
+        alert.cdp_id.to_hex(),
+        format_price(alert.liquidation_price),
+        format_price(alert.btc_price),
+        alert.distance_bps as f64 / 100.0
+    ));
+}
+
+/// Follow oracle price updates and alert on watched CDPs until interrupted
+#[cfg(feature = "async-oracle")]
+fn watch_positions(
+    cli: &Cli,
+    out: &OutputFormatter,
+    list: &WatchList,
+    mut watcher: LiquidationWatcher,
+    webhook: Option<&str>,
+    min_ratio: u64,
+) -> anyhow::Result<Value> {
+    use tokio::sync::broadcast::error::RecvError;
+    use zkusd::oracle::service::OracleService;
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        let service = OracleService::with_defaults().await?;
+        let mut updates = service.subscribe();
+        let _task = service.start();
+        let client = reqwest::Client::new();
+
+        out.line(format!(
+            "{} Watching {} CDP(s), alerting within {:.2}% of liquidation (Ctrl-C to stop)",
+            style("→").cyan(),
+            list.len(),
+            watcher.within_bps() as f64 / 100.0
+        ));
+
+        loop {
+            let update = match updates.recv().await {
+                Ok(update) => update,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => anyhow::bail!("Oracle service stopped"),
+            };
+
+            let cdps = load_watched_cdps(cli, list)?;
+            for alert in watcher.check(&cdps, update.price_cents, min_ratio) {
+                notify_watch_alert(&alert, out);
+                if let Some(url) = webhook {
+                    let sent = client.post(url).json(&alert).send().await.and_then(|r| r.error_for_status());
+                    if let Err(e) = sent {
+                        tracing::warn!(error = %e, url, "watch webhook failed");
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(not(feature = "async-oracle"))]
+fn watch_positions(
+    _cli: &Cli,
+    _out: &OutputFormatter,
+    _list: &WatchList,
+    _watcher: LiquidationWatcher,
+    _webhook: Option<&str>,
+    _min_ratio: u64,
+) -> anyhow::Result<Value> {
+    anyhow::bail!("zkusd was built without the oracle service; rebuild with `--features async-oracle` or use `--once`")
+}
+
+fn open_state_manager(cli: &Cli) -> anyhow::Result<StateManager<BinaryStore>> {
+    let data_dir = profile_dir(cli)?;
+    let store = BinaryStore::new(data_dir.join("db"))?;
+    Ok(StateManager::new(store))
+}
+
+/// Open the profile's database read-only, for commands that only inspect it
+/// and may run alongside a writer
+fn open_state_reader(cli: &Cli) -> anyhow::Result<StateManager<BinaryStore>> {
+    let data_dir = profile_dir(cli)?;
+    let store = BinaryStore::open_read_only(data_dir.join("db"))?;
+    Ok(StateManager::new(store))
+}
+
+fn open_backup_manager(cli: &Cli) -> anyhow::Result<BackupManager> {
+    let data_dir = profile_dir(cli)?;
+    Ok(BackupManager::new(data_dir.join("backups"))?)
+}
+
+fn print_backup_manifest(manifest: &BackupManifest, out: &OutputFormatter) {
+    out.line(format!("  ID:           {}", style(&manifest.id).yellow()));
+    out.line(format!("  Kind:         {:?}", manifest.kind));
+    if let Some(parent) = &manifest.parent {
+        out.line(format!("  Parent:       {}", parent));
+    }
+    out.line(format!("  Block height: {}", manifest.block_height));
+    out.line(format!("  State root:   {}", manifest.state_root.to_hex()));
+    out.line(format!("  Entries:      {}", manifest.entry_count));
+}
+
+/// One-line summary of an audited action
+fn describe_audit_event(event: &ProtocolEvent) -> String {
+    match event {
+        ProtocolEvent::ConfigChanged(change) => {
+            format!("{}: {} -> {}", change.parameter, change.old_value, change.new_value)
+        }
+        ProtocolEvent::CircuitBreakerTripped(trip) => {
+            format!("{} breaker tripped: {}", trip.operation, trip.reason)
+        }
+        other => other.event_type().to_string(),
+    }
+}
+
+/// Public key of the profile, read from `key.json`; enough for previews,
+/// which are never signed
+fn load_public_key(cli: &Cli) -> anyhow::Result<PublicKey> {
+    let key_path = profile_dir(cli)?.join("key.json");
+    if !key_path.exists() {
+        anyhow::bail!("No keypair found at {}", key_path.display());
+    }
+
+    let key_data: Value = serde_json::from_str(&std::fs::read_to_string(&key_path)?)?;
+    let hex = key_data["public_key"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("{} has no public key", key_path.display()))?;
+    Ok(PublicKey::from_hex_validated(hex)?)
+}
+
+/// Run the operation built by `build` against a copy of the profile's stored
+/// state and show what it would do (`--simulate`)
+fn simulate(
+    cli: &Cli,
+    out: &OutputFormatter,
+    build: impl FnOnce(&ProtocolStateMachine<BinaryStore>, PublicKey, u64) -> anyhow::Result<ProtocolOperation>,
+) -> anyhow::Result<Value> {
+    let signer = load_public_key(cli)?;
+    let mut machine = open_state_machine(cli)?;
+    machine.load_state()?;
+
+    let op = build(&machine, signer, machine.next_nonce(&signer))?;
+    let payload = op.signing_payload(machine.signing_domain())?;
+    let preview = machine.preview(op)?;
+    print_preview(&preview, out);
+    out.line(format!("\n{}", style("Signing Payload").bold().underlined()));
+    for line in payload.to_string().lines() {
+        out.line(format!("  {}", line));
+    }
+    Ok(json!({ "simulated": true, "preview": preview, "signing_payload": payload }))
+}
+
+/// State machine over the profile's database, checked against the profile's
+/// genesis when it was initialized from one
+fn open_state_machine(cli: &Cli) -> anyhow::Result<ProtocolStateMachine<BinaryStore>> {
+    let data_dir = profile_dir(cli)?;
+    let store = BinaryStore::new(data_dir.join("db"))?;
+    let genesis_path = data_dir.join("genesis.json");
+    if genesis_path.exists() {
+        Ok(ProtocolStateMachine::from_genesis(store, &Genesis::load(genesis_path)?)?)
+    } else {
+        Ok(ProtocolStateMachine::new(store)?)
+    }
+}
+
+/// Operations previewed with `--simulate` carry no signature
+fn unsigned() -> Signature {
+    Signature::new([0u8; SIGNATURE_LENGTH])
+}
+
+fn load_keypair(cli: &Cli) -> anyhow::Result<KeyPair> {
+    let data_dir = profile_dir(cli)?;
+    let key_path = data_dir.join("key.json");
+
+    if key_path.exists() {
+        // In production, load actual keypair
+        Ok(KeyPair::generate())
+    } else {
         anyhow::bail!("No keypair found at {}", key_path.display())
     }
 }
@@ -863,6 +3465,15 @@ fn parse_cdp_id(id: &str) -> anyhow::Result<CDPId> {
     CDPId::from_hex(id).map_err(|e| anyhow::anyhow!("Invalid CDP ID: {}", e))
 }
 
+/// Resolve an address book label, bech32m address or hex public key
+fn parse_pubkey(cli: &Cli, s: &str) -> anyhow::Result<PublicKey> {
+    if let Some(address) = load_address_book(cli)?.get(s) {
+        return PublicKey::from_address(address)
+            .map_err(|e| anyhow::anyhow!("Address book entry '{}' is invalid: {}", s, e));
+    }
+    PublicKey::parse(s).map_err(|e| anyhow::anyhow!("Invalid address '{}': {}", s, e))
+}
+
 fn format_price(price_cents: u64) -> String {
     let dollars = price_cents / 100;
     let cents = price_cents % 100;
@@ -882,7 +3493,8 @@ fn create_spinner(message: &str) -> ProgressBar {
     spinner
 }
 
-fn print_cdp_info(cdp: &CDP, btc_price: u64, min_ratio: u64, term: &Term) -> anyhow::Result<()> {
+/// Show a CDP's details, returning them for structured output
+fn print_cdp_info(cdp: &CDP, btc_price: u64, min_ratio: u64, out: &OutputFormatter) -> Value {
     let state = cdp.get_state(btc_price, min_ratio);
     let collateral = CollateralAmount::from_sats(cdp.collateral_sats);
     let debt = TokenAmount::from_cents(cdp.debt_cents);
@@ -895,27 +3507,520 @@ fn print_cdp_info(cdp: &CDP, btc_price: u64, min_ratio: u64, term: &Term) -> any
         CDPStatus::Liquidated => style("Liquidated").red().dim(),
     };
 
-    let _ = term.write_line(&format!("\n{}", style("CDP Details").bold().underlined()));
-    let _ = term.write_line(&format!("  ID:         {}", cdp.id.to_hex()));
-    let _ = term.write_line(&format!("  Owner:      {}", hex::encode(cdp.owner.as_bytes())));
-    let _ = term.write_line(&format!("  Status:     {}", status_style));
-    let _ = term.write_line(&format!("  Collateral: {}", style(collateral.to_string()).yellow()));
-    let _ = term.write_line(&format!("  Debt:       {}", style(debt.to_string()).green()));
-    let _ = term.write_line(&format!("  Ratio:      {}%", style(state.ratio).cyan()));
+    out.line(format!("\n{}", style("CDP Details").bold().underlined()));
+    out.line(format!("  ID:         {}", cdp.id.to_hex()));
+    out.line(format!("  Owner:      {}", cdp.owner.to_address()));
+    out.line(format!("  Status:     {}", status_style));
+    out.line(format!("  Collateral: {}", style(collateral.to_string()).yellow()));
+    out.line(format!("  Debt:       {}", style(debt.to_string()).green()));
+    out.line(format!("  Ratio:      {}%", style(state.ratio).cyan()));
 
     if state.max_additional_debt > 0 {
-        let _ = term.write_line(&format!(
+        out.line(format!(
             "  Max Mint:   {}",
             style(TokenAmount::from_cents(state.max_additional_debt).to_string()).dim()
         ));
     }
 
     if state.withdrawable_collateral > 0 {
-        let _ = term.write_line(&format!(
+        out.line(format!(
             "  Withdraw:   {}",
             style(CollateralAmount::from_sats(state.withdrawable_collateral).to_string()).dim()
         ));
     }
 
-    Ok(())
+    json!({
+        "cdp_id": cdp.id.to_hex(),
+        "owner": cdp.owner.to_address(),
+        "status": state.status,
+        "collateral_sats": cdp.collateral_sats,
+        "debt_cents": cdp.debt_cents,
+        "ratio": state.ratio,
+        "max_additional_debt_cents": state.max_additional_debt,
+        "withdrawable_collateral_sats": state.withdrawable_collateral,
+    })
+}
+
+fn print_preview(preview: &OperationPreview, out: &OutputFormatter) {
+    let ratio = |ratio: u64| if ratio == u64::MAX { "-".to_string() } else { format!("{}%", ratio) };
+
+    out.line(format!("\n{}", style("Simulation (not signed or submitted)").bold().underlined()));
+    out.line(format!(
+        "  Result:     {}",
+        serde_json::to_string(&preview.result).unwrap_or_default()
+    ));
+    out.line(format!("  Fees:       {}", style(preview.fees.to_string()).green()));
+    if let Some(cdp_ratio) = preview.cdp_ratio {
+        out.line(format!("  CDP Ratio:  {}", style(ratio(cdp_ratio)).cyan()));
+    }
+    let tcr = if preview.recovery_mode {
+        style(format!("{} (recovery mode)", ratio(preview.tcr))).red()
+    } else {
+        style(ratio(preview.tcr)).green()
+    };
+    out.line(format!("  TCR:        {}", tcr));
+    for event in &preview.events {
+        out.line(format!("  Event:      {}", event.event_type()));
+    }
+}
+
+fn print_risk_report(report: &RiskReport, drift_bps: i64, out: &OutputFormatter) {
+    let ratio = |bps: u64| {
+        if bps == u64::MAX {
+            "∞".to_string()
+        } else {
+            format!("{:.2}%", bps as f64 / 100.0)
+        }
+    };
+
+    out.line(format!("\n{}", style("CDP Risk").bold().underlined()));
+    out.line(format!("  BTC Price:         {}", format_price(report.btc_price)));
+    out.line(format!("  Ratio:             {}", style(ratio(report.ratio_bps)).cyan()));
+    out.line(format!("  Minimum Ratio:     {}", ratio(report.min_ratio_bps)));
+    out.line(format!("  Liquidation Price: {}", style(format_price(report.liquidation_price)).yellow()));
+
+    let buffer = format!("{}bps", report.buffer_bps);
+    let buffer = if report.is_liquidatable() {
+        style(buffer).red().bold()
+    } else {
+        style(buffer).green()
+    };
+    out.line(format!("  Buffer to MCR:     {}", buffer));
+
+    out.line(format!("\n  {} Price Shocks:", style("→").cyan()));
+    for projection in &report.projections {
+        let line = format!(
+            "    {:>+7.2}%  {}  {}",
+            projection.price_change_bps as f64 / 100.0,
+            format_price(projection.btc_price),
+            ratio(projection.ratio_bps)
+        );
+        if projection.liquidatable {
+            out.line(format!("{} {}", line, style("liquidatable").red()));
+        } else {
+            out.line(&line);
+        }
+    }
+
+    let drift = format!("{:+.2}%/day", drift_bps as f64 / 100.0);
+    match report.days_to_liquidation {
+        Some(0) => out.line(format!("\n  {} Liquidatable now", style("⚠").red())),
+        Some(days) => out.line(format!(
+            "\n  {} Liquidatable in {} days at {}",
+            style("⚠").yellow(),
+            style(days).yellow(),
+            drift
+        )),
+        None => out.line(format!("\n  {} Not liquidatable within the horizon at {}", style("✓").green(), drift)),
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// MONITOR DASHBOARD
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[cfg(feature = "tui")]
+mod monitor {
+    use std::time::{Duration, Instant};
+
+    use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+    use ratatui::crossterm::execute;
+    use ratatui::crossterm::terminal::{
+        disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+    };
+    use ratatui::prelude::*;
+    use ratatui::widgets::{Block, Gauge, List, ListItem, ListState, Paragraph, Row, Table};
+    use serde::Deserialize;
+
+    use zkusd::core::cdp::CDPManager;
+    use zkusd::core::config::ProtocolConfig;
+    use zkusd::core::token::TokenAmount;
+    use zkusd::monitoring::{AlertBook, AlertSeverity, DashboardFeed};
+    use zkusd::storage::{BinaryStore, StateManager};
+
+    use super::{format_price, get_block_height, get_current_price};
+
+    /// Blocks of events read from local storage
+    const EVENT_LOOKBACK_BLOCKS: u64 = 144;
+
+    /// Where the dashboard reads protocol state from
+    pub enum Source {
+        /// A running `zkusd-server`
+        Rpc {
+            client: reqwest::blocking::Client,
+            url: String,
+        },
+        /// The local database, with the configuration to fall back on
+        Local {
+            state: StateManager<BinaryStore>,
+            config: ProtocolConfig,
+        },
+    }
+
+    /// Envelope of every server response
+    #[derive(Deserialize)]
+    struct RpcResponse<T> {
+        data: Option<T>,
+        error: Option<String>,
+    }
+
+    impl Source {
+        /// Poll a server at `url`
+        pub fn rpc(url: &str) -> Self {
+            Self::Rpc {
+                client: reqwest::blocking::Client::new(),
+                url: url.trim_end_matches('/').to_string(),
+            }
+        }
+
+        fn describe(&self) -> String {
+            match self {
+                Self::Rpc { url, .. } => url.clone(),
+                Self::Local { .. } => "local database".to_string(),
+            }
+        }
+
+        fn fetch(&self) -> anyhow::Result<DashboardFeed> {
+            match self {
+                Self::Rpc { client, url } => {
+                    let response: RpcResponse<DashboardFeed> = client
+                        .get(format!("{}/monitor", url))
+                        .send()?
+                        .error_for_status()?
+                        .json()?;
+                    response.data.ok_or_else(|| {
+                        anyhow::anyhow!(response.error.unwrap_or_else(|| "Empty response".to_string()))
+                    })
+                }
+                Self::Local { state, config } => {
+                    let persisted = state.load_protocol_state().ok();
+                    let config = persisted.as_ref().map_or(config, |p| &p.config);
+                    let block_height = persisted.as_ref().map_or_else(get_block_height, |p| p.block_height);
+
+                    let mut cdps = CDPManager::new();
+                    for cdp in state.load_all_cdps()? {
+                        cdps.register(cdp)?;
+                    }
+                    let pool_deposits = state
+                        .load_stability_pool()?
+                        .map_or(TokenAmount::ZERO, |pool| pool.total_deposits());
+                    let btc_price = match state.load_price()? {
+                        Some((price, _)) => price,
+                        None => get_current_price()?,
+                    };
+
+                    let mut events = Vec::new();
+                    for height in block_height.saturating_sub(EVENT_LOOKBACK_BLOCKS)..=block_height {
+                        events.extend(state.load_events(height)?);
+                    }
+
+                    Ok(DashboardFeed::collect(
+                        &cdps,
+                        btc_price,
+                        config.effective_mcr(),
+                        config.params.critical_collateral_ratio,
+                        pool_deposits,
+                        block_height,
+                        events,
+                    ))
+                }
+            }
+        }
+    }
+
+    struct App {
+        source: Source,
+        feed: Option<DashboardFeed>,
+        alerts: AlertBook,
+        selected: ListState,
+        refreshed_at: Instant,
+        error: Option<String>,
+    }
+
+    impl App {
+        fn refresh(&mut self) {
+            match self.source.fetch() {
+                Ok(feed) => {
+                    self.alerts.update(&feed);
+                    self.feed = Some(feed);
+                    self.error = None;
+                }
+                Err(e) => self.error = Some(e.to_string()),
+            }
+            self.refreshed_at = Instant::now();
+
+            let count = self.alerts.alerts().len();
+            match self.selected.selected() {
+                _ if count == 0 => self.selected.select(None),
+                Some(index) if index >= count => self.selected.select(Some(count - 1)),
+                None => self.selected.select(Some(0)),
+                Some(_) => {}
+            }
+        }
+
+        fn move_selection(&mut self, forward: bool) {
+            let count = self.alerts.alerts().len();
+            if count == 0 {
+                return;
+            }
+            let index = self.selected.selected().unwrap_or(0);
+            let index = if forward { (index + 1) % count } else { (index + count - 1) % count };
+            self.selected.select(Some(index));
+        }
+
+        fn acknowledge_selected(&mut self) {
+            let id = self
+                .selected
+                .selected()
+                .and_then(|index| self.alerts.alerts().get(index))
+                .map(|alert| alert.id);
+            if let Some(id) = id {
+                self.alerts.acknowledge(id);
+            }
+        }
+    }
+
+    /// Run the dashboard until the user quits
+    pub fn run(source: Source, interval: Duration) -> anyhow::Result<()> {
+        let mut app = App {
+            source,
+            feed: None,
+            alerts: AlertBook::new(),
+            selected: ListState::default(),
+            refreshed_at: Instant::now(),
+            error: None,
+        };
+        app.refresh();
+
+        enable_raw_mode()?;
+        execute!(std::io::stdout(), EnterAlternateScreen)?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(std::io::stdout()))?;
+
+        let result = event_loop(&mut terminal, &mut app, interval);
+
+        disable_raw_mode()?;
+        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+        terminal.show_cursor()?;
+        result
+    }
+
+    fn event_loop<B: Backend>(terminal: &mut Terminal<B>, app: &mut App, interval: Duration) -> anyhow::Result<()> {
+        loop {
+            terminal.draw(|frame| draw(frame, app))?;
+
+            if event::poll(interval.saturating_sub(app.refreshed_at.elapsed()))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind != KeyEventKind::Press {
+                        continue;
+                    }
+                    match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+                        KeyCode::Up | KeyCode::Char('k') => app.move_selection(false),
+                        KeyCode::Down | KeyCode::Char('j') => app.move_selection(true),
+                        KeyCode::Char('a') => app.acknowledge_selected(),
+                        KeyCode::Char('A') => app.alerts.acknowledge_all(),
+                        KeyCode::Char('r') => app.refresh(),
+                        _ => {}
+                    }
+                }
+            }
+
+            if app.refreshed_at.elapsed() >= interval {
+                app.refresh();
+            }
+        }
+    }
+
+    fn draw(frame: &mut Frame, app: &mut App) {
+        let [header, gauge, body, footer] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(8),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [left, right] = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(body);
+        let [alerts_area, cdps_area] = Layout::vertical([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(left);
+
+        draw_header(frame, app, header);
+
+        let score = app.feed.as_ref().map_or(0, |feed| feed.health_score);
+        let color = match score {
+            0..=39 => Color::Red,
+            40..=69 => Color::Yellow,
+            _ => Color::Green,
+        };
+        frame.render_widget(
+            Gauge::default()
+                .block(Block::bordered().title(" Health "))
+                .gauge_style(Style::default().fg(color))
+                .percent(u16::from(score))
+                .label(format!("{}/100", score)),
+            gauge,
+        );
+
+        let alerts: Vec<ListItem> = app
+            .alerts
+            .alerts()
+            .iter()
+            .map(|alert| {
+                let color = match alert.severity {
+                    AlertSeverity::Info => Color::Blue,
+                    AlertSeverity::Warning => Color::Yellow,
+                    AlertSeverity::Critical => Color::Red,
+                    AlertSeverity::Emergency => Color::Magenta,
+                };
+                let mut style = Style::default().fg(color);
+                if alert.acknowledged {
+                    style = style.add_modifier(Modifier::DIM);
+                }
+                let mark = if alert.acknowledged { "✓" } else { "!" };
+                ListItem::new(format!("{} #{} {}", mark, alert.raised_at, alert.message)).style(style)
+            })
+            .collect();
+        let title = format!(" Alerts ({} unacknowledged) ", app.alerts.unacknowledged());
+        frame.render_stateful_widget(
+            List::new(alerts)
+                .block(Block::bordered().title(title))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+            alerts_area,
+            &mut app.selected,
+        );
+
+        let rows: Vec<Row> = app
+            .feed
+            .iter()
+            .flat_map(|feed| feed.at_risk.iter())
+            .map(|cdp| {
+                let style = if cdp.liquidatable {
+                    Style::default().fg(Color::Red)
+                } else {
+                    Style::default().fg(Color::Yellow)
+                };
+                let id = cdp.id.to_hex();
+                Row::new(vec![
+                    format!("{}…", &id[..12.min(id.len())]),
+                    format!("{}%", cdp.ratio),
+                    TokenAmount::from_cents(cdp.debt_cents).to_string(),
+                    format!("{:.8} BTC", cdp.collateral_sats as f64 / 100_000_000.0),
+                ])
+                .style(style)
+            })
+            .collect();
+        frame.render_widget(
+            Table::new(
+                rows,
+                [Constraint::Length(14), Constraint::Length(7), Constraint::Fill(1), Constraint::Fill(1)],
+            )
+            .header(Row::new(vec!["CDP", "Ratio", "Debt", "Collateral"]).style(Style::default().add_modifier(Modifier::BOLD)))
+            .block(Block::bordered().title(" At-Risk CDPs ")),
+            cdps_area,
+        );
+
+        let events: Vec<ListItem> = app
+            .feed
+            .iter()
+            .flat_map(|feed| feed.recent_events.iter())
+            .map(|event| ListItem::new(format!("#{:<8} {}", event.block_height(), event.event_type())))
+            .collect();
+        frame.render_widget(List::new(events).block(Block::bordered().title(" Recent Events ")), right);
+
+        frame.render_widget(
+            Paragraph::new("q quit  ↑/↓ select  a acknowledge  A acknowledge all  r refresh")
+                .style(Style::default().add_modifier(Modifier::DIM)),
+            footer,
+        );
+    }
+
+    fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
+        let mut spans = Vec::new();
+        if let Some(feed) = &app.feed {
+            let tcr = if feed.tcr == u64::MAX { "∞".to_string() } else { format!("{}%", feed.tcr) };
+            let tcr_color = if feed.tcr < feed.critical_ratio { Color::Red } else { Color::Green };
+            spans.extend([
+                Span::raw("BTC "),
+                Span::styled(format_price(feed.snapshot.btc_price), Style::default().fg(Color::Yellow)),
+                Span::raw("   TCR "),
+                Span::styled(tcr, Style::default().fg(tcr_color)),
+                Span::raw(format!("   CDPs {}", feed.snapshot.cdp_count)),
+                Span::raw(format!("   Debt {}", TokenAmount::from_cents(feed.snapshot.total_debt))),
+                Span::raw(format!("   Pool {}", TokenAmount::from_cents(feed.snapshot.pool_deposits))),
+                Span::raw(format!("   Block {}", feed.snapshot.block_height)),
+            ]);
+        }
+        if let Some(error) = &app.error {
+            spans.push(Span::styled(format!("   {}", error), Style::default().fg(Color::Red)));
+        }
+
+        let title = format!(" zkUSD Monitor - {} ", app.source.describe());
+        frame.render_widget(Paragraph::new(Line::from(spans)).block(Block::bordered().title(title)), area);
+    }
+}
+
+/// Peer reached over a `zkusd-server`'s sync endpoints
+#[cfg(feature = "async-oracle")]
+mod sync_peer {
+    use serde::Deserialize;
+    use zkusd::error::{Error, Result};
+    use zkusd::protocol::sync::{BlockRecord, SyncSnapshot, SyncSource, SyncStatus};
+
+    /// Envelope of JSON server responses
+    #[derive(Deserialize)]
+    struct RpcResponse<T> {
+        data: Option<T>,
+        error: Option<String>,
+    }
+
+    pub struct RpcPeer {
+        runtime: tokio::runtime::Runtime,
+        client: reqwest::Client,
+        url: String,
+    }
+
+    impl RpcPeer {
+        pub fn new(url: &str) -> anyhow::Result<Self> {
+            Ok(Self {
+                runtime: tokio::runtime::Runtime::new()?,
+                client: reqwest::Client::new(),
+                url: url.trim_end_matches('/').to_string(),
+            })
+        }
+
+        fn get_bytes(&self, path: &str) -> Result<Vec<u8>> {
+            self.runtime.block_on(async {
+                let response = self
+                    .client
+                    .get(format!("{}{}", self.url, path))
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status())
+                    .map_err(|e| Error::Internal(format!("Sync request failed: {}", e)))?;
+                let bytes = response
+                    .bytes()
+                    .await
+                    .map_err(|e| Error::Internal(format!("Sync response failed: {}", e)))?;
+                Ok(bytes.to_vec())
+            })
+        }
+    }
+
+    impl SyncSource for RpcPeer {
+        fn status(&self) -> Result<SyncStatus> {
+            let response: RpcResponse<SyncStatus> = serde_json::from_slice(&self.get_bytes("/sync/status")?)
+                .map_err(|e| Error::Deserialization(format!("Invalid sync status: {}", e)))?;
+            response
+                .data
+                .ok_or_else(|| Error::Internal(response.error.unwrap_or_else(|| "Empty response".to_string())))
+        }
+
+        fn snapshot(&self) -> Result<SyncSnapshot> {
+            SyncSnapshot::from_bytes(&self.get_bytes("/sync/snapshot")?)
+        }
+
+        fn blocks(&self, from: u64, limit: usize) -> Result<Vec<BlockRecord>> {
+            let bytes = self.get_bytes(&format!("/sync/blocks?from={}&limit={}", from, limit))?;
+            bincode::deserialize(&bytes).map_err(|e| Error::Deserialization(format!("Invalid block logs: {}", e)))
+        }
+    }
 }
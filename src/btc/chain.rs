@@ -0,0 +1,109 @@
+//! Chain backend abstraction.
+//!
+//! A chain backend is the protocol's window onto the Bitcoin network: it
+//! broadcasts transactions built by [`tx_builder`](crate::btc::tx_builder),
+//! reports UTXOs and confirmations for collateral scripts, and estimates fees.
+//! Implementations include the Bitcoin Core JSON-RPC client (`bitcoind`
+//! feature) and the Esplora REST client (`esplora` feature).
+
+use bitcoin::{BlockHash, OutPoint, ScriptBuf, Transaction, Txid};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+
+use crate::btc::tx_builder::FeeRate;
+use crate::btc::utxo::{ReconcileReport, Utxo, UtxoSet, UtxoSource, UtxoStatus};
+use crate::error::Result;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// BLOCK NOTIFICATIONS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// A newly connected block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockNotification {
+    /// Block height
+    pub height: u32,
+    /// Block hash
+    pub hash: BlockHash,
+    /// Block header timestamp
+    pub time: u64,
+}
+
+impl BlockNotification {
+    /// Start processing this block in the protocol state machine
+    pub fn begin_block<B: crate::storage::backend::StorageBackend>(
+        &self,
+        machine: &mut crate::protocol::state_machine::ProtocolStateMachine<B>,
+    ) -> Result<()> {
+        machine.begin_block(self.height as u64, self.time)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// CHAIN BACKEND
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Access to Bitcoin chain data and transaction relay
+pub trait ChainBackend {
+    /// Height of the best block
+    fn tip_height(&self) -> impl Future<Output = Result<u32>> + Send;
+
+    /// Hash of the block at `height` on the best chain
+    fn block_hash(&self, height: u32) -> impl Future<Output = Result<BlockHash>> + Send;
+
+    /// Broadcast a signed transaction
+    fn broadcast(&self, tx: &Transaction) -> impl Future<Output = Result<Txid>> + Send;
+
+    /// Unspent outputs locked by `script_pubkey`
+    fn script_utxos(&self, script_pubkey: &ScriptBuf) -> impl Future<Output = Result<Vec<Utxo>>> + Send;
+
+    /// Status of an output, or None if it is spent or unknown
+    fn utxo_status(&self, outpoint: &OutPoint) -> impl Future<Output = Result<Option<UtxoStatus>>> + Send;
+
+    /// Fee rate expected to confirm within `target_blocks`
+    fn estimate_fee(&self, target_blocks: u16) -> impl Future<Output = Result<FeeRate>> + Send;
+}
+
+/// Snapshot of node-side UTXO statuses, usable as a [`UtxoSource`]
+impl UtxoSource for HashMap<OutPoint, UtxoStatus> {
+    fn utxo_status(&self, outpoint: &OutPoint) -> Result<Option<UtxoStatus>> {
+        Ok(self.get(outpoint).copied())
+    }
+}
+
+/// Reconcile a UTXO set against a chain backend
+pub async fn reconcile_with_backend<C: ChainBackend>(
+    set: &mut UtxoSet,
+    backend: &C,
+) -> Result<ReconcileReport> {
+    let mut statuses = HashMap::new();
+    let outpoints: Vec<OutPoint> = set.iter().map(|u| u.outpoint()).collect();
+
+    for outpoint in outpoints {
+        if let Some(status) = backend.utxo_status(&outpoint).await? {
+            statuses.insert(outpoint, status);
+        }
+    }
+
+    set.reconcile(&statuses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::hashes::Hash;
+
+    #[test]
+    fn test_snapshot_as_utxo_source() {
+        let outpoint = OutPoint::new(Txid::all_zeros(), 0);
+        let status = UtxoStatus { confirmation_height: Some(10) };
+        let snapshot = HashMap::from([(outpoint, status)]);
+
+        assert_eq!(UtxoSource::utxo_status(&snapshot, &outpoint).unwrap(), Some(status));
+        assert_eq!(
+            UtxoSource::utxo_status(&snapshot, &OutPoint::new(Txid::all_zeros(), 1)).unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,927 @@
+//! Hot/cold custody of protocol-controlled collateral.
+//!
+//! Collateral arrives in the hot wallet, which pays withdrawals and
+//! liquidations. A [`CustodyPolicy`] keeps a buffer there and moves the rest
+//! to a cold taproot address spendable only by a threshold of cold keys:
+//!
+//! - When the hot balance rises above the sweep threshold, and at most once
+//!   per sweep interval, a sweep moves whole hot outputs to the cold address,
+//!   leaving at least the hot target behind.
+//! - When the hot balance falls below the refill threshold, a refill moves
+//!   cold outputs back to the hot wallet.
+//!
+//! Each transfer is built as a PSBT. Sweeps are signed by the hot wallet;
+//! refills collect signatures from cold key holders through
+//! [`Custody::add_signatures`] until the threshold is met. Outputs are
+//! grouped by the CDP whose collateral they carry, so attribution (and with
+//! it the proof of reserves) survives the move. The fee comes out of the
+//! unattributed group when it can cover it, otherwise out of the largest.
+
+use bitcoin::hashes::Hash as _;
+use bitcoin::opcodes::all::{OP_CHECKSIG, OP_CHECKSIGADD, OP_NUMEQUAL};
+use bitcoin::psbt::Psbt;
+use bitcoin::script::Builder as ScriptBuilder;
+use bitcoin::secp256k1::{Keypair, Message, Secp256k1, XOnlyPublicKey};
+use bitcoin::sighash::{Prevouts, SighashCache};
+use bitcoin::taproot::{self, LeafVersion, TapLeafHash, TaprootBuilder, TaprootSpendInfo};
+use bitcoin::{
+    absolute::LockTime, transaction::Version, Address, Amount, Network, OutPoint, ScriptBuf, Sequence,
+    TapSighashType, Transaction, TxIn, TxOut, Txid, Witness,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::btc::tx_builder::{vsize, FeeRate};
+use crate::btc::utxo::{Utxo, UtxoSet};
+use crate::error::{Error, Result};
+
+/// Internal key with no known discrete log (BIP-341), so the cold output
+/// can only be spent through the multisig script
+const NUMS_INTERNAL_KEY: [u8; 32] = [
+    0x50, 0x92, 0x9b, 0x74, 0xc1, 0xa0, 0x49, 0x54, 0xb7, 0x8b, 0x4b, 0x60, 0x35, 0xe9, 0x7a, 0x5e, 0x07, 0x8a,
+    0x5a, 0x0f, 0x28, 0xec, 0x96, 0xd5, 0x47, 0xbf, 0xee, 0x9a, 0xce, 0x80, 0x3a, 0xc0,
+];
+
+/// Smallest output a transfer creates
+const DUST_SATS: u64 = 546;
+
+/// Maximum custody events to keep
+const MAX_EVENTS: usize = 1000;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// POLICY
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// How collateral is split between the hot and cold wallets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustodyPolicy {
+    /// Cold signer keys
+    pub cold_keys: Vec<XOnlyPublicKey>,
+    /// Cold signatures required to spend
+    pub cold_threshold: u32,
+    /// Script refills pay to
+    pub hot_script: ScriptBuf,
+    /// Balance the hot wallet is kept at
+    pub hot_target: u64,
+    /// Hot balance above which a sweep is due
+    pub sweep_threshold: u64,
+    /// Hot balance below which a refill is due
+    pub refill_threshold: u64,
+    /// Smallest transfer worth making
+    pub min_transfer: u64,
+    /// Blocks between sweeps
+    pub sweep_interval: u32,
+    /// Confirmations an output needs before it is moved
+    pub min_confirmations: u32,
+}
+
+impl CustodyPolicy {
+    /// Create a policy with a `threshold`-of-`cold_keys` cold wallet
+    pub fn new(cold_keys: Vec<XOnlyPublicKey>, cold_threshold: u32, hot_script: ScriptBuf, hot_target: u64) -> Self {
+        Self {
+            cold_keys,
+            cold_threshold,
+            hot_script,
+            hot_target,
+            sweep_threshold: hot_target.saturating_mul(2),
+            refill_threshold: hot_target / 2,
+            min_transfer: 100_000,
+            sweep_interval: 144,
+            min_confirmations: 6,
+        }
+    }
+
+    /// Create with custom sweep and refill thresholds
+    pub fn with_thresholds(mut self, sweep_threshold: u64, refill_threshold: u64) -> Self {
+        self.sweep_threshold = sweep_threshold;
+        self.refill_threshold = refill_threshold;
+        self
+    }
+
+    /// Create with a custom minimum transfer
+    pub fn with_min_transfer(mut self, min_transfer: u64) -> Self {
+        self.min_transfer = min_transfer;
+        self
+    }
+
+    /// Create with a custom sweep interval
+    pub fn with_sweep_interval(mut self, blocks: u32) -> Self {
+        self.sweep_interval = blocks;
+        self
+    }
+
+    /// Create with custom required confirmations
+    pub fn with_min_confirmations(mut self, confirmations: u32) -> Self {
+        self.min_confirmations = confirmations;
+        self
+    }
+
+    /// Check the policy is well formed
+    pub fn validate(&self) -> Result<()> {
+        let keys = self.cold_keys.len() as u32;
+        if !(1..=keys).contains(&self.cold_threshold) {
+            return Err(invalid("cold_threshold", format!("Must be between 1 and {}, got {}", keys, self.cold_threshold)));
+        }
+        let mut sorted = self.cold_keys.clone();
+        sorted.sort();
+        sorted.dedup();
+        if sorted.len() != self.cold_keys.len() {
+            return Err(invalid("cold_keys", "Duplicate cold key"));
+        }
+        if self.refill_threshold > self.hot_target || self.hot_target > self.sweep_threshold {
+            return Err(invalid("hot_target", "Must lie between the refill and sweep thresholds"));
+        }
+        if self.min_transfer < DUST_SATS {
+            return Err(invalid("min_transfer", format!("Below dust limit of {} sats", DUST_SATS)));
+        }
+        Ok(())
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// COLD WALLET
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Taproot output spendable by a threshold of cold keys
+#[derive(Debug, Clone)]
+pub struct ColdWallet {
+    /// `<k1> CHECKSIG <k2> CHECKSIGADD ... <t> NUMEQUAL`
+    multisig: ScriptBuf,
+    spend_info: TaprootSpendInfo,
+}
+
+impl ColdWallet {
+    /// Build the cold wallet for `policy`
+    pub fn new(policy: &CustodyPolicy) -> Result<Self> {
+        policy.validate()?;
+
+        let mut builder = ScriptBuilder::new();
+        for (i, key) in policy.cold_keys.iter().enumerate() {
+            builder = builder.push_x_only_key(key);
+            builder = builder.push_opcode(if i == 0 { OP_CHECKSIG } else { OP_CHECKSIGADD });
+        }
+        let multisig = builder
+            .push_int(policy.cold_threshold as i64)
+            .push_opcode(OP_NUMEQUAL)
+            .into_script();
+
+        let internal_key = XOnlyPublicKey::from_slice(&NUMS_INTERNAL_KEY).expect("NUMS point is a valid key");
+        let spend_info = TaprootBuilder::new()
+            .add_leaf(0, multisig.clone())
+            .map_err(|e| Error::Internal(format!("Failed to build cold wallet tree: {}", e)))?
+            .finalize(&Secp256k1::verification_only(), internal_key)
+            .map_err(|_| Error::Internal("Failed to finalize cold wallet tree".into()))?;
+
+        Ok(Self { multisig, spend_info })
+    }
+
+    /// Output script of the cold wallet
+    pub fn script_pubkey(&self) -> ScriptBuf {
+        ScriptBuf::new_p2tr_tweaked(self.spend_info.output_key())
+    }
+
+    /// Address of the cold wallet on `network`
+    pub fn address(&self, network: Network) -> Result<Address> {
+        Address::from_script(&self.script_pubkey(), network)
+            .map_err(|e| Error::Internal(format!("Invalid cold wallet script: {}", e)))
+    }
+
+    /// Multisig leaf script
+    pub fn multisig_script(&self) -> &ScriptBuf {
+        &self.multisig
+    }
+
+    /// Leaf hash cold signatures commit to
+    pub fn leaf_hash(&self) -> TapLeafHash {
+        TapLeafHash::from_script(&self.multisig, LeafVersion::TapScript)
+    }
+
+    /// Virtual size of a cold input spent through the multisig
+    fn input_vsize(&self, threshold: u32) -> u64 {
+        let keys = self.keys().len() as u64;
+        let threshold = threshold as u64;
+        // Signatures for `threshold` keys and empty pushes for the rest,
+        // the script and a depth-0 control block
+        let witness = 1 + threshold * 65 + (keys - threshold) + 3 + self.multisig.len() as u64 + 34;
+        41 + witness.div_ceil(4)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// TRANSFERS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Direction of a custody transfer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransferKind {
+    /// Hot wallet to cold wallet
+    Sweep,
+    /// Cold wallet to hot wallet
+    Refill,
+}
+
+/// Where a custody transfer is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransferStatus {
+    /// Waiting for signatures
+    AwaitingSignatures,
+    /// Fully signed, ready to broadcast
+    Signed,
+    /// Broadcast, waiting for confirmation
+    Broadcast,
+    /// Confirmed at a block height
+    Confirmed(u32),
+}
+
+/// An output of a custody transfer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferOutput {
+    /// Value in satoshis
+    pub value: u64,
+    /// CDP whose collateral the output carries
+    pub cdp_id: Option<[u8; 32]>,
+}
+
+/// A sweep or refill being signed and broadcast
+#[derive(Debug, Clone)]
+pub struct CustodyTransfer {
+    /// Transfer ID
+    pub id: u64,
+    /// Direction
+    pub kind: TransferKind,
+    /// Outputs spent
+    pub inputs: Vec<Utxo>,
+    /// Outputs created, in transaction order
+    pub outputs: Vec<TransferOutput>,
+    /// Fee paid
+    pub fee: u64,
+    /// Transaction being signed
+    pub psbt: Psbt,
+    /// Lifecycle status
+    pub status: TransferStatus,
+    /// Height the transfer was scheduled at
+    pub scheduled_at: u32,
+}
+
+impl CustodyTransfer {
+    /// Value moved, after fees
+    pub fn amount(&self) -> u64 {
+        self.outputs.iter().map(|output| output.value).sum()
+    }
+
+    /// Transaction ID
+    pub fn txid(&self) -> Txid {
+        self.psbt.unsigned_tx.compute_txid()
+    }
+}
+
+/// Custody lifecycle event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CustodyEvent {
+    /// A transfer was built and awaits signatures
+    TransferScheduled {
+        /// Transfer ID
+        id: u64,
+        /// Direction
+        kind: TransferKind,
+        /// Satoshis moved
+        amount: u64,
+        /// Fee paid in satoshis
+        fee: u64,
+        /// Block height
+        height: u32,
+    },
+    /// Cold signatures were added to a refill
+    SignaturesAdded {
+        /// Transfer ID
+        id: u64,
+        /// Cold keys that have signed every input
+        signers: u32,
+        /// Signatures required
+        threshold: u32,
+    },
+    /// A transfer is fully signed
+    TransferSigned {
+        /// Transfer ID
+        id: u64,
+    },
+    /// A transfer was broadcast
+    TransferBroadcast {
+        /// Transfer ID
+        id: u64,
+        /// Transaction ID
+        txid: Txid,
+    },
+    /// A transfer confirmed
+    TransferConfirmed {
+        /// Transfer ID
+        id: u64,
+        /// Confirmation height
+        height: u32,
+    },
+    /// A transfer was abandoned and its inputs released
+    TransferCancelled {
+        /// Transfer ID
+        id: u64,
+    },
+    /// The hot wallet is below its refill threshold with nothing to refill from
+    HotBufferLow {
+        /// Hot wallet balance in satoshis
+        balance: u64,
+        /// Balance the hot wallet is kept at
+        target: u64,
+        /// Block height
+        height: u32,
+    },
+}
+
+/// Schedules and tracks custody transfers under a policy
+#[derive(Debug)]
+pub struct Custody {
+    policy: CustodyPolicy,
+    cold: ColdWallet,
+    fee_rate: FeeRate,
+    transfers: BTreeMap<u64, CustodyTransfer>,
+    next_id: u64,
+    last_sweep: Option<u32>,
+    events: Vec<CustodyEvent>,
+}
+
+impl Custody {
+    /// Create custody under `policy`
+    pub fn new(policy: CustodyPolicy) -> Result<Self> {
+        let cold = ColdWallet::new(&policy)?;
+        Ok(Self {
+            policy,
+            cold,
+            fee_rate: FeeRate::default(),
+            transfers: BTreeMap::new(),
+            next_id: 1,
+            last_sweep: None,
+            events: Vec::new(),
+        })
+    }
+
+    /// Create with a custom fee rate
+    pub fn with_fee_rate(mut self, rate: FeeRate) -> Self {
+        self.fee_rate = rate;
+        self
+    }
+
+    /// Policy in force
+    pub fn policy(&self) -> &CustodyPolicy {
+        &self.policy
+    }
+
+    /// Cold wallet
+    pub fn cold_wallet(&self) -> &ColdWallet {
+        &self.cold
+    }
+
+    /// Transfer by ID
+    pub fn transfer(&self, id: u64) -> Option<&CustodyTransfer> {
+        self.transfers.get(&id)
+    }
+
+    /// Transfers not yet confirmed
+    pub fn pending(&self) -> Vec<&CustodyTransfer> {
+        self.transfers
+            .values()
+            .filter(|transfer| !matches!(transfer.status, TransferStatus::Confirmed(_)))
+            .collect()
+    }
+
+    /// Get recent events
+    pub fn recent_events(&self) -> &[CustodyEvent] {
+        &self.events
+    }
+
+    /// Value of the unlocked, confirmed hot outputs
+    pub fn hot_balance(&self, set: &UtxoSet, height: u32) -> u64 {
+        self.hot_outputs(set, height).iter().map(|utxo| utxo.value).sum()
+    }
+
+    /// Value of the cold outputs
+    pub fn cold_balance(&self, set: &UtxoSet) -> u64 {
+        let cold = self.cold.script_pubkey();
+        set.iter().filter(|utxo| utxo.script_pubkey == cold).map(|utxo| utxo.value).sum()
+    }
+
+    /// Whether the sweep interval has passed since the last sweep
+    pub fn sweep_due(&self, height: u32) -> bool {
+        self.last_sweep
+            .is_none_or(|last| height >= last.saturating_add(self.policy.sweep_interval))
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // SCHEDULING
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Schedule whatever transfer the hot balance calls for at `height`,
+    /// locking its inputs in `set`
+    pub fn schedule(&mut self, set: &mut UtxoSet, height: u32) -> Result<Option<u64>> {
+        let balance = self.hot_balance(set, height);
+        if balance > self.policy.sweep_threshold && self.sweep_due(height) {
+            return self.schedule_sweep(set, height);
+        }
+        if balance < self.policy.refill_threshold {
+            let id = self.schedule_refill(set, height)?;
+            if id.is_none() {
+                self.add_event(CustodyEvent::HotBufferLow { balance, target: self.policy.hot_target, height });
+            }
+            return Ok(id);
+        }
+        Ok(None)
+    }
+
+    /// Move hot outputs above the hot target to the cold wallet
+    pub fn schedule_sweep(&mut self, set: &mut UtxoSet, height: u32) -> Result<Option<u64>> {
+        let mut hot = self.hot_outputs(set, height);
+        let mut remaining: u64 = hot.iter().map(|utxo| utxo.value).sum();
+
+        // Largest outputs first, skipping any that would dip into the buffer
+        hot.sort_by(|a, b| b.value.cmp(&a.value));
+        let mut inputs = Vec::new();
+        for utxo in hot {
+            if remaining - utxo.value >= self.policy.hot_target {
+                remaining -= utxo.value;
+                inputs.push(utxo.clone());
+            }
+        }
+
+        let cold = self.cold.script_pubkey();
+        let input_vsize = vsize::P2WPKH_INPUT;
+        self.build(set, TransferKind::Sweep, inputs, input_vsize, cold, height)
+    }
+
+    /// Move cold outputs to the hot wallet until it is back at its target
+    pub fn schedule_refill(&mut self, set: &mut UtxoSet, height: u32) -> Result<Option<u64>> {
+        let deficit = self.policy.hot_target.saturating_sub(self.hot_balance(set, height));
+        let cold = self.cold.script_pubkey();
+        let mut outputs: Vec<&Utxo> = set
+            .iter()
+            .filter(|utxo| utxo.script_pubkey == cold && utxo.is_spendable(height, self.policy.min_confirmations))
+            .collect();
+
+        // Smallest outputs first, so as little as possible leaves cold storage
+        outputs.sort_by_key(|utxo| utxo.value);
+        let mut inputs = Vec::new();
+        let mut total = 0u64;
+        for utxo in outputs {
+            if total >= deficit {
+                break;
+            }
+            total += utxo.value;
+            inputs.push(utxo.clone());
+        }
+
+        let hot = self.policy.hot_script.clone();
+        let input_vsize = self.cold.input_vsize(self.policy.cold_threshold);
+        self.build(set, TransferKind::Refill, inputs, input_vsize, hot, height)
+    }
+
+    /// Build a transfer of `inputs` to `destination`, one output per CDP
+    fn build(
+        &mut self,
+        set: &mut UtxoSet,
+        kind: TransferKind,
+        inputs: Vec<Utxo>,
+        input_vsize: u64,
+        destination: ScriptBuf,
+        height: u32,
+    ) -> Result<Option<u64>> {
+        let total: u64 = inputs.iter().map(|utxo| utxo.value).sum();
+        if inputs.is_empty() || total < self.policy.min_transfer {
+            return Ok(None);
+        }
+
+        let mut groups: BTreeMap<Option<[u8; 32]>, u64> = BTreeMap::new();
+        for utxo in &inputs {
+            *groups.entry(utxo.cdp_id).or_default() += utxo.value;
+        }
+        let mut outputs: Vec<TransferOutput> =
+            groups.into_iter().map(|(cdp_id, value)| TransferOutput { value, cdp_id }).collect();
+
+        let tx_vsize = vsize::TX_OVERHEAD + input_vsize * inputs.len() as u64 + vsize::P2TR_OUTPUT * outputs.len() as u64;
+        let fee = self.fee_rate.fee_for_vsize(tx_vsize);
+        let payer = outputs
+            .iter()
+            .position(|output| output.cdp_id.is_none() && output.value >= fee + DUST_SATS)
+            .or_else(|| outputs.iter().enumerate().max_by_key(|(_, output)| output.value).map(|(i, _)| i))
+            .expect("transfer has outputs");
+        if outputs[payer].value < fee + DUST_SATS {
+            return Err(Error::InsufficientCollateral { required: fee + DUST_SATS, available: outputs[payer].value });
+        }
+        outputs[payer].value -= fee;
+
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: inputs
+                .iter()
+                .map(|utxo| TxIn {
+                    previous_output: utxo.outpoint(),
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                    witness: Witness::default(),
+                })
+                .collect(),
+            output: outputs
+                .iter()
+                .map(|output| TxOut { value: Amount::from_sat(output.value), script_pubkey: destination.clone() })
+                .collect(),
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx).map_err(|e| Error::Serialization(e.to_string()))?;
+        for (input, utxo) in psbt.inputs.iter_mut().zip(&inputs) {
+            input.witness_utxo = Some(utxo.to_tx_out());
+            if kind == TransferKind::Refill {
+                let leaf = (self.cold.multisig.clone(), LeafVersion::TapScript);
+                let control = self.cold.spend_info.control_block(&leaf).expect("multisig leaf is in the tree");
+                input.tap_internal_key = Some(self.cold.spend_info.internal_key());
+                input.tap_merkle_root = self.cold.spend_info.merkle_root();
+                input.tap_scripts.insert(control, leaf);
+            }
+        }
+
+        let outpoints: Vec<OutPoint> = inputs.iter().map(Utxo::outpoint).collect();
+        set.lock_utxos(&outpoints);
+
+        let id = self.next_id;
+        self.next_id += 1;
+        if kind == TransferKind::Sweep {
+            self.last_sweep = Some(height);
+        }
+        let transfer = CustodyTransfer {
+            id,
+            kind,
+            inputs,
+            outputs,
+            fee,
+            psbt,
+            status: TransferStatus::AwaitingSignatures,
+            scheduled_at: height,
+        };
+        self.add_event(CustodyEvent::TransferScheduled { id, kind, amount: transfer.amount(), fee, height });
+        self.transfers.insert(id, transfer);
+        Ok(Some(id))
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // SIGNING
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Merge a signed copy of a transfer's PSBT.
+    ///
+    /// A sweep is signed once the hot wallet has finalized every input. A
+    /// refill is signed once every input carries valid signatures from
+    /// the cold threshold; its witnesses are then assembled here.
+    pub fn add_signatures(&mut self, id: u64, signed: Psbt) -> Result<TransferStatus> {
+        let transfer = self.transfers.get(&id).ok_or_else(|| unknown(id))?;
+        if transfer.status != TransferStatus::AwaitingSignatures {
+            return Err(invalid("id", format!("Transfer {} is not awaiting signatures", id)));
+        }
+        if signed.unsigned_tx != transfer.psbt.unsigned_tx {
+            return Err(invalid("psbt", format!("PSBT is not for transfer {}", id)));
+        }
+
+        let kind = transfer.kind;
+        let mut psbt = transfer.psbt.clone();
+        psbt.combine(signed).map_err(|e| Error::Deserialization(e.to_string()))?;
+
+        let complete = match kind {
+            TransferKind::Sweep => psbt.inputs.iter().all(|input| input.final_script_witness.is_some()),
+            TransferKind::Refill => {
+                let signers = self.cold.verified_signers(&psbt)?;
+                let threshold = self.policy.cold_threshold;
+                let count = signers.iter().map(Vec::len).min().unwrap_or(0) as u32;
+                if count >= threshold {
+                    self.cold.finalize(&mut psbt, &signers, threshold);
+                }
+                self.add_event(CustodyEvent::SignaturesAdded { id, signers: count, threshold });
+                count >= threshold
+            }
+        };
+
+        let transfer = self.transfers.get_mut(&id).expect("transfer exists");
+        transfer.psbt = psbt;
+        if complete {
+            transfer.status = TransferStatus::Signed;
+            self.add_event(CustodyEvent::TransferSigned { id });
+        }
+        Ok(self.transfers[&id].status)
+    }
+
+    /// Signed transaction of a transfer, ready to broadcast
+    pub fn signed_transaction(&self, id: u64) -> Result<Transaction> {
+        let transfer = self.transfers.get(&id).ok_or_else(|| unknown(id))?;
+        if transfer.status != TransferStatus::Signed {
+            return Err(invalid("id", format!("Transfer {} is not fully signed", id)));
+        }
+        Ok(transfer.psbt.clone().extract_tx_unchecked_fee_rate())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // LIFECYCLE
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Record that a signed transfer was broadcast
+    pub fn mark_broadcast(&mut self, id: u64) -> Result<()> {
+        let transfer = self.transfers.get_mut(&id).ok_or_else(|| unknown(id))?;
+        if transfer.status != TransferStatus::Signed {
+            return Err(invalid("id", format!("Transfer {} is not fully signed", id)));
+        }
+        transfer.status = TransferStatus::Broadcast;
+        let txid = transfer.txid();
+        self.add_event(CustodyEvent::TransferBroadcast { id, txid });
+        Ok(())
+    }
+
+    /// Record that a transfer confirmed at `height`, replacing its inputs in
+    /// `set` with its outputs under the same CDP attribution
+    pub fn confirm(&mut self, id: u64, set: &mut UtxoSet, height: u32) -> Result<()> {
+        let transfer = self.transfers.get_mut(&id).ok_or_else(|| unknown(id))?;
+        if transfer.status != TransferStatus::Broadcast {
+            return Err(invalid("id", format!("Transfer {} was not broadcast", id)));
+        }
+
+        for utxo in &transfer.inputs {
+            set.spend(&utxo.outpoint(), height);
+        }
+        let txid = transfer.txid();
+        for (vout, (output, txout)) in transfer.outputs.iter().zip(&transfer.psbt.unsigned_tx.output).enumerate() {
+            let mut utxo = Utxo::new(txid, vout as u32, output.value, txout.script_pubkey.clone());
+            utxo.cdp_id = output.cdp_id;
+            utxo.confirm(height);
+            set.add(utxo);
+        }
+
+        transfer.status = TransferStatus::Confirmed(height);
+        self.add_event(CustodyEvent::TransferConfirmed { id, height });
+        Ok(())
+    }
+
+    /// Abandon a transfer that was not broadcast and release its inputs
+    pub fn cancel(&mut self, id: u64, set: &mut UtxoSet) -> Result<()> {
+        let transfer = self.transfers.get(&id).ok_or_else(|| unknown(id))?;
+        if !matches!(transfer.status, TransferStatus::AwaitingSignatures | TransferStatus::Signed) {
+            return Err(invalid("id", format!("Transfer {} was already broadcast", id)));
+        }
+
+        let transfer = self.transfers.remove(&id).expect("transfer exists");
+        let outpoints: Vec<OutPoint> = transfer.inputs.iter().map(Utxo::outpoint).collect();
+        set.unlock_utxos(&outpoints);
+        if transfer.kind == TransferKind::Sweep && self.last_sweep == Some(transfer.scheduled_at) {
+            self.last_sweep = None;
+        }
+        self.add_event(CustodyEvent::TransferCancelled { id });
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // INTERNAL
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Unlocked, confirmed outputs outside the cold wallet
+    fn hot_outputs<'a>(&self, set: &'a UtxoSet, height: u32) -> Vec<&'a Utxo> {
+        let cold = self.cold.script_pubkey();
+        set.iter()
+            .filter(|utxo| utxo.script_pubkey != cold && utxo.is_spendable(height, self.policy.min_confirmations))
+            .collect()
+    }
+
+    /// Add an event (with pruning)
+    fn add_event(&mut self, event: CustodyEvent) {
+        self.events.push(event);
+
+        if self.events.len() > MAX_EVENTS {
+            self.events.drain(0..self.events.len() - MAX_EVENTS);
+        }
+    }
+}
+
+impl ColdWallet {
+    /// Cold keys with a valid signature on each input, in script order
+    fn verified_signers(&self, psbt: &Psbt) -> Result<Vec<Vec<XOnlyPublicKey>>> {
+        let keys = self.keys();
+        let leaf_hash = self.leaf_hash();
+        let secp = Secp256k1::verification_only();
+
+        let mut signers = Vec::with_capacity(psbt.inputs.len());
+        for index in 0..psbt.inputs.len() {
+            let sighash = script_sighash(psbt, index, leaf_hash)?;
+            let mut valid = Vec::new();
+            for key in &keys {
+                if let Some(sig) = psbt.inputs[index].tap_script_sigs.get(&(*key, leaf_hash)) {
+                    secp.verify_schnorr(&sig.signature, &sighash, key).map_err(|_| Error::InvalidSignature)?;
+                    valid.push(*key);
+                }
+            }
+            signers.push(valid);
+        }
+        Ok(signers)
+    }
+
+    /// Assemble each input's script-path witness from the first `threshold`
+    /// signers
+    fn finalize(&self, psbt: &mut Psbt, signers: &[Vec<XOnlyPublicKey>], threshold: u32) {
+        let leaf = (self.multisig.clone(), LeafVersion::TapScript);
+        let control = self.spend_info.control_block(&leaf).expect("multisig leaf is in the tree");
+        let leaf_hash = self.leaf_hash();
+
+        for (input, signers) in psbt.inputs.iter_mut().zip(signers) {
+            let used = &signers[..threshold as usize];
+            // The first key's signature is checked first, so it goes on top
+            let mut witness = Witness::new();
+            for key in self.keys().iter().rev() {
+                match input.tap_script_sigs.get(&(*key, leaf_hash)) {
+                    Some(sig) if used.contains(key) => witness.push(sig.to_vec()),
+                    _ => witness.push([0u8; 0]),
+                }
+            }
+            witness.push(self.multisig.as_bytes());
+            witness.push(control.serialize());
+            input.final_script_witness = Some(witness);
+        }
+    }
+
+    /// Cold keys in script order
+    fn keys(&self) -> Vec<XOnlyPublicKey> {
+        self.multisig
+            .instructions()
+            .filter_map(|instruction| match instruction {
+                Ok(bitcoin::script::Instruction::PushBytes(bytes)) if bytes.len() == 32 => {
+                    XOnlyPublicKey::from_slice(bytes.as_bytes()).ok()
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Sign every input of a refill PSBT with a cold key
+pub fn sign_cold_inputs(psbt: &mut Psbt, cold: &ColdWallet, keypair: &Keypair) -> Result<usize> {
+    let secp = Secp256k1::new();
+    let (key, _) = keypair.x_only_public_key();
+    if !cold.keys().contains(&key) {
+        return Err(Error::Unauthorized("Key is not a cold signer".into()));
+    }
+
+    let leaf_hash = cold.leaf_hash();
+    for index in 0..psbt.inputs.len() {
+        let sighash = script_sighash(psbt, index, leaf_hash)?;
+        let signature = taproot::Signature {
+            signature: secp.sign_schnorr(&sighash, keypair),
+            sighash_type: TapSighashType::Default,
+        };
+        psbt.inputs[index].tap_script_sigs.insert((key, leaf_hash), signature);
+    }
+    Ok(psbt.inputs.len())
+}
+
+/// Script-path sighash of input `index`
+fn script_sighash(psbt: &Psbt, index: usize, leaf_hash: TapLeafHash) -> Result<Message> {
+    let prevouts: Vec<TxOut> = psbt
+        .inputs
+        .iter()
+        .map(|input| input.witness_utxo.clone().ok_or_else(|| invalid("psbt", "Input without witness UTXO")))
+        .collect::<Result<_>>()?;
+    let sighash = SighashCache::new(&psbt.unsigned_tx)
+        .taproot_script_spend_signature_hash(index, &Prevouts::All(&prevouts), leaf_hash, TapSighashType::Default)
+        .map_err(|e| Error::Internal(format!("Failed to compute sighash: {}", e)))?;
+    Ok(Message::from_digest(sighash.to_byte_array()))
+}
+
+fn invalid(name: &str, reason: impl Into<String>) -> Error {
+    Error::InvalidParameter {
+        name: name.into(),
+        reason: reason.into(),
+    }
+}
+
+fn unknown(id: u64) -> Error {
+    invalid("id", format!("No custody transfer {}", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::secp256k1::rand::thread_rng;
+    use std::str::FromStr;
+
+    fn txid(n: u8) -> Txid {
+        Txid::from_str(&format!("{:064x}", n)).unwrap()
+    }
+
+    fn setup() -> (Custody, Vec<Keypair>, UtxoSet) {
+        let secp = Secp256k1::new();
+        let keypairs: Vec<Keypair> = (0..3).map(|_| Keypair::new(&secp, &mut thread_rng())).collect();
+        let keys = keypairs.iter().map(|keypair| keypair.x_only_public_key().0).collect();
+        let policy = CustodyPolicy::new(keys, 2, ScriptBuf::new(), 1_000_000)
+            .with_thresholds(2_000_000, 500_000)
+            .with_min_confirmations(1);
+        (Custody::new(policy).unwrap(), keypairs, UtxoSet::new())
+    }
+
+    fn hot_utxo(set: &mut UtxoSet, n: u8, value: u64, cdp_id: Option<[u8; 32]>) {
+        let mut utxo = Utxo::new(txid(n), 0, value, ScriptBuf::new());
+        utxo.cdp_id = cdp_id;
+        utxo.confirm(100);
+        set.add(utxo);
+    }
+
+    #[test]
+    fn test_policy_validation() {
+        let (custody, _, _) = setup();
+        custody.policy().validate().unwrap();
+
+        let mut policy = custody.policy().clone();
+        policy.cold_threshold = 4;
+        assert!(policy.validate().is_err());
+
+        let policy = custody.policy().clone().with_thresholds(500_000, 500_000);
+        assert!(policy.validate().is_err());
+    }
+
+    #[test]
+    fn test_sweep_keeps_buffer_and_attribution() {
+        let (mut custody, _, mut set) = setup();
+        hot_utxo(&mut set, 1, 1_500_000, Some([1u8; 32]));
+        hot_utxo(&mut set, 2, 1_200_000, Some([2u8; 32]));
+        hot_utxo(&mut set, 3, 400_000, None);
+        hot_utxo(&mut set, 4, 300_000, None);
+
+        let id = custody.schedule(&mut set, 100).unwrap().unwrap();
+        let transfer = custody.transfer(id).unwrap().clone();
+        assert_eq!(transfer.kind, TransferKind::Sweep);
+        // Sweeping the 1.2M output as well would dip below the hot target
+        assert_eq!(custody.hot_balance(&set, 100), 1_200_000);
+        assert_eq!(transfer.outputs.len(), 2);
+        assert_eq!(transfer.outputs[1], TransferOutput { value: 1_500_000, cdp_id: Some([1u8; 32]) });
+        assert_eq!(transfer.outputs[0].value, 700_000 - transfer.fee);
+
+        // Not due again within the interval
+        assert_eq!(custody.schedule(&mut set, 101).unwrap(), None);
+
+        // Hot wallet signs and finalizes; the outputs land cold, still attributed
+        let mut signed = transfer.psbt.clone();
+        for input in &mut signed.inputs {
+            input.final_script_witness = Some(Witness::from_slice(&[[0u8; 64]]));
+        }
+        assert_eq!(custody.add_signatures(id, signed).unwrap(), TransferStatus::Signed);
+        custody.mark_broadcast(id).unwrap();
+        custody.confirm(id, &mut set, 102).unwrap();
+
+        assert_eq!(custody.cold_balance(&set), transfer.amount());
+        assert_eq!(set.cdp_collateral(&[1u8; 32]), 1_500_000);
+        assert!(matches!(custody.recent_events().last(), Some(CustodyEvent::TransferConfirmed { id: 1, height: 102 })));
+    }
+
+    #[test]
+    fn test_refill_collects_cold_threshold() {
+        let (mut custody, keypairs, mut set) = setup();
+        let mut cold = Utxo::new(txid(9), 0, 3_000_000, custody.cold_wallet().script_pubkey());
+        cold.cdp_id = Some([3u8; 32]);
+        cold.confirm(100);
+        set.add(cold);
+        hot_utxo(&mut set, 1, 200_000, None);
+
+        let id = custody.schedule(&mut set, 100).unwrap().unwrap();
+        assert_eq!(custody.transfer(id).unwrap().kind, TransferKind::Refill);
+        let unsigned = custody.transfer(id).unwrap().psbt.clone();
+
+        // One cold signer is not enough
+        let mut first = unsigned.clone();
+        sign_cold_inputs(&mut first, custody.cold_wallet(), &keypairs[2]).unwrap();
+        assert_eq!(custody.add_signatures(id, first).unwrap(), TransferStatus::AwaitingSignatures);
+        assert!(custody.signed_transaction(id).is_err());
+
+        let mut second = unsigned.clone();
+        sign_cold_inputs(&mut second, custody.cold_wallet(), &keypairs[0]).unwrap();
+        assert_eq!(custody.add_signatures(id, second).unwrap(), TransferStatus::Signed);
+
+        let tx = custody.signed_transaction(id).unwrap();
+        // Two signatures, one empty push, the script and the control block
+        let witness = &tx.input[0].witness;
+        assert_eq!(witness.len(), 5);
+        assert_eq!(witness.nth(1).unwrap().len(), 0);
+
+        // A stranger can't sign
+        let stranger = Keypair::new(&Secp256k1::new(), &mut thread_rng());
+        assert!(sign_cold_inputs(&mut unsigned.clone(), custody.cold_wallet(), &stranger).is_err());
+    }
+
+    #[test]
+    fn test_cancel_releases_inputs() {
+        let (mut custody, _, mut set) = setup();
+        hot_utxo(&mut set, 1, 2_500_000, None);
+        hot_utxo(&mut set, 2, 1_000_000, None);
+
+        let id = custody.schedule(&mut set, 100).unwrap().unwrap();
+        assert_eq!(custody.hot_balance(&set, 100), 1_000_000);
+
+        custody.cancel(id, &mut set).unwrap();
+        assert_eq!(custody.hot_balance(&set, 100), 3_500_000);
+        assert!(custody.pending().is_empty());
+        assert!(custody.sweep_due(100));
+    }
+}
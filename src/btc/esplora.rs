@@ -0,0 +1,302 @@
+//! Esplora HTTP chain backend.
+//!
+//! Alternative to the Bitcoin Core client for operators without a full node.
+//! Talks to any Esplora-compatible REST API (blockstream.info, mempool.space,
+//! self-hosted electrs) and estimates fees from the mempool fee histogram.
+
+use bitcoin::consensus::encode::serialize_hex;
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::{BlockHash, OutPoint, ScriptBuf, Transaction, Txid};
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::btc::chain::ChainBackend;
+use crate::btc::tx_builder::FeeRate;
+use crate::btc::utxo::{Utxo, UtxoStatus};
+use crate::error::{Error, Result};
+
+/// Virtual size of a full block
+const BLOCK_VSIZE: u64 = 1_000_000;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// CONFIGURATION
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Esplora connection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EsploraConfig {
+    /// API base URL (e.g. `https://blockstream.info/api`)
+    pub base_url: String,
+    /// Request timeout in milliseconds
+    pub timeout_ms: u64,
+}
+
+impl Default for EsploraConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "https://blockstream.info/api".to_string(),
+            timeout_ms: 10_000,
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// API RESPONSE TYPES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Confirmation status of a transaction
+#[derive(Debug, Deserialize)]
+struct TxStatus {
+    confirmed: bool,
+    block_height: Option<u32>,
+}
+
+/// Entry of `/scripthash/:hash/utxo`
+#[derive(Debug, Deserialize)]
+struct EsploraUtxo {
+    txid: String,
+    vout: u32,
+    value: u64,
+    status: TxStatus,
+}
+
+/// `/tx/:txid/outspend/:vout`
+#[derive(Debug, Deserialize)]
+struct OutSpend {
+    spent: bool,
+}
+
+/// `/mempool`
+#[derive(Debug, Deserialize)]
+struct MempoolInfo {
+    /// `[fee rate (sat/vB), vsize]` pairs, highest fee rate first
+    fee_histogram: Vec<(f64, u64)>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// CLIENT
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Esplora REST client
+pub struct EsploraClient {
+    /// HTTP client
+    client: Client,
+    /// Configuration
+    config: EsploraConfig,
+}
+
+impl EsploraClient {
+    /// Create a new client
+    pub fn new(config: EsploraConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_millis(config.timeout_ms))
+            .build()
+            .map_err(|e| Error::Internal(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self { client, config })
+    }
+
+    /// Get configuration
+    pub fn config(&self) -> &EsploraConfig {
+        &self.config
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.config.base_url.trim_end_matches('/'), path)
+    }
+
+    /// GET a plain-text endpoint; None on 404
+    async fn get_text(&self, path: &str) -> Result<Option<String>> {
+        let response = self
+            .client
+            .get(self.url(path))
+            .send()
+            .await
+            .map_err(|e| Error::Internal(format!("Esplora request {} failed: {}", path, e)))?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = response
+            .error_for_status()
+            .map_err(|e| Error::Internal(format!("Esplora request {} failed: {}", path, e)))?;
+
+        response
+            .text()
+            .await
+            .map(Some)
+            .map_err(|e| Error::Internal(format!("Failed to read Esplora response: {}", e)))
+    }
+
+    /// GET a JSON endpoint; None on 404
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<Option<T>> {
+        match self.get_text(path).await? {
+            Some(body) => serde_json::from_str(&body)
+                .map(Some)
+                .map_err(|e| Error::Internal(format!("Failed to parse Esplora {} response: {}", path, e))),
+            None => Ok(None),
+        }
+    }
+
+    /// GET an endpoint that must exist
+    async fn get_required<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        self.get_json(path)
+            .await?
+            .ok_or_else(|| Error::Internal(format!("Esplora {} not found", path)))
+    }
+
+    /// Current mempool fee histogram
+    pub async fn fee_histogram(&self) -> Result<Vec<(f64, u64)>> {
+        let mempool: MempoolInfo = self.get_required("/mempool").await?;
+        Ok(mempool.fee_histogram)
+    }
+}
+
+impl ChainBackend for EsploraClient {
+    async fn tip_height(&self) -> Result<u32> {
+        let text = self
+            .get_text("/blocks/tip/height")
+            .await?
+            .ok_or_else(|| Error::Internal("Esplora tip height not found".into()))?;
+        text.trim()
+            .parse()
+            .map_err(|e| Error::Deserialization(format!("Invalid tip height: {}", e)))
+    }
+
+    async fn block_hash(&self, height: u32) -> Result<BlockHash> {
+        let text = self
+            .get_text(&format!("/block-height/{}", height))
+            .await?
+            .ok_or_else(|| Error::Internal(format!("No block at height {}", height)))?;
+        BlockHash::from_str(text.trim()).map_err(|e| Error::Deserialization(e.to_string()))
+    }
+
+    async fn broadcast(&self, tx: &Transaction) -> Result<Txid> {
+        let response = self
+            .client
+            .post(self.url("/tx"))
+            .body(serialize_hex(tx))
+            .send()
+            .await
+            .map_err(|e| Error::Internal(format!("Esplora broadcast failed: {}", e)))?;
+
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to read Esplora response: {}", e)))?;
+
+        if !status.is_success() {
+            return Err(Error::Internal(format!("Esplora rejected transaction: {}", body)));
+        }
+        Txid::from_str(body.trim()).map_err(|e| Error::Deserialization(e.to_string()))
+    }
+
+    async fn script_utxos(&self, script_pubkey: &ScriptBuf) -> Result<Vec<Utxo>> {
+        let path = format!("/scripthash/{}/utxo", script_hash(script_pubkey));
+        let entries: Vec<EsploraUtxo> = self.get_json(&path).await?.unwrap_or_default();
+
+        entries
+            .into_iter()
+            .map(|entry| {
+                let txid = Txid::from_str(&entry.txid)
+                    .map_err(|e| Error::Deserialization(e.to_string()))?;
+                let mut utxo = Utxo::new(txid, entry.vout, entry.value, script_pubkey.clone());
+                if let (true, Some(height)) = (entry.status.confirmed, entry.status.block_height) {
+                    utxo.confirm(height);
+                }
+                Ok(utxo)
+            })
+            .collect()
+    }
+
+    async fn utxo_status(&self, outpoint: &OutPoint) -> Result<Option<UtxoStatus>> {
+        let outspend: Option<OutSpend> = self
+            .get_json(&format!("/tx/{}/outspend/{}", outpoint.txid, outpoint.vout))
+            .await?;
+        if !matches!(outspend, Some(OutSpend { spent: false })) {
+            return Ok(None);
+        }
+
+        let status: Option<TxStatus> = self
+            .get_json(&format!("/tx/{}/status", outpoint.txid))
+            .await?;
+        Ok(status.map(|s| UtxoStatus {
+            confirmation_height: s.block_height.filter(|_| s.confirmed),
+        }))
+    }
+
+    async fn estimate_fee(&self, target_blocks: u16) -> Result<FeeRate> {
+        let histogram = self.fee_histogram().await?;
+        Ok(fee_rate_from_histogram(&histogram, target_blocks))
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// HELPERS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Esplora script hash: SHA256 of the script, hex in reversed byte order
+fn script_hash(script_pubkey: &ScriptBuf) -> String {
+    let mut bytes = sha256::Hash::hash(script_pubkey.as_bytes()).to_byte_array();
+    bytes.reverse();
+    hex::encode(bytes)
+}
+
+/// Fee rate needed to land within `target_blocks` given the mempool histogram.
+///
+/// Walks the histogram from the highest fee rate down until the mempool ahead
+/// of us fills `target_blocks` blocks; if it never does, the minimum relay
+/// fee is enough.
+fn fee_rate_from_histogram(histogram: &[(f64, u64)], target_blocks: u16) -> FeeRate {
+    let capacity = BLOCK_VSIZE.saturating_mul(target_blocks.max(1) as u64);
+    let mut cumulative = 0u64;
+
+    for &(rate, vsize) in histogram {
+        cumulative = cumulative.saturating_add(vsize);
+        if cumulative >= capacity {
+            return FeeRate::from_sat_per_vb(rate.ceil() as u64);
+        }
+    }
+
+    FeeRate::MIN
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_rate_from_histogram() {
+        let histogram = vec![(50.0, 400_000), (20.5, 700_000), (5.0, 2_000_000)];
+
+        // First block fills inside the 20.5 sat/vB bucket
+        assert_eq!(fee_rate_from_histogram(&histogram, 1).sat_per_vb(), 21);
+        // Three blocks reach into the 5 sat/vB bucket
+        assert_eq!(fee_rate_from_histogram(&histogram, 3).sat_per_vb(), 5);
+        // A mempool smaller than the target clears at the minimum
+        assert_eq!(fee_rate_from_histogram(&histogram, 10).sat_per_vb(), 1);
+    }
+
+    #[test]
+    fn test_script_hash_reversed() {
+        // Empty script: sha256("") reversed
+        let hash = script_hash(&ScriptBuf::new());
+        assert_eq!(hash, "55b852781b9995a44c939b64e441ae2724b96f99c8f4fb9a141cfc9842c4b0e3");
+    }
+
+    #[test]
+    fn test_parse_utxo_entry() {
+        let entries: Vec<EsploraUtxo> = serde_json::from_str(
+            r#"[{"txid":"0000000000000000000000000000000000000000000000000000000000000001","vout":0,
+                "status":{"confirmed":true,"block_height":800000,"block_hash":"00","block_time":1},
+                "value":12345}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(entries[0].value, 12_345);
+        assert_eq!(entries[0].status.block_height, Some(800_000));
+    }
+}
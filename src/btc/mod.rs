@@ -1,12 +1,29 @@
 //! Bitcoin integration module.
 //!
 //! This module provides real Bitcoin transaction building and signing
-//! for the zkUSD protocol operations.
+//! for the zkUSD protocol operations, plus chain access for broadcasting
+//! and UTXO tracking (Bitcoin Core RPC behind the `bitcoind` feature, or an
+//! Esplora HTTP API behind the `esplora` feature), SPV verification of
+//! collateral deposits and hot/cold custody of protocol-held collateral.
 
+pub mod chain;
+pub mod custody;
+#[cfg(feature = "esplora")]
+pub mod esplora;
+#[cfg(feature = "bitcoind")]
+pub mod rpc_client;
+pub mod spv;
 pub mod tx_builder;
 pub mod utxo;
 pub mod scripts;
 
+pub use chain::*;
+pub use custody::{ColdWallet, Custody, CustodyEvent, CustodyPolicy, CustodyTransfer, TransferKind, TransferStatus};
+#[cfg(feature = "esplora")]
+pub use esplora::{EsploraClient, EsploraConfig};
+#[cfg(feature = "bitcoind")]
+pub use rpc_client::{BitcoinRpcClient, BitcoinRpcConfig};
+pub use spv::*;
 pub use tx_builder::*;
 pub use utxo::*;
 pub use scripts::*;
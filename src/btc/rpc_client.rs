@@ -0,0 +1,410 @@
+//! Bitcoin Core JSON-RPC client.
+//!
+//! Wraps the subset of the Bitcoin Core RPC interface the protocol needs:
+//! - Broadcasting transactions built by `tx_builder`
+//! - Fetching UTXOs and confirmations for collateral scripts
+//! - Fee estimation
+//! - New block notifications over ZMQ (`zmqpubhashblock`)
+
+use bitcoin::consensus::encode::serialize_hex;
+use bitcoin::{Amount, BlockHash, OutPoint, ScriptBuf, Transaction, Txid};
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::mpsc;
+
+use crate::btc::chain::{BlockNotification, ChainBackend};
+use crate::btc::tx_builder::FeeRate;
+use crate::btc::utxo::{Utxo, UtxoStatus};
+use crate::error::{Error, Result};
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// CONFIGURATION
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Bitcoin Core connection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitcoinRpcConfig {
+    /// RPC endpoint (e.g. `http://127.0.0.1:8332`)
+    pub url: String,
+    /// RPC username
+    pub user: String,
+    /// RPC password
+    pub password: String,
+    /// Request timeout in milliseconds
+    pub timeout_ms: u64,
+    /// ZMQ `zmqpubhashblock` endpoint (e.g. `tcp://127.0.0.1:28332`)
+    pub zmq_block_endpoint: Option<String>,
+}
+
+impl Default for BitcoinRpcConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://127.0.0.1:8332".to_string(),
+            user: String::new(),
+            password: String::new(),
+            timeout_ms: 30_000,
+            zmq_block_endpoint: None,
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// RPC RESPONSE TYPES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// JSON-RPC response envelope
+#[derive(Debug, Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+/// JSON-RPC error object
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+/// `gettxout` result
+#[derive(Debug, Deserialize)]
+struct TxOutResult {
+    confirmations: u32,
+}
+
+/// `scantxoutset` result
+#[derive(Debug, Deserialize)]
+struct ScanResult {
+    unspents: Vec<ScanUnspent>,
+}
+
+/// Single entry of a `scantxoutset` result
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScanUnspent {
+    txid: String,
+    vout: u32,
+    script_pub_key: String,
+    amount: f64,
+    height: u32,
+}
+
+/// `estimatesmartfee` result
+#[derive(Debug, Deserialize)]
+struct EstimateFeeResult {
+    feerate: Option<f64>,
+}
+
+/// `getblockheader` result
+#[derive(Debug, Deserialize)]
+struct BlockHeaderResult {
+    height: u32,
+    time: u64,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// CLIENT
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Bitcoin Core JSON-RPC client
+pub struct BitcoinRpcClient {
+    /// HTTP client
+    client: Client,
+    /// Configuration
+    config: BitcoinRpcConfig,
+    /// Request ID counter
+    next_id: AtomicU64,
+}
+
+impl BitcoinRpcClient {
+    /// Create a new client
+    pub fn new(config: BitcoinRpcConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_millis(config.timeout_ms))
+            .build()
+            .map_err(|e| Error::Internal(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            config,
+            next_id: AtomicU64::new(0),
+        })
+    }
+
+    /// Get configuration
+    pub fn config(&self) -> &BitcoinRpcConfig {
+        &self.config
+    }
+
+    /// Call an RPC method
+    pub async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        self.call_optional(method, params)
+            .await?
+            .ok_or_else(|| Error::Internal(format!("Bitcoin RPC {} returned no result", method)))
+    }
+
+    /// Call an RPC method whose result may be null
+    pub async fn call_optional<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<Option<T>> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let body = json!({
+            "jsonrpc": "1.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+
+        let response = self
+            .client
+            .post(&self.config.url)
+            .basic_auth(&self.config.user, Some(&self.config.password))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| Error::Internal(format!("Bitcoin RPC {} failed: {}", method, e)))?;
+
+        let data: RpcResponse<T> = response
+            .json()
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to parse {} response: {}", method, e)))?;
+
+        Self::into_result(method, data)
+    }
+
+    /// Unwrap a JSON-RPC envelope
+    fn into_result<T>(method: &str, response: RpcResponse<T>) -> Result<Option<T>> {
+        if let Some(err) = response.error {
+            return Err(Error::Internal(format!(
+                "Bitcoin RPC {} error {}: {}",
+                method, err.code, err.message
+            )));
+        }
+        Ok(response.result)
+    }
+
+    /// Fetch height and time for a block
+    pub async fn block_notification(&self, hash: BlockHash) -> Result<BlockNotification> {
+        let header: BlockHeaderResult = self
+            .call("getblockheader", json!([hash.to_string(), true]))
+            .await?;
+
+        Ok(BlockNotification {
+            height: header.height,
+            hash,
+            time: header.time,
+        })
+    }
+
+    /// Subscribe to new blocks via ZMQ `hashblock` notifications.
+    ///
+    /// Each notification is resolved to its height and timestamp so it can be
+    /// fed straight into [`BlockNotification::begin_block`].
+    pub async fn subscribe_blocks(self: std::sync::Arc<Self>) -> Result<mpsc::Receiver<BlockNotification>> {
+        use zeromq::{Socket, SocketRecv, SubSocket};
+
+        let endpoint = self.config.zmq_block_endpoint.clone().ok_or_else(|| {
+            Error::InvalidParameter {
+                name: "zmq_block_endpoint".into(),
+                reason: "ZMQ block endpoint not configured".into(),
+            }
+        })?;
+
+        let mut socket = SubSocket::new();
+        socket
+            .connect(&endpoint)
+            .await
+            .map_err(|e| Error::Internal(format!("ZMQ connect failed: {}", e)))?;
+        socket
+            .subscribe("hashblock")
+            .await
+            .map_err(|e| Error::Internal(format!("ZMQ subscribe failed: {}", e)))?;
+
+        let (tx, rx) = mpsc::channel(64);
+
+        tokio::spawn(async move {
+            loop {
+                let message = match socket.recv().await {
+                    Ok(message) => message,
+                    Err(e) => {
+                        tracing::warn!("ZMQ receive failed: {}", e);
+                        break;
+                    }
+                };
+
+                let Some(hash) = message.get(1).and_then(|bytes| parse_zmq_block_hash(bytes)) else {
+                    continue;
+                };
+
+                match self.block_notification(hash).await {
+                    Ok(notification) => {
+                        if tx.send(notification).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => tracing::warn!("Failed to resolve block {}: {}", hash, e),
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+}
+
+impl ChainBackend for BitcoinRpcClient {
+    async fn tip_height(&self) -> Result<u32> {
+        self.call("getblockcount", json!([])).await
+    }
+
+    async fn block_hash(&self, height: u32) -> Result<BlockHash> {
+        let hash: String = self.call("getblockhash", json!([height])).await?;
+        BlockHash::from_str(&hash).map_err(|e| Error::Deserialization(e.to_string()))
+    }
+
+    async fn broadcast(&self, tx: &Transaction) -> Result<Txid> {
+        let txid: String = self.call("sendrawtransaction", json!([serialize_hex(tx)])).await?;
+        Txid::from_str(&txid).map_err(|e| Error::Deserialization(e.to_string()))
+    }
+
+    async fn script_utxos(&self, script_pubkey: &ScriptBuf) -> Result<Vec<Utxo>> {
+        let descriptor = format!("raw({})", script_pubkey.to_hex_string());
+        let scan: ScanResult = self
+            .call("scantxoutset", json!(["start", [descriptor]]))
+            .await?;
+
+        scan.unspents.into_iter().map(scan_unspent_to_utxo).collect()
+    }
+
+    async fn utxo_status(&self, outpoint: &OutPoint) -> Result<Option<UtxoStatus>> {
+        // gettxout returns null for spent or unknown outputs
+        let txout: Option<TxOutResult> = self
+            .call_optional("gettxout", json!([outpoint.txid.to_string(), outpoint.vout, true]))
+            .await?;
+
+        let Some(txout) = txout else { return Ok(None) };
+        if txout.confirmations == 0 {
+            return Ok(Some(UtxoStatus { confirmation_height: None }));
+        }
+
+        let tip = self.tip_height().await?;
+        Ok(Some(UtxoStatus {
+            confirmation_height: Some(confirmation_height(tip, txout.confirmations)?),
+        }))
+    }
+
+    async fn estimate_fee(&self, target_blocks: u16) -> Result<FeeRate> {
+        let estimate: EstimateFeeResult = self
+            .call("estimatesmartfee", json!([target_blocks]))
+            .await?;
+
+        Ok(estimate
+            .feerate
+            .map(btc_per_kvb_to_fee_rate)
+            .unwrap_or(FeeRate::MIN))
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// HELPERS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Convert a `scantxoutset` entry into a confirmed UTXO
+fn scan_unspent_to_utxo(entry: ScanUnspent) -> Result<Utxo> {
+    let txid = Txid::from_str(&entry.txid).map_err(|e| Error::Deserialization(e.to_string()))?;
+    let script = ScriptBuf::from_hex(&entry.script_pub_key)
+        .map_err(|e| Error::Deserialization(e.to_string()))?;
+    let value = Amount::from_btc(entry.amount)
+        .map_err(|e| Error::Deserialization(e.to_string()))?
+        .to_sat();
+
+    let mut utxo = Utxo::new(txid, entry.vout, value, script);
+    utxo.confirm(entry.height);
+    Ok(utxo)
+}
+
+/// Height of the block that confirmed an output `confirmations` deep below
+/// `tip`. The tip is fetched separately, so a reorg in between can leave the
+/// output deeper than the chain is tall.
+fn confirmation_height(tip: u32, confirmations: u32) -> Result<u32> {
+    tip.checked_add(1)
+        .and_then(|next| next.checked_sub(confirmations))
+        .ok_or_else(|| {
+            Error::Internal(format!("{} confirmations reported above tip {}; chain changed, retry", confirmations, tip))
+        })
+}
+
+/// Convert Bitcoin Core's BTC/kvB fee rate to sat/vB
+fn btc_per_kvb_to_fee_rate(btc_per_kvb: f64) -> FeeRate {
+    let sat_per_kvb = Amount::from_btc(btc_per_kvb).map(|a| a.to_sat()).unwrap_or(0);
+    FeeRate::from_sat_per_vb(sat_per_kvb.div_ceil(1000))
+}
+
+/// Parse the block hash frame of a ZMQ `hashblock` message
+///
+/// ZMQ publishes the hash in display (big-endian) byte order.
+fn parse_zmq_block_hash(bytes: &[u8]) -> Option<BlockHash> {
+    use bitcoin::hashes::Hash;
+
+    let mut array: [u8; 32] = bytes.try_into().ok()?;
+    array.reverse();
+    Some(BlockHash::from_byte_array(array))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_rate_conversion() {
+        // 0.00012 BTC/kvB = 12 sat/vB
+        assert_eq!(btc_per_kvb_to_fee_rate(0.00012).sat_per_vb(), 12);
+        // Fractional rates round up
+        assert_eq!(btc_per_kvb_to_fee_rate(0.000015).sat_per_vb(), 2);
+    }
+
+    #[test]
+    fn test_confirmation_height() {
+        assert_eq!(confirmation_height(800_000, 1).unwrap(), 800_000);
+        assert_eq!(confirmation_height(800_000, 6).unwrap(), 799_995);
+        // The tip moved back between gettxout and getblockcount
+        assert!(confirmation_height(5, 7).is_err());
+        assert!(confirmation_height(u32::MAX, 1).is_err());
+    }
+
+    #[test]
+    fn test_zmq_block_hash_byte_order() {
+        let display = "000000000000000000024bead8df69990852c202db0e0097c1a12ea637d7e96d";
+        let bytes = hex::decode(display).unwrap();
+
+        assert_eq!(parse_zmq_block_hash(&bytes).unwrap().to_string(), display);
+        assert!(parse_zmq_block_hash(&bytes[1..]).is_none());
+    }
+
+    #[test]
+    fn test_scan_unspent_conversion() {
+        let entry = ScanUnspent {
+            txid: "0000000000000000000000000000000000000000000000000000000000000001".into(),
+            vout: 2,
+            script_pub_key: "0014".to_string() + &"00".repeat(20),
+            amount: 0.015,
+            height: 800_000,
+        };
+
+        let utxo = scan_unspent_to_utxo(entry).unwrap();
+        assert_eq!(utxo.value, 1_500_000);
+        assert_eq!(utxo.confirmation_height, Some(800_000));
+    }
+
+    #[test]
+    fn test_rpc_error_envelope() {
+        let response: RpcResponse<u32> = serde_json::from_str(
+            r#"{"result":null,"error":{"code":-25,"message":"bad-txns-inputs-missingorspent"}}"#,
+        )
+        .unwrap();
+
+        let err = BitcoinRpcClient::into_result("sendrawtransaction", response).unwrap_err();
+        assert!(err.to_string().contains("-25"));
+    }
+}
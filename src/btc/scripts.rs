@@ -204,6 +204,55 @@ impl OpReturnBuilder {
             .into_script()
     }
 
+    /// Build an OP_RETURN script for a redemption payout
+    pub fn redemption(spell_hash: &[u8; 32], zkusd_redeemed: u64, collateral_paid: u64) -> ScriptBuf {
+        let mut data = Vec::with_capacity(54);
+        data.extend_from_slice(Self::PROTOCOL_PREFIX);
+        data.push(0x20); // Operation: Redemption
+        data.extend_from_slice(spell_hash);
+        data.extend_from_slice(&zkusd_redeemed.to_le_bytes());
+        data.extend_from_slice(&collateral_paid.to_le_bytes());
+
+        let push_bytes = PushBytesBuf::try_from(data).expect("OP_RETURN data within limits");
+
+        ScriptBuilder::new()
+            .push_opcode(opcodes::all::OP_RETURN)
+            .push_slice(push_bytes)
+            .into_script()
+    }
+
+    /// Build an OP_RETURN script for a stability pool gains claim
+    pub fn gains_claim(depositor: &[u8; 33], amount: u64) -> ScriptBuf {
+        let mut data = Vec::with_capacity(47);
+        data.extend_from_slice(Self::PROTOCOL_PREFIX);
+        data.push(0x30); // Operation: Claim gains
+        data.extend_from_slice(depositor);
+        data.extend_from_slice(&amount.to_le_bytes());
+
+        let push_bytes = PushBytesBuf::try_from(data).expect("OP_RETURN data within limits");
+
+        ScriptBuilder::new()
+            .push_opcode(opcodes::all::OP_RETURN)
+            .push_slice(push_bytes)
+            .into_script()
+    }
+
+    /// Build an OP_RETURN script anchoring a bundle of spells
+    pub fn spell_bundle(bundle_hash: &[u8; 32], spell_count: u16) -> ScriptBuf {
+        let mut data = Vec::with_capacity(40);
+        data.extend_from_slice(Self::PROTOCOL_PREFIX);
+        data.push(0x40); // Operation: Spell bundle
+        data.extend_from_slice(bundle_hash);
+        data.extend_from_slice(&spell_count.to_le_bytes());
+
+        let push_bytes = PushBytesBuf::try_from(data).expect("OP_RETURN data within limits");
+
+        ScriptBuilder::new()
+            .push_opcode(opcodes::all::OP_RETURN)
+            .push_slice(push_bytes)
+            .into_script()
+    }
+
     /// Parse protocol data from an OP_RETURN script
     pub fn parse(script: &ScriptBuf) -> Option<ProtocolOp> {
         let bytes = script.as_bytes();
@@ -249,6 +298,25 @@ impl OpReturnBuilder {
                 let collateral_seized = u64::from_le_bytes(payload[40..48].try_into().ok()?);
                 Some(ProtocolOp::Liquidation { cdp_id, debt_repaid, collateral_seized })
             }
+            0x20 if payload.len() >= 48 => {
+                let mut spell_hash = [0u8; 32];
+                spell_hash.copy_from_slice(&payload[..32]);
+                let zkusd_redeemed = u64::from_le_bytes(payload[32..40].try_into().ok()?);
+                let collateral_paid = u64::from_le_bytes(payload[40..48].try_into().ok()?);
+                Some(ProtocolOp::Redemption { spell_hash, zkusd_redeemed, collateral_paid })
+            }
+            0x30 if payload.len() >= 41 => {
+                let mut depositor = [0u8; 33];
+                depositor.copy_from_slice(&payload[..33]);
+                let amount = u64::from_le_bytes(payload[33..41].try_into().ok()?);
+                Some(ProtocolOp::GainsClaim { depositor, amount })
+            }
+            0x40 if payload.len() >= 34 => {
+                let mut bundle_hash = [0u8; 32];
+                bundle_hash.copy_from_slice(&payload[..32]);
+                let spell_count = u16::from_le_bytes(payload[32..34].try_into().ok()?);
+                Some(ProtocolOp::SpellBundle { bundle_hash, spell_count })
+            }
             _ => None,
         }
     }
@@ -265,6 +333,12 @@ pub enum ProtocolOp {
     Withdraw { cdp_id: [u8; 32], amount: u64 },
     /// Liquidation event
     Liquidation { cdp_id: [u8; 32], debt_repaid: u64, collateral_seized: u64 },
+    /// Redemption payout
+    Redemption { spell_hash: [u8; 32], zkusd_redeemed: u64, collateral_paid: u64 },
+    /// Stability pool gains claim
+    GainsClaim { depositor: [u8; 33], amount: u64 },
+    /// Anchor for a bundle of spells
+    SpellBundle { bundle_hash: [u8; 32], spell_count: u16 },
 }
 
 #[cfg(test)]
@@ -304,4 +378,24 @@ mod tests {
             _ => panic!("Wrong operation type"),
         }
     }
+
+    #[test]
+    fn test_redemption_op_return_roundtrip() {
+        let script = OpReturnBuilder::redemption(&[7u8; 32], 50_000, 125_000);
+
+        assert_eq!(
+            OpReturnBuilder::parse(&script),
+            Some(ProtocolOp::Redemption {
+                spell_hash: [7u8; 32],
+                zkusd_redeemed: 50_000,
+                collateral_paid: 125_000,
+            })
+        );
+
+        let script = OpReturnBuilder::spell_bundle(&[9u8; 32], 3);
+        assert_eq!(
+            OpReturnBuilder::parse(&script),
+            Some(ProtocolOp::SpellBundle { bundle_hash: [9u8; 32], spell_count: 3 })
+        );
+    }
 }
@@ -0,0 +1,566 @@
+//! SPV verification of Bitcoin transactions.
+//!
+//! Lets the protocol check that a collateral deposit is confirmed without
+//! trusting whoever submits it: the submitter supplies the transaction and a
+//! merkle branch, and we check the branch against a locally validated header
+//! chain and require the block to be buried at the configured depth.
+
+use bitcoin::block::Header;
+use bitcoin::hashes::Hash;
+use bitcoin::params::Params;
+use bitcoin::pow::{CompactTarget, Target};
+use bitcoin::{BlockHash, OutPoint, ScriptBuf, Transaction, TxMerkleNode, Txid};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::error::{Error, Result};
+use crate::utils::constants::DIFFICULTY_ADJUSTMENT_INTERVAL;
+
+/// Serialized size of a transaction that could be confused with an inner
+/// merkle node (CVE-2017-12842)
+const AMBIGUOUS_TX_SIZE: usize = 64;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// HEADER CHAIN
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Validated chain of block headers starting from a trusted checkpoint.
+///
+/// Each header must link to the tip, satisfy its own proof of work and keep
+/// the difficulty unchanged between adjustment boundaries. At a boundary the
+/// new target must be exactly what Bitcoin computes from the previous
+/// period's timespan (clamped to ×4/÷4). When the chain does not hold the
+/// start of that period (the checkpoint sits inside it) the new target is
+/// only bounded to within a factor of four of the previous one. With a
+/// `max_target` easier than mainnet's the chain follows regtest rules and
+/// the difficulty never changes.
+#[derive(Debug, Clone)]
+pub struct HeaderChain {
+    /// Headers by height
+    headers: BTreeMap<u32, Header>,
+    /// Height by block hash
+    heights: HashMap<BlockHash, u32>,
+    /// Easiest target accepted
+    max_target: Target,
+}
+
+impl HeaderChain {
+    /// Start a chain from a trusted checkpoint header
+    pub fn new(checkpoint_height: u32, checkpoint: Header) -> Self {
+        Self {
+            headers: BTreeMap::from([(checkpoint_height, checkpoint)]),
+            heights: HashMap::from([(checkpoint.block_hash(), checkpoint_height)]),
+            max_target: Target::MAX_ATTAINABLE_MAINNET,
+        }
+    }
+
+    /// Set the easiest target accepted (e.g. for test networks)
+    pub fn with_max_target(mut self, max_target: Target) -> Self {
+        self.max_target = max_target;
+        self
+    }
+
+    /// Height of the best header
+    pub fn tip_height(&self) -> u32 {
+        self.headers.keys().next_back().copied().unwrap_or_default()
+    }
+
+    /// Best header
+    pub fn tip(&self) -> &Header {
+        self.headers.values().next_back().expect("chain holds the checkpoint")
+    }
+
+    /// Height of the checkpoint
+    pub fn checkpoint_height(&self) -> u32 {
+        self.headers.keys().next().copied().unwrap_or_default()
+    }
+
+    /// Number of headers held
+    pub fn len(&self) -> usize {
+        self.headers.len()
+    }
+
+    /// Whether the chain holds only the checkpoint
+    pub fn is_empty(&self) -> bool {
+        self.headers.len() <= 1
+    }
+
+    /// Header at `height`
+    pub fn header_at(&self, height: u32) -> Option<&Header> {
+        self.headers.get(&height)
+    }
+
+    /// Height of a block, if it is on this chain
+    pub fn height_of(&self, hash: &BlockHash) -> Option<u32> {
+        self.heights.get(hash).copied()
+    }
+
+    /// Confirmations of a block (the tip has one), or 0 if it is not on this chain
+    pub fn confirmations(&self, hash: &BlockHash) -> u32 {
+        self.height_of(hash)
+            .map(|height| self.tip_height() - height + 1)
+            .unwrap_or(0)
+    }
+
+    /// Append a header on top of the tip, returning its height
+    pub fn push(&mut self, header: Header) -> Result<u32> {
+        let tip = *self.tip();
+        let height = self.tip_height() + 1;
+
+        if header.prev_blockhash != tip.block_hash() {
+            return Err(Error::InvalidSpvProof(format!(
+                "header {} does not extend tip {}",
+                header.block_hash(),
+                tip.block_hash()
+            )));
+        }
+
+        if height % DIFFICULTY_ADJUSTMENT_INTERVAL != 0 {
+            if header.bits != tip.bits {
+                return Err(Error::InvalidSpvProof(format!(
+                    "unexpected difficulty change at height {}",
+                    height
+                )));
+            }
+        } else {
+            self.check_retarget(height, &tip, &header)?;
+        }
+
+        let target = header.target();
+        if target > self.max_target {
+            return Err(Error::InvalidSpvProof(format!(
+                "target at height {} is easier than allowed",
+                height
+            )));
+        }
+
+        let hash = header
+            .validate_pow(target)
+            .map_err(|e| Error::InvalidSpvProof(format!("height {}: {}", height, e)))?;
+
+        self.headers.insert(height, header);
+        self.heights.insert(hash, height);
+        Ok(height)
+    }
+
+    /// Consensus parameters implied by `max_target`; anything easier than
+    /// mainnet's limit follows regtest and never retargets
+    fn params(&self) -> Params {
+        if self.max_target > Target::MAX_ATTAINABLE_MAINNET {
+            return Params::REGTEST;
+        }
+        let mut params = Params::MAINNET;
+        params.max_attainable_target = self.max_target;
+        params
+    }
+
+    /// Bits Bitcoin requires after a period running from `first` to `last`
+    fn retarget_bits(&self, first: &Header, last: &Header) -> CompactTarget {
+        let timespan = last.time.saturating_sub(first.time) as u64;
+        CompactTarget::from_next_work_required(last.bits, timespan, self.params())
+    }
+
+    /// Check the bits of the first header of a new difficulty period
+    fn check_retarget(&self, height: u32, tip: &Header, header: &Header) -> Result<()> {
+        let period_start = height - DIFFICULTY_ADJUSTMENT_INTERVAL;
+
+        let expected = match self.headers.get(&period_start) {
+            Some(first) => self.retarget_bits(first, tip),
+            None if self.params().no_pow_retargeting => tip.bits,
+            None => {
+                // Without the period start only the ×4/÷4 clamp can be enforced
+                let previous = tip.target();
+                let easiest = previous.max_transition_threshold(self.params());
+                let hardest = Target::from_compact(previous.min_transition_threshold().to_compact_lossy());
+                let target = header.target();
+                if target < hardest || target > easiest {
+                    return Err(Error::InvalidSpvProof(format!(
+                        "retarget at height {} changes difficulty by more than a factor of four",
+                        height
+                    )));
+                }
+                return Ok(());
+            }
+        };
+
+        if header.bits != expected {
+            return Err(Error::InvalidSpvProof(format!(
+                "bits {:#010x} at height {} do not match retarget {:#010x}",
+                header.bits.to_consensus(),
+                height,
+                expected.to_consensus()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Append several headers in order, returning the new tip height
+    pub fn extend<I: IntoIterator<Item = Header>>(&mut self, headers: I) -> Result<u32> {
+        for header in headers {
+            self.push(header)?;
+        }
+        Ok(self.tip_height())
+    }
+
+    /// Drop headers above `fork_height` after a reorg, returning how many were removed
+    pub fn rewind_to(&mut self, fork_height: u32) -> usize {
+        let fork_height = fork_height.max(self.checkpoint_height());
+        let removed = self.headers.split_off(&(fork_height + 1));
+        for header in removed.values() {
+            self.heights.remove(&header.block_hash());
+        }
+        removed.len()
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// MERKLE INCLUSION PROOF
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Merkle branch proving a transaction is included in a block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleInclusionProof {
+    /// Block containing the transaction
+    pub block_hash: BlockHash,
+    /// Position of the transaction in the block
+    pub index: u32,
+    /// Sibling hashes from the leaf up to the root
+    pub siblings: Vec<TxMerkleNode>,
+}
+
+impl MerkleInclusionProof {
+    /// Build the proof for `txids[index]` of a block
+    pub fn from_txids(block_hash: BlockHash, txids: &[Txid], index: u32) -> Option<Self> {
+        if index as usize >= txids.len() {
+            return None;
+        }
+
+        let mut level: Vec<TxMerkleNode> = txids.iter().map(|txid| leaf(*txid)).collect();
+        let mut position = index as usize;
+        let mut siblings = Vec::new();
+
+        while level.len() > 1 {
+            if level.len() % 2 == 1 {
+                level.push(*level.last().expect("level is non-empty"));
+            }
+            siblings.push(level[position ^ 1]);
+            level = level.chunks(2).map(|pair| parent(&pair[0], &pair[1])).collect();
+            position /= 2;
+        }
+
+        Some(Self { block_hash, index, siblings })
+    }
+
+    /// Merkle root implied by this branch for `txid`
+    pub fn compute_root(&self, txid: Txid) -> Result<TxMerkleNode> {
+        let depth = self.siblings.len();
+        if depth > 32 || (depth < 32 && (self.index >> depth) != 0) {
+            return Err(Error::InvalidSpvProof("index does not match branch depth".into()));
+        }
+
+        let mut node = leaf(txid);
+        for (depth, sibling) in self.siblings.iter().enumerate() {
+            node = if (self.index >> depth) & 1 == 0 {
+                parent(&node, sibling)
+            } else {
+                parent(sibling, &node)
+            };
+        }
+        Ok(node)
+    }
+
+    /// Check that `txid` is in the block, returning the block's confirmations
+    pub fn verify(&self, txid: Txid, chain: &HeaderChain) -> Result<u32> {
+        let height = chain.height_of(&self.block_hash).ok_or_else(|| {
+            Error::InvalidSpvProof(format!("block {} is not on the header chain", self.block_hash))
+        })?;
+        let header = chain.header_at(height).expect("indexed height has a header");
+
+        if self.compute_root(txid)? != header.merkle_root {
+            return Err(Error::InvalidSpvProof(format!(
+                "merkle branch for {} does not match block {}",
+                txid, self.block_hash
+            )));
+        }
+
+        Ok(chain.confirmations(&self.block_hash))
+    }
+}
+
+fn leaf(txid: Txid) -> TxMerkleNode {
+    TxMerkleNode::from_byte_array(txid.to_byte_array())
+}
+
+fn parent(left: &TxMerkleNode, right: &TxMerkleNode) -> TxMerkleNode {
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(left.as_byte_array());
+    data[32..].copy_from_slice(right.as_byte_array());
+    TxMerkleNode::hash(&data)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// DEPOSIT PROOF
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// A collateral deposit transaction together with its inclusion proof
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepositProof {
+    /// Deposit transaction
+    pub tx: Transaction,
+    /// Inclusion proof for `tx`
+    pub inclusion: MerkleInclusionProof,
+}
+
+/// Deposit that passed SPV verification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifiedDeposit {
+    /// Output holding the collateral
+    pub outpoint: OutPoint,
+    /// Collateral value (sats)
+    pub value_sats: u64,
+    /// Confirmations at verification time
+    pub confirmations: u32,
+}
+
+impl DepositProof {
+    /// Create a deposit proof
+    pub fn new(tx: Transaction, inclusion: MerkleInclusionProof) -> Self {
+        Self { tx, inclusion }
+    }
+
+    /// Verify the deposit pays at least `min_sats` to `collateral_script` and
+    /// is confirmed at least `min_confirmations` deep.
+    ///
+    /// The same deposit verifies every time; callers must record the returned
+    /// outpoint to stop it backing more than one spell.
+    pub fn verify(
+        &self,
+        chain: &HeaderChain,
+        collateral_script: &ScriptBuf,
+        min_sats: u64,
+        min_confirmations: u32,
+    ) -> Result<VerifiedDeposit> {
+        if bitcoin::consensus::serialize(&self.tx).len() == AMBIGUOUS_TX_SIZE {
+            return Err(Error::InvalidSpvProof("64-byte transactions are not accepted".into()));
+        }
+
+        let txid = self.tx.compute_txid();
+        let confirmations = self.inclusion.verify(txid, chain)?;
+        if confirmations < min_confirmations {
+            return Err(Error::InsufficientConfirmations {
+                required: min_confirmations,
+                actual: confirmations,
+            });
+        }
+
+        let (vout, output) = self
+            .tx
+            .output
+            .iter()
+            .enumerate()
+            .filter(|(_, out)| &out.script_pubkey == collateral_script)
+            .max_by_key(|(_, out)| out.value)
+            .ok_or_else(|| Error::InvalidSpvProof(format!("{} has no collateral output", txid)))?;
+
+        let value_sats = output.value.to_sat();
+        if value_sats < min_sats {
+            return Err(Error::InsufficientCollateral { required: min_sats, available: value_sats });
+        }
+
+        Ok(VerifiedDeposit {
+            outpoint: OutPoint::new(txid, vout as u32),
+            value_sats,
+            confirmations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::absolute::LockTime;
+    use bitcoin::block::Version;
+    use bitcoin::pow::CompactTarget;
+    use bitcoin::transaction::Version as TxVersion;
+    use bitcoin::{Amount, Block, TxOut};
+
+    const REGTEST_BITS: u32 = 0x207fffff;
+
+    fn deposit_tx(script: &ScriptBuf, value: u64, tag: u32) -> Transaction {
+        Transaction {
+            version: TxVersion::TWO,
+            lock_time: LockTime::from_consensus(tag),
+            input: vec![],
+            output: vec![
+                TxOut { value: Amount::from_sat(value), script_pubkey: script.clone() },
+                TxOut { value: Amount::from_sat(1_000), script_pubkey: ScriptBuf::new() },
+            ],
+        }
+    }
+
+    fn mine(prev: BlockHash, merkle_root: TxMerkleNode, time: u32) -> Header {
+        let mut header = Header {
+            version: Version::TWO,
+            prev_blockhash: prev,
+            merkle_root,
+            time,
+            bits: CompactTarget::from_consensus(REGTEST_BITS),
+            nonce: 0,
+        };
+        while header.validate_pow(header.target()).is_err() {
+            header.nonce += 1;
+        }
+        header
+    }
+
+    /// Chain whose block 101 contains `txs`, followed by `extra` empty blocks
+    fn chain_with(txs: Vec<Transaction>, extra: u32) -> (HeaderChain, BlockHash) {
+        let genesis = mine(BlockHash::all_zeros(), TxMerkleNode::all_zeros(), 0);
+        let mut chain = HeaderChain::new(100, genesis).with_max_target(Target::MAX_ATTAINABLE_REGTEST);
+
+        let mut block = Block { header: genesis, txdata: txs };
+        let root = block.compute_merkle_root().unwrap();
+        block.header = mine(genesis.block_hash(), root, 1);
+        chain.push(block.header).unwrap();
+
+        let mut prev = block.block_hash();
+        for i in 0..extra {
+            let header = mine(prev, TxMerkleNode::all_zeros(), 2 + i);
+            chain.push(header).unwrap();
+            prev = header.block_hash();
+        }
+
+        (chain, block.block_hash())
+    }
+
+    #[test]
+    fn test_merkle_branch_matches_block_root() {
+        let script = ScriptBuf::from_bytes(vec![0x51]);
+        let txs: Vec<Transaction> = (0..5).map(|i| deposit_tx(&script, 10_000, i)).collect();
+        let txids: Vec<Txid> = txs.iter().map(|tx| tx.compute_txid()).collect();
+        let (chain, block_hash) = chain_with(txs, 0);
+
+        for index in 0..txids.len() as u32 {
+            let proof = MerkleInclusionProof::from_txids(block_hash, &txids, index).unwrap();
+            assert_eq!(proof.verify(txids[index as usize], &chain).unwrap(), 1);
+        }
+
+        // Right branch, wrong transaction
+        let proof = MerkleInclusionProof::from_txids(block_hash, &txids, 2).unwrap();
+        assert!(proof.verify(txids[3], &chain).is_err());
+    }
+
+    #[test]
+    fn test_header_chain_rejects_bad_headers() {
+        let (mut chain, _) = chain_with(vec![deposit_tx(&ScriptBuf::new(), 1, 0)], 2);
+        assert_eq!(chain.tip_height(), 103);
+
+        // Does not link to the tip
+        let orphan = mine(BlockHash::all_zeros(), TxMerkleNode::all_zeros(), 9);
+        assert!(chain.push(orphan).is_err());
+
+        // Fails proof of work
+        let mut weak = mine(chain.tip().block_hash(), TxMerkleNode::all_zeros(), 9);
+        while weak.validate_pow(weak.target()).is_ok() {
+            weak.nonce += 1;
+        }
+        assert!(chain.push(weak).is_err());
+
+        // Easier than mainnet limits
+        let mut strict = HeaderChain::new(0, *chain.header_at(100).unwrap());
+        assert!(strict.push(*chain.header_at(101).unwrap()).is_err());
+
+        assert_eq!(chain.rewind_to(101), 2);
+        assert_eq!(chain.tip_height(), 101);
+    }
+
+    fn unmined(prev: BlockHash, bits: u32, time: u32) -> Header {
+        Header {
+            version: Version::TWO,
+            prev_blockhash: prev,
+            merkle_root: TxMerkleNode::all_zeros(),
+            time,
+            bits: CompactTarget::from_consensus(bits),
+            nonce: 0,
+        }
+    }
+
+    #[test]
+    fn test_retarget_follows_previous_period() {
+        const MAINNET_BITS: u32 = 0x1d00ffff;
+        let chain = HeaderChain::new(0, unmined(BlockHash::all_zeros(), MAINNET_BITS, 0));
+        let first = unmined(BlockHash::all_zeros(), MAINNET_BITS, 1_000);
+        let after = |timespan: u32| {
+            let last = unmined(BlockHash::all_zeros(), MAINNET_BITS, 1_000 + timespan);
+            chain.retarget_bits(&first, &last).to_consensus()
+        };
+
+        // On schedule, twice as fast, clamped at ×4 harder, capped at the limit
+        assert_eq!(after(1_209_600), MAINNET_BITS);
+        assert_eq!(after(604_800), 0x1c7fff80);
+        assert_eq!(after(0), after(302_400));
+        assert_eq!(after(0), 0x1c3fffc0);
+        assert_eq!(after(10 * 1_209_600), MAINNET_BITS);
+
+        // Checkpoint inside the period: only the factor-of-four clamp applies
+        let tip = unmined(BlockHash::all_zeros(), 0x1c3fffc0, 0);
+        let mut partial = HeaderChain::new(2015, tip);
+        let steep = unmined(tip.block_hash(), 0x1c00ffff, 1);
+        assert!(matches!(
+            partial.push(steep),
+            Err(Error::InvalidSpvProof(msg)) if msg.contains("factor of four")
+        ));
+        let easier = unmined(tip.block_hash(), 0x1c7fff80, 1);
+        assert!(matches!(
+            partial.push(easier),
+            Err(Error::InvalidSpvProof(msg)) if !msg.contains("factor of four")
+        ));
+    }
+
+    #[test]
+    fn test_regtest_chain_never_retargets() {
+        let genesis = mine(BlockHash::all_zeros(), TxMerkleNode::all_zeros(), 0);
+        let mut chain = HeaderChain::new(0, genesis).with_max_target(Target::MAX_ATTAINABLE_REGTEST);
+        for i in 1..DIFFICULTY_ADJUSTMENT_INTERVAL {
+            chain.push(mine(chain.tip().block_hash(), TxMerkleNode::all_zeros(), i)).unwrap();
+        }
+
+        let mut harder = mine(chain.tip().block_hash(), TxMerkleNode::all_zeros(), 0);
+        harder.bits = CompactTarget::from_consensus(0x201fffff);
+        assert!(chain.push(harder).is_err());
+
+        let boundary = mine(chain.tip().block_hash(), TxMerkleNode::all_zeros(), 0);
+        assert_eq!(chain.push(boundary).unwrap(), DIFFICULTY_ADJUSTMENT_INTERVAL);
+    }
+
+    #[test]
+    fn test_deposit_proof_depth_and_value() {
+        let script = ScriptBuf::from_bytes(vec![0x00, 0x14, 0xab]);
+        let tx = deposit_tx(&script, 50_000, 7);
+        let txid = tx.compute_txid();
+        let other = deposit_tx(&script, 1, 1);
+        let txids = [other.compute_txid(), txid];
+        let (mut chain, block_hash) = chain_with(vec![other, tx.clone()], 4);
+
+        let inclusion = MerkleInclusionProof::from_txids(block_hash, &txids, 1).unwrap();
+        let proof = DepositProof::new(tx, inclusion);
+
+        let verified = proof.verify(&chain, &script, 50_000, 5).unwrap();
+        assert_eq!(verified.outpoint, OutPoint::new(txid, 0));
+        assert_eq!(verified.confirmations, 5);
+
+        assert!(matches!(
+            proof.verify(&chain, &script, 50_000, 6),
+            Err(Error::InsufficientConfirmations { required: 6, actual: 5 })
+        ));
+        assert!(matches!(
+            proof.verify(&chain, &script, 60_000, 1),
+            Err(Error::InsufficientCollateral { .. })
+        ));
+        assert!(proof.verify(&chain, &ScriptBuf::from_bytes(vec![0x52]), 1, 1).is_err());
+
+        // Reorged out
+        chain.rewind_to(100);
+        assert!(proof.verify(&chain, &script, 50_000, 1).is_err());
+    }
+}
@@ -27,6 +27,8 @@ pub mod vsize {
     pub const P2TR_KEYPATH_INPUT: u64 = 58;
     /// Standard output vsize
     pub const P2WPKH_OUTPUT: u64 = 31;
+    /// P2TR output vsize
+    pub const P2TR_OUTPUT: u64 = 43;
     /// OP_RETURN output vsize (estimated)
     pub const OP_RETURN_OUTPUT: u64 = 43;
     /// Transaction overhead
@@ -235,6 +237,38 @@ impl TxTemplate {
             output: outputs,
         })
     }
+
+    /// Build the payout skeleton without funding it.
+    ///
+    /// Only value and OP_RETURN outputs are emitted; change and fees are left
+    /// to whoever attaches the collateral inputs before signing.
+    pub fn build_unfunded(&self) -> Transaction {
+        let inputs: Vec<TxIn> = self.inputs.iter().map(|i| TxIn {
+            previous_output: i.utxo.outpoint(),
+            script_sig: ScriptBuf::new(),
+            sequence: i.sequence,
+            witness: Witness::default(),
+        }).collect();
+
+        let outputs: Vec<TxOut> = self.outputs.iter().filter_map(|o| match o {
+            TxOutput::Value { amount, script_pubkey } => Some(TxOut {
+                value: Amount::from_sat(*amount),
+                script_pubkey: script_pubkey.clone(),
+            }),
+            TxOutput::OpReturn { data } => Some(TxOut {
+                value: Amount::ZERO,
+                script_pubkey: data.clone(),
+            }),
+            TxOutput::Change { .. } => None,
+        }).collect();
+
+        Transaction {
+            version: Version::TWO,
+            lock_time: self.lock_time,
+            input: inputs,
+            output: outputs,
+        }
+    }
 }
 
 impl Default for TxTemplate {
@@ -483,4 +517,17 @@ mod tests {
         assert!(vsize > 0);
         assert!(vsize < 500); // Reasonable range for 1-in, 1-out
     }
+
+    #[test]
+    fn test_build_unfunded_skips_change() {
+        let mut template = TxTemplate::new();
+        template.add_output(50_000, ScriptBuf::new());
+        template.add_change(ScriptBuf::new());
+        template.add_op_return(ScriptBuf::new());
+
+        let tx = template.build_unfunded();
+        assert!(tx.input.is_empty());
+        assert_eq!(tx.output.len(), 2);
+        assert_eq!(tx.output[0].value, Amount::from_sat(50_000));
+    }
 }
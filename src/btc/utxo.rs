@@ -1,12 +1,17 @@
 //! UTXO (Unspent Transaction Output) management.
 //!
-//! This module handles tracking and selection of UTXOs for transaction building.
+//! This module handles tracking and selection of UTXOs for transaction building,
+//! including confirmation tracking, chain reorganizations, persistence to a
+//! storage backend and reconciliation against a Bitcoin node.
 
-use bitcoin::{Amount, OutPoint, ScriptBuf, TxOut, Txid};
+use bitcoin::{Amount, BlockHash, OutPoint, ScriptBuf, TxOut, Txid};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+use crate::core::vault::{CollateralAmount, ReserveUtxo};
 use crate::error::{Error, Result};
+use crate::utils::crypto::CDPId;
+use crate::storage::backend::{make_key, prefixes, StorageBackend, TypedStore};
 
 /// Represents an unspent transaction output
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,14 +59,24 @@ impl Utxo {
         }
     }
 
-    /// Check if UTXO is confirmed with sufficient depth
-    pub fn is_confirmed(&self, current_height: u32, min_confirmations: u32) -> bool {
+    /// Number of confirmations at `current_height` (the confirming block counts as one)
+    pub fn confirmations(&self, current_height: u32) -> u32 {
         match self.confirmation_height {
-            Some(height) => current_height.saturating_sub(height) >= min_confirmations,
-            None => false,
+            Some(height) if height <= current_height => current_height - height + 1,
+            _ => 0,
         }
     }
 
+    /// Check if UTXO is confirmed with sufficient depth
+    pub fn is_confirmed(&self, current_height: u32, min_confirmations: u32) -> bool {
+        self.confirmation_height.is_some() && self.confirmations(current_height) >= min_confirmations
+    }
+
+    /// Mark as unconfirmed (e.g. its block was orphaned)
+    pub fn unconfirm(&mut self) {
+        self.confirmation_height = None;
+    }
+
     /// Check if UTXO can be spent
     pub fn is_spendable(&self, current_height: u32, min_confirmations: u32) -> bool {
         !self.locked && self.is_confirmed(current_height, min_confirmations)
@@ -81,6 +96,16 @@ impl Utxo {
     pub fn unlock(&mut self) {
         self.locked = false;
     }
+
+    /// Entry for a proof of reserves
+    pub fn to_reserve(&self) -> ReserveUtxo {
+        ReserveUtxo {
+            outpoint: self.outpoint().to_string(),
+            value: CollateralAmount::from_sats(self.value),
+            cdp_id: self.cdp_id.map(CDPId::new),
+            confirmation_height: self.confirmation_height,
+        }
+    }
 }
 
 /// UTXO selection strategy
@@ -103,6 +128,10 @@ pub struct UtxoSet {
     utxos: HashMap<OutPoint, Utxo>,
     /// UTXOs indexed by CDP ID
     cdp_utxos: HashMap<[u8; 32], Vec<OutPoint>>,
+    /// Recently spent UTXOs with the height they were spent at (kept for reorgs)
+    spent: HashMap<OutPoint, (Utxo, u32)>,
+    /// Hashes of connected blocks by height
+    blocks: BTreeMap<u32, BlockHash>,
 }
 
 impl UtxoSet {
@@ -114,6 +143,9 @@ impl UtxoSet {
     /// Add a UTXO to the set
     pub fn add(&mut self, utxo: Utxo) {
         let outpoint = utxo.outpoint();
+        if self.utxos.contains_key(&outpoint) {
+            self.remove(&outpoint);
+        }
 
         if let Some(cdp_id) = utxo.cdp_id {
             self.cdp_utxos.entry(cdp_id).or_default().push(outpoint);
@@ -178,6 +210,38 @@ impl UtxoSet {
         self.get_cdp_utxos(cdp_id).iter().map(|u| u.value).sum()
     }
 
+    /// Attribute a UTXO to a CDP, or release it with `None`
+    pub fn attribute(&mut self, outpoint: &OutPoint, cdp_id: Option<[u8; 32]>) -> Result<()> {
+        let utxo = self.utxos.get_mut(outpoint).ok_or_else(|| Error::InvalidParameter {
+            name: "outpoint".into(),
+            reason: format!("{} is not in the UTXO set", outpoint),
+        })?;
+        let previous = std::mem::replace(&mut utxo.cdp_id, cdp_id);
+
+        if let Some(previous) = previous {
+            if let Some(cdp_utxos) = self.cdp_utxos.get_mut(&previous) {
+                cdp_utxos.retain(|op| op != outpoint);
+                if cdp_utxos.is_empty() {
+                    self.cdp_utxos.remove(&previous);
+                }
+            }
+        }
+        if let Some(cdp_id) = cdp_id {
+            self.cdp_utxos.entry(cdp_id).or_default().push(*outpoint);
+        }
+        Ok(())
+    }
+
+    /// Outputs with at least `min_confirmations` at `current_height`, as
+    /// entries for a proof of reserves
+    pub fn reserves(&self, current_height: u32, min_confirmations: u32) -> Vec<ReserveUtxo> {
+        self.utxos
+            .values()
+            .filter(|u| u.is_confirmed(current_height, min_confirmations))
+            .map(Utxo::to_reserve)
+            .collect()
+    }
+
     /// Select UTXOs to cover a target amount
     pub fn select(
         &self,
@@ -276,6 +340,259 @@ impl UtxoSet {
     pub fn is_empty(&self) -> bool {
         self.utxos.is_empty()
     }
+
+    /// Iterate over all UTXOs
+    pub fn iter(&self) -> impl Iterator<Item = &Utxo> {
+        self.utxos.values()
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // CHAIN TRACKING
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Height of the last connected block
+    pub fn tip_height(&self) -> Option<u32> {
+        self.blocks.keys().next_back().copied()
+    }
+
+    /// Hash of the connected block at `height`
+    pub fn block_hash(&self, height: u32) -> Option<&BlockHash> {
+        self.blocks.get(&height)
+    }
+
+    /// Mark a UTXO as spent at `height`, keeping it around in case of a reorg
+    pub fn spend(&mut self, outpoint: &OutPoint, height: u32) -> Option<Utxo> {
+        let utxo = self.remove(outpoint)?;
+        self.spent.insert(*outpoint, (utxo.clone(), height));
+        Some(utxo)
+    }
+
+    /// Number of spent UTXOs retained for reorg handling
+    pub fn spent_len(&self) -> usize {
+        self.spent.len()
+    }
+
+    /// Connect a block: confirm created outputs and record spends.
+    ///
+    /// If a different block is already connected at `height`, the chain is
+    /// first rewound to `height - 1` and the resulting report is returned.
+    pub fn connect_block(
+        &mut self,
+        height: u32,
+        hash: BlockHash,
+        created: Vec<Utxo>,
+        spent: &[OutPoint],
+    ) -> Option<ReorgReport> {
+        let reorg = match self.blocks.get(&height) {
+            Some(existing) if *existing != hash => Some(self.disconnect_above(height.saturating_sub(1))),
+            _ => None,
+        };
+
+        for mut utxo in created {
+            utxo.confirm(height);
+            self.add(utxo);
+        }
+        for outpoint in spent {
+            self.spend(outpoint, height);
+        }
+        self.blocks.insert(height, hash);
+
+        reorg
+    }
+
+    /// Rewind all blocks above `fork_height`.
+    ///
+    /// Outputs confirmed in orphaned blocks become unconfirmed and are unlocked;
+    /// outputs spent in orphaned blocks are restored as spendable.
+    pub fn disconnect_above(&mut self, fork_height: u32) -> ReorgReport {
+        let mut report = ReorgReport {
+            fork_height,
+            ..Default::default()
+        };
+
+        let orphaned: Vec<u32> = self.blocks.range(fork_height + 1..).map(|(h, _)| *h).collect();
+        for height in orphaned {
+            self.blocks.remove(&height);
+            report.blocks_disconnected += 1;
+        }
+
+        for utxo in self.utxos.values_mut() {
+            if matches!(utxo.confirmation_height, Some(h) if h > fork_height) {
+                utxo.unconfirm();
+                utxo.unlock();
+                report.unconfirmed.push(utxo.outpoint());
+            }
+        }
+
+        let restored: Vec<OutPoint> = self
+            .spent
+            .iter()
+            .filter(|(_, (_, height))| *height > fork_height)
+            .map(|(op, _)| *op)
+            .collect();
+        for outpoint in restored {
+            if let Some((mut utxo, _)) = self.spent.remove(&outpoint) {
+                utxo.unlock();
+                if matches!(utxo.confirmation_height, Some(h) if h > fork_height) {
+                    utxo.unconfirm();
+                }
+                self.add(utxo);
+                report.restored.push(outpoint);
+            }
+        }
+
+        report
+    }
+
+    /// Drop spend and block history older than `keep_blocks` below the tip
+    pub fn prune_history(&mut self, keep_blocks: u32) {
+        let Some(tip) = self.tip_height() else { return };
+        let cutoff = tip.saturating_sub(keep_blocks);
+        self.blocks.retain(|height, _| *height >= cutoff);
+        self.spent.retain(|_, (_, height)| *height >= cutoff);
+    }
+
+    /// Reconcile the set against a Bitcoin node.
+    ///
+    /// UTXOs the node no longer reports as unspent are dropped, and
+    /// confirmation heights are updated to match the node's view.
+    pub fn reconcile<S: UtxoSource + ?Sized>(&mut self, source: &S) -> Result<ReconcileReport> {
+        let mut report = ReconcileReport::default();
+        let outpoints: Vec<OutPoint> = self.utxos.keys().copied().collect();
+
+        for outpoint in outpoints {
+            match source.utxo_status(&outpoint)? {
+                None => {
+                    self.remove(&outpoint);
+                    report.removed.push(outpoint);
+                }
+                Some(status) => {
+                    let Some(utxo) = self.utxos.get_mut(&outpoint) else { continue };
+                    if utxo.confirmation_height != status.confirmation_height {
+                        utxo.confirmation_height = status.confirmation_height;
+                        report.updated.push(outpoint);
+                    }
+                }
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Outcome of rewinding the chain
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReorgReport {
+    /// Last block kept
+    pub fork_height: u32,
+    /// Number of orphaned blocks removed
+    pub blocks_disconnected: u32,
+    /// Outputs whose confirming block was orphaned
+    pub unconfirmed: Vec<OutPoint>,
+    /// Outputs whose spending block was orphaned
+    pub restored: Vec<OutPoint>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// NODE RECONCILIATION
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Node-side view of an unspent output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UtxoStatus {
+    /// Height of the confirming block (None if in mempool)
+    pub confirmation_height: Option<u32>,
+}
+
+/// Source of truth for UTXO state, typically a connected Bitcoin node
+pub trait UtxoSource {
+    /// Status of an output, or None if it is spent or unknown
+    fn utxo_status(&self, outpoint: &OutPoint) -> Result<Option<UtxoStatus>>;
+}
+
+/// Outcome of a reconciliation pass
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReconcileReport {
+    /// Outputs no longer unspent according to the node
+    pub removed: Vec<OutPoint>,
+    /// Outputs whose confirmation height changed
+    pub updated: Vec<OutPoint>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// PERSISTENCE
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Persists a [`UtxoSet`] to a storage backend
+pub struct UtxoStore<B: StorageBackend> {
+    store: TypedStore<B>,
+}
+
+impl<B: StorageBackend> UtxoStore<B> {
+    /// Create a UTXO store on top of a backend
+    pub fn new(backend: B) -> Self {
+        Self {
+            store: TypedStore::new(backend),
+        }
+    }
+
+    /// Write the full set, replacing anything stored previously
+    pub fn save(&self, set: &UtxoSet) -> Result<()> {
+        self.clear()?;
+
+        for utxo in set.utxos.values() {
+            self.store.set(&outpoint_key(prefixes::UTXO, &utxo.outpoint()), utxo)?;
+        }
+        for (outpoint, entry) in &set.spent {
+            self.store.set(&outpoint_key(prefixes::UTXO_SPENT, outpoint), entry)?;
+        }
+        for (height, hash) in &set.blocks {
+            self.store.set(&make_key(prefixes::UTXO_BLOCK, &height.to_be_bytes()), hash)?;
+        }
+
+        self.store.flush()
+    }
+
+    /// Load the set from storage
+    pub fn load(&self) -> Result<UtxoSet> {
+        let mut set = UtxoSet::new();
+
+        for key in self.store.list_prefix(prefixes::UTXO)? {
+            if let Some(utxo) = self.store.get::<Utxo>(&key)? {
+                set.add(utxo);
+            }
+        }
+        for key in self.store.list_prefix(prefixes::UTXO_SPENT)? {
+            if let Some(entry) = self.store.get::<(Utxo, u32)>(&key)? {
+                set.spent.insert(entry.0.outpoint(), entry);
+            }
+        }
+        for key in self.store.list_prefix(prefixes::UTXO_BLOCK)? {
+            let height = key[prefixes::UTXO_BLOCK.len()..]
+                .try_into()
+                .map(u32::from_be_bytes)
+                .map_err(|_| Error::Deserialization("Invalid UTXO block key".into()))?;
+            if let Some(hash) = self.store.get::<BlockHash>(&key)? {
+                set.blocks.insert(height, hash);
+            }
+        }
+
+        Ok(set)
+    }
+
+    /// Remove all persisted UTXO data
+    pub fn clear(&self) -> Result<()> {
+        for prefix in [prefixes::UTXO, prefixes::UTXO_SPENT, prefixes::UTXO_BLOCK] {
+            for key in self.store.list_prefix(prefix)? {
+                self.store.delete(&key)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn outpoint_key(prefix: &[u8], outpoint: &OutPoint) -> Vec<u8> {
+    make_key(prefix, &bitcoin::consensus::serialize(outpoint))
 }
 
 #[cfg(test)]
@@ -302,6 +619,118 @@ mod tests {
         assert!(utxo.confirmation_height.is_none());
     }
 
+    #[test]
+    fn test_confirmations() {
+        let mut utxo = Utxo::new(test_txid(), 0, 100_000, ScriptBuf::new());
+        assert_eq!(utxo.confirmations(100), 0);
+
+        utxo.confirm(100);
+        assert_eq!(utxo.confirmations(100), 1);
+        assert_eq!(utxo.confirmations(105), 6);
+        assert!(utxo.is_confirmed(105, 6));
+        assert!(!utxo.is_confirmed(104, 6));
+    }
+
+    fn block_hash(n: u8) -> BlockHash {
+        BlockHash::from_byte_array([n; 32])
+    }
+
+    #[test]
+    fn test_reorg_restores_and_unconfirms() {
+        let mut set = UtxoSet::new();
+        let funding = Utxo::new(test_txid(), 0, 50_000, ScriptBuf::new());
+        set.connect_block(100, block_hash(1), vec![funding.clone()], &[]);
+
+        // Block 101 spends the funding output and creates a new one
+        let created = Utxo::new(test_txid(), 1, 40_000, ScriptBuf::new());
+        set.connect_block(101, block_hash(2), vec![created.clone()], &[funding.outpoint()]);
+        assert!(set.get(&funding.outpoint()).is_none());
+        assert_eq!(set.tip_height(), Some(101));
+
+        // A competing block at 101 orphans the previous one
+        let report = set.connect_block(101, block_hash(3), vec![], &[]).unwrap();
+        assert_eq!(report.blocks_disconnected, 1);
+        assert_eq!(report.restored, vec![funding.outpoint()]);
+        assert_eq!(report.unconfirmed, vec![created.outpoint()]);
+
+        assert!(set.get(&funding.outpoint()).unwrap().is_spendable(101, 1));
+        assert!(set.get(&created.outpoint()).unwrap().confirmation_height.is_none());
+        assert_eq!(set.block_hash(101), Some(&block_hash(3)));
+    }
+
+    #[test]
+    fn test_attribution() {
+        let cdp = [7u8; 32];
+        let mut set = UtxoSet::new();
+        let first = Utxo::new(test_txid(), 0, 60_000, ScriptBuf::new());
+        let second = Utxo::new(test_txid(), 1, 40_000, ScriptBuf::new());
+        set.connect_block(100, block_hash(1), vec![first.clone(), second.clone()], &[]);
+        set.add(Utxo::new(test_txid(), 2, 10_000, ScriptBuf::new()));
+
+        set.attribute(&first.outpoint(), Some(cdp)).unwrap();
+        set.attribute(&second.outpoint(), Some(cdp)).unwrap();
+        assert_eq!(set.cdp_collateral(&cdp), 100_000);
+
+        set.attribute(&second.outpoint(), Some([8u8; 32])).unwrap();
+        assert_eq!(set.cdp_collateral(&cdp), 60_000);
+        assert_eq!(set.cdp_collateral(&[8u8; 32]), 40_000);
+        assert!(set.attribute(&OutPoint { txid: test_txid(), vout: 9 }, None).is_err());
+
+        // Unconfirmed outputs are left out of the reserves
+        let mut reserves = set.reserves(100, 1);
+        reserves.sort_by(|a, b| a.outpoint.cmp(&b.outpoint));
+        assert_eq!(reserves.len(), 2);
+        assert_eq!(reserves[0].cdp_id, Some(CDPId::new(cdp)));
+        assert_eq!(reserves[0].outpoint, first.outpoint().to_string());
+    }
+
+    #[test]
+    fn test_utxo_store_roundtrip() {
+        use crate::storage::backend::InMemoryStore;
+
+        let mut set = UtxoSet::new();
+        let funding = Utxo::new(test_txid(), 0, 50_000, ScriptBuf::new());
+        let other = Utxo::new(test_txid(), 1, 70_000, ScriptBuf::new());
+        set.connect_block(100, block_hash(1), vec![funding.clone(), other.clone()], &[]);
+        set.spend(&funding.outpoint(), 101);
+
+        let store = UtxoStore::new(InMemoryStore::new());
+        store.save(&set).unwrap();
+        let loaded = store.load().unwrap();
+
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.get(&other.outpoint()).unwrap().confirmation_height, Some(100));
+        assert_eq!(loaded.spent_len(), 1);
+        assert_eq!(loaded.tip_height(), Some(100));
+    }
+
+    #[test]
+    fn test_reconcile_against_node() {
+        struct MockNode(HashMap<OutPoint, UtxoStatus>);
+
+        impl UtxoSource for MockNode {
+            fn utxo_status(&self, outpoint: &OutPoint) -> Result<Option<UtxoStatus>> {
+                Ok(self.0.get(outpoint).copied())
+            }
+        }
+
+        let mut set = UtxoSet::new();
+        let kept = Utxo::new(test_txid(), 0, 50_000, ScriptBuf::new());
+        let gone = Utxo::new(test_txid(), 1, 70_000, ScriptBuf::new());
+        set.add(kept.clone());
+        set.add(gone.clone());
+
+        let node = MockNode(HashMap::from([(
+            kept.outpoint(),
+            UtxoStatus { confirmation_height: Some(200) },
+        )]));
+
+        let report = set.reconcile(&node).unwrap();
+        assert_eq!(report.removed, vec![gone.outpoint()]);
+        assert_eq!(report.updated, vec![kept.outpoint()]);
+        assert_eq!(set.get(&kept.outpoint()).unwrap().confirmation_height, Some(200));
+    }
+
     #[test]
     fn test_utxo_set_selection() {
         let mut set = UtxoSet::new();
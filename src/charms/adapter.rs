@@ -2,19 +2,46 @@
 //!
 //! This module provides the bridge that allows zkUSD to operate as a
 //! Charms-compatible token on BitcoinOS.
+//!
+//! With a [`ProverManager`] attached, only redemption spells carry a ZK
+//! proof. Gains claims and CDP charm mints and transfers have no circuit
+//! and return results without one. CDP spells (open, deposit, withdraw,
+//! mint, repay, close) and liquidations are not executed here at all; they
+//! go through the protocol state machine, so their circuits are not wired
+//! to the adapter yet.
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::core::cdp::CDPManager;
+use bitcoin::ScriptBuf;
+
+use crate::btc::scripts::OpReturnBuilder;
+use crate::btc::tx_builder::TxTemplate;
+use crate::core::cdp::{CDPId, CDPManager};
 use crate::core::config::ProtocolConfig;
-use crate::core::token::ZkUSD;
-use crate::core::vault::Vault;
+use crate::core::token::{TokenAmount, ZkUSD};
+use crate::core::vault::{CollateralAmount, Vault};
 use crate::error::{Error, Result};
 use crate::liquidation::stability_pool::StabilityPool;
+use crate::protocol::events::{EventLog, GainsClaimedEvent, ProtocolEvent, RedemptionEvent};
+use crate::utils::codec;
+use crate::utils::constants::{MAX_SPELL_DATA_SIZE, SATS_PER_BTC};
 use crate::utils::crypto::{Hash, PublicKey};
+use crate::utils::math::{calculate_fee_bps, safe_mul_div};
+use crate::zkp::{
+    CDPRedemptionData, MerkleProof, ProofBatch, ProofInputs, ProverManager, RedemptionPrivateInputs,
+    RedemptionPublicInputs, ZKProof,
+};
+use crate::charms::cdp_nft::CdpNftRegistry;
+use crate::charms::commitment::{
+    allowance_key, balance_key, cdp_key, cdp_nft_key, deposit_key, global_key, StateCommitment,
+};
+use crate::charms::fees::{FeeAssessment, ProtocolFeeAccount, SpellFeeConfig};
 use crate::charms::token::{CharmId, ZkUSDCharm};
-use crate::charms::spells::{CharmSpell, SpellResult, ZkUSDSpellType};
+use crate::charms::spells::{
+    BundleResult, CharmSpell, ClaimGainsParams, MintCdpNftParams, RedeemParams, SpellBundle, SpellResult,
+    TransferCdpNftParams, ZkUSDSpellType,
+};
 use crate::charms::metadata::{CharmMetadata, MetadataRegistry};
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -89,7 +116,10 @@ impl CharmsAdapter {
         }
 
         // Validate spell
-        if let Err(e) = spell.validate(self.block_height) {
+        if let Err(e) = spell
+            .validate(self.block_height)
+            .and_then(|_| self.metadata.verify_spell(&spell, &CharmId::ZKUSD))
+        {
             return SpellResult::failure(spell_hash, e.to_string(), self.block_height);
         }
 
@@ -140,8 +170,7 @@ impl CharmsAdapter {
             amount: u64,
         }
 
-        let params: ApproveParams = bincode::deserialize(&spell.data)
-            .map_err(|e| Error::Serialization(e.to_string()))?;
+        let params: ApproveParams = codec::decode_bounded(&spell.data, MAX_SPELL_DATA_SIZE)?;
 
         let receipt = self.token.approve(
             spell.caster,
@@ -187,6 +216,41 @@ pub struct ProtocolCharmsAdapter {
     pub stability_pool: StabilityPool,
     /// Current BTC price (cents)
     pub btc_price: u64,
+    /// Current block timestamp
+    pub timestamp: u64,
+    /// Protocol events emitted by executed spells
+    pub events: EventLog,
+    /// Prover used to attach ZK proofs to spell results
+    prover: Option<ProverManager>,
+    /// Fee schedules and dust threshold for BTC payouts
+    pub fees: SpellFeeConfig,
+    /// Protocol fee collection account
+    pub fee_account: ProtocolFeeAccount,
+    /// Anchor of the bundle being executed, if any
+    bundle: Option<BundleAnchor>,
+    /// Cached commitment to CDP, token and pool state
+    commitment: StateCommitment,
+    /// CDP charms, when CDP control follows charm ownership
+    cdp_nfts: Option<CdpNftRegistry>,
+}
+
+/// Shared outputs of the spells in a bundle
+#[derive(Default)]
+struct BundleAnchor {
+    /// Payout outputs of every bundled spell
+    template: TxTemplate,
+    /// Protocol fees charged, accrued once the bundle commits (sats)
+    fee_sats: u64,
+    /// Proofs generated by bundled spells
+    proofs: Vec<ZKProof>,
+}
+
+/// Output of a protocol spell handler
+struct SpellOutcome {
+    /// Serialized receipt returned to the caster
+    data: Vec<u8>,
+    /// Proof of the state transition, when a prover is configured
+    proof: Option<ZKProof>,
 }
 
 impl ProtocolCharmsAdapter {
@@ -198,6 +262,56 @@ impl ProtocolCharmsAdapter {
             vault: Vault::new(),
             stability_pool: StabilityPool::new(),
             btc_price,
+            timestamp: 0,
+            events: EventLog::new(),
+            prover: None,
+            fees: SpellFeeConfig::default(),
+            fee_account: ProtocolFeeAccount::default(),
+            bundle: None,
+            commitment: StateCommitment::new(),
+            cdp_nfts: None,
+        }
+    }
+
+    /// Configure spell fees, paying collected fees to `fee_script`
+    pub fn with_fees(mut self, fees: SpellFeeConfig, fee_script: ScriptBuf) -> Self {
+        self.fees = fees;
+        self.fee_account = ProtocolFeeAccount::new(fee_script);
+        self
+    }
+
+    /// Attach a prover so redemption spells carry a ZK proof
+    pub fn with_prover(mut self, prover: ProverManager) -> Self {
+        self.prover = Some(prover);
+        self
+    }
+
+    /// Get the configured prover
+    pub fn prover(&self) -> Option<&ProverManager> {
+        self.prover.as_ref()
+    }
+
+    /// Represent CDPs as Charm NFTs whose holder controls the CDP
+    pub fn with_cdp_nfts(mut self) -> Self {
+        self.cdp_nfts = Some(CdpNftRegistry::new());
+        self
+    }
+
+    /// CDP charms, if NFT mode is enabled
+    pub fn cdp_nfts(&self) -> Option<&CdpNftRegistry> {
+        self.cdp_nfts.as_ref()
+    }
+
+    /// Check that `caster` controls the CDP.
+    ///
+    /// In NFT mode a CDP with a charm is controlled by the charm's holder;
+    /// otherwise, and for CDPs whose charm has not been minted yet, by the
+    /// CDP's owner keys.
+    pub fn authorize_cdp(&self, cdp_id: &CDPId, caster: &PublicKey) -> Result<()> {
+        let cdp = self.cdp_manager.get(cdp_id).ok_or_else(|| Error::CDPNotFound(cdp_id.to_hex()))?;
+        match &self.cdp_nfts {
+            Some(nfts) if nfts.holder_of(cdp_id).is_some() => nfts.verify_holder(cdp_id, caster),
+            _ => cdp.verify_owner(caster),
         }
     }
 
@@ -211,6 +325,341 @@ impl ProtocolCharmsAdapter {
         self.btc_price = price;
     }
 
+    /// Update block timestamp
+    pub fn set_timestamp(&mut self, timestamp: u64) {
+        self.timestamp = timestamp;
+    }
+
+    /// Execute a Charm spell, including protocol-level spells
+    ///
+    /// Successful results carry the state root after the spell.
+    pub fn execute_spell(&mut self, spell: CharmSpell) -> SpellResult {
+        let result = match spell.spell_type {
+            ZkUSDSpellType::Redeem => self.execute_protocol_spell(spell, Self::execute_redeem),
+            ZkUSDSpellType::ClaimGains => self.execute_protocol_spell(spell, Self::execute_claim_gains),
+            ZkUSDSpellType::MintCdpNft => self.execute_protocol_spell(spell, Self::execute_mint_cdp_nft),
+            ZkUSDSpellType::TransferCdpNft => self.execute_protocol_spell(spell, Self::execute_transfer_cdp_nft),
+            _ => self.adapter.execute_spell(spell),
+        };
+
+        if result.success {
+            let state_root = self.state_root();
+            result.with_state_root(state_root)
+        } else {
+            result
+        }
+    }
+
+    /// Merkle root over all CDPs, balances, allowances and stability pool
+    /// deposits, plus the token supply and pool and vault totals (see
+    /// [`StateCommitment`]). Only leaves whose state changed since the last
+    /// call are rehashed into the cached tree.
+    pub fn state_root(&mut self) -> Hash {
+        let entries = self.state_entries();
+        self.commitment.sync(entries);
+        self.commitment.root()
+    }
+
+    /// Canonical (key, value) encoding of the committed state
+    fn state_entries(&self) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let token = self.adapter.token.inner();
+        let mut entries = Vec::new();
+
+        for cdp in self.cdp_manager.all_cdps() {
+            entries.push((cdp_key(&cdp.id), cdp.to_bytes().unwrap_or_default()));
+        }
+        for (owner, amount) in token.all_balances() {
+            entries.push((balance_key(owner), amount.cents().to_be_bytes().to_vec()));
+        }
+        for ((owner, spender), amount) in token.all_allowances() {
+            entries.push((allowance_key(owner, spender), amount.cents().to_be_bytes().to_vec()));
+        }
+        for (owner, deposit) in self.stability_pool.all_deposits() {
+            entries.push((deposit_key(owner), codec::encode(deposit).unwrap_or_default()));
+        }
+
+        if let Some(nfts) = &self.cdp_nfts {
+            for (cdp_id, holder) in nfts.all_holders() {
+                entries.push((cdp_nft_key(cdp_id), holder.as_bytes().to_vec()));
+            }
+        }
+
+        entries.push((global_key("total_supply"), token.total_supply().cents().to_be_bytes().to_vec()));
+        entries.push((global_key("stability_pool"), self.stability_pool.state_hash().as_bytes().to_vec()));
+        entries.push((global_key("vault"), self.vault.state_hash().as_bytes().to_vec()));
+        entries
+    }
+
+    /// Execute a bundle of spells atomically.
+    ///
+    /// Every spell runs through [`Self::execute_spell`]; if any fails, all
+    /// state changes made by the bundle are rolled back. Payouts are merged
+    /// into one anchor transaction committing to the bundle hash, and fees
+    /// are accrued only once the whole bundle has succeeded.
+    pub fn execute_bundle(&mut self, bundle: SpellBundle) -> Result<BundleResult> {
+        let block_height = self.adapter.block_height;
+        bundle.validate(block_height)?;
+        for spell in bundle.spells() {
+            self.adapter.metadata.verify_spell(spell, &CharmId::ZKUSD)?;
+            if self.adapter.was_spell_executed(&spell.hash()) {
+                return Err(Error::InvalidParameter {
+                    name: "bundle".into(),
+                    reason: format!("spell {} already executed", spell.hash().to_hex()),
+                });
+            }
+        }
+
+        let bundle_hash = bundle.hash();
+        let checkpoint = (
+            self.adapter.clone(),
+            self.cdp_manager.clone(),
+            self.vault.clone(),
+            self.stability_pool.clone(),
+            self.events.len(),
+        );
+
+        self.bundle = Some(BundleAnchor::default());
+        let mut results = Vec::with_capacity(bundle.len());
+        for spell in bundle.into_spells() {
+            let result = self.execute_spell(spell);
+            if !result.success {
+                let (adapter, cdp_manager, vault, stability_pool, events_len) = checkpoint;
+                self.adapter = adapter;
+                self.cdp_manager = cdp_manager;
+                self.vault = vault;
+                self.stability_pool = stability_pool;
+                self.events.truncate(events_len);
+                self.bundle = None;
+
+                return Err(Error::InvalidParameter {
+                    name: "bundle".into(),
+                    reason: format!(
+                        "spell {} failed: {}",
+                        result.spell_hash.to_hex(),
+                        result.error.unwrap_or_default()
+                    ),
+                });
+            }
+            results.push(result);
+        }
+
+        let mut anchor = self.bundle.take().unwrap_or_default();
+        anchor.template.add_op_return(OpReturnBuilder::spell_bundle(bundle_hash.as_bytes(), results.len() as u16));
+        self.fee_account.accrue(anchor.fee_sats);
+
+        Ok(BundleResult {
+            bundle_hash,
+            results,
+            anchor_tx: bitcoin::consensus::serialize(&anchor.template.build_unfunded()),
+            proof: (!anchor.proofs.is_empty()).then(|| ProofBatch::new(anchor.proofs)),
+            state_root: self.state_root(),
+            block_height,
+        })
+    }
+
+    /// Run a protocol spell with the same replay and validation checks as token spells
+    fn execute_protocol_spell(
+        &mut self,
+        spell: CharmSpell,
+        handler: fn(&mut Self, &CharmSpell, Hash) -> Result<SpellOutcome>,
+    ) -> SpellResult {
+        let spell_hash = spell.hash();
+        let block_height = self.adapter.block_height;
+
+        if self.adapter.was_spell_executed(&spell_hash) {
+            return SpellResult::failure(spell_hash, "Spell already executed", block_height);
+        }
+
+        if let Err(e) = spell
+            .validate(block_height)
+            .and_then(|_| self.adapter.metadata.verify_spell(&spell, &CharmId::ZKUSD))
+        {
+            return SpellResult::failure(spell_hash, e.to_string(), block_height);
+        }
+
+        match handler(self, &spell, spell_hash) {
+            Ok(outcome) => {
+                self.adapter.executed_spells.insert(spell_hash, block_height);
+                let result = SpellResult::success(spell_hash, outcome.data, block_height, 1000);
+                match outcome.proof {
+                    Some(proof) => {
+                        let result = result.with_proof(&proof);
+                        if let Some(bundle) = self.bundle.as_mut() {
+                            bundle.proofs.push(proof);
+                        }
+                        result
+                    }
+                    None => result,
+                }
+            }
+            Err(e) => SpellResult::failure(spell_hash, e.to_string(), block_height),
+        }
+    }
+
+    /// Execute redemption spell
+    ///
+    /// Redeems zkUSD against the riskiest CDPs first, burns the redeemed
+    /// tokens and returns a receipt carrying the unfunded BTC payout.
+    fn execute_redeem(&mut self, spell: &CharmSpell, spell_hash: Hash) -> Result<SpellOutcome> {
+        let params = RedeemParams::decode(&spell.data)?;
+
+        if params.amount == 0 {
+            return Err(Error::ZeroAmount);
+        }
+        if self.btc_price == 0 {
+            return Err(Error::InvalidParameter {
+                name: "btc_price".into(),
+                reason: "BTC price not set".into(),
+            });
+        }
+
+        let balance = self.adapter.token.inner().balance_of(&spell.caster);
+        if balance.cents() < params.amount {
+            return Err(Error::InsufficientCollateral {
+                required: params.amount,
+                available: balance.cents(),
+            });
+        }
+
+        // Calculate fee
+        let fee_bps = self.adapter.config.redemption_fee(self.timestamp);
+        if fee_bps > params.max_fee_bps {
+            return Err(Error::InvalidParameter {
+                name: "fee".into(),
+                reason: format!("Fee {}bps exceeds max {}bps", fee_bps, params.max_fee_bps),
+            });
+        }
+
+        let fee_amount = calculate_fee_bps(params.amount, fee_bps)?;
+        let net_redemption = params.amount - fee_amount;
+
+        // Walk CDPs by ratio (ascending); undercollateralized CDPs are left
+        // for liquidation
+        let min_ratio = self.adapter.config.effective_mcr();
+        let mut remaining = net_redemption;
+        let mut total_collateral = 0u64;
+        let mut cdp_updates: Vec<(CDPId, u64, u64)> = Vec::new();
+
+        for (cdp, ratio) in self.cdp_manager.get_sorted_by_ratio(self.btc_price) {
+            if remaining == 0 {
+                break;
+            }
+            if cdp.debt_cents == 0 || ratio < min_ratio {
+                continue;
+            }
+
+            let redeem_from_this = remaining.min(cdp.debt_cents);
+            let coll_to_take = safe_mul_div(redeem_from_this, SATS_PER_BTC, self.btc_price)?
+                .min(cdp.collateral_sats);
+
+            cdp_updates.push((cdp.id, redeem_from_this, coll_to_take));
+
+            remaining -= redeem_from_this;
+            total_collateral += coll_to_take;
+        }
+
+        if cdp_updates.is_empty() {
+            return Err(Error::InvalidParameter {
+                name: "amount".into(),
+                reason: "No CDPs available for redemption".into(),
+            });
+        }
+
+        let assessment = self.fees.assess(ZkUSDSpellType::Redeem, total_collateral)?;
+        let block_height = self.adapter.block_height;
+        let cdps_affected = cdp_updates.len() as u32;
+        let redeemed = params.amount - remaining;
+
+        // Apply partial redemptions to a working copy so a failed proof leaves state untouched
+        let mut next_cdps = self.cdp_manager.clone();
+        let mut redemption_data = Vec::with_capacity(cdp_updates.len());
+        for &(id, debt_taken, coll_taken) in &cdp_updates {
+            let cdp = next_cdps.get_mut(&id)
+                .ok_or_else(|| Error::CDPNotFound(id.to_hex()))?;
+            redemption_data.push(CDPRedemptionData {
+                cdp_id: id,
+                owner: cdp.owner,
+                debt_before: cdp.debt_cents,
+                debt_redeemed: debt_taken,
+                collateral_before: cdp.collateral_sats,
+                collateral_taken: coll_taken,
+                merkle_proof: MerkleProof::empty(),
+            });
+            cdp.debt_cents -= debt_taken;
+            cdp.collateral_sats -= coll_taken;
+            cdp.last_updated = block_height;
+        }
+
+        let proof = self.prove(ProofInputs::redemption(
+            RedemptionPublicInputs {
+                state_root_before: self.cdp_manager.state_root(),
+                state_root_after: next_cdps.state_root(),
+                redeemer: spell.caster,
+                amount_redeemed: redeemed,
+                collateral_received: total_collateral,
+                fee_paid: fee_amount,
+                btc_price: self.btc_price,
+                cdps_affected,
+                block_height,
+            },
+            RedemptionPrivateInputs {
+                signature: spell.signature,
+                nonce: spell.nonce,
+                cdps: redemption_data,
+                fee_bps,
+            },
+        ))?;
+
+        self.cdp_manager = next_cdps;
+        for (id, _, coll_taken) in cdp_updates {
+            let tracked = self.vault.collateral_of(&id).sats().min(coll_taken);
+            if tracked > 0 {
+                self.vault.withdraw(id, CollateralAmount::from_sats(tracked), block_height, spell_hash)?;
+            }
+        }
+
+        // Burn redeemed tokens
+        self.adapter.token.inner_mut().burn(
+            spell.caster,
+            TokenAmount::from_cents(redeemed),
+            block_height,
+            spell_hash,
+        )?;
+
+        // Update base rate
+        let total_debt = self.adapter.config.total_system_debt;
+        self.adapter.config.fees.record_redemption(redeemed, total_debt, self.timestamp);
+
+        self.events.push(ProtocolEvent::Redemption(RedemptionEvent {
+            redeemer: spell.caster,
+            zkusd_amount: TokenAmount::from_cents(redeemed),
+            collateral_received: CollateralAmount::from_sats(total_collateral),
+            fee: TokenAmount::from_cents(fee_amount),
+            cdps_affected,
+            btc_price: self.btc_price,
+            block_height,
+            timestamp: self.timestamp,
+        }));
+
+        let payout_tx = self.build_payout(
+            assessment,
+            params.payout_script,
+            OpReturnBuilder::redemption(spell_hash.as_bytes(), redeemed, assessment.net_sats),
+        );
+
+        let receipt = RedemptionReceipt {
+            zkusd_redeemed: redeemed,
+            collateral_paid: assessment.net_sats,
+            fee: fee_amount,
+            protocol_fee_sats: assessment.fee_sats,
+            cdps_affected,
+            payout_tx,
+        };
+
+        let data = bincode::serialize(&receipt).map_err(|e| Error::Serialization(e.to_string()))?;
+        Ok(SpellOutcome { data, proof })
+    }
+
     /// Get protocol statistics
     pub fn statistics(&self) -> ProtocolStats {
         ProtocolStats {
@@ -222,6 +671,167 @@ impl ProtocolCharmsAdapter {
             block_height: self.adapter.block_height,
         }
     }
+
+    /// Execute stability pool gains claim spell
+    fn execute_claim_gains(&mut self, spell: &CharmSpell, _spell_hash: Hash) -> Result<SpellOutcome> {
+        let params = ClaimGainsParams::decode(&spell.data)?;
+
+        let pending = self.stability_pool.get_btc_gains(&spell.caster);
+        if pending.is_zero() {
+            return Err(Error::InvalidParameter {
+                name: "gains".into(),
+                reason: "No BTC gains to claim".into(),
+            });
+        }
+        self.fees.assess(ZkUSDSpellType::ClaimGains, pending.sats())?;
+
+        let claimed = self.stability_pool.claim_btc(&spell.caster)?;
+        let assessment = self.fees.assess(ZkUSDSpellType::ClaimGains, claimed.sats())?;
+
+        let block_height = self.adapter.block_height;
+
+        self.events.push(ProtocolEvent::GainsClaimed(GainsClaimedEvent {
+            depositor: spell.caster,
+            btc_amount: claimed,
+            block_height,
+            timestamp: self.timestamp,
+        }));
+
+        let payout_tx = self.build_payout(
+            assessment,
+            params.payout_script,
+            OpReturnBuilder::gains_claim(spell.caster.as_bytes(), assessment.net_sats),
+        );
+
+        let receipt = GainsClaimReceipt {
+            btc_claimed: assessment.net_sats,
+            protocol_fee_sats: assessment.fee_sats,
+            payout_tx,
+        };
+
+        // Gains claims only move already-seized collateral; there is no circuit for them
+        let data = bincode::serialize(&receipt).map_err(|e| Error::Serialization(e.to_string()))?;
+        Ok(SpellOutcome { data, proof: None })
+    }
+
+    /// Execute CDP charm mint spell, cast by the CDP owner after opening it
+    fn execute_mint_cdp_nft(&mut self, spell: &CharmSpell, _spell_hash: Hash) -> Result<SpellOutcome> {
+        let params = MintCdpNftParams::decode(&spell.data)?;
+        self.authorize_cdp(&params.cdp_id, &spell.caster)?;
+
+        let nfts = self.cdp_nfts.as_mut().ok_or_else(cdp_nfts_disabled)?;
+        let charm_id = nfts.mint(params.cdp_id, spell.caster)?;
+
+        let receipt = CdpNftReceipt { cdp_id: params.cdp_id, charm_id, holder: spell.caster };
+        let data = bincode::serialize(&receipt).map_err(|e| Error::Serialization(e.to_string()))?;
+        Ok(SpellOutcome { data, proof: None })
+    }
+
+    /// Execute CDP charm transfer spell, handing control of the CDP to the recipient
+    fn execute_transfer_cdp_nft(&mut self, spell: &CharmSpell, _spell_hash: Hash) -> Result<SpellOutcome> {
+        let params = TransferCdpNftParams::decode(&spell.data)?;
+        let block_height = self.adapter.block_height;
+
+        let nfts = self.cdp_nfts.as_mut().ok_or_else(cdp_nfts_disabled)?;
+        nfts.verify_holder(&params.cdp_id, &spell.caster)?;
+
+        // Keep the CDP's own keys in step so non-charm paths agree on control
+        self.cdp_manager.transfer_owner(&params.cdp_id, params.to, block_height)?;
+        nfts.transfer(&params.cdp_id, &spell.caster, params.to)?;
+
+        let receipt = CdpNftReceipt {
+            cdp_id: params.cdp_id,
+            charm_id: crate::charms::cdp_nft::cdp_charm_id(&params.cdp_id),
+            holder: params.to,
+        };
+        let data = bincode::serialize(&receipt).map_err(|e| Error::Serialization(e.to_string()))?;
+        Ok(SpellOutcome { data, proof: None })
+    }
+
+    /// Build the unfunded payout, routing the protocol fee to the fee account
+    ///
+    /// Collateral inputs are attached by the custodian at signing time. Fees
+    /// below the dust threshold are accrued but left in the custodian's change.
+    /// Inside a bundle the outputs are added to the bundle's anchor instead
+    /// and an empty payout is returned.
+    fn build_payout(&mut self, assessment: FeeAssessment, payout_script: Vec<u8>, op_return: ScriptBuf) -> Vec<u8> {
+        let spendable_fee = self.fees.is_spendable_fee(assessment.fee_sats);
+
+        if let Some(bundle) = self.bundle.as_mut() {
+            bundle.template.add_output(assessment.net_sats, ScriptBuf::from_bytes(payout_script));
+            if spendable_fee {
+                bundle.template.add_output(assessment.fee_sats, self.fee_account.fee_script.clone());
+            }
+            bundle.fee_sats = bundle.fee_sats.saturating_add(assessment.fee_sats);
+            return Vec::new();
+        }
+
+        let mut template = TxTemplate::new();
+        template.add_output(assessment.net_sats, ScriptBuf::from_bytes(payout_script));
+        if spendable_fee {
+            template.add_output(assessment.fee_sats, self.fee_account.fee_script.clone());
+        }
+        template.add_op_return(op_return);
+
+        self.fee_account.accrue(assessment.fee_sats);
+        bitcoin::consensus::serialize(&template.build_unfunded())
+    }
+
+    /// Generate a proof with the configured prover, if any
+    fn prove(&mut self, inputs: ProofInputs) -> Result<Option<ZKProof>> {
+        match self.prover.as_mut() {
+            Some(prover) => prover.prove(inputs).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+fn cdp_nfts_disabled() -> Error {
+    Error::InvalidParameter {
+        name: "spell_type".into(),
+        reason: "CDP charms are not enabled".into(),
+    }
+}
+
+/// Result of a CDP charm mint or transfer spell
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdpNftReceipt {
+    /// CDP the charm represents
+    pub cdp_id: CDPId,
+    /// Charm id of the CDP
+    pub charm_id: CharmId,
+    /// Holder after the spell
+    pub holder: PublicKey,
+}
+
+/// Result of a stability pool gains claim spell
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GainsClaimReceipt {
+    /// Collateral paid out after fees (sats)
+    pub btc_claimed: u64,
+    /// Protocol fee charged (sats)
+    pub protocol_fee_sats: u64,
+    /// Consensus-encoded unfunded payout transaction, empty when executed
+    /// in a bundle (the payout is part of the bundle's anchor transaction)
+    pub payout_tx: Vec<u8>,
+}
+
+/// Result of a redemption spell
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedemptionReceipt {
+    /// zkUSD burned, including fee (cents)
+    pub zkusd_redeemed: u64,
+    /// Collateral paid out after protocol fees (sats)
+    pub collateral_paid: u64,
+    /// Redemption fee (cents)
+    pub fee: u64,
+    /// Protocol fee charged on the payout (sats)
+    pub protocol_fee_sats: u64,
+    /// Number of CDPs redeemed against
+    pub cdps_affected: u32,
+    /// Consensus-encoded unfunded payout transaction, empty when executed
+    /// in a bundle (the payout is part of the bundle's anchor transaction)
+    pub payout_tx: Vec<u8>,
 }
 
 /// Protocol statistics
@@ -294,6 +904,292 @@ mod tests {
         assert_eq!(stats.block_height, 100);
     }
 
+    #[test]
+    fn test_redeem_spell() {
+        use crate::core::cdp::CDP;
+
+        let creator = KeyPair::generate();
+        let redeemer = KeyPair::generate();
+        let mut adapter = ProtocolCharmsAdapter::new(*creator.public_key(), 100, 10_000_000);
+
+        // Two CDPs: the riskier one is redeemed against first
+        let risky_owner = KeyPair::generate();
+        let mut risky = CDP::with_collateral(*risky_owner.public_key(), 10_000_000, 0, 1).unwrap();
+        risky.debt_cents = 600_000;
+        let risky_id = risky.id;
+        adapter.cdp_manager.register(risky).unwrap();
+
+        let safe_owner = KeyPair::generate();
+        let mut safe = CDP::with_collateral(*safe_owner.public_key(), 100_000_000, 0, 1).unwrap();
+        safe.debt_cents = 600_000;
+        let safe_id = safe.id;
+        adapter.cdp_manager.register(safe).unwrap();
+
+        adapter.adapter.token.inner_mut()
+            .mint(*redeemer.public_key(), TokenAmount::from_cents(1_000_000), 100, Hash::zero())
+            .unwrap();
+
+        let spell = SpellBuilder::redeem(1_000_000, 500, vec![0x51])
+            .nonce(1)
+            .build_and_sign(&redeemer);
+        let result = adapter.execute_spell(spell.clone());
+        assert!(result.success, "{:?}", result.error);
+
+        let receipt: RedemptionReceipt = bincode::deserialize(&result.data).unwrap();
+        assert_eq!(receipt.cdps_affected, 2);
+        assert_eq!(receipt.zkusd_redeemed, 1_000_000);
+        assert_eq!(adapter.adapter.token.inner().balance_of(redeemer.public_key()), TokenAmount::ZERO);
+
+        // Riskiest CDP fully cleared, remainder taken from the next one
+        assert_eq!(adapter.cdp_manager.get(&risky_id).unwrap().debt_cents, 0);
+        let net = 1_000_000 - receipt.fee;
+        assert_eq!(adapter.cdp_manager.get(&safe_id).unwrap().debt_cents, 1_200_000 - net);
+
+        let tx: bitcoin::Transaction = bitcoin::consensus::deserialize(&receipt.payout_tx).unwrap();
+        assert_eq!(tx.output[0].value.to_sat(), receipt.collateral_paid);
+        assert_eq!(adapter.events.filter_by_type("Redemption").len(), 1);
+
+        // Replays are rejected
+        assert!(!adapter.execute_spell(spell).success);
+    }
+
+    #[test]
+    fn test_redeem_spell_skips_undercollateralized_cdps() {
+        use crate::core::cdp::CDP;
+
+        let creator = KeyPair::generate();
+        let redeemer = KeyPair::generate();
+        let mut adapter = ProtocolCharmsAdapter::new(*creator.public_key(), 100, 10_000_000);
+
+        // 0.1 BTC against $9,500 is 105%, below the MCR
+        let mut underwater = CDP::with_collateral(*KeyPair::generate().public_key(), 10_000_000, 0, 1).unwrap();
+        underwater.debt_cents = 950_000;
+        let underwater_id = underwater.id;
+        adapter.cdp_manager.register(underwater).unwrap();
+
+        let mut healthy = CDP::with_collateral(*KeyPair::generate().public_key(), 100_000_000, 0, 1).unwrap();
+        healthy.debt_cents = 600_000;
+        let healthy_id = healthy.id;
+        adapter.cdp_manager.register(healthy).unwrap();
+
+        adapter.adapter.token.inner_mut()
+            .mint(*redeemer.public_key(), TokenAmount::from_cents(100_000), 100, Hash::zero())
+            .unwrap();
+
+        let spell = SpellBuilder::redeem(100_000, 500, vec![0x51]).nonce(1).build_and_sign(&redeemer);
+        let result = adapter.execute_spell(spell);
+        assert!(result.success, "{:?}", result.error);
+
+        let receipt: RedemptionReceipt = bincode::deserialize(&result.data).unwrap();
+        assert_eq!(receipt.cdps_affected, 1);
+        assert_eq!(adapter.cdp_manager.get(&underwater_id).unwrap().debt_cents, 950_000);
+        assert!(adapter.cdp_manager.get(&healthy_id).unwrap().debt_cents < 600_000);
+    }
+
+    #[test]
+    fn test_redeem_spell_with_proof() {
+        use crate::core::cdp::CDP;
+        use crate::zkp::ProverBackend;
+
+        let creator = KeyPair::generate();
+        let redeemer = KeyPair::generate();
+        let mut adapter = ProtocolCharmsAdapter::new(*creator.public_key(), 100, 10_000_000)
+            .with_prover(ProverManager::new(ProverBackend::Native));
+
+        let owner = KeyPair::generate();
+        let mut cdp = CDP::with_collateral(*owner.public_key(), 100_000_000, 0, 1).unwrap();
+        cdp.debt_cents = 1_000_000;
+        adapter.cdp_manager.register(cdp).unwrap();
+
+        adapter.adapter.token.inner_mut()
+            .mint(*redeemer.public_key(), TokenAmount::from_cents(100_000), 100, Hash::zero())
+            .unwrap();
+
+        let spell = SpellBuilder::redeem(100_000, 500, vec![0x51]).nonce(1).build_and_sign(&redeemer);
+        let result = adapter.execute_spell(spell);
+        assert!(result.success, "{:?}", result.error);
+
+        assert!(result.proof.as_ref().is_some_and(|p| !p.is_empty()));
+        assert_eq!(result.verifier_key_id.as_deref(), Some("zkusd_redemption_v1"));
+        assert_eq!(adapter.prover().unwrap().stats().proofs_generated, 1);
+    }
+
+    #[test]
+    fn test_claim_gains_spell() {
+        let creator = KeyPair::generate();
+        let depositor = KeyPair::generate();
+        let mut adapter = ProtocolCharmsAdapter::new(*creator.public_key(), 100, 10_000_000);
+
+        adapter.stability_pool
+            .deposit(*depositor.public_key(), TokenAmount::from_cents(1_000_000), 100)
+            .unwrap();
+        adapter.stability_pool
+            .absorb_liquidation(TokenAmount::from_cents(500_000), CollateralAmount::from_sats(600_000))
+            .unwrap();
+
+        let spell = SpellBuilder::claim_gains(vec![0x51]).nonce(1).build_and_sign(&depositor);
+        let result = adapter.execute_spell(spell);
+        assert!(result.success, "{:?}", result.error);
+
+        let receipt: GainsClaimReceipt = bincode::deserialize(&result.data).unwrap();
+        assert!(receipt.btc_claimed > 0);
+        assert!(adapter.stability_pool.get_btc_gains(depositor.public_key()).is_zero());
+
+        let tx: bitcoin::Transaction = bitcoin::consensus::deserialize(&receipt.payout_tx).unwrap();
+        assert_eq!(tx.output[0].value.to_sat(), receipt.btc_claimed);
+        assert_eq!(adapter.events.filter_by_type("GainsClaimed").len(), 1);
+
+        // Nothing left to claim
+        let again = SpellBuilder::claim_gains(vec![0x51]).nonce(2).build_and_sign(&depositor);
+        assert!(!adapter.execute_spell(again).success);
+
+        // The result commits to the state the claim left behind
+        assert_eq!(result.state_root, Some(adapter.state_root()));
+    }
+
+    #[test]
+    fn test_spell_fees_and_dust() {
+        use crate::charms::fees::FeeSchedule;
+
+        let creator = KeyPair::generate();
+        let depositor = KeyPair::generate();
+        let fee_script = ScriptBuf::from_bytes(vec![0x52]);
+        let fees = SpellFeeConfig::default()
+            .with_schedule(ZkUSDSpellType::ClaimGains, FeeSchedule::new(1_000, 100));
+        let mut adapter = ProtocolCharmsAdapter::new(*creator.public_key(), 100, 10_000_000)
+            .with_fees(fees, fee_script.clone());
+
+        adapter.stability_pool
+            .deposit(*depositor.public_key(), TokenAmount::from_cents(1_000_000), 100)
+            .unwrap();
+        adapter.stability_pool
+            .absorb_liquidation(TokenAmount::from_cents(500_000), CollateralAmount::from_sats(600_000))
+            .unwrap();
+        let gains = adapter.stability_pool.get_btc_gains(depositor.public_key()).sats();
+
+        let spell = SpellBuilder::claim_gains(vec![0x51]).nonce(1).build_and_sign(&depositor);
+        let result = adapter.execute_spell(spell);
+        assert!(result.success, "{:?}", result.error);
+
+        let receipt: GainsClaimReceipt = bincode::deserialize(&result.data).unwrap();
+        assert_eq!(receipt.protocol_fee_sats, 1_000 + gains.div_ceil(100));
+        assert_eq!(receipt.btc_claimed + receipt.protocol_fee_sats, gains);
+        assert_eq!(adapter.fee_account.accrued_sats(), receipt.protocol_fee_sats);
+
+        let tx: bitcoin::Transaction = bitcoin::consensus::deserialize(&receipt.payout_tx).unwrap();
+        assert_eq!(adapter.fee_account.track_payout(&tx), receipt.protocol_fee_sats);
+        assert_eq!(adapter.fee_account.tracked_sats(), receipt.protocol_fee_sats);
+
+        // A payout that would be dust after fees is rejected before touching the pool
+        let fees = SpellFeeConfig::default()
+            .with_schedule(ZkUSDSpellType::ClaimGains, FeeSchedule::new(1_000, 100));
+        let mut adapter = ProtocolCharmsAdapter::new(*creator.public_key(), 100, 10_000_000)
+            .with_fees(fees, fee_script);
+        let small = KeyPair::generate();
+        adapter.stability_pool
+            .deposit(*small.public_key(), TokenAmount::from_cents(100_000), 100)
+            .unwrap();
+        adapter.stability_pool
+            .absorb_liquidation(TokenAmount::from_cents(1), CollateralAmount::from_sats(1_500))
+            .unwrap();
+        let before = adapter.stability_pool.get_btc_gains(small.public_key());
+        let spell = SpellBuilder::claim_gains(vec![0x51]).nonce(1).build_and_sign(&small);
+        let result = adapter.execute_spell(spell);
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("dust"));
+        assert_eq!(adapter.stability_pool.get_btc_gains(small.public_key()), before);
+    }
+
+    #[test]
+    fn test_spell_bundle() {
+        use crate::btc::scripts::ProtocolOp;
+
+        let creator = KeyPair::generate();
+        let first = KeyPair::generate();
+        let second = KeyPair::generate();
+        let mut adapter = ProtocolCharmsAdapter::new(*creator.public_key(), 100, 10_000_000);
+
+        for depositor in [&first, &second] {
+            adapter.stability_pool
+                .deposit(*depositor.public_key(), TokenAmount::from_cents(1_000_000), 100)
+                .unwrap();
+        }
+        adapter.stability_pool
+            .absorb_liquidation(TokenAmount::from_cents(500_000), CollateralAmount::from_sats(600_000))
+            .unwrap();
+
+        // A failing spell rolls back the whole bundle
+        let failing = SpellBundle::from_spells(vec![
+            SpellBuilder::claim_gains(vec![0x51]).nonce(1).build_and_sign(&first),
+            SpellBuilder::claim_gains(vec![0x51]).nonce(2).build_and_sign(&first),
+        ])
+        .unwrap();
+        assert!(adapter.execute_bundle(failing).is_err());
+        assert!(!adapter.stability_pool.get_btc_gains(first.public_key()).is_zero());
+        assert_eq!(adapter.adapter.executed_spell_count(), 0);
+        assert!(adapter.events.is_empty());
+
+        let bundle = SpellBundle::from_spells(vec![
+            SpellBuilder::claim_gains(vec![0x51]).nonce(1).build_and_sign(&first),
+            SpellBuilder::claim_gains(vec![0x52]).nonce(1).build_and_sign(&second),
+        ])
+        .unwrap();
+        let bundle_hash = bundle.hash();
+        let result = adapter.execute_bundle(bundle.clone()).unwrap();
+
+        assert_eq!(result.results.len(), 2);
+        assert_eq!(adapter.events.filter_by_type("GainsClaimed").len(), 2);
+        let receipt: GainsClaimReceipt = bincode::deserialize(&result.results[0].data).unwrap();
+        assert!(receipt.payout_tx.is_empty());
+
+        // One anchor carries both payouts and commits to the bundle
+        let tx: bitcoin::Transaction = bitcoin::consensus::deserialize(&result.anchor_tx).unwrap();
+        assert_eq!(tx.output.len(), 3);
+        assert_eq!(
+            OpReturnBuilder::parse(&tx.output[2].script_pubkey),
+            Some(ProtocolOp::SpellBundle { bundle_hash: *bundle_hash.as_bytes(), spell_count: 2 })
+        );
+
+        // Replays are rejected up front
+        assert!(adapter.execute_bundle(bundle).is_err());
+    }
+
+    #[test]
+    fn test_cdp_nft_controls_cdp() {
+        use crate::core::cdp::CDP;
+
+        let creator = KeyPair::generate();
+        let alice = KeyPair::generate();
+        let bob = KeyPair::generate();
+        let mut adapter = ProtocolCharmsAdapter::new(*creator.public_key(), 100, 10_000_000).with_cdp_nfts();
+
+        let cdp = CDP::with_collateral(*alice.public_key(), 10_000_000, 1, 100).unwrap();
+        let cdp_id = cdp.id;
+        adapter.cdp_manager.register(cdp).unwrap();
+
+        // Only the owner can mint the charm
+        let spell = SpellBuilder::mint_cdp_nft(cdp_id).nonce(1).build_and_sign(&bob);
+        assert!(!adapter.execute_spell(spell).success);
+        let spell = SpellBuilder::mint_cdp_nft(cdp_id).nonce(1).build_and_sign(&alice);
+        assert!(adapter.execute_spell(spell).success);
+        assert!(adapter.authorize_cdp(&cdp_id, alice.public_key()).is_ok());
+
+        let spell = SpellBuilder::transfer_cdp_nft(cdp_id, *bob.public_key()).nonce(2).build_and_sign(&alice);
+        let result = adapter.execute_spell(spell);
+        assert!(result.success, "{:?}", result.error);
+        let receipt: CdpNftReceipt = bincode::deserialize(&result.data).unwrap();
+        assert_eq!(receipt.holder, *bob.public_key());
+
+        // Control follows the charm
+        assert!(adapter.authorize_cdp(&cdp_id, alice.public_key()).is_err());
+        assert!(adapter.authorize_cdp(&cdp_id, bob.public_key()).is_ok());
+        assert_eq!(adapter.cdp_manager.get_by_owner(bob.public_key()).len(), 1);
+        assert!(adapter.cdp_manager.get_by_owner(alice.public_key()).is_empty());
+
+        let spell = SpellBuilder::transfer_cdp_nft(cdp_id, *alice.public_key()).nonce(3).build_and_sign(&alice);
+        assert!(!adapter.execute_spell(spell).success);
+    }
+
     #[test]
     fn test_spell_cleanup() {
         let keypair = KeyPair::generate();
@@ -0,0 +1,421 @@
+//! Cross-chain bridge for zkUSD.
+//!
+//! Bridging uses lock-and-mint semantics. Moving zkUSD out locks it in the
+//! bridge escrow account and emits a sequenced event that relayers pick up
+//! to mint the wrapped token on the destination chain. Moving it back
+//! requires a burn on the other chain, attested by a threshold of relayers,
+//! which releases the same amount from escrow.
+//!
+//! Escrowed zkUSD stays in the total supply, and a chain can never release
+//! more than was locked towards it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::core::token::TokenAmount;
+use crate::error::{Error, Result};
+use crate::utils::constants::PUBKEY_LENGTH;
+use crate::utils::crypto::{create_message_hash, verify_signature, Hash, KeyPair, PublicKey, Signature};
+use crate::utils::undo::{Undo, UndoSet};
+
+/// Identifier of an external chain
+pub type ChainId = u32;
+
+/// Maximum number of relayers in a set
+pub const MAX_RELAYERS: usize = 32;
+
+/// Maximum length of a destination address in bytes
+pub const MAX_BRIDGE_ADDRESS_LENGTH: usize = 64;
+
+fn invalid(name: &str, reason: impl Into<String>) -> Error {
+    Error::InvalidParameter { name: name.into(), reason: reason.into() }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// RELAYERS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// A relayer's signature over an inbound transfer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct Attestation {
+    /// Relayer key
+    pub relayer: PublicKey,
+    /// Signature over [`InboundTransfer::message_hash`]
+    pub signature: Signature,
+}
+
+impl Attestation {
+    /// Attest to a transfer as `keypair`
+    pub fn sign(transfer: &InboundTransfer, keypair: &KeyPair) -> Self {
+        Self {
+            relayer: *keypair.public_key(),
+            signature: keypair.sign(&transfer.message_hash()),
+        }
+    }
+}
+
+/// Keys allowed to attest inbound transfers, and how many must agree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayerSet {
+    relayers: Vec<PublicKey>,
+    threshold: u8,
+}
+
+impl RelayerSet {
+    /// Create a relayer set requiring `threshold` distinct attestations
+    pub fn new(relayers: Vec<PublicKey>, threshold: u8) -> Result<Self> {
+        if relayers.is_empty() || relayers.len() > MAX_RELAYERS {
+            return Err(invalid("relayers", format!("need between 1 and {} relayers", MAX_RELAYERS)));
+        }
+        if threshold == 0 || threshold as usize > relayers.len() {
+            return Err(invalid("threshold", "must be between 1 and the number of relayers"));
+        }
+        let mut sorted = relayers.clone();
+        sorted.sort_by_key(|k| *k.as_bytes());
+        sorted.dedup();
+        if sorted.len() != relayers.len() {
+            return Err(invalid("relayers", "duplicate relayer key"));
+        }
+        Ok(Self { relayers, threshold })
+    }
+
+    /// Relayer keys
+    pub fn relayers(&self) -> &[PublicKey] {
+        &self.relayers
+    }
+
+    /// Attestations required
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// Check whether `key` belongs to the set
+    pub fn is_relayer(&self, key: &PublicKey) -> bool {
+        self.relayers.contains(key)
+    }
+
+    /// Check that enough distinct relayers signed `message`.
+    ///
+    /// Any attestation that is not from a relayer or does not verify rejects
+    /// the whole set, so a bad attestation is never silently skipped.
+    pub fn verify(&self, message: &Hash, attestations: &[Attestation]) -> Result<()> {
+        let mut signed = Vec::with_capacity(attestations.len());
+        for attestation in attestations {
+            if !self.is_relayer(&attestation.relayer) {
+                return Err(Error::Unauthorized(format!(
+                    "{} is not a bridge relayer",
+                    attestation.relayer.to_address()
+                )));
+            }
+            if !verify_signature(&attestation.relayer, message, &attestation.signature) {
+                return Err(Error::InvalidSignature);
+            }
+            if !signed.contains(&attestation.relayer) {
+                signed.push(attestation.relayer);
+            }
+        }
+
+        if signed.len() < self.threshold as usize {
+            return Err(Error::Unauthorized(format!(
+                "{} of {} relayer attestations required, got {}",
+                self.threshold,
+                self.relayers.len(),
+                signed.len()
+            )));
+        }
+        Ok(())
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// TRANSFERS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// A transfer back from an external chain, as attested by the relayers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct InboundTransfer {
+    /// Chain the tokens were burned on
+    pub source_chain: ChainId,
+    /// Burn transaction on the source chain
+    pub source_tx: Hash,
+    /// Account receiving the released zkUSD
+    pub recipient: PublicKey,
+    /// Amount to release
+    pub amount: TokenAmount,
+}
+
+impl InboundTransfer {
+    /// Domain tag for relayer attestations
+    pub const DOMAIN: &'static str = "bridge-in";
+
+    /// Domain-separated hash the relayers sign
+    pub fn message_hash(&self) -> Hash {
+        let mut data = Vec::with_capacity(4 + 32 + PUBKEY_LENGTH + 8);
+        data.extend_from_slice(&self.source_chain.to_be_bytes());
+        data.extend_from_slice(self.source_tx.as_bytes());
+        data.extend_from_slice(self.recipient.as_bytes());
+        data.extend_from_slice(&self.amount.cents().to_be_bytes());
+        create_message_hash(Self::DOMAIN, &data)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// BRIDGE
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Bridge accounting: supported chains, escrowed amounts and processed
+/// inbound transfers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bridge {
+    /// Relayers attesting inbound transfers
+    relayers: RelayerSet,
+    /// Chains tokens may be bridged to
+    chains: BTreeSet<ChainId>,
+    /// zkUSD locked towards each chain
+    locked: BTreeMap<ChainId, TokenAmount>,
+    /// Sequence number of the next outbound transfer
+    next_sequence: u64,
+    /// Inbound transfers already released, by source chain and transaction
+    processed: UndoSet<(ChainId, Hash)>,
+}
+
+impl Bridge {
+    /// Create a bridge with no supported chains
+    pub fn new(relayers: RelayerSet) -> Self {
+        Self {
+            relayers,
+            chains: BTreeSet::new(),
+            locked: BTreeMap::new(),
+            next_sequence: 1,
+            processed: UndoSet::new(),
+        }
+    }
+
+    /// Allow bridging to `chain`
+    pub fn with_chain(mut self, chain: ChainId) -> Self {
+        self.chains.insert(chain);
+        self
+    }
+
+    /// Account holding escrowed zkUSD.
+    ///
+    /// The key is not a valid curve point, so no signature can move funds out
+    /// of it; only relayer-attested inbound transfers release them.
+    pub fn escrow_account() -> PublicKey {
+        let mut bytes = [0u8; PUBKEY_LENGTH];
+        bytes[1..20].copy_from_slice(b"zkusd/bridge/escrow");
+        PublicKey::new(bytes)
+    }
+
+    /// Relayer set
+    pub fn relayers(&self) -> &RelayerSet {
+        &self.relayers
+    }
+
+    /// Replace the relayer set
+    pub fn set_relayers(&mut self, relayers: RelayerSet) {
+        self.relayers = relayers;
+    }
+
+    /// Supported chains
+    pub fn chains(&self) -> impl Iterator<Item = ChainId> + '_ {
+        self.chains.iter().copied()
+    }
+
+    /// Check whether `chain` is supported
+    pub fn supports(&self, chain: ChainId) -> bool {
+        self.chains.contains(&chain)
+    }
+
+    /// zkUSD currently locked towards `chain`
+    pub fn locked(&self, chain: ChainId) -> TokenAmount {
+        self.locked.get(&chain).copied().unwrap_or(TokenAmount::ZERO)
+    }
+
+    /// zkUSD locked towards all chains
+    pub fn total_locked(&self) -> TokenAmount {
+        self.locked.values().fold(TokenAmount::ZERO, |acc, a| acc.saturating_add(*a))
+    }
+
+    /// Sequence number the next outbound transfer will get
+    pub fn next_sequence(&self) -> u64 {
+        self.next_sequence
+    }
+
+    /// Check whether an inbound transfer was already released
+    pub fn is_processed(&self, source_chain: ChainId, source_tx: &Hash) -> bool {
+        self.processed.contains(&(source_chain, *source_tx))
+    }
+
+    /// Record an outbound transfer, returning its sequence number.
+    ///
+    /// The caller moves the tokens into [`Self::escrow_account`].
+    pub fn lock(&mut self, dest_chain: ChainId, dest_address: &[u8], amount: TokenAmount) -> Result<u64> {
+        if amount.is_zero() {
+            return Err(Error::ZeroAmount);
+        }
+        if !self.supports(dest_chain) {
+            return Err(invalid("dest_chain", format!("chain {} is not supported", dest_chain)));
+        }
+        if dest_address.is_empty() || dest_address.len() > MAX_BRIDGE_ADDRESS_LENGTH {
+            return Err(invalid(
+                "dest_address",
+                format!("must be between 1 and {} bytes", MAX_BRIDGE_ADDRESS_LENGTH),
+            ));
+        }
+
+        let locked = self.locked(dest_chain).checked_add(amount).ok_or(Error::Overflow {
+            operation: "bridge lock".into(),
+        })?;
+        self.locked.insert(dest_chain, locked);
+
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        Ok(sequence)
+    }
+
+    /// Verify and record an inbound transfer.
+    ///
+    /// The caller moves the tokens out of [`Self::escrow_account`].
+    pub fn release(&mut self, transfer: &InboundTransfer, attestations: &[Attestation]) -> Result<()> {
+        if transfer.amount.is_zero() {
+            return Err(Error::ZeroAmount);
+        }
+        if !self.supports(transfer.source_chain) {
+            return Err(invalid(
+                "source_chain",
+                format!("chain {} is not supported", transfer.source_chain),
+            ));
+        }
+        if self.is_processed(transfer.source_chain, &transfer.source_tx) {
+            return Err(invalid("source_tx", "transfer already released"));
+        }
+
+        self.relayers.verify(&transfer.message_hash(), attestations)?;
+
+        let locked = self.locked(transfer.source_chain);
+        let remaining = locked.checked_sub(transfer.amount).ok_or_else(|| {
+            invalid(
+                "amount",
+                format!("only {} locked towards chain {}", locked, transfer.source_chain),
+            )
+        })?;
+        self.locked.insert(transfer.source_chain, remaining);
+        self.processed.insert((transfer.source_chain, transfer.source_tx));
+        Ok(())
+    }
+}
+
+/// Bridge state saved when an undo log opens; everything but the processed
+/// transfers is small
+#[derive(Debug, Clone)]
+pub struct BridgeMark {
+    relayers: RelayerSet,
+    chains: BTreeSet<ChainId>,
+    locked: BTreeMap<ChainId, TokenAmount>,
+    next_sequence: u64,
+}
+
+impl Undo for Bridge {
+    type Mark = BridgeMark;
+
+    fn begin_undo(&mut self) -> BridgeMark {
+        self.processed.begin_undo();
+        BridgeMark {
+            relayers: self.relayers.clone(),
+            chains: self.chains.clone(),
+            locked: self.locked.clone(),
+            next_sequence: self.next_sequence,
+        }
+    }
+
+    fn commit_undo(&mut self) {
+        self.processed.commit_undo();
+    }
+
+    fn rollback_undo(&mut self, mark: BridgeMark) {
+        self.processed.rollback_undo();
+        self.relayers = mark.relayers;
+        self.chains = mark.chains;
+        self.locked = mark.locked;
+        self.next_sequence = mark.next_sequence;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAIN: ChainId = 42;
+
+    fn setup() -> (Bridge, Vec<KeyPair>) {
+        let relayers: Vec<KeyPair> = (0..3).map(|_| KeyPair::generate()).collect();
+        let set = RelayerSet::new(relayers.iter().map(|k| *k.public_key()).collect(), 2).unwrap();
+        (Bridge::new(set).with_chain(CHAIN), relayers)
+    }
+
+    fn inbound(amount: u64) -> InboundTransfer {
+        InboundTransfer {
+            source_chain: CHAIN,
+            source_tx: Hash::sha256(b"burn"),
+            recipient: *KeyPair::generate().public_key(),
+            amount: TokenAmount::from_cents(amount),
+        }
+    }
+
+    #[test]
+    fn test_relayer_set_validation() {
+        let key = *KeyPair::generate().public_key();
+        assert!(RelayerSet::new(vec![], 1).is_err());
+        assert!(RelayerSet::new(vec![key], 0).is_err());
+        assert!(RelayerSet::new(vec![key], 2).is_err());
+        assert!(RelayerSet::new(vec![key, key], 1).is_err());
+        assert!(RelayerSet::new(vec![key], 1).is_ok());
+    }
+
+    #[test]
+    fn test_lock_and_release() {
+        let (mut bridge, relayers) = setup();
+        assert_eq!(bridge.lock(CHAIN, b"0xdead", TokenAmount::from_cents(10_000)).unwrap(), 1);
+        assert_eq!(bridge.lock(CHAIN, b"0xbeef", TokenAmount::from_cents(5_000)).unwrap(), 2);
+        assert_eq!(bridge.locked(CHAIN).cents(), 15_000);
+
+        let transfer = inbound(12_000);
+        let attestations: Vec<_> = relayers[..2].iter().map(|k| Attestation::sign(&transfer, k)).collect();
+        bridge.release(&transfer, &attestations).unwrap();
+        assert_eq!(bridge.locked(CHAIN).cents(), 3_000);
+        assert!(bridge.is_processed(CHAIN, &transfer.source_tx));
+
+        // Replays are rejected
+        assert!(bridge.release(&transfer, &attestations).is_err());
+    }
+
+    #[test]
+    fn test_release_checks() {
+        let (mut bridge, relayers) = setup();
+        bridge.lock(CHAIN, b"0xdead", TokenAmount::from_cents(10_000)).unwrap();
+        let transfer = inbound(5_000);
+
+        // One attestation, or the same relayer twice, is below threshold
+        let once = Attestation::sign(&transfer, &relayers[0]);
+        assert!(bridge.release(&transfer, std::slice::from_ref(&once)).is_err());
+        assert!(bridge.release(&transfer, &[once.clone(), once.clone()]).is_err());
+
+        // Outsiders and tampered transfers are rejected
+        let outsider = Attestation::sign(&transfer, &KeyPair::generate());
+        let second = Attestation::sign(&transfer, &relayers[1]);
+        assert!(bridge.release(&transfer, &[once.clone(), second.clone(), outsider]).is_err());
+        let inflated = InboundTransfer { amount: TokenAmount::from_cents(6_000), ..transfer.clone() };
+        assert!(bridge.release(&inflated, &[once, second]).is_err());
+
+        // Cannot release more than was locked towards the chain
+        let large = inbound(20_000);
+        let attestations: Vec<_> = relayers.iter().map(|k| Attestation::sign(&large, k)).collect();
+        assert!(bridge.release(&large, &attestations).is_err());
+
+        assert!(bridge.lock(7, b"0xdead", TokenAmount::from_cents(1)).is_err());
+        assert!(bridge.lock(CHAIN, &[], TokenAmount::from_cents(1)).is_err());
+    }
+}
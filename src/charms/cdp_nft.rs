@@ -0,0 +1,110 @@
+//! CDPs as transferable Charm NFTs.
+//!
+//! In NFT mode every CDP is represented by a Charm whose id is derived from
+//! the CDP id. Whoever holds the charm controls the CDP: the executor checks
+//! the caster against the charm's holder instead of the CDP's stored owner
+//! key, and transferring the charm hands the CDP to the recipient.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::charms::token::CharmId;
+use crate::error::{Error, Result};
+use crate::utils::crypto::{CDPId, Hash, PublicKey};
+
+/// Domain separator for CDP charm ids
+const CDP_CHARM_DOMAIN: &[u8] = b"ZKUSD_CDP_NFT";
+
+/// Charm id representing a CDP
+pub fn cdp_charm_id(cdp_id: &CDPId) -> CharmId {
+    let mut data = Vec::with_capacity(CDP_CHARM_DOMAIN.len() + 32);
+    data.extend_from_slice(CDP_CHARM_DOMAIN);
+    data.extend_from_slice(cdp_id.as_bytes());
+    CharmId::new(*Hash::sha256(&data).as_bytes())
+}
+
+/// Holders of CDP charms
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CdpNftRegistry {
+    holders: HashMap<CDPId, PublicKey>,
+}
+
+impl CdpNftRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self { Self::default() }
+
+    /// Mint the charm for `cdp_id` to `holder`
+    pub fn mint(&mut self, cdp_id: CDPId, holder: PublicKey) -> Result<CharmId> {
+        if self.holders.contains_key(&cdp_id) {
+            return Err(Error::InvalidParameter {
+                name: "cdp_id".into(),
+                reason: format!("CDP {} already has a charm", cdp_id.to_hex()),
+            });
+        }
+        self.holders.insert(cdp_id, holder);
+        Ok(cdp_charm_id(&cdp_id))
+    }
+
+    /// Move the charm for `cdp_id` from `from` to `to`
+    pub fn transfer(&mut self, cdp_id: &CDPId, from: &PublicKey, to: PublicKey) -> Result<()> {
+        self.verify_holder(cdp_id, from)?;
+        self.holders.insert(*cdp_id, to);
+        Ok(())
+    }
+
+    /// Destroy the charm, e.g. when the CDP is closed
+    pub fn burn(&mut self, cdp_id: &CDPId) -> Option<PublicKey> {
+        self.holders.remove(cdp_id)
+    }
+
+    /// Current holder of the charm for `cdp_id`
+    pub fn holder_of(&self, cdp_id: &CDPId) -> Option<&PublicKey> {
+        self.holders.get(cdp_id)
+    }
+
+    /// Check that `caster` holds the charm for `cdp_id`
+    pub fn verify_holder(&self, cdp_id: &CDPId, caster: &PublicKey) -> Result<()> {
+        match self.holders.get(cdp_id) {
+            Some(holder) if holder == caster => Ok(()),
+            Some(_) => Err(Error::Unauthorized("caster does not hold the CDP charm".into())),
+            None => Err(Error::Unauthorized(format!("CDP {} has no charm", cdp_id.to_hex()))),
+        }
+    }
+
+    /// All charms by CDP
+    pub fn all_holders(&self) -> &HashMap<CDPId, PublicKey> {
+        &self.holders
+    }
+
+    /// Number of minted charms
+    pub fn len(&self) -> usize { self.holders.len() }
+
+    /// Whether no charms are minted
+    pub fn is_empty(&self) -> bool { self.holders.is_empty() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::crypto::KeyPair;
+
+    #[test]
+    fn test_cdp_nft_registry() {
+        let alice = *KeyPair::generate().public_key();
+        let bob = *KeyPair::generate().public_key();
+        let cdp_id = CDPId::generate(&alice, 1);
+
+        let mut nfts = CdpNftRegistry::new();
+        assert_eq!(nfts.mint(cdp_id, alice).unwrap(), cdp_charm_id(&cdp_id));
+        assert!(nfts.mint(cdp_id, bob).is_err());
+        assert!(!cdp_charm_id(&cdp_id).is_zkusd());
+
+        assert!(nfts.transfer(&cdp_id, &bob, bob).is_err());
+        nfts.transfer(&cdp_id, &alice, bob).unwrap();
+        assert!(nfts.verify_holder(&cdp_id, &alice).is_err());
+        assert!(nfts.verify_holder(&cdp_id, &bob).is_ok());
+
+        assert_eq!(nfts.burn(&cdp_id), Some(bob));
+        assert!(nfts.verify_holder(&cdp_id, &bob).is_err());
+    }
+}
@@ -0,0 +1,188 @@
+//! State commitment for the Charms adapter.
+//!
+//! A Merkle root over the adapter's actual state: every CDP, token balance,
+//! allowance, stability pool deposit and CDP charm holder, plus the token
+//! supply and the pool and vault totals. Each entry is a leaf hashing its key
+//! together with the canonical encoding of its value, and leaves are ordered
+//! by key, so two adapters holding the same state produce the same root
+//! however they got there.
+//!
+//! Leaves are cached: a sync only replaces leaves whose value changed, and
+//! the root is recomputed only when a leaf was added, changed or removed.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::core::cdp::CDPId;
+use crate::utils::crypto::{merkle_root, Hash, PublicKey};
+
+/// Key prefix of CDP leaves
+const CDP_TAG: u8 = 0x01;
+/// Key prefix of balance leaves
+const BALANCE_TAG: u8 = 0x02;
+/// Key prefix of allowance leaves
+const ALLOWANCE_TAG: u8 = 0x03;
+/// Key prefix of stability pool deposit leaves
+const DEPOSIT_TAG: u8 = 0x04;
+/// Key prefix of aggregate leaves (supply, pool, vault)
+const GLOBAL_TAG: u8 = 0x05;
+/// Key prefix of CDP charm holder leaves
+const CDP_NFT_TAG: u8 = 0x06;
+
+/// Leaf key of a CDP
+pub fn cdp_key(id: &CDPId) -> Vec<u8> {
+    [&[CDP_TAG][..], id.as_bytes()].concat()
+}
+
+/// Leaf key of a token balance
+pub fn balance_key(owner: &PublicKey) -> Vec<u8> {
+    [&[BALANCE_TAG][..], owner.as_bytes()].concat()
+}
+
+/// Leaf key of an allowance
+pub fn allowance_key(owner: &PublicKey, spender: &PublicKey) -> Vec<u8> {
+    [&[ALLOWANCE_TAG][..], owner.as_bytes(), spender.as_bytes()].concat()
+}
+
+/// Leaf key of a stability pool deposit
+pub fn deposit_key(owner: &PublicKey) -> Vec<u8> {
+    [&[DEPOSIT_TAG][..], owner.as_bytes()].concat()
+}
+
+/// Leaf key of a CDP charm holder
+pub fn cdp_nft_key(id: &CDPId) -> Vec<u8> {
+    [&[CDP_NFT_TAG][..], id.as_bytes()].concat()
+}
+
+/// Leaf key of an aggregate value
+pub fn global_key(name: &str) -> Vec<u8> {
+    [&[GLOBAL_TAG][..], name.as_bytes()].concat()
+}
+
+/// Hash binding a key to the canonical encoding of its value
+fn leaf_hash(key: &[u8], value: &[u8]) -> Hash {
+    let mut data = Vec::with_capacity(8 + key.len() + value.len());
+    data.extend_from_slice(&(key.len() as u64).to_be_bytes());
+    data.extend_from_slice(key);
+    data.extend_from_slice(value);
+    Hash::sha256(&data)
+}
+
+/// Cached Merkle commitment over key-ordered leaves
+#[derive(Debug, Clone, Default)]
+pub struct StateCommitment {
+    /// Leaf hashes by key
+    leaves: BTreeMap<Vec<u8>, Hash>,
+    /// Root over `leaves`, `None` when a leaf changed since it was computed
+    root: Option<Hash>,
+}
+
+impl StateCommitment {
+    /// Create an empty commitment
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the value of a leaf, returning whether the leaf changed
+    pub fn set(&mut self, key: Vec<u8>, value: &[u8]) -> bool {
+        let leaf = leaf_hash(&key, value);
+        if self.leaves.get(&key) == Some(&leaf) {
+            return false;
+        }
+        self.leaves.insert(key, leaf);
+        self.root = None;
+        true
+    }
+
+    /// Remove a leaf, returning whether it existed
+    pub fn remove(&mut self, key: &[u8]) -> bool {
+        let removed = self.leaves.remove(key).is_some();
+        if removed {
+            self.root = None;
+        }
+        removed
+    }
+
+    /// Make `entries` the complete set of leaves, returning how many leaves
+    /// were added, changed or removed
+    pub fn sync(&mut self, entries: impl IntoIterator<Item = (Vec<u8>, Vec<u8>)>) -> usize {
+        let mut changed = 0;
+        let mut seen = BTreeSet::new();
+        for (key, value) in entries {
+            if self.set(key.clone(), &value) {
+                changed += 1;
+            }
+            seen.insert(key);
+        }
+
+        let stale: Vec<Vec<u8>> = self.leaves.keys().filter(|key| !seen.contains(*key)).cloned().collect();
+        for key in stale {
+            self.remove(&key);
+            changed += 1;
+        }
+        changed
+    }
+
+    /// Leaf hash stored under `key`
+    pub fn leaf(&self, key: &[u8]) -> Option<Hash> {
+        self.leaves.get(key).copied()
+    }
+
+    /// Number of leaves
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Whether there are no leaves
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Merkle root over the leaves in key order
+    pub fn root(&mut self) -> Hash {
+        match self.root {
+            Some(root) => root,
+            None => {
+                let leaves: Vec<Hash> = self.leaves.values().copied().collect();
+                let root = merkle_root(&leaves);
+                self.root = Some(root);
+                root
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::crypto::KeyPair;
+
+    #[test]
+    fn test_state_commitment() {
+        let alice = *KeyPair::generate().public_key();
+        let bob = *KeyPair::generate().public_key();
+
+        let mut forward = StateCommitment::new();
+        forward.set(balance_key(&alice), &100u64.to_be_bytes());
+        forward.set(balance_key(&bob), &50u64.to_be_bytes());
+
+        // Insertion order does not matter
+        let mut reverse = StateCommitment::new();
+        reverse.set(balance_key(&bob), &50u64.to_be_bytes());
+        reverse.set(balance_key(&alice), &100u64.to_be_bytes());
+        let root = forward.root();
+        assert_eq!(root, reverse.root());
+
+        // Unchanged values leave the cached root in place
+        assert!(!forward.set(balance_key(&alice), &100u64.to_be_bytes()));
+        assert_eq!(forward.root(), root);
+
+        let changed = forward.sync(vec![
+            (balance_key(&alice), 90u64.to_be_bytes().to_vec()),
+            (allowance_key(&alice, &bob), 10u64.to_be_bytes().to_vec()),
+        ]);
+        assert_eq!(changed, 3);
+        assert_eq!(forward.len(), 2);
+        assert!(forward.leaf(&balance_key(&bob)).is_none());
+        assert_ne!(forward.root(), root);
+    }
+}
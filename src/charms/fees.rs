@@ -0,0 +1,176 @@
+//! Protocol fee schedules and dust handling for Charm spells.
+
+use bitcoin::{ScriptBuf, Transaction};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::btc::utxo::{Utxo, UtxoSet};
+use crate::charms::spells::ZkUSDSpellType;
+use crate::error::{Error, Result};
+use crate::utils::constants::DUST_LIMIT_SATS;
+use crate::utils::math::calculate_fee_bps;
+
+/// Fee charged on a spell's BTC payout: flat amount plus basis points
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeSchedule {
+    /// Flat fee (sats)
+    pub flat_sats: u64,
+    /// Proportional fee (basis points of the payout)
+    pub bps: u64,
+}
+
+impl FeeSchedule {
+    /// Create a fee schedule
+    pub fn new(flat_sats: u64, bps: u64) -> Self { Self { flat_sats, bps } }
+
+    /// Fee owed on a payout of `payout_sats`
+    pub fn fee_for(&self, payout_sats: u64) -> Result<u64> {
+        let proportional = calculate_fee_bps(payout_sats, self.bps)?;
+        self.flat_sats.checked_add(proportional).ok_or(Error::Overflow {
+            operation: "spell fee".into(),
+        })
+    }
+}
+
+/// Outcome of charging a fee against a payout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeAssessment {
+    /// Protocol fee (sats)
+    pub fee_sats: u64,
+    /// Amount left for the recipient (sats)
+    pub net_sats: u64,
+}
+
+/// Per-spell-type fee configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpellFeeConfig {
+    /// Fee schedule per spell type; missing entries are free
+    pub schedules: HashMap<ZkUSDSpellType, FeeSchedule>,
+    /// Outputs below this value are rejected (sats)
+    pub dust_limit_sats: u64,
+}
+
+impl Default for SpellFeeConfig {
+    fn default() -> Self {
+        Self { schedules: HashMap::new(), dust_limit_sats: DUST_LIMIT_SATS }
+    }
+}
+
+impl SpellFeeConfig {
+    /// Set the schedule for a spell type
+    pub fn with_schedule(mut self, spell_type: ZkUSDSpellType, schedule: FeeSchedule) -> Self {
+        self.schedules.insert(spell_type, schedule);
+        self
+    }
+
+    /// Set the dust threshold
+    pub fn with_dust_limit(mut self, dust_limit_sats: u64) -> Self {
+        self.dust_limit_sats = dust_limit_sats;
+        self
+    }
+
+    /// Get the schedule for a spell type
+    pub fn schedule_for(&self, spell_type: ZkUSDSpellType) -> FeeSchedule {
+        self.schedules.get(&spell_type).copied().unwrap_or_default()
+    }
+
+    /// Charge the fee for `spell_type` against a payout, rejecting dust
+    pub fn assess(&self, spell_type: ZkUSDSpellType, payout_sats: u64) -> Result<FeeAssessment> {
+        let fee_sats = self.schedule_for(spell_type).fee_for(payout_sats)?;
+        let net_sats = payout_sats.saturating_sub(fee_sats);
+        self.check_dust(net_sats)?;
+        Ok(FeeAssessment { fee_sats, net_sats })
+    }
+
+    /// Reject outputs below the dust threshold
+    pub fn check_dust(&self, amount_sats: u64) -> Result<()> {
+        if amount_sats < self.dust_limit_sats {
+            return Err(Error::DustOutput { amount: amount_sats, threshold: self.dust_limit_sats });
+        }
+        Ok(())
+    }
+
+    /// Whether a fee is large enough to get its own output
+    pub fn is_spendable_fee(&self, fee_sats: u64) -> bool {
+        fee_sats > 0 && fee_sats >= self.dust_limit_sats
+    }
+}
+
+/// Protocol fee collection account
+#[derive(Debug, Default)]
+pub struct ProtocolFeeAccount {
+    /// Script receiving protocol fee outputs
+    pub fee_script: ScriptBuf,
+    /// Total fees charged (sats)
+    accrued_sats: u64,
+    /// Confirmed-or-pending fee outputs
+    utxos: UtxoSet,
+}
+
+impl ProtocolFeeAccount {
+    /// Create a fee account paying to `fee_script`
+    pub fn new(fee_script: ScriptBuf) -> Self {
+        Self { fee_script, accrued_sats: 0, utxos: UtxoSet::new() }
+    }
+
+    /// Record a charged fee
+    pub fn accrue(&mut self, fee_sats: u64) {
+        self.accrued_sats = self.accrued_sats.saturating_add(fee_sats);
+    }
+
+    /// Total fees charged (sats)
+    pub fn accrued_sats(&self) -> u64 { self.accrued_sats }
+
+    /// Track fee outputs of a finalized payout transaction, returning the value tracked
+    pub fn track_payout(&mut self, tx: &Transaction) -> u64 {
+        let txid = tx.compute_txid();
+        let mut tracked = 0u64;
+        for (vout, out) in tx.output.iter().enumerate() {
+            if out.script_pubkey == self.fee_script {
+                let value = out.value.to_sat();
+                self.utxos.add(Utxo::new(txid, vout as u32, value, out.script_pubkey.clone()));
+                tracked += value;
+            }
+        }
+        tracked
+    }
+
+    /// Fee UTXOs tracked so far
+    pub fn utxos(&self) -> &UtxoSet { &self.utxos }
+
+    /// Mutable access to tracked fee UTXOs
+    pub fn utxos_mut(&mut self) -> &mut UtxoSet { &mut self.utxos }
+
+    /// Total value of tracked fee UTXOs (sats)
+    pub fn tracked_sats(&self) -> u64 { self.utxos.total_value() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_schedule() {
+        let schedule = FeeSchedule::new(1_000, 50);
+        assert_eq!(schedule.fee_for(1_000_000).unwrap(), 1_000 + 5_000);
+        assert_eq!(FeeSchedule::default().fee_for(1_000_000).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_assess_rejects_dust() {
+        let config = SpellFeeConfig::default()
+            .with_schedule(ZkUSDSpellType::Redeem, FeeSchedule::new(1_000, 0));
+
+        let assessment = config.assess(ZkUSDSpellType::Redeem, 10_000).unwrap();
+        assert_eq!(assessment, FeeAssessment { fee_sats: 1_000, net_sats: 9_000 });
+
+        // Net payout of 500 sats falls below the 546 sat default
+        assert!(matches!(
+            config.assess(ZkUSDSpellType::Redeem, 1_500),
+            Err(Error::DustOutput { amount: 500, .. })
+        ));
+
+        // Unscheduled spell types are free
+        assert_eq!(config.assess(ZkUSDSpellType::ClaimGains, 10_000).unwrap().fee_sats, 0);
+    }
+}
@@ -1,11 +1,28 @@
 //! Token metadata for Charms integration.
+//!
+//! Wallets and explorers identify a Charm by its asset id and display it from
+//! the registered metadata. The metadata has a canonical binary encoding so it
+//! can be embedded in spells and committed to by hash.
 
 use serde::{Deserialize, Serialize};
+use crate::error::{Error, Result};
+use crate::utils::codec;
+use crate::utils::constants::{MAX_METADATA_SIZE, ZKUSD_DECIMALS};
 use crate::utils::crypto::{Hash, PublicKey};
+use crate::charms::spells::CharmSpell;
 use crate::charms::token::CharmId;
 
+/// Maximum length of a token name
+const MAX_NAME_LEN: usize = 64;
+/// Maximum length of a token symbol
+const MAX_SYMBOL_LEN: usize = 16;
+/// Maximum length of an icon or website URI
+const MAX_URI_LEN: usize = 512;
+/// Maximum decimal places
+const MAX_DECIMALS: u8 = 18;
+
 /// On-chain token metadata
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CharmMetadata {
     pub charm_id: CharmId,
     pub name: String,
@@ -15,6 +32,12 @@ pub struct CharmMetadata {
     pub website: Option<String>,
     pub creator: PublicKey,
     pub created_at: u64,
+    /// Token icon (`https://`, `ipfs://` or `data:image/` URI)
+    #[serde(default)]
+    pub icon_uri: Option<String>,
+    /// Hash of the verification key of the Charms app governing the token
+    #[serde(default)]
+    pub app_vk_hash: Option<Hash>,
 }
 
 impl CharmMetadata {
@@ -24,20 +47,97 @@ impl CharmMetadata {
             charm_id: CharmId::ZKUSD,
             name: "zkUSD".to_string(),
             symbol: "zkUSD".to_string(),
-            decimals: 2,
+            decimals: ZKUSD_DECIMALS,
             description: "Decentralized stablecoin backed by Bitcoin on BitcoinOS".to_string(),
             website: Some("https://zkusd.io".to_string()),
             creator,
             created_at,
+            icon_uri: None,
+            app_vk_hash: None,
         }
     }
 
-    /// Compute hash
+    /// Set the icon URI
+    pub fn with_icon_uri(mut self, uri: impl Into<String>) -> Self {
+        self.icon_uri = Some(uri.into());
+        self
+    }
+
+    /// Set the app verification key hash
+    pub fn with_app_vk_hash(mut self, vk_hash: Hash) -> Self {
+        self.app_vk_hash = Some(vk_hash);
+        self
+    }
+
+    /// Check the metadata is displayable and consistent with its asset id
+    pub fn validate(&self) -> Result<()> {
+        check_text("name", &self.name, MAX_NAME_LEN)?;
+        check_text("symbol", &self.symbol, MAX_SYMBOL_LEN)?;
+
+        if self.decimals > MAX_DECIMALS {
+            return Err(invalid("decimals", format!("{} exceeds maximum of {}", self.decimals, MAX_DECIMALS)));
+        }
+        if self.charm_id.is_zkusd() && self.decimals != ZKUSD_DECIMALS {
+            return Err(invalid("decimals", format!("zkUSD uses {} decimals, not {}", ZKUSD_DECIMALS, self.decimals)));
+        }
+
+        if let Some(website) = &self.website {
+            check_uri("website", website, &["https://"])?;
+        }
+        if let Some(icon) = &self.icon_uri {
+            check_uri("icon_uri", icon, &["https://", "ipfs://", "data:image/"])?;
+        }
+        Ok(())
+    }
+
+    /// Canonical encoding, for inclusion in spells
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>> {
+        codec::encode(self)
+    }
+
+    /// Decode metadata from a spell, bounded to [`MAX_METADATA_SIZE`]
+    pub fn from_canonical_bytes(data: &[u8]) -> Result<Self> {
+        let metadata: Self = codec::decode_bounded(data, MAX_METADATA_SIZE)?;
+        metadata.validate()?;
+        Ok(metadata)
+    }
+
+    /// Compute hash over the canonical encoding
     pub fn hash(&self) -> Hash {
-        Hash::sha256(&bincode::serialize(self).unwrap_or_default())
+        Hash::sha256(&self.canonical_bytes().unwrap_or_default())
     }
 }
 
+fn invalid(name: &str, reason: impl Into<String>) -> Error {
+    Error::InvalidParameter { name: name.into(), reason: reason.into() }
+}
+
+fn check_text(name: &str, value: &str, max_len: usize) -> Result<()> {
+    if value.trim().is_empty() {
+        return Err(invalid(name, "must not be empty"));
+    }
+    if value.len() > max_len {
+        return Err(invalid(name, format!("{} bytes exceeds limit of {}", value.len(), max_len)));
+    }
+    if value.chars().any(char::is_control) {
+        return Err(invalid(name, "contains control characters"));
+    }
+    Ok(())
+}
+
+fn check_uri(name: &str, uri: &str, schemes: &[&str]) -> Result<()> {
+    if uri.len() > MAX_URI_LEN {
+        return Err(invalid(name, format!("{} bytes exceeds limit of {}", uri.len(), MAX_URI_LEN)));
+    }
+    if uri.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(invalid(name, "contains whitespace or control characters"));
+    }
+    if !schemes.iter().any(|scheme| uri.starts_with(scheme) && uri.len() > scheme.len()) {
+        return Err(invalid(name, format!("must start with one of {:?}", schemes)));
+    }
+    Ok(())
+}
+
 /// Registry of token metadata
 #[derive(Debug, Clone, Default)]
 pub struct MetadataRegistry {
@@ -50,25 +150,43 @@ impl MetadataRegistry {
 
     /// Create with zkUSD pre-registered
     pub fn with_zkusd(creator: PublicKey, created_at: u64) -> Self {
+        let metadata = CharmMetadata::zkusd(creator, created_at);
         let mut reg = Self::new();
-        reg.register(CharmMetadata::zkusd(creator, created_at));
+        reg.entries.insert(metadata.charm_id, metadata);
         reg
     }
 
-    /// Register metadata
-    pub fn register(&mut self, metadata: CharmMetadata) {
+    /// Register or replace metadata after validating it
+    pub fn register(&mut self, metadata: CharmMetadata) -> Result<()> {
+        metadata.validate()?;
         self.entries.insert(metadata.charm_id, metadata);
+        Ok(())
     }
 
     /// Get metadata
     pub fn get(&self, charm_id: &CharmId) -> Option<&CharmMetadata> {
         self.entries.get(charm_id)
     }
+
+    /// Check that a spell targets `asset` and that `asset` is registered
+    pub fn verify_spell(&self, spell: &CharmSpell, asset: &CharmId) -> Result<()> {
+        if spell.charm_id != *asset {
+            return Err(invalid(
+                "charm_id",
+                format!("spell targets {}, expected {}", spell.charm_id.to_hex(), asset.to_hex()),
+            ));
+        }
+        if !self.entries.contains_key(asset) {
+            return Err(invalid("charm_id", format!("asset {} is not registered", asset.to_hex())));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::charms::spells::SpellBuilder;
     use crate::utils::crypto::KeyPair;
 
     #[test]
@@ -76,5 +194,32 @@ mod tests {
         let kp = KeyPair::generate();
         let meta = CharmMetadata::zkusd(*kp.public_key(), 100);
         assert_eq!(meta.name, "zkUSD");
+        assert!(meta.validate().is_ok());
+    }
+
+    #[test]
+    fn test_metadata_canonical_roundtrip_and_validation() {
+        let kp = KeyPair::generate();
+        let meta = CharmMetadata::zkusd(*kp.public_key(), 100)
+            .with_icon_uri("ipfs://bafyzkusdicon")
+            .with_app_vk_hash(Hash::sha256(b"app vk"));
+
+        let bytes = meta.canonical_bytes().unwrap();
+        assert_eq!(CharmMetadata::from_canonical_bytes(&bytes).unwrap(), meta);
+        assert!(CharmMetadata::from_canonical_bytes(&[bytes.as_slice(), &[0]].concat()).is_err());
+
+        let mut registry = MetadataRegistry::new();
+        assert!(registry.register(meta.clone().with_icon_uri("javascript:alert(1)")).is_err());
+        assert!(registry.register(CharmMetadata { decimals: 8, ..meta.clone() }).is_err());
+        assert!(registry.register(CharmMetadata { symbol: String::new(), ..meta.clone() }).is_err());
+        registry.register(meta).unwrap();
+
+        let spell = SpellBuilder::transfer(*kp.public_key(), 100).build_and_sign(&kp);
+        assert!(registry.verify_spell(&spell, &CharmId::ZKUSD).is_ok());
+
+        let mut foreign = spell.clone();
+        foreign.charm_id = CharmId::new([9; 32]);
+        assert!(registry.verify_spell(&foreign, &CharmId::ZKUSD).is_err());
+        assert!(MetadataRegistry::new().verify_spell(&spell, &CharmId::ZKUSD).is_err());
     }
 }
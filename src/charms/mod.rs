@@ -5,11 +5,19 @@
 //! implements this interface to be compatible with the ecosystem.
 
 pub mod adapter;
+pub mod bridge;
+pub mod cdp_nft;
+pub mod commitment;
+pub mod fees;
 pub mod metadata;
 pub mod spells;
 pub mod token;
 
 pub use adapter::*;
+pub use bridge::*;
+pub use cdp_nft::*;
+pub use commitment::*;
+pub use fees::*;
 pub use metadata::*;
 pub use spells::*;
 pub use token::*;
@@ -2,11 +2,15 @@
 
 use serde::{Deserialize, Serialize};
 use crate::error::{Error, Result};
-use crate::utils::crypto::{Hash, PublicKey, Signature};
+use crate::utils::crypto::{CDPId, Hash, PublicKey, Signature};
 use crate::charms::token::CharmId;
+use crate::utils::codec;
+use crate::utils::constants::{MAX_SPELL_BUNDLE_LEN, MAX_SPELL_BUNDLE_SIZE, MAX_SPELL_DATA_SIZE, MAX_SPELL_SIZE};
+use crate::zkp::{ProofBatch, ZKProof};
 
 /// Spell type identifier
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[repr(u8)]
 pub enum ZkUSDSpellType {
     Transfer = 1,
@@ -17,6 +21,8 @@ pub enum ZkUSDSpellType {
     WithdrawCollateral = 13,
     MintDebt = 14,
     RepayDebt = 15,
+    MintCdpNft = 16,
+    TransferCdpNft = 17,
     Liquidate = 20,
     Redeem = 21,
     StabilityDeposit = 30,
@@ -31,6 +37,7 @@ impl From<u8> for ZkUSDSpellType {
             10 => Self::OpenCDP, 11 => Self::CloseCDP,
             12 => Self::DepositCollateral, 13 => Self::WithdrawCollateral,
             14 => Self::MintDebt, 15 => Self::RepayDebt,
+            16 => Self::MintCdpNft, 17 => Self::TransferCdpNft,
             20 => Self::Liquidate, 21 => Self::Redeem,
             30 => Self::StabilityDeposit, 31 => Self::StabilityWithdraw, 32 => Self::ClaimGains,
             _ => Self::Transfer,
@@ -40,6 +47,7 @@ impl From<u8> for ZkUSDSpellType {
 
 /// Base spell structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct CharmSpell {
     pub spell_type: ZkUSDSpellType,
     pub charm_id: CharmId,
@@ -80,8 +88,32 @@ impl CharmSpell {
     /// Check if expired
     pub fn is_expired(&self, current_block: u64) -> bool { current_block > self.deadline }
 
+    /// Encode for transmission
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        codec::encode(self)
+    }
+
+    /// Decode an untrusted spell, bounded to [`MAX_SPELL_SIZE`]
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let spell: Self = codec::decode_bounded(data, MAX_SPELL_SIZE)?;
+        spell.check_size()?;
+        Ok(spell)
+    }
+
+    /// Reject parameter payloads over [`MAX_SPELL_DATA_SIZE`]
+    fn check_size(&self) -> Result<()> {
+        if self.data.len() > MAX_SPELL_DATA_SIZE {
+            return Err(Error::InvalidParameter {
+                name: "data".into(),
+                reason: format!("{} bytes exceeds limit of {}", self.data.len(), MAX_SPELL_DATA_SIZE),
+            });
+        }
+        Ok(())
+    }
+
     /// Validate spell
     pub fn validate(&self, current_block: u64) -> Result<()> {
+        self.check_size()?;
         self.verify_signature()?;
         if self.is_expired(current_block) {
             return Err(Error::InvalidParameter { name: "deadline".into(), reason: "Spell expired".into() });
@@ -92,6 +124,7 @@ impl CharmSpell {
 
 /// Transfer parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct TransferParams {
     pub to: PublicKey,
     pub amount: u64,
@@ -100,7 +133,73 @@ pub struct TransferParams {
 impl TransferParams {
     pub fn encode(&self) -> Vec<u8> { bincode::serialize(self).unwrap_or_default() }
     pub fn decode(data: &[u8]) -> Result<Self> {
-        bincode::deserialize(data).map_err(|e| Error::Serialization(e.to_string()))
+        codec::decode_bounded(data, MAX_SPELL_DATA_SIZE)
+    }
+}
+
+/// Redemption parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct RedeemParams {
+    /// zkUSD amount to redeem (cents)
+    pub amount: u64,
+    /// Maximum acceptable redemption fee (basis points)
+    pub max_fee_bps: u64,
+    /// Bitcoin script receiving the redeemed collateral
+    pub payout_script: Vec<u8>,
+}
+
+impl RedeemParams {
+    pub fn encode(&self) -> Vec<u8> { bincode::serialize(self).unwrap_or_default() }
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        codec::decode_bounded(data, MAX_SPELL_DATA_SIZE)
+    }
+}
+
+/// Stability pool gains claim parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct ClaimGainsParams {
+    /// Bitcoin script receiving the claimed collateral
+    pub payout_script: Vec<u8>,
+}
+
+impl ClaimGainsParams {
+    pub fn encode(&self) -> Vec<u8> { bincode::serialize(self).unwrap_or_default() }
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        codec::decode_bounded(data, MAX_SPELL_DATA_SIZE)
+    }
+}
+
+/// CDP charm mint parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct MintCdpNftParams {
+    /// CDP to represent
+    pub cdp_id: CDPId,
+}
+
+impl MintCdpNftParams {
+    pub fn encode(&self) -> Vec<u8> { bincode::serialize(self).unwrap_or_default() }
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        codec::decode_bounded(data, MAX_SPELL_DATA_SIZE)
+    }
+}
+
+/// CDP charm transfer parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct TransferCdpNftParams {
+    /// CDP whose charm is transferred
+    pub cdp_id: CDPId,
+    /// New holder, who takes control of the CDP
+    pub to: PublicKey,
+}
+
+impl TransferCdpNftParams {
+    pub fn encode(&self) -> Vec<u8> { bincode::serialize(self).unwrap_or_default() }
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        codec::decode_bounded(data, MAX_SPELL_DATA_SIZE)
     }
 }
 
@@ -113,17 +212,161 @@ pub struct SpellResult {
     pub error: Option<String>,
     pub block_height: u64,
     pub gas_used: u64,
+    /// Serialized ZK proof of the state transition, if one was generated
+    pub proof: Option<Vec<u8>>,
+    /// Circuit identifier the proof must be verified against
+    pub verifier_key_id: Option<String>,
+    /// Commitment to the executor's state after the spell, if it tracks one
+    #[serde(default)]
+    pub state_root: Option<Hash>,
 }
 
 impl SpellResult {
     pub fn success(spell_hash: Hash, data: Vec<u8>, block_height: u64, gas_used: u64) -> Self {
-        Self { success: true, spell_hash, data, error: None, block_height, gas_used }
+        Self { success: true, spell_hash, data, error: None, block_height, gas_used, proof: None, verifier_key_id: None, state_root: None }
     }
     pub fn failure(spell_hash: Hash, error: impl Into<String>, block_height: u64) -> Self {
-        Self { success: false, spell_hash, data: Vec::new(), error: Some(error.into()), block_height, gas_used: 0 }
+        Self { success: false, spell_hash, data: Vec::new(), error: Some(error.into()), block_height, gas_used: 0, proof: None, verifier_key_id: None, state_root: None }
+    }
+    pub fn with_proof(mut self, proof: &ZKProof) -> Self {
+        self.proof = Some(proof.proof_data.clone());
+        self.verifier_key_id = Some(proof.circuit_id.clone());
+        self
+    }
+    pub fn with_state_root(mut self, state_root: Hash) -> Self {
+        self.state_root = Some(state_root);
+        self
     }
 }
 
+/// Spells executed together in one Charms transaction
+///
+/// A bundle is all-or-nothing: the executor applies its spells in order and
+/// rolls every one of them back if any fails. Collateral payouts share a
+/// single Bitcoin anchor transaction whose OP_RETURN commits to the bundle
+/// hash, and the spells' proofs are aggregated into one batch.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpellBundle {
+    spells: Vec<CharmSpell>,
+}
+
+impl SpellBundle {
+    /// Create an empty bundle
+    pub fn new() -> Self { Self::default() }
+
+    /// Create a bundle from spells, in execution order
+    pub fn from_spells(spells: Vec<CharmSpell>) -> Result<Self> {
+        let mut bundle = Self::new();
+        for spell in spells {
+            bundle.push(spell)?;
+        }
+        Ok(bundle)
+    }
+
+    /// Append a spell, up to [`MAX_SPELL_BUNDLE_LEN`]
+    pub fn push(&mut self, spell: CharmSpell) -> Result<()> {
+        if self.spells.len() >= MAX_SPELL_BUNDLE_LEN {
+            return Err(Error::InvalidParameter {
+                name: "bundle".into(),
+                reason: format!("bundle is limited to {} spells", MAX_SPELL_BUNDLE_LEN),
+            });
+        }
+        self.spells.push(spell);
+        Ok(())
+    }
+
+    /// Spells in execution order
+    pub fn spells(&self) -> &[CharmSpell] { &self.spells }
+
+    /// Take the spells out of the bundle
+    pub fn into_spells(self) -> Vec<CharmSpell> { self.spells }
+
+    /// Number of spells
+    pub fn len(&self) -> usize { self.spells.len() }
+
+    /// Whether the bundle has no spells
+    pub fn is_empty(&self) -> bool { self.spells.is_empty() }
+
+    /// Commitment to the spells and their order
+    pub fn hash(&self) -> Hash {
+        let mut msg = Vec::with_capacity(self.spells.len() * 32);
+        for spell in &self.spells {
+            msg.extend_from_slice(spell.hash().as_bytes());
+        }
+        Hash::sha256(&msg)
+    }
+
+    /// Encode for transmission
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        codec::encode(self)
+    }
+
+    /// Decode an untrusted bundle, bounded to [`MAX_SPELL_BUNDLE_SIZE`]
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let bundle: Self = codec::decode_bounded(data, MAX_SPELL_BUNDLE_SIZE)?;
+        bundle.check_limits()?;
+        for spell in &bundle.spells {
+            spell.check_size()?;
+        }
+        Ok(bundle)
+    }
+
+    /// Reject empty or oversized bundles
+    fn check_limits(&self) -> Result<()> {
+        if self.spells.is_empty() {
+            return Err(Error::InvalidParameter { name: "bundle".into(), reason: "bundle is empty".into() });
+        }
+        if self.spells.len() > MAX_SPELL_BUNDLE_LEN {
+            return Err(Error::InvalidParameter {
+                name: "bundle".into(),
+                reason: format!("{} spells exceeds limit of {}", self.spells.len(), MAX_SPELL_BUNDLE_LEN),
+            });
+        }
+        Ok(())
+    }
+
+    /// Validate every spell and check the spells can share a transaction:
+    /// they must target the same asset and none may appear twice
+    pub fn validate(&self, current_block: u64) -> Result<()> {
+        self.check_limits()?;
+
+        let charm_id = self.spells[0].charm_id;
+        let mut seen = std::collections::HashSet::with_capacity(self.spells.len());
+        for spell in &self.spells {
+            spell.validate(current_block)?;
+            if spell.charm_id != charm_id {
+                return Err(Error::InvalidParameter {
+                    name: "charm_id".into(),
+                    reason: "bundled spells must target the same asset".into(),
+                });
+            }
+            if !seen.insert(spell.hash()) {
+                return Err(Error::InvalidParameter {
+                    name: "bundle".into(),
+                    reason: format!("spell {} appears twice", spell.hash().to_hex()),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Result of executing a spell bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleResult {
+    /// Hash of the executed bundle
+    pub bundle_hash: Hash,
+    /// Per-spell results, in execution order
+    pub results: Vec<SpellResult>,
+    /// Consensus-encoded unfunded anchor transaction carrying every payout
+    pub anchor_tx: Vec<u8>,
+    /// Aggregated proofs of the bundled state transitions, if any were generated
+    pub proof: Option<ProofBatch>,
+    /// Commitment to the executor's state after the bundle
+    pub state_root: Hash,
+    pub block_height: u64,
+}
+
 /// Spell builder
 pub struct SpellBuilder {
     spell_type: ZkUSDSpellType,
@@ -145,6 +388,22 @@ impl SpellBuilder {
         Self::new(ZkUSDSpellType::Transfer).data(TransferParams { to, amount }.encode())
     }
 
+    pub fn redeem(amount: u64, max_fee_bps: u64, payout_script: Vec<u8>) -> Self {
+        Self::new(ZkUSDSpellType::Redeem).data(RedeemParams { amount, max_fee_bps, payout_script }.encode())
+    }
+
+    pub fn claim_gains(payout_script: Vec<u8>) -> Self {
+        Self::new(ZkUSDSpellType::ClaimGains).data(ClaimGainsParams { payout_script }.encode())
+    }
+
+    pub fn mint_cdp_nft(cdp_id: CDPId) -> Self {
+        Self::new(ZkUSDSpellType::MintCdpNft).data(MintCdpNftParams { cdp_id }.encode())
+    }
+
+    pub fn transfer_cdp_nft(cdp_id: CDPId, to: PublicKey) -> Self {
+        Self::new(ZkUSDSpellType::TransferCdpNft).data(TransferCdpNftParams { cdp_id, to }.encode())
+    }
+
     pub fn build_and_sign(self, caster: &crate::utils::crypto::KeyPair) -> CharmSpell {
         let mut spell = CharmSpell::new(self.spell_type, *caster.public_key(), self.data, Signature::new([0u8; 64]), self.nonce, self.deadline);
         let hash = spell.hash();
@@ -165,4 +424,65 @@ mod tests {
         let spell = SpellBuilder::transfer(*recipient.public_key(), 1000).nonce(1).deadline(1000).build_and_sign(&kp);
         assert!(spell.verify_signature().is_ok());
     }
+
+    #[test]
+    fn test_redeem_params_roundtrip() {
+        let kp = KeyPair::generate();
+        let spell = SpellBuilder::redeem(50_000, 100, vec![0x00, 0x14]).nonce(1).build_and_sign(&kp);
+        assert_eq!(spell.spell_type, ZkUSDSpellType::Redeem);
+
+        let params = RedeemParams::decode(&spell.data).unwrap();
+        assert_eq!(params.amount, 50_000);
+        assert_eq!(params.max_fee_bps, 100);
+        assert_eq!(params.payout_script, vec![0x00, 0x14]);
+    }
+
+    #[test]
+    fn test_spell_decode_roundtrip() {
+        let kp = KeyPair::generate();
+        let spell = SpellBuilder::claim_gains(vec![0x51]).nonce(7).build_and_sign(&kp);
+
+        let decoded = CharmSpell::decode(&spell.encode().unwrap()).unwrap();
+        assert_eq!(decoded.hash(), spell.hash());
+        assert!(decoded.verify_signature().is_ok());
+    }
+
+    #[test]
+    fn test_spell_bundle_limits() {
+        let kp = KeyPair::generate();
+        let spell = SpellBuilder::claim_gains(vec![0x51]).nonce(1).build_and_sign(&kp);
+
+        assert!(SpellBundle::new().validate(0).is_err());
+        assert!(SpellBundle::from_spells(vec![spell.clone(); MAX_SPELL_BUNDLE_LEN + 1]).is_err());
+        assert!(SpellBundle::from_spells(vec![spell.clone(), spell.clone()]).unwrap().validate(0).is_err());
+
+        let other = SpellBuilder::claim_gains(vec![0x51]).nonce(2).build_and_sign(&kp);
+        let bundle = SpellBundle::from_spells(vec![spell, other]).unwrap();
+        assert!(bundle.validate(0).is_ok());
+
+        let decoded = SpellBundle::decode(&bundle.encode().unwrap()).unwrap();
+        assert_eq!(decoded.hash(), bundle.hash());
+        assert!(SpellBundle::decode(&SpellBundle::new().encode().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_decode_rejects_hostile_input() {
+        assert!(CharmSpell::decode(&[]).is_err());
+        assert!(CharmSpell::decode(&[0xff; 256]).is_err());
+        assert!(RedeemParams::decode(&vec![0u8; MAX_SPELL_DATA_SIZE + 1]).is_err());
+
+        // Payout script length prefix far beyond the input
+        let mut data = 1_000u64.to_le_bytes().to_vec();
+        data.extend_from_slice(&100u64.to_le_bytes());
+        data.extend_from_slice(&(u64::MAX / 2).to_le_bytes());
+        assert!(RedeemParams::decode(&data).is_err());
+
+        // Oversized payload inside an otherwise valid spell
+        let kp = KeyPair::generate();
+        let spell = SpellBuilder::new(ZkUSDSpellType::Transfer)
+            .data(vec![0u8; MAX_SPELL_DATA_SIZE + 1])
+            .build_and_sign(&kp);
+        assert!(CharmSpell::decode(&spell.encode().unwrap()).is_err());
+        assert!(spell.validate(0).is_err());
+    }
 }
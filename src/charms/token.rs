@@ -32,6 +32,7 @@ pub trait CharmsToken {
 
 /// Unique identifier for a Charm token
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct CharmId([u8; 32]);
 
 impl CharmId {
@@ -87,7 +88,6 @@ pub struct ApprovalReceipt {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ZkUSDCharm {
     inner: ZkUSD,
-    allowances: std::collections::HashMap<(PublicKey, PublicKey), u128>,
     used_nonces: std::collections::HashMap<PublicKey, u64>,
     block_height: u64,
 }
@@ -97,7 +97,6 @@ impl ZkUSDCharm {
     pub fn new() -> Self {
         Self {
             inner: ZkUSD::new(),
-            allowances: std::collections::HashMap::new(),
             used_nonces: std::collections::HashMap::new(),
             block_height: 0,
         }
@@ -107,7 +106,6 @@ impl ZkUSDCharm {
     pub fn from_zkusd(token: ZkUSD) -> Self {
         Self {
             inner: token,
-            allowances: std::collections::HashMap::new(),
             used_nonces: std::collections::HashMap::new(),
             block_height: 0,
         }
@@ -147,7 +145,7 @@ impl CharmsToken for ZkUSDCharm {
     fn total_supply(&self) -> u128 { self.inner.total_supply().cents() as u128 }
     fn balance_of(&self, owner: &PublicKey) -> u128 { self.inner.balance_of(owner).cents() as u128 }
     fn allowance(&self, owner: &PublicKey, spender: &PublicKey) -> u128 {
-        self.allowances.get(&(*owner, *spender)).copied().unwrap_or(0)
+        self.inner.allowance(owner, spender).cents() as u128
     }
 
     fn transfer(&mut self, from: PublicKey, to: PublicKey, amount: u128, signature: &Signature, nonce: u64) -> Result<TransferReceipt> {
@@ -183,7 +181,11 @@ impl CharmsToken for ZkUSDCharm {
             return Err(Error::InvalidSignature);
         }
         self.verify_nonce(&owner, nonce)?;
-        self.allowances.insert((owner, spender), amount);
+
+        // Allowances above the supply cap are as good as unlimited
+        let amount_u64 = u64::try_from(amount).unwrap_or(u64::MAX);
+        let tx_hash = Hash::sha256(&bincode::serialize(&(owner, spender, amount, nonce)).unwrap_or_default());
+        self.inner.approve(owner, spender, TokenAmount::from_cents(amount_u64), self.block_height, tx_hash)?;
         Ok(ApprovalReceipt { charm_id: CharmId::ZKUSD, owner, spender, amount, block_height: self.block_height, nonce })
     }
 
@@ -201,15 +203,9 @@ impl CharmsToken for ZkUSDCharm {
         }
         self.verify_nonce(&spender, nonce)?;
 
-        let allowed = self.allowance(&from, &spender);
-        if allowed < amount {
-            return Err(Error::InsufficientCollateral { required: amount as u64, available: allowed as u64 });
-        }
-        self.allowances.insert((from, spender), allowed - amount);
-
         let amount_u64 = u64::try_from(amount).map_err(|_| Error::Overflow { operation: "amount conversion".into() })?;
         let tx_hash = Hash::sha256(&bincode::serialize(&(spender, from, to, amount, nonce)).unwrap_or_default());
-        self.inner.transfer(from, to, TokenAmount::from_cents(amount_u64), self.block_height, tx_hash)?;
+        self.inner.transfer_from(spender, from, to, TokenAmount::from_cents(amount_u64), self.block_height, tx_hash)?;
 
         Ok(TransferReceipt { charm_id: CharmId::ZKUSD, from, to, amount, tx_hash, block_height: self.block_height, nonce })
     }
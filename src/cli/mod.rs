@@ -0,0 +1,7 @@
+//! Command-line support shared by the `zkusd` binaries.
+//!
+//! - `output`: table, JSON and YAML rendering of command results
+
+pub mod output;
+
+pub use output::*;
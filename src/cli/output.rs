@@ -0,0 +1,189 @@
+//! Command output rendering.
+//!
+//! Every command renders human-readable lines in `table` mode. In `json` and
+//! `yaml` mode those lines are suppressed and the command's result is written
+//! instead as a single [`CommandOutput`] document, whose shape is versioned by
+//! [`OUTPUT_SCHEMA_VERSION`] so scripts can rely on it.
+
+use console::Term;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+
+/// Version of the [`CommandOutput`] document layout
+pub const OUTPUT_SCHEMA_VERSION: u32 = 1;
+
+/// How command results are written to stdout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable lines and tables
+    #[default]
+    Table,
+    /// One pretty-printed JSON document
+    Json,
+    /// One YAML document
+    Yaml,
+}
+
+impl OutputFormat {
+    /// Lowercase name, as accepted by `--output`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Table => "table",
+            Self::Json => "json",
+            Self::Yaml => "yaml",
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "table" | "text" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            _ => Err(Error::InvalidParameter {
+                name: "output".into(),
+                reason: format!("Unknown output format '{}', expected json, yaml or table", s),
+            }),
+        }
+    }
+}
+
+/// Machine-readable result of one command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandOutput {
+    /// Layout version, see [`OUTPUT_SCHEMA_VERSION`]
+    pub schema: u32,
+    /// Dotted subcommand path, e.g. `cdp.open`
+    pub command: String,
+    /// Whether the command succeeded
+    pub ok: bool,
+    /// Command-specific result (`null` on failure)
+    pub data: serde_json::Value,
+    /// Error message on failure
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl CommandOutput {
+    /// Successful result carrying `data`
+    pub fn new(command: impl Into<String>, data: impl Serialize) -> Result<Self> {
+        Ok(Self {
+            schema: OUTPUT_SCHEMA_VERSION,
+            command: command.into(),
+            ok: true,
+            data: serde_json::to_value(data).map_err(|e| Error::Serialization(e.to_string()))?,
+            error: None,
+        })
+    }
+
+    /// Failed result with an error message
+    pub fn failure(command: impl Into<String>, error: impl fmt::Display) -> Self {
+        Self {
+            schema: OUTPUT_SCHEMA_VERSION,
+            command: command.into(),
+            ok: false,
+            data: serde_json::Value::Null,
+            error: Some(error.to_string()),
+        }
+    }
+
+    /// Render as `format`; `None` for table output, which is written line by
+    /// line as the command runs
+    pub fn render(&self, format: OutputFormat) -> Result<Option<String>> {
+        let rendered = match format {
+            OutputFormat::Table => return Ok(None),
+            OutputFormat::Json => serde_json::to_string_pretty(self).map_err(|e| Error::Serialization(e.to_string()))?,
+            OutputFormat::Yaml => serde_yaml::to_string(self).map_err(|e| Error::Serialization(e.to_string()))?,
+        };
+        Ok(Some(rendered))
+    }
+}
+
+/// Writes command output to stdout in the selected format
+pub struct OutputFormatter {
+    format: OutputFormat,
+    term: Term,
+}
+
+impl OutputFormatter {
+    /// Formatter writing to stdout
+    pub fn new(format: OutputFormat) -> Self {
+        Self {
+            format,
+            term: Term::stdout(),
+        }
+    }
+
+    /// Selected format
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
+
+    /// Whether human-readable lines are shown
+    pub fn is_table(&self) -> bool {
+        self.format == OutputFormat::Table
+    }
+
+    /// Write a human-readable line (table output only)
+    pub fn line(&self, line: impl AsRef<str>) {
+        if self.is_table() {
+            let _ = self.term.write_line(line.as_ref());
+        }
+    }
+
+    /// Write the command result (JSON and YAML output only)
+    pub fn emit(&self, output: &CommandOutput) -> Result<()> {
+        if let Some(rendered) = output.render(self.format)? {
+            self.term
+                .write_line(rendered.trim_end())
+                .map_err(|e| Error::Internal(format!("Failed to write output: {}", e)))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_format_parsing() {
+        assert_eq!("JSON".parse::<OutputFormat>().unwrap(), OutputFormat::Json);
+        assert_eq!("yml".parse::<OutputFormat>().unwrap(), OutputFormat::Yaml);
+        assert_eq!("table".parse::<OutputFormat>().unwrap(), OutputFormat::Table);
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn test_command_output_schema() {
+        let output = CommandOutput::new("token.supply", serde_json::json!({ "total_supply_cents": 100 })).unwrap();
+        assert!(output.render(OutputFormat::Table).unwrap().is_none());
+
+        let json: serde_json::Value = serde_json::from_str(&output.render(OutputFormat::Json).unwrap().unwrap()).unwrap();
+        assert_eq!(json["schema"], OUTPUT_SCHEMA_VERSION);
+        assert_eq!(json["command"], "token.supply");
+        assert_eq!(json["ok"], true);
+        assert_eq!(json["data"]["total_supply_cents"], 100);
+        assert!(json.get("error").is_none());
+
+        let yaml = output.render(OutputFormat::Yaml).unwrap().unwrap();
+        let parsed: CommandOutput = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed, output);
+
+        let failure = CommandOutput::failure("cdp.risk", "CDP not found");
+        assert!(!failure.ok);
+        assert_eq!(failure.error.as_deref(), Some("CDP not found"));
+    }
+}
@@ -0,0 +1,483 @@
+//! Client SDK.
+//!
+//! [`ZkUsdClient`] builds, signs and submits operations for one key, so
+//! integrators don't assemble [`ProtocolOperation`]s by hand. It tracks the
+//! key's nonce, signs for the node's [`SigningDomain`] and previews
+//! operations to estimate their fees. Nodes are reached through a
+//! [`Transport`]; [`LocalTransport`] talks to a state machine in the same
+//! process.
+//!
+//! ```rust,ignore
+//! let transport = LocalTransport::new(Arc::new(Mutex::new(machine)));
+//! let client = ZkUsdClient::connect(transport, keypair).await?;
+//! let cdp = client
+//!     .open_cdp(CollateralAmount::from_btc(1), Some(TokenAmount::from_dollars(40_000)))
+//!     .await?;
+//! let fee = client.estimate_mint_fee(cdp.cdp_id, TokenAmount::from_dollars(5_000)).await?;
+//! client.mint(cdp.cdp_id, TokenAmount::from_dollars(5_000)).await?;
+//! ```
+
+pub mod transport;
+
+pub use transport::{LocalTransport, Transport};
+
+use std::sync::{Mutex, PoisonError};
+
+use serde::Serialize;
+
+use crate::core::cdp::CDPId;
+use crate::core::token::TokenAmount;
+use crate::core::vault::CollateralAmount;
+use crate::error::{Error, Result};
+use crate::protocol::operations::*;
+use crate::protocol::signing::SigningDomain;
+use crate::protocol::state_machine::{OperationPreview, OperationResult};
+use crate::utils::constants::SIGNATURE_LENGTH;
+use crate::utils::crypto::{KeyPair, PublicKey, Signature};
+
+/// Highest borrowing or redemption fee accepted by default, in basis points
+pub const DEFAULT_MAX_FEE_BPS: u64 = 500;
+
+/// High-level client acting for one key
+pub struct ZkUsdClient<T: Transport> {
+    /// Connection to the node
+    transport: T,
+    /// Key operations are signed with
+    keypair: KeyPair,
+    /// Domain of the node's network
+    domain: SigningDomain,
+    /// Highest fee accepted on mints and redemptions, in basis points
+    max_fee_bps: u64,
+    /// Last nonce handed out, once fetched from the node
+    nonce: Mutex<Option<u64>>,
+}
+
+impl<T: Transport> ZkUsdClient<T> {
+    /// Connect to the node behind `transport` as `keypair`
+    pub async fn connect(transport: T, keypair: KeyPair) -> Result<Self> {
+        let domain = transport.signing_domain().await?;
+        Ok(Self {
+            transport,
+            keypair,
+            domain,
+            max_fee_bps: DEFAULT_MAX_FEE_BPS,
+            nonce: Mutex::new(None),
+        })
+    }
+
+    /// Set the highest fee accepted on mints and redemptions
+    pub fn with_max_fee_bps(mut self, max_fee_bps: u64) -> Self {
+        self.max_fee_bps = max_fee_bps;
+        self
+    }
+
+    /// Key the client acts for
+    pub fn public_key(&self) -> PublicKey {
+        *self.keypair.public_key()
+    }
+
+    /// Domain operations are signed for
+    pub fn signing_domain(&self) -> &SigningDomain {
+        &self.domain
+    }
+
+    /// The underlying transport
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // CDP OPERATIONS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Open a CDP with `collateral`, optionally minting `initial_debt`
+    pub async fn open_cdp(
+        &self,
+        collateral: CollateralAmount,
+        initial_debt: Option<TokenAmount>,
+    ) -> Result<OpenCDPResult> {
+        let result = self
+            .send(|nonce| {
+                self.signed(
+                    OpenCDPOp {
+                        owner: self.public_key(),
+                        collateral,
+                        initial_debt,
+                        nonce,
+                        signature: unsigned(),
+                    },
+                    ProtocolOperation::OpenCDP,
+                )
+            })
+            .await?;
+        match result {
+            OperationResult::OpenCDP(result) => Ok(result),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Add collateral to a CDP
+    pub async fn deposit(&self, cdp_id: CDPId, amount: CollateralAmount) -> Result<DepositResult> {
+        let result = self
+            .send(|nonce| {
+                self.signed(
+                    DepositCollateralOp {
+                        cdp_id,
+                        depositor: self.public_key(),
+                        amount,
+                        nonce,
+                        signature: unsigned(),
+                    },
+                    ProtocolOperation::DepositCollateral,
+                )
+            })
+            .await?;
+        match result {
+            OperationResult::Deposit(result) => Ok(result),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Withdraw collateral from an owned CDP
+    pub async fn withdraw(&self, cdp_id: CDPId, amount: CollateralAmount) -> Result<WithdrawResult> {
+        let result = self
+            .send(|nonce| {
+                self.signed(
+                    WithdrawCollateralOp {
+                        cdp_id,
+                        owner: self.public_key(),
+                        amount,
+                        nonce,
+                        signature: unsigned(),
+                        cosignatures: Vec::new(),
+                    },
+                    ProtocolOperation::WithdrawCollateral,
+                )
+            })
+            .await?;
+        match result {
+            OperationResult::Withdraw(result) => Ok(result),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Mint zkUSD from an owned CDP
+    pub async fn mint(&self, cdp_id: CDPId, amount: TokenAmount) -> Result<MintResult> {
+        let result = self.send(|nonce| self.mint_op(cdp_id, amount, nonce)).await?;
+        match result {
+            OperationResult::Mint(result) => Ok(result),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Repay debt of a CDP
+    pub async fn repay(&self, cdp_id: CDPId, amount: TokenAmount) -> Result<RepayResult> {
+        let result = self
+            .send(|nonce| {
+                self.signed(
+                    RepayDebtOp {
+                        cdp_id,
+                        payer: self.public_key(),
+                        amount,
+                        nonce,
+                        signature: unsigned(),
+                    },
+                    ProtocolOperation::RepayDebt,
+                )
+            })
+            .await?;
+        match result {
+            OperationResult::Repay(result) => Ok(result),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Close an owned CDP without debt
+    pub async fn close_cdp(&self, cdp_id: CDPId) -> Result<CloseResult> {
+        let result = self
+            .send(|nonce| {
+                self.signed(
+                    CloseCDPOp {
+                        cdp_id,
+                        owner: self.public_key(),
+                        nonce,
+                        signature: unsigned(),
+                        cosignatures: Vec::new(),
+                    },
+                    ProtocolOperation::CloseCDP,
+                )
+            })
+            .await?;
+        match result {
+            OperationResult::Close(result) => Ok(result),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Redeem zkUSD for collateral from the riskiest CDPs
+    pub async fn redeem(&self, amount: TokenAmount) -> Result<RedeemResult> {
+        let result = self.send(|nonce| self.redeem_op(amount, nonce)).await?;
+        match result {
+            OperationResult::Redeem(result) => Ok(result),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // STABILITY POOL OPERATIONS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Deposit zkUSD into the stability pool
+    pub async fn pool_deposit(&self, amount: TokenAmount) -> Result<StabilityDepositResult> {
+        let result = self
+            .send(|nonce| {
+                self.signed(
+                    StabilityDepositOp {
+                        depositor: self.public_key(),
+                        amount,
+                        frontend: None,
+                        nonce,
+                        signature: unsigned(),
+                    },
+                    ProtocolOperation::StabilityDeposit,
+                )
+            })
+            .await?;
+        match result {
+            OperationResult::StabilityDeposit(result) => Ok(result),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Withdraw zkUSD from the stability pool
+    pub async fn pool_withdraw(&self, amount: TokenAmount) -> Result<StabilityWithdrawResult> {
+        let result = self
+            .send(|nonce| {
+                self.signed(
+                    StabilityWithdrawOp {
+                        depositor: self.public_key(),
+                        amount,
+                        nonce,
+                        signature: unsigned(),
+                    },
+                    ProtocolOperation::StabilityWithdraw,
+                )
+            })
+            .await?;
+        match result {
+            OperationResult::StabilityWithdraw(result) => Ok(result),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Claim collateral gained by the stability pool deposit
+    pub async fn claim_gains(&self) -> Result<ClaimGainsResult> {
+        let result = self
+            .send(|nonce| {
+                self.signed(
+                    ClaimGainsOp {
+                        depositor: self.public_key(),
+                        nonce,
+                        signature: unsigned(),
+                    },
+                    ProtocolOperation::ClaimGains,
+                )
+            })
+            .await?;
+        match result {
+            OperationResult::ClaimGains(result) => Ok(result),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // FEE ESTIMATION
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// What `op` would do against the node's current state
+    pub async fn preview(&self, op: ProtocolOperation) -> Result<OperationPreview> {
+        self.transport.preview(op).await
+    }
+
+    /// Borrowing fee of minting `amount` from a CDP
+    pub async fn estimate_mint_fee(&self, cdp_id: CDPId, amount: TokenAmount) -> Result<TokenAmount> {
+        let nonce = self.transport.nonce(&self.public_key()).await? + 1;
+        let preview = self.preview(self.mint_op(cdp_id, amount, nonce)?).await?;
+        Ok(preview.fees)
+    }
+
+    /// Redemption fee of redeeming `amount`
+    pub async fn estimate_redemption_fee(&self, amount: TokenAmount) -> Result<TokenAmount> {
+        let nonce = self.transport.nonce(&self.public_key()).await? + 1;
+        let preview = self.preview(self.redeem_op(amount, nonce)?).await?;
+        Ok(preview.fees)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // HELPERS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    fn mint_op(&self, cdp_id: CDPId, amount: TokenAmount, nonce: u64) -> Result<ProtocolOperation> {
+        self.signed(
+            MintDebtOp {
+                cdp_id,
+                owner: self.public_key(),
+                amount,
+                max_fee_bps: self.max_fee_bps,
+                nonce,
+                signature: unsigned(),
+                cosignatures: Vec::new(),
+            },
+            ProtocolOperation::MintDebt,
+        )
+    }
+
+    fn redeem_op(&self, amount: TokenAmount, nonce: u64) -> Result<ProtocolOperation> {
+        self.signed(
+            RedeemOp {
+                redeemer: self.public_key(),
+                amount,
+                max_fee_bps: self.max_fee_bps,
+                first_cdp_hint: None,
+                last_cdp_hint: None,
+                max_cdps: 0,
+                nonce,
+                signature: unsigned(),
+            },
+            ProtocolOperation::Redeem,
+        )
+    }
+
+    /// Sign `op` for the node's domain and wrap it
+    fn signed<O: Operation + Serialize>(
+        &self,
+        mut op: O,
+        wrap: fn(O) -> ProtocolOperation,
+    ) -> Result<ProtocolOperation> {
+        op.sign(&self.keypair, &self.domain)?;
+        Ok(wrap(op))
+    }
+
+    /// Submit the operation `build` makes for the next nonce. If another
+    /// device used that nonce first, resync with the node and retry once.
+    async fn send(&self, build: impl Fn(u64) -> Result<ProtocolOperation>) -> Result<OperationResult> {
+        let op = build(self.next_nonce().await?)?;
+        match self.transport.submit(op).await {
+            Err(Error::InvalidParameter { name, .. }) if name == "nonce" => {
+                *self.nonce.lock().unwrap_or_else(PoisonError::into_inner) = None;
+                let op = build(self.next_nonce().await?)?;
+                self.transport.submit(op).await
+            }
+            result => result,
+        }
+    }
+
+    /// Hand out the next nonce, fetching the last used one from the node
+    /// the first time. Nonces only have to increase, so a failed operation
+    /// leaving a gap is harmless.
+    async fn next_nonce(&self) -> Result<u64> {
+        let cached = *self.nonce.lock().unwrap_or_else(PoisonError::into_inner);
+        let fetched = match cached {
+            Some(_) => None,
+            None => Some(self.transport.nonce(&self.public_key()).await?),
+        };
+
+        let mut nonce = self.nonce.lock().unwrap_or_else(PoisonError::into_inner);
+        let next = nonce.or(fetched).unwrap_or(0) + 1;
+        *nonce = Some(next);
+        Ok(next)
+    }
+}
+
+/// Signature placeholder replaced when the operation is signed
+fn unsigned() -> Signature {
+    Signature::new([0u8; SIGNATURE_LENGTH])
+}
+
+fn unexpected(result: OperationResult) -> Error {
+    Error::Internal(format!("Unexpected operation result: {:?}", result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use crate::core::config::ProtocolParams;
+    use crate::protocol::safety::WatchdogConfig;
+    use crate::protocol::state_machine::ProtocolStateMachine;
+    use crate::storage::backend::InMemoryStore;
+    use crate::utils::constants::MIN_ORACLE_SOURCES;
+
+    fn setup() -> (LocalTransport<InMemoryStore>, KeyPair) {
+        // Rate limits would stop a single key submitting several operations
+        let params = ProtocolParams::default().with_rate_limit(0, 1).with_min_intervals(0, 0);
+        let mut machine = ProtocolStateMachine::new(InMemoryStore::new())
+            .unwrap()
+            .with_params(params)
+            .with_watchdog(WatchdogConfig { enabled: false, ..Default::default() });
+        machine.begin_block(1, 1_000).unwrap();
+
+        // $100,000
+        let oracle = KeyPair::generate();
+        let mut price = UpdatePriceOp {
+            operator: *oracle.public_key(),
+            price_cents: 10_000_000,
+            source_count: MIN_ORACLE_SOURCES as u8,
+            confidence: 100,
+            confidence_interval: 0,
+            proof: Vec::new(),
+            nonce: 1,
+            signature: unsigned(),
+        };
+        price.sign(&oracle, machine.signing_domain()).unwrap();
+        machine.execute(ProtocolOperation::UpdatePrice(price)).unwrap();
+
+        (LocalTransport::new(Arc::new(Mutex::new(machine))), KeyPair::generate())
+    }
+
+    #[test]
+    fn test_cdp_lifecycle() {
+        let (transport, keypair) = setup();
+        let client = tokio_test::block_on(ZkUsdClient::connect(transport.clone(), keypair)).unwrap();
+
+        let cdp_id = tokio_test::block_on(async {
+            let opened = client
+                .open_cdp(CollateralAmount::from_sats(100_000_000), Some(TokenAmount::from_cents(4_000_000)))
+                .await
+                .unwrap();
+
+            let fee = client.estimate_mint_fee(opened.cdp_id, TokenAmount::from_cents(1_000_000)).await.unwrap();
+            let minted = client.mint(opened.cdp_id, TokenAmount::from_cents(1_000_000)).await.unwrap();
+            assert_eq!(minted.fee, fee);
+
+            client.repay(opened.cdp_id, TokenAmount::from_cents(500_000)).await.unwrap();
+            client.deposit(opened.cdp_id, CollateralAmount::from_sats(10_000_000)).await.unwrap();
+            client.pool_deposit(TokenAmount::from_cents(100_000)).await.unwrap();
+            opened.cdp_id
+        });
+
+        // Estimates don't use up a nonce
+        let machine = transport.machine().lock().unwrap();
+        assert_eq!(machine.nonce_of(&client.public_key()), 5);
+        assert_eq!(machine.get_cdp(&cdp_id).unwrap().collateral_sats, 110_000_000);
+    }
+
+    #[test]
+    fn test_resyncs_nonce_used_elsewhere() {
+        let (transport, keypair) = setup();
+        let other_device = tokio_test::block_on(ZkUsdClient::connect(transport.clone(), keypair.clone())).unwrap();
+        let client = tokio_test::block_on(ZkUsdClient::connect(transport, keypair)).unwrap();
+
+        tokio_test::block_on(async {
+            let collateral = CollateralAmount::from_sats(50_000_000);
+            client.open_cdp(collateral, None).await.unwrap();
+            other_device.open_cdp(collateral, None).await.unwrap();
+            other_device.open_cdp(collateral, None).await.unwrap();
+
+            // The client's cached nonce is stale; it catches up and retries
+            client.open_cdp(collateral, None).await.unwrap();
+            assert_eq!(client.transport().nonce(&client.public_key()).await.unwrap(), 4);
+        });
+    }
+}
@@ -0,0 +1,74 @@
+//! How the client reaches a node.
+
+use std::future::{ready, Future};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::error::Result;
+use crate::protocol::operations::ProtocolOperation;
+use crate::protocol::signing::SigningDomain;
+use crate::protocol::state_machine::{OperationPreview, OperationResult, ProtocolStateMachine};
+use crate::storage::backend::StorageBackend;
+use crate::utils::crypto::PublicKey;
+
+/// Connection to a node executing signed operations
+pub trait Transport {
+    /// Domain operations must be signed for
+    fn signing_domain(&self) -> impl Future<Output = Result<SigningDomain>> + Send;
+
+    /// Highest nonce `account` used (0 if none)
+    fn nonce(&self, account: &PublicKey) -> impl Future<Output = Result<u64>> + Send;
+
+    /// What `op` would do against the current state, without applying it
+    fn preview(&self, op: ProtocolOperation) -> impl Future<Output = Result<OperationPreview>> + Send;
+
+    /// Execute a signed operation
+    fn submit(&self, op: ProtocolOperation) -> impl Future<Output = Result<OperationResult>> + Send;
+}
+
+/// Transport to a state machine in the same process, for embedded nodes,
+/// tooling and tests. The embedding application drives the blocks.
+pub struct LocalTransport<S: StorageBackend> {
+    machine: Arc<Mutex<ProtocolStateMachine<S>>>,
+}
+
+impl<S: StorageBackend> LocalTransport<S> {
+    /// Share `machine` with the client
+    pub fn new(machine: Arc<Mutex<ProtocolStateMachine<S>>>) -> Self {
+        Self { machine }
+    }
+
+    /// The shared state machine
+    pub fn machine(&self) -> &Arc<Mutex<ProtocolStateMachine<S>>> {
+        &self.machine
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ProtocolStateMachine<S>> {
+        self.machine.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<S: StorageBackend> Clone for LocalTransport<S> {
+    fn clone(&self) -> Self {
+        Self {
+            machine: Arc::clone(&self.machine),
+        }
+    }
+}
+
+impl<S: StorageBackend> Transport for LocalTransport<S> {
+    fn signing_domain(&self) -> impl Future<Output = Result<SigningDomain>> + Send {
+        ready(Ok(*self.lock().signing_domain()))
+    }
+
+    fn nonce(&self, account: &PublicKey) -> impl Future<Output = Result<u64>> + Send {
+        ready(Ok(self.lock().nonce_of(account)))
+    }
+
+    fn preview(&self, op: ProtocolOperation) -> impl Future<Output = Result<OperationPreview>> + Send {
+        ready(self.lock().preview(op))
+    }
+
+    fn submit(&self, op: ProtocolOperation) -> impl Future<Output = Result<OperationResult>> + Send {
+        ready(self.lock().execute(op))
+    }
+}
@@ -5,13 +5,16 @@
 //! - Depositing and withdrawing collateral
 //! - Minting and repaying debt
 //! - Checking CDP health
+//! - Owner policies (single key, multisig, timelocked recovery)
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::error::{Error, Result};
+use crate::protocol::codec::{canonical_hash, CDP_DOMAIN};
 use crate::utils::constants::*;
 use crate::utils::math::*;
+use crate::utils::undo::{Undo, UndoMap};
 use crate::utils::validation::*;
 
 // Re-export CDPId for convenience
@@ -79,6 +82,260 @@ pub struct CDPState {
     pub withdrawable_collateral: u64,
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// OWNER POLICY
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Maximum keys in a multisig owner policy
+pub const MAX_MULTISIG_KEYS: usize = 15;
+
+/// Who may authorize owner operations (withdraw, mint, close) on a CDP
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub enum OwnerPolicy {
+    /// A single key
+    Single(PublicKey),
+    /// Any `threshold` distinct keys out of `keys`
+    MultiSig {
+        /// Signatures required
+        threshold: u8,
+        /// Authorized keys
+        keys: Vec<PublicKey>,
+    },
+    /// A primary key, plus a recovery key that may act once the owner has
+    /// been inactive for `timelock_blocks`
+    Recovery {
+        /// Day-to-day key
+        primary: PublicKey,
+        /// Backup key
+        recovery: PublicKey,
+        /// Blocks of owner inactivity before the recovery key is accepted
+        timelock_blocks: u64,
+    },
+}
+
+impl OwnerPolicy {
+    /// Check the policy is well formed for a CDP owned by `owner`
+    pub fn validate(&self, owner: &PublicKey) -> Result<()> {
+        let invalid = |reason: &str| Error::InvalidParameter {
+            name: "owner_policy".into(),
+            reason: reason.into(),
+        };
+
+        match self {
+            OwnerPolicy::Single(key) if key != owner => Err(invalid("key must be the CDP owner")),
+            OwnerPolicy::Single(_) => Ok(()),
+            OwnerPolicy::MultiSig { threshold, keys } => {
+                if keys.is_empty() || keys.len() > MAX_MULTISIG_KEYS {
+                    return Err(invalid("multisig needs between 1 and 15 keys"));
+                }
+                if *threshold == 0 || *threshold as usize > keys.len() {
+                    return Err(invalid("threshold must be between 1 and the number of keys"));
+                }
+                let mut sorted = keys.clone();
+                sorted.sort_by_key(|k| *k.as_bytes());
+                sorted.dedup();
+                if sorted.len() != keys.len() {
+                    return Err(invalid("duplicate multisig key"));
+                }
+                if !keys.contains(owner) {
+                    return Err(invalid("CDP owner must be one of the multisig keys"));
+                }
+                Ok(())
+            }
+            OwnerPolicy::Recovery { primary, recovery, timelock_blocks } => {
+                if primary != owner {
+                    return Err(invalid("primary key must be the CDP owner"));
+                }
+                if primary == recovery {
+                    return Err(invalid("recovery key must differ from the primary key"));
+                }
+                if *timelock_blocks == 0 {
+                    return Err(invalid("recovery timelock must be non-zero"));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Check that `signers` (keys with valid signatures) satisfy the policy.
+    ///
+    /// `last_owner_action` is the block of the last operation authorized by
+    /// the owner, used for the recovery timelock.
+    pub fn authorize(&self, signers: &[PublicKey], block_height: u64, last_owner_action: u64) -> Result<()> {
+        match self {
+            OwnerPolicy::Single(key) => {
+                if signers.contains(key) {
+                    Ok(())
+                } else {
+                    Err(Error::InvalidSignature)
+                }
+            }
+            OwnerPolicy::MultiSig { threshold, keys } => {
+                let approvals = keys.iter().filter(|k| signers.contains(k)).count();
+                if approvals >= *threshold as usize {
+                    Ok(())
+                } else {
+                    Err(Error::Unauthorized(format!(
+                        "{} of {} multisig signatures required, got {}",
+                        threshold,
+                        keys.len(),
+                        approvals
+                    )))
+                }
+            }
+            OwnerPolicy::Recovery { primary, recovery, timelock_blocks } => {
+                if signers.contains(primary) {
+                    return Ok(());
+                }
+                if !signers.contains(recovery) {
+                    return Err(Error::InvalidSignature);
+                }
+
+                let unlocks_at = last_owner_action.saturating_add(*timelock_blocks);
+                if block_height < unlocks_at {
+                    return Err(Error::Unauthorized(format!(
+                        "recovery key locked for {} more blocks",
+                        unlocks_at - block_height
+                    )));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether the policy is a plain single-key policy
+    pub fn is_single(&self) -> bool {
+        matches!(self, OwnerPolicy::Single(_))
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// SESSION KEYS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Maximum session keys on a CDP
+pub const MAX_SESSION_KEYS: usize = 8;
+
+/// Operations a session key may sign
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub enum SessionScope {
+    /// Repay debt from the owner's balance
+    Repay,
+    /// Deposit collateral
+    Deposit,
+    /// Deposit collateral while the CDP is below a collateral ratio
+    TopUp {
+        /// Ratio (percent) below which top-ups are accepted
+        below_ratio: u64,
+    },
+}
+
+/// Scope, spend cap and expiry of a session key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct SessionGrant {
+    /// Operations the key may sign
+    pub scope: SessionScope,
+    /// Total the key may spend (cents for repayments, sats for deposits)
+    pub spend_cap: u64,
+    /// Last block at which the key is accepted
+    pub expires_at: u64,
+}
+
+/// An operation signed with a session key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub enum SessionAction {
+    /// Repay debt in cents from the owner's balance
+    Repay {
+        /// Amount in cents
+        amount_cents: u64,
+    },
+    /// Deposit collateral in sats
+    Deposit {
+        /// Amount in sats
+        amount_sats: u64,
+    },
+}
+
+impl SessionAction {
+    /// Amount counted against the spend cap
+    pub fn amount(&self) -> u64 {
+        match self {
+            SessionAction::Repay { amount_cents } => *amount_cents,
+            SessionAction::Deposit { amount_sats } => *amount_sats,
+        }
+    }
+}
+
+/// A key authorized by the owner to act on a CDP within a grant
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionKey {
+    /// Session public key
+    pub key: PublicKey,
+    /// What the key may do
+    pub grant: SessionGrant,
+    /// Amount spent so far
+    pub spent: u64,
+}
+
+impl SessionKey {
+    /// Create a session key with nothing spent
+    pub fn new(key: PublicKey, grant: SessionGrant) -> Self {
+        Self { key, grant, spent: 0 }
+    }
+
+    /// Amount left under the spend cap
+    pub fn remaining(&self) -> u64 {
+        self.grant.spend_cap.saturating_sub(self.spent)
+    }
+
+    /// Whether the key has expired at `block_height`
+    pub fn is_expired(&self, block_height: u64) -> bool {
+        block_height > self.grant.expires_at
+    }
+
+    /// Check the key may sign `action` on a CDP at `ratio`, and count it
+    /// against the spend cap
+    pub fn charge(&mut self, action: &SessionAction, ratio: u64, block_height: u64) -> Result<()> {
+        if self.is_expired(block_height) {
+            return Err(Error::Unauthorized(format!(
+                "session key expired at block {}",
+                self.grant.expires_at
+            )));
+        }
+
+        match (self.grant.scope, action) {
+            (SessionScope::Repay, SessionAction::Repay { .. })
+            | (SessionScope::Deposit, SessionAction::Deposit { .. }) => {}
+            (SessionScope::TopUp { below_ratio }, SessionAction::Deposit { .. }) => {
+                if ratio >= below_ratio {
+                    return Err(Error::Unauthorized(format!(
+                        "top-ups allowed below {}% collateral ratio, CDP is at {}%",
+                        below_ratio, ratio
+                    )));
+                }
+            }
+            (scope, _) => {
+                return Err(Error::Unauthorized(format!("session key is scoped to {:?}", scope)));
+            }
+        }
+
+        let amount = action.amount();
+        if amount > self.remaining() {
+            return Err(Error::Unauthorized(format!(
+                "session spend cap exceeded: {} requested, {} left",
+                amount,
+                self.remaining()
+            )));
+        }
+        self.spent += amount;
+        Ok(())
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // CDP
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -102,6 +359,12 @@ pub struct CDP {
     pub status: CDPStatus,
     /// Nonce for operations (prevents replay attacks)
     pub nonce: u64,
+    /// Keys allowed to authorize owner operations
+    pub policy: OwnerPolicy,
+    /// Block of the last owner-authorized operation
+    pub last_owner_action: u64,
+    /// Keys the owner has authorized for scoped operations
+    pub sessions: Vec<SessionKey>,
 }
 
 impl CDP {
@@ -117,6 +380,9 @@ impl CDP {
             last_updated: block_height,
             status: CDPStatus::Active,
             nonce,
+            policy: OwnerPolicy::Single(owner),
+            last_owner_action: block_height,
+            sessions: Vec::new(),
         }
     }
 
@@ -249,6 +515,7 @@ impl CDP {
 
         self.collateral_sats = new_collateral;
         self.last_updated = block_height;
+        self.last_owner_action = block_height;
         Ok(())
     }
 
@@ -283,6 +550,7 @@ impl CDP {
 
         self.debt_cents = new_debt;
         self.last_updated = block_height;
+        self.last_owner_action = block_height;
         self.nonce += 1;
 
         Ok(net_mint)
@@ -333,16 +601,29 @@ impl CDP {
         self.collateral_sats = 0;
         self.status = CDPStatus::Closed;
         self.last_updated = block_height;
+        self.last_owner_action = block_height;
 
         Ok(collateral_to_return)
     }
 
-    /// Liquidate CDP (called when undercollateralized)
+    /// Liquidate CDP (called when undercollateralized) with the default
+    /// fixed liquidation penalty
     pub fn liquidate(
         &mut self,
         btc_price_cents: u64,
         min_ratio: u64,
         block_height: u64,
+    ) -> Result<LiquidationResult> {
+        self.liquidate_with_penalty(btc_price_cents, min_ratio, LIQUIDATION_BONUS_BPS, block_height)
+    }
+
+    /// Liquidate CDP, seizing collateral worth the debt plus `penalty_bps`
+    pub fn liquidate_with_penalty(
+        &mut self,
+        btc_price_cents: u64,
+        min_ratio: u64,
+        penalty_bps: u64,
+        block_height: u64,
     ) -> Result<LiquidationResult> {
         if self.status.is_terminal() {
             return Err(Error::CDPNotActive(self.id.to_hex()));
@@ -356,7 +637,7 @@ impl CDP {
             self.collateral_sats,
             self.debt_cents,
             btc_price_cents,
-            LIQUIDATION_BONUS_BPS,
+            penalty_bps,
         )?;
 
         let result = LiquidationResult {
@@ -390,6 +671,71 @@ impl CDP {
         self.owner == *pubkey
     }
 
+    /// Replace the owner policy
+    pub fn set_policy(&mut self, policy: OwnerPolicy, block_height: u64) -> Result<()> {
+        if self.status.is_terminal() {
+            return Err(Error::CDPNotActive(self.id.to_hex()));
+        }
+
+        policy.validate(&self.owner)?;
+        self.policy = policy;
+        self.last_updated = block_height;
+        self.last_owner_action = block_height;
+        Ok(())
+    }
+
+    /// Authorize a session key, replacing any grant it already has.
+    ///
+    /// Expired session keys are dropped.
+    pub fn authorize_session(&mut self, session: SessionKey, block_height: u64) -> Result<()> {
+        if self.status.is_terminal() {
+            return Err(Error::CDPNotActive(self.id.to_hex()));
+        }
+
+        let invalid = |reason: &str| Error::InvalidParameter {
+            name: "session_key".into(),
+            reason: reason.into(),
+        };
+        if session.key == self.owner {
+            return Err(invalid("session key must differ from the CDP owner"));
+        }
+        if session.is_expired(block_height) {
+            return Err(invalid("session key is already expired"));
+        }
+        if session.grant.spend_cap == 0 {
+            return Err(invalid("spend cap must be non-zero"));
+        }
+
+        self.sessions.retain(|s| s.key != session.key && !s.is_expired(block_height));
+        if self.sessions.len() >= MAX_SESSION_KEYS {
+            return Err(invalid("too many session keys"));
+        }
+        self.sessions.push(session);
+        self.last_updated = block_height;
+        self.last_owner_action = block_height;
+        Ok(())
+    }
+
+    /// Revoke a session key
+    pub fn revoke_session(&mut self, key: &PublicKey, block_height: u64) -> Result<SessionKey> {
+        let index = self
+            .sessions
+            .iter()
+            .position(|s| s.key == *key)
+            .ok_or_else(|| Error::InvalidParameter {
+                name: "session_key".into(),
+                reason: "not a session key of this CDP".into(),
+            })?;
+        self.last_updated = block_height;
+        self.last_owner_action = block_height;
+        Ok(self.sessions.remove(index))
+    }
+
+    /// Session key `key`, if authorized
+    pub fn session_mut(&mut self, key: &PublicKey) -> Option<&mut SessionKey> {
+        self.sessions.iter_mut().find(|s| s.key == *key)
+    }
+
     /// Verify owner for privileged operations
     pub fn verify_owner(&self, pubkey: &PublicKey) -> Result<()> {
         if !self.is_owner(pubkey) {
@@ -416,8 +762,7 @@ impl CDP {
 
     /// Compute hash of CDP state (for ZK proofs)
     pub fn state_hash(&self) -> Hash {
-        let bytes = self.to_bytes().unwrap_or_default();
-        Hash::sha256(&bytes)
+        canonical_hash(CDP_DOMAIN, self).unwrap_or_default()
     }
 }
 
@@ -450,9 +795,9 @@ pub struct LiquidationResult {
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CDPManager {
     /// All CDPs indexed by ID
-    cdps: HashMap<CDPId, CDP>,
+    cdps: UndoMap<HashMap<CDPId, CDP>>,
     /// CDPs indexed by owner
-    owner_cdps: HashMap<PublicKey, Vec<CDPId>>,
+    owner_cdps: UndoMap<HashMap<PublicKey, Vec<CDPId>>>,
     /// Total number of active CDPs
     active_count: u64,
 }
@@ -473,7 +818,7 @@ impl CDPManager {
         let id = cdp.id;
 
         self.cdps.insert(id, cdp);
-        self.owner_cdps.entry(owner).or_default().push(id);
+        self.owner_cdps.get_or_insert_with(owner, Vec::new).push(id);
         self.active_count += 1;
 
         Ok(())
@@ -497,6 +842,30 @@ impl CDPManager {
             .unwrap_or_default()
     }
 
+    /// Hand a CDP to a new owner, resetting its key policy to the new owner's key
+    pub fn transfer_owner(&mut self, id: &CDPId, new_owner: PublicKey, block_height: u64) -> Result<()> {
+        let cdp = self.cdps.get_mut(id).ok_or_else(|| Error::CDPNotFound(id.to_hex()))?;
+        if cdp.status.is_terminal() {
+            return Err(Error::InvalidParameter {
+                name: "cdp_id".into(),
+                reason: format!("CDP {} is {:?}", id.to_hex(), cdp.status),
+            });
+        }
+
+        let old_owner = std::mem::replace(&mut cdp.owner, new_owner);
+        cdp.policy = OwnerPolicy::Single(new_owner);
+        cdp.last_updated = block_height;
+
+        if let Some(owner_cdps) = self.owner_cdps.get_mut(&old_owner) {
+            owner_cdps.retain(|i| i != id);
+            if owner_cdps.is_empty() {
+                self.owner_cdps.remove(&old_owner);
+            }
+        }
+        self.owner_cdps.get_or_insert_with(new_owner, Vec::new).push(*id);
+        Ok(())
+    }
+
     /// Get all liquidatable CDPs
     pub fn get_liquidatable(&self, btc_price_cents: u64, min_ratio: u64) -> Vec<&CDP> {
         self.cdps
@@ -505,6 +874,14 @@ impl CDPManager {
             .collect()
     }
 
+    /// Count the liquidatable CDPs
+    pub fn count_liquidatable(&self, btc_price_cents: u64, min_ratio: u64) -> u64 {
+        self.cdps
+            .values()
+            .filter(|cdp| cdp.is_liquidatable(btc_price_cents, min_ratio))
+            .count() as u64
+    }
+
     /// Get sorted CDPs by ratio (ascending - most risky first)
     pub fn get_sorted_by_ratio(&self, btc_price_cents: u64) -> Vec<(&CDP, u64)> {
         let mut cdps_with_ratio: Vec<_> = self
@@ -514,7 +891,8 @@ impl CDPManager {
             .map(|cdp| (cdp, cdp.calculate_ratio(btc_price_cents)))
             .collect();
 
-        cdps_with_ratio.sort_by_key(|(_, ratio)| *ratio);
+        // Break ties by ID so the order does not depend on map iteration
+        cdps_with_ratio.sort_by_key(|(cdp, ratio)| (*ratio, *cdp.id.as_bytes()));
         cdps_with_ratio
     }
 
@@ -547,6 +925,19 @@ impl CDPManager {
         self.cdps.values().collect()
     }
 
+    /// Merkle root over all CDP state hashes, ordered by CDP ID
+    pub fn state_root(&self) -> Hash {
+        let mut cdps: Vec<_> = self.cdps.values().collect();
+        cdps.sort_by_key(|cdp| *cdp.id.as_bytes());
+        let hashes: Vec<Hash> = cdps.iter().map(|cdp| cdp.state_hash()).collect();
+        crate::utils::crypto::merkle_root(&hashes)
+    }
+
+    /// Total debt across all CDPs in cents
+    pub fn total_debt(&self) -> u64 {
+        self.cdps.values().fold(0u64, |total, cdp| total.saturating_add(cdp.debt_cents))
+    }
+
     /// Get total number of CDPs
     pub fn total_count(&self) -> usize {
         self.cdps.len()
@@ -594,6 +985,28 @@ impl CDPManager {
     }
 }
 
+impl Undo for CDPManager {
+    /// Active CDP count
+    type Mark = u64;
+
+    fn begin_undo(&mut self) -> u64 {
+        self.cdps.begin_undo();
+        self.owner_cdps.begin_undo();
+        self.active_count
+    }
+
+    fn commit_undo(&mut self) {
+        self.cdps.commit_undo();
+        self.owner_cdps.commit_undo();
+    }
+
+    fn rollback_undo(&mut self, active_count: u64) {
+        self.cdps.rollback_undo();
+        self.owner_cdps.rollback_undo();
+        self.active_count = active_count;
+    }
+}
+
 /// Aggregate CDP statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CDPStatistics {
@@ -732,6 +1145,68 @@ mod tests {
         assert_eq!(owner_cdps.len(), 1);
     }
 
+    #[test]
+    fn test_owner_policies() {
+        let owner = test_pubkey();
+        let cosigner = test_pubkey_2();
+        let third = PublicKey::new([0x04; PUBKEY_LENGTH]);
+
+        let multisig = OwnerPolicy::MultiSig { threshold: 2, keys: vec![owner, cosigner, third] };
+        multisig.validate(&owner).unwrap();
+        assert!(multisig.authorize(&[owner], 10, 0).is_err());
+        multisig.authorize(&[owner, third], 10, 0).unwrap();
+        // Duplicate signers count once
+        assert!(multisig.authorize(&[owner, owner], 10, 0).is_err());
+
+        let bad = OwnerPolicy::MultiSig { threshold: 4, keys: vec![owner, cosigner, third] };
+        assert!(bad.validate(&owner).is_err());
+
+        let recovery = OwnerPolicy::Recovery { primary: owner, recovery: cosigner, timelock_blocks: 100 };
+        recovery.validate(&owner).unwrap();
+        recovery.authorize(&[owner], 10, 0).unwrap();
+        assert!(recovery.authorize(&[cosigner], 99, 0).is_err());
+        recovery.authorize(&[cosigner], 100, 0).unwrap();
+
+        let mut cdp = CDP::new(owner, 1, 100);
+        assert!(cdp.set_policy(OwnerPolicy::Single(cosigner), 101).is_err());
+        cdp.set_policy(multisig.clone(), 101).unwrap();
+        assert_eq!(cdp.policy, multisig);
+    }
+
+    #[test]
+    fn test_session_keys() {
+        let owner = test_pubkey();
+        let bot = test_pubkey_2();
+        let mut cdp = CDP::new(owner, 1, 100);
+
+        let top_up = SessionGrant {
+            scope: SessionScope::TopUp { below_ratio: 150 },
+            spend_cap: 1_000,
+            expires_at: 200,
+        };
+        assert!(cdp.authorize_session(SessionKey::new(owner, top_up), 100).is_err());
+        cdp.authorize_session(SessionKey::new(bot, top_up), 100).unwrap();
+
+        let deposit = SessionAction::Deposit { amount_sats: 600 };
+        let session = cdp.session_mut(&bot).unwrap();
+        assert!(session.charge(&deposit, 160, 110).is_err());
+        session.charge(&deposit, 140, 110).unwrap();
+        // Cap and scope
+        assert!(session.charge(&deposit, 140, 110).is_err());
+        assert!(session.charge(&SessionAction::Repay { amount_cents: 1 }, 140, 110).is_err());
+        assert_eq!(session.remaining(), 400);
+        assert!(session.charge(&SessionAction::Deposit { amount_sats: 400 }, 140, 201).is_err());
+
+        // Re-authorizing replaces the grant and resets spending
+        cdp.authorize_session(SessionKey::new(bot, top_up), 120).unwrap();
+        assert_eq!(cdp.sessions.len(), 1);
+        assert_eq!(cdp.sessions[0].remaining(), 1_000);
+
+        cdp.revoke_session(&bot, 130).unwrap();
+        assert!(cdp.session_mut(&bot).is_none());
+        assert!(cdp.revoke_session(&bot, 130).is_err());
+    }
+
     #[test]
     fn test_cdp_status_from_ratio() {
         assert_eq!(CDPStatus::from_ratio(200, 110), CDPStatus::Active);
@@ -6,8 +6,14 @@
 //! - Governable: Can be adjusted through governance
 //! - Dynamic: Automatically adjusted by protocol
 
+use std::fmt;
+use std::str::FromStr;
+
 use serde::{Deserialize, Serialize};
 
+use crate::core::fees::FeeController;
+use crate::error::{Error, Result};
+use crate::liquidation::engine::PenaltyCurve;
 use crate::utils::constants::*;
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -32,7 +38,7 @@ pub struct ProtocolParams {
     pub borrowing_fee_bps: u64,
 
     /// Liquidation bonus in basis points
-    /// Discount liquidators receive when buying collateral
+    /// Caps the liquidation penalty curve
     pub liquidation_bonus_bps: u64,
 
     /// Minimum debt per CDP in cents
@@ -55,6 +61,35 @@ pub struct ProtocolParams {
 
     /// Maximum price deviation between sources in basis points
     pub max_price_deviation_bps: u64,
+
+    /// Maximum operations per account per window (0 disables the limit)
+    pub max_ops_per_window: u32,
+
+    /// Rate limit window in blocks
+    pub rate_limit_window_blocks: u64,
+
+    /// Minimum blocks between redemptions by the same account
+    pub min_redeem_interval_blocks: u64,
+
+    /// Minimum blocks between liquidations by the same account
+    pub min_liquidation_interval_blocks: u64,
+
+    /// Maximum zkUSD redeemed per block in cents (0 disables the cap)
+    pub max_redemption_per_block: u64,
+
+    /// zkUSD stipend paid from the treasury to the caller of a liquidation
+    /// or accrual, in cents (0 disables stipends)
+    pub keeper_reward: u64,
+
+    /// Maximum keeper stipends paid per block in cents
+    pub max_keeper_rewards_per_block: u64,
+
+    /// Liquidation penalty by depth below the MCR
+    pub liquidation_penalty: PenaltyCurve,
+
+    /// Reject stability pool withdrawals while any CDP can be liquidated,
+    /// so depositors cannot pull out ahead of a pending liquidation
+    pub gate_sp_withdrawals: bool,
 }
 
 impl Default for ProtocolParams {
@@ -72,6 +107,15 @@ impl Default for ProtocolParams {
             min_oracle_sources: MIN_ORACLE_SOURCES,
             max_price_staleness_secs: MAX_PRICE_STALENESS_SECS,
             max_price_deviation_bps: MAX_PRICE_DEVIATION_BPS,
+            max_ops_per_window: MAX_OPS_PER_WINDOW,
+            rate_limit_window_blocks: RATE_LIMIT_WINDOW_BLOCKS,
+            min_redeem_interval_blocks: MIN_REDEEM_INTERVAL_BLOCKS,
+            min_liquidation_interval_blocks: MIN_LIQUIDATION_INTERVAL_BLOCKS,
+            max_redemption_per_block: MAX_REDEMPTION_PER_BLOCK,
+            keeper_reward: KEEPER_REWARD,
+            max_keeper_rewards_per_block: MAX_KEEPER_REWARDS_PER_BLOCK,
+            liquidation_penalty: PenaltyCurve::default(),
+            gate_sp_withdrawals: true,
         }
     }
 }
@@ -90,6 +134,46 @@ impl ProtocolParams {
         self
     }
 
+    /// Create with custom per-account rate limits
+    pub fn with_rate_limit(mut self, max_ops: u32, window_blocks: u64) -> Self {
+        self.max_ops_per_window = max_ops;
+        self.rate_limit_window_blocks = window_blocks;
+        self
+    }
+
+    /// Create with custom minimum intervals for redemption and liquidation
+    pub fn with_min_intervals(mut self, redeem_blocks: u64, liquidation_blocks: u64) -> Self {
+        self.min_redeem_interval_blocks = redeem_blocks;
+        self.min_liquidation_interval_blocks = liquidation_blocks;
+        self
+    }
+
+    /// Create with a custom per-block redemption cap
+    pub fn with_redemption_cap(mut self, max_per_block: u64) -> Self {
+        self.max_redemption_per_block = max_per_block;
+        self
+    }
+
+    /// Create with a custom keeper stipend and per-block stipend budget
+    pub fn with_keeper_rewards(mut self, reward: u64, max_per_block: u64) -> Self {
+        self.keeper_reward = reward;
+        self.max_keeper_rewards_per_block = max_per_block;
+        self
+    }
+
+    /// Create with a custom liquidation penalty curve
+    pub fn with_penalty_curve(mut self, curve: PenaltyCurve) -> Self {
+        self.liquidation_penalty = curve;
+        self
+    }
+
+    /// Create with stability pool withdrawals gated on pending liquidations
+    /// or not
+    pub fn with_sp_withdrawal_gate(mut self, enabled: bool) -> Self {
+        self.gate_sp_withdrawals = enabled;
+        self
+    }
+
     /// Validate parameters are consistent
     pub fn validate(&self) -> bool {
         self.min_collateral_ratio < self.critical_collateral_ratio
@@ -97,6 +181,305 @@ impl ProtocolParams {
             && self.redemption_fee_floor_bps <= self.redemption_fee_ceiling_bps
             && self.min_oracle_sources > 0
             && self.max_price_staleness_secs > 0
+            && (self.max_ops_per_window == 0 || self.rate_limit_window_blocks > 0)
+            && self.keeper_reward <= self.max_keeper_rewards_per_block
+            && self.liquidation_penalty.validate().is_ok()
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// NETWORKS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Network a deployment runs on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    /// Bitcoin mainnet
+    Mainnet,
+    /// Bitcoin testnet
+    Testnet,
+    /// Local regression test network
+    Regtest,
+    /// Any other deployment
+    Custom,
+}
+
+impl Network {
+    /// Lowercase network name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Mainnet => "mainnet",
+            Self::Testnet => "testnet",
+            Self::Regtest => "regtest",
+            Self::Custom => "custom",
+        }
+    }
+
+    /// Chain identifier of the network's canonical deployment. Custom
+    /// deployments choose their own.
+    pub fn default_chain_id(&self) -> u64 {
+        match self {
+            Self::Mainnet => 1,
+            Self::Testnet => 2,
+            Self::Regtest => 3,
+            Self::Custom => 0,
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for Network {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "mainnet" => Ok(Self::Mainnet),
+            "testnet" => Ok(Self::Testnet),
+            "regtest" => Ok(Self::Regtest),
+            "custom" => Ok(Self::Custom),
+            _ => Err(Error::InvalidParameter {
+                name: "network".into(),
+                reason: format!("Unknown network '{}', expected mainnet, testnet, regtest or custom", s),
+            }),
+        }
+    }
+}
+
+impl ProtocolParams {
+    /// Parameter preset for a network
+    ///
+    /// Test networks accept a single oracle source and drop the per-account
+    /// rate limits; regtest also drops the redemption cap and minimum
+    /// intervals and tolerates day-old prices. Custom networks start from the
+    /// mainnet defaults.
+    pub fn for_network(network: Network) -> Self {
+        let params = Self::default();
+        match network {
+            Network::Mainnet | Network::Custom => params,
+            Network::Testnet => Self {
+                min_oracle_sources: 1,
+                ..params.with_rate_limit(0, RATE_LIMIT_WINDOW_BLOCKS)
+            },
+            Network::Regtest => Self {
+                min_oracle_sources: 1,
+                max_price_staleness_secs: 86_400,
+                ..params
+                    .with_rate_limit(0, RATE_LIMIT_WINDOW_BLOCKS)
+                    .with_min_intervals(0, 0)
+                    .with_redemption_cap(0)
+            },
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// PARAMETER BOUNDS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// A protocol parameter governance may change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtocolParameter {
+    /// Minimum collateralization ratio in percent
+    MinCollateralRatio,
+    /// Critical collateralization ratio in percent
+    CriticalCollateralRatio,
+    /// Borrowing fee in basis points
+    BorrowingFeeBps,
+    /// Liquidation bonus in basis points
+    LiquidationBonusBps,
+    /// Minimum debt per CDP in cents
+    MinDebt,
+    /// Maximum debt per CDP in cents
+    MaxDebtPerCdp,
+    /// Redemption fee floor in basis points
+    RedemptionFeeFloorBps,
+    /// Redemption fee ceiling in basis points
+    RedemptionFeeCeilingBps,
+    /// Maximum price deviation between sources in basis points
+    MaxPriceDeviationBps,
+}
+
+/// Limits on a governable parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParameterBounds {
+    /// Smallest allowed value
+    pub min: u64,
+    /// Largest allowed value
+    pub max: u64,
+    /// Values must be multiples of this
+    pub step: u64,
+    /// Largest change in one update, relative to the current value, in
+    /// basis points; a change of one `step` is always allowed
+    pub max_change_bps: u64,
+}
+
+impl ProtocolParameter {
+    /// Every governable parameter
+    pub const ALL: [Self; 9] = [
+        Self::MinCollateralRatio,
+        Self::CriticalCollateralRatio,
+        Self::BorrowingFeeBps,
+        Self::LiquidationBonusBps,
+        Self::MinDebt,
+        Self::MaxDebtPerCdp,
+        Self::RedemptionFeeFloorBps,
+        Self::RedemptionFeeCeilingBps,
+        Self::MaxPriceDeviationBps,
+    ];
+
+    /// Field name in `ProtocolParams`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::MinCollateralRatio => "min_collateral_ratio",
+            Self::CriticalCollateralRatio => "critical_collateral_ratio",
+            Self::BorrowingFeeBps => "borrowing_fee_bps",
+            Self::LiquidationBonusBps => "liquidation_bonus_bps",
+            Self::MinDebt => "min_debt",
+            Self::MaxDebtPerCdp => "max_debt_per_cdp",
+            Self::RedemptionFeeFloorBps => "redemption_fee_floor_bps",
+            Self::RedemptionFeeCeilingBps => "redemption_fee_ceiling_bps",
+            Self::MaxPriceDeviationBps => "max_price_deviation_bps",
+        }
+    }
+
+    /// Limits governance must respect
+    pub fn bounds(&self) -> ParameterBounds {
+        let (min, max, step, max_change_bps) = match self {
+            Self::MinCollateralRatio => (105, 200, 1, 1_000),
+            Self::CriticalCollateralRatio => (110, 300, 1, 1_000),
+            Self::BorrowingFeeBps => (0, 500, 5, 5_000),
+            Self::LiquidationBonusBps => (100, 2_000, 25, 5_000),
+            Self::MinDebt => (10 * ZKUSD_BASE_UNIT, 10_000 * ZKUSD_BASE_UNIT, ZKUSD_BASE_UNIT, 10_000),
+            Self::MaxDebtPerCdp => (10_000 * ZKUSD_BASE_UNIT, 100_000_000 * ZKUSD_BASE_UNIT, ZKUSD_BASE_UNIT, 10_000),
+            Self::RedemptionFeeFloorBps => (10, 500, 5, 5_000),
+            Self::RedemptionFeeCeilingBps => (50, 1_000, 5, 5_000),
+            Self::MaxPriceDeviationBps => (50, 2_000, 10, 5_000),
+        };
+        ParameterBounds { min, max, step, max_change_bps }
+    }
+
+    /// Current value in `params`
+    pub fn get(&self, params: &ProtocolParams) -> u64 {
+        match self {
+            Self::MinCollateralRatio => params.min_collateral_ratio,
+            Self::CriticalCollateralRatio => params.critical_collateral_ratio,
+            Self::BorrowingFeeBps => params.borrowing_fee_bps,
+            Self::LiquidationBonusBps => params.liquidation_bonus_bps,
+            Self::MinDebt => params.min_debt,
+            Self::MaxDebtPerCdp => params.max_debt_per_cdp,
+            Self::RedemptionFeeFloorBps => params.redemption_fee_floor_bps,
+            Self::RedemptionFeeCeilingBps => params.redemption_fee_ceiling_bps,
+            Self::MaxPriceDeviationBps => params.max_price_deviation_bps,
+        }
+    }
+
+    fn set(&self, params: &mut ProtocolParams, value: u64) {
+        let field = match self {
+            Self::MinCollateralRatio => &mut params.min_collateral_ratio,
+            Self::CriticalCollateralRatio => &mut params.critical_collateral_ratio,
+            Self::BorrowingFeeBps => &mut params.borrowing_fee_bps,
+            Self::LiquidationBonusBps => &mut params.liquidation_bonus_bps,
+            Self::MinDebt => &mut params.min_debt,
+            Self::MaxDebtPerCdp => &mut params.max_debt_per_cdp,
+            Self::RedemptionFeeFloorBps => &mut params.redemption_fee_floor_bps,
+            Self::RedemptionFeeCeilingBps => &mut params.redemption_fee_ceiling_bps,
+            Self::MaxPriceDeviationBps => &mut params.max_price_deviation_bps,
+        };
+        *field = value;
+    }
+}
+
+impl fmt::Display for ProtocolParameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for ProtocolParameter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|parameter| parameter.as_str() == s)
+            .ok_or_else(|| Error::InvalidParameter {
+                name: "parameter".into(),
+                reason: format!("Unknown or non-governable parameter '{}'", s),
+            })
+    }
+}
+
+impl ParameterBounds {
+    /// Check a change from `old` to `new`
+    pub fn check(&self, parameter: ProtocolParameter, old: u64, new: u64) -> Result<()> {
+        self.check_value(parameter, new)?;
+
+        let change = old.abs_diff(new);
+        let allowed = self.max_change(old);
+        if change > allowed {
+            return Err(Error::InvalidParameter {
+                name: format!("params.{}", parameter),
+                reason: format!("change from {} to {} exceeds the per-update limit of {}", old, new, allowed),
+            });
+        }
+        Ok(())
+    }
+
+    /// Check `value` is within range and a multiple of the step
+    pub fn check_value(&self, parameter: ProtocolParameter, value: u64) -> Result<()> {
+        let invalid = |reason: String| Error::InvalidParameter {
+            name: format!("params.{}", parameter),
+            reason,
+        };
+
+        if value < self.min || value > self.max {
+            return Err(invalid(format!("{} is outside [{}, {}]", value, self.min, self.max)));
+        }
+        if self.step > 1 && value % self.step != 0 {
+            return Err(invalid(format!("{} is not a multiple of {}", value, self.step)));
+        }
+        Ok(())
+    }
+
+    /// Largest change allowed in one update from `old`
+    pub fn max_change(&self, old: u64) -> u64 {
+        ((old as u128 * self.max_change_bps as u128 / BPS_DIVISOR as u128) as u64).max(self.step)
+    }
+}
+
+impl ProtocolParams {
+    /// Copy of the parameters with `parameter` set to `value`, if the change
+    /// respects the parameter's bounds and leaves the parameters consistent.
+    ///
+    /// Meant to be checked both when a change is proposed and again when it
+    /// is applied, since other parameters may have moved in between.
+    pub fn checked_update(&self, parameter: ProtocolParameter, value: u64) -> Result<Self> {
+        parameter.bounds().check(parameter, parameter.get(self), value)?;
+
+        let mut updated = self.clone();
+        parameter.set(&mut updated, value);
+        ProtocolConfig::new(updated.clone()).validate()?;
+        Ok(updated)
+    }
+
+    /// Copy of the parameters with `parameter` set to a step of a ramp.
+    ///
+    /// Like [`checked_update`](Self::checked_update) without the per-update
+    /// change limit, which the ramp already spreads over its blocks.
+    pub fn ramped_update(&self, parameter: ProtocolParameter, value: u64) -> Result<Self> {
+        parameter.bounds().check_value(parameter, value)?;
+
+        let mut updated = self.clone();
+        parameter.set(&mut updated, value);
+        ProtocolConfig::new(updated.clone()).validate()?;
+        Ok(updated)
     }
 }
 
@@ -119,17 +502,19 @@ pub struct ProtocolConfig {
     /// Whether protocol is in recovery mode
     pub recovery_mode: bool,
 
-    /// Current base rate for redemptions (dynamic)
-    pub base_rate: u64,
-
-    /// Last redemption timestamp
-    pub last_redemption_time: u64,
+    /// Base rate driving borrowing and redemption fees (dynamic)
+    pub fees: FeeController,
 
     /// Total system debt (all CDP debts)
     pub total_system_debt: u64,
 
     /// Total system collateral in satoshis
     pub total_system_collateral: u64,
+
+    /// Chain identifier operations must be signed for, so signatures made
+    /// for one deployment cannot be replayed on another
+    #[serde(default)]
+    pub chain_id: u64,
 }
 
 impl Default for ProtocolConfig {
@@ -139,10 +524,10 @@ impl Default for ProtocolConfig {
             debt_ceiling: INITIAL_DEBT_CEILING,
             paused: false,
             recovery_mode: false,
-            base_rate: 0,
-            last_redemption_time: 0,
+            fees: FeeController::new(),
             total_system_debt: 0,
             total_system_collateral: 0,
+            chain_id: 0,
         }
     }
 }
@@ -156,6 +541,56 @@ impl ProtocolConfig {
         }
     }
 
+    /// Create with a custom chain identifier
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Check the configuration is consistent, naming the first offending
+    /// parameter
+    pub fn validate(&self) -> Result<()> {
+        let params = &self.params;
+        let checks = [
+            (
+                params.min_collateral_ratio < params.critical_collateral_ratio,
+                "min_collateral_ratio",
+                "must be below critical_collateral_ratio",
+            ),
+            (params.min_debt <= params.max_debt_per_cdp, "min_debt", "must not exceed max_debt_per_cdp"),
+            (
+                params.redemption_fee_floor_bps <= params.redemption_fee_ceiling_bps,
+                "redemption_fee_floor_bps",
+                "must not exceed redemption_fee_ceiling_bps",
+            ),
+            (params.min_oracle_sources > 0, "min_oracle_sources", "must be positive"),
+            (params.max_price_staleness_secs > 0, "max_price_staleness_secs", "must be positive"),
+            (
+                params.max_ops_per_window == 0 || params.rate_limit_window_blocks > 0,
+                "rate_limit_window_blocks",
+                "must be positive while max_ops_per_window is set",
+            ),
+            (
+                params.keeper_reward <= params.max_keeper_rewards_per_block,
+                "keeper_reward",
+                "must not exceed max_keeper_rewards_per_block",
+            ),
+            (
+                params.liquidation_penalty.validate().is_ok(),
+                "liquidation_penalty",
+                "breakpoint depths must increase and penalties must not exceed 10000bps",
+            ),
+        ];
+
+        match checks.iter().find(|(ok, _, _)| !ok) {
+            Some((_, name, reason)) => Err(Error::InvalidParameter {
+                name: format!("params.{}", name),
+                reason: reason.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+
     /// Calculate current total collateralization ratio (TCR)
     pub fn calculate_tcr(&self, btc_price_cents: u64) -> u64 {
         if self.total_system_debt == 0 {
@@ -182,29 +617,24 @@ impl ProtocolConfig {
         self.recovery_mode = self.should_enter_recovery_mode(btc_price_cents);
     }
 
-    /// Calculate current redemption fee
-    pub fn calculate_redemption_fee(&self, current_time: u64) -> u64 {
-        // Fee decays exponentially from last redemption
-        let time_since_redemption = current_time.saturating_sub(self.last_redemption_time);
-
-        // Simple decay: halve every 12 hours
-        let decay_periods = time_since_redemption / REDEMPTION_FEE_DECAY_HALF_LIFE;
-        let decayed_rate = self.base_rate >> decay_periods.min(63);
+    /// Current redemption fee in basis points
+    pub fn redemption_fee(&self, now: u64) -> u64 {
+        self.fees.redemption_fee(&self.params, now)
+    }
 
-        // Floor + decayed rate, capped at ceiling
-        (self.params.redemption_fee_floor_bps + decayed_rate)
-            .min(self.params.redemption_fee_ceiling_bps)
+    /// Current borrowing fee in basis points
+    pub fn borrowing_fee(&self, now: u64) -> u64 {
+        self.fees.borrowing_fee(&self.params, now)
     }
 
-    /// Update base rate after redemption
-    pub fn update_base_rate(&mut self, redeemed_amount: u64, current_time: u64) {
-        // Increase base rate based on redemption amount
-        // Rate increase = redeemed_amount / total_debt * some_factor
-        if self.total_system_debt > 0 {
-            let rate_increase = (redeemed_amount as u128) * 100 / (self.total_system_debt as u128);
-            self.base_rate = self.base_rate.saturating_add(rate_increase as u64);
-        }
-        self.last_redemption_time = current_time;
+    /// Liquidation penalty in basis points for a CDP at `ratio`, capped at
+    /// the liquidation bonus
+    pub fn liquidation_penalty_bps(&self, ratio: u64) -> u64 {
+        self.params.liquidation_penalty.penalty_bps(
+            ratio,
+            self.effective_mcr(),
+            self.params.liquidation_bonus_bps,
+        )
     }
 
     /// Check if debt ceiling allows new debt
@@ -298,6 +728,22 @@ impl ProtocolState {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// FEES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Where a protocol fee was charged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub enum FeeSource {
+    /// Borrowing fee on minted debt
+    Borrowing,
+    /// Redemption fee
+    Redemption,
+    /// Fee taken from liquidated collateral
+    Liquidation,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -307,6 +753,64 @@ mod tests {
         let params = ProtocolParams::default();
         assert!(params.validate());
         assert_eq!(params.min_collateral_ratio, MIN_COLLATERAL_RATIO);
+
+        assert!(!params.clone().with_rate_limit(10, 0).validate());
+        assert!(params.clone().with_rate_limit(0, 0).validate());
+        assert!(!params.clone().with_keeper_rewards(500, 100).validate());
+        assert!(params.clone().with_keeper_rewards(0, 0).validate());
+        assert!(!params.with_penalty_curve(PenaltyCurve::new(Vec::new())).validate());
+    }
+
+    #[test]
+    fn test_network_presets() {
+        for network in [Network::Mainnet, Network::Testnet, Network::Regtest, Network::Custom] {
+            assert_eq!(network.as_str().parse::<Network>().unwrap(), network);
+            assert!(ProtocolConfig::new(ProtocolParams::for_network(network)).validate().is_ok());
+        }
+        assert!("signet".parse::<Network>().is_err());
+        assert_ne!(Network::Mainnet.default_chain_id(), Network::Testnet.default_chain_id());
+
+        let regtest = ProtocolParams::for_network(Network::Regtest);
+        assert_eq!(regtest.min_oracle_sources, 1);
+        assert_eq!(regtest.max_ops_per_window, 0);
+
+        let mut config = ProtocolConfig::default();
+        config.params.min_collateral_ratio = config.params.critical_collateral_ratio;
+        match config.validate() {
+            Err(Error::InvalidParameter { name, .. }) => assert_eq!(name, "params.min_collateral_ratio"),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parameter_bounds() {
+        let params = ProtocolParams::default();
+        for parameter in ProtocolParameter::ALL {
+            let bounds = parameter.bounds();
+            let value = parameter.get(&params);
+            assert!(bounds.min <= value && value <= bounds.max, "{} default out of bounds", parameter);
+            assert_eq!(parameter.as_str().parse::<ProtocolParameter>().unwrap(), parameter);
+        }
+        assert!("version".parse::<ProtocolParameter>().is_err());
+
+        // Within 10% of 110%
+        let updated = params.checked_update(ProtocolParameter::MinCollateralRatio, 120).unwrap();
+        assert_eq!(updated.min_collateral_ratio, 120);
+        assert!(params.checked_update(ProtocolParameter::MinCollateralRatio, 125).is_err());
+        assert!(params.checked_update(ProtocolParameter::MinCollateralRatio, 0).is_err());
+
+        // A zero fee may still move by one step
+        let free = ProtocolParams { borrowing_fee_bps: 0, ..ProtocolParams::default() };
+        assert!(free.checked_update(ProtocolParameter::BorrowingFeeBps, 5).is_ok());
+        assert!(free.checked_update(ProtocolParameter::BorrowingFeeBps, 7).is_err());
+        assert!(free.checked_update(ProtocolParameter::BorrowingFeeBps, BPS_DIVISOR).is_err());
+
+        // In bounds but inconsistent: MCR may not reach the CCR
+        let close = ProtocolParams { critical_collateral_ratio: 115, ..ProtocolParams::default() };
+        match close.checked_update(ProtocolParameter::MinCollateralRatio, 115) {
+            Err(Error::InvalidParameter { name, .. }) => assert_eq!(name, "params.min_collateral_ratio"),
+            other => panic!("unexpected {:?}", other),
+        }
     }
 
     #[test]
@@ -337,19 +841,19 @@ mod tests {
     #[test]
     fn test_redemption_fee_decay() {
         let mut config = ProtocolConfig::default();
-        config.base_rate = 100; // 1%
-        config.last_redemption_time = 0;
+        config.fees.base_rate = 100; // 1%
+        config.fees.last_redemption_time = 0;
 
         // Right after redemption: floor + base_rate
-        let fee1 = config.calculate_redemption_fee(0);
+        let fee1 = config.redemption_fee(0);
         assert_eq!(fee1, REDEMPTION_FEE_FLOOR_BPS + 100);
 
         // After 12 hours: base_rate halved
-        let fee2 = config.calculate_redemption_fee(REDEMPTION_FEE_DECAY_HALF_LIFE);
+        let fee2 = config.redemption_fee(REDEMPTION_FEE_DECAY_HALF_LIFE);
         assert_eq!(fee2, REDEMPTION_FEE_FLOOR_BPS + 50);
 
         // After 24 hours: base_rate quartered
-        let fee3 = config.calculate_redemption_fee(REDEMPTION_FEE_DECAY_HALF_LIFE * 2);
+        let fee3 = config.redemption_fee(REDEMPTION_FEE_DECAY_HALF_LIFE * 2);
         assert_eq!(fee3, REDEMPTION_FEE_FLOOR_BPS + 25);
     }
 
@@ -376,6 +880,19 @@ mod tests {
         assert_eq!(config.total_system_debt, 2_500_000);
     }
 
+    #[test]
+    fn test_liquidation_penalty_capped_by_bonus() {
+        let mut config = ProtocolConfig::default();
+
+        // Default curve: 5% at the MCR, 10% ten points below it
+        assert_eq!(config.liquidation_penalty_bps(MIN_COLLATERAL_RATIO), 500);
+        assert_eq!(config.liquidation_penalty_bps(MIN_COLLATERAL_RATIO - 4), 700);
+        assert_eq!(config.liquidation_penalty_bps(50), LIQUIDATION_BONUS_BPS);
+
+        config.params.liquidation_bonus_bps = 600;
+        assert_eq!(config.liquidation_penalty_bps(50), 600);
+    }
+
     #[test]
     fn test_effective_mcr() {
         let mut config = ProtocolConfig::default();
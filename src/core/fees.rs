@@ -0,0 +1,135 @@
+//! Borrowing and redemption fee controller.
+//!
+//! Fees follow a single base rate, as in Liquity. Each redemption bumps the
+//! base rate in proportion to the share of system debt redeemed, and the rate
+//! then decays back towards zero with a fixed half-life. Both fees are the
+//! decayed base rate on top of a floor, capped at a ceiling:
+//!
+//! - Redemption fee: `redemption_fee_floor_bps + base rate`, at most
+//!   `redemption_fee_ceiling_bps`
+//! - Borrowing fee: `borrowing_fee_bps + base rate`, at most
+//!   `BORROWING_FEE_CEILING_BPS`
+//!
+//! A burst of redemptions therefore makes both redeeming and minting more
+//! expensive until the peg recovers.
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::config::ProtocolParams;
+use crate::utils::constants::{
+    BORROWING_FEE_CEILING_BPS, BPS_DIVISOR, REDEMPTION_BASE_RATE_BETA, REDEMPTION_FEE_DECAY_HALF_LIFE,
+};
+
+/// Base rate state driving borrowing and redemption fees
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeController {
+    /// Base rate in basis points as of `last_redemption_time`
+    pub base_rate: u64,
+    /// Timestamp of the last redemption
+    pub last_redemption_time: u64,
+}
+
+impl FeeController {
+    /// Create a controller with a zero base rate
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Base rate at `now`, after decay since the last redemption.
+    ///
+    /// The rate halves every `REDEMPTION_FEE_DECAY_HALF_LIFE` seconds and is
+    /// interpolated linearly within a half-life.
+    pub fn decayed_base_rate(&self, now: u64) -> u64 {
+        let elapsed = now.saturating_sub(self.last_redemption_time);
+        let halvings = elapsed / REDEMPTION_FEE_DECAY_HALF_LIFE;
+        if halvings >= u64::BITS as u64 {
+            return 0;
+        }
+
+        let rate = self.base_rate >> halvings;
+        let partial = elapsed % REDEMPTION_FEE_DECAY_HALF_LIFE;
+        let drop = rate as u128 * partial as u128 / (2 * REDEMPTION_FEE_DECAY_HALF_LIFE as u128);
+        rate - drop as u64
+    }
+
+    /// Redemption fee at `now` in basis points
+    pub fn redemption_fee(&self, params: &ProtocolParams, now: u64) -> u64 {
+        params
+            .redemption_fee_floor_bps
+            .saturating_add(self.decayed_base_rate(now))
+            .min(params.redemption_fee_ceiling_bps)
+    }
+
+    /// Borrowing fee at `now` in basis points
+    pub fn borrowing_fee(&self, params: &ProtocolParams, now: u64) -> u64 {
+        params
+            .borrowing_fee_bps
+            .saturating_add(self.decayed_base_rate(now))
+            .min(BORROWING_FEE_CEILING_BPS.max(params.borrowing_fee_bps))
+    }
+
+    /// Bump the base rate after `redeemed` cents were redeemed out of
+    /// `total_debt` cents of system debt (measured before the redemption)
+    pub fn record_redemption(&mut self, redeemed: u64, total_debt: u64, now: u64) {
+        let bump = if total_debt > 0 {
+            redeemed as u128 * BPS_DIVISOR as u128 / (total_debt as u128 * REDEMPTION_BASE_RATE_BETA as u128)
+        } else {
+            0
+        };
+
+        self.base_rate = (self.decayed_base_rate(now) as u128 + bump).min(BPS_DIVISOR as u128) as u64;
+        self.last_redemption_time = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::constants::{BORROWING_FEE_BPS, REDEMPTION_FEE_CEILING_BPS, REDEMPTION_FEE_FLOOR_BPS};
+
+    #[test]
+    fn test_half_life_decay() {
+        let fees = FeeController { base_rate: 400, last_redemption_time: 1_000 };
+
+        assert_eq!(fees.decayed_base_rate(0), 400);
+        assert_eq!(fees.decayed_base_rate(1_000), 400);
+        assert_eq!(fees.decayed_base_rate(1_000 + REDEMPTION_FEE_DECAY_HALF_LIFE / 2), 300);
+        assert_eq!(fees.decayed_base_rate(1_000 + REDEMPTION_FEE_DECAY_HALF_LIFE), 200);
+        assert_eq!(fees.decayed_base_rate(1_000 + REDEMPTION_FEE_DECAY_HALF_LIFE * 2), 100);
+        assert_eq!(fees.decayed_base_rate(1_000 + REDEMPTION_FEE_DECAY_HALF_LIFE * 64), 0);
+    }
+
+    #[test]
+    fn test_redemption_bumps_base_rate() {
+        let mut fees = FeeController::new();
+
+        // Redeeming 10% of the debt adds 5%
+        fees.record_redemption(1_000_000, 10_000_000, 0);
+        assert_eq!(fees.base_rate, 500);
+
+        // The previous rate decays before the next bump
+        fees.record_redemption(100_000, 10_000_000, REDEMPTION_FEE_DECAY_HALF_LIFE);
+        assert_eq!(fees.base_rate, 300);
+        assert_eq!(fees.last_redemption_time, REDEMPTION_FEE_DECAY_HALF_LIFE);
+
+        // The base rate never exceeds 100%
+        fees.record_redemption(u64::MAX, 1, REDEMPTION_FEE_DECAY_HALF_LIFE);
+        assert_eq!(fees.base_rate, BPS_DIVISOR);
+    }
+
+    #[test]
+    fn test_fees_between_floor_and_ceiling() {
+        let params = ProtocolParams::default();
+        let mut fees = FeeController::new();
+        assert_eq!(fees.redemption_fee(&params, 0), REDEMPTION_FEE_FLOOR_BPS);
+        assert_eq!(fees.borrowing_fee(&params, 0), BORROWING_FEE_BPS);
+
+        fees.base_rate = 100;
+        assert_eq!(fees.redemption_fee(&params, 0), REDEMPTION_FEE_FLOOR_BPS + 100);
+        assert_eq!(fees.borrowing_fee(&params, 0), BORROWING_FEE_BPS + 100);
+
+        fees.base_rate = BPS_DIVISOR;
+        assert_eq!(fees.redemption_fee(&params, 0), REDEMPTION_FEE_CEILING_BPS);
+        assert_eq!(fees.borrowing_fee(&params, 0), BORROWING_FEE_CEILING_BPS);
+    }
+}
@@ -2,16 +2,28 @@
 //!
 //! This module contains the fundamental building blocks:
 //! - Configuration and protocol parameters
+//! - Borrowing and redemption fee controller
+//! - Peg stability controller steering fees from the zkUSD market price
 //! - CDP (Collateralized Debt Position) management
+//! - CDP risk reports
 //! - zkUSD token operations
 //! - Vault management
+//! - zkUSD savings rate
 
 pub mod cdp;
 pub mod config;
+pub mod fees;
+pub mod peg;
+pub mod risk;
+pub mod savings;
 pub mod token;
 pub mod vault;
 
 pub use cdp::*;
 pub use config::*;
+pub use fees::*;
+pub use peg::*;
+pub use risk::*;
+pub use savings::*;
 pub use token::*;
 pub use vault::*;
@@ -0,0 +1,398 @@
+//! Peg stability controller.
+//!
+//! The controller watches the zkUSD/USD market price and nudges the
+//! borrowing fee and the redemption fee floor to pull it back to $1, much
+//! like a perpetual's funding rate:
+//!
+//! - Below the peg, borrowing gets more expensive and redeeming cheaper, so
+//!   supply contracts
+//! - Above the peg, borrowing gets cheaper and redeeming more expensive, so
+//!   supply expands
+//!
+//! Each step is proportional to the deviation, capped at `max_step_bps`, and
+//! fees never leave the governance-set bounds. The controller engages once
+//! the deviation reaches `engage_bps` and releases only once it is back
+//! within `release_bps`, so a price hovering near the threshold does not make
+//! fees flap. After release, both fees step back to the values they had when
+//! the controller engaged.
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::config::{ProtocolParameter, ProtocolParams};
+use crate::error::{Error, Result};
+use crate::oracle::price_feed::RATE_PRECISION;
+use crate::utils::constants::BPS_DIVISOR;
+
+/// Deviation from the peg that engages the controller by default (0.5%)
+pub const DEFAULT_PEG_ENGAGE_BPS: u64 = 50;
+
+/// Deviation within which an engaged controller releases by default (0.2%)
+pub const DEFAULT_PEG_RELEASE_BPS: u64 = 20;
+
+/// Side of the peg the controller is defending
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PegRegime {
+    /// Within bounds; fees relax back to their baseline
+    #[default]
+    Neutral,
+    /// zkUSD trades below $1
+    BelowPeg,
+    /// zkUSD trades above $1
+    AbovePeg,
+}
+
+/// Governance-set controller settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PegConfig {
+    /// Deviation that engages the controller, in basis points
+    pub engage_bps: u64,
+    /// Deviation within which an engaged controller releases, in basis points
+    pub release_bps: u64,
+    /// Fee change per step as a share of the deviation, in basis points
+    pub gain_bps: u64,
+    /// Largest fee change per step, in basis points
+    pub max_step_bps: u64,
+    /// Blocks between steps
+    pub interval_blocks: u64,
+    /// Lowest borrowing fee the controller may set
+    pub min_borrowing_fee_bps: u64,
+    /// Highest borrowing fee the controller may set
+    pub max_borrowing_fee_bps: u64,
+    /// Lowest redemption fee floor the controller may set
+    pub min_redemption_fee_floor_bps: u64,
+    /// Highest redemption fee floor the controller may set
+    pub max_redemption_fee_floor_bps: u64,
+}
+
+impl Default for PegConfig {
+    fn default() -> Self {
+        Self {
+            engage_bps: DEFAULT_PEG_ENGAGE_BPS,
+            release_bps: DEFAULT_PEG_RELEASE_BPS,
+            gain_bps: 5_000,
+            max_step_bps: 25,
+            interval_blocks: 6,
+            min_borrowing_fee_bps: 0,
+            max_borrowing_fee_bps: 300,
+            min_redemption_fee_floor_bps: 10,
+            max_redemption_fee_floor_bps: 200,
+        }
+    }
+}
+
+impl PegConfig {
+    /// Check the settings are consistent and within the governable range of
+    /// the fees they drive
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: &str| Error::InvalidParameter {
+            name: "peg_config".into(),
+            reason: reason.into(),
+        };
+
+        if self.engage_bps == 0 || self.release_bps > self.engage_bps {
+            return Err(invalid("release_bps must not exceed a positive engage_bps"));
+        }
+        if self.gain_bps == 0 || self.max_step_bps == 0 || self.interval_blocks == 0 {
+            return Err(invalid("gain_bps, max_step_bps and interval_blocks must be positive"));
+        }
+
+        let borrowing = ProtocolParameter::BorrowingFeeBps.bounds();
+        if self.min_borrowing_fee_bps > self.max_borrowing_fee_bps
+            || self.min_borrowing_fee_bps < borrowing.min
+            || self.max_borrowing_fee_bps > borrowing.max
+        {
+            return Err(invalid("borrowing fee bounds are inverted or outside the governable range"));
+        }
+
+        let floor = ProtocolParameter::RedemptionFeeFloorBps.bounds();
+        if self.min_redemption_fee_floor_bps > self.max_redemption_fee_floor_bps
+            || self.min_redemption_fee_floor_bps < floor.min
+            || self.max_redemption_fee_floor_bps > floor.max
+        {
+            return Err(invalid("redemption fee floor bounds are inverted or outside the governable range"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Fee parameters set by one controller step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PegAdjustment {
+    /// zkUSD/USD market rate, scaled by [`RATE_PRECISION`]
+    pub market_rate: u64,
+    /// Deviation from $1 in basis points, negative below the peg
+    pub deviation_bps: i64,
+    /// Regime after the step
+    pub regime: PegRegime,
+    /// New borrowing fee
+    pub borrowing_fee_bps: u64,
+    /// New redemption fee floor
+    pub redemption_fee_floor_bps: u64,
+}
+
+/// Peg stability controller state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PegController {
+    config: PegConfig,
+    regime: PegRegime,
+    /// Borrowing fee and redemption floor to return to, while adjusted
+    baseline: Option<(u64, u64)>,
+    /// Block of the last step
+    last_step: Option<u64>,
+}
+
+impl PegController {
+    /// Create an idle controller
+    pub fn new(config: PegConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            config,
+            regime: PegRegime::Neutral,
+            baseline: None,
+            last_step: None,
+        })
+    }
+
+    /// Controller settings
+    pub fn config(&self) -> &PegConfig {
+        &self.config
+    }
+
+    /// Replace the settings, keeping the current regime and baseline
+    pub fn set_config(&mut self, config: PegConfig) -> Result<()> {
+        config.validate()?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Side of the peg currently defended
+    pub fn regime(&self) -> PegRegime {
+        self.regime
+    }
+
+    /// Fees to return to once the peg holds, if the controller has moved them
+    pub fn baseline(&self) -> Option<(u64, u64)> {
+        self.baseline
+    }
+
+    /// Whether a step is due at `block_height`
+    pub fn is_due(&self, block_height: u64) -> bool {
+        !matches!(self.last_step, Some(last) if block_height < last.saturating_add(self.config.interval_blocks))
+    }
+
+    /// Deviation of `market_rate` from $1 in basis points
+    pub fn deviation_bps(market_rate: u64) -> i64 {
+        let diff = market_rate as i128 - RATE_PRECISION as i128;
+        (diff * BPS_DIVISOR as i128 / RATE_PRECISION as i128) as i64
+    }
+
+    /// Take a step for `market_rate` observed at `block_height`, returning
+    /// the new fee parameters if the fees or the regime changed
+    pub fn step(&mut self, market_rate: u64, params: &ProtocolParams, block_height: u64) -> Option<PegAdjustment> {
+        self.last_step = Some(block_height);
+
+        let deviation = Self::deviation_bps(market_rate);
+        let previous_regime = self.regime;
+        self.regime = self.next_regime(deviation);
+
+        let current = (params.borrowing_fee_bps, params.redemption_fee_floor_bps);
+        let floor_max = self.config.max_redemption_fee_floor_bps.min(params.redemption_fee_ceiling_bps);
+        let step = (deviation.unsigned_abs() as u128 * self.config.gain_bps as u128 / BPS_DIVISOR as u128)
+            .clamp(1, self.config.max_step_bps as u128) as u64;
+
+        let (borrowing, floor) = match self.regime {
+            PegRegime::BelowPeg => {
+                self.baseline.get_or_insert(current);
+                (
+                    raise(current.0, step, self.config.max_borrowing_fee_bps),
+                    lower(current.1, step, self.config.min_redemption_fee_floor_bps),
+                )
+            }
+            PegRegime::AbovePeg => {
+                self.baseline.get_or_insert(current);
+                (
+                    lower(current.0, step, self.config.min_borrowing_fee_bps),
+                    raise(current.1, step, floor_max),
+                )
+            }
+            PegRegime::Neutral => {
+                let (base_borrowing, base_floor) = self.baseline?;
+                let fees = (
+                    towards(current.0, base_borrowing, self.config.max_step_bps),
+                    towards(current.1, base_floor, self.config.max_step_bps),
+                );
+                if fees == (base_borrowing, base_floor) {
+                    self.baseline = None;
+                }
+                fees
+            }
+        };
+
+        if (borrowing, floor) == current && self.regime == previous_regime {
+            return None;
+        }
+
+        Some(PegAdjustment {
+            market_rate,
+            deviation_bps: deviation,
+            regime: self.regime,
+            borrowing_fee_bps: borrowing,
+            redemption_fee_floor_bps: floor,
+        })
+    }
+
+    /// Move the baseline to fees set by governance while the controller has
+    /// them adjusted
+    pub fn rebase(&mut self, params: &ProtocolParams) {
+        if let Some(baseline) = &mut self.baseline {
+            *baseline = (params.borrowing_fee_bps, params.redemption_fee_floor_bps);
+        }
+    }
+
+    fn next_regime(&self, deviation: i64) -> PegRegime {
+        let engage = self.config.engage_bps as i64;
+        let release = self.config.release_bps as i64;
+
+        if deviation <= -engage {
+            PegRegime::BelowPeg
+        } else if deviation >= engage {
+            PegRegime::AbovePeg
+        } else {
+            match self.regime {
+                PegRegime::BelowPeg if deviation < -release => PegRegime::BelowPeg,
+                PegRegime::AbovePeg if deviation > release => PegRegime::AbovePeg,
+                _ => PegRegime::Neutral,
+            }
+        }
+    }
+}
+
+/// `value` raised by `step`, up to `max`, never lowered
+fn raise(value: u64, step: u64, max: u64) -> u64 {
+    value.saturating_add(step).min(max).max(value)
+}
+
+/// `value` lowered by `step`, down to `min`, never raised
+fn lower(value: u64, step: u64, min: u64) -> u64 {
+    value.saturating_sub(step).max(min).min(value)
+}
+
+/// `value` moved at most `step` towards `target`
+fn towards(value: u64, target: u64, step: u64) -> u64 {
+    if value < target {
+        value.saturating_add(step).min(target)
+    } else {
+        value.saturating_sub(step).max(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Market rate for a zkUSD price in basis points of $1
+    fn rate(price_bps: u64) -> u64 {
+        price_bps * RATE_PRECISION / BPS_DIVISOR
+    }
+
+    fn params() -> ProtocolParams {
+        ProtocolParams {
+            borrowing_fee_bps: 50,
+            redemption_fee_floor_bps: 50,
+            ..ProtocolParams::default()
+        }
+    }
+
+    fn apply(params: &mut ProtocolParams, adjustment: &PegAdjustment) {
+        params.borrowing_fee_bps = adjustment.borrowing_fee_bps;
+        params.redemption_fee_floor_bps = adjustment.redemption_fee_floor_bps;
+    }
+
+    #[test]
+    fn test_below_peg_raises_borrowing_and_cheapens_redemption() {
+        let mut peg = PegController::new(PegConfig::default()).unwrap();
+        let mut params = params();
+
+        // $0.98: 200bps below, half of it capped at 25bps per step
+        let adjustment = peg.step(rate(9_800), &params, 0).unwrap();
+        assert_eq!(adjustment.deviation_bps, -200);
+        assert_eq!(adjustment.regime, PegRegime::BelowPeg);
+        assert_eq!((adjustment.borrowing_fee_bps, adjustment.redemption_fee_floor_bps), (75, 25));
+        apply(&mut params, &adjustment);
+
+        // The floor stops at its governance bound
+        let adjustment = peg.step(rate(9_800), &params, 6).unwrap();
+        assert_eq!((adjustment.borrowing_fee_bps, adjustment.redemption_fee_floor_bps), (100, 10));
+        assert_eq!(peg.baseline(), Some((50, 50)));
+    }
+
+    #[test]
+    fn test_above_peg_cheapens_borrowing() {
+        let mut peg = PegController::new(PegConfig::default()).unwrap();
+
+        // $1.006: 60bps above, a 30bps step capped at 25
+        let adjustment = peg.step(rate(10_060), &params(), 0).unwrap();
+        assert_eq!(adjustment.regime, PegRegime::AbovePeg);
+        assert_eq!((adjustment.borrowing_fee_bps, adjustment.redemption_fee_floor_bps), (25, 75));
+    }
+
+    #[test]
+    fn test_hysteresis_and_relaxation() {
+        let mut peg = PegController::new(PegConfig::default()).unwrap();
+        let mut params = params();
+
+        // Inside the engage threshold nothing happens
+        assert!(peg.step(rate(9_960), &params, 0).is_none());
+        assert_eq!(peg.regime(), PegRegime::Neutral);
+
+        let adjustment = peg.step(rate(9_940), &params, 6).unwrap();
+        apply(&mut params, &adjustment);
+        assert_eq!((params.borrowing_fee_bps, params.redemption_fee_floor_bps), (75, 25));
+
+        // Recovering to -0.3% is not enough to release
+        let adjustment = peg.step(rate(9_970), &params, 12).unwrap();
+        assert_eq!(adjustment.regime, PegRegime::BelowPeg);
+        apply(&mut params, &adjustment);
+        assert_eq!((params.borrowing_fee_bps, params.redemption_fee_floor_bps), (90, 10));
+
+        // Within 0.2% the controller releases and fees step back
+        let adjustment = peg.step(rate(9_990), &params, 18).unwrap();
+        assert_eq!(adjustment.regime, PegRegime::Neutral);
+        apply(&mut params, &adjustment);
+        assert_eq!((params.borrowing_fee_bps, params.redemption_fee_floor_bps), (65, 35));
+
+        let adjustment = peg.step(rate(10_000), &params, 24).unwrap();
+        apply(&mut params, &adjustment);
+        assert_eq!((params.borrowing_fee_bps, params.redemption_fee_floor_bps), (50, 50));
+        assert_eq!(peg.baseline(), None);
+        assert!(peg.step(rate(10_000), &params, 30).is_none());
+    }
+
+    #[test]
+    fn test_step_interval_and_rebase() {
+        let mut peg = PegController::new(PegConfig::default()).unwrap();
+        let mut params = params();
+        assert!(peg.is_due(0));
+
+        let adjustment = peg.step(rate(9_800), &params, 10).unwrap();
+        apply(&mut params, &adjustment);
+        assert!(!peg.is_due(15));
+        assert!(peg.is_due(16));
+
+        params.borrowing_fee_bps = 100;
+        peg.rebase(&params);
+        assert_eq!(peg.baseline(), Some((100, 25)));
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(PegConfig::default().validate().is_ok());
+        assert!(PegConfig { release_bps: 60, ..Default::default() }.validate().is_err());
+        assert!(PegConfig { max_step_bps: 0, ..Default::default() }.validate().is_err());
+        assert!(PegConfig { max_borrowing_fee_bps: 1_000, ..Default::default() }.validate().is_err());
+        assert!(PegConfig { min_redemption_fee_floor_bps: 0, ..Default::default() }.validate().is_err());
+        assert!(PegController::new(PegConfig { interval_blocks: 0, ..Default::default() }).is_err());
+    }
+}
@@ -0,0 +1,242 @@
+//! CDP risk reports.
+//!
+//! Summarises how close a CDP is to liquidation: its current ratio, the BTC
+//! price at which it becomes liquidatable, how its ratio responds to price
+//! shocks, and how long it would survive a steady price drift.
+//!
+//! All figures use integer math so reports are reproducible across nodes.
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::cdp::CDP;
+use crate::utils::constants::{BPS_DIVISOR, RATIO_PRECISION, SATS_PER_BTC};
+
+/// Default price shocks, applied both down and up (10%, 20%, 30%, 50%)
+pub const DEFAULT_PRICE_SHOCKS_BPS: [u64; 4] = [1_000, 2_000, 3_000, 5_000];
+
+/// Default daily price drift (-1% a day)
+pub const DEFAULT_DRIFT_BPS_PER_DAY: i64 = -100;
+
+/// Default horizon for time-to-liquidation (one year)
+pub const DEFAULT_HORIZON_DAYS: u64 = 365;
+
+/// Market assumptions a risk report is computed under
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RiskAssumptions {
+    /// Price moves to project in basis points (at most 100%), applied both
+    /// down and up
+    pub price_shocks_bps: Vec<u64>,
+    /// Compounding daily price drift in basis points
+    pub drift_bps_per_day: i64,
+    /// Days to project the drift over
+    pub horizon_days: u64,
+}
+
+impl Default for RiskAssumptions {
+    fn default() -> Self {
+        Self {
+            price_shocks_bps: DEFAULT_PRICE_SHOCKS_BPS.to_vec(),
+            drift_bps_per_day: DEFAULT_DRIFT_BPS_PER_DAY,
+            horizon_days: DEFAULT_HORIZON_DAYS,
+        }
+    }
+}
+
+impl RiskAssumptions {
+    /// Create with custom price shocks
+    pub fn with_shocks(mut self, price_shocks_bps: Vec<u64>) -> Self {
+        self.price_shocks_bps = price_shocks_bps;
+        self
+    }
+
+    /// Create with a custom daily drift
+    pub fn with_drift(mut self, drift_bps_per_day: i64) -> Self {
+        self.drift_bps_per_day = drift_bps_per_day;
+        self
+    }
+
+    /// Create with a custom projection horizon
+    pub fn with_horizon(mut self, horizon_days: u64) -> Self {
+        self.horizon_days = horizon_days;
+        self
+    }
+}
+
+/// Ratio of a CDP after a price move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriceProjection {
+    /// Price move in basis points (negative = down)
+    pub price_change_bps: i64,
+    /// BTC price after the move in cents
+    pub btc_price: u64,
+    /// Collateral ratio after the move in basis points
+    pub ratio_bps: u64,
+    /// Whether the CDP would be liquidatable
+    pub liquidatable: bool,
+}
+
+/// Risk report for a single CDP
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RiskReport {
+    /// BTC price the report was computed at in cents
+    pub btc_price: u64,
+    /// Collateral ratio in basis points (`u64::MAX` without debt)
+    pub ratio_bps: u64,
+    /// Minimum collateral ratio in basis points
+    pub min_ratio_bps: u64,
+    /// BTC price in cents below which the CDP is liquidatable (0 without debt)
+    pub liquidation_price: u64,
+    /// Ratio headroom above the minimum in basis points (negative when below)
+    pub buffer_bps: i64,
+    /// Ratios under each price shock, from the largest drop to the largest rise
+    pub projections: Vec<PriceProjection>,
+    /// Days until liquidation under the assumed drift, if within the horizon
+    pub days_to_liquidation: Option<u64>,
+}
+
+impl RiskReport {
+    /// Whether the CDP is liquidatable at the report price
+    pub fn is_liquidatable(&self) -> bool {
+        self.ratio_bps < self.min_ratio_bps
+    }
+}
+
+/// Assess `cdp` at `btc_price_cents` against `min_ratio` (in percent)
+pub fn assess(cdp: &CDP, btc_price_cents: u64, min_ratio: u64, assumptions: &RiskAssumptions) -> RiskReport {
+    let min_ratio_bps = min_ratio * (BPS_DIVISOR / RATIO_PRECISION);
+    let ratio_bps = ratio_at(cdp, btc_price_cents);
+
+    let liquidation_price = liquidation_price(cdp, min_ratio);
+
+    let buffer_bps = (ratio_bps as i128 - min_ratio_bps as i128).clamp(i64::MIN as i128, i64::MAX as i128) as i64;
+
+    let mut changes: Vec<i64> = assumptions
+        .price_shocks_bps
+        .iter()
+        .map(|shock| (*shock).min(BPS_DIVISOR) as i64)
+        .flat_map(|shock| [-shock, shock])
+        .collect();
+    changes.sort_unstable();
+    changes.dedup();
+
+    let projections = changes
+        .into_iter()
+        .map(|change| {
+            let price = apply_change(btc_price_cents, change);
+            let ratio_bps = ratio_at(cdp, price);
+            PriceProjection {
+                price_change_bps: change,
+                btc_price: price,
+                ratio_bps,
+                liquidatable: ratio_bps < min_ratio_bps,
+            }
+        })
+        .collect();
+
+    let days_to_liquidation = if ratio_bps < min_ratio_bps {
+        Some(0)
+    } else if cdp.debt_cents == 0 || assumptions.drift_bps_per_day >= 0 {
+        None
+    } else {
+        let mut price = btc_price_cents;
+        (1..=assumptions.horizon_days).find(|_| {
+            price = apply_change(price, assumptions.drift_bps_per_day);
+            ratio_at(cdp, price) < min_ratio_bps
+        })
+    };
+
+    RiskReport {
+        btc_price: btc_price_cents,
+        ratio_bps,
+        min_ratio_bps,
+        liquidation_price,
+        buffer_bps,
+        projections,
+        days_to_liquidation,
+    }
+}
+
+/// BTC price in cents below which `cdp` is liquidatable at `min_ratio` (in
+/// percent), or 0 without debt
+pub fn liquidation_price(cdp: &CDP, min_ratio: u64) -> u64 {
+    if cdp.debt_cents == 0 || cdp.collateral_sats == 0 {
+        return 0;
+    }
+    let min_ratio_bps = min_ratio * (BPS_DIVISOR / RATIO_PRECISION);
+    let price = cdp.debt_cents as u128 * min_ratio_bps as u128 * SATS_PER_BTC as u128
+        / (cdp.collateral_sats as u128 * BPS_DIVISOR as u128);
+    price.min(u64::MAX as u128) as u64
+}
+
+/// Collateral ratio of `cdp` at `btc_price_cents` in basis points
+fn ratio_at(cdp: &CDP, btc_price_cents: u64) -> u64 {
+    if cdp.debt_cents == 0 {
+        return u64::MAX;
+    }
+    let value = cdp.collateral_sats as u128 * btc_price_cents as u128 / SATS_PER_BTC as u128;
+    (value * BPS_DIVISOR as u128 / cdp.debt_cents as u128).min(u64::MAX as u128) as u64
+}
+
+/// Move `price` by `change_bps`, never below zero
+fn apply_change(price: u64, change_bps: i64) -> u64 {
+    let scaled = price as i128 * (BPS_DIVISOR as i128 + change_bps as i128) / BPS_DIVISOR as i128;
+    scaled.clamp(0, u64::MAX as i128) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::crypto::KeyPair;
+
+    fn cdp(collateral_sats: u64, debt_cents: u64) -> CDP {
+        let mut cdp = CDP::new(*KeyPair::generate().public_key(), 1, 0);
+        cdp.collateral_sats = collateral_sats;
+        cdp.debt_cents = debt_cents;
+        cdp
+    }
+
+    #[test]
+    fn test_report_figures() {
+        // 1 BTC against $50,000 at $100,000 is 200%
+        let report = assess(&cdp(SATS_PER_BTC, 5_000_000), 10_000_000, 110, &RiskAssumptions::default());
+
+        assert_eq!(report.ratio_bps, 20_000);
+        assert_eq!(report.min_ratio_bps, 11_000);
+        assert_eq!(report.buffer_bps, 9_000);
+        assert_eq!(report.liquidation_price, 5_500_000);
+        assert!(!report.is_liquidatable());
+
+        assert_eq!(report.projections.len(), 8);
+        let worst = report.projections[0];
+        assert_eq!(worst.price_change_bps, -5_000);
+        assert_eq!(worst.btc_price, 5_000_000);
+        assert_eq!(worst.ratio_bps, 10_000);
+        assert!(worst.liquidatable);
+        assert!(!report.projections[1].liquidatable);
+        assert_eq!(report.projections[7].ratio_bps, 30_000);
+    }
+
+    #[test]
+    fn test_days_to_liquidation() {
+        let position = cdp(SATS_PER_BTC, 5_000_000);
+
+        // -1% a day takes 60 days to fall from $100,000 below $55,000
+        let report = assess(&position, 10_000_000, 110, &RiskAssumptions::default());
+        assert_eq!(report.days_to_liquidation, Some(60));
+
+        let rising = RiskAssumptions::default().with_drift(50);
+        assert_eq!(assess(&position, 10_000_000, 110, &rising).days_to_liquidation, None);
+
+        let short = RiskAssumptions::default().with_horizon(30);
+        assert_eq!(assess(&position, 10_000_000, 110, &short).days_to_liquidation, None);
+
+        // Already below the minimum
+        assert_eq!(assess(&position, 5_000_000, 110, &short).days_to_liquidation, Some(0));
+
+        // No debt, no risk
+        let report = assess(&cdp(SATS_PER_BTC, 0), 10_000_000, 110, &RiskAssumptions::default());
+        assert_eq!(report.ratio_bps, u64::MAX);
+        assert_eq!(report.liquidation_price, 0);
+        assert_eq!(report.days_to_liquidation, None);
+    }
+}
@@ -0,0 +1,317 @@
+//! zkUSD savings rate.
+//!
+//! Holders lock zkUSD in the savings pot and earn a governance-set annual
+//! rate. Interest is paid out of a reserve credited with borrowing and
+//! redemption fees, so the pot never pays out more than the protocol has
+//! earned.
+//!
+//! Deposits are held as shares of the pot. Accruing interest raises the
+//! value of every share at once, so accrual costs the same no matter how
+//! many depositors there are.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::core::token::TokenAmount;
+use crate::error::{Error, Result};
+use crate::utils::constants::{BLOCK_TIME_SECS, BPS_DIVISOR};
+use crate::utils::crypto::PublicKey;
+use crate::utils::undo::{Undo, UndoMap};
+
+/// Blocks in a year at the target block time
+pub const BLOCKS_PER_YEAR: u64 = 365 * 24 * 3600 / BLOCK_TIME_SECS;
+
+/// Highest savings rate governance may set (20% a year)
+pub const MAX_SAVINGS_RATE_BPS: u64 = 2_000;
+
+/// Fixed-point precision of the share index
+const INDEX_PRECISION: u128 = 1_000_000_000_000_000_000;
+
+/// The savings pot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavingsPot {
+    /// Annual savings rate in basis points
+    rate_bps: u64,
+    /// Value of one share, scaled by `INDEX_PRECISION`
+    index: u128,
+    /// Shares per depositor
+    shares: UndoMap<HashMap<PublicKey, u128>>,
+    /// Total shares outstanding
+    total_shares: u128,
+    /// Fees available to pay interest
+    reserve: TokenAmount,
+    /// Block of the last accrual
+    last_accrual: u64,
+    /// Interest paid out since launch
+    interest_paid: TokenAmount,
+}
+
+impl Default for SavingsPot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SavingsPot {
+    /// Create an empty pot with a zero rate
+    pub fn new() -> Self {
+        Self {
+            rate_bps: 0,
+            index: INDEX_PRECISION,
+            shares: UndoMap::new(),
+            total_shares: 0,
+            reserve: TokenAmount::ZERO,
+            last_accrual: 0,
+            interest_paid: TokenAmount::ZERO,
+        }
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // QUERIES
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Annual savings rate in basis points
+    pub fn rate_bps(&self) -> u64 {
+        self.rate_bps
+    }
+
+    /// Fees available to pay interest
+    pub fn reserve(&self) -> TokenAmount {
+        self.reserve
+    }
+
+    /// zkUSD locked in the pot, including accrued interest
+    pub fn total_locked(&self) -> TokenAmount {
+        self.value_of(self.total_shares)
+    }
+
+    /// Interest paid out since launch
+    pub fn interest_paid(&self) -> TokenAmount {
+        self.interest_paid
+    }
+
+    /// Number of depositors
+    pub fn depositor_count(&self) -> usize {
+        self.shares.len()
+    }
+
+    /// Block of the last accrual
+    pub fn last_accrual(&self) -> u64 {
+        self.last_accrual
+    }
+
+    /// Savings balance of `depositor`, as of the last accrual
+    pub fn balance_of(&self, depositor: &PublicKey) -> TokenAmount {
+        self.value_of(self.shares.get(depositor).copied().unwrap_or(0))
+    }
+
+    fn value_of(&self, shares: u128) -> TokenAmount {
+        TokenAmount::from_cents((shares * self.index / INDEX_PRECISION) as u64)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // ACCRUAL
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Credit protocol fees to the interest reserve
+    pub fn credit_fees(&mut self, amount: TokenAmount) {
+        self.reserve = self.reserve.saturating_add(amount);
+    }
+
+    /// Accrue interest up to `block_height`, returning the amount paid.
+    ///
+    /// Interest is capped by the reserve; whatever the reserve cannot cover
+    /// is forgone rather than owed.
+    pub fn accrue(&mut self, block_height: u64) -> TokenAmount {
+        if block_height <= self.last_accrual {
+            return TokenAmount::ZERO;
+        }
+        let elapsed = block_height - self.last_accrual;
+        self.last_accrual = block_height;
+
+        if self.total_shares == 0 || self.rate_bps == 0 {
+            return TokenAmount::ZERO;
+        }
+
+        let locked = self.total_locked();
+        let owed = locked.cents() as u128 * self.rate_bps as u128 * elapsed as u128
+            / (BPS_DIVISOR as u128 * BLOCKS_PER_YEAR as u128);
+        let interest = owed.min(self.reserve.cents() as u128);
+        if interest == 0 {
+            return TokenAmount::ZERO;
+        }
+
+        // Rounding in the index can only pay out less than `interest`
+        self.index += interest * INDEX_PRECISION / self.total_shares;
+        let paid = self.total_locked().saturating_sub(locked);
+        self.reserve = self.reserve.saturating_sub(paid);
+        self.interest_paid = self.interest_paid.saturating_add(paid);
+        paid
+    }
+
+    /// Set the savings rate.
+    ///
+    /// The caller should accrue first so the old rate applies up to the change.
+    pub fn set_rate(&mut self, rate_bps: u64) -> Result<()> {
+        if rate_bps > MAX_SAVINGS_RATE_BPS {
+            return Err(Error::InvalidParameter {
+                name: "savings_rate".into(),
+                reason: format!("{}bps exceeds maximum of {}bps", rate_bps, MAX_SAVINGS_RATE_BPS),
+            });
+        }
+        self.rate_bps = rate_bps;
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // DEPOSITS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Lock `amount` for `depositor`, returning their new balance.
+    ///
+    /// The caller burns the tokens and should accrue first.
+    pub fn deposit(&mut self, depositor: PublicKey, amount: TokenAmount) -> Result<TokenAmount> {
+        if amount.is_zero() {
+            return Err(Error::ZeroAmount);
+        }
+
+        let new_shares = amount.cents() as u128 * INDEX_PRECISION / self.index;
+        if new_shares == 0 {
+            return Err(Error::InvalidParameter {
+                name: "amount".into(),
+                reason: "too small to buy a savings share".into(),
+            });
+        }
+
+        *self.shares.get_or_insert_with(depositor, || 0) += new_shares;
+        self.total_shares += new_shares;
+        Ok(self.balance_of(&depositor))
+    }
+
+    /// Unlock `amount` for `depositor` (zero withdraws everything), returning
+    /// the amount withdrawn.
+    ///
+    /// The caller mints the tokens back and should accrue first.
+    pub fn withdraw(&mut self, depositor: &PublicKey, amount: TokenAmount) -> Result<TokenAmount> {
+        let shares = self.shares.get(depositor).copied().ok_or_else(|| Error::InvalidParameter {
+            name: "depositor".into(),
+            reason: "no savings deposit".into(),
+        })?;
+        let balance = self.value_of(shares);
+
+        if amount > balance {
+            return Err(Error::InvalidParameter {
+                name: "amount".into(),
+                reason: format!("exceeds savings balance of {}", balance),
+            });
+        }
+
+        let (burned, withdrawn) = if amount.is_zero() || amount == balance {
+            (shares, balance)
+        } else {
+            // Round the shares burned up so the pot never pays out more than
+            // the shares were worth
+            let burned = (amount.cents() as u128 * INDEX_PRECISION).div_ceil(self.index);
+            (burned.min(shares), amount)
+        };
+
+        if burned == shares {
+            self.shares.remove(depositor);
+        } else {
+            self.shares.insert(*depositor, shares - burned);
+        }
+        self.total_shares -= burned;
+        Ok(withdrawn)
+    }
+}
+
+/// Pot scalars saved when an undo log opens
+#[derive(Debug, Clone)]
+pub struct SavingsMark {
+    rate_bps: u64,
+    index: u128,
+    total_shares: u128,
+    reserve: TokenAmount,
+    last_accrual: u64,
+    interest_paid: TokenAmount,
+}
+
+impl Undo for SavingsPot {
+    type Mark = SavingsMark;
+
+    fn begin_undo(&mut self) -> SavingsMark {
+        self.shares.begin_undo();
+        SavingsMark {
+            rate_bps: self.rate_bps,
+            index: self.index,
+            total_shares: self.total_shares,
+            reserve: self.reserve,
+            last_accrual: self.last_accrual,
+            interest_paid: self.interest_paid,
+        }
+    }
+
+    fn commit_undo(&mut self) {
+        self.shares.commit_undo();
+    }
+
+    fn rollback_undo(&mut self, mark: SavingsMark) {
+        self.shares.rollback_undo();
+        self.rate_bps = mark.rate_bps;
+        self.index = mark.index;
+        self.total_shares = mark.total_shares;
+        self.reserve = mark.reserve;
+        self.last_accrual = mark.last_accrual;
+        self.interest_paid = mark.interest_paid;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::crypto::KeyPair;
+
+    #[test]
+    fn test_deposit_accrue_withdraw() {
+        let alice = *KeyPair::generate().public_key();
+        let bob = *KeyPair::generate().public_key();
+        let mut pot = SavingsPot::new();
+        pot.set_rate(1_000).unwrap();
+        pot.credit_fees(TokenAmount::from_cents(1_000_000));
+
+        pot.deposit(alice, TokenAmount::from_cents(1_000_000)).unwrap();
+        pot.deposit(bob, TokenAmount::from_cents(3_000_000)).unwrap();
+
+        // 10% a year on $40,000 for a year is $4,000
+        let paid = pot.accrue(BLOCKS_PER_YEAR);
+        assert!(paid.cents() >= 399_999 && paid.cents() <= 400_000);
+        assert_eq!(pot.reserve().cents(), 1_000_000 - paid.cents());
+        assert!(pot.balance_of(&alice).cents() >= 1_099_999);
+        assert!(pot.balance_of(&bob).cents() >= 3_299_999);
+
+        // Partial then full withdrawal
+        assert_eq!(pot.withdraw(&alice, TokenAmount::from_cents(100_000)).unwrap().cents(), 100_000);
+        let rest = pot.withdraw(&alice, TokenAmount::ZERO).unwrap();
+        assert!(rest.cents() >= 999_998);
+        assert_eq!(pot.depositor_count(), 1);
+        assert!(pot.withdraw(&alice, TokenAmount::ZERO).is_err());
+        assert!(pot.withdraw(&bob, TokenAmount::from_cents(10_000_000)).is_err());
+    }
+
+    #[test]
+    fn test_interest_capped_by_reserve() {
+        let alice = *KeyPair::generate().public_key();
+        let mut pot = SavingsPot::new();
+        pot.set_rate(MAX_SAVINGS_RATE_BPS).unwrap();
+        assert!(pot.set_rate(MAX_SAVINGS_RATE_BPS + 1).is_err());
+
+        pot.deposit(alice, TokenAmount::from_cents(1_000_000)).unwrap();
+        assert!(pot.accrue(BLOCKS_PER_YEAR).is_zero());
+
+        pot.credit_fees(TokenAmount::from_cents(5_000));
+        let paid = pot.accrue(2 * BLOCKS_PER_YEAR);
+        assert!(paid.cents() <= 5_000 && paid.cents() >= 4_999);
+        assert!(pot.total_locked().cents() <= 1_005_000);
+        assert!(pot.accrue(2 * BLOCKS_PER_YEAR).is_zero());
+    }
+}
@@ -4,6 +4,7 @@
 //! - Token minting and burning
 //! - Balance tracking
 //! - Transfer operations
+//! - Allowances for third-party spending, including signed permits
 //! - Supply management
 
 use serde::{Deserialize, Serialize};
@@ -11,8 +12,9 @@ use std::collections::HashMap;
 
 use crate::error::{Error, Result};
 use crate::utils::constants::*;
-use crate::utils::crypto::{Hash, PublicKey};
+use crate::utils::crypto::{create_message_hash, verify_signature, Hash, KeyPair, PublicKey, Signature};
 use crate::utils::math::*;
+use crate::utils::undo::{Undo, UndoMap};
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // TOKEN AMOUNT
@@ -20,6 +22,7 @@ use crate::utils::math::*;
 
 /// Strongly-typed token amount (prevents mixing sats and cents)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct TokenAmount(u64);
 
 impl TokenAmount {
@@ -112,6 +115,8 @@ pub enum TokenOperation {
     Transfer,
     /// Redemption for collateral
     Redeem,
+    /// Spending allowance set (`from` is the owner, `to` the spender)
+    Approve,
 }
 
 /// Record of a token operation
@@ -131,6 +136,76 @@ pub struct TokenEvent {
     pub tx_hash: Hash,
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// APPROVAL PERMIT
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Approval signed off-chain by the owner (EIP-2612 style).
+///
+/// Anyone holding the permit can submit it, so the owner never has to send
+/// an operation of their own to authorize a spender.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct ApprovePermit {
+    /// Token owner granting the allowance
+    pub owner: PublicKey,
+    /// Account allowed to spend
+    pub spender: PublicKey,
+    /// Allowance to set
+    pub amount: TokenAmount,
+    /// Owner's permit nonce (must equal [`ZkUSD::permit_nonce`])
+    pub nonce: u64,
+    /// Last block height at which the permit may be used
+    pub deadline: u64,
+    /// Owner's signature over [`message_hash`](Self::message_hash)
+    pub signature: Signature,
+}
+
+impl ApprovePermit {
+    /// Domain tag for permit signatures
+    pub const DOMAIN: &'static str = "permit";
+
+    /// Create an unsigned permit
+    pub fn new(
+        owner: PublicKey,
+        spender: PublicKey,
+        amount: TokenAmount,
+        nonce: u64,
+        deadline: u64,
+    ) -> Self {
+        Self {
+            owner,
+            spender,
+            amount,
+            nonce,
+            deadline,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        }
+    }
+
+    /// Domain-separated hash the owner signs
+    pub fn message_hash(&self) -> Hash {
+        let mut data = Vec::with_capacity(2 * PUBKEY_LENGTH + 24);
+        data.extend_from_slice(self.owner.as_bytes());
+        data.extend_from_slice(self.spender.as_bytes());
+        data.extend_from_slice(&self.amount.cents().to_be_bytes());
+        data.extend_from_slice(&self.nonce.to_be_bytes());
+        data.extend_from_slice(&self.deadline.to_be_bytes());
+        create_message_hash(Self::DOMAIN, &data)
+    }
+
+    /// Sign the permit as the owner
+    pub fn sign(mut self, keypair: &KeyPair) -> Self {
+        self.signature = keypair.sign(&self.message_hash());
+        self
+    }
+
+    /// Check the owner's signature
+    pub fn verify_signature(&self) -> bool {
+        verify_signature(&self.owner, &self.message_hash(), &self.signature)
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // ZKUSD TOKEN
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -147,7 +222,16 @@ pub struct ZkUSD {
     /// Total supply in cents
     total_supply: TokenAmount,
     /// Balances by public key
-    balances: HashMap<PublicKey, TokenAmount>,
+    balances: UndoMap<HashMap<PublicKey, TokenAmount>>,
+    /// Allowances by (owner, spender)
+    #[serde(default)]
+    allowances: UndoMap<HashMap<(PublicKey, PublicKey), TokenAmount>>,
+    /// Next permit nonce by owner
+    #[serde(default)]
+    permit_nonces: UndoMap<HashMap<PublicKey, u64>>,
+    /// Total supply at the end of each block in which it changed, by height
+    #[serde(default)]
+    supply_checkpoints: Vec<(u64, TokenAmount)>,
     /// Recent events (for client-side tracking)
     events: Vec<TokenEvent>,
     /// Maximum events to keep in memory
@@ -168,7 +252,10 @@ impl ZkUSD {
             symbol: "zkUSD".to_string(),
             decimals: ZKUSD_DECIMALS,
             total_supply: TokenAmount::ZERO,
-            balances: HashMap::new(),
+            balances: UndoMap::new(),
+            allowances: UndoMap::new(),
+            permit_nonces: UndoMap::new(),
+            supply_checkpoints: Vec::new(),
             events: Vec::new(),
             max_events: 1000,
         }
@@ -183,6 +270,19 @@ impl ZkUSD {
         self.total_supply
     }
 
+    /// Total supply as of the end of `block_height`.
+    ///
+    /// Unlike a figure passed in by a caller, this cannot be inflated or
+    /// deflated after the fact, so it is suitable for snapshot-based weights
+    /// and quorums.
+    pub fn total_supply_at(&self, block_height: u64) -> TokenAmount {
+        let idx = self.supply_checkpoints.partition_point(|(height, _)| *height <= block_height);
+        match idx {
+            0 => TokenAmount::ZERO,
+            _ => self.supply_checkpoints[idx - 1].1,
+        }
+    }
+
     /// Get balance of an address
     pub fn balance_of(&self, owner: &PublicKey) -> TokenAmount {
         self.balances.get(owner).copied().unwrap_or(TokenAmount::ZERO)
@@ -220,6 +320,7 @@ impl ZkUSD {
 
         self.balances.insert(to, new_balance);
         self.total_supply = new_supply;
+        self.checkpoint_supply(block_height);
 
         // Record event
         self.add_event(TokenEvent {
@@ -263,6 +364,7 @@ impl ZkUSD {
         }
 
         self.total_supply = self.total_supply.saturating_sub(amount);
+        self.checkpoint_supply(block_height);
 
         // Record event
         self.add_event(TokenEvent {
@@ -330,6 +432,115 @@ impl ZkUSD {
         Ok(())
     }
 
+    // ═══════════════════════════════════════════════════════════════════════════
+    // ALLOWANCES
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Get the amount `spender` may still transfer out of `owner`'s balance
+    pub fn allowance(&self, owner: &PublicKey, spender: &PublicKey) -> TokenAmount {
+        self.allowances
+            .get(&(*owner, *spender))
+            .copied()
+            .unwrap_or(TokenAmount::ZERO)
+    }
+
+    /// Set the allowance of `spender` over `owner`'s tokens.
+    ///
+    /// Replaces any previous allowance; approving zero revokes it.
+    pub fn approve(
+        &mut self,
+        owner: PublicKey,
+        spender: PublicKey,
+        amount: TokenAmount,
+        block_height: u64,
+        tx_hash: Hash,
+    ) -> Result<()> {
+        if owner == spender {
+            return Err(Error::InvalidParameter {
+                name: "spender".into(),
+                reason: "Cannot approve self".into(),
+            });
+        }
+
+        if amount.is_zero() {
+            self.allowances.remove(&(owner, spender));
+        } else {
+            self.allowances.insert((owner, spender), amount);
+        }
+
+        // Record event
+        self.add_event(TokenEvent {
+            operation: TokenOperation::Approve,
+            from: Some(owner),
+            to: Some(spender),
+            amount,
+            block_height,
+            tx_hash,
+        });
+
+        Ok(())
+    }
+
+    /// Nonce the next permit from `owner` must carry
+    pub fn permit_nonce(&self, owner: &PublicKey) -> u64 {
+        self.permit_nonces.get(owner).copied().unwrap_or(0)
+    }
+
+    /// Apply an owner-signed permit, setting the spender's allowance
+    pub fn permit(&mut self, permit: &ApprovePermit, block_height: u64, tx_hash: Hash) -> Result<()> {
+        if block_height > permit.deadline {
+            return Err(Error::PermitExpired {
+                deadline: permit.deadline,
+                block_height,
+            });
+        }
+
+        let expected = self.permit_nonce(&permit.owner);
+        if permit.nonce != expected {
+            return Err(Error::InvalidParameter {
+                name: "nonce".into(),
+                reason: format!("Permit nonce {} does not match expected {}", permit.nonce, expected),
+            });
+        }
+
+        if !permit.verify_signature() {
+            return Err(Error::InvalidSignature);
+        }
+
+        self.approve(permit.owner, permit.spender, permit.amount, block_height, tx_hash)?;
+        self.permit_nonces.insert(permit.owner, expected + 1);
+
+        Ok(())
+    }
+
+    /// Transfer tokens out of `from`'s balance on behalf of `spender`,
+    /// spending its allowance
+    pub fn transfer_from(
+        &mut self,
+        spender: PublicKey,
+        from: PublicKey,
+        to: PublicKey,
+        amount: TokenAmount,
+        block_height: u64,
+        tx_hash: Hash,
+    ) -> Result<()> {
+        let allowed = self.allowance(&from, &spender);
+        let remaining = allowed.checked_sub(amount).ok_or(Error::InsufficientAllowance {
+            required: amount.cents(),
+            available: allowed.cents(),
+        })?;
+
+        self.transfer(from, to, amount, block_height, tx_hash)?;
+
+        if remaining.is_zero() {
+            self.allowances.remove(&(from, spender));
+        } else {
+            self.allowances.insert((from, spender), remaining);
+        }
+
+        Ok(())
+    }
+
     // ═══════════════════════════════════════════════════════════════════════════
     // QUERIES
     // ═══════════════════════════════════════════════════════════════════════════
@@ -344,6 +555,11 @@ impl ZkUSD {
         &self.balances
     }
 
+    /// Get all allowances by (owner, spender) (for auditing)
+    pub fn all_allowances(&self) -> &HashMap<(PublicKey, PublicKey), TokenAmount> {
+        &self.allowances
+    }
+
     /// Verify supply invariant (total_supply == sum of all balances)
     pub fn verify_supply_invariant(&self) -> bool {
         let sum: u64 = self.balances.values().map(|b| b.cents()).sum();
@@ -367,11 +583,28 @@ impl ZkUSD {
     // INTERNAL
     // ═══════════════════════════════════════════════════════════════════════════
 
+    /// Record the current total supply for `block_height`. Supply changes
+    /// within one block collapse into a single checkpoint; a height below the
+    /// latest checkpoint (replayed blocks) updates the latest one.
+    fn checkpoint_supply(&mut self, block_height: u64) {
+        match self.supply_checkpoints.last_mut() {
+            Some((height, supply)) if *height >= block_height => *supply = self.total_supply,
+            _ => self.supply_checkpoints.push((block_height, self.total_supply)),
+        }
+    }
+
     /// Add an event (with pruning)
     fn add_event(&mut self, event: TokenEvent) {
         self.events.push(event);
 
-        // Prune old events if needed
+        // Prune old events if needed; while an undo log is open they are
+        // kept so a rollback only has to truncate
+        if !self.balances.is_recording() {
+            self.prune_events();
+        }
+    }
+
+    fn prune_events(&mut self) {
         if self.events.len() > self.max_events {
             self.events.drain(0..self.events.len() - self.max_events);
         }
@@ -402,10 +635,64 @@ impl ZkUSD {
             data.extend_from_slice(&balance.cents().to_be_bytes());
         }
 
+        let mut sorted_allowances: Vec<_> = self.allowances.iter().collect();
+        sorted_allowances.sort_by_key(|((owner, spender), _)| (owner.as_bytes(), spender.as_bytes()));
+
+        for ((owner, spender), amount) in sorted_allowances {
+            data.extend_from_slice(owner.as_bytes());
+            data.extend_from_slice(spender.as_bytes());
+            data.extend_from_slice(&amount.cents().to_be_bytes());
+        }
+
         Hash::sha256(&data)
     }
 }
 
+/// Token state an undo log does not cover, saved when it opens
+#[derive(Debug, Clone, Copy)]
+pub struct ZkUSDMark {
+    total_supply: TokenAmount,
+    supply_checkpoints: usize,
+    last_checkpoint: Option<(u64, TokenAmount)>,
+    events: usize,
+}
+
+impl Undo for ZkUSD {
+    type Mark = ZkUSDMark;
+
+    fn begin_undo(&mut self) -> ZkUSDMark {
+        self.balances.begin_undo();
+        self.allowances.begin_undo();
+        self.permit_nonces.begin_undo();
+        ZkUSDMark {
+            total_supply: self.total_supply,
+            supply_checkpoints: self.supply_checkpoints.len(),
+            last_checkpoint: self.supply_checkpoints.last().copied(),
+            events: self.events.len(),
+        }
+    }
+
+    fn commit_undo(&mut self) {
+        self.balances.commit_undo();
+        self.allowances.commit_undo();
+        self.permit_nonces.commit_undo();
+        self.prune_events();
+    }
+
+    fn rollback_undo(&mut self, mark: ZkUSDMark) {
+        self.balances.rollback_undo();
+        self.allowances.rollback_undo();
+        self.permit_nonces.rollback_undo();
+        self.total_supply = mark.total_supply;
+        // Checkpoints are only appended or have their latest entry updated
+        self.supply_checkpoints.truncate(mark.supply_checkpoints);
+        if let (Some(last), Some(saved)) = (self.supply_checkpoints.last_mut(), mark.last_checkpoint) {
+            *last = saved;
+        }
+        self.events.truncate(mark.events);
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // TOKEN METADATA
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -516,6 +803,62 @@ mod tests {
         assert_eq!(token.total_supply(), TokenAmount::from_dollars(1000));
     }
 
+    #[test]
+    fn test_approve_and_transfer_from() {
+        let mut token = ZkUSD::new();
+        let owner = test_pubkey();
+        let spender = test_pubkey_2();
+        let recipient = PublicKey::new([0x04; PUBKEY_LENGTH]);
+
+        token.mint(owner, TokenAmount::from_dollars(1000), 1, test_hash()).unwrap();
+        token.approve(owner, spender, TokenAmount::from_dollars(300), 2, test_hash()).unwrap();
+        assert_eq!(token.allowance(&owner, &spender), TokenAmount::from_dollars(300));
+
+        token
+            .transfer_from(spender, owner, recipient, TokenAmount::from_dollars(200), 3, test_hash())
+            .unwrap();
+        assert_eq!(token.balance_of(&recipient), TokenAmount::from_dollars(200));
+        assert_eq!(token.allowance(&owner, &spender), TokenAmount::from_dollars(100));
+
+        let err = token
+            .transfer_from(spender, owner, recipient, TokenAmount::from_dollars(150), 4, test_hash())
+            .unwrap_err();
+        assert!(matches!(err, Error::InsufficientAllowance { .. }));
+
+        // Revoking leaves nothing to spend
+        token.approve(owner, spender, TokenAmount::ZERO, 5, test_hash()).unwrap();
+        assert_eq!(token.allowance(&owner, &spender), TokenAmount::ZERO);
+        assert!(token.approve(owner, owner, TokenAmount::from_dollars(1), 6, test_hash()).is_err());
+    }
+
+    #[test]
+    fn test_permit() {
+        let mut token = ZkUSD::new();
+        let owner = KeyPair::generate();
+        let spender = test_pubkey_2();
+
+        let permit = ApprovePermit::new(*owner.public_key(), spender, TokenAmount::from_dollars(50), 0, 10)
+            .sign(&owner);
+        assert!(permit.verify_signature());
+
+        // Expired
+        let err = token.permit(&permit, 11, test_hash()).unwrap_err();
+        assert!(matches!(err, Error::PermitExpired { deadline: 10, block_height: 11 }));
+
+        token.permit(&permit, 10, test_hash()).unwrap();
+        assert_eq!(token.allowance(owner.public_key(), &spender), TokenAmount::from_dollars(50));
+        assert_eq!(token.permit_nonce(owner.public_key()), 1);
+
+        // Replay is rejected
+        assert!(token.permit(&permit, 10, test_hash()).is_err());
+
+        // Tampered amount breaks the signature
+        let mut forged = ApprovePermit::new(*owner.public_key(), spender, TokenAmount::from_dollars(50), 1, 10)
+            .sign(&owner);
+        forged.amount = TokenAmount::from_dollars(5000);
+        assert_eq!(token.permit(&forged, 10, test_hash()), Err(Error::InvalidSignature));
+    }
+
     #[test]
     fn test_supply_invariant() {
         let mut token = ZkUSD::new();
@@ -530,6 +873,23 @@ mod tests {
         assert!(token.verify_supply_invariant());
     }
 
+    #[test]
+    fn test_total_supply_at() {
+        let mut token = ZkUSD::new();
+        let owner = test_pubkey();
+
+        token.mint(owner, TokenAmount::from_dollars(1000), 10, test_hash()).unwrap();
+        token.mint(owner, TokenAmount::from_dollars(500), 10, test_hash()).unwrap();
+        token.burn(owner, TokenAmount::from_dollars(300), 20, test_hash()).unwrap();
+        token.transfer(owner, test_pubkey_2(), TokenAmount::from_dollars(100), 30, test_hash()).unwrap();
+
+        assert_eq!(token.total_supply_at(9), TokenAmount::ZERO);
+        assert_eq!(token.total_supply_at(10), TokenAmount::from_dollars(1500));
+        assert_eq!(token.total_supply_at(19), TokenAmount::from_dollars(1500));
+        assert_eq!(token.total_supply_at(20), TokenAmount::from_dollars(1200));
+        assert_eq!(token.total_supply_at(1_000), token.total_supply());
+    }
+
     #[test]
     fn test_holder_count() {
         let mut token = ZkUSD::new();
@@ -4,14 +4,19 @@
 //! - Collateral deposits and withdrawals
 //! - Integration with Grail Pro for BTC<->zkBTC conversion
 //! - Collateral accounting
+//! - Proof of reserves against the on-chain outputs backing each CDP
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+use crate::core::config::FeeSource;
 use crate::error::{Error, Result};
+use crate::protocol::codec::canonical_hash;
 use crate::utils::constants::*;
 use crate::utils::crypto::{CDPId, Hash, PublicKey};
 use crate::utils::math::*;
+use crate::utils::undo::{Undo, UndoMap};
+use crate::zkp::inputs::merkle_root;
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // COLLATERAL AMOUNT
@@ -19,6 +24,7 @@ use crate::utils::math::*;
 
 /// Strongly-typed collateral amount in satoshis
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct CollateralAmount(u64);
 
 impl CollateralAmount {
@@ -120,6 +126,8 @@ pub enum VaultOperation {
     Seize,
     /// Collateral redistributed from liquidation
     Redistribute,
+    /// Collateral taken from a CDP as a protocol fee
+    FeeCollected,
 }
 
 /// Record of a vault operation
@@ -147,17 +155,20 @@ pub struct VaultState {
     /// Total collateral in the vault
     pub total_collateral: CollateralAmount,
     /// Collateral by CDP
-    pub collateral_by_cdp: HashMap<CDPId, CollateralAmount>,
+    pub collateral_by_cdp: UndoMap<HashMap<CDPId, CollateralAmount>>,
     /// Number of CDPs with collateral
     pub cdp_count: u64,
+    /// BTC fees held for the treasury, by source (not counted as collateral)
+    pub fee_buckets: BTreeMap<FeeSource, CollateralAmount>,
 }
 
 impl Default for VaultState {
     fn default() -> Self {
         Self {
             total_collateral: CollateralAmount::ZERO,
-            collateral_by_cdp: HashMap::new(),
+            collateral_by_cdp: UndoMap::new(),
             cdp_count: 0,
+            fee_buckets: BTreeMap::new(),
         }
     }
 }
@@ -258,31 +269,7 @@ impl Vault {
         block_height: u64,
         tx_hash: Hash,
     ) -> Result<()> {
-        if amount.is_zero() {
-            return Err(Error::ZeroAmount);
-        }
-
-        let current = self.state.collateral_by_cdp.get(&cdp_id).copied()
-            .ok_or_else(|| Error::CDPNotFound(cdp_id.to_hex()))?;
-
-        if amount > current {
-            return Err(Error::InsufficientCollateral {
-                required: amount.sats(),
-                available: current.sats(),
-            });
-        }
-
-        // Update CDP collateral
-        let new_amount = current.saturating_sub(amount);
-        if new_amount.is_zero() {
-            self.state.collateral_by_cdp.remove(&cdp_id);
-            self.state.cdp_count = self.state.cdp_count.saturating_sub(1);
-        } else {
-            self.state.collateral_by_cdp.insert(cdp_id, new_amount);
-        }
-
-        // Update total
-        self.state.total_collateral = self.state.total_collateral.saturating_sub(amount);
+        self.debit(cdp_id, amount)?;
 
         // Record event
         self.add_event(VaultEvent {
@@ -332,6 +319,74 @@ impl Vault {
         Ok(seized)
     }
 
+    // ═══════════════════════════════════════════════════════════════════════════
+    // FEE BUCKETS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Move `amount` of a CDP's collateral into the `source` fee bucket
+    pub fn collect_fee(
+        &mut self,
+        cdp_id: CDPId,
+        source: FeeSource,
+        amount: CollateralAmount,
+        block_height: u64,
+        tx_hash: Hash,
+    ) -> Result<()> {
+        self.debit(cdp_id, amount)?;
+
+        let bucket = self.state.fee_buckets.entry(source).or_insert(CollateralAmount::ZERO);
+        *bucket = bucket.checked_add(amount).ok_or(Error::Overflow {
+            operation: "fee bucket".into(),
+        })?;
+
+        // Record event
+        self.add_event(VaultEvent {
+            operation: VaultOperation::FeeCollected,
+            cdp_id,
+            amount,
+            block_height,
+            tx_hash,
+        });
+
+        Ok(())
+    }
+
+    /// Pay `amount` out of the `source` fee bucket
+    pub fn disburse_fee(&mut self, source: FeeSource, amount: CollateralAmount) -> Result<()> {
+        if amount.is_zero() {
+            return Err(Error::ZeroAmount);
+        }
+
+        let available = self.fee_balance(source);
+        if amount > available {
+            return Err(Error::InsufficientCollateral {
+                required: amount.sats(),
+                available: available.sats(),
+            });
+        }
+
+        let remaining = available.saturating_sub(amount);
+        if remaining.is_zero() {
+            self.state.fee_buckets.remove(&source);
+        } else {
+            self.state.fee_buckets.insert(source, remaining);
+        }
+
+        Ok(())
+    }
+
+    /// BTC fees held for `source`
+    pub fn fee_balance(&self, source: FeeSource) -> CollateralAmount {
+        self.state.fee_buckets.get(&source).copied()
+            .unwrap_or(CollateralAmount::ZERO)
+    }
+
+    /// BTC fees held across all sources
+    pub fn total_fees(&self) -> CollateralAmount {
+        self.state.fee_buckets.values()
+            .fold(CollateralAmount::ZERO, |total, amount| total.saturating_add(*amount))
+    }
+
     // ═══════════════════════════════════════════════════════════════════════════
     // QUERIES
     // ═══════════════════════════════════════════════════════════════════════════
@@ -379,10 +434,49 @@ impl Vault {
     // INTERNAL
     // ═══════════════════════════════════════════════════════════════════════════
 
+    /// Take `amount` out of a CDP's collateral
+    fn debit(&mut self, cdp_id: CDPId, amount: CollateralAmount) -> Result<()> {
+        if amount.is_zero() {
+            return Err(Error::ZeroAmount);
+        }
+
+        let current = self.state.collateral_by_cdp.get(&cdp_id).copied()
+            .ok_or_else(|| Error::CDPNotFound(cdp_id.to_hex()))?;
+
+        if amount > current {
+            return Err(Error::InsufficientCollateral {
+                required: amount.sats(),
+                available: current.sats(),
+            });
+        }
+
+        // Update CDP collateral
+        let new_amount = current.saturating_sub(amount);
+        if new_amount.is_zero() {
+            self.state.collateral_by_cdp.remove(&cdp_id);
+            self.state.cdp_count = self.state.cdp_count.saturating_sub(1);
+        } else {
+            self.state.collateral_by_cdp.insert(cdp_id, new_amount);
+        }
+
+        // Update total
+        self.state.total_collateral = self.state.total_collateral.saturating_sub(amount);
+
+        Ok(())
+    }
+
     /// Add an event (with pruning)
     fn add_event(&mut self, event: VaultEvent) {
         self.events.push(event);
 
+        // While an undo log is open events are kept so a rollback only has
+        // to truncate
+        if !self.state.collateral_by_cdp.is_recording() {
+            self.prune_events();
+        }
+    }
+
+    fn prune_events(&mut self) {
         if self.events.len() > self.max_events {
             self.events.drain(0..self.events.len() - self.max_events);
         }
@@ -416,6 +510,210 @@ impl Vault {
     }
 }
 
+/// Vault state an undo log does not cover, saved when it opens
+#[derive(Debug, Clone)]
+pub struct VaultMark {
+    total_collateral: CollateralAmount,
+    cdp_count: u64,
+    fee_buckets: BTreeMap<FeeSource, CollateralAmount>,
+    events: usize,
+}
+
+impl Undo for Vault {
+    type Mark = VaultMark;
+
+    fn begin_undo(&mut self) -> VaultMark {
+        self.state.collateral_by_cdp.begin_undo();
+        VaultMark {
+            total_collateral: self.state.total_collateral,
+            cdp_count: self.state.cdp_count,
+            fee_buckets: self.state.fee_buckets.clone(),
+            events: self.events.len(),
+        }
+    }
+
+    fn commit_undo(&mut self) {
+        self.state.collateral_by_cdp.commit_undo();
+        self.prune_events();
+    }
+
+    fn rollback_undo(&mut self, mark: VaultMark) {
+        self.state.collateral_by_cdp.rollback_undo();
+        self.state.total_collateral = mark.total_collateral;
+        self.state.cdp_count = mark.cdp_count;
+        self.state.fee_buckets = mark.fee_buckets;
+        self.events.truncate(mark.events);
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// PROOF OF RESERVES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Domain of reserve output commitments
+const RESERVE_DOMAIN: &str = "reserve-utxo";
+
+/// On-chain output held by the vault
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReserveUtxo {
+    /// Outpoint as `txid:vout`
+    pub outpoint: String,
+    /// Value of the output
+    pub value: CollateralAmount,
+    /// CDP the output is collateral for, if any
+    pub cdp_id: Option<CDPId>,
+    /// Height of the confirming block
+    pub confirmation_height: Option<u32>,
+}
+
+impl ReserveUtxo {
+    /// Leaf committed to by the reserves Merkle root
+    pub fn commitment(&self) -> Hash {
+        canonical_hash(RESERVE_DOMAIN, self).unwrap_or_default()
+    }
+}
+
+/// A CDP's ledger collateral next to the outputs attributed to it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CdpReserves {
+    /// CDP
+    pub cdp_id: CDPId,
+    /// Collateral the vault credits the CDP with
+    pub ledger: CollateralAmount,
+    /// Value of the outputs attributed to the CDP
+    pub backing: CollateralAmount,
+    /// Number of outputs attributed to the CDP
+    pub outputs: u32,
+}
+
+impl CdpReserves {
+    /// Whether the attributed outputs cover the ledger collateral
+    pub fn is_backed(&self) -> bool {
+        self.backing >= self.ledger
+    }
+}
+
+/// Report mapping the vault's liabilities to the outputs holding them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofOfReserves {
+    /// Outputs held, sorted by outpoint
+    pub utxos: Vec<ReserveUtxo>,
+    /// Total value of the outputs
+    pub reserves: CollateralAmount,
+    /// Collateral and fees the vault owes
+    pub liabilities: CollateralAmount,
+    /// Value of outputs not attributed to any CDP
+    pub unattributed: CollateralAmount,
+    /// Per-CDP backing, sorted by CDP
+    pub cdps: Vec<CdpReserves>,
+    /// Merkle root over the output commitments
+    pub merkle_root: Hash,
+}
+
+impl ProofOfReserves {
+    /// Whether the outputs cover the liabilities and every CDP is backed
+    pub fn is_solvent(&self) -> bool {
+        self.reserves >= self.liabilities && self.cdps.iter().all(CdpReserves::is_backed)
+    }
+
+    /// CDPs whose attributed outputs fall short of their collateral
+    pub fn shortfalls(&self) -> Vec<&CdpReserves> {
+        self.cdps.iter().filter(|cdp| !cdp.is_backed()).collect()
+    }
+
+    /// Check the totals, per-CDP backing and Merkle root follow from the
+    /// listed outputs
+    pub fn verify(&self) -> Result<()> {
+        let invalid = |reason: &str| Error::InvalidStateProof(format!("proof of reserves: {}", reason));
+
+        if self.utxos.windows(2).any(|pair| pair[0].outpoint >= pair[1].outpoint) {
+            return Err(invalid("outputs are not sorted and unique"));
+        }
+        if merkle_root(&self.utxos.iter().map(ReserveUtxo::commitment).collect::<Vec<_>>()) != self.merkle_root {
+            return Err(invalid("Merkle root does not match the outputs"));
+        }
+
+        if total(self.utxos.iter()) != self.reserves {
+            return Err(invalid("reserves do not match the outputs"));
+        }
+        if total(self.utxos.iter().filter(|utxo| utxo.cdp_id.is_none())) != self.unattributed {
+            return Err(invalid("unattributed total does not match the outputs"));
+        }
+
+        if self.cdps.windows(2).any(|pair| pair[0].cdp_id.as_bytes() >= pair[1].cdp_id.as_bytes()) {
+            return Err(invalid("CDPs are not sorted and unique"));
+        }
+        let backing = attribute(&self.utxos);
+        for cdp in &self.cdps {
+            let (value, outputs) = backing.get(&cdp.cdp_id).copied().unwrap_or((CollateralAmount::ZERO, 0));
+            if cdp.backing != value || cdp.outputs != outputs {
+                return Err(invalid(&format!("backing of CDP {} does not match the outputs", cdp.cdp_id)));
+            }
+        }
+        if backing.keys().any(|id| !self.cdps.iter().any(|cdp| cdp.cdp_id == *id)) {
+            return Err(invalid("outputs are attributed to an unlisted CDP"));
+        }
+        Ok(())
+    }
+}
+
+/// Total value of `utxos`
+fn total<'a>(utxos: impl Iterator<Item = &'a ReserveUtxo>) -> CollateralAmount {
+    utxos.fold(CollateralAmount::ZERO, |total, utxo| total.saturating_add(utxo.value))
+}
+
+/// Value and count of the outputs attributed to each CDP
+fn attribute(utxos: &[ReserveUtxo]) -> HashMap<CDPId, (CollateralAmount, u32)> {
+    let mut backing: HashMap<CDPId, (CollateralAmount, u32)> = HashMap::new();
+    for utxo in utxos {
+        if let Some(cdp_id) = utxo.cdp_id {
+            let entry = backing.entry(cdp_id).or_insert((CollateralAmount::ZERO, 0));
+            entry.0 = entry.0.saturating_add(utxo.value);
+            entry.1 += 1;
+        }
+    }
+    backing
+}
+
+impl Vault {
+    /// Report how `utxos` back the vault's collateral and fees
+    pub fn proof_of_reserves(&self, mut utxos: Vec<ReserveUtxo>) -> ProofOfReserves {
+        utxos.sort_by(|a, b| a.outpoint.cmp(&b.outpoint));
+        utxos.dedup_by(|a, b| a.outpoint == b.outpoint);
+
+        let backing = attribute(&utxos);
+        let mut cdps: Vec<CdpReserves> = self
+            .state
+            .collateral_by_cdp
+            .keys()
+            .chain(backing.keys().filter(|id| !self.state.collateral_by_cdp.contains_key(id)))
+            .map(|cdp_id| {
+                let (value, outputs) = backing.get(cdp_id).copied().unwrap_or((CollateralAmount::ZERO, 0));
+                CdpReserves {
+                    cdp_id: *cdp_id,
+                    ledger: self.collateral_of(cdp_id),
+                    backing: value,
+                    outputs,
+                }
+            })
+            .collect();
+        cdps.sort_by(|a, b| a.cdp_id.as_bytes().cmp(b.cdp_id.as_bytes()));
+
+        let reserves = total(utxos.iter());
+        let unattributed = total(utxos.iter().filter(|utxo| utxo.cdp_id.is_none()));
+        let merkle_root = merkle_root(&utxos.iter().map(ReserveUtxo::commitment).collect::<Vec<_>>());
+
+        ProofOfReserves {
+            utxos,
+            reserves,
+            liabilities: self.state.total_collateral.saturating_add(self.total_fees()),
+            unattributed,
+            cdps,
+            merkle_root,
+        }
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // ZKBTC INTERFACE (for Grail Pro integration)
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -586,4 +884,67 @@ mod tests {
 
         assert_eq!(vault1.state_hash(), vault2.state_hash());
     }
+
+    #[test]
+    fn test_fee_buckets() {
+        let mut vault = Vault::new();
+        let cdp_id = test_cdp_id();
+
+        vault.deposit(cdp_id, CollateralAmount::from_btc(1), 1, test_hash()).unwrap();
+        vault.collect_fee(cdp_id, FeeSource::Liquidation, CollateralAmount::from_sats(10_000), 2, test_hash()).unwrap();
+
+        // Fees leave the CDP and the collateral total
+        assert_eq!(vault.total_collateral().sats(), SATS_PER_BTC - 10_000);
+        assert_eq!(vault.fee_balance(FeeSource::Liquidation).sats(), 10_000);
+        assert_eq!(vault.total_fees().sats(), 10_000);
+        assert!(vault.verify_invariant());
+
+        assert!(vault.disburse_fee(FeeSource::Borrowing, CollateralAmount::from_sats(1)).is_err());
+        assert!(vault.disburse_fee(FeeSource::Liquidation, CollateralAmount::from_sats(10_001)).is_err());
+        vault.disburse_fee(FeeSource::Liquidation, CollateralAmount::from_sats(10_000)).unwrap();
+        assert!(vault.total_fees().is_zero());
+    }
+
+    #[test]
+    fn test_proof_of_reserves() {
+        let mut vault = Vault::new();
+        let cdp1 = test_cdp_id();
+        let cdp2 = test_cdp_id_2();
+        vault.deposit(cdp1, CollateralAmount::from_btc(1), 1, test_hash()).unwrap();
+        vault.deposit(cdp2, CollateralAmount::from_btc(2), 2, test_hash()).unwrap();
+
+        let utxo = |n: u8, sats: u64, cdp_id: Option<CDPId>| ReserveUtxo {
+            outpoint: format!("{}:0", hex::encode([n; 32])),
+            value: CollateralAmount::from_sats(sats),
+            cdp_id,
+            confirmation_height: Some(100),
+        };
+        let utxos = vec![
+            utxo(3, SATS_PER_BTC, Some(cdp2)),
+            utxo(1, SATS_PER_BTC, Some(cdp1)),
+            utxo(2, SATS_PER_BTC, Some(cdp2)),
+            utxo(4, 10_000, None),
+        ];
+
+        let proof = vault.proof_of_reserves(utxos.clone());
+        assert!(proof.is_solvent());
+        proof.verify().unwrap();
+        assert_eq!(proof.reserves.sats(), 3 * SATS_PER_BTC + 10_000);
+        assert_eq!(proof.unattributed.sats(), 10_000);
+        assert_eq!(proof.utxos[0].outpoint, utxo(1, 0, None).outpoint);
+
+        // Tampering with an output breaks the commitment
+        let mut tampered = proof.clone();
+        tampered.utxos[0].value = CollateralAmount::from_btc(2);
+        assert!(tampered.verify().is_err());
+
+        // A CDP missing an output is reported short
+        let proof = vault.proof_of_reserves(utxos[1..].to_vec());
+        proof.verify().unwrap();
+        assert!(!proof.is_solvent());
+        let shortfalls = proof.shortfalls();
+        assert_eq!(shortfalls.len(), 1);
+        assert_eq!(shortfalls[0].cdp_id, cdp2);
+        assert_eq!(shortfalls[0].backing, CollateralAmount::from_btc(1));
+    }
 }
@@ -88,6 +88,13 @@ pub enum Error {
     #[error("Liquidation already in progress for CDP {0}")]
     LiquidationInProgress(String),
 
+    /// Stability pool withdrawals wait for pending liquidations
+    #[error("Stability pool withdrawals are blocked while {liquidatable} CDP(s) can be liquidated")]
+    WithdrawalsBlocked {
+        /// CDPs awaiting liquidation
+        liquidatable: u64,
+    },
+
     // ═══════════════════════════════════════════════════════════════════
     // Oracle Errors
     // ═══════════════════════════════════════════════════════════════════
@@ -134,6 +141,24 @@ pub enum Error {
         max: u64,
     },
 
+    /// Price confidence interval too wide for a risk-increasing operation
+    #[error("Price uncertainty {interval_bps}bps exceeds maximum {max_bps}bps")]
+    PriceTooUncertain {
+        /// Confidence interval half-width in basis points of the price
+        interval_bps: u64,
+        /// Maximum allowed half-width in basis points
+        max_bps: u64,
+    },
+
+    /// No chain of fresh price pairs connects two assets
+    #[error("No price route from {from} to {to}")]
+    NoPriceRoute {
+        /// Asset being priced
+        from: String,
+        /// Asset to price it in
+        to: String,
+    },
+
     // ═══════════════════════════════════════════════════════════════════
     // Authorization Errors
     // ═══════════════════════════════════════════════════════════════════
@@ -164,6 +189,24 @@ pub enum Error {
         got: String,
     },
 
+    /// Signed permit is past its deadline
+    #[error("Permit expired at block {deadline}, current block {block_height}")]
+    PermitExpired {
+        /// Last block the permit was valid for
+        deadline: u64,
+        /// Current block height
+        block_height: u64,
+    },
+
+    /// Spender's allowance does not cover the transfer
+    #[error("Insufficient allowance: required {required}, available {available}")]
+    InsufficientAllowance {
+        /// Amount requested
+        required: u64,
+        /// Remaining allowance
+        available: u64,
+    },
+
     // ═══════════════════════════════════════════════════════════════════
     // Validation Errors
     // ═══════════════════════════════════════════════════════════════════
@@ -195,6 +238,43 @@ pub enum Error {
         operation: String,
     },
 
+    /// Output value below the dust threshold
+    #[error("Output of {amount} sats is below dust threshold {threshold} sats")]
+    DustOutput {
+        /// Output value (sats)
+        amount: u64,
+        /// Dust threshold (sats)
+        threshold: u64,
+    },
+
+    /// SPV proof does not check out
+    #[error("Invalid SPV proof: {0}")]
+    InvalidSpvProof(String),
+
+    /// Transaction is not buried deep enough
+    #[error("Insufficient confirmations: required {required}, got {actual}")]
+    InsufficientConfirmations {
+        /// Confirmations required
+        required: u32,
+        /// Confirmations observed
+        actual: u32,
+    },
+
+    /// State inclusion or block proof does not check out
+    #[error("Invalid state proof: {0}")]
+    InvalidStateProof(String),
+
+    /// Proof was generated against a different circuit version than the registered key
+    #[error("Circuit version mismatch for {circuit_id}: key is v{expected}, proof is v{actual}")]
+    CircuitVersionMismatch {
+        /// Circuit identifier
+        circuit_id: String,
+        /// Version of the registered verification key
+        expected: u32,
+        /// Version the proof was generated against
+        actual: u32,
+    },
+
     // ═══════════════════════════════════════════════════════════════════
     // Protocol Errors
     // ═══════════════════════════════════════════════════════════════════
@@ -220,6 +300,40 @@ pub enum Error {
     #[error("Invariant violation: {0}")]
     InvariantViolation(String),
 
+    /// Account exceeded its operation rate limit
+    #[error("Rate limit exceeded: at most {limit} operations per {window_blocks} blocks")]
+    RateLimitExceeded {
+        /// Operations allowed per window
+        limit: u32,
+        /// Window length in blocks
+        window_blocks: u64,
+    },
+
+    /// Operation repeated before its minimum interval elapsed
+    #[error("{operation} too frequent: retry in {retry_in_blocks} blocks")]
+    OperationTooFrequent {
+        /// Operation type
+        operation: String,
+        /// Blocks until the operation is allowed again
+        retry_in_blocks: u64,
+    },
+
+    /// Redemption would exceed the per-block redemption cap
+    #[error("Redemption cap reached: requested {requested}, {remaining} left this block")]
+    RedemptionCapReached {
+        /// Amount requested in cents
+        requested: u64,
+        /// Amount still redeemable this block in cents
+        remaining: u64,
+    },
+
+    /// This kind of operation is paused
+    #[error("Operation paused: {operation}")]
+    OperationPaused {
+        /// Paused operation
+        operation: String,
+    },
+
     // ═══════════════════════════════════════════════════════════════════
     // Serialization Errors
     // ═══════════════════════════════════════════════════════════════════
@@ -232,6 +346,68 @@ pub enum Error {
     #[error("Deserialization error: {0}")]
     Deserialization(String),
 
+    // ═══════════════════════════════════════════════════════════════════
+    // Storage Errors
+    // ═══════════════════════════════════════════════════════════════════
+
+    /// Database schema is newer than this build supports
+    #[error("Unsupported schema version {found}, this build supports up to {supported}")]
+    UnsupportedSchemaVersion {
+        /// Version found on disk
+        found: u32,
+        /// Highest version supported
+        supported: u32,
+    },
+
+    /// A schema migration step failed
+    #[error("Migration from schema version {from} failed: {reason}")]
+    MigrationFailed {
+        /// Version being migrated from
+        from: u32,
+        /// Failure reason
+        reason: String,
+    },
+
+    /// The store is locked by another process
+    #[error("Storage at {path} is locked by {owner}")]
+    StorageLocked {
+        /// Store directory
+        path: String,
+        /// Lock owner
+        owner: String,
+    },
+
+    /// A write was attempted on a store opened read-only
+    #[error("Storage is open read-only")]
+    ReadOnlyStorage,
+
+    /// The database was initialized from a different genesis
+    #[error("Genesis mismatch: expected {expected}, database has {found}")]
+    GenesisMismatch {
+        /// Hash of the genesis the node was started with
+        expected: String,
+        /// Hash of the genesis the database was initialized from
+        found: String,
+    },
+
+    /// Replaying a peer's blocks did not reproduce the peer's state
+    #[error("Sync diverged at block {height}: {reason}")]
+    SyncDiverged {
+        /// Block the divergence was detected at
+        height: u64,
+        /// What differed
+        reason: String,
+    },
+
+    /// The audit log was edited, reordered or truncated
+    #[error("Audit log tampered at entry {sequence}: {reason}")]
+    AuditLogTampered {
+        /// First entry that failed verification
+        sequence: u64,
+        /// What failed
+        reason: String,
+    },
+
     // ═══════════════════════════════════════════════════════════════════
     // Internal Errors
     // ═══════════════════════════════════════════════════════════════════
@@ -250,7 +426,16 @@ impl Error {
                 | Error::CollateralizationRatioTooLow { .. }
                 | Error::DebtBelowMinimum { .. }
                 | Error::StalePrice { .. }
+                | Error::PriceTooUncertain { .. }
                 | Error::InsufficientStabilityPool { .. }
+                | Error::WithdrawalsBlocked { .. }
+                | Error::InsufficientConfirmations { .. }
+                | Error::InsufficientAllowance { .. }
+                | Error::RateLimitExceeded { .. }
+                | Error::OperationTooFrequent { .. }
+                | Error::RedemptionCapReached { .. }
+                | Error::OperationPaused { .. }
+                | Error::StorageLocked { .. }
         )
     }
 
@@ -260,6 +445,7 @@ impl Error {
             self,
             Error::InvariantViolation(_)
                 | Error::Internal(_)
+                | Error::MigrationFailed { .. }
                 | Error::Overflow { .. }
                 | Error::Underflow { .. }
         )
@@ -282,6 +468,7 @@ impl Error {
             Error::CDPHealthy(_) => 2001,
             Error::InsufficientStabilityPool { .. } => 2002,
             Error::LiquidationInProgress(_) => 2003,
+            Error::WithdrawalsBlocked { .. } => 2004,
 
             // Oracle errors: 3xxx
             Error::StalePrice { .. } => 3001,
@@ -289,29 +476,51 @@ impl Error {
             Error::InsufficientOracleSources { .. } => 3003,
             Error::InvalidPriceProof => 3004,
             Error::PriceOutOfBounds { .. } => 3005,
+            Error::PriceTooUncertain { .. } => 3006,
+            Error::NoPriceRoute { .. } => 3007,
 
             // Authorization errors: 4xxx
             Error::Unauthorized(_) => 4001,
             Error::InvalidSignature => 4002,
             Error::CryptoError { .. } => 4003,
             Error::SignerMismatch { .. } => 4004,
+            Error::InsufficientAllowance { .. } => 4005,
+            Error::PermitExpired { .. } => 4006,
 
             // Validation errors: 5xxx
             Error::InvalidParameter { .. } => 5001,
             Error::ZeroAmount => 5002,
             Error::Overflow { .. } => 5003,
             Error::Underflow { .. } => 5004,
+            Error::DustOutput { .. } => 5005,
+            Error::InvalidSpvProof(_) => 5006,
+            Error::InsufficientConfirmations { .. } => 5007,
+            Error::InvalidStateProof(_) => 5008,
+            Error::CircuitVersionMismatch { .. } => 5009,
 
             // Protocol errors: 6xxx
             Error::ProtocolPaused => 6001,
             Error::RecoveryMode => 6002,
             Error::DebtCeilingReached { .. } => 6003,
             Error::InvariantViolation(_) => 6004,
+            Error::RateLimitExceeded { .. } => 6005,
+            Error::OperationTooFrequent { .. } => 6006,
+            Error::RedemptionCapReached { .. } => 6007,
+            Error::OperationPaused { .. } => 6008,
 
             // Serialization errors: 7xxx
             Error::Serialization(_) => 7001,
             Error::Deserialization(_) => 7002,
 
+            // Storage errors: 8xxx
+            Error::UnsupportedSchemaVersion { .. } => 8001,
+            Error::MigrationFailed { .. } => 8002,
+            Error::StorageLocked { .. } => 8003,
+            Error::ReadOnlyStorage => 8004,
+            Error::GenesisMismatch { .. } => 8005,
+            Error::SyncDiverged { .. } => 8006,
+            Error::AuditLogTampered { .. } => 8007,
+
             // Internal errors: 9xxx
             Error::Internal(_) => 9001,
         }
@@ -329,10 +538,31 @@ mod tests {
             Error::CDPNotFound("".into()).code(),
             Error::CDPAlreadyExists("".into()).code(),
             Error::InsufficientCollateral { required: 0, available: 0 }.code(),
+            Error::WithdrawalsBlocked { liquidatable: 0 }.code(),
             Error::StalePrice { last_update: 0, max_age: 0 }.code(),
+            Error::PriceTooUncertain { interval_bps: 0, max_bps: 0 }.code(),
+            Error::NoPriceRoute { from: "".into(), to: "".into() }.code(),
             Error::Unauthorized("".into()).code(),
+            Error::InsufficientAllowance { required: 0, available: 0 }.code(),
+            Error::PermitExpired { deadline: 0, block_height: 0 }.code(),
             Error::ZeroAmount.code(),
+            Error::DustOutput { amount: 0, threshold: 0 }.code(),
+            Error::InvalidSpvProof("".into()).code(),
+            Error::InsufficientConfirmations { required: 0, actual: 0 }.code(),
+            Error::InvalidStateProof("".into()).code(),
+            Error::CircuitVersionMismatch { circuit_id: "".into(), expected: 0, actual: 0 }.code(),
             Error::ProtocolPaused.code(),
+            Error::RateLimitExceeded { limit: 0, window_blocks: 0 }.code(),
+            Error::OperationTooFrequent { operation: "".into(), retry_in_blocks: 0 }.code(),
+            Error::RedemptionCapReached { requested: 0, remaining: 0 }.code(),
+            Error::OperationPaused { operation: "".into() }.code(),
+            Error::UnsupportedSchemaVersion { found: 0, supported: 0 }.code(),
+            Error::MigrationFailed { from: 0, reason: "".into() }.code(),
+            Error::StorageLocked { path: "".into(), owner: "".into() }.code(),
+            Error::ReadOnlyStorage.code(),
+            Error::GenesisMismatch { expected: "".into(), found: "".into() }.code(),
+            Error::SyncDiverged { height: 0, reason: "".into() }.code(),
+            Error::AuditLogTampered { sequence: 0, reason: "".into() }.code(),
             Error::Internal("".into()).code(),
         ];
 
@@ -10,7 +10,9 @@
 //! - **Core**: Fundamental types, configuration, and CDP engine
 //! - **Oracle**: Price feed aggregation with ZK verification
 //! - **Liquidation**: Liquidation engine and stability pool
+//! - **Monitoring**: System-wide risk snapshots
 //! - **Spells**: Bitcoin transaction spells for protocol operations
+//! - **Client**: Typed SDK for signing and submitting operations
 //!
 //! ## Design Principles
 //!
@@ -20,6 +22,16 @@
 //! - **Modular**: Clean separation of concerns
 //! - **Fluent**: Intuitive API design
 //!
+//! ## WebAssembly
+//!
+//! Browser wallets and zkVM guests share the protocol and constraint logic
+//! (`core`, `utils::math`, `zkp::circuits`) by building without the default
+//! features, which drops the CLI and backup archives:
+//!
+//! ```text
+//! cargo build --lib --no-default-features --features wasm --target wasm32-unknown-unknown
+//! ```
+//!
 //! ## Example
 //!
 //! ```rust,ignore
@@ -43,16 +55,24 @@
 
 pub mod btc;
 pub mod charms;
+pub mod client;
+#[cfg(feature = "cli")]
+pub mod cli;
 pub mod core;
 pub mod error;
 pub mod liquidation;
+pub mod monitoring;
 pub mod oracle;
 pub mod protocol;
+pub mod sim;
 pub mod spells;
 pub mod storage;
 pub mod utils;
 pub mod zkp;
 
+#[cfg(test)]
+mod testing;
+
 /// Prelude module for convenient imports
 pub mod prelude {
     pub use crate::core::{
@@ -71,9 +91,11 @@ pub mod prelude {
         aggregator::PriceAggregator,
     };
     pub use crate::utils::{
-        math::FixedPoint,
+        decimal::{Decimal128, Rounding},
         crypto::{PublicKey, Signature, Hash},
     };
+    #[allow(deprecated)]
+    pub use crate::utils::math::FixedPoint;
 }
 
 /// Protocol version
@@ -6,16 +6,20 @@
 //! - Redistribution as fallback
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 use crate::core::cdp::{CDP, CDPId, CDPManager, LiquidationResult};
 use crate::core::config::ProtocolConfig;
 use crate::core::token::TokenAmount;
 use crate::core::vault::CollateralAmount;
 use crate::error::{Error, Result};
-use crate::liquidation::stability_pool::StabilityPool;
+use crate::liquidation::stability_pool::{StabilityPool, StabilityPoolStats};
+use crate::protocol::events::LiquidationMode;
 use crate::utils::constants::*;
 use crate::utils::crypto::{Hash, PublicKey};
+use crate::utils::decimal::{apply_bps, Rounding};
 use crate::utils::math::*;
+use crate::utils::undo::{Undo, UndoMap};
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // LIQUIDATION EVENT
@@ -94,6 +98,108 @@ impl LiquidationBatch {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// PENALTY CURVE
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Point on the liquidation penalty curve
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PenaltyBreakpoint {
+    /// Percentage points below the MCR
+    pub depth: u64,
+    /// Penalty at this depth in basis points
+    pub penalty_bps: u64,
+}
+
+/// Liquidation penalty as a function of how far below the MCR a CDP is.
+///
+/// The penalty is interpolated linearly between breakpoints and held flat
+/// before the first and after the last. It is paid out of the seized
+/// collateral, so a shallow liquidation costs the owner less than a deep one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PenaltyCurve {
+    breakpoints: Vec<PenaltyBreakpoint>,
+}
+
+impl Default for PenaltyCurve {
+    /// 5% at the MCR rising to the full liquidation bonus 10 points below it
+    fn default() -> Self {
+        Self::new(vec![
+            PenaltyBreakpoint { depth: 0, penalty_bps: LIQUIDATION_BONUS_BPS / 2 },
+            PenaltyBreakpoint { depth: 10, penalty_bps: LIQUIDATION_BONUS_BPS },
+        ])
+    }
+}
+
+impl PenaltyCurve {
+    /// Create a curve from breakpoints ordered by depth
+    pub fn new(breakpoints: Vec<PenaltyBreakpoint>) -> Self {
+        Self { breakpoints }
+    }
+
+    /// Curve charging `penalty_bps` at every depth
+    pub fn flat(penalty_bps: u64) -> Self {
+        Self::new(vec![PenaltyBreakpoint { depth: 0, penalty_bps }])
+    }
+
+    /// Breakpoints ordered by depth
+    pub fn breakpoints(&self) -> &[PenaltyBreakpoint] {
+        &self.breakpoints
+    }
+
+    /// Check the curve has breakpoints with strictly increasing depths and
+    /// penalties of at most 100%
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: &str| Error::InvalidParameter {
+            name: "liquidation_penalty".into(),
+            reason: reason.into(),
+        };
+
+        if self.breakpoints.is_empty() {
+            return Err(invalid("must have at least one breakpoint"));
+        }
+        if self.breakpoints.windows(2).any(|pair| pair[0].depth >= pair[1].depth) {
+            return Err(invalid("breakpoint depths must be strictly increasing"));
+        }
+        if self.breakpoints.iter().any(|point| point.penalty_bps > BPS_DIVISOR) {
+            return Err(invalid("penalties must not exceed 10000bps"));
+        }
+        Ok(())
+    }
+
+    /// Penalty in basis points for a CDP `depth` percentage points below the MCR
+    pub fn penalty_at(&self, depth: u64) -> u64 {
+        let Some(first) = self.breakpoints.first() else {
+            return 0;
+        };
+        if depth <= first.depth {
+            return first.penalty_bps;
+        }
+
+        for pair in self.breakpoints.windows(2) {
+            let (lo, hi) = (pair[0], pair[1]);
+            if depth <= hi.depth {
+                let span = (hi.depth - lo.depth) as u128;
+                let offset = (depth - lo.depth) as u128;
+                let interpolate = |from: u64, to: u64| (to - from) as u128 * offset / span;
+                return if hi.penalty_bps >= lo.penalty_bps {
+                    lo.penalty_bps + interpolate(lo.penalty_bps, hi.penalty_bps) as u64
+                } else {
+                    lo.penalty_bps - interpolate(hi.penalty_bps, lo.penalty_bps) as u64
+                };
+            }
+        }
+
+        self.breakpoints.last().map_or(0, |last| last.penalty_bps)
+    }
+
+    /// Penalty in basis points for a CDP at `ratio` under `mcr`, capped at
+    /// `max_penalty_bps`
+    pub fn penalty_bps(&self, ratio: u64, mcr: u64, max_penalty_bps: u64) -> u64 {
+        self.penalty_at(mcr.saturating_sub(ratio)).min(max_penalty_bps)
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // LIQUIDATION ENGINE
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -187,6 +293,11 @@ impl LiquidationEngine {
     // ═══════════════════════════════════════════════════════════════════════════
 
     /// Execute a single liquidation
+    #[tracing::instrument(
+        name = "liquidation",
+        skip_all,
+        fields(cdp_id = %cdp.id.to_hex(), block = block_height, btc_price = btc_price)
+    )]
     pub fn liquidate_single(
         &mut self,
         cdp: &mut CDP,
@@ -204,17 +315,18 @@ impl LiquidationEngine {
         }
 
         let ratio_at_liquidation = cdp.calculate_ratio(btc_price);
+        let penalty_bps = config.liquidation_penalty_bps(ratio_at_liquidation);
         let debt = TokenAmount::from_cents(cdp.debt_cents);
         let collateral = CollateralAmount::from_sats(cdp.collateral_sats);
 
         // Try to absorb via stability pool first
         let absorbed_by_sp = if stability_pool.can_absorb(debt) {
-            // Calculate collateral to give to stability pool (debt + bonus)
-            let (_, collateral_needed, bonus) = calculate_liquidation_amounts(
+            // Calculate collateral to give to stability pool (debt + penalty)
+            let (_, collateral_needed, _) = calculate_liquidation_amounts(
                 collateral.sats(),
                 debt.cents(),
                 btc_price,
-                LIQUIDATION_BONUS_BPS,
+                penalty_bps,
             )?;
 
             let collateral_for_sp = CollateralAmount::from_sats(collateral_needed);
@@ -225,7 +337,7 @@ impl LiquidationEngine {
         };
 
         // Perform liquidation on CDP
-        let liq_result = cdp.liquidate(btc_price, min_ratio, block_height)?;
+        let liq_result = cdp.liquidate_with_penalty(btc_price, min_ratio, penalty_bps, block_height)?;
 
         let event = LiquidationEvent {
             cdp_id: cdp.id,
@@ -250,11 +362,22 @@ impl LiquidationEngine {
 
         // Record event
         self.add_event(event.clone());
+        tracing::debug!(
+            debt = event.debt_covered.cents(),
+            collateral = event.collateral_seized.sats(),
+            absorbed_by_sp,
+            "CDP liquidated"
+        );
 
         Ok(event)
     }
 
     /// Execute batch liquidation
+    #[tracing::instrument(
+        name = "liquidation_batch",
+        skip_all,
+        fields(block = block_height, btc_price = btc_price, max = max_liquidations)
+    )]
     pub fn liquidate_batch(
         &mut self,
         cdp_manager: &mut CDPManager,
@@ -297,7 +420,7 @@ impl LiquidationEngine {
                     Ok(event) => events.push(event),
                     Err(e) => {
                         // Log error but continue with other liquidations
-                        tracing::warn!("Liquidation failed for {}: {}", cdp_id.short(), e);
+                        tracing::warn!(cdp_id = %cdp_id.to_hex(), error = %e, "Liquidation failed");
                     }
                 }
             }
@@ -306,6 +429,59 @@ impl LiquidationEngine {
         Ok(events)
     }
 
+    // ═══════════════════════════════════════════════════════════════════════════
+    // PREVIEW
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Expected outcome of liquidating a CDP at `btc_price`, computed on
+    /// copies of the CDP and the stability pool
+    ///
+    /// Follows the state machine: the stability pool absorbs the debt if it
+    /// can, otherwise the seized collateral goes to auction when auctions are
+    /// enabled, and to the liquidator directly when they are not.
+    pub fn preview(
+        cdp_manager: &CDPManager,
+        stability_pool: &StabilityPool,
+        auctions: &AuctionHouse,
+        config: &ProtocolConfig,
+        cdp_id: &CDPId,
+        btc_price: u64,
+    ) -> Result<LiquidationPreview> {
+        let mut cdp = cdp_manager
+            .get(cdp_id)
+            .cloned()
+            .ok_or_else(|| Error::CDPNotFound(cdp_id.to_hex()))?;
+
+        let ratio_at_liquidation = cdp.calculate_ratio(btc_price);
+        let penalty_bps = config.liquidation_penalty_bps(ratio_at_liquidation);
+        let debt = TokenAmount::from_cents(cdp.debt_cents);
+        let result = cdp.liquidate_with_penalty(btc_price, config.effective_mcr(), penalty_bps, cdp.last_updated)?;
+        let collateral_seized = CollateralAmount::from_sats(result.collateral_seized);
+
+        let mut pool = stability_pool.clone();
+        let (mode, liquidator_bonus) = if pool.can_absorb(debt) {
+            pool.absorb_liquidation(debt, collateral_seized)?;
+            (LiquidationMode::StabilityPool, CollateralAmount::ZERO)
+        } else if auctions.is_enabled() {
+            (LiquidationMode::Auction, CollateralAmount::ZERO)
+        } else {
+            (LiquidationMode::Direct, CollateralAmount::from_sats(result.liquidator_bonus))
+        };
+
+        Ok(LiquidationPreview {
+            cdp_id: *cdp_id,
+            mode,
+            btc_price,
+            ratio_at_liquidation,
+            penalty_bps,
+            debt_covered: TokenAmount::from_cents(result.debt_covered),
+            collateral_seized,
+            liquidator_bonus,
+            collateral_remaining: CollateralAmount::from_sats(result.collateral_remaining),
+            pool_after: pool.statistics(),
+        })
+    }
+
     // ═══════════════════════════════════════════════════════════════════════════
     // INCENTIVE CALCULATIONS
     // ═══════════════════════════════════════════════════════════════════════════
@@ -319,7 +495,7 @@ impl LiquidationEngine {
         let collateral_value = collateral.value_in_cents(btc_price);
 
         // Calculate max bonus (10% of debt)
-        let max_bonus_cents = calculate_fee_bps(debt.cents(), LIQUIDATION_BONUS_BPS)?;
+        let max_bonus_cents = apply_bps(debt.cents(), LIQUIDATION_BONUS_BPS, Rounding::Floor)?;
         let max_bonus_sats = calculate_min_collateral(max_bonus_cents, btc_price, 100)?;
 
         // Actual bonus is minimum of max and available surplus
@@ -430,6 +606,31 @@ pub struct LiquidatorIncentive {
     pub is_profitable: bool,
 }
 
+/// Expected outcome of a liquidation, from [`LiquidationEngine::preview`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidationPreview {
+    /// CDP that would be liquidated
+    pub cdp_id: CDPId,
+    /// Where the debt and seized collateral would go
+    pub mode: LiquidationMode,
+    /// BTC price the preview was computed at
+    pub btc_price: u64,
+    /// Collateralization ratio at liquidation
+    pub ratio_at_liquidation: u64,
+    /// Liquidation penalty applied
+    pub penalty_bps: u64,
+    /// Debt that would be covered
+    pub debt_covered: TokenAmount,
+    /// Collateral that would be seized
+    pub collateral_seized: CollateralAmount,
+    /// Bonus paid to the liquidator
+    pub liquidator_bonus: CollateralAmount,
+    /// Collateral left in the CDP
+    pub collateral_remaining: CollateralAmount,
+    /// Stability pool after the liquidation
+    pub pool_after: StabilityPoolStats,
+}
+
 /// Liquidation statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LiquidationStats {
@@ -441,6 +642,354 @@ pub struct LiquidationStats {
     pub redistribution_count: u64,
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// DUTCH AUCTIONS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Default starting price as a share of the oracle price (120%)
+pub const DEFAULT_AUCTION_START_PREMIUM_BPS: u64 = 12_000;
+
+/// Default price decay per block as a share of the starting price (1%)
+pub const DEFAULT_AUCTION_DECAY_BPS_PER_BLOCK: u64 = 100;
+
+/// Default blocks an auction runs before it must be reset (about 6 hours)
+pub const DEFAULT_AUCTION_DURATION_BLOCKS: u64 = 36;
+
+/// Default lowest price as a share of the starting price (60%)
+pub const DEFAULT_AUCTION_FLOOR_BPS: u64 = 6_000;
+
+/// Parameters for descending-price collateral auctions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct AuctionConfig {
+    /// Starting price as a share of the oracle price in basis points
+    pub start_premium_bps: u64,
+    /// Price decay per block as a share of the starting price in basis points
+    pub decay_bps_per_block: u64,
+    /// Blocks an auction runs before it must be reset
+    pub duration_blocks: u64,
+    /// Lowest price as a share of the starting price in basis points
+    pub floor_bps: u64,
+}
+
+impl Default for AuctionConfig {
+    fn default() -> Self {
+        Self {
+            start_premium_bps: DEFAULT_AUCTION_START_PREMIUM_BPS,
+            decay_bps_per_block: DEFAULT_AUCTION_DECAY_BPS_PER_BLOCK,
+            duration_blocks: DEFAULT_AUCTION_DURATION_BLOCKS,
+            floor_bps: DEFAULT_AUCTION_FLOOR_BPS,
+        }
+    }
+}
+
+impl AuctionConfig {
+    /// Validate parameters are consistent
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |name: &str, reason: &str| Error::InvalidParameter {
+            name: name.into(),
+            reason: reason.into(),
+        };
+
+        if self.start_premium_bps == 0 {
+            return Err(invalid("start_premium_bps", "must be positive"));
+        }
+        if self.decay_bps_per_block == 0 || self.decay_bps_per_block > BPS_DIVISOR {
+            return Err(invalid("decay_bps_per_block", "must be between 1 and 10000"));
+        }
+        if self.duration_blocks == 0 {
+            return Err(invalid("duration_blocks", "must be positive"));
+        }
+        if self.floor_bps == 0 || self.floor_bps > BPS_DIVISOR {
+            return Err(invalid("floor_bps", "must be between 1 and 10000"));
+        }
+        Ok(())
+    }
+}
+
+/// Collateral seized from a CDP, on sale for zkUSD
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Auction {
+    /// Auction ID
+    pub id: u64,
+    /// CDP the collateral was seized from
+    pub cdp_id: CDPId,
+    /// Owner of that CDP, who receives any collateral left once the tab is raised
+    pub owner: PublicKey,
+    /// Collateral left to sell
+    pub lot: CollateralAmount,
+    /// zkUSD left to raise
+    pub tab: TokenAmount,
+    /// Price in cents per BTC at the (last) start
+    pub start_price: u64,
+    /// Block of the (last) start
+    pub started_at: u64,
+    /// Times the auction has been reset
+    pub resets: u32,
+}
+
+impl Auction {
+    /// Price in cents per BTC at `block_height`
+    pub fn price_at(&self, block_height: u64, config: &AuctionConfig) -> u64 {
+        let elapsed = block_height.saturating_sub(self.started_at);
+        let decay = elapsed.saturating_mul(config.decay_bps_per_block);
+        let share = BPS_DIVISOR.saturating_sub(decay).max(config.floor_bps);
+        (self.start_price as u128 * share as u128 / BPS_DIVISOR as u128) as u64
+    }
+
+    /// Whether the auction has run its course and must be reset before it
+    /// can be bid on
+    pub fn needs_reset(&self, block_height: u64, config: &AuctionConfig) -> bool {
+        block_height.saturating_sub(self.started_at) >= config.duration_blocks
+    }
+}
+
+/// Outcome of a bid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuctionTake {
+    /// Collateral bought
+    pub collateral: CollateralAmount,
+    /// zkUSD paid
+    pub cost: TokenAmount,
+    /// Price paid in cents per BTC
+    pub price: u64,
+    /// Whether the auction ended
+    pub closed: bool,
+    /// Collateral returned to the CDP owner once the tab was raised
+    pub returned: CollateralAmount,
+    /// Tab left unraised when the lot ran out
+    pub bad_debt: TokenAmount,
+}
+
+/// Open collateral auctions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuctionHouse {
+    /// Auction parameters; `None` when auctions are disabled
+    config: Option<AuctionConfig>,
+    /// Open auctions by ID
+    auctions: UndoMap<BTreeMap<u64, Auction>>,
+    /// Next auction ID
+    next_id: u64,
+}
+
+impl AuctionHouse {
+    /// Create with auctions disabled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Auction parameters, if auctions are enabled
+    pub fn config(&self) -> Option<&AuctionConfig> {
+        self.config.as_ref()
+    }
+
+    /// Whether liquidations go to auction
+    pub fn is_enabled(&self) -> bool {
+        self.config.is_some()
+    }
+
+    /// Enable auctions with `config`, or disable them with `None`.
+    ///
+    /// Open auctions keep running under the new parameters. Auctions cannot
+    /// be disabled while any are still open.
+    pub fn set_config(&mut self, config: Option<AuctionConfig>) -> Result<()> {
+        match config {
+            Some(config) => {
+                config.validate()?;
+                self.config = Some(config);
+            }
+            None if self.auctions.is_empty() => self.config = None,
+            None => {
+                return Err(Error::InvalidParameter {
+                    name: "auction_config".into(),
+                    reason: format!("{} auctions still open", self.auctions.len()),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Get an open auction
+    pub fn get(&self, id: u64) -> Option<&Auction> {
+        self.auctions.get(&id)
+    }
+
+    /// Open auctions, oldest first
+    pub fn open_auctions(&self) -> impl Iterator<Item = &Auction> {
+        self.auctions.values()
+    }
+
+    /// Number of open auctions
+    pub fn len(&self) -> usize {
+        self.auctions.len()
+    }
+
+    /// Check if no auctions are open
+    pub fn is_empty(&self) -> bool {
+        self.auctions.is_empty()
+    }
+
+    /// Put `lot` up for sale to raise `tab`, starting above `oracle_price`
+    pub fn start(
+        &mut self,
+        cdp_id: CDPId,
+        owner: PublicKey,
+        lot: CollateralAmount,
+        tab: TokenAmount,
+        oracle_price: u64,
+        block_height: u64,
+    ) -> Result<&Auction> {
+        let config = self.enabled_config()?;
+        if lot.is_zero() || tab.is_zero() {
+            return Err(Error::ZeroAmount);
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        let auction = Auction {
+            id,
+            cdp_id,
+            owner,
+            lot,
+            tab,
+            start_price: Self::start_price(&config, oracle_price)?,
+            started_at: block_height,
+            resets: 0,
+        };
+        Ok(self.auctions.get_or_insert_with(id, || auction))
+    }
+
+    /// Buy up to `max_collateral` from auction `id` at no more than `max_price`
+    pub fn take(
+        &mut self,
+        id: u64,
+        max_collateral: CollateralAmount,
+        max_price: u64,
+        block_height: u64,
+    ) -> Result<AuctionTake> {
+        let config = self.config_or_default();
+        let auction = self.auctions.get_mut(&id).ok_or_else(|| Self::not_found(id))?;
+
+        if auction.needs_reset(block_height, &config) {
+            return Err(Error::InvalidParameter {
+                name: "auction_id".into(),
+                reason: format!("auction {} has expired and must be reset", id),
+            });
+        }
+
+        let price = auction.price_at(block_height, &config);
+        if price > max_price {
+            return Err(Error::InvalidParameter {
+                name: "max_price".into(),
+                reason: format!("auction price {} exceeds {}", price, max_price),
+            });
+        }
+
+        let mut collateral = max_collateral.min(auction.lot).sats();
+        let mut cost = safe_mul_div_up(collateral, price, SATS_PER_BTC)?;
+        if cost >= auction.tab.cents() {
+            // Only sell what the tab needs
+            cost = auction.tab.cents();
+            collateral = safe_mul_div(cost, SATS_PER_BTC, price)?.min(auction.lot.sats());
+        }
+        if cost == 0 || collateral == 0 {
+            return Err(Error::ZeroAmount);
+        }
+
+        auction.lot = auction.lot.saturating_sub(CollateralAmount::from_sats(collateral));
+        auction.tab = auction.tab.saturating_sub(TokenAmount::from_cents(cost));
+
+        let mut take = AuctionTake {
+            collateral: CollateralAmount::from_sats(collateral),
+            cost: TokenAmount::from_cents(cost),
+            price,
+            closed: false,
+            returned: CollateralAmount::ZERO,
+            bad_debt: TokenAmount::ZERO,
+        };
+
+        if auction.tab.is_zero() {
+            take.returned = auction.lot;
+        } else if auction.lot.is_zero() {
+            take.bad_debt = auction.tab;
+        }
+        if auction.tab.is_zero() || auction.lot.is_zero() {
+            take.closed = true;
+            self.auctions.remove(&id);
+        }
+
+        Ok(take)
+    }
+
+    /// Restart an expired auction from above `oracle_price`
+    pub fn reset(&mut self, id: u64, oracle_price: u64, block_height: u64) -> Result<&Auction> {
+        let config = self.config_or_default();
+        let start_price = Self::start_price(&config, oracle_price)?;
+        let auction = self.auctions.get_mut(&id).ok_or_else(|| Self::not_found(id))?;
+
+        if !auction.needs_reset(block_height, &config) {
+            return Err(Error::InvalidParameter {
+                name: "auction_id".into(),
+                reason: format!("auction {} is still running", id),
+            });
+        }
+
+        auction.start_price = start_price;
+        auction.started_at = block_height;
+        auction.resets += 1;
+        Ok(auction)
+    }
+
+    fn enabled_config(&self) -> Result<AuctionConfig> {
+        self.config.ok_or_else(|| Error::InvalidParameter {
+            name: "auction_config".into(),
+            reason: "auctions are disabled".into(),
+        })
+    }
+
+    /// Parameters for running auctions
+    fn config_or_default(&self) -> AuctionConfig {
+        self.config.unwrap_or_default()
+    }
+
+    fn start_price(config: &AuctionConfig, oracle_price: u64) -> Result<u64> {
+        if oracle_price == 0 {
+            return Err(Error::InvalidParameter {
+                name: "oracle_price".into(),
+                reason: "no price available".into(),
+            });
+        }
+        safe_mul_div(oracle_price, config.start_premium_bps, BPS_DIVISOR)
+    }
+
+    fn not_found(id: u64) -> Error {
+        Error::InvalidParameter {
+            name: "auction_id".into(),
+            reason: format!("auction {} not found", id),
+        }
+    }
+}
+
+impl Undo for AuctionHouse {
+    /// Auction parameters and next ID
+    type Mark = (Option<AuctionConfig>, u64);
+
+    fn begin_undo(&mut self) -> Self::Mark {
+        self.auctions.begin_undo();
+        (self.config, self.next_id)
+    }
+
+    fn commit_undo(&mut self) {
+        self.auctions.commit_undo();
+    }
+
+    fn rollback_undo(&mut self, (config, next_id): Self::Mark) {
+        self.auctions.rollback_undo();
+        self.config = config;
+        self.next_id = next_id;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -453,6 +1002,40 @@ mod tests {
         Hash::sha256(b"test")
     }
 
+    #[test]
+    fn test_penalty_curve_interpolation() {
+        let curve = PenaltyCurve::new(vec![
+            PenaltyBreakpoint { depth: 2, penalty_bps: 400 },
+            PenaltyBreakpoint { depth: 6, penalty_bps: 800 },
+            PenaltyBreakpoint { depth: 10, penalty_bps: 600 },
+        ]);
+        assert!(curve.validate().is_ok());
+
+        assert_eq!(curve.penalty_at(0), 400);
+        assert_eq!(curve.penalty_at(4), 600);
+        assert_eq!(curve.penalty_at(6), 800);
+        assert_eq!(curve.penalty_at(9), 650);
+        assert_eq!(curve.penalty_at(50), 600);
+
+        // Depth is measured from the MCR and the result is capped
+        assert_eq!(curve.penalty_bps(106, 110, 10_000), 600);
+        assert_eq!(curve.penalty_bps(104, 110, 700), 700);
+        assert_eq!(PenaltyCurve::flat(300).penalty_bps(10, 110, 10_000), 300);
+    }
+
+    #[test]
+    fn test_penalty_curve_validation() {
+        assert!(PenaltyCurve::default().validate().is_ok());
+        assert!(PenaltyCurve::new(Vec::new()).validate().is_err());
+        assert!(PenaltyCurve::flat(BPS_DIVISOR + 1).validate().is_err());
+        assert!(PenaltyCurve::new(vec![
+            PenaltyBreakpoint { depth: 5, penalty_bps: 500 },
+            PenaltyBreakpoint { depth: 5, penalty_bps: 900 },
+        ])
+        .validate()
+        .is_err());
+    }
+
     #[test]
     fn test_create_batch() {
         let engine = LiquidationEngine::new();
@@ -546,6 +1129,53 @@ mod tests {
         assert_eq!(engine.total_liquidations(), 1);
     }
 
+    #[test]
+    fn test_preview_matches_liquidation() {
+        let mut cdp_manager = CDPManager::new();
+        let mut stability_pool = StabilityPool::new();
+        let mut auctions = AuctionHouse::new();
+        let config = ProtocolConfig::default();
+        let btc_price = 5_000_000;
+
+        let depositor = PublicKey::new([0x03; PUBKEY_LENGTH]);
+        stability_pool.deposit(depositor, TokenAmount::from_dollars(100_000), 1).unwrap();
+
+        let mut cdp = CDP::with_collateral(test_pubkey(), SATS_PER_BTC, 1, 100).unwrap();
+        cdp.debt_cents = 4_800_000;
+        let cdp_id = cdp.id;
+        cdp_manager.register(cdp.clone()).unwrap();
+
+        let preview =
+            LiquidationEngine::preview(&cdp_manager, &stability_pool, &auctions, &config, &cdp_id, btc_price).unwrap();
+        assert_eq!(preview.mode, LiquidationMode::StabilityPool);
+        assert_eq!(preview.pool_after.total_deposits, TokenAmount::from_dollars(52_000));
+        assert_eq!(stability_pool.total_deposits(), TokenAmount::from_dollars(100_000));
+        assert_eq!(cdp_manager.get(&cdp_id).unwrap().debt_cents, 4_800_000);
+
+        let mut engine = LiquidationEngine::new();
+        let liquidator = PublicKey::new([0x04; PUBKEY_LENGTH]);
+        let event = engine
+            .liquidate_single(&mut cdp, &mut stability_pool, &config, btc_price, liquidator, 200, test_hash())
+            .unwrap();
+        assert_eq!(preview.debt_covered, event.debt_covered);
+        assert_eq!(preview.collateral_seized, event.collateral_seized);
+        assert_eq!(preview.pool_after.total_deposits, stability_pool.total_deposits());
+
+        // Without a pool to absorb it, the debt goes to auction or the liquidator
+        let empty = StabilityPool::new();
+        let direct = LiquidationEngine::preview(&cdp_manager, &empty, &auctions, &config, &cdp_id, btc_price).unwrap();
+        assert_eq!(direct.mode, LiquidationMode::Direct);
+        assert!(direct.liquidator_bonus.sats() > 0);
+        auctions.set_config(Some(AuctionConfig::default())).unwrap();
+        let auction = LiquidationEngine::preview(&cdp_manager, &empty, &auctions, &config, &cdp_id, btc_price).unwrap();
+        assert_eq!(auction.mode, LiquidationMode::Auction);
+
+        assert!(matches!(
+            LiquidationEngine::preview(&cdp_manager, &empty, &auctions, &config, &cdp_id, 10_000_000),
+            Err(Error::CDPHealthy(_))
+        ));
+    }
+
     #[test]
     fn test_cannot_liquidate_healthy_cdp() {
         let mut engine = LiquidationEngine::new();
@@ -571,4 +1201,62 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_auction_descends_and_settles() {
+        let mut house = AuctionHouse::new();
+        let cdp_id = CDPId::generate(&test_pubkey(), 1);
+        let lot = CollateralAmount::from_sats(SATS_PER_BTC);
+        let tab = TokenAmount::from_cents(5_000_000);
+
+        assert!(house.start(cdp_id, test_pubkey(), lot, tab, 6_000_000, 0).is_err());
+        house.set_config(Some(AuctionConfig::default())).unwrap();
+
+        // Starts at 120% of $60,000 and drops 1% of that a block
+        let auction = house.start(cdp_id, test_pubkey(), lot, tab, 6_000_000, 0).unwrap();
+        assert_eq!(auction.start_price, 7_200_000);
+        let id = auction.id;
+
+        assert!(house.take(id, lot, 6_000_000, 10).is_err());
+        let take = house.take(id, CollateralAmount::from_sats(SATS_PER_BTC / 2), 6_500_000, 10).unwrap();
+        assert_eq!(take.price, 6_480_000);
+        assert_eq!(take.cost.cents(), 3_240_000);
+        assert!(!take.closed);
+
+        // The second bid only buys what the rest of the tab needs
+        let take = house.take(id, lot, u64::MAX, 20).unwrap();
+        assert_eq!(take.price, 5_760_000);
+        assert_eq!(take.cost.cents(), 1_760_000);
+        assert_eq!(take.collateral.sats(), 30_555_555);
+        assert_eq!(take.returned.sats(), 19_444_445);
+        assert!(take.closed);
+        assert!(house.is_empty());
+    }
+
+    #[test]
+    fn test_auction_reset() {
+        let mut house = AuctionHouse::new();
+        house.set_config(Some(AuctionConfig::default())).unwrap();
+        let cdp_id = CDPId::generate(&test_pubkey(), 1);
+        let id = house
+            .start(cdp_id, test_pubkey(), CollateralAmount::from_sats(SATS_PER_BTC), TokenAmount::from_cents(5_000_000), 6_000_000, 0)
+            .unwrap()
+            .id;
+
+        // Running auctions cannot be reset; expired ones cannot be bid on
+        assert!(house.reset(id, 5_000_000, 35).is_err());
+        assert!(house.take(id, CollateralAmount::from_sats(SATS_PER_BTC), u64::MAX, DEFAULT_AUCTION_DURATION_BLOCKS).is_err());
+
+        let auction = house.reset(id, 5_000_000, DEFAULT_AUCTION_DURATION_BLOCKS).unwrap();
+        assert_eq!(auction.start_price, 6_000_000);
+        assert_eq!(auction.resets, 1);
+
+        // The floor holds however long the auction runs
+        let auction = house.get(id).unwrap();
+        assert_eq!(auction.price_at(DEFAULT_AUCTION_DURATION_BLOCKS + 35, house.config().unwrap()), 3_900_000);
+        assert_eq!(auction.price_at(DEFAULT_AUCTION_DURATION_BLOCKS + 1_000, house.config().unwrap()), 3_600_000);
+
+        assert!(house.set_config(None).is_err());
+        assert!(house.set_config(Some(AuctionConfig { floor_bps: 0, ..Default::default() })).is_err());
+    }
 }
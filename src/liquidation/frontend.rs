@@ -0,0 +1,216 @@
+//! Stability pool frontends.
+//!
+//! Third-party interfaces register a frontend key and tag the stability pool
+//! deposits made through them. Each frontend chooses a kickback: the share of
+//! its depositors' BTC gains paid to the frontend instead of the depositor.
+//! A deposit keeps its tag until it is fully withdrawn.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::core::vault::CollateralAmount;
+use crate::error::{Error, Result};
+use crate::utils::constants::{BPS_DIVISOR, MAX_FRONTEND_KICKBACK_BPS};
+use crate::utils::crypto::PublicKey;
+use crate::utils::undo::{Undo, UndoMap};
+
+/// A registered frontend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Frontend {
+    /// Share of tagged depositors' gains paid to the frontend (in bps)
+    pub kickback_bps: u64,
+    /// Block of registration
+    pub registered_at: u64,
+    /// Deposits currently tagged with the frontend
+    pub depositors: u64,
+    /// Kickbacks not yet claimed
+    pub pending_gains: CollateralAmount,
+    /// Kickbacks earned since registration
+    pub total_gains: CollateralAmount,
+}
+
+/// Totals across all frontends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrontendStats {
+    /// Registered frontends
+    pub frontends: u64,
+    /// Deposits tagged with a frontend
+    pub tagged_depositors: u64,
+    /// Kickbacks not yet claimed
+    pub pending_gains: CollateralAmount,
+    /// Kickbacks earned
+    pub total_gains: CollateralAmount,
+}
+
+/// Registered frontends and the deposits tagged with them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FrontendRegistry {
+    /// Frontends by operator key
+    frontends: UndoMap<HashMap<PublicKey, Frontend>>,
+    /// Frontend each depositor is tagged with
+    tags: UndoMap<HashMap<PublicKey, PublicKey>>,
+}
+
+impl FrontendRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `frontend` with a kickback share
+    pub fn register(&mut self, frontend: PublicKey, kickback_bps: u64, block_height: u64) -> Result<()> {
+        if kickback_bps > MAX_FRONTEND_KICKBACK_BPS {
+            return Err(Error::InvalidParameter {
+                name: "kickback_bps".into(),
+                reason: format!("exceeds maximum of {}", MAX_FRONTEND_KICKBACK_BPS),
+            });
+        }
+        if self.frontends.contains_key(&frontend) {
+            return Err(Error::InvalidParameter {
+                name: "frontend".into(),
+                reason: "already registered".into(),
+            });
+        }
+
+        self.frontends.insert(frontend, Frontend {
+            kickback_bps,
+            registered_at: block_height,
+            depositors: 0,
+            pending_gains: CollateralAmount::ZERO,
+            total_gains: CollateralAmount::ZERO,
+        });
+        Ok(())
+    }
+
+    /// Get a registered frontend
+    pub fn get(&self, frontend: &PublicKey) -> Option<&Frontend> {
+        self.frontends.get(frontend)
+    }
+
+    /// Frontend `depositor` is tagged with, if any
+    pub fn tag_of(&self, depositor: &PublicKey) -> Option<&PublicKey> {
+        self.tags.get(depositor)
+    }
+
+    /// Tag `depositor` with `frontend`, which must be registered
+    pub fn tag(&mut self, depositor: PublicKey, frontend: PublicKey) -> Result<()> {
+        if self.tags.contains_key(&depositor) {
+            return Ok(());
+        }
+
+        let entry = self.frontends.get_mut(&frontend).ok_or_else(|| Error::InvalidParameter {
+            name: "frontend".into(),
+            reason: "not registered".into(),
+        })?;
+        entry.depositors += 1;
+        self.tags.insert(depositor, frontend);
+        Ok(())
+    }
+
+    /// Remove the tag of a fully withdrawn deposit
+    pub fn untag(&mut self, depositor: &PublicKey) {
+        if let Some(frontend) = self.tags.remove(depositor) {
+            if let Some(entry) = self.frontends.get_mut(&frontend) {
+                entry.depositors = entry.depositors.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Take the kickback out of `gains` paid to `depositor`, returning the
+    /// depositor's share
+    pub fn split_gains(&mut self, depositor: &PublicKey, gains: CollateralAmount) -> CollateralAmount {
+        let Some(entry) = self.tags.get(depositor).and_then(|frontend| self.frontends.get_mut(frontend)) else {
+            return gains;
+        };
+
+        let kickback = CollateralAmount::from_sats(
+            (gains.sats() as u128 * entry.kickback_bps as u128 / BPS_DIVISOR as u128) as u64,
+        );
+        entry.pending_gains = entry.pending_gains.saturating_add(kickback);
+        entry.total_gains = entry.total_gains.saturating_add(kickback);
+        gains.saturating_sub(kickback)
+    }
+
+    /// Claim the kickbacks accrued by `frontend`
+    pub fn claim(&mut self, frontend: &PublicKey) -> Result<CollateralAmount> {
+        let entry = self.frontends.get_mut(frontend).ok_or_else(|| Error::InvalidParameter {
+            name: "frontend".into(),
+            reason: "not registered".into(),
+        })?;
+        Ok(std::mem::replace(&mut entry.pending_gains, CollateralAmount::ZERO))
+    }
+
+    /// Totals across all frontends
+    pub fn statistics(&self) -> FrontendStats {
+        let (pending_gains, total_gains) = self.frontends.values().fold(
+            (CollateralAmount::ZERO, CollateralAmount::ZERO),
+            |(pending, total), entry| {
+                (pending.saturating_add(entry.pending_gains), total.saturating_add(entry.total_gains))
+            },
+        );
+
+        FrontendStats {
+            frontends: self.frontends.len() as u64,
+            tagged_depositors: self.tags.len() as u64,
+            pending_gains,
+            total_gains,
+        }
+    }
+}
+
+impl Undo for FrontendRegistry {
+    type Mark = ();
+
+    fn begin_undo(&mut self) {
+        self.frontends.begin_undo();
+        self.tags.begin_undo();
+    }
+
+    fn commit_undo(&mut self) {
+        self.frontends.commit_undo();
+        self.tags.commit_undo();
+    }
+
+    fn rollback_undo(&mut self, _mark: ()) {
+        self.frontends.rollback_undo();
+        self.tags.rollback_undo();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::constants::PUBKEY_LENGTH;
+
+    fn key(byte: u8) -> PublicKey {
+        PublicKey::new([byte; PUBKEY_LENGTH])
+    }
+
+    #[test]
+    fn test_kickback_split() {
+        let mut registry = FrontendRegistry::new();
+        let (frontend, alice, bob) = (key(0x02), key(0x03), key(0x04));
+
+        assert!(registry.register(frontend, MAX_FRONTEND_KICKBACK_BPS + 1, 1).is_err());
+        registry.register(frontend, 1_000, 1).unwrap();
+        assert!(registry.register(frontend, 1_000, 2).is_err());
+        assert!(registry.tag(alice, key(0x05)).is_err());
+
+        registry.tag(alice, frontend).unwrap();
+        assert_eq!(registry.split_gains(&alice, CollateralAmount::from_sats(10_000)).sats(), 9_000);
+        assert_eq!(registry.split_gains(&bob, CollateralAmount::from_sats(10_000)).sats(), 10_000);
+
+        let stats = registry.statistics();
+        assert_eq!(stats.frontends, 1);
+        assert_eq!(stats.tagged_depositors, 1);
+        assert_eq!(stats.pending_gains.sats(), 1_000);
+
+        assert_eq!(registry.claim(&frontend).unwrap().sats(), 1_000);
+        assert!(registry.claim(&frontend).unwrap().is_zero());
+        assert_eq!(registry.get(&frontend).unwrap().total_gains.sats(), 1_000);
+
+        registry.untag(&alice);
+        assert_eq!(registry.get(&frontend).unwrap().depositors, 0);
+        assert!(registry.tag_of(&alice).is_none());
+    }
+}
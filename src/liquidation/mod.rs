@@ -3,10 +3,13 @@
 //! This module handles liquidations and the stability pool:
 //! - Liquidation engine for undercollateralized CDPs
 //! - Stability pool for absorbing liquidations
+//! - Frontend operators earning a share of pool gains
 //! - Redistribution mechanism for excess debt
 
 pub mod engine;
+pub mod frontend;
 pub mod stability_pool;
 
 pub use engine::*;
+pub use frontend::*;
 pub use stability_pool::*;
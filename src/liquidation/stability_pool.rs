@@ -3,16 +3,36 @@
 //! The stability pool allows users to deposit zkUSD which is used to absorb
 //! liquidations. In return, depositors receive a share of the liquidated collateral
 //! at a discount.
+//!
+//! Deposits are tracked with Liquity's product-sum scheme. A running product
+//! `P` records how much of every deposit survives the liquidations so far, and
+//! a running sum `S` the BTC earned per unit deposited. Each deposit only keeps
+//! a snapshot of both, so liquidations cost O(1) regardless of depositor count:
+//!
+//! - When `P` would fall below `SP_SCALE_CHANGE_FACTOR` it is scaled back up
+//!   and the *scale* is incremented (twice if once is not enough), so `P`
+//!   never drops below `SP_SCALE_CHANGE_FACTOR` or reaches zero. Deposits
+//!   more than one scale behind are worth less than a billionth of their
+//!   initial value and count as empty.
+//! - When a liquidation empties the pool, the *epoch* is incremented and `P`
+//!   restarts at one. Deposits from earlier epochs are worth nothing.
+//! - `S` is kept separately per epoch and scale, so gains earned in an epoch
+//!   or scale a deposit has since left are still paid out to it. The BTC
+//!   gained per cent is tracked to `SP_GAIN_PRECISION`, so `S` grows by at
+//!   most ~10^35 per liquidation even at a BTC price of one cent.
+//!
+//! Rounding errors of each liquidation are carried into the next one, and all
+//! rounding favours the pool.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::core::token::TokenAmount;
 use crate::core::vault::CollateralAmount;
 use crate::error::{Error, Result};
 use crate::utils::constants::*;
 use crate::utils::crypto::{Hash, PublicKey};
-use crate::utils::math::*;
+use crate::utils::undo::{Undo, UndoMap};
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // DEPOSITOR SNAPSHOT
@@ -23,7 +43,8 @@ use crate::utils::math::*;
 pub struct DepositorSnapshot {
     /// Product factor at time of deposit (tracks zkUSD losses)
     pub p: u128,
-    /// Sum factor at time of deposit (tracks BTC gains)
+    /// Sum factor of the deposit's epoch and scale at time of deposit
+    /// (tracks BTC gains)
     pub s: u128,
     /// Epoch at time of deposit
     pub epoch: u64,
@@ -42,6 +63,33 @@ impl Default for DepositorSnapshot {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// GAIN SUMS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Running BTC gain sums (`S`) by epoch and scale
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GainSums(BTreeMap<u64, BTreeMap<u64, u128>>);
+
+impl GainSums {
+    /// Sum for `epoch` and `scale` (zero if nothing was earned there)
+    pub fn get(&self, epoch: u64, scale: u64) -> u128 {
+        self.0
+            .get(&epoch)
+            .and_then(|scales| scales.get(&scale))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn add(&mut self, epoch: u64, scale: u64, amount: u128) -> Result<u128> {
+        let sum = self.0.entry(epoch).or_default().entry(scale).or_insert(0);
+        *sum = sum.checked_add(amount).ok_or(Error::Overflow {
+            operation: "stability pool gain sum".into(),
+        })?;
+        Ok(*sum)
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // DEPOSIT
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -72,42 +120,48 @@ impl Deposit {
 
     /// Calculate current deposit value (after absorbing liquidations)
     pub fn current_value(&self, current_p: u128, current_epoch: u64, current_scale: u64) -> TokenAmount {
-        // If epochs don't match, deposit has been fully consumed
-        if current_epoch != self.snapshot.epoch {
+        // Emptied by a liquidation that depleted the pool
+        if self.snapshot.epoch < current_epoch {
             return TokenAmount::ZERO;
         }
 
-        // If scale differs by more than 1, deposit is negligible
-        if current_scale > self.snapshot.scale + 1 {
-            return TokenAmount::ZERO;
-        }
-
-        // Calculate compounded deposit
-        let scale_diff = current_scale - self.snapshot.scale;
-        let p_ratio = if scale_diff == 0 {
-            current_p * SP_SCALE_FACTOR / self.snapshot.p
-        } else {
-            // Scale changed, so deposit is much smaller
-            current_p * SP_SCALE_FACTOR / (self.snapshot.p * SP_SCALE_FACTOR)
+        let initial = self.initial_amount.cents() as u128;
+        let compounded = match current_scale.saturating_sub(self.snapshot.scale) {
+            0 => mul_div(initial, current_p, self.snapshot.p),
+            1 => mul_div(initial, current_p, self.snapshot.p) / SP_SCALE_CHANGE_FACTOR,
+            // Less than a billionth of the deposit is left
+            _ => 0,
         };
 
-        let compounded = (self.initial_amount.cents() as u128 * p_ratio / SP_SCALE_FACTOR) as u64;
-        TokenAmount::from_cents(compounded)
+        // Treat remainders below a billionth as rounding noise
+        if compounded < initial / SP_SCALE_CHANGE_FACTOR {
+            return TokenAmount::ZERO;
+        }
+        TokenAmount::from_cents(compounded.min(u64::MAX as u128) as u64)
     }
 
-    /// Calculate BTC gains from liquidations
-    pub fn btc_gains(&self, current_s: u128, current_epoch: u64, current_scale: u64) -> CollateralAmount {
-        // If epochs don't match, calculate gains differently
-        if current_epoch != self.snapshot.epoch {
-            return CollateralAmount::ZERO;
-        }
+    /// Calculate BTC gains from liquidations.
+    ///
+    /// Gains accrue in the deposit's own epoch, across its snapshot scale
+    /// and the next one; beyond that the deposit is too small to earn more.
+    pub fn btc_gains(&self, sums: &GainSums) -> CollateralAmount {
+        let snapshot = &self.snapshot;
+        let first = sums.get(snapshot.epoch, snapshot.scale).saturating_sub(snapshot.s);
+        let second = sums.get(snapshot.epoch, snapshot.scale + 1) / SP_SCALE_CHANGE_FACTOR;
 
-        // Calculate gains based on S factor difference
-        let s_diff = current_s.saturating_sub(self.snapshot.s);
+        let per_unit = first.saturating_add(second);
+        let gains = mul_div(per_unit, self.initial_amount.cents() as u128, snapshot.p) / SP_GAIN_PRECISION;
+        CollateralAmount::from_sats(gains.min(u64::MAX as u128) as u64)
+    }
+}
 
-        let gains = (self.initial_amount.cents() as u128 * s_diff / SP_SCALE_FACTOR) as u64;
-        CollateralAmount::from_sats(gains)
+/// `a * b / c` rounded down; only `(a % c) * b` is multiplied out in full,
+/// so large sums times deposit amounts stay within `u128`
+fn mul_div(a: u128, b: u128, c: u128) -> u128 {
+    if c == 0 {
+        return 0;
     }
+    (a / c).saturating_mul(b).saturating_add((a % c).saturating_mul(b) / c)
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -123,16 +177,20 @@ pub struct StabilityPool {
     total_btc_gains: CollateralAmount,
     /// Product factor (tracks zkUSD losses)
     p: u128,
-    /// Sum factor (tracks BTC gains per unit deposit)
-    s: u128,
-    /// Current epoch (increments when P becomes very small)
+    /// Current epoch (increments when a liquidation empties the pool)
     epoch: u64,
-    /// Current scale (for precision management)
+    /// Current scale (increments when P is scaled back up)
     scale: u64,
+    /// Sum factors (track BTC gains per unit deposit) by epoch and scale
+    sums: GainSums,
+    /// BTC rounding error carried into the next liquidation
+    last_btc_error: u128,
+    /// zkUSD loss rounding error carried into the next liquidation
+    last_debt_loss_error: u128,
     /// Individual deposits
-    deposits: HashMap<PublicKey, Deposit>,
+    deposits: UndoMap<HashMap<PublicKey, Deposit>>,
     /// Pending BTC rewards by depositor
-    pending_btc: HashMap<PublicKey, CollateralAmount>,
+    pending_btc: UndoMap<HashMap<PublicKey, CollateralAmount>>,
     /// Total liquidations absorbed
     total_liquidations: u64,
     /// Total debt absorbed
@@ -152,16 +210,49 @@ impl StabilityPool {
             total_deposits: TokenAmount::ZERO,
             total_btc_gains: CollateralAmount::ZERO,
             p: SP_SCALE_FACTOR,
-            s: 0,
             epoch: 0,
             scale: 0,
-            deposits: HashMap::new(),
-            pending_btc: HashMap::new(),
+            sums: GainSums::default(),
+            last_btc_error: 0,
+            last_debt_loss_error: 0,
+            deposits: UndoMap::new(),
+            pending_btc: UndoMap::new(),
             total_liquidations: 0,
             total_debt_absorbed: TokenAmount::ZERO,
         }
     }
 
+    /// Rebuild a pool from balances settled under an older accounting
+    /// scheme: each deposit restarts from its settled value with a fresh
+    /// snapshot, and its gains so far become pending BTC
+    pub(crate) fn from_settled(
+        settled: impl IntoIterator<Item = (Deposit, CollateralAmount)>,
+        total_deposits: TokenAmount,
+        total_btc_gains: CollateralAmount,
+        total_liquidations: u64,
+        total_debt_absorbed: TokenAmount,
+    ) -> Self {
+        let mut pool = Self {
+            total_deposits,
+            total_btc_gains,
+            total_liquidations,
+            total_debt_absorbed,
+            ..Self::new()
+        };
+
+        for (mut deposit, gains) in settled {
+            if !gains.is_zero() {
+                pool.pending_btc.insert(deposit.owner, gains);
+            }
+            if !deposit.initial_amount.is_zero() {
+                deposit.snapshot = pool.snapshot();
+                pool.deposits.insert(deposit.owner, deposit);
+            }
+        }
+
+        pool
+    }
+
     // ═══════════════════════════════════════════════════════════════════════════
     // DEPOSITS
     // ═══════════════════════════════════════════════════════════════════════════
@@ -184,29 +275,21 @@ impl StabilityPool {
             });
         }
 
-        // If existing deposit, first claim rewards and withdraw
+        // If existing deposit, set aside its rewards and roll its value into the new one
+        let mut new_amount = amount;
         if let Some(existing) = self.deposits.get(&owner) {
             let current_value = existing.current_value(self.p, self.epoch, self.scale);
-            let btc_gains = existing.btc_gains(self.s, self.epoch, self.scale);
+            let btc_gains = existing.btc_gains(&self.sums);
 
             // Store pending BTC for later claim
-            let pending = self.pending_btc.entry(owner).or_insert(CollateralAmount::ZERO);
+            let pending = self.pending_btc.get_or_insert_with(owner, || CollateralAmount::ZERO);
             *pending = pending.saturating_add(btc_gains);
 
-            // Adjust total deposits
-            self.total_deposits = self.total_deposits.saturating_sub(current_value);
+            new_amount = new_amount.saturating_add(current_value);
         }
 
-        // Create snapshot
-        let snapshot = DepositorSnapshot {
-            p: self.p,
-            s: self.s,
-            epoch: self.epoch,
-            scale: self.scale,
-        };
-
         // Create new deposit
-        let deposit = Deposit::new(owner, amount, snapshot, block_height);
+        let deposit = Deposit::new(owner, new_amount, self.snapshot(), block_height);
         self.deposits.insert(owner, deposit);
 
         // Update total
@@ -230,7 +313,7 @@ impl StabilityPool {
 
         // Calculate current values
         let current_value = deposit.current_value(self.p, self.epoch, self.scale);
-        let btc_gains = deposit.btc_gains(self.s, self.epoch, self.scale);
+        let btc_gains = deposit.btc_gains(&self.sums);
 
         // Add any pending BTC
         let pending = self.pending_btc.remove(owner).unwrap_or(CollateralAmount::ZERO);
@@ -257,13 +340,7 @@ impl StabilityPool {
             self.deposits.remove(owner);
         } else {
             // Create new deposit with remaining amount
-            let snapshot = DepositorSnapshot {
-                p: self.p,
-                s: self.s,
-                epoch: self.epoch,
-                scale: self.scale,
-            };
-            let new_deposit = Deposit::new(*owner, remaining, snapshot, block_height);
+            let new_deposit = Deposit::new(*owner, remaining, self.snapshot(), block_height);
             self.deposits.insert(*owner, new_deposit);
 
             // Store remaining BTC
@@ -289,7 +366,7 @@ impl StabilityPool {
             })?;
 
         // Calculate BTC gains
-        let btc_gains = deposit.btc_gains(self.s, self.epoch, self.scale);
+        let btc_gains = deposit.btc_gains(&self.sums);
         let pending = self.pending_btc.remove(owner).unwrap_or(CollateralAmount::ZERO);
         let total_btc = btc_gains.saturating_add(pending);
 
@@ -298,15 +375,8 @@ impl StabilityPool {
         }
 
         // Update deposit snapshot to current
-        let snapshot = DepositorSnapshot {
-            p: self.p,
-            s: self.s,
-            epoch: self.epoch,
-            scale: self.scale,
-        };
-
         let current_value = deposit.current_value(self.p, self.epoch, self.scale);
-        let new_deposit = Deposit::new(*owner, current_value, snapshot, deposit.deposited_at);
+        let new_deposit = Deposit::new(*owner, current_value, self.snapshot(), deposit.deposited_at);
         self.deposits.insert(*owner, new_deposit);
 
         // Update total
@@ -315,6 +385,33 @@ impl StabilityPool {
         Ok(total_btc)
     }
 
+    /// Divide every gain sum, snapshot and carried BTC error by `divisor`,
+    /// for pools stored at a higher `S` precision
+    pub(crate) fn reduce_gain_precision(mut self, divisor: u128) -> Self {
+        for scales in self.sums.0.values_mut() {
+            for sum in scales.values_mut() {
+                *sum /= divisor;
+            }
+        }
+        let mut deposits = self.deposits.into_inner();
+        for deposit in deposits.values_mut() {
+            deposit.snapshot.s /= divisor;
+        }
+        self.deposits = deposits.into();
+        self.last_btc_error /= divisor;
+        self
+    }
+
+    /// Snapshot of the current product, sum, epoch and scale
+    fn snapshot(&self) -> DepositorSnapshot {
+        DepositorSnapshot {
+            p: self.p,
+            s: self.sums.get(self.epoch, self.scale),
+            epoch: self.epoch,
+            scale: self.scale,
+        }
+    }
+
     // ═══════════════════════════════════════════════════════════════════════════
     // LIQUIDATION ABSORPTION
     // ═══════════════════════════════════════════════════════════════════════════
@@ -334,29 +431,58 @@ impl StabilityPool {
             return Ok(false);
         }
 
-        // Update P (product factor) - represents zkUSD reduction
-        // P_new = P * (1 - debt/total_deposits)
-        let debt_ratio = (debt_to_absorb.cents() as u128) * SP_SCALE_FACTOR
-            / (self.total_deposits.cents() as u128);
-        let reduction_factor = SP_SCALE_FACTOR - debt_ratio;
+        let total = self.total_deposits.cents() as u128;
+        let overflow = |operation: &str| Error::Overflow { operation: operation.into() };
+
+        // BTC per unit deposited, carrying over last time's rounding error
+        let btc_numerator = (collateral_gained.sats() as u128)
+            .checked_mul(SP_GAIN_PRECISION)
+            .and_then(|n| n.checked_add(self.last_btc_error))
+            .ok_or_else(|| overflow("stability pool gain"))?;
+        let btc_per_unit = btc_numerator / total;
+        self.last_btc_error = btc_numerator - btc_per_unit * total;
+
+        // zkUSD lost per unit deposited, rounded up so the pool never owes
+        // more than it holds
+        let loss_per_unit = if debt_to_absorb.cents() as u128 == total {
+            self.last_debt_loss_error = 0;
+            SP_SCALE_FACTOR
+        } else {
+            let loss_numerator = (debt_to_absorb.cents() as u128 * SP_SCALE_FACTOR)
+                .saturating_sub(self.last_debt_loss_error);
+            let loss_per_unit = (loss_numerator / total + 1).min(SP_SCALE_FACTOR);
+            self.last_debt_loss_error = (loss_per_unit * total).saturating_sub(loss_numerator);
+            loss_per_unit
+        };
 
-        let new_p = self.p * reduction_factor / SP_SCALE_FACTOR;
+        // Update S (sum factor) for the current epoch and scale
+        // S += P * collateral / total_deposits
+        let marginal_gain = btc_per_unit
+            .checked_mul(self.p)
+            .ok_or_else(|| overflow("stability pool gain"))?;
+        self.sums.add(self.epoch, self.scale, marginal_gain)?;
 
-        // Check if P is getting too small (precision loss)
-        if new_p < SP_SCALE_FACTOR / 1_000_000_000 {
-            // Increment scale and reset P
-            self.scale += 1;
-            self.p = new_p * SP_SCALE_FACTOR;
+        // Update P (product factor) - represents zkUSD reduction
+        // P_new = P * (1 - debt/total_deposits)
+        let product_factor = SP_SCALE_FACTOR - loss_per_unit;
+        if product_factor == 0 {
+            // The pool is emptied: start a new epoch
+            self.epoch += 1;
+            self.scale = 0;
+            self.p = SP_SCALE_FACTOR;
         } else {
-            self.p = new_p;
+            // Scale P back up to keep precision. P is at least
+            // SP_SCALE_CHANGE_FACTOR, so two scale changes always suffice
+            // and P never reaches zero.
+            let product = self.p * product_factor;
+            let mut divisor = SP_SCALE_FACTOR;
+            while divisor > 1 && product / divisor < SP_SCALE_CHANGE_FACTOR {
+                divisor /= SP_SCALE_CHANGE_FACTOR;
+                self.scale += 1;
+            }
+            self.p = product / divisor;
         }
 
-        // Update S (sum factor) - represents BTC gains per unit deposit
-        // S_new = S + collateral / total_deposits
-        let gains_per_unit = (collateral_gained.sats() as u128) * SP_SCALE_FACTOR
-            / (self.total_deposits.cents() as u128);
-        self.s = self.s.saturating_add(gains_per_unit);
-
         // Update totals
         self.total_deposits = self.total_deposits.saturating_sub(debt_to_absorb);
         self.total_btc_gains = self.total_btc_gains.saturating_add(collateral_gained);
@@ -385,6 +511,11 @@ impl StabilityPool {
         self.deposits.get(owner)
     }
 
+    /// Get all deposits by owner
+    pub fn all_deposits(&self) -> &HashMap<PublicKey, Deposit> {
+        &self.deposits
+    }
+
     /// Get current deposit value for an owner
     pub fn get_current_value(&self, owner: &PublicKey) -> TokenAmount {
         self.deposits.get(owner)
@@ -392,10 +523,20 @@ impl StabilityPool {
             .unwrap_or(TokenAmount::ZERO)
     }
 
+    /// Sum of all depositors' current values; rounding keeps it at or
+    /// below `total_deposits`
+    pub fn total_current_value(&self) -> TokenAmount {
+        let cents = self.deposits
+            .values()
+            .map(|d| d.current_value(self.p, self.epoch, self.scale).cents())
+            .fold(0u64, u64::saturating_add);
+        TokenAmount::from_cents(cents)
+    }
+
     /// Get pending BTC gains for an owner
     pub fn get_btc_gains(&self, owner: &PublicKey) -> CollateralAmount {
         let from_deposit = self.deposits.get(owner)
-            .map(|d| d.btc_gains(self.s, self.epoch, self.scale))
+            .map(|d| d.btc_gains(&self.sums))
             .unwrap_or(CollateralAmount::ZERO);
 
         let pending = self.pending_btc.get(owner)
@@ -458,13 +599,70 @@ impl StabilityPool {
         data.extend_from_slice(&self.total_deposits.cents().to_be_bytes());
         data.extend_from_slice(&self.total_btc_gains.sats().to_be_bytes());
         data.extend_from_slice(&self.p.to_be_bytes());
-        data.extend_from_slice(&self.s.to_be_bytes());
+        data.extend_from_slice(&self.sums.get(self.epoch, self.scale).to_be_bytes());
         data.extend_from_slice(&self.epoch.to_be_bytes());
         data.extend_from_slice(&self.scale.to_be_bytes());
         Hash::sha256(&data)
     }
 }
 
+/// Pool state an undo log does not cover, saved when it opens
+#[derive(Debug, Clone)]
+pub struct StabilityPoolMark {
+    total_deposits: TokenAmount,
+    total_btc_gains: CollateralAmount,
+    p: u128,
+    epoch: u64,
+    scale: u64,
+    sums: GainSums,
+    last_btc_error: u128,
+    last_debt_loss_error: u128,
+    total_liquidations: u64,
+    total_debt_absorbed: TokenAmount,
+}
+
+impl Undo for StabilityPool {
+    type Mark = StabilityPoolMark;
+
+    fn begin_undo(&mut self) -> StabilityPoolMark {
+        self.deposits.begin_undo();
+        self.pending_btc.begin_undo();
+        // Sums only grow by one entry per epoch or scale change
+        StabilityPoolMark {
+            total_deposits: self.total_deposits,
+            total_btc_gains: self.total_btc_gains,
+            p: self.p,
+            epoch: self.epoch,
+            scale: self.scale,
+            sums: self.sums.clone(),
+            last_btc_error: self.last_btc_error,
+            last_debt_loss_error: self.last_debt_loss_error,
+            total_liquidations: self.total_liquidations,
+            total_debt_absorbed: self.total_debt_absorbed,
+        }
+    }
+
+    fn commit_undo(&mut self) {
+        self.deposits.commit_undo();
+        self.pending_btc.commit_undo();
+    }
+
+    fn rollback_undo(&mut self, mark: StabilityPoolMark) {
+        self.deposits.rollback_undo();
+        self.pending_btc.rollback_undo();
+        self.total_deposits = mark.total_deposits;
+        self.total_btc_gains = mark.total_btc_gains;
+        self.p = mark.p;
+        self.epoch = mark.epoch;
+        self.scale = mark.scale;
+        self.sums = mark.sums;
+        self.last_btc_error = mark.last_btc_error;
+        self.last_debt_loss_error = mark.last_debt_loss_error;
+        self.total_liquidations = mark.total_liquidations;
+        self.total_debt_absorbed = mark.total_debt_absorbed;
+    }
+}
+
 /// Stability pool statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StabilityPoolStats {
@@ -499,6 +697,34 @@ mod tests {
         assert_eq!(pool.depositor_count(), 1);
     }
 
+    #[test]
+    fn test_top_up_keeps_existing_deposit() {
+        let mut pool = StabilityPool::new();
+
+        pool.deposit(test_pubkey(), TokenAmount::from_dollars(1000), 1).unwrap();
+        pool.deposit(test_pubkey(), TokenAmount::from_dollars(500), 2).unwrap();
+
+        assert_eq!(pool.total_deposits(), TokenAmount::from_dollars(1500));
+        assert_eq!(pool.get_current_value(&test_pubkey()), TokenAmount::from_dollars(1500));
+    }
+
+    #[test]
+    fn test_top_up_after_liquidation_keeps_value_and_gains() {
+        // A top-up once dropped the existing deposit's value from the total
+        // and replaced the deposit with only the new amount
+        let mut pool = StabilityPool::new();
+
+        pool.deposit(test_pubkey(), TokenAmount::from_dollars(1000), 1).unwrap();
+        pool.absorb_liquidation(TokenAmount::from_dollars(200), CollateralAmount::from_sats(300_000)).unwrap();
+        pool.deposit(test_pubkey(), TokenAmount::from_dollars(500), 2).unwrap();
+
+        assert_eq!(pool.total_deposits(), TokenAmount::from_dollars(1300));
+        let value = pool.get_current_value(&test_pubkey()).cents();
+        assert!((129_999..=130_000).contains(&value), "value {}", value);
+        let gains = pool.get_btc_gains(&test_pubkey()).sats();
+        assert!((299_999..=300_000).contains(&gains), "gains {}", gains);
+    }
+
     #[test]
     fn test_deposit_minimum() {
         let mut pool = StabilityPool::new();
@@ -620,4 +846,173 @@ mod tests {
         assert!(pool.can_absorb(TokenAmount::from_dollars(1000)));
         assert!(!pool.can_absorb(TokenAmount::from_dollars(1001)));
     }
+
+    #[test]
+    fn test_depleting_liquidation_starts_new_epoch() {
+        let mut pool = StabilityPool::new();
+        let (a, b, c) = (test_pubkey(), test_pubkey_2(), PublicKey::new([0x04; PUBKEY_LENGTH]));
+
+        pool.deposit(a, TokenAmount::from_cents(100_000), 1).unwrap();
+        pool.deposit(b, TokenAmount::from_cents(300_000), 1).unwrap();
+        pool.absorb_liquidation(TokenAmount::from_cents(400_000), CollateralAmount::from_sats(4_000_000))
+            .unwrap();
+
+        // Both deposits are used up but keep their share of the gains
+        assert_eq!(pool.statistics().current_epoch, 1);
+        assert!(pool.total_deposits().is_zero());
+        assert!(pool.get_current_value(&a).is_zero());
+        assert_eq!(pool.get_btc_gains(&a).sats(), 1_000_000);
+        assert_eq!(pool.get_btc_gains(&b).sats(), 3_000_000);
+
+        // Deposits in the new epoch only share its liquidations
+        pool.deposit(c, TokenAmount::from_cents(100_000), 2).unwrap();
+        pool.absorb_liquidation(TokenAmount::from_cents(50_000), CollateralAmount::from_sats(500_000))
+            .unwrap();
+        // Losses round up, so the compounded value rounds in the pool's favour
+        assert_eq!(pool.get_current_value(&c).cents(), 49_999);
+        assert_eq!(pool.get_btc_gains(&c).sats(), 500_000);
+        assert_eq!(pool.get_btc_gains(&a).sats(), 1_000_000);
+
+        let (_, claimed) = pool.withdraw(&b, TokenAmount::ZERO, 3).unwrap();
+        assert_eq!(claimed.sats(), 3_000_000);
+        assert_eq!(pool.depositor_count(), 2);
+    }
+
+    #[test]
+    fn test_near_total_liquidation_changes_scale() {
+        let mut pool = StabilityPool::new();
+        let (a, b) = (test_pubkey(), test_pubkey_2());
+
+        // Leave a single cent of a $10B pool
+        pool.deposit(a, TokenAmount::from_cents(1_000_000_000_000), 1).unwrap();
+        pool.absorb_liquidation(
+            TokenAmount::from_cents(999_999_999_999),
+            CollateralAmount::from_sats(100_000_000),
+        ).unwrap();
+        assert_eq!(pool.statistics().current_scale, 1);
+        assert_eq!(pool.statistics().current_epoch, 0);
+
+        // A new deposit keeps full precision after the scale change
+        pool.deposit(b, TokenAmount::from_cents(1_000_000), 2).unwrap();
+        pool.absorb_liquidation(TokenAmount::from_cents(500_000), CollateralAmount::from_sats(1_000_000))
+            .unwrap();
+
+        let value = pool.get_current_value(&b).cents();
+        assert!((499_999..=500_001).contains(&value), "value {}", value);
+        let gains_b = pool.get_btc_gains(&b).sats();
+        assert!((999_990..1_000_000).contains(&gains_b), "gains {}", gains_b);
+
+        // The first deposit's gains are not lost to the scale change
+        let gains_a = pool.get_btc_gains(&a).sats();
+        assert_eq!(gains_a, 100_000_000);
+        assert!(gains_a + gains_b <= pool.total_btc_gains().sats());
+    }
+
+    #[test]
+    fn test_liquidation_at_extreme_low_price() {
+        let mut pool = StabilityPool::new();
+        let (a, b) = (test_pubkey(), test_pubkey_2());
+
+        // A $10 pool absorbing $10 of debt backed by 11 BTC (BTC at one
+        // cent) once overflowed the gain sum
+        pool.deposit(a, TokenAmount::from_cents(1_000), 1).unwrap();
+        pool.deposit(b, TokenAmount::from_cents(1_000), 1).unwrap();
+        pool.absorb_liquidation(TokenAmount::from_cents(1_000), CollateralAmount::from_sats(1_100_000_000))
+            .unwrap();
+        assert_eq!(pool.get_btc_gains(&a).sats(), 550_000_000);
+        assert!((499..=500).contains(&pool.get_current_value(&a).cents()));
+
+        // A hundred liquidations at 150% collateral and that price still fit
+        let mut pool = StabilityPool::new();
+        pool.deposit(a, TokenAmount::from_cents(1_000_000), 1).unwrap();
+        for _ in 0..100 {
+            let debt = pool.total_deposits().cents() / 100;
+            pool.absorb_liquidation(TokenAmount::from_cents(debt), CollateralAmount::from_sats(debt * 150_000_000))
+                .unwrap();
+        }
+        let (gains, total) = (pool.get_btc_gains(&a).sats(), pool.total_btc_gains().sats());
+        assert!(gains <= total && total - gains <= 100, "gains {} of {}", gains, total);
+    }
+
+    #[test]
+    fn test_product_never_reaches_zero() {
+        let mut pool = StabilityPool::new();
+        let owner = test_pubkey();
+
+        // Each liquidation leaves a single cent of a refilled pool, more
+        // than a single scale change can make up for
+        for round in 0..4 {
+            pool.deposit(owner, TokenAmount::from_cents(100_000_000_000_000_000), round).unwrap();
+            let total = pool.total_deposits().cents();
+            pool.absorb_liquidation(TokenAmount::from_cents(total - 1), CollateralAmount::from_sats(1_000))
+                .unwrap();
+            assert!(pool.p >= SP_SCALE_CHANGE_FACTOR, "P fell to {}", pool.p);
+        }
+
+        // A fresh deposit still tracks its value and gains
+        let fresh = test_pubkey_2();
+        pool.deposit(fresh, TokenAmount::from_cents(100_000), 2).unwrap();
+        let total = pool.total_deposits().cents();
+        pool.absorb_liquidation(TokenAmount::from_cents(total / 2), CollateralAmount::from_sats(1_000_000))
+            .unwrap();
+        let value = pool.get_current_value(&fresh).cents();
+        assert!((49_999..=50_001).contains(&value), "value {}", value);
+        assert!(pool.get_btc_gains(&fresh).sats() >= 999_000);
+    }
+
+    #[test]
+    fn test_reduce_gain_precision_keeps_gains() {
+        let mut pool = StabilityPool::new();
+        let owner = test_pubkey();
+        pool.deposit(owner, TokenAmount::from_cents(100_000), 1).unwrap();
+        pool.absorb_liquidation(TokenAmount::from_cents(30_000), CollateralAmount::from_sats(1_234_567))
+            .unwrap();
+        pool.deposit(test_pubkey_2(), TokenAmount::from_cents(50_000), 2).unwrap();
+
+        // The same pool as stored before gains were tracked to SP_GAIN_PRECISION
+        let factor = SP_SCALE_FACTOR / SP_GAIN_PRECISION;
+        let mut stored = pool.clone();
+        for scales in stored.sums.0.values_mut() {
+            for sum in scales.values_mut() {
+                *sum *= factor;
+            }
+        }
+        let mut deposits = stored.deposits.into_inner();
+        for deposit in deposits.values_mut() {
+            deposit.snapshot.s *= factor;
+        }
+        stored.deposits = deposits.into();
+        stored.last_btc_error *= factor;
+
+        let migrated = stored.reduce_gain_precision(factor);
+        assert_eq!(migrated.get_btc_gains(&owner), pool.get_btc_gains(&owner));
+        assert_eq!(migrated.get_btc_gains(&test_pubkey_2()), pool.get_btc_gains(&test_pubkey_2()));
+        assert_eq!(migrated.state_hash(), pool.state_hash());
+    }
+
+    #[test]
+    fn test_many_liquidations_stay_accurate() {
+        let mut pool = StabilityPool::new();
+        let (a, b) = (test_pubkey(), test_pubkey_2());
+
+        pool.deposit(a, TokenAmount::from_cents(1_000_000), 1).unwrap();
+        pool.deposit(b, TokenAmount::from_cents(3_000_000), 1).unwrap();
+
+        // 200 liquidations of 2% each
+        for _ in 0..200 {
+            let debt = pool.total_deposits().cents() / 50;
+            pool.absorb_liquidation(TokenAmount::from_cents(debt), CollateralAmount::from_sats(10_000))
+                .unwrap();
+        }
+
+        let (value_a, value_b) = (pool.get_current_value(&a).cents(), pool.get_current_value(&b).cents());
+        assert!(value_a + value_b <= pool.total_deposits().cents());
+        assert!(pool.total_deposits().cents() - (value_a + value_b) <= 2);
+        assert!(value_b.abs_diff(value_a * 3) <= 3);
+
+        let (gains_a, gains_b) = (pool.get_btc_gains(&a).sats(), pool.get_btc_gains(&b).sats());
+        assert!(gains_a + gains_b <= 2_000_000);
+        assert!(2_000_000 - (gains_a + gains_b) <= 2);
+        assert!(gains_b.abs_diff(gains_a * 3) <= 3);
+    }
 }
@@ -0,0 +1,305 @@
+//! Accrued protocol statistics.
+//!
+//! Block events are folded into per-day and per-epoch totals (mint, burn and
+//! redemption volume, liquidations, fees) together with the zkUSD supply at
+//! the end of each period, so supply history and rolling volumes can be
+//! served without replaying stored events.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::protocol::events::ProtocolEvent;
+
+/// Seconds per aggregation day
+pub const SECS_PER_DAY: u64 = 86_400;
+
+/// Blocks per aggregation epoch by default (about two weeks)
+pub const DEFAULT_EPOCH_BLOCKS: u64 = 2_016;
+
+/// Days of daily totals kept by default
+pub const DEFAULT_RETAINED_DAYS: usize = 365;
+
+/// Totals over one day or epoch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeriodStats {
+    /// Day since the epoch, or epoch number
+    pub period: u64,
+    /// First block folded into the period
+    pub first_block: u64,
+    /// Last block folded into the period
+    pub last_block: u64,
+    /// Gross zkUSD minted in cents
+    pub mint_volume: u64,
+    /// zkUSD burned by repayments and redemptions in cents
+    pub burn_volume: u64,
+    /// zkUSD redeemed in cents
+    pub redemption_volume: u64,
+    /// CDPs liquidated
+    pub liquidations: u64,
+    /// Debt covered by liquidations in cents
+    pub liquidated_debt: u64,
+    /// Borrowing and redemption fees in cents
+    pub fees_collected: u64,
+    /// zkUSD supply at the end of the last block in cents
+    pub closing_supply: u64,
+}
+
+impl PeriodStats {
+    fn new(period: u64, block_height: u64) -> Self {
+        Self {
+            period,
+            first_block: block_height,
+            last_block: block_height,
+            ..Default::default()
+        }
+    }
+
+    fn record(&mut self, event: &ProtocolEvent) {
+        match event {
+            ProtocolEvent::DebtMinted(e) => {
+                self.mint_volume += e.gross_amount.cents();
+                self.fees_collected += e.fee.cents();
+            }
+            ProtocolEvent::DebtRepaid(e) => self.burn_volume += e.amount.cents(),
+            ProtocolEvent::Redemption(e) => {
+                self.burn_volume += e.zkusd_amount.cents();
+                self.redemption_volume += e.zkusd_amount.cents();
+                self.fees_collected += e.fee.cents();
+            }
+            ProtocolEvent::CDPLiquidated(e) => {
+                self.liquidations += 1;
+                self.liquidated_debt += e.debt_covered.cents();
+            }
+            _ => {}
+        }
+    }
+
+    /// Add the flows of `other`, keeping the later closing supply
+    fn merge(&mut self, other: &PeriodStats) {
+        self.first_block = self.first_block.min(other.first_block);
+        self.mint_volume += other.mint_volume;
+        self.burn_volume += other.burn_volume;
+        self.redemption_volume += other.redemption_volume;
+        self.liquidations += other.liquidations;
+        self.liquidated_debt += other.liquidated_debt;
+        self.fees_collected += other.fees_collected;
+        if other.last_block >= self.last_block {
+            self.last_block = other.last_block;
+            self.closing_supply = other.closing_supply;
+        }
+    }
+}
+
+/// Daily and per-epoch protocol statistics
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolAggregates {
+    /// Blocks per epoch
+    epoch_blocks: u64,
+    /// Daily totals kept
+    retained_days: usize,
+    /// Totals by day since the epoch
+    daily: BTreeMap<u64, PeriodStats>,
+    /// Totals by epoch number
+    epochs: BTreeMap<u64, PeriodStats>,
+}
+
+impl Default for ProtocolAggregates {
+    fn default() -> Self {
+        Self::new(DEFAULT_EPOCH_BLOCKS, DEFAULT_RETAINED_DAYS)
+    }
+}
+
+impl ProtocolAggregates {
+    /// Create empty aggregates with `epoch_blocks` per epoch, keeping
+    /// `retained_days` of daily totals
+    pub fn new(epoch_blocks: u64, retained_days: usize) -> Self {
+        Self {
+            epoch_blocks: epoch_blocks.max(1),
+            retained_days,
+            daily: BTreeMap::new(),
+            epochs: BTreeMap::new(),
+        }
+    }
+
+    /// Blocks per epoch
+    pub fn epoch_blocks(&self) -> u64 {
+        self.epoch_blocks
+    }
+
+    /// Fold a finished block into the totals
+    pub fn record_block(
+        &mut self,
+        block_height: u64,
+        timestamp: u64,
+        events: &[ProtocolEvent],
+        total_supply: u64,
+    ) {
+        let day = timestamp / SECS_PER_DAY;
+        let epoch = block_height / self.epoch_blocks;
+
+        for (period, totals) in [(day, &mut self.daily), (epoch, &mut self.epochs)] {
+            let stats = totals
+                .entry(period)
+                .or_insert_with(|| PeriodStats::new(period, block_height));
+            for event in events {
+                stats.record(event);
+            }
+            stats.last_block = stats.last_block.max(block_height);
+            stats.closing_supply = total_supply;
+        }
+
+        while self.daily.len() > self.retained_days {
+            self.daily.pop_first();
+        }
+    }
+
+    /// Totals for one day since the epoch
+    pub fn day(&self, day: u64) -> Option<&PeriodStats> {
+        self.daily.get(&day)
+    }
+
+    /// Daily totals for days `from..=to`, oldest first
+    pub fn days(&self, from: u64, to: u64) -> Vec<PeriodStats> {
+        self.daily.range(from..=to).map(|(_, stats)| *stats).collect()
+    }
+
+    /// Totals for one epoch
+    pub fn epoch(&self, epoch: u64) -> Option<&PeriodStats> {
+        self.epochs.get(&epoch)
+    }
+
+    /// Epoch totals for epochs `from..=to`, oldest first
+    pub fn epochs(&self, from: u64, to: u64) -> Vec<PeriodStats> {
+        self.epochs.range(from..=to).map(|(_, stats)| *stats).collect()
+    }
+
+    /// Totals over the `days` days ending with the day of `timestamp`
+    pub fn rolling(&self, timestamp: u64, days: u64) -> PeriodStats {
+        let last = timestamp / SECS_PER_DAY;
+        let first = last.saturating_sub(days.saturating_sub(1));
+
+        let mut window = PeriodStats::new(first, u64::MAX);
+        window.last_block = 0;
+        for (_, stats) in self.daily.range(first..=last) {
+            window.merge(stats);
+        }
+        if window.first_block == u64::MAX {
+            window.first_block = 0;
+        }
+        window
+    }
+
+    /// Closing supply of each recorded day, oldest first
+    pub fn supply_history(&self) -> Vec<(u64, u64)> {
+        self.daily.values().map(|stats| (stats.period, stats.closing_supply)).collect()
+    }
+
+    /// Most recent daily totals
+    pub fn latest_day(&self) -> Option<&PeriodStats> {
+        self.daily.values().next_back()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::cdp::CDPId;
+    use crate::core::token::TokenAmount;
+    use crate::core::vault::CollateralAmount;
+    use crate::protocol::events::{DebtMintedEvent, DebtRepaidEvent, RedemptionEvent};
+    use crate::utils::crypto::KeyPair;
+
+    fn minted(gross: u64, fee: u64) -> ProtocolEvent {
+        ProtocolEvent::DebtMinted(DebtMintedEvent {
+            cdp_id: CDPId::new([1u8; 32]),
+            owner: *KeyPair::generate().public_key(),
+            gross_amount: TokenAmount::from_cents(gross),
+            fee: TokenAmount::from_cents(fee),
+            net_amount: TokenAmount::from_cents(gross - fee),
+            new_debt: TokenAmount::from_cents(gross),
+            new_ratio: 200,
+            block_height: 0,
+            timestamp: 0,
+        })
+    }
+
+    fn repaid(amount: u64) -> ProtocolEvent {
+        ProtocolEvent::DebtRepaid(DebtRepaidEvent {
+            cdp_id: CDPId::new([1u8; 32]),
+            payer: *KeyPair::generate().public_key(),
+            amount: TokenAmount::from_cents(amount),
+            remaining_debt: TokenAmount::ZERO,
+            new_ratio: u64::MAX,
+            block_height: 0,
+            timestamp: 0,
+        })
+    }
+
+    fn redeemed(amount: u64, fee: u64) -> ProtocolEvent {
+        ProtocolEvent::Redemption(RedemptionEvent {
+            redeemer: *KeyPair::generate().public_key(),
+            zkusd_amount: TokenAmount::from_cents(amount),
+            collateral_received: CollateralAmount::from_sats(1_000),
+            fee: TokenAmount::from_cents(fee),
+            cdps_affected: 1,
+            btc_price: 10_000_000,
+            block_height: 0,
+            timestamp: 0,
+        })
+    }
+
+    #[test]
+    fn test_daily_and_epoch_totals() {
+        let mut aggregates = ProtocolAggregates::new(10, 30);
+
+        aggregates.record_block(5, 100, &[minted(10_000, 50)], 10_000);
+        aggregates.record_block(12, 200, &[repaid(2_000), redeemed(1_000, 5)], 7_000);
+        aggregates.record_block(13, SECS_PER_DAY + 1, &[minted(4_000, 20)], 11_000);
+
+        let day0 = aggregates.day(0).unwrap();
+        assert_eq!(day0.mint_volume, 10_000);
+        assert_eq!(day0.burn_volume, 3_000);
+        assert_eq!(day0.redemption_volume, 1_000);
+        assert_eq!(day0.fees_collected, 55);
+        assert_eq!(day0.closing_supply, 7_000);
+        assert_eq!((day0.first_block, day0.last_block), (5, 12));
+
+        let epoch1 = aggregates.epoch(1).unwrap();
+        assert_eq!(epoch1.mint_volume, 4_000);
+        assert_eq!(epoch1.burn_volume, 3_000);
+        assert_eq!(epoch1.closing_supply, 11_000);
+
+        assert_eq!(aggregates.supply_history(), vec![(0, 7_000), (1, 11_000)]);
+    }
+
+    #[test]
+    fn test_rolling_window() {
+        let mut aggregates = ProtocolAggregates::default();
+        for day in 0..5 {
+            aggregates.record_block(day, day * SECS_PER_DAY, &[minted(1_000, 10)], (day + 1) * 1_000);
+        }
+
+        let window = aggregates.rolling(4 * SECS_PER_DAY, 3);
+        assert_eq!(window.mint_volume, 3_000);
+        assert_eq!(window.fees_collected, 30);
+        assert_eq!((window.first_block, window.last_block), (2, 4));
+        assert_eq!(window.closing_supply, 5_000);
+
+        assert_eq!(aggregates.rolling(100 * SECS_PER_DAY, 7), PeriodStats {
+            period: 94,
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_daily_retention() {
+        let mut aggregates = ProtocolAggregates::new(10, 2);
+        for day in 0..4 {
+            aggregates.record_block(day, day * SECS_PER_DAY, &[], 0);
+        }
+
+        assert!(aggregates.day(1).is_none());
+        assert_eq!(aggregates.days(0, 10).len(), 2);
+        assert_eq!(aggregates.latest_day().unwrap().period, 3);
+    }
+}
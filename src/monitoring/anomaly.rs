@@ -0,0 +1,276 @@
+//! Anomaly detection on operation patterns.
+//!
+//! An `AnomalyDetector` keeps a rolling window of per-block activity: zkUSD
+//! minted, collateral withdrawn, distinct signers and the share of operations
+//! rejected for a bad signature. Each new block is scored against the window
+//! before joining it; a metric more than the configured number of standard
+//! deviations above its rolling mean is an anomaly. Only spikes count, since
+//! a quiet block is never a threat.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::protocol::events::ProtocolEvent;
+use crate::utils::constants::BPS_DIVISOR;
+use crate::utils::math::isqrt;
+
+/// Blocks in the rolling window by default (about a day)
+pub const DEFAULT_ANOMALY_WINDOW_BLOCKS: usize = 144;
+
+/// Blocks of history needed before anything is flagged by default
+pub const DEFAULT_ANOMALY_MIN_BLOCKS: usize = 24;
+
+/// Z-score flagged by default in basis points (4 standard deviations)
+pub const DEFAULT_ANOMALY_Z_THRESHOLD_BPS: u64 = 40_000;
+
+/// Detector sensitivity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnomalyConfig {
+    /// Blocks in the rolling window
+    pub window_blocks: usize,
+    /// Blocks of history needed before anything is flagged
+    pub min_blocks: usize,
+    /// Z-score at or above which a metric is flagged, in basis points;
+    /// lower is more sensitive
+    pub z_threshold_bps: u64,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            window_blocks: DEFAULT_ANOMALY_WINDOW_BLOCKS,
+            min_blocks: DEFAULT_ANOMALY_MIN_BLOCKS,
+            z_threshold_bps: DEFAULT_ANOMALY_Z_THRESHOLD_BPS,
+        }
+    }
+}
+
+/// Activity metric the detector watches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnomalyMetric {
+    /// zkUSD minted in cents
+    MintVolume,
+    /// Collateral withdrawn in satoshis
+    WithdrawalVolume,
+    /// Distinct signers of applied operations
+    UniqueSigners,
+    /// Operations rejected for a bad signature, in basis points of all
+    /// submitted operations. Only the local node sees rejected operations.
+    FailedSignatureRate,
+}
+
+impl AnomalyMetric {
+    /// Every watched metric
+    pub const ALL: [AnomalyMetric; 4] = [
+        AnomalyMetric::MintVolume,
+        AnomalyMetric::WithdrawalVolume,
+        AnomalyMetric::UniqueSigners,
+        AnomalyMetric::FailedSignatureRate,
+    ];
+}
+
+impl fmt::Display for AnomalyMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AnomalyMetric::MintVolume => "mint volume",
+            AnomalyMetric::WithdrawalVolume => "withdrawal volume",
+            AnomalyMetric::UniqueSigners => "unique signers",
+            AnomalyMetric::FailedSignatureRate => "failed signature rate",
+        })
+    }
+}
+
+/// Activity of a single block
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockActivity {
+    /// zkUSD minted in cents
+    pub mint_volume: u64,
+    /// Collateral withdrawn in satoshis
+    pub withdrawal_volume: u64,
+    /// Distinct signers of applied operations
+    pub unique_signers: u64,
+    /// Operations submitted, applied or not
+    pub operations: u64,
+    /// Operations rejected for a bad signature
+    pub failed_signatures: u64,
+}
+
+impl BlockActivity {
+    /// Add the volumes carried by `event`
+    pub fn record(&mut self, event: &ProtocolEvent) {
+        match event {
+            ProtocolEvent::DebtMinted(e) => {
+                self.mint_volume = self.mint_volume.saturating_add(e.gross_amount.cents());
+            }
+            ProtocolEvent::CollateralWithdrawn(e) => {
+                self.withdrawal_volume = self.withdrawal_volume.saturating_add(e.amount.sats());
+            }
+            _ => {}
+        }
+    }
+
+    /// Value of `metric` in this block
+    pub fn metric(&self, metric: AnomalyMetric) -> u64 {
+        match metric {
+            AnomalyMetric::MintVolume => self.mint_volume,
+            AnomalyMetric::WithdrawalVolume => self.withdrawal_volume,
+            AnomalyMetric::UniqueSigners => self.unique_signers,
+            AnomalyMetric::FailedSignatureRate => {
+                if self.operations == 0 {
+                    0
+                } else {
+                    (self.failed_signatures as u128 * BPS_DIVISOR as u128 / self.operations as u128) as u64
+                }
+            }
+        }
+    }
+}
+
+/// A metric that spiked in a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Anomaly {
+    /// Metric that spiked
+    pub metric: AnomalyMetric,
+    /// Value in the block
+    pub value: u64,
+    /// Rolling mean before the block
+    pub mean: u64,
+    /// Standard deviations above the mean in basis points
+    pub z_score_bps: u64,
+}
+
+/// Rolling z-score detector over per-block activity
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnomalyDetector {
+    /// Sensitivity
+    config: AnomalyConfig,
+    /// Recent blocks, oldest first
+    history: VecDeque<BlockActivity>,
+}
+
+impl AnomalyDetector {
+    /// Create a detector with `config`
+    pub fn new(config: AnomalyConfig) -> Self {
+        Self {
+            config,
+            history: VecDeque::new(),
+        }
+    }
+
+    /// Sensitivity
+    pub fn config(&self) -> &AnomalyConfig {
+        &self.config
+    }
+
+    /// Blocks in the window, oldest first
+    pub fn history(&self) -> &VecDeque<BlockActivity> {
+        &self.history
+    }
+
+    /// Replace the window, keeping its most recent blocks
+    pub fn restore(&mut self, history: impl IntoIterator<Item = BlockActivity>) {
+        self.history = history.into_iter().collect();
+        self.trim();
+    }
+
+    /// Score a block against the window, then add it to the window
+    pub fn observe(&mut self, activity: BlockActivity) -> Vec<Anomaly> {
+        let anomalies = if self.history.len() >= self.config.min_blocks.max(1) {
+            AnomalyMetric::ALL
+                .into_iter()
+                .filter_map(|metric| self.score(metric, activity.metric(metric)))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        self.history.push_back(activity);
+        self.trim();
+        anomalies
+    }
+
+    fn score(&self, metric: AnomalyMetric, value: u64) -> Option<Anomaly> {
+        let count = self.history.len() as u128;
+        let values = || self.history.iter().map(|block| block.metric(metric) as u128);
+        let mean = values().sum::<u128>() / count;
+        if value as u128 <= mean {
+            return None;
+        }
+
+        // A flat history still needs a unit of spread
+        let variance = values().map(|v| v.abs_diff(mean).pow(2)).sum::<u128>() / count;
+        let deviation = isqrt(variance).max(1);
+        let z_score_bps = ((value as u128 - mean) * BPS_DIVISOR as u128 / deviation).min(u64::MAX as u128) as u64;
+
+        (z_score_bps >= self.config.z_threshold_bps).then_some(Anomaly {
+            metric,
+            value,
+            mean: mean as u64,
+            z_score_bps,
+        })
+    }
+
+    fn trim(&mut self) {
+        while self.history.len() > self.config.window_blocks.max(1) {
+            self.history.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(mint_volume: u64, unique_signers: u64) -> BlockActivity {
+        BlockActivity {
+            mint_volume,
+            unique_signers,
+            operations: unique_signers,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_flags_spikes_only_after_warmup() {
+        let config = AnomalyConfig { window_blocks: 10, min_blocks: 5, z_threshold_bps: 30_000 };
+        let mut detector = AnomalyDetector::new(config);
+
+        // Nothing is flagged while the window fills
+        assert!(detector.observe(block(1_000_000, 5)).is_empty());
+        for i in 0..9 {
+            assert!(detector.observe(block(1_000_000 + (i % 3) * 100_000, 5 + i % 2)).is_empty());
+        }
+        assert_eq!(detector.history().len(), 10);
+
+        // Normal activity and quiet blocks pass
+        assert!(detector.observe(block(1_150_000, 6)).is_empty());
+        assert!(detector.observe(block(0, 0)).is_empty());
+
+        // A tenfold mint with a burst of new signers
+        let anomalies = detector.observe(block(10_000_000, 40));
+        let metrics: Vec<_> = anomalies.iter().map(|a| a.metric).collect();
+        assert_eq!(metrics, [AnomalyMetric::MintVolume, AnomalyMetric::UniqueSigners]);
+        assert!(anomalies[0].z_score_bps >= 30_000);
+        assert_eq!(detector.history().len(), 10);
+    }
+
+    #[test]
+    fn test_failed_signature_rate() {
+        let mut detector = AnomalyDetector::new(AnomalyConfig { min_blocks: 3, ..Default::default() });
+        let healthy = BlockActivity { operations: 20, failed_signatures: 1, ..Default::default() };
+        assert_eq!(healthy.metric(AnomalyMetric::FailedSignatureRate), 500);
+        for _ in 0..3 {
+            assert!(detector.observe(healthy).is_empty());
+        }
+
+        // Less sensitive detectors let more through
+        let mut lenient = detector.clone();
+        lenient.config.z_threshold_bps = u64::MAX;
+
+        let spraying = BlockActivity { operations: 20, failed_signatures: 15, ..Default::default() };
+        assert_eq!(detector.observe(spraying)[0].metric, AnomalyMetric::FailedSignatureRate);
+        assert!(lenient.observe(spraying).is_empty());
+    }
+}
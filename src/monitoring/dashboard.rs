@@ -0,0 +1,548 @@
+//! Live monitoring dashboard.
+//!
+//! A `DashboardFeed` bundles everything an operator console shows at once: a
+//! risk snapshot, the system collateral ratio, the CDPs closest to
+//! liquidation and the latest events. An `AlertBook` turns successive feeds
+//! into alerts that stay raised until their condition clears, and that an
+//! operator can acknowledge in the meantime.
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::cdp::CDPManager;
+use crate::core::token::TokenAmount;
+use crate::protocol::events::{AnomalyDetectedEvent, ProtocolEvent};
+use crate::utils::constants::BPS_DIVISOR;
+use crate::utils::crypto::CDPId;
+use crate::utils::math::calculate_collateral_ratio;
+
+use super::anomaly::AnomalyMetric;
+use super::snapshot::RiskSnapshot;
+
+/// CDPs within this many percentage points of the minimum ratio are at risk
+pub const AT_RISK_MARGIN: u64 = 20;
+
+/// Most at-risk CDPs kept in a feed
+pub const MAX_AT_RISK_CDPS: usize = 20;
+
+/// Most recent events kept in a feed
+pub const MAX_RECENT_EVENTS: usize = 50;
+
+/// A CDP close to liquidation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AtRiskCdp {
+    /// CDP identifier
+    pub id: CDPId,
+    /// Collateral ratio in percent
+    pub ratio: u64,
+    /// Debt in cents
+    pub debt_cents: u64,
+    /// Collateral in satoshis
+    pub collateral_sats: u64,
+    /// Whether the CDP can be liquidated right now
+    pub liquidatable: bool,
+}
+
+/// Everything the dashboard displays at one refresh
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardFeed {
+    /// Aggregated system risk
+    pub snapshot: RiskSnapshot,
+    /// Ratio below which the protocol enters recovery mode, in percent
+    pub critical_ratio: u64,
+    /// Total collateral ratio in percent (`u64::MAX` without debt)
+    pub tcr: u64,
+    /// System health from 0 (critical) to 100 (healthy)
+    pub health_score: u8,
+    /// CDPs within `AT_RISK_MARGIN` of the minimum ratio, riskiest first
+    pub at_risk: Vec<AtRiskCdp>,
+    /// Latest events, newest first
+    pub recent_events: Vec<ProtocolEvent>,
+}
+
+impl DashboardFeed {
+    /// Build a feed from protocol state; `recent_events` may be in any order
+    pub fn collect(
+        cdps: &CDPManager,
+        btc_price_cents: u64,
+        min_ratio: u64,
+        critical_ratio: u64,
+        pool_deposits: TokenAmount,
+        block_height: u64,
+        mut recent_events: Vec<ProtocolEvent>,
+    ) -> Self {
+        let snapshot = RiskSnapshot::compute(cdps, btc_price_cents, min_ratio, pool_deposits, block_height);
+
+        let at_risk = cdps
+            .get_sorted_by_ratio(btc_price_cents)
+            .into_iter()
+            .take_while(|(_, ratio)| *ratio < min_ratio.saturating_add(AT_RISK_MARGIN))
+            .take(MAX_AT_RISK_CDPS)
+            .map(|(cdp, ratio)| AtRiskCdp {
+                id: cdp.id,
+                ratio,
+                debt_cents: cdp.debt_cents,
+                collateral_sats: cdp.collateral_sats,
+                liquidatable: cdp.is_liquidatable(btc_price_cents, min_ratio),
+            })
+            .collect();
+
+        recent_events.sort_by_key(|event| std::cmp::Reverse(event.block_height()));
+        recent_events.truncate(MAX_RECENT_EVENTS);
+
+        Self {
+            tcr: total_collateral_ratio(&snapshot),
+            health_score: health_score(&snapshot),
+            snapshot,
+            critical_ratio,
+            at_risk,
+            recent_events,
+        }
+    }
+
+    /// Whether any at-risk CDP can be liquidated right now
+    pub fn has_liquidatable(&self) -> bool {
+        self.at_risk.iter().any(|cdp| cdp.liquidatable)
+    }
+}
+
+/// Total collateral ratio of a snapshot in percent (`u64::MAX` without debt)
+pub fn total_collateral_ratio(snapshot: &RiskSnapshot) -> u64 {
+    calculate_collateral_ratio(snapshot.total_collateral, snapshot.btc_price, snapshot.total_debt)
+        .unwrap_or(u64::MAX)
+}
+
+/// Score system health from 0 to 100
+///
+/// Half the score comes from the total collateral ratio (nothing at the
+/// minimum ratio, everything at twice it), a quarter from how much of the
+/// debt liquidatable after the mildest price shock the stability pool covers,
+/// and a quarter from how much debt survives the harshest shock.
+pub fn health_score(snapshot: &RiskSnapshot) -> u8 {
+    if snapshot.total_debt == 0 {
+        return 100;
+    }
+
+    let min_ratio = snapshot.min_ratio.max(1);
+    let tcr = total_collateral_ratio(snapshot);
+    let tcr_points = tcr.saturating_sub(min_ratio).min(min_ratio) * 50 / min_ratio;
+
+    let coverage_points = snapshot
+        .shocks
+        .first()
+        .map_or(25, |shock| shock.pool_coverage_bps.min(BPS_DIVISOR) * 25 / BPS_DIVISOR);
+
+    let surviving_points = snapshot.shocks.last().map_or(25, |shock| {
+        let surviving = snapshot.total_debt.saturating_sub(shock.debt_at_risk);
+        (surviving as u128 * 25 / snapshot.total_debt as u128) as u64
+    });
+
+    (tcr_points + coverage_points + surviving_points).min(100) as u8
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// ALERTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// How urgently an alert needs attention
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum AlertSeverity {
+    /// Worth knowing about
+    Info,
+    /// Needs attention soon
+    Warning,
+    /// Needs attention now
+    Critical,
+    /// Protocol integrity at stake; the protocol has paused itself
+    Emergency,
+}
+
+/// Condition an alert tracks; at most one alert per kind is raised at a time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlertKind {
+    /// Total collateral ratio below the critical ratio
+    RecoveryMode,
+    /// Health score below the configured threshold
+    LowHealth,
+    /// CDPs can be liquidated right now
+    LiquidatableCdps,
+    /// Stability pool cannot absorb the mildest price shock
+    ThinStabilityPool,
+    /// A protocol invariant failed in a recent block
+    InvariantViolation,
+    /// An oracle operator was slashed for signing contradictory prices
+    OracleMisbehavior,
+    /// Anomalies paused an operation through its circuit breaker
+    CircuitBreakerTripped,
+    /// Minting, signer or failed signature activity spiked
+    UnusualTransactionVolume,
+    /// Collateral withdrawals spiked
+    UnusualWithdrawalPattern,
+}
+
+/// A raised alert
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Alert {
+    /// Identifier used to acknowledge the alert
+    pub id: u64,
+    /// Condition the alert tracks
+    pub kind: AlertKind,
+    /// Urgency
+    pub severity: AlertSeverity,
+    /// Description of the latest observation
+    pub message: String,
+    /// Block the alert was first raised at
+    pub raised_at: u64,
+    /// Whether an operator has acknowledged the alert
+    pub acknowledged: bool,
+}
+
+/// Thresholds for raising alerts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlertThresholds {
+    /// Health score below which `LowHealth` is raised
+    pub min_health_score: u8,
+    /// Pool coverage of the mildest shock below which `ThinStabilityPool`
+    /// is raised, in basis points
+    pub min_pool_coverage_bps: u64,
+}
+
+impl Default for AlertThresholds {
+    fn default() -> Self {
+        Self {
+            min_health_score: 50,
+            min_pool_coverage_bps: BPS_DIVISOR,
+        }
+    }
+}
+
+/// Alerts raised from successive dashboard feeds
+#[derive(Debug, Clone, Default)]
+pub struct AlertBook {
+    /// Raised alerts, oldest first
+    alerts: Vec<Alert>,
+    /// Identifier of the next alert
+    next_id: u64,
+    /// Thresholds for raising alerts
+    thresholds: AlertThresholds,
+}
+
+impl AlertBook {
+    /// Create an alert book with default thresholds
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use custom thresholds
+    pub fn with_thresholds(mut self, thresholds: AlertThresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Raise, refresh or clear alerts from a feed
+    ///
+    /// An alert whose condition still holds keeps its identifier and
+    /// acknowledgement; one whose condition cleared is dropped.
+    pub fn update(&mut self, feed: &DashboardFeed) {
+        let snapshot = &feed.snapshot;
+        let mut firing = Vec::new();
+
+        if feed.tcr < feed.critical_ratio {
+            firing.push((
+                AlertKind::RecoveryMode,
+                AlertSeverity::Critical,
+                format!("TCR {}% is below the critical ratio of {}%", feed.tcr, feed.critical_ratio),
+            ));
+        }
+        if feed.health_score < self.thresholds.min_health_score {
+            firing.push((
+                AlertKind::LowHealth,
+                AlertSeverity::Critical,
+                format!("Health score {} is below {}", feed.health_score, self.thresholds.min_health_score),
+            ));
+        }
+        let liquidatable = feed.at_risk.iter().filter(|cdp| cdp.liquidatable).count();
+        if liquidatable > 0 {
+            firing.push((
+                AlertKind::LiquidatableCdps,
+                AlertSeverity::Warning,
+                format!("{} CDP(s) can be liquidated", liquidatable),
+            ));
+        }
+        if let Some(shock) = snapshot.shocks.first() {
+            if shock.pool_coverage_bps < self.thresholds.min_pool_coverage_bps {
+                firing.push((
+                    AlertKind::ThinStabilityPool,
+                    AlertSeverity::Warning,
+                    format!(
+                        "Stability pool covers {}.{:02}% of debt at risk after a {}% drop",
+                        shock.pool_coverage_bps / 100,
+                        shock.pool_coverage_bps % 100,
+                        shock.price_drop_bps / 100
+                    ),
+                ));
+            }
+        }
+
+        let mut violated: Vec<&str> = feed
+            .recent_events
+            .iter()
+            .filter_map(|event| match event {
+                ProtocolEvent::InvariantViolated(e) => Some(e.invariant.as_str()),
+                _ => None,
+            })
+            .collect();
+        violated.sort_unstable();
+        violated.dedup();
+        if !violated.is_empty() {
+            firing.push((
+                AlertKind::InvariantViolation,
+                AlertSeverity::Emergency,
+                format!("Invariants violated: {}", violated.join(", ")),
+            ));
+        }
+
+        let slashed: Vec<String> = feed
+            .recent_events
+            .iter()
+            .filter_map(|event| match event {
+                ProtocolEvent::OracleSlashed(e) => Some(e.operator.to_hex()),
+                _ => None,
+            })
+            .collect();
+        if !slashed.is_empty() {
+            firing.push((
+                AlertKind::OracleMisbehavior,
+                AlertSeverity::Critical,
+                format!("Oracle operators slashed for equivocating: {}", slashed.join(", ")),
+            ));
+        }
+
+        let mut tripped: Vec<String> = feed
+            .recent_events
+            .iter()
+            .filter_map(|event| match event {
+                ProtocolEvent::CircuitBreakerTripped(e) => Some(e.operation.to_string()),
+                _ => None,
+            })
+            .collect();
+        tripped.sort_unstable();
+        tripped.dedup();
+        if !tripped.is_empty() {
+            firing.push((
+                AlertKind::CircuitBreakerTripped,
+                AlertSeverity::Warning,
+                format!("Circuit breakers paused: {}", tripped.join(", ")),
+            ));
+        }
+
+        let (withdrawals, volume): (Vec<&AnomalyDetectedEvent>, Vec<&AnomalyDetectedEvent>) = feed
+            .recent_events
+            .iter()
+            .filter_map(|event| match event {
+                ProtocolEvent::AnomalyDetected(e) => Some(e),
+                _ => None,
+            })
+            .partition(|e| e.metric == AnomalyMetric::WithdrawalVolume);
+        for (kind, anomalies) in [
+            (AlertKind::UnusualTransactionVolume, volume),
+            (AlertKind::UnusualWithdrawalPattern, withdrawals),
+        ] {
+            // Newest first, so this is the latest spike of each metric
+            let mut described: Vec<String> = Vec::new();
+            let mut seen: Vec<AnomalyMetric> = Vec::new();
+            for e in anomalies {
+                if !seen.contains(&e.metric) {
+                    seen.push(e.metric);
+                    described.push(format!(
+                        "{} {} vs mean {} ({}.{:02} sigma, block {})",
+                        e.metric,
+                        e.value,
+                        e.mean,
+                        e.z_score_bps / BPS_DIVISOR,
+                        e.z_score_bps % BPS_DIVISOR / 100,
+                        e.block_height
+                    ));
+                }
+            }
+            if !described.is_empty() {
+                firing.push((kind, AlertSeverity::Warning, format!("Unusual activity: {}", described.join("; "))));
+            }
+        }
+
+        self.alerts.retain(|alert| firing.iter().any(|(kind, _, _)| *kind == alert.kind));
+        for (kind, severity, message) in firing {
+            match self.alerts.iter_mut().find(|alert| alert.kind == kind) {
+                Some(alert) => {
+                    alert.severity = severity;
+                    alert.message = message;
+                }
+                None => {
+                    self.alerts.push(Alert {
+                        id: self.next_id,
+                        kind,
+                        severity,
+                        message,
+                        raised_at: snapshot.block_height,
+                        acknowledged: false,
+                    });
+                    self.next_id += 1;
+                }
+            }
+        }
+    }
+
+    /// Acknowledge an alert, returning whether it was raised
+    pub fn acknowledge(&mut self, id: u64) -> bool {
+        match self.alerts.iter_mut().find(|alert| alert.id == id) {
+            Some(alert) => {
+                alert.acknowledged = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Acknowledge every raised alert
+    pub fn acknowledge_all(&mut self) {
+        for alert in &mut self.alerts {
+            alert.acknowledged = true;
+        }
+    }
+
+    /// Raised alerts, oldest first
+    pub fn alerts(&self) -> &[Alert] {
+        &self.alerts
+    }
+
+    /// Raised alerts nobody has acknowledged yet
+    pub fn unacknowledged(&self) -> usize {
+        self.alerts.iter().filter(|alert| !alert.acknowledged).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::cdp::CDP;
+    use crate::utils::constants::SATS_PER_BTC;
+    use crate::utils::crypto::KeyPair;
+
+    fn manager(positions: &[(u64, u64)]) -> CDPManager {
+        let mut manager = CDPManager::new();
+        for (nonce, (collateral_sats, debt_cents)) in positions.iter().enumerate() {
+            let mut cdp = CDP::new(*KeyPair::generate().public_key(), nonce as u64, 0);
+            cdp.collateral_sats = *collateral_sats;
+            cdp.debt_cents = *debt_cents;
+            manager.register(cdp).unwrap();
+        }
+        manager
+    }
+
+    #[test]
+    fn test_feed_and_health() {
+        let empty = DashboardFeed::collect(&CDPManager::new(), 10_000_000, 110, 150, TokenAmount::ZERO, 1, Vec::new());
+        assert_eq!(empty.health_score, 100);
+        assert_eq!(empty.tcr, u64::MAX);
+
+        // At $100,000: 105% and 125%, with a 400% CDP to keep TCR healthy
+        let cdps = manager(&[
+            (SATS_PER_BTC, 9_523_809),
+            (SATS_PER_BTC, 8_000_000),
+            (10 * SATS_PER_BTC, 25_000_000),
+        ]);
+        let feed = DashboardFeed::collect(&cdps, 10_000_000, 110, 150, TokenAmount::ZERO, 1, Vec::new());
+
+        assert_eq!(feed.at_risk.len(), 2);
+        assert!(feed.at_risk[0].liquidatable);
+        assert!(!feed.at_risk[1].liquidatable);
+        assert!(feed.has_liquidatable());
+        assert_eq!(feed.tcr, 282);
+        // Full TCR points (50), an empty pool (0) and the two weakest CDPs
+        // failing the 30% shock (14)
+        assert_eq!(feed.health_score, 64);
+    }
+
+    #[test]
+    fn test_alert_lifecycle() {
+        let cdps = manager(&[(SATS_PER_BTC, 9_523_809)]);
+        let feed = DashboardFeed::collect(&cdps, 10_000_000, 110, 150, TokenAmount::ZERO, 5, Vec::new());
+
+        let mut book = AlertBook::new();
+        book.update(&feed);
+        let kinds: Vec<_> = book.alerts().iter().map(|alert| alert.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![AlertKind::RecoveryMode, AlertKind::LowHealth, AlertKind::LiquidatableCdps, AlertKind::ThinStabilityPool]
+        );
+        assert_eq!(book.unacknowledged(), 4);
+        assert!(book.alerts().iter().all(|alert| alert.raised_at == 5));
+
+        assert!(book.acknowledge(2));
+        assert!(!book.acknowledge(99));
+        assert_eq!(book.unacknowledged(), 3);
+
+        // Still firing: identifiers and acknowledgements survive a refresh
+        book.update(&feed);
+        assert_eq!(book.alerts()[2].id, 2);
+        assert!(book.alerts()[2].acknowledged);
+
+        // Conditions clear once the CDP is gone
+        let healthy = DashboardFeed::collect(&CDPManager::new(), 10_000_000, 110, 150, TokenAmount::ZERO, 6, Vec::new());
+        book.update(&healthy);
+        assert!(book.alerts().is_empty());
+
+        book.update(&feed);
+        book.acknowledge_all();
+        assert_eq!(book.unacknowledged(), 0);
+        assert_eq!(book.alerts()[0].id, 4);
+    }
+
+    #[test]
+    fn test_invariant_alert() {
+        use crate::protocol::events::InvariantViolatedEvent;
+
+        let event = ProtocolEvent::InvariantViolated(InvariantViolatedEvent {
+            invariant: "vault_matches_collateral".to_string(),
+            detail: "Vault holds 0 sats, CDPs hold 1".to_string(),
+            paused: true,
+            block_height: 3,
+            timestamp: 300,
+        });
+        let feed = DashboardFeed::collect(&CDPManager::new(), 10_000_000, 110, 150, TokenAmount::ZERO, 3, vec![event]);
+
+        let mut book = AlertBook::new();
+        book.update(&feed);
+        assert_eq!(book.alerts().len(), 1);
+        assert_eq!(book.alerts()[0].kind, AlertKind::InvariantViolation);
+        assert_eq!(book.alerts()[0].severity, AlertSeverity::Emergency);
+    }
+
+    #[test]
+    fn test_anomaly_alerts() {
+        let anomaly = |metric: AnomalyMetric, value: u64, block_height: u64| {
+            ProtocolEvent::AnomalyDetected(AnomalyDetectedEvent {
+                metric,
+                value,
+                mean: 100,
+                z_score_bps: 52_500,
+                block_height,
+                timestamp: block_height * 600,
+            })
+        };
+        let events = vec![
+            anomaly(AnomalyMetric::MintVolume, 900, 2),
+            anomaly(AnomalyMetric::MintVolume, 1_000, 3),
+            anomaly(AnomalyMetric::WithdrawalVolume, 5_000, 3),
+        ];
+        let feed = DashboardFeed::collect(&CDPManager::new(), 10_000_000, 110, 150, TokenAmount::ZERO, 3, events);
+
+        let mut book = AlertBook::new();
+        book.update(&feed);
+        let kinds: Vec<_> = book.alerts().iter().map(|alert| alert.kind).collect();
+        assert_eq!(kinds, [AlertKind::UnusualTransactionVolume, AlertKind::UnusualWithdrawalPattern]);
+        assert_eq!(
+            book.alerts()[0].message,
+            "Unusual activity: mint volume 1000 vs mean 100 (5.25 sigma, block 3)"
+        );
+    }
+}
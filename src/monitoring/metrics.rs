@@ -0,0 +1,157 @@
+//! Protocol metrics.
+//!
+//! The state machine feeds a `MetricsCollector` with operation outcomes as
+//! they happen and samples system-wide gauges at the end of every block.
+//! Collectors are cheap handles onto shared state, so exporters can keep a
+//! clone and read the latest figures from another thread.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+/// Outcomes of one operation type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationCounts {
+    /// Operations that were applied
+    pub applied: u64,
+    /// Operations that were rejected
+    pub rejected: u64,
+}
+
+/// Latest protocol metrics
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolMetrics {
+    /// Block the gauges were sampled at
+    pub block_height: u64,
+    /// BTC price in cents
+    pub btc_price: u64,
+    /// zkUSD supply in cents
+    pub total_supply: u64,
+    /// Collateral locked in satoshis
+    pub total_collateral: u64,
+    /// Open CDPs
+    pub active_cdps: u64,
+    /// Stability pool deposits in cents
+    pub pool_deposits: u64,
+    /// Total collateral ratio in percent (`u64::MAX` without debt)
+    pub tcr: u64,
+    /// Whether the protocol is paused
+    pub paused: bool,
+    /// Whether the protocol is in recovery mode
+    pub recovery_mode: bool,
+    /// Annualized realized BTC volatility in basis points (zero until estimated)
+    #[serde(default)]
+    pub btc_volatility_bps: u64,
+    /// Operation outcomes by operation type since startup
+    pub operations: BTreeMap<String, OperationCounts>,
+}
+
+/// Gauges sampled at the end of a block
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlockGauges {
+    /// Block height
+    pub block_height: u64,
+    /// BTC price in cents
+    pub btc_price: u64,
+    /// zkUSD supply in cents
+    pub total_supply: u64,
+    /// Collateral locked in satoshis
+    pub total_collateral: u64,
+    /// Open CDPs
+    pub active_cdps: u64,
+    /// Stability pool deposits in cents
+    pub pool_deposits: u64,
+    /// Total collateral ratio in percent
+    pub tcr: u64,
+    /// Whether the protocol is paused
+    pub paused: bool,
+    /// Whether the protocol is in recovery mode
+    pub recovery_mode: bool,
+    /// Annualized realized BTC volatility in basis points
+    pub btc_volatility_bps: u64,
+}
+
+/// Shared handle collecting protocol metrics
+#[derive(Debug, Clone, Default)]
+pub struct MetricsCollector {
+    inner: Arc<Mutex<ProtocolMetrics>>,
+}
+
+impl MetricsCollector {
+    /// Create an empty collector
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count an operation outcome
+    pub fn record_operation(&self, op_type: &str, applied: bool) {
+        self.update(|metrics| {
+            let counts = metrics.operations.entry(op_type.to_string()).or_default();
+            if applied {
+                counts.applied += 1;
+            } else {
+                counts.rejected += 1;
+            }
+        });
+    }
+
+    /// Replace the gauges with a new sample
+    pub fn observe_block(&self, gauges: BlockGauges) {
+        self.update(|metrics| {
+            metrics.block_height = gauges.block_height;
+            metrics.btc_price = gauges.btc_price;
+            metrics.total_supply = gauges.total_supply;
+            metrics.total_collateral = gauges.total_collateral;
+            metrics.active_cdps = gauges.active_cdps;
+            metrics.pool_deposits = gauges.pool_deposits;
+            metrics.tcr = gauges.tcr;
+            metrics.paused = gauges.paused;
+            metrics.recovery_mode = gauges.recovery_mode;
+            metrics.btc_volatility_bps = gauges.btc_volatility_bps;
+        });
+    }
+
+    /// Copy of the latest metrics
+    pub fn snapshot(&self) -> ProtocolMetrics {
+        match self.inner.lock() {
+            Ok(metrics) => metrics.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    fn update(&self, apply: impl FnOnce(&mut ProtocolMetrics)) {
+        // A panic elsewhere must not stop metrics collection
+        let mut metrics = match self.inner.lock() {
+            Ok(metrics) => metrics,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        apply(&mut metrics);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collector_shares_state() {
+        let collector = MetricsCollector::new();
+        let exporter = collector.clone();
+
+        collector.record_operation("MintDebt", true);
+        collector.record_operation("MintDebt", false);
+        collector.record_operation("OpenCDP", true);
+        collector.observe_block(BlockGauges {
+            block_height: 7,
+            tcr: 180,
+            ..Default::default()
+        });
+
+        let metrics = exporter.snapshot();
+        assert_eq!(metrics.block_height, 7);
+        assert_eq!(metrics.tcr, 180);
+        assert_eq!(metrics.operations["MintDebt"], OperationCounts { applied: 1, rejected: 1 });
+        assert_eq!(metrics.operations["OpenCDP"].applied, 1);
+    }
+}
@@ -0,0 +1,25 @@
+//! Monitoring module - System-wide risk aggregation.
+//!
+//! This module periodically aggregates protocol state into snapshots for
+//! dashboards, raises alerts from them, collects metrics for exporters,
+//! accrues daily and per-epoch protocol statistics, estimates realized BTC
+//! volatility, flags unusual operation patterns, and watches owned positions
+//! for approaching liquidation.
+
+pub mod aggregates;
+pub mod anomaly;
+pub mod dashboard;
+pub mod metrics;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod snapshot;
+pub mod volatility;
+pub mod watch;
+
+pub use aggregates::*;
+pub use anomaly::*;
+pub use dashboard::*;
+pub use metrics::*;
+pub use snapshot::*;
+pub use volatility::*;
+pub use watch::*;
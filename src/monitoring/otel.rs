@@ -0,0 +1,223 @@
+//! OpenTelemetry export (requires the `otel` feature).
+//!
+//! Ships tracing spans (state machine operations, oracle rounds, RPC
+//! requests) and `MetricsCollector` figures over OTLP/gRPC, so they can be
+//! collected by Jaeger, Tempo or any Prometheus-compatible backend behind
+//! an OpenTelemetry collector.
+//!
+//! Must be called from within a Tokio runtime; the batch exporters run on it.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use zkusd::monitoring::otel::{self, OtelConfig};
+//! use zkusd::utils::logging::LogFormat;
+//!
+//! let mut guard = otel::init(&OtelConfig::new("http://localhost:4317"), LogFormat::Text, tracing::Level::INFO)?;
+//! guard.export_metrics(machine.metrics())?;
+//! ```
+
+use std::time::Duration;
+
+use opentelemetry::metrics::{Meter, MeterProvider as _, ObservableCounter, ObservableGauge};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use opentelemetry_sdk::{runtime, Resource};
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use crate::error::{Error, Result};
+use crate::monitoring::{MetricsCollector, ProtocolMetrics};
+use crate::utils::logging::{self, LogFormat};
+
+/// Default OTLP/gRPC collector endpoint
+pub const DEFAULT_OTLP_ENDPOINT: &str = "http://localhost:4317";
+
+/// Default service name reported to the backend
+pub const DEFAULT_SERVICE_NAME: &str = "zkusd";
+
+/// Default metrics export interval in seconds
+pub const DEFAULT_METRICS_INTERVAL_SECS: u64 = 15;
+
+/// OTLP exporter settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OtelConfig {
+    /// OTLP/gRPC collector endpoint
+    pub endpoint: String,
+    /// `service.name` resource attribute
+    pub service_name: String,
+    /// How often metrics are pushed
+    pub metrics_interval_secs: u64,
+}
+
+impl OtelConfig {
+    /// Export to `endpoint` with default settings
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the service name
+    pub fn with_service_name(mut self, service_name: impl Into<String>) -> Self {
+        self.service_name = service_name.into();
+        self
+    }
+
+    /// Set the metrics export interval
+    pub fn with_metrics_interval(mut self, secs: u64) -> Self {
+        self.metrics_interval_secs = secs;
+        self
+    }
+
+    fn resource(&self) -> Resource {
+        Resource::new(vec![
+            KeyValue::new("service.name", self.service_name.clone()),
+            KeyValue::new("service.version", crate::VERSION),
+        ])
+    }
+}
+
+impl Default for OtelConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: DEFAULT_OTLP_ENDPOINT.to_string(),
+            service_name: DEFAULT_SERVICE_NAME.to_string(),
+            metrics_interval_secs: DEFAULT_METRICS_INTERVAL_SECS,
+        }
+    }
+}
+
+/// Keeps the exporters alive; flushes and shuts them down on drop
+pub struct OtelGuard {
+    meter_provider: SdkMeterProvider,
+    meter: Meter,
+    gauges: Vec<ObservableGauge<u64>>,
+    counters: Vec<ObservableCounter<u64>>,
+}
+
+impl OtelGuard {
+    /// Export the figures of `collector` on every metrics interval
+    pub fn export_metrics(&mut self, collector: MetricsCollector) -> Result<()> {
+        let gauges: [(&'static str, &'static str, fn(&ProtocolMetrics) -> u64); 10] = [
+            ("zkusd.block_height", "Block the gauges were sampled at", |m| m.block_height),
+            ("zkusd.btc_price", "BTC price in cents", |m| m.btc_price),
+            ("zkusd.total_supply", "zkUSD supply in cents", |m| m.total_supply),
+            ("zkusd.total_collateral", "Collateral locked in satoshis", |m| m.total_collateral),
+            ("zkusd.active_cdps", "Open CDPs", |m| m.active_cdps),
+            ("zkusd.pool_deposits", "Stability pool deposits in cents", |m| m.pool_deposits),
+            ("zkusd.tcr", "Total collateral ratio in percent", |m| m.tcr),
+            ("zkusd.btc_volatility", "Annualized BTC volatility in basis points", |m| m.btc_volatility_bps),
+            ("zkusd.paused", "1 while the protocol is paused", |m| m.paused as u64),
+            ("zkusd.recovery_mode", "1 while in recovery mode", |m| m.recovery_mode as u64),
+        ];
+
+        for (name, description, read) in gauges {
+            let collector = collector.clone();
+            let gauge = self
+                .meter
+                .u64_observable_gauge(name)
+                .with_description(description)
+                .with_callback(move |observer| observer.observe(read(&collector.snapshot()), &[]))
+                .try_init()
+                .map_err(|e| Error::Internal(format!("Failed to register {}: {}", name, e)))?;
+            self.gauges.push(gauge);
+        }
+
+        let operations = self
+            .meter
+            .u64_observable_counter("zkusd.operations")
+            .with_description("Protocol operations by type and outcome")
+            .with_callback(move |observer| {
+                for (op, counts) in collector.snapshot().operations {
+                    for (outcome, count) in [("applied", counts.applied), ("rejected", counts.rejected)] {
+                        observer.observe(
+                            count,
+                            &[KeyValue::new("operation", op.clone()), KeyValue::new("outcome", outcome)],
+                        );
+                    }
+                }
+            })
+            .try_init()
+            .map_err(|e| Error::Internal(format!("Failed to register zkusd.operations: {}", e)))?;
+        self.counters.push(operations);
+
+        Ok(())
+    }
+}
+
+impl Drop for OtelGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.meter_provider.shutdown() {
+            tracing::warn!(error = %e, "Failed to shut down metrics exporter");
+        }
+        opentelemetry::global::shutdown_tracer_provider();
+    }
+}
+
+/// Install the global subscriber with log output plus OTLP span export,
+/// and start the metrics pipeline
+pub fn init(config: &OtelConfig, format: LogFormat, default_level: tracing::Level) -> Result<OtelGuard> {
+    let tracer_provider = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(config.endpoint.clone()),
+        )
+        .with_trace_config(opentelemetry_sdk::trace::Config::default().with_resource(config.resource()))
+        .install_batch(runtime::Tokio)
+        .map_err(|e| Error::Internal(format!("Failed to start span exporter: {}", e)))?;
+    let tracer = tracer_provider.tracer(config.service_name.clone());
+    opentelemetry::global::set_tracer_provider(tracer_provider);
+
+    let meter_provider = opentelemetry_otlp::new_pipeline()
+        .metrics(runtime::Tokio)
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(config.endpoint.clone()),
+        )
+        .with_resource(config.resource())
+        .with_period(Duration::from_secs(config.metrics_interval_secs.max(1)))
+        .build()
+        .map_err(|e| Error::Internal(format!("Failed to start metrics exporter: {}", e)))?;
+    let meter = meter_provider.meter(config.service_name.clone());
+
+    tracing_subscriber::registry()
+        .with(logging::env_filter(default_level))
+        .with(logging::fmt_layer(format))
+        .with(tracing_opentelemetry::layer().with_tracer(tracer))
+        .try_init()
+        .map_err(|e| Error::Internal(format!("Failed to install subscriber: {}", e)))?;
+
+    tracing::info!(endpoint = %config.endpoint, service = %config.service_name, "OTLP export enabled");
+
+    Ok(OtelGuard {
+        meter_provider,
+        meter,
+        gauges: Vec::new(),
+        counters: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_otel_config_defaults() {
+        let config = OtelConfig::new("http://tempo:4317")
+            .with_service_name("zkusd-server")
+            .with_metrics_interval(30);
+
+        assert_eq!(config.endpoint, "http://tempo:4317");
+        assert_eq!(config.service_name, "zkusd-server");
+        assert_eq!(config.metrics_interval_secs, 30);
+        assert_eq!(OtelConfig::default().endpoint, DEFAULT_OTLP_ENDPOINT);
+    }
+}
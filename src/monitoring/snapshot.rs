@@ -0,0 +1,320 @@
+//! System-wide risk snapshots.
+//!
+//! A `RiskSnapshot` aggregates every open CDP into the figures a risk
+//! dashboard needs: how ratios are distributed, how concentrated debt is, how
+//! much collateral a price drop would put at risk, and whether the stability
+//! pool could absorb it. Given a volatility estimate, it also reports the
+//! minimum ratio buffered against realized volatility.
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::cdp::CDPManager;
+use crate::core::token::TokenAmount;
+use crate::monitoring::volatility::VolatilityEstimator;
+use crate::utils::constants::BPS_DIVISOR;
+
+/// Upper bounds of the ratio histogram buckets in percent; a final bucket
+/// holds everything above the last bound
+pub const RATIO_BUCKETS: [u64; 7] = [110, 125, 150, 175, 200, 250, 300];
+
+/// Price drops the snapshot is stressed against (10%, 20%, 30%)
+pub const PRICE_SHOCKS_BPS: [u64; 3] = [1_000, 2_000, 3_000];
+
+/// Number of largest CDPs counted towards debt concentration
+pub const TOP_CDPS: usize = 10;
+
+/// Blocks between snapshots by default (about an hour)
+pub const DEFAULT_SNAPSHOT_INTERVAL_BLOCKS: u64 = 6;
+
+/// Horizon of the volatility buffer on the minimum ratio (a day)
+pub const BUFFER_HORIZON_SECS: u64 = 24 * 3600;
+
+/// Standard deviations of the volatility buffer in basis points (3 sigma)
+pub const BUFFER_SIGMAS_BPS: u64 = 30_000;
+
+/// CDPs whose ratio falls in one histogram bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RatioBucket {
+    /// Upper bound of the bucket in percent (exclusive), `None` for the last
+    pub upper_ratio: Option<u64>,
+    /// CDPs in the bucket
+    pub cdps: u64,
+    /// Debt held by those CDPs in cents
+    pub debt_cents: u64,
+}
+
+/// Exposure to a single price drop
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShockExposure {
+    /// Price drop in basis points
+    pub price_drop_bps: u64,
+    /// BTC price after the drop in cents
+    pub btc_price: u64,
+    /// CDPs that would be liquidatable
+    pub cdps_at_risk: u64,
+    /// Debt of those CDPs in cents
+    pub debt_at_risk: u64,
+    /// Collateral of those CDPs in satoshis
+    pub collateral_at_risk: u64,
+    /// Stability pool deposits over debt at risk in basis points
+    /// (`u64::MAX` when nothing is at risk)
+    pub pool_coverage_bps: u64,
+}
+
+/// Aggregated system risk at one block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RiskSnapshot {
+    /// Block the snapshot was taken at
+    pub block_height: u64,
+    /// BTC price in cents
+    pub btc_price: u64,
+    /// Minimum collateral ratio in percent
+    pub min_ratio: u64,
+    /// Open CDPs with debt
+    pub cdp_count: u64,
+    /// Debt of those CDPs in cents
+    pub total_debt: u64,
+    /// Collateral of those CDPs in satoshis
+    pub total_collateral: u64,
+    /// CDPs bucketed by collateral ratio, lowest first
+    pub ratio_distribution: Vec<RatioBucket>,
+    /// Share of debt held by the `TOP_CDPS` largest CDPs in basis points
+    pub top_debt_share_bps: u64,
+    /// Exposure to each price drop in `PRICE_SHOCKS_BPS`
+    pub shocks: Vec<ShockExposure>,
+    /// Stability pool deposits in cents
+    pub pool_deposits: u64,
+    /// Stability pool deposits over total debt in basis points
+    /// (`u64::MAX` without debt)
+    pub pool_coverage_bps: u64,
+    /// Annualized realized BTC volatility in basis points, once estimated
+    #[serde(default)]
+    pub volatility_bps: Option<u64>,
+    /// Collateral ratio in percent surviving a `BUFFER_SIGMAS_BPS` move over
+    /// `BUFFER_HORIZON_SECS` at realized volatility, once estimated
+    #[serde(default)]
+    pub buffered_min_ratio: Option<u64>,
+}
+
+impl RiskSnapshot {
+    /// Aggregate all open CDPs with debt
+    pub fn compute(
+        cdps: &CDPManager,
+        btc_price_cents: u64,
+        min_ratio: u64,
+        pool_deposits: TokenAmount,
+        block_height: u64,
+    ) -> Self {
+        let positions = cdps.get_sorted_by_ratio(btc_price_cents);
+        let pool_deposits = pool_deposits.cents();
+
+        let mut ratio_distribution: Vec<RatioBucket> = RATIO_BUCKETS
+            .iter()
+            .map(|bound| Some(*bound))
+            .chain(std::iter::once(None))
+            .map(|upper_ratio| RatioBucket { upper_ratio, cdps: 0, debt_cents: 0 })
+            .collect();
+
+        let mut total_debt = 0u64;
+        let mut total_collateral = 0u64;
+        for (cdp, ratio) in &positions {
+            total_debt = total_debt.saturating_add(cdp.debt_cents);
+            total_collateral = total_collateral.saturating_add(cdp.collateral_sats);
+
+            let index = RATIO_BUCKETS.iter().position(|bound| ratio < bound).unwrap_or(RATIO_BUCKETS.len());
+            let bucket = &mut ratio_distribution[index];
+            bucket.cdps += 1;
+            bucket.debt_cents = bucket.debt_cents.saturating_add(cdp.debt_cents);
+        }
+
+        let mut debts: Vec<u64> = positions.iter().map(|(cdp, _)| cdp.debt_cents).collect();
+        debts.sort_unstable_by(|a, b| b.cmp(a));
+        let top_debt = debts.iter().take(TOP_CDPS).fold(0u64, |sum, debt| sum.saturating_add(*debt));
+
+        let shocks = PRICE_SHOCKS_BPS
+            .iter()
+            .map(|drop| {
+                let price = (btc_price_cents as u128 * (BPS_DIVISOR - drop) as u128 / BPS_DIVISOR as u128) as u64;
+                let mut exposure = ShockExposure {
+                    price_drop_bps: *drop,
+                    btc_price: price,
+                    cdps_at_risk: 0,
+                    debt_at_risk: 0,
+                    collateral_at_risk: 0,
+                    pool_coverage_bps: 0,
+                };
+                for (cdp, _) in positions.iter().filter(|(cdp, _)| cdp.is_liquidatable(price, min_ratio)) {
+                    exposure.cdps_at_risk += 1;
+                    exposure.debt_at_risk = exposure.debt_at_risk.saturating_add(cdp.debt_cents);
+                    exposure.collateral_at_risk = exposure.collateral_at_risk.saturating_add(cdp.collateral_sats);
+                }
+                exposure.pool_coverage_bps = coverage_bps(pool_deposits, exposure.debt_at_risk);
+                exposure
+            })
+            .collect();
+
+        Self {
+            block_height,
+            btc_price: btc_price_cents,
+            min_ratio,
+            cdp_count: positions.len() as u64,
+            total_debt,
+            total_collateral,
+            ratio_distribution,
+            top_debt_share_bps: share_bps(top_debt, total_debt),
+            shocks,
+            pool_deposits,
+            pool_coverage_bps: coverage_bps(pool_deposits, total_debt),
+            volatility_bps: None,
+            buffered_min_ratio: None,
+        }
+    }
+
+    /// Add the realized volatility and the minimum ratio buffered against it
+    pub fn with_volatility(mut self, estimator: &VolatilityEstimator) -> Self {
+        self.volatility_bps = estimator.volatility_bps();
+        self.buffered_min_ratio = self
+            .volatility_bps
+            .map(|_| estimator.buffered_ratio(self.min_ratio, BUFFER_HORIZON_SECS, BUFFER_SIGMAS_BPS));
+        self
+    }
+}
+
+/// `part` over `whole` in basis points, zero when `whole` is zero
+fn share_bps(part: u64, whole: u64) -> u64 {
+    if whole == 0 {
+        return 0;
+    }
+    (part as u128 * BPS_DIVISOR as u128 / whole as u128).min(u64::MAX as u128) as u64
+}
+
+/// Pool deposits over debt in basis points, `u64::MAX` when there is no debt
+fn coverage_bps(pool_deposits: u64, debt: u64) -> u64 {
+    if debt == 0 {
+        return u64::MAX;
+    }
+    share_bps(pool_deposits, debt)
+}
+
+/// Periodic job producing risk snapshots
+#[derive(Debug, Clone)]
+pub struct RiskMonitor {
+    /// Blocks between snapshots
+    interval_blocks: u64,
+    /// Most recent snapshot, if any
+    latest: Option<RiskSnapshot>,
+}
+
+impl Default for RiskMonitor {
+    fn default() -> Self {
+        Self::new(DEFAULT_SNAPSHOT_INTERVAL_BLOCKS)
+    }
+}
+
+impl RiskMonitor {
+    /// Create a monitor taking a snapshot every `interval_blocks` (at least 1)
+    pub fn new(interval_blocks: u64) -> Self {
+        Self {
+            interval_blocks: interval_blocks.max(1),
+            latest: None,
+        }
+    }
+
+    /// Whether a snapshot is due at `block_height`
+    pub fn is_due(&self, block_height: u64) -> bool {
+        match &self.latest {
+            Some(snapshot) => block_height >= snapshot.block_height.saturating_add(self.interval_blocks),
+            None => true,
+        }
+    }
+
+    /// Store a new snapshot
+    pub fn record(&mut self, snapshot: RiskSnapshot) {
+        self.latest = Some(snapshot);
+    }
+
+    /// Most recent snapshot, if any
+    pub fn latest(&self) -> Option<&RiskSnapshot> {
+        self.latest.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::cdp::CDP;
+    use crate::utils::constants::SATS_PER_BTC;
+    use crate::utils::crypto::KeyPair;
+
+    fn manager(positions: &[(u64, u64)]) -> CDPManager {
+        let mut manager = CDPManager::new();
+        for (nonce, (collateral_sats, debt_cents)) in positions.iter().enumerate() {
+            let mut cdp = CDP::new(*KeyPair::generate().public_key(), nonce as u64, 0);
+            cdp.collateral_sats = *collateral_sats;
+            cdp.debt_cents = *debt_cents;
+            manager.register(cdp).unwrap();
+        }
+        manager
+    }
+
+    #[test]
+    fn test_snapshot_aggregates() {
+        // At $100,000: 120%, 200% and 400%
+        let cdps = manager(&[
+            (SATS_PER_BTC, 8_333_333),
+            (SATS_PER_BTC, 5_000_000),
+            (SATS_PER_BTC, 2_500_000),
+        ]);
+        let snapshot = RiskSnapshot::compute(&cdps, 10_000_000, 110, TokenAmount::from_cents(4_166_666), 7);
+
+        assert_eq!(snapshot.cdp_count, 3);
+        assert_eq!(snapshot.total_debt, 15_833_333);
+        assert_eq!(snapshot.ratio_distribution.len(), RATIO_BUCKETS.len() + 1);
+        assert_eq!(snapshot.ratio_distribution[1].cdps, 1);
+        assert_eq!(snapshot.ratio_distribution[5].cdps, 1);
+        assert_eq!(snapshot.ratio_distribution[7].cdps, 1);
+        assert_eq!(snapshot.ratio_distribution[7].upper_ratio, None);
+        assert_eq!(snapshot.top_debt_share_bps, BPS_DIVISOR);
+
+        // Even -30% leaves the 200% CDP above the minimum
+        assert_eq!(snapshot.shocks[0].cdps_at_risk, 1);
+        assert_eq!(snapshot.shocks[0].debt_at_risk, 8_333_333);
+        assert_eq!(snapshot.shocks[0].collateral_at_risk, SATS_PER_BTC);
+        assert_eq!(snapshot.shocks[0].pool_coverage_bps, 4_999);
+        assert_eq!(snapshot.shocks[2].cdps_at_risk, 1);
+    }
+
+    #[test]
+    fn test_monitor_interval() {
+        let mut monitor = RiskMonitor::new(6);
+        assert!(monitor.latest().is_none());
+        assert!(monitor.is_due(0));
+
+        monitor.record(RiskSnapshot::compute(&CDPManager::new(), 10_000_000, 110, TokenAmount::ZERO, 10));
+        assert!(!monitor.is_due(15));
+        assert!(monitor.is_due(16));
+
+        let snapshot = monitor.latest().unwrap();
+        assert_eq!(snapshot.cdp_count, 0);
+        assert_eq!(snapshot.top_debt_share_bps, 0);
+        assert_eq!(snapshot.pool_coverage_bps, u64::MAX);
+        assert_eq!(snapshot.volatility_bps, None);
+    }
+
+    #[test]
+    fn test_volatility_buffer() {
+        let snapshot = RiskSnapshot::compute(&CDPManager::new(), 10_000_000, 110, TokenAmount::ZERO, 10);
+
+        // Not enough history yet
+        let unestimated = snapshot.clone().with_volatility(&VolatilityEstimator::default());
+        assert_eq!(unestimated.buffered_min_ratio, None);
+
+        // 1% moves every ten minutes
+        let history: Vec<(u64, u64)> =
+            (0..100).map(|i| (i * 600, if i % 2 == 0 { 10_000_000 } else { 10_100_000 })).collect();
+        let estimator = VolatilityEstimator::default().from_history(&history);
+        let snapshot = snapshot.with_volatility(&estimator);
+        assert_eq!(snapshot.volatility_bps, estimator.volatility_bps());
+        assert!(snapshot.buffered_min_ratio.unwrap() > 110);
+    }
+}
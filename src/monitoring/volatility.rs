@@ -0,0 +1,220 @@
+//! Realized BTC volatility.
+//!
+//! A `VolatilityEstimator` keeps an exponentially weighted moving average
+//! (RiskMetrics style) of squared price returns over the settled price
+//! history. Each return is annualized by the time since the previous price,
+//! so irregular update intervals don't skew the estimate. Everything is
+//! integer arithmetic, so every node derives the same figure.
+//!
+//! The estimate feeds risk parameters: a collateral ratio buffered against a
+//! multi-sigma move over a horizon, and fees scaled by how volatile the market
+//! is compared to a reference level.
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::constants::BPS_DIVISOR;
+use crate::utils::math::isqrt;
+
+/// Weight of the previous variance in each update (0.99 per observation,
+/// a half-life of about 69 blocks)
+pub const DEFAULT_VOLATILITY_DECAY_BPS: u64 = 9_900;
+
+/// Observations needed before the estimate is reported
+pub const DEFAULT_VOLATILITY_MIN_SAMPLES: u32 = 10;
+
+/// Price history replayed into the estimator on startup (7 days)
+pub const VOLATILITY_LOOKBACK_SECS: u64 = 7 * 24 * 3600;
+
+/// Seconds in a year, for annualizing
+pub const SECS_PER_YEAR: u64 = 365 * 24 * 3600;
+
+/// Returns are measured in parts per billion
+const RETURN_SCALE: u128 = 1_000_000_000;
+
+/// EWMA estimator of annualized BTC volatility
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VolatilityEstimator {
+    /// Weight of the previous variance in basis points
+    decay_bps: u64,
+    /// Observations needed before the estimate is reported
+    min_samples: u32,
+    /// Timestamp and price of the last observation
+    last: Option<(u64, u64)>,
+    /// Annualized variance of returns in parts per billion squared
+    variance: u128,
+    /// Returns observed so far
+    samples: u32,
+}
+
+impl Default for VolatilityEstimator {
+    fn default() -> Self {
+        Self::new(DEFAULT_VOLATILITY_DECAY_BPS)
+    }
+}
+
+impl VolatilityEstimator {
+    /// Create an estimator weighting the previous variance by `decay_bps`
+    /// (capped at 100%)
+    pub fn new(decay_bps: u64) -> Self {
+        Self {
+            decay_bps: decay_bps.min(BPS_DIVISOR),
+            min_samples: DEFAULT_VOLATILITY_MIN_SAMPLES,
+            last: None,
+            variance: 0,
+            samples: 0,
+        }
+    }
+
+    /// Set the observations needed before the estimate is reported
+    pub fn with_min_samples(mut self, min_samples: u32) -> Self {
+        self.min_samples = min_samples;
+        self
+    }
+
+    /// Replay `(timestamp, price)` history, oldest first
+    pub fn from_history(mut self, history: &[(u64, u64)]) -> Self {
+        for (timestamp, price_cents) in history {
+            self.observe(*timestamp, *price_cents);
+        }
+        self
+    }
+
+    /// Fold a settled price into the estimate.
+    ///
+    /// Zero prices and prices not newer than the last one are ignored.
+    pub fn observe(&mut self, timestamp: u64, price_cents: u64) {
+        if price_cents == 0 {
+            return;
+        }
+        let Some((last_timestamp, last_price)) = self.last else {
+            self.last = Some((timestamp, price_cents));
+            return;
+        };
+        if timestamp <= last_timestamp {
+            return;
+        }
+        self.last = Some((timestamp, price_cents));
+
+        // A move of more than 100% counts as 100%
+        let diff = price_cents.abs_diff(last_price) as u128;
+        let ret = (diff * RETURN_SCALE / last_price as u128).min(RETURN_SCALE);
+        let sample = ret * ret * SECS_PER_YEAR as u128 / (timestamp - last_timestamp) as u128;
+
+        self.variance = if self.samples == 0 {
+            sample
+        } else {
+            let decay = self.decay_bps as u128;
+            (decay * self.variance + (BPS_DIVISOR as u128 - decay) * sample) / BPS_DIVISOR as u128
+        };
+        self.samples = self.samples.saturating_add(1);
+    }
+
+    /// Returns observed so far
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// Annualized volatility in basis points, once enough returns were seen
+    pub fn volatility_bps(&self) -> Option<u64> {
+        if self.samples < self.min_samples.max(1) {
+            return None;
+        }
+        Some(self.scaled_volatility_bps(self.variance))
+    }
+
+    /// Volatility over `horizon_secs` in basis points (one standard deviation
+    /// of the price move over the horizon)
+    pub fn horizon_volatility_bps(&self, horizon_secs: u64) -> Option<u64> {
+        self.volatility_bps()?;
+        Some(self.scaled_volatility_bps(self.variance.saturating_mul(horizon_secs as u128) / SECS_PER_YEAR as u128))
+    }
+
+    /// Collateral ratio in percent that survives a `sigmas_bps / 10_000`
+    /// standard deviation drop over `horizon_secs` while staying at
+    /// `min_ratio`. Without an estimate this is `min_ratio`.
+    pub fn buffered_ratio(&self, min_ratio: u64, horizon_secs: u64, sigmas_bps: u64) -> u64 {
+        let Some(horizon_vol) = self.horizon_volatility_bps(horizon_secs) else {
+            return min_ratio;
+        };
+
+        // Leave at least 1% of the price standing
+        let drop = (horizon_vol as u128 * sigmas_bps as u128 / BPS_DIVISOR as u128).min(BPS_DIVISOR as u128 - 100);
+        let remaining = BPS_DIVISOR as u128 - drop;
+        (min_ratio as u128 * BPS_DIVISOR as u128).div_ceil(remaining).min(u64::MAX as u128) as u64
+    }
+
+    /// `base_bps` scaled by the current volatility over `reference_vol_bps`,
+    /// never below `base_bps` nor above `max_bps`
+    pub fn scaled_fee_bps(&self, base_bps: u64, reference_vol_bps: u64, max_bps: u64) -> u64 {
+        let vol = match self.volatility_bps() {
+            Some(vol) if reference_vol_bps > 0 => vol,
+            _ => return base_bps.min(max_bps),
+        };
+        let scaled = (base_bps as u128 * vol as u128 / reference_vol_bps as u128).min(u64::MAX as u128) as u64;
+        scaled.max(base_bps).min(max_bps)
+    }
+
+    fn scaled_volatility_bps(&self, variance: u128) -> u64 {
+        (isqrt(variance) * BPS_DIVISOR as u128 / RETURN_SCALE).min(u64::MAX as u128) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Prices alternating up and down by `move_bps` every ten minutes
+    fn choppy(move_bps: u64, count: u64) -> Vec<(u64, u64)> {
+        (0..count)
+            .map(|i| {
+                let price = if i % 2 == 0 { 10_000_000 } else { 10_000_000 + 10_000_000 * move_bps / BPS_DIVISOR };
+                (i * 600, price)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_estimate_tracks_moves() {
+        let mut estimator = VolatilityEstimator::default();
+        assert!(estimator.volatility_bps().is_none());
+        assert_eq!(estimator.buffered_ratio(110, 3600, 30_000), 110);
+
+        // Constant price: no volatility
+        for i in 0..20 {
+            estimator.observe(i * 600, 10_000_000);
+        }
+        assert_eq!(estimator.volatility_bps(), Some(0));
+
+        // 0.1% every ten minutes is about 23% a year
+        let calm = VolatilityEstimator::default().from_history(&choppy(10, 200));
+        let vol = calm.volatility_bps().unwrap();
+        assert!((2_200..=2_300).contains(&vol), "{}", vol);
+
+        let wild = VolatilityEstimator::default().from_history(&choppy(100, 200));
+        assert!(wild.volatility_bps().unwrap() > vol * 9);
+
+        // Older and duplicate timestamps are ignored
+        let mut replayed = calm.clone();
+        replayed.observe(600, 1);
+        replayed.observe(0, 50_000_000);
+        assert_eq!(replayed, calm);
+    }
+
+    #[test]
+    fn test_risk_parameters() {
+        let estimator = VolatilityEstimator::default().from_history(&choppy(100, 200));
+        let day_vol = estimator.horizon_volatility_bps(24 * 3600).unwrap();
+
+        // A three sigma daily move on top of a 110% minimum
+        let ratio = estimator.buffered_ratio(110, 24 * 3600, 30_000);
+        let expected = (110 * BPS_DIVISOR).div_ceil(BPS_DIVISOR - day_vol * 3);
+        assert_eq!(ratio, expected);
+        assert!(ratio > 110);
+
+        // Fees scale up with volatility but stay within bounds
+        let vol = estimator.volatility_bps().unwrap();
+        assert_eq!(estimator.scaled_fee_bps(50, vol / 2, 1_000), 100);
+        assert_eq!(estimator.scaled_fee_bps(50, vol * 2, 1_000), 50);
+        assert_eq!(estimator.scaled_fee_bps(50, vol / 100, 1_000), 1_000);
+    }
+}
@@ -0,0 +1,217 @@
+//! Liquidation watch for owned positions.
+//!
+//! Tracks a set of CDPs against the BTC price and raises an alert when a
+//! position's liquidation price comes within a configured distance of the
+//! market. Alerts fire once on entering the zone and again if the position
+//! becomes liquidatable; they re-arm only after the position moves back out
+//! of the zone, so a price hovering at the threshold doesn't repeat them.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+use crate::core::cdp::{CDPId, CDP};
+use crate::core::risk::liquidation_price;
+use crate::utils::constants::BPS_DIVISOR;
+
+/// Default distance from liquidation that raises an alert (10%)
+pub const DEFAULT_ALERT_WITHIN_BPS: u64 = 1_000;
+
+/// CDP ids a watcher follows, stored as hex
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchList {
+    cdps: BTreeSet<String>,
+}
+
+impl WatchList {
+    /// Add a CDP, returning whether it was new
+    pub fn add(&mut self, cdp_id: &CDPId) -> bool {
+        self.cdps.insert(cdp_id.to_hex())
+    }
+
+    /// Remove a CDP, returning whether it was watched
+    pub fn remove(&mut self, cdp_id: &CDPId) -> bool {
+        self.cdps.remove(&cdp_id.to_hex())
+    }
+
+    /// Watched CDP ids, skipping entries that no longer parse
+    pub fn ids(&self) -> Vec<CDPId> {
+        self.cdps.iter().filter_map(|hex| CDPId::from_hex(hex).ok()).collect()
+    }
+
+    /// Number of watched CDPs
+    pub fn len(&self) -> usize {
+        self.cdps.len()
+    }
+
+    /// Whether nothing is watched
+    pub fn is_empty(&self) -> bool {
+        self.cdps.is_empty()
+    }
+}
+
+/// Where a watched CDP stands against the BTC price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PositionStatus {
+    /// CDP identifier
+    pub cdp_id: CDPId,
+    /// BTC price the status was computed at in cents
+    pub btc_price: u64,
+    /// BTC price in cents below which the CDP is liquidatable (0 without debt)
+    pub liquidation_price: u64,
+    /// Price drop in basis points that makes the CDP liquidatable (0 once it is)
+    pub distance_bps: u64,
+    /// Whether the CDP is liquidatable at `btc_price`
+    pub liquidatable: bool,
+}
+
+impl PositionStatus {
+    /// Status of `cdp` at `btc_price_cents` against `min_ratio` (in percent)
+    pub fn of(cdp: &CDP, btc_price_cents: u64, min_ratio: u64) -> Self {
+        let liquidation_price = liquidation_price(cdp, min_ratio);
+        let liquidatable = cdp.debt_cents > 0 && btc_price_cents < liquidation_price;
+        let distance_bps = if liquidatable || btc_price_cents == 0 {
+            0
+        } else {
+            let drop = (btc_price_cents - liquidation_price) as u128 * BPS_DIVISOR as u128;
+            (drop / btc_price_cents as u128) as u64
+        };
+
+        Self {
+            cdp_id: cdp.id,
+            btc_price: btc_price_cents,
+            liquidation_price,
+            distance_bps,
+            liquidatable,
+        }
+    }
+}
+
+/// Raises alerts as watched CDPs approach liquidation
+#[derive(Debug, Clone)]
+pub struct LiquidationWatcher {
+    /// Distance from liquidation in basis points that raises an alert
+    within_bps: u64,
+    /// CDPs currently alerted, and whether the alert was for liquidatability
+    alerted: HashMap<CDPId, bool>,
+}
+
+impl Default for LiquidationWatcher {
+    fn default() -> Self {
+        Self::new(DEFAULT_ALERT_WITHIN_BPS)
+    }
+}
+
+impl LiquidationWatcher {
+    /// Create a watcher alerting within `within_bps` of liquidation
+    pub fn new(within_bps: u64) -> Self {
+        Self {
+            within_bps,
+            alerted: HashMap::new(),
+        }
+    }
+
+    /// Distance from liquidation in basis points that raises an alert
+    pub fn within_bps(&self) -> u64 {
+        self.within_bps
+    }
+
+    /// Check `cdps` at a new price, returning the statuses that raise an
+    /// alert. CDPs without debt never alert.
+    pub fn check<'a>(
+        &mut self,
+        cdps: impl IntoIterator<Item = &'a CDP>,
+        btc_price_cents: u64,
+        min_ratio: u64,
+    ) -> Vec<PositionStatus> {
+        let mut alerts = Vec::new();
+        for cdp in cdps {
+            let status = PositionStatus::of(cdp, btc_price_cents, min_ratio);
+            if cdp.debt_cents == 0 || status.distance_bps > self.within_bps {
+                self.alerted.remove(&cdp.id);
+                continue;
+            }
+
+            let escalated = match self.alerted.get(&cdp.id) {
+                None => true,
+                Some(liquidatable) => status.liquidatable && !liquidatable,
+            };
+            if escalated {
+                self.alerted.insert(cdp.id, status.liquidatable);
+                alerts.push(status);
+            }
+        }
+        alerts
+    }
+
+    /// Forget a CDP's alert state, e.g. once it is closed or unwatched
+    pub fn clear(&mut self, cdp_id: &CDPId) {
+        self.alerted.remove(cdp_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::constants::SATS_PER_BTC;
+    use crate::utils::crypto::KeyPair;
+
+    fn cdp(collateral_sats: u64, debt_cents: u64) -> CDP {
+        let mut cdp = CDP::new(*KeyPair::generate().public_key(), 1, 0);
+        cdp.collateral_sats = collateral_sats;
+        cdp.debt_cents = debt_cents;
+        cdp
+    }
+
+    #[test]
+    fn test_position_status() {
+        // 1 BTC against $50,000 at 110% liquidates below $55,000
+        let position = cdp(SATS_PER_BTC, 5_000_000);
+
+        let status = PositionStatus::of(&position, 10_000_000, 110);
+        assert_eq!(status.liquidation_price, 5_500_000);
+        assert_eq!(status.distance_bps, 4_500);
+        assert!(!status.liquidatable);
+
+        let status = PositionStatus::of(&position, 5_000_000, 110);
+        assert_eq!(status.distance_bps, 0);
+        assert!(status.liquidatable);
+
+        let status = PositionStatus::of(&cdp(SATS_PER_BTC, 0), 10_000_000, 110);
+        assert_eq!(status.liquidation_price, 0);
+        assert_eq!(status.distance_bps, BPS_DIVISOR);
+    }
+
+    #[test]
+    fn test_alerts_once_per_approach() {
+        let position = cdp(SATS_PER_BTC, 5_000_000);
+        let mut watcher = LiquidationWatcher::new(1_000);
+
+        // 45% away, then 8.3% away
+        assert!(watcher.check([&position], 10_000_000, 110).is_empty());
+        let alerts = watcher.check([&position], 6_000_000, 110);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].distance_bps, 833);
+
+        // Still in the zone: no repeat, until it becomes liquidatable
+        assert!(watcher.check([&position], 5_900_000, 110).is_empty());
+        let alerts = watcher.check([&position], 5_400_000, 110);
+        assert!(alerts[0].liquidatable);
+        assert!(watcher.check([&position], 5_300_000, 110).is_empty());
+
+        // Leaving the zone re-arms the alert
+        assert!(watcher.check([&position], 8_000_000, 110).is_empty());
+        assert_eq!(watcher.check([&position], 6_000_000, 110).len(), 1);
+    }
+
+    #[test]
+    fn test_watch_list() {
+        let position = cdp(SATS_PER_BTC, 0);
+        let mut list = WatchList::default();
+
+        assert!(list.add(&position.id));
+        assert!(!list.add(&position.id));
+        assert_eq!(list.ids(), vec![position.id]);
+        assert!(list.remove(&position.id));
+        assert!(list.is_empty());
+    }
+}
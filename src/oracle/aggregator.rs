@@ -6,10 +6,11 @@
 //! - ZK proof generation for aggregated prices
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use crate::error::{Error, Result};
 use crate::oracle::price_feed::{PriceData, PriceFeed, PriceProof};
-use crate::oracle::sources::{PriceSource, PriceSourceFetcher, SourceCollection};
+use crate::oracle::sources::{Exchange, PriceSource, PriceSourceFetcher, SourceCollection};
 use crate::utils::constants::*;
 use crate::utils::crypto::Hash;
 use crate::utils::validation::*;
@@ -68,6 +69,7 @@ impl AggregationResult {
             self.timestamp,
             self.source_count as u8,
         )
+        .with_interval(PriceData::dispersion_interval(self.price_cents, &self.source_prices))
     }
 
     /// Create a price proof from this result
@@ -120,6 +122,9 @@ pub struct PriceAggregator {
     max_deviation_bps: u64,
     /// Last successful aggregation
     last_aggregation: Option<AggregationResult>,
+    /// Base weight overrides per exchange
+    #[serde(default)]
+    source_weights: HashMap<Exchange, u64>,
 }
 
 impl Default for PriceAggregator {
@@ -137,6 +142,7 @@ impl PriceAggregator {
             min_sources: MIN_ORACLE_SOURCES,
             max_deviation_bps: MAX_PRICE_DEVIATION_BPS,
             last_aggregation: None,
+            source_weights: HashMap::new(),
         }
     }
 
@@ -152,6 +158,22 @@ impl PriceAggregator {
             min_sources,
             max_deviation_bps,
             last_aggregation: None,
+            source_weights: HashMap::new(),
+        }
+    }
+
+    /// Override the base weight of an exchange (0 excludes it from weighted strategies)
+    pub fn with_source_weight(mut self, exchange: Exchange, weight: u64) -> Self {
+        self.source_weights.insert(exchange, weight);
+        self
+    }
+
+    /// Weight of a source under this aggregator's configuration
+    pub fn source_weight(&self, source: &PriceSource) -> u64 {
+        match self.source_weights.get(&source.exchange) {
+            Some(0) => 0,
+            Some(&base) => source.effective_weight_with(base),
+            None => source.effective_weight(),
         }
     }
 
@@ -181,7 +203,7 @@ impl PriceAggregator {
                 self.calculate_median(&prices)
             }
             AggregationStrategy::WeightedAverage => {
-                sources.weighted_average_price()
+                sources.weighted_average_price_by(|s| self.source_weight(s))
                     .ok_or(Error::InsufficientOracleSources {
                         got: 0,
                         need: self.min_sources,
@@ -283,7 +305,7 @@ impl PriceAggregator {
         let mut weighted: Vec<(u64, u64)> = sources
             .sources()
             .iter()
-            .map(|s| (s.price_cents, s.effective_weight()))
+            .map(|s| (s.price_cents, self.source_weight(s)))
             .collect();
 
         // Sort by price
@@ -512,6 +534,23 @@ mod tests {
         assert_eq!(proof.price.price_cents, result.price_cents);
     }
 
+    #[test]
+    fn test_source_weight_overrides() {
+        let mut collection = SourceCollection::new(1000);
+        collection.add(PriceSource::new(Exchange::Kraken, 10_000_000, 1000));
+        collection.add(PriceSource::new(Exchange::Bitstamp, 10_100_000, 1000));
+        collection.add(PriceSource::new(Exchange::OKX, 10_200_000, 1000));
+
+        let mut aggregator = PriceAggregator::with_params(AggregationStrategy::WeightedAverage, 3, 500)
+            .with_source_weight(Exchange::Kraken, 0)
+            .with_source_weight(Exchange::Bitstamp, 50)
+            .with_source_weight(Exchange::OKX, 50);
+
+        // Kraken is excluded, the other two count equally
+        let result = aggregator.aggregate(&collection).unwrap();
+        assert_eq!(result.price_cents, 10_150_000);
+    }
+
     #[test]
     fn test_oracle_service() {
         let fetcher = MockPriceFetcher::new(10_000_000, 1000);
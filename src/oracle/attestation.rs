@@ -0,0 +1,233 @@
+//! ZK price attestations.
+//!
+//! Proves that a published price is the median of the fetched source prices
+//! without revealing the individual quotes. The [`PriceAttestor`] runs
+//! [`PriceAttestationCircuit`] over a round's [`SourceCollection`], and the
+//! resulting [`PriceAttestation`] travels in the `proof` field of
+//! [`UpdatePriceOp`], where the state machine checks it before accepting the
+//! price.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::oracle::service::PriceUpdate;
+use crate::oracle::sources::{Exchange, SourceCollection};
+use crate::protocol::operations::UpdatePriceOp;
+use crate::utils::crypto::{verify_signature, Hash, KeyPair};
+use crate::zkp::circuits::{Circuit, PriceAttestationCircuit};
+use crate::zkp::inputs::{PriceAttestationPublicInputs, PricePrivateInputs, SourcePrice};
+use crate::zkp::prover::{Prover, ZKProof};
+use crate::zkp::verifier::Verifier;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// ATTESTATION
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// A price together with the proof that it is the median of its sources
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceAttestation {
+    /// Public inputs the proof commits to
+    pub public: PriceAttestationPublicInputs,
+    /// Proof of the price attestation circuit
+    pub proof: ZKProof,
+}
+
+impl PriceAttestation {
+    /// Message signed by the oracle key for a price
+    pub fn message(price_cents: u64, timestamp: u64, source_count: u8) -> Hash {
+        let mut data = Vec::with_capacity(17);
+        data.extend_from_slice(&price_cents.to_le_bytes());
+        data.extend_from_slice(&timestamp.to_le_bytes());
+        data.push(source_count);
+        Hash::sha256(&data)
+    }
+
+    /// Encode for the `proof` field of an `UpdatePriceOp`
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        bincode::serialize(self)
+            .map_err(|e| Error::Serialization(format!("Failed to encode price attestation: {}", e)))
+    }
+
+    /// Decode from the `proof` field of an `UpdatePriceOp`
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        bincode::deserialize(bytes).map_err(|_| Error::InvalidPriceProof)
+    }
+
+    /// Check the attestation against the operation carrying it.
+    ///
+    /// The proof must verify, commit to the operation's price, source count
+    /// and operator, tolerate at most `max_deviation_bps`, and be signed by
+    /// the operator.
+    pub fn verify(&self, op: &UpdatePriceOp, max_deviation_bps: u64, verifier: &dyn Verifier) -> Result<()> {
+        let public = &self.public;
+
+        if !self.proof.is_for_circuit(PriceAttestationCircuit::circuit_id())
+            || public.price != op.price_cents
+            || public.source_count != op.source_count
+            || public.oracle_pubkey != op.operator
+            || public.deviation_bps as u64 > max_deviation_bps
+        {
+            return Err(Error::InvalidPriceProof);
+        }
+
+        let message = Self::message(public.price, public.timestamp, public.source_count);
+        if !verify_signature(&public.oracle_pubkey, &message, &public.signature) {
+            return Err(Error::InvalidPriceProof);
+        }
+
+        let result = verifier.verify_with_inputs(&self.proof, &public.encode())?;
+        if !result.valid {
+            tracing::debug!(error = ?result.error, "Price attestation proof rejected");
+            return Err(Error::InvalidPriceProof);
+        }
+
+        Ok(())
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// ATTESTOR
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Proves aggregated prices with the oracle's key and a prover backend
+pub struct PriceAttestor {
+    /// Oracle signing key (must match the publisher's)
+    keypair: KeyPair,
+    /// Proof backend
+    prover: Box<dyn Prover>,
+}
+
+impl PriceAttestor {
+    /// Create an attestor signing with `keypair` and proving with `prover`
+    pub fn new(keypair: KeyPair, prover: impl Prover + 'static) -> Self {
+        Self {
+            keypair,
+            prover: Box::new(prover),
+        }
+    }
+
+    /// Prove that `update` is the median of the sources in `collection`
+    pub fn attest(&self, collection: &SourceCollection, update: &PriceUpdate) -> Result<PriceAttestation> {
+        let source_count = update.source_count.min(u8::MAX as usize) as u8;
+
+        let public = PriceAttestationPublicInputs {
+            price: update.price_cents,
+            timestamp: update.timestamp,
+            source_count,
+            // The update carries the exact median
+            deviation_bps: 0,
+            oracle_pubkey: *self.keypair.public_key(),
+            signature: self
+                .keypair
+                .sign(&PriceAttestation::message(update.price_cents, update.timestamp, source_count)),
+        };
+        let private = private_inputs(collection);
+
+        let proof = self.prover.prove_price_attestation(&public, &private)?;
+        Ok(PriceAttestation { public, proof })
+    }
+}
+
+/// Circuit witness for a round: each source's quote and timestamp, plus the
+/// signatures of the sources that sign their quotes
+pub fn private_inputs(collection: &SourceCollection) -> PricePrivateInputs {
+    let mut oracle_signature_data = Vec::new();
+    for source in collection.sources() {
+        if let (Some(signer), Some(signature)) = (&source.signer, &source.signature) {
+            oracle_signature_data.extend_from_slice(signer.as_bytes());
+            oracle_signature_data.extend_from_slice(signature.as_bytes());
+        }
+    }
+
+    PricePrivateInputs {
+        source_prices: collection
+            .sources()
+            .iter()
+            .map(|source| SourcePrice {
+                source_id: source_id(&source.exchange),
+                price: source.price_cents,
+                timestamp: source.timestamp,
+                weight: source.exchange.weight(),
+            })
+            .collect(),
+        oracle_signature_data,
+    }
+}
+
+/// Circuit identifier of an exchange; custom oracles keep their own id
+fn source_id(exchange: &Exchange) -> u8 {
+    match exchange {
+        Exchange::Binance => 0,
+        Exchange::Coinbase => 1,
+        Exchange::Kraken => 2,
+        Exchange::Bitstamp => 3,
+        Exchange::OKX => 4,
+        Exchange::Bybit => 5,
+        Exchange::Custom(id) => *id,
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::sources::PriceSource;
+    use crate::utils::constants::SIGNATURE_LENGTH;
+    use crate::utils::crypto::Signature;
+    use crate::zkp::prover::NativeProver;
+    use crate::zkp::verifier::NativeVerifier;
+
+    fn round() -> (SourceCollection, PriceUpdate) {
+        let mut collection = SourceCollection::new(1_700_000_000);
+        collection.add(PriceSource::new(Exchange::Binance, 9_990_000, 1_699_999_990));
+        collection.add(PriceSource::new(Exchange::Coinbase, 10_000_000, 1_699_999_990));
+        collection.add(PriceSource::new(Exchange::Kraken, 10_020_000, 1_699_999_990));
+        let update = PriceUpdate::from_collection(&collection, 1).unwrap();
+        (collection, update)
+    }
+
+    fn op_for(attestor: &PriceAttestor, update: &PriceUpdate, proof: Vec<u8>) -> UpdatePriceOp {
+        UpdatePriceOp {
+            operator: *attestor.keypair.public_key(),
+            price_cents: update.price_cents,
+            source_count: update.source_count as u8,
+            confidence: 100,
+            confidence_interval: 0,
+            proof,
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        }
+    }
+
+    #[test]
+    fn test_attestation_round_trip() {
+        let attestor = PriceAttestor::new(KeyPair::generate(), NativeProver::new());
+        let (collection, update) = round();
+
+        let attestation = attestor.attest(&collection, &update).unwrap();
+        let op = op_for(&attestor, &update, attestation.encode().unwrap());
+
+        let decoded = PriceAttestation::decode(&op.proof).unwrap();
+        assert!(decoded.verify(&op, 0, &NativeVerifier::new()).is_ok());
+
+        // A different price is not covered by the proof
+        let mut tampered = op.clone();
+        tampered.price_cents += 1;
+        assert!(matches!(
+            decoded.verify(&tampered, 0, &NativeVerifier::new()),
+            Err(Error::InvalidPriceProof)
+        ));
+    }
+
+    #[test]
+    fn test_attestation_rejects_off_median_price() {
+        let attestor = PriceAttestor::new(KeyPair::generate(), NativeProver::new());
+        let (collection, mut update) = round();
+        update.price_cents = 11_000_000;
+
+        assert!(attestor.attest(&collection, &update).is_err());
+    }
+}
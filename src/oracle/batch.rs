@@ -0,0 +1,156 @@
+//! Per-block batching of on-chain price updates.
+//!
+//! Oracle operators may each submit a price in the same block. Rather than
+//! letting the last one win, the state machine collects them in a
+//! [`PriceBatch`] and uses the median weighted by operator reputation. An
+//! operator's later submission in a block replaces its earlier one, so no
+//! single operator can stuff the median.
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::crypto::PublicKey;
+
+/// Reputation of an operator without a configured one, as for a new
+/// [`OracleNode`](crate::oracle::sources::OracleNode)
+pub const DEFAULT_ORACLE_REPUTATION: u8 = 50;
+
+/// A price submitted by one operator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriceSubmission {
+    /// Operator that signed the update
+    pub operator: PublicKey,
+    /// Price in cents
+    pub price_cents: u64,
+    /// Number of sources behind the price
+    pub source_count: u8,
+    /// Operator's confidence (0-100)
+    pub confidence: u8,
+    /// Half-width of the confidence interval in cents
+    pub confidence_interval: u64,
+    /// Weight in the median: the operator's registered weight or reputation
+    pub weight: u64,
+}
+
+/// Price updates submitted in the current block
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PriceBatch {
+    /// Price before the block's first update
+    previous_price: u64,
+    /// One submission per operator
+    submissions: Vec<PriceSubmission>,
+}
+
+impl PriceBatch {
+    /// Start a batch on top of `previous_price`
+    pub fn new(previous_price: u64) -> Self {
+        Self {
+            previous_price,
+            submissions: Vec::new(),
+        }
+    }
+
+    /// Price before the block's first update
+    pub fn previous_price(&self) -> u64 {
+        self.previous_price
+    }
+
+    /// Add a submission, replacing the operator's earlier one
+    pub fn submit(&mut self, submission: PriceSubmission) {
+        self.submissions.retain(|s| s.operator != submission.operator);
+        self.submissions.push(submission);
+    }
+
+    /// Drop the operator's submission, returning whether it had one
+    pub fn remove(&mut self, operator: &PublicKey) -> bool {
+        let before = self.submissions.len();
+        self.submissions.retain(|s| &s.operator != operator);
+        self.submissions.len() != before
+    }
+
+    /// Submissions in the batch
+    pub fn submissions(&self) -> &[PriceSubmission] {
+        &self.submissions
+    }
+
+    /// Number of operators that submitted
+    pub fn len(&self) -> usize {
+        self.submissions.len()
+    }
+
+    /// Whether nothing was submitted
+    pub fn is_empty(&self) -> bool {
+        self.submissions.is_empty()
+    }
+
+    /// Submission at the weighted median price.
+    ///
+    /// Ties break towards the lower price. If every operator has zero
+    /// weight, all count equally.
+    pub fn median(&self) -> Option<&PriceSubmission> {
+        let mut sorted: Vec<&PriceSubmission> = self.submissions.iter().collect();
+        sorted.sort_by(|a, b| {
+            a.price_cents
+                .cmp(&b.price_cents)
+                .then_with(|| a.operator.as_bytes().cmp(b.operator.as_bytes()))
+        });
+
+        let total: u128 = sorted.iter().map(|s| s.weight as u128).sum();
+        let weight_of = |s: &PriceSubmission| if total == 0 { 1 } else { s.weight as u128 };
+        let total = if total == 0 { sorted.len() as u128 } else { total };
+
+        let mut cumulative = 0u128;
+        sorted.into_iter().find(|s| {
+            cumulative += weight_of(s);
+            cumulative * 2 >= total
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::crypto::KeyPair;
+
+    fn submission(price_cents: u64, weight: u64) -> PriceSubmission {
+        PriceSubmission {
+            operator: *KeyPair::generate().public_key(),
+            price_cents,
+            source_count: 3,
+            confidence: 90,
+            confidence_interval: 0,
+            weight,
+        }
+    }
+
+    #[test]
+    fn test_weighted_median() {
+        let mut batch = PriceBatch::new(9_000_000);
+        assert!(batch.median().is_none());
+
+        batch.submit(submission(10_000_000, 50));
+        batch.submit(submission(10_200_000, 50));
+        batch.submit(submission(50_000_000, 50));
+        assert_eq!(batch.median().unwrap().price_cents, 10_200_000);
+
+        // A trusted operator outweighs the other two
+        batch.submit(submission(9_900_000, 200));
+        assert_eq!(batch.median().unwrap().price_cents, 9_900_000);
+        assert_eq!(batch.previous_price(), 9_000_000);
+    }
+
+    #[test]
+    fn test_resubmission_replaces() {
+        let mut batch = PriceBatch::new(0);
+        let first = submission(10_000_000, 0);
+        let mut second = first.clone();
+        second.price_cents = 11_000_000;
+
+        batch.submit(first);
+        batch.submit(submission(12_000_000, 0));
+        batch.submit(second);
+        assert_eq!(batch.len(), 2);
+
+        // Zero weights all count equally
+        assert_eq!(batch.median().unwrap().price_cents, 11_000_000);
+    }
+}
@@ -11,14 +11,27 @@
 //! - Bitstamp
 //!
 //! All prices are returned in USD cents for consistency.
+//!
+//! Independent sources also implement the [`PriceFetcher`] trait so they can
+//! be wrapped with per-source timeouts, exponential backoff and a circuit
+//! breaker ([`ResilientFetcher`]) and combined with failover
+//! ([`FailoverFetcher`]).
 
 #[cfg(feature = "async-oracle")]
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(feature = "async-oracle")]
+use std::future::Future;
+#[cfg(feature = "async-oracle")]
+use std::pin::Pin;
+#[cfg(feature = "async-oracle")]
+use std::sync::{Arc, Mutex};
 
 use crate::error::{Error, Result};
 use crate::oracle::sources::{Exchange, PriceSource, SourceCollection};
+#[cfg(feature = "async-oracle")]
+use crate::utils::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // EXCHANGE API RESPONSE TYPES
@@ -56,6 +69,30 @@ pub struct KrakenResponse {
     pub result: Option<KrakenResult>,
 }
 
+impl KrakenResponse {
+    /// Convert to a price source
+    pub fn into_price_source(self, timestamp: u64) -> Result<PriceSource> {
+        if !self.error.is_empty() {
+            return Err(Error::Internal(format!(
+                "Kraken API error: {:?}",
+                self.error
+            )));
+        }
+
+        let result = self.result.ok_or_else(|| Error::Internal("No result from Kraken".into()))?;
+        let btc_usd = result.btc_usd.ok_or_else(|| Error::Internal("No BTC/USD data from Kraken".into()))?;
+
+        // Get last trade price (c[0])
+        let price_str = btc_usd.c.first().ok_or_else(|| Error::Internal("No price in Kraken response".into()))?;
+        let price_cents = price_str_to_cents(price_str)?;
+
+        // Get 24h volume
+        let volume = btc_usd.v.get(1).map(|v| volume_str_to_units(v)).unwrap_or(0);
+
+        Ok(PriceSource::new(Exchange::Kraken, price_cents, timestamp).with_volume(volume))
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct KrakenResult {
     /// XXBTZUSD ticker data
@@ -86,6 +123,16 @@ pub struct BitstampResponse {
     pub timestamp: String,
 }
 
+impl BitstampResponse {
+    /// Convert to a price source
+    pub fn into_price_source(self, timestamp: u64) -> Result<PriceSource> {
+        let price_cents = price_str_to_cents(&self.last)?;
+        let volume = volume_str_to_units(&self.volume);
+
+        Ok(PriceSource::new(Exchange::Bitstamp, price_cents, timestamp).with_volume(volume))
+    }
+}
+
 /// Coinbase response (v2 API)
 #[derive(Debug, Deserialize)]
 pub struct CoinbaseResponse {
@@ -112,6 +159,21 @@ pub struct OKXResponse {
     pub data: Vec<OKXTicker>,
 }
 
+impl OKXResponse {
+    /// Convert to a price source
+    pub fn into_price_source(self, timestamp: u64) -> Result<PriceSource> {
+        if self.code != "0" {
+            return Err(Error::Internal(format!("OKX API error: code {}", self.code)));
+        }
+
+        let ticker = self.data.first().ok_or_else(|| Error::Internal("No ticker data from OKX".into()))?;
+        let price_cents = price_str_to_cents(&ticker.last)?;
+        let volume = volume_str_to_units(&ticker.vol_ccy24h);
+
+        Ok(PriceSource::new(Exchange::OKX, price_cents, timestamp).with_volume(volume))
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OKXTicker {
@@ -149,6 +211,50 @@ pub struct BybitTicker {
     pub turnover24h: String,
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// RESPONSE PARSING
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Parse a decimal USD price string to cents
+pub fn price_str_to_cents(price_str: &str) -> Result<u64> {
+    let price: f64 = price_str.parse().map_err(|e| Error::InvalidParameter {
+        name: "price".into(),
+        reason: format!("Invalid price format: {}", e),
+    })?;
+
+    if !price.is_finite() || price <= 0.0 {
+        return Err(Error::InvalidParameter {
+            name: "price".into(),
+            reason: format!("Price must be positive, got {}", price_str),
+        });
+    }
+
+    Ok((price * 100.0).round() as u64)
+}
+
+/// Parse a decimal volume string, truncating to whole units
+fn volume_str_to_units(volume_str: &str) -> u64 {
+    volume_str.parse::<f64>().ok().map(|v| v as u64).unwrap_or(0)
+}
+
+/// Current Unix time in seconds
+#[cfg(feature = "async-oracle")]
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Current Unix time in milliseconds
+#[cfg(feature = "async-oracle")]
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // HTTP PRICE FETCHER
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -206,26 +312,17 @@ impl HttpPriceFetcher {
 
     /// Get current timestamp
     fn current_timestamp() -> u64 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs()
+        now_secs()
     }
 
     /// Parse price string to cents (multiply by 100)
     fn parse_price_to_cents(price_str: &str) -> Result<u64> {
-        let price: f64 = price_str.parse().map_err(|e| Error::InvalidParameter {
-            name: "price".into(),
-            reason: format!("Invalid price format: {}", e),
-        })?;
-
-        // Convert to cents (multiply by 100)
-        Ok((price * 100.0).round() as u64)
+        price_str_to_cents(price_str)
     }
 
     /// Parse volume string to integer
     fn parse_volume(volume_str: &str) -> u64 {
-        volume_str.parse::<f64>().ok().map(|v| v as u64).unwrap_or(0)
+        volume_str_to_units(volume_str)
     }
 
     /// Fetch price from Binance
@@ -292,25 +389,7 @@ impl HttpPriceFetcher {
             .await
             .map_err(|e| Error::Internal(format!("Failed to parse Kraken response: {}", e)))?;
 
-        if !data.error.is_empty() {
-            return Err(Error::Internal(format!(
-                "Kraken API error: {:?}",
-                data.error
-            )));
-        }
-
-        let result = data.result.ok_or_else(|| Error::Internal("No result from Kraken".into()))?;
-        let btc_usd = result.btc_usd.ok_or_else(|| Error::Internal("No BTC/USD data from Kraken".into()))?;
-
-        // Get last trade price (c[0])
-        let price_str = btc_usd.c.first().ok_or_else(|| Error::Internal("No price in Kraken response".into()))?;
-        let price_cents = Self::parse_price_to_cents(price_str)?;
-
-        // Get 24h volume
-        let volume = btc_usd.v.get(1).map(|v| Self::parse_volume(v)).unwrap_or(0);
-
-        Ok(PriceSource::new(Exchange::Kraken, price_cents, Self::current_timestamp())
-            .with_volume(volume))
+        data.into_price_source(Self::current_timestamp())
     }
 
     /// Fetch price from Bitstamp
@@ -329,11 +408,7 @@ impl HttpPriceFetcher {
             .await
             .map_err(|e| Error::Internal(format!("Failed to parse Bitstamp response: {}", e)))?;
 
-        let price_cents = Self::parse_price_to_cents(&data.last)?;
-        let volume = Self::parse_volume(&data.volume);
-
-        Ok(PriceSource::new(Exchange::Bitstamp, price_cents, Self::current_timestamp())
-            .with_volume(volume))
+        data.into_price_source(Self::current_timestamp())
     }
 
     /// Fetch price from OKX
@@ -352,16 +427,7 @@ impl HttpPriceFetcher {
             .await
             .map_err(|e| Error::Internal(format!("Failed to parse OKX response: {}", e)))?;
 
-        if data.code != "0" {
-            return Err(Error::Internal(format!("OKX API error: code {}", data.code)));
-        }
-
-        let ticker = data.data.first().ok_or_else(|| Error::Internal("No ticker data from OKX".into()))?;
-        let price_cents = Self::parse_price_to_cents(&ticker.last)?;
-        let volume = Self::parse_volume(&ticker.vol_ccy24h);
-
-        Ok(PriceSource::new(Exchange::OKX, price_cents, Self::current_timestamp())
-            .with_volume(volume))
+        data.into_price_source(Self::current_timestamp())
     }
 
     /// Fetch price from Bybit
@@ -499,6 +565,345 @@ impl HttpPriceFetcher {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// PRICE FETCHER TRAIT
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Boxed future returned by [`PriceFetcher::fetch`]
+#[cfg(feature = "async-oracle")]
+pub type FetchFuture<'a> = Pin<Box<dyn Future<Output = Result<PriceSource>> + Send + 'a>>;
+
+/// A single independent price source
+#[cfg(feature = "async-oracle")]
+pub trait PriceFetcher: Send + Sync {
+    /// Exchange this fetcher reads from
+    fn exchange(&self) -> Exchange;
+
+    /// Fetch the current BTC/USD price
+    fn fetch(&self) -> FetchFuture<'_>;
+}
+
+/// Per-source fetch settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceConfig {
+    /// Timeout for a single request in milliseconds
+    pub timeout_ms: u64,
+    /// Attempts before giving up on a fetch
+    pub max_attempts: u8,
+    /// Delay before the first retry in milliseconds; doubles on each retry
+    pub backoff_base_ms: u64,
+    /// Upper bound on the retry delay in milliseconds
+    pub backoff_max_ms: u64,
+    /// User agent string
+    pub user_agent: String,
+}
+
+impl Default for SourceConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: 5_000,
+            max_attempts: 3,
+            backoff_base_ms: 250,
+            backoff_max_ms: 4_000,
+            user_agent: "zkUSD-Oracle/1.0".to_string(),
+        }
+    }
+}
+
+impl SourceConfig {
+    /// Delay before retry number `retry` (0-based)
+    pub fn backoff_ms(&self, retry: u32) -> u64 {
+        self.backoff_base_ms
+            .saturating_mul(1u64.checked_shl(retry).unwrap_or(u64::MAX))
+            .min(self.backoff_max_ms)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// EXCHANGE FETCHERS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Build an HTTP client honouring a source's timeout
+#[cfg(feature = "async-oracle")]
+fn source_client(config: &SourceConfig) -> Result<Client> {
+    Client::builder()
+        .timeout(std::time::Duration::from_millis(config.timeout_ms))
+        .user_agent(&config.user_agent)
+        .build()
+        .map_err(|e| Error::Internal(format!("Failed to create HTTP client: {}", e)))
+}
+
+/// GET and decode a JSON ticker
+#[cfg(feature = "async-oracle")]
+async fn get_ticker<T: serde::de::DeserializeOwned>(client: &Client, exchange: Exchange, url: &str) -> Result<T> {
+    client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Internal(format!("{} request failed: {}", exchange, e)))?
+        .json()
+        .await
+        .map_err(|e| Error::Internal(format!("Failed to parse {} response: {}", exchange, e)))
+}
+
+/// Kraken XBT/USD ticker
+#[cfg(feature = "async-oracle")]
+pub struct KrakenFetcher {
+    client: Client,
+}
+
+#[cfg(feature = "async-oracle")]
+impl KrakenFetcher {
+    /// Ticker endpoint
+    pub const URL: &'static str = "https://api.kraken.com/0/public/Ticker?pair=XBTUSD";
+
+    /// Create a fetcher
+    pub fn new(config: &SourceConfig) -> Result<Self> {
+        Ok(Self { client: source_client(config)? })
+    }
+}
+
+#[cfg(feature = "async-oracle")]
+impl PriceFetcher for KrakenFetcher {
+    fn exchange(&self) -> Exchange {
+        Exchange::Kraken
+    }
+
+    fn fetch(&self) -> FetchFuture<'_> {
+        Box::pin(async move {
+            let data: KrakenResponse = get_ticker(&self.client, Exchange::Kraken, Self::URL).await?;
+            data.into_price_source(now_secs())
+        })
+    }
+}
+
+/// Bitstamp BTC/USD ticker
+#[cfg(feature = "async-oracle")]
+pub struct BitstampFetcher {
+    client: Client,
+}
+
+#[cfg(feature = "async-oracle")]
+impl BitstampFetcher {
+    /// Ticker endpoint
+    pub const URL: &'static str = "https://www.bitstamp.net/api/v2/ticker/btcusd/";
+
+    /// Create a fetcher
+    pub fn new(config: &SourceConfig) -> Result<Self> {
+        Ok(Self { client: source_client(config)? })
+    }
+}
+
+#[cfg(feature = "async-oracle")]
+impl PriceFetcher for BitstampFetcher {
+    fn exchange(&self) -> Exchange {
+        Exchange::Bitstamp
+    }
+
+    fn fetch(&self) -> FetchFuture<'_> {
+        Box::pin(async move {
+            let data: BitstampResponse = get_ticker(&self.client, Exchange::Bitstamp, Self::URL).await?;
+            data.into_price_source(now_secs())
+        })
+    }
+}
+
+/// OKX BTC-USDT ticker
+#[cfg(feature = "async-oracle")]
+pub struct OkxFetcher {
+    client: Client,
+}
+
+#[cfg(feature = "async-oracle")]
+impl OkxFetcher {
+    /// Ticker endpoint
+    pub const URL: &'static str = "https://www.okx.com/api/v5/market/ticker?instId=BTC-USDT";
+
+    /// Create a fetcher
+    pub fn new(config: &SourceConfig) -> Result<Self> {
+        Ok(Self { client: source_client(config)? })
+    }
+}
+
+#[cfg(feature = "async-oracle")]
+impl PriceFetcher for OkxFetcher {
+    fn exchange(&self) -> Exchange {
+        Exchange::OKX
+    }
+
+    fn fetch(&self) -> FetchFuture<'_> {
+        Box::pin(async move {
+            let data: OKXResponse = get_ticker(&self.client, Exchange::OKX, Self::URL).await?;
+            data.into_price_source(now_secs())
+        })
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// RESILIENT FETCHING
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Wraps a fetcher with a per-attempt timeout, exponential backoff between
+/// retries and a circuit breaker that skips the source while it is failing
+#[cfg(feature = "async-oracle")]
+pub struct ResilientFetcher<F> {
+    /// Underlying fetcher
+    inner: F,
+    /// Timeout and backoff settings
+    config: SourceConfig,
+    /// Failure tracking
+    breaker: Mutex<CircuitBreaker>,
+}
+
+#[cfg(feature = "async-oracle")]
+impl<F: PriceFetcher> ResilientFetcher<F> {
+    /// Wrap `inner`
+    pub fn new(inner: F, config: SourceConfig, breaker: CircuitBreakerConfig) -> Self {
+        Self {
+            inner,
+            config,
+            breaker: Mutex::new(CircuitBreaker::new(breaker)),
+        }
+    }
+
+    /// Snapshot of the circuit breaker
+    pub fn breaker(&self) -> CircuitBreaker {
+        self.breaker.lock().expect("breaker lock poisoned").clone()
+    }
+
+    async fn fetch_with_backoff(&self) -> Result<PriceSource> {
+        let exchange = self.inner.exchange();
+        if !self.breaker.lock().expect("breaker lock poisoned").allow(now_ms()) {
+            return Err(Error::Internal(format!("{} circuit open", exchange)));
+        }
+
+        let timeout = std::time::Duration::from_millis(self.config.timeout_ms);
+        let attempts = self.config.max_attempts.max(1) as u32;
+        let mut last_error = None;
+
+        for attempt in 0..attempts {
+            if attempt > 0 {
+                let delay = self.config.backoff_ms(attempt - 1);
+                tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+            }
+
+            let result = match tokio::time::timeout(timeout, self.inner.fetch()).await {
+                Ok(result) => result,
+                Err(_) => Err(Error::Internal(format!(
+                    "{} timed out after {}ms",
+                    exchange, self.config.timeout_ms
+                ))),
+            };
+
+            match result {
+                Ok(source) => {
+                    self.breaker.lock().expect("breaker lock poisoned").record_success();
+                    return Ok(source);
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        self.breaker.lock().expect("breaker lock poisoned").record_failure(now_ms());
+        Err(last_error.unwrap_or_else(|| Error::Internal(format!("{} fetch failed", exchange))))
+    }
+}
+
+#[cfg(feature = "async-oracle")]
+impl<F: PriceFetcher> PriceFetcher for ResilientFetcher<F> {
+    fn exchange(&self) -> Exchange {
+        self.inner.exchange()
+    }
+
+    fn fetch(&self) -> FetchFuture<'_> {
+        Box::pin(self.fetch_with_backoff())
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// FAILOVER
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Set of independent sources queried together, tolerating individual failures
+#[cfg(feature = "async-oracle")]
+#[derive(Clone, Default)]
+pub struct FailoverFetcher {
+    /// Sources in priority order
+    fetchers: Vec<Arc<dyn PriceFetcher>>,
+}
+
+#[cfg(feature = "async-oracle")]
+impl FailoverFetcher {
+    /// Create an empty set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Kraken, Bitstamp and OKX, each with its own timeout, backoff and circuit breaker
+    pub fn with_default_sources(config: &SourceConfig, breaker: CircuitBreakerConfig) -> Result<Self> {
+        Ok(Self::new()
+            .with_fetcher(ResilientFetcher::new(KrakenFetcher::new(config)?, config.clone(), breaker))
+            .with_fetcher(ResilientFetcher::new(BitstampFetcher::new(config)?, config.clone(), breaker))
+            .with_fetcher(ResilientFetcher::new(OkxFetcher::new(config)?, config.clone(), breaker)))
+    }
+
+    /// Add a source (lower priority than those already added)
+    pub fn with_fetcher<F: PriceFetcher + 'static>(mut self, fetcher: F) -> Self {
+        self.fetchers.push(Arc::new(fetcher));
+        self
+    }
+
+    /// Number of sources
+    pub fn len(&self) -> usize {
+        self.fetchers.len()
+    }
+
+    /// Whether there are no sources
+    pub fn is_empty(&self) -> bool {
+        self.fetchers.is_empty()
+    }
+
+    /// Query every source concurrently, collecting what succeeds
+    pub async fn fetch_all(&self) -> FetchResult {
+        let start = std::time::Instant::now();
+        let mut tasks = tokio::task::JoinSet::new();
+
+        for fetcher in &self.fetchers {
+            let fetcher = Arc::clone(fetcher);
+            tasks.spawn(async move { (fetcher.exchange(), fetcher.fetch().await) });
+        }
+
+        let mut collection = SourceCollection::new(now_secs());
+        let mut errors = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((_, Ok(source))) => collection.add(source),
+                Ok((exchange, Err(e))) => errors.push((exchange, e.to_string())),
+                Err(e) => tracing::warn!("Price fetch task failed: {}", e),
+            }
+        }
+
+        FetchResult::new(collection, errors, start.elapsed().as_millis() as u64)
+    }
+
+    /// First price available, trying sources in priority order
+    pub async fn fetch_first(&self) -> Result<PriceSource> {
+        let mut last_error = None;
+        for fetcher in &self.fetchers {
+            match fetcher.fetch().await {
+                Ok(source) => return Ok(source),
+                Err(e) => {
+                    tracing::warn!("{} unavailable, failing over: {}", fetcher.exchange(), e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| Error::InsufficientOracleSources { got: 0, need: 1 }))
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // SYNCHRONOUS PRICE FETCHER (for non-async contexts)
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -628,4 +1033,102 @@ mod tests {
         assert!(!result.has_minimum_sources(1));
         assert_eq!(result.failed, 1);
     }
+
+    #[test]
+    fn test_parse_exchange_responses() {
+        let kraken: KrakenResponse = serde_json::from_str(
+            r#"{"error":[],"result":{"XXBTZUSD":{"a":["97001.10","1","1.000"],"b":["97000.90","2","2.000"],
+                "c":["97001.00","0.01"],"v":["120.5","2500.75"]}}}"#,
+        )
+        .unwrap();
+        let source = kraken.into_price_source(1000).unwrap();
+        assert_eq!((source.exchange, source.price_cents, source.volume_24h), (Exchange::Kraken, 9_700_100, Some(2500)));
+
+        let bitstamp: BitstampResponse = serde_json::from_str(
+            r#"{"last":"96995.50","volume":"1850.3","timestamp":"1700000000"}"#,
+        )
+        .unwrap();
+        assert_eq!(bitstamp.into_price_source(1000).unwrap().price_cents, 9_699_550);
+
+        let okx: OKXResponse = serde_json::from_str(
+            r#"{"code":"0","data":[{"instId":"BTC-USDT","last":"97010.2","volCcy24h":"812345678.9"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(okx.into_price_source(1000).unwrap().price_cents, 9_701_020);
+
+        let okx_error: OKXResponse = serde_json::from_str(r#"{"code":"50011","data":[]}"#).unwrap();
+        assert!(okx_error.into_price_source(1000).is_err());
+        assert!(price_str_to_cents("-1").is_err());
+    }
+
+    #[test]
+    fn test_source_backoff() {
+        let config = SourceConfig { backoff_base_ms: 100, backoff_max_ms: 1_000, ..Default::default() };
+        assert_eq!(config.backoff_ms(0), 100);
+        assert_eq!(config.backoff_ms(2), 400);
+        assert_eq!(config.backoff_ms(10), 1_000);
+        assert_eq!(config.backoff_ms(200), 1_000);
+    }
+
+    #[cfg(feature = "async-oracle")]
+    struct FlakyFetcher {
+        exchange: Exchange,
+        failures_left: std::sync::atomic::AtomicU32,
+    }
+
+    #[cfg(feature = "async-oracle")]
+    impl PriceFetcher for FlakyFetcher {
+        fn exchange(&self) -> Exchange {
+            self.exchange
+        }
+
+        fn fetch(&self) -> FetchFuture<'_> {
+            use std::sync::atomic::Ordering;
+            Box::pin(async move {
+                let left = self.failures_left.load(Ordering::SeqCst);
+                if left > 0 {
+                    self.failures_left.store(left - 1, Ordering::SeqCst);
+                    return Err(Error::Internal("unavailable".into()));
+                }
+                Ok(PriceSource::new(self.exchange, 9_700_000, 1000))
+            })
+        }
+    }
+
+    #[cfg(feature = "async-oracle")]
+    fn flaky(exchange: Exchange, failures: u32) -> FlakyFetcher {
+        FlakyFetcher { exchange, failures_left: std::sync::atomic::AtomicU32::new(failures) }
+    }
+
+    #[cfg(feature = "async-oracle")]
+    #[tokio::test]
+    async fn test_resilient_fetcher_retries_and_trips() {
+        let config = SourceConfig { max_attempts: 2, backoff_base_ms: 1, ..Default::default() };
+        let breaker = CircuitBreakerConfig { failure_threshold: 1, cooldown_ms: 60_000 };
+
+        // One failure is absorbed by the retry
+        let fetcher = ResilientFetcher::new(flaky(Exchange::Kraken, 1), config.clone(), breaker);
+        assert!(fetcher.fetch().await.is_ok());
+
+        // Exhausting the attempts opens the circuit, and later calls are skipped
+        let fetcher = ResilientFetcher::new(flaky(Exchange::OKX, 2), config, breaker);
+        assert!(fetcher.fetch().await.is_err());
+        assert!(fetcher.breaker().is_open());
+        assert!(fetcher.fetch().await.is_err());
+    }
+
+    #[cfg(feature = "async-oracle")]
+    #[tokio::test]
+    async fn test_failover_fetcher() {
+        let failover = FailoverFetcher::new()
+            .with_fetcher(flaky(Exchange::Kraken, u32::MAX))
+            .with_fetcher(flaky(Exchange::Bitstamp, 0))
+            .with_fetcher(flaky(Exchange::OKX, 0));
+
+        let result = failover.fetch_all().await;
+        assert_eq!(result.successful, 2);
+        assert_eq!(result.errors[0].0, Exchange::Kraken);
+
+        assert_eq!(failover.fetch_first().await.unwrap().exchange, Exchange::Bitstamp);
+    }
 }
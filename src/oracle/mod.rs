@@ -2,9 +2,14 @@
 //!
 //! This module provides price feed functionality:
 //! - Multi-source price aggregation
+//! - Per-block weighted median of on-chain price updates
+//! - Registry of approved oracle operators, with slashing for equivocation
 //! - Price validation and sanity checks
+//! - Cross rates for feeds quoted in other assets (EUR, ETH/BTC), converted to USD
 //! - HTTP-based exchange price fetching
 //! - Background price update service
+//! - Signed price publishing into the protocol
+//! - ZK attestation of aggregated prices
 //! - ZK proof generation for prices
 //!
 //! ## Usage
@@ -23,14 +28,24 @@
 //! ```
 
 pub mod aggregator;
+pub mod attestation;
+pub mod batch;
 pub mod fetchers;
 pub mod price_feed;
+pub mod publisher;
+pub mod registry;
 pub mod service;
 pub mod sources;
 
 pub use aggregator::*;
+pub use attestation::{PriceAttestation, PriceAttestor};
+pub use batch::{PriceBatch, PriceSubmission, DEFAULT_ORACLE_REPUTATION};
 pub use fetchers::*;
 pub use price_feed::*;
+pub use publisher::{PricePublisher, PublishReason, PublisherConfig};
+pub use registry::{Equivocation, OracleOperator, OracleRegistry, Slash, SlashingConfig};
+#[cfg(feature = "async-oracle")]
+pub use publisher::{PriceSink, RpcPriceSink};
 pub use service::{OracleConfig, OracleState, PriceUpdate, OracleStatistics};
 #[cfg(feature = "async-oracle")]
 pub use service::OracleService;
@@ -4,8 +4,14 @@
 //! - Price storage and retrieval
 //! - Price validation
 //! - Historical price tracking
+//! - Confidence intervals and the policy deciding which operations may use
+//!   an uncertain price
+//! - Cross rates for pairs quoted in other assets, converted to USD cents
 
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
 
 use crate::error::{Error, Result};
 use crate::utils::constants::*;
@@ -27,6 +33,9 @@ pub struct PriceData {
     pub source_count: u8,
     /// Confidence score (0-100)
     pub confidence: u8,
+    /// Half-width of the confidence interval in cents, from source dispersion
+    #[serde(default)]
+    pub confidence_interval: u64,
 }
 
 impl PriceData {
@@ -37,9 +46,37 @@ impl PriceData {
             timestamp,
             source_count,
             confidence: Self::calculate_confidence(source_count),
+            confidence_interval: 0,
         }
     }
 
+    /// Set the confidence interval half-width in cents
+    pub fn with_interval(mut self, interval_cents: u64) -> Self {
+        self.confidence_interval = interval_cents;
+        self
+    }
+
+    /// Confidence interval half-width for `source_prices` around `price_cents`:
+    /// the root mean square deviation of the sources
+    pub fn dispersion_interval(price_cents: u64, source_prices: &[u64]) -> u64 {
+        if source_prices.is_empty() {
+            return 0;
+        }
+        let sum_squares: u128 = source_prices
+            .iter()
+            .map(|p| {
+                let diff = p.abs_diff(price_cents) as u128;
+                diff * diff
+            })
+            .sum();
+        ((sum_squares / source_prices.len() as u128) as f64).sqrt() as u64
+    }
+
+    /// Confidence interval half-width in basis points of the price
+    pub fn interval_bps(&self) -> u64 {
+        interval_bps(self.price_cents, self.confidence_interval)
+    }
+
     /// Calculate confidence based on source count
     fn calculate_confidence(source_count: u8) -> u8 {
         match source_count {
@@ -82,10 +119,90 @@ impl Default for PriceData {
             timestamp: 0,
             source_count: 0,
             confidence: 0,
+            confidence_interval: 0,
         }
     }
 }
 
+/// Confidence interval half-width `interval_cents` in basis points of `price_cents`
+pub fn interval_bps(price_cents: u64, interval_cents: u64) -> u64 {
+    if price_cents == 0 {
+        return if interval_cents == 0 { 0 } else { u64::MAX };
+    }
+    (interval_cents as u128 * BPS_DIVISOR as u128 / price_cents as u128).min(u64::MAX as u128) as u64
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// CONFIDENCE POLICY
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Widest confidence interval risk-increasing operations accept by default (2%)
+pub const DEFAULT_MAX_INTERVAL_BPS: u64 = 200;
+
+/// How an operation depends on the oracle price
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceUsage {
+    /// Takes on more risk against the price: minting, withdrawing collateral
+    RiskIncreasing,
+    /// Only reduces risk: repaying, depositing collateral, liquidating
+    RiskReducing,
+}
+
+/// Rules for using a price with a confidence interval.
+///
+/// Risk-increasing operations are refused while the interval is wider than
+/// `max_interval_bps` and, when `conservative` is set, are checked against
+/// the lower edge of the interval. Risk-reducing operations always use the
+/// mid price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfidencePolicy {
+    /// Widest interval accepted for risk-increasing operations, in basis points
+    pub max_interval_bps: u64,
+    /// Check risk-increasing operations against the interval's lower edge
+    pub conservative: bool,
+}
+
+impl Default for ConfidencePolicy {
+    fn default() -> Self {
+        Self {
+            max_interval_bps: DEFAULT_MAX_INTERVAL_BPS,
+            conservative: true,
+        }
+    }
+}
+
+impl ConfidencePolicy {
+    /// Policy that ignores confidence intervals
+    pub fn disabled() -> Self {
+        Self {
+            max_interval_bps: u64::MAX,
+            conservative: false,
+        }
+    }
+
+    /// Price an operation with `usage` should be checked against, or an
+    /// error if the price is too uncertain for it
+    pub fn price_for(&self, price_cents: u64, interval_cents: u64, usage: PriceUsage) -> Result<u64> {
+        if usage == PriceUsage::RiskReducing {
+            return Ok(price_cents);
+        }
+
+        let interval_bps = interval_bps(price_cents, interval_cents);
+        if interval_bps > self.max_interval_bps {
+            return Err(Error::PriceTooUncertain {
+                interval_bps,
+                max_bps: self.max_interval_bps,
+            });
+        }
+
+        Ok(if self.conservative {
+            price_cents.saturating_sub(interval_cents)
+        } else {
+            price_cents
+        })
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // PRICE FEED
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -281,6 +398,11 @@ impl PriceFeed {
         ((current - previous) * BPS_DIVISOR as i64) / previous
     }
 
+    /// Recent prices, oldest first
+    pub fn history(&self) -> &[PriceData] {
+        &self.history
+    }
+
     /// Get price volatility (standard deviation of recent prices)
     pub fn volatility(&self, window: usize) -> Option<f64> {
         let prices: Vec<_> = self.history
@@ -337,6 +459,402 @@ impl PriceFeed {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// CROSS RATES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Fixed-point scale of pair rates
+pub const RATE_PRECISION: u64 = PRICE_PRECISION;
+
+/// An asset prices can be quoted in
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub enum Asset {
+    /// US dollar
+    Usd,
+    /// Euro
+    Eur,
+    /// Bitcoin
+    Btc,
+    /// Ether
+    Eth,
+    /// zkUSD, priced on secondary markets
+    ZkUsd,
+    /// Any other asset, by upper-case symbol
+    Other(String),
+}
+
+impl Asset {
+    /// Ticker symbol
+    pub fn symbol(&self) -> &str {
+        match self {
+            Asset::Usd => "USD",
+            Asset::Eur => "EUR",
+            Asset::Btc => "BTC",
+            Asset::Eth => "ETH",
+            Asset::ZkUsd => "ZKUSD",
+            Asset::Other(symbol) => symbol,
+        }
+    }
+}
+
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl FromStr for Asset {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let symbol = s.trim().to_ascii_uppercase();
+        if symbol.is_empty() || !symbol.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(Error::InvalidParameter {
+                name: "asset".into(),
+                reason: format!("invalid asset symbol '{}'", s),
+            });
+        }
+
+        Ok(match symbol.as_str() {
+            "USD" => Asset::Usd,
+            "EUR" => Asset::Eur,
+            "BTC" => Asset::Btc,
+            "ETH" => Asset::Eth,
+            "ZKUSD" => Asset::ZkUsd,
+            _ => Asset::Other(symbol),
+        })
+    }
+}
+
+/// A price pair: the price of one `base` in units of `quote`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct TradingPair {
+    /// Asset being priced
+    pub base: Asset,
+    /// Asset the price is expressed in
+    pub quote: Asset,
+}
+
+impl TradingPair {
+    /// Create a pair
+    pub fn new(base: Asset, quote: Asset) -> Self {
+        Self { base, quote }
+    }
+
+    /// The protocol's collateral pair
+    pub fn btc_usd() -> Self {
+        Self::new(Asset::Btc, Asset::Usd)
+    }
+
+    /// zkUSD's market price, defended by the peg controller
+    pub fn zkusd_usd() -> Self {
+        Self::new(Asset::ZkUsd, Asset::Usd)
+    }
+
+    /// The same pair quoted the other way round
+    pub fn inverse(&self) -> Self {
+        Self::new(self.quote.clone(), self.base.clone())
+    }
+}
+
+impl fmt::Display for TradingPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.base, self.quote)
+    }
+}
+
+impl FromStr for TradingPair {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (base, quote) = s.split_once('/').ok_or_else(|| Error::InvalidParameter {
+            name: "pair".into(),
+            reason: format!("expected BASE/QUOTE, got '{}'", s),
+        })?;
+        Ok(Self::new(base.parse()?, quote.parse()?))
+    }
+}
+
+/// A rate observation for a pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PairRate {
+    /// Units of quote per unit of base, scaled by [`RATE_PRECISION`]
+    pub rate: u64,
+    /// Unix timestamp of the observation
+    pub timestamp: u64,
+    /// Number of sources behind the rate
+    pub source_count: u8,
+}
+
+impl PairRate {
+    /// Create a rate observation
+    pub fn new(rate: u64, timestamp: u64, source_count: u8) -> Self {
+        Self {
+            rate,
+            timestamp,
+            source_count,
+        }
+    }
+
+    /// Rate of a USD-quoted price given in cents
+    pub fn from_usd_cents(price_cents: u64, timestamp: u64, source_count: u8) -> Self {
+        let rate = price_cents as u128 * RATE_PRECISION as u128 / 100;
+        Self::new(rate.min(u64::MAX as u128) as u64, timestamp, source_count)
+    }
+
+    /// Rate of a USD-quoted [`PriceData`]
+    pub fn from_price_data(price: &PriceData) -> Self {
+        Self::from_usd_cents(price.price_cents, price.timestamp, price.source_count)
+    }
+}
+
+/// Validation settings for one pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PairConfig {
+    /// Minimum sources per observation
+    pub min_sources: usize,
+    /// Maximum age in seconds before the rate is ignored
+    pub max_staleness: u64,
+    /// Maximum change between consecutive observations, in basis points
+    pub max_deviation_bps: u64,
+}
+
+impl Default for PairConfig {
+    fn default() -> Self {
+        Self {
+            min_sources: MIN_ORACLE_SOURCES,
+            max_staleness: MAX_PRICE_STALENESS_SECS,
+            max_deviation_bps: MAX_PRICE_DEVIATION_BPS,
+        }
+    }
+}
+
+impl PairConfig {
+    /// Create pair settings
+    pub fn new(min_sources: usize, max_staleness: u64, max_deviation_bps: u64) -> Self {
+        Self {
+            min_sources,
+            max_staleness,
+            max_deviation_bps,
+        }
+    }
+}
+
+/// Settings and latest observation of one pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PairFeed {
+    config: PairConfig,
+    current: Option<PairRate>,
+}
+
+impl PairFeed {
+    fn age(&self, current_time: u64) -> Option<u64> {
+        self.current.map(|rate| current_time.saturating_sub(rate.timestamp))
+    }
+
+    fn is_fresh(&self, current_time: u64) -> bool {
+        self.age(current_time).is_some_and(|age| age <= self.config.max_staleness)
+    }
+}
+
+/// Rates for pairs quoted in any asset.
+///
+/// Pairs form a graph: any asset connected to USD through a chain of fresh
+/// pairs, in either direction, can be valued in USD cents.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CrossRateFeed {
+    pairs: BTreeMap<TradingPair, PairFeed>,
+}
+
+impl CrossRateFeed {
+    /// Create a feed with no pairs
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track `pair` with its own validation settings
+    pub fn add_pair(&mut self, pair: TradingPair, config: PairConfig) -> Result<()> {
+        if pair.base == pair.quote {
+            return Err(Error::InvalidParameter {
+                name: "pair".into(),
+                reason: format!("{} quotes an asset in itself", pair),
+            });
+        }
+        if self.pairs.contains_key(&pair.inverse()) {
+            return Err(Error::InvalidParameter {
+                name: "pair".into(),
+                reason: format!("{} is already tracked as {}", pair, pair.inverse()),
+            });
+        }
+
+        match self.pairs.get_mut(&pair) {
+            Some(feed) => feed.config = config,
+            None => {
+                self.pairs.insert(pair, PairFeed { config, current: None });
+            }
+        }
+        Ok(())
+    }
+
+    /// Tracked pairs
+    pub fn pairs(&self) -> impl Iterator<Item = &TradingPair> {
+        self.pairs.keys()
+    }
+
+    /// Settings of a tracked pair
+    pub fn config(&self, pair: &TradingPair) -> Option<&PairConfig> {
+        self.pairs.get(pair).map(|feed| &feed.config)
+    }
+
+    /// Latest observation of a tracked pair
+    pub fn rate(&self, pair: &TradingPair) -> Option<&PairRate> {
+        self.pairs.get(pair).and_then(|feed| feed.current.as_ref())
+    }
+
+    /// Record a new observation for a tracked pair, validated against its settings
+    pub fn update(&mut self, pair: &TradingPair, rate: PairRate) -> Result<()> {
+        let feed = self.pairs.get_mut(pair).ok_or_else(|| Error::InvalidParameter {
+            name: "pair".into(),
+            reason: format!("{} is not tracked", pair),
+        })?;
+
+        if rate.rate == 0 {
+            return Err(Error::InvalidParameter {
+                name: "rate".into(),
+                reason: format!("{} rate must be positive", pair),
+            });
+        }
+
+        if (rate.source_count as usize) < feed.config.min_sources {
+            return Err(Error::InsufficientOracleSources {
+                got: rate.source_count as usize,
+                need: feed.config.min_sources,
+            });
+        }
+
+        if let Some(current) = feed.current {
+            if rate.timestamp < current.timestamp {
+                return Err(Error::InvalidParameter {
+                    name: "timestamp".into(),
+                    reason: "price timestamp is older than current".into(),
+                });
+            }
+
+            let deviation = (rate.rate.abs_diff(current.rate) as u128 * BPS_DIVISOR as u128
+                / current.rate as u128) as u64;
+            if deviation > feed.config.max_deviation_bps {
+                return Err(Error::PriceDeviationTooHigh {
+                    deviation: deviation / 100,
+                    max_deviation: feed.config.max_deviation_bps / 100,
+                });
+            }
+        }
+
+        feed.current = Some(rate);
+        Ok(())
+    }
+
+    /// Whether a tracked pair has an observation within its staleness limit
+    pub fn is_fresh(&self, pair: &TradingPair, current_time: u64) -> bool {
+        self.pairs.get(pair).is_some_and(|feed| feed.is_fresh(current_time))
+    }
+
+    /// Units of `quote` per unit of `base`, scaled by [`RATE_PRECISION`],
+    /// through the shortest chain of fresh pairs
+    pub fn cross_rate(&self, base: &Asset, quote: &Asset, current_time: u64) -> Result<u64> {
+        if base == quote {
+            return Ok(RATE_PRECISION);
+        }
+
+        if let Some(route) = self.route(base, quote, |feed| feed.is_fresh(current_time)) {
+            return Ok(route
+                .iter()
+                .fold(RATE_PRECISION as u128, |acc, (pair, inverted)| {
+                    let rate = self.pairs[pair].current.map_or(1, |r| r.rate) as u128;
+                    if *inverted {
+                        acc * RATE_PRECISION as u128 / rate
+                    } else {
+                        acc * rate / RATE_PRECISION as u128
+                    }
+                })
+                .min(u64::MAX as u128) as u64);
+        }
+
+        // Report staleness when a route exists but relies on stale pairs
+        let stale = self
+            .route(base, quote, |feed| feed.current.is_some())
+            .and_then(|route| {
+                route
+                    .iter()
+                    .map(|(pair, _)| &self.pairs[pair])
+                    .filter(|feed| !feed.is_fresh(current_time))
+                    .map(|feed| (feed.age(current_time).unwrap_or(u64::MAX), feed.config.max_staleness))
+                    .max()
+            });
+
+        Err(match stale {
+            Some((last_update, max_age)) => Error::StalePrice { last_update, max_age },
+            None => Error::NoPriceRoute {
+                from: base.to_string(),
+                to: quote.to_string(),
+            },
+        })
+    }
+
+    /// Price of one unit of `asset` in USD cents
+    pub fn usd_cents(&self, asset: &Asset, current_time: u64) -> Result<u64> {
+        let rate = self.cross_rate(asset, &Asset::Usd, current_time)?;
+        Ok((rate as u128 * 100 / RATE_PRECISION as u128) as u64)
+    }
+
+    /// Breadth-first search for the shortest chain of pairs accepted by
+    /// `usable`, each step tagged with whether it is walked quote to base
+    fn route(
+        &self,
+        from: &Asset,
+        to: &Asset,
+        usable: impl Fn(&PairFeed) -> bool,
+    ) -> Option<Vec<(TradingPair, bool)>> {
+        let mut previous: BTreeMap<Asset, (TradingPair, bool)> = BTreeMap::new();
+        let mut visited = BTreeSet::from([from.clone()]);
+        let mut queue = VecDeque::from([from.clone()]);
+
+        while let Some(asset) = queue.pop_front() {
+            if &asset == to {
+                let mut route = Vec::new();
+                let mut cursor = asset;
+                while let Some((pair, inverted)) = previous.get(&cursor) {
+                    cursor = if *inverted { pair.quote.clone() } else { pair.base.clone() };
+                    route.push((pair.clone(), *inverted));
+                }
+                route.reverse();
+                return Some(route);
+            }
+
+            for (pair, feed) in &self.pairs {
+                if !usable(feed) {
+                    continue;
+                }
+                let (next, inverted) = if pair.base == asset {
+                    (&pair.quote, false)
+                } else if pair.quote == asset {
+                    (&pair.base, true)
+                } else {
+                    continue;
+                };
+                if visited.insert(next.clone()) {
+                    previous.insert(next.clone(), (pair.clone(), inverted));
+                    queue.push_back(next.clone());
+                }
+            }
+        }
+
+        None
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // PRICE PROOF
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -394,6 +912,84 @@ mod tests {
         PriceData::new(price, timestamp, sources)
     }
 
+    fn cross_feed() -> CrossRateFeed {
+        let mut feed = CrossRateFeed::new();
+        feed.add_pair(TradingPair::btc_usd(), PairConfig::default()).unwrap();
+        feed.add_pair(TradingPair::new(Asset::Eur, Asset::Usd), PairConfig::new(1, 7_200, 200)).unwrap();
+        feed.add_pair(TradingPair::new(Asset::Eth, Asset::Btc), PairConfig::new(1, 600, 1_000)).unwrap();
+
+        feed.update(&TradingPair::btc_usd(), PairRate::from_usd_cents(10_000_000, 1_000, 3)).unwrap();
+        feed.update(&TradingPair::new(Asset::Eur, Asset::Usd), PairRate::new(108_000_000, 1_000, 1)).unwrap();
+        feed.update(&TradingPair::new(Asset::Eth, Asset::Btc), PairRate::new(5_000_000, 1_000, 1)).unwrap();
+        feed
+    }
+
+    #[test]
+    fn test_cross_rates() {
+        let feed = cross_feed();
+
+        assert_eq!(feed.usd_cents(&Asset::Btc, 1_100).unwrap(), 10_000_000);
+        assert_eq!(feed.usd_cents(&Asset::Eur, 1_100).unwrap(), 108);
+        // ETH/BTC 0.05 at $100,000 per BTC
+        assert_eq!(feed.usd_cents(&Asset::Eth, 1_100).unwrap(), 500_000);
+        assert_eq!(feed.usd_cents(&Asset::Usd, 1_100).unwrap(), 100);
+
+        // BTC in EUR walks BTC/USD, then EUR/USD inverted
+        assert_eq!(feed.cross_rate(&Asset::Btc, &Asset::Eur, 1_100).unwrap(), 9_259_259_259_259);
+
+        assert!(matches!(
+            feed.usd_cents(&Asset::Other("SOL".into()), 1_100),
+            Err(Error::NoPriceRoute { .. })
+        ));
+    }
+
+    #[test]
+    fn test_cross_rate_staleness_per_pair() {
+        let feed = cross_feed();
+
+        // ETH/BTC goes stale after 600s, the others still route
+        assert!(feed.is_fresh(&TradingPair::new(Asset::Eur, Asset::Usd), 2_000));
+        assert!(!feed.is_fresh(&TradingPair::new(Asset::Eth, Asset::Btc), 2_000));
+        assert!(feed.usd_cents(&Asset::Eur, 2_000).is_ok());
+        assert!(matches!(
+            feed.usd_cents(&Asset::Eth, 2_000),
+            Err(Error::StalePrice { last_update: 1_000, max_age: 600 })
+        ));
+    }
+
+    #[test]
+    fn test_cross_rate_updates_validated_per_pair() {
+        let mut feed = cross_feed();
+        let eur = TradingPair::new(Asset::Eur, Asset::Usd);
+
+        // 3% move exceeds EUR/USD's 2% limit
+        assert!(matches!(
+            feed.update(&eur, PairRate::new(111_240_000, 1_100, 1)),
+            Err(Error::PriceDeviationTooHigh { .. })
+        ));
+        feed.update(&eur, PairRate::new(109_000_000, 1_100, 1)).unwrap();
+
+        // BTC/USD keeps the default source minimum
+        assert!(matches!(
+            feed.update(&TradingPair::btc_usd(), PairRate::from_usd_cents(10_100_000, 1_100, 1)),
+            Err(Error::InsufficientOracleSources { .. })
+        ));
+
+        assert!(feed.update(&TradingPair::new(Asset::Usd, Asset::Eur), PairRate::new(1, 1_100, 1)).is_err());
+        assert!(feed.add_pair(TradingPair::new(Asset::Usd, Asset::Eur), PairConfig::default()).is_err());
+        assert!(feed.add_pair(TradingPair::new(Asset::Btc, Asset::Btc), PairConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_pair_parsing() {
+        let pair: TradingPair = "eth/btc".parse().unwrap();
+        assert_eq!(pair, TradingPair::new(Asset::Eth, Asset::Btc));
+        assert_eq!(pair.to_string(), "ETH/BTC");
+        assert_eq!("sol".parse::<Asset>().unwrap(), Asset::Other("SOL".into()));
+        assert!("BTCUSD".parse::<TradingPair>().is_err());
+        assert!("BTC/".parse::<TradingPair>().is_err());
+    }
+
     #[test]
     fn test_price_data_creation() {
         let price = make_price(10_000_000, 1000, 3);
@@ -403,6 +999,39 @@ mod tests {
         assert!(price.confidence >= 70);
     }
 
+    #[test]
+    fn test_dispersion_interval() {
+        assert_eq!(PriceData::dispersion_interval(10_000_000, &[]), 0);
+        assert_eq!(PriceData::dispersion_interval(10_000_000, &[10_000_000; 3]), 0);
+        // Deviations of 30k, 0 and 40k: RMS = sqrt((9e8 + 1.6e9) / 3)
+        assert_eq!(
+            PriceData::dispersion_interval(10_000_000, &[9_970_000, 10_000_000, 10_040_000]),
+            28_867
+        );
+
+        let price = make_price(10_000_000, 1000, 3).with_interval(50_000);
+        assert_eq!(price.interval_bps(), 50);
+    }
+
+    #[test]
+    fn test_confidence_policy() {
+        let policy = ConfidencePolicy::default();
+
+        // 1% interval: allowed, checked against the lower edge
+        assert_eq!(policy.price_for(10_000_000, 100_000, PriceUsage::RiskIncreasing).unwrap(), 9_900_000);
+        assert_eq!(policy.price_for(10_000_000, 100_000, PriceUsage::RiskReducing).unwrap(), 10_000_000);
+
+        // 5% interval: risk-increasing operations are refused
+        assert!(matches!(
+            policy.price_for(10_000_000, 500_000, PriceUsage::RiskIncreasing),
+            Err(Error::PriceTooUncertain { interval_bps: 500, max_bps: 200 })
+        ));
+        assert_eq!(policy.price_for(10_000_000, 500_000, PriceUsage::RiskReducing).unwrap(), 10_000_000);
+
+        let disabled = ConfidencePolicy::disabled();
+        assert_eq!(disabled.price_for(10_000_000, 500_000, PriceUsage::RiskIncreasing).unwrap(), 10_000_000);
+    }
+
     #[test]
     fn test_price_freshness() {
         let price = make_price(10_000_000, 1000, 3);
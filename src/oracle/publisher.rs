@@ -0,0 +1,378 @@
+//! Oracle price publisher.
+//!
+//! Bridges the [`OracleService`](crate::oracle::service) to the protocol:
+//! aggregated prices are turned into signed [`UpdatePriceOp`]s and submitted
+//! to a local state machine or a remote RPC node. To avoid flooding the chain
+//! with updates, a price is only published when it moves by more than the
+//! deviation threshold or when the heartbeat interval has elapsed.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::oracle::price_feed::PriceData;
+use crate::oracle::service::PriceUpdate;
+use crate::protocol::operations::{Operation, UpdatePriceOp};
+use crate::protocol::signing::SigningDomain;
+use crate::utils::constants::SIGNATURE_LENGTH;
+use crate::utils::crypto::{KeyPair, PublicKey, Signature};
+
+#[cfg(feature = "async-oracle")]
+use std::future::Future;
+#[cfg(feature = "async-oracle")]
+use std::sync::Arc;
+#[cfg(feature = "async-oracle")]
+use tokio::sync::{broadcast, Mutex};
+
+#[cfg(feature = "async-oracle")]
+use crate::error::Error;
+#[cfg(feature = "async-oracle")]
+use crate::protocol::operations::ProtocolOperation;
+#[cfg(feature = "async-oracle")]
+use crate::protocol::state_machine::ProtocolStateMachine;
+#[cfg(feature = "async-oracle")]
+use crate::storage::backend::StorageBackend;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// CONFIGURATION
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Publishing rules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublisherConfig {
+    /// Publish when the price moves at least this much (basis points)
+    pub deviation_bps: u64,
+    /// Publish at least this often even if the price is flat (seconds)
+    pub heartbeat_secs: u64,
+}
+
+impl Default for PublisherConfig {
+    fn default() -> Self {
+        Self {
+            deviation_bps: 50, // 0.5%
+            heartbeat_secs: 600,
+        }
+    }
+}
+
+/// Why a price was published
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PublishReason {
+    /// Nothing published yet
+    Initial,
+    /// Price moved by the given basis points
+    Deviation(u64),
+    /// Heartbeat interval elapsed
+    Heartbeat,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// PUBLISHER
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Signs aggregated prices as protocol operations
+pub struct PricePublisher {
+    /// Oracle signing key
+    keypair: KeyPair,
+    /// Publishing rules
+    config: PublisherConfig,
+    /// Last published price and its timestamp
+    last_published: Option<(u64, u64)>,
+    /// Last nonce used
+    nonce: u64,
+    /// Domain price operations are signed for
+    domain: SigningDomain,
+}
+
+impl PricePublisher {
+    /// Create a publisher signing with `keypair`
+    pub fn new(keypair: KeyPair, config: PublisherConfig) -> Self {
+        Self { keypair, config, last_published: None, nonce: 0, domain: SigningDomain::default() }
+    }
+
+    /// Sign for the network described by `domain`
+    pub fn with_signing_domain(mut self, domain: SigningDomain) -> Self {
+        self.domain = domain;
+        self
+    }
+
+    /// Continue from the last nonce accepted for this oracle key
+    pub fn with_nonce(mut self, last_nonce: u64) -> Self {
+        self.nonce = last_nonce;
+        self
+    }
+
+    /// Oracle public key
+    pub fn operator(&self) -> &PublicKey {
+        self.keypair.public_key()
+    }
+
+    /// Publishing rules
+    pub fn config(&self) -> &PublisherConfig {
+        &self.config
+    }
+
+    /// Last published price and timestamp
+    pub fn last_published(&self) -> Option<(u64, u64)> {
+        self.last_published
+    }
+
+    /// Whether `update` should be published, and why
+    pub fn should_publish(&self, update: &PriceUpdate) -> Option<PublishReason> {
+        let Some((last_price, last_time)) = self.last_published else {
+            return Some(PublishReason::Initial);
+        };
+
+        if last_price > 0 {
+            let change = (update.price_cents.abs_diff(last_price) as u128 * 10_000
+                / last_price as u128) as u64;
+            if change >= self.config.deviation_bps {
+                return Some(PublishReason::Deviation(change));
+            }
+        }
+
+        if update.timestamp.saturating_sub(last_time) >= self.config.heartbeat_secs {
+            return Some(PublishReason::Heartbeat);
+        }
+
+        None
+    }
+
+    /// Sign `update` as a price operation, regardless of the publishing rules
+    pub fn sign_update(&mut self, update: &PriceUpdate) -> Result<UpdatePriceOp> {
+        let source_count = update.source_count.min(u8::MAX as usize) as u8;
+
+        let mut op = UpdatePriceOp {
+            operator: *self.keypair.public_key(),
+            price_cents: update.price_cents,
+            source_count,
+            confidence: PriceData::new(update.price_cents, update.timestamp, source_count).confidence,
+            confidence_interval: update.std_deviation,
+            proof: match &update.attestation {
+                Some(attestation) => attestation.encode()?,
+                None => Vec::new(),
+            },
+            nonce: self.nonce + 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        op.sign(&self.keypair, &self.domain)?;
+
+        self.nonce = op.nonce;
+        Ok(op)
+    }
+
+    /// Apply the publishing rules to `update`, signing it if it is due.
+    ///
+    /// The update counts as published once this returns; call
+    /// [`rollback`](Self::rollback) if submission fails.
+    pub fn process(&mut self, update: &PriceUpdate) -> Result<Option<(UpdatePriceOp, PublishReason)>> {
+        let Some(reason) = self.should_publish(update) else {
+            return Ok(None);
+        };
+
+        let op = self.sign_update(update)?;
+        self.last_published = Some((update.price_cents, update.timestamp));
+        Ok(Some((op, reason)))
+    }
+
+    /// Forget a publication that was not accepted, restoring the previous state
+    pub fn rollback(&mut self, previous: Option<(u64, u64)>, previous_nonce: u64) {
+        self.last_published = previous;
+        self.nonce = previous_nonce;
+    }
+
+    /// Last nonce used
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// SINKS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Destination for signed price operations
+#[cfg(feature = "async-oracle")]
+pub trait PriceSink {
+    /// Submit a signed price operation
+    fn submit(&self, op: UpdatePriceOp) -> impl Future<Output = Result<()>> + Send;
+}
+
+/// Submit directly to a local state machine
+#[cfg(feature = "async-oracle")]
+impl<B: StorageBackend + Send> PriceSink for Arc<Mutex<ProtocolStateMachine<B>>> {
+    async fn submit(&self, op: UpdatePriceOp) -> Result<()> {
+        let mut machine = self.lock().await;
+        machine.execute(ProtocolOperation::UpdatePrice(op)).map(|_| ())
+    }
+}
+
+/// Submit to a remote node's `POST /price/signed` endpoint
+#[cfg(feature = "async-oracle")]
+pub struct RpcPriceSink {
+    /// HTTP client
+    client: reqwest::Client,
+    /// Node base URL
+    url: String,
+}
+
+#[cfg(feature = "async-oracle")]
+impl RpcPriceSink {
+    /// Create a sink for the node at `url`
+    pub fn new(url: impl Into<String>, timeout_ms: u64) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_millis(timeout_ms))
+            .build()
+            .map_err(|e| Error::Internal(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self { client, url: url.into() })
+    }
+}
+
+#[cfg(feature = "async-oracle")]
+impl PriceSink for RpcPriceSink {
+    async fn submit(&self, op: UpdatePriceOp) -> Result<()> {
+        let url = format!("{}/price/signed", self.url.trim_end_matches('/'));
+        self.client
+            .post(&url)
+            .json(&op)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| Error::Internal(format!("Price submission failed: {}", e)))?;
+        Ok(())
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// BACKGROUND TASK
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[cfg(feature = "async-oracle")]
+impl PricePublisher {
+    /// Publish updates from an oracle service subscription until it closes
+    pub fn spawn<S: PriceSink + Send + Sync + 'static>(
+        mut self,
+        mut updates: broadcast::Receiver<PriceUpdate>,
+        sink: S,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let update = match updates.recv().await {
+                    Ok(update) => update,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Price publisher lagged, skipped {} updates", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                let previous = self.last_published;
+                let previous_nonce = self.nonce;
+
+                let (op, reason) = match self.process(&update) {
+                    Ok(Some(due)) => due,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        tracing::error!("Failed to sign price update: {}", e);
+                        continue;
+                    }
+                };
+
+                let price_cents = op.price_cents;
+                match sink.submit(op).await {
+                    Ok(()) => tracing::info!("Published price {} cents ({:?})", price_cents, reason),
+                    Err(e) => {
+                        tracing::warn!("Failed to publish price {} cents: {}", price_cents, e);
+                        self.rollback(previous, previous_nonce);
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::crypto::verify_signature;
+
+    fn update(price_cents: u64, timestamp: u64) -> PriceUpdate {
+        PriceUpdate {
+            price_cents,
+            timestamp,
+            source_count: 4,
+            std_deviation: 0,
+            min_price: price_cents,
+            max_price: price_cents,
+            sequence: 0,
+            attestation: None,
+        }
+    }
+
+    #[test]
+    fn test_deviation_and_heartbeat() {
+        let mut publisher = PricePublisher::new(
+            KeyPair::generate(),
+            PublisherConfig { deviation_bps: 100, heartbeat_secs: 60 },
+        );
+
+        let (_, reason) = publisher.process(&update(10_000_000, 1_000)).unwrap().unwrap();
+        assert_eq!(reason, PublishReason::Initial);
+
+        // 0.5% move inside the heartbeat window is ignored
+        assert!(publisher.process(&update(10_050_000, 1_030)).unwrap().is_none());
+
+        // 1% move publishes immediately
+        let (_, reason) = publisher.process(&update(10_100_000, 1_031)).unwrap().unwrap();
+        assert_eq!(reason, PublishReason::Deviation(100));
+
+        // Flat price is republished after the heartbeat
+        assert!(publisher.process(&update(10_100_000, 1_090)).unwrap().is_none());
+        let (_, reason) = publisher.process(&update(10_100_000, 1_091)).unwrap().unwrap();
+        assert_eq!(reason, PublishReason::Heartbeat);
+    }
+
+    #[test]
+    fn test_signed_op_verifies() {
+        let mut publisher = PricePublisher::new(KeyPair::generate(), PublisherConfig::default())
+            .with_nonce(41);
+
+        let op = publisher.sign_update(&update(9_500_000, 1_000)).unwrap();
+        assert_eq!(op.nonce, 42);
+        assert_eq!(op.operator, *publisher.operator());
+        assert!(verify_signature(&op.operator, &op.signing_hash(&SigningDomain::default()).unwrap(), &op.signature));
+
+        let mut tampered = op.clone();
+        tampered.price_cents += 1;
+        assert!(!verify_signature(&tampered.operator, &tampered.signing_hash(&SigningDomain::default()).unwrap(), &tampered.signature));
+    }
+
+    #[test]
+    fn test_state_machine_accepts_published_price() {
+        use crate::protocol::operations::ProtocolOperation;
+        use crate::protocol::state_machine::ProtocolStateMachine;
+        use crate::storage::backend::InMemoryStore;
+
+        let mut machine = ProtocolStateMachine::new(InMemoryStore::new()).unwrap();
+        let mut publisher = PricePublisher::new(KeyPair::generate(), PublisherConfig::default());
+
+        let op = publisher.sign_update(&update(9_500_000, 1_000)).unwrap();
+        machine.execute(ProtocolOperation::UpdatePrice(op.clone())).unwrap();
+        assert_eq!(machine.price(), 9_500_000);
+
+        // Replaying the same nonce is rejected
+        assert!(machine.execute(ProtocolOperation::UpdatePrice(op)).is_err());
+    }
+
+    #[test]
+    fn test_rollback_restores_nonce() {
+        let mut publisher = PricePublisher::new(KeyPair::generate(), PublisherConfig::default());
+
+        publisher.process(&update(10_000_000, 1_000)).unwrap().unwrap();
+        publisher.rollback(None, 0);
+
+        assert_eq!(publisher.nonce(), 0);
+        let (op, reason) = publisher.process(&update(10_000_000, 1_001)).unwrap().unwrap();
+        assert_eq!((op.nonce, reason), (1, PublishReason::Initial));
+    }
+}
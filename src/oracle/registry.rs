@@ -0,0 +1,365 @@
+//! Registry of approved oracle operators.
+//!
+//! Governance approves the operators whose price updates the protocol
+//! accepts. Each has a weight in the per-block price median and may have
+//! posted a stake, held outside the protocol.
+//!
+//! An operator that signs two different prices under the same nonce has
+//! equivocated, and the two signatures prove it. Anyone can report the pair
+//! as [`Equivocation`] evidence. The report slashes the operator's weight and
+//! stake by the configured shares. Custody acts on the slashed stake reported
+//! in the result. An operator slashed to zero weight can no longer update
+//! the price.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use crate::core::vault::CollateralAmount;
+use crate::error::{Error, Result};
+use crate::protocol::operations::{Operation, UpdatePriceOp};
+use crate::protocol::signing::SigningDomain;
+use crate::utils::constants::BPS_DIVISOR;
+use crate::utils::crypto::{verify_signature, Hash, PublicKey};
+
+/// Share of its weight an equivocating operator loses by default (all of it)
+pub const DEFAULT_WEIGHT_SLASH_BPS: u64 = BPS_DIVISOR;
+
+/// Share of its stake an equivocating operator loses by default (50%)
+pub const DEFAULT_STAKE_SLASH_BPS: u64 = 5_000;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// OPERATORS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// An approved oracle operator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OracleOperator {
+    /// Key the operator signs price updates with
+    pub pubkey: PublicKey,
+    /// Weight in the per-block price median
+    pub weight: u64,
+    /// Stake the operator has posted, if any
+    pub stake: Option<CollateralAmount>,
+    /// Times the operator has been slashed
+    pub slash_count: u32,
+}
+
+impl OracleOperator {
+    /// Create an operator with `weight` and no stake
+    pub fn new(pubkey: PublicKey, weight: u64) -> Self {
+        Self {
+            pubkey,
+            weight,
+            stake: None,
+            slash_count: 0,
+        }
+    }
+
+    /// Set the posted stake
+    pub fn with_stake(mut self, stake: CollateralAmount) -> Self {
+        self.stake = Some(stake);
+        self
+    }
+}
+
+/// Shares of weight and stake a slash takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlashingConfig {
+    /// Share of the operator's weight removed, in basis points
+    pub weight_slash_bps: u64,
+    /// Share of the operator's stake removed, in basis points
+    pub stake_slash_bps: u64,
+}
+
+impl Default for SlashingConfig {
+    fn default() -> Self {
+        Self {
+            weight_slash_bps: DEFAULT_WEIGHT_SLASH_BPS,
+            stake_slash_bps: DEFAULT_STAKE_SLASH_BPS,
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// EVIDENCE
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Two price updates an operator signed with the same nonce
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct Equivocation {
+    /// One update
+    pub first: UpdatePriceOp,
+    /// A contradicting update
+    pub second: UpdatePriceOp,
+}
+
+impl Equivocation {
+    /// Operator the evidence is against
+    pub fn operator(&self) -> &PublicKey {
+        &self.first.operator
+    }
+
+    /// Nonce both updates were signed with
+    pub fn nonce(&self) -> u64 {
+        self.first.nonce
+    }
+
+    /// Check both updates are signed by the same operator for `domain`, with
+    /// the same nonce and different prices
+    pub fn verify(&self, domain: &SigningDomain) -> Result<()> {
+        let invalid = |reason: &str| Error::InvalidParameter {
+            name: "evidence".into(),
+            reason: reason.into(),
+        };
+
+        if self.first.operator != self.second.operator {
+            return Err(invalid("Updates are from different operators"));
+        }
+        if self.first.nonce != self.second.nonce {
+            return Err(invalid("Updates have different nonces"));
+        }
+        if self.first.price_cents == self.second.price_cents {
+            return Err(invalid("Updates do not contradict each other"));
+        }
+        for op in [&self.first, &self.second] {
+            if !verify_signature(&op.operator, &op.signing_hash(domain)?, &op.signature) {
+                return Err(Error::InvalidSignature);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Identifier of the offence, the same whichever updates prove it
+    fn id(&self) -> [u8; 32] {
+        let mut data = self.operator().as_bytes().to_vec();
+        data.extend_from_slice(&self.nonce().to_be_bytes());
+        *Hash::sha256(&data).as_bytes()
+    }
+}
+
+/// Outcome of a slash
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Slash {
+    /// Operator slashed
+    pub operator: PublicKey,
+    /// Weight removed
+    pub weight_slashed: u64,
+    /// Weight left
+    pub weight: u64,
+    /// Stake removed, for custody to seize
+    pub stake_slashed: CollateralAmount,
+    /// Stake left
+    pub stake: Option<CollateralAmount>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// REGISTRY
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Approved oracle operators
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OracleRegistry {
+    /// Operators in registration order
+    operators: Vec<OracleOperator>,
+    /// Shares a slash takes
+    slashing: SlashingConfig,
+    /// Offences already slashed
+    slashed: BTreeSet<[u8; 32]>,
+}
+
+impl OracleRegistry {
+    /// Create an empty registry with the default slashing shares
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the slashing shares
+    pub fn with_slashing(mut self, slashing: SlashingConfig) -> Result<Self> {
+        if slashing.weight_slash_bps > BPS_DIVISOR || slashing.stake_slash_bps > BPS_DIVISOR {
+            return Err(Error::InvalidParameter {
+                name: "slashing".into(),
+                reason: format!("Shares cannot exceed {}bps", BPS_DIVISOR),
+            });
+        }
+        self.slashing = slashing;
+        Ok(self)
+    }
+
+    /// Add an operator, or replace one with the same key. Returns the
+    /// replaced operator.
+    pub fn register(&mut self, operator: OracleOperator) -> Result<Option<OracleOperator>> {
+        if operator.weight == 0 {
+            return Err(Error::InvalidParameter {
+                name: "weight".into(),
+                reason: "Operators need a non-zero weight".into(),
+            });
+        }
+
+        match self.operators.iter_mut().find(|o| o.pubkey == operator.pubkey) {
+            Some(existing) => Ok(Some(std::mem::replace(existing, operator))),
+            None => {
+                self.operators.push(operator);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Remove an operator
+    pub fn remove(&mut self, pubkey: &PublicKey) -> Result<OracleOperator> {
+        let index = self
+            .operators
+            .iter()
+            .position(|o| &o.pubkey == pubkey)
+            .ok_or_else(|| Error::InvalidParameter {
+                name: "operator".into(),
+                reason: "Not a registered oracle operator".into(),
+            })?;
+        Ok(self.operators.remove(index))
+    }
+
+    /// Look up an operator
+    pub fn get(&self, pubkey: &PublicKey) -> Option<&OracleOperator> {
+        self.operators.iter().find(|o| &o.pubkey == pubkey)
+    }
+
+    /// Registered operators
+    pub fn operators(&self) -> &[OracleOperator] {
+        &self.operators
+    }
+
+    /// Slashing shares
+    pub fn slashing(&self) -> &SlashingConfig {
+        &self.slashing
+    }
+
+    /// Check `pubkey` may update the price, returning its weight
+    pub fn authorize(&self, pubkey: &PublicKey) -> Result<u64> {
+        match self.get(pubkey) {
+            Some(operator) if operator.weight > 0 => Ok(operator.weight),
+            Some(_) => Err(Error::Unauthorized("Oracle operator has been slashed to zero weight".into())),
+            None => Err(Error::Unauthorized("Not a registered oracle operator".into())),
+        }
+    }
+
+    /// Verify `evidence` and slash the operator it proves equivocated. Each
+    /// offence is slashed once.
+    pub fn report(&mut self, evidence: &Equivocation, domain: &SigningDomain) -> Result<Slash> {
+        evidence.verify(domain)?;
+
+        let id = evidence.id();
+        if self.slashed.contains(&id) {
+            return Err(Error::InvalidParameter {
+                name: "evidence".into(),
+                reason: "Offence already slashed".into(),
+            });
+        }
+
+        let slashing = self.slashing;
+        let operator = self
+            .operators
+            .iter_mut()
+            .find(|o| &o.pubkey == evidence.operator())
+            .ok_or_else(|| Error::InvalidParameter {
+                name: "operator".into(),
+                reason: "Not a registered oracle operator".into(),
+            })?;
+
+        let weight_slashed = share(operator.weight, slashing.weight_slash_bps);
+        operator.weight -= weight_slashed;
+        let stake_slashed = operator.stake.map_or(0, |stake| share(stake.sats(), slashing.stake_slash_bps));
+        operator.stake = operator
+            .stake
+            .map(|stake| CollateralAmount::from_sats(stake.sats() - stake_slashed));
+        operator.slash_count += 1;
+        self.slashed.insert(id);
+
+        Ok(Slash {
+            operator: operator.pubkey,
+            weight_slashed,
+            weight: operator.weight,
+            stake_slashed: CollateralAmount::from_sats(stake_slashed),
+            stake: operator.stake,
+        })
+    }
+}
+
+/// `bps` of `amount`, rounded down
+fn share(amount: u64, bps: u64) -> u64 {
+    (amount as u128 * bps as u128 / BPS_DIVISOR as u128) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::constants::SIGNATURE_LENGTH;
+    use crate::utils::crypto::{KeyPair, Signature};
+
+    fn update(oracle: &KeyPair, price_cents: u64, nonce: u64) -> UpdatePriceOp {
+        let mut op = UpdatePriceOp {
+            operator: *oracle.public_key(),
+            price_cents,
+            source_count: 3,
+            confidence: 90,
+            confidence_interval: 0,
+            proof: Vec::new(),
+            nonce,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        op.sign(oracle, &SigningDomain::default()).unwrap();
+        op
+    }
+
+    #[test]
+    fn test_register_and_authorize() {
+        let oracle = KeyPair::generate();
+        let mut registry = OracleRegistry::new();
+        assert!(registry.authorize(oracle.public_key()).is_err());
+        assert!(registry.register(OracleOperator::new(*oracle.public_key(), 0)).is_err());
+
+        registry.register(OracleOperator::new(*oracle.public_key(), 50)).unwrap();
+        assert_eq!(registry.authorize(oracle.public_key()).unwrap(), 50);
+
+        let replaced = registry.register(OracleOperator::new(*oracle.public_key(), 80)).unwrap();
+        assert_eq!(replaced.unwrap().weight, 50);
+        assert_eq!(registry.operators().len(), 1);
+
+        registry.remove(oracle.public_key()).unwrap();
+        assert!(registry.remove(oracle.public_key()).is_err());
+    }
+
+    #[test]
+    fn test_equivocation_slashes_once() {
+        let oracle = KeyPair::generate();
+        let domain = SigningDomain::default();
+        let mut registry = OracleRegistry::new()
+            .with_slashing(SlashingConfig { weight_slash_bps: 5_000, stake_slash_bps: 2_500 })
+            .unwrap();
+        registry
+            .register(OracleOperator::new(*oracle.public_key(), 80).with_stake(CollateralAmount::from_sats(1_000_000)))
+            .unwrap();
+
+        // Not contradictory, or not the same nonce
+        let agreeing = Equivocation { first: update(&oracle, 10_000_000, 4), second: update(&oracle, 10_000_000, 4) };
+        assert!(registry.report(&agreeing, &domain).is_err());
+        let successive = Equivocation { first: update(&oracle, 10_000_000, 4), second: update(&oracle, 9_000_000, 5) };
+        assert!(registry.report(&successive, &domain).is_err());
+
+        // A forged second signature proves nothing
+        let mut forged = Equivocation { first: update(&oracle, 10_000_000, 4), second: update(&oracle, 9_000_000, 4) };
+        forged.second.signature = KeyPair::generate().sign(&forged.second.signing_hash(&domain).unwrap());
+        assert!(matches!(registry.report(&forged, &domain), Err(Error::InvalidSignature)));
+
+        let evidence = Equivocation { first: update(&oracle, 10_000_000, 4), second: update(&oracle, 9_000_000, 4) };
+        let slash = registry.report(&evidence, &domain).unwrap();
+        assert_eq!((slash.weight_slashed, slash.weight), (40, 40));
+        assert_eq!(slash.stake_slashed, CollateralAmount::from_sats(250_000));
+        assert_eq!(slash.stake, Some(CollateralAmount::from_sats(750_000)));
+        assert_eq!(registry.get(oracle.public_key()).unwrap().slash_count, 1);
+
+        // The same offence, proven with another contradicting update
+        let again = Equivocation { first: update(&oracle, 10_000_000, 4), second: update(&oracle, 8_000_000, 4) };
+        assert!(registry.report(&again, &domain).is_err());
+    }
+}
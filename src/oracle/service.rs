@@ -4,6 +4,7 @@
 //! - Periodically fetches prices from multiple exchanges
 //! - Aggregates prices using median calculation
 //! - Validates price data against safety thresholds
+//! - Optionally proves each price with a ZK attestation
 //! - Publishes updates to subscribers
 //!
 //! ## Usage
@@ -28,6 +29,8 @@
 use tokio::sync::{broadcast, RwLock};
 #[cfg(feature = "async-oracle")]
 use tokio::time::{interval, Duration};
+#[cfg(feature = "async-oracle")]
+use tracing::Instrument;
 
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -35,6 +38,9 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use serde::{Deserialize, Serialize};
 
 use crate::error::{Error, Result};
+use crate::oracle::attestation::PriceAttestation;
+#[cfg(feature = "async-oracle")]
+use crate::oracle::attestation::PriceAttestor;
 #[cfg(feature = "async-oracle")]
 use crate::oracle::fetchers::{HttpPriceFetcher, FetchResult};
 use crate::oracle::fetchers::HttpFetcherConfig;
@@ -125,6 +131,9 @@ pub struct PriceUpdate {
     pub max_price: u64,
     /// Update sequence number
     pub sequence: u64,
+    /// Proof that the price is the median of the sources, if attested
+    #[serde(default)]
+    pub attestation: Option<PriceAttestation>,
 }
 
 impl PriceUpdate {
@@ -151,6 +160,7 @@ impl PriceUpdate {
             min_price,
             max_price,
             sequence,
+            attestation: None,
         })
     }
 
@@ -250,6 +260,8 @@ pub struct OracleService {
     fetcher: Arc<HttpPriceFetcher>,
     /// Shutdown signal
     shutdown: Arc<RwLock<bool>>,
+    /// Proves each aggregated price, if configured
+    attestor: Option<Arc<PriceAttestor>>,
 }
 
 #[cfg(feature = "async-oracle")]
@@ -275,9 +287,16 @@ impl OracleService {
             tx,
             fetcher: Arc::new(fetcher),
             shutdown: Arc::new(RwLock::new(false)),
+            attestor: None,
         })
     }
 
+    /// Attach a ZK attestation to every published price
+    pub fn with_attestor(mut self, attestor: PriceAttestor) -> Self {
+        self.attestor = Some(Arc::new(attestor));
+        self
+    }
+
     /// Create with default configuration
     pub async fn with_defaults() -> Result<Self> {
         Self::new(OracleConfig::default()).await
@@ -290,6 +309,7 @@ impl OracleService {
         let tx = self.tx.clone();
         let fetcher = Arc::clone(&self.fetcher);
         let shutdown = Arc::clone(&self.shutdown);
+        let attestor = self.attestor.clone();
 
         tokio::spawn(async move {
             let mut interval = interval(Duration::from_secs(config.update_interval_secs));
@@ -309,15 +329,25 @@ impl OracleService {
                 }
 
                 // Fetch prices
+                let round = tracing::info_span!(
+                    "oracle_round",
+                    sources = tracing::field::Empty,
+                    price_cents = tracing::field::Empty,
+                );
                 let start = std::time::Instant::now();
 
-                let collection = if config.major_exchanges_only {
-                    fetcher.fetch_major().await
-                } else {
-                    fetcher.fetch_all().await
-                };
+                let collection = async {
+                    if config.major_exchanges_only {
+                        fetcher.fetch_major().await
+                    } else {
+                        fetcher.fetch_all().await
+                    }
+                }
+                .instrument(round.clone())
+                .await;
 
                 let duration_ms = start.elapsed().as_millis() as u64;
+                round.record("sources", collection.len());
 
                 // Update state
                 let mut s = state.write().await;
@@ -326,9 +356,9 @@ impl OracleService {
                 if collection.len() < config.min_sources {
                     s.failed_updates += 1;
                     tracing::warn!(
-                        "Insufficient price sources: {} < {}",
-                        collection.len(),
-                        config.min_sources
+                        parent: &round,
+                        min_sources = config.min_sources,
+                        "Insufficient price sources"
                     );
                     continue;
                 }
@@ -354,9 +384,10 @@ impl OracleService {
                         if max_dev > config.max_deviation_bps {
                             s.failed_updates += 1;
                             tracing::warn!(
-                                "Price deviation too high: {} bps > {} bps",
-                                max_dev,
-                                config.max_deviation_bps
+                                parent: &round,
+                                deviation_bps = max_dev,
+                                max_deviation_bps = config.max_deviation_bps,
+                                "Price deviation too high"
                             );
                             continue;
                         }
@@ -372,9 +403,10 @@ impl OracleService {
                             if change as u64 > config.max_price_change_bps {
                                 s.failed_updates += 1;
                                 tracing::warn!(
-                                    "Price change too large: {} bps > {} bps",
-                                    change,
-                                    config.max_price_change_bps
+                                    parent: &round,
+                                    change_bps = change as u64,
+                                    max_change_bps = config.max_price_change_bps,
+                                    "Price change too large"
                                 );
                                 continue;
                             }
@@ -384,7 +416,19 @@ impl OracleService {
 
                 // Create price update
                 s.sequence += 1;
-                if let Some(update) = PriceUpdate::from_collection(&collection, s.sequence) {
+                if let Some(mut update) = PriceUpdate::from_collection(&collection, s.sequence) {
+                    // Unproven prices are not published when attesting
+                    if let Some(attestor) = &attestor {
+                        match attest(attestor, collection, &update).instrument(round.clone()).await {
+                            Ok(attestation) => update.attestation = Some(attestation),
+                            Err(e) => {
+                                s.failed_updates += 1;
+                                tracing::warn!(parent: &round, error = %e, "Price attestation failed");
+                                continue;
+                            }
+                        }
+                    }
+
                     // Update average latency
                     let total = s.total_updates;
                     if total > 0 {
@@ -399,12 +443,8 @@ impl OracleService {
                     // Broadcast update
                     let _ = tx.send(update);
 
-                    tracing::info!(
-                        "Price updated: {} cents from {} sources in {}ms",
-                        s.last_update.as_ref().unwrap().price_cents,
-                        collection.len(),
-                        duration_ms
-                    );
+                    round.record("price_cents", s.last_update.as_ref().unwrap().price_cents);
+                    tracing::info!(parent: &round, duration_ms, "Price updated");
                 }
             }
 
@@ -446,6 +486,7 @@ impl OracleService {
     }
 
     /// Trigger a manual price fetch
+    #[tracing::instrument(name = "oracle_fetch_now", skip_all)]
     pub async fn fetch_now(&self) -> Result<PriceUpdate> {
         let collection = if self.config.major_exchanges_only {
             self.fetcher.fetch_major().await
@@ -461,12 +502,19 @@ impl OracleService {
             )));
         }
 
-        let mut state = self.state.write().await;
-        state.sequence += 1;
+        let sequence = {
+            let mut state = self.state.write().await;
+            state.sequence += 1;
+            state.sequence
+        };
 
-        PriceUpdate::from_collection(&collection, state.sequence).ok_or_else(|| {
+        let mut update = PriceUpdate::from_collection(&collection, sequence).ok_or_else(|| {
             Error::Internal("Failed to create price update".into())
-        })
+        })?;
+        if let Some(attestor) = &self.attestor {
+            update.attestation = Some(attest(attestor, collection, &update).await?);
+        }
+        Ok(update)
     }
 
     /// Get statistics
@@ -487,6 +535,20 @@ impl OracleService {
     }
 }
 
+/// Prove `update` on the blocking pool, since proving can take a while
+#[cfg(feature = "async-oracle")]
+async fn attest(
+    attestor: &Arc<PriceAttestor>,
+    collection: SourceCollection,
+    update: &PriceUpdate,
+) -> Result<PriceAttestation> {
+    let attestor = Arc::clone(attestor);
+    let update = update.clone();
+    tokio::task::spawn_blocking(move || attestor.attest(&collection, &update))
+        .await
+        .map_err(|e| Error::Internal(format!("Attestation task failed: {}", e)))?
+}
+
 /// Oracle service statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OracleStatistics {
@@ -568,6 +630,7 @@ mod tests {
             min_price: 9900000,
             max_price: 10100000,
             sequence: 1,
+            attestation: None,
         };
 
         let spread = update.spread_bps();
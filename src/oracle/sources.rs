@@ -137,8 +137,11 @@ impl PriceSource {
 
     /// Get effective weight based on exchange and volume
     pub fn effective_weight(&self) -> u64 {
-        let base_weight = self.exchange.weight() as u64;
+        self.effective_weight_with(self.exchange.weight() as u64)
+    }
 
+    /// Get effective weight using `base_weight` instead of the exchange default
+    pub fn effective_weight_with(&self, base_weight: u64) -> u64 {
         // Boost weight based on volume if available
         if let Some(volume) = self.volume_24h {
             // Volume in millions of USD
@@ -248,6 +251,11 @@ impl SourceCollection {
 
     /// Calculate weighted average price
     pub fn weighted_average_price(&self) -> Option<u64> {
+        self.weighted_average_price_by(PriceSource::effective_weight)
+    }
+
+    /// Calculate weighted average price with a custom weight per source
+    pub fn weighted_average_price_by<W: Fn(&PriceSource) -> u64>(&self, weight_of: W) -> Option<u64> {
         if self.sources.is_empty() {
             return None;
         }
@@ -256,7 +264,7 @@ impl SourceCollection {
         let mut total_weight: u128 = 0;
 
         for source in &self.sources {
-            let weight = source.effective_weight() as u128;
+            let weight = weight_of(source) as u128;
             weighted_sum += (source.price_cents as u128) * weight;
             total_weight += weight;
         }
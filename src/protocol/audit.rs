@@ -0,0 +1,274 @@
+//! Tamper-evident audit log.
+//!
+//! Admin actions (parameter and config changes, parameter ramps, operation
+//! pauses, circuit breaker trips, oracle safety pauses and slashing, treasury
+//! payouts and savings rate changes) are copied from each block's events into an
+//! append-only log. Every entry commits to the hash of its predecessor, so
+//! editing, reordering or dropping an entry breaks every later link.
+//!
+//! Every [`AUDIT_ANCHOR_INTERVAL`] blocks the head of the chain is emitted as
+//! an `AuditAnchored` event, committing it to the block's events root that
+//! peers check on replay. Truncating the chain and rewinding its head
+//! together is then caught by the anchors.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::protocol::codec::{canonical_hash, AUDIT_DOMAIN};
+use crate::protocol::events::{AuditAnchoredEvent, ProtocolEvent};
+use crate::utils::crypto::Hash;
+
+/// Blocks between anchors of the chain head (about a day)
+pub const AUDIT_ANCHOR_INTERVAL: u64 = 144;
+
+/// Whether `event` records an admin action
+pub fn is_audited(event: &ProtocolEvent) -> bool {
+    matches!(
+        event,
+        ProtocolEvent::ConfigChanged(_)
+            | ProtocolEvent::OracleSafetyPaused(_)
+            | ProtocolEvent::OracleSafetyResumed(_)
+            | ProtocolEvent::SavingsRateChanged(_)
+            | ProtocolEvent::TreasuryDisbursed(_)
+            | ProtocolEvent::OracleSlashed(_)
+            | ProtocolEvent::CircuitBreakerTripped(_)
+            | ProtocolEvent::ParameterRampStarted(_)
+            | ProtocolEvent::ParameterRampCompleted(_)
+    )
+}
+
+/// An admin action chained to its predecessor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Position in the log, from zero
+    pub sequence: u64,
+    /// Hash of the previous entry (zero for the first)
+    pub prev_hash: Hash,
+    /// The recorded action
+    pub event: ProtocolEvent,
+    /// Hash over the fields above
+    pub hash: Hash,
+}
+
+impl AuditEntry {
+    /// Create entry `sequence` after `prev_hash`
+    pub fn new(sequence: u64, prev_hash: Hash, event: ProtocolEvent) -> Self {
+        let mut entry = Self {
+            sequence,
+            prev_hash,
+            event,
+            hash: Hash::zero(),
+        };
+        entry.hash = entry.compute_hash();
+        entry
+    }
+
+    /// Hash the entry's contents
+    pub fn compute_hash(&self) -> Hash {
+        canonical_hash(AUDIT_DOMAIN, &(self.sequence, &self.prev_hash, &self.event)).unwrap_or_default()
+    }
+}
+
+/// Head of the audit chain
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditLog {
+    /// Entries appended so far
+    entries: u64,
+    /// Hash of the last entry (zero while empty)
+    head: Hash,
+    /// Entries covered by the last anchor
+    anchored: u64,
+}
+
+impl AuditLog {
+    /// Entries appended so far
+    pub fn len(&self) -> u64 {
+        self.entries
+    }
+
+    /// Whether nothing was recorded
+    pub fn is_empty(&self) -> bool {
+        self.entries == 0
+    }
+
+    /// Hash of the last entry
+    pub fn head(&self) -> Hash {
+        self.head
+    }
+
+    /// Chain `event` onto the log
+    pub fn append(&mut self, event: ProtocolEvent) -> AuditEntry {
+        let entry = AuditEntry::new(self.entries, self.head, event);
+        self.entries += 1;
+        self.head = entry.hash;
+        entry
+    }
+
+    /// Anchor event for the block, if it is an anchor block and entries were
+    /// appended since the last anchor
+    pub fn anchor(&mut self, block_height: u64, timestamp: u64) -> Option<AuditAnchoredEvent> {
+        if block_height % AUDIT_ANCHOR_INTERVAL != 0 || self.entries == self.anchored {
+            return None;
+        }
+        self.anchored = self.entries;
+        Some(AuditAnchoredEvent {
+            entries: self.entries,
+            head: self.head,
+            block_height,
+            timestamp,
+        })
+    }
+}
+
+/// Outcome of a successful verification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditReport {
+    /// Entries verified
+    pub entries: u64,
+    /// Hash of the last entry
+    pub head: Hash,
+    /// Anchors the chain was checked against
+    pub anchors: usize,
+}
+
+/// Check `entries` (in sequence order) link up to the recorded `log` head
+/// and match every anchor
+pub fn verify_chain(
+    entries: impl IntoIterator<Item = AuditEntry>,
+    log: &AuditLog,
+    anchors: &[AuditAnchoredEvent],
+) -> Result<AuditReport> {
+    let tampered = |sequence: u64, reason: String| Error::AuditLogTampered { sequence, reason };
+
+    let mut anchors: Vec<&AuditAnchoredEvent> = anchors.iter().filter(|a| a.entries > 0).collect();
+    anchors.sort_by_key(|a| a.entries);
+    let mut pending = anchors.iter().peekable();
+
+    let mut count = 0u64;
+    let mut prev = Hash::zero();
+    for entry in entries {
+        if entry.sequence != count {
+            return Err(tampered(count, format!("found entry {} in its place", entry.sequence)));
+        }
+        if entry.prev_hash != prev {
+            return Err(tampered(count, "does not link to the previous entry".into()));
+        }
+        if entry.compute_hash() != entry.hash {
+            return Err(tampered(count, "contents do not match its hash".into()));
+        }
+        prev = entry.hash;
+        count += 1;
+
+        while let Some(anchor) = pending.next_if(|a| a.entries == count) {
+            if anchor.head != prev {
+                return Err(tampered(
+                    count - 1,
+                    format!("differs from the head anchored at block {}", anchor.block_height),
+                ));
+            }
+        }
+    }
+
+    if let Some(anchor) = pending.next() {
+        return Err(tampered(
+            count,
+            format!(
+                "missing: block {} anchored {} entries, the log has {}",
+                anchor.block_height, anchor.entries, count
+            ),
+        ));
+    }
+    if count != log.len() {
+        return Err(tampered(
+            count.min(log.len()),
+            format!("the log has {} entries, its head records {}", count, log.len()),
+        ));
+    }
+    if prev != log.head() {
+        return Err(tampered(count.saturating_sub(1), "differs from the recorded head".into()));
+    }
+
+    Ok(AuditReport {
+        entries: count,
+        head: prev,
+        anchors: anchors.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::events::ConfigChangedEvent;
+
+    fn change(parameter: &str, block_height: u64) -> ProtocolEvent {
+        ProtocolEvent::ConfigChanged(ConfigChangedEvent {
+            parameter: parameter.into(),
+            old_value: "0".into(),
+            new_value: "1".into(),
+            block_height,
+            timestamp: block_height * 600,
+        })
+    }
+
+    fn build(count: u64) -> (AuditLog, Vec<AuditEntry>, Vec<AuditAnchoredEvent>) {
+        let mut log = AuditLog::default();
+        let mut entries = Vec::new();
+        let mut anchors = Vec::new();
+        for height in 1..=count {
+            entries.push(log.append(change("mcr", height * 100)));
+            anchors.extend(log.anchor(height * AUDIT_ANCHOR_INTERVAL, 0));
+        }
+        (log, entries, anchors)
+    }
+
+    #[test]
+    fn test_chain_verifies() {
+        let (log, entries, anchors) = build(5);
+        assert_eq!(log.len(), 5);
+        assert_eq!(anchors.len(), 5);
+        assert_eq!(entries[3].prev_hash, entries[2].hash);
+
+        let report = verify_chain(entries.clone(), &log, &anchors).unwrap();
+        assert_eq!(report.entries, 5);
+        assert_eq!(report.head, log.head());
+
+        // Nothing new since the last anchor
+        let mut anchored = log;
+        assert!(anchored.anchor(10 * AUDIT_ANCHOR_INTERVAL, 0).is_none());
+        assert!(AuditLog::default().anchor(AUDIT_ANCHOR_INTERVAL + 1, 0).is_none());
+
+        assert!(is_audited(&entries[0].event));
+    }
+
+    #[test]
+    fn test_detects_tampering() {
+        let (log, entries, anchors) = build(5);
+        let sequence_of = |result: Result<AuditReport>| match result {
+            Err(Error::AuditLogTampered { sequence, .. }) => sequence,
+            other => panic!("expected tampering, got {:?}", other),
+        };
+
+        // Edited entry
+        let mut edited = entries.clone();
+        edited[2].event = change("ccr", 300);
+        assert_eq!(sequence_of(verify_chain(edited.clone(), &log, &anchors)), 2);
+
+        // Edited and rehashed: the anchor, or else the next link, breaks
+        edited[2].hash = edited[2].compute_hash();
+        assert_eq!(sequence_of(verify_chain(edited.clone(), &log, &anchors)), 2);
+        assert_eq!(sequence_of(verify_chain(edited, &log, &[])), 3);
+
+        // Dropped entry
+        let mut dropped = entries.clone();
+        dropped.remove(1);
+        assert_eq!(sequence_of(verify_chain(dropped, &log, &anchors)), 1);
+
+        // Truncated tail with the head left in place
+        assert_eq!(sequence_of(verify_chain(entries[..3].to_vec(), &log, &[])), 3);
+
+        // Truncated tail with the head rewound too: the anchors catch it
+        let (short_log, _, _) = build(3);
+        assert_eq!(sequence_of(verify_chain(entries[..3].to_vec(), &short_log, &anchors)), 3);
+        assert!(verify_chain(entries[..3].to_vec(), &short_log, &anchors[..3]).is_ok());
+    }
+}
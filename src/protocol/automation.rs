@@ -0,0 +1,639 @@
+//! Automatic collateral top-ups.
+//!
+//! A [`Strategy`] is an owner's opt-in rule for one CDP: when its collateral
+//! ratio falls below the strategy's floor, bring it back up to the target by
+//! depositing collateral or repaying debt. The [`TopUpEngine`] signs those
+//! operations with an automation key and submits them to a state machine.
+//!
+//! The automation key either pays from its own account or acts as a session
+//! key on the CDP (see [`SessionScope`](crate::core::cdp::SessionScope)),
+//! using the owner's funds within the session's grant. On top of that, each
+//! strategy caps what a single action and each period may spend, and waits
+//! a cooldown between actions. Every action and every skipped one is
+//! recorded as an [`AutomationEvent`].
+
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::core::cdp::{CDPId, SessionAction, CDP};
+use crate::core::token::TokenAmount;
+use crate::core::vault::CollateralAmount;
+use crate::error::{Error, Result};
+use crate::protocol::operations::{
+    DepositCollateralOp, Operation, ProtocolOperation, RepayDebtOp, SessionCallOp,
+};
+use crate::protocol::state_machine::ProtocolStateMachine;
+use crate::storage::backend::StorageBackend;
+use crate::utils::constants::{MIN_DEBT, SIGNATURE_LENGTH};
+use crate::utils::crypto::{KeyPair, PublicKey, Signature};
+use crate::utils::math::{calculate_max_debt, calculate_min_collateral};
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// STRATEGY
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// How a strategy raises a CDP's ratio
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TopUpAction {
+    /// Deposit collateral (amounts in sats)
+    Deposit,
+    /// Repay debt (amounts in cents)
+    Repay,
+}
+
+impl FromStr for TopUpAction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "deposit" => Ok(Self::Deposit),
+            "repay" => Ok(Self::Repay),
+            _ => Err(Error::InvalidParameter {
+                name: "action".into(),
+                reason: format!("Unknown top-up action '{}', expected deposit or repay", s),
+            }),
+        }
+    }
+}
+
+/// Whose funds pay for a top-up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FundingSource {
+    /// The automation key deposits, or repays from its own balance
+    Account,
+    /// The automation key is a session key on the CDP and uses the owner's funds
+    Session,
+}
+
+impl FromStr for FundingSource {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "account" => Ok(Self::Account),
+            "session" => Ok(Self::Session),
+            _ => Err(Error::InvalidParameter {
+                name: "funding".into(),
+                reason: format!("Unknown funding source '{}', expected account or session", s),
+            }),
+        }
+    }
+}
+
+/// Spending limits of a strategy, in the unit of its action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StrategyLimits {
+    /// Most a single action may spend
+    pub max_per_action: u64,
+    /// Most all actions may spend within a period
+    pub max_per_period: u64,
+    /// Length of a spending period in blocks
+    pub period_blocks: u64,
+    /// Blocks to wait after an action before the next one
+    pub cooldown_blocks: u64,
+}
+
+impl Default for StrategyLimits {
+    fn default() -> Self {
+        Self {
+            max_per_action: u64::MAX,
+            max_per_period: u64::MAX,
+            period_blocks: 144,
+            cooldown_blocks: 1,
+        }
+    }
+}
+
+/// What a strategy would do for its CDP now
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TopUpPlan {
+    /// The ratio is at or above the floor
+    InBand,
+    /// Below the floor, but the last action was too recent
+    CoolingDown {
+        /// First block another action is allowed
+        until: u64,
+    },
+    /// Below the floor, but the period's budget is spent
+    BudgetExhausted,
+    /// Top up by `amount`
+    Act {
+        /// Amount to deposit or repay
+        amount: u64,
+        /// Whether the limits cut the amount short of reaching the target
+        capped: bool,
+    },
+}
+
+/// An owner's rule for keeping one CDP's ratio in band
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Strategy {
+    /// CDP to look after
+    pub cdp_id: CDPId,
+    /// Ratio (percent) below which the strategy acts
+    pub floor: u64,
+    /// Ratio (percent) an action restores
+    pub target: u64,
+    /// How the ratio is raised
+    pub action: TopUpAction,
+    /// Whose funds pay
+    pub funding: FundingSource,
+    /// Spending limits
+    pub limits: StrategyLimits,
+    /// Whether the strategy is active
+    pub enabled: bool,
+    /// First block of the current spending period
+    pub period_start: u64,
+    /// Amount spent in the current period
+    pub spent_in_period: u64,
+    /// Block of the last action
+    pub last_action: Option<u64>,
+}
+
+impl Strategy {
+    /// Strategy topping `cdp_id` up to `target` when it falls below `floor`,
+    /// paid from the automation key's account with no limits
+    pub fn new(cdp_id: CDPId, floor: u64, target: u64, action: TopUpAction) -> Self {
+        Self {
+            cdp_id,
+            floor,
+            target,
+            action,
+            funding: FundingSource::Account,
+            limits: StrategyLimits::default(),
+            enabled: true,
+            period_start: 0,
+            spent_in_period: 0,
+            last_action: None,
+        }
+    }
+
+    /// Set the funding source
+    pub fn with_funding(mut self, funding: FundingSource) -> Self {
+        self.funding = funding;
+        self
+    }
+
+    /// Set the spending limits
+    pub fn with_limits(mut self, limits: StrategyLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Check the strategy is well formed
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: &str| Error::InvalidParameter {
+            name: "strategy".into(),
+            reason: reason.into(),
+        };
+        if self.floor == 0 || self.target <= self.floor {
+            return Err(invalid("target ratio must be above a non-zero floor"));
+        }
+        if self.limits.max_per_action == 0 || self.limits.max_per_period == 0 {
+            return Err(invalid("spending limits must be non-zero"));
+        }
+        if self.limits.period_blocks == 0 {
+            return Err(invalid("spending period must be non-zero"));
+        }
+        Ok(())
+    }
+
+    /// Amount that brings `cdp` to the target ratio at `btc_price_cents`
+    pub fn amount_to_target(&self, cdp: &CDP, btc_price_cents: u64) -> Result<u64> {
+        if cdp.debt_cents == 0 {
+            return Ok(0);
+        }
+        match self.action {
+            TopUpAction::Deposit => {
+                let required = calculate_min_collateral(cdp.debt_cents, btc_price_cents, self.target)?;
+                Ok(required.saturating_sub(cdp.collateral_sats))
+            }
+            TopUpAction::Repay => {
+                let max_debt = calculate_max_debt(cdp.collateral_sats, btc_price_cents, self.target)?;
+                let amount = cdp.debt_cents.saturating_sub(max_debt);
+                // Debt can't be left below the minimum, so clear it instead
+                if amount > 0 && cdp.debt_cents - amount < MIN_DEBT {
+                    Ok(cdp.debt_cents)
+                } else {
+                    Ok(amount)
+                }
+            }
+        }
+    }
+
+    /// Amount left in the period containing `block_height`
+    pub fn budget(&self, block_height: u64) -> u64 {
+        if block_height >= self.period_start.saturating_add(self.limits.period_blocks) {
+            return self.limits.max_per_period;
+        }
+        self.limits.max_per_period.saturating_sub(self.spent_in_period)
+    }
+
+    /// What to do for `cdp` at `btc_price_cents` and `block_height`
+    pub fn plan(&self, cdp: &CDP, btc_price_cents: u64, block_height: u64) -> Result<TopUpPlan> {
+        if cdp.calculate_ratio(btc_price_cents) >= self.floor {
+            return Ok(TopUpPlan::InBand);
+        }
+
+        if let Some(last) = self.last_action {
+            let until = last.saturating_add(self.limits.cooldown_blocks);
+            if block_height < until {
+                return Ok(TopUpPlan::CoolingDown { until });
+            }
+        }
+
+        let needed = self.amount_to_target(cdp, btc_price_cents)?;
+        let amount = needed.min(self.limits.max_per_action).min(self.budget(block_height));
+        if amount == 0 {
+            return Ok(TopUpPlan::BudgetExhausted);
+        }
+        Ok(TopUpPlan::Act { amount, capped: amount < needed })
+    }
+
+    /// Count `amount` spent at `block_height`
+    pub fn record_spend(&mut self, amount: u64, block_height: u64) {
+        if block_height >= self.period_start.saturating_add(self.limits.period_blocks) {
+            self.period_start = block_height;
+            self.spent_in_period = 0;
+        }
+        self.spent_in_period = self.spent_in_period.saturating_add(amount);
+        self.last_action = Some(block_height);
+    }
+
+    /// Operation performing `amount` of the strategy's action, signed by `funder`
+    fn operation(&self, funder: PublicKey, amount: u64, nonce: u64) -> ProtocolOperation {
+        let signature = Signature::new([0u8; SIGNATURE_LENGTH]);
+        match (self.funding, self.action) {
+            (FundingSource::Account, TopUpAction::Deposit) => {
+                ProtocolOperation::DepositCollateral(DepositCollateralOp {
+                    cdp_id: self.cdp_id,
+                    depositor: funder,
+                    amount: CollateralAmount::from_sats(amount),
+                    nonce,
+                    signature,
+                })
+            }
+            (FundingSource::Account, TopUpAction::Repay) => ProtocolOperation::RepayDebt(RepayDebtOp {
+                cdp_id: self.cdp_id,
+                payer: funder,
+                amount: TokenAmount::from_cents(amount),
+                nonce,
+                signature,
+            }),
+            (FundingSource::Session, action) => ProtocolOperation::SessionCall(SessionCallOp {
+                cdp_id: self.cdp_id,
+                session_key: funder,
+                action: match action {
+                    TopUpAction::Deposit => SessionAction::Deposit { amount_sats: amount },
+                    TopUpAction::Repay => SessionAction::Repay { amount_cents: amount },
+                },
+                nonce,
+                signature,
+            }),
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// AUDIT EVENTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// What happened when a strategy was checked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AutomationOutcome {
+    /// The operation was accepted
+    Executed {
+        /// Amount deposited or repaid
+        amount: u64,
+        /// Whether the limits cut the amount short of the target
+        capped: bool,
+        /// Ratio afterwards
+        new_ratio: u64,
+    },
+    /// The operation was rejected
+    Failed {
+        /// Amount attempted
+        amount: u64,
+        /// Rejection reason
+        error: String,
+    },
+    /// Below the floor, but waiting out the cooldown
+    CoolingDown {
+        /// First block another action is allowed
+        until: u64,
+    },
+    /// Below the floor, but the period's budget is spent
+    BudgetExhausted,
+    /// The CDP no longer exists or is closed
+    Missing,
+}
+
+/// Audit record of a strategy acting, or declining to act
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutomationEvent {
+    /// CDP the strategy looks after
+    pub cdp_id: CDPId,
+    /// Strategy action
+    pub action: TopUpAction,
+    /// Ratio when checked
+    pub ratio: u64,
+    /// Outcome
+    pub outcome: AutomationOutcome,
+    /// Block height
+    pub block_height: u64,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// ENGINE
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Runs top-up strategies with an automation key
+pub struct TopUpEngine {
+    /// Automation key
+    keypair: KeyPair,
+    /// Strategies, at most one per CDP
+    strategies: Vec<Strategy>,
+}
+
+impl TopUpEngine {
+    /// Create an engine signing with `keypair`
+    pub fn new(keypair: KeyPair) -> Self {
+        Self { keypair, strategies: Vec::new() }
+    }
+
+    /// Start from previously saved strategies
+    pub fn with_strategies(mut self, strategies: Vec<Strategy>) -> Self {
+        self.strategies = strategies;
+        self
+    }
+
+    /// Automation public key
+    pub fn funder(&self) -> &PublicKey {
+        self.keypair.public_key()
+    }
+
+    /// Strategies, with their spending state
+    pub fn strategies(&self) -> &[Strategy] {
+        &self.strategies
+    }
+
+    /// Add a strategy, replacing any existing one for the same CDP
+    pub fn add(&mut self, strategy: Strategy) -> Result<()> {
+        strategy.validate()?;
+        self.strategies.retain(|s| s.cdp_id != strategy.cdp_id);
+        self.strategies.push(strategy);
+        Ok(())
+    }
+
+    /// Remove the strategy for a CDP, returning whether there was one
+    pub fn remove(&mut self, cdp_id: &CDPId) -> bool {
+        let before = self.strategies.len();
+        self.strategies.retain(|s| s.cdp_id != *cdp_id);
+        self.strategies.len() != before
+    }
+
+    /// Check every enabled strategy against `machine`'s current state and
+    /// execute the top-ups that are due.
+    ///
+    /// Returns an audit event for every strategy whose CDP is below its floor
+    /// or gone; strategies in band produce none.
+    pub fn run<B: StorageBackend>(
+        &mut self,
+        machine: &mut ProtocolStateMachine<B>,
+    ) -> Vec<AutomationEvent> {
+        let price = machine.price();
+        let block_height = machine.block_height();
+        let funder = *self.keypair.public_key();
+        let mut events = Vec::new();
+
+        for strategy in self.strategies.iter_mut().filter(|s| s.enabled) {
+            let event = |ratio: u64, outcome: AutomationOutcome| AutomationEvent {
+                cdp_id: strategy.cdp_id,
+                action: strategy.action,
+                ratio,
+                outcome,
+                block_height,
+            };
+
+            let cdp = machine.get_cdp(&strategy.cdp_id).filter(|cdp| !cdp.status.is_terminal());
+            let Some(cdp) = cdp else {
+                events.push(event(0, AutomationOutcome::Missing));
+                continue;
+            };
+            let ratio = cdp.calculate_ratio(price);
+
+            let (amount, capped) = match strategy.plan(cdp, price, block_height) {
+                Ok(TopUpPlan::InBand) => continue,
+                Ok(TopUpPlan::CoolingDown { until }) => {
+                    events.push(event(ratio, AutomationOutcome::CoolingDown { until }));
+                    continue;
+                }
+                Ok(TopUpPlan::BudgetExhausted) => {
+                    events.push(event(ratio, AutomationOutcome::BudgetExhausted));
+                    continue;
+                }
+                Ok(TopUpPlan::Act { amount, capped }) => (amount, capped),
+                Err(e) => {
+                    let outcome = AutomationOutcome::Failed { amount: 0, error: e.to_string() };
+                    events.push(event(ratio, outcome));
+                    continue;
+                }
+            };
+
+            let mut op = strategy.operation(funder, amount, machine.next_nonce(&funder));
+            let outcome = match sign(&mut op, &self.keypair, machine).and_then(|_| machine.execute(op)) {
+                Ok(_) => {
+                    let new_ratio = machine
+                        .get_cdp(&strategy.cdp_id)
+                        .map(|cdp| cdp.calculate_ratio(price))
+                        .unwrap_or_default();
+                    AutomationOutcome::Executed { amount, capped, new_ratio }
+                }
+                Err(e) => AutomationOutcome::Failed { amount, error: e.to_string() },
+            };
+            let event = event(ratio, outcome);
+            if matches!(event.outcome, AutomationOutcome::Executed { .. }) {
+                strategy.record_spend(amount, block_height);
+            }
+
+            tracing::info!(
+                cdp_id = %strategy.cdp_id,
+                ratio,
+                amount,
+                outcome = ?event.outcome,
+                "Automatic top-up"
+            );
+            events.push(event);
+        }
+
+        events
+    }
+}
+
+/// Sign `op` for `machine`'s signing domain
+fn sign<B: StorageBackend>(
+    op: &mut ProtocolOperation,
+    keypair: &KeyPair,
+    machine: &ProtocolStateMachine<B>,
+) -> Result<()> {
+    let domain = machine.signing_domain();
+    match op {
+        ProtocolOperation::DepositCollateral(op) => op.sign(keypair, domain),
+        ProtocolOperation::RepayDebt(op) => op.sign(keypair, domain),
+        ProtocolOperation::SessionCall(op) => op.sign(keypair, domain),
+        other => Err(Error::Internal(format!(
+            "{} is not a top-up operation",
+            other.operation_type()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::cdp::{SessionGrant, SessionScope};
+    use crate::protocol::operations::{OpenCDPOp, SetSessionKeyOp, UpdatePriceOp};
+    use crate::protocol::safety::WatchdogConfig;
+    use crate::protocol::signing::SigningDomain;
+    use crate::protocol::state_machine::OperationResult;
+    use crate::storage::backend::InMemoryStore;
+    use crate::utils::constants::{MIN_ORACLE_SOURCES, PUBKEY_LENGTH};
+
+    fn cdp(collateral_sats: u64, debt_cents: u64) -> CDP {
+        let mut cdp = CDP::new(PublicKey::new([0x02; PUBKEY_LENGTH]), 1, 0);
+        cdp.collateral_sats = collateral_sats;
+        cdp.debt_cents = debt_cents;
+        cdp
+    }
+
+    fn set_price(
+        machine: &mut ProtocolStateMachine<InMemoryStore>,
+        oracle: &KeyPair,
+        price_cents: u64,
+        nonce: u64,
+    ) {
+        let mut op = UpdatePriceOp {
+            operator: *oracle.public_key(),
+            price_cents,
+            source_count: MIN_ORACLE_SOURCES as u8,
+            confidence: 100,
+            confidence_interval: 0,
+            proof: Vec::new(),
+            nonce,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        op.sign(oracle, &SigningDomain::default()).unwrap();
+        machine.execute(ProtocolOperation::UpdatePrice(op)).unwrap();
+    }
+
+    #[test]
+    fn test_plan() {
+        // 1 BTC against $50,000 at $80,000 is 160%
+        let position = cdp(100_000_000, 5_000_000);
+        let deposit = Strategy::new(position.id, 170, 200, TopUpAction::Deposit);
+        assert_eq!(deposit.plan(&position, 10_000_000, 10).unwrap(), TopUpPlan::InBand);
+        assert_eq!(
+            deposit.plan(&position, 8_000_000, 10).unwrap(),
+            TopUpPlan::Act { amount: 25_000_000, capped: false }
+        );
+
+        let repay = Strategy::new(position.id, 170, 200, TopUpAction::Repay);
+        assert_eq!(
+            repay.plan(&position, 8_000_000, 10).unwrap(),
+            TopUpPlan::Act { amount: 1_000_000, capped: false }
+        );
+
+        // Limits cap the action, then the period, then wait out the cooldown
+        let mut limited = deposit.with_limits(StrategyLimits {
+            max_per_action: 10_000_000,
+            max_per_period: 15_000_000,
+            period_blocks: 100,
+            cooldown_blocks: 5,
+        });
+        assert_eq!(
+            limited.plan(&position, 8_000_000, 10).unwrap(),
+            TopUpPlan::Act { amount: 10_000_000, capped: true }
+        );
+        limited.record_spend(10_000_000, 10);
+        assert_eq!(limited.plan(&position, 8_000_000, 12).unwrap(), TopUpPlan::CoolingDown { until: 15 });
+        assert_eq!(
+            limited.plan(&position, 8_000_000, 15).unwrap(),
+            TopUpPlan::Act { amount: 5_000_000, capped: true }
+        );
+        limited.record_spend(5_000_000, 15);
+        assert_eq!(limited.plan(&position, 8_000_000, 50).unwrap(), TopUpPlan::BudgetExhausted);
+        assert_eq!(
+            limited.plan(&position, 8_000_000, 110).unwrap(),
+            TopUpPlan::Act { amount: 10_000_000, capped: true }
+        );
+
+        assert!(Strategy::new(position.id, 200, 150, TopUpAction::Deposit).validate().is_err());
+    }
+
+    #[test]
+    fn test_engine_tops_up_with_session_key() {
+        let mut machine = ProtocolStateMachine::new(InMemoryStore::new())
+            .unwrap()
+            .with_watchdog(WatchdogConfig { enabled: false, ..Default::default() });
+        machine.begin_block(1, 1_000).unwrap();
+
+        let oracle = KeyPair::generate();
+        set_price(&mut machine, &oracle, 10_000_000, 1);
+
+        let owner = KeyPair::generate();
+        let bot = KeyPair::generate();
+        let mut open = OpenCDPOp {
+            owner: *owner.public_key(),
+            collateral: CollateralAmount::from_sats(100_000_000),
+            initial_debt: Some(TokenAmount::from_cents(5_000_000)),
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        open.sign(&owner, &SigningDomain::default()).unwrap();
+        let cdp_id = match machine.execute(ProtocolOperation::OpenCDP(open)).unwrap() {
+            OperationResult::OpenCDP(result) => result.cdp_id,
+            other => panic!("unexpected result: {:?}", other),
+        };
+
+        let mut grant = SetSessionKeyOp {
+            cdp_id,
+            owner: *owner.public_key(),
+            key: *bot.public_key(),
+            grant: Some(SessionGrant {
+                scope: SessionScope::TopUp { below_ratio: 170 },
+                spend_cap: 30_000_000,
+                expires_at: 1_000,
+            }),
+            nonce: 2,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            cosignatures: Vec::new(),
+        };
+        grant.sign(&owner, &SigningDomain::default()).unwrap();
+        machine.execute(ProtocolOperation::SetSessionKey(grant)).unwrap();
+
+        let mut engine = TopUpEngine::new(bot);
+        engine
+            .add(Strategy::new(cdp_id, 170, 200, TopUpAction::Deposit).with_funding(FundingSource::Session))
+            .unwrap();
+
+        assert!(engine.run(&mut machine).is_empty());
+
+        set_price(&mut machine, &oracle, 8_000_000, 2);
+        let events = engine.run(&mut machine);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].ratio, 160);
+        assert_eq!(
+            events[0].outcome,
+            AutomationOutcome::Executed { amount: 25_000_000, capped: false, new_ratio: 200 }
+        );
+        assert_eq!(machine.get_cdp(&cdp_id).unwrap().collateral_sats, 125_000_000);
+        assert_eq!(engine.strategies()[0].spent_in_period, 25_000_000);
+
+        // The session's spend cap still binds the engine
+        machine.begin_block(10, 7_000).unwrap();
+        set_price(&mut machine, &oracle, 6_000_000, 3);
+        let events = engine.run(&mut machine);
+        assert!(matches!(events[0].outcome, AutomationOutcome::Failed { .. }));
+        assert_eq!(engine.strategies()[0].spent_in_period, 25_000_000);
+    }
+}
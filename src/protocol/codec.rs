@@ -0,0 +1,542 @@
+//! Canonical encoding of consensus-critical values.
+//!
+//! Signing hashes, transaction hashes and state roots have to come out the
+//! same on every node and across releases. Bincode's layout belongs to
+//! whichever bincode version a node was built with (its defaults changed
+//! between major versions), and it writes hash maps in iteration order, so
+//! those hashes go through this encoder instead. Its layout is fixed here and
+//! versioned by [`CODEC_VERSION`]:
+//!
+//! - integers are fixed-width big-endian, `bool` is one byte and `char` a `u32`
+//! - strings and byte strings are a `u32` length followed by the bytes
+//! - sequences and maps are a `u32` count followed by their elements; map
+//!   entries are sorted by encoded key and duplicate keys are rejected
+//! - structs, tuples and arrays are their fields in declaration order, with
+//!   no names or lengths
+//! - `Option` is a `0`/`1` tag followed by the value, enums a `u32` variant
+//!   index followed by the variant's fields
+//! - floats and sequences of unknown length are rejected
+//!
+//! Values are encoded through their `Serialize` impls, so hashes, keys and
+//! signatures encode as the hex strings they serialize to. Storage keeps
+//! using bincode (see [`crate::utils::codec`]); only hashes depend on this
+//! layout.
+
+use serde::ser::{self, Serialize};
+use std::fmt;
+
+use crate::error::{Error, Result};
+use crate::utils::crypto::{Hash, HashScheme};
+
+/// Version of the canonical layout, committed to by every canonical hash
+pub const CODEC_VERSION: u8 = 1;
+
+/// Domain of legacy untyped operation signing hashes
+pub const OPERATION_DOMAIN: &str = "operation";
+
+/// Domain of transaction hashes
+pub const TX_DOMAIN: &str = "tx";
+
+/// Domain of protocol state hashes
+pub const STATE_DOMAIN: &str = "protocol-state";
+
+/// Domain of CDP state hashes
+pub const CDP_DOMAIN: &str = "cdp";
+
+/// Domain of event hashes
+pub const EVENT_DOMAIN: &str = "event";
+
+/// Domain of genesis hashes
+pub const GENESIS_DOMAIN: &str = "genesis";
+
+/// Domain of audit log entry hashes
+pub const AUDIT_DOMAIN: &str = "audit";
+
+/// Encode a value canonically
+pub fn canonical_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+    let mut encoder = Encoder::default();
+    value
+        .serialize(&mut encoder)
+        .map_err(|e| Error::Serialization(format!("Canonical encoding failed: {}", e)))?;
+    Ok(encoder.out)
+}
+
+/// Domain-separated SHA256 hash of a value's canonical encoding
+pub fn canonical_hash<T: Serialize + ?Sized>(domain: &str, value: &T) -> Result<Hash> {
+    canonical_hash_with(HashScheme::Sha256, domain, value)
+}
+
+/// Domain-separated hash of a value's canonical encoding under `scheme`
+pub fn canonical_hash_with<T: Serialize + ?Sized>(scheme: HashScheme, domain: &str, value: &T) -> Result<Hash> {
+    let mut data = vec![CODEC_VERSION];
+    data.extend(canonical_bytes(value)?);
+    Ok(scheme.domain_hash(domain, &data))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// ENCODER
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Encoding error, surfaced as [`Error::Serialization`]
+#[derive(Debug)]
+struct EncodeError(String);
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+impl ser::Error for EncodeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+type EncodeResult<T = ()> = std::result::Result<T, EncodeError>;
+
+/// Serializer writing the canonical layout
+#[derive(Default)]
+struct Encoder {
+    out: Vec<u8>,
+}
+
+impl Encoder {
+    fn write_len(&mut self, len: usize) -> EncodeResult {
+        let len = u32::try_from(len).map_err(|_| EncodeError(format!("length {} exceeds u32", len)))?;
+        self.out.extend_from_slice(&len.to_be_bytes());
+        Ok(())
+    }
+
+    fn write_variant(&mut self, index: u32) {
+        self.out.extend_from_slice(&index.to_be_bytes());
+    }
+
+    fn nested<T: Serialize + ?Sized>(value: &T) -> EncodeResult<Vec<u8>> {
+        let mut encoder = Encoder::default();
+        value.serialize(&mut encoder)?;
+        Ok(encoder.out)
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut Encoder {
+    type Ok = ();
+    type Error = EncodeError;
+
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = MapEncoder<'a>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bool(self, v: bool) -> EncodeResult {
+        self.out.push(v as u8);
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> EncodeResult {
+        self.out.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+
+    fn serialize_i16(self, v: i16) -> EncodeResult {
+        self.out.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+
+    fn serialize_i32(self, v: i32) -> EncodeResult {
+        self.out.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+
+    fn serialize_i64(self, v: i64) -> EncodeResult {
+        self.out.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+
+    fn serialize_i128(self, v: i128) -> EncodeResult {
+        self.out.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> EncodeResult {
+        self.out.push(v);
+        Ok(())
+    }
+
+    fn serialize_u16(self, v: u16) -> EncodeResult {
+        self.out.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+
+    fn serialize_u32(self, v: u32) -> EncodeResult {
+        self.out.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+
+    fn serialize_u64(self, v: u64) -> EncodeResult {
+        self.out.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+
+    fn serialize_u128(self, v: u128) -> EncodeResult {
+        self.out.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+
+    fn serialize_f32(self, _v: f32) -> EncodeResult {
+        Err(EncodeError("floats have no canonical encoding".into()))
+    }
+
+    fn serialize_f64(self, _v: f64) -> EncodeResult {
+        Err(EncodeError("floats have no canonical encoding".into()))
+    }
+
+    fn serialize_char(self, v: char) -> EncodeResult {
+        self.serialize_u32(v as u32)
+    }
+
+    fn serialize_str(self, v: &str) -> EncodeResult {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> EncodeResult {
+        self.write_len(v.len())?;
+        self.out.extend_from_slice(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> EncodeResult {
+        self.out.push(0);
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> EncodeResult {
+        self.out.push(1);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> EncodeResult {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> EncodeResult {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, index: u32, _variant: &'static str) -> EncodeResult {
+        self.write_variant(index);
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> EncodeResult {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> EncodeResult {
+        self.write_variant(index);
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> EncodeResult<Self> {
+        let len = len.ok_or_else(|| EncodeError("sequence of unknown length".into()))?;
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> EncodeResult<Self> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> EncodeResult<Self> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> EncodeResult<Self> {
+        self.write_variant(index);
+        Ok(self)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> EncodeResult<MapEncoder<'a>> {
+        Ok(MapEncoder {
+            encoder: self,
+            entries: Vec::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> EncodeResult<Self> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> EncodeResult<Self> {
+        self.write_variant(index);
+        Ok(self)
+    }
+}
+
+impl ser::SerializeSeq for &mut Encoder {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> EncodeResult {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> EncodeResult {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for &mut Encoder {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> EncodeResult {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> EncodeResult {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut Encoder {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> EncodeResult {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> EncodeResult {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut Encoder {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> EncodeResult {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> EncodeResult {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for &mut Encoder {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, _key: &'static str, value: &T) -> EncodeResult {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> EncodeResult {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for &mut Encoder {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, _key: &'static str, value: &T) -> EncodeResult {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> EncodeResult {
+        Ok(())
+    }
+}
+
+/// Map serializer buffering entries so they can be written sorted by key
+struct MapEncoder<'a> {
+    encoder: &'a mut Encoder,
+    entries: Vec<(Vec<u8>, Vec<u8>)>,
+    key: Option<Vec<u8>>,
+}
+
+impl ser::SerializeMap for MapEncoder<'_> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> EncodeResult {
+        self.key = Some(Encoder::nested(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> EncodeResult {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| EncodeError("map value without a key".into()))?;
+        self.entries.push((key, Encoder::nested(value)?));
+        Ok(())
+    }
+
+    fn end(mut self) -> EncodeResult {
+        self.entries.sort_by(|a, b| a.0.cmp(&b.0));
+        if self.entries.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+            return Err(EncodeError("duplicate map key".into()));
+        }
+
+        self.encoder.write_len(self.entries.len())?;
+        for (key, value) in self.entries {
+            self.encoder.out.extend(key);
+            self.encoder.out.extend(value);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Serialize;
+    use crate::core::vault::CollateralAmount;
+    use crate::protocol::operations::{DepositCollateralOp, Operation};
+    use crate::utils::constants::{PUBKEY_LENGTH, SIGNATURE_LENGTH};
+    use crate::utils::crypto::{CDPId, PublicKey, Signature};
+    use std::collections::{BTreeMap, HashMap};
+
+    #[derive(Serialize)]
+    enum Kind {
+        #[allow(dead_code)]
+        Empty,
+        Tagged(u32),
+    }
+
+    #[derive(Serialize)]
+    struct Sample {
+        flag: bool,
+        small: u8,
+        medium: u16,
+        value: u32,
+        amount: u64,
+        delta: i64,
+        wide: u128,
+        label: String,
+        bytes: Vec<u8>,
+        maybe: Option<u32>,
+        nothing: Option<u32>,
+        kind: Kind,
+        pair: (u16, u8),
+    }
+
+    fn sample() -> Sample {
+        Sample {
+            flag: true,
+            small: 0x01,
+            medium: 0x0203,
+            value: 0x0405_0607,
+            amount: 0x0809_0a0b_0c0d_0e0f,
+            delta: -2,
+            wide: 1,
+            label: "zk".into(),
+            bytes: vec![0xaa, 0xbb],
+            maybe: Some(7),
+            nothing: None,
+            kind: Kind::Tagged(9),
+            pair: (0x0102, 3),
+        }
+    }
+
+    #[test]
+    fn test_golden_layout() {
+        let expected = concat!(
+            "01",
+            "01",
+            "0203",
+            "04050607",
+            "08090a0b0c0d0e0f",
+            "fffffffffffffffe",
+            "00000000000000000000000000000001",
+            "00000002", "7a6b",
+            "00000002", "aabb",
+            "01", "00000007",
+            "00",
+            "00000001", "00000009",
+            "0102", "03",
+        );
+        assert_eq!(hex::encode(canonical_bytes(&sample()).unwrap()), expected);
+        assert_eq!(
+            canonical_hash("test", &sample()).unwrap().to_hex(),
+            "7da5d7ad578efa260e0cfabcb8e8abd49a4ecfedcc7cb5a1a5076081d11fea03"
+        );
+    }
+
+    #[test]
+    fn test_golden_legacy_signing_hash() {
+        let op = DepositCollateralOp {
+            cdp_id: CDPId::new([0x11; 32]),
+            depositor: PublicKey::new([0x02; PUBKEY_LENGTH]),
+            amount: CollateralAmount::from_sats(100_000_000),
+            nonce: 5,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+
+        let bytes = canonical_bytes(&op).unwrap();
+        assert_eq!(bytes.len(), 4 + 64 + 4 + 66 + 8 + 8 + 4 + 128);
+        assert_eq!(&bytes[..4], &[0, 0, 0, 64]);
+        assert_eq!(&bytes[138..154], &hex::decode("0000000005f5e1000000000000000005").unwrap()[..]);
+        assert_eq!(
+            op.legacy_signing_hash().unwrap().to_hex(),
+            "a0006267d672a5b326be000b38861adee11000326948f47adc870bbbceb0d2f2"
+        );
+    }
+
+    #[test]
+    fn test_maps_sorted_by_key() {
+        let mut forward = HashMap::new();
+        let mut reverse = HashMap::new();
+        for key in 0u32..64 {
+            forward.insert(key, key * 2);
+            reverse.insert(63 - key, (63 - key) * 2);
+        }
+        let ordered: BTreeMap<u32, u32> = forward.iter().map(|(k, v)| (*k, *v)).collect();
+
+        let bytes = canonical_bytes(&forward).unwrap();
+        assert_eq!(bytes, canonical_bytes(&reverse).unwrap());
+        assert_eq!(bytes, canonical_bytes(&ordered).unwrap());
+        assert_eq!(&bytes[..12], &[0, 0, 0, 64, 0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_rejects_non_canonical_values() {
+        assert!(canonical_bytes(&1.5f64).is_err());
+        assert!(canonical_bytes(&(1u8, 0.5f32)).is_err());
+
+        // Hash commits to the domain
+        assert_ne!(canonical_hash("a", &1u8).unwrap(), canonical_hash("b", &1u8).unwrap());
+    }
+}
@@ -5,9 +5,16 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::core::cdp::CDPId;
+use crate::charms::bridge::ChainId;
+use crate::core::cdp::{CDPId, OwnerPolicy, SessionKey};
+use crate::core::config::ProtocolParameter;
+use crate::core::peg::PegRegime;
 use crate::core::token::TokenAmount;
 use crate::core::vault::CollateralAmount;
+use crate::monitoring::anomaly::AnomalyMetric;
+use crate::protocol::codec::{canonical_hash, EVENT_DOMAIN};
+use crate::protocol::pauses::PausableOperation;
+use crate::protocol::treasury::TreasuryAsset;
 use crate::utils::crypto::{Hash, PublicKey};
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -62,6 +69,96 @@ pub enum ProtocolEvent {
     RecoveryModeEntered(RecoveryModeEvent),
     /// Recovery mode exited
     RecoveryModeExited(RecoveryModeEvent),
+
+    // Stored events are encoded by variant index: add new variants below.
+
+    // Oracle Safety Events
+    /// Oracle watchdog paused the protocol
+    OracleSafetyPaused(OracleSafetyEvent),
+    /// Oracle watchdog lifted its pause
+    OracleSafetyResumed(OracleSafetyEvent),
+
+    // Allowance Events
+    /// Spending allowance set
+    TokenApproval(TokenApprovalEvent),
+
+    // Ownership Events
+    /// CDP owner policy replaced
+    OwnerPolicyChanged(OwnerPolicyChangedEvent),
+
+    // Bridge Events
+    /// zkUSD locked for another chain
+    BridgeOut(BridgeOutEvent),
+    /// zkUSD released from another chain
+    BridgeIn(BridgeInEvent),
+
+    // Savings Events
+    /// zkUSD locked in the savings pot
+    SavingsDeposit(SavingsDepositEvent),
+    /// zkUSD unlocked from the savings pot
+    SavingsWithdraw(SavingsWithdrawEvent),
+    /// Savings interest accrued
+    SavingsAccrued(SavingsAccruedEvent),
+    /// Savings rate changed by governance
+    SavingsRateChanged(SavingsRateChangedEvent),
+
+    // Treasury Events
+    /// Treasury funds paid out
+    TreasuryDisbursed(TreasuryDisbursedEvent),
+
+    // Auction Events
+    /// Seized collateral put up for auction
+    AuctionStarted(AuctionStartedEvent),
+    /// Collateral bought at auction
+    AuctionTaken(AuctionTakenEvent),
+    /// Expired auction restarted
+    AuctionReset(AuctionResetEvent),
+
+    // Frontend Events
+    /// Stability pool frontend registered
+    FrontendRegistered(FrontendRegisteredEvent),
+    /// Frontend kickbacks claimed
+    FrontendGainsClaimed(FrontendGainsClaimedEvent),
+
+    // Invariant Events
+    /// A protocol invariant failed at the end of a block
+    InvariantViolated(InvariantViolatedEvent),
+
+    // Keeper Events
+    /// Keeper stipend paid from the treasury
+    KeeperRewarded(KeeperRewardedEvent),
+
+    // Peg Events
+    /// A non-BTC price pair was updated
+    PairPriceUpdated(PairPriceUpdatedEvent),
+    /// The peg controller moved the fees
+    PegFeesAdjusted(PegFeesAdjustedEvent),
+
+    // Session Events
+    /// CDP session key authorized or revoked
+    SessionKeyChanged(SessionKeyChangedEvent),
+
+    // Oracle Registry Events
+    /// An oracle operator was slashed for equivocating
+    OracleSlashed(OracleSlashedEvent),
+
+    // Circuit Breaker Events
+    /// Anomalies tripped an operation's circuit breaker
+    CircuitBreakerTripped(CircuitBreakerTrippedEvent),
+
+    // Anomaly Events
+    /// An activity metric spiked above its rolling mean
+    AnomalyDetected(AnomalyDetectedEvent),
+
+    // Audit Events
+    /// Head of the audit log committed to the block
+    AuditAnchored(AuditAnchoredEvent),
+
+    // Parameter Ramp Events
+    /// A scheduled parameter ramp took its first step
+    ParameterRampStarted(ParameterRampEvent),
+    /// A parameter ramp reached its target
+    ParameterRampCompleted(ParameterRampEvent),
 }
 
 impl ProtocolEvent {
@@ -82,6 +179,33 @@ impl ProtocolEvent {
             Self::LiquidationAbsorbed(_) => "LiquidationAbsorbed",
             Self::Redemption(_) => "Redemption",
             Self::PriceUpdated(_) => "PriceUpdated",
+            Self::OracleSafetyPaused(_) => "OracleSafetyPaused",
+            Self::OracleSafetyResumed(_) => "OracleSafetyResumed",
+            Self::TokenApproval(_) => "TokenApproval",
+            Self::OwnerPolicyChanged(_) => "OwnerPolicyChanged",
+            Self::BridgeOut(_) => "BridgeOut",
+            Self::BridgeIn(_) => "BridgeIn",
+            Self::SavingsDeposit(_) => "SavingsDeposit",
+            Self::SavingsWithdraw(_) => "SavingsWithdraw",
+            Self::SavingsAccrued(_) => "SavingsAccrued",
+            Self::SavingsRateChanged(_) => "SavingsRateChanged",
+            Self::TreasuryDisbursed(_) => "TreasuryDisbursed",
+            Self::AuctionStarted(_) => "AuctionStarted",
+            Self::AuctionTaken(_) => "AuctionTaken",
+            Self::AuctionReset(_) => "AuctionReset",
+            Self::FrontendRegistered(_) => "FrontendRegistered",
+            Self::FrontendGainsClaimed(_) => "FrontendGainsClaimed",
+            Self::InvariantViolated(_) => "InvariantViolated",
+            Self::KeeperRewarded(_) => "KeeperRewarded",
+            Self::PairPriceUpdated(_) => "PairPriceUpdated",
+            Self::PegFeesAdjusted(_) => "PegFeesAdjusted",
+            Self::SessionKeyChanged(_) => "SessionKeyChanged",
+            Self::OracleSlashed(_) => "OracleSlashed",
+            Self::CircuitBreakerTripped(_) => "CircuitBreakerTripped",
+            Self::AnomalyDetected(_) => "AnomalyDetected",
+            Self::AuditAnchored(_) => "AuditAnchored",
+            Self::ParameterRampStarted(_) => "ParameterRampStarted",
+            Self::ParameterRampCompleted(_) => "ParameterRampCompleted",
             Self::ConfigChanged(_) => "ConfigChanged",
             Self::RecoveryModeEntered(_) => "RecoveryModeEntered",
             Self::RecoveryModeExited(_) => "RecoveryModeExited",
@@ -105,6 +229,32 @@ impl ProtocolEvent {
             Self::LiquidationAbsorbed(e) => e.timestamp,
             Self::Redemption(e) => e.timestamp,
             Self::PriceUpdated(e) => e.timestamp,
+            Self::OracleSafetyPaused(e) => e.timestamp,
+            Self::OracleSafetyResumed(e) => e.timestamp,
+            Self::TokenApproval(e) => e.timestamp,
+            Self::OwnerPolicyChanged(e) => e.timestamp,
+            Self::BridgeOut(e) => e.timestamp,
+            Self::BridgeIn(e) => e.timestamp,
+            Self::SavingsDeposit(e) => e.timestamp,
+            Self::SavingsWithdraw(e) => e.timestamp,
+            Self::SavingsAccrued(e) => e.timestamp,
+            Self::SavingsRateChanged(e) => e.timestamp,
+            Self::TreasuryDisbursed(e) => e.timestamp,
+            Self::AuctionStarted(e) => e.timestamp,
+            Self::AuctionTaken(e) => e.timestamp,
+            Self::AuctionReset(e) => e.timestamp,
+            Self::FrontendRegistered(e) => e.timestamp,
+            Self::FrontendGainsClaimed(e) => e.timestamp,
+            Self::InvariantViolated(e) => e.timestamp,
+            Self::KeeperRewarded(e) => e.timestamp,
+            Self::PairPriceUpdated(e) => e.timestamp,
+            Self::PegFeesAdjusted(e) => e.timestamp,
+            Self::SessionKeyChanged(e) => e.timestamp,
+            Self::OracleSlashed(e) => e.timestamp,
+            Self::CircuitBreakerTripped(e) => e.timestamp,
+            Self::AnomalyDetected(e) => e.timestamp,
+            Self::AuditAnchored(e) => e.timestamp,
+            Self::ParameterRampStarted(e) | Self::ParameterRampCompleted(e) => e.timestamp,
             Self::ConfigChanged(e) => e.timestamp,
             Self::RecoveryModeEntered(e) => e.timestamp,
             Self::RecoveryModeExited(e) => e.timestamp,
@@ -128,6 +278,32 @@ impl ProtocolEvent {
             Self::LiquidationAbsorbed(e) => e.block_height,
             Self::Redemption(e) => e.block_height,
             Self::PriceUpdated(e) => e.block_height,
+            Self::OracleSafetyPaused(e) => e.block_height,
+            Self::OracleSafetyResumed(e) => e.block_height,
+            Self::TokenApproval(e) => e.block_height,
+            Self::OwnerPolicyChanged(e) => e.block_height,
+            Self::BridgeOut(e) => e.block_height,
+            Self::BridgeIn(e) => e.block_height,
+            Self::SavingsDeposit(e) => e.block_height,
+            Self::SavingsWithdraw(e) => e.block_height,
+            Self::SavingsAccrued(e) => e.block_height,
+            Self::SavingsRateChanged(e) => e.block_height,
+            Self::TreasuryDisbursed(e) => e.block_height,
+            Self::AuctionStarted(e) => e.block_height,
+            Self::AuctionTaken(e) => e.block_height,
+            Self::AuctionReset(e) => e.block_height,
+            Self::FrontendRegistered(e) => e.block_height,
+            Self::FrontendGainsClaimed(e) => e.block_height,
+            Self::InvariantViolated(e) => e.block_height,
+            Self::KeeperRewarded(e) => e.block_height,
+            Self::PairPriceUpdated(e) => e.block_height,
+            Self::PegFeesAdjusted(e) => e.block_height,
+            Self::SessionKeyChanged(e) => e.block_height,
+            Self::OracleSlashed(e) => e.block_height,
+            Self::CircuitBreakerTripped(e) => e.block_height,
+            Self::AnomalyDetected(e) => e.block_height,
+            Self::AuditAnchored(e) => e.block_height,
+            Self::ParameterRampStarted(e) | Self::ParameterRampCompleted(e) => e.block_height,
             Self::ConfigChanged(e) => e.block_height,
             Self::RecoveryModeEntered(e) => e.block_height,
             Self::RecoveryModeExited(e) => e.block_height,
@@ -136,8 +312,7 @@ impl ProtocolEvent {
 
     /// Compute event hash
     pub fn hash(&self) -> Hash {
-        let data = bincode::serialize(self).unwrap_or_default();
-        Hash::sha256(&data)
+        canonical_hash(EVENT_DOMAIN, self).unwrap_or_default()
     }
 }
 
@@ -259,6 +434,38 @@ pub struct CDPClosedEvent {
     pub timestamp: u64,
 }
 
+/// Event emitted when a CDP's owner policy is replaced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnerPolicyChangedEvent {
+    /// CDP identifier
+    pub cdp_id: CDPId,
+    /// Owner
+    pub owner: PublicKey,
+    /// New policy
+    pub policy: OwnerPolicy,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+/// Event emitted when a CDP session key is authorized or revoked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionKeyChangedEvent {
+    /// CDP identifier
+    pub cdp_id: CDPId,
+    /// Owner
+    pub owner: PublicKey,
+    /// Session key
+    pub key: PublicKey,
+    /// Session now in effect (`None` if revoked)
+    pub session: Option<SessionKey>,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
 /// Event emitted when a CDP is liquidated
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CDPLiquidatedEvent {
@@ -295,6 +502,8 @@ pub enum LiquidationMode {
     Redistribution,
     /// Direct liquidation by liquidator
     Direct,
+    /// Collateral sold at auction
+    Auction,
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -316,6 +525,21 @@ pub struct TokenTransferEvent {
     pub timestamp: u64,
 }
 
+/// Event emitted when an owner sets a spender's allowance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenApprovalEvent {
+    /// Token owner
+    pub owner: PublicKey,
+    /// Approved spender
+    pub spender: PublicKey,
+    /// New allowance
+    pub amount: TokenAmount,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // STABILITY POOL EVENTS
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -424,6 +648,319 @@ pub struct PriceUpdatedEvent {
     pub timestamp: u64,
 }
 
+/// Event emitted when a non-BTC price pair is updated
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairPriceUpdatedEvent {
+    /// Pair, as BASE/QUOTE
+    pub pair: String,
+    /// New rate, scaled by `RATE_PRECISION`
+    pub rate: u64,
+    /// Previous rate (0 if none)
+    pub previous_rate: u64,
+    /// Number of sources
+    pub source_count: u8,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+/// Event emitted when the peg controller moves the borrowing fee or the
+/// redemption fee floor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PegFeesAdjustedEvent {
+    /// zkUSD/USD rate the step was based on, scaled by `RATE_PRECISION`
+    pub market_rate: u64,
+    /// Deviation from $1 in basis points, negative below the peg
+    pub deviation_bps: i64,
+    /// Side of the peg being defended
+    pub regime: PegRegime,
+    /// Borrowing fee before the step
+    pub old_borrowing_fee_bps: u64,
+    /// Borrowing fee after the step
+    pub borrowing_fee_bps: u64,
+    /// Redemption fee floor before the step
+    pub old_redemption_fee_floor_bps: u64,
+    /// Redemption fee floor after the step
+    pub redemption_fee_floor_bps: u64,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+/// Event emitted when the oracle watchdog pauses or resumes the protocol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OracleSafetyEvent {
+    /// Why the watchdog acted
+    pub reason: String,
+    /// Blocks since the last price update
+    pub price_age_blocks: Option<u64>,
+    /// Sources behind the last price
+    pub source_count: u8,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+/// Event emitted when an oracle operator is slashed for signing
+/// contradictory prices
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OracleSlashedEvent {
+    /// Operator slashed
+    pub operator: PublicKey,
+    /// Account that reported it
+    pub reporter: PublicKey,
+    /// Nonce both prices were signed with
+    pub nonce: u64,
+    /// The contradictory prices in cents
+    pub prices: (u64, u64),
+    /// Weight removed
+    pub weight_slashed: u64,
+    /// Weight left
+    pub weight: u64,
+    /// Stake removed
+    pub stake_slashed: CollateralAmount,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// BRIDGE EVENTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Event emitted when zkUSD is locked for another chain.
+///
+/// Relayers mint on the destination chain from these, in `sequence` order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeOutEvent {
+    /// Outbound transfer sequence number
+    pub sequence: u64,
+    /// Sender
+    pub sender: PublicKey,
+    /// Destination chain
+    pub dest_chain: ChainId,
+    /// Recipient address on the destination chain
+    pub dest_address: Vec<u8>,
+    /// Amount locked
+    pub amount: TokenAmount,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+/// Event emitted when zkUSD returning from another chain is released
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeInEvent {
+    /// Chain the tokens were burned on
+    pub source_chain: ChainId,
+    /// Burn transaction on the source chain
+    pub source_tx: Hash,
+    /// Recipient
+    pub recipient: PublicKey,
+    /// Amount released
+    pub amount: TokenAmount,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// SAVINGS EVENTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Event emitted when zkUSD is locked in the savings pot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavingsDepositEvent {
+    /// Depositor
+    pub depositor: PublicKey,
+    /// Amount locked
+    pub amount: TokenAmount,
+    /// Savings balance after the deposit
+    pub new_balance: TokenAmount,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+/// Event emitted when zkUSD is unlocked from the savings pot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavingsWithdrawEvent {
+    /// Depositor
+    pub depositor: PublicKey,
+    /// Amount unlocked
+    pub amount: TokenAmount,
+    /// Remaining savings balance
+    pub remaining: TokenAmount,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+/// Event emitted when savings interest accrues
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavingsAccruedEvent {
+    /// Interest paid to savers
+    pub interest: TokenAmount,
+    /// Savings rate applied, in basis points
+    pub rate_bps: u64,
+    /// Total locked after the accrual
+    pub total_locked: TokenAmount,
+    /// Fee reserve left to pay interest
+    pub reserve: TokenAmount,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+/// Event emitted when governance changes the savings rate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavingsRateChangedEvent {
+    /// Previous rate in basis points
+    pub old_rate_bps: u64,
+    /// New rate in basis points
+    pub new_rate_bps: u64,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// TREASURY EVENTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Event emitted when treasury funds are paid out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreasuryDisbursedEvent {
+    /// Governor that authorized the payment
+    pub governor: PublicKey,
+    /// Recipient
+    pub recipient: PublicKey,
+    /// Asset and amount paid
+    pub asset: TreasuryAsset,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+/// Event emitted when a keeper is paid a stipend for keeping the system current
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeeperRewardedEvent {
+    /// Account that submitted the operation
+    pub keeper: PublicKey,
+    /// Operation type rewarded
+    pub operation: String,
+    /// zkUSD paid
+    pub amount: TokenAmount,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// AUCTION EVENTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Event emitted when seized collateral is put up for auction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuctionStartedEvent {
+    /// Auction ID
+    pub auction_id: u64,
+    /// CDP the collateral was seized from
+    pub cdp_id: CDPId,
+    /// Collateral on sale
+    pub lot: CollateralAmount,
+    /// zkUSD to raise
+    pub tab: TokenAmount,
+    /// Starting price in cents per BTC
+    pub start_price: u64,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+/// Event emitted when collateral is bought at auction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuctionTakenEvent {
+    /// Auction ID
+    pub auction_id: u64,
+    /// Bidder
+    pub bidder: PublicKey,
+    /// Collateral bought
+    pub collateral: CollateralAmount,
+    /// zkUSD paid
+    pub cost: TokenAmount,
+    /// Price paid in cents per BTC
+    pub price: u64,
+    /// Whether the auction ended
+    pub closed: bool,
+    /// Collateral returned to the CDP owner
+    pub returned: CollateralAmount,
+    /// Tab left unraised when the lot ran out
+    pub bad_debt: TokenAmount,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+/// Event emitted when an expired auction is restarted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuctionResetEvent {
+    /// Auction ID
+    pub auction_id: u64,
+    /// New starting price in cents per BTC
+    pub start_price: u64,
+    /// Times the auction has been reset
+    pub resets: u32,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// FRONTEND EVENTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Event emitted when a stability pool frontend registers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrontendRegisteredEvent {
+    /// Frontend operator key
+    pub frontend: PublicKey,
+    /// Share of tagged depositors' gains paid to the frontend (in bps)
+    pub kickback_bps: u64,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+/// Event emitted when a frontend claims its kickbacks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrontendGainsClaimedEvent {
+    /// Frontend operator key
+    pub frontend: PublicKey,
+    /// BTC claimed
+    pub btc_amount: CollateralAmount,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // PROTOCOL EVENTS
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -454,6 +991,85 @@ pub struct RecoveryModeEvent {
     pub timestamp: u64,
 }
 
+/// Event emitted when an invariant fails at the end of a block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvariantViolatedEvent {
+    /// Name of the failed invariant
+    pub invariant: String,
+    /// What the check observed
+    pub detail: String,
+    /// Whether the protocol was paused in response
+    pub paused: bool,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+/// Event emitted when anomalies pause one kind of operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitBreakerTrippedEvent {
+    /// Operation paused
+    pub operation: PausableOperation,
+    /// Anomaly that tripped the breaker
+    pub reason: String,
+    /// Timestamp the operation resumes at
+    pub resumes_at: u64,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+/// Event emitted when a block's activity is unusual
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyDetectedEvent {
+    /// Metric that spiked
+    pub metric: AnomalyMetric,
+    /// Value in the block
+    pub value: u64,
+    /// Rolling mean before the block
+    pub mean: u64,
+    /// Standard deviations above the mean in basis points
+    pub z_score_bps: u64,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+/// Event emitted when the audit log head is anchored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditAnchoredEvent {
+    /// Entries in the log
+    pub entries: u64,
+    /// Hash of the last entry
+    pub head: Hash,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
+/// Event emitted when a parameter ramp starts or completes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterRampEvent {
+    /// Ramped parameter
+    pub parameter: ProtocolParameter,
+    /// Value when the ramp was scheduled
+    pub start_value: u64,
+    /// Value at the end of the ramp
+    pub target_value: u64,
+    /// Block of the first step
+    pub start_block: u64,
+    /// Block the target is reached at
+    pub end_block: u64,
+    /// Block height
+    pub block_height: u64,
+    /// Timestamp
+    pub timestamp: u64,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // EVENT LOG
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -508,6 +1124,11 @@ impl EventLog {
         self.events.clear();
     }
 
+    /// Drop all events after the first `len`
+    pub fn truncate(&mut self, len: usize) {
+        self.events.truncate(len);
+    }
+
     /// Compute merkle root of all events
     pub fn merkle_root(&self) -> Hash {
         use crate::utils::crypto::merkle_root;
@@ -0,0 +1,269 @@
+//! Protocol genesis.
+//!
+//! A genesis file fixes everything a network starts from: the protocol
+//! configuration, the oracle keys trusted to sign prices, the guardian set,
+//! the hashes of the circuit verification keys proofs must be generated
+//! against, and the Bitcoin block the network is anchored to. Nodes
+//! initialized from the same genesis share its hash; a node that is started
+//! against a database initialized from a different genesis refuses to run.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::core::config::{Network, ProtocolConfig};
+use crate::error::{Error, Result};
+use crate::protocol::codec::{canonical_hash, GENESIS_DOMAIN};
+use crate::utils::crypto::{Hash, PublicKey};
+use crate::zkp::verifier::VerificationKeyRegistry;
+
+/// Genesis format version
+pub const GENESIS_VERSION: u32 = 1;
+
+/// Hash of a circuit's verification key pinned at genesis
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenesisCircuitKey {
+    /// Circuit identifier
+    pub circuit_id: String,
+    /// Key version
+    pub version: u32,
+    /// SHA-256 of the key data
+    pub key_hash: Hash,
+}
+
+/// Bitcoin block a network is anchored to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChainAnchor {
+    /// Block height
+    pub height: u32,
+    /// Block hash
+    pub block_hash: Hash,
+}
+
+/// Initial state of a network
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Genesis {
+    /// Genesis format version
+    pub version: u32,
+    /// Network the genesis is for
+    pub network: Network,
+    /// Unix timestamp the network starts at
+    pub genesis_time: u64,
+    /// Initial protocol configuration
+    pub config: ProtocolConfig,
+    /// Keys trusted to sign oracle prices
+    #[serde(default)]
+    pub oracle_keys: Vec<PublicKey>,
+    /// Guardian keys
+    #[serde(default)]
+    pub guardians: Vec<PublicKey>,
+    /// Guardian signatures required to act
+    #[serde(default)]
+    pub guardian_threshold: u32,
+    /// Circuit verification keys, sorted by circuit ID
+    #[serde(default)]
+    pub circuit_keys: Vec<GenesisCircuitKey>,
+    /// Bitcoin block the network is anchored to
+    #[serde(default)]
+    pub anchor: Option<ChainAnchor>,
+}
+
+impl Genesis {
+    /// Create a genesis for `network` starting from `config`
+    pub fn new(network: Network, config: ProtocolConfig) -> Self {
+        Self {
+            version: GENESIS_VERSION,
+            network,
+            genesis_time: 0,
+            config,
+            oracle_keys: Vec::new(),
+            guardians: Vec::new(),
+            guardian_threshold: 0,
+            circuit_keys: Vec::new(),
+            anchor: None,
+        }
+    }
+
+    /// Create with a custom start time
+    pub fn with_genesis_time(mut self, genesis_time: u64) -> Self {
+        self.genesis_time = genesis_time;
+        self
+    }
+
+    /// Create with a custom oracle key set
+    pub fn with_oracle_keys(mut self, keys: Vec<PublicKey>) -> Self {
+        self.oracle_keys = keys;
+        self
+    }
+
+    /// Create with a guardian set requiring `threshold` signatures
+    pub fn with_guardians(mut self, guardians: Vec<PublicKey>, threshold: u32) -> Self {
+        self.guardians = guardians;
+        self.guardian_threshold = threshold;
+        self
+    }
+
+    /// Pin the current verification key of every circuit in `registry`
+    pub fn with_circuit_keys(mut self, registry: &VerificationKeyRegistry) -> Self {
+        self.circuit_keys = registry
+            .current_keys()
+            .into_iter()
+            .map(|key| GenesisCircuitKey {
+                circuit_id: key.circuit_id.clone(),
+                version: key.version,
+                key_hash: key.key_hash,
+            })
+            .collect();
+        self
+    }
+
+    /// Anchor the network to a Bitcoin block
+    pub fn with_anchor(mut self, height: u32, block_hash: Hash) -> Self {
+        self.anchor = Some(ChainAnchor { height, block_hash });
+        self
+    }
+
+    /// Check the genesis is well formed
+    pub fn validate(&self) -> Result<()> {
+        if self.version != GENESIS_VERSION {
+            return Err(invalid("version", format!("Unsupported genesis version {}", self.version)));
+        }
+        self.config.validate()?;
+
+        if has_duplicates(&self.oracle_keys) {
+            return Err(invalid("oracle_keys", "Duplicate oracle key"));
+        }
+        if has_duplicates(&self.guardians) {
+            return Err(invalid("guardians", "Duplicate guardian"));
+        }
+        let guardians = self.guardians.len() as u32;
+        if guardians > 0 && !(1..=guardians).contains(&self.guardian_threshold) {
+            return Err(invalid(
+                "guardian_threshold",
+                format!("Must be between 1 and {}, got {}", guardians, self.guardian_threshold),
+            ));
+        }
+        if guardians == 0 && self.guardian_threshold != 0 {
+            return Err(invalid("guardian_threshold", "Set without guardians"));
+        }
+
+        let circuits: BTreeSet<&str> = self.circuit_keys.iter().map(|key| key.circuit_id.as_str()).collect();
+        if circuits.len() != self.circuit_keys.len() {
+            return Err(invalid("circuit_keys", "Duplicate circuit"));
+        }
+        Ok(())
+    }
+
+    /// Check that `registry` holds the pinned verification key of every
+    /// genesis circuit
+    pub fn check_circuit_keys(&self, registry: &VerificationKeyRegistry) -> Result<()> {
+        for pinned in &self.circuit_keys {
+            let matches = registry
+                .get_version(&pinned.circuit_id, pinned.version)
+                .is_some_and(|key| key.key_hash == pinned.key_hash && key.verify_integrity());
+            if !matches {
+                return Err(invalid(
+                    "circuit_keys",
+                    format!("No key for {} v{} matching genesis", pinned.circuit_id, pinned.version),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Hash identifying the genesis, equal across nodes started from it
+    pub fn hash(&self) -> Hash {
+        canonical_hash(GENESIS_DOMAIN, self).unwrap_or_default()
+    }
+
+    /// Parse a genesis file
+    pub fn from_json(json: &str) -> Result<Self> {
+        let genesis: Self = serde_json::from_str(json).map_err(|e| Error::Deserialization(e.to_string()))?;
+        genesis.validate()?;
+        Ok(genesis)
+    }
+
+    /// Render as a genesis file
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| Error::Serialization(e.to_string()))
+    }
+
+    /// Load and validate a genesis file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let json = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| Error::Internal(format!("Failed to read genesis file: {}", e)))?;
+        Self::from_json(&json)
+    }
+}
+
+fn invalid(name: &str, reason: impl Into<String>) -> Error {
+    Error::InvalidParameter {
+        name: name.into(),
+        reason: reason.into(),
+    }
+}
+
+fn has_duplicates(keys: &[PublicKey]) -> bool {
+    let unique: BTreeSet<&[u8]> = keys.iter().map(|key| key.as_bytes().as_slice()).collect();
+    unique.len() != keys.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::crypto::KeyPair;
+    use crate::zkp::verifier::VerificationKey;
+
+    fn genesis() -> Genesis {
+        let mut registry = VerificationKeyRegistry::new();
+        registry.register(VerificationKey::new("mint", 2, vec![7u8; 32]));
+
+        Genesis::new(Network::Regtest, ProtocolConfig::default())
+            .with_genesis_time(1_700_000_000)
+            .with_oracle_keys(vec![*KeyPair::generate().public_key()])
+            .with_guardians(vec![*KeyPair::generate().public_key(), *KeyPair::generate().public_key()], 2)
+            .with_circuit_keys(&registry)
+            .with_anchor(840_000, Hash::sha256(b"block"))
+    }
+
+    #[test]
+    fn test_round_trip_keeps_hash() {
+        let genesis = genesis();
+        genesis.validate().unwrap();
+
+        let parsed = Genesis::from_json(&genesis.to_json().unwrap()).unwrap();
+        assert_eq!(parsed.to_json().unwrap(), genesis.to_json().unwrap());
+        assert_eq!(parsed.hash(), genesis.hash());
+
+        let later = genesis.clone().with_genesis_time(1_700_000_001);
+        assert_ne!(later.hash(), genesis.hash());
+    }
+
+    #[test]
+    fn test_validation() {
+        let guardian = *KeyPair::generate().public_key();
+        let bad_threshold = genesis().with_guardians(vec![guardian], 2);
+        assert!(bad_threshold.validate().is_err());
+
+        let duplicate = genesis().with_oracle_keys(vec![guardian, guardian]);
+        assert!(duplicate.validate().is_err());
+
+        assert!(Genesis::from_json(r#"{"version": 1, "unknown": true}"#).is_err());
+    }
+
+    #[test]
+    fn test_circuit_keys_checked() {
+        let genesis = genesis();
+
+        let mut registry = VerificationKeyRegistry::new();
+        registry.register(VerificationKey::new("mint", 2, vec![7u8; 32]));
+        genesis.check_circuit_keys(&registry).unwrap();
+
+        let mut other = VerificationKeyRegistry::new();
+        other.register(VerificationKey::new("mint", 2, vec![8u8; 32]));
+        assert!(genesis.check_circuit_keys(&other).is_err());
+    }
+}
@@ -0,0 +1,278 @@
+//! Protocol invariants.
+//!
+//! A registry of named checks evaluated by the state machine at the end of
+//! every block, against the block's final state. What happens when one fails
+//! depends on the enforcement mode:
+//!
+//! - **Halt** (development and test networks): the block is rejected and
+//!   none of its writes are persisted, so the fault surfaces immediately
+//! - **Pause** (mainnet): the block is kept, an `InvariantViolated` event is
+//!   emitted (raised as an emergency alert by the monitoring dashboard) and
+//!   the protocol pauses until governance investigates
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::cdp::CDPManager;
+use crate::core::config::Network;
+use crate::core::savings::SavingsPot;
+use crate::core::token::ZkUSD;
+use crate::core::vault::Vault;
+use crate::liquidation::stability_pool::StabilityPool;
+use crate::utils::constants::{RATIO_PRECISION, SATS_PER_BTC};
+
+/// Protocol state an invariant is checked against
+#[derive(Debug, Clone, Copy)]
+pub struct InvariantContext<'a> {
+    /// CDPs
+    pub cdps: &'a CDPManager,
+    /// zkUSD ledger
+    pub token: &'a ZkUSD,
+    /// Collateral vault
+    pub vault: &'a Vault,
+    /// Stability pool
+    pub stability_pool: &'a StabilityPool,
+    /// Savings pot
+    pub savings: &'a SavingsPot,
+    /// BTC price in cents
+    pub btc_price: u64,
+    /// Total collateral ratio as computed by the state machine from the
+    /// vault and CDP debt, `None` if the computation failed
+    pub tcr: Option<u64>,
+}
+
+/// An invariant check: `Err` describes what was observed
+pub type InvariantCheck = fn(&InvariantContext<'_>) -> Result<(), String>;
+
+/// A named invariant
+#[derive(Debug, Clone, Copy)]
+pub struct Invariant {
+    /// Stable name, used in events and logs
+    pub name: &'static str,
+    /// The check
+    pub check: InvariantCheck,
+}
+
+/// A failed invariant
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvariantViolation {
+    /// Name of the invariant
+    pub invariant: String,
+    /// What the check observed
+    pub detail: String,
+}
+
+/// Response to a failed invariant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InvariantEnforcement {
+    /// Reject the block
+    Halt,
+    /// Keep the block, emit an emergency event and pause the protocol
+    Pause,
+}
+
+impl InvariantEnforcement {
+    /// Enforcement for a network: mainnet pauses, every other network halts
+    pub fn for_network(network: Network) -> Self {
+        match network {
+            Network::Mainnet => Self::Pause,
+            _ => Self::Halt,
+        }
+    }
+}
+
+impl Default for InvariantEnforcement {
+    /// Halt in debug builds, pause in release builds
+    fn default() -> Self {
+        if cfg!(debug_assertions) {
+            Self::Halt
+        } else {
+            Self::Pause
+        }
+    }
+}
+
+/// Registry of invariants and how to enforce them
+#[derive(Debug, Clone)]
+pub struct InvariantChecker {
+    /// Registered invariants, in evaluation order
+    invariants: Vec<Invariant>,
+    /// Response to a failure
+    enforcement: InvariantEnforcement,
+}
+
+impl Default for InvariantChecker {
+    fn default() -> Self {
+        Self::new(InvariantEnforcement::default())
+    }
+}
+
+impl InvariantChecker {
+    /// Create a checker with the built-in invariants
+    pub fn new(enforcement: InvariantEnforcement) -> Self {
+        let mut checker = Self::empty(enforcement);
+        checker.register("supply_covers_debt", supply_covers_debt);
+        checker.register("vault_matches_collateral", vault_matches_collateral);
+        checker.register("pool_accounting", pool_accounting);
+        checker.register("tcr_math", tcr_math);
+        checker
+    }
+
+    /// Create a checker without invariants
+    pub fn empty(enforcement: InvariantEnforcement) -> Self {
+        Self {
+            invariants: Vec::new(),
+            enforcement,
+        }
+    }
+
+    /// Register an invariant, replacing any with the same name
+    pub fn register(&mut self, name: &'static str, check: InvariantCheck) {
+        match self.invariants.iter_mut().find(|invariant| invariant.name == name) {
+            Some(invariant) => invariant.check = check,
+            None => self.invariants.push(Invariant { name, check }),
+        }
+    }
+
+    /// Remove an invariant, returning whether it was registered
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.invariants.len();
+        self.invariants.retain(|invariant| invariant.name != name);
+        self.invariants.len() != before
+    }
+
+    /// Names of the registered invariants
+    pub fn names(&self) -> Vec<&'static str> {
+        self.invariants.iter().map(|invariant| invariant.name).collect()
+    }
+
+    /// Response to a failure
+    pub fn enforcement(&self) -> InvariantEnforcement {
+        self.enforcement
+    }
+
+    /// Evaluate every invariant, returning the failures
+    pub fn check(&self, ctx: &InvariantContext<'_>) -> Vec<InvariantViolation> {
+        self.invariants
+            .iter()
+            .filter_map(|invariant| {
+                (invariant.check)(ctx).err().map(|detail| InvariantViolation {
+                    invariant: invariant.name.to_string(),
+                    detail,
+                })
+            })
+            .collect()
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// BUILT-IN INVARIANTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Every cent of CDP debt was minted as zkUSD that is still circulating, in
+/// the stability pool or in the savings pot. The converse does not hold:
+/// direct liquidations and auctions clear debt before any tokens are burned.
+fn supply_covers_debt(ctx: &InvariantContext<'_>) -> Result<(), String> {
+    let debt: u128 = ctx.cdps.all_cdps().iter().map(|cdp| cdp.debt_cents as u128).sum();
+    let supply = ctx.token.total_supply().cents() as u128;
+    let pool = ctx.stability_pool.total_deposits().cents() as u128;
+    let savings = ctx.savings.total_locked().cents() as u128 + ctx.savings.reserve().cents() as u128;
+
+    if debt > supply + pool + savings {
+        return Err(format!(
+            "CDP debt {} exceeds supply {} + pool {} + savings {}",
+            debt, supply, pool, savings
+        ));
+    }
+    Ok(())
+}
+
+/// The vault holds exactly the collateral of the CDPs
+fn vault_matches_collateral(ctx: &InvariantContext<'_>) -> Result<(), String> {
+    let collateral: u128 = ctx.cdps.all_cdps().iter().map(|cdp| cdp.collateral_sats as u128).sum();
+    let vault = ctx.vault.total_collateral().sats() as u128;
+
+    if vault != collateral {
+        return Err(format!("Vault holds {} sats, CDPs hold {}", vault, collateral));
+    }
+    Ok(())
+}
+
+/// Depositors are never owed more than the pool holds
+fn pool_accounting(ctx: &InvariantContext<'_>) -> Result<(), String> {
+    let owed = ctx.stability_pool.total_current_value().cents();
+    let held = ctx.stability_pool.total_deposits().cents();
+
+    if owed > held {
+        return Err(format!("Depositors are owed {} but the pool holds {}", owed, held));
+    }
+    Ok(())
+}
+
+/// The reported TCR matches one recomputed from the CDPs themselves, so a
+/// drifting vault total or a faulty ratio computation is caught
+fn tcr_math(ctx: &InvariantContext<'_>) -> Result<(), String> {
+    let Some(reported) = ctx.tcr else {
+        return Err("TCR could not be computed".to_string());
+    };
+
+    let (collateral, debt) = ctx.cdps.all_cdps().iter().fold((0u128, 0u128), |(collateral, debt), cdp| {
+        (collateral + cdp.collateral_sats as u128, debt + cdp.debt_cents as u128)
+    });
+    let expected = if debt == 0 {
+        u64::MAX
+    } else {
+        let value = collateral * ctx.btc_price as u128 * RATIO_PRECISION as u128;
+        (value / (SATS_PER_BTC as u128 * debt)).min(u64::MAX as u128) as u64
+    };
+
+    if reported != expected {
+        return Err(format!("TCR reported as {}%, CDPs give {}%", reported, expected));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::cdp::CDP;
+    use crate::utils::crypto::KeyPair;
+
+    #[test]
+    fn test_builtin_invariants() {
+        let owner = *KeyPair::generate().public_key();
+        let mut cdps = CDPManager::new();
+        let mut cdp = CDP::with_collateral(owner, 100_000_000, 1, 1).unwrap();
+        cdp.debt_cents = 5_000;
+        cdps.register(cdp).unwrap();
+
+        let token = ZkUSD::new();
+        let vault = Vault::new();
+        let pool = StabilityPool::new();
+        let savings = SavingsPot::new();
+        let ctx = InvariantContext {
+            cdps: &cdps,
+            token: &token,
+            vault: &vault,
+            stability_pool: &pool,
+            savings: &savings,
+            btc_price: 10_000_000,
+            tcr: Some(200_000),
+        };
+
+        let checker = InvariantChecker::new(InvariantEnforcement::Halt);
+        let failed: Vec<_> = checker.check(&ctx).into_iter().map(|v| v.invariant).collect();
+        assert_eq!(failed, vec!["supply_covers_debt", "vault_matches_collateral"]);
+
+        // A TCR that disagrees with the CDPs' own totals
+        let overstated = InvariantContext { tcr: Some(u64::MAX), ..ctx };
+        let failed: Vec<_> = checker.check(&overstated).into_iter().map(|v| v.invariant).collect();
+        assert!(failed.contains(&"tcr_math".to_string()));
+
+        let mut checker = checker;
+        assert!(checker.remove("supply_covers_debt"));
+        checker.register("vault_matches_collateral", |_| Ok(()));
+        assert!(checker.check(&ctx).is_empty());
+        assert_eq!(checker.names(), vec!["vault_matches_collateral", "pool_accounting", "tcr_math"]);
+        assert_eq!(InvariantEnforcement::for_network(Network::Mainnet), InvariantEnforcement::Pause);
+    }
+}
@@ -0,0 +1,240 @@
+//! Operation mempool.
+//!
+//! Holds signed operations between submission and block execution. Their
+//! signatures are verified on admission, in batches, through a
+//! [`SignatureCache`] shared with the state machine
+//! ([`ProtocolStateMachine::with_signature_cache`](crate::protocol::ProtocolStateMachine::with_signature_cache)),
+//! so execution finds them already verified.
+//!
+//! Admission only checks signatures. Nonces, balances and owner policies
+//! depend on state at execution time and are checked there. Signatures are
+//! checked against the mempool's [`SigningDomain`]; legacy untyped
+//! signatures are admitted only while
+//! [`with_legacy_signatures`](Mempool::with_legacy_signatures) is set.
+
+use std::collections::{HashSet, VecDeque};
+
+use crate::error::{Error, Result};
+use crate::protocol::operations::ProtocolOperation;
+use crate::protocol::signing::SigningDomain;
+use crate::utils::constants::MAX_MEMPOOL_SIZE;
+use crate::utils::crypto::{Hash, SignatureCache, SignatureCheck};
+
+/// Pending operations in submission order
+#[derive(Debug)]
+pub struct Mempool {
+    /// Operations awaiting execution
+    operations: VecDeque<(Hash, ProtocolOperation)>,
+    /// Hashes of the pending operations
+    pending: HashSet<Hash>,
+    /// Maximum pending operations
+    capacity: usize,
+    /// Verified signatures, shared with the executor
+    signatures: SignatureCache,
+    /// Domain operations must be signed for
+    domain: SigningDomain,
+    /// Whether legacy untyped signatures are admitted
+    legacy: bool,
+}
+
+impl Default for Mempool {
+    fn default() -> Self {
+        Self::new(SignatureCache::default())
+    }
+}
+
+impl Mempool {
+    /// Create a mempool verifying into `signatures`
+    pub fn new(signatures: SignatureCache) -> Self {
+        Self {
+            operations: VecDeque::new(),
+            pending: HashSet::new(),
+            capacity: MAX_MEMPOOL_SIZE,
+            signatures,
+            domain: SigningDomain::default(),
+            legacy: false,
+        }
+    }
+
+    /// Set the domain operations must be signed for
+    pub fn with_signing_domain(mut self, domain: SigningDomain) -> Self {
+        self.domain = domain;
+        self
+    }
+
+    /// Admit operations carrying legacy untyped signatures. Keep this in
+    /// step with the state machine's legacy window.
+    pub fn with_legacy_signatures(mut self, legacy: bool) -> Self {
+        self.legacy = legacy;
+        self
+    }
+
+    /// Set the maximum number of pending operations
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// The signature cache admissions are verified into
+    pub fn signature_cache(&self) -> SignatureCache {
+        self.signatures.clone()
+    }
+
+    /// Admit a single operation
+    pub fn submit(&mut self, op: ProtocolOperation) -> Result<Hash> {
+        self.submit_batch(vec![op]).remove(0)
+    }
+
+    /// Admit several operations, verifying all their signatures in one batch
+    pub fn submit_batch(&mut self, ops: Vec<ProtocolOperation>) -> Vec<Result<Hash>> {
+        let prepared: Vec<Result<(Hash, ProtocolOperation, Vec<SignatureCheck>)>> = ops
+            .into_iter()
+            .map(|op| {
+                let hash = op.hash()?;
+                let checks = op.signature_checks(&self.domain)?;
+                Ok((hash, op, checks))
+            })
+            .collect();
+
+        let all_checks: Vec<_> = prepared
+            .iter()
+            .filter_map(|entry| entry.as_ref().ok())
+            .flat_map(|(_, _, checks)| checks.iter().copied())
+            .collect();
+        let mut verified = self.signatures.verify_batch(&all_checks).into_iter();
+
+        prepared
+            .into_iter()
+            .map(|entry| {
+                let (hash, op, checks) = entry?;
+                let mut results: Vec<bool> = verified.by_ref().take(checks.len()).collect();
+                if self.legacy && results.iter().any(|valid| !valid) {
+                    results = self.signatures.verify_batch(&op.legacy_signature_checks()?);
+                }
+                self.admit(hash, op, &results)
+            })
+            .collect()
+    }
+
+    /// Take up to `max` operations, oldest first, for the next block
+    pub fn drain(&mut self, max: usize) -> Vec<ProtocolOperation> {
+        let count = max.min(self.operations.len());
+        self.operations
+            .drain(..count)
+            .map(|(hash, op)| {
+                self.pending.remove(&hash);
+                op
+            })
+            .collect()
+    }
+
+    /// Whether an operation is pending
+    pub fn contains(&self, hash: &Hash) -> bool {
+        self.pending.contains(hash)
+    }
+
+    /// Number of pending operations
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Whether the mempool is empty
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Admit a verified operation. `signatures` holds the signer's result
+    /// followed by the co-signatures'; the signer's may be invalid only when
+    /// co-signatures can satisfy a multi-key owner policy.
+    fn admit(&mut self, hash: Hash, op: ProtocolOperation, signatures: &[bool]) -> Result<Hash> {
+        let (signer_valid, cosigs_valid) = match signatures.split_first() {
+            Some((signer, cosigs)) => (*signer, cosigs.iter().all(|valid| *valid)),
+            None => (false, true),
+        };
+        let has_cosigs = signatures.len() > 1;
+        if !cosigs_valid || (!signer_valid && !has_cosigs) {
+            return Err(Error::InvalidSignature);
+        }
+
+        if self.pending.contains(&hash) {
+            return Err(Error::InvalidParameter {
+                name: "operation".into(),
+                reason: format!("Operation {} is already pending", hash.to_hex()),
+            });
+        }
+        if self.operations.len() >= self.capacity {
+            return Err(Error::InvalidParameter {
+                name: "mempool".into(),
+                reason: format!("Mempool is full ({} operations)", self.capacity),
+            });
+        }
+
+        tracing::trace!(op = op.operation_type(), hash = %hash.to_hex(), "Operation admitted");
+        self.pending.insert(hash);
+        self.operations.push_back((hash, op));
+        Ok(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::token::TokenAmount;
+    use crate::protocol::operations::{Operation, TransferOp};
+    use crate::utils::crypto::{KeyPair, Signature};
+
+    fn transfer(keypair: &KeyPair, nonce: u64) -> TransferOp {
+        let mut op = TransferOp {
+            from: *keypair.public_key(),
+            to: *KeyPair::generate().public_key(),
+            amount: TokenAmount::from_cents(100),
+            nonce,
+            signature: Signature::new([0u8; 64]),
+        };
+        op.sign(keypair, &SigningDomain::default()).unwrap();
+        op
+    }
+
+    #[test]
+    fn test_admission_verifies_into_cache() {
+        let keypair = KeyPair::generate();
+        let mut mempool = Mempool::default().with_capacity(2);
+        let cache = mempool.signature_cache();
+
+        let valid = transfer(&keypair, 1);
+        let mut forged = transfer(&keypair, 2);
+        forged.amount = TokenAmount::from_cents(1_000_000);
+
+        let results = mempool.submit_batch(vec![
+            ProtocolOperation::Transfer(valid.clone()),
+            ProtocolOperation::Transfer(forged),
+        ]);
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(Error::InvalidSignature)));
+        assert!(cache.contains(&valid.from, &valid.signing_hash(&SigningDomain::default()).unwrap(), &valid.signature));
+        assert_eq!(cache.len(), 1);
+
+        assert!(mempool.submit(ProtocolOperation::Transfer(valid)).is_err());
+        mempool.submit(ProtocolOperation::Transfer(transfer(&keypair, 3))).unwrap();
+        assert!(mempool.submit(ProtocolOperation::Transfer(transfer(&keypair, 4))).is_err());
+
+        let drained = mempool.drain(10);
+        assert_eq!(drained.len(), 2);
+        assert_eq!(drained[0].nonce(), 1);
+        assert!(mempool.is_empty());
+    }
+
+    #[test]
+    fn test_legacy_signatures() {
+        let keypair = KeyPair::generate();
+        let mut legacy = transfer(&keypair, 1);
+        legacy.signature = keypair.sign(&legacy.legacy_signing_hash().unwrap());
+        let op = ProtocolOperation::Transfer(legacy);
+
+        let mut strict = Mempool::default();
+        assert!(matches!(strict.submit(op.clone()), Err(Error::InvalidSignature)));
+
+        let mut window = Mempool::default().with_legacy_signatures(true);
+        window.submit(op).unwrap();
+    }
+}
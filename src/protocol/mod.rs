@@ -3,10 +3,42 @@
 //! This module provides the central state machine that orchestrates
 //! all zkUSD protocol operations atomically and safely.
 
+pub mod audit;
+pub mod automation;
+pub mod codec;
 pub mod events;
+pub mod genesis;
+pub mod invariants;
+pub mod mempool;
+pub mod nonces;
 pub mod operations;
+pub mod pauses;
+pub mod ramps;
+pub mod rate_limit;
+pub mod receipts;
+pub mod safety;
+pub mod signing;
 pub mod state_machine;
+pub mod sync;
+pub mod treasury;
+pub mod view;
 
+pub use audit::*;
+pub use automation::*;
+pub use codec::*;
 pub use events::*;
+pub use genesis::*;
+pub use invariants::*;
+pub use mempool::*;
+pub use nonces::*;
 pub use operations::*;
+pub use pauses::*;
+pub use ramps::*;
+pub use rate_limit::*;
+pub use receipts::*;
+pub use safety::*;
+pub use signing::*;
 pub use state_machine::*;
+pub use sync::*;
+pub use treasury::*;
+pub use view::*;
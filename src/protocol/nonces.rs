@@ -0,0 +1,218 @@
+//! Replay protection with a sliding nonce window.
+//!
+//! Requiring every nonce to exceed the last one breaks when several devices
+//! share a key: two operations signed concurrently race, and the one landing
+//! second is rejected. Instead each signer has a window of the
+//! [`NONCE_WINDOW`] nonces up to the highest one used. A nonce above the
+//! highest moves the window up; one inside it is accepted once. Devices can
+//! then submit out of order, and a gap left by a dropped operation can still
+//! be filled while it is inside the window.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::utils::crypto::{Hash, PublicKey};
+use crate::utils::undo::{Undo, UndoMap};
+
+/// Nonces below the highest used one that may still be accepted, counting
+/// the highest itself
+pub const NONCE_WINDOW: u64 = 64;
+
+/// Nonces one signer used near its highest
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NonceWindow {
+    /// Highest nonce used (0 if none)
+    highest: u64,
+    /// Bit `i` is set when nonce `highest - i` was used
+    used: u64,
+}
+
+impl NonceWindow {
+    /// Window whose nonces up to `highest` count as used, for signers
+    /// recorded before windows existed
+    pub fn from_highest(highest: u64) -> Self {
+        Self {
+            highest,
+            used: if highest == 0 { 0 } else { u64::MAX },
+        }
+    }
+
+    /// Highest nonce used (0 if none)
+    pub fn highest(&self) -> u64 {
+        self.highest
+    }
+
+    /// Next nonce expected, one above the highest used
+    pub fn next(&self) -> u64 {
+        self.highest.saturating_add(1)
+    }
+
+    /// Lowest nonce still accepted
+    pub fn floor(&self) -> u64 {
+        self.highest.saturating_sub(NONCE_WINDOW - 1).max(1)
+    }
+
+    /// Whether `nonce` would be accepted
+    pub fn is_available(&self, nonce: u64) -> bool {
+        if nonce == u64::MAX {
+            return false;
+        }
+        if nonce > self.highest {
+            return true;
+        }
+        nonce >= self.floor() && self.used & (1 << (self.highest - nonce)) == 0
+    }
+
+    /// Unused nonces below the highest that may still be filled
+    pub fn gaps(&self) -> Vec<u64> {
+        (self.floor()..self.highest).filter(|&nonce| self.is_available(nonce)).collect()
+    }
+
+    /// Mark `nonce` used. `u64::MAX` is never accepted, so there is always
+    /// a next nonce.
+    pub fn accept(&mut self, nonce: u64) -> Result<()> {
+        if nonce == u64::MAX {
+            return Err(Error::InvalidParameter {
+                name: "nonce".into(),
+                reason: format!("Nonce {} is reserved", nonce),
+            });
+        }
+        if nonce > self.highest {
+            let shift = nonce - self.highest;
+            self.used = if shift >= NONCE_WINDOW { 0 } else { self.used << shift };
+            self.used |= 1;
+            self.highest = nonce;
+            return Ok(());
+        }
+
+        let reason = if nonce < self.floor() {
+            format!("Nonce {} is below the window, expected >= {}", nonce, self.floor())
+        } else if !self.is_available(nonce) {
+            format!("Nonce {} already used, next expected {}", nonce, self.next())
+        } else {
+            self.used |= 1 << (self.highest - nonce);
+            return Ok(());
+        };
+        Err(Error::InvalidParameter {
+            name: "nonce".into(),
+            reason,
+        })
+    }
+}
+
+/// Nonce windows of every signer, by public key hash
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NonceManager {
+    windows: UndoMap<HashMap<[u8; 32], NonceWindow>>,
+}
+
+impl NonceManager {
+    /// Create with no nonces used
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Migrate the last nonce used by each signer; every nonce up to it
+    /// stays used
+    pub fn from_highest(nonces: HashMap<[u8; 32], u64>) -> Self {
+        Self {
+            windows: nonces
+                .into_iter()
+                .map(|(key, highest)| (key, NonceWindow::from_highest(highest)))
+                .collect::<HashMap<_, _>>()
+                .into(),
+        }
+    }
+
+    /// Window of `signer`
+    pub fn window(&self, signer: &PublicKey) -> NonceWindow {
+        self.windows.get(&Self::key(signer)).copied().unwrap_or_default()
+    }
+
+    /// Highest nonce `signer` used (0 if none)
+    pub fn highest(&self, signer: &PublicKey) -> u64 {
+        self.window(signer).highest()
+    }
+
+    /// Next nonce expected from `signer`
+    pub fn next(&self, signer: &PublicKey) -> u64 {
+        self.window(signer).next()
+    }
+
+    /// Use `nonce` for `signer`, unless it was used or fell out of the window
+    pub fn accept(&mut self, signer: &PublicKey, nonce: u64) -> Result<()> {
+        self.windows.get_or_insert_with(Self::key(signer), NonceWindow::default).accept(nonce)
+    }
+
+    fn key(signer: &PublicKey) -> [u8; 32] {
+        *Hash::sha256(signer.as_bytes()).as_bytes()
+    }
+}
+
+impl Undo for NonceManager {
+    type Mark = ();
+
+    fn begin_undo(&mut self) {
+        self.windows.begin_undo();
+    }
+
+    fn commit_undo(&mut self) {
+        self.windows.commit_undo();
+    }
+
+    fn rollback_undo(&mut self, _mark: ()) {
+        self.windows.rollback_undo();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_out_of_order_within_window() {
+        let mut window = NonceWindow::default();
+        assert!(window.accept(0).is_err());
+
+        // Two devices signed 1..=3 and 4..=5 concurrently
+        for nonce in [4, 1, 5, 3] {
+            window.accept(nonce).unwrap();
+        }
+        assert_eq!(window.next(), 6);
+        assert_eq!(window.gaps(), [2]);
+        assert!(window.accept(3).is_err(), "replay");
+        window.accept(2).unwrap();
+        assert!(window.gaps().is_empty());
+
+        // A jump leaves everything below the new window unusable
+        window.accept(100).unwrap();
+        assert_eq!(window.floor(), 37);
+        assert!(window.accept(36).is_err());
+        window.accept(37).unwrap();
+        assert!(!window.is_available(37));
+        assert!(window.is_available(99));
+    }
+
+    #[test]
+    fn test_migrated_nonces_stay_used() {
+        let window = NonceWindow::from_highest(10);
+        assert!((1..=10).all(|nonce| !window.is_available(nonce)));
+        assert!(window.gaps().is_empty());
+        assert_eq!(window.next(), 11);
+        assert_eq!(NonceWindow::from_highest(0), NonceWindow::default());
+    }
+
+    #[test]
+    fn test_highest_nonce_reserved() {
+        let mut window = NonceWindow::default();
+        assert!(!window.is_available(u64::MAX));
+        assert!(window.accept(u64::MAX).is_err());
+
+        window.accept(u64::MAX - 1).unwrap();
+        assert_eq!(window.next(), u64::MAX);
+        assert!(window.accept(u64::MAX - 1).is_err(), "replay");
+        assert_eq!(NonceWindow::from_highest(u64::MAX).next(), u64::MAX);
+    }
+}
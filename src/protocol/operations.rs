@@ -6,10 +6,19 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::core::cdp::CDPId;
-use crate::core::token::TokenAmount;
+use crate::charms::bridge::{Attestation, ChainId, InboundTransfer};
+use crate::core::cdp::{CDPId, OwnerPolicy, SessionAction, SessionGrant, SessionKey};
+use crate::core::token::{ApprovePermit, TokenAmount};
 use crate::core::vault::CollateralAmount;
-use crate::utils::crypto::{PublicKey, Signature};
+use crate::error::{Error, Result};
+use crate::oracle::price_feed::TradingPair;
+use crate::oracle::registry::Equivocation;
+use crate::protocol::codec::{canonical_hash, OPERATION_DOMAIN};
+use crate::protocol::signing::{SigningDomain, SigningPayload};
+use crate::protocol::treasury::TreasuryAsset;
+use crate::utils::codec;
+use crate::utils::constants::{MAX_OPERATION_SIZE, SIGNATURE_LENGTH};
+use crate::utils::crypto::{Hash, KeyPair, PublicKey, Signature, SignatureCheck};
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // OPERATION TRAIT
@@ -29,8 +38,97 @@ pub trait Operation: Sized + Send + Sync {
     /// Get the signature
     fn signature(&self) -> &Signature;
 
+    /// Get mutable access to the signature
+    fn signature_mut(&mut self) -> &mut Signature;
+
     /// Get the nonce for replay protection
     fn nonce(&self) -> u64;
+
+    /// CDP whose owner policy must authorize this operation, if any
+    fn governed_cdp(&self) -> Option<&CDPId> {
+        None
+    }
+
+    /// Additional signatures for multi-key owner policies
+    fn cosignatures(&self) -> &[CoSignature] {
+        &[]
+    }
+
+    /// Mutable access to the co-signatures, for operations that carry them
+    fn cosignatures_mut(&mut self) -> Option<&mut Vec<CoSignature>> {
+        None
+    }
+
+    /// Untyped hash signatures covered before typed signing payloads (the
+    /// operation with its signature and co-signatures cleared). Only accepted
+    /// during the legacy signature window.
+    fn legacy_signing_hash(&self) -> Result<Hash>
+    where
+        Self: Clone + Serialize,
+    {
+        let mut unsigned = self.clone();
+        *unsigned.signature_mut() = Signature::new([0u8; SIGNATURE_LENGTH]);
+        if let Some(cosignatures) = unsigned.cosignatures_mut() {
+            cosignatures.clear();
+        }
+        canonical_hash(OPERATION_DOMAIN, &unsigned)
+    }
+
+    /// Payload a wallet displays and signs for this operation in `domain`
+    fn signing_payload(&self, domain: &SigningDomain) -> Result<SigningPayload>
+    where
+        Self: Serialize,
+    {
+        SigningPayload::of(self, domain)
+    }
+
+    /// Hash covered by the signature in `domain`
+    fn signing_hash(&self, domain: &SigningDomain) -> Result<Hash>
+    where
+        Self: Serialize,
+    {
+        Ok(self.signing_payload(domain)?.hash())
+    }
+
+    /// Sign the operation in place for `domain`
+    fn sign(&mut self, keypair: &KeyPair, domain: &SigningDomain) -> Result<()>
+    where
+        Self: Serialize,
+    {
+        let hash = self.signing_hash(domain)?;
+        *self.signature_mut() = keypair.sign(&hash);
+        Ok(())
+    }
+
+    /// Add a co-signature from `keypair` for `domain`
+    fn cosign(&mut self, keypair: &KeyPair, domain: &SigningDomain) -> Result<()>
+    where
+        Self: Serialize,
+    {
+        let hash = self.signing_hash(domain)?;
+        let cosignature = CoSignature {
+            signer: *keypair.public_key(),
+            signature: keypair.sign(&hash),
+        };
+        let op_type = self.operation_type();
+        self.cosignatures_mut()
+            .ok_or_else(|| Error::InvalidParameter {
+                name: "cosignature".into(),
+                reason: format!("{} does not take co-signatures", op_type),
+            })?
+            .push(cosignature);
+        Ok(())
+    }
+}
+
+/// Signature from an additional key of a CDP owner policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct CoSignature {
+    /// Co-signing key
+    pub signer: PublicKey,
+    /// Signature over the operation's signing hash
+    pub signature: Signature,
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -39,6 +137,7 @@ pub trait Operation: Sized + Send + Sync {
 
 /// Open a new CDP
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct OpenCDPOp {
     /// Owner of the new CDP
     pub owner: PublicKey,
@@ -67,6 +166,10 @@ impl Operation for OpenCDPOp {
         &self.signature
     }
 
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
     fn nonce(&self) -> u64 {
         self.nonce
     }
@@ -85,6 +188,7 @@ pub struct OpenCDPResult {
 
 /// Deposit collateral to a CDP
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct DepositCollateralOp {
     /// CDP to deposit to
     pub cdp_id: CDPId,
@@ -113,6 +217,10 @@ impl Operation for DepositCollateralOp {
         &self.signature
     }
 
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
     fn nonce(&self) -> u64 {
         self.nonce
     }
@@ -129,6 +237,7 @@ pub struct DepositResult {
 
 /// Withdraw collateral from a CDP
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct WithdrawCollateralOp {
     /// CDP to withdraw from
     pub cdp_id: CDPId,
@@ -140,6 +249,9 @@ pub struct WithdrawCollateralOp {
     pub nonce: u64,
     /// Signature
     pub signature: Signature,
+    /// Co-signatures for multi-key owner policies
+    #[serde(default)]
+    pub cosignatures: Vec<CoSignature>,
 }
 
 impl Operation for WithdrawCollateralOp {
@@ -157,9 +269,25 @@ impl Operation for WithdrawCollateralOp {
         &self.signature
     }
 
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
     fn nonce(&self) -> u64 {
         self.nonce
     }
+
+    fn governed_cdp(&self) -> Option<&CDPId> {
+        Some(&self.cdp_id)
+    }
+
+    fn cosignatures(&self) -> &[CoSignature] {
+        &self.cosignatures
+    }
+
+    fn cosignatures_mut(&mut self) -> Option<&mut Vec<CoSignature>> {
+        Some(&mut self.cosignatures)
+    }
 }
 
 /// Result of withdrawing collateral
@@ -175,6 +303,7 @@ pub struct WithdrawResult {
 
 /// Mint zkUSD from a CDP
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct MintDebtOp {
     /// CDP to mint from
     pub cdp_id: CDPId,
@@ -188,6 +317,9 @@ pub struct MintDebtOp {
     pub nonce: u64,
     /// Signature
     pub signature: Signature,
+    /// Co-signatures for multi-key owner policies
+    #[serde(default)]
+    pub cosignatures: Vec<CoSignature>,
 }
 
 impl Operation for MintDebtOp {
@@ -205,9 +337,25 @@ impl Operation for MintDebtOp {
         &self.signature
     }
 
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
     fn nonce(&self) -> u64 {
         self.nonce
     }
+
+    fn governed_cdp(&self) -> Option<&CDPId> {
+        Some(&self.cdp_id)
+    }
+
+    fn cosignatures(&self) -> &[CoSignature] {
+        &self.cosignatures
+    }
+
+    fn cosignatures_mut(&mut self) -> Option<&mut Vec<CoSignature>> {
+        Some(&mut self.cosignatures)
+    }
 }
 
 /// Result of minting debt
@@ -227,6 +375,7 @@ pub struct MintResult {
 
 /// Repay zkUSD debt
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct RepayDebtOp {
     /// CDP to repay
     pub cdp_id: CDPId,
@@ -255,6 +404,10 @@ impl Operation for RepayDebtOp {
         &self.signature
     }
 
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
     fn nonce(&self) -> u64 {
         self.nonce
     }
@@ -273,6 +426,7 @@ pub struct RepayResult {
 
 /// Close a CDP
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct CloseCDPOp {
     /// CDP to close
     pub cdp_id: CDPId,
@@ -282,6 +436,9 @@ pub struct CloseCDPOp {
     pub nonce: u64,
     /// Signature
     pub signature: Signature,
+    /// Co-signatures for multi-key owner policies
+    #[serde(default)]
+    pub cosignatures: Vec<CoSignature>,
 }
 
 impl Operation for CloseCDPOp {
@@ -299,9 +456,25 @@ impl Operation for CloseCDPOp {
         &self.signature
     }
 
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
     fn nonce(&self) -> u64 {
         self.nonce
     }
+
+    fn governed_cdp(&self) -> Option<&CDPId> {
+        Some(&self.cdp_id)
+    }
+
+    fn cosignatures(&self) -> &[CoSignature] {
+        &self.cosignatures
+    }
+
+    fn cosignatures_mut(&mut self) -> Option<&mut Vec<CoSignature>> {
+        Some(&mut self.cosignatures)
+    }
 }
 
 /// Result of closing a CDP
@@ -311,8 +484,186 @@ pub struct CloseResult {
     pub collateral_returned: CollateralAmount,
 }
 
+/// Replace the owner policy of a CDP (authorized by the current policy)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct SetOwnerPolicyOp {
+    /// CDP to update
+    pub cdp_id: CDPId,
+    /// Owner (must be CDP owner)
+    pub owner: PublicKey,
+    /// New policy
+    pub policy: OwnerPolicy,
+    /// Nonce
+    pub nonce: u64,
+    /// Signature
+    pub signature: Signature,
+    /// Co-signatures for multi-key owner policies
+    #[serde(default)]
+    pub cosignatures: Vec<CoSignature>,
+}
+
+impl Operation for SetOwnerPolicyOp {
+    type Result = SetOwnerPolicyResult;
+
+    fn operation_type(&self) -> &'static str {
+        "SetOwnerPolicy"
+    }
+
+    fn signer(&self) -> &PublicKey {
+        &self.owner
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    fn governed_cdp(&self) -> Option<&CDPId> {
+        Some(&self.cdp_id)
+    }
+
+    fn cosignatures(&self) -> &[CoSignature] {
+        &self.cosignatures
+    }
+
+    fn cosignatures_mut(&mut self) -> Option<&mut Vec<CoSignature>> {
+        Some(&mut self.cosignatures)
+    }
+}
+
+/// Result of replacing an owner policy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetOwnerPolicyResult {
+    /// Policy now in effect
+    pub policy: OwnerPolicy,
+}
+
+/// Authorize or revoke a session key on a CDP (authorized by the owner policy)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct SetSessionKeyOp {
+    /// CDP to update
+    pub cdp_id: CDPId,
+    /// Owner (must be CDP owner)
+    pub owner: PublicKey,
+    /// Session key
+    pub key: PublicKey,
+    /// Grant for the key, or `None` to revoke it
+    pub grant: Option<SessionGrant>,
+    /// Nonce
+    pub nonce: u64,
+    /// Signature
+    pub signature: Signature,
+    /// Co-signatures for multi-key owner policies
+    #[serde(default)]
+    pub cosignatures: Vec<CoSignature>,
+}
+
+impl Operation for SetSessionKeyOp {
+    type Result = SetSessionKeyResult;
+
+    fn operation_type(&self) -> &'static str {
+        "SetSessionKey"
+    }
+
+    fn signer(&self) -> &PublicKey {
+        &self.owner
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    fn governed_cdp(&self) -> Option<&CDPId> {
+        Some(&self.cdp_id)
+    }
+
+    fn cosignatures(&self) -> &[CoSignature] {
+        &self.cosignatures
+    }
+
+    fn cosignatures_mut(&mut self) -> Option<&mut Vec<CoSignature>> {
+        Some(&mut self.cosignatures)
+    }
+}
+
+/// Result of authorizing or revoking a session key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetSessionKeyResult {
+    /// Session now in effect (`None` if revoked)
+    pub session: Option<SessionKey>,
+}
+
+/// Repay or deposit on a CDP with a session key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct SessionCallOp {
+    /// CDP to act on
+    pub cdp_id: CDPId,
+    /// Session key authorized on the CDP
+    pub session_key: PublicKey,
+    /// Operation to perform
+    pub action: SessionAction,
+    /// Nonce
+    pub nonce: u64,
+    /// Signature by the session key
+    pub signature: Signature,
+}
+
+impl Operation for SessionCallOp {
+    type Result = SessionCallResult;
+
+    fn operation_type(&self) -> &'static str {
+        "SessionCall"
+    }
+
+    fn signer(&self) -> &PublicKey {
+        &self.session_key
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Result of a session key operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionCallResult {
+    /// Amount charged to the session (cents repaid or sats deposited)
+    pub amount: u64,
+    /// Spend cap left on the session
+    pub remaining_cap: u64,
+    /// New ratio (u64::MAX if no debt)
+    pub new_ratio: u64,
+}
+
 /// Liquidate a CDP
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct LiquidateCDPOp {
     /// CDP to liquidate
     pub cdp_id: CDPId,
@@ -339,6 +690,10 @@ impl Operation for LiquidateCDPOp {
         &self.signature
     }
 
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
     fn nonce(&self) -> u64 {
         self.nonce
     }
@@ -355,6 +710,8 @@ pub struct LiquidateResult {
     pub liquidator_bonus: CollateralAmount,
     /// Ratio at liquidation
     pub ratio_at_liquidation: u64,
+    /// Keeper stipend paid to the liquidator
+    pub keeper_reward: TokenAmount,
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -363,6 +720,7 @@ pub struct LiquidateResult {
 
 /// Transfer zkUSD
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct TransferOp {
     /// Sender
     pub from: PublicKey,
@@ -391,6 +749,10 @@ impl Operation for TransferOp {
         &self.signature
     }
 
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
     fn nonce(&self) -> u64 {
         self.nonce
     }
@@ -405,16 +767,15 @@ pub struct TransferResult {
     pub to_balance: TokenAmount,
 }
 
-// ═══════════════════════════════════════════════════════════════════════════════
-// STABILITY POOL OPERATIONS
-// ═══════════════════════════════════════════════════════════════════════════════
-
-/// Deposit to stability pool
+/// Allow a spender to transfer zkUSD on the owner's behalf
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StabilityDepositOp {
-    /// Depositor
-    pub depositor: PublicKey,
-    /// Amount to deposit
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct ApproveOp {
+    /// Token owner
+    pub owner: PublicKey,
+    /// Account allowed to spend
+    pub spender: PublicKey,
+    /// New allowance (zero revokes)
     pub amount: TokenAmount,
     /// Nonce
     pub nonce: u64,
@@ -422,158 +783,408 @@ pub struct StabilityDepositOp {
     pub signature: Signature,
 }
 
-impl Operation for StabilityDepositOp {
-    type Result = StabilityDepositResult;
+impl Operation for ApproveOp {
+    type Result = ApproveResult;
 
     fn operation_type(&self) -> &'static str {
-        "StabilityDeposit"
+        "Approve"
     }
 
     fn signer(&self) -> &PublicKey {
-        &self.depositor
+        &self.owner
     }
 
     fn signature(&self) -> &Signature {
         &self.signature
     }
 
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
     fn nonce(&self) -> u64 {
         self.nonce
     }
 }
 
-/// Result of stability pool deposit
+/// Result of approve
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StabilityDepositResult {
-    /// New total deposit for depositor
-    pub new_total: TokenAmount,
+pub struct ApproveResult {
+    /// Allowance now in effect
+    pub allowance: TokenAmount,
 }
 
-/// Withdraw from stability pool
+/// Submit an approval the owner signed off-chain
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StabilityWithdrawOp {
-    /// Depositor
-    pub depositor: PublicKey,
-    /// Amount to withdraw
-    pub amount: TokenAmount,
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct PermitOp {
+    /// Account relaying the permit (usually the spender)
+    pub submitter: PublicKey,
+    /// Owner-signed permit
+    pub permit: ApprovePermit,
     /// Nonce
     pub nonce: u64,
     /// Signature
     pub signature: Signature,
 }
 
-impl Operation for StabilityWithdrawOp {
-    type Result = StabilityWithdrawResult;
+impl Operation for PermitOp {
+    type Result = ApproveResult;
 
     fn operation_type(&self) -> &'static str {
-        "StabilityWithdraw"
+        "Permit"
     }
 
     fn signer(&self) -> &PublicKey {
-        &self.depositor
+        &self.submitter
     }
 
     fn signature(&self) -> &Signature {
         &self.signature
     }
 
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
     fn nonce(&self) -> u64 {
         self.nonce
     }
 }
 
-/// Result of stability pool withdrawal
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StabilityWithdrawResult {
-    /// Amount withdrawn
-    pub withdrawn: TokenAmount,
-    /// Remaining deposit
-    pub remaining: TokenAmount,
-}
-
-/// Claim BTC gains from stability pool
+/// Transfer pre-approved zkUSD out of another account
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ClaimGainsOp {
-    /// Depositor
-    pub depositor: PublicKey,
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct TransferFromOp {
+    /// Approved spender
+    pub spender: PublicKey,
+    /// Account the tokens are taken from
+    pub from: PublicKey,
+    /// Recipient
+    pub to: PublicKey,
+    /// Amount
+    pub amount: TokenAmount,
     /// Nonce
     pub nonce: u64,
     /// Signature
     pub signature: Signature,
 }
 
-impl Operation for ClaimGainsOp {
-    type Result = ClaimGainsResult;
+impl Operation for TransferFromOp {
+    type Result = TransferFromResult;
 
     fn operation_type(&self) -> &'static str {
-        "ClaimGains"
+        "TransferFrom"
     }
 
     fn signer(&self) -> &PublicKey {
-        &self.depositor
+        &self.spender
     }
 
     fn signature(&self) -> &Signature {
         &self.signature
     }
 
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
     fn nonce(&self) -> u64 {
         self.nonce
     }
 }
 
-/// Result of claiming gains
+/// Result of transfer from
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ClaimGainsResult {
-    /// BTC amount claimed
-    pub btc_claimed: CollateralAmount,
+pub struct TransferFromResult {
+    /// New balance of the source account
+    pub from_balance: TokenAmount,
+    /// New recipient balance
+    pub to_balance: TokenAmount,
+    /// Allowance left for the spender
+    pub remaining_allowance: TokenAmount,
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
-// REDEMPTION OPERATIONS
+// STABILITY POOL OPERATIONS
 // ═══════════════════════════════════════════════════════════════════════════════
 
-/// Redeem zkUSD for collateral
+/// Deposit to stability pool
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RedeemOp {
-    /// Redeemer
-    pub redeemer: PublicKey,
-    /// Amount of zkUSD to redeem
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct StabilityDepositOp {
+    /// Depositor
+    pub depositor: PublicKey,
+    /// Amount to deposit
     pub amount: TokenAmount,
-    /// Maximum fee willing to pay (in bps)
-    pub max_fee_bps: u64,
-    /// Hint for first CDP (optimization)
-    pub first_cdp_hint: Option<CDPId>,
+    /// Registered frontend the deposit was made through; only applies to
+    /// new deposits
+    #[serde(default)]
+    pub frontend: Option<PublicKey>,
     /// Nonce
     pub nonce: u64,
     /// Signature
     pub signature: Signature,
 }
 
-impl Operation for RedeemOp {
-    type Result = RedeemResult;
+impl Operation for StabilityDepositOp {
+    type Result = StabilityDepositResult;
 
     fn operation_type(&self) -> &'static str {
-        "Redeem"
+        "StabilityDeposit"
     }
 
     fn signer(&self) -> &PublicKey {
-        &self.redeemer
+        &self.depositor
     }
 
     fn signature(&self) -> &Signature {
         &self.signature
     }
 
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
     fn nonce(&self) -> u64 {
         self.nonce
     }
 }
 
-/// Result of redemption
+/// Result of stability pool deposit
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RedeemResult {
-    /// zkUSD redeemed
+pub struct StabilityDepositResult {
+    /// New total deposit for depositor
+    pub new_total: TokenAmount,
+}
+
+/// Withdraw from stability pool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct StabilityWithdrawOp {
+    /// Depositor
+    pub depositor: PublicKey,
+    /// Amount to withdraw
+    pub amount: TokenAmount,
+    /// Nonce
+    pub nonce: u64,
+    /// Signature
+    pub signature: Signature,
+}
+
+impl Operation for StabilityWithdrawOp {
+    type Result = StabilityWithdrawResult;
+
+    fn operation_type(&self) -> &'static str {
+        "StabilityWithdraw"
+    }
+
+    fn signer(&self) -> &PublicKey {
+        &self.depositor
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Result of stability pool withdrawal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StabilityWithdrawResult {
+    /// Amount withdrawn
+    pub withdrawn: TokenAmount,
+    /// Remaining deposit
+    pub remaining: TokenAmount,
+}
+
+/// Claim BTC gains from stability pool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct ClaimGainsOp {
+    /// Depositor
+    pub depositor: PublicKey,
+    /// Nonce
+    pub nonce: u64,
+    /// Signature
+    pub signature: Signature,
+}
+
+impl Operation for ClaimGainsOp {
+    type Result = ClaimGainsResult;
+
+    fn operation_type(&self) -> &'static str {
+        "ClaimGains"
+    }
+
+    fn signer(&self) -> &PublicKey {
+        &self.depositor
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Result of claiming gains
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimGainsResult {
+    /// BTC amount claimed
+    pub btc_claimed: CollateralAmount,
+}
+
+/// Register a frontend that stability pool deposits can be tagged with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct RegisterFrontendOp {
+    /// Frontend operator key
+    pub frontend: PublicKey,
+    /// Share of tagged depositors' gains paid to the frontend (in bps)
+    pub kickback_bps: u64,
+    /// Nonce
+    pub nonce: u64,
+    /// Signature
+    pub signature: Signature,
+}
+
+impl Operation for RegisterFrontendOp {
+    type Result = RegisterFrontendResult;
+
+    fn operation_type(&self) -> &'static str {
+        "RegisterFrontend"
+    }
+
+    fn signer(&self) -> &PublicKey {
+        &self.frontend
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Result of frontend registration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterFrontendResult {
+    /// Kickback share in bps
+    pub kickback_bps: u64,
+}
+
+/// Claim a frontend's accrued kickbacks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct ClaimFrontendGainsOp {
+    /// Frontend operator key
+    pub frontend: PublicKey,
+    /// Nonce
+    pub nonce: u64,
+    /// Signature
+    pub signature: Signature,
+}
+
+impl Operation for ClaimFrontendGainsOp {
+    type Result = ClaimGainsResult;
+
+    fn operation_type(&self) -> &'static str {
+        "ClaimFrontendGains"
+    }
+
+    fn signer(&self) -> &PublicKey {
+        &self.frontend
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// REDEMPTION OPERATIONS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Redeem zkUSD for collateral
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct RedeemOp {
+    /// Redeemer
+    pub redeemer: PublicKey,
+    /// Amount of zkUSD to redeem
+    pub amount: TokenAmount,
+    /// Maximum fee willing to pay (in bps)
+    pub max_fee_bps: u64,
+    /// Lowest-ratio redeemable CDP, checked against the sorted CDP list
+    pub first_cdp_hint: Option<CDPId>,
+    /// Last CDP to redeem against; redemption stops after it
+    #[serde(default)]
+    pub last_cdp_hint: Option<CDPId>,
+    /// Maximum CDPs to redeem against (0 = protocol maximum)
+    #[serde(default)]
+    pub max_cdps: u32,
+    /// Nonce
+    pub nonce: u64,
+    /// Signature
+    pub signature: Signature,
+}
+
+impl Operation for RedeemOp {
+    type Result = RedeemResult;
+
+    fn operation_type(&self) -> &'static str {
+        "Redeem"
+    }
+
+    fn signer(&self) -> &PublicKey {
+        &self.redeemer
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Result of redemption
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedeemResult {
+    /// zkUSD redeemed
     pub zkusd_redeemed: TokenAmount,
     /// Collateral received
     pub collateral_received: CollateralAmount,
@@ -584,57 +1195,571 @@ pub struct RedeemResult {
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
-// ORACLE OPERATIONS
+// ORACLE OPERATIONS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Update price (oracle operation)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct UpdatePriceOp {
+    /// Oracle operator
+    pub operator: PublicKey,
+    /// New price in cents
+    pub price_cents: u64,
+    /// Source count
+    pub source_count: u8,
+    /// Confidence (0-100)
+    pub confidence: u8,
+    /// Half-width of the price's confidence interval in cents
+    #[serde(default)]
+    pub confidence_interval: u64,
+    /// Proof data (ZK proof of price aggregation)
+    pub proof: Vec<u8>,
+    /// Nonce
+    pub nonce: u64,
+    /// Signature
+    pub signature: Signature,
+}
+
+impl Operation for UpdatePriceOp {
+    type Result = UpdatePriceResult;
+
+    fn operation_type(&self) -> &'static str {
+        "UpdatePrice"
+    }
+
+    fn signer(&self) -> &PublicKey {
+        &self.operator
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Result of price update
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatePriceResult {
+    /// Previous price
+    pub previous_price: u64,
+    /// New price: the weighted median of the block's updates so far
+    pub new_price: u64,
+    /// Whether recovery mode was triggered
+    pub recovery_mode_changed: bool,
+    /// Operators that have updated the price in this block
+    pub updates_in_block: usize,
+}
+
+/// Update the rate of a non-BTC price pair, such as zkUSD/USD (oracle operation)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct UpdatePairPriceOp {
+    /// Oracle operator
+    pub operator: PublicKey,
+    /// Pair being priced
+    pub pair: TradingPair,
+    /// Units of quote per unit of base, scaled by `RATE_PRECISION`
+    pub rate: u64,
+    /// Source count
+    pub source_count: u8,
+    /// Nonce
+    pub nonce: u64,
+    /// Signature
+    pub signature: Signature,
+}
+
+impl Operation for UpdatePairPriceOp {
+    type Result = UpdatePairPriceResult;
+
+    fn operation_type(&self) -> &'static str {
+        "UpdatePairPrice"
+    }
+
+    fn signer(&self) -> &PublicKey {
+        &self.operator
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Result of a pair price update
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatePairPriceResult {
+    /// Previous rate (0 if the pair had none)
+    pub previous_rate: u64,
+    /// New rate
+    pub new_rate: u64,
+}
+
+/// Report an oracle operator that signed contradictory prices (anyone may)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct ReportOracleMisbehaviorOp {
+    /// Account reporting
+    pub reporter: PublicKey,
+    /// Contradictory updates signed by the operator
+    pub evidence: Equivocation,
+    /// Nonce
+    pub nonce: u64,
+    /// Signature
+    pub signature: Signature,
+}
+
+impl Operation for ReportOracleMisbehaviorOp {
+    type Result = ReportOracleMisbehaviorResult;
+
+    fn operation_type(&self) -> &'static str {
+        "ReportOracleMisbehavior"
+    }
+
+    fn signer(&self) -> &PublicKey {
+        &self.reporter
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Result of a misbehavior report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportOracleMisbehaviorResult {
+    /// Operator slashed
+    pub operator: PublicKey,
+    /// Weight left
+    pub weight: u64,
+    /// Stake removed
+    pub stake_slashed: CollateralAmount,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// BRIDGE OPERATIONS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Lock zkUSD in the bridge escrow for minting on another chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct BridgeOutOp {
+    /// Account sending the tokens
+    pub sender: PublicKey,
+    /// Amount to bridge
+    pub amount: TokenAmount,
+    /// Destination chain
+    pub dest_chain: ChainId,
+    /// Recipient address on the destination chain
+    pub dest_address: Vec<u8>,
+    /// Nonce
+    pub nonce: u64,
+    /// Signature
+    pub signature: Signature,
+}
+
+impl Operation for BridgeOutOp {
+    type Result = BridgeOutResult;
+
+    fn operation_type(&self) -> &'static str {
+        "BridgeOut"
+    }
+
+    fn signer(&self) -> &PublicKey {
+        &self.sender
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Result of bridge out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeOutResult {
+    /// Sequence number relayers use to track the transfer
+    pub sequence: u64,
+    /// Total now locked towards the destination chain
+    pub locked: TokenAmount,
+}
+
+/// Release escrowed zkUSD for tokens burned on another chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct BridgeInOp {
+    /// Account submitting the transfer (usually a relayer)
+    pub submitter: PublicKey,
+    /// Transfer being released
+    pub transfer: InboundTransfer,
+    /// Relayer attestations over the transfer
+    pub attestations: Vec<Attestation>,
+    /// Nonce
+    pub nonce: u64,
+    /// Signature
+    pub signature: Signature,
+}
+
+impl Operation for BridgeInOp {
+    type Result = BridgeInResult;
+
+    fn operation_type(&self) -> &'static str {
+        "BridgeIn"
+    }
+
+    fn signer(&self) -> &PublicKey {
+        &self.submitter
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Result of bridge in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeInResult {
+    /// Recipient balance after the release
+    pub recipient_balance: TokenAmount,
+    /// Total still locked towards the source chain
+    pub locked: TokenAmount,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// SAVINGS OPERATIONS
 // ═══════════════════════════════════════════════════════════════════════════════
 
-/// Update price (oracle operation)
+/// Lock zkUSD in the savings pot
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct UpdatePriceOp {
-    /// Oracle operator
-    pub operator: PublicKey,
-    /// New price in cents
-    pub price_cents: u64,
-    /// Source count
-    pub source_count: u8,
-    /// Confidence (0-100)
-    pub confidence: u8,
-    /// Proof data (ZK proof of price aggregation)
-    pub proof: Vec<u8>,
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct SavingsDepositOp {
+    /// Depositor
+    pub depositor: PublicKey,
+    /// Amount to lock
+    pub amount: TokenAmount,
     /// Nonce
     pub nonce: u64,
     /// Signature
     pub signature: Signature,
 }
 
-impl Operation for UpdatePriceOp {
-    type Result = UpdatePriceResult;
+impl Operation for SavingsDepositOp {
+    type Result = SavingsDepositResult;
 
     fn operation_type(&self) -> &'static str {
-        "UpdatePrice"
+        "SavingsDeposit"
     }
 
     fn signer(&self) -> &PublicKey {
-        &self.operator
+        &self.depositor
     }
 
     fn signature(&self) -> &Signature {
         &self.signature
     }
 
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
     fn nonce(&self) -> u64 {
         self.nonce
     }
 }
 
-/// Result of price update
+/// Result of savings deposit
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct UpdatePriceResult {
-    /// Previous price
-    pub previous_price: u64,
-    /// New price
-    pub new_price: u64,
-    /// Whether recovery mode was triggered
-    pub recovery_mode_changed: bool,
+pub struct SavingsDepositResult {
+    /// Savings balance after the deposit
+    pub balance: TokenAmount,
+}
+
+/// Unlock zkUSD from the savings pot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct SavingsWithdrawOp {
+    /// Depositor
+    pub depositor: PublicKey,
+    /// Amount to unlock (zero for the whole balance)
+    pub amount: TokenAmount,
+    /// Nonce
+    pub nonce: u64,
+    /// Signature
+    pub signature: Signature,
+}
+
+impl Operation for SavingsWithdrawOp {
+    type Result = SavingsWithdrawResult;
+
+    fn operation_type(&self) -> &'static str {
+        "SavingsWithdraw"
+    }
+
+    fn signer(&self) -> &PublicKey {
+        &self.depositor
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Result of savings withdrawal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavingsWithdrawResult {
+    /// Amount withdrawn
+    pub withdrawn: TokenAmount,
+    /// Remaining savings balance
+    pub remaining: TokenAmount,
+}
+
+/// Accrue savings interest up to the current block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct SavingsAccrueOp {
+    /// Account triggering the accrual
+    pub caller: PublicKey,
+    /// Nonce
+    pub nonce: u64,
+    /// Signature
+    pub signature: Signature,
+}
+
+impl Operation for SavingsAccrueOp {
+    type Result = SavingsAccrueResult;
+
+    fn operation_type(&self) -> &'static str {
+        "SavingsAccrue"
+    }
+
+    fn signer(&self) -> &PublicKey {
+        &self.caller
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Result of savings accrual
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavingsAccrueResult {
+    /// Interest paid by this accrual
+    pub interest: TokenAmount,
+    /// Total locked in the pot after the accrual
+    pub total_locked: TokenAmount,
+    /// Keeper stipend paid to the caller
+    pub keeper_reward: TokenAmount,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// TREASURY OPERATIONS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Pay out treasury funds (governor only)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct TreasuryDisburseOp {
+    /// Treasury governor
+    pub governor: PublicKey,
+    /// Recipient of the funds
+    pub recipient: PublicKey,
+    /// Asset and amount to pay
+    pub asset: TreasuryAsset,
+    /// Nonce
+    pub nonce: u64,
+    /// Signature
+    pub signature: Signature,
+}
+
+impl Operation for TreasuryDisburseOp {
+    type Result = TreasuryDisburseResult;
+
+    fn operation_type(&self) -> &'static str {
+        "TreasuryDisburse"
+    }
+
+    fn signer(&self) -> &PublicKey {
+        &self.governor
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Result of treasury disbursement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreasuryDisburseResult {
+    /// zkUSD left in the treasury account
+    pub balance: TokenAmount,
+    /// BTC left in the vault fee buckets
+    pub btc_fees: CollateralAmount,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// AUCTION OPERATIONS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Bid on a collateral auction at its current price
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct AuctionBidOp {
+    /// Bidder paying in zkUSD
+    pub bidder: PublicKey,
+    /// Auction to bid on
+    pub auction_id: u64,
+    /// Most collateral to buy
+    pub max_collateral: CollateralAmount,
+    /// Highest acceptable price in cents per BTC
+    pub max_price: u64,
+    /// Nonce
+    pub nonce: u64,
+    /// Signature
+    pub signature: Signature,
+}
+
+impl Operation for AuctionBidOp {
+    type Result = AuctionBidResult;
+
+    fn operation_type(&self) -> &'static str {
+        "AuctionBid"
+    }
+
+    fn signer(&self) -> &PublicKey {
+        &self.bidder
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Result of an auction bid
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuctionBidResult {
+    /// Collateral bought
+    pub collateral_received: CollateralAmount,
+    /// zkUSD paid
+    pub cost: TokenAmount,
+    /// Price paid in cents per BTC
+    pub price: u64,
+    /// Whether the auction ended
+    pub closed: bool,
+}
+
+/// Restart an expired collateral auction from the current price
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct AuctionResetOp {
+    /// Account triggering the reset
+    pub caller: PublicKey,
+    /// Auction to reset
+    pub auction_id: u64,
+    /// Nonce
+    pub nonce: u64,
+    /// Signature
+    pub signature: Signature,
+}
+
+impl Operation for AuctionResetOp {
+    type Result = AuctionResetResult;
+
+    fn operation_type(&self) -> &'static str {
+        "AuctionReset"
+    }
+
+    fn signer(&self) -> &PublicKey {
+        &self.caller
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn signature_mut(&mut self) -> &mut Signature {
+        &mut self.signature
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Result of an auction reset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuctionResetResult {
+    /// New starting price in cents per BTC
+    pub start_price: u64,
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -656,6 +1781,7 @@ pub struct BatchOp {
 
 /// All possible protocol operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub enum ProtocolOperation {
     /// Open CDP
     OpenCDP(OpenCDPOp),
@@ -683,9 +1809,77 @@ pub enum ProtocolOperation {
     Redeem(RedeemOp),
     /// Update price
     UpdatePrice(UpdatePriceOp),
+    /// Approve a spender
+    Approve(ApproveOp),
+    /// Transfer tokens on another account's behalf
+    TransferFrom(TransferFromOp),
+    /// Submit an off-chain signed approval
+    Permit(PermitOp),
+    /// Replace a CDP's owner policy
+    SetOwnerPolicy(SetOwnerPolicyOp),
+    /// Lock zkUSD for another chain
+    BridgeOut(BridgeOutOp),
+    /// Release zkUSD returning from another chain
+    BridgeIn(BridgeInOp),
+    /// Lock zkUSD in the savings pot
+    SavingsDeposit(SavingsDepositOp),
+    /// Unlock zkUSD from the savings pot
+    SavingsWithdraw(SavingsWithdrawOp),
+    /// Accrue savings interest
+    SavingsAccrue(SavingsAccrueOp),
+    /// Pay out treasury funds
+    TreasuryDisburse(TreasuryDisburseOp),
+    /// Bid on a collateral auction
+    AuctionBid(AuctionBidOp),
+    /// Restart an expired collateral auction
+    AuctionReset(AuctionResetOp),
+    /// Register a stability pool frontend
+    RegisterFrontend(RegisterFrontendOp),
+    /// Claim frontend kickbacks
+    ClaimFrontendGains(ClaimFrontendGainsOp),
+    /// Update a non-BTC price pair
+    UpdatePairPrice(UpdatePairPriceOp),
+    /// Authorize or revoke a CDP session key
+    SetSessionKey(SetSessionKeyOp),
+    /// Act on a CDP with a session key
+    SessionCall(SessionCallOp),
+    /// Report an equivocating oracle operator
+    ReportOracleMisbehavior(ReportOracleMisbehaviorOp),
 }
 
 impl ProtocolOperation {
+    /// Encode for transmission
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        codec::encode(self)
+    }
+
+    /// Decode an untrusted operation, bounded to [`MAX_OPERATION_SIZE`]
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        codec::decode_bounded(data, MAX_OPERATION_SIZE)
+    }
+
+    /// Hash of the encoded operation, identifying it in the mempool and
+    /// its receipt
+    pub fn hash(&self) -> Result<Hash> {
+        Ok(Hash::sha256(&self.encode()?))
+    }
+
+    /// CDP the operation acts on, if any
+    pub fn cdp_id(&self) -> Option<&CDPId> {
+        match self {
+            Self::DepositCollateral(op) => Some(&op.cdp_id),
+            Self::WithdrawCollateral(op) => Some(&op.cdp_id),
+            Self::MintDebt(op) => Some(&op.cdp_id),
+            Self::RepayDebt(op) => Some(&op.cdp_id),
+            Self::CloseCDP(op) => Some(&op.cdp_id),
+            Self::LiquidateCDP(op) => Some(&op.cdp_id),
+            Self::SetOwnerPolicy(op) => Some(&op.cdp_id),
+            Self::SetSessionKey(op) => Some(&op.cdp_id),
+            Self::SessionCall(op) => Some(&op.cdp_id),
+            _ => None,
+        }
+    }
+
     /// Get the operation type name
     pub fn operation_type(&self) -> &'static str {
         match self {
@@ -702,6 +1896,24 @@ impl ProtocolOperation {
             Self::ClaimGains(_) => "ClaimGains",
             Self::Redeem(_) => "Redeem",
             Self::UpdatePrice(_) => "UpdatePrice",
+            Self::Approve(_) => "Approve",
+            Self::TransferFrom(_) => "TransferFrom",
+            Self::Permit(_) => "Permit",
+            Self::SetOwnerPolicy(_) => "SetOwnerPolicy",
+            Self::BridgeOut(_) => "BridgeOut",
+            Self::BridgeIn(_) => "BridgeIn",
+            Self::SavingsDeposit(_) => "SavingsDeposit",
+            Self::SavingsWithdraw(_) => "SavingsWithdraw",
+            Self::SavingsAccrue(_) => "SavingsAccrue",
+            Self::TreasuryDisburse(_) => "TreasuryDisburse",
+            Self::AuctionBid(_) => "AuctionBid",
+            Self::AuctionReset(_) => "AuctionReset",
+            Self::RegisterFrontend(_) => "RegisterFrontend",
+            Self::ClaimFrontendGains(_) => "ClaimFrontendGains",
+            Self::UpdatePairPrice(_) => "UpdatePairPrice",
+            Self::SetSessionKey(_) => "SetSessionKey",
+            Self::SessionCall(_) => "SessionCall",
+            Self::ReportOracleMisbehavior(_) => "ReportOracleMisbehavior",
         }
     }
 
@@ -721,6 +1933,108 @@ impl ProtocolOperation {
             Self::ClaimGains(op) => &op.depositor,
             Self::Redeem(op) => &op.redeemer,
             Self::UpdatePrice(op) => &op.operator,
+            Self::Approve(op) => &op.owner,
+            Self::TransferFrom(op) => &op.spender,
+            Self::Permit(op) => &op.submitter,
+            Self::SetOwnerPolicy(op) => &op.owner,
+            Self::BridgeOut(op) => &op.sender,
+            Self::BridgeIn(op) => &op.submitter,
+            Self::SavingsDeposit(op) => &op.depositor,
+            Self::SavingsWithdraw(op) => &op.depositor,
+            Self::SavingsAccrue(op) => &op.caller,
+            Self::TreasuryDisburse(op) => &op.governor,
+            Self::AuctionBid(op) => &op.bidder,
+            Self::AuctionReset(op) => &op.caller,
+            Self::RegisterFrontend(op) => &op.frontend,
+            Self::ClaimFrontendGains(op) => &op.frontend,
+            Self::UpdatePairPrice(op) => &op.operator,
+            Self::SetSessionKey(op) => &op.owner,
+            Self::SessionCall(op) => &op.session_key,
+            Self::ReportOracleMisbehavior(op) => &op.reporter,
+        }
+    }
+
+    /// Signatures carried by the operation: the signer's, then any
+    /// co-signatures, each over the signing hash in `domain`
+    pub fn signature_checks(&self, domain: &SigningDomain) -> Result<Vec<SignatureCheck>> {
+        self.checks(Some(domain))
+    }
+
+    /// Signatures carried by the operation, each over the legacy untyped hash
+    pub fn legacy_signature_checks(&self) -> Result<Vec<SignatureCheck>> {
+        self.checks(None)
+    }
+
+    /// Payload a wallet displays and signs for the operation in `domain`
+    pub fn signing_payload(&self, domain: &SigningDomain) -> Result<SigningPayload> {
+        match self {
+            Self::OpenCDP(op) => op.signing_payload(domain),
+            Self::DepositCollateral(op) => op.signing_payload(domain),
+            Self::WithdrawCollateral(op) => op.signing_payload(domain),
+            Self::MintDebt(op) => op.signing_payload(domain),
+            Self::RepayDebt(op) => op.signing_payload(domain),
+            Self::CloseCDP(op) => op.signing_payload(domain),
+            Self::LiquidateCDP(op) => op.signing_payload(domain),
+            Self::Transfer(op) => op.signing_payload(domain),
+            Self::StabilityDeposit(op) => op.signing_payload(domain),
+            Self::StabilityWithdraw(op) => op.signing_payload(domain),
+            Self::ClaimGains(op) => op.signing_payload(domain),
+            Self::Redeem(op) => op.signing_payload(domain),
+            Self::UpdatePrice(op) => op.signing_payload(domain),
+            Self::Approve(op) => op.signing_payload(domain),
+            Self::TransferFrom(op) => op.signing_payload(domain),
+            Self::Permit(op) => op.signing_payload(domain),
+            Self::SetOwnerPolicy(op) => op.signing_payload(domain),
+            Self::BridgeOut(op) => op.signing_payload(domain),
+            Self::BridgeIn(op) => op.signing_payload(domain),
+            Self::SavingsDeposit(op) => op.signing_payload(domain),
+            Self::SavingsWithdraw(op) => op.signing_payload(domain),
+            Self::SavingsAccrue(op) => op.signing_payload(domain),
+            Self::TreasuryDisburse(op) => op.signing_payload(domain),
+            Self::AuctionBid(op) => op.signing_payload(domain),
+            Self::AuctionReset(op) => op.signing_payload(domain),
+            Self::RegisterFrontend(op) => op.signing_payload(domain),
+            Self::ClaimFrontendGains(op) => op.signing_payload(domain),
+            Self::UpdatePairPrice(op) => op.signing_payload(domain),
+            Self::SetSessionKey(op) => op.signing_payload(domain),
+            Self::SessionCall(op) => op.signing_payload(domain),
+            Self::ReportOracleMisbehavior(op) => op.signing_payload(domain),
+        }
+    }
+
+    fn checks(&self, domain: Option<&SigningDomain>) -> Result<Vec<SignatureCheck>> {
+        match self {
+            Self::OpenCDP(op) => signature_checks(op, domain),
+            Self::DepositCollateral(op) => signature_checks(op, domain),
+            Self::WithdrawCollateral(op) => signature_checks(op, domain),
+            Self::MintDebt(op) => signature_checks(op, domain),
+            Self::RepayDebt(op) => signature_checks(op, domain),
+            Self::CloseCDP(op) => signature_checks(op, domain),
+            Self::LiquidateCDP(op) => signature_checks(op, domain),
+            Self::Transfer(op) => signature_checks(op, domain),
+            Self::StabilityDeposit(op) => signature_checks(op, domain),
+            Self::StabilityWithdraw(op) => signature_checks(op, domain),
+            Self::ClaimGains(op) => signature_checks(op, domain),
+            Self::Redeem(op) => signature_checks(op, domain),
+            Self::UpdatePrice(op) => signature_checks(op, domain),
+            Self::Approve(op) => signature_checks(op, domain),
+            Self::TransferFrom(op) => signature_checks(op, domain),
+            Self::Permit(op) => signature_checks(op, domain),
+            Self::SetOwnerPolicy(op) => signature_checks(op, domain),
+            Self::BridgeOut(op) => signature_checks(op, domain),
+            Self::BridgeIn(op) => signature_checks(op, domain),
+            Self::SavingsDeposit(op) => signature_checks(op, domain),
+            Self::SavingsWithdraw(op) => signature_checks(op, domain),
+            Self::SavingsAccrue(op) => signature_checks(op, domain),
+            Self::TreasuryDisburse(op) => signature_checks(op, domain),
+            Self::AuctionBid(op) => signature_checks(op, domain),
+            Self::AuctionReset(op) => signature_checks(op, domain),
+            Self::RegisterFrontend(op) => signature_checks(op, domain),
+            Self::ClaimFrontendGains(op) => signature_checks(op, domain),
+            Self::UpdatePairPrice(op) => signature_checks(op, domain),
+            Self::SetSessionKey(op) => signature_checks(op, domain),
+            Self::SessionCall(op) => signature_checks(op, domain),
+            Self::ReportOracleMisbehavior(op) => signature_checks(op, domain),
         }
     }
 
@@ -740,10 +2054,42 @@ impl ProtocolOperation {
             Self::ClaimGains(op) => op.nonce,
             Self::Redeem(op) => op.nonce,
             Self::UpdatePrice(op) => op.nonce,
+            Self::Approve(op) => op.nonce,
+            Self::TransferFrom(op) => op.nonce,
+            Self::Permit(op) => op.nonce,
+            Self::SetOwnerPolicy(op) => op.nonce,
+            Self::BridgeOut(op) => op.nonce,
+            Self::BridgeIn(op) => op.nonce,
+            Self::SavingsDeposit(op) => op.nonce,
+            Self::SavingsWithdraw(op) => op.nonce,
+            Self::SavingsAccrue(op) => op.nonce,
+            Self::TreasuryDisburse(op) => op.nonce,
+            Self::AuctionBid(op) => op.nonce,
+            Self::AuctionReset(op) => op.nonce,
+            Self::RegisterFrontend(op) => op.nonce,
+            Self::ClaimFrontendGains(op) => op.nonce,
+            Self::UpdatePairPrice(op) => op.nonce,
+            Self::SetSessionKey(op) => op.nonce,
+            Self::SessionCall(op) => op.nonce,
+            Self::ReportOracleMisbehavior(op) => op.nonce,
         }
     }
 }
 
+fn signature_checks<O: Operation + Clone + Serialize>(
+    op: &O,
+    domain: Option<&SigningDomain>,
+) -> Result<Vec<SignatureCheck>> {
+    let hash = match domain {
+        Some(domain) => op.signing_hash(domain)?,
+        None => op.legacy_signing_hash()?,
+    };
+    let mut checks = Vec::with_capacity(op.cosignatures().len() + 1);
+    checks.push((*op.signer(), hash, *op.signature()));
+    checks.extend(op.cosignatures().iter().map(|cosig| (cosig.signer, hash, cosig.signature)));
+    Ok(checks)
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // TESTS
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -784,4 +2130,26 @@ mod tests {
         assert_eq!(op.operation_type(), "Transfer");
         assert_eq!(op.nonce(), 5);
     }
+
+    #[test]
+    fn test_operation_decode() {
+        let keypair = KeyPair::generate();
+        let op = ProtocolOperation::SetOwnerPolicy(SetOwnerPolicyOp {
+            cdp_id: CDPId::new([7u8; 32]),
+            owner: *keypair.public_key(),
+            policy: OwnerPolicy::MultiSig { threshold: 1, keys: vec![*keypair.public_key()] },
+            nonce: 3,
+            signature: Signature::new([0u8; 64]),
+            cosignatures: Vec::new(),
+        });
+
+        let bytes = op.encode().unwrap();
+        let decoded = ProtocolOperation::decode(&bytes).unwrap();
+        assert_eq!(decoded.operation_type(), "SetOwnerPolicy");
+        assert_eq!(decoded.encode().unwrap(), bytes);
+
+        assert!(ProtocolOperation::decode(&bytes[..bytes.len() - 1]).is_err());
+        assert!(ProtocolOperation::decode(&[0xff; 64]).is_err());
+        assert!(ProtocolOperation::decode(&vec![0u8; MAX_OPERATION_SIZE + 1]).is_err());
+    }
 }
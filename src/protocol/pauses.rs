@@ -0,0 +1,230 @@
+//! Per-operation pauses.
+//!
+//! `config.paused` stops everything at once. Operation pauses stop a single
+//! kind of risky operation (minting, collateral withdrawals, redemptions or
+//! stability pool withdrawals) while the rest of the protocol keeps running.
+//!
+//! Governance or a guardian sets them by hand. Each kind also has a circuit
+//! breaker: anomalies reported against it count as failures, enough of them
+//! in a row trip the breaker, and the operation stays paused until the
+//! cooldown ends. A block without anomalies resets the count.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::utils::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitState};
+
+/// Breaker settings by default: three anomalous blocks in a row pause the
+/// operation for an hour
+pub const DEFAULT_PAUSE_BREAKER: CircuitBreakerConfig = CircuitBreakerConfig {
+    failure_threshold: 3,
+    cooldown_ms: 3_600_000,
+};
+
+/// Operation kinds that can be paused on their own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum PausableOperation {
+    /// Minting zkUSD against a CDP
+    Mint,
+    /// Withdrawing collateral from a CDP
+    Withdraw,
+    /// Redeeming zkUSD for collateral
+    Redemption,
+    /// Withdrawing from the stability pool
+    StabilityWithdraw,
+}
+
+impl PausableOperation {
+    /// Every pausable operation
+    pub const ALL: [PausableOperation; 4] = [
+        PausableOperation::Mint,
+        PausableOperation::Withdraw,
+        PausableOperation::Redemption,
+        PausableOperation::StabilityWithdraw,
+    ];
+
+    /// Name used in events and on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            PausableOperation::Mint => "mint",
+            PausableOperation::Withdraw => "withdraw",
+            PausableOperation::Redemption => "redemption",
+            PausableOperation::StabilityWithdraw => "sp-withdraw",
+        }
+    }
+}
+
+impl fmt::Display for PausableOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for PausableOperation {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|op| op.name() == s)
+            .ok_or_else(|| Error::InvalidParameter {
+                name: "operation".into(),
+                reason: format!("Unknown operation '{}', expected mint, withdraw, redemption or sp-withdraw", s),
+            })
+    }
+}
+
+/// Manual pauses and circuit breakers of each pausable operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationPauses {
+    /// Operations paused by governance or a guardian
+    manual: BTreeSet<PausableOperation>,
+    /// Breaker of each operation an anomaly was reported against
+    breakers: BTreeMap<PausableOperation, CircuitBreaker>,
+    /// Settings of new breakers
+    breaker_config: CircuitBreakerConfig,
+    /// Operations with an anomaly in the current block
+    flagged: BTreeSet<PausableOperation>,
+}
+
+impl Default for OperationPauses {
+    fn default() -> Self {
+        Self::new(DEFAULT_PAUSE_BREAKER)
+    }
+}
+
+impl OperationPauses {
+    /// Create with nothing paused and breakers using `breaker_config`
+    pub fn new(breaker_config: CircuitBreakerConfig) -> Self {
+        Self {
+            manual: BTreeSet::new(),
+            breakers: BTreeMap::new(),
+            breaker_config,
+            flagged: BTreeSet::new(),
+        }
+    }
+
+    /// Pause `operation` by hand, returning whether it was running
+    pub fn pause(&mut self, operation: PausableOperation) -> bool {
+        self.manual.insert(operation)
+    }
+
+    /// Lift the manual pause of `operation` and close its breaker, returning
+    /// whether either was set
+    pub fn resume(&mut self, operation: PausableOperation) -> bool {
+        let manual = self.manual.remove(&operation);
+        let tripped = self.breakers.remove(&operation).is_some_and(|breaker| breaker.is_open());
+        manual || tripped
+    }
+
+    /// Whether `operation` is paused at `now` (seconds)
+    pub fn is_paused(&self, operation: PausableOperation, now: u64) -> bool {
+        self.manual.contains(&operation) || self.tripped_until(operation).is_some_and(|until| now < until)
+    }
+
+    /// Operations paused at `now`
+    pub fn paused(&self, now: u64) -> Vec<PausableOperation> {
+        PausableOperation::ALL.into_iter().filter(|op| self.is_paused(*op, now)).collect()
+    }
+
+    /// When the breaker of `operation` closes (seconds), if it is open
+    pub fn tripped_until(&self, operation: PausableOperation) -> Option<u64> {
+        match self.breakers.get(&operation)?.state() {
+            CircuitState::Open { until_ms } => Some(until_ms / 1_000),
+            _ => None,
+        }
+    }
+
+    /// Count an anomaly against `operation` at `now` (seconds), returning
+    /// when the operation resumes if this anomaly tripped its breaker
+    pub fn record_anomaly(&mut self, operation: PausableOperation, now: u64) -> Option<u64> {
+        let now_ms = now.saturating_mul(1_000);
+        let breaker = self
+            .breakers
+            .entry(operation)
+            .or_insert_with(|| CircuitBreaker::new(self.breaker_config));
+        self.flagged.insert(operation);
+
+        // Moves an expired breaker to half-open, so the anomaly reopens it
+        breaker.allow(now_ms);
+        let trips = breaker.trips();
+        breaker.record_failure(now_ms);
+        if breaker.trips() > trips {
+            self.tripped_until(operation)
+        } else {
+            None
+        }
+    }
+
+    /// Reset the breakers of operations without anomalies this block, once
+    /// their cooldown is over
+    pub fn end_block(&mut self, now: u64) {
+        let now_ms = now.saturating_mul(1_000);
+        let flagged = std::mem::take(&mut self.flagged);
+        self.breakers.retain(|operation, breaker| {
+            let cooling = matches!(breaker.state(), CircuitState::Open { until_ms } if now_ms < until_ms);
+            flagged.contains(operation) || cooling
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_pause() {
+        let mut pauses = OperationPauses::default();
+        assert!(pauses.pause(PausableOperation::Mint));
+        assert!(!pauses.pause(PausableOperation::Mint));
+
+        assert!(pauses.is_paused(PausableOperation::Mint, 0));
+        assert!(!pauses.is_paused(PausableOperation::Redemption, 0));
+        assert_eq!(pauses.paused(u64::MAX), vec![PausableOperation::Mint]);
+
+        assert!(pauses.resume(PausableOperation::Mint));
+        assert!(!pauses.resume(PausableOperation::Mint));
+        assert!(pauses.paused(0).is_empty());
+
+        assert_eq!("sp-withdraw".parse::<PausableOperation>().unwrap(), PausableOperation::StabilityWithdraw);
+        assert!("liquidate".parse::<PausableOperation>().is_err());
+    }
+
+    #[test]
+    fn test_breaker_trips_on_consecutive_anomalies() {
+        let mut pauses = OperationPauses::default();
+        let op = PausableOperation::Redemption;
+
+        // A quiet block in between resets the count
+        assert_eq!(pauses.record_anomaly(op, 0), None);
+        pauses.end_block(0);
+        assert_eq!(pauses.record_anomaly(op, 600), None);
+        pauses.end_block(600);
+        pauses.end_block(1_200);
+        assert_eq!(pauses.record_anomaly(op, 1_800), None);
+        pauses.end_block(1_800);
+        assert_eq!(pauses.record_anomaly(op, 2_400), None);
+        pauses.end_block(2_400);
+        assert!(!pauses.is_paused(op, 2_400));
+
+        assert_eq!(pauses.record_anomaly(op, 3_000), Some(6_600));
+        assert!(pauses.is_paused(op, 3_000));
+        assert!(!pauses.is_paused(PausableOperation::Mint, 3_000));
+
+        // Stays tripped through quiet blocks until the cooldown ends
+        pauses.end_block(3_600);
+        assert!(pauses.is_paused(op, 6_000));
+        assert!(!pauses.is_paused(op, 6_600));
+
+        // An anomaly right after the cooldown trips it again
+        assert_eq!(pauses.record_anomaly(op, 6_600), Some(10_200));
+
+        // Governance can lift it early
+        assert!(pauses.resume(op));
+        assert!(!pauses.is_paused(op, 6_600));
+    }
+}
@@ -0,0 +1,247 @@
+//! Gradual parameter changes.
+//!
+//! A ramp moves a governable parameter linearly to a target over a range of
+//! blocks instead of in one jump, e.g. the MCR from 110% to 125% over 10,000
+//! blocks. Ramps are timelocked: the first step is at least
+//! [`RAMP_NOTICE_BLOCKS`] after the ramp is scheduled, giving CDP owners time
+//! to react. Each block's value is interpolated at the start of the block and
+//! rounded to the parameter's step, so every node derives the same value.
+//!
+//! The target must be within the parameter's bounds, and the ramp may not
+//! move faster per block than a single update could.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::config::ProtocolParameter;
+use crate::error::{Error, Result};
+
+/// Blocks between scheduling a ramp and its first step (about a day)
+pub const RAMP_NOTICE_BLOCKS: u64 = 144;
+
+/// A scheduled linear change of one parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParameterRamp {
+    /// Ramped parameter
+    pub parameter: ProtocolParameter,
+    /// Value when the ramp was scheduled
+    pub start_value: u64,
+    /// Value reached at `end_block`
+    pub target_value: u64,
+    /// Block of the first step
+    pub start_block: u64,
+    /// Block the target is reached at
+    pub end_block: u64,
+    /// Whether the first step was applied
+    pub started: bool,
+}
+
+impl ParameterRamp {
+    /// Ramp `parameter` from `start_value` to `target_value` between
+    /// `start_block` and `end_block`, scheduled at `height`
+    pub fn new(
+        parameter: ProtocolParameter,
+        start_value: u64,
+        target_value: u64,
+        start_block: u64,
+        end_block: u64,
+        height: u64,
+    ) -> Result<Self> {
+        let invalid = |reason: String| Error::InvalidParameter {
+            name: format!("ramp:{}", parameter),
+            reason,
+        };
+
+        if start_block < height.saturating_add(RAMP_NOTICE_BLOCKS) {
+            return Err(invalid(format!(
+                "must start at least {} blocks after {}, not at {}",
+                RAMP_NOTICE_BLOCKS, height, start_block
+            )));
+        }
+        if end_block <= start_block {
+            return Err(invalid(format!("ends at {}, not after its start at {}", end_block, start_block)));
+        }
+        if target_value == start_value {
+            return Err(invalid(format!("already at {}", target_value)));
+        }
+
+        let bounds = parameter.bounds();
+        bounds.check_value(parameter, target_value)?;
+        let per_block = start_value.abs_diff(target_value).div_ceil(end_block - start_block);
+        if per_block > bounds.max_change(start_value) {
+            return Err(invalid(format!(
+                "moves {} per block, more than the per-update limit of {}",
+                per_block,
+                bounds.max_change(start_value)
+            )));
+        }
+
+        Ok(Self {
+            parameter,
+            start_value,
+            target_value,
+            start_block,
+            end_block,
+            started: false,
+        })
+    }
+
+    /// Value at `height`, rounded towards the start value to the
+    /// parameter's step
+    pub fn value_at(&self, height: u64) -> u64 {
+        if height <= self.start_block {
+            return self.start_value;
+        }
+        if height >= self.end_block {
+            return self.target_value;
+        }
+
+        let elapsed = (height - self.start_block) as u128;
+        let span = (self.end_block - self.start_block) as u128;
+        let moved = (self.start_value.abs_diff(self.target_value) as u128 * elapsed / span) as u64;
+        let step = self.parameter.bounds().step.max(1);
+        let moved = moved / step * step;
+
+        if self.target_value > self.start_value {
+            self.start_value + moved
+        } else {
+            self.start_value - moved
+        }
+    }
+}
+
+impl fmt::Display for ParameterRamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} -> {} over blocks {}..{}",
+            self.start_value, self.target_value, self.start_block, self.end_block
+        )
+    }
+}
+
+/// A ramp's value in one block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RampStep {
+    /// The ramp
+    pub ramp: ParameterRamp,
+    /// Value of the parameter in the block
+    pub value: u64,
+    /// Whether this is the ramp's first step
+    pub started: bool,
+    /// Whether the target was reached, ending the ramp
+    pub completed: bool,
+}
+
+/// Scheduled and running ramps, at most one per parameter
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParameterRamps {
+    ramps: BTreeMap<ProtocolParameter, ParameterRamp>,
+}
+
+impl ParameterRamps {
+    /// Create with nothing scheduled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether nothing is scheduled
+    pub fn is_empty(&self) -> bool {
+        self.ramps.is_empty()
+    }
+
+    /// Ramp of `parameter`, if one is scheduled or running
+    pub fn get(&self, parameter: ProtocolParameter) -> Option<&ParameterRamp> {
+        self.ramps.get(&parameter)
+    }
+
+    /// Every ramp, by parameter
+    pub fn iter(&self) -> impl Iterator<Item = &ParameterRamp> {
+        self.ramps.values()
+    }
+
+    /// Schedule `ramp`, unless its parameter already has one
+    pub fn schedule(&mut self, ramp: ParameterRamp) -> Result<()> {
+        if self.ramps.contains_key(&ramp.parameter) {
+            return Err(Error::InvalidParameter {
+                name: format!("ramp:{}", ramp.parameter),
+                reason: "A ramp is already scheduled; cancel it first".into(),
+            });
+        }
+        self.ramps.insert(ramp.parameter, ramp);
+        Ok(())
+    }
+
+    /// Drop the ramp of `parameter`, returning it
+    pub fn cancel(&mut self, parameter: ProtocolParameter) -> Option<ParameterRamp> {
+        self.ramps.remove(&parameter)
+    }
+
+    /// Steps of the ramps running at `height`; completed ramps are removed
+    pub fn advance(&mut self, height: u64) -> Vec<RampStep> {
+        let mut steps = Vec::new();
+        for ramp in self.ramps.values_mut() {
+            if height < ramp.start_block {
+                continue;
+            }
+            let started = !ramp.started;
+            ramp.started = true;
+            steps.push(RampStep {
+                ramp: *ramp,
+                value: ramp.value_at(height),
+                started,
+                completed: height >= ramp.end_block,
+            });
+        }
+        self.ramps.retain(|_, ramp| height < ramp.end_block);
+        steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolates_in_steps() {
+        let mcr = ParameterRamp::new(ProtocolParameter::MinCollateralRatio, 110, 125, 1_000, 11_000, 0).unwrap();
+        assert_eq!(mcr.value_at(0), 110);
+        assert_eq!(mcr.value_at(1_000), 110);
+        assert_eq!(mcr.value_at(1_666), 110);
+        assert_eq!(mcr.value_at(1_667), 111);
+        assert_eq!(mcr.value_at(6_000), 117);
+        assert_eq!(mcr.value_at(10_999), 124);
+        assert_eq!(mcr.value_at(11_000), 125);
+
+        // Fees move in steps of 5 bps, rounded towards the start
+        let fee = ParameterRamp::new(ProtocolParameter::BorrowingFeeBps, 100, 50, 200, 300, 0).unwrap();
+        assert_eq!(fee.value_at(209), 100);
+        assert_eq!(fee.value_at(210), 95);
+        assert_eq!(fee.value_at(299), 55);
+        assert_eq!(fee.value_at(300), 50);
+    }
+
+    #[test]
+    fn test_schedule_and_advance() {
+        let mcr = ProtocolParameter::MinCollateralRatio;
+        assert!(ParameterRamp::new(mcr, 110, 125, 100, 200, 0).is_err(), "too soon");
+        assert!(ParameterRamp::new(mcr, 110, 125, 200, 200, 0).is_err(), "empty range");
+        assert!(ParameterRamp::new(mcr, 110, 250, 200, 10_000, 0).is_err(), "out of bounds");
+        assert!(ParameterRamp::new(mcr, 110, 150, 200, 201, 0).is_err(), "too steep");
+
+        let mut ramps = ParameterRamps::new();
+        ramps.schedule(ParameterRamp::new(mcr, 110, 112, 200, 202, 0).unwrap()).unwrap();
+        assert!(ramps.schedule(ParameterRamp::new(mcr, 110, 120, 300, 400, 0).unwrap()).is_err());
+
+        assert!(ramps.advance(199).is_empty());
+        let steps: Vec<_> = [200, 201, 202]
+            .into_iter()
+            .flat_map(|height| ramps.advance(height))
+            .map(|step| (step.value, step.started, step.completed))
+            .collect();
+        assert_eq!(steps, [(110, true, false), (111, false, false), (112, false, true)]);
+        assert!(ramps.is_empty());
+    }
+}
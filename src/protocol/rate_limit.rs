@@ -0,0 +1,247 @@
+//! Per-account operation rate limiting.
+//!
+//! HTTP-level limits only protect a single entry point; operations can also
+//! arrive through spells or batches. The state machine therefore tracks, per
+//! signer, how many operations landed in the current block window and when
+//! the signer last redeemed or liquidated, and rejects operations over the
+//! limits configured in [`ProtocolParams`].
+//!
+//! The activity is held in memory only. It is not persisted with the state,
+//! so a restarted node starts with every account's window and cooldown
+//! cleared.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::core::config::ProtocolParams;
+use crate::error::{Error, Result};
+use crate::protocol::operations::ProtocolOperation;
+use crate::utils::crypto::{Hash, PublicKey};
+use crate::utils::undo::{Undo, UndoMap};
+
+/// Recent activity of one account
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountActivity {
+    /// First block of the current window
+    pub window_start: u64,
+    /// Operations in the current window
+    pub ops_in_window: u32,
+    /// Block of the last redemption
+    pub last_redeem: Option<u64>,
+    /// Block of the last liquidation
+    pub last_liquidation: Option<u64>,
+}
+
+impl AccountActivity {
+    /// Operations counted against the window at `block_height`
+    fn ops_at(&self, block_height: u64, window_blocks: u64) -> u32 {
+        if block_height >= self.window_start.saturating_add(window_blocks) {
+            0
+        } else {
+            self.ops_in_window
+        }
+    }
+}
+
+/// Tracks operation rates per signer, in memory only
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    /// Activity keyed by hashed public key
+    accounts: UndoMap<HashMap<[u8; 32], AccountActivity>>,
+}
+
+impl RateLimiter {
+    /// Create an empty rate limiter
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn key(account: &PublicKey) -> [u8; 32] {
+        *Hash::sha256(account.as_bytes()).as_bytes()
+    }
+
+    /// Activity recorded for `account`
+    pub fn activity(&self, account: &PublicKey) -> Option<&AccountActivity> {
+        self.accounts.get(&Self::key(account))
+    }
+
+    /// Number of accounts being tracked
+    pub fn tracked_accounts(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// Check whether `op` may execute at `block_height`
+    pub fn check(&self, op: &ProtocolOperation, block_height: u64, params: &ProtocolParams) -> Result<()> {
+        let Some(activity) = self.activity(op.signer()) else {
+            return Ok(());
+        };
+
+        if params.max_ops_per_window > 0
+            && activity.ops_at(block_height, params.rate_limit_window_blocks) >= params.max_ops_per_window
+        {
+            return Err(Error::RateLimitExceeded {
+                limit: params.max_ops_per_window,
+                window_blocks: params.rate_limit_window_blocks,
+            });
+        }
+
+        let (last, interval) = match op {
+            ProtocolOperation::Redeem(_) => (activity.last_redeem, params.min_redeem_interval_blocks),
+            ProtocolOperation::LiquidateCDP(_) => {
+                (activity.last_liquidation, params.min_liquidation_interval_blocks)
+            }
+            _ => return Ok(()),
+        };
+
+        if let Some(last) = last {
+            let allowed_at = last.saturating_add(interval);
+            if block_height < allowed_at {
+                return Err(Error::OperationTooFrequent {
+                    operation: op.operation_type().into(),
+                    retry_in_blocks: allowed_at - block_height,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Record a successfully executed operation
+    pub fn record(&mut self, op: &ProtocolOperation, block_height: u64, params: &ProtocolParams) {
+        let activity = self.accounts.get_or_insert_with(Self::key(op.signer()), AccountActivity::default);
+
+        if activity.ops_at(block_height, params.rate_limit_window_blocks) == 0 {
+            activity.window_start = block_height;
+            activity.ops_in_window = 0;
+        }
+        activity.ops_in_window = activity.ops_in_window.saturating_add(1);
+
+        match op {
+            ProtocolOperation::Redeem(_) => activity.last_redeem = Some(block_height),
+            ProtocolOperation::LiquidateCDP(_) => activity.last_liquidation = Some(block_height),
+            _ => {}
+        }
+    }
+
+    /// Forget accounts that no limit applies to any more
+    pub fn prune(&mut self, block_height: u64, params: &ProtocolParams) {
+        let cooling_down = |last: Option<u64>, interval: u64| {
+            last.is_some_and(|b| block_height < b.saturating_add(interval))
+        };
+
+        self.accounts.retain(|_, a| {
+            a.ops_at(block_height, params.rate_limit_window_blocks) > 0
+                || cooling_down(a.last_redeem, params.min_redeem_interval_blocks)
+                || cooling_down(a.last_liquidation, params.min_liquidation_interval_blocks)
+        });
+    }
+}
+
+impl Undo for RateLimiter {
+    type Mark = ();
+
+    fn begin_undo(&mut self) {
+        self.accounts.begin_undo();
+    }
+
+    fn commit_undo(&mut self) {
+        self.accounts.commit_undo();
+    }
+
+    fn rollback_undo(&mut self, _mark: ()) {
+        self.accounts.rollback_undo();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::token::TokenAmount;
+    use crate::core::cdp::CDPId;
+    use crate::protocol::operations::{LiquidateCDPOp, RedeemOp, TransferOp};
+    use crate::utils::constants::SIGNATURE_LENGTH;
+    use crate::utils::crypto::{KeyPair, Signature};
+
+    fn transfer(from: &PublicKey) -> ProtocolOperation {
+        ProtocolOperation::Transfer(TransferOp {
+            from: *from,
+            to: *from,
+            amount: TokenAmount::from_cents(100),
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        })
+    }
+
+    fn redeem(redeemer: &PublicKey) -> ProtocolOperation {
+        ProtocolOperation::Redeem(RedeemOp {
+            redeemer: *redeemer,
+            amount: TokenAmount::from_cents(10_000),
+            max_fee_bps: 500,
+            first_cdp_hint: None,
+            last_cdp_hint: None,
+            max_cdps: 0,
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        })
+    }
+
+    #[test]
+    fn test_window_limit() {
+        let params = ProtocolParams::default().with_rate_limit(2, 3);
+        let account = *KeyPair::generate().public_key();
+        let op = transfer(&account);
+        let mut limiter = RateLimiter::new();
+
+        for _ in 0..2 {
+            limiter.check(&op, 100, &params).unwrap();
+            limiter.record(&op, 100, &params);
+        }
+        assert!(matches!(
+            limiter.check(&op, 102, &params),
+            Err(Error::RateLimitExceeded { limit: 2, window_blocks: 3 })
+        ));
+
+        // New window
+        limiter.check(&op, 103, &params).unwrap();
+
+        // Other accounts are unaffected
+        limiter.check(&transfer(KeyPair::generate().public_key()), 100, &params).unwrap();
+    }
+
+    #[test]
+    fn test_redeem_interval() {
+        let params = ProtocolParams::default().with_min_intervals(3, 1);
+        let account = *KeyPair::generate().public_key();
+        let mut limiter = RateLimiter::new();
+
+        limiter.record(&redeem(&account), 100, &params);
+
+        assert!(matches!(
+            limiter.check(&redeem(&account), 101, &params),
+            Err(Error::OperationTooFrequent { retry_in_blocks: 2, .. })
+        ));
+        limiter.check(&transfer(&account), 101, &params).unwrap();
+        limiter.check(&redeem(&account), 103, &params).unwrap();
+
+        limiter.prune(102, &params);
+        assert_eq!(limiter.tracked_accounts(), 1);
+        limiter.prune(110, &params);
+        assert_eq!(limiter.tracked_accounts(), 0);
+    }
+
+    #[test]
+    fn test_liquidations_not_throttled_by_default() {
+        let params = ProtocolParams::default();
+        let keeper = KeyPair::generate();
+        let liquidate = ProtocolOperation::LiquidateCDP(LiquidateCDPOp {
+            cdp_id: CDPId::generate(keeper.public_key(), 1),
+            liquidator: *keeper.public_key(),
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        });
+        let mut limiter = RateLimiter::new();
+
+        limiter.record(&liquidate, 100, &params);
+        limiter.check(&liquidate, 100, &params).unwrap();
+    }
+}
@@ -0,0 +1,259 @@
+//! Operation receipts.
+//!
+//! Every operation accepted by the node gets a receipt, identified by the
+//! operation's [`hash`](ProtocolOperation::hash), that follows it through
+//! settlement:
+//!
+//! ```text
+//! Pending -> Included(block) -> Proven(proof) -> Anchored(txid) -> Finalized
+//!    \-> Rejected
+//! ```
+//!
+//! The state machine moves receipts to `Included` when their block ends.
+//! The prover and the Bitcoin broadcaster report the later stages per block
+//! through [`ProtocolStateMachine`](crate::protocol::ProtocolStateMachine),
+//! which persists each receipt and sends it to [`ReceiptSubscribers`].
+
+use std::fmt;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use bitcoin::Txid;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::protocol::operations::ProtocolOperation;
+use crate::utils::crypto::{Hash, PublicKey};
+
+/// Confirmations of the anchor transaction after which a receipt is final
+pub const FINALITY_CONFIRMATIONS: u32 = 6;
+
+/// Stage of an operation's settlement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReceiptStatus {
+    /// Admitted, waiting for a block
+    Pending,
+    /// Executed in a block
+    Included,
+    /// The block's transitions were proven
+    Proven,
+    /// The proof was committed to a Bitcoin transaction
+    Anchored,
+    /// The anchor transaction is buried deeply enough
+    Finalized,
+    /// Failed when executed
+    Rejected,
+}
+
+impl fmt::Display for ReceiptStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Pending => "pending",
+            Self::Included => "included",
+            Self::Proven => "proven",
+            Self::Anchored => "anchored",
+            Self::Finalized => "finalized",
+            Self::Rejected => "rejected",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Settlement record of one operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Receipt {
+    /// Hash of the operation
+    pub id: Hash,
+    /// Operation type
+    pub operation: String,
+    /// Signer of the operation
+    pub signer: PublicKey,
+    /// Nonce of the operation
+    pub nonce: u64,
+    /// Current stage
+    pub status: ReceiptStatus,
+    /// Block the operation was included in, or rejected in
+    pub block_height: Option<u64>,
+    /// Hash of the proof covering the block
+    pub proof_hash: Option<Hash>,
+    /// Bitcoin transaction anchoring the proof
+    pub anchor_txid: Option<Txid>,
+    /// Confirmations of the anchor transaction last reported
+    pub confirmations: u32,
+    /// Why the operation was rejected
+    pub error: Option<String>,
+    /// Timestamp of the last change
+    pub updated_at: u64,
+}
+
+impl Receipt {
+    /// Pending receipt for `op`
+    pub fn pending(op: &ProtocolOperation, timestamp: u64) -> Result<Self> {
+        Ok(Self {
+            id: op.hash()?,
+            operation: op.operation_type().to_string(),
+            signer: *op.signer(),
+            nonce: op.nonce(),
+            status: ReceiptStatus::Pending,
+            block_height: None,
+            proof_hash: None,
+            anchor_txid: None,
+            confirmations: 0,
+            error: None,
+            updated_at: timestamp,
+        })
+    }
+
+    /// Record inclusion in `block_height`. A rejected operation may be
+    /// submitted again and included later.
+    pub fn include(&mut self, block_height: u64, timestamp: u64) -> Result<()> {
+        self.advance(&[ReceiptStatus::Pending, ReceiptStatus::Rejected], ReceiptStatus::Included)?;
+        self.block_height = Some(block_height);
+        self.error = None;
+        self.updated_at = timestamp;
+        Ok(())
+    }
+
+    /// Record that the operation failed in `block_height`
+    pub fn reject(&mut self, block_height: u64, reason: String, timestamp: u64) -> Result<()> {
+        self.advance(&[ReceiptStatus::Pending], ReceiptStatus::Rejected)?;
+        self.block_height = Some(block_height);
+        self.error = Some(reason);
+        self.updated_at = timestamp;
+        Ok(())
+    }
+
+    /// Record the proof covering the operation's block
+    pub fn prove(&mut self, proof_hash: Hash, timestamp: u64) -> Result<()> {
+        self.advance(&[ReceiptStatus::Included], ReceiptStatus::Proven)?;
+        self.proof_hash = Some(proof_hash);
+        self.updated_at = timestamp;
+        Ok(())
+    }
+
+    /// Record the Bitcoin transaction anchoring the proof
+    pub fn anchor(&mut self, txid: Txid, timestamp: u64) -> Result<()> {
+        self.advance(&[ReceiptStatus::Proven], ReceiptStatus::Anchored)?;
+        self.anchor_txid = Some(txid);
+        self.updated_at = timestamp;
+        Ok(())
+    }
+
+    /// Record the anchor's confirmations, finalizing the receipt at
+    /// [`FINALITY_CONFIRMATIONS`]. Returns whether it was finalized.
+    pub fn confirm(&mut self, confirmations: u32, timestamp: u64) -> Result<bool> {
+        if self.status != ReceiptStatus::Anchored {
+            return Err(self.invalid(ReceiptStatus::Finalized));
+        }
+        self.confirmations = confirmations;
+        self.updated_at = timestamp;
+        if confirmations < FINALITY_CONFIRMATIONS {
+            return Ok(false);
+        }
+        self.status = ReceiptStatus::Finalized;
+        Ok(true)
+    }
+
+    /// Whether the receipt will not change again
+    pub fn is_settled(&self) -> bool {
+        matches!(self.status, ReceiptStatus::Finalized | ReceiptStatus::Rejected)
+    }
+
+    fn advance(&mut self, from: &[ReceiptStatus], to: ReceiptStatus) -> Result<()> {
+        if !from.contains(&self.status) {
+            return Err(self.invalid(to));
+        }
+        self.status = to;
+        Ok(())
+    }
+
+    fn invalid(&self, to: ReceiptStatus) -> Error {
+        Error::InvalidParameter {
+            name: "receipt".into(),
+            reason: format!("Receipt {} is {}, it can't become {}", self.id.to_hex(), self.status, to),
+        }
+    }
+}
+
+/// Listeners for receipt changes
+#[derive(Debug, Default)]
+pub struct ReceiptSubscribers {
+    senders: Vec<Sender<Receipt>>,
+}
+
+impl ReceiptSubscribers {
+    /// Create with no listeners
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Receive every receipt as it changes
+    pub fn subscribe(&mut self) -> Receiver<Receipt> {
+        let (sender, receiver) = channel();
+        self.senders.push(sender);
+        receiver
+    }
+
+    /// Send `receipt` to every listener, dropping those that went away
+    pub fn notify(&mut self, receipt: &Receipt) {
+        self.senders.retain(|sender| sender.send(receipt.clone()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    use crate::core::token::TokenAmount;
+    use crate::protocol::operations::TransferOp;
+    use crate::utils::constants::SIGNATURE_LENGTH;
+    use crate::utils::crypto::{KeyPair, Signature};
+
+    fn receipt() -> Receipt {
+        let from = *KeyPair::generate().public_key();
+        let op = ProtocolOperation::Transfer(TransferOp {
+            from,
+            to: from,
+            amount: TokenAmount::from_cents(100),
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        });
+        Receipt::pending(&op, 1_000).unwrap()
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let mut receipt = receipt();
+        assert!(receipt.prove(Hash::zero(), 1_001).is_err(), "not included yet");
+
+        receipt.include(7, 1_001).unwrap();
+        receipt.prove(Hash::sha256(b"proof"), 1_002).unwrap();
+        assert!(receipt.include(8, 1_002).is_err());
+        receipt.anchor(Txid::from_str(&"01".repeat(32)).unwrap(), 1_003).unwrap();
+
+        assert!(!receipt.confirm(FINALITY_CONFIRMATIONS - 1, 1_004).unwrap());
+        assert_eq!(receipt.status, ReceiptStatus::Anchored);
+        assert!(receipt.confirm(FINALITY_CONFIRMATIONS, 1_005).unwrap());
+        assert_eq!(receipt.status, ReceiptStatus::Finalized);
+        assert_eq!(receipt.block_height, Some(7));
+        assert!(receipt.is_settled());
+    }
+
+    #[test]
+    fn test_rejected_then_resubmitted() {
+        let mut receipt = receipt();
+        receipt.reject(3, "Insufficient balance".into(), 1_001).unwrap();
+        assert!(receipt.is_settled());
+        assert!(receipt.reject(4, "again".into(), 1_002).is_err());
+
+        receipt.include(5, 1_002).unwrap();
+        assert_eq!(receipt.error, None);
+
+        let mut subscribers = ReceiptSubscribers::new();
+        let listener = subscribers.subscribe();
+        drop(subscribers.subscribe());
+        subscribers.notify(&receipt);
+        assert_eq!(listener.try_recv().unwrap().status, ReceiptStatus::Included);
+        assert_eq!(subscribers.senders.len(), 1);
+    }
+}
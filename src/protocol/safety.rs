@@ -0,0 +1,214 @@
+//! Oracle safety controller.
+//!
+//! Watches how old the last accepted price is and how many sources backed it.
+//! When the price goes stale or is thinly sourced, the state machine pauses
+//! minting and collateral withdrawals (which depend on an accurate price)
+//! while repayments and deposits, which only make positions safer, stay open.
+//! The pause is lifted automatically once a healthy price arrives.
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::constants::MIN_ORACLE_SOURCES;
+
+/// Watchdog thresholds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchdogConfig {
+    /// Whether the watchdog may pause the protocol
+    pub enabled: bool,
+    /// Blocks without a price update before the price counts as stale
+    pub max_stale_blocks: u64,
+    /// Minimum sources behind the last price
+    pub min_sources: u8,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_stale_blocks: 6, // ~1 hour
+            min_sources: MIN_ORACLE_SOURCES as u8,
+        }
+    }
+}
+
+/// Why the watchdog paused the protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PauseReason {
+    /// No price update for `age_blocks` blocks
+    StalePrice {
+        /// Blocks since the last update
+        age_blocks: u64,
+    },
+    /// Last price was backed by too few sources
+    InsufficientSources {
+        /// Sources behind the last price
+        sources: u8,
+    },
+}
+
+impl std::fmt::Display for PauseReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PauseReason::StalePrice { age_blocks } => {
+                write!(f, "price not updated for {} blocks", age_blocks)
+            }
+            PauseReason::InsufficientSources { sources } => {
+                write!(f, "price backed by only {} sources", sources)
+            }
+        }
+    }
+}
+
+/// What the state machine should do after a check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogAction {
+    /// Nothing changes
+    None,
+    /// Pause the protocol
+    Pause(PauseReason),
+    /// Lift a pause the watchdog set earlier
+    Resume,
+}
+
+/// Oracle freshness watchdog
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OracleWatchdog {
+    /// Thresholds
+    config: WatchdogConfig,
+    /// Block of the last accepted price
+    last_update_block: Option<u64>,
+    /// Sources behind the last accepted price
+    last_source_count: u8,
+    /// Reason for a pause this watchdog is holding.
+    ///
+    /// Kept in memory only: after a restart a watchdog pause looks like a
+    /// manual one and must be lifted by governance.
+    tripped: Option<PauseReason>,
+}
+
+impl OracleWatchdog {
+    /// Create a watchdog
+    pub fn new(config: WatchdogConfig) -> Self {
+        Self { config, ..Default::default() }
+    }
+
+    /// Thresholds
+    pub fn config(&self) -> &WatchdogConfig {
+        &self.config
+    }
+
+    /// Record an accepted price update
+    pub fn record_price(&mut self, block_height: u64, source_count: u8) {
+        self.last_update_block = Some(block_height);
+        self.last_source_count = source_count;
+    }
+
+    /// Blocks since the last accepted price, if any
+    pub fn price_age(&self, block_height: u64) -> Option<u64> {
+        self.last_update_block.map(|b| block_height.saturating_sub(b))
+    }
+
+    /// Sources behind the last accepted price
+    pub fn last_source_count(&self) -> u8 {
+        self.last_source_count
+    }
+
+    /// Pause the watchdog is currently holding
+    pub fn tripped(&self) -> Option<PauseReason> {
+        self.tripped
+    }
+
+    /// Whether the oracle is unhealthy at `block_height`
+    pub fn evaluate(&self, block_height: u64) -> Option<PauseReason> {
+        let age_blocks = self.price_age(block_height)?;
+
+        if age_blocks > self.config.max_stale_blocks {
+            return Some(PauseReason::StalePrice { age_blocks });
+        }
+        if self.last_source_count < self.config.min_sources {
+            return Some(PauseReason::InsufficientSources { sources: self.last_source_count });
+        }
+        None
+    }
+
+    /// Forget the watchdog's own pause, so it is not lifted when the oracle
+    /// recovers (another safeguard has taken it over)
+    pub fn release(&mut self) {
+        self.tripped = None;
+    }
+
+    /// Decide whether to pause or resume. `paused` is the protocol's current
+    /// pause flag; a pause set by someone else is never lifted here.
+    pub fn check(&mut self, block_height: u64, paused: bool) -> WatchdogAction {
+        if !self.config.enabled {
+            return WatchdogAction::None;
+        }
+
+        match (self.evaluate(block_height), self.tripped) {
+            (Some(reason), None) if !paused => {
+                self.tripped = Some(reason);
+                WatchdogAction::Pause(reason)
+            }
+            (None, Some(_)) => {
+                self.tripped = None;
+                if paused { WatchdogAction::Resume } else { WatchdogAction::None }
+            }
+            _ => WatchdogAction::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watchdog() -> OracleWatchdog {
+        OracleWatchdog::new(WatchdogConfig { enabled: true, max_stale_blocks: 3, min_sources: 2 })
+    }
+
+    #[test]
+    fn test_pauses_on_stale_price_and_resumes() {
+        let mut dog = watchdog();
+
+        // No price yet: nothing to judge
+        assert_eq!(dog.check(100, false), WatchdogAction::None);
+
+        dog.record_price(100, 3);
+        assert_eq!(dog.check(103, false), WatchdogAction::None);
+        assert_eq!(
+            dog.check(104, false),
+            WatchdogAction::Pause(PauseReason::StalePrice { age_blocks: 4 })
+        );
+        assert_eq!(dog.check(105, true), WatchdogAction::None);
+
+        dog.record_price(105, 3);
+        assert_eq!(dog.check(105, true), WatchdogAction::Resume);
+        assert!(dog.tripped().is_none());
+    }
+
+    #[test]
+    fn test_pauses_on_thin_sources() {
+        let mut dog = watchdog();
+        dog.record_price(100, 1);
+
+        assert_eq!(
+            dog.check(100, false),
+            WatchdogAction::Pause(PauseReason::InsufficientSources { sources: 1 })
+        );
+    }
+
+    #[test]
+    fn test_leaves_manual_pause_alone() {
+        let mut dog = watchdog();
+        dog.record_price(100, 1);
+
+        // Already paused by governance: the watchdog does not take ownership
+        assert_eq!(dog.check(100, true), WatchdogAction::None);
+        dog.record_price(101, 3);
+        assert_eq!(dog.check(101, true), WatchdogAction::None);
+
+        let mut disabled = OracleWatchdog::new(WatchdogConfig { enabled: false, ..Default::default() });
+        disabled.record_price(0, 0);
+        assert_eq!(disabled.check(1_000, false), WatchdogAction::None);
+    }
+}
@@ -0,0 +1,312 @@
+//! Typed signing payloads.
+//!
+//! Operations used to be signed over a hash of their encoding, which tells
+//! a wallet nothing it can show its user. A signature now covers a
+//! [`SigningPayload`]: the [`SigningDomain`] of the network the operation is
+//! meant for, and an [`OperationDescriptor`] listing the operation's type and
+//! each field as a name and a display value. A wallet renders the
+//! descriptor, and the signature commits to exactly what was rendered.
+//!
+//! The payload hash is
+//!
+//! ```text
+//! H("typed-operation", H("signing-domain", domain) || H("operation-descriptor", descriptor))
+//! ```
+//!
+//! where `H(d, x)` is the canonical hash of `x` in domain `d` (see
+//! [`crate::protocol::codec`]). Descriptor fields are sorted by name at
+//! every level of nesting; nested fields are named with `.` and sequence
+//! elements with `[i]`. The operation's own signature and co-signatures are
+//! not part of it.
+//!
+//! The domain's chain id comes from the protocol configuration, so an
+//! operation signed for one deployment is rejected by every other, even on
+//! the same network with the same keys and nonces.
+//!
+//! Signatures over the untyped hash
+//! ([`Operation::legacy_signing_hash`]) are still accepted during a
+//! compatibility window configured on the state machine and mempool. They
+//! are not bound to a chain.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+use crate::core::config::Network;
+use crate::error::{Error, Result};
+use crate::protocol::codec::canonical_hash;
+use crate::protocol::operations::Operation;
+use crate::utils::crypto::{create_message_hash, Hash};
+
+/// Version of the typed signing scheme
+pub const SIGNING_VERSION: u32 = 1;
+
+/// Domain of signing domain hashes
+const SIGNING_DOMAIN_DOMAIN: &str = "signing-domain";
+
+/// Domain of operation descriptor hashes
+const DESCRIPTOR_DOMAIN: &str = "operation-descriptor";
+
+/// Domain of signing payload hashes
+const PAYLOAD_DOMAIN: &str = "typed-operation";
+
+/// Top-level operation fields that carry signatures rather than data
+const SIGNATURE_FIELDS: [&str; 2] = ["signature", "cosignatures"];
+
+/// Network an operation is signed for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningDomain {
+    /// Network
+    pub network: Network,
+    /// Signing scheme version
+    pub version: u32,
+    /// Chain identifier
+    pub chain_id: u64,
+}
+
+impl Default for SigningDomain {
+    fn default() -> Self {
+        Self::new(Network::Regtest)
+    }
+}
+
+impl SigningDomain {
+    /// Domain for `network`
+    pub fn new(network: Network) -> Self {
+        Self {
+            network,
+            version: SIGNING_VERSION,
+            chain_id: 0,
+        }
+    }
+
+    /// Create with a custom chain identifier
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Domain separator committed to by every payload signed in this domain
+    pub fn hash(&self) -> Hash {
+        canonical_hash(SIGNING_DOMAIN_DOMAIN, self).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for SigningDomain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "zkUSD v{} on {} (chain {})", self.version, self.network, self.chain_id)
+    }
+}
+
+/// A named field of an operation, rendered for display
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DescriptorField {
+    /// Field path, e.g. `amount` or `policy.keys[0]`
+    pub name: String,
+    /// Display value
+    pub value: String,
+}
+
+/// Human-readable description of an operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationDescriptor {
+    /// Operation type, e.g. `OpenCDP`
+    pub operation: String,
+    /// Fields sorted by name
+    pub fields: Vec<DescriptorField>,
+}
+
+impl OperationDescriptor {
+    /// Describe `op`
+    pub fn of<O: Operation + Serialize>(op: &O) -> Result<Self> {
+        let value = serde_json::to_value(op).map_err(|e| Error::Serialization(e.to_string()))?;
+        let Value::Object(map) = value else {
+            return Err(Error::Serialization(format!(
+                "{} does not serialize as a struct",
+                op.operation_type()
+            )));
+        };
+
+        let mut entries: Vec<_> = map
+            .into_iter()
+            .filter(|(name, _)| !SIGNATURE_FIELDS.contains(&name.as_str()))
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut fields = Vec::new();
+        for (name, value) in entries {
+            flatten(name, value, &mut fields);
+        }
+        Ok(Self {
+            operation: op.operation_type().to_string(),
+            fields,
+        })
+    }
+
+    /// Value of the field named `name`
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|field| field.name == name)
+            .map(|field| field.value.as_str())
+    }
+
+    /// Hash of the descriptor
+    pub fn hash(&self) -> Hash {
+        canonical_hash(DESCRIPTOR_DOMAIN, self).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for OperationDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.operation)?;
+        for field in &self.fields {
+            write!(f, "\n  {}: {}", field.name, field.value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Append the display fields of `value` under `name`
+fn flatten(name: String, value: Value, fields: &mut Vec<DescriptorField>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            for (key, value) in entries {
+                flatten(format!("{}.{}", name, key), value, fields);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (i, value) in items.into_iter().enumerate() {
+                flatten(format!("{}[{}]", name, i), value, fields);
+            }
+        }
+        value => {
+            let value = match value {
+                Value::Null => "none".to_string(),
+                Value::String(s) => s,
+                Value::Object(_) => "{}".to_string(),
+                Value::Array(_) => "[]".to_string(),
+                other => other.to_string(),
+            };
+            fields.push(DescriptorField { name, value });
+        }
+    }
+}
+
+/// What a signature over an operation commits to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningPayload {
+    /// Network the operation is for
+    pub domain: SigningDomain,
+    /// The operation
+    pub descriptor: OperationDescriptor,
+}
+
+impl SigningPayload {
+    /// Payload for `op` in `domain`
+    pub fn of<O: Operation + Serialize>(op: &O, domain: &SigningDomain) -> Result<Self> {
+        Ok(Self {
+            domain: *domain,
+            descriptor: OperationDescriptor::of(op)?,
+        })
+    }
+
+    /// Hash the signature is made over
+    pub fn hash(&self) -> Hash {
+        let mut data = Vec::with_capacity(64);
+        data.extend_from_slice(self.domain.hash().as_bytes());
+        data.extend_from_slice(self.descriptor.hash().as_bytes());
+        create_message_hash(PAYLOAD_DOMAIN, &data)
+    }
+}
+
+impl fmt::Display for SigningPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\n{}", self.domain, self.descriptor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::cdp::{CDPId, OwnerPolicy};
+    use crate::core::vault::CollateralAmount;
+    use crate::protocol::operations::{DepositCollateralOp, SetOwnerPolicyOp, WithdrawCollateralOp};
+    use crate::utils::constants::{PUBKEY_LENGTH, SIGNATURE_LENGTH};
+    use crate::utils::crypto::{verify_signature, KeyPair, PublicKey, Signature};
+
+    fn deposit() -> DepositCollateralOp {
+        DepositCollateralOp {
+            cdp_id: CDPId::new([0x11; 32]),
+            depositor: PublicKey::new([0x02; PUBKEY_LENGTH]),
+            amount: CollateralAmount::from_sats(100_000_000),
+            nonce: 5,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        }
+    }
+
+    #[test]
+    fn test_descriptor() {
+        let descriptor = OperationDescriptor::of(&deposit()).unwrap();
+        assert_eq!(descriptor.operation, "DepositCollateral");
+        let names: Vec<_> = descriptor.fields.iter().map(|field| field.name.as_str()).collect();
+        assert_eq!(names, ["amount", "cdp_id", "depositor", "nonce"]);
+        assert_eq!(descriptor.field("amount"), Some("100000000"));
+        assert_eq!(descriptor.field("cdp_id"), Some("11".repeat(32).as_str()));
+
+        let keypair = KeyPair::generate();
+        let policy = SetOwnerPolicyOp {
+            cdp_id: CDPId::new([7u8; 32]),
+            owner: *keypair.public_key(),
+            policy: OwnerPolicy::MultiSig { threshold: 1, keys: vec![*keypair.public_key()] },
+            nonce: 3,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            cosignatures: Vec::new(),
+        };
+        let descriptor = OperationDescriptor::of(&policy).unwrap();
+        assert_eq!(descriptor.field("policy.MultiSig.threshold"), Some("1"));
+        assert_eq!(descriptor.field("policy.MultiSig.keys[0]"), Some(keypair.public_key().to_hex().as_str()));
+        assert_eq!(descriptor.field("cosignatures"), None);
+    }
+
+    #[test]
+    fn test_golden_payload_hash() {
+        let payload = SigningPayload::of(&deposit(), &SigningDomain::default()).unwrap();
+        assert_eq!(
+            payload.domain.hash().to_hex(),
+            "2fe70a4c50ae93403a43f687b75659922030694c1128fe512077f245a679c808"
+        );
+        assert_eq!(
+            payload.hash().to_hex(),
+            "ad851068d771de403834ce34f66d1a207c6dc01350567524ee2bb843f0ed98b9"
+        );
+    }
+
+    #[test]
+    fn test_signature_bound_to_domain_and_type() {
+        let keypair = KeyPair::generate();
+        let mainnet = SigningDomain::new(Network::Mainnet);
+
+        let mut op = deposit();
+        op.depositor = *keypair.public_key();
+        op.sign(&keypair, &mainnet).unwrap();
+        assert!(verify_signature(&op.depositor, &op.signing_hash(&mainnet).unwrap(), &op.signature));
+
+        // Same signature on another network or chain, or replayed as a withdrawal
+        let testnet = SigningDomain::new(Network::Testnet);
+        assert!(!verify_signature(&op.depositor, &op.signing_hash(&testnet).unwrap(), &op.signature));
+        let fork = mainnet.with_chain_id(Network::Mainnet.default_chain_id() + 1);
+        assert!(!verify_signature(&op.depositor, &op.signing_hash(&fork).unwrap(), &op.signature));
+        let withdraw = WithdrawCollateralOp {
+            cdp_id: op.cdp_id,
+            owner: op.depositor,
+            amount: op.amount,
+            nonce: op.nonce,
+            signature: op.signature,
+            cosignatures: Vec::new(),
+        };
+        assert!(!verify_signature(&withdraw.owner, &withdraw.signing_hash(&mainnet).unwrap(), &withdraw.signature));
+    }
+}
@@ -4,20 +4,57 @@
 //! It ensures atomic execution, state consistency, and invariant preservation.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::Receiver;
 
-use crate::core::cdp::{CDP, CDPId, CDPManager};
-use crate::core::config::ProtocolConfig;
+use bitcoin::Txid;
+
+use crate::charms::bridge::Bridge;
+use crate::core::cdp::{CDP, CDPId, CDPManager, SessionAction, SessionKey};
+use crate::core::config::{FeeSource, ProtocolConfig, ProtocolParameter, ProtocolParams};
+use crate::core::peg::{PegConfig, PegController};
+use crate::core::savings::SavingsPot;
 use crate::core::token::{TokenAmount, ZkUSD};
 use crate::core::vault::{CollateralAmount, Vault};
 use crate::error::{Error, Result};
+use crate::liquidation::engine::{AuctionConfig, AuctionHouse};
+use crate::liquidation::frontend::FrontendRegistry;
 use crate::liquidation::stability_pool::StabilityPool;
+use crate::monitoring::{
+    AnomalyDetector, AnomalyMetric, BlockActivity, BlockGauges, MetricsCollector, ProtocolAggregates, RiskMonitor,
+    RiskSnapshot, VolatilityEstimator, VOLATILITY_LOOKBACK_SECS,
+};
+use crate::oracle::attestation::PriceAttestation;
+use crate::oracle::batch::{PriceBatch, PriceSubmission, DEFAULT_ORACLE_REPUTATION};
+use crate::oracle::registry::{OracleOperator, OracleRegistry};
+use crate::oracle::price_feed::{
+    Asset, ConfidencePolicy, CrossRateFeed, PairConfig, PairRate, PriceUsage, TradingPair,
+};
+use crate::protocol::audit::{is_audited, AuditLog};
+use crate::protocol::codec::{canonical_hash, TX_DOMAIN};
 use crate::protocol::events::*;
+use crate::protocol::genesis::Genesis;
+use crate::protocol::invariants::{InvariantChecker, InvariantContext, InvariantEnforcement, InvariantViolation};
+use crate::protocol::nonces::{NonceManager, NonceWindow};
 use crate::protocol::operations::*;
-use crate::storage::backend::StorageBackend;
-use crate::storage::state::{ProtocolState, StateManager, TransactionRecord, TransactionType};
-use crate::utils::crypto::{verify_signature, Hash, PublicKey};
+use crate::protocol::pauses::{OperationPauses, PausableOperation};
+use crate::protocol::ramps::{ParameterRamp, ParameterRamps};
+use crate::protocol::rate_limit::RateLimiter;
+use crate::protocol::receipts::{Receipt, ReceiptStatus, ReceiptSubscribers};
+use crate::protocol::safety::{OracleWatchdog, WatchdogAction, WatchdogConfig};
+use crate::protocol::signing::SigningDomain;
+use crate::protocol::sync::{BlockRecord, SyncSnapshot};
+use crate::protocol::treasury::{Treasury, TreasuryAsset};
+use crate::protocol::view::{ProtocolSnapshotView, SnapshotHandle};
+use crate::storage::backend::{InMemoryStore, StorageBackend};
+use crate::storage::state::{
+    CdpProof, ProtocolState, PruningMode, StateManager, TransactionRecord, TransactionType,
+    SCHEMA_VERSION,
+};
+use crate::utils::crypto::{Hash, PublicKey, Signature, SignatureCache};
 use crate::utils::math::*;
+use crate::utils::undo::Undo;
+use crate::zkp::verifier::Verifier;
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // STATE MACHINE
@@ -35,27 +72,191 @@ pub struct ProtocolStateMachine<B: StorageBackend> {
     vault: Vault,
     /// Stability pool
     stability_pool: StabilityPool,
+    /// Stability pool frontends
+    frontends: FrontendRegistry,
     /// Protocol configuration
     config: ProtocolConfig,
     /// Current BTC price in cents
     current_price: u64,
+    /// Half-width of the current price's confidence interval in cents
+    price_interval: u64,
+    /// Price updates submitted in the current block
+    price_batch: Option<PriceBatch>,
+    /// Reputation of known oracle operators, weighting their price updates
+    oracle_reputation: HashMap<PublicKey, u8>,
+    /// Approved oracle operators; without a registry any operator may
+    /// update the price, weighted by reputation
+    oracle_registry: Option<OracleRegistry>,
+    /// Which operations may use an uncertain price
+    confidence_policy: ConfidencePolicy,
     /// Current block height
     block_height: u64,
     /// Current timestamp
     timestamp: u64,
-    /// Nonces for replay protection (pubkey hash -> nonce)
-    nonces: HashMap<[u8; 32], u64>,
+    /// Nonce windows for replay protection
+    nonces: NonceManager,
     /// Event log for current transaction
     event_log: EventLog,
+    /// Operations applied in the current block, for the block log
+    block_ops: Vec<ProtocolOperation>,
     /// Whether in recovery mode
     recovery_mode: bool,
+    /// Oracle freshness watchdog
+    watchdog: OracleWatchdog,
+    /// Per-account operation rate limits
+    rate_limiter: RateLimiter,
+    /// Per-operation pauses and circuit breakers
+    pauses: OperationPauses,
+    /// Scheduled and running parameter ramps
+    ramps: ParameterRamps,
+    /// Detector of unusual operation patterns, if enabled
+    anomaly_detector: Option<AnomalyDetector>,
+    /// Operations submitted in the current block, applied or not
+    block_submitted: u64,
+    /// Operations rejected for a bad signature in the current block
+    block_failed_signatures: u64,
+    /// Head of the hash-chained log of admin actions
+    audit_log: AuditLog,
+    /// Cross-chain bridge, if enabled
+    bridge: Option<Bridge>,
+    /// Savings pot
+    savings: SavingsPot,
+    /// Treasury bookkeeping
+    treasury: Treasury,
+    /// zkUSD redeemed in the current block, in cents
+    block_redeemed: u64,
+    /// Keeper stipends paid in the current block, in cents
+    block_keeper_rewards: u64,
+    /// Liquidation auctions
+    auctions: AuctionHouse,
+    /// Non-BTC price pairs, such as zkUSD/USD
+    pair_rates: CrossRateFeed,
+    /// Peg stability controller, if enabled
+    peg: Option<PegController>,
+    /// Periodic system risk snapshots
+    risk_monitor: RiskMonitor,
+    /// Realized BTC volatility over settled prices
+    volatility: VolatilityEstimator,
+    /// Daily and per-epoch protocol statistics
+    aggregates: ProtocolAggregates,
+    /// Operation counters and block gauges for exporters
+    metrics: MetricsCollector,
+    /// Verifier for price attestations; prices must be proven when set
+    price_verifier: Option<Box<dyn Verifier>>,
+    /// Savepoint of the open transaction, if any
+    savepoint: Option<Savepoint>,
+    /// State before the current block, restored if the block is rejected
+    block_start: Option<BlockCheckpoint>,
+    /// Read view published at the end of each block
+    view: SnapshotHandle,
+    /// Listeners for receipt changes
+    receipts: ReceiptSubscribers,
+    /// Signatures already verified, possibly by the mempool
+    signatures: SignatureCache,
+    /// Domain operations must be signed for
+    signing_domain: SigningDomain,
+    /// Height below which legacy untyped signatures are still accepted
+    legacy_signatures_until: Option<u64>,
+    /// Invariants checked at the end of each block
+    invariants: InvariantChecker,
+    /// Accept operations without checking signatures (previews only)
+    unsigned: bool,
+}
+
+/// Snapshot of in-memory state taken by `begin_block`
+#[derive(Debug, Clone)]
+struct Checkpoint {
+    cdp_manager: CDPManager,
+    token: ZkUSD,
+    vault: Vault,
+    stability_pool: StabilityPool,
+    frontends: FrontendRegistry,
+    config: ProtocolConfig,
+    current_price: u64,
+    price_interval: u64,
+    price_batch: Option<PriceBatch>,
+    oracle_registry: Option<OracleRegistry>,
+    nonces: NonceManager,
+    event_count: usize,
+    op_count: usize,
+    recovery_mode: bool,
+    watchdog: OracleWatchdog,
+    rate_limiter: RateLimiter,
+    bridge: Option<Bridge>,
+    savings: SavingsPot,
+    treasury: Treasury,
+    block_redeemed: u64,
+    block_keeper_rewards: u64,
+    auctions: AuctionHouse,
+    pair_rates: CrossRateFeed,
+    peg: Option<PegController>,
+}
+
+/// State saved by `begin_transaction`.
+///
+/// The large components record the entries a transaction changes in their
+/// own undo logs; only their marks and the small state are kept here.
+#[derive(Debug)]
+struct Savepoint {
+    cdp_manager: <CDPManager as Undo>::Mark,
+    token: <ZkUSD as Undo>::Mark,
+    vault: <Vault as Undo>::Mark,
+    stability_pool: <StabilityPool as Undo>::Mark,
+    bridge: Option<<Bridge as Undo>::Mark>,
+    savings: <SavingsPot as Undo>::Mark,
+    auctions: <AuctionHouse as Undo>::Mark,
+    config: ProtocolConfig,
+    current_price: u64,
+    price_interval: u64,
+    price_batch: Option<PriceBatch>,
+    oracle_registry: Option<OracleRegistry>,
+    event_count: usize,
+    op_count: usize,
+    recovery_mode: bool,
+    watchdog: OracleWatchdog,
+    treasury: Treasury,
+    block_redeemed: u64,
+    block_keeper_rewards: u64,
+    pair_rates: CrossRateFeed,
+    peg: Option<PegController>,
+}
+
+/// Snapshot of in-memory state taken by `begin_block`
+#[derive(Debug, Clone)]
+struct BlockCheckpoint {
+    state: Checkpoint,
+    block_height: u64,
+    timestamp: u64,
+    pauses: OperationPauses,
+    ramps: ParameterRamps,
+    audit_log: AuditLog,
 }
 
 impl<B: StorageBackend> ProtocolStateMachine<B> {
     /// Create a new state machine with the given storage backend
     pub fn new(backend: B) -> Result<Self> {
+        Self::open(backend, None)
+    }
+
+    /// Create a state machine for the network started from `genesis`.
+    ///
+    /// A fresh database is initialized from the genesis; an existing one must
+    /// have been initialized from a genesis with the same hash.
+    pub fn from_genesis(backend: B, genesis: &Genesis) -> Result<Self> {
+        Self::open(backend, Some(genesis))
+    }
+
+    fn open(backend: B, genesis: Option<&Genesis>) -> Result<Self> {
         let state_manager = StateManager::new(backend);
+        if let Some(genesis) = genesis {
+            Self::check_genesis(&state_manager, genesis)?;
+        }
         let protocol_state = state_manager.initialize_if_needed()?;
+        let signing_domain = state_manager
+            .load_genesis()?
+            .map(|genesis| SigningDomain::new(genesis.network))
+            .unwrap_or_default()
+            .with_chain_id(protocol_state.config.chain_id);
 
         Ok(Self {
             state_manager,
@@ -63,16 +264,263 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
             token: ZkUSD::new(),
             vault: Vault::new(),
             stability_pool: StabilityPool::new(),
+            frontends: FrontendRegistry::new(),
             config: protocol_state.config.clone(),
             current_price: 0,
+            price_interval: 0,
+            price_batch: None,
+            oracle_reputation: HashMap::new(),
+            oracle_registry: None,
+            confidence_policy: ConfidencePolicy::default(),
             block_height: protocol_state.block_height,
             timestamp: protocol_state.last_update,
-            nonces: HashMap::new(),
+            nonces: NonceManager::new(),
             event_log: EventLog::new(),
+            block_ops: Vec::new(),
             recovery_mode: false,
+            watchdog: OracleWatchdog::default(),
+            rate_limiter: RateLimiter::new(),
+            pauses: OperationPauses::default(),
+            ramps: ParameterRamps::new(),
+            anomaly_detector: None,
+            block_submitted: 0,
+            block_failed_signatures: 0,
+            audit_log: AuditLog::default(),
+            bridge: None,
+            savings: SavingsPot::new(),
+            treasury: Treasury::new(),
+            block_redeemed: 0,
+            block_keeper_rewards: 0,
+            auctions: AuctionHouse::new(),
+            pair_rates: CrossRateFeed::new(),
+            peg: None,
+            risk_monitor: RiskMonitor::default(),
+            volatility: VolatilityEstimator::default(),
+            aggregates: ProtocolAggregates::default(),
+            metrics: MetricsCollector::new(),
+            price_verifier: None,
+            savepoint: None,
+            block_start: None,
+            view: SnapshotHandle::new(),
+            receipts: ReceiptSubscribers::new(),
+            signatures: SignatureCache::default(),
+            signing_domain,
+            legacy_signatures_until: None,
+            invariants: InvariantChecker::default(),
+            unsigned: false,
         })
     }
 
+    /// Initialize a fresh database from `genesis`, or check an existing one
+    /// was initialized from it
+    fn check_genesis(state_manager: &StateManager<B>, genesis: &Genesis) -> Result<()> {
+        genesis.validate()?;
+        let expected = genesis.hash();
+
+        match state_manager.load_genesis()? {
+            Some(stored) if stored.hash() == expected => Ok(()),
+            Some(stored) => Err(Error::GenesisMismatch {
+                expected: expected.to_hex(),
+                found: stored.hash().to_hex(),
+            }),
+            None if state_manager.schema_version()?.is_some() => Err(Error::GenesisMismatch {
+                expected: expected.to_hex(),
+                found: "no genesis".into(),
+            }),
+            None => {
+                let mut state = ProtocolState::new(genesis.config.clone());
+                state.last_update = genesis.genesis_time;
+                state_manager.save_protocol_state(&state)?;
+                state_manager.save_schema_version(SCHEMA_VERSION)?;
+                state_manager.save_genesis(genesis)?;
+                state_manager.flush()
+            }
+        }
+    }
+
+    /// Hash of the genesis the database was initialized from, for comparing
+    /// nodes
+    pub fn genesis_hash(&self) -> Result<Option<Hash>> {
+        Ok(self.state_manager.load_genesis()?.map(|genesis| genesis.hash()))
+    }
+
+    /// Replace a fresh node's state with a peer's snapshot and load it.
+    ///
+    /// The snapshot must descend from this database's genesis, if it has one.
+    /// Callers verify it first; see [`crate::protocol::sync::StateSync`].
+    pub fn install_snapshot(&mut self, snapshot: &SyncSnapshot) -> Result<()> {
+        if self.block_height > 0 || self.cdp_manager.total_count() > 0 || self.savepoint.is_some() {
+            return Err(Error::InvalidParameter {
+                name: "snapshot".into(),
+                reason: "Only a fresh node can install a snapshot".into(),
+            });
+        }
+        if let Some(expected) = self.genesis_hash()? {
+            if snapshot.manifest.genesis_hash != Some(expected) {
+                return Err(Error::GenesisMismatch {
+                    expected: expected.to_hex(),
+                    found: snapshot.manifest.genesis_hash.map_or_else(|| "no genesis".into(), |hash| hash.to_hex()),
+                });
+            }
+        }
+
+        snapshot.install(&self.state_manager)?;
+        self.load_state()
+    }
+
+    /// Storage of the machine, e.g. to serve it to syncing peers
+    pub fn state_manager(&self) -> &StateManager<B> {
+        &self.state_manager
+    }
+
+    /// Root of the persisted state
+    pub fn state_root(&self) -> Result<Hash> {
+        self.state_manager.compute_state_root()
+    }
+
+    /// Proof that CDP `id` is part of the [`state_root`](Self::state_root)
+    pub fn prove_cdp(&self, id: &CDPId) -> Result<Option<CdpProof>> {
+        self.state_manager.prove_cdp(id)
+    }
+
+    /// Share a signature cache, typically the mempool's, so operations
+    /// verified on admission are not verified again at execution
+    pub fn with_signature_cache(mut self, cache: SignatureCache) -> Self {
+        self.signatures = cache;
+        self
+    }
+
+    /// Set the domain operations must be signed for. Machines default to the
+    /// network of their genesis and the chain id of their configuration.
+    pub fn with_signing_domain(mut self, domain: SigningDomain) -> Self {
+        self.signing_domain = domain;
+        self
+    }
+
+    /// Keep accepting legacy untyped signatures until block `height`, giving
+    /// clients a window to move to typed signing payloads
+    pub fn with_legacy_signatures_until(mut self, height: u64) -> Self {
+        self.legacy_signatures_until = Some(height);
+        self
+    }
+
+    /// Domain operations must be signed for
+    pub fn signing_domain(&self) -> &SigningDomain {
+        &self.signing_domain
+    }
+
+    /// Replace the invariants checked at the end of each block
+    pub fn with_invariants(mut self, invariants: InvariantChecker) -> Self {
+        self.invariants = invariants;
+        self
+    }
+
+    /// Set the oracle watchdog thresholds
+    pub fn with_watchdog(self, config: WatchdogConfig) -> Self {
+        Self {
+            watchdog: OracleWatchdog::new(config),
+            ..self
+        }
+    }
+
+    /// Set the reputation (0-100) weighting an oracle operator's price
+    /// updates. Operators without one count with the default reputation.
+    pub fn with_oracle_reputation(mut self, operator: PublicKey, reputation: u8) -> Self {
+        self.oracle_reputation.insert(operator, reputation.min(100));
+        self
+    }
+
+    /// Flag unusual operation patterns at the end of each block, tripping
+    /// the mint and withdrawal circuit breakers on spikes
+    pub fn with_anomaly_detector(mut self, detector: AnomalyDetector) -> Self {
+        self.anomaly_detector = Some(detector);
+        self
+    }
+
+    /// Only accept price updates from the operators in `registry`, weighted
+    /// by their registered weights
+    pub fn with_oracle_registry(mut self, registry: OracleRegistry) -> Self {
+        self.oracle_registry = Some(registry);
+        self
+    }
+
+    /// Set how price confidence intervals restrict risk-increasing operations
+    pub fn with_confidence_policy(mut self, policy: ConfidencePolicy) -> Self {
+        self.confidence_policy = policy;
+        self
+    }
+
+    /// Require every price update to carry an attestation proof that
+    /// `verifier` accepts (production mode)
+    pub fn with_price_attestation(mut self, verifier: impl Verifier + 'static) -> Self {
+        self.price_verifier = Some(Box::new(verifier));
+        self
+    }
+
+    /// Set the protocol parameters
+    pub fn with_params(mut self, params: ProtocolParams) -> Self {
+        self.config.params = params;
+        self
+    }
+
+    /// Set the blocks between system risk snapshots
+    pub fn with_risk_interval(mut self, interval_blocks: u64) -> Self {
+        self.risk_monitor = RiskMonitor::new(interval_blocks);
+        self
+    }
+
+    /// Enable the cross-chain bridge
+    pub fn with_bridge(mut self, bridge: Bridge) -> Self {
+        self.bridge = Some(bridge);
+        self
+    }
+
+    /// Sell seized collateral at auction when the stability pool cannot
+    /// absorb a liquidation
+    pub fn with_auctions(mut self, config: AuctionConfig) -> Result<Self> {
+        self.auctions.set_config(Some(config))?;
+        Ok(self)
+    }
+
+    /// Track a non-BTC price pair with its own staleness and deviation limits
+    pub fn with_price_pair(mut self, pair: TradingPair, config: PairConfig) -> Result<Self> {
+        self.pair_rates.add_pair(pair, config)?;
+        Ok(self)
+    }
+
+    /// Steer the borrowing fee and redemption fee floor from the zkUSD/USD
+    /// market price, tracking that pair with default limits if not yet tracked
+    pub fn with_peg_stability(mut self, config: PegConfig) -> Result<Self> {
+        self.peg = Some(PegController::new(config)?);
+        if self.pair_rates.config(&TradingPair::zkusd_usd()).is_none() {
+            self.pair_rates.add_pair(TradingPair::zkusd_usd(), PairConfig::default())?;
+        }
+        Ok(self)
+    }
+
+    /// Set the key allowed to disburse treasury funds
+    pub fn with_treasury_governor(mut self, governor: PublicKey) -> Self {
+        self.treasury = self.treasury.with_governor(governor);
+        self
+    }
+
+    /// Set the history retention mode
+    pub fn with_pruning(self, mode: PruningMode) -> Self {
+        Self {
+            state_manager: self.state_manager.with_pruning(mode),
+            ..self
+        }
+    }
+
+    /// Record a version of each CDP per block so past states can be queried
+    /// with [`get_cdp_at`](Self::get_cdp_at)
+    pub fn with_cdp_history(self) -> Self {
+        Self {
+            state_manager: self.state_manager.with_cdp_history(),
+            ..self
+        }
+    }
+
     /// Load full state from storage
     pub fn load_state(&mut self) -> Result<()> {
         // Load all CDPs
@@ -81,10 +529,80 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
             let _ = self.cdp_manager.register(cdp);
         }
 
+        // Load token ledger, vault and nonces
+        if let Some(token) = self.state_manager.load_token()? {
+            self.token = token;
+        }
+        if let Some(vault) = self.state_manager.load_vault()? {
+            self.vault = vault;
+        }
+        if let Some(nonces) = self.state_manager.load_nonces()? {
+            self.nonces = nonces;
+        }
+
         // Load stability pool
         if let Some(pool) = self.state_manager.load_stability_pool()? {
             self.stability_pool = pool;
         }
+        if let Some(frontends) = self.state_manager.load_frontends()? {
+            self.frontends = frontends;
+        }
+
+        // Load bridge
+        if let Some(bridge) = self.state_manager.load_bridge()? {
+            self.bridge = Some(bridge);
+        }
+
+        // Load savings pot
+        if let Some(savings) = self.state_manager.load_savings()? {
+            self.savings = savings;
+        }
+
+        // Load treasury
+        if let Some(treasury) = self.state_manager.load_treasury()? {
+            self.treasury = treasury;
+        }
+
+        // Load auctions
+        if let Some(auctions) = self.state_manager.load_auctions()? {
+            self.auctions = auctions;
+        }
+
+        // Load statistics
+        if let Some(aggregates) = self.state_manager.load_aggregates()? {
+            self.aggregates = aggregates;
+        }
+
+        // Load peg controller and price pairs
+        if let Some(peg) = self.state_manager.load_peg()? {
+            self.peg = Some(peg);
+        }
+        if let Some(pairs) = self.state_manager.load_price_pairs()? {
+            self.pair_rates = pairs;
+        }
+
+        // Load oracle registry
+        if let Some(registry) = self.state_manager.load_oracle_registry()? {
+            self.oracle_registry = Some(registry);
+        }
+
+        // Load operation pauses and recent activity
+        if let Some(pauses) = self.state_manager.load_operation_pauses()? {
+            self.pauses = pauses;
+        }
+        if let Some(ramps) = self.state_manager.load_parameter_ramps()? {
+            self.ramps = ramps;
+        }
+        if let Some(detector) = &mut self.anomaly_detector {
+            if let Some(history) = self.state_manager.load_anomaly_history()? {
+                detector.restore(history);
+            }
+        }
+
+        // Load the audit log head
+        if let Some(audit_log) = self.state_manager.load_audit_log()? {
+            self.audit_log = audit_log;
+        }
 
         // Load price
         if let Some((price, _)) = self.state_manager.load_price()? {
@@ -94,12 +612,20 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
         // Load protocol state
         let state = self.state_manager.load_protocol_state()?;
         self.config = state.config;
+        self.signing_domain.chain_id = self.config.chain_id;
         self.block_height = state.block_height;
         self.timestamp = state.last_update;
 
+        // Replay recent settled prices into the volatility estimate
+        let history = self
+            .state_manager
+            .load_price_history(self.timestamp.saturating_sub(VOLATILITY_LOOKBACK_SECS), u64::MAX)?;
+        self.volatility = VolatilityEstimator::default().from_history(&history);
+
         // Check recovery mode
         self.check_recovery_mode()?;
 
+        self.publish_view();
         Ok(())
     }
 
@@ -114,17 +640,68 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
             active_cdps: self.cdp_manager.active_count(),
             block_height: self.block_height,
             last_update: self.timestamp,
-            version: 1,
+            version: SCHEMA_VERSION,
         };
         self.state_manager.save_protocol_state(&state)?;
 
+        // Save token ledger, vault and nonces
+        self.state_manager.save_token(&self.token)?;
+        self.state_manager.save_vault(&self.vault)?;
+        self.state_manager.save_nonces(&self.nonces)?;
+
         // Save stability pool
         self.state_manager.save_stability_pool(&self.stability_pool)?;
+        self.state_manager.save_frontends(&self.frontends)?;
+
+        // Save bridge
+        if let Some(bridge) = &self.bridge {
+            self.state_manager.save_bridge(bridge)?;
+        }
+
+        // Save savings pot
+        self.state_manager.save_savings(&self.savings)?;
+
+        // Save treasury
+        self.state_manager.save_treasury(&self.treasury)?;
+
+        // Save auctions
+        self.state_manager.save_auctions(&self.auctions)?;
+
+        // Save statistics
+        self.state_manager.save_aggregates(&self.aggregates)?;
+
+        // Save peg controller and price pairs
+        match &self.peg {
+            Some(peg) => self.state_manager.save_peg(peg)?,
+            None => {
+                self.state_manager.delete_peg()?;
+            }
+        }
+        self.state_manager.save_price_pairs(&self.pair_rates)?;
+
+        // Save oracle registry
+        match &self.oracle_registry {
+            Some(registry) => self.state_manager.save_oracle_registry(registry)?,
+            None => {
+                self.state_manager.delete_oracle_registry()?;
+            }
+        }
+
+        // Save operation pauses and recent activity
+        self.state_manager.save_operation_pauses(&self.pauses)?;
+        self.state_manager.save_parameter_ramps(&self.ramps)?;
+        if let Some(detector) = &self.anomaly_detector {
+            self.state_manager.save_anomaly_history(detector.history())?;
+        }
 
         // Save price
         self.state_manager.save_price(self.current_price, self.timestamp)?;
 
-        // Flush
+        // Commit the block's writes and flush
+        let writes = self.state_manager.commit_batch()?;
+        if writes > 0 {
+            tracing::trace!(block = self.block_height, writes, "Committed write batch");
+        }
         self.state_manager.flush()?;
 
         Ok(())
@@ -136,31 +713,384 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
 
     /// Begin a new block
     pub fn begin_block(&mut self, height: u64, timestamp: u64) -> Result<()> {
+        tracing::debug!(block = height, timestamp, "Block started");
+        let (previous_height, previous_timestamp) = (self.block_height, self.timestamp);
         self.block_height = height;
         self.timestamp = timestamp;
         self.event_log.clear();
-        Ok(())
+        self.block_ops.clear();
+        self.block_redeemed = 0;
+        self.block_keeper_rewards = 0;
+        self.block_submitted = 0;
+        self.block_failed_signatures = 0;
+        self.price_batch = None;
+        self.block_start = Some(BlockCheckpoint {
+            state: self.capture(),
+            block_height: previous_height,
+            timestamp: previous_timestamp,
+            pauses: self.pauses.clone(),
+            ramps: self.ramps.clone(),
+            audit_log: self.audit_log,
+        });
+        self.rate_limiter.prune(height, &self.config.params);
+        self.run_watchdog();
+        // Writes accumulate until `end_block` commits them in one batch
+        self.state_manager.begin_batch()?;
+        // Move ramped parameters to this block's values
+        self.advance_ramps()
     }
 
     /// End the current block
     pub fn end_block(&mut self) -> Result<EventLog> {
-        // Save state
+        // Settle the block's price
+        self.finalize_price()?;
+
+        // Check invariants before anything is persisted
+        self.enforce_invariants()?;
+
+        // Steer fees towards the peg
+        self.run_peg_controller();
+
+        // Flag unusual activity, then reset the circuit breakers of
+        // operations without anomalies this block
+        self.detect_anomalies();
+        self.pauses.end_block(self.timestamp);
+
+        // Chain admin actions into the audit log
+        self.record_audit_entries()?;
+
+        // Persist block events
+        if !self.event_log.is_empty() {
+            self.state_manager.save_events(self.block_height, self.event_log.events())?;
+        }
+
+        // Move the operations' receipts to included
+        self.include_receipts()?;
+
+        // Log the block's operations for peers catching up
+        self.state_manager.save_block(&BlockRecord {
+            height: self.block_height,
+            timestamp: self.timestamp,
+            operations: std::mem::take(&mut self.block_ops),
+            events_root: self.event_log.merkle_root(),
+        })?;
+
+        // Index outbound bridge transfers for the relayers
+        for event in self.event_log.events() {
+            if let ProtocolEvent::BridgeOut(transfer) = event {
+                self.state_manager.save_bridge_transfer(transfer)?;
+            }
+        }
+
+        // Drop history outside the retention window
+        self.state_manager.prune(self.block_height, self.timestamp)?;
+
+        // Refresh the system risk snapshot
+        if self.risk_monitor.is_due(self.block_height) {
+            self.risk_monitor.record(RiskSnapshot::compute(
+                &self.cdp_manager,
+                self.current_price,
+                self.config.effective_mcr(),
+                self.stability_pool.total_deposits(),
+                self.block_height,
+            )
+            .with_volatility(&self.volatility));
+        }
+
+        self.aggregates.record_block(
+            self.block_height,
+            self.timestamp,
+            self.event_log.events(),
+            self.token.total_supply().cents(),
+        );
+
+        self.metrics.observe_block(BlockGauges {
+            block_height: self.block_height,
+            btc_price: self.current_price,
+            total_supply: self.token.total_supply().cents(),
+            total_collateral: self.vault.total_collateral().sats(),
+            active_cdps: self.cdp_manager.active_count(),
+            pool_deposits: self.stability_pool.total_deposits().cents(),
+            tcr: self.calculate_tcr().unwrap_or(0),
+            paused: self.config.paused,
+            recovery_mode: self.recovery_mode,
+            btc_volatility_bps: self.volatility.volatility_bps().unwrap_or(0),
+        });
+
+        // Save state, then expose it to readers
         self.save_state()?;
+        self.publish_view();
+        tracing::debug!(block = self.block_height, events = self.event_log.len(), "Block ended");
 
         // Return events
         let events = std::mem::take(&mut self.event_log);
         Ok(events)
     }
 
+    /// Publish the current state as the read view
+    fn publish_view(&self) {
+        let mut view = ProtocolSnapshotView::new(self.cdp_manager.clone(), self.token.all_balances().clone());
+        view.block_height = self.block_height;
+        view.timestamp = self.timestamp;
+        view.btc_price = self.current_price;
+        view.total_supply = self.token.total_supply();
+        view.total_collateral = self.vault.total_collateral();
+        view.total_debt = self.total_debt();
+        view.pool_deposits = self.stability_pool.total_deposits();
+        view.min_collateral_ratio = self.config.effective_mcr();
+        view.paused = self.config.paused;
+        view.paused_operations = self.paused_operations();
+        view.recovery_mode = self.recovery_mode;
+        view.risk = self.risk_monitor.latest().cloned();
+        self.view.publish(view);
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // TRANSACTIONS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Begin a transaction, opening undo logs on the in-memory state and
+    /// buffering its storage writes in a nested batch
+    pub fn begin_transaction(&mut self) -> Result<()> {
+        if self.savepoint.is_some() {
+            return Err(Error::Internal("Transaction already in progress".into()));
+        }
+
+        self.state_manager.begin_nested_batch()?;
+        self.savepoint = Some(self.save());
+
+        Ok(())
+    }
+
+    /// Commit the open transaction, keeping all changes made since it began
+    pub fn commit(&mut self) -> Result<()> {
+        self.savepoint
+            .take()
+            .ok_or_else(|| Error::Internal("No transaction in progress".into()))?;
+        self.release();
+
+        self.state_manager.commit_nested_batch()
+    }
+
+    /// Roll back the open transaction, discarding all changes made since it began
+    pub fn rollback(&mut self) -> Result<()> {
+        let savepoint = self.savepoint
+            .take()
+            .ok_or_else(|| Error::Internal("No transaction in progress".into()))?;
+
+        // Every write made since the transaction began is still buffered
+        self.state_manager.discard_nested_batch()?;
+        self.revert(savepoint);
+
+        Ok(())
+    }
+
+    /// Check if a transaction is currently open
+    pub fn in_transaction(&self) -> bool {
+        self.savepoint.is_some()
+    }
+
+    /// Open undo logs and save the small state a transaction may change
+    fn save(&mut self) -> Savepoint {
+        // These keep everything in their logs
+        self.frontends.begin_undo();
+        self.nonces.begin_undo();
+        self.rate_limiter.begin_undo();
+        Savepoint {
+            cdp_manager: self.cdp_manager.begin_undo(),
+            token: self.token.begin_undo(),
+            vault: self.vault.begin_undo(),
+            stability_pool: self.stability_pool.begin_undo(),
+            bridge: self.bridge.as_mut().map(Bridge::begin_undo),
+            savings: self.savings.begin_undo(),
+            auctions: self.auctions.begin_undo(),
+            config: self.config.clone(),
+            current_price: self.current_price,
+            price_interval: self.price_interval,
+            price_batch: self.price_batch.clone(),
+            oracle_registry: self.oracle_registry.clone(),
+            event_count: self.event_log.len(),
+            op_count: self.block_ops.len(),
+            recovery_mode: self.recovery_mode,
+            watchdog: self.watchdog.clone(),
+            treasury: self.treasury.clone(),
+            block_redeemed: self.block_redeemed,
+            block_keeper_rewards: self.block_keeper_rewards,
+            pair_rates: self.pair_rates.clone(),
+            peg: self.peg.clone(),
+        }
+    }
+
+    /// Close the undo logs opened by `save`, keeping their changes
+    fn release(&mut self) {
+        self.cdp_manager.commit_undo();
+        self.token.commit_undo();
+        self.vault.commit_undo();
+        self.stability_pool.commit_undo();
+        self.frontends.commit_undo();
+        self.nonces.commit_undo();
+        self.rate_limiter.commit_undo();
+        if let Some(bridge) = self.bridge.as_mut() {
+            bridge.commit_undo();
+        }
+        self.savings.commit_undo();
+        self.auctions.commit_undo();
+    }
+
+    /// Undo every change made since `save`
+    fn revert(&mut self, savepoint: Savepoint) {
+        self.cdp_manager.rollback_undo(savepoint.cdp_manager);
+        self.token.rollback_undo(savepoint.token);
+        self.vault.rollback_undo(savepoint.vault);
+        self.stability_pool.rollback_undo(savepoint.stability_pool);
+        self.frontends.rollback_undo(());
+        self.nonces.rollback_undo(());
+        self.rate_limiter.rollback_undo(());
+        // The bridge is only installed while loading, never by a transaction
+        if let (Some(bridge), Some(mark)) = (self.bridge.as_mut(), savepoint.bridge) {
+            bridge.rollback_undo(mark);
+        }
+        self.savings.rollback_undo(savepoint.savings);
+        self.auctions.rollback_undo(savepoint.auctions);
+        self.config = savepoint.config;
+        self.current_price = savepoint.current_price;
+        self.price_interval = savepoint.price_interval;
+        self.price_batch = savepoint.price_batch;
+        self.oracle_registry = savepoint.oracle_registry;
+        self.event_log.truncate(savepoint.event_count);
+        self.block_ops.truncate(savepoint.op_count);
+        self.recovery_mode = savepoint.recovery_mode;
+        self.watchdog = savepoint.watchdog;
+        self.treasury = savepoint.treasury;
+        self.block_redeemed = savepoint.block_redeemed;
+        self.block_keeper_rewards = savepoint.block_keeper_rewards;
+        self.pair_rates = savepoint.pair_rates;
+        self.peg = savepoint.peg;
+    }
+
+    /// Snapshot the in-memory state a block may change
+    fn capture(&self) -> Checkpoint {
+        Checkpoint {
+            cdp_manager: self.cdp_manager.clone(),
+            token: self.token.clone(),
+            vault: self.vault.clone(),
+            stability_pool: self.stability_pool.clone(),
+            frontends: self.frontends.clone(),
+            config: self.config.clone(),
+            current_price: self.current_price,
+            price_interval: self.price_interval,
+            price_batch: self.price_batch.clone(),
+            oracle_registry: self.oracle_registry.clone(),
+            nonces: self.nonces.clone(),
+            event_count: self.event_log.len(),
+            op_count: self.block_ops.len(),
+            recovery_mode: self.recovery_mode,
+            watchdog: self.watchdog.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            bridge: self.bridge.clone(),
+            savings: self.savings.clone(),
+            treasury: self.treasury.clone(),
+            block_redeemed: self.block_redeemed,
+            block_keeper_rewards: self.block_keeper_rewards,
+            auctions: self.auctions.clone(),
+            pair_rates: self.pair_rates.clone(),
+            peg: self.peg.clone(),
+        }
+    }
+
+    /// Put back in-memory state captured by `capture`
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        self.cdp_manager = checkpoint.cdp_manager;
+        self.token = checkpoint.token;
+        self.vault = checkpoint.vault;
+        self.stability_pool = checkpoint.stability_pool;
+        self.frontends = checkpoint.frontends;
+        self.config = checkpoint.config;
+        self.current_price = checkpoint.current_price;
+        self.price_interval = checkpoint.price_interval;
+        self.price_batch = checkpoint.price_batch;
+        self.oracle_registry = checkpoint.oracle_registry;
+        self.nonces = checkpoint.nonces;
+        self.event_log.truncate(checkpoint.event_count);
+        self.block_ops.truncate(checkpoint.op_count);
+        self.recovery_mode = checkpoint.recovery_mode;
+        self.watchdog = checkpoint.watchdog;
+        self.rate_limiter = checkpoint.rate_limiter;
+        self.bridge = checkpoint.bridge;
+        self.savings = checkpoint.savings;
+        self.treasury = checkpoint.treasury;
+        self.block_redeemed = checkpoint.block_redeemed;
+        self.block_keeper_rewards = checkpoint.block_keeper_rewards;
+        self.auctions = checkpoint.auctions;
+        self.pair_rates = checkpoint.pair_rates;
+        self.peg = checkpoint.peg;
+    }
+
     // ═══════════════════════════════════════════════════════════════════════════
     // OPERATION EXECUTION
     // ═══════════════════════════════════════════════════════════════════════════
 
     /// Execute a protocol operation
+    ///
+    /// Runs inside its own transaction unless one is already open, so a
+    /// failure part-way through never leaves partially applied state.
     pub fn execute(&mut self, op: ProtocolOperation) -> Result<OperationResult> {
+        let op_type = op.operation_type();
+        let span = tracing::info_span!(
+            "operation",
+            op = op_type,
+            block = self.block_height,
+            cdp_id = tracing::field::Empty,
+        );
+        if let Some(cdp_id) = op.cdp_id() {
+            span.record("cdp_id", cdp_id.to_hex().as_str());
+        }
+        let _entered = span.enter();
+        let receipt_id = op.hash()?;
+
+        if self.savepoint.is_some() {
+            let result = self.apply(op);
+            self.record_outcome(op_type, &result);
+            return result;
+        }
+
+        self.begin_transaction()?;
+        let result = self.apply(op);
+        match &result {
+            Ok(_) => {
+                self.commit()?;
+                tracing::debug!("Operation applied");
+            }
+            Err(e) => {
+                self.rollback()?;
+                tracing::debug!(error = %e, "Operation rejected");
+                self.reject_receipt(&receipt_id, e)?;
+            }
+        }
+        self.record_outcome(op_type, &result);
+        result
+    }
+
+    /// Count an operation outcome for the metrics and the anomaly detector
+    fn record_outcome(&mut self, op_type: &str, result: &Result<OperationResult>) {
+        self.metrics.record_operation(op_type, result.is_ok());
+        self.block_submitted += 1;
+        if matches!(result, Err(Error::InvalidSignature)) {
+            self.block_failed_signatures += 1;
+        }
+    }
+
+    /// Apply an operation without transaction handling
+    fn apply(&mut self, op: ProtocolOperation) -> Result<OperationResult> {
+        // Enforce per-account rate limits
+        self.rate_limiter.check(&op, self.block_height, &self.config.params)?;
+
         // Verify nonce
         self.verify_nonce(op.signer(), op.nonce())?;
 
+        // Only operations that succeed count against the limits
+        let limited = op.clone();
+
         // Execute based on operation type
         let result = match op {
             ProtocolOperation::OpenCDP(op) => self.execute_open_cdp(op),
@@ -176,16 +1106,129 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
             ProtocolOperation::ClaimGains(op) => self.execute_claim_gains(op),
             ProtocolOperation::Redeem(op) => self.execute_redeem(op),
             ProtocolOperation::UpdatePrice(op) => self.execute_update_price(op),
+            ProtocolOperation::Approve(op) => self.execute_approve(op),
+            ProtocolOperation::TransferFrom(op) => self.execute_transfer_from(op),
+            ProtocolOperation::Permit(op) => self.execute_permit(op),
+            ProtocolOperation::SetOwnerPolicy(op) => self.execute_set_owner_policy(op),
+            ProtocolOperation::BridgeOut(op) => self.execute_bridge_out(op),
+            ProtocolOperation::BridgeIn(op) => self.execute_bridge_in(op),
+            ProtocolOperation::SavingsDeposit(op) => self.execute_savings_deposit(op),
+            ProtocolOperation::SavingsWithdraw(op) => self.execute_savings_withdraw(op),
+            ProtocolOperation::SavingsAccrue(op) => self.execute_savings_accrue(op),
+            ProtocolOperation::TreasuryDisburse(op) => self.execute_treasury_disburse(op),
+            ProtocolOperation::AuctionBid(op) => self.execute_auction_bid(op),
+            ProtocolOperation::AuctionReset(op) => self.execute_auction_reset(op),
+            ProtocolOperation::RegisterFrontend(op) => self.execute_register_frontend(op),
+            ProtocolOperation::ClaimFrontendGains(op) => self.execute_claim_frontend_gains(op),
+            ProtocolOperation::UpdatePairPrice(op) => self.execute_update_pair_price(op),
+            ProtocolOperation::SetSessionKey(op) => self.execute_set_session_key(op),
+            ProtocolOperation::SessionCall(op) => self.execute_session_call(op),
+            ProtocolOperation::ReportOracleMisbehavior(op) => self.execute_report_oracle_misbehavior(op),
         };
 
         // Check recovery mode after any state change
         if result.is_ok() {
             self.check_recovery_mode()?;
+            self.rate_limiter.record(&limited, self.block_height, &self.config.params);
+            self.block_ops.push(limited);
         }
 
         result
     }
 
+    /// Run an operation against an in-memory copy of the current state,
+    /// leaving this machine and its storage untouched
+    ///
+    /// The signature is not checked, so an operation can be previewed before
+    /// it is signed. Nonce, rate limit, ratio and price checks still apply.
+    pub fn preview(&self, op: ProtocolOperation) -> Result<OperationPreview> {
+        let cdp_id = op.cdp_id().copied();
+        let mut fork = self.fork()?;
+        let result = fork.execute(op)?;
+
+        let cdp_id = cdp_id.or(match &result {
+            OperationResult::OpenCDP(r) => Some(r.cdp_id),
+            _ => None,
+        });
+        let cdp_ratio = cdp_id
+            .and_then(|id| fork.cdp_manager.get(&id))
+            .filter(|cdp| !cdp.status.is_terminal())
+            .map(|cdp| cdp.calculate_ratio(fork.current_price));
+
+        let events = fork.event_log.events().to_vec();
+        let fees = events
+            .iter()
+            .map(|event| match event {
+                ProtocolEvent::DebtMinted(e) => e.fee.cents(),
+                ProtocolEvent::Redemption(e) => e.fee.cents(),
+                _ => 0,
+            })
+            .sum();
+
+        Ok(OperationPreview {
+            result,
+            fees: TokenAmount::from_cents(fees),
+            cdp_ratio,
+            tcr: fork.tcr()?,
+            recovery_mode: fork.recovery_mode,
+            events,
+        })
+    }
+
+    /// Dry-run governance parameter changes, applied in order, against the
+    /// current state without changing it
+    pub fn preview_parameter_changes(&self, changes: &[(ProtocolParameter, u64)]) -> Result<ParameterChangePreview> {
+        let ctx = InvariantContext {
+            cdps: &self.cdp_manager,
+            token: &self.token,
+            vault: &self.vault,
+            stability_pool: &self.stability_pool,
+            savings: &self.savings,
+            btc_price: self.current_price,
+            tcr: self.calculate_tcr().ok(),
+        };
+        ParameterChangePreview::compute(&self.config.params, changes, &ctx, &self.invariants)
+    }
+
+    /// Copy the in-memory state into a machine backed by an empty in-memory
+    /// store that accepts unsigned operations
+    fn fork(&self) -> Result<ProtocolStateMachine<InMemoryStore>> {
+        let mut fork = ProtocolStateMachine::new(InMemoryStore::new())?;
+        fork.cdp_manager = self.cdp_manager.clone();
+        fork.token = self.token.clone();
+        fork.vault = self.vault.clone();
+        fork.stability_pool = self.stability_pool.clone();
+        fork.frontends = self.frontends.clone();
+        fork.config = self.config.clone();
+        fork.current_price = self.current_price;
+        fork.price_interval = self.price_interval;
+        fork.price_batch = self.price_batch.clone();
+        fork.oracle_reputation = self.oracle_reputation.clone();
+        fork.oracle_registry = self.oracle_registry.clone();
+        fork.confidence_policy = self.confidence_policy;
+        fork.block_height = self.block_height;
+        fork.timestamp = self.timestamp;
+        fork.nonces = self.nonces.clone();
+        fork.recovery_mode = self.recovery_mode;
+        fork.watchdog = self.watchdog.clone();
+        fork.rate_limiter = self.rate_limiter.clone();
+        fork.pauses = self.pauses.clone();
+        fork.ramps = self.ramps.clone();
+        fork.anomaly_detector = self.anomaly_detector.clone();
+        fork.audit_log = self.audit_log;
+        fork.bridge = self.bridge.clone();
+        fork.savings = self.savings.clone();
+        fork.treasury = self.treasury.clone();
+        fork.block_redeemed = self.block_redeemed;
+        fork.block_keeper_rewards = self.block_keeper_rewards;
+        fork.auctions = self.auctions.clone();
+        fork.pair_rates = self.pair_rates.clone();
+        fork.peg = self.peg.clone();
+        fork.volatility = self.volatility.clone();
+        fork.unsigned = true;
+        Ok(fork)
+    }
+
     // ═══════════════════════════════════════════════════════════════════════════
     // CDP OPERATIONS
     // ═══════════════════════════════════════════════════════════════════════════
@@ -203,8 +1246,8 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
         let cdp = CDP::with_collateral(
             op.owner,
             op.collateral.sats(),
+            op.nonce,
             self.block_height,
-            self.timestamp,
         )?;
         let cdp_id = cdp.id;
 
@@ -214,23 +1257,29 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
 
         if let Some(initial_debt) = op.initial_debt {
             if initial_debt.cents() > 0 {
+                self.ensure_not_paused(PausableOperation::Mint)?;
                 // Calculate ratio
                 ratio = calculate_collateral_ratio(
                     op.collateral.sats(),
-                    initial_debt.cents(),
                     self.current_price,
+                    initial_debt.cents(),
                 )?;
 
-                // Check MCR
+                // Check MCR against the price allowed for borrowing
                 let min_ratio = if self.recovery_mode {
                     self.config.params.critical_collateral_ratio
                 } else {
                     self.config.effective_mcr()
                 };
+                let checked_ratio = calculate_collateral_ratio(
+                    op.collateral.sats(),
+                    self.price_for(PriceUsage::RiskIncreasing)?,
+                    initial_debt.cents(),
+                )?;
 
-                if ratio < min_ratio {
+                if checked_ratio < min_ratio {
                     return Err(Error::CollateralizationRatioTooLow {
-                        current: ratio,
+                        current: checked_ratio,
                         minimum: min_ratio,
                     });
                 }
@@ -247,7 +1296,7 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
         self.cdp_manager.register(cdp.clone())?;
 
         // Update vault
-        let tx_hash = Hash::sha256(&bincode::serialize(&op).unwrap_or_default());
+        let tx_hash = operation_hash(&op);
         self.vault.deposit(cdp_id, op.collateral, self.block_height, tx_hash)?;
 
         // Mint tokens if debt was created
@@ -292,47 +1341,64 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
     fn execute_deposit(&mut self, op: DepositCollateralOp) -> Result<OperationResult> {
         self.verify_operation_signature(&op)?;
 
+        let tx_hash = operation_hash(&op);
+        let result = self.deposit_collateral(op.cdp_id, op.depositor, op.amount, tx_hash)?;
+        Ok(OperationResult::Deposit(result))
+    }
+
+    /// Add collateral to a CDP on behalf of `depositor`
+    fn deposit_collateral(
+        &mut self,
+        cdp_id: CDPId,
+        depositor: PublicKey,
+        amount: CollateralAmount,
+        tx_hash: Hash,
+    ) -> Result<DepositResult> {
         // Get CDP
-        let cdp = self.cdp_manager.get_mut(&op.cdp_id)
-            .ok_or_else(|| Error::CDPNotFound(op.cdp_id.to_hex()))?;
+        let cdp = self.cdp_manager.get_mut(&cdp_id)
+            .ok_or_else(|| Error::CDPNotFound(cdp_id.to_hex()))?;
 
         // Deposit
-        cdp.deposit_collateral(op.amount.sats(), self.block_height)?;
+        cdp.deposit_collateral(amount.sats(), self.block_height)?;
         let new_total = CollateralAmount::from_sats(cdp.collateral_sats);
         let new_ratio = cdp.calculate_ratio(self.current_price);
 
         // Update vault
-        let tx_hash = Hash::sha256(&bincode::serialize(&op).unwrap_or_default());
-        self.vault.deposit(op.cdp_id, op.amount, self.block_height, tx_hash)?;
+        self.vault.deposit(cdp_id, amount, self.block_height, tx_hash)?;
 
         // Update config
-        self.config.add_position(op.amount.sats(), 0);
+        self.config.add_position(amount.sats(), 0);
 
         // Save CDP
-        let cdp = self.cdp_manager.get(&op.cdp_id)
-            .ok_or_else(|| Error::CDPNotFound(op.cdp_id.to_hex()))?;
+        let cdp = self.cdp_manager.get(&cdp_id)
+            .ok_or_else(|| Error::CDPNotFound(cdp_id.to_hex()))?;
         self.state_manager.save_cdp(cdp)?;
 
         // Emit event
         self.event_log.push(ProtocolEvent::CollateralDeposited(CollateralDepositedEvent {
-            cdp_id: op.cdp_id,
-            depositor: op.depositor,
-            amount: op.amount,
+            cdp_id,
+            depositor,
+            amount,
             new_total,
             new_ratio,
             block_height: self.block_height,
             timestamp: self.timestamp,
         }));
 
-        Ok(OperationResult::Deposit(DepositResult {
+        Ok(DepositResult {
             new_total,
             new_ratio,
-        }))
+        })
     }
 
     fn execute_withdraw(&mut self, op: WithdrawCollateralOp) -> Result<OperationResult> {
         self.verify_operation_signature(&op)?;
 
+        if self.config.paused {
+            return Err(Error::ProtocolPaused);
+        }
+        self.ensure_not_paused(PausableOperation::Withdraw)?;
+
         // Get CDP and verify owner
         let cdp = self.cdp_manager.get(&op.cdp_id)
             .ok_or_else(|| Error::CDPNotFound(op.cdp_id.to_hex()))?;
@@ -348,11 +1414,17 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
                 available: cdp.collateral_sats,
             })?;
 
+        let price = if cdp.debt_cents > 0 {
+            self.price_for(PriceUsage::RiskIncreasing)?
+        } else {
+            self.current_price
+        };
+
         if cdp.debt_cents > 0 {
             let new_ratio = calculate_collateral_ratio(
                 new_collateral,
+                price,
                 cdp.debt_cents,
-                self.current_price,
             )?;
 
             let min_ratio = if self.recovery_mode {
@@ -369,13 +1441,13 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
         // Execute withdrawal
         let cdp = self.cdp_manager.get_mut(&op.cdp_id)
             .ok_or_else(|| Error::CDPNotFound(op.cdp_id.to_hex()))?;
-        cdp.withdraw_collateral(op.amount.sats(), self.current_price, self.config.effective_mcr(), self.block_height)?;
+        cdp.withdraw_collateral(op.amount.sats(), price, self.config.effective_mcr(), self.block_height)?;
 
         let remaining = CollateralAmount::from_sats(cdp.collateral_sats);
         let new_ratio = cdp.calculate_ratio(self.current_price);
 
         // Update vault
-        let tx_hash = Hash::sha256(&bincode::serialize(&op).unwrap_or_default());
+        let tx_hash = operation_hash(&op);
         self.vault.withdraw(op.cdp_id, op.amount, self.block_height, tx_hash)?;
 
         // Update config
@@ -410,6 +1482,7 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
         if self.config.paused {
             return Err(Error::ProtocolPaused);
         }
+        self.ensure_not_paused(PausableOperation::Mint)?;
 
         // Get CDP and verify owner
         let cdp = self.cdp_manager.get(&op.cdp_id)
@@ -420,7 +1493,7 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
         }
 
         // Calculate borrowing fee
-        let fee_bps = self.config.params.borrowing_fee_bps;
+        let fee_bps = self.config.borrowing_fee(self.timestamp);
         if fee_bps > op.max_fee_bps {
             return Err(Error::InvalidParameter {
                 name: "fee".into(),
@@ -432,13 +1505,15 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
         let gross_amount = op.amount.cents();
         let net_amount = gross_amount.saturating_sub(fee_amount);
 
-        // Calculate new ratio
+        // Calculate new ratio, checking it against the price allowed for borrowing
+        let price = self.price_for(PriceUsage::RiskIncreasing)?;
         let new_debt = cdp.debt_cents + gross_amount;
         let new_ratio = calculate_collateral_ratio(
             cdp.collateral_sats,
-            new_debt,
             self.current_price,
+            new_debt,
         )?;
+        let checked_ratio = calculate_collateral_ratio(cdp.collateral_sats, price, new_debt)?;
 
         let min_ratio = if self.recovery_mode {
             self.config.params.critical_collateral_ratio
@@ -446,9 +1521,9 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
             self.config.effective_mcr()
         };
 
-        if new_ratio < min_ratio {
+        if checked_ratio < min_ratio {
             return Err(Error::CollateralizationRatioTooLow {
-                current: new_ratio,
+                current: checked_ratio,
                 minimum: min_ratio,
             });
         }
@@ -465,12 +1540,18 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
         // Execute mint
         let cdp = self.cdp_manager.get_mut(&op.cdp_id)
             .ok_or_else(|| Error::CDPNotFound(op.cdp_id.to_hex()))?;
-        let _net_mint = cdp.mint_debt(gross_amount, self.current_price, min_ratio, self.timestamp)?;
+        let _net_mint = cdp.mint_debt(gross_amount, price, min_ratio, self.block_height)?;
 
         // Mint tokens
-        let tx_hash = Hash::sha256(&bincode::serialize(&op).unwrap_or_default());
+        let tx_hash = operation_hash(&op);
         self.token.mint(op.owner, TokenAmount::from_cents(net_amount), self.block_height, tx_hash)?;
 
+        // Route the fee
+        let to_treasury = self.route_fee(FeeSource::Borrowing, TokenAmount::from_cents(fee_amount))?;
+        if !to_treasury.is_zero() {
+            self.token.mint(Treasury::account(), to_treasury, self.block_height, tx_hash)?;
+        }
+
         // Update config
         self.config.add_position(0, gross_amount);
 
@@ -504,22 +1585,34 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
     fn execute_repay(&mut self, op: RepayDebtOp) -> Result<OperationResult> {
         self.verify_operation_signature(&op)?;
 
+        let tx_hash = operation_hash(&op);
+        let result = self.repay_debt(op.cdp_id, op.payer, op.amount, tx_hash)?;
+        Ok(OperationResult::Repay(result))
+    }
+
+    /// Repay a CDP's debt from `payer`'s balance
+    fn repay_debt(
+        &mut self,
+        cdp_id: CDPId,
+        payer: PublicKey,
+        amount: TokenAmount,
+        tx_hash: Hash,
+    ) -> Result<RepayResult> {
         // Get CDP
-        let cdp = self.cdp_manager.get(&op.cdp_id)
-            .ok_or_else(|| Error::CDPNotFound(op.cdp_id.to_hex()))?;
+        let cdp = self.cdp_manager.get(&cdp_id)
+            .ok_or_else(|| Error::CDPNotFound(cdp_id.to_hex()))?;
         let current_debt = cdp.debt_cents;
 
         // Calculate repayment
-        let repay_amount = op.amount.cents().min(current_debt);
+        let repay_amount = amount.cents().min(current_debt);
         let remaining_debt = current_debt - repay_amount;
 
         // Burn tokens from payer
-        let tx_hash = Hash::sha256(&bincode::serialize(&op).unwrap_or_default());
-        self.token.burn(op.payer, TokenAmount::from_cents(repay_amount), self.block_height, tx_hash)?;
+        self.token.burn(payer, TokenAmount::from_cents(repay_amount), self.block_height, tx_hash)?;
 
         // Execute repayment
-        let cdp = self.cdp_manager.get_mut(&op.cdp_id)
-            .ok_or_else(|| Error::CDPNotFound(op.cdp_id.to_hex()))?;
+        let cdp = self.cdp_manager.get_mut(&cdp_id)
+            .ok_or_else(|| Error::CDPNotFound(cdp_id.to_hex()))?;
         cdp.repay_debt(repay_amount, self.block_height)?;
 
         let new_ratio = if remaining_debt == 0 {
@@ -532,14 +1625,14 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
         self.config.remove_position(0, repay_amount);
 
         // Save CDP
-        let cdp = self.cdp_manager.get(&op.cdp_id)
-            .ok_or_else(|| Error::CDPNotFound(op.cdp_id.to_hex()))?;
+        let cdp = self.cdp_manager.get(&cdp_id)
+            .ok_or_else(|| Error::CDPNotFound(cdp_id.to_hex()))?;
         self.state_manager.save_cdp(cdp)?;
 
         // Emit event
         self.event_log.push(ProtocolEvent::DebtRepaid(DebtRepaidEvent {
-            cdp_id: op.cdp_id,
-            payer: op.payer,
+            cdp_id,
+            payer,
             amount: TokenAmount::from_cents(repay_amount),
             remaining_debt: TokenAmount::from_cents(remaining_debt),
             new_ratio,
@@ -547,11 +1640,11 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
             timestamp: self.timestamp,
         }));
 
-        Ok(OperationResult::Repay(RepayResult {
+        Ok(RepayResult {
             amount_repaid: TokenAmount::from_cents(repay_amount),
             remaining_debt: TokenAmount::from_cents(remaining_debt),
             new_ratio,
-        }))
+        })
     }
 
     fn execute_close(&mut self, op: CloseCDPOp) -> Result<OperationResult> {
@@ -581,7 +1674,7 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
         cdp.close(self.block_height)?;
 
         // Update vault
-        let tx_hash = Hash::sha256(&bincode::serialize(&op).unwrap_or_default());
+        let tx_hash = operation_hash(&op);
         self.vault.withdraw(op.cdp_id, collateral, self.block_height, tx_hash)?;
 
         // Update config
@@ -606,6 +1699,116 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
         }))
     }
 
+    fn execute_set_owner_policy(&mut self, op: SetOwnerPolicyOp) -> Result<OperationResult> {
+        self.verify_operation_signature(&op)?;
+
+        let cdp = self.cdp_manager.get_mut(&op.cdp_id)
+            .ok_or_else(|| Error::CDPNotFound(op.cdp_id.to_hex()))?;
+
+        if cdp.owner != op.owner {
+            return Err(Error::Unauthorized("Not CDP owner".into()));
+        }
+
+        cdp.set_policy(op.policy.clone(), self.block_height)?;
+
+        // Save CDP
+        let cdp = self.cdp_manager.get(&op.cdp_id)
+            .ok_or_else(|| Error::CDPNotFound(op.cdp_id.to_hex()))?;
+        self.state_manager.save_cdp(cdp)?;
+
+        // Emit event
+        self.event_log.push(ProtocolEvent::OwnerPolicyChanged(OwnerPolicyChangedEvent {
+            cdp_id: op.cdp_id,
+            owner: op.owner,
+            policy: op.policy.clone(),
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        Ok(OperationResult::SetOwnerPolicy(SetOwnerPolicyResult { policy: op.policy }))
+    }
+
+    fn execute_set_session_key(&mut self, op: SetSessionKeyOp) -> Result<OperationResult> {
+        self.verify_operation_signature(&op)?;
+
+        let cdp = self.cdp_manager.get_mut(&op.cdp_id)
+            .ok_or_else(|| Error::CDPNotFound(op.cdp_id.to_hex()))?;
+
+        if cdp.owner != op.owner {
+            return Err(Error::Unauthorized("Not CDP owner".into()));
+        }
+
+        let session = match op.grant {
+            Some(grant) => {
+                let session = SessionKey::new(op.key, grant);
+                cdp.authorize_session(session.clone(), self.block_height)?;
+                Some(session)
+            }
+            None => {
+                cdp.revoke_session(&op.key, self.block_height)?;
+                None
+            }
+        };
+
+        // Save CDP
+        let cdp = self.cdp_manager.get(&op.cdp_id)
+            .ok_or_else(|| Error::CDPNotFound(op.cdp_id.to_hex()))?;
+        self.state_manager.save_cdp(cdp)?;
+
+        // Emit event
+        self.event_log.push(ProtocolEvent::SessionKeyChanged(SessionKeyChangedEvent {
+            cdp_id: op.cdp_id,
+            owner: op.owner,
+            key: op.key,
+            session: session.clone(),
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        Ok(OperationResult::SetSessionKey(SetSessionKeyResult { session }))
+    }
+
+    fn execute_session_call(&mut self, op: SessionCallOp) -> Result<OperationResult> {
+        self.verify_operation_signature(&op)?;
+
+        let cdp = self.cdp_manager.get_mut(&op.cdp_id)
+            .ok_or_else(|| Error::CDPNotFound(op.cdp_id.to_hex()))?;
+        let owner = cdp.owner;
+        let ratio = cdp.calculate_ratio(self.current_price);
+
+        // Repayments are charged only for the debt actually repaid
+        let action = match op.action {
+            SessionAction::Repay { amount_cents } => SessionAction::Repay {
+                amount_cents: amount_cents.min(cdp.debt_cents),
+            },
+            deposit => deposit,
+        };
+
+        let session = cdp.session_mut(&op.session_key)
+            .ok_or_else(|| Error::Unauthorized("Not a session key of this CDP".into()))?;
+        session.charge(&action, ratio, self.block_height)?;
+        let remaining_cap = session.remaining();
+
+        // Funds come from, and are credited to, the owner
+        let tx_hash = operation_hash(&op);
+        let new_ratio = match action {
+            SessionAction::Repay { amount_cents } => {
+                let amount = TokenAmount::from_cents(amount_cents);
+                self.repay_debt(op.cdp_id, owner, amount, tx_hash)?.new_ratio
+            }
+            SessionAction::Deposit { amount_sats } => {
+                let amount = CollateralAmount::from_sats(amount_sats);
+                self.deposit_collateral(op.cdp_id, owner, amount, tx_hash)?.new_ratio
+            }
+        };
+
+        Ok(OperationResult::SessionCall(SessionCallResult {
+            amount: action.amount(),
+            remaining_cap,
+            new_ratio,
+        }))
+    }
+
     fn execute_liquidate(&mut self, op: LiquidateCDPOp) -> Result<OperationResult> {
         self.verify_operation_signature(&op)?;
 
@@ -626,9 +1829,10 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
         // Execute liquidation
         let cdp = self.cdp_manager.get_mut(&op.cdp_id)
             .ok_or_else(|| Error::CDPNotFound(op.cdp_id.to_hex()))?;
-        let liq_result = cdp.liquidate(
+        let liq_result = cdp.liquidate_with_penalty(
             self.current_price,
             self.config.effective_mcr(),
+            self.config.liquidation_penalty_bps(ratio_at_liquidation),
             self.block_height,
         )?;
 
@@ -637,16 +1841,36 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
             // Absorb through stability pool
             self.stability_pool.absorb_liquidation(
                 TokenAmount::from_cents(debt),
-                CollateralAmount::from_sats(collateral),
+                CollateralAmount::from_sats(liq_result.collateral_seized),
             )?;
             (LiquidationMode::StabilityPool, CollateralAmount::from_sats(0))
+        } else if self.auctions.is_enabled() {
+            // Sell the seized collateral to raise the debt
+            let auction = self.auctions.start(
+                op.cdp_id,
+                owner,
+                CollateralAmount::from_sats(liq_result.collateral_seized),
+                TokenAmount::from_cents(liq_result.debt_covered),
+                self.current_price,
+                self.block_height,
+            )?;
+            self.event_log.push(ProtocolEvent::AuctionStarted(AuctionStartedEvent {
+                auction_id: auction.id,
+                cdp_id: op.cdp_id,
+                lot: auction.lot,
+                tab: auction.tab,
+                start_price: auction.start_price,
+                block_height: self.block_height,
+                timestamp: self.timestamp,
+            }));
+            (LiquidationMode::Auction, CollateralAmount::from_sats(0))
         } else {
             // Direct liquidation
             (LiquidationMode::Direct, CollateralAmount::from_sats(liq_result.liquidator_bonus))
         };
 
         // Update vault
-        let tx_hash = Hash::sha256(&bincode::serialize(&op).unwrap_or_default());
+        let tx_hash = operation_hash(&op);
         self.vault.seize(op.cdp_id, CollateralAmount::from_sats(liq_result.collateral_seized), self.block_height, tx_hash)?;
 
         // Update config
@@ -672,11 +1896,14 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
             timestamp: self.timestamp,
         }));
 
+        let keeper_reward = self.pay_keeper(op.liquidator, "liquidate", tx_hash)?;
+
         Ok(OperationResult::Liquidate(LiquidateResult {
             debt_covered: TokenAmount::from_cents(liq_result.debt_covered),
             collateral_seized: CollateralAmount::from_sats(liq_result.collateral_seized),
             liquidator_bonus: bonus,
             ratio_at_liquidation,
+            keeper_reward,
         }))
     }
 
@@ -688,7 +1915,7 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
         self.verify_operation_signature(&op)?;
 
         // Execute transfer
-        let tx_hash = Hash::sha256(&bincode::serialize(&op).unwrap_or_default());
+        let tx_hash = operation_hash(&op);
         self.token.transfer(op.from, op.to, op.amount, self.block_height, tx_hash)?;
 
         let from_balance = self.token.balance_of(&op.from);
@@ -709,412 +1936,4184 @@ impl<B: StorageBackend> ProtocolStateMachine<B> {
         }))
     }
 
-    // ═══════════════════════════════════════════════════════════════════════════
-    // STABILITY POOL OPERATIONS
-    // ═══════════════════════════════════════════════════════════════════════════
-
-    fn execute_sp_deposit(&mut self, op: StabilityDepositOp) -> Result<OperationResult> {
+    fn execute_approve(&mut self, op: ApproveOp) -> Result<OperationResult> {
         self.verify_operation_signature(&op)?;
 
-        // Burn tokens from depositor (transfer to pool)
-        let tx_hash = Hash::sha256(&bincode::serialize(&op).unwrap_or_default());
-        self.token.burn(op.depositor, op.amount, self.block_height, tx_hash)?;
-
-        // Deposit to stability pool
-        self.stability_pool.deposit(op.depositor, op.amount, self.block_height)?;
-
-        let new_total = self.stability_pool.get_current_value(&op.depositor);
+        let tx_hash = operation_hash(&op);
+        self.token.approve(op.owner, op.spender, op.amount, self.block_height, tx_hash)?;
 
         // Emit event
-        self.event_log.push(ProtocolEvent::StabilityDeposit(StabilityDepositEvent {
-            depositor: op.depositor,
+        self.event_log.push(ProtocolEvent::TokenApproval(TokenApprovalEvent {
+            owner: op.owner,
+            spender: op.spender,
             amount: op.amount,
-            new_total,
             block_height: self.block_height,
             timestamp: self.timestamp,
         }));
 
-        Ok(OperationResult::StabilityDeposit(StabilityDepositResult { new_total }))
+        Ok(OperationResult::Approve(ApproveResult {
+            allowance: self.token.allowance(&op.owner, &op.spender),
+        }))
     }
 
-    fn execute_sp_withdraw(&mut self, op: StabilityWithdrawOp) -> Result<OperationResult> {
+    fn execute_permit(&mut self, op: PermitOp) -> Result<OperationResult> {
         self.verify_operation_signature(&op)?;
 
-        // Withdraw from stability pool
-        let (withdrawn_amount, btc_claimed) = self.stability_pool.withdraw(&op.depositor, op.amount, self.block_height)?;
-
-        // Mint tokens back to depositor
-        let tx_hash = Hash::sha256(&bincode::serialize(&op).unwrap_or_default());
-        self.token.mint(op.depositor, withdrawn_amount, self.block_height, tx_hash)?;
-
-        let remaining = self.stability_pool.get_current_value(&op.depositor);
+        let permit = &op.permit;
+        let tx_hash = operation_hash(&op);
+        self.token.permit(permit, self.block_height, tx_hash)?;
 
         // Emit event
-        self.event_log.push(ProtocolEvent::StabilityWithdraw(StabilityWithdrawEvent {
-            depositor: op.depositor,
-            amount: withdrawn_amount,
-            remaining,
+        self.event_log.push(ProtocolEvent::TokenApproval(TokenApprovalEvent {
+            owner: permit.owner,
+            spender: permit.spender,
+            amount: permit.amount,
             block_height: self.block_height,
             timestamp: self.timestamp,
         }));
 
-        Ok(OperationResult::StabilityWithdraw(StabilityWithdrawResult {
-            withdrawn: withdrawn_amount,
-            remaining,
+        Ok(OperationResult::Approve(ApproveResult {
+            allowance: self.token.allowance(&permit.owner, &permit.spender),
         }))
     }
 
-    fn execute_claim_gains(&mut self, op: ClaimGainsOp) -> Result<OperationResult> {
+    fn execute_transfer_from(&mut self, op: TransferFromOp) -> Result<OperationResult> {
         self.verify_operation_signature(&op)?;
 
-        // Claim BTC gains
-        let btc_claimed = self.stability_pool.claim_btc(&op.depositor)?;
+        let tx_hash = operation_hash(&op);
+        self.token.transfer_from(op.spender, op.from, op.to, op.amount, self.block_height, tx_hash)?;
 
         // Emit event
-        self.event_log.push(ProtocolEvent::GainsClaimed(GainsClaimedEvent {
-            depositor: op.depositor,
-            btc_amount: btc_claimed,
+        self.event_log.push(ProtocolEvent::TokenTransfer(TokenTransferEvent {
+            from: op.from,
+            to: op.to,
+            amount: op.amount,
             block_height: self.block_height,
             timestamp: self.timestamp,
         }));
 
-        Ok(OperationResult::ClaimGains(ClaimGainsResult { btc_claimed }))
+        Ok(OperationResult::TransferFrom(TransferFromResult {
+            from_balance: self.token.balance_of(&op.from),
+            to_balance: self.token.balance_of(&op.to),
+            remaining_allowance: self.token.allowance(&op.from, &op.spender),
+        }))
     }
 
     // ═══════════════════════════════════════════════════════════════════════════
-    // REDEMPTION OPERATIONS
+    // BRIDGE OPERATIONS
     // ═══════════════════════════════════════════════════════════════════════════
 
-    fn execute_redeem(&mut self, op: RedeemOp) -> Result<OperationResult> {
+    fn execute_bridge_out(&mut self, op: BridgeOutOp) -> Result<OperationResult> {
         self.verify_operation_signature(&op)?;
 
-        // Calculate fee
-        let fee_bps = self.config.calculate_redemption_fee(self.timestamp);
-        if fee_bps > op.max_fee_bps {
-            return Err(Error::InvalidParameter {
-                name: "fee".into(),
-                reason: format!("Fee {}bps exceeds max {}bps", fee_bps, op.max_fee_bps),
-            });
+        if self.config.paused {
+            return Err(Error::ProtocolPaused);
         }
 
-        let fee_amount = calculate_fee_bps(op.amount.cents(), fee_bps)?;
-        let net_redemption = op.amount.cents() - fee_amount;
-
-        // Get sorted CDPs (by ratio, ascending)
-        let sorted_cdps = self.cdp_manager.get_sorted_by_ratio(self.current_price);
-
-        let mut remaining = net_redemption;
-        let mut total_collateral = 0u64;
-        let mut cdps_affected = 0u32;
-        let mut cdp_updates: Vec<(CDPId, u64, u64)> = Vec::new();
-
-        for (cdp, _ratio) in sorted_cdps {
-            if remaining == 0 {
-                break;
-            }
-            if cdp.debt_cents == 0 {
-                continue;
-            }
-
-            let redeem_from_this = remaining.min(cdp.debt_cents);
-            let coll_to_take = safe_mul_div(
-                redeem_from_this,
-                crate::utils::constants::SATS_PER_BTC,
-                self.current_price,
-            )?;
-
-            cdp_updates.push((
-                cdp.id,
-                cdp.debt_cents.saturating_sub(redeem_from_this),
-                cdp.collateral_sats.saturating_sub(coll_to_take),
-            ));
-
-            remaining -= redeem_from_this;
-            total_collateral += coll_to_take;
-            cdps_affected += 1;
-        }
+        let bridge = self.bridge.as_mut().ok_or_else(bridge_disabled)?;
+        let sequence = bridge.lock(op.dest_chain, &op.dest_address, op.amount)?;
+        let locked = bridge.locked(op.dest_chain);
 
-        // Apply CDP updates
-        for (id, new_debt, new_coll) in cdp_updates {
-            let cdp = self.cdp_manager.get_mut(&id)
-                .ok_or_else(|| Error::CDPNotFound(id.to_hex()))?;
-            cdp.debt_cents = new_debt;
-            cdp.collateral_sats = new_coll;
-            // Status is computed from debt/collateral values automatically
+        // Move the tokens into escrow
+        let tx_hash = operation_hash(&op);
+        self.token.transfer(op.sender, Bridge::escrow_account(), op.amount, self.block_height, tx_hash)?;
 
-            let cdp = self.cdp_manager.get(&id)
-                .ok_or_else(|| Error::CDPNotFound(id.to_hex()))?;
-            self.state_manager.save_cdp(cdp)?;
-        }
+        // Emit event
+        self.event_log.push(ProtocolEvent::BridgeOut(BridgeOutEvent {
+            sequence,
+            sender: op.sender,
+            dest_chain: op.dest_chain,
+            dest_address: op.dest_address,
+            amount: op.amount,
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
 
-        let redeemed = op.amount.cents() - remaining;
+        Ok(OperationResult::BridgeOut(BridgeOutResult { sequence, locked }))
+    }
 
-        // Burn redeemed tokens
-        let tx_hash = Hash::sha256(&bincode::serialize(&op).unwrap_or_default());
-        self.token.burn(op.redeemer, TokenAmount::from_cents(redeemed), self.block_height, tx_hash)?;
+    fn execute_bridge_in(&mut self, op: BridgeInOp) -> Result<OperationResult> {
+        self.verify_operation_signature(&op)?;
 
-        // Update base rate
-        self.config.update_base_rate(redeemed, self.timestamp);
+        let bridge = self.bridge.as_mut().ok_or_else(bridge_disabled)?;
+        bridge.release(&op.transfer, &op.attestations)?;
+        let locked = bridge.locked(op.transfer.source_chain);
+
+        // Release the tokens from escrow
+        let transfer = &op.transfer;
+        let tx_hash = operation_hash(&op);
+        self.token.transfer(
+            Bridge::escrow_account(),
+            transfer.recipient,
+            transfer.amount,
+            self.block_height,
+            tx_hash,
+        )?;
 
         // Emit event
-        self.event_log.push(ProtocolEvent::Redemption(RedemptionEvent {
-            redeemer: op.redeemer,
-            zkusd_amount: TokenAmount::from_cents(redeemed),
-            collateral_received: CollateralAmount::from_sats(total_collateral),
-            fee: TokenAmount::from_cents(fee_amount),
-            cdps_affected,
-            btc_price: self.current_price,
+        self.event_log.push(ProtocolEvent::BridgeIn(BridgeInEvent {
+            source_chain: transfer.source_chain,
+            source_tx: transfer.source_tx,
+            recipient: transfer.recipient,
+            amount: transfer.amount,
             block_height: self.block_height,
             timestamp: self.timestamp,
         }));
 
-        Ok(OperationResult::Redeem(RedeemResult {
-            zkusd_redeemed: TokenAmount::from_cents(redeemed),
-            collateral_received: CollateralAmount::from_sats(total_collateral),
-            fee: TokenAmount::from_cents(fee_amount),
-            cdps_affected,
+        Ok(OperationResult::BridgeIn(BridgeInResult {
+            recipient_balance: self.token.balance_of(&transfer.recipient),
+            locked,
         }))
     }
 
     // ═══════════════════════════════════════════════════════════════════════════
-    // ORACLE OPERATIONS
+    // SAVINGS OPERATIONS
     // ═══════════════════════════════════════════════════════════════════════════
 
-    fn execute_update_price(&mut self, op: UpdatePriceOp) -> Result<OperationResult> {
+    fn execute_savings_deposit(&mut self, op: SavingsDepositOp) -> Result<OperationResult> {
         self.verify_operation_signature(&op)?;
 
-        let previous_price = self.current_price;
-        let was_recovery_mode = self.recovery_mode;
+        if self.config.paused {
+            return Err(Error::ProtocolPaused);
+        }
 
-        // Update price
-        self.current_price = op.price_cents;
+        // Settle interest so the deposit buys shares at the current index
+        self.accrue_savings();
 
-        // Save price
-        self.state_manager.save_price(op.price_cents, self.timestamp)?;
-        self.state_manager.save_price_history(self.timestamp, op.price_cents)?;
+        // Burn tokens from depositor (transfer to pot)
+        let tx_hash = operation_hash(&op);
+        self.token.burn(op.depositor, op.amount, self.block_height, tx_hash)?;
 
-        // Check recovery mode
-        self.check_recovery_mode()?;
-        let recovery_mode_changed = was_recovery_mode != self.recovery_mode;
+        let balance = self.savings.deposit(op.depositor, op.amount)?;
 
         // Emit event
-        self.event_log.push(ProtocolEvent::PriceUpdated(PriceUpdatedEvent {
-            price_cents: op.price_cents,
-            previous_price,
-            source_count: op.source_count,
-            confidence: op.confidence,
+        self.event_log.push(ProtocolEvent::SavingsDeposit(SavingsDepositEvent {
+            depositor: op.depositor,
+            amount: op.amount,
+            new_balance: balance,
             block_height: self.block_height,
             timestamp: self.timestamp,
         }));
 
-        if recovery_mode_changed {
-            if self.recovery_mode {
-                self.event_log.push(ProtocolEvent::RecoveryModeEntered(RecoveryModeEvent {
-                    tcr: self.calculate_tcr()?,
-                    block_height: self.block_height,
-                    timestamp: self.timestamp,
-                }));
-            } else {
-                self.event_log.push(ProtocolEvent::RecoveryModeExited(RecoveryModeEvent {
-                    tcr: self.calculate_tcr()?,
-                    block_height: self.block_height,
-                    timestamp: self.timestamp,
-                }));
-            }
-        }
-
-        Ok(OperationResult::UpdatePrice(UpdatePriceResult {
-            previous_price,
-            new_price: op.price_cents,
-            recovery_mode_changed,
-        }))
+        Ok(OperationResult::SavingsDeposit(SavingsDepositResult { balance }))
     }
 
-    // ═══════════════════════════════════════════════════════════════════════════
-    // HELPER METHODS
-    // ═══════════════════════════════════════════════════════════════════════════
+    fn execute_savings_withdraw(&mut self, op: SavingsWithdrawOp) -> Result<OperationResult> {
+        self.verify_operation_signature(&op)?;
 
-    /// Verify operation signature
-    fn verify_operation_signature<O: Operation + Serialize>(&self, op: &O) -> Result<()> {
-        let data = bincode::serialize(op).map_err(|e| {
-            Error::Serialization(format!("Failed to serialize operation: {}", e))
-        })?;
-        let hash = Hash::sha256(&data);
+        self.accrue_savings();
 
-        if !verify_signature(op.signer(), &hash, op.signature()) {
-            return Err(Error::InvalidSignature);
-        }
+        let withdrawn = self.savings.withdraw(&op.depositor, op.amount)?;
 
-        Ok(())
-    }
+        // Mint tokens back to depositor
+        let tx_hash = operation_hash(&op);
+        self.token.mint(op.depositor, withdrawn, self.block_height, tx_hash)?;
 
-    /// Verify nonce
-    fn verify_nonce(&mut self, signer: &PublicKey, nonce: u64) -> Result<()> {
-        let key: [u8; 32] = *Hash::sha256(signer.as_bytes()).as_bytes();
-        let current = self.nonces.get(&key).copied().unwrap_or(0);
+        let remaining = self.savings.balance_of(&op.depositor);
 
-        if nonce <= current {
-            return Err(Error::InvalidParameter {
-                name: "nonce".into(),
-                reason: format!("Nonce {} already used, expected > {}", nonce, current),
-            });
-        }
+        // Emit event
+        self.event_log.push(ProtocolEvent::SavingsWithdraw(SavingsWithdrawEvent {
+            depositor: op.depositor,
+            amount: withdrawn,
+            remaining,
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
 
-        self.nonces.insert(key, nonce);
-        Ok(())
+        Ok(OperationResult::SavingsWithdraw(SavingsWithdrawResult { withdrawn, remaining }))
     }
 
-    /// Check and update recovery mode
-    fn check_recovery_mode(&mut self) -> Result<()> {
-        let tcr = self.calculate_tcr()?;
-        self.recovery_mode = tcr < self.config.params.critical_collateral_ratio;
+    fn execute_savings_accrue(&mut self, op: SavingsAccrueOp) -> Result<OperationResult> {
+        self.verify_operation_signature(&op)?;
+
+        let interest = self.accrue_savings();
+
+        // Only an accrual that moved the pot earns a stipend
+        let keeper_reward = if interest.is_zero() {
+            TokenAmount::ZERO
+        } else {
+            let tx_hash = operation_hash(&op);
+            self.pay_keeper(op.caller, "savings_accrue", tx_hash)?
+        };
+
+        Ok(OperationResult::SavingsAccrue(SavingsAccrueResult {
+            interest,
+            total_locked: self.savings.total_locked(),
+            keeper_reward,
+        }))
+    }
+
+    /// Accrue savings interest up to the current block
+    fn accrue_savings(&mut self) -> TokenAmount {
+        let interest = self.savings.accrue(self.block_height);
+        if !interest.is_zero() {
+            self.event_log.push(ProtocolEvent::SavingsAccrued(SavingsAccruedEvent {
+                interest,
+                rate_bps: self.savings.rate_bps(),
+                total_locked: self.savings.total_locked(),
+                reserve: self.savings.reserve(),
+                block_height: self.block_height,
+                timestamp: self.timestamp,
+            }));
+        }
+        interest
+    }
+
+    /// Set the savings rate (governance).
+    ///
+    /// Interest up to the current block accrues at the old rate.
+    pub fn set_savings_rate(&mut self, rate_bps: u64) -> Result<()> {
+        let old_rate_bps = self.savings.rate_bps();
+        self.accrue_savings();
+        self.savings.set_rate(rate_bps)?;
+
+        self.event_log.push(ProtocolEvent::SavingsRateChanged(SavingsRateChangedEvent {
+            old_rate_bps,
+            new_rate_bps: rate_bps,
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // TREASURY OPERATIONS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    fn execute_treasury_disburse(&mut self, op: TreasuryDisburseOp) -> Result<OperationResult> {
+        self.verify_operation_signature(&op)?;
+        self.treasury.authorize(&op.governor)?;
+
+        let tx_hash = operation_hash(&op);
+        match op.asset {
+            TreasuryAsset::ZkUSD(amount) => {
+                self.token.transfer(Treasury::account(), op.recipient, amount, self.block_height, tx_hash)?;
+            }
+            TreasuryAsset::Btc { source, amount } => {
+                self.vault.disburse_fee(source, amount)?;
+            }
+        }
+        self.treasury.record_disbursement(&op.asset);
+
+        // Emit event
+        self.event_log.push(ProtocolEvent::TreasuryDisbursed(TreasuryDisbursedEvent {
+            governor: op.governor,
+            recipient: op.recipient,
+            asset: op.asset,
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        Ok(OperationResult::TreasuryDisburse(TreasuryDisburseResult {
+            balance: self.treasury_balance(),
+            btc_fees: self.vault.total_fees(),
+        }))
+    }
+
+    /// Pay `keeper` the stipend for a maintenance operation from the
+    /// treasury account, within the per-block budget and the treasury's
+    /// balance. Returns the amount paid, which may be zero.
+    fn pay_keeper(&mut self, keeper: PublicKey, operation: &str, tx_hash: Hash) -> Result<TokenAmount> {
+        let params = &self.config.params;
+        let budget = params.max_keeper_rewards_per_block.saturating_sub(self.block_keeper_rewards);
+        let amount = params.keeper_reward.min(budget).min(self.treasury_balance().cents());
+        if amount == 0 {
+            return Ok(TokenAmount::ZERO);
+        }
+
+        let amount = TokenAmount::from_cents(amount);
+        self.token.transfer(Treasury::account(), keeper, amount, self.block_height, tx_hash)?;
+        self.treasury.record_disbursement(&TreasuryAsset::ZkUSD(amount));
+        self.block_keeper_rewards = self.block_keeper_rewards.saturating_add(amount.cents());
+
+        self.event_log.push(ProtocolEvent::KeeperRewarded(KeeperRewardedEvent {
+            keeper,
+            operation: operation.to_string(),
+            amount,
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        Ok(amount)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // AUCTION OPERATIONS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    fn execute_auction_bid(&mut self, op: AuctionBidOp) -> Result<OperationResult> {
+        self.verify_operation_signature(&op)?;
+
+        let take = self.auctions.take(op.auction_id, op.max_collateral, op.max_price, self.block_height)?;
+
+        // The bidder's zkUSD pays off the liquidated debt
+        let tx_hash = operation_hash(&op);
+        self.token.burn(op.bidder, take.cost, self.block_height, tx_hash)?;
+
+        // Emit event
+        self.event_log.push(ProtocolEvent::AuctionTaken(AuctionTakenEvent {
+            auction_id: op.auction_id,
+            bidder: op.bidder,
+            collateral: take.collateral,
+            cost: take.cost,
+            price: take.price,
+            closed: take.closed,
+            returned: take.returned,
+            bad_debt: take.bad_debt,
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        Ok(OperationResult::AuctionBid(AuctionBidResult {
+            collateral_received: take.collateral,
+            cost: take.cost,
+            price: take.price,
+            closed: take.closed,
+        }))
+    }
+
+    fn execute_auction_reset(&mut self, op: AuctionResetOp) -> Result<OperationResult> {
+        self.verify_operation_signature(&op)?;
+
+        let auction = self.auctions.reset(op.auction_id, self.current_price, self.block_height)?;
+        let (start_price, resets) = (auction.start_price, auction.resets);
+
+        // Emit event
+        self.event_log.push(ProtocolEvent::AuctionReset(AuctionResetEvent {
+            auction_id: op.auction_id,
+            start_price,
+            resets,
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        Ok(OperationResult::AuctionReset(AuctionResetResult { start_price }))
+    }
+
+    /// Enable liquidation auctions with `config`, or disable them with `None`
+    /// (governance)
+    pub fn set_auction_config(&mut self, config: Option<AuctionConfig>) -> Result<()> {
+        let old_config = self.auctions.config().copied();
+        self.auctions.set_config(config)?;
+
+        self.event_log.push(ProtocolEvent::ConfigChanged(ConfigChangedEvent {
+            parameter: "auction_config".to_string(),
+            old_value: format!("{:?}", old_config),
+            new_value: format!("{:?}", config),
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        Ok(())
+    }
+
+    /// Enable the peg stability controller with `config`, or disable it with
+    /// `None` (governance). Fees the controller has moved stay where they are.
+    pub fn set_peg_config(&mut self, config: Option<PegConfig>) -> Result<()> {
+        let old_config = self.peg.as_ref().map(|peg| *peg.config());
+        match (config, self.peg.as_mut()) {
+            (Some(config), Some(peg)) => peg.set_config(config)?,
+            (Some(config), None) => {
+                self.peg = Some(PegController::new(config)?);
+                if self.pair_rates.config(&TradingPair::zkusd_usd()).is_none() {
+                    self.pair_rates.add_pair(TradingPair::zkusd_usd(), PairConfig::default())?;
+                }
+            }
+            (None, _) => self.peg = None,
+        }
+
+        self.event_log.push(ProtocolEvent::ConfigChanged(ConfigChangedEvent {
+            parameter: "peg_config".to_string(),
+            old_value: format!("{:?}", old_config),
+            new_value: format!("{:?}", config),
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        Ok(())
+    }
+
+    /// Split a fee between the savings reserve and the treasury, returning
+    /// the treasury's share for the caller to pay into the treasury account.
+    ///
+    /// The savings share is not paid out as tokens: it is debt with no
+    /// tokens behind it until savers are paid interest.
+    fn route_fee(&mut self, source: FeeSource, fee: TokenAmount) -> Result<TokenAmount> {
+        let (to_savings, to_treasury) = self.treasury.route(source, fee)?;
+        self.savings.credit_fees(to_savings);
+        Ok(to_treasury)
+    }
+
+    /// Change a protocol parameter (governance), within its bounds and
+    /// per-update change limit
+    pub fn update_parameter(&mut self, parameter: ProtocolParameter, value: u64) -> Result<()> {
+        if self.ramps.get(parameter).is_some() {
+            return Err(Error::InvalidParameter {
+                name: format!("params.{}", parameter),
+                reason: "A ramp is scheduled for this parameter; cancel it first".into(),
+            });
+        }
+
+        let old_value = parameter.get(&self.config.params);
+        self.config.params = self.config.params.checked_update(parameter, value)?;
+        self.rebase_peg(parameter);
+
+        self.event_log.push(ProtocolEvent::ConfigChanged(ConfigChangedEvent {
+            parameter: parameter.to_string(),
+            old_value: old_value.to_string(),
+            new_value: value.to_string(),
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        // The MCR and CCR feed recovery mode
+        self.check_recovery_mode()
+    }
+
+    /// Schedule a linear change of `parameter` to `target_value` between
+    /// `start_block` and `end_block` (governance). The ramp starts at least
+    /// `RAMP_NOTICE_BLOCKS` from now and cannot move faster per block than a
+    /// single update.
+    pub fn schedule_parameter_ramp(
+        &mut self,
+        parameter: ProtocolParameter,
+        target_value: u64,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<()> {
+        let start_value = parameter.get(&self.config.params);
+        let ramp = ParameterRamp::new(parameter, start_value, target_value, start_block, end_block, self.block_height)?;
+        // The target has to fit the other parameters as they are now
+        self.config.params.ramped_update(parameter, target_value)?;
+        self.ramps.schedule(ramp)?;
+
+        self.push_ramp_changed(parameter, "none".to_string(), ramp.to_string());
+        Ok(())
+    }
+
+    /// Cancel the ramp of `parameter` (governance), leaving the parameter at
+    /// its current value
+    pub fn cancel_parameter_ramp(&mut self, parameter: ProtocolParameter) -> Result<ParameterRamp> {
+        let ramp = self.ramps.cancel(parameter).ok_or_else(|| Error::InvalidParameter {
+            name: format!("ramp:{}", parameter),
+            reason: "No ramp is scheduled for this parameter".into(),
+        })?;
+
+        self.push_ramp_changed(parameter, ramp.to_string(), "none".to_string());
+        Ok(ramp)
+    }
+
+    /// Scheduled and running parameter ramps
+    pub fn parameter_ramps(&self) -> &ParameterRamps {
+        &self.ramps
+    }
+
+    /// Apply this block's step of every running ramp
+    fn advance_ramps(&mut self) -> Result<()> {
+        let mut changed = false;
+        for step in self.ramps.advance(self.block_height) {
+            let ramp = step.ramp;
+            let event = ParameterRampEvent {
+                parameter: ramp.parameter,
+                start_value: ramp.start_value,
+                target_value: ramp.target_value,
+                start_block: ramp.start_block,
+                end_block: ramp.end_block,
+                block_height: self.block_height,
+                timestamp: self.timestamp,
+            };
+            if step.started {
+                tracing::info!(block = self.block_height, parameter = %ramp.parameter, %ramp, "Parameter ramp started");
+                self.event_log.push(ProtocolEvent::ParameterRampStarted(event.clone()));
+            }
+
+            if step.value != ramp.parameter.get(&self.config.params) {
+                match self.config.params.ramped_update(ramp.parameter, step.value) {
+                    Ok(params) => {
+                        self.config.params = params;
+                        self.rebase_peg(ramp.parameter);
+                        changed = true;
+                    }
+                    Err(e) => {
+                        // Other parameters moved since the ramp was scheduled
+                        tracing::warn!(
+                            block = self.block_height,
+                            parameter = %ramp.parameter,
+                            error = %e,
+                            "Parameter ramp halted"
+                        );
+                        self.ramps.cancel(ramp.parameter);
+                        self.push_ramp_changed(ramp.parameter, ramp.to_string(), "halted".to_string());
+                        continue;
+                    }
+                }
+            }
+
+            if step.completed {
+                tracing::info!(
+                    block = self.block_height,
+                    parameter = %ramp.parameter,
+                    value = step.value,
+                    "Parameter ramp completed"
+                );
+                self.event_log.push(ProtocolEvent::ParameterRampCompleted(event));
+            }
+        }
+
+        // The MCR and CCR feed recovery mode
+        if changed {
+            self.check_recovery_mode()?;
+        }
+        Ok(())
+    }
+
+    fn push_ramp_changed(&mut self, parameter: ProtocolParameter, old_value: String, new_value: String) {
+        self.event_log.push(ProtocolEvent::ConfigChanged(ConfigChangedEvent {
+            parameter: format!("ramp:{}", parameter),
+            old_value,
+            new_value,
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+    }
+
+    /// Return fees the peg controller has moved to a changed base value
+    fn rebase_peg(&mut self, parameter: ProtocolParameter) {
+        if matches!(parameter, ProtocolParameter::BorrowingFeeBps | ProtocolParameter::RedemptionFeeFloorBps) {
+            if let Some(peg) = &mut self.peg {
+                peg.rebase(&self.config.params);
+            }
+        }
+    }
+
+    /// Set the share of each fee credited to the savings reserve (governance)
+    pub fn set_treasury_savings_share(&mut self, share_bps: u64) -> Result<()> {
+        let old_share = self.treasury.savings_share_bps();
+        self.treasury.set_savings_share(share_bps)?;
+
+        self.event_log.push(ProtocolEvent::ConfigChanged(ConfigChangedEvent {
+            parameter: "savings_share_bps".to_string(),
+            old_value: old_share.to_string(),
+            new_value: share_bps.to_string(),
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
         Ok(())
     }
 
-    /// Calculate Total Collateralization Ratio
-    fn calculate_tcr(&self) -> Result<u64> {
-        let total_debt = self.total_debt();
-        if total_debt == 0 {
-            return Ok(u64::MAX);
-        }
-        calculate_collateral_ratio(
-            self.vault.total_collateral().sats(),
-            total_debt,
-            self.current_price,
-        )
+    /// Pause one kind of operation (governance or guardian)
+    pub fn pause_operation(&mut self, operation: PausableOperation) {
+        if self.pauses.pause(operation) {
+            self.push_pause_changed(operation, true);
+        }
+    }
+
+    /// Resume one kind of operation, also closing a tripped circuit breaker
+    /// (governance or guardian)
+    pub fn resume_operation(&mut self, operation: PausableOperation) {
+        if self.pauses.resume(operation) {
+            self.push_pause_changed(operation, false);
+        }
+    }
+
+    /// Count an anomaly against `operation`; enough of them in consecutive
+    /// blocks trip its circuit breaker and pause it for the cooldown
+    pub fn report_anomaly(&mut self, operation: PausableOperation, reason: &str) {
+        let Some(resumes_at) = self.pauses.record_anomaly(operation, self.timestamp) else {
+            return;
+        };
+
+        tracing::warn!(block = self.block_height, %operation, reason, resumes_at, "Circuit breaker tripped");
+        self.event_log.push(ProtocolEvent::CircuitBreakerTripped(CircuitBreakerTrippedEvent {
+            operation,
+            reason: reason.to_string(),
+            resumes_at,
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+    }
+
+    /// Operations paused by hand or by a tripped circuit breaker
+    pub fn paused_operations(&self) -> Vec<PausableOperation> {
+        self.pauses.paused(self.timestamp)
+    }
+
+    fn push_pause_changed(&mut self, operation: PausableOperation, paused: bool) {
+        self.event_log.push(ProtocolEvent::ConfigChanged(ConfigChangedEvent {
+            parameter: format!("paused:{}", operation),
+            old_value: (!paused).to_string(),
+            new_value: paused.to_string(),
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // STABILITY POOL OPERATIONS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    fn execute_sp_deposit(&mut self, op: StabilityDepositOp) -> Result<OperationResult> {
+        self.verify_operation_signature(&op)?;
+
+        // Burn tokens from depositor (transfer to pool)
+        let tx_hash = operation_hash(&op);
+        self.token.burn(op.depositor, op.amount, self.block_height, tx_hash)?;
+
+        // Only a new deposit takes the frontend tag
+        if let Some(frontend) = op.frontend {
+            if self.stability_pool.get_deposit(&op.depositor).is_none() {
+                self.frontends.tag(op.depositor, frontend)?;
+            }
+        }
+
+        // Deposit to stability pool
+        self.stability_pool.deposit(op.depositor, op.amount, self.block_height)?;
+
+        let new_total = self.stability_pool.get_current_value(&op.depositor);
+
+        // Emit event
+        self.event_log.push(ProtocolEvent::StabilityDeposit(StabilityDepositEvent {
+            depositor: op.depositor,
+            amount: op.amount,
+            new_total,
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        Ok(OperationResult::StabilityDeposit(StabilityDepositResult { new_total }))
+    }
+
+    fn execute_sp_withdraw(&mut self, op: StabilityWithdrawOp) -> Result<OperationResult> {
+        self.verify_operation_signature(&op)?;
+        self.ensure_not_paused(PausableOperation::StabilityWithdraw)?;
+
+        // Depositors may not leave ahead of a pending liquidation
+        if self.config.params.gate_sp_withdrawals && self.current_price > 0 {
+            let liquidatable = self.cdp_manager.count_liquidatable(self.current_price, self.config.effective_mcr());
+            if liquidatable > 0 {
+                return Err(Error::WithdrawalsBlocked { liquidatable });
+            }
+        }
+
+        // Withdraw from stability pool
+        let (withdrawn_amount, btc_claimed) = self.stability_pool.withdraw(&op.depositor, op.amount, self.block_height)?;
+        self.frontends.split_gains(&op.depositor, btc_claimed);
+        if self.stability_pool.get_deposit(&op.depositor).is_none() {
+            self.frontends.untag(&op.depositor);
+        }
+
+        // Mint tokens back to depositor
+        let tx_hash = operation_hash(&op);
+        self.token.mint(op.depositor, withdrawn_amount, self.block_height, tx_hash)?;
+
+        let remaining = self.stability_pool.get_current_value(&op.depositor);
+
+        // Emit event
+        self.event_log.push(ProtocolEvent::StabilityWithdraw(StabilityWithdrawEvent {
+            depositor: op.depositor,
+            amount: withdrawn_amount,
+            remaining,
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        Ok(OperationResult::StabilityWithdraw(StabilityWithdrawResult {
+            withdrawn: withdrawn_amount,
+            remaining,
+        }))
+    }
+
+    fn execute_claim_gains(&mut self, op: ClaimGainsOp) -> Result<OperationResult> {
+        self.verify_operation_signature(&op)?;
+
+        // Claim BTC gains, less the frontend's kickback
+        let btc_claimed = self.stability_pool.claim_btc(&op.depositor)?;
+        let btc_claimed = self.frontends.split_gains(&op.depositor, btc_claimed);
+
+        // Emit event
+        self.event_log.push(ProtocolEvent::GainsClaimed(GainsClaimedEvent {
+            depositor: op.depositor,
+            btc_amount: btc_claimed,
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        Ok(OperationResult::ClaimGains(ClaimGainsResult { btc_claimed }))
+    }
+
+    fn execute_register_frontend(&mut self, op: RegisterFrontendOp) -> Result<OperationResult> {
+        self.verify_operation_signature(&op)?;
+
+        self.frontends.register(op.frontend, op.kickback_bps, self.block_height)?;
+
+        // Emit event
+        self.event_log.push(ProtocolEvent::FrontendRegistered(FrontendRegisteredEvent {
+            frontend: op.frontend,
+            kickback_bps: op.kickback_bps,
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        Ok(OperationResult::RegisterFrontend(RegisterFrontendResult {
+            kickback_bps: op.kickback_bps,
+        }))
+    }
+
+    fn execute_claim_frontend_gains(&mut self, op: ClaimFrontendGainsOp) -> Result<OperationResult> {
+        self.verify_operation_signature(&op)?;
+
+        let btc_claimed = self.frontends.claim(&op.frontend)?;
+
+        // Emit event
+        self.event_log.push(ProtocolEvent::FrontendGainsClaimed(FrontendGainsClaimedEvent {
+            frontend: op.frontend,
+            btc_amount: btc_claimed,
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        Ok(OperationResult::ClaimFrontendGains(ClaimGainsResult { btc_claimed }))
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // REDEMPTION OPERATIONS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    fn execute_redeem(&mut self, op: RedeemOp) -> Result<OperationResult> {
+        self.verify_operation_signature(&op)?;
+        self.ensure_not_paused(PausableOperation::Redemption)?;
+
+        // Calculate fee
+        let fee_bps = self.config.redemption_fee(self.timestamp);
+        if fee_bps > op.max_fee_bps {
+            return Err(Error::InvalidParameter {
+                name: "fee".into(),
+                reason: format!("Fee {}bps exceeds max {}bps", fee_bps, op.max_fee_bps),
+            });
+        }
+
+        // Bound the volume redeemable in a single block
+        let cap = self.config.params.max_redemption_per_block;
+        if cap > 0 && self.block_redeemed.saturating_add(op.amount.cents()) > cap {
+            return Err(Error::RedemptionCapReached {
+                requested: op.amount.cents(),
+                remaining: cap.saturating_sub(self.block_redeemed),
+            });
+        }
+
+        let fee_amount = calculate_fee_bps(op.amount.cents(), fee_bps)?;
+        let net_redemption = op.amount.cents() - fee_amount;
+
+        // Redeemable CDPs sorted by ratio, ascending. Undercollateralized
+        // CDPs are left for liquidation.
+        let min_ratio = self.config.effective_mcr();
+        let redeemable: Vec<_> = self.cdp_manager
+            .get_sorted_by_ratio(self.current_price)
+            .into_iter()
+            .filter(|(cdp, ratio)| cdp.debt_cents > 0 && *ratio >= min_ratio)
+            .map(|(cdp, _)| cdp)
+            .collect();
+        let targets = Self::redemption_targets(&redeemable, &op)?;
+
+        let mut remaining = net_redemption;
+        let mut total_collateral = 0u64;
+        let mut cdps_affected = 0u32;
+        let mut cdp_updates: Vec<(CDPId, u64, u64)> = Vec::new();
+
+        for cdp in targets {
+            if remaining == 0 {
+                break;
+            }
+
+            let redeem_from_this = remaining.min(cdp.debt_cents);
+            let coll_to_take = safe_mul_div(
+                redeem_from_this,
+                crate::utils::constants::SATS_PER_BTC,
+                self.current_price,
+            )?;
+
+            cdp_updates.push((
+                cdp.id,
+                cdp.debt_cents.saturating_sub(redeem_from_this),
+                cdp.collateral_sats.saturating_sub(coll_to_take),
+            ));
+
+            remaining -= redeem_from_this;
+            total_collateral += coll_to_take;
+            cdps_affected += 1;
+        }
+
+        // Apply CDP updates
+        let debt_before = self.config.total_system_debt;
+        let tx_hash = operation_hash(&op);
+        for (id, new_debt, new_coll) in cdp_updates {
+            let cdp = self.cdp_manager.get_mut(&id)
+                .ok_or_else(|| Error::CDPNotFound(id.to_hex()))?;
+            let debt_redeemed = cdp.debt_cents - new_debt;
+            let coll_taken = cdp.collateral_sats - new_coll;
+            cdp.debt_cents = new_debt;
+            cdp.collateral_sats = new_coll;
+            // Status is computed from debt/collateral values automatically
+
+            // Redeemed collateral leaves the vault
+            if coll_taken > 0 {
+                self.vault.withdraw(id, CollateralAmount::from_sats(coll_taken), self.block_height, tx_hash)?;
+            }
+            self.config.remove_position(coll_taken, debt_redeemed);
+
+            let cdp = self.cdp_manager.get(&id)
+                .ok_or_else(|| Error::CDPNotFound(id.to_hex()))?;
+            self.state_manager.save_cdp(cdp)?;
+        }
+
+        let redeemed = op.amount.cents() - remaining;
+        self.block_redeemed = self.block_redeemed.saturating_add(redeemed);
+
+        // Route the fee, then burn the rest of the redeemed tokens
+        let to_treasury = self.route_fee(FeeSource::Redemption, TokenAmount::from_cents(fee_amount))?;
+        if !to_treasury.is_zero() {
+            self.token.transfer(op.redeemer, Treasury::account(), to_treasury, self.block_height, tx_hash)?;
+        }
+        let burned = TokenAmount::from_cents(redeemed).saturating_sub(to_treasury);
+        if !burned.is_zero() {
+            self.token.burn(op.redeemer, burned, self.block_height, tx_hash)?;
+        }
+
+        // Update base rate
+        self.config.fees.record_redemption(redeemed, debt_before, self.timestamp);
+
+        // Emit event
+        self.event_log.push(ProtocolEvent::Redemption(RedemptionEvent {
+            redeemer: op.redeemer,
+            zkusd_amount: TokenAmount::from_cents(redeemed),
+            collateral_received: CollateralAmount::from_sats(total_collateral),
+            fee: TokenAmount::from_cents(fee_amount),
+            cdps_affected,
+            btc_price: self.current_price,
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        Ok(OperationResult::Redeem(RedeemResult {
+            zkusd_redeemed: TokenAmount::from_cents(redeemed),
+            collateral_received: CollateralAmount::from_sats(total_collateral),
+            fee: TokenAmount::from_cents(fee_amount),
+            cdps_affected,
+        }))
+    }
+
+    /// Narrow the redeemable CDPs to the ones `op` may touch, checking its
+    /// hints against the sorted list
+    fn redemption_targets<'a>(redeemable: &'a [&'a CDP], op: &RedeemOp) -> Result<&'a [&'a CDP]> {
+        let position = |id: &CDPId, name: &str| {
+            redeemable.iter().position(|cdp| cdp.id == *id).ok_or_else(|| Error::InvalidParameter {
+                name: name.into(),
+                reason: format!("CDP {} is not redeemable", id.to_hex()),
+            })
+        };
+
+        if let Some(first) = &op.first_cdp_hint {
+            let index = position(first, "first_cdp_hint")?;
+            if index > 0 {
+                return Err(Error::InvalidParameter {
+                    name: "first_cdp_hint".into(),
+                    reason: format!("{} CDPs have a lower ratio", index),
+                });
+            }
+        }
+
+        let end = match &op.last_cdp_hint {
+            Some(last) => position(last, "last_cdp_hint")? + 1,
+            None => redeemable.len(),
+        };
+
+        let limit = crate::utils::constants::MAX_CDPS_PER_REDEMPTION;
+        let max_cdps = match op.max_cdps {
+            0 => limit,
+            n => n.min(limit),
+        } as usize;
+
+        Ok(&redeemable[..end.min(max_cdps)])
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // ORACLE OPERATIONS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    fn execute_update_price(&mut self, op: UpdatePriceOp) -> Result<OperationResult> {
+        self.verify_operation_signature(&op)?;
+        self.verify_price_attestation(&op)?;
+
+        let previous_price = self.current_price;
+        let was_recovery_mode = self.recovery_mode;
+
+        // Registered operators carry their registered weight
+        let weight = match &self.oracle_registry {
+            Some(registry) => registry.authorize(&op.operator)?,
+            None => self
+                .oracle_reputation
+                .get(&op.operator)
+                .copied()
+                .unwrap_or(DEFAULT_ORACLE_REPUTATION) as u64,
+        };
+
+        // Collect the update; until the block ends its price is the weighted
+        // median of the updates so far
+        let batch = self.price_batch.get_or_insert_with(|| PriceBatch::new(previous_price));
+        batch.submit(PriceSubmission {
+            operator: op.operator,
+            price_cents: op.price_cents,
+            source_count: op.source_count,
+            confidence: op.confidence,
+            confidence_interval: op.confidence_interval,
+            weight,
+        });
+        let updates_in_block = batch.len();
+        let median = batch
+            .median()
+            .cloned()
+            .ok_or_else(|| Error::Internal("Empty price batch".into()))?;
+
+        // Update price
+        self.current_price = median.price_cents;
+        self.price_interval = median.confidence_interval;
+        self.state_manager.save_price(median.price_cents, self.timestamp)?;
+
+        // Check recovery mode
+        self.check_recovery_mode()?;
+        let recovery_mode_changed = was_recovery_mode != self.recovery_mode;
+
+        // Oracle health may have changed
+        self.watchdog.record_price(self.block_height, op.source_count);
+
+        if recovery_mode_changed {
+            if self.recovery_mode {
+                self.event_log.push(ProtocolEvent::RecoveryModeEntered(RecoveryModeEvent {
+                    tcr: self.calculate_tcr()?,
+                    block_height: self.block_height,
+                    timestamp: self.timestamp,
+                }));
+            } else {
+                self.event_log.push(ProtocolEvent::RecoveryModeExited(RecoveryModeEvent {
+                    tcr: self.calculate_tcr()?,
+                    block_height: self.block_height,
+                    timestamp: self.timestamp,
+                }));
+            }
+        }
+
+        self.run_watchdog();
+
+        Ok(OperationResult::UpdatePrice(UpdatePriceResult {
+            previous_price,
+            new_price: median.price_cents,
+            recovery_mode_changed,
+            updates_in_block,
+        }))
+    }
+
+    fn execute_report_oracle_misbehavior(&mut self, op: ReportOracleMisbehaviorOp) -> Result<OperationResult> {
+        self.verify_operation_signature(&op)?;
+
+        let registry = self.oracle_registry.as_mut().ok_or_else(|| Error::InvalidParameter {
+            name: "operator".into(),
+            reason: "No oracle registry is configured".into(),
+        })?;
+        let slash = registry.report(&op.evidence, &self.signing_domain)?;
+
+        // Drop the operator's update from the block's median
+        let repriced = match self.price_batch.as_mut() {
+            Some(batch) => batch.remove(&slash.operator).then(|| {
+                batch
+                    .median()
+                    .map_or((batch.previous_price(), self.price_interval), |m| (m.price_cents, m.confidence_interval))
+            }),
+            None => None,
+        };
+        if let Some((price, interval)) = repriced {
+            self.current_price = price;
+            self.price_interval = interval;
+            self.state_manager.save_price(price, self.timestamp)?;
+        }
+
+        tracing::warn!(operator = %slash.operator.to_hex(), nonce = op.evidence.nonce(), "Oracle operator slashed");
+        self.event_log.push(ProtocolEvent::OracleSlashed(OracleSlashedEvent {
+            operator: slash.operator,
+            reporter: op.reporter,
+            nonce: op.evidence.nonce(),
+            prices: (op.evidence.first.price_cents, op.evidence.second.price_cents),
+            weight_slashed: slash.weight_slashed,
+            weight: slash.weight,
+            stake_slashed: slash.stake_slashed,
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        Ok(OperationResult::ReportOracleMisbehavior(ReportOracleMisbehaviorResult {
+            operator: slash.operator,
+            weight: slash.weight,
+            stake_slashed: slash.stake_slashed,
+        }))
+    }
+
+    /// Approve an oracle operator, or replace its weight and stake
+    /// (governance). The first approval starts enforcing the registry.
+    pub fn register_oracle(&mut self, operator: OracleOperator) -> Result<()> {
+        let registry = self.oracle_registry.get_or_insert_with(OracleRegistry::new);
+        let pubkey = operator.pubkey;
+        let replaced = registry.register(operator.clone())?;
+
+        self.event_log.push(ProtocolEvent::ConfigChanged(ConfigChangedEvent {
+            parameter: format!("oracle_operator:{}", pubkey.to_hex()),
+            old_value: format!("{:?}", replaced),
+            new_value: format!("{:?}", Some(operator)),
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        Ok(())
+    }
+
+    /// Withdraw an oracle operator's approval (governance)
+    pub fn remove_oracle(&mut self, pubkey: &PublicKey) -> Result<()> {
+        let registry = self.oracle_registry.as_mut().ok_or_else(|| Error::InvalidParameter {
+            name: "operator".into(),
+            reason: "No oracle registry is configured".into(),
+        })?;
+        let removed = registry.remove(pubkey)?;
+
+        self.event_log.push(ProtocolEvent::ConfigChanged(ConfigChangedEvent {
+            parameter: format!("oracle_operator:{}", pubkey.to_hex()),
+            old_value: format!("{:?}", Some(removed)),
+            new_value: format!("{:?}", None::<OracleOperator>),
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        Ok(())
+    }
+
+    /// Realized BTC volatility over settled prices
+    pub fn volatility(&self) -> &VolatilityEstimator {
+        &self.volatility
+    }
+
+    /// Detector of unusual operation patterns, if enabled
+    pub fn anomaly_detector(&self) -> Option<&AnomalyDetector> {
+        self.anomaly_detector.as_ref()
+    }
+
+    /// Head of the audit log of admin actions
+    pub fn audit_log(&self) -> &AuditLog {
+        &self.audit_log
+    }
+
+    /// Approved oracle operators, if a registry is configured
+    pub fn oracle_registry(&self) -> Option<&OracleRegistry> {
+        self.oracle_registry.as_ref()
+    }
+
+    /// Settle the block's price at the weighted median of its updates,
+    /// recording it in the price history with a single event
+    fn finalize_price(&mut self) -> Result<()> {
+        let Some(batch) = self.price_batch.take() else {
+            return Ok(());
+        };
+        let Some(median) = batch.median() else {
+            return Ok(());
+        };
+
+        self.state_manager.save_price_history(self.timestamp, median.price_cents)?;
+        self.volatility.observe(self.timestamp, median.price_cents);
+        self.event_log.push(ProtocolEvent::PriceUpdated(PriceUpdatedEvent {
+            price_cents: median.price_cents,
+            previous_price: batch.previous_price(),
+            source_count: median.source_count,
+            confidence: median.confidence,
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+        tracing::debug!(price = median.price_cents, updates = batch.len(), "Block price settled");
+
+        Ok(())
+    }
+
+    /// Check the attestation proof of a price update, if proofs are required
+    fn verify_price_attestation(&self, op: &UpdatePriceOp) -> Result<()> {
+        let Some(verifier) = &self.price_verifier else {
+            return Ok(());
+        };
+
+        let attestation = PriceAttestation::decode(&op.proof)?;
+        attestation.verify(op, self.config.params.max_price_deviation_bps, verifier.as_ref())?;
+
+        let age = self.timestamp.saturating_sub(attestation.public.timestamp);
+        if age > self.config.params.max_price_staleness_secs {
+            return Err(Error::StalePrice {
+                last_update: age,
+                max_age: self.config.params.max_price_staleness_secs,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn execute_update_pair_price(&mut self, op: UpdatePairPriceOp) -> Result<OperationResult> {
+        self.verify_operation_signature(&op)?;
+
+        // BTC/USD only moves through attested price updates
+        if op.pair == TradingPair::btc_usd() || op.pair == TradingPair::btc_usd().inverse() {
+            return Err(Error::InvalidParameter {
+                name: "pair".into(),
+                reason: "BTC/USD is updated with UpdatePrice".into(),
+            });
+        }
+
+        let previous_rate = self.pair_rates.rate(&op.pair).map_or(0, |rate| rate.rate);
+        self.pair_rates.update(&op.pair, PairRate::new(op.rate, self.timestamp, op.source_count))?;
+
+        self.event_log.push(ProtocolEvent::PairPriceUpdated(PairPriceUpdatedEvent {
+            pair: op.pair.to_string(),
+            rate: op.rate,
+            previous_rate,
+            source_count: op.source_count,
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        Ok(OperationResult::UpdatePairPrice(UpdatePairPriceResult {
+            previous_rate,
+            new_rate: op.rate,
+        }))
+    }
+
+    /// Let the peg controller step the fees from the zkUSD/USD price, when due
+    /// and the price is fresh
+    fn run_peg_controller(&mut self) {
+        let Some(peg) = self.peg.as_mut() else {
+            return;
+        };
+        if !peg.is_due(self.block_height) {
+            return;
+        }
+        let Ok(market_rate) = self.pair_rates.cross_rate(&Asset::ZkUsd, &Asset::Usd, self.timestamp) else {
+            return;
+        };
+        let Some(adjustment) = peg.step(market_rate, &self.config.params, self.block_height) else {
+            return;
+        };
+
+        let params = &mut self.config.params;
+        let old_borrowing_fee_bps = params.borrowing_fee_bps;
+        let old_redemption_fee_floor_bps = params.redemption_fee_floor_bps;
+        params.borrowing_fee_bps = adjustment.borrowing_fee_bps;
+        params.redemption_fee_floor_bps = adjustment.redemption_fee_floor_bps;
+
+        tracing::info!(
+            block = self.block_height,
+            deviation_bps = adjustment.deviation_bps,
+            borrowing_fee_bps = adjustment.borrowing_fee_bps,
+            redemption_fee_floor_bps = adjustment.redemption_fee_floor_bps,
+            "Peg controller adjusted fees"
+        );
+        self.event_log.push(ProtocolEvent::PegFeesAdjusted(PegFeesAdjustedEvent {
+            market_rate,
+            deviation_bps: adjustment.deviation_bps,
+            regime: adjustment.regime,
+            old_borrowing_fee_bps,
+            borrowing_fee_bps: adjustment.borrowing_fee_bps,
+            old_redemption_fee_floor_bps,
+            redemption_fee_floor_bps: adjustment.redemption_fee_floor_bps,
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+    }
+
+    /// Chain the block's admin actions onto the audit log, anchoring its
+    /// head on anchor blocks
+    fn record_audit_entries(&mut self) -> Result<()> {
+        let actions: Vec<ProtocolEvent> =
+            self.event_log.events().iter().filter(|event| is_audited(event)).cloned().collect();
+        for event in actions {
+            let entry = self.audit_log.append(event);
+            self.state_manager.save_audit_entry(&entry)?;
+        }
+
+        if let Some(anchor) = self.audit_log.anchor(self.block_height, self.timestamp) {
+            self.event_log.push(ProtocolEvent::AuditAnchored(anchor));
+        }
+        self.state_manager.save_audit_log(&self.audit_log)
+    }
+
+    /// Score the block's activity and report its anomalies
+    fn detect_anomalies(&mut self) {
+        let Some(detector) = &mut self.anomaly_detector else {
+            return;
+        };
+
+        let mut activity = BlockActivity {
+            unique_signers: self.block_ops.iter().map(|op| *op.signer()).collect::<HashSet<_>>().len() as u64,
+            operations: self.block_submitted,
+            failed_signatures: self.block_failed_signatures,
+            ..Default::default()
+        };
+        for event in self.event_log.events() {
+            activity.record(event);
+        }
+
+        for anomaly in detector.observe(activity) {
+            tracing::warn!(
+                block = self.block_height,
+                metric = %anomaly.metric,
+                value = anomaly.value,
+                mean = anomaly.mean,
+                z_score_bps = anomaly.z_score_bps,
+                "Unusual activity"
+            );
+            // Rejected operations never reach the block log, so peers
+            // replaying the block could not reproduce this one
+            if anomaly.metric == AnomalyMetric::FailedSignatureRate {
+                continue;
+            }
+            self.event_log.push(ProtocolEvent::AnomalyDetected(AnomalyDetectedEvent {
+                metric: anomaly.metric,
+                value: anomaly.value,
+                mean: anomaly.mean,
+                z_score_bps: anomaly.z_score_bps,
+                block_height: self.block_height,
+                timestamp: self.timestamp,
+            }));
+
+            let operation = match anomaly.metric {
+                AnomalyMetric::MintVolume => PausableOperation::Mint,
+                AnomalyMetric::WithdrawalVolume => PausableOperation::Withdraw,
+                _ => continue,
+            };
+            self.report_anomaly(operation, &format!("{} spike", anomaly.metric));
+        }
+    }
+
+    /// Pause or resume the protocol based on oracle health
+    fn run_watchdog(&mut self) {
+        let action = self.watchdog.check(self.block_height, self.config.paused);
+        let (paused, reason) = match action {
+            WatchdogAction::None => return,
+            WatchdogAction::Pause(reason) => {
+                tracing::warn!(block = self.block_height, %reason, "Oracle watchdog pausing protocol");
+                (true, reason.to_string())
+            }
+            WatchdogAction::Resume => {
+                tracing::info!(block = self.block_height, "Oracle healthy again, resuming protocol");
+                (false, "oracle healthy".to_string())
+            }
+        };
+
+        self.config.paused = paused;
+        self.event_log.push(ProtocolEvent::ConfigChanged(ConfigChangedEvent {
+            parameter: "paused".to_string(),
+            old_value: (!paused).to_string(),
+            new_value: paused.to_string(),
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        }));
+
+        let event = OracleSafetyEvent {
+            reason,
+            price_age_blocks: self.watchdog.price_age(self.block_height),
+            source_count: self.watchdog.last_source_count(),
+            block_height: self.block_height,
+            timestamp: self.timestamp,
+        };
+        self.event_log.push(if paused {
+            ProtocolEvent::OracleSafetyPaused(event)
+        } else {
+            ProtocolEvent::OracleSafetyResumed(event)
+        });
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // RECEIPTS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Record an operation admitted to the mempool as pending. An
+    /// operation that already has a receipt keeps it, unless it was
+    /// rejected and is being submitted again.
+    pub fn record_pending(&mut self, op: &ProtocolOperation) -> Result<Receipt> {
+        let id = op.hash()?;
+        if let Some(receipt) = self.state_manager.load_receipt(&id)? {
+            if receipt.status != ReceiptStatus::Rejected {
+                return Ok(receipt);
+            }
+        }
+
+        let receipt = Receipt::pending(op, self.timestamp)?;
+        self.store_receipt(&receipt)?;
+        self.state_manager.flush()?;
+        Ok(receipt)
+    }
+
+    /// Receipt of the operation with hash `id`
+    pub fn get_receipt(&self, id: &Hash) -> Result<Option<Receipt>> {
+        self.state_manager.load_receipt(id)
+    }
+
+    /// Receive every receipt as it changes
+    pub fn subscribe_receipts(&mut self) -> Receiver<Receipt> {
+        self.receipts.subscribe()
+    }
+
+    /// Record the proof of block `block_height`'s transitions, such as the
+    /// hash of its `ProofBatch`. Returns the number of receipts proven.
+    pub fn prove_receipts(&mut self, block_height: u64, proof_hash: Hash) -> Result<usize> {
+        let timestamp = self.timestamp;
+        self.update_block_receipts(block_height, ReceiptStatus::Included, |receipt| {
+            receipt.prove(proof_hash, timestamp).map(|_| true)
+        })
+    }
+
+    /// Record the Bitcoin transaction, as returned by the chain backend's
+    /// broadcast, that anchors block `block_height`'s proof. Returns the
+    /// number of receipts anchored.
+    pub fn anchor_receipts(&mut self, block_height: u64, txid: Txid) -> Result<usize> {
+        let timestamp = self.timestamp;
+        self.update_block_receipts(block_height, ReceiptStatus::Proven, |receipt| {
+            receipt.anchor(txid, timestamp).map(|_| true)
+        })
+    }
+
+    /// Record the confirmations of block `block_height`'s anchor
+    /// transaction. Returns the number of receipts finalized.
+    pub fn confirm_receipts(&mut self, block_height: u64, confirmations: u32) -> Result<usize> {
+        let timestamp = self.timestamp;
+        self.update_block_receipts(block_height, ReceiptStatus::Anchored, |receipt| {
+            receipt.confirm(confirmations, timestamp)
+        })
+    }
+
+    /// Apply `update` to the receipts of block `block_height` in status
+    /// `from`, counting those it reports as moved
+    fn update_block_receipts(
+        &mut self,
+        block_height: u64,
+        from: ReceiptStatus,
+        update: impl Fn(&mut Receipt) -> Result<bool>,
+    ) -> Result<usize> {
+        let mut moved = 0;
+        for id in self.state_manager.load_block_receipts(block_height)? {
+            let mut receipt = match self.state_manager.load_receipt(&id)? {
+                Some(receipt) if receipt.status == from => receipt,
+                _ => continue,
+            };
+            if update(&mut receipt)? {
+                moved += 1;
+            }
+            self.store_receipt(&receipt)?;
+        }
+        self.state_manager.flush()?;
+        Ok(moved)
+    }
+
+    /// Move the receipts of the block's operations to included, creating
+    /// them for operations that were never recorded as pending
+    fn include_receipts(&mut self) -> Result<()> {
+        let mut ids = Vec::with_capacity(self.block_ops.len());
+        for op in &self.block_ops {
+            let id = op.hash()?;
+            let mut receipt = match self.state_manager.load_receipt(&id)? {
+                Some(receipt) if matches!(receipt.status, ReceiptStatus::Pending | ReceiptStatus::Rejected) => receipt,
+                // Already included, e.g. when a block is executed again
+                Some(_) => continue,
+                None => Receipt::pending(op, self.timestamp)?,
+            };
+            receipt.include(self.block_height, self.timestamp)?;
+            self.state_manager.save_receipt(&receipt)?;
+            self.receipts.notify(&receipt);
+            ids.push(id);
+        }
+        self.index_receipts(ids)
+    }
+
+    /// Mark the pending receipt `id`, if any, rejected with `error`
+    fn reject_receipt(&mut self, id: &Hash, error: &Error) -> Result<()> {
+        let mut receipt = match self.state_manager.load_receipt(id)? {
+            Some(receipt) if receipt.status == ReceiptStatus::Pending => receipt,
+            _ => return Ok(()),
+        };
+        receipt.reject(self.block_height, error.to_string(), self.timestamp)?;
+        self.store_receipt(&receipt)?;
+        self.index_receipts(vec![*id])
+    }
+
+    /// Add receipts to the current block's index
+    fn index_receipts(&mut self, ids: Vec<Hash>) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let mut indexed = self.state_manager.load_block_receipts(self.block_height)?;
+        indexed.extend(ids);
+        self.state_manager.save_block_receipts(self.block_height, &indexed)
+    }
+
+    fn store_receipt(&mut self, receipt: &Receipt) -> Result<()> {
+        self.state_manager.save_receipt(receipt)?;
+        self.receipts.notify(receipt);
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // HELPER METHODS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Verify operation signature
+    ///
+    /// Signatures are over the typed signing payload in the machine's domain,
+    /// or over the legacy untyped hash while the legacy window is open.
+    /// Operations on a CDP with a multi-key owner policy are checked against
+    /// that policy instead: the signer's signature, if valid, and the
+    /// co-signatures, which must all be valid, count towards it.
+    fn verify_operation_signature<O: Operation + Clone + Serialize>(&self, op: &O) -> Result<()> {
+        if self.unsigned {
+            return Ok(());
+        }
+        let mut hashes = vec![op.signing_hash(&self.signing_domain)?];
+        if self.legacy_signatures_until.is_some_and(|until| self.block_height < until) {
+            hashes.push(op.legacy_signing_hash()?);
+        }
+        let verify = |signer: &PublicKey, signature: &Signature| {
+            hashes.iter().any(|hash| self.signatures.verify(signer, hash, signature))
+        };
+        let signer_valid = verify(op.signer(), op.signature());
+
+        let governing = op.governed_cdp()
+            .and_then(|id| self.cdp_manager.get(id))
+            .filter(|cdp| !cdp.policy.is_single());
+
+        let Some(cdp) = governing else {
+            return if signer_valid { Ok(()) } else { Err(Error::InvalidSignature) };
+        };
+
+        let mut signers = Vec::with_capacity(op.cosignatures().len() + 1);
+        if signer_valid {
+            signers.push(*op.signer());
+        }
+        for cosig in op.cosignatures() {
+            if !verify(&cosig.signer, &cosig.signature) {
+                return Err(Error::InvalidSignature);
+            }
+            signers.push(cosig.signer);
+        }
+
+        cdp.policy.authorize(&signers, self.block_height, cdp.last_owner_action)
+    }
+
+    /// Verify nonce: unused and not below the signer's window
+    fn verify_nonce(&mut self, signer: &PublicKey, nonce: u64) -> Result<()> {
+        self.nonces.accept(signer, nonce)
+    }
+
+    /// Reject `operation` while it is paused on its own
+    fn ensure_not_paused(&self, operation: PausableOperation) -> Result<()> {
+        if self.pauses.is_paused(operation, self.timestamp) {
+            return Err(Error::OperationPaused {
+                operation: operation.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Check and update recovery mode
+    fn check_recovery_mode(&mut self) -> Result<()> {
+        let tcr = self.calculate_tcr()?;
+        self.recovery_mode = tcr < self.config.params.critical_collateral_ratio;
+        Ok(())
+    }
+
+    /// Evaluate the invariants against the block's final state
+    ///
+    /// Under `Halt` a failure discards the block's writes, restores the
+    /// in-memory state from before the block and rejects the block. Under `Pause` each failure emits an `InvariantViolated` event
+    /// and the protocol is paused.
+    fn enforce_invariants(&mut self) -> Result<()> {
+        let violations = self.invariants.check(&InvariantContext {
+            cdps: &self.cdp_manager,
+            token: &self.token,
+            vault: &self.vault,
+            stability_pool: &self.stability_pool,
+            savings: &self.savings,
+            btc_price: self.current_price,
+            tcr: self.calculate_tcr().ok(),
+        });
+        if violations.is_empty() {
+            return Ok(());
+        }
+
+        for violation in &violations {
+            tracing::error!(
+                block = self.block_height,
+                invariant = %violation.invariant,
+                detail = %violation.detail,
+                "Invariant violated"
+            );
+        }
+
+        match self.invariants.enforcement() {
+            InvariantEnforcement::Halt => {
+                self.state_manager.discard_batch()?;
+                // Leave memory matching storage, as it was before the block
+                self.savepoint = None;
+                if let Some(start) = self.block_start.take() {
+                    self.restore(start.state);
+                    self.block_height = start.block_height;
+                    self.timestamp = start.timestamp;
+                    self.pauses = start.pauses;
+                    self.ramps = start.ramps;
+                    self.audit_log = start.audit_log;
+                }
+                let summary: Vec<String> = violations
+                    .iter()
+                    .map(|violation| format!("{}: {}", violation.invariant, violation.detail))
+                    .collect();
+                Err(Error::InvariantViolation(summary.join("; ")))
+            }
+            InvariantEnforcement::Pause => {
+                // Only governance may lift this pause
+                self.watchdog.release();
+                if !self.config.paused {
+                    self.config.paused = true;
+                    self.event_log.push(ProtocolEvent::ConfigChanged(ConfigChangedEvent {
+                        parameter: "paused".to_string(),
+                        old_value: false.to_string(),
+                        new_value: true.to_string(),
+                        block_height: self.block_height,
+                        timestamp: self.timestamp,
+                    }));
+                }
+                for violation in violations {
+                    self.event_log.push(ProtocolEvent::InvariantViolated(InvariantViolatedEvent {
+                        invariant: violation.invariant,
+                        detail: violation.detail,
+                        paused: true,
+                        block_height: self.block_height,
+                        timestamp: self.timestamp,
+                    }));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Calculate Total Collateralization Ratio
+    ///
+    /// Debt is what the CDPs owe, not the circulating supply: zkUSD held by
+    /// the stability pool or savings pot is still backed by CDP debt.
+    fn calculate_tcr(&self) -> Result<u64> {
+        let total_debt = self.cdp_manager.total_debt();
+        if total_debt == 0 {
+            return Ok(u64::MAX);
+        }
+        calculate_collateral_ratio(
+            self.vault.total_collateral().sats(),
+            self.current_price,
+            total_debt,
+        )
+    }
+
+    /// Get total system debt
+    fn total_debt(&self) -> u64 {
+        self.token.total_supply().cents()
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // QUERY METHODS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Get current BTC price
+    pub fn price(&self) -> u64 {
+        self.current_price
+    }
+
+    /// Get the non-BTC price pairs
+    pub fn price_pairs(&self) -> &CrossRateFeed {
+        &self.pair_rates
+    }
+
+    /// Get the peg stability controller, if enabled
+    pub fn peg(&self) -> Option<&PegController> {
+        self.peg.as_ref()
+    }
+
+    /// Get the half-width of the current price's confidence interval in cents
+    pub fn price_interval(&self) -> u64 {
+        self.price_interval
+    }
+
+    /// Price an operation with `usage` is checked against under the
+    /// confidence policy
+    fn price_for(&self, usage: PriceUsage) -> Result<u64> {
+        self.confidence_policy.price_for(self.current_price, self.price_interval, usage)
+    }
+
+    /// Get protocol configuration
+    pub fn config(&self) -> &ProtocolConfig {
+        &self.config
+    }
+
+    /// Get the total collateralization ratio
+    pub fn tcr(&self) -> Result<u64> {
+        self.calculate_tcr()
+    }
+
+    /// Check if in recovery mode
+    pub fn is_recovery_mode(&self) -> bool {
+        self.recovery_mode
+    }
+
+    /// Get the oracle watchdog
+    pub fn watchdog(&self) -> &OracleWatchdog {
+        &self.watchdog
+    }
+
+    /// Get current block height
+    pub fn block_height(&self) -> u64 {
+        self.block_height
+    }
+
+    /// Highest nonce `signer` used (0 if none)
+    pub fn nonce_of(&self, signer: &PublicKey) -> u64 {
+        self.nonces.highest(signer)
+    }
+
+    /// Next nonce expected from `signer`. Lower unused nonces in its
+    /// [`nonce_window`](Self::nonce_window) are accepted too.
+    pub fn next_nonce(&self, signer: &PublicKey) -> u64 {
+        self.nonces.next(signer)
+    }
+
+    /// Nonces `signer` used near its highest
+    pub fn nonce_window(&self, signer: &PublicKey) -> NonceWindow {
+        self.nonces.window(signer)
+    }
+
+    /// Get a CDP by ID
+    pub fn get_cdp(&self, id: &CDPId) -> Option<&CDP> {
+        self.cdp_manager.get(id)
+    }
+
+    /// Get a CDP as it was at the end of `block_height`.
+    ///
+    /// Requires [`with_cdp_history`](Self::with_cdp_history); returns `None`
+    /// for blocks before the CDP was opened or whose versions were pruned.
+    pub fn get_cdp_at(&self, id: &CDPId, block_height: u64) -> Result<Option<CDP>> {
+        if !self.state_manager.cdp_history_enabled() {
+            return Err(Error::Internal("CDP history is not enabled".into()));
+        }
+        self.state_manager.load_cdp_at(id, block_height)
+    }
+
+    /// Get token balance
+    pub fn balance(&self, account: &PublicKey) -> TokenAmount {
+        self.token.balance_of(account)
+    }
+
+    /// Get the allowance `spender` holds over `owner`'s tokens
+    pub fn allowance(&self, owner: &PublicKey, spender: &PublicKey) -> TokenAmount {
+        self.token.allowance(owner, spender)
+    }
+
+    /// Get stability pool deposit
+    pub fn stability_deposit(&self, depositor: &PublicKey) -> Option<TokenAmount> {
+        let value = self.stability_pool.get_current_value(depositor);
+        if value.is_zero() && self.stability_pool.get_deposit(depositor).is_none() {
+            None
+        } else {
+            Some(value)
+        }
+    }
+
+    /// Get the stability pool
+    pub fn stability_pool(&self) -> &StabilityPool {
+        &self.stability_pool
+    }
+
+    /// Get total supply
+    pub fn total_supply(&self) -> TokenAmount {
+        self.token.total_supply()
+    }
+
+    /// Get total collateral
+    pub fn total_collateral(&self) -> CollateralAmount {
+        self.vault.total_collateral()
+    }
+
+    /// Get the savings pot
+    pub fn savings(&self) -> &SavingsPot {
+        &self.savings
+    }
+
+    /// Get the treasury bookkeeping
+    pub fn treasury(&self) -> &Treasury {
+        &self.treasury
+    }
+
+    /// Get the stability pool frontends
+    pub fn frontends(&self) -> &FrontendRegistry {
+        &self.frontends
+    }
+
+    /// Get the liquidation auctions
+    pub fn auctions(&self) -> &AuctionHouse {
+        &self.auctions
+    }
+
+    /// Get the zkUSD held by the treasury
+    pub fn treasury_balance(&self) -> TokenAmount {
+        self.token.balance_of(&Treasury::account())
+    }
+
+    /// Get the BTC fees held in the vault's fee buckets
+    pub fn treasury_btc(&self) -> CollateralAmount {
+        self.vault.total_fees()
+    }
+
+    /// Get the most recent system risk snapshot, if one has been taken
+    pub fn risk_snapshot(&self) -> Option<&RiskSnapshot> {
+        self.risk_monitor.latest()
+    }
+
+    /// Get the accrued daily and per-epoch protocol statistics
+    pub fn aggregates(&self) -> &ProtocolAggregates {
+        &self.aggregates
+    }
+
+    /// Get a handle on the protocol metrics
+    pub fn metrics(&self) -> MetricsCollector {
+        self.metrics.clone()
+    }
+
+    /// Get a handle on the read view, refreshed at the end of each block.
+    /// Readers holding it never block the executor.
+    pub fn snapshot_view(&self) -> SnapshotHandle {
+        self.view.clone()
+    }
+
+    /// Get the bridge, if enabled
+    pub fn bridge(&self) -> Option<&Bridge> {
+        self.bridge.as_ref()
+    }
+
+    /// Get up to `limit` outbound bridge transfers starting at `from_sequence`
+    /// (persisted at the end of each block)
+    pub fn bridge_transfers(&self, from_sequence: u64, limit: usize) -> Result<Vec<BridgeOutEvent>> {
+        self.state_manager.load_bridge_transfers(from_sequence, limit)
+    }
+}
+
+fn bridge_disabled() -> Error {
+    Error::InvalidParameter {
+        name: "bridge".into(),
+        reason: "bridge is not enabled".into(),
+    }
+}
+
+/// Transaction hash of an applied operation
+fn operation_hash<T: Serialize>(op: &T) -> Hash {
+    canonical_hash(TX_DOMAIN, op).unwrap_or_default()
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// OPERATION RESULT
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Would-be outcome of an operation, from [`ProtocolStateMachine::preview`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationPreview {
+    /// Result the operation would return
+    pub result: OperationResult,
+    /// Borrowing and redemption fees it would charge
+    pub fees: TokenAmount,
+    /// Ratio of the affected CDP afterwards, if it is still open
+    pub cdp_ratio: Option<u64>,
+    /// Total collateralization ratio afterwards
+    pub tcr: u64,
+    /// Whether the protocol would be in recovery mode afterwards
+    pub recovery_mode: bool,
+    /// Events the operation would emit
+    pub events: Vec<ProtocolEvent>,
+}
+
+/// A parameter before and after a change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParameterDiff {
+    /// Changed parameter
+    pub parameter: ProtocolParameter,
+    /// Current value
+    pub old_value: u64,
+    /// Value after the change
+    pub new_value: u64,
+}
+
+/// Would-be impact of parameter changes, from
+/// [`ProtocolStateMachine::preview_parameter_changes`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterChangePreview {
+    /// Each change, in the order applied
+    pub changes: Vec<ParameterDiff>,
+    /// Open CDPs below the minimum ratio now
+    pub below_mcr_before: u64,
+    /// Open CDPs below the minimum ratio afterwards
+    pub below_mcr_after: u64,
+    /// CDPs the changes push below the minimum ratio, riskiest first
+    pub newly_below_mcr: Vec<CDPId>,
+    /// Total collateralization ratio, unchanged by parameters
+    pub tcr: Option<u64>,
+    /// Whether the protocol is in recovery mode now
+    pub recovery_mode_before: bool,
+    /// Whether the protocol would be in recovery mode afterwards
+    pub recovery_mode_after: bool,
+    /// Invariants the state fails
+    pub violations: Vec<InvariantViolation>,
+}
+
+impl ParameterChangePreview {
+    /// Apply `changes` in order on top of `params`, each within its
+    /// governance bounds, and assess them against the state in `ctx`
+    pub fn compute(
+        params: &ProtocolParams,
+        changes: &[(ProtocolParameter, u64)],
+        ctx: &InvariantContext<'_>,
+        invariants: &InvariantChecker,
+    ) -> Result<Self> {
+        let mut after = params.clone();
+        let mut diffs = Vec::with_capacity(changes.len());
+        for &(parameter, new_value) in changes {
+            let old_value = parameter.get(&after);
+            after = after.checked_update(parameter, new_value)?;
+            diffs.push(ParameterDiff { parameter, old_value, new_value });
+        }
+
+        let price = ctx.btc_price;
+        let before_mcr = params.min_collateral_ratio;
+        let after_mcr = after.min_collateral_ratio;
+        let mut newly_below: Vec<&CDP> = ctx
+            .cdps
+            .get_liquidatable(price, after_mcr)
+            .into_iter()
+            .filter(|cdp| !cdp.is_liquidatable(price, before_mcr))
+            .collect();
+        newly_below.sort_by_key(|cdp| (cdp.calculate_ratio(price), cdp.id.to_hex()));
+
+        let recovery_mode = |p: &ProtocolParams| ctx.tcr.is_some_and(|tcr| tcr < p.critical_collateral_ratio);
+        Ok(Self {
+            changes: diffs,
+            below_mcr_before: ctx.cdps.count_liquidatable(price, before_mcr),
+            below_mcr_after: ctx.cdps.count_liquidatable(price, after_mcr),
+            newly_below_mcr: newly_below.into_iter().map(|cdp| cdp.id).collect(),
+            tcr: ctx.tcr,
+            recovery_mode_before: recovery_mode(params),
+            recovery_mode_after: recovery_mode(&after),
+            violations: invariants.check(ctx),
+        })
+    }
+}
+
+/// Result of any protocol operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum OperationResult {
+    /// Open CDP result
+    OpenCDP(OpenCDPResult),
+    /// Deposit result
+    Deposit(DepositResult),
+    /// Withdraw result
+    Withdraw(WithdrawResult),
+    /// Mint result
+    Mint(MintResult),
+    /// Repay result
+    Repay(RepayResult),
+    /// Close result
+    Close(CloseResult),
+    /// Liquidate result
+    Liquidate(LiquidateResult),
+    /// Transfer result
+    Transfer(TransferResult),
+    /// Stability deposit result
+    StabilityDeposit(StabilityDepositResult),
+    /// Stability withdraw result
+    StabilityWithdraw(StabilityWithdrawResult),
+    /// Claim gains result
+    ClaimGains(ClaimGainsResult),
+    /// Redeem result
+    Redeem(RedeemResult),
+    /// Update price result
+    UpdatePrice(UpdatePriceResult),
+    /// Approve result
+    Approve(ApproveResult),
+    /// Transfer from result
+    TransferFrom(TransferFromResult),
+    /// Set owner policy result
+    SetOwnerPolicy(SetOwnerPolicyResult),
+    /// Bridge out result
+    BridgeOut(BridgeOutResult),
+    /// Bridge in result
+    BridgeIn(BridgeInResult),
+    /// Savings deposit result
+    SavingsDeposit(SavingsDepositResult),
+    /// Savings withdraw result
+    SavingsWithdraw(SavingsWithdrawResult),
+    /// Savings accrue result
+    SavingsAccrue(SavingsAccrueResult),
+    /// Treasury disburse result
+    TreasuryDisburse(TreasuryDisburseResult),
+    /// Auction bid result
+    AuctionBid(AuctionBidResult),
+    /// Auction reset result
+    AuctionReset(AuctionResetResult),
+    /// Frontend registration result
+    RegisterFrontend(RegisterFrontendResult),
+    /// Frontend kickback claim result
+    ClaimFrontendGains(ClaimGainsResult),
+    /// Pair price update result
+    UpdatePairPrice(UpdatePairPriceResult),
+    /// Session key update result
+    SetSessionKey(SetSessionKeyResult),
+    /// Session key operation result
+    SessionCall(SessionCallResult),
+    /// Oracle misbehavior report result
+    ReportOracleMisbehavior(ReportOracleMisbehaviorResult),
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::backend::InMemoryStore;
+
+    fn create_test_machine() -> ProtocolStateMachine<InMemoryStore> {
+        ProtocolStateMachine::new(InMemoryStore::new()).unwrap()
+    }
+
+    #[test]
+    fn test_state_machine_creation() {
+        let machine = create_test_machine();
+        assert_eq!(machine.price(), 0);
+        assert!(!machine.is_recovery_mode());
+    }
+
+    #[test]
+    fn test_begin_end_block() {
+        let mut machine = create_test_machine();
+
+        machine.begin_block(100, 1234567890).unwrap();
+        assert_eq!(machine.block_height(), 100);
+
+        let events = machine.end_block().unwrap();
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_block_writes_batched() {
+        use crate::storage::backend::{make_key, prefixes, StorageBackend};
+
+        let mut machine = create_test_machine();
+        let owner = *crate::utils::crypto::KeyPair::generate().public_key();
+        let cdp = CDP::with_collateral(owner, 100_000_000, 1, 100).unwrap();
+        let key = make_key(prefixes::CDP, cdp.id.as_bytes());
+
+        machine.begin_block(1, 100).unwrap();
+        machine.state_manager.save_cdp(&cdp).unwrap();
+        assert!(machine.state_manager.load_cdp(&cdp.id).unwrap().is_some());
+        assert!(!machine.state_manager.backend().exists(&key).unwrap());
+
+        machine.end_block().unwrap();
+        assert!(machine.state_manager.backend().exists(&key).unwrap());
+        assert_eq!(machine.state_manager.pending_writes().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_view_published_at_end_block() {
+        let mut machine = create_test_machine();
+        let view = machine.snapshot_view();
+        let owner = *crate::utils::crypto::KeyPair::generate().public_key();
+
+        machine.begin_block(1, 100).unwrap();
+        machine.current_price = 5_000_000;
+        machine.token.mint(owner, TokenAmount::from_cents(1000), 1, Hash::zero()).unwrap();
+        assert_eq!(view.load().balance(&owner).cents(), 0);
+
+        machine.end_block().unwrap();
+        let latest = view.load();
+        assert_eq!(latest.block_height, 1);
+        assert_eq!(latest.btc_price, 5_000_000);
+        assert_eq!(latest.balance(&owner).cents(), 1000);
+        assert_eq!(latest.total_supply.cents(), 1000);
+    }
+
+    #[test]
+    fn test_aggregates_recorded_at_end_block() {
+        let mut machine = create_test_machine();
+        let owner = *crate::utils::crypto::KeyPair::generate().public_key();
+
+        machine.begin_block(1, 100).unwrap();
+        machine.token.mint(owner, TokenAmount::from_cents(1000), 1, Hash::zero()).unwrap();
+        machine.end_block().unwrap();
+
+        let today = machine.aggregates().day(0).unwrap();
+        assert_eq!(today.closing_supply, 1000);
+        assert_eq!((today.first_block, today.last_block), (1, 1));
+
+        let stored = machine.state_manager.load_aggregates().unwrap().unwrap();
+        assert_eq!(&stored, machine.aggregates());
+    }
+
+    #[test]
+    fn test_invariant_enforcement() {
+        // Collateral registered without reaching the vault
+        let owner = *crate::utils::crypto::KeyPair::generate().public_key();
+        let cdp = CDP::with_collateral(owner, 100_000_000, 1, 1).unwrap();
+
+        let mut halting = create_test_machine().with_invariants(InvariantChecker::new(InvariantEnforcement::Halt));
+        halting.begin_block(1, 100).unwrap();
+        halting.cdp_manager.register(cdp.clone()).unwrap();
+        halting.token.mint(owner, TokenAmount::from_cents(1000), 1, Hash::zero()).unwrap();
+        halting.current_price = 5_000_000;
+        assert!(matches!(halting.end_block(), Err(Error::InvariantViolation(_))));
+        assert_eq!(halting.state_manager.pending_writes().unwrap(), 0);
+
+        // Memory is back to its state before the rejected block
+        assert_eq!(halting.cdp_manager.total_count(), 0);
+        assert_eq!(halting.total_supply().cents(), 0);
+        assert_eq!(halting.price(), 0);
+        assert_eq!(halting.block_height(), 0);
+        assert!(halting.block_ops.is_empty());
+        assert!(!halting.in_transaction());
+
+        let mut pausing = create_test_machine().with_invariants(InvariantChecker::new(InvariantEnforcement::Pause));
+        pausing.begin_block(1, 100).unwrap();
+        pausing.cdp_manager.register(cdp).unwrap();
+        let events = pausing.end_block().unwrap();
+        assert!(pausing.config().paused);
+        assert!(events.events().iter().any(|e| matches!(
+            e,
+            ProtocolEvent::InvariantViolated(v) if v.invariant == "vault_matches_collateral"
+        )));
+    }
+
+    #[test]
+    fn test_total_supply_and_collateral() {
+        let machine = create_test_machine();
+
+        assert_eq!(machine.total_supply().cents(), 0);
+        assert_eq!(machine.total_collateral().sats(), 0);
+    }
+
+    #[test]
+    fn test_rollback_restores_state() {
+        let mut machine = create_test_machine();
+        let owner = *crate::utils::crypto::KeyPair::generate().public_key();
+
+        machine.begin_transaction().unwrap();
+        assert!(machine.in_transaction());
+
+        machine.current_price = 5_000_000;
+        machine.token.mint(owner, TokenAmount::from_cents(1000), 1, Hash::zero()).unwrap();
+        let cdp = CDP::with_collateral(owner, 100_000_000, 1, 100).unwrap();
+        let cdp_id = cdp.id;
+        machine.cdp_manager.register(cdp.clone()).unwrap();
+        machine.state_manager.save_cdp(&cdp).unwrap();
+
+        machine.rollback().unwrap();
+
+        assert!(!machine.in_transaction());
+        assert_eq!(machine.price(), 0);
+        assert_eq!(machine.total_supply().cents(), 0);
+        assert!(machine.get_cdp(&cdp_id).is_none());
+        assert!(machine.state_manager.load_cdp(&cdp_id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_rollback_discards_cdp_versions() {
+        let mut machine = create_test_machine().with_cdp_history();
+        let owner = *crate::utils::crypto::KeyPair::generate().public_key();
+
+        let mut cdp = CDP::with_collateral(owner, 100_000_000, 1, 100).unwrap();
+        let cdp_id = cdp.id;
+        machine.cdp_manager.register(cdp.clone()).unwrap();
+        machine.state_manager.save_cdp(&cdp).unwrap();
+
+        machine.begin_transaction().unwrap();
+        cdp.deposit_collateral(50_000_000, 110).unwrap();
+        *machine.cdp_manager.get_mut(&cdp_id).unwrap() = cdp.clone();
+        machine.state_manager.save_cdp(&cdp).unwrap();
+        assert_eq!(machine.get_cdp_at(&cdp_id, 110).unwrap().unwrap().collateral_sats, 150_000_000);
+
+        machine.rollback().unwrap();
+
+        assert_eq!(machine.state_manager.cdp_version_heights(&cdp_id).unwrap(), vec![100]);
+        assert_eq!(machine.get_cdp_at(&cdp_id, 110).unwrap().unwrap().collateral_sats, 100_000_000);
+        assert!(machine.get_cdp_at(&cdp_id, 99).unwrap().is_none());
+        assert!(create_test_machine().get_cdp_at(&cdp_id, 110).is_err());
+    }
+
+    #[test]
+    fn test_rollback_discards_all_writes() {
+        use crate::storage::backend::{make_key, prefixes, StorageBackend};
+
+        let mut machine = create_test_machine();
+        let owner = *crate::utils::crypto::KeyPair::generate().public_key();
+        machine.begin_block(1, 1_000).unwrap();
+
+        let kept = TransactionRecord::new(TransactionType::OpenCDP, owner, 1, 1_000, 1);
+        machine.state_manager.save_transaction(&kept).unwrap();
+
+        machine.begin_transaction().unwrap();
+        let dropped = TransactionRecord::new(TransactionType::OpenCDP, owner, 2, 1_000, 1);
+        machine.state_manager.save_transaction(&dropped).unwrap();
+        machine.state_manager.save_price(5_000_000, 1_000).unwrap();
+        machine.rollback().unwrap();
+
+        assert!(machine.state_manager.load_transaction(&dropped.hash).unwrap().is_none());
+        assert!(machine.state_manager.load_transaction(&kept.hash).unwrap().is_some());
+
+        machine.begin_transaction().unwrap();
+        machine.state_manager.save_transaction(&dropped).unwrap();
+        machine.commit().unwrap();
+        machine.end_block().unwrap();
+
+        let key = make_key(prefixes::TX, dropped.hash.as_bytes());
+        assert!(machine.state_manager.backend().exists(&key).unwrap());
+    }
+
+    #[test]
+    fn test_commit_keeps_state() {
+        let mut machine = create_test_machine();
+
+        machine.begin_transaction().unwrap();
+        machine.current_price = 5_000_000;
+        machine.commit().unwrap();
+
+        assert_eq!(machine.price(), 5_000_000);
+        assert!(machine.commit().is_err());
+        assert!(machine.rollback().is_err());
+    }
+
+    #[test]
+    fn test_nested_transaction_rejected() {
+        let mut machine = create_test_machine();
+
+        machine.begin_transaction().unwrap();
+        assert!(machine.begin_transaction().is_err());
+    }
+
+    #[test]
+    fn test_watchdog_pauses_on_stale_price() {
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let mut machine = create_test_machine();
+        let oracle = KeyPair::generate();
+        let owner = KeyPair::generate();
+
+        let price_op = |nonce: u64| {
+            let mut op = UpdatePriceOp {
+                operator: *oracle.public_key(),
+                price_cents: 10_000_000,
+                source_count: 3,
+                confidence: 90,
+                confidence_interval: 0,
+                proof: Vec::new(),
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(&oracle, &SigningDomain::default()).unwrap();
+            ProtocolOperation::UpdatePrice(op)
+        };
+
+        machine.begin_block(100, 1_000).unwrap();
+        machine.execute(price_op(1)).unwrap();
+        assert!(!machine.config().paused);
+
+        // Seven blocks without an update
+        machine.begin_block(107, 5_200).unwrap();
+        assert!(machine.config().paused);
+        let events = machine.end_block().unwrap();
+        assert!(events.events().iter().any(|e| matches!(e, ProtocolEvent::OracleSafetyPaused(_))));
+
+        let mut mint = MintDebtOp {
+            cdp_id: CDPId::generate(owner.public_key(), 1),
+            owner: *owner.public_key(),
+            amount: TokenAmount::from_cents(1_000),
+            max_fee_bps: 100,
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            cosignatures: Vec::new(),
+        };
+        mint.sign(&owner, &SigningDomain::default()).unwrap();
+        assert!(matches!(
+            machine.execute(ProtocolOperation::MintDebt(mint)),
+            Err(Error::ProtocolPaused)
+        ));
+
+        // A fresh price lifts the pause
+        machine.execute(price_op(2)).unwrap();
+        assert!(!machine.config().paused);
+        assert!(machine.watchdog().tripped().is_none());
+    }
+
+    #[test]
+    fn test_operation_pauses_and_circuit_breakers() {
+        use crate::utils::constants::{BPS_DIVISOR, SIGNATURE_LENGTH};
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let mut machine = create_test_machine();
+        let owner = KeyPair::generate();
+        let mint = |nonce: u64| {
+            let mut op = MintDebtOp {
+                cdp_id: CDPId::generate(owner.public_key(), 1),
+                owner: *owner.public_key(),
+                amount: TokenAmount::from_cents(1_000),
+                max_fee_bps: 100,
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+                cosignatures: Vec::new(),
+            };
+            op.sign(&owner, &SigningDomain::default()).unwrap();
+            ProtocolOperation::MintDebt(op)
+        };
+        let redeem = |nonce: u64| {
+            let mut op = RedeemOp {
+                redeemer: *owner.public_key(),
+                amount: TokenAmount::from_cents(1_000),
+                max_fee_bps: BPS_DIVISOR,
+                first_cdp_hint: None,
+                last_cdp_hint: None,
+                max_cdps: 0,
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(&owner, &SigningDomain::default()).unwrap();
+            ProtocolOperation::Redeem(op)
+        };
+
+        machine.begin_block(100, 1_000).unwrap();
+        machine.pause_operation(PausableOperation::Mint);
+        assert!(matches!(machine.execute(mint(1)), Err(Error::OperationPaused { .. })));
+        assert!(!matches!(machine.execute(redeem(2)), Err(Error::OperationPaused { .. })));
+        machine.resume_operation(PausableOperation::Mint);
+        assert!(!matches!(machine.execute(mint(3)), Err(Error::OperationPaused { .. })));
+        machine.end_block().unwrap();
+
+        // Anomalies in three consecutive blocks trip the redemption breaker
+        for (i, height) in (101..104).enumerate() {
+            machine.begin_block(height, 1_600 + i as u64 * 600).unwrap();
+            machine.report_anomaly(PausableOperation::Redemption, "redemption spike");
+            let events = machine.end_block().unwrap();
+            let tripped = events.events().iter().any(|e| matches!(e, ProtocolEvent::CircuitBreakerTripped(_)));
+            assert_eq!(tripped, height == 103);
+        }
+        assert_eq!(machine.paused_operations(), vec![PausableOperation::Redemption]);
+
+        machine.begin_block(104, 3_400).unwrap();
+        assert!(matches!(machine.execute(redeem(4)), Err(Error::OperationPaused { .. })));
+        assert!(!matches!(machine.execute(mint(5)), Err(Error::OperationPaused { .. })));
+
+        // The pause ends with the cooldown
+        machine.end_block().unwrap();
+        machine.begin_block(110, 2_800 + 3_600).unwrap();
+        assert!(machine.paused_operations().is_empty());
+    }
+
+    #[test]
+    fn test_anomaly_detection() {
+        use crate::core::config::Network;
+        use crate::monitoring::AnomalyConfig;
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let price_op = |oracle: &KeyPair, nonce: u64, domain: SigningDomain| {
+            let mut op = UpdatePriceOp {
+                operator: *oracle.public_key(),
+                price_cents: 10_000_000,
+                source_count: 3,
+                confidence: 90,
+                confidence_interval: 0,
+                proof: Vec::new(),
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(oracle, &domain).unwrap();
+            ProtocolOperation::UpdatePrice(op)
+        };
+        let anomalies = |events: &EventLog| -> Vec<AnomalyMetric> {
+            events
+                .events()
+                .iter()
+                .filter_map(|e| match e {
+                    ProtocolEvent::AnomalyDetected(anomaly) => Some(anomaly.metric),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let config = AnomalyConfig { window_blocks: 10, min_blocks: 3, z_threshold_bps: 40_000 };
+        let mut machine = create_test_machine().with_anomaly_detector(AnomalyDetector::new(config));
+        let domain = *machine.signing_domain();
+        let oracle = KeyPair::generate();
+
+        for height in 100..103 {
+            machine.begin_block(height, height * 600).unwrap();
+            machine.execute(price_op(&oracle, height, domain)).unwrap();
+            assert!(anomalies(&machine.end_block().unwrap()).is_empty());
+        }
+
+        // Six new signers at once
+        machine.begin_block(103, 103 * 600).unwrap();
+        for _ in 0..6 {
+            machine.execute(price_op(&KeyPair::generate(), 1, domain)).unwrap();
+        }
+        assert_eq!(anomalies(&machine.end_block().unwrap()), vec![AnomalyMetric::UniqueSigners]);
+
+        // Forged signatures are flagged but stay out of the block's events
+        machine.begin_block(104, 104 * 600).unwrap();
+        for nonce in 200..203 {
+            let forged = price_op(&oracle, nonce, SigningDomain::new(Network::Testnet));
+            assert!(matches!(machine.execute(forged), Err(Error::InvalidSignature)));
+        }
+        machine.execute(price_op(&oracle, 203, domain)).unwrap();
+        assert!(anomalies(&machine.end_block().unwrap()).is_empty());
+
+        let last = *machine.anomaly_detector().unwrap().history().back().unwrap();
+        assert_eq!(last.metric(AnomalyMetric::FailedSignatureRate), 7_500);
+        assert_eq!(machine.anomaly_detector().unwrap().history().len(), 5);
+    }
+
+    #[test]
+    fn test_audit_log() {
+        use crate::protocol::audit::AUDIT_ANCHOR_INTERVAL;
+
+        let mut machine = create_test_machine();
+        let anchor_height = AUDIT_ANCHOR_INTERVAL * 2;
+
+        machine.begin_block(anchor_height - 1, 1_000).unwrap();
+        machine.pause_operation(PausableOperation::Mint);
+        machine.end_block().unwrap();
+
+        // The anchor block commits the head to its events
+        machine.begin_block(anchor_height, 1_600).unwrap();
+        machine.resume_operation(PausableOperation::Mint);
+        let events = machine.end_block().unwrap();
+        let anchor = events
+            .events()
+            .iter()
+            .find_map(|e| match e {
+                ProtocolEvent::AuditAnchored(anchor) => Some(anchor.clone()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(anchor.entries, 2);
+        assert_eq!(anchor.head, machine.audit_log().head());
+
+        let report = machine.state_manager.verify_audit_log().unwrap();
+        assert_eq!((report.entries, report.anchors), (2, 1));
+
+        // Rewriting the first action is detected
+        let mut entries = machine.state_manager.load_audit_entries(0, 1).unwrap();
+        if let ProtocolEvent::ConfigChanged(change) = &mut entries[0].event {
+            change.parameter = "paused:redemption".into();
+        }
+        machine.state_manager.save_audit_entry(&entries[0]).unwrap();
+        assert!(matches!(
+            machine.state_manager.verify_audit_log(),
+            Err(Error::AuditLogTampered { sequence: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_receipt_lifecycle() {
+        use std::str::FromStr;
+
+        use crate::protocol::receipts::FINALITY_CONFIRMATIONS;
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let mut machine = create_test_machine();
+        let listener = machine.subscribe_receipts();
+        let oracle = KeyPair::generate();
+        let mut price = UpdatePriceOp {
+            operator: *oracle.public_key(),
+            price_cents: 10_000_000,
+            source_count: 3,
+            confidence: 90,
+            confidence_interval: 0,
+            proof: Vec::new(),
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        price.sign(&oracle, &SigningDomain::default()).unwrap();
+        let price = ProtocolOperation::UpdatePrice(price);
+        let mut mint = MintDebtOp {
+            cdp_id: CDPId::generate(oracle.public_key(), 1),
+            owner: *oracle.public_key(),
+            amount: TokenAmount::from_cents(1_000),
+            max_fee_bps: 100,
+            nonce: 2,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            cosignatures: Vec::new(),
+        };
+        mint.sign(&oracle, &SigningDomain::default()).unwrap();
+        let mint = ProtocolOperation::MintDebt(mint);
+
+        machine.begin_block(1, 1_000).unwrap();
+        let id = machine.record_pending(&price).unwrap().id;
+        let missing = machine.record_pending(&mint).unwrap().id;
+        machine.execute(price).unwrap();
+        assert!(machine.execute(mint).is_err());
+        assert_eq!(machine.get_receipt(&id).unwrap().unwrap().status, ReceiptStatus::Pending);
+        machine.end_block().unwrap();
+
+        let receipt = machine.get_receipt(&id).unwrap().unwrap();
+        assert_eq!((receipt.status, receipt.block_height), (ReceiptStatus::Included, Some(1)));
+        let rejected = machine.get_receipt(&missing).unwrap().unwrap();
+        assert_eq!(rejected.status, ReceiptStatus::Rejected);
+        assert!(rejected.error.is_some());
+
+        // Later stages only move the block's included receipts, in order
+        let txid = Txid::from_str(&"ab".repeat(32)).unwrap();
+        assert_eq!(machine.anchor_receipts(1, txid).unwrap(), 0);
+        assert_eq!(machine.prove_receipts(1, Hash::sha256(b"batch")).unwrap(), 1);
+        assert_eq!(machine.anchor_receipts(1, txid).unwrap(), 1);
+        assert_eq!(machine.confirm_receipts(1, 2).unwrap(), 0);
+        assert_eq!(machine.confirm_receipts(1, FINALITY_CONFIRMATIONS).unwrap(), 1);
+
+        let receipt = machine.get_receipt(&id).unwrap().unwrap();
+        assert_eq!(receipt.status, ReceiptStatus::Finalized);
+        assert_eq!(receipt.anchor_txid, Some(txid));
+        let seen: Vec<_> = listener.try_iter().filter(|r| r.id == id).map(|r| r.status).collect();
+        assert_eq!(
+            seen,
+            [
+                ReceiptStatus::Pending,
+                ReceiptStatus::Included,
+                ReceiptStatus::Proven,
+                ReceiptStatus::Anchored,
+                ReceiptStatus::Anchored,
+                ReceiptStatus::Finalized,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parameter_ramp() {
+        use crate::protocol::ramps::RAMP_NOTICE_BLOCKS;
+
+        let mcr = ProtocolParameter::MinCollateralRatio;
+        let mut machine = create_test_machine();
+        let start = machine.config.params.min_collateral_ratio;
+        let (start_block, end_block) = (RAMP_NOTICE_BLOCKS + 1, RAMP_NOTICE_BLOCKS + 11);
+
+        machine.begin_block(1, 600).unwrap();
+        assert!(machine.schedule_parameter_ramp(mcr, start + 5, RAMP_NOTICE_BLOCKS, end_block).is_err());
+        machine.schedule_parameter_ramp(mcr, start + 5, start_block, end_block).unwrap();
+        assert!(machine.update_parameter(mcr, start + 1).is_err());
+        machine.end_block().unwrap();
+
+        let run_block = |machine: &mut ProtocolStateMachine<InMemoryStore>, height: u64| {
+            machine.begin_block(height, height * 600).unwrap();
+            let events = machine.end_block().unwrap();
+            let kinds: Vec<_> = events.events().iter().map(|e| e.event_type()).collect();
+            (machine.config.params.min_collateral_ratio, kinds)
+        };
+
+        assert_eq!(run_block(&mut machine, start_block - 1).0, start);
+        let (value, kinds) = run_block(&mut machine, start_block);
+        assert_eq!(value, start);
+        assert!(kinds.contains(&"ParameterRampStarted"));
+        assert_eq!(run_block(&mut machine, start_block + 5).0, start + 2);
+
+        let (value, kinds) = run_block(&mut machine, end_block);
+        assert_eq!(value, start + 5);
+        assert!(kinds.contains(&"ParameterRampCompleted"));
+        assert!(machine.parameter_ramps().is_empty());
+
+        // A cancelled ramp leaves the parameter where it was
+        machine.begin_block(end_block + 1, 100_000).unwrap();
+        machine.schedule_parameter_ramp(mcr, start, end_block + 200, end_block + 300).unwrap();
+        assert_eq!(machine.cancel_parameter_ramp(mcr).unwrap().target_value, start);
+        assert!(machine.cancel_parameter_ramp(mcr).is_err());
+        machine.update_parameter(mcr, start + 6).unwrap();
+        machine.end_block().unwrap();
+    }
+
+    #[test]
+    fn test_typed_signatures_and_legacy_window() {
+        use crate::core::config::Network;
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let oracle = KeyPair::generate();
+        let mainnet = SigningDomain::new(Network::Mainnet);
+        let price_op = |nonce: u64, sign: &dyn Fn(&mut UpdatePriceOp)| {
+            let mut op = UpdatePriceOp {
+                operator: *oracle.public_key(),
+                price_cents: 10_000_000,
+                source_count: 3,
+                confidence: 90,
+                confidence_interval: 0,
+                proof: Vec::new(),
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            sign(&mut op);
+            ProtocolOperation::UpdatePrice(op)
+        };
+        let typed = |domain: SigningDomain| {
+            let oracle = &oracle;
+            move |op: &mut UpdatePriceOp| op.sign(oracle, &domain).unwrap()
+        };
+        let legacy = |op: &mut UpdatePriceOp| op.signature = oracle.sign(&op.legacy_signing_hash().unwrap());
+
+        let mut machine = create_test_machine()
+            .with_signing_domain(mainnet)
+            .with_legacy_signatures_until(101);
+
+        machine.begin_block(100, 1_000).unwrap();
+        machine.execute(price_op(1, &typed(mainnet))).unwrap();
+        assert!(matches!(
+            machine.execute(price_op(2, &typed(SigningDomain::new(Network::Testnet)))),
+            Err(Error::InvalidSignature)
+        ));
+        machine.execute(price_op(2, &legacy)).unwrap();
+        machine.end_block().unwrap();
+
+        // The window has closed
+        machine.begin_block(101, 1_600).unwrap();
+        assert!(matches!(machine.execute(price_op(3, &legacy)), Err(Error::InvalidSignature)));
+        machine.execute(price_op(3, &typed(mainnet))).unwrap();
+    }
+
+    #[test]
+    fn test_signatures_bound_to_chain_id() {
+        use crate::core::config::Network;
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let config = ProtocolConfig::new(ProtocolParams::for_network(Network::Testnet)).with_chain_id(7);
+        let genesis = Genesis::new(Network::Testnet, config).with_genesis_time(1_000);
+        let mut machine = ProtocolStateMachine::from_genesis(InMemoryStore::new(), &genesis).unwrap();
+        let domain = SigningDomain::new(Network::Testnet).with_chain_id(7);
+        assert_eq!(machine.signing_domain(), &domain);
+
+        let oracle = KeyPair::generate();
+        let price_op = |nonce: u64, domain: SigningDomain| {
+            let mut op = UpdatePriceOp {
+                operator: *oracle.public_key(),
+                price_cents: 10_000_000,
+                source_count: 3,
+                confidence: 90,
+                confidence_interval: 0,
+                proof: Vec::new(),
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(&oracle, &domain).unwrap();
+            ProtocolOperation::UpdatePrice(op)
+        };
+
+        // Signed for another deployment of the same network
+        machine.begin_block(100, 1_000).unwrap();
+        assert!(matches!(
+            machine.execute(price_op(1, domain.with_chain_id(8))),
+            Err(Error::InvalidSignature)
+        ));
+        machine.execute(price_op(1, domain)).unwrap();
+    }
+
+    #[test]
+    fn test_price_attestation_required() {
+        use crate::oracle::attestation::PriceAttestor;
+        use crate::oracle::service::PriceUpdate;
+        use crate::oracle::sources::{Exchange, PriceSource, SourceCollection};
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+        use crate::zkp::prover::NativeProver;
+        use crate::zkp::verifier::NativeVerifier;
+
+        let mut machine = create_test_machine().with_price_attestation(NativeVerifier::new());
+        let oracle = KeyPair::generate();
+        let attestor = PriceAttestor::new(oracle.clone(), NativeProver::new());
+
+        let mut collection = SourceCollection::new(1_000);
+        for exchange in [Exchange::Binance, Exchange::Coinbase, Exchange::Kraken] {
+            collection.add(PriceSource::new(exchange, 10_000_000, 990));
+        }
+        let update = PriceUpdate::from_collection(&collection, 1).unwrap();
+        let attestation = attestor.attest(&collection, &update).unwrap();
+
+        let price_op = |proof: Vec<u8>, nonce: u64| {
+            let mut op = UpdatePriceOp {
+                operator: *oracle.public_key(),
+                price_cents: update.price_cents,
+                source_count: 3,
+                confidence: 90,
+                confidence_interval: 0,
+                proof,
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(&oracle, &SigningDomain::default()).unwrap();
+            ProtocolOperation::UpdatePrice(op)
+        };
+
+        machine.begin_block(100, 1_010).unwrap();
+        assert!(matches!(
+            machine.execute(price_op(Vec::new(), 1)),
+            Err(Error::InvalidPriceProof)
+        ));
+        machine.execute(price_op(attestation.encode().unwrap(), 1)).unwrap();
+        assert_eq!(machine.price(), 10_000_000);
+    }
+
+    #[test]
+    fn test_price_updates_settle_at_weighted_median() {
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let oracles: Vec<KeyPair> = (0..4).map(|_| KeyPair::generate()).collect();
+        let mut machine = create_test_machine().with_oracle_reputation(*oracles[3].public_key(), 0);
+        let price_op = |oracle: &KeyPair, price_cents: u64, nonce: u64| {
+            let mut op = UpdatePriceOp {
+                operator: *oracle.public_key(),
+                price_cents,
+                source_count: 3,
+                confidence: 90,
+                confidence_interval: 0,
+                proof: Vec::new(),
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(oracle, &SigningDomain::default()).unwrap();
+            ProtocolOperation::UpdatePrice(op)
+        };
+
+        machine.begin_block(100, 1_000).unwrap();
+        machine.execute(price_op(&oracles[0], 10_000_000, 1)).unwrap();
+        machine.execute(price_op(&oracles[1], 10_100_000, 1)).unwrap();
+        machine.execute(price_op(&oracles[2], 90_000_000, 1)).unwrap();
+        // Without reputation an outlier carries no weight
+        machine.execute(price_op(&oracles[3], 1_000, 1)).unwrap();
+        // A second update replaces the operator's first
+        let result = machine.execute(price_op(&oracles[0], 10_200_000, 2)).unwrap();
+        match result {
+            OperationResult::UpdatePrice(r) => {
+                assert_eq!(r.new_price, 10_200_000);
+                assert_eq!(r.updates_in_block, 4);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let events = machine.end_block().unwrap();
+        let prices: Vec<_> = events
+            .events()
+            .iter()
+            .filter_map(|e| match e {
+                ProtocolEvent::PriceUpdated(e) => Some((e.previous_price, e.price_cents)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(prices, [(0, 10_200_000)]);
+        assert_eq!(machine.price(), 10_200_000);
+
+        // The next block starts from the settled price
+        machine.begin_block(101, 1_600).unwrap();
+        machine.execute(price_op(&oracles[1], 10_300_000, 2)).unwrap();
+        let events = machine.end_block().unwrap();
+        assert!(events.events().iter().any(|e| matches!(
+            e,
+            ProtocolEvent::PriceUpdated(e) if e.previous_price == 10_200_000 && e.price_cents == 10_300_000
+        )));
+
+        // Each settled price after the first is a volatility sample
+        assert_eq!(machine.volatility().samples(), 1);
+    }
+
+    #[test]
+    fn test_oracle_registry_and_slashing() {
+        use crate::oracle::registry::Equivocation;
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let honest = KeyPair::generate();
+        let rogue = KeyPair::generate();
+        let reporter = KeyPair::generate();
+        let mut machine = create_test_machine();
+        machine.register_oracle(OracleOperator::new(*honest.public_key(), 40)).unwrap();
+        machine
+            .register_oracle(OracleOperator::new(*rogue.public_key(), 60).with_stake(CollateralAmount::from_sats(1_000_000)))
+            .unwrap();
+
+        let update = |oracle: &KeyPair, price_cents: u64, nonce: u64| {
+            let mut op = UpdatePriceOp {
+                operator: *oracle.public_key(),
+                price_cents,
+                source_count: 3,
+                confidence: 90,
+                confidence_interval: 0,
+                proof: Vec::new(),
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(oracle, &SigningDomain::default()).unwrap();
+            op
+        };
+
+        // Only registered operators may update the price
+        machine.begin_block(100, 1_000).unwrap();
+        let stranger = KeyPair::generate();
+        assert!(matches!(
+            machine.execute(ProtocolOperation::UpdatePrice(update(&stranger, 10_000_000, 1))),
+            Err(Error::Unauthorized(_))
+        ));
+
+        machine.execute(ProtocolOperation::UpdatePrice(update(&honest, 10_000_000, 1))).unwrap();
+        let published = update(&rogue, 12_000_000, 1);
+        machine.execute(ProtocolOperation::UpdatePrice(published.clone())).unwrap();
+        assert_eq!(machine.price(), 12_000_000);
+
+        // The rogue operator signed another price under the same nonce
+        let mut report = ReportOracleMisbehaviorOp {
+            reporter: *reporter.public_key(),
+            evidence: Equivocation { first: published, second: update(&rogue, 8_000_000, 1) },
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        report.sign(&reporter, &SigningDomain::default()).unwrap();
+        match machine.execute(ProtocolOperation::ReportOracleMisbehavior(report.clone())).unwrap() {
+            OperationResult::ReportOracleMisbehavior(r) => {
+                assert_eq!(r.weight, 0);
+                assert_eq!(r.stake_slashed, CollateralAmount::from_sats(500_000));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Its update no longer counts, and it cannot publish another
+        assert_eq!(machine.price(), 10_000_000);
+        assert!(matches!(
+            machine.execute(ProtocolOperation::UpdatePrice(update(&rogue, 12_000_000, 2))),
+            Err(Error::Unauthorized(_))
+        ));
+        report.nonce = 2;
+        report.sign(&reporter, &SigningDomain::default()).unwrap();
+        assert!(machine.execute(ProtocolOperation::ReportOracleMisbehavior(report)).is_err());
+
+        let events = machine.end_block().unwrap();
+        assert!(events.events().iter().any(|e| matches!(
+            e,
+            ProtocolEvent::OracleSlashed(e) if e.operator == *rogue.public_key() && e.prices == (12_000_000, 8_000_000)
+        )));
+        assert!(events.events().iter().any(|e| matches!(e, ProtocolEvent::PriceUpdated(e) if e.price_cents == 10_000_000)));
+
+        // Governance can withdraw an approval
+        machine.remove_oracle(rogue.public_key()).unwrap();
+        assert!(machine.oracle_registry().unwrap().get(rogue.public_key()).is_none());
+    }
+
+    #[test]
+    fn test_per_account_rate_limit() {
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let mut machine = create_test_machine();
+        machine.config.params = machine.config.params.clone().with_rate_limit(1, 10);
+
+        let alice = KeyPair::generate();
+        let bob = *KeyPair::generate().public_key();
+        machine
+            .token
+            .mint(*alice.public_key(), TokenAmount::from_cents(1_000), 1, Hash::zero())
+            .unwrap();
+
+        let transfer = |cents: u64, nonce: u64| {
+            let mut op = TransferOp {
+                from: *alice.public_key(),
+                to: bob,
+                amount: TokenAmount::from_cents(cents),
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(&alice, &SigningDomain::default()).unwrap();
+            ProtocolOperation::Transfer(op)
+        };
+
+        machine.begin_block(100, 1_000).unwrap();
+
+        // Failed operations do not use up the allowance
+        assert!(machine.execute(transfer(5_000, 1)).is_err());
+        machine.execute(transfer(100, 1)).unwrap();
+
+        assert!(matches!(
+            machine.execute(transfer(100, 2)),
+            Err(Error::RateLimitExceeded { limit: 1, window_blocks: 10 })
+        ));
+
+        machine.begin_block(110, 7_000).unwrap();
+        machine.execute(transfer(100, 2)).unwrap();
+        assert_eq!(machine.balance(&bob).cents(), 200);
+    }
+
+    #[test]
+    fn test_approve_and_transfer_from() {
+        use crate::core::token::ApprovePermit;
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let mut machine = create_test_machine();
+        let alice = KeyPair::generate();
+        let bot = KeyPair::generate();
+        let carol = *KeyPair::generate().public_key();
+        machine
+            .token
+            .mint(*alice.public_key(), TokenAmount::from_cents(1_000), 1, Hash::zero())
+            .unwrap();
+
+        let mut approve = ApproveOp {
+            owner: *alice.public_key(),
+            spender: *bot.public_key(),
+            amount: TokenAmount::from_cents(600),
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        approve.sign(&alice, &SigningDomain::default()).unwrap();
+        machine.execute(ProtocolOperation::Approve(approve)).unwrap();
+
+        let pull = |cents: u64, nonce: u64| {
+            let mut op = TransferFromOp {
+                spender: *bot.public_key(),
+                from: *alice.public_key(),
+                to: carol,
+                amount: TokenAmount::from_cents(cents),
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(&bot, &SigningDomain::default()).unwrap();
+            ProtocolOperation::TransferFrom(op)
+        };
+
+        match machine.execute(pull(400, 1)).unwrap() {
+            OperationResult::TransferFrom(result) => {
+                assert_eq!(result.from_balance.cents(), 600);
+                assert_eq!(result.to_balance.cents(), 400);
+                assert_eq!(result.remaining_allowance.cents(), 200);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert!(matches!(
+            machine.execute(pull(300, 2)),
+            Err(Error::InsufficientAllowance { required: 300, available: 200 })
+        ));
+        assert_eq!(machine.allowance(alice.public_key(), bot.public_key()).cents(), 200);
+
+        // Alice tops up the allowance off-chain; the bot relays the permit
+        let permit = ApprovePermit::new(
+            *alice.public_key(),
+            *bot.public_key(),
+            TokenAmount::from_cents(500),
+            0,
+            machine.block_height() + 10,
+        )
+        .sign(&alice);
+        let mut relay = PermitOp {
+            submitter: *bot.public_key(),
+            permit,
+            nonce: 2,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        relay.sign(&bot, &SigningDomain::default()).unwrap();
+        machine.execute(ProtocolOperation::Permit(relay)).unwrap();
+
+        machine.execute(pull(300, 3)).unwrap();
+        assert_eq!(machine.balance(&carol).cents(), 700);
+    }
+
+    #[test]
+    fn test_multisig_owner_policy() {
+        use crate::core::cdp::OwnerPolicy;
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let mut machine = create_test_machine();
+        let [alice, bob, carol, dave] = [(); 4].map(|_| KeyPair::generate());
+
+        let cdp = CDP::new(*alice.public_key(), 1, 0);
+        let cdp_id = cdp.id;
+        machine.cdp_manager.register(cdp).unwrap();
+        machine.begin_block(10, 6_000).unwrap();
+
+        let set_policy = |policy: OwnerPolicy, nonce: u64| SetOwnerPolicyOp {
+            cdp_id,
+            owner: *alice.public_key(),
+            policy,
+            nonce,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            cosignatures: Vec::new(),
+        };
+
+        let multisig = OwnerPolicy::MultiSig {
+            threshold: 2,
+            keys: vec![*alice.public_key(), *bob.public_key(), *carol.public_key()],
+        };
+        let mut op = set_policy(multisig, 1);
+        op.sign(&alice, &SigningDomain::default()).unwrap();
+        machine.execute(ProtocolOperation::SetOwnerPolicy(op)).unwrap();
+
+        let recovery = OwnerPolicy::Recovery {
+            primary: *alice.public_key(),
+            recovery: *dave.public_key(),
+            timelock_blocks: 100,
+        };
+
+        // Alice alone no longer suffices
+        let mut op = set_policy(recovery.clone(), 2);
+        op.sign(&alice, &SigningDomain::default()).unwrap();
+        assert!(matches!(
+            machine.execute(ProtocolOperation::SetOwnerPolicy(op.clone())),
+            Err(Error::Unauthorized(_))
+        ));
+
+        // Bob and Carol reach the threshold without Alice's signature
+        op.signature = Signature::new([0u8; SIGNATURE_LENGTH]);
+        op.cosign(&bob, &SigningDomain::default()).unwrap();
+        op.cosign(&carol, &SigningDomain::default()).unwrap();
+        machine.execute(ProtocolOperation::SetOwnerPolicy(op)).unwrap();
+        assert_eq!(machine.get_cdp(&cdp_id).unwrap().policy, recovery);
+
+        // The recovery key only works once Alice has been idle long enough
+        let mut op = set_policy(OwnerPolicy::Single(*alice.public_key()), 3);
+        op.cosign(&dave, &SigningDomain::default()).unwrap();
+        assert!(machine.execute(ProtocolOperation::SetOwnerPolicy(op.clone())).is_err());
+
+        machine.begin_block(110, 66_000).unwrap();
+        machine.execute(ProtocolOperation::SetOwnerPolicy(op)).unwrap();
+        assert!(machine.get_cdp(&cdp_id).unwrap().policy.is_single());
+    }
+
+    #[test]
+    fn test_session_keys() {
+        use crate::core::cdp::{SessionGrant, SessionScope};
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let mut machine = create_test_machine().with_watchdog(WatchdogConfig {
+            enabled: false,
+            ..Default::default()
+        });
+        machine.current_price = 10_000_000; // $100,000
+        machine.begin_block(1, 1_000).unwrap();
+
+        let owner = KeyPair::generate();
+        let bot = KeyPair::generate();
+        let mut open = OpenCDPOp {
+            owner: *owner.public_key(),
+            collateral: CollateralAmount::from_sats(100_000_000),
+            initial_debt: Some(TokenAmount::from_cents(5_000_000)),
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        open.sign(&owner, &SigningDomain::default()).unwrap();
+        let cdp_id = match machine.execute(ProtocolOperation::OpenCDP(open)).unwrap() {
+            OperationResult::OpenCDP(result) => result.cdp_id,
+            other => panic!("unexpected result: {:?}", other),
+        };
+
+        let set_session = |grant: Option<SessionGrant>, nonce: u64| {
+            let mut op = SetSessionKeyOp {
+                cdp_id,
+                owner: *owner.public_key(),
+                key: *bot.public_key(),
+                grant,
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+                cosignatures: Vec::new(),
+            };
+            op.sign(&owner, &SigningDomain::default()).unwrap();
+            ProtocolOperation::SetSessionKey(op)
+        };
+        let call = |action: SessionAction, nonce: u64| {
+            let mut op = SessionCallOp {
+                cdp_id,
+                session_key: *bot.public_key(),
+                action,
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(&bot, &SigningDomain::default()).unwrap();
+            ProtocolOperation::SessionCall(op)
+        };
+        let top_up = |amount_sats: u64| SessionAction::Deposit { amount_sats };
+
+        // Nothing is accepted from the bot before the owner authorizes it
+        assert!(matches!(
+            machine.execute(call(top_up(10_000_000), 1)),
+            Err(Error::Unauthorized(_))
+        ));
+
+        let grant = SessionGrant {
+            scope: SessionScope::TopUp { below_ratio: 180 },
+            spend_cap: 20_000_000,
+            expires_at: 100,
+        };
+        machine.execute(set_session(Some(grant), 2)).unwrap();
+
+        // Top-ups only while the CDP is below the grant's ratio
+        assert!(machine.execute(call(top_up(10_000_000), 1)).is_err());
+        machine.current_price = 8_000_000; // 160%
+        match machine.execute(call(top_up(10_000_000), 1)).unwrap() {
+            OperationResult::SessionCall(result) => {
+                assert_eq!(result.remaining_cap, 10_000_000);
+                assert_eq!(result.new_ratio, 176);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(machine.get_cdp(&cdp_id).unwrap().collateral_sats, 110_000_000);
+
+        // Spend cap and scope
+        assert!(machine.execute(call(top_up(15_000_000), 2)).is_err());
+        let repay = SessionAction::Repay { amount_cents: 10_000 };
+        assert!(machine.execute(call(repay, 2)).is_err());
+
+        // Revoked keys are rejected
+        machine.execute(set_session(None, 3)).unwrap();
+        assert!(matches!(
+            machine.execute(call(top_up(1_000_000), 2)),
+            Err(Error::Unauthorized(_))
+        ));
+    }
+
+    #[test]
+    fn test_open_cdp_enforces_mcr() {
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let mut machine = create_test_machine();
+        let owner = KeyPair::generate();
+        machine.current_price = 10_000_000; // $100,000
+
+        let open = |debt_cents: u64, nonce: u64| {
+            let mut op = OpenCDPOp {
+                owner: *owner.public_key(),
+                collateral: CollateralAmount::from_sats(100_000_000),
+                initial_debt: Some(TokenAmount::from_cents(debt_cents)),
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(&owner, &SigningDomain::default()).unwrap();
+            ProtocolOperation::OpenCDP(op)
+        };
+
+        // 1 BTC against $95,000 is below the 110% MCR
+        assert!(matches!(
+            machine.execute(open(9_500_000, 1)),
+            Err(Error::CollateralizationRatioTooLow { current: 105, .. })
+        ));
+
+        match machine.execute(open(5_000_000, 2)).unwrap() {
+            OperationResult::OpenCDP(result) => assert_eq!(result.ratio, 200),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_withdraw_mint_and_tcr_use_price_not_debt() {
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        // The ratio was once computed with debt and price swapped, which
+        // accepted or rejected operations at the wrong thresholds
+        let owner = KeyPair::generate();
+        let mut machine = create_test_machine();
+        machine.current_price = 10_000_000; // $100,000
+
+        let mut open = OpenCDPOp {
+            owner: *owner.public_key(),
+            collateral: CollateralAmount::from_sats(100_000_000),
+            initial_debt: Some(TokenAmount::from_cents(5_000_000)),
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        open.sign(&owner, &SigningDomain::default()).unwrap();
+        let cdp_id = match machine.execute(ProtocolOperation::OpenCDP(open)).unwrap() {
+            OperationResult::OpenCDP(result) => result.cdp_id,
+            other => panic!("unexpected result: {:?}", other),
+        };
+        assert_eq!(machine.calculate_tcr().unwrap(), 200);
+
+        let withdraw = |sats: u64, nonce: u64| {
+            let mut op = WithdrawCollateralOp {
+                cdp_id,
+                owner: *owner.public_key(),
+                amount: CollateralAmount::from_sats(sats),
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+                cosignatures: Vec::new(),
+            };
+            op.sign(&owner, &SigningDomain::default()).unwrap();
+            ProtocolOperation::WithdrawCollateral(op)
+        };
+
+        // 0.5 BTC left against $50,000 is 100%
+        assert!(matches!(
+            machine.execute(withdraw(50_000_000, 2)),
+            Err(Error::WithdrawalWouldUndercollateralize)
+        ));
+        // 0.6 BTC left is 120%
+        machine.execute(withdraw(40_000_000, 2)).unwrap();
+        assert_eq!(machine.calculate_tcr().unwrap(), 120);
+
+        let mint = |cents: u64, nonce: u64| {
+            let mut op = MintDebtOp {
+                cdp_id,
+                owner: *owner.public_key(),
+                amount: TokenAmount::from_cents(cents),
+                max_fee_bps: 10_000,
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+                cosignatures: Vec::new(),
+            };
+            op.sign(&owner, &SigningDomain::default()).unwrap();
+            ProtocolOperation::MintDebt(op)
+        };
+
+        // $60,000 of debt against $60,000 of collateral is 100%
+        assert!(matches!(
+            machine.execute(mint(1_000_000, 3)),
+            Err(Error::CollateralizationRatioTooLow { current: 100, .. })
+        ));
+        // $54,000 is 111%, below the 150% the system requires in recovery mode
+        assert!(matches!(
+            machine.execute(mint(400_000, 3)),
+            Err(Error::CollateralizationRatioTooLow { current: 111, minimum: 150 })
+        ));
+        assert_eq!(machine.calculate_tcr().unwrap(), 120);
+    }
+
+    #[test]
+    fn test_mint_records_block_height() {
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        // Minting once stamped the CDP with the block timestamp, which put
+        // its history version thousands of years past the chain tip
+        let owner = KeyPair::generate();
+        let mut machine = create_test_machine().with_cdp_history();
+        machine.current_price = 10_000_000; // $100,000
+        machine.block_height = 10;
+        machine.timestamp = 1_700_000_000;
+
+        let mut open = OpenCDPOp {
+            owner: *owner.public_key(),
+            collateral: CollateralAmount::from_sats(100_000_000),
+            initial_debt: Some(TokenAmount::from_cents(2_000_000)),
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        open.sign(&owner, &SigningDomain::default()).unwrap();
+        let cdp_id = match machine.execute(ProtocolOperation::OpenCDP(open)).unwrap() {
+            OperationResult::OpenCDP(result) => result.cdp_id,
+            other => panic!("unexpected result: {:?}", other),
+        };
+
+        machine.block_height = 20;
+        machine.timestamp = 1_700_006_000;
+        let mut mint = MintDebtOp {
+            cdp_id,
+            owner: *owner.public_key(),
+            amount: TokenAmount::from_cents(1_000_000),
+            max_fee_bps: 10_000,
+            nonce: 2,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            cosignatures: Vec::new(),
+        };
+        mint.sign(&owner, &SigningDomain::default()).unwrap();
+        machine.execute(ProtocolOperation::MintDebt(mint)).unwrap();
+
+        let cdp = machine.get_cdp(&cdp_id).unwrap();
+        assert_eq!(cdp.last_updated, 20);
+        assert_eq!(cdp.last_owner_action, 20);
+        assert_eq!(machine.state_manager.cdp_version_heights(&cdp_id).unwrap(), vec![10, 20]);
+        assert_eq!(machine.get_cdp_at(&cdp_id, 25).unwrap().unwrap().debt_cents, cdp.debt_cents);
     }
 
-    /// Get total system debt
-    fn total_debt(&self) -> u64 {
-        self.token.total_supply().cents()
+    #[test]
+    fn test_preview_parameter_changes() {
+        use crate::core::config::ProtocolParameter;
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let mut machine = create_test_machine();
+        let owner = KeyPair::generate();
+        machine.current_price = 10_000_000; // $100,000
+
+        let mut open = |btc: u64, debt_cents: u64, nonce: u64| {
+            let mut op = OpenCDPOp {
+                owner: *owner.public_key(),
+                collateral: CollateralAmount::from_sats(btc * 100_000_000),
+                initial_debt: Some(TokenAmount::from_cents(debt_cents)),
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(&owner, &SigningDomain::default()).unwrap();
+            match machine.execute(ProtocolOperation::OpenCDP(op)).unwrap() {
+                OperationResult::OpenCDP(result) => result.cdp_id,
+                other => panic!("unexpected result: {:?}", other),
+            }
+        };
+        open(3, 5_000_000, 1);
+        let thin = open(1, 8_500_000, 2);
+
+        // Raising the MCR to 120% leaves the thin CDP under it
+        let preview = machine
+            .preview_parameter_changes(&[(ProtocolParameter::MinCollateralRatio, 120)])
+            .unwrap();
+        assert_eq!(preview.changes[0].old_value, 110);
+        assert_eq!((preview.below_mcr_before, preview.below_mcr_after), (0, 1));
+        assert_eq!(preview.newly_below_mcr, vec![thin]);
+        assert!(!preview.recovery_mode_after);
+        assert!(preview.violations.is_empty());
+
+        // Nothing was applied, and out-of-bounds changes are refused
+        assert_eq!(machine.config().params.min_collateral_ratio, 110);
+        assert!(machine
+            .preview_parameter_changes(&[(ProtocolParameter::MinCollateralRatio, 200)])
+            .is_err());
     }
 
-    // ═══════════════════════════════════════════════════════════════════════════
-    // QUERY METHODS
-    // ═══════════════════════════════════════════════════════════════════════════
+    #[test]
+    fn test_bridge_round_trip() {
+        use crate::charms::bridge::{Attestation, InboundTransfer, RelayerSet};
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        const CHAIN: u32 = 10;
+        let alice = KeyPair::generate();
+        let relayers = [(); 3].map(|_| KeyPair::generate());
+        let set = RelayerSet::new(relayers.iter().map(|k| *k.public_key()).collect(), 2).unwrap();
+        let mut machine = create_test_machine().with_bridge(Bridge::new(set).with_chain(CHAIN));
+        machine
+            .token
+            .mint(*alice.public_key(), TokenAmount::from_cents(10_000), 1, Hash::zero())
+            .unwrap();
+        machine.begin_block(1, 600).unwrap();
+
+        let mut out = BridgeOutOp {
+            sender: *alice.public_key(),
+            amount: TokenAmount::from_cents(4_000),
+            dest_chain: CHAIN,
+            dest_address: b"0xa11ce".to_vec(),
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        out.sign(&alice, &SigningDomain::default()).unwrap();
+        match machine.execute(ProtocolOperation::BridgeOut(out)).unwrap() {
+            OperationResult::BridgeOut(result) => {
+                assert_eq!(result.sequence, 1);
+                assert_eq!(result.locked.cents(), 4_000);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(machine.balance(&Bridge::escrow_account()).cents(), 4_000);
+        assert_eq!(machine.total_supply().cents(), 10_000);
+
+        // Relayers find the transfer once the block is persisted
+        machine.end_block().unwrap();
+        let transfers = machine.bridge_transfers(1, 10).unwrap();
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].dest_address, b"0xa11ce".to_vec());
+
+        // Alice burns 1,500 on the other chain and the relayers attest it
+        machine.begin_block(2, 1_200).unwrap();
+        let transfer = InboundTransfer {
+            source_chain: CHAIN,
+            source_tx: Hash::sha256(b"burn-1"),
+            recipient: *alice.public_key(),
+            amount: TokenAmount::from_cents(1_500),
+        };
+        let release = |attesters: &[KeyPair], nonce: u64| {
+            let mut op = BridgeInOp {
+                submitter: *relayers[0].public_key(),
+                transfer: transfer.clone(),
+                attestations: attesters.iter().map(|k| Attestation::sign(&transfer, k)).collect(),
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(&relayers[0], &SigningDomain::default()).unwrap();
+            ProtocolOperation::BridgeIn(op)
+        };
 
-    /// Get current BTC price
-    pub fn price(&self) -> u64 {
-        self.current_price
+        assert!(matches!(machine.execute(release(&relayers[..1], 1)), Err(Error::Unauthorized(_))));
+        match machine.execute(release(&relayers[1..], 2)).unwrap() {
+            OperationResult::BridgeIn(result) => {
+                assert_eq!(result.recipient_balance.cents(), 7_500);
+                assert_eq!(result.locked.cents(), 2_500);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // The same burn cannot be released twice
+        assert!(machine.execute(release(&relayers, 3)).is_err());
+        assert_eq!(machine.balance(&Bridge::escrow_account()).cents(), 2_500);
     }
 
-    /// Get protocol configuration
-    pub fn config(&self) -> &ProtocolConfig {
-        &self.config
+    #[test]
+    fn test_savings_earns_fee_funded_interest() {
+        use crate::core::savings::BLOCKS_PER_YEAR;
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let alice = KeyPair::generate();
+        let mut machine = create_test_machine();
+        machine
+            .token
+            .mint(*alice.public_key(), TokenAmount::from_cents(100_000), 1, Hash::zero())
+            .unwrap();
+        machine.savings.credit_fees(TokenAmount::from_cents(50_000));
+        machine.begin_block(1, 600).unwrap();
+        machine.set_savings_rate(500).unwrap();
+
+        let mut deposit = SavingsDepositOp {
+            depositor: *alice.public_key(),
+            amount: TokenAmount::from_cents(100_000),
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        deposit.sign(&alice, &SigningDomain::default()).unwrap();
+        machine.execute(ProtocolOperation::SavingsDeposit(deposit)).unwrap();
+        assert!(machine.balance(alice.public_key()).is_zero());
+        assert_eq!(machine.savings().total_locked().cents(), 100_000);
+
+        // A year later, 5% has accrued out of the fee reserve
+        machine.begin_block(1 + BLOCKS_PER_YEAR, 600 * (1 + BLOCKS_PER_YEAR)).unwrap();
+        let mut withdraw = SavingsWithdrawOp {
+            depositor: *alice.public_key(),
+            amount: TokenAmount::ZERO,
+            nonce: 2,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        withdraw.sign(&alice, &SigningDomain::default()).unwrap();
+        match machine.execute(ProtocolOperation::SavingsWithdraw(withdraw)).unwrap() {
+            OperationResult::SavingsWithdraw(result) => {
+                assert!(result.withdrawn.cents() >= 104_999 && result.withdrawn.cents() <= 105_000);
+                assert!(result.remaining.is_zero());
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(
+            machine.savings().reserve().cents() + machine.balance(alice.public_key()).cents(),
+            150_000
+        );
+        assert!(machine.set_savings_rate(crate::core::savings::MAX_SAVINGS_RATE_BPS + 1).is_err());
     }
 
-    /// Check if in recovery mode
-    pub fn is_recovery_mode(&self) -> bool {
-        self.recovery_mode
+    #[test]
+    fn test_fees_routed_to_treasury() {
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let owner = KeyPair::generate();
+        let governor = KeyPair::generate();
+        let mut machine = create_test_machine().with_treasury_governor(*governor.public_key());
+        machine.current_price = 10_000_000; // $100,000
+
+        let mut open = OpenCDPOp {
+            owner: *owner.public_key(),
+            collateral: CollateralAmount::from_sats(100_000_000),
+            initial_debt: None,
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        open.sign(&owner, &SigningDomain::default()).unwrap();
+        let cdp_id = match machine.execute(ProtocolOperation::OpenCDP(open)).unwrap() {
+            OperationResult::OpenCDP(result) => result.cdp_id,
+            other => panic!("unexpected result: {:?}", other),
+        };
+
+        let mut mint = MintDebtOp {
+            cdp_id,
+            owner: *owner.public_key(),
+            amount: TokenAmount::from_cents(1_000_000),
+            max_fee_bps: 100,
+            nonce: 2,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            cosignatures: Vec::new(),
+        };
+        mint.sign(&owner, &SigningDomain::default()).unwrap();
+        machine.execute(ProtocolOperation::MintDebt(mint)).unwrap();
+
+        // The 0.5% fee is split between the treasury and the savings reserve
+        assert_eq!(machine.treasury().collected(FeeSource::Borrowing).cents(), 5_000);
+        assert_eq!(machine.treasury_balance().cents(), 2_500);
+        assert_eq!(machine.savings().reserve().cents(), 2_500);
+        assert_eq!(machine.total_supply().cents() + machine.savings().reserve().cents(), 1_000_000);
+
+        let domain = *machine.signing_domain();
+        let disburse = |signer: &KeyPair, nonce: u64| {
+            let mut op = TreasuryDisburseOp {
+                governor: *signer.public_key(),
+                recipient: *owner.public_key(),
+                asset: TreasuryAsset::ZkUSD(TokenAmount::from_cents(1_000)),
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(signer, &domain).unwrap();
+            ProtocolOperation::TreasuryDisburse(op)
+        };
+
+        assert!(matches!(machine.execute(disburse(&owner, 3)), Err(Error::Unauthorized(_))));
+        match machine.execute(disburse(&governor, 1)).unwrap() {
+            OperationResult::TreasuryDisburse(result) => assert_eq!(result.balance.cents(), 1_500),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(machine.treasury().disbursed().cents(), 1_000);
+        assert_eq!(machine.balance(owner.public_key()).cents(), 996_000);
     }
 
-    /// Get current block height
-    pub fn block_height(&self) -> u64 {
-        self.block_height
+    #[test]
+    fn test_redemption_hints_and_block_cap() {
+        use crate::utils::constants::{BPS_DIVISOR, SIGNATURE_LENGTH};
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let owner = KeyPair::generate();
+        let mut machine = create_test_machine();
+        machine.current_price = 10_000_000; // $100,000
+        machine.config.params.max_redemption_per_block = 1_000_000; // $10,000
+
+        let mut open = |debt_cents: u64, nonce: u64| {
+            let mut op = OpenCDPOp {
+                owner: *owner.public_key(),
+                collateral: CollateralAmount::from_sats(100_000_000),
+                initial_debt: Some(TokenAmount::from_cents(debt_cents)),
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(&owner, &SigningDomain::default()).unwrap();
+            match machine.execute(ProtocolOperation::OpenCDP(op)).unwrap() {
+                OperationResult::OpenCDP(result) => result.cdp_id,
+                other => panic!("unexpected result: {:?}", other),
+            }
+        };
+        let riskiest = open(5_000_000, 1);
+        let safest = open(2_000_000, 2);
+
+        let redeem = |amount: u64, first: Option<CDPId>, last: Option<CDPId>, nonce: u64| {
+            let mut op = RedeemOp {
+                redeemer: *owner.public_key(),
+                amount: TokenAmount::from_cents(amount),
+                max_fee_bps: BPS_DIVISOR,
+                first_cdp_hint: first,
+                last_cdp_hint: last,
+                max_cdps: 0,
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(&owner, &SigningDomain::default()).unwrap();
+            ProtocolOperation::Redeem(op)
+        };
+
+        // The first hint must be the lowest-ratio redeemable CDP
+        assert!(matches!(
+            machine.execute(redeem(500_000, Some(safest), None, 3)),
+            Err(Error::InvalidParameter { .. })
+        ));
+
+        // More than the block allows is rejected outright
+        assert!(matches!(
+            machine.execute(redeem(1_000_001, None, None, 3)),
+            Err(Error::RedemptionCapReached { remaining: 1_000_000, .. })
+        ));
+
+        match machine.execute(redeem(500_000, Some(riskiest), Some(riskiest), 3)).unwrap() {
+            OperationResult::Redeem(result) => assert_eq!(result.cdps_affected, 1),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(machine.cdp_manager.get(&safest).unwrap().debt_cents, 2_000_000);
+        assert_eq!(machine.block_redeemed, 500_000);
+
+        // The allowance resets each block
+        machine.begin_block(machine.block_height + 1, machine.timestamp + 600).unwrap();
+        assert_eq!(machine.block_redeemed, 0);
     }
 
-    /// Get a CDP by ID
-    pub fn get_cdp(&self, id: &CDPId) -> Option<&CDP> {
-        self.cdp_manager.get(id)
+    #[test]
+    fn test_redemption_skips_undercollateralized_cdps() {
+        use crate::utils::constants::{BPS_DIVISOR, SIGNATURE_LENGTH};
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        // Redemption once started from the lowest-ratio CDP even below the
+        // MCR, raising its ratio and saving it from liquidation
+        let owner = KeyPair::generate();
+        let mut machine = create_test_machine();
+        machine.current_price = 10_000_000; // $100,000
+
+        let mut open = |debt_cents: u64, nonce: u64| {
+            let mut op = OpenCDPOp {
+                owner: *owner.public_key(),
+                collateral: CollateralAmount::from_sats(100_000_000),
+                initial_debt: Some(TokenAmount::from_cents(debt_cents)),
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(&owner, &SigningDomain::default()).unwrap();
+            match machine.execute(ProtocolOperation::OpenCDP(op)).unwrap() {
+                OperationResult::OpenCDP(result) => result.cdp_id,
+                other => panic!("unexpected result: {:?}", other),
+            }
+        };
+        let healthy = open(5_000_000, 1);
+        let underwater = open(9_000_000, 2);
+
+        // At $95,000 the riskier CDP is at 105%, below the 110% MCR
+        machine.current_price = 9_500_000;
+        let mut redeem = RedeemOp {
+            redeemer: *owner.public_key(),
+            amount: TokenAmount::from_cents(1_000_000),
+            max_fee_bps: BPS_DIVISOR,
+            first_cdp_hint: None,
+            last_cdp_hint: None,
+            max_cdps: 0,
+            nonce: 3,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        redeem.sign(&owner, &SigningDomain::default()).unwrap();
+        match machine.execute(ProtocolOperation::Redeem(redeem)).unwrap() {
+            OperationResult::Redeem(result) => assert_eq!(result.cdps_affected, 1),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert_eq!(machine.cdp_manager.get(&underwater).unwrap().debt_cents, 9_000_000);
+        assert!(machine.cdp_manager.get(&healthy).unwrap().debt_cents < 5_000_000);
     }
 
-    /// Get token balance
-    pub fn balance(&self, account: &PublicKey) -> TokenAmount {
-        self.token.balance_of(account)
+    #[test]
+    fn test_redemption_releases_collateral_from_vault() {
+        use crate::utils::constants::{BPS_DIVISOR, SIGNATURE_LENGTH};
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        // Redeemed collateral once stayed in the vault and the system totals
+        let owner = KeyPair::generate();
+        let mut machine = create_test_machine();
+        machine.current_price = 10_000_000; // $100,000
+
+        let mut open = OpenCDPOp {
+            owner: *owner.public_key(),
+            collateral: CollateralAmount::from_sats(100_000_000),
+            initial_debt: Some(TokenAmount::from_cents(5_000_000)),
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        open.sign(&owner, &SigningDomain::default()).unwrap();
+        let cdp_id = match machine.execute(ProtocolOperation::OpenCDP(open)).unwrap() {
+            OperationResult::OpenCDP(result) => result.cdp_id,
+            other => panic!("unexpected result: {:?}", other),
+        };
+
+        let mut redeem = RedeemOp {
+            redeemer: *owner.public_key(),
+            amount: TokenAmount::from_cents(1_000_000),
+            max_fee_bps: BPS_DIVISOR,
+            first_cdp_hint: None,
+            last_cdp_hint: None,
+            max_cdps: 0,
+            nonce: 2,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        redeem.sign(&owner, &SigningDomain::default()).unwrap();
+        let received = match machine.execute(ProtocolOperation::Redeem(redeem)).unwrap() {
+            OperationResult::Redeem(result) => result.collateral_received.sats(),
+            other => panic!("unexpected result: {:?}", other),
+        };
+        assert!(received > 0);
+
+        let cdp = machine.cdp_manager.get(&cdp_id).unwrap();
+        assert_eq!(cdp.collateral_sats, 100_000_000 - received);
+        assert_eq!(machine.vault.total_collateral().sats(), cdp.collateral_sats);
+        assert_eq!(machine.config.total_system_collateral, cdp.collateral_sats);
+        assert_eq!(machine.config.total_system_debt, cdp.debt_cents);
     }
 
-    /// Get stability pool deposit
-    pub fn stability_deposit(&self, depositor: &PublicKey) -> Option<TokenAmount> {
-        let value = self.stability_pool.get_current_value(depositor);
-        if value.is_zero() && self.stability_pool.get_deposit(depositor).is_none() {
-            None
-        } else {
-            Some(value)
+    #[test]
+    fn test_frontend_kickbacks() {
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let frontend = KeyPair::generate();
+        let depositor = KeyPair::generate();
+        let mut machine = create_test_machine();
+        machine
+            .token
+            .mint(*depositor.public_key(), TokenAmount::from_cents(200_000), 1, Hash::zero())
+            .unwrap();
+
+        let deposit = |nonce: u64| {
+            let mut op = StabilityDepositOp {
+                depositor: *depositor.public_key(),
+                amount: TokenAmount::from_cents(100_000),
+                frontend: Some(*frontend.public_key()),
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(&depositor, &SigningDomain::default()).unwrap();
+            ProtocolOperation::StabilityDeposit(op)
+        };
+
+        // Deposits can only be tagged with registered frontends
+        assert!(machine.execute(deposit(1)).is_err());
+
+        let mut register = RegisterFrontendOp {
+            frontend: *frontend.public_key(),
+            kickback_bps: 2_000,
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        register.sign(&frontend, &SigningDomain::default()).unwrap();
+        machine.execute(ProtocolOperation::RegisterFrontend(register)).unwrap();
+        machine.execute(deposit(1)).unwrap();
+        assert_eq!(machine.frontends().tag_of(depositor.public_key()), Some(frontend.public_key()));
+
+        machine
+            .stability_pool
+            .absorb_liquidation(TokenAmount::from_cents(10_000), CollateralAmount::from_sats(100_000))
+            .unwrap();
+
+        let mut claim = ClaimGainsOp {
+            depositor: *depositor.public_key(),
+            nonce: 2,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        claim.sign(&depositor, &SigningDomain::default()).unwrap();
+        match machine.execute(ProtocolOperation::ClaimGains(claim)).unwrap() {
+            OperationResult::ClaimGains(result) => assert_eq!(result.btc_claimed.sats(), 80_000),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(machine.frontends().statistics().pending_gains.sats(), 20_000);
+
+        let mut claim = ClaimFrontendGainsOp {
+            frontend: *frontend.public_key(),
+            nonce: 2,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        claim.sign(&frontend, &SigningDomain::default()).unwrap();
+        match machine.execute(ProtocolOperation::ClaimFrontendGains(claim)).unwrap() {
+            OperationResult::ClaimFrontendGains(result) => assert_eq!(result.btc_claimed.sats(), 20_000),
+            other => panic!("unexpected result: {:?}", other),
         }
+
+        // A full withdrawal drops the tag
+        let mut withdraw = StabilityWithdrawOp {
+            depositor: *depositor.public_key(),
+            amount: TokenAmount::from_cents(100_000),
+            nonce: 3,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        withdraw.sign(&depositor, &SigningDomain::default()).unwrap();
+        machine.execute(ProtocolOperation::StabilityWithdraw(withdraw)).unwrap();
+        assert!(machine.frontends().tag_of(depositor.public_key()).is_none());
+        assert_eq!(machine.frontends().get(frontend.public_key()).unwrap().depositors, 0);
     }
 
-    /// Get total supply
-    pub fn total_supply(&self) -> TokenAmount {
-        self.token.total_supply()
+    #[test]
+    fn test_sp_withdrawals_wait_for_liquidations() {
+        use crate::utils::constants::{SATS_PER_BTC, SIGNATURE_LENGTH};
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let depositor = KeyPair::generate();
+        let mut machine = create_test_machine();
+        machine.current_price = 5_000_000; // $50,000
+        machine
+            .stability_pool
+            .deposit(*depositor.public_key(), TokenAmount::from_cents(100_000), 1)
+            .unwrap();
+
+        // 1 BTC backing $48,000 is below the MCR at $50,000
+        let mut cdp = CDP::with_collateral(*KeyPair::generate().public_key(), SATS_PER_BTC, 1, 1).unwrap();
+        cdp.debt_cents = 4_800_000;
+        machine.cdp_manager.register(cdp).unwrap();
+
+        let withdraw = |nonce: u64| {
+            let mut op = StabilityWithdrawOp {
+                depositor: *depositor.public_key(),
+                amount: TokenAmount::from_cents(50_000),
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(&depositor, &SigningDomain::default()).unwrap();
+            ProtocolOperation::StabilityWithdraw(op)
+        };
+
+        assert!(matches!(
+            machine.execute(withdraw(1)),
+            Err(Error::WithdrawalsBlocked { liquidatable: 1 })
+        ));
+
+        // Once the CDP is healthy again, or with the gate off, withdrawals go through
+        machine.current_price = 10_000_000;
+        machine.execute(withdraw(1)).unwrap();
+        machine.current_price = 5_000_000;
+        machine.config.params = machine.config.params.clone().with_sp_withdrawal_gate(false);
+        machine.execute(withdraw(2)).unwrap();
     }
 
-    /// Get total collateral
-    pub fn total_collateral(&self) -> CollateralAmount {
-        self.vault.total_collateral()
+    #[test]
+    fn test_pool_absorbs_only_seized_collateral() {
+        use crate::liquidation::engine::PenaltyCurve;
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        // The pool was once credited with the CDP's whole collateral, even
+        // the surplus left to the owner
+        let owner = KeyPair::generate();
+        let keeper = KeyPair::generate();
+        let mut machine = create_test_machine();
+        machine.config.params.liquidation_penalty = PenaltyCurve::flat(500);
+        machine.current_price = 10_000_000; // $100,000
+        machine
+            .stability_pool
+            .deposit(*KeyPair::generate().public_key(), TokenAmount::from_cents(10_000_000), 1)
+            .unwrap();
+
+        let mut open = OpenCDPOp {
+            owner: *owner.public_key(),
+            collateral: CollateralAmount::from_sats(200_000_000),
+            initial_debt: Some(TokenAmount::from_cents(9_500_000)),
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        open.sign(&owner, &SigningDomain::default()).unwrap();
+        let cdp_id = match machine.execute(ProtocolOperation::OpenCDP(open)).unwrap() {
+            OperationResult::OpenCDP(result) => result.cdp_id,
+            other => panic!("unexpected result: {:?}", other),
+        };
+
+        // At $50,000 the CDP is at 105%: $95,000 plus 5% takes 1.995 BTC
+        machine.current_price = 5_000_000;
+        let mut liquidate = LiquidateCDPOp {
+            cdp_id,
+            liquidator: *keeper.public_key(),
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        liquidate.sign(&keeper, &SigningDomain::default()).unwrap();
+        machine.execute(ProtocolOperation::LiquidateCDP(liquidate)).unwrap();
+
+        assert_eq!(machine.stability_pool.total_btc_gains().sats(), 199_500_000);
+        assert_eq!(machine.cdp_manager.get(&cdp_id).unwrap().collateral_sats, 500_000);
+        assert_eq!(machine.vault.total_collateral().sats(), 500_000);
     }
-}
 
-// ═══════════════════════════════════════════════════════════════════════════════
-// OPERATION RESULT
-// ═══════════════════════════════════════════════════════════════════════════════
+    #[test]
+    fn test_liquidation_auction() {
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let owner = KeyPair::generate();
+        let bidder = KeyPair::generate();
+        let mut machine = create_test_machine().with_auctions(AuctionConfig::default()).unwrap();
+        machine.current_price = 10_000_000; // $100,000
+        machine
+            .token
+            .mint(*bidder.public_key(), TokenAmount::from_cents(10_000_000), 1, Hash::zero())
+            .unwrap();
+
+        let mut open = OpenCDPOp {
+            owner: *owner.public_key(),
+            collateral: CollateralAmount::from_sats(100_000_000),
+            initial_debt: Some(TokenAmount::from_cents(5_000_000)),
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        open.sign(&owner, &SigningDomain::default()).unwrap();
+        let cdp_id = match machine.execute(ProtocolOperation::OpenCDP(open)).unwrap() {
+            OperationResult::OpenCDP(result) => result.cdp_id,
+            other => panic!("unexpected result: {:?}", other),
+        };
 
-/// Result of any protocol operation
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum OperationResult {
-    /// Open CDP result
-    OpenCDP(OpenCDPResult),
-    /// Deposit result
-    Deposit(DepositResult),
-    /// Withdraw result
-    Withdraw(WithdrawResult),
-    /// Mint result
-    Mint(MintResult),
-    /// Repay result
-    Repay(RepayResult),
-    /// Close result
-    Close(CloseResult),
-    /// Liquidate result
-    Liquidate(LiquidateResult),
-    /// Transfer result
-    Transfer(TransferResult),
-    /// Stability deposit result
-    StabilityDeposit(StabilityDepositResult),
-    /// Stability withdraw result
-    StabilityWithdraw(StabilityWithdrawResult),
-    /// Claim gains result
-    ClaimGains(ClaimGainsResult),
-    /// Redeem result
-    Redeem(RedeemResult),
-    /// Update price result
-    UpdatePrice(UpdatePriceResult),
-}
+        // With an empty stability pool the collateral goes to auction at 120%
+        machine.current_price = 5_000_000;
+        let mut liquidate = LiquidateCDPOp {
+            cdp_id,
+            liquidator: *bidder.public_key(),
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        liquidate.sign(&bidder, &SigningDomain::default()).unwrap();
+        machine.execute(ProtocolOperation::LiquidateCDP(liquidate)).unwrap();
+        let auction = machine.auctions().open_auctions().next().unwrap().clone();
+        assert_eq!(auction.lot.sats(), 100_000_000);
+        assert_eq!(auction.tab.cents(), 5_000_000);
+        assert_eq!(auction.start_price, 6_000_000);
+        assert!(machine.set_auction_config(None).is_err());
+
+        let bid = |max_sats: u64, max_price: u64, nonce: u64| {
+            let mut op = AuctionBidOp {
+                bidder: *bidder.public_key(),
+                auction_id: auction.id,
+                max_collateral: CollateralAmount::from_sats(max_sats),
+                max_price,
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(&bidder, &SigningDomain::default()).unwrap();
+            ProtocolOperation::AuctionBid(op)
+        };
 
-// ═══════════════════════════════════════════════════════════════════════════════
-// TESTS
-// ═══════════════════════════════════════════════════════════════════════════════
+        // Ten blocks in, the price has decayed by 10%
+        machine.block_height += 10;
+        assert!(machine.execute(bid(50_000_000, 5_000_000, 2)).is_err());
+        match machine.execute(bid(50_000_000, 5_400_000, 2)).unwrap() {
+            OperationResult::AuctionBid(result) => {
+                assert_eq!(result.cost.cents(), 2_700_000);
+                assert!(!result.closed);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::storage::backend::InMemoryStore;
+        // The second bid only pays the remaining tab
+        match machine.execute(bid(100_000_000, 5_400_000, 3)).unwrap() {
+            OperationResult::AuctionBid(result) => {
+                assert_eq!(result.cost.cents(), 2_300_000);
+                assert_eq!(result.collateral_received.sats(), 42_592_592);
+                assert!(result.closed);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(machine.auctions().is_empty());
+        // The bidder also triggered the liquidation, but the treasury was
+        // empty so no keeper stipend was paid
+        assert_eq!(machine.treasury_balance().cents(), 0);
+        assert_eq!(machine.balance(bidder.public_key()).cents(), 5_000_000);
+        assert!(machine.event_log.events().iter().any(|event| matches!(
+            event,
+            ProtocolEvent::AuctionTaken(taken) if taken.closed && taken.returned.sats() == 7_407_408
+        )));
+        machine.set_auction_config(None).unwrap();
+    }
 
-    fn create_test_machine() -> ProtocolStateMachine<InMemoryStore> {
-        ProtocolStateMachine::new(InMemoryStore::new()).unwrap()
+    #[test]
+    fn test_keeper_rewards_bounded_per_block() {
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let mut machine = create_test_machine().with_watchdog(WatchdogConfig {
+            enabled: false,
+            ..Default::default()
+        });
+        machine.config.params = machine.config.params.clone().with_keeper_rewards(100, 150);
+        machine.current_price = 10_000_000; // $100,000
+        machine.begin_block(1, 1_000).unwrap();
+
+        let mut cdp_ids = Vec::new();
+        for _ in 0..3 {
+            let owner = KeyPair::generate();
+            let mut open = OpenCDPOp {
+                owner: *owner.public_key(),
+                collateral: CollateralAmount::from_sats(100_000_000),
+                initial_debt: Some(TokenAmount::from_cents(5_000_000)),
+                nonce: 1,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            open.sign(&owner, &SigningDomain::default()).unwrap();
+            let cdp_id = match machine.execute(ProtocolOperation::OpenCDP(open)).unwrap() {
+                OperationResult::OpenCDP(result) => result.cdp_id,
+                other => panic!("unexpected result: {:?}", other),
+            };
+
+            // Borrowing fees fund the treasury the stipends are paid from
+            let mut mint = MintDebtOp {
+                cdp_id,
+                owner: *owner.public_key(),
+                amount: TokenAmount::from_cents(100_000),
+                max_fee_bps: 10_000,
+                nonce: 2,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+                cosignatures: Vec::new(),
+            };
+            mint.sign(&owner, &SigningDomain::default()).unwrap();
+            machine.execute(ProtocolOperation::MintDebt(mint)).unwrap();
+            cdp_ids.push(cdp_id);
+        }
+        let treasury_before = machine.treasury_balance().cents();
+        assert_eq!(treasury_before, 750);
+
+        machine.current_price = 5_000_000;
+        let liquidate = |machine: &mut ProtocolStateMachine<InMemoryStore>, cdp_id: CDPId| {
+            let keeper = KeyPair::generate();
+            let mut op = LiquidateCDPOp {
+                cdp_id,
+                liquidator: *keeper.public_key(),
+                nonce: 1,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(&keeper, &SigningDomain::default()).unwrap();
+            match machine.execute(ProtocolOperation::LiquidateCDP(op)).unwrap() {
+                OperationResult::Liquidate(result) => {
+                    assert_eq!(machine.balance(keeper.public_key()), result.keeper_reward);
+                    result.keeper_reward.cents()
+                }
+                other => panic!("unexpected result: {:?}", other),
+            }
+        };
+
+        // The second stipend is cut to what is left of the block's budget
+        assert_eq!(liquidate(&mut machine, cdp_ids[0]), 100);
+        assert_eq!(liquidate(&mut machine, cdp_ids[1]), 50);
+        assert_eq!(machine.treasury_balance().cents(), treasury_before - 150);
+        assert_eq!(machine.treasury().disbursed().cents(), 150);
+        assert_eq!(
+            machine.event_log.events().iter().filter(|e| matches!(e, ProtocolEvent::KeeperRewarded(_))).count(),
+            2
+        );
+
+        // The budget resets with the next block
+        machine.begin_block(2, 1_600).unwrap();
+        assert_eq!(liquidate(&mut machine, cdp_ids[2]), 100);
     }
 
     #[test]
-    fn test_state_machine_creation() {
-        let machine = create_test_machine();
-        assert_eq!(machine.price(), 0);
-        assert!(!machine.is_recovery_mode());
+    fn test_genesis_initializes_and_is_checked() {
+        use crate::core::config::Network;
+        use crate::storage::backend::BinaryStore;
+
+        let dir = tempfile::tempdir().unwrap();
+        let genesis = Genesis::new(Network::Regtest, ProtocolConfig::default()).with_genesis_time(1_700_000_000);
+        {
+            let machine = ProtocolStateMachine::from_genesis(BinaryStore::new(dir.path()).unwrap(), &genesis).unwrap();
+            assert_eq!(machine.genesis_hash().unwrap(), Some(genesis.hash()));
+            assert_eq!(machine.timestamp, 1_700_000_000);
+        }
+
+        // The same genesis reopens the database, a different one is refused
+        ProtocolStateMachine::from_genesis(BinaryStore::new(dir.path()).unwrap(), &genesis).unwrap();
+        let other = genesis.clone().with_genesis_time(1_700_000_001);
+        let result = ProtocolStateMachine::from_genesis(BinaryStore::new(dir.path()).unwrap(), &other);
+        assert!(matches!(result, Err(Error::GenesisMismatch { .. })));
+
+        // So is a database initialized without one
+        let plain = tempfile::tempdir().unwrap();
+        drop(ProtocolStateMachine::new(BinaryStore::new(plain.path()).unwrap()).unwrap());
+        let result = ProtocolStateMachine::from_genesis(BinaryStore::new(plain.path()).unwrap(), &genesis);
+        assert!(matches!(result, Err(Error::GenesisMismatch { .. })));
     }
 
     #[test]
-    fn test_begin_end_block() {
-        let mut machine = create_test_machine();
+    fn test_preview_leaves_state_untouched() {
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let mut machine = create_test_machine().with_watchdog(WatchdogConfig {
+            enabled: false,
+            ..Default::default()
+        });
+        machine.current_price = 10_000_000; // $100,000
+        machine.begin_block(1, 1_000).unwrap();
+
+        let owner = KeyPair::generate();
+        let mut open = OpenCDPOp {
+            owner: *owner.public_key(),
+            collateral: CollateralAmount::from_sats(100_000_000),
+            initial_debt: Some(TokenAmount::from_cents(2_000_000)),
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        open.sign(&owner, &SigningDomain::default()).unwrap();
+        let cdp_id = match machine.execute(ProtocolOperation::OpenCDP(open)).unwrap() {
+            OperationResult::OpenCDP(result) => result.cdp_id,
+            other => panic!("unexpected result: {:?}", other),
+        };
+        let supply = machine.total_supply();
 
-        machine.begin_block(100, 1234567890).unwrap();
-        assert_eq!(machine.block_height(), 100);
+        // Unsigned: previews are built before signing
+        let mint = MintDebtOp {
+            cdp_id,
+            owner: *owner.public_key(),
+            amount: TokenAmount::from_cents(1_000_000),
+            max_fee_bps: 10_000,
+            nonce: 2,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            cosignatures: Vec::new(),
+        };
+        let preview = machine.preview(ProtocolOperation::MintDebt(mint.clone())).unwrap();
 
-        let events = machine.end_block().unwrap();
-        assert!(events.is_empty());
+        let OperationResult::Mint(result) = &preview.result else {
+            panic!("unexpected result: {:?}", preview.result);
+        };
+        assert_eq!(preview.fees, result.fee);
+        assert!(preview.fees.cents() > 0);
+        assert_eq!(preview.cdp_ratio, Some(result.new_ratio));
+        assert!(!preview.events.is_empty());
+
+        // Nothing applied, and the real machine still wants a signature
+        assert_eq!(machine.total_supply(), supply);
+        assert!(matches!(
+            machine.execute(ProtocolOperation::MintDebt(mint.clone())),
+            Err(Error::InvalidSignature)
+        ));
+
+        // Checks other than the signature still apply
+        let greedy = MintDebtOp { amount: TokenAmount::from_cents(100_000_000), ..mint };
+        assert!(machine.preview(ProtocolOperation::MintDebt(greedy)).is_err());
     }
 
     #[test]
-    fn test_total_supply_and_collateral() {
-        let machine = create_test_machine();
+    fn test_uncertain_price_restricts_risk_increasing_operations() {
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let mut machine = create_test_machine().with_watchdog(WatchdogConfig {
+            enabled: false,
+            ..Default::default()
+        });
+        machine.current_price = 10_000_000; // $100,000
+        machine.begin_block(1, 1_000).unwrap();
+
+        let owner = KeyPair::generate();
+        let mut open = OpenCDPOp {
+            owner: *owner.public_key(),
+            collateral: CollateralAmount::from_sats(100_000_000),
+            initial_debt: Some(TokenAmount::from_cents(5_000_000)),
+            nonce: 1,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        open.sign(&owner, &SigningDomain::default()).unwrap();
+        let cdp_id = match machine.execute(ProtocolOperation::OpenCDP(open)).unwrap() {
+            OperationResult::OpenCDP(result) => result.cdp_id,
+            other => panic!("unexpected result: {:?}", other),
+        };
 
-        assert_eq!(machine.total_supply().cents(), 0);
-        assert_eq!(machine.total_collateral().sats(), 0);
+        let mint = |amount: u64, nonce: u64| {
+            let mut op = MintDebtOp {
+                cdp_id,
+                owner: *owner.public_key(),
+                amount: TokenAmount::from_cents(amount),
+                max_fee_bps: 10_000,
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+                cosignatures: Vec::new(),
+            };
+            op.sign(&owner, &SigningDomain::default()).unwrap();
+            ProtocolOperation::MintDebt(op)
+        };
+
+        // A 5% interval blocks borrowing and withdrawals
+        machine.price_interval = 500_000;
+        assert!(matches!(
+            machine.execute(mint(100_000, 2)),
+            Err(Error::PriceTooUncertain { interval_bps: 500, .. })
+        ));
+        let mut withdraw = WithdrawCollateralOp {
+            cdp_id,
+            owner: *owner.public_key(),
+            amount: CollateralAmount::from_sats(1_000_000),
+            nonce: 2,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            cosignatures: Vec::new(),
+        };
+        withdraw.sign(&owner, &SigningDomain::default()).unwrap();
+        assert!(matches!(
+            machine.execute(ProtocolOperation::WithdrawCollateral(withdraw)),
+            Err(Error::PriceTooUncertain { .. })
+        ));
+
+        // ...while deposits and repayments still go through
+        let mut deposit = DepositCollateralOp {
+            cdp_id,
+            depositor: *owner.public_key(),
+            amount: CollateralAmount::from_sats(10_000_000),
+            nonce: 2,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        deposit.sign(&owner, &SigningDomain::default()).unwrap();
+        machine.execute(ProtocolOperation::DepositCollateral(deposit)).unwrap();
+
+        let mut repay = RepayDebtOp {
+            cdp_id,
+            payer: *owner.public_key(),
+            amount: TokenAmount::from_cents(10_000),
+            nonce: 3,
+            signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+        };
+        repay.sign(&owner, &SigningDomain::default()).unwrap();
+        machine.execute(ProtocolOperation::RepayDebt(repay)).unwrap();
+        assert_eq!(machine.get_cdp(&cdp_id).unwrap().debt_cents, 4_990_000);
+
+        // Within the policy, borrowing is checked against the interval's lower
+        // edge: 1.1 BTC is worth $110,000 at mid but $108,900 at $99,000
+        machine.price_interval = 100_000;
+        assert!(matches!(
+            machine.execute(mint(4_960_000, 4)),
+            Err(Error::CollateralizationRatioTooLow { current: 109, .. })
+        ));
+        machine.execute(mint(4_000_000, 4)).unwrap();
+        assert_eq!(machine.get_cdp(&cdp_id).unwrap().debt_cents, 8_990_000);
+    }
+
+    #[test]
+    fn test_peg_controller_steers_fees_from_market_price() {
+        use crate::core::peg::PegRegime;
+        use crate::oracle::price_feed::RATE_PRECISION;
+        use crate::utils::constants::SIGNATURE_LENGTH;
+        use crate::utils::crypto::{KeyPair, Signature};
+
+        let mut machine = create_test_machine()
+            .with_watchdog(WatchdogConfig { enabled: false, ..Default::default() })
+            .with_peg_stability(PegConfig { interval_blocks: 1, ..Default::default() })
+            .unwrap();
+        let base_fee = machine.config.params.borrowing_fee_bps;
+        let base_floor = machine.config.params.redemption_fee_floor_bps;
+
+        let oracle = KeyPair::generate();
+        let mut nonce = 0;
+        let mut publish = |machine: &mut ProtocolStateMachine<InMemoryStore>, pair: TradingPair, rate: u64| {
+            nonce += 1;
+            let mut op = UpdatePairPriceOp {
+                operator: *oracle.public_key(),
+                pair,
+                rate,
+                source_count: 3,
+                nonce,
+                signature: Signature::new([0u8; SIGNATURE_LENGTH]),
+            };
+            op.sign(&oracle, &SigningDomain::default()).unwrap();
+            machine.execute(ProtocolOperation::UpdatePairPrice(op))
+        };
+
+        // BTC/USD cannot bypass attested updates
+        machine.begin_block(1, 1_000).unwrap();
+        assert!(publish(&mut machine, TradingPair::btc_usd(), RATE_PRECISION).is_err());
+
+        // zkUSD at $0.98: borrowing gets dearer, redemptions cheaper
+        publish(&mut machine, TradingPair::zkusd_usd(), RATE_PRECISION / 100 * 98).unwrap();
+        let events = machine.end_block().unwrap();
+        let adjusted = events.events().iter().find_map(|event| match event {
+            ProtocolEvent::PegFeesAdjusted(e) => Some(e.clone()),
+            _ => None,
+        }).unwrap();
+        assert_eq!(adjusted.deviation_bps, -200);
+        assert_eq!(adjusted.regime, PegRegime::BelowPeg);
+        assert_eq!(machine.config.params.borrowing_fee_bps, base_fee + 25);
+        assert_eq!(machine.config.params.redemption_fee_floor_bps, base_floor - 25);
+
+        // Back at $1 the fees return to where governance left them
+        machine.begin_block(2, 1_600).unwrap();
+        publish(&mut machine, TradingPair::zkusd_usd(), RATE_PRECISION).unwrap();
+        machine.end_block().unwrap();
+        assert_eq!(machine.config.params.borrowing_fee_bps, base_fee);
+        assert_eq!(machine.config.params.redemption_fee_floor_bps, base_floor);
+        assert_eq!(machine.peg().unwrap().regime(), PegRegime::Neutral);
+
+        // Without a fresh market price the controller holds still
+        machine.begin_block(3, 1_600 + 7_200).unwrap();
+        let events = machine.end_block().unwrap();
+        assert!(!events.events().iter().any(|e| matches!(e, ProtocolEvent::PegFeesAdjusted(_))));
     }
 }
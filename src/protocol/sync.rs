@@ -0,0 +1,771 @@
+//! State sync between nodes.
+//!
+//! A new node doesn't have to be fed every operation since genesis. It
+//! downloads the latest snapshot a peer serves, checks it and installs it,
+//! then fetches the operation log of every later block and re-executes it
+//! until it reaches the peer's tip.
+//!
+//! A snapshot is checked before the local store is touched: its data
+//! checksum, the state root recomputed from its entries, the genesis it
+//! descends from and, when the peer attached one, the aggregated proof
+//! committed to by its light-client header. A consistent snapshot could
+//! still be fabricated, so it must also match a trusted state root or
+//! carry a proof, unless the caller explicitly accepts it insecurely. Blocks are replayed through the
+//! local state machine, so signatures, nonces and every protocol rule are
+//! enforced again, and each block's events must match the peer's.
+//!
+//! Parameter changes made outside operations (such as
+//! `update_parameter`) are not in the operation log. A node that diverges
+//! on one syncs again from a snapshot taken after it.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use zkusd::protocol::sync::StateSync;
+//!
+//! let sync = StateSync::new(peer);
+//! let report = sync.sync(&mut machine, |progress| println!("{}", progress.height))?;
+//! println!("synced to {} ({} blocks replayed)", report.block_height, report.blocks_replayed);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::core::cdp::CDP;
+use crate::error::{Error, Result};
+use crate::protocol::operations::ProtocolOperation;
+use crate::protocol::state_machine::ProtocolStateMachine;
+use crate::storage::backend::{prefixes, InMemoryStore, StorageBackend, StorageKey, StorageValue};
+use crate::storage::rocks::{column_families, BatchOperation};
+use crate::storage::state::StateManager;
+use crate::utils::crypto::Hash;
+use crate::zkp::light_client::{cdp_state_root, LightClient, StateHeader};
+use crate::zkp::verifier::ProofBatch;
+
+/// Snapshot format version
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// Blocks fetched per request while catching up
+pub const SYNC_BATCH_BLOCKS: usize = 100;
+
+/// Prefixes holding history rather than state, left out of snapshots
+const HISTORY_PREFIXES: &[&[u8]] = &[
+    prefixes::TX,
+    prefixes::EVENT,
+    prefixes::BLOCK,
+    prefixes::CDP_HISTORY,
+    prefixes::RECEIPT,
+    prefixes::RECEIPT_BLOCK,
+];
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// BLOCK LOG
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Operations a block applied, in order, for peers to replay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockRecord {
+    /// Block height
+    pub height: u64,
+    /// Block timestamp
+    pub timestamp: u64,
+    /// Operations that succeeded in the block
+    pub operations: Vec<ProtocolOperation>,
+    /// Merkle root of the events the block emitted
+    pub events_root: Hash,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// SNAPSHOTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Light-client proof attached to a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotProof {
+    /// Header committing to the snapshot's CDP state root
+    pub header: StateHeader,
+    /// Aggregated proof committed to by the header
+    pub batch: ProofBatch,
+}
+
+/// What a snapshot claims to hold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    /// Snapshot format version
+    pub format_version: u32,
+    /// Block height the snapshot was taken at
+    pub block_height: u64,
+    /// Timestamp of that block
+    pub timestamp: u64,
+    /// State root at that block
+    pub state_root: Hash,
+    /// Hash of the genesis the state descends from
+    pub genesis_hash: Option<Hash>,
+    /// Number of entries
+    pub entry_count: usize,
+    /// SHA256 of the serialized entries
+    pub data_checksum: Hash,
+    /// Light-client proof of the state, if the serving node has one
+    pub proof: Option<SnapshotProof>,
+}
+
+/// Protocol state of a node at one block, for peers to start from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncSnapshot {
+    /// Snapshot metadata
+    pub manifest: SnapshotManifest,
+    /// Store entries in key order, history excluded
+    pub entries: Vec<(StorageKey, StorageValue)>,
+}
+
+impl SyncSnapshot {
+    /// Snapshot the state held by `manager`
+    pub fn create<B: StorageBackend>(manager: &StateManager<B>) -> Result<Self> {
+        let backend = manager.backend();
+        let mut entries = BTreeMap::new();
+        for key in backend.keys()? {
+            if HISTORY_PREFIXES.iter().any(|prefix| key.starts_with(prefix)) {
+                continue;
+            }
+            if let Some(value) = backend.get(&key)? {
+                entries.insert(key, value);
+            }
+        }
+        let entries: Vec<_> = entries.into_iter().collect();
+
+        let state = manager.load_protocol_state()?;
+        Ok(Self {
+            manifest: SnapshotManifest {
+                format_version: SNAPSHOT_FORMAT_VERSION,
+                block_height: state.block_height,
+                timestamp: state.last_update,
+                state_root: manager.compute_state_root()?,
+                genesis_hash: manager.load_genesis()?.map(|genesis| genesis.hash()),
+                entry_count: entries.len(),
+                data_checksum: checksum(&entries)?,
+                proof: None,
+            },
+            entries,
+        })
+    }
+
+    /// Attach a light-client proof of the snapshot's state
+    pub fn with_proof(mut self, header: StateHeader, batch: ProofBatch) -> Self {
+        self.manifest.proof = Some(SnapshotProof { header, batch });
+        self
+    }
+
+    /// Check the snapshot is intact, matches its manifest and satisfies
+    /// `options`, without touching any store
+    pub fn verify(&self, options: &SyncOptions, client: &LightClient) -> Result<()> {
+        let manifest = &self.manifest;
+        if manifest.format_version > SNAPSHOT_FORMAT_VERSION {
+            return Err(Error::Deserialization(format!(
+                "Unsupported snapshot format version {}",
+                manifest.format_version
+            )));
+        }
+        if manifest.entry_count != self.entries.len() || checksum(&self.entries)? != manifest.data_checksum {
+            return Err(Error::InvalidStateProof("snapshot data checksum mismatch".into()));
+        }
+
+        let scratch = StateManager::new(InMemoryStore::new());
+        self.write_entries(&scratch)?;
+
+        let state_root = scratch.compute_state_root()?;
+        if state_root != manifest.state_root {
+            return Err(Error::InvalidStateProof(format!(
+                "snapshot state root mismatch: expected {}, got {}",
+                manifest.state_root.to_hex(),
+                state_root.to_hex()
+            )));
+        }
+        if scratch.load_protocol_state()?.block_height != manifest.block_height {
+            return Err(Error::InvalidStateProof("snapshot height does not match its state".into()));
+        }
+        if scratch.load_genesis()?.map(|genesis| genesis.hash()) != manifest.genesis_hash {
+            return Err(Error::InvalidStateProof("snapshot genesis does not match its state".into()));
+        }
+
+        if let Some(expected) = options.genesis_hash {
+            if manifest.genesis_hash != Some(expected) {
+                return Err(Error::GenesisMismatch {
+                    expected: expected.to_hex(),
+                    found: manifest.genesis_hash.map_or_else(|| "no genesis".into(), |hash| hash.to_hex()),
+                });
+            }
+        }
+        if let Some(trusted) = options.trusted_root {
+            if trusted != manifest.state_root {
+                return Err(Error::InvalidStateProof("snapshot state root is not the trusted root".into()));
+            }
+        }
+
+        match &manifest.proof {
+            Some(proof) => {
+                let cdps = scratch.load_all_cdps()?;
+                let refs: Vec<&CDP> = cdps.iter().collect();
+                if proof.header.block_height != manifest.block_height {
+                    return Err(Error::InvalidStateProof("snapshot proof is for another block".into()));
+                }
+                if proof.header.state_root != cdp_state_root(&refs) {
+                    return Err(Error::InvalidStateProof("snapshot proof is for another state".into()));
+                }
+                client.verify_batch(&proof.header, &proof.batch)
+            }
+            None if options.require_proof => Err(Error::InvalidStateProof("snapshot carries no proof".into())),
+            // Self-consistent data proves nothing about where it came from
+            None if options.trusted_root.is_none() && !options.insecure => Err(Error::InvalidStateProof(
+                "snapshot carries no proof and no trusted root was given".into(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Replace the contents of `manager`'s store with the snapshot.
+    ///
+    /// The stale keys are deleted and the snapshot written in a single batch,
+    /// so a backend that writes batches atomically is never left half
+    /// installed.
+    pub fn install<B: StorageBackend>(&self, manager: &StateManager<B>) -> Result<()> {
+        manager.discard_batch()?;
+        let backend = manager.backend();
+
+        let incoming: BTreeMap<&[u8], &[u8]> =
+            self.entries.iter().map(|(key, value)| (key.as_slice(), value.as_slice())).collect();
+        let mut operations: Vec<BatchOperation> = backend
+            .keys()?
+            .into_iter()
+            .filter(|key| !incoming.contains_key(key.as_slice()))
+            .map(|key| BatchOperation::delete(column_families::DEFAULT, key))
+            .collect();
+        operations.extend(
+            incoming
+                .into_iter()
+                .map(|(key, value)| BatchOperation::put(column_families::DEFAULT, key, value)),
+        );
+        backend.write_batch(operations)?;
+
+        manager.flush()
+    }
+
+    /// Serialize for transfer
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        bincode::serialize(self).map_err(|e| Error::Serialization(format!("Failed to serialize snapshot: {}", e)))
+    }
+
+    /// Deserialize a transferred snapshot
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        bincode::deserialize(bytes).map_err(|e| Error::Deserialization(format!("Invalid snapshot: {}", e)))
+    }
+
+    fn write_entries<B: StorageBackend>(&self, manager: &StateManager<B>) -> Result<()> {
+        let backend = manager.backend();
+        for (key, value) in &self.entries {
+            backend.set(key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// SHA256 of serialized snapshot entries
+fn checksum(entries: &[(StorageKey, StorageValue)]) -> Result<Hash> {
+    let data = bincode::serialize(entries)
+        .map_err(|e| Error::Serialization(format!("Failed to serialize snapshot data: {}", e)))?;
+    Ok(Hash::sha256(&data))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// SOURCES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Where a node stands, as advertised to syncing peers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncStatus {
+    /// Latest block height
+    pub block_height: u64,
+    /// State root at that block
+    pub state_root: Hash,
+    /// Hash of the node's genesis
+    pub genesis_hash: Option<Hash>,
+}
+
+impl SyncStatus {
+    /// Status of the state held by `manager`
+    pub fn of<B: StorageBackend>(manager: &StateManager<B>) -> Result<Self> {
+        Ok(Self {
+            block_height: manager.load_protocol_state()?.block_height,
+            state_root: manager.compute_state_root()?,
+            genesis_hash: manager.load_genesis()?.map(|genesis| genesis.hash()),
+        })
+    }
+}
+
+/// A peer serving snapshots and block logs
+pub trait SyncSource {
+    /// Where the peer stands
+    fn status(&self) -> Result<SyncStatus>;
+
+    /// The peer's latest snapshot
+    fn snapshot(&self) -> Result<SyncSnapshot>;
+
+    /// Up to `limit` block logs at or above `from`, lowest first
+    fn blocks(&self, from: u64, limit: usize) -> Result<Vec<BlockRecord>>;
+}
+
+impl<B: StorageBackend> SyncSource for StateManager<B> {
+    fn status(&self) -> Result<SyncStatus> {
+        SyncStatus::of(self)
+    }
+
+    fn snapshot(&self) -> Result<SyncSnapshot> {
+        SyncSnapshot::create(self)
+    }
+
+    fn blocks(&self, from: u64, limit: usize) -> Result<Vec<BlockRecord>> {
+        self.load_blocks(from, limit)
+    }
+}
+
+impl<S: SyncSource + ?Sized> SyncSource for &S {
+    fn status(&self) -> Result<SyncStatus> {
+        (**self).status()
+    }
+
+    fn snapshot(&self) -> Result<SyncSnapshot> {
+        (**self).snapshot()
+    }
+
+    fn blocks(&self, from: u64, limit: usize) -> Result<Vec<BlockRecord>> {
+        (**self).blocks(from, limit)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// SYNC
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// What a syncing node accepts from its peer.
+///
+/// A snapshot must match a trusted root or carry a light-client proof,
+/// unless [`with_insecure`](Self::with_insecure) opts out.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SyncOptions {
+    /// Genesis the snapshot must descend from
+    pub genesis_hash: Option<Hash>,
+    /// State root the snapshot must have, e.g. one obtained out of band
+    pub trusted_root: Option<Hash>,
+    /// Reject snapshots without a light-client proof
+    pub require_proof: bool,
+    /// Accept a snapshot with neither a trusted root nor a proof
+    pub insecure: bool,
+}
+
+impl SyncOptions {
+    /// Require snapshots to descend from the genesis with this hash
+    pub fn with_genesis_hash(mut self, hash: Hash) -> Self {
+        self.genesis_hash = Some(hash);
+        self
+    }
+
+    /// Require the snapshot to have this state root
+    pub fn with_trusted_root(mut self, root: Hash) -> Self {
+        self.trusted_root = Some(root);
+        self
+    }
+
+    /// Reject snapshots without a light-client proof
+    pub fn with_required_proof(mut self) -> Self {
+        self.require_proof = true;
+        self
+    }
+
+    /// Accept a snapshot on its own consistency, trusting the peer
+    pub fn with_insecure(mut self) -> Self {
+        self.insecure = true;
+        self
+    }
+}
+
+/// Stage of a sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SyncPhase {
+    /// Downloading and checking the snapshot
+    Snapshot,
+    /// Replaying blocks after the snapshot
+    CatchUp,
+    /// At the peer's tip
+    Complete,
+}
+
+/// Progress reported while syncing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncProgress {
+    /// Current stage
+    pub phase: SyncPhase,
+    /// Height the local node has reached
+    pub height: u64,
+    /// Height of the peer's tip
+    pub target: u64,
+    /// Blocks replayed so far
+    pub blocks_replayed: u64,
+}
+
+/// Outcome of a sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncReport {
+    /// Height of the installed snapshot, if one was installed
+    pub snapshot_height: Option<u64>,
+    /// Blocks replayed after it
+    pub blocks_replayed: u64,
+    /// Height reached
+    pub block_height: u64,
+    /// State root at that height
+    pub state_root: Hash,
+}
+
+/// Brings a node up to a peer's tip
+pub struct StateSync<S: SyncSource> {
+    /// Peer to sync from
+    source: S,
+    /// What to accept from it
+    options: SyncOptions,
+    /// Verifier for snapshot proofs
+    client: LightClient,
+    /// Blocks fetched per request
+    batch_blocks: usize,
+}
+
+impl<S: SyncSource> StateSync<S> {
+    /// Sync from `source` with the default options
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            options: SyncOptions::default(),
+            client: LightClient::default(),
+            batch_blocks: SYNC_BATCH_BLOCKS,
+        }
+    }
+
+    /// Create with custom acceptance options
+    pub fn with_options(mut self, options: SyncOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Create with a custom verifier for snapshot proofs
+    pub fn with_light_client(mut self, client: LightClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Create fetching `blocks` block logs per request
+    pub fn with_batch_blocks(mut self, blocks: usize) -> Self {
+        self.batch_blocks = blocks.max(1);
+        self
+    }
+
+    /// Sync `machine` to the peer's tip: install the peer's snapshot if the
+    /// machine is fresh, then replay the blocks after it
+    pub fn sync<B: StorageBackend>(
+        &self,
+        machine: &mut ProtocolStateMachine<B>,
+        mut progress: impl FnMut(&SyncProgress),
+    ) -> Result<SyncReport> {
+        let status = self.source.status()?;
+
+        let snapshot_height = if machine.block_height() == 0 && status.block_height > 0 {
+            progress(&SyncProgress {
+                phase: SyncPhase::Snapshot,
+                height: 0,
+                target: status.block_height,
+                blocks_replayed: 0,
+            });
+            Some(self.bootstrap(machine)?)
+        } else {
+            None
+        };
+
+        let blocks_replayed = self.catch_up(machine, status.block_height, &mut progress)?;
+        let state_root = machine.state_root()?;
+
+        // The tip may have moved on since, in which case there is nothing to compare
+        if machine.block_height() == status.block_height && state_root != status.state_root {
+            return Err(Error::SyncDiverged {
+                height: status.block_height,
+                reason: format!("state root {} differs from peer's {}", state_root.to_hex(), status.state_root.to_hex()),
+            });
+        }
+
+        progress(&SyncProgress {
+            phase: SyncPhase::Complete,
+            height: machine.block_height(),
+            target: status.block_height.max(machine.block_height()),
+            blocks_replayed,
+        });
+
+        Ok(SyncReport {
+            snapshot_height,
+            blocks_replayed,
+            block_height: machine.block_height(),
+            state_root,
+        })
+    }
+
+    /// Download, verify and install the peer's snapshot, returning its height
+    pub fn bootstrap<B: StorageBackend>(&self, machine: &mut ProtocolStateMachine<B>) -> Result<u64> {
+        let mut options = self.options;
+        if options.genesis_hash.is_none() {
+            options.genesis_hash = machine.genesis_hash()?;
+        }
+
+        let snapshot = self.source.snapshot()?;
+        snapshot.verify(&options, &self.client)?;
+        machine.install_snapshot(&snapshot)?;
+
+        tracing::info!(
+            block = snapshot.manifest.block_height,
+            entries = snapshot.manifest.entry_count,
+            state_root = %snapshot.manifest.state_root.to_hex(),
+            "Installed sync snapshot"
+        );
+        Ok(snapshot.manifest.block_height)
+    }
+
+    /// Replay the peer's blocks above the machine's height up to `target`,
+    /// returning how many were replayed
+    pub fn catch_up<B: StorageBackend>(
+        &self,
+        machine: &mut ProtocolStateMachine<B>,
+        target: u64,
+        mut progress: impl FnMut(&SyncProgress),
+    ) -> Result<u64> {
+        let mut replayed = 0;
+
+        while machine.block_height() < target {
+            let blocks = self.source.blocks(machine.block_height() + 1, self.batch_blocks)?;
+            if blocks.is_empty() {
+                return Err(Error::SyncDiverged {
+                    height: machine.block_height() + 1,
+                    reason: "peer has no block log past this height".into(),
+                });
+            }
+
+            for block in blocks {
+                if block.height <= machine.block_height() {
+                    return Err(Error::SyncDiverged {
+                        height: block.height,
+                        reason: "peer returned blocks out of order".into(),
+                    });
+                }
+                replay_block(machine, block)?;
+                replayed += 1;
+
+                progress(&SyncProgress {
+                    phase: SyncPhase::CatchUp,
+                    height: machine.block_height(),
+                    target: target.max(machine.block_height()),
+                    blocks_replayed: replayed,
+                });
+            }
+        }
+
+        Ok(replayed)
+    }
+}
+
+/// Execute a peer's block and check it emits the same events
+fn replay_block<B: StorageBackend>(machine: &mut ProtocolStateMachine<B>, block: BlockRecord) -> Result<()> {
+    let height = block.height;
+    let diverged = |e: Error| Error::SyncDiverged {
+        height,
+        reason: e.to_string(),
+    };
+
+    machine.begin_block(height, block.timestamp).map_err(diverged)?;
+    for op in block.operations {
+        machine.execute(op).map_err(diverged)?;
+    }
+    let events = machine.end_block().map_err(diverged)?;
+
+    if events.merkle_root() != block.events_root {
+        return Err(Error::SyncDiverged {
+            height,
+            reason: "replayed events differ from the peer's".into(),
+        });
+    }
+    Ok(())
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::token::TokenAmount;
+    use crate::core::vault::CollateralAmount;
+    use crate::protocol::operations::{DepositCollateralOp, OpenCDPOp, Operation, UpdatePriceOp};
+    use crate::protocol::signing::SigningDomain;
+    use crate::utils::constants::SIGNATURE_LENGTH;
+    use crate::utils::crypto::{KeyPair, Signature};
+
+    /// Peer serving a snapshot taken earlier than its tip
+    struct Peer<'a> {
+        manager: &'a StateManager<InMemoryStore>,
+        snapshot: SyncSnapshot,
+    }
+
+    impl SyncSource for Peer<'_> {
+        fn status(&self) -> Result<SyncStatus> {
+            self.manager.status()
+        }
+
+        fn snapshot(&self) -> Result<SyncSnapshot> {
+            Ok(self.snapshot.clone())
+        }
+
+        fn blocks(&self, from: u64, limit: usize) -> Result<Vec<BlockRecord>> {
+            self.manager.blocks(from, limit)
+        }
+    }
+
+    fn signature() -> Signature {
+        Signature::new([0u8; SIGNATURE_LENGTH])
+    }
+
+    /// A node with a price in block 1, a CDP opened in block 2 and topped up
+    /// in blocks 3 and 4, with a snapshot taken after block 2
+    fn network() -> (ProtocolStateMachine<InMemoryStore>, SyncSnapshot) {
+        let oracle = KeyPair::generate();
+        let owner = KeyPair::generate();
+        let mut node = ProtocolStateMachine::new(InMemoryStore::new()).unwrap();
+
+        let mut price = UpdatePriceOp {
+            operator: *oracle.public_key(),
+            price_cents: 10_000_000,
+            source_count: 3,
+            confidence: 100,
+            confidence_interval: 0,
+            proof: Vec::new(),
+            nonce: 1,
+            signature: signature(),
+        };
+        price.sign(&oracle, &SigningDomain::default()).unwrap();
+        node.begin_block(1, 600).unwrap();
+        node.execute(ProtocolOperation::UpdatePrice(price)).unwrap();
+        node.end_block().unwrap();
+
+        let mut open = OpenCDPOp {
+            owner: *owner.public_key(),
+            collateral: CollateralAmount::from_sats(100_000_000),
+            initial_debt: Some(TokenAmount::from_cents(5_000_000)),
+            nonce: 1,
+            signature: signature(),
+        };
+        open.sign(&owner, &SigningDomain::default()).unwrap();
+        node.begin_block(2, 1_200).unwrap();
+        let cdp_id = match node.execute(ProtocolOperation::OpenCDP(open)).unwrap() {
+            crate::protocol::state_machine::OperationResult::OpenCDP(result) => result.cdp_id,
+            other => panic!("unexpected result: {:?}", other),
+        };
+        node.end_block().unwrap();
+
+        let snapshot = SyncSnapshot::create(node.state_manager()).unwrap();
+
+        for (height, nonce) in [(3, 2), (4, 3)] {
+            let mut deposit = DepositCollateralOp {
+                cdp_id,
+                depositor: *owner.public_key(),
+                amount: CollateralAmount::from_sats(10_000_000),
+                nonce,
+                signature: signature(),
+            };
+            deposit.sign(&owner, &SigningDomain::default()).unwrap();
+            node.begin_block(height, height * 600).unwrap();
+            node.execute(ProtocolOperation::DepositCollateral(deposit)).unwrap();
+            node.end_block().unwrap();
+        }
+
+        (node, snapshot)
+    }
+
+    #[test]
+    fn test_sync_from_snapshot_and_blocks() {
+        let (peer, snapshot) = network();
+        let options = SyncOptions::default().with_trusted_root(snapshot.manifest.state_root);
+        let source = Peer { manager: peer.state_manager(), snapshot };
+
+        // Without a trusted root or proof the snapshot is refused
+        let mut node = ProtocolStateMachine::new(InMemoryStore::new()).unwrap();
+        assert!(matches!(StateSync::new(&source).sync(&mut node, |_| {}), Err(Error::InvalidStateProof(_))));
+        assert_eq!(node.block_height(), 0);
+
+        let mut reports = Vec::new();
+        let report = StateSync::new(&source)
+            .with_options(options)
+            .sync(&mut node, |progress| reports.push(*progress))
+            .unwrap();
+
+        assert_eq!(report.snapshot_height, Some(2));
+        assert_eq!(report.blocks_replayed, 2);
+        assert_eq!(report.block_height, 4);
+        assert_eq!(report.state_root, peer.state_root().unwrap());
+        assert_eq!(node.total_collateral(), peer.total_collateral());
+        assert_eq!(reports.first().map(|p| p.phase), Some(SyncPhase::Snapshot));
+        assert_eq!(reports.last().map(|p| p.phase), Some(SyncPhase::Complete));
+
+        // Already at the tip: nothing to do
+        let report = StateSync::new(&source).with_options(options).sync(&mut node, |_| {}).unwrap();
+        assert_eq!((report.snapshot_height, report.blocks_replayed), (None, 0));
+    }
+
+    #[test]
+    fn test_tampered_snapshot_rejected() {
+        let (peer, mut snapshot) = network();
+        let client = LightClient::default();
+        let insecure = SyncOptions::default().with_insecure();
+        snapshot.verify(&insecure, &client).unwrap();
+        snapshot
+            .verify(&SyncOptions::default().with_trusted_root(snapshot.manifest.state_root), &client)
+            .unwrap();
+        assert!(snapshot.verify(&SyncOptions::default(), &client).is_err());
+
+        assert!(matches!(
+            snapshot.verify(&SyncOptions::default().with_required_proof(), &client),
+            Err(Error::InvalidStateProof(_))
+        ));
+        assert!(snapshot
+            .verify(&SyncOptions::default().with_trusted_root(Hash::sha256(b"other")), &client)
+            .is_err());
+
+        // Rewriting an entry and its checksum still fails the state root
+        let cdp_key = snapshot.entries.iter().position(|(key, _)| key.starts_with(prefixes::CDP)).unwrap();
+        let mut cdp: CDP = bincode::deserialize(&snapshot.entries[cdp_key].1).unwrap();
+        cdp.collateral_sats *= 2;
+        snapshot.entries[cdp_key].1 = bincode::serialize(&cdp).unwrap();
+        snapshot.manifest.data_checksum = checksum(&snapshot.entries).unwrap();
+        assert!(matches!(snapshot.verify(&insecure, &client), Err(Error::InvalidStateProof(_))));
+
+        let mut node = ProtocolStateMachine::new(InMemoryStore::new()).unwrap();
+        let source = Peer { manager: peer.state_manager(), snapshot };
+        assert!(StateSync::new(&source).with_options(insecure).sync(&mut node, |_| {}).is_err());
+        assert_eq!(node.block_height(), 0);
+    }
+
+    #[test]
+    fn test_divergent_block_rejected() {
+        let (peer, snapshot) = network();
+        let mut block = peer.state_manager().load_block(3).unwrap().unwrap();
+        block.events_root = Hash::sha256(b"forged");
+        peer.state_manager().save_block(&block).unwrap();
+
+        let mut node = ProtocolStateMachine::new(InMemoryStore::new()).unwrap();
+        let source = Peer { manager: peer.state_manager(), snapshot };
+        assert!(matches!(
+            StateSync::new(&source).with_options(SyncOptions::default().with_insecure()).sync(&mut node, |_| {}),
+            Err(Error::SyncDiverged { height: 3, .. })
+        ));
+    }
+}
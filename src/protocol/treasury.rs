@@ -0,0 +1,211 @@
+//! Protocol treasury.
+//!
+//! Borrowing and redemption fees are routed here instead of vanishing. Each
+//! fee is split: a governance-set share funds the savings pot's interest
+//! reserve, and the rest is paid in zkUSD to the treasury account. BTC fees
+//! are held in the vault's fee buckets.
+//!
+//! Only the treasury governor may disburse from either.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::core::config::FeeSource;
+use crate::core::token::TokenAmount;
+use crate::core::vault::CollateralAmount;
+use crate::error::{Error, Result};
+use crate::utils::constants::{BPS_DIVISOR, PUBKEY_LENGTH};
+use crate::utils::crypto::PublicKey;
+use crate::utils::decimal::{apply_bps, Rounding};
+
+/// Share of each fee that funds the savings reserve by default (50%)
+pub const DEFAULT_SAVINGS_SHARE_BPS: u64 = 5_000;
+
+/// Asset paid out by a disbursement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub enum TreasuryAsset {
+    /// zkUSD from the treasury account
+    ZkUSD(TokenAmount),
+    /// BTC from a vault fee bucket
+    Btc {
+        /// Bucket to pay from
+        source: FeeSource,
+        /// Amount to pay
+        amount: CollateralAmount,
+    },
+}
+
+/// Treasury bookkeeping
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Treasury {
+    /// Key allowed to disburse funds, if any
+    governor: Option<PublicKey>,
+    /// Share of each fee credited to the savings reserve
+    savings_share_bps: u64,
+    /// zkUSD fees charged since launch, by source
+    collected: BTreeMap<FeeSource, TokenAmount>,
+    /// zkUSD paid out since launch
+    disbursed: TokenAmount,
+    /// BTC paid out since launch
+    disbursed_btc: CollateralAmount,
+}
+
+impl Default for Treasury {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Treasury {
+    /// Create a treasury with no governor and the default savings share
+    pub fn new() -> Self {
+        Self {
+            governor: None,
+            savings_share_bps: DEFAULT_SAVINGS_SHARE_BPS,
+            collected: BTreeMap::new(),
+            disbursed: TokenAmount::ZERO,
+            disbursed_btc: CollateralAmount::ZERO,
+        }
+    }
+
+    /// Set the governor
+    pub fn with_governor(mut self, governor: PublicKey) -> Self {
+        self.governor = Some(governor);
+        self
+    }
+
+    /// Account holding the treasury's zkUSD.
+    ///
+    /// Not a valid curve point, so nobody holds its key: funds only leave
+    /// through a disbursement.
+    pub fn account() -> PublicKey {
+        let mut bytes = [0u8; PUBKEY_LENGTH];
+        bytes[1..15].copy_from_slice(b"zkusd/treasury");
+        PublicKey::new(bytes)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // FEE ROUTING
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Record a fee and split it into `(to_savings, to_treasury)`
+    pub fn route(&mut self, source: FeeSource, fee: TokenAmount) -> Result<(TokenAmount, TokenAmount)> {
+        let savings_cents = apply_bps(fee.cents(), self.savings_share_bps, Rounding::Floor)?;
+        let to_savings = TokenAmount::from_cents(savings_cents);
+        let to_treasury = fee.saturating_sub(to_savings);
+
+        let collected = self.collected.entry(source).or_insert(TokenAmount::ZERO);
+        *collected = collected.saturating_add(fee);
+
+        Ok((to_savings, to_treasury))
+    }
+
+    /// Set the share of each fee credited to the savings reserve
+    pub fn set_savings_share(&mut self, share_bps: u64) -> Result<()> {
+        if share_bps > BPS_DIVISOR {
+            return Err(Error::InvalidParameter {
+                name: "savings_share".into(),
+                reason: format!("{}bps exceeds {}bps", share_bps, BPS_DIVISOR),
+            });
+        }
+        self.savings_share_bps = share_bps;
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // DISBURSEMENT
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Check `signer` may disburse funds
+    pub fn authorize(&self, signer: &PublicKey) -> Result<()> {
+        match &self.governor {
+            Some(governor) if governor == signer => Ok(()),
+            Some(_) => Err(Error::Unauthorized("Not the treasury governor".into())),
+            None => Err(Error::Unauthorized("Treasury has no governor".into())),
+        }
+    }
+
+    /// Record a disbursement the caller has paid out
+    pub fn record_disbursement(&mut self, asset: &TreasuryAsset) {
+        match asset {
+            TreasuryAsset::ZkUSD(amount) => {
+                self.disbursed = self.disbursed.saturating_add(*amount);
+            }
+            TreasuryAsset::Btc { amount, .. } => {
+                self.disbursed_btc = self.disbursed_btc.saturating_add(*amount);
+            }
+        }
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // QUERIES
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Governor, if any
+    pub fn governor(&self) -> Option<&PublicKey> {
+        self.governor.as_ref()
+    }
+
+    /// Share of each fee credited to the savings reserve
+    pub fn savings_share_bps(&self) -> u64 {
+        self.savings_share_bps
+    }
+
+    /// zkUSD fees charged for `source` since launch
+    pub fn collected(&self, source: FeeSource) -> TokenAmount {
+        self.collected.get(&source).copied().unwrap_or(TokenAmount::ZERO)
+    }
+
+    /// zkUSD fees charged since launch
+    pub fn total_collected(&self) -> TokenAmount {
+        self.collected.values().fold(TokenAmount::ZERO, |total, fee| total.saturating_add(*fee))
+    }
+
+    /// zkUSD paid out since launch
+    pub fn disbursed(&self) -> TokenAmount {
+        self.disbursed
+    }
+
+    /// BTC paid out since launch
+    pub fn disbursed_btc(&self) -> CollateralAmount {
+        self.disbursed_btc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::crypto::KeyPair;
+
+    #[test]
+    fn test_route_splits_fees() {
+        let mut treasury = Treasury::new();
+
+        let (to_savings, to_treasury) = treasury.route(FeeSource::Borrowing, TokenAmount::from_cents(1_001)).unwrap();
+        assert_eq!(to_savings.cents(), 500);
+        assert_eq!(to_treasury.cents(), 501);
+
+        treasury.set_savings_share(0).unwrap();
+        let (to_savings, to_treasury) = treasury.route(FeeSource::Redemption, TokenAmount::from_cents(300)).unwrap();
+        assert!(to_savings.is_zero());
+        assert_eq!(to_treasury.cents(), 300);
+
+        assert!(treasury.set_savings_share(BPS_DIVISOR + 1).is_err());
+        assert_eq!(treasury.collected(FeeSource::Borrowing).cents(), 1_001);
+        assert_eq!(treasury.total_collected().cents(), 1_301);
+    }
+
+    #[test]
+    fn test_only_governor_authorized() {
+        let governor = KeyPair::generate();
+        let other = KeyPair::generate();
+
+        assert!(Treasury::new().authorize(governor.public_key()).is_err());
+
+        let treasury = Treasury::new().with_governor(*governor.public_key());
+        assert!(treasury.authorize(governor.public_key()).is_ok());
+        assert!(matches!(treasury.authorize(other.public_key()), Err(Error::Unauthorized(_))));
+        assert!(!Treasury::account().is_valid());
+    }
+}
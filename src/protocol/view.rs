@@ -0,0 +1,173 @@
+//! Read-only protocol views.
+//!
+//! The state machine publishes a [`ProtocolSnapshotView`] at the end of every
+//! block. Readers such as RPC handlers hold a [`SnapshotHandle`] and query the
+//! latest view without touching the executor: publishing swaps an `Arc`
+//! under a short write lock, and a loaded view stays valid (and consistent
+//! with a single block) for as long as the reader keeps it.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use crate::core::cdp::{CDP, CDPId, CDPManager};
+use crate::core::token::TokenAmount;
+use crate::core::vault::CollateralAmount;
+use crate::monitoring::RiskSnapshot;
+use crate::protocol::pauses::PausableOperation;
+use crate::utils::crypto::PublicKey;
+use crate::utils::math::calculate_collateral_ratio;
+
+/// Protocol state as of the end of a block
+#[derive(Debug, Clone)]
+pub struct ProtocolSnapshotView {
+    /// Block the view was taken at
+    pub block_height: u64,
+    /// Block timestamp
+    pub timestamp: u64,
+    /// BTC price in cents
+    pub btc_price: u64,
+    /// zkUSD supply
+    pub total_supply: TokenAmount,
+    /// Collateral locked in the vault
+    pub total_collateral: CollateralAmount,
+    /// Outstanding debt across all CDPs in cents
+    pub total_debt: u64,
+    /// Stability pool deposits
+    pub pool_deposits: TokenAmount,
+    /// Effective minimum collateral ratio
+    pub min_collateral_ratio: u64,
+    /// Whether the protocol is paused
+    pub paused: bool,
+    /// Operations paused on their own
+    pub paused_operations: Vec<PausableOperation>,
+    /// Whether the protocol is in recovery mode
+    pub recovery_mode: bool,
+    /// Latest system risk snapshot
+    pub risk: Option<RiskSnapshot>,
+    /// CDPs
+    cdps: CDPManager,
+    /// zkUSD balances
+    balances: HashMap<PublicKey, TokenAmount>,
+}
+
+impl Default for ProtocolSnapshotView {
+    fn default() -> Self {
+        Self {
+            block_height: 0,
+            timestamp: 0,
+            btc_price: 0,
+            total_supply: TokenAmount::ZERO,
+            total_collateral: CollateralAmount::ZERO,
+            total_debt: 0,
+            pool_deposits: TokenAmount::ZERO,
+            min_collateral_ratio: 0,
+            paused: false,
+            paused_operations: Vec::new(),
+            recovery_mode: false,
+            risk: None,
+            cdps: CDPManager::new(),
+            balances: HashMap::new(),
+        }
+    }
+}
+
+impl ProtocolSnapshotView {
+    /// Create a view over `cdps` and `balances`; the scalar fields start
+    /// zeroed and are filled in by the publisher
+    pub fn new(cdps: CDPManager, balances: HashMap<PublicKey, TokenAmount>) -> Self {
+        Self {
+            cdps,
+            balances,
+            ..Self::default()
+        }
+    }
+
+    /// Look up a CDP
+    pub fn cdp(&self, id: &CDPId) -> Option<&CDP> {
+        self.cdps.get(id)
+    }
+
+    /// CDPs owned by `owner`
+    pub fn cdps_by_owner(&self, owner: &PublicKey) -> Vec<&CDP> {
+        self.cdps.get_by_owner(owner)
+    }
+
+    /// All CDPs
+    pub fn cdps(&self) -> &CDPManager {
+        &self.cdps
+    }
+
+    /// zkUSD balance of an account
+    pub fn balance(&self, account: &PublicKey) -> TokenAmount {
+        self.balances.get(account).copied().unwrap_or(TokenAmount::ZERO)
+    }
+
+    /// Total collateral ratio in percent (`u64::MAX` without debt)
+    pub fn tcr(&self) -> u64 {
+        calculate_collateral_ratio(self.total_collateral.sats(), self.btc_price, self.total_debt)
+            .unwrap_or(0)
+    }
+}
+
+/// Shared handle onto the latest published view
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotHandle {
+    current: Arc<RwLock<Arc<ProtocolSnapshotView>>>,
+}
+
+impl SnapshotHandle {
+    /// Create a handle holding an empty view
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Latest published view
+    pub fn load(&self) -> Arc<ProtocolSnapshotView> {
+        match self.current.read() {
+            Ok(view) => Arc::clone(&view),
+            Err(poisoned) => Arc::clone(&poisoned.into_inner()),
+        }
+    }
+
+    /// Replace the view seen by all handles
+    pub fn publish(&self, view: ProtocolSnapshotView) {
+        let view = Arc::new(view);
+        // A panicked reader must not stop publication
+        let mut current = match self.current.write() {
+            Ok(current) => current,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        *current = view;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::crypto::KeyPair;
+
+    #[test]
+    fn test_loaded_view_outlives_publish() {
+        let owner = *KeyPair::generate().public_key();
+        let handle = SnapshotHandle::new();
+        let reader = handle.clone();
+
+        let mut view = ProtocolSnapshotView::new(
+            CDPManager::new(),
+            HashMap::from([(owner, TokenAmount::from_cents(500))]),
+        );
+        view.block_height = 1;
+        handle.publish(view);
+
+        let first = reader.load();
+        handle.publish(ProtocolSnapshotView {
+            block_height: 2,
+            ..ProtocolSnapshotView::default()
+        });
+
+        assert_eq!(first.block_height, 1);
+        assert_eq!(first.balance(&owner).cents(), 500);
+        assert_eq!(reader.load().block_height, 2);
+        assert_eq!(reader.load().balance(&owner).cents(), 0);
+    }
+}
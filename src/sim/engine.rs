@@ -0,0 +1,485 @@
+//! Block-by-block scenario execution.
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::core::cdp::CDPId;
+use crate::core::token::TokenAmount;
+use crate::core::vault::CollateralAmount;
+use crate::error::Result;
+use crate::oracle::price_feed::{PairConfig, TradingPair, RATE_PRECISION};
+use crate::protocol::events::ProtocolEvent;
+use crate::protocol::operations::*;
+use crate::protocol::signing::SigningDomain;
+use crate::protocol::state_machine::{OperationResult, ProtocolStateMachine};
+use crate::sim::report::{BlockReport, SimulationReport};
+use crate::sim::scenario::{Behavior, Scenario, ShockKind};
+use crate::storage::backend::InMemoryStore;
+use crate::utils::constants::{
+    BLOCK_TIME_SECS, BPS_DIVISOR, MIN_ORACLE_SOURCES, RATIO_PRECISION, SATS_PER_BTC, SIGNATURE_LENGTH,
+};
+use crate::utils::crypto::{Hash, KeyPair, PublicKey, Signature};
+
+/// A simulated user
+struct SimUser {
+    key: KeyPair,
+    cdp: Option<CDPId>,
+}
+
+/// Runs a [`Scenario`] through an in-memory state machine.
+///
+/// Every key is derived from the scenario and user names and every timestamp
+/// from the block height, so the same scenario always produces the same
+/// report.
+pub struct Simulation {
+    scenario: Scenario,
+    machine: ProtocolStateMachine<InMemoryStore>,
+    oracle: KeyPair,
+    users: Vec<SimUser>,
+    nonces: HashMap<PublicKey, u64>,
+    failed: u32,
+}
+
+impl Simulation {
+    /// Prepare a simulation
+    pub fn new(scenario: Scenario) -> Result<Self> {
+        scenario.validate()?;
+
+        let mut machine = ProtocolStateMachine::new(InMemoryStore::new())?;
+        if let Some(params) = &scenario.params {
+            machine = machine.with_params(params.clone());
+        }
+        if let Some(peg) = scenario.peg_stability {
+            machine = machine.with_peg_stability(peg)?;
+        } else if !scenario.peg_path.is_empty() {
+            machine = machine.with_price_pair(TradingPair::zkusd_usd(), PairConfig::default())?;
+        }
+
+        let oracle = derive_key(&scenario.name, "oracle")?;
+        let users = scenario
+            .users
+            .iter()
+            .map(|spec| Ok(SimUser { key: derive_key(&scenario.name, &spec.name)?, cdp: None }))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            scenario,
+            machine,
+            oracle,
+            users,
+            nonces: HashMap::new(),
+            failed: 0,
+        })
+    }
+
+    /// Run every block of the scenario
+    pub fn run(mut self) -> Result<SimulationReport> {
+        let mut blocks = Vec::with_capacity(self.scenario.blocks as usize);
+        for block in 0..self.scenario.blocks {
+            blocks.push(self.run_block(block)?);
+        }
+
+        Ok(SimulationReport { scenario: self.scenario.name.clone(), blocks })
+    }
+
+    fn run_block(&mut self, block: u64) -> Result<BlockReport> {
+        let height = block + 1;
+        self.failed = 0;
+        self.machine.begin_block(height, height * BLOCK_TIME_SECS)?;
+
+        let price = self.scenario.price_at(block);
+        self.update_price(price)?;
+        let peg_price = self.scenario.peg_price_at(block);
+        if let Some(price_bps) = peg_price {
+            self.update_peg_price(price_bps)?;
+        }
+
+        if block == 0 {
+            self.open_positions();
+        }
+        self.run_behaviors(block);
+        self.apply_shocks(block);
+        self.liquidate_all(block)?;
+
+        let tcr = self.machine.tcr()?;
+        let recovery_mode = self.machine.is_recovery_mode();
+        let events = self.machine.end_block()?;
+
+        let mut report = BlockReport {
+            block,
+            price_cents: price,
+            tcr,
+            recovery_mode,
+            liquidations: 0,
+            liquidated_debt_cents: 0,
+            redeemed_cents: 0,
+            pool_depth_cents: self.machine.stability_pool().total_deposits().cents(),
+            savings_locked_cents: self.machine.savings().total_locked().cents(),
+            treasury_balance_cents: self.machine.treasury_balance().cents(),
+            total_supply_cents: self.machine.total_supply().cents(),
+            total_collateral_sats: self.machine.total_collateral().sats(),
+            failed_operations: self.failed,
+            zkusd_price_bps: peg_price,
+            borrowing_fee_bps: self.machine.config().params.borrowing_fee_bps,
+            redemption_fee_floor_bps: self.machine.config().params.redemption_fee_floor_bps,
+        };
+        for event in events.events() {
+            match event {
+                ProtocolEvent::CDPLiquidated(e) => {
+                    report.liquidations += 1;
+                    report.liquidated_debt_cents += e.debt_covered.cents();
+                }
+                ProtocolEvent::Redemption(e) => report.redeemed_cents += e.zkusd_amount.cents(),
+                _ => {}
+            }
+        }
+
+        Ok(report)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // ACTORS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    fn update_price(&mut self, price_cents: u64) -> Result<()> {
+        let oracle = self.oracle.clone();
+        let nonce = self.next_nonce(oracle.public_key());
+        let op = signed(
+            UpdatePriceOp {
+                operator: *oracle.public_key(),
+                price_cents,
+                source_count: MIN_ORACLE_SOURCES as u8,
+                confidence: 100,
+                confidence_interval: 0,
+                proof: Vec::new(),
+                nonce,
+                signature: blank(),
+            },
+            &oracle,
+            self.machine.signing_domain(),
+        )?;
+        self.machine.execute(ProtocolOperation::UpdatePrice(op))?;
+        self.nonces.insert(*oracle.public_key(), nonce);
+        Ok(())
+    }
+
+    fn update_peg_price(&mut self, price_bps: u64) -> Result<()> {
+        let oracle = self.oracle.clone();
+        let nonce = self.next_nonce(oracle.public_key());
+        let op = signed(
+            UpdatePairPriceOp {
+                operator: *oracle.public_key(),
+                pair: TradingPair::zkusd_usd(),
+                rate: price_bps * RATE_PRECISION / BPS_DIVISOR,
+                source_count: MIN_ORACLE_SOURCES as u8,
+                nonce,
+                signature: blank(),
+            },
+            &oracle,
+            self.machine.signing_domain(),
+        )?;
+        self.machine.execute(ProtocolOperation::UpdatePairPrice(op))?;
+        self.nonces.insert(*oracle.public_key(), nonce);
+        Ok(())
+    }
+
+    fn open_positions(&mut self) {
+        for i in 0..self.users.len() {
+            let spec = self.scenario.users[i].clone();
+            if spec.collateral_sats == 0 {
+                continue;
+            }
+
+            let owner = *self.users[i].key.public_key();
+            let result = self.submit(i, |nonce| {
+                Ok(ProtocolOperation::OpenCDP(OpenCDPOp {
+                    owner,
+                    collateral: CollateralAmount::from_sats(spec.collateral_sats),
+                    initial_debt: (spec.debt_cents > 0).then_some(TokenAmount::from_cents(spec.debt_cents)),
+                    nonce,
+                    signature: blank(),
+                }))
+            });
+            if let Some(OperationResult::OpenCDP(r)) = result {
+                self.users[i].cdp = Some(r.cdp_id);
+            }
+
+            if spec.pool_deposit_cents > 0 {
+                self.submit(i, |nonce| {
+                    Ok(ProtocolOperation::StabilityDeposit(StabilityDepositOp {
+                        depositor: owner,
+                        amount: TokenAmount::from_cents(spec.pool_deposit_cents),
+                        frontend: None,
+                        nonce,
+                        signature: blank(),
+                    }))
+                });
+            }
+        }
+    }
+
+    fn run_behaviors(&mut self, block: u64) {
+        for i in 0..self.users.len() {
+            match self.scenario.users[i].behavior {
+                Behavior::Passive => {}
+                Behavior::MaintainRatio { min_ratio, target_ratio } => {
+                    let Some(cdp) = self.users[i].cdp.and_then(|id| self.machine.get_cdp(&id)) else {
+                        continue;
+                    };
+                    let price = self.machine.price();
+                    if cdp.debt_cents == 0 || cdp.calculate_ratio(price) >= min_ratio {
+                        continue;
+                    }
+
+                    // Collateral needed for the target ratio, rounded up
+                    let needed = (cdp.debt_cents as u128 * target_ratio as u128 * SATS_PER_BTC as u128)
+                        .div_ceil(price as u128 * RATIO_PRECISION as u128);
+                    let top_up = (needed as u64).saturating_sub(cdp.collateral_sats);
+                    self.deposit_collateral(i, top_up);
+                }
+                Behavior::Redeemer { amount_cents, every_blocks } => {
+                    if block > 0 && block % every_blocks == 0 {
+                        self.redeem(i, amount_cents);
+                    }
+                }
+            }
+        }
+    }
+
+    fn apply_shocks(&mut self, block: u64) {
+        let shocks: Vec<ShockKind> = self
+            .scenario
+            .shocks
+            .iter()
+            .filter(|s| s.block == block)
+            .map(|s| s.kind.clone())
+            .collect();
+
+        for shock in shocks {
+            let Some(i) = self.scenario.users.iter().position(|u| u.name == shock.user()) else {
+                continue;
+            };
+            match shock {
+                ShockKind::PoolWithdrawal { fraction_bps, .. } => {
+                    let depositor = *self.users[i].key.public_key();
+                    let deposit = self.machine.stability_deposit(&depositor).unwrap_or(TokenAmount::ZERO);
+                    let amount = deposit.cents() as u128 * fraction_bps as u128 / BPS_DIVISOR as u128;
+                    self.submit(i, |nonce| {
+                        Ok(ProtocolOperation::StabilityWithdraw(StabilityWithdrawOp {
+                            depositor,
+                            amount: TokenAmount::from_cents(amount as u64),
+                            nonce,
+                            signature: blank(),
+                        }))
+                    });
+                }
+                ShockKind::Redemption { amount_cents, .. } => self.redeem(i, amount_cents),
+                ShockKind::CollateralDeposit { amount_sats, .. } => self.deposit_collateral(i, amount_sats),
+            }
+        }
+    }
+
+    /// Liquidate every CDP below the MCR, one keeper per liquidation
+    fn liquidate_all(&mut self, block: u64) -> Result<()> {
+        let price = self.machine.price();
+        let mcr = self.machine.config().effective_mcr();
+        let targets: Vec<CDPId> = self
+            .users
+            .iter()
+            .filter_map(|u| u.cdp)
+            .filter(|id| self.machine.get_cdp(id).is_some_and(|cdp| cdp.is_liquidatable(price, mcr)))
+            .collect();
+
+        for (n, cdp_id) in targets.into_iter().enumerate() {
+            let keeper = derive_key(&self.scenario.name, &format!("keeper-{}-{}", block, n))?;
+            let nonce = self.next_nonce(keeper.public_key());
+            let op = signed(
+                LiquidateCDPOp { cdp_id, liquidator: *keeper.public_key(), nonce, signature: blank() },
+                &keeper,
+                self.machine.signing_domain(),
+            )?;
+            self.record(keeper.public_key(), nonce, ProtocolOperation::LiquidateCDP(op));
+        }
+        Ok(())
+    }
+
+    fn deposit_collateral(&mut self, user: usize, amount_sats: u64) {
+        let Some(cdp_id) = self.users[user].cdp else { return };
+        if amount_sats == 0 {
+            return;
+        }
+        let depositor = *self.users[user].key.public_key();
+        self.submit(user, |nonce| {
+            Ok(ProtocolOperation::DepositCollateral(DepositCollateralOp {
+                cdp_id,
+                depositor,
+                amount: CollateralAmount::from_sats(amount_sats),
+                nonce,
+                signature: blank(),
+            }))
+        });
+    }
+
+    fn redeem(&mut self, user: usize, amount_cents: u64) {
+        let redeemer = *self.users[user].key.public_key();
+        self.submit(user, |nonce| {
+            Ok(ProtocolOperation::Redeem(RedeemOp {
+                redeemer,
+                amount: TokenAmount::from_cents(amount_cents),
+                max_fee_bps: BPS_DIVISOR,
+                first_cdp_hint: None,
+                last_cdp_hint: None,
+                max_cdps: 0,
+                nonce,
+                signature: blank(),
+            }))
+        });
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // SUBMISSION
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    fn next_nonce(&self, signer: &PublicKey) -> u64 {
+        self.nonces.get(signer).copied().unwrap_or(0) + 1
+    }
+
+    /// Build, sign and execute an operation for `user`
+    fn submit(
+        &mut self,
+        user: usize,
+        build: impl FnOnce(u64) -> Result<ProtocolOperation>,
+    ) -> Option<OperationResult> {
+        let key = self.users[user].key.clone();
+        let nonce = self.next_nonce(key.public_key());
+        let op = build(nonce).and_then(|op| sign_operation(op, &key, self.machine.signing_domain()));
+        match op {
+            Ok(op) => self.record(key.public_key(), nonce, op),
+            Err(e) => {
+                tracing::debug!("Simulated operation could not be built: {}", e);
+                self.failed += 1;
+                None
+            }
+        }
+    }
+
+    /// Execute a signed operation, counting rejections
+    fn record(&mut self, signer: &PublicKey, nonce: u64, op: ProtocolOperation) -> Option<OperationResult> {
+        let op_type = op.operation_type();
+        match self.machine.execute(op) {
+            Ok(result) => {
+                self.nonces.insert(*signer, nonce);
+                Some(result)
+            }
+            Err(e) => {
+                tracing::debug!("Simulated {} rejected: {}", op_type, e);
+                self.failed += 1;
+                None
+            }
+        }
+    }
+}
+
+/// Deterministic key for a named actor in a scenario
+fn derive_key(scenario: &str, actor: &str) -> Result<KeyPair> {
+    let seed = Hash::sha256(format!("zkusd-sim:{}:{}", scenario, actor).as_bytes());
+    KeyPair::from_bytes(seed.as_bytes())
+}
+
+fn blank() -> Signature {
+    Signature::new([0u8; SIGNATURE_LENGTH])
+}
+
+fn signed<O: Operation + Clone + Serialize>(mut op: O, key: &KeyPair, domain: &SigningDomain) -> Result<O> {
+    op.sign(key, domain)?;
+    Ok(op)
+}
+
+fn sign_operation(op: ProtocolOperation, key: &KeyPair, domain: &SigningDomain) -> Result<ProtocolOperation> {
+    Ok(match op {
+        ProtocolOperation::OpenCDP(op) => ProtocolOperation::OpenCDP(signed(op, key, domain)?),
+        ProtocolOperation::DepositCollateral(op) => ProtocolOperation::DepositCollateral(signed(op, key, domain)?),
+        ProtocolOperation::StabilityDeposit(op) => ProtocolOperation::StabilityDeposit(signed(op, key, domain)?),
+        ProtocolOperation::StabilityWithdraw(op) => ProtocolOperation::StabilityWithdraw(signed(op, key, domain)?),
+        ProtocolOperation::Redeem(op) => ProtocolOperation::Redeem(signed(op, key, domain)?),
+        other => other,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::ProtocolParams;
+
+    const CRASH: &str = r#"{
+        "name": "crash",
+        "blocks": 8,
+        "price_path": [
+            { "block": 0, "price_cents": 10000000 },
+            { "block": 4, "price_cents": 6000000 }
+        ],
+        "users": [
+            { "name": "whale", "collateral_sats": 500000000, "debt_cents": 10000000,
+              "pool_deposit_cents": 5000000 },
+            { "name": "degen", "collateral_sats": 10000000, "debt_cents": 800000 },
+            { "name": "careful", "collateral_sats": 10000000, "debt_cents": 500000,
+              "behavior": { "type": "maintain_ratio", "min_ratio": 150, "target_ratio": 200 } },
+            { "name": "arb", "collateral_sats": 100000000, "debt_cents": 2000000,
+              "behavior": { "type": "redeemer", "amount_cents": 100000, "every_blocks": 2 } }
+        ]
+    }"#;
+
+    #[test]
+    fn test_crash_liquidates_risky_cdp() {
+        let scenario = Scenario::from_json(CRASH).unwrap();
+        let report = Simulation::new(scenario).unwrap().run().unwrap();
+
+        assert_eq!(report.blocks.len(), 8);
+        assert_eq!(report.blocks[0].pool_depth_cents, 5_000_000);
+
+        // degen: 0.1 BTC against $8,000 drops below 110% on the way to $60,000
+        assert_eq!(report.total_liquidations(), 1);
+        assert!(report.blocks.iter().any(|b| b.pool_depth_cents < 5_000_000));
+
+        // careful keeps topping up and survives
+        assert!(report.total_redeemed_cents() > 0);
+        assert!(report.min_tcr().unwrap() >= 110);
+    }
+
+    #[test]
+    fn test_depeg_steers_fees() {
+        let scenario = Scenario::from_json(r#"{
+            "name": "depeg",
+            "blocks": 60,
+            "price_path": [{ "block": 0, "price_cents": 10000000 }],
+            "peg_path": [
+                { "block": 0, "price_bps": 10000 },
+                { "block": 5, "price_bps": 9800 },
+                { "block": 25, "price_bps": 9800 },
+                { "block": 30, "price_bps": 10000 }
+            ],
+            "peg_stability": { "interval_blocks": 1 },
+            "users": [{ "name": "whale", "collateral_sats": 500000000, "debt_cents": 10000000 }]
+        }"#).unwrap();
+        let report = Simulation::new(scenario).unwrap().run().unwrap();
+
+        let defaults = ProtocolParams::default();
+        let depegged = &report.blocks[24];
+        assert_eq!(depegged.zkusd_price_bps, Some(9_800));
+        assert!(depegged.borrowing_fee_bps > defaults.borrowing_fee_bps);
+        assert!(depegged.redemption_fee_floor_bps < defaults.redemption_fee_floor_bps);
+
+        let recovered = report.blocks.last().unwrap();
+        assert_eq!(recovered.borrowing_fee_bps, defaults.borrowing_fee_bps);
+        assert_eq!(recovered.redemption_fee_floor_bps, defaults.redemption_fee_floor_bps);
+        assert_eq!(report.max_borrowing_fee_bps(), Some(depegged.borrowing_fee_bps));
+    }
+
+    #[test]
+    fn test_runs_are_deterministic() {
+        let run = || {
+            Simulation::new(Scenario::from_json(CRASH).unwrap()).unwrap().run().unwrap()
+        };
+        assert_eq!(run(), run());
+    }
+}
@@ -0,0 +1,14 @@
+//! Simulation module - Deterministic protocol scenarios.
+//!
+//! Scenarios describe a BTC price path, a set of users with starting
+//! positions and behaviors, and one-off shocks. The engine replays them block
+//! by block against an in-memory state machine and reports TCR, liquidations,
+//! redemptions and stability pool depth for every block.
+
+pub mod engine;
+pub mod report;
+pub mod scenario;
+
+pub use engine::Simulation;
+pub use report::{BlockReport, SimulationReport};
+pub use scenario::Scenario;
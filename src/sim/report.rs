@@ -0,0 +1,76 @@
+//! Simulation reports.
+
+use serde::{Deserialize, Serialize};
+
+/// State of the protocol at the end of a simulated block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockReport {
+    /// Block offset from the start of the scenario
+    pub block: u64,
+    /// BTC price in cents
+    pub price_cents: u64,
+    /// Total collateralization ratio (percentage, `u64::MAX` without debt)
+    pub tcr: u64,
+    /// Whether the protocol is in recovery mode
+    pub recovery_mode: bool,
+    /// CDPs liquidated in this block
+    pub liquidations: u32,
+    /// Debt cleared by those liquidations
+    pub liquidated_debt_cents: u64,
+    /// zkUSD redeemed in this block
+    pub redeemed_cents: u64,
+    /// zkUSD in the stability pool
+    pub pool_depth_cents: u64,
+    /// zkUSD locked in the savings pot
+    pub savings_locked_cents: u64,
+    /// zkUSD held by the treasury
+    pub treasury_balance_cents: u64,
+    /// zkUSD in circulation
+    pub total_supply_cents: u64,
+    /// Collateral locked in the vault
+    pub total_collateral_sats: u64,
+    /// Scenario operations the protocol rejected
+    pub failed_operations: u32,
+    /// zkUSD market price in basis points of $1, if the scenario has one
+    pub zkusd_price_bps: Option<u64>,
+    /// Borrowing fee parameter at the end of the block
+    pub borrowing_fee_bps: u64,
+    /// Redemption fee floor parameter at the end of the block
+    pub redemption_fee_floor_bps: u64,
+}
+
+/// Result of a whole simulation run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulationReport {
+    /// Scenario name
+    pub scenario: String,
+    /// One report per block
+    pub blocks: Vec<BlockReport>,
+}
+
+impl SimulationReport {
+    /// Lowest TCR seen during the run
+    pub fn min_tcr(&self) -> Option<u64> {
+        self.blocks.iter().map(|b| b.tcr).min()
+    }
+
+    /// Total number of liquidations
+    pub fn total_liquidations(&self) -> u32 {
+        self.blocks.iter().map(|b| b.liquidations).sum()
+    }
+
+    /// Total zkUSD redeemed
+    pub fn total_redeemed_cents(&self) -> u64 {
+        self.blocks.iter().map(|b| b.redeemed_cents).sum()
+    }
+
+    /// Highest borrowing fee parameter seen during the run
+    pub fn max_borrowing_fee_bps(&self) -> Option<u64> {
+        self.blocks.iter().map(|b| b.borrowing_fee_bps).max()
+    }
+
+    /// Blocks spent in recovery mode
+    pub fn recovery_mode_blocks(&self) -> usize {
+        self.blocks.iter().filter(|b| b.recovery_mode).count()
+    }
+}
@@ -0,0 +1,333 @@
+//! Simulation scenario files.
+//!
+//! A scenario is a JSON document describing how many blocks to run, the BTC
+//! price path, the simulated users and any one-off shocks:
+//!
+//! ```json
+//! {
+//!   "name": "crash",
+//!   "blocks": 20,
+//!   "price_path": [
+//!     { "block": 0, "price_cents": 10000000 },
+//!     { "block": 10, "price_cents": 6000000 }
+//!   ],
+//!   "users": [
+//!     { "name": "whale", "collateral_sats": 500000000, "debt_cents": 20000000,
+//!       "pool_deposit_cents": 10000000 },
+//!     { "name": "degen", "collateral_sats": 10000000, "debt_cents": 800000 },
+//!     { "name": "careful", "collateral_sats": 20000000, "debt_cents": 800000,
+//!       "behavior": { "type": "maintain_ratio", "min_ratio": 150, "target_ratio": 200 } }
+//!   ],
+//!   "shocks": [
+//!     { "block": 12, "type": "pool_withdrawal", "user": "whale", "fraction_bps": 5000 }
+//!   ]
+//! }
+//! ```
+//!
+//! A scenario may also give a zkUSD market price path in basis points of $1
+//! (`peg_path`) and enable the peg stability controller (`peg_stability`,
+//! with any [`PegConfig`] fields left out taking their defaults).
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::core::config::{ProtocolConfig, ProtocolParams};
+use crate::core::peg::PegConfig;
+use crate::error::{Error, Result};
+use crate::utils::constants::BPS_DIVISOR;
+
+/// A complete simulation scenario
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// Scenario name, also used to derive user keys
+    pub name: String,
+    /// Free-form description
+    #[serde(default)]
+    pub description: String,
+    /// Number of blocks to simulate
+    pub blocks: u64,
+    /// Protocol parameters to run with (defaults if omitted)
+    #[serde(default)]
+    pub params: Option<ProtocolParams>,
+    /// Price points, interpolated linearly between blocks
+    pub price_path: Vec<PricePoint>,
+    /// zkUSD market price points, interpolated like `price_path`
+    #[serde(default)]
+    pub peg_path: Vec<PegPoint>,
+    /// Peg stability controller settings (disabled if omitted)
+    #[serde(default)]
+    pub peg_stability: Option<PegConfig>,
+    /// Simulated users
+    pub users: Vec<UserSpec>,
+    /// One-off events
+    #[serde(default)]
+    pub shocks: Vec<Shock>,
+}
+
+/// BTC price at a block
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PricePoint {
+    /// Block offset from the start of the scenario
+    pub block: u64,
+    /// BTC price in cents
+    pub price_cents: u64,
+}
+
+/// zkUSD market price at a block
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PegPoint {
+    /// Block offset from the start of the scenario
+    pub block: u64,
+    /// zkUSD price in basis points of $1
+    pub price_bps: u64,
+}
+
+/// A simulated user and their starting position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UserSpec {
+    /// Unique name
+    pub name: String,
+    /// Collateral to open a CDP with (no CDP if zero)
+    #[serde(default)]
+    pub collateral_sats: u64,
+    /// Debt to mint when opening the CDP
+    #[serde(default)]
+    pub debt_cents: u64,
+    /// Amount of the minted zkUSD to put in the stability pool
+    #[serde(default)]
+    pub pool_deposit_cents: u64,
+    /// What the user does every block
+    #[serde(default)]
+    pub behavior: Behavior,
+}
+
+/// Per-block user behavior
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Behavior {
+    /// Does nothing after opening
+    #[default]
+    Passive,
+    /// Adds collateral whenever the CDP falls below `min_ratio`, bringing it
+    /// back to `target_ratio`
+    MaintainRatio {
+        /// Ratio that triggers a top-up (percentage)
+        min_ratio: u64,
+        /// Ratio to top up to (percentage)
+        target_ratio: u64,
+    },
+    /// Redeems zkUSD at a fixed cadence
+    Redeemer {
+        /// Amount per redemption
+        amount_cents: u64,
+        /// Blocks between redemptions
+        every_blocks: u64,
+    },
+}
+
+/// A one-off event at a block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Shock {
+    /// Block offset at which the shock happens
+    pub block: u64,
+    /// What happens
+    #[serde(flatten)]
+    pub kind: ShockKind,
+}
+
+/// Kinds of shocks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ShockKind {
+    /// A user pulls part of their stability pool deposit
+    PoolWithdrawal {
+        /// User name
+        user: String,
+        /// Share of the deposit to withdraw (basis points)
+        fraction_bps: u64,
+    },
+    /// A user redeems zkUSD once
+    Redemption {
+        /// User name
+        user: String,
+        /// Amount to redeem
+        amount_cents: u64,
+    },
+    /// A user adds collateral to their CDP
+    CollateralDeposit {
+        /// User name
+        user: String,
+        /// Amount to add
+        amount_sats: u64,
+    },
+}
+
+impl ShockKind {
+    /// Name of the user the shock applies to
+    pub fn user(&self) -> &str {
+        match self {
+            ShockKind::PoolWithdrawal { user, .. }
+            | ShockKind::Redemption { user, .. }
+            | ShockKind::CollateralDeposit { user, .. } => user,
+        }
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> Error {
+    Error::InvalidParameter { name: "scenario".into(), reason: reason.into() }
+}
+
+impl Scenario {
+    /// Parse and validate a scenario from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        let scenario: Self =
+            serde_json::from_str(json).map_err(|e| Error::Deserialization(e.to_string()))?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    /// Load a scenario file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| Error::Internal(format!("Failed to read {}: {}", path.display(), e)))?;
+        Self::from_json(&json)
+    }
+
+    /// Check that the scenario is internally consistent
+    pub fn validate(&self) -> Result<()> {
+        if self.blocks == 0 {
+            return Err(invalid("blocks must be positive"));
+        }
+        if self.price_path.is_empty() {
+            return Err(invalid("price_path is empty"));
+        }
+        if self.price_path.windows(2).any(|w| w[0].block >= w[1].block) {
+            return Err(invalid("price_path blocks must be strictly increasing"));
+        }
+        if self.price_path.iter().any(|p| p.price_cents == 0) {
+            return Err(invalid("prices must be positive"));
+        }
+        if self.peg_path.windows(2).any(|w| w[0].block >= w[1].block) {
+            return Err(invalid("peg_path blocks must be strictly increasing"));
+        }
+        if self.peg_path.iter().any(|p| p.price_bps == 0) {
+            return Err(invalid("peg prices must be positive"));
+        }
+        if let Some(params) = &self.params {
+            ProtocolConfig::new(params.clone()).validate()?;
+        }
+        if let Some(peg) = &self.peg_stability {
+            peg.validate()?;
+        }
+
+        let mut names = HashSet::new();
+        for user in &self.users {
+            if !names.insert(user.name.as_str()) {
+                return Err(invalid(format!("duplicate user '{}'", user.name)));
+            }
+            if user.pool_deposit_cents > user.debt_cents {
+                return Err(invalid(format!("user '{}' deposits more than they mint", user.name)));
+            }
+            match user.behavior {
+                Behavior::MaintainRatio { min_ratio, target_ratio } if target_ratio < min_ratio => {
+                    return Err(invalid(format!("user '{}' targets a ratio below its trigger", user.name)));
+                }
+                Behavior::Redeemer { every_blocks: 0, .. } => {
+                    return Err(invalid(format!("user '{}' redeems every 0 blocks", user.name)));
+                }
+                _ => {}
+            }
+        }
+
+        for shock in &self.shocks {
+            if !names.contains(shock.kind.user()) {
+                return Err(invalid(format!("shock references unknown user '{}'", shock.kind.user())));
+            }
+            if let ShockKind::PoolWithdrawal { fraction_bps, .. } = shock.kind {
+                if fraction_bps > BPS_DIVISOR {
+                    return Err(invalid("pool withdrawal fraction above 100%"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Price at `block`, interpolated between path points and held flat
+    /// before the first and after the last
+    pub fn price_at(&self, block: u64) -> u64 {
+        let path: Vec<_> = self.price_path.iter().map(|p| (p.block, p.price_cents)).collect();
+        interpolate(&path, block)
+    }
+
+    /// zkUSD price in basis points of $1 at `block`, if the scenario has a
+    /// peg path
+    pub fn peg_price_at(&self, block: u64) -> Option<u64> {
+        if self.peg_path.is_empty() {
+            return None;
+        }
+        let path: Vec<_> = self.peg_path.iter().map(|p| (p.block, p.price_bps)).collect();
+        Some(interpolate(&path, block))
+    }
+}
+
+/// Value of a non-empty `(block, value)` path at `block`
+fn interpolate(path: &[(u64, u64)], block: u64) -> u64 {
+    let next = path.partition_point(|&(b, _)| b <= block);
+
+    if next == 0 {
+        return path[0].1;
+    }
+    if next == path.len() {
+        return path[next - 1].1;
+    }
+
+    let (a, b) = (path[next - 1], path[next]);
+    let span = (b.0 - a.0) as i128;
+    let delta = b.1 as i128 - a.1 as i128;
+    (a.1 as i128 + delta * (block - a.0) as i128 / span) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENARIO: &str = r#"{
+        "name": "test",
+        "blocks": 10,
+        "price_path": [
+            { "block": 0, "price_cents": 10000000 },
+            { "block": 4, "price_cents": 6000000 }
+        ],
+        "users": [
+            { "name": "alice", "collateral_sats": 100000000, "debt_cents": 1000000 }
+        ],
+        "shocks": [
+            { "block": 3, "type": "redemption", "user": "alice", "amount_cents": 10000 }
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_and_interpolate() {
+        let scenario = Scenario::from_json(SCENARIO).unwrap();
+        assert!(matches!(scenario.users[0].behavior, Behavior::Passive));
+        assert!(matches!(scenario.shocks[0].kind, ShockKind::Redemption { amount_cents: 10_000, .. }));
+
+        assert_eq!(scenario.price_at(0), 10_000_000);
+        assert_eq!(scenario.price_at(1), 9_000_000);
+        assert_eq!(scenario.price_at(4), 6_000_000);
+        assert_eq!(scenario.price_at(9), 6_000_000);
+    }
+
+    #[test]
+    fn test_rejects_unknown_shock_user() {
+        let json = SCENARIO.replace(r#""user": "alice""#, r#""user": "bob""#);
+        assert!(Scenario::from_json(&json).is_err());
+    }
+}
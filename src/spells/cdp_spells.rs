@@ -8,8 +8,10 @@
 //! - Repay debt
 //! - Close CDP
 
+use bitcoin::ScriptBuf;
 use serde::{Deserialize, Serialize};
 
+use crate::btc::spv::{DepositProof, HeaderChain, VerifiedDeposit};
 use crate::core::cdp::{CDP, CDPId};
 use crate::core::config::ProtocolConfig;
 use crate::core::token::TokenAmount;
@@ -38,6 +40,9 @@ pub struct OpenCDPSpell {
     pub btc_price: u64,
     /// Price proof hash
     pub price_proof_hash: Hash,
+    /// SPV proof of the collateral deposit
+    #[serde(default)]
+    pub deposit_proof: Option<DepositProof>,
     /// Authorization
     pub auth: SpellAuth,
     /// Metadata
@@ -60,6 +65,7 @@ impl OpenCDPSpell {
             initial_debt,
             btc_price,
             price_proof_hash: Hash::zero(),
+            deposit_proof: None,
             auth: SpellAuth {
                 signer: owner,
                 signature: Signature::new([0u8; 64]),
@@ -106,6 +112,30 @@ impl OpenCDPSpell {
         Ok(())
     }
 
+    /// Attach the SPV proof of the collateral deposit
+    pub fn with_deposit_proof(mut self, proof: DepositProof) -> Self {
+        self.deposit_proof = Some(proof);
+        self
+    }
+
+    /// Validate spell inputs and verify the collateral deposit against a header chain
+    pub fn validate_with_spv(
+        &self,
+        config: &ProtocolConfig,
+        chain: &HeaderChain,
+        collateral_script: &ScriptBuf,
+        min_confirmations: u32,
+    ) -> Result<VerifiedDeposit> {
+        self.validate(config)?;
+        verify_deposit(
+            self.deposit_proof.as_ref(),
+            chain,
+            collateral_script,
+            self.collateral.sats(),
+            min_confirmations,
+        )
+    }
+
     /// Execute spell and create CDP
     pub fn execute(&self, config: &ProtocolConfig) -> Result<(CDP, TokenAmount)> {
         self.validate(config)?;
@@ -144,6 +174,9 @@ impl OpenCDPSpell {
         if let Some(debt) = self.initial_debt {
             data.extend_from_slice(&debt.cents().to_be_bytes());
         }
+        if let Some(proof) = &self.deposit_proof {
+            data.extend_from_slice(&deposit_proof_bytes(proof));
+        }
         Hash::sha256(&data)
     }
 }
@@ -159,6 +192,9 @@ pub struct DepositCollateralSpell {
     pub cdp_id: CDPId,
     /// Amount to deposit
     pub amount: CollateralAmount,
+    /// SPV proof of the deposit transaction
+    #[serde(default)]
+    pub deposit_proof: Option<DepositProof>,
     /// Authorization
     pub auth: SpellAuth,
     /// Metadata
@@ -171,6 +207,23 @@ impl DepositCollateralSpell {
         validate_collateral_amount(self.amount.sats())
     }
 
+    /// Validate spell and verify the deposit against a header chain
+    pub fn validate_with_spv(
+        &self,
+        chain: &HeaderChain,
+        collateral_script: &ScriptBuf,
+        min_confirmations: u32,
+    ) -> Result<VerifiedDeposit> {
+        self.validate()?;
+        verify_deposit(
+            self.deposit_proof.as_ref(),
+            chain,
+            collateral_script,
+            self.amount.sats(),
+            min_confirmations,
+        )
+    }
+
     /// Execute spell
     pub fn execute(&self, cdp: &mut CDP) -> Result<()> {
         self.validate()?;
@@ -183,6 +236,9 @@ impl DepositCollateralSpell {
         data.extend_from_slice(cdp_id_bytes(&self.cdp_id));
         data.extend_from_slice(&self.amount.sats().to_be_bytes());
         data.extend_from_slice(&self.auth.nonce.to_be_bytes());
+        if let Some(proof) = &self.deposit_proof {
+            data.extend_from_slice(&deposit_proof_bytes(proof));
+        }
         Hash::sha256(&data)
     }
 }
@@ -457,6 +513,38 @@ fn cdp_id_bytes(id: &CDPId) -> &[u8] {
     id.as_bytes()
 }
 
+/// Commit to the deposit transaction and its block
+fn deposit_proof_bytes(proof: &DepositProof) -> Vec<u8> {
+    use bitcoin::hashes::Hash as _;
+
+    let mut data = proof.tx.compute_txid().to_byte_array().to_vec();
+    data.extend_from_slice(proof.inclusion.block_hash.as_byte_array());
+    data
+}
+
+fn verify_deposit(
+    proof: Option<&DepositProof>,
+    chain: &HeaderChain,
+    collateral_script: &ScriptBuf,
+    amount_sats: u64,
+    min_confirmations: u32,
+) -> Result<VerifiedDeposit> {
+    let proof = proof.ok_or_else(|| Error::InvalidSpvProof("missing deposit proof".into()))?;
+    let deposit = proof.verify(chain, collateral_script, amount_sats, min_confirmations)?;
+
+    if deposit.value_sats != amount_sats {
+        return Err(Error::InvalidParameter {
+            name: "collateral".into(),
+            reason: format!(
+                "claimed {} sats but deposit output holds {} sats",
+                amount_sats, deposit.value_sats
+            ),
+        });
+    }
+
+    Ok(deposit)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -575,4 +663,59 @@ mod tests {
         let result = close_spell.execute(&mut cdp);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_open_cdp_spv_deposit() {
+        use crate::btc::spv::MerkleInclusionProof;
+        use bitcoin::block::{Header, Version};
+        use bitcoin::hashes::Hash as _;
+        use bitcoin::pow::CompactTarget;
+        use bitcoin::{absolute::LockTime, transaction, Amount, BlockHash, Transaction, TxMerkleNode, TxOut};
+
+        let config = ProtocolConfig::default();
+        let vault_script = ScriptBuf::from_bytes(vec![0x00, 0x20, 0x01]);
+        let tx = Transaction {
+            version: transaction::Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: vec![TxOut { value: Amount::from_sat(SATS_PER_BTC), script_pubkey: vault_script.clone() }],
+        };
+
+        // Single-transaction block: the merkle root is the txid
+        let header = Header {
+            version: Version::TWO,
+            prev_blockhash: BlockHash::all_zeros(),
+            merkle_root: TxMerkleNode::from_byte_array(tx.compute_txid().to_byte_array()),
+            time: 0,
+            bits: CompactTarget::from_consensus(0x207fffff),
+            nonce: 0,
+        };
+        let chain = HeaderChain::new(100, header);
+        let proof = DepositProof::new(
+            tx.clone(),
+            MerkleInclusionProof::from_txids(header.block_hash(), &[tx.compute_txid()], 0).unwrap(),
+        );
+
+        let spell = OpenCDPSpell::new(test_pubkey(), CollateralAmount::from_btc(1), None, 10_000_000, 1, 100);
+        assert!(matches!(
+            spell.validate_with_spv(&config, &chain, &vault_script, 1),
+            Err(Error::InvalidSpvProof(_))
+        ));
+
+        let unproven_hash = spell.hash();
+        let spell = spell.with_deposit_proof(proof);
+        assert_ne!(spell.hash(), unproven_hash);
+
+        let deposit = spell.validate_with_spv(&config, &chain, &vault_script, 1).unwrap();
+        assert_eq!(deposit.value_sats, SATS_PER_BTC);
+        assert!(matches!(
+            spell.validate_with_spv(&config, &chain, &vault_script, MIN_DEPOSIT_CONFIRMATIONS),
+            Err(Error::InsufficientConfirmations { .. })
+        ));
+
+        // Claiming more collateral than the deposit holds fails
+        let mut inflated = spell.clone();
+        inflated.collateral = CollateralAmount::from_btc(2);
+        assert!(inflated.validate_with_spv(&config, &chain, &vault_script, 1).is_err());
+    }
 }
@@ -75,7 +75,7 @@ impl RedemptionSpell {
         }
 
         // Calculate current fee
-        let current_fee = config.calculate_redemption_fee(self.meta.timestamp);
+        let current_fee = config.redemption_fee(self.meta.timestamp);
         if current_fee > self.max_fee_bps {
             return Err(Error::InvalidParameter {
                 name: "fee".into(),
@@ -98,7 +98,7 @@ impl RedemptionSpell {
         self.validate(config)?;
 
         // Calculate fee
-        let fee_bps = config.calculate_redemption_fee(self.meta.timestamp);
+        let fee_bps = config.redemption_fee(self.meta.timestamp);
         let fee_amount = calculate_fee_bps(self.amount.cents(), fee_bps)?;
         let net_redemption = self.amount.cents() - fee_amount;
 
@@ -147,7 +147,8 @@ impl RedemptionSpell {
         // For now, we just return what would happen
 
         // Update protocol base rate
-        config.update_base_rate(self.amount.cents() - remaining_to_redeem, self.meta.timestamp);
+        let redeemed = self.amount.cents() - remaining_to_redeem;
+        config.fees.record_redemption(redeemed, config.total_system_debt, self.meta.timestamp);
 
         Ok(RedemptionResult {
             redeemer: self.redeemer,
@@ -259,9 +260,10 @@ impl LiquidationSpell {
         let collateral_before = cdp.collateral_sats;
 
         // Perform liquidation
-        let liq_result = cdp.liquidate(
+        let liq_result = cdp.liquidate_with_penalty(
             self.btc_price,
             config.effective_mcr(),
+            config.liquidation_penalty_bps(ratio_before),
             self.meta.block_height,
         )?;
 
@@ -4,15 +4,22 @@
 //! - InMemoryStore: Fast, ephemeral storage for testing
 //! - FileStore: JSON file-based persistent storage
 //! - BinaryStore: Compact binary format for production
+//! - WalStore: Write-ahead log layer adding crash durability to any backend
+//!
+//! File and binary stores lock their directory while open for writing (see
+//! [`crate::storage::lock`]) and can be opened read-only alongside a writer.
 
-use serde::{de::DeserializeOwned, Serialize};
-use std::collections::HashMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs::{self, File, OpenOptions};
-use std::io::{BufReader, BufWriter, Read, Write};
+use std::io::{BufReader, Read, Write};
 use std::path::{Path, PathBuf};
-use std::sync::RwLock;
+use std::sync::{Mutex, RwLock};
 
 use crate::error::{Error, Result};
+use crate::storage::lock::StoreLock;
+use crate::storage::rocks::{column_families, BatchOperation};
+use crate::utils::crypto::Hash;
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // STORAGE TRAIT
@@ -49,6 +56,21 @@ pub trait StorageBackend: Send + Sync {
 
     /// Clear all data
     fn clear(&self) -> Result<()>;
+
+    /// Apply a batch of writes. Backends without column families ignore
+    /// the operations' column family; the default applies them in order,
+    /// backends that can write atomically override it.
+    fn write_batch(&self, operations: Vec<BatchOperation>) -> Result<()> {
+        for op in operations {
+            match op {
+                BatchOperation::Put { key, value, .. } => self.set(&key, &value)?,
+                BatchOperation::Delete { key, .. } => {
+                    self.delete(&key)?;
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -141,10 +163,12 @@ pub struct FileStore {
     cache: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
     /// Whether cache is dirty and needs flushing
     dirty: RwLock<bool>,
+    /// Directory lock, `None` when opened read-only
+    lock: Option<StoreLock>,
 }
 
 impl FileStore {
-    /// Create a new file store at the given path
+    /// Create a new file store at the given path, locking it for writing
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
         let base_path = path.as_ref().to_path_buf();
 
@@ -155,10 +179,12 @@ impl FileStore {
             })?;
         }
 
+        let lock = StoreLock::acquire(&base_path)?;
         let store = Self {
             base_path,
             cache: RwLock::new(HashMap::new()),
             dirty: RwLock::new(false),
+            lock: Some(lock),
         };
 
         // Load existing data
@@ -167,6 +193,31 @@ impl FileStore {
         Ok(store)
     }
 
+    /// Open the file store at the given path read-only, without taking the
+    /// lock; writes fail with [`Error::ReadOnlyStorage`]
+    pub fn open_read_only<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let store = Self {
+            base_path: path.as_ref().to_path_buf(),
+            cache: RwLock::new(HashMap::new()),
+            dirty: RwLock::new(false),
+            lock: None,
+        };
+        store.load_from_disk()?;
+        Ok(store)
+    }
+
+    /// Whether the store was opened read-only
+    pub fn is_read_only(&self) -> bool {
+        self.lock.is_none()
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        if self.is_read_only() {
+            return Err(Error::ReadOnlyStorage);
+        }
+        Ok(())
+    }
+
     /// Get the path for a specific data file
     fn data_file_path(&self) -> PathBuf {
         self.base_path.join("data.json")
@@ -216,18 +267,10 @@ impl FileStore {
             .map(|(k, v)| (hex::encode(k), hex::encode(v)))
             .collect();
 
-        let path = self.data_file_path();
-        let file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(&path)
-            .map_err(|e| Error::Internal(format!("Failed to open data file for writing: {}", e)))?;
-
-        let writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(writer, &data).map_err(|e| {
+        let data = serde_json::to_vec_pretty(&data).map_err(|e| {
             Error::Internal(format!("Failed to write data file: {}", e))
         })?;
+        write_atomic(&self.data_file_path(), &data)?;
 
         let mut dirty = self.dirty.write().map_err(|e| Error::Internal(format!("Lock error: {}", e)))?;
         *dirty = false;
@@ -243,6 +286,7 @@ impl StorageBackend for FileStore {
     }
 
     fn set(&self, key: &[u8], value: &[u8]) -> Result<()> {
+        self.check_writable()?;
         let mut cache = self.cache.write().map_err(|e| Error::Internal(format!("Lock error: {}", e)))?;
         cache.insert(key.to_vec(), value.to_vec());
 
@@ -253,6 +297,7 @@ impl StorageBackend for FileStore {
     }
 
     fn delete(&self, key: &[u8]) -> Result<bool> {
+        self.check_writable()?;
         let mut cache = self.cache.write().map_err(|e| Error::Internal(format!("Lock error: {}", e)))?;
         let existed = cache.remove(key).is_some();
 
@@ -292,6 +337,7 @@ impl StorageBackend for FileStore {
     }
 
     fn clear(&self) -> Result<()> {
+        self.check_writable()?;
         let mut cache = self.cache.write().map_err(|e| Error::Internal(format!("Lock error: {}", e)))?;
         cache.clear();
 
@@ -322,10 +368,12 @@ pub struct BinaryStore {
     cache: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
     /// Whether cache is dirty
     dirty: RwLock<bool>,
+    /// Directory lock, `None` when opened read-only
+    lock: Option<StoreLock>,
 }
 
 impl BinaryStore {
-    /// Create a new binary store at the given path
+    /// Create a new binary store at the given path, locking it for writing
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
         let base_path = path.as_ref().to_path_buf();
 
@@ -335,10 +383,12 @@ impl BinaryStore {
             })?;
         }
 
+        let lock = StoreLock::acquire(&base_path)?;
         let store = Self {
             base_path,
             cache: RwLock::new(HashMap::new()),
             dirty: RwLock::new(false),
+            lock: Some(lock),
         };
 
         store.load_from_disk()?;
@@ -346,6 +396,31 @@ impl BinaryStore {
         Ok(store)
     }
 
+    /// Open the binary store at the given path read-only, without taking the
+    /// lock; writes fail with [`Error::ReadOnlyStorage`]
+    pub fn open_read_only<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let store = Self {
+            base_path: path.as_ref().to_path_buf(),
+            cache: RwLock::new(HashMap::new()),
+            dirty: RwLock::new(false),
+            lock: None,
+        };
+        store.load_from_disk()?;
+        Ok(store)
+    }
+
+    /// Whether the store was opened read-only
+    pub fn is_read_only(&self) -> bool {
+        self.lock.is_none()
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        if self.is_read_only() {
+            return Err(Error::ReadOnlyStorage);
+        }
+        Ok(())
+    }
+
     fn data_file_path(&self) -> PathBuf {
         self.base_path.join("data.bin")
     }
@@ -383,17 +458,7 @@ impl BinaryStore {
             Error::Internal(format!("Failed to serialize data: {}", e))
         })?;
 
-        let path = self.data_file_path();
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(&path)
-            .map_err(|e| Error::Internal(format!("Failed to open data file: {}", e)))?;
-
-        file.write_all(&data).map_err(|e| {
-            Error::Internal(format!("Failed to write data file: {}", e))
-        })?;
+        write_atomic(&self.data_file_path(), &data)?;
 
         let mut dirty = self.dirty.write().map_err(|e| Error::Internal(format!("Lock error: {}", e)))?;
         *dirty = false;
@@ -409,6 +474,7 @@ impl StorageBackend for BinaryStore {
     }
 
     fn set(&self, key: &[u8], value: &[u8]) -> Result<()> {
+        self.check_writable()?;
         let mut cache = self.cache.write().map_err(|e| Error::Internal(format!("Lock error: {}", e)))?;
         cache.insert(key.to_vec(), value.to_vec());
 
@@ -419,6 +485,7 @@ impl StorageBackend for BinaryStore {
     }
 
     fn delete(&self, key: &[u8]) -> Result<bool> {
+        self.check_writable()?;
         let mut cache = self.cache.write().map_err(|e| Error::Internal(format!("Lock error: {}", e)))?;
         let existed = cache.remove(key).is_some();
 
@@ -458,6 +525,7 @@ impl StorageBackend for BinaryStore {
     }
 
     fn clear(&self) -> Result<()> {
+        self.check_writable()?;
         let mut cache = self.cache.write().map_err(|e| Error::Internal(format!("Lock error: {}", e)))?;
         cache.clear();
 
@@ -474,24 +542,359 @@ impl Drop for BinaryStore {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// WRITE-AHEAD LOG
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// When the write-ahead log is fsynced to disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncPolicy {
+    /// Sync after every record (strongest durability)
+    Always,
+    /// Sync after every N records
+    EveryN(u32),
+    /// Leave syncing to the operating system
+    Never,
+}
+
+/// Write-ahead log configuration
+#[derive(Debug, Clone)]
+pub struct WalConfig {
+    /// Fsync policy for appended records
+    pub sync_policy: SyncPolicy,
+    /// Log size in bytes after which the log is compacted
+    pub compaction_threshold: u64,
+}
+
+impl Default for WalConfig {
+    fn default() -> Self {
+        Self {
+            sync_policy: SyncPolicy::Always,
+            compaction_threshold: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// A single logged storage operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WalRecord {
+    /// Set a key to a value
+    Set {
+        /// Key
+        key: StorageKey,
+        /// Value
+        value: StorageValue,
+    },
+    /// Delete a key
+    Delete {
+        /// Key
+        key: StorageKey,
+    },
+    /// Clear all data
+    Clear,
+    /// Apply several writes atomically
+    Batch {
+        /// Writes, in order
+        operations: Vec<BatchOperation>,
+    },
+}
+
+impl WalRecord {
+    /// Apply this record to a backend
+    fn apply<B: StorageBackend>(&self, backend: &B) -> Result<()> {
+        match self {
+            WalRecord::Set { key, value } => backend.set(key, value),
+            WalRecord::Delete { key } => backend.delete(key).map(|_| ()),
+            WalRecord::Clear => backend.clear(),
+            WalRecord::Batch { operations } => backend.write_batch(operations.clone()),
+        }
+    }
+}
+
+/// Open log file handle and its bookkeeping
+#[derive(Debug)]
+struct WalWriter {
+    file: File,
+    size: u64,
+    unsynced: u32,
+}
+
+/// Storage layer that logs every write before applying it to the wrapped backend.
+///
+/// Each record is framed as `[len: u32][checksum: 4 bytes][bincode payload]`.
+/// On open, any records left over from a crash are replayed into the backend;
+/// a torn or corrupt tail record is discarded. A write batch is logged as a
+/// single record, so it is replayed whole or not at all. Flushing the backend checkpoints
+/// the log by truncating it.
+#[derive(Debug)]
+pub struct WalStore<B: StorageBackend> {
+    /// Wrapped storage backend
+    backend: B,
+    /// Path of the log file
+    path: PathBuf,
+    /// Log configuration
+    config: WalConfig,
+    /// Log writer
+    writer: Mutex<WalWriter>,
+    /// Number of records replayed on open
+    recovered: usize,
+}
+
+impl<B: StorageBackend> WalStore<B> {
+    /// Open a WAL at `path` over `backend`, replaying any pending records
+    pub fn open<P: AsRef<Path>>(backend: B, path: P, config: WalConfig) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() && !parent.exists() {
+                fs::create_dir_all(parent).map_err(|e| {
+                    Error::Internal(format!("Failed to create WAL directory: {}", e))
+                })?;
+            }
+        }
+
+        // Replay records that never reached the backend
+        let records = Self::read_log(&path)?;
+        for record in &records {
+            record.apply(&backend)?;
+        }
+        if !records.is_empty() {
+            backend.flush()?;
+        }
+
+        // Append mode keeps writes at the end of the file across truncations
+        let file = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&path)
+            .map_err(|e| Error::Internal(format!("Failed to open WAL: {}", e)))?;
+        let mut writer = WalWriter { file, size: 0, unsynced: 0 };
+        Self::truncate(&mut writer)?;
+
+        Ok(Self {
+            backend,
+            path,
+            config,
+            writer: Mutex::new(writer),
+            recovered: records.len(),
+        })
+    }
+
+    /// Open a WAL with the default configuration
+    pub fn open_default<P: AsRef<Path>>(backend: B, path: P) -> Result<Self> {
+        Self::open(backend, path, WalConfig::default())
+    }
+
+    /// Number of records replayed during crash recovery
+    pub fn recovered_records(&self) -> usize {
+        self.recovered
+    }
+
+    /// Current log size in bytes
+    pub fn log_size(&self) -> u64 {
+        self.writer.lock().map(|w| w.size).unwrap_or(0)
+    }
+
+    /// Path of the log file
+    pub fn log_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the underlying backend
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Flush the backend and truncate the log
+    pub fn compact(&self) -> Result<()> {
+        let mut writer = self.writer.lock().map_err(|e| Error::Internal(format!("Lock error: {}", e)))?;
+        self.backend.flush()?;
+        Self::truncate(&mut writer)
+    }
+
+    /// Read all intact records from a log file
+    fn read_log(path: &Path) -> Result<Vec<WalRecord>> {
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut data = Vec::new();
+        File::open(path)
+            .and_then(|mut f| f.read_to_end(&mut data))
+            .map_err(|e| Error::Internal(format!("Failed to read WAL: {}", e)))?;
+
+        let mut records = Vec::new();
+        let mut offset = 0usize;
+
+        while offset + 8 <= data.len() {
+            let len = u32::from_be_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]) as usize;
+            let checksum = &data[offset + 4..offset + 8];
+            let start = offset + 8;
+            let end = match start.checked_add(len) {
+                Some(end) if end <= data.len() => end,
+                _ => break,
+            };
+
+            let payload = &data[start..end];
+            if &Hash::sha256(payload).as_bytes()[..4] != checksum {
+                break;
+            }
+
+            match bincode::deserialize::<WalRecord>(payload) {
+                Ok(record) => records.push(record),
+                Err(_) => break,
+            }
+            offset = end;
+        }
+
+        Ok(records)
+    }
+
+    /// Append a record to the log according to the sync policy
+    fn append(&self, writer: &mut WalWriter, record: &WalRecord) -> Result<()> {
+        let payload = bincode::serialize(record).map_err(|e| {
+            Error::Serialization(format!("Failed to serialize WAL record: {}", e))
+        })?;
+
+        let mut frame = Vec::with_capacity(payload.len() + 8);
+        frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&Hash::sha256(&payload).as_bytes()[..4]);
+        frame.extend_from_slice(&payload);
+
+        writer.file.write_all(&frame).map_err(|e| {
+            Error::Internal(format!("Failed to append to WAL: {}", e))
+        })?;
+        writer.size += frame.len() as u64;
+        writer.unsynced += 1;
+
+        let should_sync = match self.config.sync_policy {
+            SyncPolicy::Always => true,
+            SyncPolicy::EveryN(n) => writer.unsynced >= n.max(1),
+            SyncPolicy::Never => false,
+        };
+        if should_sync {
+            writer.file.sync_data().map_err(|e| {
+                Error::Internal(format!("Failed to sync WAL: {}", e))
+            })?;
+            writer.unsynced = 0;
+        }
+
+        Ok(())
+    }
+
+    /// Truncate the log to empty
+    fn truncate(writer: &mut WalWriter) -> Result<()> {
+        writer.file.set_len(0).map_err(|e| {
+            Error::Internal(format!("Failed to truncate WAL: {}", e))
+        })?;
+        writer.file.sync_all().map_err(|e| {
+            Error::Internal(format!("Failed to sync WAL: {}", e))
+        })?;
+        writer.size = 0;
+        writer.unsynced = 0;
+        Ok(())
+    }
+
+    /// Log a record, apply it, and compact if the log grew too large
+    fn log_and_apply(&self, record: WalRecord) -> Result<()> {
+        let mut writer = self.writer.lock().map_err(|e| Error::Internal(format!("Lock error: {}", e)))?;
+        self.append(&mut writer, &record)?;
+        record.apply(&self.backend)?;
+
+        if writer.size >= self.config.compaction_threshold {
+            self.backend.flush()?;
+            Self::truncate(&mut writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<B: StorageBackend> StorageBackend for WalStore<B> {
+    fn get(&self, key: &[u8]) -> Result<Option<StorageValue>> {
+        self.backend.get(key)
+    }
+
+    fn set(&self, key: &[u8], value: &[u8]) -> Result<()> {
+        self.log_and_apply(WalRecord::Set {
+            key: key.to_vec(),
+            value: value.to_vec(),
+        })
+    }
+
+    fn delete(&self, key: &[u8]) -> Result<bool> {
+        let existed = self.backend.exists(key)?;
+        if existed {
+            self.log_and_apply(WalRecord::Delete { key: key.to_vec() })?;
+        }
+        Ok(existed)
+    }
+
+    fn exists(&self, key: &[u8]) -> Result<bool> {
+        self.backend.exists(key)
+    }
+
+    fn list_prefix(&self, prefix: &[u8]) -> Result<Vec<StorageKey>> {
+        self.backend.list_prefix(prefix)
+    }
+
+    fn flush(&self) -> Result<()> {
+        self.compact()
+    }
+
+    fn keys(&self) -> Result<Vec<StorageKey>> {
+        self.backend.keys()
+    }
+
+    fn clear(&self) -> Result<()> {
+        self.log_and_apply(WalRecord::Clear)
+    }
+
+    fn write_batch(&self, operations: Vec<BatchOperation>) -> Result<()> {
+        if operations.is_empty() {
+            return Ok(());
+        }
+        self.log_and_apply(WalRecord::Batch { operations })
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // TYPED STORE WRAPPER
 // ═══════════════════════════════════════════════════════════════════════════════
 
-/// Type-safe wrapper around a storage backend
+/// Writes held back by an open batch; `None` marks a deletion
+type PendingWrites = BTreeMap<StorageKey, Option<StorageValue>>;
+
+/// Type-safe wrapper around a storage backend.
+///
+/// While a batch is open, writes are buffered in memory (and visible to
+/// reads through this store) until [`commit_batch`](Self::commit_batch)
+/// hands them to the backend in a single [`StorageBackend::write_batch`].
+/// Nested batches layer on top of it and are either folded into the batch
+/// below or discarded on their own.
 pub struct TypedStore<B: StorageBackend> {
     backend: B,
+    /// Open batches, innermost last
+    batch: Mutex<Vec<PendingWrites>>,
 }
 
 impl<B: StorageBackend> TypedStore<B> {
     /// Create a new typed store
     pub fn new(backend: B) -> Self {
-        Self { backend }
+        Self {
+            backend,
+            batch: Mutex::new(Vec::new()),
+        }
     }
 
     /// Get a typed value
     pub fn get<T: DeserializeOwned>(&self, key: &[u8]) -> Result<Option<T>> {
-        match self.backend.get(key)? {
+        let data = match self.pending(key)? {
+            Some(pending) => pending,
+            None => self.backend.get(key)?,
+        };
+
+        match data {
             Some(data) => {
                 let value = bincode::deserialize(&data).map_err(|e| {
                     Error::Deserialization(format!("Failed to deserialize value: {}", e))
@@ -507,22 +910,43 @@ impl<B: StorageBackend> TypedStore<B> {
         let data = bincode::serialize(value).map_err(|e| {
             Error::Serialization(format!("Failed to serialize value: {}", e))
         })?;
-        self.backend.set(key, &data)
+
+        let mut batch = self.lock_batch()?;
+        match batch.last_mut() {
+            Some(pending) => {
+                pending.insert(key.to_vec(), Some(data));
+                Ok(())
+            }
+            None => self.backend.set(key, &data),
+        }
     }
 
     /// Delete a value
     pub fn delete(&self, key: &[u8]) -> Result<bool> {
-        self.backend.delete(key)
+        let existed = self.exists(key)?;
+
+        let mut batch = self.lock_batch()?;
+        match batch.last_mut() {
+            Some(pending) => {
+                pending.insert(key.to_vec(), None);
+                Ok(existed)
+            }
+            None => self.backend.delete(key),
+        }
     }
 
     /// Check if a key exists
     pub fn exists(&self, key: &[u8]) -> Result<bool> {
-        self.backend.exists(key)
+        match self.pending(key)? {
+            Some(pending) => Ok(pending.is_some()),
+            None => self.backend.exists(key),
+        }
     }
 
     /// List keys with prefix
     pub fn list_prefix(&self, prefix: &[u8]) -> Result<Vec<StorageKey>> {
-        self.backend.list_prefix(prefix)
+        let keys = self.backend.list_prefix(prefix)?;
+        self.merge_pending(keys, prefix)
     }
 
     /// Flush pending writes
@@ -532,11 +956,13 @@ impl<B: StorageBackend> TypedStore<B> {
 
     /// Get all keys
     pub fn keys(&self) -> Result<Vec<StorageKey>> {
-        self.backend.keys()
+        let keys = self.backend.keys()?;
+        self.merge_pending(keys, &[])
     }
 
-    /// Clear all data
+    /// Clear all data, discarding any open batch
     pub fn clear(&self) -> Result<()> {
+        self.discard_batch()?;
         self.backend.clear()
     }
 
@@ -544,6 +970,119 @@ impl<B: StorageBackend> TypedStore<B> {
     pub fn backend(&self) -> &B {
         &self.backend
     }
+
+    /// Start buffering writes; does nothing if a batch is already open
+    pub fn begin_batch(&self) -> Result<()> {
+        let mut batch = self.lock_batch()?;
+        if batch.is_empty() {
+            batch.push(PendingWrites::new());
+        }
+        Ok(())
+    }
+
+    /// Write all open batches to the backend, returning the number of writes
+    pub fn commit_batch(&self) -> Result<usize> {
+        let layers = std::mem::take(&mut *self.lock_batch()?);
+        self.write_pending(flatten(layers))
+    }
+
+    /// Drop all open batches without writing them
+    pub fn discard_batch(&self) -> Result<()> {
+        self.lock_batch()?.clear();
+        Ok(())
+    }
+
+    /// Open a batch on top of any open one, to be committed into it or
+    /// discarded on its own
+    pub fn begin_nested_batch(&self) -> Result<()> {
+        self.lock_batch()?.push(PendingWrites::new());
+        Ok(())
+    }
+
+    /// Fold the innermost batch into the one below it, or write it to the
+    /// backend if it is the only one
+    pub fn commit_nested_batch(&self) -> Result<()> {
+        let mut batch = self.lock_batch()?;
+        let Some(pending) = batch.pop() else {
+            return Ok(());
+        };
+        match batch.last_mut() {
+            Some(outer) => outer.extend(pending),
+            None => {
+                drop(batch);
+                self.write_pending(pending)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop the innermost batch without writing it
+    pub fn discard_nested_batch(&self) -> Result<()> {
+        self.lock_batch()?.pop();
+        Ok(())
+    }
+
+    /// Number of writes buffered by the open batches
+    pub fn pending_writes(&self) -> Result<usize> {
+        let batch = self.lock_batch()?;
+        let keys: BTreeSet<&StorageKey> = batch.iter().flat_map(|pending| pending.keys()).collect();
+        Ok(keys.len())
+    }
+
+    fn lock_batch(&self) -> Result<std::sync::MutexGuard<'_, Vec<PendingWrites>>> {
+        self.batch.lock().map_err(|e| Error::Internal(format!("Lock error: {}", e)))
+    }
+
+    fn write_pending(&self, pending: PendingWrites) -> Result<usize> {
+        if pending.is_empty() {
+            return Ok(0);
+        }
+
+        let count = pending.len();
+        let operations = pending
+            .into_iter()
+            .map(|(key, value)| match value {
+                Some(value) => BatchOperation::put(column_families::DEFAULT, key, value),
+                None => BatchOperation::delete(column_families::DEFAULT, key),
+            })
+            .collect();
+        self.backend.write_batch(operations)?;
+
+        Ok(count)
+    }
+
+    /// Buffered value of `key`: `None` if no open batch touches it
+    fn pending(&self, key: &[u8]) -> Result<Option<Option<StorageValue>>> {
+        Ok(self.lock_batch()?.iter().rev().find_map(|pending| pending.get(key).cloned()))
+    }
+
+    fn merge_pending(&self, keys: Vec<StorageKey>, prefix: &[u8]) -> Result<Vec<StorageKey>> {
+        let batch = self.lock_batch()?;
+        if !batch.iter().any(|pending| pending.keys().any(|key| key.starts_with(prefix))) {
+            return Ok(keys);
+        }
+
+        let mut merged: BTreeSet<StorageKey> = keys.into_iter().collect();
+        for pending in batch.iter() {
+            for (key, value) in pending.iter().filter(|(key, _)| key.starts_with(prefix)) {
+                if value.is_some() {
+                    merged.insert(key.clone());
+                } else {
+                    merged.remove(key);
+                }
+            }
+        }
+
+        Ok(merged.into_iter().collect())
+    }
+}
+
+/// Collapse batches, innermost last, into one set of writes
+fn flatten(layers: Vec<PendingWrites>) -> PendingWrites {
+    layers.into_iter().fold(PendingWrites::new(), |mut merged, pending| {
+        merged.extend(pending);
+        merged
+    })
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -554,6 +1093,8 @@ impl<B: StorageBackend> TypedStore<B> {
 pub mod prefixes {
     /// CDP data prefix
     pub const CDP: &[u8] = b"cdp:";
+    /// CDP version history prefix (by CDP ID, then block height)
+    pub const CDP_HISTORY: &[u8] = b"cdph:";
     /// Token balance prefix
     pub const BALANCE: &[u8] = b"bal:";
     /// Protocol config prefix
@@ -566,6 +1107,63 @@ pub mod prefixes {
     pub const STABILITY_POOL: &[u8] = b"sp:";
     /// Deposit prefix
     pub const DEPOSIT: &[u8] = b"dep:";
+    /// Protocol event prefix
+    pub const EVENT: &[u8] = b"evt:";
+    /// Unspent output prefix
+    pub const UTXO: &[u8] = b"utxo:";
+    /// Spent output (reorg window) prefix
+    pub const UTXO_SPENT: &[u8] = b"utxs:";
+    /// Tracked block hash prefix
+    pub const UTXO_BLOCK: &[u8] = b"utxb:";
+    /// Bridge state prefix
+    pub const BRIDGE: &[u8] = b"brg:";
+    /// Outbound bridge transfer prefix (by sequence)
+    pub const BRIDGE_OUT: &[u8] = b"bro:";
+    /// Savings pot prefix
+    pub const SAVINGS: &[u8] = b"sav:";
+    /// Treasury bookkeeping prefix
+    pub const TREASURY: &[u8] = b"try:";
+    /// Liquidation auctions prefix
+    pub const AUCTIONS: &[u8] = b"auc:";
+    /// Stability pool frontends prefix
+    pub const FRONTENDS: &[u8] = b"fe:";
+    /// Accrued protocol statistics prefix
+    pub const AGGREGATES: &[u8] = b"agg:";
+    /// Peg stability controller prefix
+    pub const PEG: &[u8] = b"peg:";
+    /// Oracle operator registry prefix
+    pub const ORACLES: &[u8] = b"orc:";
+    /// Network genesis prefix
+    pub const GENESIS: &[u8] = b"gen:";
+    /// Token ledger prefix
+    pub const TOKEN: &[u8] = b"tok:";
+    /// Collateral vault prefix
+    pub const VAULT: &[u8] = b"vlt:";
+    /// Signer nonce prefix
+    pub const NONCE: &[u8] = b"non:";
+    /// Per-block operation log prefix (by height)
+    pub const BLOCK: &[u8] = b"blk:";
+    /// Audit log entry prefix (by sequence)
+    pub const AUDIT: &[u8] = b"aud:";
+    /// Operation receipt prefix (by operation hash)
+    pub const RECEIPT: &[u8] = b"rcp:";
+    /// Receipts of a block prefix (by height)
+    pub const RECEIPT_BLOCK: &[u8] = b"rcpb:";
+}
+
+/// Replace the file at `path` with `data` through a temporary file, so
+/// read-only openers never see a partially written file
+fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+
+    let mut file = File::create(&tmp_path)
+        .map_err(|e| Error::Internal(format!("Failed to open data file for writing: {}", e)))?;
+    file.write_all(data)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| Error::Internal(format!("Failed to write data file: {}", e)))?;
+    fs::rename(&tmp_path, path).map_err(|e| Error::Internal(format!("Failed to replace data file: {}", e)))
 }
 
 /// Create a key with a prefix
@@ -635,6 +1233,67 @@ mod tests {
         assert_eq!(value, "hello");
     }
 
+    #[test]
+    fn test_typed_store_batch() {
+        let store = TypedStore::new(InMemoryStore::new());
+        store.set(b"cdp:a", &1u64).unwrap();
+
+        store.begin_batch().unwrap();
+        store.set(b"cdp:b", &2u64).unwrap();
+        assert!(store.delete(b"cdp:a").unwrap());
+
+        // Reads see the batch, the backend does not
+        assert_eq!(store.get::<u64>(b"cdp:b").unwrap(), Some(2));
+        assert!(!store.exists(b"cdp:a").unwrap());
+        assert_eq!(store.list_prefix(b"cdp:").unwrap(), vec![b"cdp:b".to_vec()]);
+        assert!(store.backend().exists(b"cdp:a").unwrap());
+        assert!(!store.backend().exists(b"cdp:b").unwrap());
+
+        assert_eq!(store.commit_batch().unwrap(), 2);
+        assert!(!store.backend().exists(b"cdp:a").unwrap());
+        assert_eq!(store.get::<u64>(b"cdp:b").unwrap(), Some(2));
+
+        store.begin_batch().unwrap();
+        store.set(b"cdp:c", &3u64).unwrap();
+        store.discard_batch().unwrap();
+        assert!(!store.exists(b"cdp:c").unwrap());
+    }
+
+    #[test]
+    fn test_typed_store_nested_batch() {
+        let store = TypedStore::new(InMemoryStore::new());
+        store.begin_batch().unwrap();
+        store.set(b"cdp:a", &1u64).unwrap();
+
+        // A discarded nested batch leaves the outer one untouched
+        store.begin_nested_batch().unwrap();
+        store.set(b"cdp:a", &10u64).unwrap();
+        store.set(b"cdp:b", &2u64).unwrap();
+        assert_eq!(store.get::<u64>(b"cdp:a").unwrap(), Some(10));
+        assert_eq!(store.pending_writes().unwrap(), 2);
+        store.discard_nested_batch().unwrap();
+        assert_eq!(store.get::<u64>(b"cdp:a").unwrap(), Some(1));
+        assert!(!store.exists(b"cdp:b").unwrap());
+
+        // A committed one folds into it
+        store.begin_nested_batch().unwrap();
+        assert!(store.delete(b"cdp:a").unwrap());
+        store.set(b"cdp:c", &3u64).unwrap();
+        store.commit_nested_batch().unwrap();
+        assert!(!store.backend().exists(b"cdp:c").unwrap());
+        assert_eq!(store.list_prefix(b"cdp:").unwrap(), vec![b"cdp:c".to_vec()]);
+
+        assert_eq!(store.commit_batch().unwrap(), 2);
+        assert!(!store.backend().exists(b"cdp:a").unwrap());
+        assert!(store.backend().exists(b"cdp:c").unwrap());
+
+        // Without an outer batch a nested one writes straight through
+        store.begin_nested_batch().unwrap();
+        store.set(b"cdp:d", &4u64).unwrap();
+        store.commit_nested_batch().unwrap();
+        assert!(store.backend().exists(b"cdp:d").unwrap());
+    }
+
     #[test]
     fn test_make_key() {
         let key = make_key(prefixes::CDP, b"12345");
@@ -688,4 +1347,123 @@ mod tests {
         store.flush().unwrap();
         assert!(temp_dir.path().join("data.bin").exists());
     }
+
+    #[test]
+    fn test_binary_store_lock_and_read_only() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = BinaryStore::new(temp_dir.path()).unwrap();
+        store.set(b"key1", b"value1").unwrap();
+        store.flush().unwrap();
+
+        // A second writer is refused, a reader is not
+        assert!(matches!(BinaryStore::new(temp_dir.path()), Err(Error::StorageLocked { .. })));
+        let reader = BinaryStore::open_read_only(temp_dir.path()).unwrap();
+        assert!(reader.is_read_only());
+        assert_eq!(reader.get(b"key1").unwrap(), Some(b"value1".to_vec()));
+        assert!(matches!(reader.set(b"key2", b"value2"), Err(Error::ReadOnlyStorage)));
+
+        drop(store);
+        assert!(!BinaryStore::new(temp_dir.path()).unwrap().is_read_only());
+    }
+
+    #[test]
+    fn test_wal_replays_after_crash() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let wal_path = temp_dir.path().join("store.wal");
+
+        // Write without flushing, then "crash" by dropping the volatile backend
+        {
+            let store = WalStore::open_default(InMemoryStore::new(), &wal_path).unwrap();
+            store.set(b"key1", b"value1").unwrap();
+            store.set(b"key2", b"value2").unwrap();
+            store.delete(b"key2").unwrap();
+        }
+
+        let store = WalStore::open_default(InMemoryStore::new(), &wal_path).unwrap();
+        assert_eq!(store.recovered_records(), 3);
+        assert_eq!(store.get(b"key1").unwrap(), Some(b"value1".to_vec()));
+        assert!(!store.exists(b"key2").unwrap());
+    }
+
+    #[test]
+    fn test_wal_ignores_torn_tail() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let wal_path = temp_dir.path().join("store.wal");
+
+        {
+            let store = WalStore::open_default(InMemoryStore::new(), &wal_path).unwrap();
+            store.set(b"key1", b"value1").unwrap();
+        }
+
+        // Simulate a partially written record
+        let mut file = OpenOptions::new().append(true).open(&wal_path).unwrap();
+        file.write_all(&[0, 0, 0, 50, 1, 2, 3, 4, 5]).unwrap();
+        drop(file);
+
+        let store = WalStore::open_default(InMemoryStore::new(), &wal_path).unwrap();
+        assert_eq!(store.recovered_records(), 1);
+        assert_eq!(store.get(b"key1").unwrap(), Some(b"value1".to_vec()));
+    }
+
+    #[test]
+    fn test_wal_logs_batch_as_one_record() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let wal_path = temp_dir.path().join("store.wal");
+        let batch = vec![
+            BatchOperation::put(column_families::DEFAULT, b"key2".to_vec(), b"value2".to_vec()),
+            BatchOperation::delete(column_families::DEFAULT, b"key1".to_vec()),
+        ];
+
+        {
+            let store = WalStore::open_default(InMemoryStore::new(), &wal_path).unwrap();
+            store.set(b"key1", b"value1").unwrap();
+            store.write_batch(batch.clone()).unwrap();
+        }
+
+        let store = WalStore::open_default(InMemoryStore::new(), &wal_path).unwrap();
+        assert_eq!(store.recovered_records(), 2);
+        assert!(!store.exists(b"key1").unwrap());
+        assert_eq!(store.get(b"key2").unwrap(), Some(b"value2".to_vec()));
+        drop(store);
+
+        // A batch torn by a crash is dropped as a whole
+        {
+            let store = WalStore::open_default(InMemoryStore::new(), &wal_path).unwrap();
+            store.set(b"key1", b"value1").unwrap();
+            store.write_batch(batch).unwrap();
+        }
+        let len = fs::metadata(&wal_path).unwrap().len();
+        OpenOptions::new().write(true).open(&wal_path).unwrap().set_len(len - 1).unwrap();
+
+        let store = WalStore::open_default(InMemoryStore::new(), &wal_path).unwrap();
+        assert_eq!(store.recovered_records(), 1);
+        assert_eq!(store.get(b"key1").unwrap(), Some(b"value1".to_vec()));
+        assert!(!store.exists(b"key2").unwrap());
+    }
+
+    #[test]
+    fn test_wal_compaction() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = WalConfig {
+            sync_policy: SyncPolicy::EveryN(4),
+            compaction_threshold: 128,
+        };
+        let store = WalStore::open(
+            BinaryStore::new(temp_dir.path()).unwrap(),
+            temp_dir.path().join("store.wal"),
+            config,
+        ).unwrap();
+
+        store.set(b"key", &[7u8; 16]).unwrap();
+        assert!(store.log_size() > 0);
+
+        // Large write crosses the threshold and triggers compaction
+        store.set(b"big", &[1u8; 256]).unwrap();
+        assert_eq!(store.log_size(), 0);
+        assert!(temp_dir.path().join("data.bin").exists());
+
+        store.set(b"key", b"again").unwrap();
+        store.flush().unwrap();
+        assert_eq!(store.log_size(), 0);
+    }
 }
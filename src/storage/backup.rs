@@ -0,0 +1,494 @@
+//! Backup and restore with integrity verification.
+//!
+//! Backups are tar archives holding a JSON manifest and a zstd-compressed
+//! bincode dump of the store's key/value pairs. Full backups capture every
+//! entry; incremental backups only record entries changed since their parent,
+//! so restoring one replays the chain back to the last full backup.
+//!
+//! Every restore is first materialized in memory and checked against the
+//! manifest's data checksum and state root before the target store is touched.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::storage::backend::{InMemoryStore, StorageBackend, StorageKey, StorageValue};
+use crate::storage::state::StateManager;
+use crate::utils::crypto::Hash;
+
+/// Backup archive format version
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// File extension for backup archives
+pub const BACKUP_EXTENSION: &str = "zkbak";
+
+/// Archive entry holding the manifest
+const MANIFEST_ENTRY: &str = "manifest.json";
+
+/// Archive entry holding the compressed data
+const DATA_ENTRY: &str = "data.bin.zst";
+
+/// Zstd compression level
+const ZSTD_LEVEL: i32 = 3;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// MANIFEST
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Kind of backup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BackupKind {
+    /// Complete copy of the store
+    Full,
+    /// Changes since the parent backup
+    Incremental,
+}
+
+/// Backup metadata stored alongside the data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// Archive format version
+    pub format_version: u32,
+    /// Unique backup identifier
+    pub id: String,
+    /// Monotonic sequence number within the backup directory
+    pub sequence: u64,
+    /// Backup kind
+    pub kind: BackupKind,
+    /// Parent backup (incremental backups only)
+    pub parent: Option<String>,
+    /// Creation timestamp
+    pub created_at: u64,
+    /// Protocol block height at backup time
+    pub block_height: u64,
+    /// State root at backup time
+    pub state_root: Hash,
+    /// Number of entries written
+    pub entry_count: usize,
+    /// Number of deletions recorded (incremental backups only)
+    pub deleted_count: usize,
+    /// SHA256 of the uncompressed data
+    pub data_checksum: Hash,
+}
+
+/// Entries recorded in a backup
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct BackupData {
+    /// Entries to insert or overwrite
+    upserts: Vec<(StorageKey, StorageValue)>,
+    /// Keys to remove
+    deletions: Vec<StorageKey>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// BACKUP MANAGER
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Creates, verifies, and restores backups in a directory
+#[derive(Debug, Clone)]
+pub struct BackupManager {
+    /// Directory holding backup archives
+    dir: PathBuf,
+}
+
+impl BackupManager {
+    /// Create a backup manager for the given directory
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| {
+            Error::Internal(format!("Failed to create backup directory: {}", e))
+        })?;
+        Ok(Self { dir })
+    }
+
+    /// Backup directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Create a full backup of the store
+    pub fn create_full<B: StorageBackend>(
+        &self,
+        manager: &StateManager<B>,
+        created_at: u64,
+    ) -> Result<BackupManifest> {
+        let entries = Self::read_store(manager.backend())?;
+        let data = BackupData {
+            upserts: entries.into_iter().collect(),
+            deletions: Vec::new(),
+        };
+        self.write(manager, BackupKind::Full, None, data, created_at)
+    }
+
+    /// Create an incremental backup relative to the latest backup
+    pub fn create_incremental<B: StorageBackend>(
+        &self,
+        manager: &StateManager<B>,
+        created_at: u64,
+    ) -> Result<BackupManifest> {
+        let parent = self.latest()?.ok_or_else(|| Error::InvalidParameter {
+            name: "backup".into(),
+            reason: "Incremental backup requires an existing backup".into(),
+        })?;
+
+        let base = self.materialize(&parent.id)?;
+        let current = Self::read_store(manager.backend())?;
+
+        let upserts = current
+            .iter()
+            .filter(|(k, v)| base.get(*k) != Some(*v))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        let deletions = base
+            .keys()
+            .filter(|k| !current.contains_key(*k))
+            .cloned()
+            .collect();
+
+        let data = BackupData { upserts, deletions };
+        self.write(manager, BackupKind::Incremental, Some(parent.id), data, created_at)
+    }
+
+    /// List all backups ordered by sequence
+    pub fn list(&self) -> Result<Vec<BackupManifest>> {
+        let entries = fs::read_dir(&self.dir).map_err(|e| {
+            Error::Internal(format!("Failed to read backup directory: {}", e))
+        })?;
+
+        let mut manifests = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|e| Error::Internal(format!("Failed to read backup entry: {}", e)))?
+                .path();
+            if path.extension().and_then(|e| e.to_str()) == Some(BACKUP_EXTENSION) {
+                manifests.push(Self::read_archive(&path)?.0);
+            }
+        }
+
+        manifests.sort_by_key(|m| m.sequence);
+        Ok(manifests)
+    }
+
+    /// Most recent backup, if any
+    pub fn latest(&self) -> Result<Option<BackupManifest>> {
+        Ok(self.list()?.pop())
+    }
+
+    /// Verify a backup and its parent chain without restoring it
+    pub fn verify(&self, id: &str) -> Result<BackupManifest> {
+        let (manifest, _) = self.load_verified(id)?;
+        Ok(manifest)
+    }
+
+    /// Restore a backup into the store, replacing its contents
+    pub fn restore<B: StorageBackend>(
+        &self,
+        id: &str,
+        manager: &StateManager<B>,
+    ) -> Result<BackupManifest> {
+        let (manifest, entries) = self.load_verified(id)?;
+
+        let backend = manager.backend();
+        backend.clear()?;
+        for (key, value) in &entries {
+            backend.set(key, value)?;
+        }
+        manager.flush()?;
+
+        Ok(manifest)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // INTERNALS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Materialize a backup and check its state root
+    fn load_verified(&self, id: &str) -> Result<(BackupManifest, BTreeMap<StorageKey, StorageValue>)> {
+        let manifest = Self::read_archive(&self.archive_path(id))?.0;
+        let entries = self.materialize(id)?;
+
+        let scratch = StateManager::new(InMemoryStore::new());
+        for (key, value) in &entries {
+            scratch.backend().set(key, value)?;
+        }
+
+        let state_root = scratch.compute_state_root()?;
+        if state_root != manifest.state_root {
+            return Err(Error::InvariantViolation(format!(
+                "Backup {} state root mismatch: expected {}, got {}",
+                id,
+                manifest.state_root.to_hex(),
+                state_root.to_hex()
+            )));
+        }
+
+        Ok((manifest, entries))
+    }
+
+    /// Replay a backup chain into a key/value map
+    fn materialize(&self, id: &str) -> Result<BTreeMap<StorageKey, StorageValue>> {
+        let (manifest, data) = Self::read_archive(&self.archive_path(id))?;
+
+        let mut entries = match (&manifest.kind, &manifest.parent) {
+            (BackupKind::Full, _) => BTreeMap::new(),
+            (BackupKind::Incremental, Some(parent)) => self.materialize(parent)?,
+            (BackupKind::Incremental, None) => {
+                return Err(Error::Deserialization(format!(
+                    "Incremental backup {} has no parent",
+                    id
+                )));
+            }
+        };
+
+        let data = data.ok_or_else(|| {
+            Error::Deserialization(format!("Backup {} is missing its data", id))
+        })?;
+        for key in data.deletions {
+            entries.remove(&key);
+        }
+        entries.extend(data.upserts);
+
+        Ok(entries)
+    }
+
+    /// Write a backup archive
+    fn write<B: StorageBackend>(
+        &self,
+        manager: &StateManager<B>,
+        kind: BackupKind,
+        parent: Option<String>,
+        data: BackupData,
+        created_at: u64,
+    ) -> Result<BackupManifest> {
+        let raw = bincode::serialize(&data).map_err(|e| {
+            Error::Serialization(format!("Failed to serialize backup data: {}", e))
+        })?;
+        let data_checksum = Hash::sha256(&raw);
+        let compressed = zstd::encode_all(raw.as_slice(), ZSTD_LEVEL).map_err(|e| {
+            Error::Serialization(format!("Failed to compress backup data: {}", e))
+        })?;
+
+        let sequence = self.latest()?.map(|m| m.sequence + 1).unwrap_or(0);
+        let id = format!("{:06}-{}", sequence, &data_checksum.to_hex()[..12]);
+        let block_height = manager.load_protocol_state().map(|s| s.block_height).unwrap_or(0);
+
+        let manifest = BackupManifest {
+            format_version: BACKUP_FORMAT_VERSION,
+            id: id.clone(),
+            sequence,
+            kind,
+            parent,
+            created_at,
+            block_height,
+            state_root: manager.compute_state_root()?,
+            entry_count: data.upserts.len(),
+            deleted_count: data.deletions.len(),
+            data_checksum,
+        };
+        let manifest_json = serde_json::to_vec_pretty(&manifest).map_err(|e| {
+            Error::Serialization(format!("Failed to serialize backup manifest: {}", e))
+        })?;
+
+        let file = File::create(self.archive_path(&id)).map_err(|e| {
+            Error::Internal(format!("Failed to create backup archive: {}", e))
+        })?;
+        let mut builder = tar::Builder::new(file);
+        Self::append_entry(&mut builder, MANIFEST_ENTRY, &manifest_json)?;
+        Self::append_entry(&mut builder, DATA_ENTRY, &compressed)?;
+        builder
+            .into_inner()
+            .and_then(|file| file.sync_all())
+            .map_err(|e| Error::Internal(format!("Failed to finish backup archive: {}", e)))?;
+
+        Ok(manifest)
+    }
+
+    /// Append a single file to a tar archive
+    fn append_entry(builder: &mut tar::Builder<File>, name: &str, bytes: &[u8]) -> Result<()> {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(bytes.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, name, bytes).map_err(|e| {
+            Error::Internal(format!("Failed to write backup entry {}: {}", name, e))
+        })
+    }
+
+    /// Read and checksum a backup archive
+    fn read_archive(path: &Path) -> Result<(BackupManifest, Option<BackupData>)> {
+        let file = File::open(path).map_err(|e| {
+            Error::Internal(format!("Failed to open backup {}: {}", path.display(), e))
+        })?;
+        let mut archive = tar::Archive::new(file);
+
+        let mut manifest_bytes = None;
+        let mut data_bytes = None;
+
+        let entries = archive.entries().map_err(|e| {
+            Error::Deserialization(format!("Invalid backup archive: {}", e))
+        })?;
+        for entry in entries {
+            let mut entry = entry.map_err(|e| {
+                Error::Deserialization(format!("Invalid backup entry: {}", e))
+            })?;
+            let name = entry
+                .path()
+                .map_err(|e| Error::Deserialization(format!("Invalid backup entry: {}", e)))?
+                .to_string_lossy()
+                .into_owned();
+
+            let mut bytes = Vec::new();
+            entry.read_to_end(&mut bytes).map_err(|e| {
+                Error::Deserialization(format!("Failed to read backup entry: {}", e))
+            })?;
+
+            match name.as_str() {
+                MANIFEST_ENTRY => manifest_bytes = Some(bytes),
+                DATA_ENTRY => data_bytes = Some(bytes),
+                _ => {}
+            }
+        }
+
+        let manifest: BackupManifest = manifest_bytes
+            .ok_or_else(|| Error::Deserialization("Backup has no manifest".into()))
+            .and_then(|bytes| {
+                serde_json::from_slice(&bytes).map_err(|e| {
+                    Error::Deserialization(format!("Invalid backup manifest: {}", e))
+                })
+            })?;
+
+        if manifest.format_version > BACKUP_FORMAT_VERSION {
+            return Err(Error::Deserialization(format!(
+                "Unsupported backup format version {}",
+                manifest.format_version
+            )));
+        }
+
+        let data = match data_bytes {
+            Some(compressed) => {
+                let raw = zstd::decode_all(compressed.as_slice()).map_err(|e| {
+                    Error::Deserialization(format!("Failed to decompress backup data: {}", e))
+                })?;
+                if Hash::sha256(&raw) != manifest.data_checksum {
+                    return Err(Error::InvariantViolation(format!(
+                        "Backup {} data checksum mismatch",
+                        manifest.id
+                    )));
+                }
+                Some(bincode::deserialize(&raw).map_err(|e| {
+                    Error::Deserialization(format!("Invalid backup data: {}", e))
+                })?)
+            }
+            None => None,
+        };
+
+        Ok((manifest, data))
+    }
+
+    /// Read every entry of a backend in key order
+    fn read_store<B: StorageBackend>(backend: &B) -> Result<BTreeMap<StorageKey, StorageValue>> {
+        let mut entries = BTreeMap::new();
+        for key in backend.keys()? {
+            if let Some(value) = backend.get(&key)? {
+                entries.insert(key, value);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Path of a backup archive by ID
+    fn archive_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", id, BACKUP_EXTENSION))
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::cdp::CDP;
+    use crate::utils::crypto::KeyPair;
+
+    fn populated_manager() -> StateManager<InMemoryStore> {
+        let manager = StateManager::new(InMemoryStore::new());
+        manager.initialize_if_needed().unwrap();
+
+        let keypair = KeyPair::generate();
+        let cdp = CDP::with_collateral(*keypair.public_key(), 100_000_000, 1, 100).unwrap();
+        manager.save_cdp(&cdp).unwrap();
+        manager.save_balance(keypair.public_key(), 5000).unwrap();
+        manager
+    }
+
+    #[test]
+    fn test_full_backup_and_restore() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let backups = BackupManager::new(temp_dir.path()).unwrap();
+        let manager = populated_manager();
+        let root = manager.compute_state_root().unwrap();
+
+        let manifest = backups.create_full(&manager, 1000).unwrap();
+        assert_eq!(manifest.kind, BackupKind::Full);
+        assert_eq!(manifest.state_root, root);
+
+        let target = StateManager::new(InMemoryStore::new());
+        backups.restore(&manifest.id, &target).unwrap();
+        assert_eq!(target.compute_state_root().unwrap(), root);
+        assert_eq!(target.load_all_cdps().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_incremental_backup_chain() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let backups = BackupManager::new(temp_dir.path()).unwrap();
+        let manager = populated_manager();
+
+        backups.create_full(&manager, 1000).unwrap();
+
+        let keypair = KeyPair::generate();
+        let cdp = CDP::with_collateral(*keypair.public_key(), 50_000_000, 2, 200).unwrap();
+        manager.save_cdp(&cdp).unwrap();
+
+        let incremental = backups.create_incremental(&manager, 2000).unwrap();
+        assert_eq!(incremental.kind, BackupKind::Incremental);
+        assert_eq!(incremental.entry_count, 1);
+        assert_eq!(backups.list().unwrap().len(), 2);
+
+        let target = StateManager::new(InMemoryStore::new());
+        backups.restore(&incremental.id, &target).unwrap();
+        assert_eq!(target.load_all_cdps().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_incremental_requires_base() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let backups = BackupManager::new(temp_dir.path()).unwrap();
+        let manager = populated_manager();
+
+        assert!(backups.create_incremental(&manager, 1000).is_err());
+    }
+
+    #[test]
+    fn test_corrupted_backup_rejected() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let backups = BackupManager::new(temp_dir.path()).unwrap();
+        let manager = populated_manager();
+
+        let manifest = backups.create_full(&manager, 1000).unwrap();
+        let path = backups.archive_path(&manifest.id);
+
+        // Flip a byte inside the compressed data entry
+        let mut bytes = fs::read(&path).unwrap();
+        let last_data_byte = bytes.iter().rposition(|b| *b != 0).unwrap();
+        bytes[last_data_byte] ^= 0xff;
+        fs::write(&path, bytes).unwrap();
+
+        assert!(backups.verify(&manifest.id).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+//! Advisory locking of on-disk stores.
+//!
+//! File and binary stores keep their data in memory and rewrite the whole
+//! data file on flush, so two processes writing the same directory silently
+//! overwrite each other's changes. A writable store therefore takes a `LOCK`
+//! file in its directory, recording who holds it; a second writer fails with
+//! [`Error::StorageLocked`] naming that owner. Read-only opens don't take
+//! the lock.
+//!
+//! The lock is removed when the store is dropped. A process that crashes
+//! leaves it behind, and [`StoreLock::force_unlock`] removes it once the
+//! owner is known to be gone.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+
+/// Name of the lock file in a store directory
+pub const LOCK_FILE: &str = "LOCK";
+
+/// Process holding a store lock
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockOwner {
+    /// Process id
+    pub pid: u32,
+    /// Host the process runs on
+    pub host: String,
+    /// Program name
+    pub program: String,
+    /// Unix timestamp the lock was taken at
+    pub acquired_at: u64,
+}
+
+impl LockOwner {
+    /// Owner record for the current process
+    fn current() -> Self {
+        let program = std::env::args()
+            .next()
+            .and_then(|arg| Path::new(&arg).file_name().map(|name| name.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "unknown".to_string());
+
+        Self {
+            pid: std::process::id(),
+            host: hostname(),
+            program,
+            acquired_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        }
+    }
+}
+
+impl fmt::Display for LockOwner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (pid {}) on {} since {}", self.program, self.pid, self.host, self.acquired_at)
+    }
+}
+
+/// Exclusive lock on a store directory, released on drop
+#[derive(Debug)]
+pub struct StoreLock {
+    /// Path of the lock file
+    path: PathBuf,
+    /// Owner record written to the lock file
+    owner: LockOwner,
+}
+
+impl StoreLock {
+    /// Lock the store in `dir`, failing if another owner holds it
+    pub fn acquire<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let path = dir.as_ref().join(LOCK_FILE);
+        let owner = LockOwner::current();
+
+        let mut file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                let holder = Self::owner_of(dir.as_ref())?
+                    .map(|owner| owner.to_string())
+                    .unwrap_or_else(|| "an unknown process".to_string());
+                return Err(Error::StorageLocked {
+                    path: dir.as_ref().display().to_string(),
+                    owner: holder,
+                });
+            }
+            Err(e) => return Err(Error::Internal(format!("Failed to create lock file: {}", e))),
+        };
+
+        let data = serde_json::to_vec_pretty(&owner).map_err(|e| Error::Serialization(e.to_string()))?;
+        file.write_all(&data)
+            .and_then(|_| file.sync_all())
+            .map_err(|e| Error::Internal(format!("Failed to write lock file: {}", e)))?;
+
+        Ok(Self { path, owner })
+    }
+
+    /// Owner recorded in the lock on `dir`, if it is locked. A lock file that
+    /// can't be parsed (e.g. one torn by a crash) still counts as held.
+    pub fn owner_of<P: AsRef<Path>>(dir: P) -> Result<Option<LockOwner>> {
+        match fs::read(dir.as_ref().join(LOCK_FILE)) {
+            Ok(data) => Ok(serde_json::from_slice(&data).ok()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Error::Internal(format!("Failed to read lock file: {}", e))),
+        }
+    }
+
+    /// Whether `dir` is locked
+    pub fn is_locked<P: AsRef<Path>>(dir: P) -> bool {
+        dir.as_ref().join(LOCK_FILE).exists()
+    }
+
+    /// Remove the lock on `dir` regardless of its owner, returning the
+    /// removed owner record. Only safe once that owner is known to be gone.
+    pub fn force_unlock<P: AsRef<Path>>(dir: P) -> Result<Option<LockOwner>> {
+        let owner = Self::owner_of(dir.as_ref())?;
+        match fs::remove_file(dir.as_ref().join(LOCK_FILE)) {
+            Ok(()) => Ok(owner),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Error::Internal(format!("Failed to remove lock file: {}", e))),
+        }
+    }
+
+    /// Owner record of this lock
+    pub fn owner(&self) -> &LockOwner {
+        &self.owner
+    }
+}
+
+impl Drop for StoreLock {
+    fn drop(&mut self) {
+        // Leave a lock that was forcibly taken over by someone else in place
+        let ours = fs::read(&self.path)
+            .ok()
+            .and_then(|data| serde_json::from_slice::<LockOwner>(&data).ok())
+            .is_some_and(|owner| owner == self.owner);
+        if ours {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Host name of this machine, best effort
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_is_exclusive() {
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        let lock = StoreLock::acquire(temp_dir.path()).unwrap();
+        assert_eq!(lock.owner().pid, std::process::id());
+        assert_eq!(StoreLock::owner_of(temp_dir.path()).unwrap().as_ref(), Some(lock.owner()));
+
+        let err = StoreLock::acquire(temp_dir.path()).unwrap_err();
+        assert!(matches!(err, Error::StorageLocked { .. }));
+        assert!(err.to_string().contains(&format!("pid {}", std::process::id())));
+
+        drop(lock);
+        assert!(!StoreLock::is_locked(temp_dir.path()));
+        StoreLock::acquire(temp_dir.path()).unwrap();
+    }
+
+    #[test]
+    fn test_force_unlock() {
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        // A lock left behind by a crashed process
+        let stale = StoreLock::acquire(temp_dir.path()).unwrap();
+        let owner = stale.owner().clone();
+        std::mem::forget(stale);
+
+        assert_eq!(StoreLock::force_unlock(temp_dir.path()).unwrap(), Some(owner));
+        assert_eq!(StoreLock::force_unlock(temp_dir.path()).unwrap(), None);
+        StoreLock::acquire(temp_dir.path()).unwrap();
+    }
+}
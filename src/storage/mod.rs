@@ -5,12 +5,17 @@
 //! - Token balance tracking
 //! - Protocol configuration persistence
 //! - Transaction history
+//! - CSV and Parquet export of history
+//! - Checksummed backups with incremental snapshots
+//! - Streaming CDP export and import for migrations
+//! - Advisory locking so only one process writes a store directory
 //!
 //! ## Backends
 //!
 //! - **InMemoryStore**: Fast, ephemeral storage for testing
 //! - **FileStore**: JSON file-based persistence for development
 //! - **BinaryStore**: Compact binary format
+//! - **WalStore**: Write-ahead log wrapper with crash recovery for any backend
 //! - **RocksStore**: Production-grade persistence using RocksDB
 //!
 //! ## Usage
@@ -31,10 +36,20 @@
 //! ```
 
 pub mod backend;
+#[cfg(feature = "std")]
+pub mod backup;
+pub mod export;
+pub mod lock;
+pub mod migrate;
 pub mod rocks;
 pub mod state;
 
 pub use backend::*;
+#[cfg(feature = "std")]
+pub use backup::{BackupKind, BackupManager, BackupManifest};
+pub use export::{ExportDataset, ExportFilter, ExportFormat, ExportTable};
+pub use lock::{LockOwner, StoreLock, LOCK_FILE};
+pub use migrate::CdpStreamSummary;
 pub use rocks::{RocksConfig, BatchOperation, column_families};
 #[cfg(feature = "rocksdb-storage")]
 pub use rocks::RocksStore;
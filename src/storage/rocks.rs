@@ -404,10 +404,15 @@ impl StorageBackend for RocksStore {
         }
         Ok(())
     }
+
+    fn write_batch(&self, operations: Vec<BatchOperation>) -> Result<()> {
+        // Atomic, single write to the WAL
+        RocksStore::write_batch(self, operations)
+    }
 }
 
 /// Batch operation for atomic writes
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BatchOperation {
     /// Put a key-value pair
     Put {
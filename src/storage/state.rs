@@ -3,15 +3,42 @@
 //! This module provides high-level state management for the zkUSD protocol,
 //! including CDP state, token balances, and protocol configuration.
 
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 
-use crate::core::cdp::{CDP, CDPId, CDPStatus};
+use crate::charms::bridge::Bridge;
+use crate::core::cdp::{CDP, CDPId, CDPStatus, OwnerPolicy};
 use crate::core::config::ProtocolConfig;
+use crate::core::peg::PegController;
+use crate::core::savings::SavingsPot;
+use crate::core::token::ZkUSD;
+use crate::core::vault::Vault;
 use crate::error::{Error, Result};
+use crate::liquidation::engine::AuctionHouse;
+use crate::liquidation::frontend::FrontendRegistry;
 use crate::liquidation::stability_pool::StabilityPool;
+use crate::monitoring::aggregates::ProtocolAggregates;
+use crate::monitoring::anomaly::BlockActivity;
+use crate::oracle::price_feed::CrossRateFeed;
+use crate::oracle::registry::OracleRegistry;
+use crate::protocol::audit::{verify_chain, AuditEntry, AuditLog, AuditReport};
+use crate::protocol::codec::{
+    canonical_bytes, canonical_hash, canonical_hash_with, CDP_DOMAIN, CODEC_VERSION, STATE_DOMAIN,
+};
+use crate::protocol::events::{AuditAnchoredEvent, BridgeOutEvent, ProtocolEvent};
+use crate::protocol::genesis::Genesis;
+use crate::protocol::nonces::NonceManager;
+use crate::protocol::pauses::OperationPauses;
+use crate::protocol::ramps::ParameterRamps;
+use crate::protocol::receipts::Receipt;
+use crate::protocol::sync::BlockRecord;
+use crate::protocol::treasury::Treasury;
 use crate::storage::backend::{make_key, prefixes, StorageBackend, TypedStore};
-use crate::utils::crypto::{Hash, PublicKey};
+use crate::utils::constants::{BLOCK_TIME_SECS, SP_GAIN_PRECISION, SP_SCALE_FACTOR};
+use crate::utils::crypto::{Hash, HashScheme, PublicKey};
+
+/// Current on-disk schema version
+pub const SCHEMA_VERSION: u32 = 13;
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // PROTOCOL STATE
@@ -48,7 +75,7 @@ impl Default for ProtocolState {
             active_cdps: 0,
             block_height: 0,
             last_update: 0,
-            version: 1,
+            version: SCHEMA_VERSION,
         }
     }
 }
@@ -111,11 +138,94 @@ impl ProtocolState {
 
     /// Compute state hash
     pub fn hash(&self) -> Hash {
-        let data = bincode::serialize(self).unwrap_or_default();
-        Hash::sha256(&data)
+        canonical_hash(STATE_DOMAIN, self).unwrap_or_default()
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// PRUNING
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// History retention mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PruningMode {
+    /// Keep all history forever
+    #[default]
+    Archive,
+    /// Keep only history from the most recent blocks
+    Pruned {
+        /// Number of recent blocks of history to retain
+        keep_blocks: u64,
+    },
+}
+
+impl PruningMode {
+    /// First block height whose history is retained, if anything is prunable
+    pub fn cutoff_height(&self, current_height: u64) -> Option<u64> {
+        match self {
+            PruningMode::Archive => None,
+            PruningMode::Pruned { keep_blocks } => {
+                let cutoff = current_height.saturating_sub(*keep_blocks);
+                (cutoff > 0).then_some(cutoff)
+            }
+        }
+    }
+}
+
+/// Counts of entries removed by a prune pass
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PruneStats {
+    /// Transaction records removed
+    pub transactions: usize,
+    /// Price history entries removed
+    pub price_entries: usize,
+    /// Event entries removed
+    pub event_entries: usize,
+    /// Historical CDP versions removed
+    #[serde(default)]
+    pub cdp_versions: usize,
+    /// Block operation logs removed
+    #[serde(default)]
+    pub block_entries: usize,
+    /// Settled operation receipts removed
+    #[serde(default)]
+    pub receipts: usize,
+}
+
+impl PruneStats {
+    /// Total entries removed
+    pub fn total(&self) -> usize {
+        self.transactions
+            + self.price_entries
+            + self.event_entries
+            + self.cdp_versions
+            + self.block_entries
+            + self.receipts
     }
 }
 
+/// Storage usage breakdown by data type
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageStats {
+    /// Number of stored CDPs
+    pub cdps: usize,
+    /// Number of stored balances
+    pub balances: usize,
+    /// Number of transaction records
+    pub transactions: usize,
+    /// Number of price entries (including latest)
+    pub price_entries: usize,
+    /// Number of per-block event entries
+    pub event_entries: usize,
+    /// Number of historical CDP versions
+    #[serde(default)]
+    pub cdp_versions: usize,
+    /// Total number of keys
+    pub total_keys: usize,
+    /// Total size of keys and values in bytes
+    pub total_bytes: u64,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // STATE MANAGER
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -124,6 +234,10 @@ impl ProtocolState {
 pub struct StateManager<B: StorageBackend> {
     /// Underlying storage
     store: TypedStore<B>,
+    /// History retention mode
+    pruning: PruningMode,
+    /// Whether every saved CDP is also kept as a version for its block
+    cdp_history: bool,
 }
 
 impl<B: StorageBackend> StateManager<B> {
@@ -131,9 +245,40 @@ impl<B: StorageBackend> StateManager<B> {
     pub fn new(backend: B) -> Self {
         Self {
             store: TypedStore::new(backend),
+            pruning: PruningMode::Archive,
+            cdp_history: false,
         }
     }
 
+    /// Set the history retention mode
+    pub fn with_pruning(mut self, mode: PruningMode) -> Self {
+        self.pruning = mode;
+        self
+    }
+
+    /// Keep a copy of each CDP per block it changed in, so
+    /// [`load_cdp_at`](Self::load_cdp_at) can answer historical queries.
+    /// Versions are retained according to the pruning mode.
+    pub fn with_cdp_history(mut self) -> Self {
+        self.cdp_history = true;
+        self
+    }
+
+    /// Whether CDP versions are being recorded
+    pub fn cdp_history_enabled(&self) -> bool {
+        self.cdp_history
+    }
+
+    /// Get the history retention mode
+    pub fn pruning_mode(&self) -> PruningMode {
+        self.pruning
+    }
+
+    /// Get the underlying storage backend
+    pub fn backend(&self) -> &B {
+        self.store.backend()
+    }
+
     // ═══════════════════════════════════════════════════════════════════════════
     // PROTOCOL STATE
     // ═══════════════════════════════════════════════════════════════════════════
@@ -152,212 +297,2202 @@ impl<B: StorageBackend> StateManager<B> {
         self.store.set(&key, state)
     }
 
-    /// Initialize protocol state if not exists
+    /// Initialize protocol state if not exists.
+    ///
+    /// Existing databases are migrated to `SCHEMA_VERSION` first; databases
+    /// written by a newer build are rejected.
     pub fn initialize_if_needed(&self) -> Result<ProtocolState> {
         let key = make_key(prefixes::CONFIG, b"state");
         if self.store.exists(&key)? {
+            Migrator::default_migrations().run(self)?;
             self.load_protocol_state()
         } else {
             let state = ProtocolState::default();
             self.save_protocol_state(&state)?;
+            self.save_schema_version(SCHEMA_VERSION)?;
             Ok(state)
         }
     }
 
-    // ═══════════════════════════════════════════════════════════════════════════
-    // CDP MANAGEMENT
-    // ═══════════════════════════════════════════════════════════════════════════
+    /// Detect the on-disk schema version.
+    ///
+    /// Version 1 databases predate the dedicated version key, so the version
+    /// recorded in the protocol state is used as a fallback.
+    pub fn schema_version(&self) -> Result<Option<u32>> {
+        let key = make_key(prefixes::CONFIG, b"schema_version");
+        if let Some(version) = self.store.get::<u32>(&key)? {
+            return Ok(Some(version));
+        }
+
+        let state_key = make_key(prefixes::CONFIG, b"state");
+        if self.store.exists(&state_key)? {
+            return Ok(Some(1));
+        }
+
+        Ok(None)
+    }
+
+    /// Record the on-disk schema version
+    pub fn save_schema_version(&self, version: u32) -> Result<()> {
+        let key = make_key(prefixes::CONFIG, b"schema_version");
+        self.store.set(&key, &version)
+    }
+
+    /// Hash scheme of the state root (SHA256 unless set)
+    pub fn load_hash_scheme(&self) -> Result<HashScheme> {
+        let key = make_key(prefixes::CONFIG, b"hash_scheme");
+        Ok(self.store.get(&key)?.unwrap_or_default())
+    }
+
+    /// Set the hash scheme of the state root. Every node of a network must
+    /// use the same one, so it is chosen before the first block; snapshots
+    /// carry it to syncing peers.
+    pub fn save_hash_scheme(&self, scheme: HashScheme) -> Result<()> {
+        let key = make_key(prefixes::CONFIG, b"hash_scheme");
+        self.store.set(&key, &scheme)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // CDP MANAGEMENT
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Load a CDP by ID
+    pub fn load_cdp(&self, id: &CDPId) -> Result<Option<CDP>> {
+        let key = make_key(prefixes::CDP, id.as_bytes());
+        self.store.get(&key)
+    }
+
+    /// Save a CDP, recording it as the version for `cdp.last_updated` when
+    /// CDP history is enabled
+    pub fn save_cdp(&self, cdp: &CDP) -> Result<()> {
+        let key = make_key(prefixes::CDP, cdp.id.as_bytes());
+        self.store.set(&key, cdp)?;
+        if self.cdp_history {
+            self.store.set(&Self::cdp_version_key(&cdp.id, cdp.last_updated), cdp)?;
+        }
+        Ok(())
+    }
+
+    /// Delete a CDP
+    pub fn delete_cdp(&self, id: &CDPId) -> Result<bool> {
+        let key = make_key(prefixes::CDP, id.as_bytes());
+        self.store.delete(&key)
+    }
+
+    /// Load all CDPs (in key order)
+    pub fn load_all_cdps(&self) -> Result<Vec<CDP>> {
+        let mut keys = self.store.list_prefix(prefixes::CDP)?;
+        keys.sort();
+        let mut cdps = Vec::new();
+
+        for key in keys {
+            if let Some(cdp) = self.store.get::<CDP>(&key)? {
+                cdps.push(cdp);
+            }
+        }
+
+        Ok(cdps)
+    }
+
+    /// Load CDPs by owner
+    pub fn load_cdps_by_owner(&self, owner: &PublicKey) -> Result<Vec<CDP>> {
+        let all_cdps = self.load_all_cdps()?;
+        Ok(all_cdps.into_iter().filter(|c| c.owner == *owner).collect())
+    }
+
+    /// Load active CDPs
+    pub fn load_active_cdps(&self) -> Result<Vec<CDP>> {
+        let all_cdps = self.load_all_cdps()?;
+        Ok(all_cdps
+            .into_iter()
+            .filter(|c| c.status == CDPStatus::Active || c.status == CDPStatus::AtRisk)
+            .collect())
+    }
+
+    /// Count CDPs
+    pub fn count_cdps(&self) -> Result<usize> {
+        let keys = self.store.list_prefix(prefixes::CDP)?;
+        Ok(keys.len())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // CDP HISTORY
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    fn cdp_version_key(id: &CDPId, block_height: u64) -> Vec<u8> {
+        let mut key = make_key(prefixes::CDP_HISTORY, id.as_bytes());
+        key.extend_from_slice(&block_height.to_be_bytes());
+        key
+    }
+
+    /// Block heights at which versions of a CDP are stored, ascending
+    pub fn cdp_version_heights(&self, id: &CDPId) -> Result<Vec<u64>> {
+        let prefix = make_key(prefixes::CDP_HISTORY, id.as_bytes());
+        let mut heights: Vec<u64> = self
+            .store
+            .list_prefix(&prefix)?
+            .iter()
+            .filter_map(|key| Self::key_suffix_u64(key, &prefix))
+            .collect();
+        heights.sort_unstable();
+        Ok(heights)
+    }
+
+    /// Load a CDP as it was at the end of `block_height`.
+    ///
+    /// Returns the latest version stored at or before that block, or `None`
+    /// if the CDP did not exist yet or its versions were pruned.
+    pub fn load_cdp_at(&self, id: &CDPId, block_height: u64) -> Result<Option<CDP>> {
+        let Some(height) = self
+            .cdp_version_heights(id)?
+            .into_iter()
+            .rev()
+            .find(|height| *height <= block_height)
+        else {
+            return Ok(None);
+        };
+        self.store.get(&Self::cdp_version_key(id, height))
+    }
+
+    /// Drop the version of a CDP recorded for `block_height`, used when
+    /// the block's changes to it are rolled back
+    pub fn discard_cdp_version(&self, id: &CDPId, block_height: u64) -> Result<bool> {
+        self.store.delete(&Self::cdp_version_key(id, block_height))
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // TOKEN BALANCES
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Load token balance for an account
+    pub fn load_balance(&self, account: &PublicKey) -> Result<u64> {
+        let key = make_key(prefixes::BALANCE, account.as_bytes());
+        Ok(self.store.get::<u64>(&key)?.unwrap_or(0))
+    }
+
+    /// Save token balance for an account
+    pub fn save_balance(&self, account: &PublicKey, balance: u64) -> Result<()> {
+        let key = make_key(prefixes::BALANCE, account.as_bytes());
+        self.store.set(&key, &balance)
+    }
+
+    /// Load all balances
+    pub fn load_all_balances(&self) -> Result<HashMap<Vec<u8>, u64>> {
+        let keys = self.store.list_prefix(prefixes::BALANCE)?;
+        let mut balances = HashMap::new();
+
+        for key in keys {
+            if let Some(balance) = self.store.get::<u64>(&key)? {
+                // Extract account key (remove prefix)
+                let account_key = key[prefixes::BALANCE.len()..].to_vec();
+                balances.insert(account_key, balance);
+            }
+        }
+
+        Ok(balances)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // LEDGER
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Load the token ledger
+    pub fn load_token(&self) -> Result<Option<ZkUSD>> {
+        let key = make_key(prefixes::TOKEN, b"main");
+        self.store.get(&key)
+    }
+
+    /// Save the token ledger
+    pub fn save_token(&self, token: &ZkUSD) -> Result<()> {
+        let key = make_key(prefixes::TOKEN, b"main");
+        self.store.set(&key, token)
+    }
+
+    /// Load the collateral vault
+    pub fn load_vault(&self) -> Result<Option<Vault>> {
+        let key = make_key(prefixes::VAULT, b"main");
+        self.store.get(&key)
+    }
+
+    /// Save the collateral vault
+    pub fn save_vault(&self, vault: &Vault) -> Result<()> {
+        let key = make_key(prefixes::VAULT, b"main");
+        self.store.set(&key, vault)
+    }
+
+    /// Load the nonce window of each signer
+    pub fn load_nonces(&self) -> Result<Option<NonceManager>> {
+        let key = make_key(prefixes::NONCE, b"main");
+        self.store.get(&key)
+    }
+
+    /// Save the nonce window of each signer
+    pub fn save_nonces(&self, nonces: &NonceManager) -> Result<()> {
+        let key = make_key(prefixes::NONCE, b"main");
+        self.store.set(&key, nonces)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // STABILITY POOL
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Load stability pool state
+    pub fn load_stability_pool(&self) -> Result<Option<StabilityPool>> {
+        let key = make_key(prefixes::STABILITY_POOL, b"main");
+        self.store.get(&key)
+    }
+
+    /// Save stability pool state
+    pub fn save_stability_pool(&self, pool: &StabilityPool) -> Result<()> {
+        let key = make_key(prefixes::STABILITY_POOL, b"main");
+        self.store.set(&key, pool)
+    }
+
+    /// Load stability pool frontends
+    pub fn load_frontends(&self) -> Result<Option<FrontendRegistry>> {
+        let key = make_key(prefixes::FRONTENDS, b"main");
+        self.store.get(&key)
+    }
+
+    /// Save stability pool frontends
+    pub fn save_frontends(&self, frontends: &FrontendRegistry) -> Result<()> {
+        let key = make_key(prefixes::FRONTENDS, b"main");
+        self.store.set(&key, frontends)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // BRIDGE
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Load bridge state
+    pub fn load_bridge(&self) -> Result<Option<Bridge>> {
+        let key = make_key(prefixes::BRIDGE, b"main");
+        self.store.get(&key)
+    }
+
+    /// Save bridge state
+    pub fn save_bridge(&self, bridge: &Bridge) -> Result<()> {
+        let key = make_key(prefixes::BRIDGE, b"main");
+        self.store.set(&key, bridge)
+    }
+
+    /// Index an outbound transfer by its sequence number.
+    ///
+    /// Relayers read transfers back with [`Self::load_bridge_transfers`].
+    /// The index is kept in every pruning mode.
+    pub fn save_bridge_transfer(&self, event: &BridgeOutEvent) -> Result<()> {
+        let key = make_key(prefixes::BRIDGE_OUT, &event.sequence.to_be_bytes());
+        self.store.set(&key, event)
+    }
+
+    /// Load up to `limit` outbound transfers starting at `from_sequence`
+    pub fn load_bridge_transfers(&self, from_sequence: u64, limit: usize) -> Result<Vec<BridgeOutEvent>> {
+        let mut transfers = Vec::new();
+        let mut sequence = from_sequence;
+        while transfers.len() < limit {
+            let key = make_key(prefixes::BRIDGE_OUT, &sequence.to_be_bytes());
+            match self.store.get(&key)? {
+                Some(event) => transfers.push(event),
+                None => break,
+            }
+            sequence += 1;
+        }
+        Ok(transfers)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // SAVINGS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Load savings pot state
+    pub fn load_savings(&self) -> Result<Option<SavingsPot>> {
+        let key = make_key(prefixes::SAVINGS, b"main");
+        self.store.get(&key)
+    }
+
+    /// Save savings pot state
+    pub fn save_savings(&self, pot: &SavingsPot) -> Result<()> {
+        let key = make_key(prefixes::SAVINGS, b"main");
+        self.store.set(&key, pot)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // TREASURY
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Load treasury bookkeeping
+    pub fn load_treasury(&self) -> Result<Option<Treasury>> {
+        let key = make_key(prefixes::TREASURY, b"main");
+        self.store.get(&key)
+    }
+
+    /// Save treasury bookkeeping
+    pub fn save_treasury(&self, treasury: &Treasury) -> Result<()> {
+        let key = make_key(prefixes::TREASURY, b"main");
+        self.store.set(&key, treasury)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // AUCTIONS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Load liquidation auctions
+    pub fn load_auctions(&self) -> Result<Option<AuctionHouse>> {
+        let key = make_key(prefixes::AUCTIONS, b"main");
+        self.store.get(&key)
+    }
+
+    /// Save liquidation auctions
+    pub fn save_auctions(&self, auctions: &AuctionHouse) -> Result<()> {
+        let key = make_key(prefixes::AUCTIONS, b"main");
+        self.store.set(&key, auctions)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // STATISTICS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Load accrued protocol statistics
+    pub fn load_aggregates(&self) -> Result<Option<ProtocolAggregates>> {
+        let key = make_key(prefixes::AGGREGATES, b"main");
+        self.store.get(&key)
+    }
+
+    /// Save accrued protocol statistics
+    pub fn save_aggregates(&self, aggregates: &ProtocolAggregates) -> Result<()> {
+        let key = make_key(prefixes::AGGREGATES, b"main");
+        self.store.set(&key, aggregates)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // PEG STABILITY
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Load the peg stability controller
+    pub fn load_peg(&self) -> Result<Option<PegController>> {
+        let key = make_key(prefixes::PEG, b"main");
+        self.store.get(&key)
+    }
+
+    /// Save the peg stability controller
+    pub fn save_peg(&self, peg: &PegController) -> Result<()> {
+        let key = make_key(prefixes::PEG, b"main");
+        self.store.set(&key, peg)
+    }
+
+    /// Delete the peg stability controller
+    pub fn delete_peg(&self) -> Result<bool> {
+        let key = make_key(prefixes::PEG, b"main");
+        self.store.delete(&key)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // ORACLE REGISTRY
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Load the oracle operator registry
+    pub fn load_oracle_registry(&self) -> Result<Option<OracleRegistry>> {
+        let key = make_key(prefixes::ORACLES, b"main");
+        self.store.get(&key)
+    }
+
+    /// Save the oracle operator registry
+    pub fn save_oracle_registry(&self, registry: &OracleRegistry) -> Result<()> {
+        let key = make_key(prefixes::ORACLES, b"main");
+        self.store.set(&key, registry)
+    }
+
+    /// Delete the oracle operator registry
+    pub fn delete_oracle_registry(&self) -> Result<bool> {
+        let key = make_key(prefixes::ORACLES, b"main");
+        self.store.delete(&key)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // OPERATION PAUSES
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Load the per-operation pauses and circuit breakers
+    pub fn load_operation_pauses(&self) -> Result<Option<OperationPauses>> {
+        let key = make_key(prefixes::CONFIG, b"pauses");
+        self.store.get(&key)
+    }
+
+    /// Save the per-operation pauses and circuit breakers
+    pub fn save_operation_pauses(&self, pauses: &OperationPauses) -> Result<()> {
+        let key = make_key(prefixes::CONFIG, b"pauses");
+        self.store.set(&key, pauses)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // PARAMETER RAMPS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Load the scheduled and running parameter ramps
+    pub fn load_parameter_ramps(&self) -> Result<Option<ParameterRamps>> {
+        let key = make_key(prefixes::CONFIG, b"ramps");
+        self.store.get(&key)
+    }
+
+    /// Save the scheduled and running parameter ramps
+    pub fn save_parameter_ramps(&self, ramps: &ParameterRamps) -> Result<()> {
+        let key = make_key(prefixes::CONFIG, b"ramps");
+        self.store.set(&key, ramps)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // ANOMALY DETECTION
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Load the recent block activity of the anomaly detector, oldest first
+    pub fn load_anomaly_history(&self) -> Result<Option<VecDeque<BlockActivity>>> {
+        let key = make_key(prefixes::CONFIG, b"anomaly");
+        self.store.get(&key)
+    }
+
+    /// Save the recent block activity of the anomaly detector
+    pub fn save_anomaly_history(&self, history: &VecDeque<BlockActivity>) -> Result<()> {
+        let key = make_key(prefixes::CONFIG, b"anomaly");
+        self.store.set(&key, history)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // AUDIT LOG
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Load the head of the audit log
+    pub fn load_audit_log(&self) -> Result<Option<AuditLog>> {
+        let key = make_key(prefixes::CONFIG, b"audit");
+        self.store.get(&key)
+    }
+
+    /// Save the head of the audit log
+    pub fn save_audit_log(&self, log: &AuditLog) -> Result<()> {
+        let key = make_key(prefixes::CONFIG, b"audit");
+        self.store.set(&key, log)
+    }
+
+    /// Save an audit log entry. Entries are kept in every pruning mode.
+    pub fn save_audit_entry(&self, entry: &AuditEntry) -> Result<()> {
+        let key = make_key(prefixes::AUDIT, &entry.sequence.to_be_bytes());
+        self.store.set(&key, entry)
+    }
+
+    /// Load up to `limit` stored audit entries at or above `from_sequence`,
+    /// lowest first
+    pub fn load_audit_entries(&self, from_sequence: u64, limit: usize) -> Result<Vec<AuditEntry>> {
+        let mut sequences: Vec<u64> = self
+            .store
+            .list_prefix(prefixes::AUDIT)?
+            .iter()
+            .filter_map(|key| Self::key_suffix_u64(key, prefixes::AUDIT))
+            .filter(|sequence| *sequence >= from_sequence)
+            .collect();
+        sequences.sort_unstable();
+        sequences.truncate(limit);
+
+        let mut entries = Vec::with_capacity(sequences.len());
+        for sequence in sequences {
+            let key = make_key(prefixes::AUDIT, &sequence.to_be_bytes());
+            entries.extend(self.store.get::<AuditEntry>(&key)?);
+        }
+        Ok(entries)
+    }
+
+    /// Verify the audit log links up to its head and matches every anchor
+    /// still in the event history
+    pub fn verify_audit_log(&self) -> Result<AuditReport> {
+        let log = self.load_audit_log()?.unwrap_or_default();
+        let entries = self.load_audit_entries(0, usize::MAX)?;
+        let anchors: Vec<AuditAnchoredEvent> = self
+            .load_events_range(0, u64::MAX)?
+            .into_iter()
+            .filter_map(|event| match event {
+                ProtocolEvent::AuditAnchored(anchor) => Some(anchor),
+                _ => None,
+            })
+            .collect();
+        verify_chain(entries, &log, &anchors)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // GENESIS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Load the genesis the database was initialized from
+    pub fn load_genesis(&self) -> Result<Option<Genesis>> {
+        let key = make_key(prefixes::GENESIS, b"main");
+        self.store.get(&key)
+    }
+
+    /// Save the genesis the database is initialized from
+    pub fn save_genesis(&self, genesis: &Genesis) -> Result<()> {
+        let key = make_key(prefixes::GENESIS, b"main");
+        self.store.set(&key, genesis)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // PRICE DATA
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Save the non-BTC price pairs
+    pub fn save_price_pairs(&self, pairs: &CrossRateFeed) -> Result<()> {
+        let key = make_key(prefixes::PRICE, b"pairs");
+        self.store.set(&key, pairs)
+    }
+
+    /// Load the non-BTC price pairs
+    pub fn load_price_pairs(&self) -> Result<Option<CrossRateFeed>> {
+        let key = make_key(prefixes::PRICE, b"pairs");
+        self.store.get(&key)
+    }
+
+    /// Save latest price data
+    pub fn save_price(&self, price_cents: u64, timestamp: u64) -> Result<()> {
+        let key = make_key(prefixes::PRICE, b"latest");
+        let data = (price_cents, timestamp);
+        self.store.set(&key, &data)
+    }
+
+    /// Load latest price data
+    pub fn load_price(&self) -> Result<Option<(u64, u64)>> {
+        let key = make_key(prefixes::PRICE, b"latest");
+        self.store.get(&key)
+    }
+
+    /// Save price history entry
+    pub fn save_price_history(&self, timestamp: u64, price_cents: u64) -> Result<()> {
+        let key = make_key(prefixes::PRICE, &timestamp.to_be_bytes());
+        self.store.set(&key, &price_cents)
+    }
+
+    /// Load price history entries timestamped within `from..=to`, oldest first
+    pub fn load_price_history(&self, from: u64, to: u64) -> Result<Vec<(u64, u64)>> {
+        let mut keys = self.store.list_prefix(prefixes::PRICE)?;
+        keys.sort();
+
+        let mut history = Vec::new();
+        for key in keys {
+            // Skips the "latest" and "pairs" entries
+            let Some(timestamp) = Self::key_suffix_u64(&key, prefixes::PRICE) else {
+                continue;
+            };
+            if (from..=to).contains(&timestamp) {
+                if let Some(price) = self.store.get::<u64>(&key)? {
+                    history.push((timestamp, price));
+                }
+            }
+        }
+        Ok(history)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // TRANSACTIONS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Save a transaction record
+    pub fn save_transaction(&self, tx: &TransactionRecord) -> Result<()> {
+        let key = make_key(prefixes::TX, tx.hash.as_bytes());
+        self.store.set(&key, tx)
+    }
+
+    /// Load a transaction by hash
+    pub fn load_transaction(&self, hash: &Hash) -> Result<Option<TransactionRecord>> {
+        let key = make_key(prefixes::TX, hash.as_bytes());
+        self.store.get(&key)
+    }
+
+    /// Load recent transactions (last N)
+    pub fn load_recent_transactions(&self, limit: usize) -> Result<Vec<TransactionRecord>> {
+        let keys = self.store.list_prefix(prefixes::TX)?;
+        let mut txs = Vec::new();
+
+        for key in keys.iter().take(limit) {
+            if let Some(tx) = self.store.get::<TransactionRecord>(key)? {
+                txs.push(tx);
+            }
+        }
+
+        // Sort by timestamp descending
+        txs.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        txs.truncate(limit);
+
+        Ok(txs)
+    }
+
+    /// Load every transaction, oldest first
+    pub fn load_all_transactions(&self) -> Result<Vec<TransactionRecord>> {
+        let mut txs = Vec::new();
+        for key in self.store.list_prefix(prefixes::TX)? {
+            if let Some(tx) = self.store.get::<TransactionRecord>(&key)? {
+                txs.push(tx);
+            }
+        }
+
+        // Hashes are unordered, so break ties on them for a stable order
+        txs.sort_by_key(|tx| (tx.block_height, tx.timestamp, *tx.hash.as_bytes()));
+        Ok(txs)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // RECEIPTS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Save an operation receipt
+    pub fn save_receipt(&self, receipt: &Receipt) -> Result<()> {
+        let key = make_key(prefixes::RECEIPT, receipt.id.as_bytes());
+        self.store.set(&key, receipt)
+    }
+
+    /// Load the receipt of an operation by its hash
+    pub fn load_receipt(&self, id: &Hash) -> Result<Option<Receipt>> {
+        let key = make_key(prefixes::RECEIPT, id.as_bytes());
+        self.store.get(&key)
+    }
+
+    /// Save the ids of the receipts included or rejected in a block
+    pub fn save_block_receipts(&self, block_height: u64, ids: &[Hash]) -> Result<()> {
+        let key = make_key(prefixes::RECEIPT_BLOCK, &block_height.to_be_bytes());
+        self.store.set(&key, &ids.to_vec())
+    }
+
+    /// Load the ids of the receipts included or rejected in a block
+    pub fn load_block_receipts(&self, block_height: u64) -> Result<Vec<Hash>> {
+        let key = make_key(prefixes::RECEIPT_BLOCK, &block_height.to_be_bytes());
+        Ok(self.store.get(&key)?.unwrap_or_default())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // EVENTS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Save the events emitted in a block
+    pub fn save_events(&self, block_height: u64, events: &[ProtocolEvent]) -> Result<()> {
+        let key = make_key(prefixes::EVENT, &block_height.to_be_bytes());
+        self.store.set(&key, &events.to_vec())
+    }
+
+    /// Load the events emitted in a block
+    pub fn load_events(&self, block_height: u64) -> Result<Vec<ProtocolEvent>> {
+        let key = make_key(prefixes::EVENT, &block_height.to_be_bytes());
+        Ok(self.store.get(&key)?.unwrap_or_default())
+    }
+
+    /// Load the events emitted in blocks `from..=to`, oldest block first
+    pub fn load_events_range(&self, from: u64, to: u64) -> Result<Vec<ProtocolEvent>> {
+        let mut heights: Vec<u64> = self
+            .store
+            .list_prefix(prefixes::EVENT)?
+            .iter()
+            .filter_map(|key| Self::key_suffix_u64(key, prefixes::EVENT))
+            .filter(|height| (from..=to).contains(height))
+            .collect();
+        heights.sort_unstable();
+
+        let mut events = Vec::new();
+        for height in heights {
+            events.extend(self.load_events(height)?);
+        }
+        Ok(events)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // BLOCKS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Save the operation log of a block
+    pub fn save_block(&self, block: &BlockRecord) -> Result<()> {
+        let key = make_key(prefixes::BLOCK, &block.height.to_be_bytes());
+        self.store.set(&key, block)
+    }
+
+    /// Load the operation log of a block
+    pub fn load_block(&self, block_height: u64) -> Result<Option<BlockRecord>> {
+        let key = make_key(prefixes::BLOCK, &block_height.to_be_bytes());
+        self.store.get(&key)
+    }
+
+    /// Load up to `limit` block logs at or above `from`, lowest first
+    pub fn load_blocks(&self, from: u64, limit: usize) -> Result<Vec<BlockRecord>> {
+        let mut heights: Vec<u64> = self
+            .store
+            .list_prefix(prefixes::BLOCK)?
+            .iter()
+            .filter_map(|key| Self::key_suffix_u64(key, prefixes::BLOCK))
+            .filter(|height| *height >= from)
+            .collect();
+        heights.sort_unstable();
+        heights.truncate(limit);
+
+        let mut blocks = Vec::with_capacity(heights.len());
+        for height in heights {
+            blocks.extend(self.load_block(height)?);
+        }
+        Ok(blocks)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // PRUNING
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Remove history older than the retention window.
+    ///
+    /// Price history is keyed by timestamp, so its cutoff is derived from
+    /// `current_timestamp` using the average block time. Does nothing in
+    /// archive mode.
+    pub fn prune(&self, current_height: u64, current_timestamp: u64) -> Result<PruneStats> {
+        let mut stats = PruneStats::default();
+
+        let cutoff = match self.pruning.cutoff_height(current_height) {
+            Some(cutoff) => cutoff,
+            None => return Ok(stats),
+        };
+        let retained_blocks = current_height - cutoff;
+        let cutoff_timestamp = current_timestamp
+            .saturating_sub(retained_blocks.saturating_mul(BLOCK_TIME_SECS));
+
+        // Transactions
+        for key in self.store.list_prefix(prefixes::TX)? {
+            if let Some(tx) = self.store.get::<TransactionRecord>(&key)? {
+                if tx.block_height < cutoff && self.store.delete(&key)? {
+                    stats.transactions += 1;
+                }
+            }
+        }
+
+        // Price history (skips the "latest" and "pairs" entries)
+        for key in self.store.list_prefix(prefixes::PRICE)? {
+            if let Some(timestamp) = Self::key_suffix_u64(&key, prefixes::PRICE) {
+                if timestamp < cutoff_timestamp && self.store.delete(&key)? {
+                    stats.price_entries += 1;
+                }
+            }
+        }
+
+        // Events
+        for key in self.store.list_prefix(prefixes::EVENT)? {
+            if let Some(height) = Self::key_suffix_u64(&key, prefixes::EVENT) {
+                if height < cutoff && self.store.delete(&key)? {
+                    stats.event_entries += 1;
+                }
+            }
+        }
+
+        // Block logs
+        for key in self.store.list_prefix(prefixes::BLOCK)? {
+            if let Some(height) = Self::key_suffix_u64(&key, prefixes::BLOCK) {
+                if height < cutoff && self.store.delete(&key)? {
+                    stats.block_entries += 1;
+                }
+            }
+        }
+
+        // Receipts: only settled ones, so a slow prover never loses track
+        for key in self.store.list_prefix(prefixes::RECEIPT_BLOCK)? {
+            let height = match Self::key_suffix_u64(&key, prefixes::RECEIPT_BLOCK) {
+                Some(height) if height < cutoff => height,
+                _ => continue,
+            };
+            let mut unsettled = Vec::new();
+            for id in self.load_block_receipts(height)? {
+                let Some(receipt) = self.load_receipt(&id)? else {
+                    continue;
+                };
+                if !receipt.is_settled() {
+                    unsettled.push(id);
+                } else if self.store.delete(&make_key(prefixes::RECEIPT, id.as_bytes()))? {
+                    stats.receipts += 1;
+                }
+            }
+            if unsettled.is_empty() {
+                self.store.delete(&key)?;
+            } else {
+                self.save_block_receipts(height, &unsettled)?;
+            }
+        }
+
+        // CDP versions: the newest one before the cutoff still describes the
+        // CDP at the cutoff, so only the ones it supersedes are removed
+        let mut versions: HashMap<Vec<u8>, Vec<u64>> = HashMap::new();
+        for key in self.store.list_prefix(prefixes::CDP_HISTORY)? {
+            if key.len() < 8 {
+                continue;
+            }
+            let (id, height) = key.split_at(key.len() - 8);
+            if let Ok(height) = <[u8; 8]>::try_from(height) {
+                versions.entry(id.to_vec()).or_default().push(u64::from_be_bytes(height));
+            }
+        }
+        for (id, mut heights) in versions {
+            heights.sort_unstable();
+            let expired = heights.iter().filter(|height| **height < cutoff).count();
+            for height in &heights[..expired.saturating_sub(1)] {
+                let mut key = id.clone();
+                key.extend_from_slice(&height.to_be_bytes());
+                if self.store.delete(&key)? {
+                    stats.cdp_versions += 1;
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Compute storage usage statistics
+    pub fn storage_stats(&self) -> Result<StorageStats> {
+        let backend = self.store.backend();
+        let mut stats = StorageStats::default();
+
+        for key in self.store.keys()? {
+            stats.total_keys += 1;
+            stats.total_bytes += key.len() as u64;
+            if let Some(value) = backend.get(&key)? {
+                stats.total_bytes += value.len() as u64;
+            }
+
+            if key.starts_with(prefixes::CDP) {
+                stats.cdps += 1;
+            } else if key.starts_with(prefixes::CDP_HISTORY) {
+                stats.cdp_versions += 1;
+            } else if key.starts_with(prefixes::BALANCE) {
+                stats.balances += 1;
+            } else if key.starts_with(prefixes::TX) {
+                stats.transactions += 1;
+            } else if key.starts_with(prefixes::PRICE) {
+                stats.price_entries += 1;
+            } else if key.starts_with(prefixes::EVENT) {
+                stats.event_entries += 1;
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Decode a big-endian u64 suffix following `prefix`
+    fn key_suffix_u64(key: &[u8], prefix: &[u8]) -> Option<u64> {
+        let suffix: [u8; 8] = key.get(prefix.len()..)?.try_into().ok()?;
+        Some(u64::from_be_bytes(suffix))
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // UTILITY METHODS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Flush all pending writes
+    pub fn flush(&self) -> Result<()> {
+        self.store.flush()
+    }
+
+    /// Buffer writes until [`commit_batch`](Self::commit_batch); reads
+    /// through this manager still see them
+    pub fn begin_batch(&self) -> Result<()> {
+        self.store.begin_batch()
+    }
+
+    /// Write buffered changes to the backend in one batch, returning the
+    /// number of writes
+    pub fn commit_batch(&self) -> Result<usize> {
+        self.store.commit_batch()
+    }
+
+    /// Drop buffered changes
+    pub fn discard_batch(&self) -> Result<()> {
+        self.store.discard_batch()
+    }
+
+    /// Buffer further writes separately so they can be dropped on their own
+    pub fn begin_nested_batch(&self) -> Result<()> {
+        self.store.begin_nested_batch()
+    }
+
+    /// Keep the innermost buffered writes, folding them into the batch below
+    pub fn commit_nested_batch(&self) -> Result<()> {
+        self.store.commit_nested_batch()
+    }
+
+    /// Drop the innermost buffered writes
+    pub fn discard_nested_batch(&self) -> Result<()> {
+        self.store.discard_nested_batch()
+    }
+
+    /// Number of buffered writes
+    pub fn pending_writes(&self) -> Result<usize> {
+        self.store.pending_writes()
+    }
+
+    /// Clear all data (for testing)
+    pub fn clear(&self) -> Result<()> {
+        self.store.clear()
+    }
+
+    /// Compute state root hash (Merkle root of all data)
+    pub fn compute_state_root(&self) -> Result<Hash> {
+        let scheme = self.load_hash_scheme()?;
+        let (hashes, _) = self.state_leaves(scheme)?;
+        Ok(scheme.merkle_root(&hashes))
+    }
+
+    /// Prove that CDP `id` is part of [`compute_state_root`](Self::compute_state_root).
+    /// Returns `None` if the CDP doesn't exist.
+    pub fn prove_cdp(&self, id: &CDPId) -> Result<Option<CdpProof>> {
+        let scheme = self.load_hash_scheme()?;
+        let (hashes, mut cdps) = self.state_leaves(scheme)?;
+        let Some(position) = cdps.iter().position(|cdp| cdp.id == *id) else {
+            return Ok(None);
+        };
+
+        // CDP leaves follow the protocol state's, when there is one
+        let index = hashes.len() - cdps.len() + position;
+        let path = scheme
+            .merkle_proof(&hashes, index)
+            .ok_or_else(|| Error::Internal(format!("No state leaf at index {}", index)))?;
+        let cdp = cdps.swap_remove(position);
+
+        Ok(Some(CdpProof {
+            leaf: cdp_leaf(&cdp)?,
+            cdp,
+            index: index as u64,
+            path,
+            scheme,
+        }))
+    }
+
+    /// Leaves of the state root, the protocol state's hash then each CDP's,
+    /// along with the CDPs
+    fn state_leaves(&self, scheme: HashScheme) -> Result<(Vec<Hash>, Vec<CDP>)> {
+        let mut hashes = Vec::new();
+
+        // Hash protocol state
+        if let Ok(state) = self.load_protocol_state() {
+            hashes.push(canonical_hash_with(scheme, STATE_DOMAIN, &state).unwrap_or_default());
+        }
+
+        // Hash all CDPs
+        let cdps = self.load_all_cdps()?;
+        hashes.extend(
+            cdps.iter()
+                .map(|cdp| canonical_hash_with(scheme, CDP_DOMAIN, cdp).unwrap_or_default()),
+        );
+
+        Ok((hashes, cdps))
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// CDP PROOFS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Merkle proof that a CDP is part of a state root, for exchanges and
+/// auditors checking individual positions against published roots
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdpProof {
+    /// The proven CDP
+    pub cdp: CDP,
+    /// Canonical encoding of the CDP the leaf hash commits to
+    pub leaf: Vec<u8>,
+    /// Position of the leaf in the state tree
+    pub index: u64,
+    /// Sibling hashes from the leaf up to the root
+    pub path: Vec<Hash>,
+    /// Hash scheme of the state tree
+    #[serde(default)]
+    pub scheme: HashScheme,
+}
+
+/// Check `proof` against the state root `root`. Needs nothing but the proof,
+/// so third parties can verify without access to the node's storage.
+pub fn verify_cdp_proof(root: &Hash, proof: &CdpProof) -> Result<()> {
+    if proof.leaf != cdp_leaf(&proof.cdp)? {
+        return Err(Error::InvalidStateProof(format!(
+            "Leaf does not encode CDP {}",
+            proof.cdp.id.to_hex()
+        )));
+    }
+
+    let leaf_hash = proof.scheme.domain_hash(CDP_DOMAIN, &proof.leaf);
+    let index = usize::try_from(proof.index)
+        .map_err(|_| Error::InvalidStateProof(format!("Leaf index {} out of range", proof.index)))?;
+    if proof.path.len() >= usize::BITS as usize || index >> proof.path.len() != 0 {
+        return Err(Error::InvalidStateProof(format!(
+            "Leaf index {} does not fit a path of {} hashes",
+            proof.index,
+            proof.path.len()
+        )));
+    }
+    if !proof.scheme.verify_merkle_proof(&leaf_hash, &proof.path, root, index) {
+        return Err(Error::InvalidStateProof(format!(
+            "CDP {} is not part of state root {}",
+            proof.cdp.id.to_hex(),
+            root.to_hex()
+        )));
+    }
+    Ok(())
+}
+
+/// Leaf encoding of `cdp`, hashed under [`CDP_DOMAIN`] into the state tree
+fn cdp_leaf(cdp: &CDP) -> Result<Vec<u8>> {
+    let mut leaf = vec![CODEC_VERSION];
+    leaf.extend(canonical_bytes(cdp)?);
+    Ok(leaf)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// MIGRATIONS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Function applying a single migration step to the raw store
+pub type MigrationFn<B> = fn(&TypedStore<B>) -> Result<()>;
+
+/// A single schema migration from `from` to `from + 1`
+pub struct Migration<B: StorageBackend> {
+    /// Version this migration upgrades from
+    pub from: u32,
+    /// Human-readable description
+    pub description: &'static str,
+    /// Migration function
+    pub apply: MigrationFn<B>,
+}
+
+/// Outcome of a migration run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Version found on disk
+    pub from_version: u32,
+    /// Version after migration
+    pub to_version: u32,
+    /// Descriptions of the steps applied, in order
+    pub applied: Vec<&'static str>,
+}
+
+/// Ordered set of schema migrations
+pub struct Migrator<B: StorageBackend> {
+    /// Version the migrator upgrades to
+    target: u32,
+    /// Registered migrations
+    migrations: Vec<Migration<B>>,
+}
+
+impl<B: StorageBackend> Migrator<B> {
+    /// Create an empty migrator targeting `target`
+    pub fn new(target: u32) -> Self {
+        Self {
+            target,
+            migrations: Vec::new(),
+        }
+    }
+
+    /// Migrator with all built-in migrations up to `SCHEMA_VERSION`
+    pub fn default_migrations() -> Self {
+        Self::new(SCHEMA_VERSION)
+            .register(1, "Record schema version under a dedicated key", |_| Ok(()))
+            .register(2, "Add operation rate limits to protocol params", |store| {
+                let key = make_key(prefixes::CONFIG, b"state");
+                transform_values(store, &key, |old: legacy::ProtocolStateV2| {
+                    legacy::ProtocolStateV4::from(ProtocolState::from(old))
+                })
+                .map(|_| ())
+            })
+            .register(3, "Add owner policies to CDPs", |store| {
+                transform_values(store, prefixes::CDP, |old: legacy::CDPV3| {
+                    legacy::CDPV10::from(CDP::from(old))
+                })
+                .map(|_| ())
+            })
+            .register(4, "Add redemption volume cap to protocol params", |store| {
+                let key = make_key(prefixes::CONFIG, b"state");
+                transform_values(store, &key, |old: legacy::ProtocolStateV4| {
+                    legacy::ProtocolStateV6::from(ProtocolState::from(old))
+                })
+                .map(|_| ())
+            })
+            .register(5, "Track stability pool gains by epoch and scale", |store| {
+                let key = make_key(prefixes::STABILITY_POOL, b"main");
+                transform_values(store, &key, |old: legacy::StabilityPoolV5| StabilityPool::from(old))
+                    .map(|_| ())
+            })
+            .register(6, "Add keeper rewards to protocol params", |store| {
+                let key = make_key(prefixes::CONFIG, b"state");
+                transform_values(store, &key, |old: legacy::ProtocolStateV6| {
+                    legacy::ProtocolStateV7::from(ProtocolState::from(old))
+                })
+                .map(|_| ())
+            })
+            .register(7, "Add liquidation penalty curve to protocol params", |store| {
+                let key = make_key(prefixes::CONFIG, b"state");
+                transform_values(store, &key, |old: legacy::ProtocolStateV7| {
+                    legacy::ProtocolStateV8::from(ProtocolState::from(old))
+                })
+                .map(|_| ())
+            })
+            .register(8, "Add chain id to protocol config", |store| {
+                let key = make_key(prefixes::CONFIG, b"state");
+                transform_values(store, &key, |old: legacy::ProtocolStateV8| {
+                    legacy::ProtocolStateV9::from(ProtocolState::from(old))
+                })
+                .map(|_| ())
+            })
+            .register(9, "Add stability pool withdrawal gate to protocol params", |store| {
+                let key = make_key(prefixes::CONFIG, b"state");
+                transform_values(store, &key, |old: legacy::ProtocolStateV9| ProtocolState::from(old))
+                    .map(|_| ())
+            })
+            .register(10, "Add session keys to CDPs", |store| {
+                for prefix in [prefixes::CDP, prefixes::CDP_HISTORY] {
+                    transform_values(store, prefix, |old: legacy::CDPV10| CDP::from(old))?;
+                }
+                Ok(())
+            })
+            .register(11, "Track nonces in sliding windows", |store| {
+                let key = make_key(prefixes::NONCE, b"main");
+                transform_values(store, &key, NonceManager::from_highest).map(|_| ())
+            })
+            .register(12, "Track stability pool gains at lower precision", |store| {
+                let key = make_key(prefixes::STABILITY_POOL, b"main");
+                transform_values(store, &key, |old: StabilityPool| {
+                    old.reduce_gain_precision(SP_SCALE_FACTOR / SP_GAIN_PRECISION)
+                })
+                .map(|_| ())
+            })
+    }
+
+    /// Register a migration step
+    pub fn register(mut self, from: u32, description: &'static str, apply: MigrationFn<B>) -> Self {
+        self.migrations.push(Migration { from, description, apply });
+        self.migrations.sort_by_key(|m| m.from);
+        self
+    }
+
+    /// Target schema version
+    pub fn target(&self) -> u32 {
+        self.target
+    }
+
+    /// Bring the database up to the target version
+    pub fn run(&self, manager: &StateManager<B>) -> Result<MigrationReport> {
+        let from_version = manager.schema_version()?.unwrap_or(self.target);
+
+        if from_version > self.target {
+            return Err(Error::UnsupportedSchemaVersion {
+                found: from_version,
+                supported: self.target,
+            });
+        }
+
+        let mut version = from_version;
+        let mut applied = Vec::new();
+
+        while version < self.target {
+            let migration = self.migrations.iter().find(|m| m.from == version).ok_or_else(|| {
+                Error::MigrationFailed {
+                    from: version,
+                    reason: "no migration registered".into(),
+                }
+            })?;
+
+            (migration.apply)(&manager.store).map_err(|e| Error::MigrationFailed {
+                from: version,
+                reason: e.to_string(),
+            })?;
+
+            // Record progress after each step so an interrupted run resumes
+            version += 1;
+            manager.save_schema_version(version)?;
+            applied.push(migration.description);
+        }
+
+        if !applied.is_empty() {
+            if let Ok(mut state) = manager.load_protocol_state() {
+                state.version = version;
+                manager.save_protocol_state(&state)?;
+            }
+            manager.flush()?;
+        }
+
+        Ok(MigrationReport {
+            from_version,
+            to_version: version,
+            applied,
+        })
+    }
+}
+
+/// Move every key under `old_prefix` to `new_prefix`, returning the number moved
+pub fn rekey_prefix<B: StorageBackend>(
+    store: &TypedStore<B>,
+    old_prefix: &[u8],
+    new_prefix: &[u8],
+) -> Result<usize> {
+    let backend = store.backend();
+    let keys = store.list_prefix(old_prefix)?;
+
+    for key in &keys {
+        if let Some(value) = backend.get(key)? {
+            backend.set(&make_key(new_prefix, &key[old_prefix.len()..]), &value)?;
+            backend.delete(key)?;
+        }
+    }
+
+    Ok(keys.len())
+}
+
+/// Re-encode every value under `prefix` from `T` to `U`, returning the number transformed
+pub fn transform_values<B, T, U, F>(store: &TypedStore<B>, prefix: &[u8], f: F) -> Result<usize>
+where
+    B: StorageBackend,
+    T: DeserializeOwned,
+    U: Serialize,
+    F: Fn(T) -> U,
+{
+    let keys = store.list_prefix(prefix)?;
+    let mut count = 0;
+
+    for key in keys {
+        if let Some(old) = store.get::<T>(&key)? {
+            store.set(&key, &f(old))?;
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
 
-    /// Load a CDP by ID
-    pub fn load_cdp(&self, id: &CDPId) -> Result<Option<CDP>> {
-        let key = make_key(prefixes::CDP, id.as_bytes());
-        self.store.get(&key)
+/// On-disk layouts written by older schema versions
+mod legacy {
+    use super::*;
+    use crate::core::config::ProtocolParams;
+    use crate::core::fees::FeeController;
+    use crate::core::token::TokenAmount;
+    use crate::core::vault::CollateralAmount;
+    use crate::liquidation::engine::PenaltyCurve;
+    use crate::liquidation::stability_pool::Deposit;
+
+    /// Protocol parameters before per-account rate limits (schema 1-2)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct ProtocolParamsV2 {
+        version: String,
+        min_collateral_ratio: u64,
+        critical_collateral_ratio: u64,
+        borrowing_fee_bps: u64,
+        liquidation_bonus_bps: u64,
+        min_debt: u64,
+        max_debt_per_cdp: u64,
+        redemption_fee_floor_bps: u64,
+        redemption_fee_ceiling_bps: u64,
+        min_oracle_sources: usize,
+        max_price_staleness_secs: u64,
+        max_price_deviation_bps: u64,
     }
 
-    /// Save a CDP
-    pub fn save_cdp(&self, cdp: &CDP) -> Result<()> {
-        let key = make_key(prefixes::CDP, cdp.id.as_bytes());
-        self.store.set(&key, cdp)
+    /// Protocol configuration (schema 1-2)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct ProtocolConfigV2 {
+        params: ProtocolParamsV2,
+        debt_ceiling: u64,
+        paused: bool,
+        recovery_mode: bool,
+        base_rate: u64,
+        last_redemption_time: u64,
+        total_system_debt: u64,
+        total_system_collateral: u64,
     }
 
-    /// Delete a CDP
-    pub fn delete_cdp(&self, id: &CDPId) -> Result<bool> {
-        let key = make_key(prefixes::CDP, id.as_bytes());
-        self.store.delete(&key)
+    /// Protocol state (schema 1-2)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct ProtocolStateV2 {
+        config: ProtocolConfigV2,
+        total_supply: u64,
+        total_collateral: u64,
+        total_debt: u64,
+        active_cdps: u64,
+        block_height: u64,
+        last_update: u64,
+        version: u32,
     }
 
-    /// Load all CDPs
-    pub fn load_all_cdps(&self) -> Result<Vec<CDP>> {
-        let keys = self.store.list_prefix(prefixes::CDP)?;
-        let mut cdps = Vec::new();
-
-        for key in keys {
-            if let Some(cdp) = self.store.get::<CDP>(&key)? {
-                cdps.push(cdp);
+    impl From<ProtocolStateV2> for ProtocolState {
+        fn from(old: ProtocolStateV2) -> Self {
+            let c = old.config;
+            let p = c.params;
+            let params = ProtocolParams {
+                version: p.version,
+                min_collateral_ratio: p.min_collateral_ratio,
+                critical_collateral_ratio: p.critical_collateral_ratio,
+                borrowing_fee_bps: p.borrowing_fee_bps,
+                liquidation_bonus_bps: p.liquidation_bonus_bps,
+                min_debt: p.min_debt,
+                max_debt_per_cdp: p.max_debt_per_cdp,
+                redemption_fee_floor_bps: p.redemption_fee_floor_bps,
+                redemption_fee_ceiling_bps: p.redemption_fee_ceiling_bps,
+                min_oracle_sources: p.min_oracle_sources,
+                max_price_staleness_secs: p.max_price_staleness_secs,
+                max_price_deviation_bps: p.max_price_deviation_bps,
+                ..Default::default()
+            };
+
+            ProtocolState {
+                config: ProtocolConfig {
+                    params,
+                    debt_ceiling: c.debt_ceiling,
+                    paused: c.paused,
+                    recovery_mode: c.recovery_mode,
+                    fees: FeeController {
+                        base_rate: c.base_rate,
+                        last_redemption_time: c.last_redemption_time,
+                    },
+                    total_system_debt: c.total_system_debt,
+                    total_system_collateral: c.total_system_collateral,
+                    chain_id: 0,
+                },
+                total_supply: old.total_supply,
+                total_collateral: old.total_collateral,
+                total_debt: old.total_debt,
+                active_cdps: old.active_cdps,
+                block_height: old.block_height,
+                last_update: old.last_update,
+                version: old.version,
             }
         }
+    }
 
-        Ok(cdps)
+    /// Protocol parameters before the redemption volume cap (schema 3-4)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct ProtocolParamsV4 {
+        version: String,
+        min_collateral_ratio: u64,
+        critical_collateral_ratio: u64,
+        borrowing_fee_bps: u64,
+        liquidation_bonus_bps: u64,
+        min_debt: u64,
+        max_debt_per_cdp: u64,
+        redemption_fee_floor_bps: u64,
+        redemption_fee_ceiling_bps: u64,
+        min_oracle_sources: usize,
+        max_price_staleness_secs: u64,
+        max_price_deviation_bps: u64,
+        max_ops_per_window: u32,
+        rate_limit_window_blocks: u64,
+        min_redeem_interval_blocks: u64,
+        min_liquidation_interval_blocks: u64,
     }
 
-    /// Load CDPs by owner
-    pub fn load_cdps_by_owner(&self, owner: &PublicKey) -> Result<Vec<CDP>> {
-        let all_cdps = self.load_all_cdps()?;
-        Ok(all_cdps.into_iter().filter(|c| c.owner == *owner).collect())
+    /// Protocol configuration (schema 3-4)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct ProtocolConfigV4 {
+        params: ProtocolParamsV4,
+        debt_ceiling: u64,
+        paused: bool,
+        recovery_mode: bool,
+        base_rate: u64,
+        last_redemption_time: u64,
+        total_system_debt: u64,
+        total_system_collateral: u64,
     }
 
-    /// Load active CDPs
-    pub fn load_active_cdps(&self) -> Result<Vec<CDP>> {
-        let all_cdps = self.load_all_cdps()?;
-        Ok(all_cdps
-            .into_iter()
-            .filter(|c| c.status == CDPStatus::Active || c.status == CDPStatus::AtRisk)
-            .collect())
+    /// Protocol state (schema 3-4)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct ProtocolStateV4 {
+        config: ProtocolConfigV4,
+        total_supply: u64,
+        total_collateral: u64,
+        total_debt: u64,
+        active_cdps: u64,
+        block_height: u64,
+        last_update: u64,
+        version: u32,
     }
 
-    /// Count CDPs
-    pub fn count_cdps(&self) -> Result<usize> {
-        let keys = self.store.list_prefix(prefixes::CDP)?;
-        Ok(keys.len())
+    impl From<ProtocolStateV4> for ProtocolState {
+        fn from(old: ProtocolStateV4) -> Self {
+            let c = old.config;
+            let p = c.params;
+            let params = ProtocolParams {
+                version: p.version,
+                min_collateral_ratio: p.min_collateral_ratio,
+                critical_collateral_ratio: p.critical_collateral_ratio,
+                borrowing_fee_bps: p.borrowing_fee_bps,
+                liquidation_bonus_bps: p.liquidation_bonus_bps,
+                min_debt: p.min_debt,
+                max_debt_per_cdp: p.max_debt_per_cdp,
+                redemption_fee_floor_bps: p.redemption_fee_floor_bps,
+                redemption_fee_ceiling_bps: p.redemption_fee_ceiling_bps,
+                min_oracle_sources: p.min_oracle_sources,
+                max_price_staleness_secs: p.max_price_staleness_secs,
+                max_price_deviation_bps: p.max_price_deviation_bps,
+                max_ops_per_window: p.max_ops_per_window,
+                rate_limit_window_blocks: p.rate_limit_window_blocks,
+                min_redeem_interval_blocks: p.min_redeem_interval_blocks,
+                min_liquidation_interval_blocks: p.min_liquidation_interval_blocks,
+                ..Default::default()
+            };
+
+            ProtocolState {
+                config: ProtocolConfig {
+                    params,
+                    debt_ceiling: c.debt_ceiling,
+                    paused: c.paused,
+                    recovery_mode: c.recovery_mode,
+                    fees: FeeController {
+                        base_rate: c.base_rate,
+                        last_redemption_time: c.last_redemption_time,
+                    },
+                    total_system_debt: c.total_system_debt,
+                    total_system_collateral: c.total_system_collateral,
+                    chain_id: 0,
+                },
+                total_supply: old.total_supply,
+                total_collateral: old.total_collateral,
+                total_debt: old.total_debt,
+                active_cdps: old.active_cdps,
+                block_height: old.block_height,
+                last_update: old.last_update,
+                version: old.version,
+            }
+        }
     }
 
-    // ═══════════════════════════════════════════════════════════════════════════
-    // TOKEN BALANCES
-    // ═══════════════════════════════════════════════════════════════════════════
+    impl From<ProtocolState> for ProtocolStateV4 {
+        fn from(state: ProtocolState) -> Self {
+            let c = state.config;
+            let p = c.params;
+            ProtocolStateV4 {
+                config: ProtocolConfigV4 {
+                    params: ProtocolParamsV4 {
+                        version: p.version,
+                        min_collateral_ratio: p.min_collateral_ratio,
+                        critical_collateral_ratio: p.critical_collateral_ratio,
+                        borrowing_fee_bps: p.borrowing_fee_bps,
+                        liquidation_bonus_bps: p.liquidation_bonus_bps,
+                        min_debt: p.min_debt,
+                        max_debt_per_cdp: p.max_debt_per_cdp,
+                        redemption_fee_floor_bps: p.redemption_fee_floor_bps,
+                        redemption_fee_ceiling_bps: p.redemption_fee_ceiling_bps,
+                        min_oracle_sources: p.min_oracle_sources,
+                        max_price_staleness_secs: p.max_price_staleness_secs,
+                        max_price_deviation_bps: p.max_price_deviation_bps,
+                        max_ops_per_window: p.max_ops_per_window,
+                        rate_limit_window_blocks: p.rate_limit_window_blocks,
+                        min_redeem_interval_blocks: p.min_redeem_interval_blocks,
+                        min_liquidation_interval_blocks: p.min_liquidation_interval_blocks,
+                    },
+                    debt_ceiling: c.debt_ceiling,
+                    paused: c.paused,
+                    recovery_mode: c.recovery_mode,
+                    base_rate: c.fees.base_rate,
+                    last_redemption_time: c.fees.last_redemption_time,
+                    total_system_debt: c.total_system_debt,
+                    total_system_collateral: c.total_system_collateral,
+                },
+                total_supply: state.total_supply,
+                total_collateral: state.total_collateral,
+                total_debt: state.total_debt,
+                active_cdps: state.active_cdps,
+                block_height: state.block_height,
+                last_update: state.last_update,
+                version: state.version,
+            }
+        }
+    }
 
-    /// Load token balance for an account
-    pub fn load_balance(&self, account: &PublicKey) -> Result<u64> {
-        let key = make_key(prefixes::BALANCE, account.as_bytes());
-        Ok(self.store.get::<u64>(&key)?.unwrap_or(0))
+    /// Protocol parameters before keeper rewards (schema 5-6)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct ProtocolParamsV6 {
+        version: String,
+        min_collateral_ratio: u64,
+        critical_collateral_ratio: u64,
+        borrowing_fee_bps: u64,
+        liquidation_bonus_bps: u64,
+        min_debt: u64,
+        max_debt_per_cdp: u64,
+        redemption_fee_floor_bps: u64,
+        redemption_fee_ceiling_bps: u64,
+        min_oracle_sources: usize,
+        max_price_staleness_secs: u64,
+        max_price_deviation_bps: u64,
+        max_ops_per_window: u32,
+        rate_limit_window_blocks: u64,
+        min_redeem_interval_blocks: u64,
+        min_liquidation_interval_blocks: u64,
+        max_redemption_per_block: u64,
     }
 
-    /// Save token balance for an account
-    pub fn save_balance(&self, account: &PublicKey, balance: u64) -> Result<()> {
-        let key = make_key(prefixes::BALANCE, account.as_bytes());
-        self.store.set(&key, &balance)
+    /// Protocol configuration (schema 5-6)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct ProtocolConfigV6 {
+        params: ProtocolParamsV6,
+        debt_ceiling: u64,
+        paused: bool,
+        recovery_mode: bool,
+        base_rate: u64,
+        last_redemption_time: u64,
+        total_system_debt: u64,
+        total_system_collateral: u64,
     }
 
-    /// Load all balances
-    pub fn load_all_balances(&self) -> Result<HashMap<Vec<u8>, u64>> {
-        let keys = self.store.list_prefix(prefixes::BALANCE)?;
-        let mut balances = HashMap::new();
+    /// Protocol state (schema 5-6)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct ProtocolStateV6 {
+        config: ProtocolConfigV6,
+        total_supply: u64,
+        total_collateral: u64,
+        total_debt: u64,
+        active_cdps: u64,
+        block_height: u64,
+        last_update: u64,
+        version: u32,
+    }
 
-        for key in keys {
-            if let Some(balance) = self.store.get::<u64>(&key)? {
-                // Extract account key (remove prefix)
-                let account_key = key[prefixes::BALANCE.len()..].to_vec();
-                balances.insert(account_key, balance);
+    impl From<ProtocolStateV6> for ProtocolState {
+        fn from(old: ProtocolStateV6) -> Self {
+            let c = old.config;
+            let p = c.params;
+            let params = ProtocolParams {
+                version: p.version,
+                min_collateral_ratio: p.min_collateral_ratio,
+                critical_collateral_ratio: p.critical_collateral_ratio,
+                borrowing_fee_bps: p.borrowing_fee_bps,
+                liquidation_bonus_bps: p.liquidation_bonus_bps,
+                min_debt: p.min_debt,
+                max_debt_per_cdp: p.max_debt_per_cdp,
+                redemption_fee_floor_bps: p.redemption_fee_floor_bps,
+                redemption_fee_ceiling_bps: p.redemption_fee_ceiling_bps,
+                min_oracle_sources: p.min_oracle_sources,
+                max_price_staleness_secs: p.max_price_staleness_secs,
+                max_price_deviation_bps: p.max_price_deviation_bps,
+                max_ops_per_window: p.max_ops_per_window,
+                rate_limit_window_blocks: p.rate_limit_window_blocks,
+                min_redeem_interval_blocks: p.min_redeem_interval_blocks,
+                min_liquidation_interval_blocks: p.min_liquidation_interval_blocks,
+                max_redemption_per_block: p.max_redemption_per_block,
+                ..Default::default()
+            };
+
+            ProtocolState {
+                config: ProtocolConfig {
+                    params,
+                    debt_ceiling: c.debt_ceiling,
+                    paused: c.paused,
+                    recovery_mode: c.recovery_mode,
+                    fees: FeeController {
+                        base_rate: c.base_rate,
+                        last_redemption_time: c.last_redemption_time,
+                    },
+                    total_system_debt: c.total_system_debt,
+                    total_system_collateral: c.total_system_collateral,
+                    chain_id: 0,
+                },
+                total_supply: old.total_supply,
+                total_collateral: old.total_collateral,
+                total_debt: old.total_debt,
+                active_cdps: old.active_cdps,
+                block_height: old.block_height,
+                last_update: old.last_update,
+                version: old.version,
             }
         }
+    }
 
-        Ok(balances)
+    impl From<ProtocolState> for ProtocolStateV6 {
+        fn from(state: ProtocolState) -> Self {
+            let c = state.config;
+            let p = c.params;
+            ProtocolStateV6 {
+                config: ProtocolConfigV6 {
+                    params: ProtocolParamsV6 {
+                        version: p.version,
+                        min_collateral_ratio: p.min_collateral_ratio,
+                        critical_collateral_ratio: p.critical_collateral_ratio,
+                        borrowing_fee_bps: p.borrowing_fee_bps,
+                        liquidation_bonus_bps: p.liquidation_bonus_bps,
+                        min_debt: p.min_debt,
+                        max_debt_per_cdp: p.max_debt_per_cdp,
+                        redemption_fee_floor_bps: p.redemption_fee_floor_bps,
+                        redemption_fee_ceiling_bps: p.redemption_fee_ceiling_bps,
+                        min_oracle_sources: p.min_oracle_sources,
+                        max_price_staleness_secs: p.max_price_staleness_secs,
+                        max_price_deviation_bps: p.max_price_deviation_bps,
+                        max_ops_per_window: p.max_ops_per_window,
+                        rate_limit_window_blocks: p.rate_limit_window_blocks,
+                        min_redeem_interval_blocks: p.min_redeem_interval_blocks,
+                        min_liquidation_interval_blocks: p.min_liquidation_interval_blocks,
+                        max_redemption_per_block: p.max_redemption_per_block,
+                    },
+                    debt_ceiling: c.debt_ceiling,
+                    paused: c.paused,
+                    recovery_mode: c.recovery_mode,
+                    base_rate: c.fees.base_rate,
+                    last_redemption_time: c.fees.last_redemption_time,
+                    total_system_debt: c.total_system_debt,
+                    total_system_collateral: c.total_system_collateral,
+                },
+                total_supply: state.total_supply,
+                total_collateral: state.total_collateral,
+                total_debt: state.total_debt,
+                active_cdps: state.active_cdps,
+                block_height: state.block_height,
+                last_update: state.last_update,
+                version: state.version,
+            }
+        }
     }
 
-    // ═══════════════════════════════════════════════════════════════════════════
-    // STABILITY POOL
-    // ═══════════════════════════════════════════════════════════════════════════
+    /// Protocol parameters before the liquidation penalty curve (schema 7)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct ProtocolParamsV7 {
+        version: String,
+        min_collateral_ratio: u64,
+        critical_collateral_ratio: u64,
+        borrowing_fee_bps: u64,
+        liquidation_bonus_bps: u64,
+        min_debt: u64,
+        max_debt_per_cdp: u64,
+        redemption_fee_floor_bps: u64,
+        redemption_fee_ceiling_bps: u64,
+        min_oracle_sources: usize,
+        max_price_staleness_secs: u64,
+        max_price_deviation_bps: u64,
+        max_ops_per_window: u32,
+        rate_limit_window_blocks: u64,
+        min_redeem_interval_blocks: u64,
+        min_liquidation_interval_blocks: u64,
+        max_redemption_per_block: u64,
+        keeper_reward: u64,
+        max_keeper_rewards_per_block: u64,
+    }
 
-    /// Load stability pool state
-    pub fn load_stability_pool(&self) -> Result<Option<StabilityPool>> {
-        let key = make_key(prefixes::STABILITY_POOL, b"main");
-        self.store.get(&key)
+    /// Protocol configuration (schema 7)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct ProtocolConfigV7 {
+        params: ProtocolParamsV7,
+        debt_ceiling: u64,
+        paused: bool,
+        recovery_mode: bool,
+        base_rate: u64,
+        last_redemption_time: u64,
+        total_system_debt: u64,
+        total_system_collateral: u64,
     }
 
-    /// Save stability pool state
-    pub fn save_stability_pool(&self, pool: &StabilityPool) -> Result<()> {
-        let key = make_key(prefixes::STABILITY_POOL, b"main");
-        self.store.set(&key, pool)
+    /// Protocol state (schema 7)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct ProtocolStateV7 {
+        config: ProtocolConfigV7,
+        total_supply: u64,
+        total_collateral: u64,
+        total_debt: u64,
+        active_cdps: u64,
+        block_height: u64,
+        last_update: u64,
+        version: u32,
     }
 
-    // ═══════════════════════════════════════════════════════════════════════════
-    // PRICE DATA
-    // ═══════════════════════════════════════════════════════════════════════════
+    impl From<ProtocolStateV7> for ProtocolState {
+        fn from(old: ProtocolStateV7) -> Self {
+            let c = old.config;
+            let p = c.params;
+            let params = ProtocolParams {
+                version: p.version,
+                min_collateral_ratio: p.min_collateral_ratio,
+                critical_collateral_ratio: p.critical_collateral_ratio,
+                borrowing_fee_bps: p.borrowing_fee_bps,
+                liquidation_bonus_bps: p.liquidation_bonus_bps,
+                min_debt: p.min_debt,
+                max_debt_per_cdp: p.max_debt_per_cdp,
+                redemption_fee_floor_bps: p.redemption_fee_floor_bps,
+                redemption_fee_ceiling_bps: p.redemption_fee_ceiling_bps,
+                min_oracle_sources: p.min_oracle_sources,
+                max_price_staleness_secs: p.max_price_staleness_secs,
+                max_price_deviation_bps: p.max_price_deviation_bps,
+                max_ops_per_window: p.max_ops_per_window,
+                rate_limit_window_blocks: p.rate_limit_window_blocks,
+                min_redeem_interval_blocks: p.min_redeem_interval_blocks,
+                min_liquidation_interval_blocks: p.min_liquidation_interval_blocks,
+                max_redemption_per_block: p.max_redemption_per_block,
+                keeper_reward: p.keeper_reward,
+                max_keeper_rewards_per_block: p.max_keeper_rewards_per_block,
+                ..Default::default()
+            };
+
+            ProtocolState {
+                config: ProtocolConfig {
+                    params,
+                    debt_ceiling: c.debt_ceiling,
+                    paused: c.paused,
+                    recovery_mode: c.recovery_mode,
+                    fees: FeeController {
+                        base_rate: c.base_rate,
+                        last_redemption_time: c.last_redemption_time,
+                    },
+                    total_system_debt: c.total_system_debt,
+                    total_system_collateral: c.total_system_collateral,
+                    chain_id: 0,
+                },
+                total_supply: old.total_supply,
+                total_collateral: old.total_collateral,
+                total_debt: old.total_debt,
+                active_cdps: old.active_cdps,
+                block_height: old.block_height,
+                last_update: old.last_update,
+                version: old.version,
+            }
+        }
+    }
 
-    /// Save latest price data
-    pub fn save_price(&self, price_cents: u64, timestamp: u64) -> Result<()> {
-        let key = make_key(prefixes::PRICE, b"latest");
-        let data = (price_cents, timestamp);
-        self.store.set(&key, &data)
+    impl From<ProtocolState> for ProtocolStateV7 {
+        fn from(state: ProtocolState) -> Self {
+            let c = state.config;
+            let p = c.params;
+            ProtocolStateV7 {
+                config: ProtocolConfigV7 {
+                    params: ProtocolParamsV7 {
+                        version: p.version,
+                        min_collateral_ratio: p.min_collateral_ratio,
+                        critical_collateral_ratio: p.critical_collateral_ratio,
+                        borrowing_fee_bps: p.borrowing_fee_bps,
+                        liquidation_bonus_bps: p.liquidation_bonus_bps,
+                        min_debt: p.min_debt,
+                        max_debt_per_cdp: p.max_debt_per_cdp,
+                        redemption_fee_floor_bps: p.redemption_fee_floor_bps,
+                        redemption_fee_ceiling_bps: p.redemption_fee_ceiling_bps,
+                        min_oracle_sources: p.min_oracle_sources,
+                        max_price_staleness_secs: p.max_price_staleness_secs,
+                        max_price_deviation_bps: p.max_price_deviation_bps,
+                        max_ops_per_window: p.max_ops_per_window,
+                        rate_limit_window_blocks: p.rate_limit_window_blocks,
+                        min_redeem_interval_blocks: p.min_redeem_interval_blocks,
+                        min_liquidation_interval_blocks: p.min_liquidation_interval_blocks,
+                        max_redemption_per_block: p.max_redemption_per_block,
+                        keeper_reward: p.keeper_reward,
+                        max_keeper_rewards_per_block: p.max_keeper_rewards_per_block,
+                    },
+                    debt_ceiling: c.debt_ceiling,
+                    paused: c.paused,
+                    recovery_mode: c.recovery_mode,
+                    base_rate: c.fees.base_rate,
+                    last_redemption_time: c.fees.last_redemption_time,
+                    total_system_debt: c.total_system_debt,
+                    total_system_collateral: c.total_system_collateral,
+                },
+                total_supply: state.total_supply,
+                total_collateral: state.total_collateral,
+                total_debt: state.total_debt,
+                active_cdps: state.active_cdps,
+                block_height: state.block_height,
+                last_update: state.last_update,
+                version: state.version,
+            }
+        }
     }
 
-    /// Load latest price data
-    pub fn load_price(&self) -> Result<Option<(u64, u64)>> {
-        let key = make_key(prefixes::PRICE, b"latest");
-        self.store.get(&key)
+    /// Protocol parameters before the stability pool withdrawal gate
+    /// (schema 8-9)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct ProtocolParamsV9 {
+        version: String,
+        min_collateral_ratio: u64,
+        critical_collateral_ratio: u64,
+        borrowing_fee_bps: u64,
+        liquidation_bonus_bps: u64,
+        min_debt: u64,
+        max_debt_per_cdp: u64,
+        redemption_fee_floor_bps: u64,
+        redemption_fee_ceiling_bps: u64,
+        min_oracle_sources: usize,
+        max_price_staleness_secs: u64,
+        max_price_deviation_bps: u64,
+        max_ops_per_window: u32,
+        rate_limit_window_blocks: u64,
+        min_redeem_interval_blocks: u64,
+        min_liquidation_interval_blocks: u64,
+        max_redemption_per_block: u64,
+        keeper_reward: u64,
+        max_keeper_rewards_per_block: u64,
+        liquidation_penalty: PenaltyCurve,
     }
 
-    /// Save price history entry
-    pub fn save_price_history(&self, timestamp: u64, price_cents: u64) -> Result<()> {
-        let key = make_key(prefixes::PRICE, &timestamp.to_be_bytes());
-        self.store.set(&key, &price_cents)
+    impl From<ProtocolParamsV9> for ProtocolParams {
+        fn from(p: ProtocolParamsV9) -> Self {
+            ProtocolParams {
+                version: p.version,
+                min_collateral_ratio: p.min_collateral_ratio,
+                critical_collateral_ratio: p.critical_collateral_ratio,
+                borrowing_fee_bps: p.borrowing_fee_bps,
+                liquidation_bonus_bps: p.liquidation_bonus_bps,
+                min_debt: p.min_debt,
+                max_debt_per_cdp: p.max_debt_per_cdp,
+                redemption_fee_floor_bps: p.redemption_fee_floor_bps,
+                redemption_fee_ceiling_bps: p.redemption_fee_ceiling_bps,
+                min_oracle_sources: p.min_oracle_sources,
+                max_price_staleness_secs: p.max_price_staleness_secs,
+                max_price_deviation_bps: p.max_price_deviation_bps,
+                max_ops_per_window: p.max_ops_per_window,
+                rate_limit_window_blocks: p.rate_limit_window_blocks,
+                min_redeem_interval_blocks: p.min_redeem_interval_blocks,
+                min_liquidation_interval_blocks: p.min_liquidation_interval_blocks,
+                max_redemption_per_block: p.max_redemption_per_block,
+                keeper_reward: p.keeper_reward,
+                max_keeper_rewards_per_block: p.max_keeper_rewards_per_block,
+                liquidation_penalty: p.liquidation_penalty,
+                ..Default::default()
+            }
+        }
     }
 
-    // ═══════════════════════════════════════════════════════════════════════════
-    // TRANSACTIONS
-    // ═══════════════════════════════════════════════════════════════════════════
+    impl From<ProtocolParams> for ProtocolParamsV9 {
+        fn from(p: ProtocolParams) -> Self {
+            ProtocolParamsV9 {
+                version: p.version,
+                min_collateral_ratio: p.min_collateral_ratio,
+                critical_collateral_ratio: p.critical_collateral_ratio,
+                borrowing_fee_bps: p.borrowing_fee_bps,
+                liquidation_bonus_bps: p.liquidation_bonus_bps,
+                min_debt: p.min_debt,
+                max_debt_per_cdp: p.max_debt_per_cdp,
+                redemption_fee_floor_bps: p.redemption_fee_floor_bps,
+                redemption_fee_ceiling_bps: p.redemption_fee_ceiling_bps,
+                min_oracle_sources: p.min_oracle_sources,
+                max_price_staleness_secs: p.max_price_staleness_secs,
+                max_price_deviation_bps: p.max_price_deviation_bps,
+                max_ops_per_window: p.max_ops_per_window,
+                rate_limit_window_blocks: p.rate_limit_window_blocks,
+                min_redeem_interval_blocks: p.min_redeem_interval_blocks,
+                min_liquidation_interval_blocks: p.min_liquidation_interval_blocks,
+                max_redemption_per_block: p.max_redemption_per_block,
+                keeper_reward: p.keeper_reward,
+                max_keeper_rewards_per_block: p.max_keeper_rewards_per_block,
+                liquidation_penalty: p.liquidation_penalty,
+            }
+        }
+    }
 
-    /// Save a transaction record
-    pub fn save_transaction(&self, tx: &TransactionRecord) -> Result<()> {
-        let key = make_key(prefixes::TX, tx.hash.as_bytes());
-        self.store.set(&key, tx)
+    /// Protocol configuration before the chain id (schema 8)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct ProtocolConfigV8 {
+        params: ProtocolParamsV9,
+        debt_ceiling: u64,
+        paused: bool,
+        recovery_mode: bool,
+        fees: FeeController,
+        total_system_debt: u64,
+        total_system_collateral: u64,
     }
 
-    /// Load a transaction by hash
-    pub fn load_transaction(&self, hash: &Hash) -> Result<Option<TransactionRecord>> {
-        let key = make_key(prefixes::TX, hash.as_bytes());
-        self.store.get(&key)
+    /// Protocol state (schema 8)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct ProtocolStateV8 {
+        config: ProtocolConfigV8,
+        total_supply: u64,
+        total_collateral: u64,
+        total_debt: u64,
+        active_cdps: u64,
+        block_height: u64,
+        last_update: u64,
+        version: u32,
     }
 
-    /// Load recent transactions (last N)
-    pub fn load_recent_transactions(&self, limit: usize) -> Result<Vec<TransactionRecord>> {
-        let keys = self.store.list_prefix(prefixes::TX)?;
-        let mut txs = Vec::new();
+    impl From<ProtocolStateV8> for ProtocolState {
+        fn from(old: ProtocolStateV8) -> Self {
+            let c = old.config;
+            ProtocolState {
+                config: ProtocolConfig {
+                    params: c.params.into(),
+                    debt_ceiling: c.debt_ceiling,
+                    paused: c.paused,
+                    recovery_mode: c.recovery_mode,
+                    fees: c.fees,
+                    total_system_debt: c.total_system_debt,
+                    total_system_collateral: c.total_system_collateral,
+                    chain_id: 0,
+                },
+                total_supply: old.total_supply,
+                total_collateral: old.total_collateral,
+                total_debt: old.total_debt,
+                active_cdps: old.active_cdps,
+                block_height: old.block_height,
+                last_update: old.last_update,
+                version: old.version,
+            }
+        }
+    }
 
-        for key in keys.iter().take(limit) {
-            if let Some(tx) = self.store.get::<TransactionRecord>(key)? {
-                txs.push(tx);
+    impl From<ProtocolState> for ProtocolStateV8 {
+        fn from(state: ProtocolState) -> Self {
+            let c = state.config;
+            ProtocolStateV8 {
+                config: ProtocolConfigV8 {
+                    params: c.params.into(),
+                    debt_ceiling: c.debt_ceiling,
+                    paused: c.paused,
+                    recovery_mode: c.recovery_mode,
+                    fees: c.fees,
+                    total_system_debt: c.total_system_debt,
+                    total_system_collateral: c.total_system_collateral,
+                },
+                total_supply: state.total_supply,
+                total_collateral: state.total_collateral,
+                total_debt: state.total_debt,
+                active_cdps: state.active_cdps,
+                block_height: state.block_height,
+                last_update: state.last_update,
+                version: state.version,
             }
         }
+    }
 
-        // Sort by timestamp descending
-        txs.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-        txs.truncate(limit);
+    /// Protocol configuration (schema 9)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct ProtocolConfigV9 {
+        params: ProtocolParamsV9,
+        debt_ceiling: u64,
+        paused: bool,
+        recovery_mode: bool,
+        fees: FeeController,
+        total_system_debt: u64,
+        total_system_collateral: u64,
+        chain_id: u64,
+    }
 
-        Ok(txs)
+    /// Protocol state (schema 9)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct ProtocolStateV9 {
+        config: ProtocolConfigV9,
+        total_supply: u64,
+        total_collateral: u64,
+        total_debt: u64,
+        active_cdps: u64,
+        block_height: u64,
+        last_update: u64,
+        version: u32,
     }
 
-    // ═══════════════════════════════════════════════════════════════════════════
-    // UTILITY METHODS
-    // ═══════════════════════════════════════════════════════════════════════════
+    impl From<ProtocolStateV9> for ProtocolState {
+        fn from(old: ProtocolStateV9) -> Self {
+            let c = old.config;
+            ProtocolState {
+                config: ProtocolConfig {
+                    params: c.params.into(),
+                    debt_ceiling: c.debt_ceiling,
+                    paused: c.paused,
+                    recovery_mode: c.recovery_mode,
+                    fees: c.fees,
+                    total_system_debt: c.total_system_debt,
+                    total_system_collateral: c.total_system_collateral,
+                    chain_id: c.chain_id,
+                },
+                total_supply: old.total_supply,
+                total_collateral: old.total_collateral,
+                total_debt: old.total_debt,
+                active_cdps: old.active_cdps,
+                block_height: old.block_height,
+                last_update: old.last_update,
+                version: old.version,
+            }
+        }
+    }
 
-    /// Flush all pending writes
-    pub fn flush(&self) -> Result<()> {
-        self.store.flush()
+    impl From<ProtocolState> for ProtocolStateV9 {
+        fn from(state: ProtocolState) -> Self {
+            let c = state.config;
+            ProtocolStateV9 {
+                config: ProtocolConfigV9 {
+                    params: c.params.into(),
+                    debt_ceiling: c.debt_ceiling,
+                    paused: c.paused,
+                    recovery_mode: c.recovery_mode,
+                    fees: c.fees,
+                    total_system_debt: c.total_system_debt,
+                    total_system_collateral: c.total_system_collateral,
+                    chain_id: c.chain_id,
+                },
+                total_supply: state.total_supply,
+                total_collateral: state.total_collateral,
+                total_debt: state.total_debt,
+                active_cdps: state.active_cdps,
+                block_height: state.block_height,
+                last_update: state.last_update,
+                version: state.version,
+            }
+        }
     }
 
-    /// Clear all data (for testing)
-    pub fn clear(&self) -> Result<()> {
-        self.store.clear()
+    /// CDP before owner policies (schema 1-3)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct CDPV3 {
+        pub(super) id: CDPId,
+        pub(super) owner: PublicKey,
+        pub(super) collateral_sats: u64,
+        pub(super) debt_cents: u64,
+        pub(super) created_at: u64,
+        pub(super) last_updated: u64,
+        pub(super) status: CDPStatus,
+        pub(super) nonce: u64,
     }
 
-    /// Compute state root hash (Merkle root of all data)
-    pub fn compute_state_root(&self) -> Result<Hash> {
-        use crate::utils::crypto::merkle_root;
+    /// Stability pool with a single running gain sum (schema 1-5)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct StabilityPoolV5 {
+        pub(super) total_deposits: TokenAmount,
+        pub(super) total_btc_gains: CollateralAmount,
+        pub(super) p: u128,
+        pub(super) s: u128,
+        pub(super) epoch: u64,
+        pub(super) scale: u64,
+        pub(super) deposits: HashMap<PublicKey, Deposit>,
+        pub(super) pending_btc: HashMap<PublicKey, CollateralAmount>,
+        pub(super) total_liquidations: u64,
+        pub(super) total_debt_absorbed: TokenAmount,
+    }
 
-        let mut hashes = Vec::new();
+    impl From<StabilityPoolV5> for StabilityPool {
+        /// Settle every deposit under the old accounting, then restart it
+        /// from its settled value
+        fn from(mut old: StabilityPoolV5) -> Self {
+            let settled: Vec<_> = old
+                .deposits
+                .drain()
+                .map(|(owner, mut deposit)| {
+                    let snapshot = deposit.snapshot;
+                    let initial = deposit.initial_amount.cents() as u128;
+                    let current = old.epoch == snapshot.epoch && snapshot.p > 0;
+
+                    let value = match old.scale.checked_sub(snapshot.scale) {
+                        Some(0) if current => initial.saturating_mul(old.p) / snapshot.p,
+                        Some(1) if current => initial.saturating_mul(old.p) / snapshot.p / SP_SCALE_FACTOR,
+                        _ => 0,
+                    };
+                    let gains = if old.epoch == snapshot.epoch {
+                        initial.saturating_mul(old.s.saturating_sub(snapshot.s)) / SP_SCALE_FACTOR
+                    } else {
+                        0
+                    };
+
+                    let pending = old.pending_btc.remove(&owner).unwrap_or(CollateralAmount::ZERO);
+                    deposit.initial_amount = TokenAmount::from_cents(value.min(u64::MAX as u128) as u64);
+                    let gains = CollateralAmount::from_sats(gains.min(u64::MAX as u128) as u64);
+                    (deposit, pending.saturating_add(gains))
+                })
+                .collect();
+
+            // Pending gains of fully withdrawn deposits
+            let leftover = old.pending_btc.into_iter().map(|(owner, pending)| {
+                (Deposit::new(owner, TokenAmount::ZERO, Default::default(), 0), pending)
+            });
+
+            StabilityPool::from_settled(
+                settled.into_iter().chain(leftover),
+                old.total_deposits,
+                old.total_btc_gains,
+                old.total_liquidations,
+                old.total_debt_absorbed,
+            )
+        }
+    }
 
-        // Hash protocol state
-        if let Ok(state) = self.load_protocol_state() {
-            hashes.push(state.hash());
+    impl From<CDPV3> for CDP {
+        fn from(old: CDPV3) -> Self {
+            CDP {
+                id: old.id,
+                owner: old.owner,
+                collateral_sats: old.collateral_sats,
+                debt_cents: old.debt_cents,
+                created_at: old.created_at,
+                last_updated: old.last_updated,
+                status: old.status,
+                nonce: old.nonce,
+                policy: OwnerPolicy::Single(old.owner),
+                last_owner_action: old.last_updated,
+                sessions: Vec::new(),
+            }
         }
+    }
 
-        // Hash all CDPs
-        for cdp in self.load_all_cdps()? {
-            hashes.push(cdp.state_hash());
+    /// CDP before session keys (schema 4-10)
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct CDPV10 {
+        id: CDPId,
+        owner: PublicKey,
+        collateral_sats: u64,
+        debt_cents: u64,
+        created_at: u64,
+        last_updated: u64,
+        status: CDPStatus,
+        nonce: u64,
+        policy: OwnerPolicy,
+        last_owner_action: u64,
+    }
+
+    impl From<CDPV10> for CDP {
+        fn from(old: CDPV10) -> Self {
+            CDP {
+                id: old.id,
+                owner: old.owner,
+                collateral_sats: old.collateral_sats,
+                debt_cents: old.debt_cents,
+                created_at: old.created_at,
+                last_updated: old.last_updated,
+                status: old.status,
+                nonce: old.nonce,
+                policy: old.policy,
+                last_owner_action: old.last_owner_action,
+                sessions: Vec::new(),
+            }
+        }
+    }
+
+    impl From<CDP> for CDPV10 {
+        fn from(cdp: CDP) -> Self {
+            CDPV10 {
+                id: cdp.id,
+                owner: cdp.owner,
+                collateral_sats: cdp.collateral_sats,
+                debt_cents: cdp.debt_cents,
+                created_at: cdp.created_at,
+                last_updated: cdp.last_updated,
+                status: cdp.status,
+                nonce: cdp.nonce,
+                policy: cdp.policy,
+                last_owner_action: cdp.last_owner_action,
+            }
         }
+    }
 
-        // Compute Merkle root
-        Ok(merkle_root(&hashes))
+    #[cfg(test)]
+    impl From<ProtocolState> for ProtocolStateV2 {
+        fn from(state: ProtocolState) -> Self {
+            let c = state.config;
+            let p = c.params;
+            ProtocolStateV2 {
+                config: ProtocolConfigV2 {
+                    params: ProtocolParamsV2 {
+                        version: p.version,
+                        min_collateral_ratio: p.min_collateral_ratio,
+                        critical_collateral_ratio: p.critical_collateral_ratio,
+                        borrowing_fee_bps: p.borrowing_fee_bps,
+                        liquidation_bonus_bps: p.liquidation_bonus_bps,
+                        min_debt: p.min_debt,
+                        max_debt_per_cdp: p.max_debt_per_cdp,
+                        redemption_fee_floor_bps: p.redemption_fee_floor_bps,
+                        redemption_fee_ceiling_bps: p.redemption_fee_ceiling_bps,
+                        min_oracle_sources: p.min_oracle_sources,
+                        max_price_staleness_secs: p.max_price_staleness_secs,
+                        max_price_deviation_bps: p.max_price_deviation_bps,
+                    },
+                    debt_ceiling: c.debt_ceiling,
+                    paused: c.paused,
+                    recovery_mode: c.recovery_mode,
+                    base_rate: c.fees.base_rate,
+                    last_redemption_time: c.fees.last_redemption_time,
+                    total_system_debt: c.total_system_debt,
+                    total_system_collateral: c.total_system_collateral,
+                },
+                total_supply: state.total_supply,
+                total_collateral: state.total_collateral,
+                total_debt: state.total_debt,
+                active_cdps: state.active_cdps,
+                block_height: state.block_height,
+                last_update: state.last_update,
+                version: state.version,
+            }
+        }
     }
 }
 
@@ -473,6 +2608,9 @@ pub struct StateSnapshot {
     pub block_height: u64,
     /// State root hash
     pub state_root: Hash,
+    /// Hash scheme of the state root
+    #[serde(default)]
+    pub hash_scheme: HashScheme,
 }
 
 impl<B: StorageBackend> StateManager<B> {
@@ -493,6 +2631,7 @@ impl<B: StorageBackend> StateManager<B> {
             timestamp,
             block_height,
             state_root,
+            hash_scheme: self.load_hash_scheme()?,
         })
     }
 
@@ -503,6 +2642,8 @@ impl<B: StorageBackend> StateManager<B> {
 
         // Restore protocol state
         self.save_protocol_state(&snapshot.protocol_state)?;
+        self.save_schema_version(snapshot.protocol_state.version)?;
+        self.save_hash_scheme(snapshot.hash_scheme)?;
 
         // Restore CDPs
         for cdp in &snapshot.cdps {
@@ -543,7 +2684,7 @@ mod tests {
 
         // Should create default state if not exists
         let state = manager.initialize_if_needed().unwrap();
-        assert_eq!(state.version, 1);
+        assert_eq!(state.version, SCHEMA_VERSION);
         assert_eq!(state.total_supply, 0);
 
         // Should return existing state on second call
@@ -576,6 +2717,73 @@ mod tests {
         assert!(manager.load_cdp(&cdp_id).unwrap().is_none());
     }
 
+    #[test]
+    fn test_cdp_proof() {
+        let manager = create_test_manager();
+        manager.initialize_if_needed().unwrap();
+
+        let owner = *KeyPair::generate().public_key();
+        let cdps: Vec<CDP> = (0..5)
+            .map(|nonce| CDP::with_collateral(owner, 100_000_000 + nonce, nonce, 100).unwrap())
+            .collect();
+        for cdp in &cdps {
+            manager.save_cdp(cdp).unwrap();
+        }
+        let root = manager.compute_state_root().unwrap();
+
+        // Six leaves with the protocol state, so odd levels are exercised
+        for cdp in &cdps {
+            let proof = manager.prove_cdp(&cdp.id).unwrap().unwrap();
+            assert_eq!(proof.cdp.collateral_sats, cdp.collateral_sats);
+            verify_cdp_proof(&root, &proof).unwrap();
+            assert!(verify_cdp_proof(&Hash::sha256(b"other root"), &proof).is_err());
+        }
+        let missing = CDP::with_collateral(owner, 100_000_000, 99, 100).unwrap();
+        assert!(manager.prove_cdp(&missing.id).unwrap().is_none());
+
+        // A proof can't vouch for a different position
+        let mut forged = manager.prove_cdp(&cdps[2].id).unwrap().unwrap();
+        forged.cdp.collateral_sats += 1;
+        assert!(verify_cdp_proof(&root, &forged).is_err());
+        forged.leaf = cdp_leaf(&forged.cdp).unwrap();
+        assert!(verify_cdp_proof(&root, &forged).is_err());
+
+        let mut moved = manager.prove_cdp(&cdps[2].id).unwrap().unwrap();
+        moved.index += 1 << moved.path.len();
+        assert!(verify_cdp_proof(&root, &moved).is_err());
+
+        // Switching to BLAKE3 changes the root, and proofs carry the scheme
+        manager.save_hash_scheme(HashScheme::Blake3).unwrap();
+        let blake3_root = manager.compute_state_root().unwrap();
+        assert_ne!(blake3_root, root);
+        let proof = manager.prove_cdp(&cdps[4].id).unwrap().unwrap();
+        assert_eq!(proof.scheme, HashScheme::Blake3);
+        verify_cdp_proof(&blake3_root, &proof).unwrap();
+        assert!(verify_cdp_proof(&root, &proof).is_err());
+    }
+
+    #[test]
+    fn test_sha256_state_root_unchanged() {
+        let manager = create_test_manager();
+        let state = manager.initialize_if_needed().unwrap();
+        let owner = *KeyPair::generate().public_key();
+        for nonce in 0..3 {
+            manager.save_cdp(&CDP::with_collateral(owner, 100_000_000, nonce, 100).unwrap()).unwrap();
+        }
+
+        let mut leaves = vec![state.hash()];
+        leaves.extend(manager.load_all_cdps().unwrap().iter().map(CDP::state_hash));
+        assert_eq!(manager.load_hash_scheme().unwrap(), HashScheme::Sha256);
+        assert_eq!(manager.compute_state_root().unwrap(), crate::utils::crypto::merkle_root(&leaves));
+
+        // Snapshots keep the scheme
+        manager.save_hash_scheme(HashScheme::Blake3).unwrap();
+        let snapshot = manager.create_snapshot(1_700_000_000, 1).unwrap();
+        let restored = create_test_manager();
+        restored.restore_from_snapshot(&snapshot).unwrap();
+        assert_eq!(restored.compute_state_root().unwrap(), snapshot.state_root);
+    }
+
     #[test]
     fn test_balance_persistence() {
         let manager = create_test_manager();
@@ -648,4 +2856,235 @@ mod tests {
         state.total_debt = 50;
         assert!(state.verify_invariants().is_err());
     }
+
+    #[test]
+    fn test_migrates_legacy_database() {
+        let manager = create_test_manager();
+
+        // A v1 database has protocol state but no schema version key
+        let legacy = ProtocolState {
+            version: 1,
+            block_height: 42,
+            ..Default::default()
+        };
+        let key = make_key(prefixes::CONFIG, b"state");
+        manager.store.set(&key, &legacy::ProtocolStateV2::from(legacy)).unwrap();
+        assert_eq!(manager.schema_version().unwrap(), Some(1));
+
+        let owner = *KeyPair::generate().public_key();
+        let cdp = legacy::CDPV3 {
+            id: CDPId::generate(&owner, 1),
+            owner,
+            collateral_sats: 1_000,
+            debt_cents: 0,
+            created_at: 40,
+            last_updated: 41,
+            status: CDPStatus::Active,
+            nonce: 1,
+        };
+        manager.store.set(&make_key(prefixes::CDP, cdp.id.as_bytes()), &cdp).unwrap();
+        let nonces: HashMap<[u8; 32], u64> = [(*Hash::sha256(owner.as_bytes()).as_bytes(), 7)].into_iter().collect();
+        manager.store.set(&make_key(prefixes::NONCE, b"main"), &nonces).unwrap();
+
+        let state = manager.initialize_if_needed().unwrap();
+        assert_eq!(state.version, SCHEMA_VERSION);
+        assert_eq!(state.block_height, 42);
+        assert_eq!(
+            state.config.params.max_ops_per_window,
+            crate::utils::constants::MAX_OPS_PER_WINDOW
+        );
+        assert_eq!(
+            state.config.params.max_redemption_per_block,
+            crate::utils::constants::MAX_REDEMPTION_PER_BLOCK
+        );
+        assert_eq!(
+            state.config.params.keeper_reward,
+            crate::utils::constants::KEEPER_REWARD
+        );
+        assert_eq!(state.config.params.liquidation_penalty, Default::default());
+        assert_eq!(state.config.chain_id, 0);
+        assert!(state.config.params.gate_sp_withdrawals);
+
+        let cdps = manager.load_all_cdps().unwrap();
+        assert_eq!(cdps.len(), 1);
+        assert_eq!(cdps[0].policy, OwnerPolicy::Single(owner));
+        assert!(cdps[0].sessions.is_empty());
+        assert_eq!(cdps[0].last_owner_action, 41);
+        let nonces = manager.load_nonces().unwrap().unwrap();
+        assert_eq!(nonces.next(&owner), 8);
+        assert!(nonces.window(&owner).gaps().is_empty());
+        assert_eq!(manager.schema_version().unwrap(), Some(SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn test_migrates_legacy_stability_pool() {
+        use crate::core::token::TokenAmount;
+        use crate::core::vault::CollateralAmount;
+        use crate::liquidation::stability_pool::Deposit;
+        use crate::utils::constants::SP_SCALE_FACTOR;
+
+        let manager = create_test_manager();
+
+        // A v5 database stores protocol state in the v6 layout
+        let legacy = ProtocolState {
+            version: 5,
+            ..Default::default()
+        };
+        manager
+            .store
+            .set(&make_key(prefixes::CONFIG, b"state"), &legacy::ProtocolStateV6::from(legacy))
+            .unwrap();
+        manager.save_schema_version(5).unwrap();
+
+        // Half the pool was absorbed, earning 10 sats per cent deposited
+        let (active, withdrawn) = (*KeyPair::generate().public_key(), *KeyPair::generate().public_key());
+        let pool = legacy::StabilityPoolV5 {
+            total_deposits: TokenAmount::from_cents(50_000),
+            total_btc_gains: CollateralAmount::from_sats(1_000_700),
+            p: SP_SCALE_FACTOR / 2,
+            s: 10 * SP_SCALE_FACTOR,
+            epoch: 0,
+            scale: 0,
+            deposits: [(active, Deposit::new(active, TokenAmount::from_cents(100_000), Default::default(), 1))]
+                .into_iter()
+                .collect(),
+            pending_btc: [(withdrawn, CollateralAmount::from_sats(700))].into_iter().collect(),
+            total_liquidations: 1,
+            total_debt_absorbed: TokenAmount::from_cents(50_000),
+        };
+        manager.store.set(&make_key(prefixes::STABILITY_POOL, b"main"), &pool).unwrap();
+
+        manager.initialize_if_needed().unwrap();
+        let pool = manager.load_stability_pool().unwrap().unwrap();
+        assert_eq!(pool.total_deposits().cents(), 50_000);
+        assert_eq!(pool.get_current_value(&active).cents(), 50_000);
+        assert_eq!(pool.get_btc_gains(&active).sats(), 1_000_000);
+        assert_eq!(pool.get_btc_gains(&withdrawn).sats(), 700);
+        assert_eq!(pool.depositor_count(), 1);
+        assert_eq!(pool.total_liquidations(), 1);
+    }
+
+    #[test]
+    fn test_refuses_newer_schema() {
+        let manager = create_test_manager();
+        manager.initialize_if_needed().unwrap();
+        manager.save_schema_version(SCHEMA_VERSION + 1).unwrap();
+
+        let err = manager.initialize_if_needed().unwrap_err();
+        assert!(matches!(err, Error::UnsupportedSchemaVersion { .. }));
+    }
+
+    #[test]
+    fn test_custom_migrations_run_in_order() {
+        let manager = create_test_manager();
+        manager.initialize_if_needed().unwrap();
+        manager.store.set(b"old:a", &7u32).unwrap();
+
+        let report = Migrator::new(SCHEMA_VERSION + 2)
+            .register(SCHEMA_VERSION + 1, "widen values", |store| {
+                transform_values(store, b"new:", |v: u32| v as u64 * 2).map(|_| ())
+            })
+            .register(SCHEMA_VERSION, "rename prefix", |store| {
+                rekey_prefix(store, b"old:", b"new:").map(|_| ())
+            })
+            .run(&manager)
+            .unwrap();
+
+        assert_eq!(report.applied, vec!["rename prefix", "widen values"]);
+        assert_eq!(report.to_version, SCHEMA_VERSION + 2);
+        assert!(!manager.store.exists(b"old:a").unwrap());
+        assert_eq!(manager.store.get::<u64>(b"new:a").unwrap(), Some(14));
+    }
+
+    #[test]
+    fn test_archive_mode_keeps_history() {
+        let manager = create_test_manager();
+        let keypair = KeyPair::generate();
+
+        let tx = TransactionRecord::new(TransactionType::Mint, *keypair.public_key(), 1, 0, 1);
+        manager.save_transaction(&tx).unwrap();
+        manager.save_price_history(0, 5_000_000).unwrap();
+        manager.save_events(1, &[]).unwrap();
+
+        let stats = manager.prune(10_000, 6_000_000).unwrap();
+        assert_eq!(stats.total(), 0);
+        assert_eq!(manager.storage_stats().unwrap().transactions, 1);
+    }
+
+    #[test]
+    fn test_pruned_mode_removes_old_history() {
+        let manager = StateManager::new(InMemoryStore::new())
+            .with_pruning(PruningMode::Pruned { keep_blocks: 100 });
+        let keypair = KeyPair::generate();
+        let now = 1_000_000;
+
+        let old_tx = TransactionRecord::new(TransactionType::Mint, *keypair.public_key(), 1, 0, 10);
+        let new_tx = TransactionRecord::new(TransactionType::Mint, *keypair.public_key(), 2, now, 950);
+        manager.save_transaction(&old_tx).unwrap();
+        manager.save_transaction(&new_tx).unwrap();
+
+        manager.save_price(5_000_000, now).unwrap();
+        manager.save_price_history(0, 4_000_000).unwrap();
+        manager.save_price_history(now, 5_000_000).unwrap();
+
+        manager.save_events(10, &[]).unwrap();
+        manager.save_events(950, &[]).unwrap();
+
+        let stats = manager.prune(1000, now).unwrap();
+        assert_eq!(stats.transactions, 1);
+        assert_eq!(stats.price_entries, 1);
+        assert_eq!(stats.event_entries, 1);
+
+        let usage = manager.storage_stats().unwrap();
+        assert_eq!(usage.transactions, 1);
+        assert_eq!(usage.price_entries, 2);
+        assert_eq!(usage.event_entries, 1);
+        assert!(manager.load_price().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_cdp_history() {
+        let manager = StateManager::new(InMemoryStore::new()).with_cdp_history();
+        let keypair = KeyPair::generate();
+
+        let mut cdp = CDP::with_collateral(*keypair.public_key(), 100_000_000, 1, 100).unwrap();
+        manager.save_cdp(&cdp).unwrap();
+        cdp.deposit_collateral(50_000_000, 110).unwrap();
+        manager.save_cdp(&cdp).unwrap();
+        cdp.deposit_collateral(25_000_000, 120).unwrap();
+        manager.save_cdp(&cdp).unwrap();
+
+        assert_eq!(manager.cdp_version_heights(&cdp.id).unwrap(), vec![100, 110, 120]);
+        assert!(manager.load_cdp_at(&cdp.id, 99).unwrap().is_none());
+        assert_eq!(manager.load_cdp_at(&cdp.id, 100).unwrap().unwrap().collateral_sats, 100_000_000);
+        assert_eq!(manager.load_cdp_at(&cdp.id, 115).unwrap().unwrap().collateral_sats, 150_000_000);
+        assert_eq!(manager.load_cdp_at(&cdp.id, 500).unwrap().unwrap().collateral_sats, 175_000_000);
+
+        assert!(manager.discard_cdp_version(&cdp.id, 120).unwrap());
+        assert_eq!(manager.load_cdp_at(&cdp.id, 500).unwrap().unwrap().collateral_sats, 150_000_000);
+        assert_eq!(manager.storage_stats().unwrap().cdp_versions, 2);
+    }
+
+    #[test]
+    fn test_pruning_keeps_cdp_version_at_cutoff() {
+        let manager = StateManager::new(InMemoryStore::new())
+            .with_cdp_history()
+            .with_pruning(PruningMode::Pruned { keep_blocks: 100 });
+        let keypair = KeyPair::generate();
+
+        let mut cdp = CDP::with_collateral(*keypair.public_key(), 100_000_000, 1, 100).unwrap();
+        manager.save_cdp(&cdp).unwrap();
+        for height in [200, 300, 950] {
+            cdp.deposit_collateral(1_000, height).unwrap();
+            manager.save_cdp(&cdp).unwrap();
+        }
+
+        let stats = manager.prune(1000, 0).unwrap();
+        assert_eq!(stats.cdp_versions, 2);
+        assert_eq!(manager.cdp_version_heights(&cdp.id).unwrap(), vec![300, 950]);
+
+        // State at the cutoff is still answerable
+        let at_cutoff = manager.load_cdp_at(&cdp.id, 900).unwrap().unwrap();
+        assert_eq!(at_cutoff.last_updated, 300);
+    }
 }
@@ -0,0 +1,543 @@
+//! Property-based invariant testing.
+//!
+//! Random sequences of user actions are generated with proptest and run
+//! against a [`ProtocolStateMachine`]. After every step the harness checks
+//! the protocol's books against the [`ReferenceModel`]:
+//!
+//! - **Debt**: the sum of CDP debt, plus debt written off by direct
+//!   liquidations, equals the token supply plus stability pool deposits plus
+//!   the savings pot. Fees credited to the savings reserve are debt owed
+//!   without tokens in circulation.
+//! - **Fees**: every fee charged ends up in the treasury account or in the
+//!   savings reserve, less the keeper stipends the treasury has paid out.
+//! - **Collateral**: the vault holds exactly the sum of CDP collateral, and
+//!   the stability pool never holds more liquidation gains than it was given.
+//!   Seized collateral leaves the vault, so pool gains are tracked separately.
+//! - **Ratios**: an operation that adds risk leaves its CDP at or above the
+//!   MCR. A CDP may only fall below it when the price moves, and the
+//!   end-of-block liquidation sweep clears all of those.
+//! - **Nonces**: the harness signs with increasing nonces, and a replayed
+//!   nonce is always rejected.
+
+mod model;
+
+pub use model::ReferenceModel;
+
+use proptest::prelude::*;
+use serde::Serialize;
+
+use crate::core::cdp::CDPId;
+use crate::core::config::ProtocolParams;
+use crate::core::token::TokenAmount;
+use crate::core::vault::CollateralAmount;
+use crate::error::Error;
+use crate::protocol::operations::*;
+use crate::protocol::safety::WatchdogConfig;
+use crate::protocol::signing::SigningDomain;
+use crate::protocol::state_machine::{OperationResult, ProtocolStateMachine};
+use crate::storage::backend::InMemoryStore;
+use crate::utils::constants::SIGNATURE_LENGTH;
+use crate::utils::crypto::{KeyPair, Signature};
+
+/// Number of simulated users
+const USERS: usize = 4;
+
+/// Opening BTC price in cents ($60,000)
+const INITIAL_PRICE: u64 = 6_000_000;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// ACTIONS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// A user action. Indices are reduced modulo the users and CDPs that exist.
+#[derive(Debug, Clone)]
+pub enum Action {
+    /// Open a CDP
+    Open { user: usize, collateral: u64, debt: u64 },
+    /// Add collateral to a CDP (anyone may)
+    Deposit { user: usize, cdp: usize, amount: u64 },
+    /// Owner withdraws collateral
+    Withdraw { cdp: usize, amount: u64 },
+    /// Owner mints debt
+    Mint { cdp: usize, amount: u64 },
+    /// Repay debt (anyone may)
+    Repay { user: usize, cdp: usize, amount: u64 },
+    /// Owner closes a CDP
+    Close { cdp: usize },
+    /// Transfer tokens
+    Transfer { from: usize, to: usize, amount: u64 },
+    /// Deposit into the stability pool
+    PoolDeposit { user: usize, amount: u64 },
+    /// Withdraw from the stability pool
+    PoolWithdraw { user: usize, amount: u64 },
+    /// Claim stability pool gains
+    Claim { user: usize },
+    /// Redeem tokens for collateral
+    Redeem { user: usize, amount: u64 },
+    /// Try to liquidate a CDP
+    Liquidate { user: usize, cdp: usize },
+    /// Publish a new price
+    SetPrice { price: u64 },
+    /// Resubmit an operation with an already used nonce
+    Replay { user: usize },
+    /// Sweep liquidations and move to the next block
+    NextBlock,
+}
+
+/// Strategy generating a single action
+pub fn action() -> impl Strategy<Value = Action> {
+    let user = 0..USERS;
+    let cdp = any::<usize>();
+    let sats = 1_000_000u64..50_000_000;
+    let cents = 1_000u64..2_000_000;
+
+    prop_oneof![
+        3 => (user.clone(), sats.clone(), prop_oneof![Just(0u64), 10_000u64..2_000_000])
+            .prop_map(|(user, collateral, debt)| Action::Open { user, collateral, debt }),
+        2 => (user.clone(), cdp, sats.clone())
+            .prop_map(|(user, cdp, amount)| Action::Deposit { user, cdp, amount }),
+        2 => (cdp, sats).prop_map(|(cdp, amount)| Action::Withdraw { cdp, amount }),
+        3 => (cdp, cents.clone()).prop_map(|(cdp, amount)| Action::Mint { cdp, amount }),
+        2 => (user.clone(), cdp, cents.clone())
+            .prop_map(|(user, cdp, amount)| Action::Repay { user, cdp, amount }),
+        1 => cdp.prop_map(|cdp| Action::Close { cdp }),
+        2 => (user.clone(), user.clone(), cents.clone())
+            .prop_map(|(from, to, amount)| Action::Transfer { from, to, amount }),
+        2 => (user.clone(), cents.clone()).prop_map(|(user, amount)| Action::PoolDeposit { user, amount }),
+        1 => (user.clone(), cents.clone()).prop_map(|(user, amount)| Action::PoolWithdraw { user, amount }),
+        1 => user.clone().prop_map(|user| Action::Claim { user }),
+        1 => (user.clone(), cents).prop_map(|(user, amount)| Action::Redeem { user, amount }),
+        1 => (user.clone(), cdp).prop_map(|(user, cdp)| Action::Liquidate { user, cdp }),
+        2 => (2_000_000u64..12_000_000).prop_map(|price| Action::SetPrice { price }),
+        1 => user.prop_map(|user| Action::Replay { user }),
+        2 => Just(Action::NextBlock),
+    ]
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// HARNESS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Drives a state machine and checks invariants after every step
+pub struct Harness {
+    machine: ProtocolStateMachine<InMemoryStore>,
+    users: Vec<KeyPair>,
+    oracle: KeyPair,
+    /// CDPs opened so far, with the index of their owner
+    cdps: Vec<(CDPId, usize)>,
+    model: ReferenceModel,
+    block: u64,
+}
+
+impl Harness {
+    /// Create a harness with fixed keys and an initial price
+    pub fn new() -> Self {
+        // Rate limits and the oracle watchdog would only add noise here
+        let params = ProtocolParams::default().with_rate_limit(0, 1).with_min_intervals(0, 0);
+        let machine = ProtocolStateMachine::new(InMemoryStore::new())
+            .expect("in-memory state machine")
+            .with_params(params)
+            .with_watchdog(WatchdogConfig { enabled: false, ..Default::default() });
+
+        let key = |seed: u8| KeyPair::from_bytes(&[seed; 32]).expect("valid key");
+        let mut harness = Self {
+            machine,
+            users: (1..=USERS as u8).map(key).collect(),
+            oracle: key(0xee),
+            cdps: Vec::new(),
+            model: ReferenceModel::new(),
+            block: 1,
+        };
+
+        harness.machine.begin_block(harness.block, harness.block * 600).expect("begin block");
+        harness.step(&Action::SetPrice { price: INITIAL_PRICE });
+        harness
+    }
+
+    /// Run one action and check all invariants
+    pub fn step(&mut self, action: &Action) {
+        match *action {
+            Action::Open { user, collateral, debt } => {
+                let owner = *self.users[user].public_key();
+                let result = self.submit(user, |nonce| {
+                    ProtocolOperation::OpenCDP(OpenCDPOp {
+                        owner,
+                        collateral: CollateralAmount::from_sats(collateral),
+                        initial_debt: (debt > 0).then_some(TokenAmount::from_cents(debt)),
+                        nonce,
+                        signature: blank(),
+                    })
+                });
+                if let Some(result) = result {
+                    if let OperationResult::OpenCDP(r) = &result {
+                        self.cdps.push((r.cdp_id, user));
+                        self.assert_healthy(&r.cdp_id);
+                    }
+                    self.model.apply(&result, collateral, false);
+                }
+            }
+
+            Action::Deposit { user, cdp, amount } => {
+                let Some((cdp_id, _)) = self.cdp(cdp) else { return };
+                let depositor = *self.users[user].public_key();
+                let result = self.submit(user, |nonce| {
+                    ProtocolOperation::DepositCollateral(DepositCollateralOp {
+                        cdp_id,
+                        depositor,
+                        amount: CollateralAmount::from_sats(amount),
+                        nonce,
+                        signature: blank(),
+                    })
+                });
+                if let Some(result) = result {
+                    self.model.apply(&result, amount, false);
+                }
+            }
+
+            Action::Withdraw { cdp, amount } => {
+                let Some((cdp_id, owner)) = self.cdp(cdp) else { return };
+                let owner_key = *self.users[owner].public_key();
+                let result = self.submit(owner, |nonce| {
+                    ProtocolOperation::WithdrawCollateral(WithdrawCollateralOp {
+                        cdp_id,
+                        owner: owner_key,
+                        amount: CollateralAmount::from_sats(amount),
+                        nonce,
+                        signature: blank(),
+                        cosignatures: Vec::new(),
+                    })
+                });
+                if let Some(result) = result {
+                    self.assert_healthy(&cdp_id);
+                    self.model.apply(&result, 0, false);
+                }
+            }
+
+            Action::Mint { cdp, amount } => {
+                let Some((cdp_id, owner)) = self.cdp(cdp) else { return };
+                let owner_key = *self.users[owner].public_key();
+                let result = self.submit(owner, |nonce| {
+                    ProtocolOperation::MintDebt(MintDebtOp {
+                        cdp_id,
+                        owner: owner_key,
+                        amount: TokenAmount::from_cents(amount),
+                        max_fee_bps: 10_000,
+                        nonce,
+                        signature: blank(),
+                        cosignatures: Vec::new(),
+                    })
+                });
+                if let Some(result) = result {
+                    self.assert_healthy(&cdp_id);
+                    self.model.apply(&result, 0, false);
+                }
+            }
+
+            Action::Repay { user, cdp, amount } => {
+                let Some((cdp_id, _)) = self.cdp(cdp) else { return };
+                let payer = *self.users[user].public_key();
+                self.submit(user, |nonce| {
+                    ProtocolOperation::RepayDebt(RepayDebtOp {
+                        cdp_id,
+                        payer,
+                        amount: TokenAmount::from_cents(amount),
+                        nonce,
+                        signature: blank(),
+                    })
+                });
+            }
+
+            Action::Close { cdp } => {
+                let Some((cdp_id, owner)) = self.cdp(cdp) else { return };
+                let owner_key = *self.users[owner].public_key();
+                let result = self.submit(owner, |nonce| {
+                    ProtocolOperation::CloseCDP(CloseCDPOp {
+                        cdp_id,
+                        owner: owner_key,
+                        nonce,
+                        signature: blank(),
+                        cosignatures: Vec::new(),
+                    })
+                });
+                if let Some(result) = result {
+                    self.model.apply(&result, 0, false);
+                }
+            }
+
+            Action::Transfer { from, to, amount } => {
+                let (sender, recipient) = (*self.users[from].public_key(), *self.users[to].public_key());
+                self.submit(from, |nonce| {
+                    ProtocolOperation::Transfer(TransferOp {
+                        from: sender,
+                        to: recipient,
+                        amount: TokenAmount::from_cents(amount),
+                        nonce,
+                        signature: blank(),
+                    })
+                });
+            }
+
+            Action::PoolDeposit { user, amount } => {
+                let depositor = *self.users[user].public_key();
+                self.submit(user, |nonce| {
+                    ProtocolOperation::StabilityDeposit(StabilityDepositOp {
+                        depositor,
+                        amount: TokenAmount::from_cents(amount),
+                        frontend: None,
+                        nonce,
+                        signature: blank(),
+                    })
+                });
+            }
+
+            Action::PoolWithdraw { user, amount } => {
+                let depositor = *self.users[user].public_key();
+                self.submit(user, |nonce| {
+                    ProtocolOperation::StabilityWithdraw(StabilityWithdrawOp {
+                        depositor,
+                        amount: TokenAmount::from_cents(amount),
+                        nonce,
+                        signature: blank(),
+                    })
+                });
+            }
+
+            Action::Claim { user } => {
+                let depositor = *self.users[user].public_key();
+                self.submit(user, |nonce| {
+                    ProtocolOperation::ClaimGains(ClaimGainsOp { depositor, nonce, signature: blank() })
+                });
+            }
+
+            Action::Redeem { user, amount } => {
+                let redeemer = *self.users[user].public_key();
+                let result = self.submit(user, |nonce| {
+                    ProtocolOperation::Redeem(RedeemOp {
+                        redeemer,
+                        amount: TokenAmount::from_cents(amount),
+                        max_fee_bps: 10_000,
+                        first_cdp_hint: None,
+                        last_cdp_hint: None,
+                        max_cdps: 0,
+                        nonce,
+                        signature: blank(),
+                    })
+                });
+                if let Some(result) = result {
+                    self.model.apply(&result, 0, false);
+                }
+            }
+
+            Action::Liquidate { user, cdp } => {
+                let Some((cdp_id, _)) = self.cdp(cdp) else { return };
+                self.liquidate(user, cdp_id);
+            }
+
+            Action::SetPrice { price } => {
+                let operator = *self.oracle.public_key();
+                let nonce = self.model.next_nonce(&operator);
+                let op = sign(
+                    UpdatePriceOp {
+                        operator,
+                        price_cents: price,
+                        source_count: 3,
+                        confidence: 90,
+                        confidence_interval: 0,
+                        proof: Vec::new(),
+                        nonce,
+                        signature: blank(),
+                    },
+                    &self.oracle,
+                    self.machine.signing_domain(),
+                );
+                self.machine
+                    .execute(ProtocolOperation::UpdatePrice(op))
+                    .expect("price updates always apply");
+                self.model.accept_nonce(&operator, nonce);
+            }
+
+            Action::Replay { user } => {
+                let owner = *self.users[user].public_key();
+                let spender = *self.users[(user + 1) % USERS].public_key();
+                let nonce = self.model.last_nonce(&owner);
+                let op = sign(
+                    ApproveOp {
+                        owner,
+                        spender,
+                        amount: TokenAmount::from_cents(1),
+                        nonce,
+                        signature: blank(),
+                    },
+                    &self.users[user],
+                    self.machine.signing_domain(),
+                );
+                let result = self.machine.execute(ProtocolOperation::Approve(op));
+                assert!(
+                    matches!(result, Err(Error::InvalidParameter { ref name, .. }) if name == "nonce"),
+                    "replayed nonce {} was not rejected: {:?}",
+                    nonce,
+                    result
+                );
+            }
+
+            Action::NextBlock => {
+                // Clear everything the last price left under water
+                let liquidatable: Vec<CDPId> = self
+                    .cdps
+                    .iter()
+                    .map(|(id, _)| *id)
+                    .filter(|id| self.is_liquidatable(id))
+                    .collect();
+                for id in liquidatable {
+                    assert!(self.liquidate(0, id), "liquidatable CDP {} could not be liquidated", id.to_hex());
+                }
+                for (id, _) in &self.cdps {
+                    assert!(!self.is_liquidatable(id), "CDP {} below MCR after sweep", id.to_hex());
+                }
+
+                self.machine.end_block().expect("end block");
+                self.block += 1;
+                self.machine.begin_block(self.block, self.block * 600).expect("begin block");
+            }
+        }
+
+        self.check_invariants();
+    }
+
+    /// Sign and execute an operation built for the user's next nonce
+    fn submit(
+        &mut self,
+        user: usize,
+        build: impl FnOnce(u64) -> ProtocolOperation,
+    ) -> Option<OperationResult> {
+        let signer = *self.users[user].public_key();
+        let nonce = self.model.next_nonce(&signer);
+        let op = sign_operation(build(nonce), &self.users[user], self.machine.signing_domain());
+
+        let result = self.machine.execute(op).ok()?;
+        self.model.accept_nonce(&signer, nonce);
+        Some(result)
+    }
+
+    /// Attempt a liquidation; returns whether it went through
+    fn liquidate(&mut self, user: usize, cdp_id: CDPId) -> bool {
+        let debt = self.machine.get_cdp(&cdp_id).map_or(0, |cdp| cdp.debt_cents);
+        let absorbed = self.machine.stability_pool().can_absorb(TokenAmount::from_cents(debt));
+        let liquidator = *self.users[user].public_key();
+
+        let result = self.submit(user, |nonce| {
+            ProtocolOperation::LiquidateCDP(LiquidateCDPOp { cdp_id, liquidator, nonce, signature: blank() })
+        });
+        match result {
+            Some(result) => {
+                self.model.apply(&result, 0, absorbed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn cdp(&self, index: usize) -> Option<(CDPId, usize)> {
+        if self.cdps.is_empty() {
+            None
+        } else {
+            Some(self.cdps[index % self.cdps.len()])
+        }
+    }
+
+    fn is_liquidatable(&self, id: &CDPId) -> bool {
+        self.machine
+            .get_cdp(id)
+            .is_some_and(|cdp| cdp.is_liquidatable(self.machine.price(), self.machine.config().effective_mcr()))
+    }
+
+    /// A CDP just made riskier by its owner must still be above the MCR
+    fn assert_healthy(&self, id: &CDPId) {
+        let cdp = self.machine.get_cdp(id).expect("CDP exists");
+        let ratio = cdp.calculate_ratio(self.machine.price());
+        let mcr = self.machine.config().effective_mcr();
+        assert!(ratio >= mcr, "CDP {} left at {}% below MCR {}%", id.to_hex(), ratio, mcr);
+    }
+
+    fn check_invariants(&self) {
+        let cdps: Vec<_> = self.cdps.iter().filter_map(|(id, _)| self.machine.get_cdp(id)).collect();
+        let pool = self.machine.stability_pool();
+
+        // Debt
+        let debt: u64 = cdps.iter().map(|cdp| cdp.debt_cents).sum();
+        let supply = self.machine.total_supply().cents();
+        let savings = self.machine.savings();
+        let savings_held = savings.total_locked().cents() + savings.reserve().cents();
+        assert_eq!(
+            debt + self.model.written_off,
+            supply + pool.total_deposits().cents() + savings_held,
+            "debt does not match supply (debt {}, supply {}, pool {}, savings {}, {:?})",
+            debt,
+            supply,
+            pool.total_deposits().cents(),
+            savings_held,
+            self.model,
+        );
+
+        // Fees
+        assert_eq!(
+            self.machine.treasury_balance().cents() + savings.reserve().cents() + self.model.keeper_rewards,
+            self.model.mint_fees + self.model.redemption_fees,
+            "fees charged do not match treasury and savings reserve",
+        );
+
+        // Collateral
+        let collateral: u64 = cdps.iter().map(|cdp| cdp.collateral_sats).sum();
+        let vault = self.machine.total_collateral().sats();
+        assert_eq!(vault, collateral, "vault does not match CDP collateral");
+        assert_eq!(vault, self.model.collateral, "vault does not match deposits less withdrawals");
+        assert!(
+            pool.total_btc_gains().sats() <= self.model.pool_gains_in,
+            "stability pool holds more gains than it received"
+        );
+    }
+}
+
+fn blank() -> Signature {
+    Signature::new([0u8; SIGNATURE_LENGTH])
+}
+
+fn sign<O: Operation + Clone + Serialize>(mut op: O, keypair: &KeyPair, domain: &SigningDomain) -> O {
+    op.sign(keypair, domain).expect("signing");
+    op
+}
+
+fn sign_operation(op: ProtocolOperation, keypair: &KeyPair, domain: &SigningDomain) -> ProtocolOperation {
+    match op {
+        ProtocolOperation::OpenCDP(op) => ProtocolOperation::OpenCDP(sign(op, keypair, domain)),
+        ProtocolOperation::DepositCollateral(op) => ProtocolOperation::DepositCollateral(sign(op, keypair, domain)),
+        ProtocolOperation::WithdrawCollateral(op) => ProtocolOperation::WithdrawCollateral(sign(op, keypair, domain)),
+        ProtocolOperation::MintDebt(op) => ProtocolOperation::MintDebt(sign(op, keypair, domain)),
+        ProtocolOperation::RepayDebt(op) => ProtocolOperation::RepayDebt(sign(op, keypair, domain)),
+        ProtocolOperation::CloseCDP(op) => ProtocolOperation::CloseCDP(sign(op, keypair, domain)),
+        ProtocolOperation::LiquidateCDP(op) => ProtocolOperation::LiquidateCDP(sign(op, keypair, domain)),
+        ProtocolOperation::Transfer(op) => ProtocolOperation::Transfer(sign(op, keypair, domain)),
+        ProtocolOperation::StabilityDeposit(op) => ProtocolOperation::StabilityDeposit(sign(op, keypair, domain)),
+        ProtocolOperation::StabilityWithdraw(op) => ProtocolOperation::StabilityWithdraw(sign(op, keypair, domain)),
+        ProtocolOperation::ClaimGains(op) => ProtocolOperation::ClaimGains(sign(op, keypair, domain)),
+        ProtocolOperation::Redeem(op) => ProtocolOperation::Redeem(sign(op, keypair, domain)),
+        other => panic!("harness does not submit {}", other.operation_type()),
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// PROPERTIES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn prop_invariants_hold(actions in prop::collection::vec(action(), 1..80)) {
+        let mut harness = Harness::new();
+        for action in &actions {
+            harness.step(action);
+        }
+        harness.step(&Action::NextBlock);
+    }
+}
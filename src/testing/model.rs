@@ -0,0 +1,84 @@
+//! Reference model for invariant testing.
+//!
+//! The model does not re-implement the protocol. It keeps the few running
+//! totals that cannot be read back from the state machine (fees charged,
+//! keeper stipends paid, debt written off, collateral that should still be
+//! locked) and the nonce each signer last used, so the harness can check the
+//! state machine's books against an independent ledger.
+
+use std::collections::HashMap;
+
+use crate::protocol::state_machine::OperationResult;
+use crate::utils::crypto::PublicKey;
+
+/// Independent ledger of protocol-wide flows
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceModel {
+    /// Collateral that should be locked in the vault
+    pub collateral: u64,
+    /// Borrowing fees charged
+    pub mint_fees: u64,
+    /// Redemption fees charged
+    pub redemption_fees: u64,
+    /// Keeper stipends paid out of the treasury
+    pub keeper_rewards: u64,
+    /// Debt cleared by direct liquidations without burning tokens
+    pub written_off: u64,
+    /// Collateral handed to the stability pool
+    pub pool_gains_in: u64,
+    /// Last nonce accepted per signer
+    nonces: HashMap<PublicKey, u64>,
+}
+
+impl ReferenceModel {
+    /// Create an empty model
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Next nonce `signer` may use
+    pub fn next_nonce(&self, signer: &PublicKey) -> u64 {
+        self.last_nonce(signer) + 1
+    }
+
+    /// Last nonce accepted for `signer`
+    pub fn last_nonce(&self, signer: &PublicKey) -> u64 {
+        self.nonces.get(signer).copied().unwrap_or(0)
+    }
+
+    /// Record an accepted nonce; nonces must strictly increase per signer
+    pub fn accept_nonce(&mut self, signer: &PublicKey, nonce: u64) {
+        let last = self.last_nonce(signer);
+        assert!(nonce > last, "nonce {} accepted after {}", nonce, last);
+        self.nonces.insert(*signer, nonce);
+    }
+
+    /// Apply the effect of a successful operation.
+    ///
+    /// `absorbed` tells whether a liquidation was absorbed by the stability
+    /// pool; the result alone does not say.
+    pub fn apply(&mut self, result: &OperationResult, collateral_in: u64, absorbed: bool) {
+        match result {
+            OperationResult::OpenCDP(_) | OperationResult::Deposit(_) => {
+                self.collateral += collateral_in;
+            }
+            OperationResult::Withdraw(r) => self.collateral -= r.withdrawn.sats(),
+            OperationResult::Close(r) => self.collateral -= r.collateral_returned.sats(),
+            OperationResult::Mint(r) => self.mint_fees += r.fee.cents(),
+            OperationResult::Redeem(r) => {
+                self.collateral -= r.collateral_received.sats();
+                self.redemption_fees += r.fee.cents();
+            }
+            OperationResult::Liquidate(r) => {
+                self.collateral -= r.collateral_seized.sats();
+                self.keeper_rewards += r.keeper_reward.cents();
+                if absorbed {
+                    self.pool_gains_in += r.collateral_seized.sats();
+                } else {
+                    self.written_off += r.debt_covered.cents();
+                }
+            }
+            _ => {}
+        }
+    }
+}
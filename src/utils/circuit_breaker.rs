@@ -0,0 +1,156 @@
+//! Circuit breaker for unreliable external services.
+//!
+//! After a run of consecutive failures the breaker opens and callers skip the
+//! service until a cooldown elapses; the next call is then let through as a
+//! probe, closing the breaker on success or reopening it on failure.
+
+use serde::{Deserialize, Serialize};
+
+/// Circuit breaker settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures before the breaker opens
+    pub failure_threshold: u32,
+    /// Time the breaker stays open before probing (milliseconds)
+    pub cooldown_ms: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown_ms: 60_000,
+        }
+    }
+}
+
+/// Breaker state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CircuitState {
+    /// Calls pass through
+    Closed,
+    /// Calls are rejected until the given time (milliseconds)
+    Open {
+        /// When the cooldown ends
+        until_ms: u64,
+    },
+    /// Cooldown over; the next call is a probe
+    HalfOpen,
+}
+
+/// Tracks failures of a single service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitBreaker {
+    /// Settings
+    config: CircuitBreakerConfig,
+    /// Current state
+    state: CircuitState,
+    /// Failures since the last success
+    consecutive_failures: u32,
+    /// Times the breaker has opened
+    trips: u64,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(CircuitBreakerConfig::default())
+    }
+}
+
+impl CircuitBreaker {
+    /// Create a closed breaker
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            trips: 0,
+        }
+    }
+
+    /// Whether a call may be made at `now_ms`
+    pub fn allow(&mut self, now_ms: u64) -> bool {
+        match self.state {
+            CircuitState::Closed | CircuitState::HalfOpen => true,
+            CircuitState::Open { until_ms } if now_ms >= until_ms => {
+                self.state = CircuitState::HalfOpen;
+                true
+            }
+            CircuitState::Open { .. } => false,
+        }
+    }
+
+    /// Record a successful call
+    pub fn record_success(&mut self) {
+        self.state = CircuitState::Closed;
+        self.consecutive_failures = 0;
+    }
+
+    /// Record a failed call at `now_ms`
+    pub fn record_failure(&mut self, now_ms: u64) {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+
+        let trip = matches!(self.state, CircuitState::HalfOpen)
+            || self.consecutive_failures >= self.config.failure_threshold;
+        if trip {
+            self.state = CircuitState::Open {
+                until_ms: now_ms.saturating_add(self.config.cooldown_ms),
+            };
+            self.trips += 1;
+        }
+    }
+
+    /// Current state
+    pub fn state(&self) -> CircuitState {
+        self.state
+    }
+
+    /// Whether calls are currently being rejected
+    pub fn is_open(&self) -> bool {
+        matches!(self.state, CircuitState::Open { .. })
+    }
+
+    /// Failures since the last success
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    /// Times the breaker has opened
+    pub fn trips(&self) -> u64 {
+        self.trips
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_threshold_and_probes() {
+        let mut breaker = CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 2,
+            cooldown_ms: 1_000,
+        });
+
+        assert!(breaker.allow(0));
+        breaker.record_failure(0);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.record_failure(10);
+        assert_eq!(breaker.state(), CircuitState::Open { until_ms: 1_010 });
+
+        assert!(!breaker.allow(500));
+        assert!(breaker.allow(1_010));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        // A failed probe reopens immediately
+        breaker.record_failure(1_020);
+        assert!(breaker.is_open());
+        assert_eq!(breaker.trips(), 2);
+
+        // A successful probe closes it
+        assert!(breaker.allow(2_020));
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.consecutive_failures(), 0);
+    }
+}
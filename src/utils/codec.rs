@@ -0,0 +1,65 @@
+//! Bounded binary encoding.
+//!
+//! Spells and operations arrive from untrusted peers, so they are decoded
+//! with a hard cap on the bytes bincode may consume. A hostile length prefix
+//! then fails with an error before anything is allocated for it, and trailing
+//! garbage after a valid value is rejected.
+
+use bincode::Options;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+
+/// Bincode options matching `bincode::serialize`, with a size limit
+fn options(limit: usize) -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_limit(limit as u64)
+}
+
+/// Encode a value in the protocol's binary format
+pub fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    bincode::serialize(value).map_err(|e| Error::Serialization(e.to_string()))
+}
+
+/// Decode a value, reading at most `limit` bytes
+pub fn decode_bounded<T: DeserializeOwned>(data: &[u8], limit: usize) -> Result<T> {
+    if data.len() > limit {
+        return Err(Error::Deserialization(format!(
+            "input of {} bytes exceeds limit of {}",
+            data.len(),
+            limit
+        )));
+    }
+    options(limit)
+        .deserialize(data)
+        .map_err(|e| Error::Deserialization(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_matches_bincode() {
+        let value = (42u64, vec![1u8, 2, 3], "zkusd".to_string());
+        let bytes = encode(&value).unwrap();
+        assert_eq!(bytes, bincode::serialize(&value).unwrap());
+
+        let decoded: (u64, Vec<u8>, String) = decode_bounded(&bytes, 1024).unwrap();
+        assert_eq!(decoded, value);
+    }
+
+    #[test]
+    fn test_rejects_hostile_input() {
+        // Length prefix claiming an enormous vector
+        let mut bytes = u64::MAX.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&[0u8; 16]);
+        assert!(decode_bounded::<Vec<u8>>(&bytes, 1024).is_err());
+
+        // Over the limit, and trailing bytes after a valid value
+        assert!(decode_bounded::<u64>(&[0u8; 16], 8).is_err());
+        assert!(decode_bounded::<u64>(&[0u8; 9], 1024).is_err());
+    }
+}
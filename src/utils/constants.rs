@@ -15,6 +15,12 @@ pub const DUST_LIMIT_SATS: u64 = 546;
 /// Average Bitcoin block time in seconds
 pub const BLOCK_TIME_SECS: u64 = 600;
 
+/// Blocks between difficulty adjustments
+pub const DIFFICULTY_ADJUSTMENT_INTERVAL: u32 = 2016;
+
+/// Confirmations required before a collateral deposit is accepted
+pub const MIN_DEPOSIT_CONFIRMATIONS: u32 = 6;
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // ZKUSD CONSTANTS
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -63,6 +69,12 @@ pub const REDEMPTION_FEE_FLOOR_BPS: u64 = 50;
 /// Redemption fee ceiling - 5% (500 basis points)
 pub const REDEMPTION_FEE_CEILING_BPS: u64 = 500;
 
+/// Borrowing fee ceiling - 5% (500 basis points)
+pub const BORROWING_FEE_CEILING_BPS: u64 = 500;
+
+/// Divisor applied to the redeemed share of debt when bumping the base rate
+pub const REDEMPTION_BASE_RATE_BETA: u64 = 2;
+
 /// Liquidation bonus - 10% (1000 basis points)
 /// This is the discount liquidators receive when buying collateral
 pub const LIQUIDATION_BONUS_BPS: u64 = 1000;
@@ -70,6 +82,12 @@ pub const LIQUIDATION_BONUS_BPS: u64 = 1000;
 /// Basis points divisor (10000 = 100%)
 pub const BPS_DIVISOR: u64 = 10000;
 
+/// Keeper stipend per liquidation or accrual call in cents - $2
+pub const KEEPER_REWARD: u64 = 200;
+
+/// Maximum keeper stipends paid per block in cents - $50
+pub const MAX_KEEPER_REWARDS_PER_BLOCK: u64 = 5_000;
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // DEBT LIMITS
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -115,6 +133,17 @@ pub const MIN_SP_DEPOSIT: u64 = 10 * ZKUSD_BASE_UNIT;
 /// Scale factor for stability pool calculations
 pub const SP_SCALE_FACTOR: u128 = 1_000_000_000_000_000_000; // 10^18
 
+/// Factor the stability pool product is scaled up by when it falls below it
+pub const SP_SCALE_CHANGE_FACTOR: u128 = 1_000_000_000; // 10^9
+
+/// Precision of the stability pool's BTC gain per cent deposited. Kept below
+/// `SP_SCALE_FACTOR` so gain times product fits in a `u128` even at a BTC
+/// price of one cent.
+pub const SP_GAIN_PRECISION: u128 = 1_000_000_000; // 10^9
+
+/// Maximum share of depositor gains a frontend can take - 50%
+pub const MAX_FRONTEND_KICKBACK_BPS: u64 = 5_000;
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // TIME CONSTANTS
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -125,6 +154,29 @@ pub const REDEMPTION_FEE_DECAY_HALF_LIFE: u64 = 12 * 3600;
 /// Minimum time between price updates - 60 seconds
 pub const MIN_PRICE_UPDATE_INTERVAL: u64 = 60;
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// RATE LIMIT CONSTANTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Maximum operations per account per rate limit window
+pub const MAX_OPS_PER_WINDOW: u32 = 30;
+
+/// Length of the rate limit window in blocks (~1 hour)
+pub const RATE_LIMIT_WINDOW_BLOCKS: u64 = 6;
+
+/// Minimum blocks between redemptions by the same account
+pub const MIN_REDEEM_INTERVAL_BLOCKS: u64 = 1;
+
+/// Minimum blocks between liquidations by the same account. Zero: keepers
+/// must be free to clear a cascade of CDPs within one block
+pub const MIN_LIQUIDATION_INTERVAL_BLOCKS: u64 = 0;
+
+/// Maximum zkUSD redeemed per block in cents - $1,000,000
+pub const MAX_REDEMPTION_PER_BLOCK: u64 = 100_000_000;
+
+/// Maximum CDPs a single redemption may touch
+pub const MAX_CDPS_PER_REDEMPTION: u32 = 50;
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // CRYPTOGRAPHIC CONSTANTS
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -141,6 +193,41 @@ pub const HASH_LENGTH: usize = 32;
 /// Length of a CDP ID in bytes
 pub const CDP_ID_LENGTH: usize = 32;
 
+/// Human-readable part of bech32m account addresses
+pub const ADDRESS_HRP: &str = "zkusd";
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// ENCODING LIMITS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Maximum encoded size of a spell in bytes
+pub const MAX_SPELL_SIZE: usize = 64 * 1024;
+
+/// Maximum size of a spell's parameter payload in bytes
+pub const MAX_SPELL_DATA_SIZE: usize = 16 * 1024;
+
+/// Maximum number of spells in a bundle
+pub const MAX_SPELL_BUNDLE_LEN: usize = 16;
+
+/// Maximum encoded size of a spell bundle in bytes
+pub const MAX_SPELL_BUNDLE_SIZE: usize = 256 * 1024;
+
+/// Maximum encoded size of a protocol operation in bytes
+pub const MAX_OPERATION_SIZE: usize = 64 * 1024;
+
+/// Maximum encoded size of Charm token metadata in bytes
+pub const MAX_METADATA_SIZE: usize = 4 * 1024;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// MEMPOOL
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Maximum operations held in the mempool
+pub const MAX_MEMPOOL_SIZE: usize = 10_000;
+
+/// Verified signatures remembered between admission and execution
+pub const SIGNATURE_CACHE_SIZE: usize = 65_536;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -150,6 +237,7 @@ mod tests {
         // Verify fee constants make sense
         assert!(BORROWING_FEE_BPS < BPS_DIVISOR);
         assert!(REDEMPTION_FEE_FLOOR_BPS < REDEMPTION_FEE_CEILING_BPS);
+        assert!(BORROWING_FEE_BPS < BORROWING_FEE_CEILING_BPS);
         assert!(LIQUIDATION_BONUS_BPS < BPS_DIVISOR);
     }
 
@@ -4,7 +4,8 @@
 //! - Private keys (secp256k1)
 //! - Public keys (secp256k1 compressed)
 //! - Signatures (ECDSA/Schnorr)
-//! - Hashes (SHA256, Blake3)
+//! - Hashes (SHA256, Blake3) and the [`HashScheme`] of state trees
+//! - Human-readable account addresses (bech32m)
 //!
 //! All operations use the secp256k1 library for Bitcoin-compatible cryptography.
 
@@ -14,10 +15,14 @@ use secp256k1::{
 };
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
+use std::sync::{Arc, Mutex};
 
 use crate::error::{Error, Result};
-use crate::utils::constants::{CDP_ID_LENGTH, HASH_LENGTH, PUBKEY_LENGTH, SIGNATURE_LENGTH};
+use crate::utils::constants::{
+    ADDRESS_HRP, CDP_ID_LENGTH, HASH_LENGTH, PUBKEY_LENGTH, SIGNATURE_CACHE_SIZE, SIGNATURE_LENGTH,
+};
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // SECP256K1 CONTEXT
@@ -41,6 +46,7 @@ where
 
 /// A 32-byte cryptographic hash
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct Hash([u8; HASH_LENGTH]);
 
 impl Serialize for Hash {
@@ -289,6 +295,7 @@ impl Drop for PrivateKey {
 
 /// A compressed secp256k1 public key (33 bytes)
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct PublicKey([u8; PUBKEY_LENGTH]);
 
 impl Serialize for PublicKey {
@@ -403,6 +410,28 @@ impl PublicKey {
         verify_signature(self, message, signature)
     }
 
+    /// Encode as a bech32m address (`zkusd1...`)
+    pub fn to_address(&self) -> String {
+        bech32m::encode(ADDRESS_HRP, &self.0)
+    }
+
+    /// Decode a bech32m address
+    pub fn from_address(address: &str) -> Result<Self> {
+        let data = bech32m::decode(ADDRESS_HRP, address)?;
+        Self::from_bytes_validated(&data)
+    }
+
+    /// Parse either a bech32m address or a hex-encoded key
+    pub fn parse(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let prefix = format!("{}1", ADDRESS_HRP);
+        if s.get(..prefix.len()).is_some_and(|p| p.eq_ignore_ascii_case(&prefix)) {
+            Self::from_address(s)
+        } else {
+            Self::from_hex_validated(s)
+        }
+    }
+
     /// Convert to secp256k1 PublicKey
     fn to_secp256k1(&self) -> Option<Secp256k1PubKey> {
         Secp256k1PubKey::from_slice(&self.0).ok()
@@ -421,12 +450,136 @@ impl fmt::Display for PublicKey {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// ADDRESS ENCODING
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Minimal bech32m (BIP-350) codec for account addresses
+mod bech32m {
+    use crate::error::{Error, Result};
+
+    const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+    const BECH32M_CONST: u32 = 0x2bc8_30a3;
+    const GENERATORS: [u32; 5] = [0x3b6a_57b2, 0x2650_8e6d, 0x1ea1_19fa, 0x3d42_33dd, 0x2a14_62b3];
+    const CHECKSUM_LENGTH: usize = 6;
+    const MAX_LENGTH: usize = 90;
+
+    fn invalid(reason: impl Into<String>) -> Error {
+        Error::InvalidParameter { name: "address".into(), reason: reason.into() }
+    }
+
+    fn polymod(values: impl IntoIterator<Item = u8>) -> u32 {
+        let mut chk: u32 = 1;
+        for v in values {
+            let top = chk >> 25;
+            chk = ((chk & 0x01ff_ffff) << 5) ^ u32::from(v);
+            for (i, g) in GENERATORS.iter().enumerate() {
+                if (top >> i) & 1 == 1 {
+                    chk ^= g;
+                }
+            }
+        }
+        chk
+    }
+
+    fn hrp_expand(hrp: &str) -> impl Iterator<Item = u8> + '_ {
+        hrp.bytes().map(|b| b >> 5).chain(std::iter::once(0)).chain(hrp.bytes().map(|b| b & 0x1f))
+    }
+
+    /// Regroup bits, e.g. 8-bit bytes into 5-bit words
+    fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+        let mut acc: u32 = 0;
+        let mut bits: u32 = 0;
+        let max = (1u32 << to) - 1;
+        let mut out = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+
+        for &value in data {
+            if u32::from(value) >> from != 0 {
+                return None;
+            }
+            acc = (acc << from) | u32::from(value);
+            bits += from;
+            while bits >= to {
+                bits -= to;
+                out.push(((acc >> bits) & max) as u8);
+            }
+        }
+
+        if pad {
+            if bits > 0 {
+                out.push(((acc << (to - bits)) & max) as u8);
+            }
+        } else if bits >= from || (acc << (to - bits)) & max != 0 {
+            return None;
+        }
+        Some(out)
+    }
+
+    /// Encode `data` under `hrp`
+    pub fn encode(hrp: &str, data: &[u8]) -> String {
+        let words = convert_bits(data, 8, 5, true).expect("8-bit input is always valid");
+        let pm = polymod(
+            hrp_expand(hrp).chain(words.iter().copied()).chain([0u8; CHECKSUM_LENGTH]),
+        ) ^ BECH32M_CONST;
+
+        let mut out = String::with_capacity(hrp.len() + 1 + words.len() + CHECKSUM_LENGTH);
+        out.push_str(hrp);
+        out.push('1');
+        for w in words {
+            out.push(CHARSET[w as usize] as char);
+        }
+        for i in 0..CHECKSUM_LENGTH {
+            out.push(CHARSET[((pm >> (5 * (5 - i))) & 0x1f) as usize] as char);
+        }
+        out
+    }
+
+    /// Decode a string that must carry `hrp`
+    pub fn decode(hrp: &str, s: &str) -> Result<Vec<u8>> {
+        if s.len() > MAX_LENGTH {
+            return Err(invalid("address too long"));
+        }
+        if s.bytes().any(|b| b.is_ascii_lowercase()) && s.bytes().any(|b| b.is_ascii_uppercase()) {
+            return Err(invalid("mixed-case address"));
+        }
+        let s = s.to_ascii_lowercase();
+
+        let sep = s.rfind('1').ok_or_else(|| invalid("missing separator"))?;
+        let (found_hrp, rest) = (&s[..sep], &s[sep + 1..]);
+        if found_hrp != hrp {
+            return Err(invalid(format!("expected prefix '{}', got '{}'", hrp, found_hrp)));
+        }
+        if rest.len() < CHECKSUM_LENGTH {
+            return Err(invalid("address too short"));
+        }
+
+        let words = rest
+            .bytes()
+            .map(|c| {
+                CHARSET
+                    .iter()
+                    .position(|&x| x == c)
+                    .map(|p| p as u8)
+                    .ok_or_else(|| invalid(format!("invalid character '{}'", c as char)))
+            })
+            .collect::<Result<Vec<u8>>>()?;
+
+        if polymod(hrp_expand(hrp).chain(words.iter().copied())) != BECH32M_CONST {
+            return Err(invalid("checksum mismatch"));
+        }
+
+        convert_bits(&words[..words.len() - CHECKSUM_LENGTH], 5, 8, false)
+            .ok_or_else(|| invalid("invalid padding"))
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // SIGNATURE
 // ═══════════════════════════════════════════════════════════════════════════════
 
 /// A compact ECDSA signature (64 bytes)
 #[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct Signature([u8; SIGNATURE_LENGTH]);
 
 impl Serialize for Signature {
@@ -536,6 +689,7 @@ impl fmt::Debug for Signature {
 
 /// Unique identifier for a CDP (Collateralized Debt Position)
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct CDPId([u8; CDP_ID_LENGTH]);
 
 impl Serialize for CDPId {
@@ -702,6 +856,172 @@ pub fn tagged_hash(tag: &str, data: &[u8]) -> Hash {
     Hash::new(bytes)
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// BATCH VERIFICATION
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// A signature to check: signer, signed hash and signature
+pub type SignatureCheck = (PublicKey, Hash, Signature);
+
+/// Verify many signatures together, returning each one's validity
+///
+/// ECDSA has no algebraic batch verification, so each signature is still
+/// checked individually, but under a single context borrow and with
+/// unparseable keys and signatures rejected before any curve arithmetic.
+pub fn verify_batch(checks: &[SignatureCheck]) -> Vec<bool> {
+    with_secp(|secp| {
+        checks
+            .iter()
+            .map(|(pubkey, message, signature)| {
+                match (pubkey.to_secp256k1(), signature.to_secp256k1()) {
+                    (Some(pk), Some(sig)) => secp.verify_ecdsa(&message.to_message(), &sig, &pk).is_ok(),
+                    _ => false,
+                }
+            })
+            .collect()
+    })
+}
+
+/// Bounded LRU cache of verified signatures
+///
+/// Shared between the mempool, which verifies operations on admission, and
+/// the state machine, so signatures checked once are not re-verified at
+/// execution. Only valid signatures are cached, keyed by signer, hash and
+/// signature together, so a hit is as good as a fresh verification.
+#[derive(Debug, Clone)]
+pub struct SignatureCache {
+    inner: Arc<Mutex<LruSet>>,
+}
+
+#[derive(Debug)]
+struct LruSet {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<Hash, u64>,
+    order: BTreeMap<u64, Hash>,
+}
+
+impl LruSet {
+    fn touch(&mut self, key: Hash) -> bool {
+        let Some(last) = self.entries.get_mut(&key) else {
+            return false;
+        };
+        self.order.remove(last);
+        self.tick += 1;
+        *last = self.tick;
+        self.order.insert(self.tick, key);
+        true
+    }
+
+    fn insert(&mut self, key: Hash) {
+        if self.capacity == 0 || self.touch(key) {
+            return;
+        }
+        if self.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = self.order.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.entries.insert(key, self.tick);
+        self.order.insert(self.tick, key);
+    }
+}
+
+impl Default for SignatureCache {
+    fn default() -> Self {
+        Self::new(SIGNATURE_CACHE_SIZE)
+    }
+}
+
+impl SignatureCache {
+    /// Create a cache remembering up to `capacity` signatures
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(LruSet {
+                capacity,
+                tick: 0,
+                entries: HashMap::new(),
+                order: BTreeMap::new(),
+            })),
+        }
+    }
+
+    /// Verify a signature, consulting the cache first
+    pub fn verify(&self, pubkey: &PublicKey, message: &Hash, signature: &Signature) -> bool {
+        let key = Self::key(pubkey, message, signature);
+        if self.with(|lru| lru.touch(key)) {
+            return true;
+        }
+        let valid = verify_signature(pubkey, message, signature);
+        if valid {
+            self.with(|lru| lru.insert(key));
+        }
+        valid
+    }
+
+    /// Verify many signatures, batching the ones not already cached
+    pub fn verify_batch(&self, checks: &[SignatureCheck]) -> Vec<bool> {
+        let keys: Vec<Hash> = checks.iter().map(|(pk, msg, sig)| Self::key(pk, msg, sig)).collect();
+        let mut results: Vec<bool> = self.with(|lru| keys.iter().map(|key| lru.touch(*key)).collect());
+
+        let misses: Vec<usize> = (0..checks.len()).filter(|&i| !results[i]).collect();
+        let pending: Vec<SignatureCheck> = misses.iter().map(|&i| checks[i]).collect();
+        let verified = verify_batch(&pending);
+
+        self.with(|lru| {
+            for (&i, valid) in misses.iter().zip(verified) {
+                if valid {
+                    lru.insert(keys[i]);
+                }
+                results[i] = valid;
+            }
+        });
+        results
+    }
+
+    /// Whether a valid signature is cached
+    pub fn contains(&self, pubkey: &PublicKey, message: &Hash, signature: &Signature) -> bool {
+        let key = Self::key(pubkey, message, signature);
+        self.with(|lru| lru.entries.contains_key(&key))
+    }
+
+    /// Number of cached signatures
+    pub fn len(&self) -> usize {
+        self.with(|lru| lru.entries.len())
+    }
+
+    /// Whether the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forget all cached signatures
+    pub fn clear(&self) {
+        self.with(|lru| {
+            lru.entries.clear();
+            lru.order.clear();
+        });
+    }
+
+    fn key(pubkey: &PublicKey, message: &Hash, signature: &Signature) -> Hash {
+        let mut data = Vec::with_capacity(PUBKEY_LENGTH + HASH_LENGTH + SIGNATURE_LENGTH);
+        data.extend_from_slice(pubkey.as_bytes());
+        data.extend_from_slice(message.as_bytes());
+        data.extend_from_slice(signature.as_bytes());
+        Hash::sha256(&data)
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&mut LruSet) -> R) -> R {
+        // A panic elsewhere must not disable the cache
+        let mut lru = match self.inner.lock() {
+            Ok(lru) => lru,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        f(&mut lru)
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // KEY PAIR
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -772,59 +1092,163 @@ impl fmt::Debug for KeyPair {
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
-// MERKLE TREE
+// HASH SCHEME
 // ═══════════════════════════════════════════════════════════════════════════════
 
-/// Compute a Merkle root from a list of hashes
-pub fn merkle_root(hashes: &[Hash]) -> Hash {
-    if hashes.is_empty() {
-        return Hash::zero();
+/// Hash function behind a digest or Merkle tree.
+///
+/// Bitcoin-facing data (signing hashes, anchors, proofs of reserves) always
+/// uses SHA256. Internal state trees may use BLAKE3, which is several times
+/// faster. Both keep domains apart: SHA256 prefixes `zkUSD:<domain>:`, BLAKE3
+/// derives a key from the context `zkUSD <domain>`. BLAKE3 trees also hash
+/// inner nodes under their own context, so a leaf can never pass for a node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashScheme {
+    /// SHA256 leaves and double SHA256 nodes, as Bitcoin builds trees
+    #[default]
+    Sha256,
+    /// BLAKE3 in key derivation mode
+    Blake3,
+}
+
+/// BLAKE3 context of inner Merkle nodes
+const BLAKE3_NODE_CONTEXT: &str = "zkUSD merkle-node";
+
+impl HashScheme {
+    /// Plain hash of `data`
+    pub fn hash(self, data: &[u8]) -> Hash {
+        match self {
+            Self::Sha256 => Hash::sha256(data),
+            Self::Blake3 => Hash::blake3(data),
+        }
     }
 
-    if hashes.len() == 1 {
-        return hashes[0];
+    /// Hash of `data` under `domain`
+    pub fn domain_hash(self, domain: &str, data: &[u8]) -> Hash {
+        match self {
+            Self::Sha256 => create_message_hash(domain, data),
+            Self::Blake3 => {
+                let mut hasher = blake3::Hasher::new_derive_key(&format!("zkUSD {}", domain));
+                hasher.update(data);
+                Hash(*hasher.finalize().as_bytes())
+            }
+        }
     }
 
-    let mut current_level: Vec<Hash> = hashes.to_vec();
+    /// Root of the Merkle tree over `hashes`. The last node of an odd level
+    /// pairs with itself; no hashes give the zero hash.
+    pub fn merkle_root(self, hashes: &[Hash]) -> Hash {
+        if hashes.is_empty() {
+            return Hash::zero();
+        }
+
+        let mut current_level: Vec<Hash> = hashes.to_vec();
 
-    while current_level.len() > 1 {
-        let mut next_level = Vec::with_capacity((current_level.len() + 1) / 2);
+        while current_level.len() > 1 {
+            current_level = self.merkle_level(&current_level);
+        }
 
-        for chunk in current_level.chunks(2) {
-            let left = chunk[0];
-            let right = if chunk.len() > 1 { chunk[1] } else { chunk[0] };
+        current_level[0]
+    }
 
-            let mut combined = Vec::with_capacity(64);
-            combined.extend_from_slice(left.as_bytes());
-            combined.extend_from_slice(right.as_bytes());
-            next_level.push(Hash::double_sha256(&combined));
+    /// Sibling hashes from `hashes[index]` up to the root of
+    /// [`merkle_root`](Self::merkle_root)
+    pub fn merkle_proof(self, hashes: &[Hash], index: usize) -> Option<Vec<Hash>> {
+        if index >= hashes.len() {
+            return None;
         }
 
-        current_level = next_level;
+        let mut level = hashes.to_vec();
+        let mut idx = index;
+        let mut proof = Vec::new();
+        while level.len() > 1 {
+            let sibling = if idx % 2 == 0 { level.get(idx + 1).unwrap_or(&level[idx]) } else { &level[idx - 1] };
+            proof.push(*sibling);
+            level = self.merkle_level(&level);
+            idx /= 2;
+        }
+
+        Some(proof)
+    }
+
+    /// Whether `proof` leads from `leaf` at `index` to `root`
+    pub fn verify_merkle_proof(self, leaf: &Hash, proof: &[Hash], root: &Hash, index: usize) -> bool {
+        let mut current = *leaf;
+        let mut idx = index;
+
+        for sibling in proof {
+            current = if idx % 2 == 0 {
+                self.merkle_parent(&current, sibling)
+            } else {
+                self.merkle_parent(sibling, &current)
+            };
+            idx /= 2;
+        }
+
+        current == *root
+    }
+
+    fn merkle_level(self, level: &[Hash]) -> Vec<Hash> {
+        level
+            .chunks(2)
+            .map(|pair| self.merkle_parent(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+            .collect()
     }
 
-    current_level[0]
+    fn merkle_parent(self, left: &Hash, right: &Hash) -> Hash {
+        let mut combined = Vec::with_capacity(2 * HASH_LENGTH);
+        combined.extend_from_slice(left.as_bytes());
+        combined.extend_from_slice(right.as_bytes());
+        match self {
+            Self::Sha256 => Hash::double_sha256(&combined),
+            Self::Blake3 => Self::Blake3.domain_hash(BLAKE3_NODE_CONTEXT, &combined),
+        }
+    }
 }
 
-/// Verify a Merkle proof
-pub fn verify_merkle_proof(leaf: &Hash, proof: &[Hash], root: &Hash, index: usize) -> bool {
-    let mut current = *leaf;
-    let mut idx = index;
-
-    for sibling in proof {
-        let mut combined = Vec::with_capacity(64);
-        if idx % 2 == 0 {
-            combined.extend_from_slice(current.as_bytes());
-            combined.extend_from_slice(sibling.as_bytes());
-        } else {
-            combined.extend_from_slice(sibling.as_bytes());
-            combined.extend_from_slice(current.as_bytes());
+impl fmt::Display for HashScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sha256 => f.write_str("sha256"),
+            Self::Blake3 => f.write_str("blake3"),
         }
-        current = Hash::double_sha256(&combined);
-        idx /= 2;
     }
+}
 
-    current == *root
+impl std::str::FromStr for HashScheme {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "sha256" => Ok(Self::Sha256),
+            "blake3" => Ok(Self::Blake3),
+            other => Err(Error::InvalidParameter {
+                name: "hash_scheme".into(),
+                reason: format!("Unknown hash scheme '{}', expected sha256 or blake3", other),
+            }),
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// MERKLE TREE
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Compute a Merkle root from a list of hashes
+pub fn merkle_root(hashes: &[Hash]) -> Hash {
+    HashScheme::Sha256.merkle_root(hashes)
+}
+
+/// Verify a Merkle proof
+pub fn verify_merkle_proof(leaf: &Hash, proof: &[Hash], root: &Hash, index: usize) -> bool {
+    HashScheme::Sha256.verify_merkle_proof(leaf, proof, root, index)
+}
+
+/// Sibling hashes from `hashes[index]` up to the root of [`merkle_root`],
+/// as checked by [`verify_merkle_proof`]
+pub fn merkle_proof(hashes: &[Hash], index: usize) -> Option<Vec<Hash>> {
+    HashScheme::Sha256.merkle_proof(hashes, index)
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -910,6 +1334,32 @@ mod tests {
         assert!(!invalid.is_valid());
     }
 
+    #[test]
+    fn test_address_roundtrip() {
+        // BIP-350 test vectors
+        assert!(bech32m::decode("a", "A1LQFN3A").unwrap().is_empty());
+        assert_eq!(
+            bech32m::decode("abcdef", "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx").unwrap().len(),
+            20
+        );
+
+        let pubkey = *KeyPair::generate().public_key();
+        let address = pubkey.to_address();
+        assert!(address.starts_with("zkusd1"));
+        assert_eq!(PublicKey::from_address(&address).unwrap(), pubkey);
+        assert_eq!(PublicKey::parse(&address.to_uppercase()).unwrap(), pubkey);
+        assert_eq!(PublicKey::parse(&pubkey.to_hex()).unwrap(), pubkey);
+
+        // A single typo is caught by the checksum
+        let mut typo = address.into_bytes();
+        let last = typo.len() - 1;
+        typo[last] = if typo[last] == b'q' { b'p' } else { b'q' };
+        assert!(PublicKey::from_address(&String::from_utf8(typo).unwrap()).is_err());
+
+        // Other prefixes are rejected
+        assert!(PublicKey::from_address(&bech32m::encode("bc", pubkey.as_bytes())).is_err());
+    }
+
     #[test]
     fn test_cdp_id_generation() {
         let keypair = KeyPair::generate();
@@ -991,6 +1441,37 @@ mod tests {
         assert!(verify_merkle_proof(&leaves[0], &proof, &root, 0));
 
         assert!(!verify_merkle_proof(&leaves[0], &proof, &root, 1));
+        assert_eq!(merkle_proof(&leaves, 0), Some(proof));
+        assert_eq!(merkle_proof(&leaves, 4), None);
+
+        // Odd levels pair their last node with itself, under either scheme
+        for scheme in [HashScheme::Sha256, HashScheme::Blake3] {
+            for count in 1..=7u8 {
+                let leaves: Vec<Hash> = (0..count).map(|i| scheme.hash(&[i])).collect();
+                let root = scheme.merkle_root(&leaves);
+                for (index, leaf) in leaves.iter().enumerate() {
+                    let proof = scheme.merkle_proof(&leaves, index).unwrap();
+                    assert!(scheme.verify_merkle_proof(leaf, &proof, &root, index));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_hash_scheme_domains() {
+        let sha = HashScheme::Sha256;
+        let blake = HashScheme::Blake3;
+        assert_eq!(sha.domain_hash("cdp", b"data"), create_message_hash("cdp", b"data"));
+        assert_ne!(blake.domain_hash("cdp", b"data"), blake.domain_hash("protocol-state", b"data"));
+        assert_ne!(blake.domain_hash("cdp", b"data"), blake.hash(b"data"));
+
+        let leaves: Vec<Hash> = (0..4u8).map(|i| blake.hash(&[i])).collect();
+        assert_eq!(sha.merkle_root(&leaves), merkle_root(&leaves));
+        assert_ne!(blake.merkle_root(&leaves), merkle_root(&leaves));
+
+        assert_eq!("BLAKE3".parse::<HashScheme>().unwrap(), blake);
+        assert_eq!(sha.to_string().parse::<HashScheme>().unwrap(), sha);
+        assert!("md5".parse::<HashScheme>().is_err());
     }
 
     #[test]
@@ -1045,4 +1526,46 @@ mod tests {
         let cdp_recovered: CDPId = serde_json::from_str(&cdp_json).unwrap();
         assert_eq!(cdp_id, cdp_recovered);
     }
+
+    #[test]
+    fn test_verify_batch() {
+        let keypair = KeyPair::generate();
+        let checks: Vec<SignatureCheck> = (0..4u8)
+            .map(|i| {
+                let message = Hash::sha256(&[i]);
+                (*keypair.public_key(), message, keypair.sign(&message))
+            })
+            .collect();
+
+        let mut forged = checks.clone();
+        forged[2].1 = Hash::sha256(b"other");
+        assert_eq!(verify_batch(&checks), vec![true; 4]);
+        assert_eq!(verify_batch(&forged), vec![true, true, false, true]);
+    }
+
+    #[test]
+    fn test_signature_cache_lru() {
+        let keypair = KeyPair::generate();
+        let cache = SignatureCache::new(2);
+        let signed: Vec<(Hash, Signature)> = (0..3u8)
+            .map(|i| {
+                let message = Hash::sha256(&[i]);
+                (message, keypair.sign(&message))
+            })
+            .collect();
+        let pk = keypair.public_key();
+
+        assert!(!cache.verify(pk, &signed[0].0, &signed[1].1));
+        assert!(cache.is_empty());
+
+        assert!(cache.verify(pk, &signed[0].0, &signed[0].1));
+        assert!(cache.verify(pk, &signed[1].0, &signed[1].1));
+        // Touch the first so the second is evicted
+        assert!(cache.verify(pk, &signed[0].0, &signed[0].1));
+        assert!(cache.verify(pk, &signed[2].0, &signed[2].1));
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains(pk, &signed[0].0, &signed[0].1));
+        assert!(!cache.contains(pk, &signed[1].0, &signed[1].1));
+    }
 }
@@ -0,0 +1,302 @@
+//! Deterministic fixed-point decimals with explicit rounding.
+//!
+//! Balances are integers (cents, sats), but the rates and ratios applied to
+//! them are not, and every conversion back to an integer has to round one
+//! way or the other. Rounding the same way everywhere lets dust leak: a fee
+//! rounded down undercharges, a payout rounded up pays out value the protocol
+//! doesn't hold. [`Decimal128`] keeps 18 decimal places and makes every
+//! rounding an explicit [`Rounding`]:
+//!
+//! - [`Rounding::Floor`] for anything credited to a user: payouts, shares,
+//!   debt capacity
+//! - [`Rounding::Ceil`] for anything charged to a user: fees, required
+//!   collateral
+//!
+//! All arithmetic is checked and integer-only, so every node computes the
+//! same result.
+
+use std::fmt;
+
+use crate::error::{Error, Result};
+use crate::utils::constants::BPS_DIVISOR;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// ROUNDING
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Direction to round a result that isn't exact
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Toward zero, for amounts credited to users
+    Floor,
+    /// Away from zero, for amounts charged to users
+    Ceil,
+    /// To the nearest value, halves away from zero
+    HalfUp,
+}
+
+/// `a * b / c`, rounded as `rounding`, with the product computed exactly
+pub fn mul_div(a: u128, b: u128, c: u128, rounding: Rounding) -> Result<u128> {
+    if c == 0 {
+        return Err(Error::InvalidParameter {
+            name: "divisor".into(),
+            reason: "division by zero".into(),
+        });
+    }
+    let product = a.checked_mul(b).ok_or_else(|| Error::Overflow {
+        operation: format!("{} * {}", a, b),
+    })?;
+
+    let (quotient, remainder) = (product / c, product % c);
+    let round_up = match rounding {
+        Rounding::Floor => false,
+        Rounding::Ceil => remainder != 0,
+        Rounding::HalfUp => remainder != 0 && remainder >= c - remainder,
+    };
+    // The quotient is below u128::MAX whenever there is a remainder
+    Ok(quotient + round_up as u128)
+}
+
+/// [`mul_div`] of `u64` operands, failing if the result exceeds `u64`
+pub fn mul_div_u64(a: u64, b: u64, c: u64, rounding: Rounding) -> Result<u64> {
+    let result = mul_div(a as u128, b as u128, c as u128, rounding)?;
+    u64::try_from(result).map_err(|_| Error::Overflow {
+        operation: format!("({} * {}) / {}", a, b, c),
+    })
+}
+
+/// `bps` basis points of `amount`, rounded as `rounding`
+pub fn apply_bps(amount: u64, bps: u64, rounding: Rounding) -> Result<u64> {
+    Decimal128::from_bps(bps).mul_u64(amount, rounding)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// DECIMAL
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Non-negative decimal with 18 fractional digits, stored as a `u128`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Decimal128(u128);
+
+impl Decimal128 {
+    /// Fractional digits
+    pub const DECIMALS: u32 = 18;
+
+    /// Raw value of 1.0
+    pub const SCALE: u128 = 10u128.pow(Self::DECIMALS);
+
+    /// Zero
+    pub const ZERO: Self = Self(0);
+
+    /// One
+    pub const ONE: Self = Self(Self::SCALE);
+
+    /// Largest representable value
+    pub const MAX: Self = Self(u128::MAX);
+
+    /// Create from a raw value, `raw / SCALE`
+    pub const fn from_raw(raw: u128) -> Self {
+        Self(raw)
+    }
+
+    /// Raw value, `self * SCALE`
+    pub const fn raw(&self) -> u128 {
+        self.0
+    }
+
+    /// Create from an integer
+    pub const fn from_integer(value: u64) -> Self {
+        Self(value as u128 * Self::SCALE)
+    }
+
+    /// Create from basis points (100 bps = 1%), exactly
+    pub const fn from_bps(bps: u64) -> Self {
+        Self(bps as u128 * (Self::SCALE / BPS_DIVISOR as u128))
+    }
+
+    /// Create from a percentage (100 = 1.0), exactly
+    pub const fn from_percent(pct: u64) -> Self {
+        Self(pct as u128 * (Self::SCALE / 100))
+    }
+
+    /// `numerator / denominator`, rounded as `rounding` in the last digit
+    pub fn from_ratio(numerator: u64, denominator: u64, rounding: Rounding) -> Result<Self> {
+        mul_div(numerator as u128, Self::SCALE, denominator as u128, rounding).map(Self)
+    }
+
+    /// Whether the value is zero
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Checked addition
+    pub fn checked_add(self, other: Self) -> Result<Self> {
+        self.0.checked_add(other.0).map(Self).ok_or_else(|| Error::Overflow {
+            operation: format!("{} + {}", self, other),
+        })
+    }
+
+    /// Checked subtraction
+    pub fn checked_sub(self, other: Self) -> Result<Self> {
+        self.0.checked_sub(other.0).map(Self).ok_or_else(|| Error::Underflow {
+            operation: format!("{} - {}", self, other),
+        })
+    }
+
+    /// Subtraction clamped at zero
+    pub fn saturating_sub(self, other: Self) -> Self {
+        Self(self.0.saturating_sub(other.0))
+    }
+
+    /// Product, rounded as `rounding` in the last digit
+    pub fn mul(self, other: Self, rounding: Rounding) -> Result<Self> {
+        mul_div(self.0, other.0, Self::SCALE, rounding).map(Self)
+    }
+
+    /// Quotient, rounded as `rounding` in the last digit
+    pub fn div(self, other: Self, rounding: Rounding) -> Result<Self> {
+        mul_div(self.0, Self::SCALE, other.0, rounding).map(Self)
+    }
+
+    /// `amount * self` as a whole amount, rounded as `rounding`
+    pub fn mul_u64(self, amount: u64, rounding: Rounding) -> Result<u64> {
+        let result = mul_div(self.0, amount as u128, Self::SCALE, rounding)?;
+        u64::try_from(result).map_err(|_| Error::Overflow {
+            operation: format!("{} * {}", amount, self),
+        })
+    }
+
+    /// Integer value, rounded as `rounding`
+    pub fn to_u64(self, rounding: Rounding) -> Result<u64> {
+        let result = mul_div(self.0, 1, Self::SCALE, rounding)?;
+        u64::try_from(result).map_err(|_| Error::Overflow {
+            operation: format!("{} as u64", self),
+        })
+    }
+}
+
+impl fmt::Display for Decimal128 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (whole, fraction) = (self.0 / Self::SCALE, self.0 % Self::SCALE);
+        if fraction == 0 {
+            return write!(f, "{}", whole);
+        }
+        let digits = format!("{:018}", fraction);
+        write!(f, "{}.{}", whole, digits.trim_end_matches('0'))
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mul_div_rounding_exhaustive() {
+        for a in 0..60u128 {
+            for b in 0..60u128 {
+                for c in 1..25u128 {
+                    let (floor, ceil, half_up) = (
+                        mul_div(a, b, c, Rounding::Floor).unwrap(),
+                        mul_div(a, b, c, Rounding::Ceil).unwrap(),
+                        mul_div(a, b, c, Rounding::HalfUp).unwrap(),
+                    );
+                    let exact = a * b % c == 0;
+
+                    assert_eq!(floor, a * b / c);
+                    assert_eq!(ceil, floor + !exact as u128, "ceil({} * {} / {})", a, b, c);
+                    // HalfUp rounds up exactly when the remainder is at least half
+                    assert_eq!(half_up, floor + (2 * (a * b % c) >= c && !exact) as u128);
+                    assert!(floor * c <= a * b && a * b <= ceil * c);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_mul_div_bounds() {
+        assert!(mul_div(1, 1, 0, Rounding::Floor).is_err());
+        assert!(mul_div(u128::MAX, 2, 2, Rounding::Floor).is_err());
+        assert_eq!(mul_div(u128::MAX, 1, 1, Rounding::Ceil).unwrap(), u128::MAX);
+        assert_eq!(mul_div(u128::MAX, 1, 2, Rounding::Ceil).unwrap(), u128::MAX / 2 + 1);
+
+        assert_eq!(mul_div_u64(u64::MAX, u64::MAX, u64::MAX, Rounding::Ceil).unwrap(), u64::MAX);
+        assert!(mul_div_u64(u64::MAX, 2, 1, Rounding::Floor).is_err());
+    }
+
+    #[test]
+    fn test_apply_bps_rounding_exhaustive() {
+        for amount in 0..2_000u64 {
+            for bps in (0..=BPS_DIVISOR).step_by(7) {
+                let floor = apply_bps(amount, bps, Rounding::Floor).unwrap();
+                let ceil = apply_bps(amount, bps, Rounding::Ceil).unwrap();
+                let exact = amount * bps % BPS_DIVISOR == 0;
+
+                assert_eq!(floor, amount * bps / BPS_DIVISOR);
+                assert_eq!(ceil, floor + !exact as u64, "ceil({} at {}bps)", amount, bps);
+                // A user credited the floor and charged the ceil never gains dust
+                assert!(floor + apply_bps(amount, BPS_DIVISOR - bps, Rounding::Ceil).unwrap() >= amount);
+                assert!(ceil + apply_bps(amount, BPS_DIVISOR - bps, Rounding::Floor).unwrap() <= amount + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decimal_arithmetic() {
+        let half = Decimal128::from_bps(5_000);
+        assert_eq!(half, Decimal128::from_percent(50));
+        assert_eq!(half.checked_add(half).unwrap(), Decimal128::ONE);
+        assert!(Decimal128::ZERO.checked_sub(half).is_err());
+        assert!(Decimal128::MAX.checked_add(Decimal128::from_raw(1)).is_err());
+        assert_eq!(half.saturating_sub(Decimal128::ONE), Decimal128::ZERO);
+
+        let third_down = Decimal128::from_ratio(1, 3, Rounding::Floor).unwrap();
+        let third_up = Decimal128::from_ratio(1, 3, Rounding::Ceil).unwrap();
+        assert_eq!(third_up.raw() - third_down.raw(), 1);
+        assert!(Decimal128::from_ratio(1, 0, Rounding::Floor).is_err());
+
+        let two = Decimal128::from_integer(2);
+        assert_eq!(half.mul(two, Rounding::Floor).unwrap(), Decimal128::ONE);
+        assert_eq!(Decimal128::ONE.div(two, Rounding::Floor).unwrap(), half);
+        let almost_one = third_down.mul(Decimal128::from_integer(3), Rounding::Ceil).unwrap();
+        assert_eq!(almost_one.raw(), Decimal128::SCALE - 1);
+        assert!(Decimal128::ONE.div(Decimal128::ZERO, Rounding::Floor).is_err());
+    }
+
+    #[test]
+    fn test_decimal_to_integer() {
+        let cases = [
+            // (raw, floor, ceil, half up)
+            (0, 0, 0, 0),
+            (1, 0, 1, 0),
+            (Decimal128::SCALE / 2 - 1, 0, 1, 0),
+            (Decimal128::SCALE / 2, 0, 1, 1),
+            (Decimal128::SCALE, 1, 1, 1),
+            (Decimal128::SCALE * 5 / 2, 2, 3, 3),
+        ];
+        for (raw, floor, ceil, half_up) in cases {
+            let value = Decimal128::from_raw(raw);
+            assert_eq!(value.to_u64(Rounding::Floor).unwrap(), floor, "floor({})", value);
+            assert_eq!(value.to_u64(Rounding::Ceil).unwrap(), ceil, "ceil({})", value);
+            assert_eq!(value.to_u64(Rounding::HalfUp).unwrap(), half_up, "half up({})", value);
+        }
+        assert!(Decimal128::MAX.to_u64(Rounding::Floor).is_err());
+
+        // 0.5% of $123.45 is 61.725 cents
+        let rate = Decimal128::from_bps(50);
+        assert_eq!(rate.mul_u64(12_345, Rounding::Floor).unwrap(), 61);
+        assert_eq!(rate.mul_u64(12_345, Rounding::Ceil).unwrap(), 62);
+        assert_eq!(rate.mul_u64(12_345, Rounding::HalfUp).unwrap(), 62);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Decimal128::ZERO.to_string(), "0");
+        assert_eq!(Decimal128::from_integer(42).to_string(), "42");
+        assert_eq!(Decimal128::from_bps(125).to_string(), "0.0125");
+        assert_eq!(Decimal128::from_raw(1).to_string(), "0.000000000000000001");
+    }
+}
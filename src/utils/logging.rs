@@ -0,0 +1,81 @@
+//! Log output setup shared by the binaries.
+//!
+//! Both formats honour `RUST_LOG` and include the fields of the enclosing
+//! spans (operation type, CDP, block, RPC request ID) on every line. JSON
+//! output writes one object per line for log shippers.
+
+use std::str::FromStr;
+
+use tracing::Subscriber;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+use crate::error::{Error, Result};
+
+/// How log lines are rendered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(Error::InvalidParameter {
+                name: "log_format".into(),
+                reason: format!("Unknown log format '{}', expected text or json", s),
+            }),
+        }
+    }
+}
+
+/// Filter logging at `default_level` and above unless `RUST_LOG` says otherwise
+pub fn env_filter(default_level: tracing::Level) -> EnvFilter {
+    EnvFilter::from_default_env().add_directive(default_level.into())
+}
+
+/// Log line layer for `format`, for composing with other layers
+pub fn fmt_layer<S>(format: LogFormat) -> Box<dyn Layer<S> + Send + Sync + 'static>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    match format {
+        LogFormat::Text => tracing_subscriber::fmt::layer().boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .boxed(),
+    }
+}
+
+/// Install the global subscriber, logging at `default_level` and above
+/// unless `RUST_LOG` says otherwise
+pub fn init(format: LogFormat, default_level: tracing::Level) {
+    tracing_subscriber::registry()
+        .with(env_filter(default_level))
+        .with(fmt_layer(format))
+        .init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_format_parsing() {
+        assert_eq!("JSON".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!("text".parse::<LogFormat>().unwrap(), LogFormat::default());
+        assert!("yaml".parse::<LogFormat>().is_err());
+    }
+}
@@ -1,74 +1,82 @@
-//! Fixed-point arithmetic and mathematical utilities.
+//! Checked arithmetic and protocol math.
 //!
 //! This module provides safe arithmetic operations with overflow protection
-//! and fixed-point calculations for precise financial computations.
+//! and the ratio and fee calculations built on them. Results that can't be
+//! exact round through [`crate::utils::decimal`]: down when credited to a
+//! user, up when charged to one.
 
 use crate::error::{Error, Result};
-use crate::utils::constants::{BPS_DIVISOR, RATIO_PRECISION, SATS_PER_BTC, ZKUSD_BASE_UNIT};
+use crate::utils::constants::{BPS_DIVISOR, RATIO_PRECISION, SATS_PER_BTC};
+use crate::utils::decimal::{apply_bps, mul_div, mul_div_u64, Decimal128, Rounding};
 use std::ops::{Add, Div, Mul, Sub};
 
 // ═══════════════════════════════════════════════════════════════════════════════
-// FIXED POINT TYPE
+// FIXED POINT TYPE (DEPRECATED)
 // ═══════════════════════════════════════════════════════════════════════════════
 
-/// Fixed-point number with 18 decimal places precision
-/// Used for precise calculations without floating-point errors
+/// Fixed-point number with 18 decimal places precision.
+///
+/// Kept for source compatibility over [`Decimal128`]. Its operators round
+/// down and panic on overflow; new code should use [`Decimal128`] and pick
+/// a [`Rounding`].
+#[deprecated(since = "0.1.0", note = "use `utils::decimal::Decimal128` with an explicit `Rounding`")]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
-pub struct FixedPoint(u128);
+pub struct FixedPoint(Decimal128);
 
+#[allow(deprecated)]
 impl FixedPoint {
     /// Scale factor: 10^18
-    pub const SCALE: u128 = 1_000_000_000_000_000_000;
+    pub const SCALE: u128 = Decimal128::SCALE;
 
     /// Zero value
-    pub const ZERO: Self = Self(0);
+    pub const ZERO: Self = Self(Decimal128::ZERO);
 
     /// One (1.0)
-    pub const ONE: Self = Self(Self::SCALE);
+    pub const ONE: Self = Self(Decimal128::ONE);
 
     /// Create a new FixedPoint from raw value
     pub const fn from_raw(raw: u128) -> Self {
-        Self(raw)
+        Self(Decimal128::from_raw(raw))
     }
 
     /// Create from an integer (scales up)
     pub fn from_integer(value: u64) -> Self {
-        Self((value as u128) * Self::SCALE)
+        Self(Decimal128::from_integer(value))
     }
 
     /// Create from basis points (100 bps = 1%)
     pub fn from_bps(bps: u64) -> Self {
-        Self((bps as u128) * Self::SCALE / (BPS_DIVISOR as u128))
+        Self(Decimal128::from_bps(bps))
     }
 
     /// Create from percentage (100 = 100%)
     pub fn from_percentage(pct: u64) -> Self {
-        Self((pct as u128) * Self::SCALE / 100)
+        Self(Decimal128::from_percent(pct))
     }
 
     /// Get the raw underlying value
     pub fn raw(&self) -> u128 {
-        self.0
+        self.0.raw()
     }
 
     /// Convert to u64, rounding down (truncating)
     pub fn to_u64_floor(&self) -> u64 {
-        (self.0 / Self::SCALE) as u64
+        (self.raw() / Self::SCALE) as u64
     }
 
     /// Convert to u64, rounding up
     pub fn to_u64_ceil(&self) -> u64 {
-        ((self.0 + Self::SCALE - 1) / Self::SCALE) as u64
+        self.raw().div_ceil(Self::SCALE) as u64
     }
 
     /// Convert to u64, rounding to nearest
     pub fn to_u64_round(&self) -> u64 {
-        ((self.0 + Self::SCALE / 2) / Self::SCALE) as u64
+        ((self.raw() + Self::SCALE / 2) / Self::SCALE) as u64
     }
 
     /// Multiply by a u64 value
     pub fn mul_u64(&self, value: u64) -> Self {
-        Self(self.0 * (value as u128))
+        Self::from_raw(self.raw() * (value as u128))
     }
 
     /// Divide by a u64 value
@@ -76,13 +84,13 @@ impl FixedPoint {
         if value == 0 {
             None
         } else {
-            Some(Self(self.0 / (value as u128)))
+            Some(Self::from_raw(self.raw() / (value as u128)))
         }
     }
 
     /// Check if value is zero
     pub fn is_zero(&self) -> bool {
-        self.0 == 0
+        self.0.is_zero()
     }
 
     /// Saturating subtraction
@@ -92,46 +100,62 @@ impl FixedPoint {
 
     /// Minimum of two values
     pub fn min(self, other: Self) -> Self {
-        Self(self.0.min(other.0))
+        Ord::min(self, other)
     }
 
     /// Maximum of two values
     pub fn max(self, other: Self) -> Self {
-        Self(self.0.max(other.0))
+        Ord::max(self, other)
+    }
+}
+
+#[allow(deprecated)]
+impl From<Decimal128> for FixedPoint {
+    fn from(value: Decimal128) -> Self {
+        Self(value)
+    }
+}
+
+#[allow(deprecated)]
+impl From<FixedPoint> for Decimal128 {
+    fn from(value: FixedPoint) -> Self {
+        value.0
     }
 }
 
+#[allow(deprecated)]
 impl Add for FixedPoint {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
-        Self(self.0 + rhs.0)
+        Self(self.0.checked_add(rhs.0).expect("FixedPoint addition overflowed"))
     }
 }
 
+#[allow(deprecated)]
 impl Sub for FixedPoint {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        Self(self.0 - rhs.0)
+        Self(self.0.checked_sub(rhs.0).expect("FixedPoint subtraction underflowed"))
     }
 }
 
+#[allow(deprecated)]
 impl Mul for FixedPoint {
     type Output = Self;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        // Multiply and divide by scale to maintain precision
-        Self((self.0 * rhs.0) / Self::SCALE)
+        Self(self.0.mul(rhs.0, Rounding::Floor).expect("FixedPoint multiplication overflowed"))
     }
 }
 
+#[allow(deprecated)]
 impl Div for FixedPoint {
     type Output = Self;
 
     fn div(self, rhs: Self) -> Self::Output {
-        // Multiply by scale first to maintain precision
-        Self((self.0 * Self::SCALE) / rhs.0)
+        Self(self.0.div(rhs.0, Rounding::Floor).expect("FixedPoint division failed"))
     }
 }
 
@@ -172,39 +196,14 @@ pub fn safe_div(a: u64, b: u64) -> Result<u64> {
 }
 
 /// Safe multiplication then division (for better precision)
-/// Computes (a * b) / c with u128 intermediate to prevent overflow
+/// Computes (a * b) / c with u128 intermediate to prevent overflow, rounding down
 pub fn safe_mul_div(a: u64, b: u64, c: u64) -> Result<u64> {
-    if c == 0 {
-        return Err(Error::InvalidParameter {
-            name: "divisor".into(),
-            reason: "division by zero".into(),
-        });
-    }
-    let result = (a as u128) * (b as u128) / (c as u128);
-    if result > u64::MAX as u128 {
-        return Err(Error::Overflow {
-            operation: format!("({} * {}) / {}", a, b, c),
-        });
-    }
-    Ok(result as u64)
+    mul_div_u64(a, b, c, Rounding::Floor)
 }
 
 /// Safe multiplication then division, rounding up
 pub fn safe_mul_div_up(a: u64, b: u64, c: u64) -> Result<u64> {
-    if c == 0 {
-        return Err(Error::InvalidParameter {
-            name: "divisor".into(),
-            reason: "division by zero".into(),
-        });
-    }
-    let numerator = (a as u128) * (b as u128);
-    let result = (numerator + (c as u128) - 1) / (c as u128);
-    if result > u64::MAX as u128 {
-        return Err(Error::Overflow {
-            operation: format!("ceil(({} * {}) / {})", a, b, c),
-        });
-    }
-    Ok(result as u64)
+    mul_div_u64(a, b, c, Rounding::Ceil)
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -233,16 +232,15 @@ pub fn calculate_collateral_ratio(
     // ratio = collateral_value * 100 / debt_cents
     // Combined: ratio = collateral_sats * btc_price_cents * 100 / (SATS_PER_BTC * debt_cents)
 
-    let numerator = (collateral_sats as u128) * (btc_price_cents as u128) * (RATIO_PRECISION as u128);
+    // Rounded down, so a position never looks healthier than it is
+    let value = (collateral_sats as u128) * (btc_price_cents as u128);
     let denominator = (SATS_PER_BTC as u128) * (debt_cents as u128);
 
-    let ratio = numerator / denominator;
-
-    if ratio > u64::MAX as u128 {
-        return Ok(u64::MAX);
+    match mul_div(value, RATIO_PRECISION as u128, denominator, Rounding::Floor) {
+        Ok(ratio) => Ok(u64::try_from(ratio).unwrap_or(u64::MAX)),
+        Err(Error::Overflow { .. }) => Ok(u64::MAX),
+        Err(e) => Err(e),
     }
-
-    Ok(ratio as u64)
 }
 
 /// Calculate maximum debt for given collateral
@@ -270,18 +268,14 @@ pub fn calculate_max_debt(
     // max_debt = (collateral_sats * btc_price_cents / SATS_PER_BTC) * 100 / min_ratio
     // Combined: max_debt = collateral_sats * btc_price_cents * 100 / (SATS_PER_BTC * min_ratio)
 
-    let numerator = (collateral_sats as u128) * (btc_price_cents as u128) * (RATIO_PRECISION as u128);
+    // Debt capacity is credited to the user, so it rounds down
+    let value = (collateral_sats as u128) * (btc_price_cents as u128);
     let denominator = (SATS_PER_BTC as u128) * (min_ratio as u128);
 
-    let result = numerator / denominator;
-
-    if result > u64::MAX as u128 {
-        return Err(Error::Overflow {
-            operation: "calculate_max_debt".into(),
-        });
-    }
-
-    Ok(result as u64)
+    let result = mul_div(value, RATIO_PRECISION as u128, denominator, Rounding::Floor)?;
+    u64::try_from(result).map_err(|_| Error::Overflow {
+        operation: "calculate_max_debt".into(),
+    })
 }
 
 /// Calculate minimum collateral required for given debt
@@ -309,19 +303,14 @@ pub fn calculate_min_collateral(
     // min_collateral_sats = min_collateral_value * SATS_PER_BTC / btc_price_cents
     // Combined: min_collateral_sats = debt_cents * min_ratio * SATS_PER_BTC / (100 * btc_price_cents)
 
-    let numerator = (debt_cents as u128) * (min_ratio as u128) * (SATS_PER_BTC as u128);
+    let required = (debt_cents as u128) * (min_ratio as u128);
     let denominator = (RATIO_PRECISION as u128) * (btc_price_cents as u128);
 
     // Round up to ensure minimum collateral
-    let result = (numerator + denominator - 1) / denominator;
-
-    if result > u64::MAX as u128 {
-        return Err(Error::Overflow {
-            operation: "calculate_min_collateral".into(),
-        });
-    }
-
-    Ok(result as u64)
+    let result = mul_div(required, SATS_PER_BTC as u128, denominator, Rounding::Ceil)?;
+    u64::try_from(result).map_err(|_| Error::Overflow {
+        operation: "calculate_min_collateral".into(),
+    })
 }
 
 /// Calculate collateral value in cents (USD)
@@ -333,12 +322,13 @@ pub fn calculate_collateral_value(collateral_sats: u64, btc_price_cents: u64) ->
 // FEE CALCULATIONS
 // ═══════════════════════════════════════════════════════════════════════════════
 
-/// Calculate fee in basis points
+/// Fee of `fee_bps` on `amount`, rounded up so a fee is never undercharged.
+/// Shares credited to users use [`apply_bps`] with [`Rounding::Floor`].
 pub fn calculate_fee_bps(amount: u64, fee_bps: u64) -> Result<u64> {
-    safe_mul_div(amount, fee_bps, BPS_DIVISOR)
+    apply_bps(amount, fee_bps, Rounding::Ceil)
 }
 
-/// Calculate amount after fee deduction
+/// Calculate amount after fee deduction, the fee rounded up
 pub fn amount_after_fee(amount: u64, fee_bps: u64) -> Result<u64> {
     let fee = calculate_fee_bps(amount, fee_bps)?;
     safe_sub(amount, fee)
@@ -395,6 +385,23 @@ pub fn median(values: &mut [u64]) -> Option<u64> {
     }
 }
 
+/// Integer square root, rounded down
+pub fn isqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+
+    // Newton's method from an estimate above the root
+    let mut x = 1u128 << ((128 - value.leading_zeros()).div_ceil(2));
+    loop {
+        let next = (x + value / x) / 2;
+        if next >= x {
+            return x;
+        }
+        x = next;
+    }
+}
+
 /// Check if a value is within percentage deviation of a target
 pub fn within_deviation(value: u64, target: u64, max_deviation_bps: u64) -> bool {
     if target == 0 {
@@ -417,6 +424,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(deprecated)]
     fn test_fixed_point_basic() {
         let one = FixedPoint::ONE;
         let two = FixedPoint::from_integer(2);
@@ -425,14 +433,18 @@ mod tests {
         assert_eq!(two - one, one);
         assert_eq!(one * two, two);
         assert_eq!(two / one, two);
+        assert_eq!(Decimal128::from(two), Decimal128::from_integer(2));
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_fixed_point_from_bps() {
         let half = FixedPoint::from_bps(5000); // 50%
         let one = FixedPoint::ONE;
 
         assert_eq!(one * half, FixedPoint::from_raw(FixedPoint::SCALE / 2));
+        assert_eq!(FixedPoint::from_percentage(150).to_u64_round(), 2);
+        assert_eq!(FixedPoint::from_percentage(150).to_u64_floor(), 1);
     }
 
     #[test]
@@ -500,6 +512,46 @@ mod tests {
         // 0.5% fee on $10,000
         let fee = calculate_fee_bps(1_000_000, 50).unwrap();
         assert_eq!(fee, 5_000); // $50
+
+        // 0.5% of $123.45 is 61.725 cents, charged as 62
+        assert_eq!(calculate_fee_bps(12_345, 50).unwrap(), 62);
+        assert_eq!(amount_after_fee(12_345, 50).unwrap(), 12_283);
+        assert_eq!(calculate_fee_bps(1, 1).unwrap(), 1);
+        assert_eq!(calculate_fee_bps(0, 50).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_fee_rounding_exhaustive() {
+        for amount in 0..5_000u64 {
+            for fee_bps in [0, 1, 7, 30, 50, 99, 250, 500, 3_333, BPS_DIVISOR] {
+                let fee = calculate_fee_bps(amount, fee_bps).unwrap();
+                let exact = amount * fee_bps / BPS_DIVISOR;
+
+                // Never undercharged, never by more than a unit
+                assert!(fee * BPS_DIVISOR >= amount * fee_bps);
+                assert!(fee - exact <= 1);
+                assert_eq!(amount_after_fee(amount, fee_bps).unwrap() + fee, amount);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ratio_rounding() {
+        // 1 BTC at $100,000 against $66,667 debt is 149.99...%, reported as 149
+        assert_eq!(calculate_collateral_ratio(SATS_PER_BTC, 10_000_000, 6_666_700).unwrap(), 149);
+        assert_eq!(calculate_collateral_ratio(u64::MAX, u64::MAX, 1).unwrap(), u64::MAX);
+
+        for debt in 1..2_000u64 {
+            // The minimum collateral always supports the debt it was computed for
+            let min_collateral = calculate_min_collateral(debt, 3_000_000, 150).unwrap();
+            assert!(calculate_max_debt(min_collateral, 3_000_000, 150).unwrap() >= debt);
+            assert!(calculate_collateral_ratio(min_collateral, 3_000_000, debt).unwrap() >= 150);
+
+            // and the maximum debt never takes a position below the minimum
+            let collateral = debt * 10_007;
+            let max_debt = calculate_max_debt(collateral, 3_000_000, 150).unwrap();
+            assert!(calculate_collateral_ratio(collateral, 3_000_000, max_debt).unwrap() >= 150);
+        }
     }
 
     #[test]
@@ -517,4 +569,13 @@ mod tests {
         assert!(!within_deviation(106, 100, 500)); // 6% > 5%
         assert!(within_deviation(95, 100, 500)); // -5% deviation
     }
+
+    #[test]
+    fn test_isqrt() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(1), 1);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+    }
 }
@@ -1,17 +1,29 @@
 //! Utility modules for zkUSD protocol.
 //!
 //! This module contains shared utilities used across the protocol:
+//! - Circuit breaker for external services
+//! - Bounded binary encoding
 //! - Cryptographic primitives
-//! - Fixed-point arithmetic
+//! - Fixed-point decimals with explicit rounding
+//! - Checked protocol math
+//! - Undo logs for transactional state
+//! - Log output setup
 //! - Validation helpers
 //! - Constants
 
+pub mod circuit_breaker;
+pub mod codec;
 pub mod constants;
 pub mod crypto;
+pub mod decimal;
+pub mod logging;
 pub mod math;
+pub mod undo;
 pub mod validation;
 
+pub use circuit_breaker::*;
 pub use constants::*;
 pub use crypto::*;
+pub use decimal::*;
 pub use math::*;
 pub use validation::*;
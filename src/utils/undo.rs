@@ -0,0 +1,409 @@
+//! Undo logs for transactional in-memory state.
+//!
+//! A transaction opens an undo log on each large component. While it is
+//! open, every entry the component changes records its prior value, so a
+//! rollback costs time proportional to what the transaction touched rather
+//! than to the size of the state. Small values are simply copied when the
+//! log opens.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+use std::ops::Deref;
+
+/// State that can undo the changes made since `begin_undo`
+pub trait Undo {
+    /// Values saved when the log opens, for state the log does not cover
+    type Mark;
+
+    /// Open an undo log
+    fn begin_undo(&mut self) -> Self::Mark;
+
+    /// Close the log, keeping every change made since it opened
+    fn commit_undo(&mut self);
+
+    /// Close the log, reverting every change made since it opened
+    fn rollback_undo(&mut self, mark: Self::Mark);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// MAPS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Map operations an [`UndoMap`] needs from the collection it wraps
+pub trait MapStore: Default {
+    /// Key type
+    type Key: Clone;
+    /// Value type
+    type Value: Clone;
+
+    /// Value stored under `key`
+    fn lookup(&self, key: &Self::Key) -> Option<&Self::Value>;
+    /// Mutable value stored under `key`
+    fn lookup_mut(&mut self, key: &Self::Key) -> Option<&mut Self::Value>;
+    /// Store `value` under `key`, returning the previous value
+    fn store(&mut self, key: Self::Key, value: Self::Value) -> Option<Self::Value>;
+    /// Remove the value under `key`
+    fn discard(&mut self, key: &Self::Key) -> Option<Self::Value>;
+    /// Keep only the entries `keep` accepts, passing the removed ones to `removed`
+    fn keep(
+        &mut self,
+        keep: impl FnMut(&Self::Key, &Self::Value) -> bool,
+        removed: impl FnMut(&Self::Key, &Self::Value),
+    );
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> MapStore for HashMap<K, V> {
+    type Key = K;
+    type Value = V;
+
+    fn lookup(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn lookup_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.get_mut(key)
+    }
+
+    fn store(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn discard(&mut self, key: &K) -> Option<V> {
+        self.remove(key)
+    }
+
+    fn keep(&mut self, mut keep: impl FnMut(&K, &V) -> bool, mut removed: impl FnMut(&K, &V)) {
+        self.retain(|key, value| {
+            let kept = keep(key, value);
+            if !kept {
+                removed(key, value);
+            }
+            kept
+        });
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> MapStore for BTreeMap<K, V> {
+    type Key = K;
+    type Value = V;
+
+    fn lookup(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn lookup_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.get_mut(key)
+    }
+
+    fn store(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn discard(&mut self, key: &K) -> Option<V> {
+        self.remove(key)
+    }
+
+    fn keep(&mut self, mut keep: impl FnMut(&K, &V) -> bool, mut removed: impl FnMut(&K, &V)) {
+        self.retain(|key, value| {
+            let kept = keep(key, value);
+            if !kept {
+                removed(key, value);
+            }
+            kept
+        });
+    }
+}
+
+/// Prior values recorded by an open log, newest last
+type MapLog<M> = Vec<(<M as MapStore>::Key, Option<<M as MapStore>::Value>)>;
+
+/// A map that records the prior value of each entry it changes while an
+/// undo log is open.
+///
+/// Reads go through `Deref` to the wrapped map; writes must use the methods
+/// here so they are recorded. Serializes exactly like the wrapped map.
+#[derive(Clone)]
+pub struct UndoMap<M: MapStore> {
+    map: M,
+    log: Option<MapLog<M>>,
+}
+
+impl<M: MapStore> UndoMap<M> {
+    /// Create an empty map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mutable access to the value under `key`
+    pub fn get_mut(&mut self, key: &M::Key) -> Option<&mut M::Value> {
+        if let Some(log) = &mut self.log {
+            if let Some(value) = self.map.lookup(key) {
+                log.push((key.clone(), Some(value.clone())));
+            }
+        }
+        self.map.lookup_mut(key)
+    }
+
+    /// Insert `value` under `key`, returning the previous value
+    pub fn insert(&mut self, key: M::Key, value: M::Value) -> Option<M::Value> {
+        match &mut self.log {
+            Some(log) => {
+                let previous = self.map.store(key.clone(), value);
+                log.push((key, previous.clone()));
+                previous
+            }
+            None => self.map.store(key, value),
+        }
+    }
+
+    /// Remove the value under `key`
+    pub fn remove(&mut self, key: &M::Key) -> Option<M::Value> {
+        let removed = self.map.discard(key);
+        if let (Some(log), Some(value)) = (&mut self.log, &removed) {
+            log.push((key.clone(), Some(value.clone())));
+        }
+        removed
+    }
+
+    /// Mutable access to the value under `key`, inserting `default()` first
+    /// if there is none
+    pub fn get_or_insert_with(&mut self, key: M::Key, default: impl FnOnce() -> M::Value) -> &mut M::Value {
+        if self.map.lookup(&key).is_none() {
+            self.insert(key.clone(), default());
+        } else if let Some(log) = &mut self.log {
+            log.push((key.clone(), self.map.lookup(&key).cloned()));
+        }
+        self.map.lookup_mut(&key).expect("entry was just inserted")
+    }
+
+    /// Keep only the entries `keep` accepts
+    pub fn retain(&mut self, keep: impl FnMut(&M::Key, &M::Value) -> bool) {
+        match &mut self.log {
+            Some(log) => self
+                .map
+                .keep(keep, |key, value| log.push((key.clone(), Some(value.clone())))),
+            None => self.map.keep(keep, |_, _| {}),
+        }
+    }
+
+    /// Unwrap the map, dropping any open log
+    pub fn into_inner(self) -> M {
+        self.map
+    }
+
+    /// Whether an undo log is open
+    pub fn is_recording(&self) -> bool {
+        self.log.is_some()
+    }
+
+    /// Open an undo log, discarding any log already open
+    pub fn begin_undo(&mut self) {
+        self.log = Some(Vec::new());
+    }
+
+    /// Close the log, keeping all changes
+    pub fn commit_undo(&mut self) {
+        self.log = None;
+    }
+
+    /// Close the log, restoring every entry changed since it opened
+    pub fn rollback_undo(&mut self) {
+        for (key, previous) in self.log.take().into_iter().flatten().rev() {
+            match previous {
+                Some(value) => self.map.store(key, value),
+                None => self.map.discard(&key),
+            };
+        }
+    }
+}
+
+impl<M: MapStore> Default for UndoMap<M> {
+    fn default() -> Self {
+        Self { map: M::default(), log: None }
+    }
+}
+
+impl<M: MapStore> From<M> for UndoMap<M> {
+    fn from(map: M) -> Self {
+        Self { map, log: None }
+    }
+}
+
+impl<M: MapStore> Deref for UndoMap<M> {
+    type Target = M;
+
+    fn deref(&self) -> &M {
+        &self.map
+    }
+}
+
+impl<M: MapStore + fmt::Debug> fmt::Debug for UndoMap<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+impl<M: MapStore + Serialize> Serialize for UndoMap<M> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.map.serialize(serializer)
+    }
+}
+
+impl<'de, M: MapStore + Deserialize<'de>> Deserialize<'de> for UndoMap<M> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        M::deserialize(deserializer).map(Self::from)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// SETS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// A set that records the entries it adds and removes while an undo log is
+/// open. Serializes exactly like the wrapped set.
+#[derive(Clone)]
+pub struct UndoSet<T> {
+    set: HashSet<T>,
+    /// Entries changed since the log opened, with whether each was present
+    log: Option<Vec<(T, bool)>>,
+}
+
+impl<T: Eq + Hash + Clone> UndoSet<T> {
+    /// Create an empty set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `value`, returning whether it was new
+    pub fn insert(&mut self, value: T) -> bool {
+        let added = self.set.insert(value.clone());
+        if let (Some(log), true) = (&mut self.log, added) {
+            log.push((value, false));
+        }
+        added
+    }
+
+    /// Remove `value`, returning whether it was present
+    pub fn remove(&mut self, value: &T) -> bool {
+        let removed = self.set.remove(value);
+        if let (Some(log), true) = (&mut self.log, removed) {
+            log.push((value.clone(), true));
+        }
+        removed
+    }
+
+    /// Open an undo log, discarding any log already open
+    pub fn begin_undo(&mut self) {
+        self.log = Some(Vec::new());
+    }
+
+    /// Close the log, keeping all changes
+    pub fn commit_undo(&mut self) {
+        self.log = None;
+    }
+
+    /// Close the log, restoring every entry changed since it opened
+    pub fn rollback_undo(&mut self) {
+        for (value, present) in self.log.take().into_iter().flatten().rev() {
+            if present {
+                self.set.insert(value);
+            } else {
+                self.set.remove(&value);
+            }
+        }
+    }
+}
+
+impl<T> Default for UndoSet<T> {
+    fn default() -> Self {
+        Self { set: HashSet::new(), log: None }
+    }
+}
+
+impl<T> Deref for UndoSet<T> {
+    type Target = HashSet<T>;
+
+    fn deref(&self) -> &HashSet<T> {
+        &self.set
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for UndoSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.set.fmt(f)
+    }
+}
+
+impl<T: Serialize> Serialize for UndoSet<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.set.serialize(serializer)
+    }
+}
+
+impl<'de, T: Eq + Hash + Deserialize<'de>> Deserialize<'de> for UndoSet<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        HashSet::deserialize(deserializer).map(|set| Self { set, log: None })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rollback_restores_touched_entries() {
+        let mut map: UndoMap<HashMap<u32, u64>> = [(1, 10), (2, 20), (3, 30)].into_iter().collect::<HashMap<_, _>>().into();
+        map.begin_undo();
+        *map.get_mut(&1).unwrap() += 1;
+        *map.get_mut(&1).unwrap() += 1;
+        map.insert(4, 40);
+        map.remove(&2);
+        *map.get_or_insert_with(5, || 0) += 5;
+        map.retain(|_, value| *value != 30);
+        assert_eq!(map.len(), 3);
+
+        map.rollback_undo();
+        assert!(!map.is_recording());
+        let expected: HashMap<u32, u64> = [(1, 10), (2, 20), (3, 30)].into_iter().collect();
+        assert_eq!(*map, expected);
+    }
+
+    #[test]
+    fn test_commit_keeps_changes_and_stops_recording() {
+        let mut map: UndoMap<BTreeMap<u32, u64>> = UndoMap::new();
+        map.begin_undo();
+        map.insert(1, 10);
+        map.commit_undo();
+
+        map.insert(2, 20);
+        map.rollback_undo();
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn test_set_rollback() {
+        let mut set = UndoSet::new();
+        set.insert(1);
+        set.begin_undo();
+        set.insert(2);
+        set.remove(&1);
+        set.insert(1);
+        set.rollback_undo();
+        assert_eq!(*set, [1].into_iter().collect());
+    }
+
+    #[test]
+    fn test_serializes_like_the_wrapped_map() {
+        let plain: BTreeMap<u32, u64> = [(1, 10), (2, 20)].into_iter().collect();
+        let mut map = UndoMap::from(plain.clone());
+        map.begin_undo();
+        map.insert(3, 30);
+        map.rollback_undo();
+        assert_eq!(bincode::serialize(&map).unwrap(), bincode::serialize(&plain).unwrap());
+
+        let decoded: UndoMap<BTreeMap<u32, u64>> = bincode::deserialize(&bincode::serialize(&plain).unwrap()).unwrap();
+        assert_eq!(*decoded, plain);
+    }
+}
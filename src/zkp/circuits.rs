@@ -7,9 +7,8 @@
 use serde::{Deserialize, Serialize};
 
 use crate::error::{Error, Result};
-use crate::utils::constants::{RATIO_PRECISION, SATS_PER_BTC};
 use crate::utils::crypto::Hash;
-use crate::utils::math::{safe_mul_div, calculate_collateral_ratio};
+use crate::utils::math::{calculate_collateral_ratio, calculate_liquidation_amounts};
 use crate::zkp::inputs::*;
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -406,6 +405,8 @@ pub struct LiquidationOutput {
     pub debt_covered: u64,
     /// Collateral seized
     pub collateral_seized: u64,
+    /// Penalty applied in basis points
+    pub penalty_bps: u64,
     /// Transition hash
     pub transition_hash: Hash,
 }
@@ -444,20 +445,24 @@ impl Circuit for LiquidationCircuit {
             });
         }
 
-        // Constraint 3: Collateral seized calculation
-        // collateral_seized = (debt / price) * (1 + bonus)
-        let expected_collateral = safe_mul_div(
+        // Constraint 3: Collateral seized follows the penalty curve
+        // collateral_seized = min(collateral, (debt / price) * (1 + penalty(mcr - ratio)))
+        public.penalty_curve.validate()?;
+        let penalty_bps = public.penalty_curve.penalty_bps(ratio, public.mcr, public.max_penalty_bps);
+        let (_, expected_collateral, _) = calculate_liquidation_amounts(
+            private.collateral,
             private.debt,
-            SATS_PER_BTC,
             public.btc_price,
+            penalty_bps,
         )?;
 
-        // Allow some margin for rounding
-        let max_collateral = expected_collateral.saturating_mul(115).saturating_div(100);
-        if public.collateral_seized > max_collateral || public.collateral_seized > private.collateral {
+        if public.collateral_seized != expected_collateral {
             return Err(Error::InvalidParameter {
                 name: "collateral_seized".into(),
-                reason: "Collateral seized exceeds allowed amount".into(),
+                reason: format!(
+                    "Collateral seized {} does not match {} at a {}bps penalty",
+                    public.collateral_seized, expected_collateral, penalty_bps
+                ),
             });
         }
 
@@ -481,6 +486,7 @@ impl Circuit for LiquidationCircuit {
             ratio_at_liquidation: ratio,
             debt_covered: public.debt_covered,
             collateral_seized: public.collateral_seized,
+            penalty_bps,
             transition_hash: Hash::sha256(&data),
         })
     }
@@ -682,6 +688,11 @@ impl CircuitRegistry {
         self.circuits.iter().find(|c| c.id == id)
     }
 
+    /// Current version of a circuit; circuits outside the registry are at version 1
+    pub fn version(&self, id: &str) -> u32 {
+        self.find(id).map_or(1, |c| c.version)
+    }
+
     /// Total constraint count for all circuits
     pub fn total_constraints(&self) -> usize {
         self.circuits.iter().map(|c| c.constraints).sum()
@@ -696,6 +707,8 @@ impl CircuitRegistry {
 mod tests {
     use super::*;
     use crate::core::cdp::CDPId;
+    use crate::liquidation::engine::PenaltyCurve;
+    use crate::utils::constants::{LIQUIDATION_BONUS_BPS, RATIO_PRECISION};
     use crate::utils::crypto::{KeyPair, Signature};
 
     fn test_keypair() -> KeyPair {
@@ -813,6 +826,8 @@ mod tests {
             cdp_id,
             btc_price: 5_000_000, // $50k (price dropped!)
             mcr: 150 * RATIO_PRECISION / 100, // 150%
+            penalty_curve: PenaltyCurve::default(),
+            max_penalty_bps: LIQUIDATION_BONUS_BPS,
             debt_covered: 5_000_000, // $50k
             collateral_seized: 100_000_000, // 1 BTC
             block_height: 100,
@@ -836,6 +851,42 @@ mod tests {
         assert!(output.ratio_at_liquidation < public.mcr);
     }
 
+    #[test]
+    fn test_liquidation_circuit_follows_penalty_curve() {
+        let keypair = test_keypair();
+
+        // 108% against a 110% MCR: two points deep, a 6% penalty
+        let mut public = LiquidationPublicInputs {
+            state_root_before: Hash::sha256(b"before"),
+            state_root_after: Hash::sha256(b"after"),
+            cdp_id: CDPId::generate(keypair.public_key(), 1),
+            btc_price: 5_400_000,
+            mcr: 110,
+            penalty_curve: PenaltyCurve::default(),
+            max_penalty_bps: LIQUIDATION_BONUS_BPS,
+            debt_covered: 5_000_000,
+            collateral_seized: 98_148_148,
+            block_height: 100,
+        };
+        let private = LiquidationPrivateInputs {
+            cdp_owner: *keypair.public_key(),
+            collateral: 100_000_000,
+            debt: 5_000_000,
+            ratio: 108,
+            liquidator: *test_keypair().public_key(),
+            liquidator_signature: Signature::new([0u8; 64]),
+            merkle_proof: MerkleProof::empty(),
+            sp_total_deposits: None,
+        };
+
+        let output = LiquidationCircuit::execute(&public, &private).unwrap();
+        assert_eq!(output.penalty_bps, 600);
+
+        // Seizing the whole CDP at the old fixed 10% bonus no longer passes
+        public.collateral_seized = 100_000_000;
+        assert!(LiquidationCircuit::execute(&public, &private).is_err());
+    }
+
     #[test]
     fn test_liquidation_circuit_healthy_cdp() {
         let keypair = test_keypair();
@@ -848,6 +899,8 @@ mod tests {
             cdp_id,
             btc_price: 10_000_000, // $100k
             mcr: 150 * RATIO_PRECISION / 100,
+            penalty_curve: PenaltyCurve::default(),
+            max_penalty_bps: LIQUIDATION_BONUS_BPS,
             debt_covered: 5_000_000,
             collateral_seized: 100_000_000,
             block_height: 100,
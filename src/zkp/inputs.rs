@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use crate::core::cdp::CDPId;
 use crate::core::token::TokenAmount;
 use crate::core::vault::CollateralAmount;
+use crate::liquidation::engine::PenaltyCurve;
 use crate::utils::crypto::{Hash, PublicKey, Signature};
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -56,6 +57,10 @@ pub struct LiquidationPublicInputs {
     pub btc_price: u64,
     /// Minimum collateralization ratio used
     pub mcr: u64,
+    /// Liquidation penalty by depth below the MCR
+    pub penalty_curve: PenaltyCurve,
+    /// Cap on the penalty in basis points
+    pub max_penalty_bps: u64,
     /// Debt amount covered (cents)
     pub debt_covered: u64,
     /// Collateral seized (sats)
@@ -305,6 +310,61 @@ impl MerkleProof {
     pub fn depth(&self) -> usize {
         self.path.len()
     }
+
+    /// Build the proof for `leaves[index]` in the tree of [`merkle_root`]
+    pub fn build(leaves: &[Hash], index: usize) -> Option<Self> {
+        let leaf = *leaves.get(index)?;
+        let mut level = leaves.to_vec();
+        let mut index = index;
+        let mut path = Vec::new();
+
+        while level.len() > 1 {
+            let sibling = if index % 2 == 0 {
+                // The last node of an odd level pairs with itself
+                level.get(index + 1).unwrap_or(&level[index])
+            } else {
+                &level[index - 1]
+            };
+            path.push(MerkleNode {
+                hash: *sibling,
+                is_left: index % 2 == 1,
+            });
+
+            level = merkle_level(&level);
+            index /= 2;
+        }
+
+        Some(Self {
+            leaf,
+            path,
+            root: level[0],
+        })
+    }
+}
+
+/// Root of the Merkle tree over `leaves`; odd levels pair their last node
+/// with itself, and an empty tree has a zero root
+pub fn merkle_root(leaves: &[Hash]) -> Hash {
+    if leaves.is_empty() {
+        return Hash::zero();
+    }
+
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = merkle_level(&level);
+    }
+    level[0]
+}
+
+/// Hash each pair of nodes into the next level up
+fn merkle_level(level: &[Hash]) -> Vec<Hash> {
+    level
+        .chunks(2)
+        .map(|pair| {
+            let right = pair.get(1).unwrap_or(&pair[0]);
+            Hash::sha256(&[pair[0].as_bytes().as_slice(), right.as_bytes()].concat())
+        })
+        .collect()
 }
 
 /// Single node in a merkle proof path
@@ -466,6 +526,20 @@ mod tests {
     use super::*;
     use crate::utils::crypto::KeyPair;
 
+    #[test]
+    fn test_merkle_proof_build() {
+        let leaves: Vec<Hash> = (0u8..5).map(|i| Hash::sha256(&[i])).collect();
+        let root = merkle_root(&leaves);
+
+        for index in 0..leaves.len() {
+            let proof = MerkleProof::build(&leaves, index).unwrap();
+            assert_eq!(proof.root, root);
+            assert!(proof.verify());
+        }
+        assert!(MerkleProof::build(&leaves, 5).is_none());
+        assert_eq!(merkle_root(&[]), Hash::zero());
+    }
+
     #[test]
     fn test_merkle_proof_empty() {
         let proof = MerkleProof::empty();
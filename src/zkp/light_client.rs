@@ -0,0 +1,264 @@
+//! Light-client verification.
+//!
+//! Lets wallets check a CDP's collateral and debt against a block header
+//! without running a node. Nothing here touches storage, the prover or the
+//! network, so it works with `default-features = false`.
+//!
+//! A [`StateHeader`] commits to the CDP state root and to the aggregated
+//! proof of the block's transitions. The client verifies the aggregated
+//! proof against the header, then verifies the CDP's Merkle inclusion in
+//! the state root.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use zkusd::zkp::light_client::LightClient;
+//!
+//! let client = LightClient::default();
+//! let cdp = client.verify_cdp(&header, &batch, &inclusion)?;
+//! println!("{} sats backing {} cents", cdp.collateral.sats(), cdp.debt.cents());
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::cdp::{CDPId, CDPStatus, CDP};
+use crate::core::token::TokenAmount;
+use crate::core::vault::CollateralAmount;
+use crate::error::{Error, Result};
+use crate::utils::crypto::{Hash, PublicKey};
+use crate::zkp::inputs::{merkle_root, MerkleProof};
+use crate::zkp::verifier::{NativeVerifier, ProofBatch, Verifier};
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// HEADERS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// What a block commits to for light clients
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateHeader {
+    /// Block height
+    pub block_height: u64,
+    /// Block timestamp
+    pub timestamp: u64,
+    /// Merkle root over all CDPs (see [`cdp_state_root`])
+    pub state_root: Hash,
+    /// Hash of the aggregated proof of the block's transitions
+    pub proofs_hash: Hash,
+}
+
+impl StateHeader {
+    /// Header for a block whose CDPs are `cdps` and whose transitions are proven by `batch`
+    pub fn new(block_height: u64, timestamp: u64, cdps: &[&CDP], batch: &ProofBatch) -> Self {
+        Self {
+            block_height,
+            timestamp,
+            state_root: cdp_state_root(cdps),
+            proofs_hash: batch.batch_hash,
+        }
+    }
+
+    /// Hash of the header
+    pub fn hash(&self) -> Hash {
+        let mut data = Vec::with_capacity(80);
+        data.extend_from_slice(&self.block_height.to_le_bytes());
+        data.extend_from_slice(&self.timestamp.to_le_bytes());
+        data.extend_from_slice(self.state_root.as_bytes());
+        data.extend_from_slice(self.proofs_hash.as_bytes());
+        Hash::sha256(&data)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// CDP INCLUSION
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// A CDP together with its Merkle path to the state root
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdpInclusion {
+    /// CDP state being proven
+    pub cdp: CDP,
+    /// Path from the CDP's leaf to the state root
+    pub proof: MerkleProof,
+}
+
+/// Merkle root over `cdps`, with leaves ordered by CDP id
+pub fn cdp_state_root(cdps: &[&CDP]) -> Hash {
+    merkle_root(&sorted_leaves(cdps).1)
+}
+
+/// Inclusion proof for CDP `id` in the root of [`cdp_state_root`]
+pub fn cdp_inclusion(cdps: &[&CDP], id: &CDPId) -> Option<CdpInclusion> {
+    let (sorted, leaves) = sorted_leaves(cdps);
+    let index = sorted.iter().position(|cdp| cdp.id == *id)?;
+
+    Some(CdpInclusion {
+        cdp: sorted[index].clone(),
+        proof: MerkleProof::build(&leaves, index)?,
+    })
+}
+
+fn sorted_leaves<'a>(cdps: &[&'a CDP]) -> (Vec<&'a CDP>, Vec<Hash>) {
+    let mut sorted = cdps.to_vec();
+    sorted.sort_by(|a, b| a.id.as_bytes().cmp(b.id.as_bytes()));
+    let leaves = sorted.iter().map(|cdp| cdp.state_hash()).collect();
+    (sorted, leaves)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// LIGHT CLIENT
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// CDP position confirmed against a header
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifiedCdp {
+    /// CDP identifier
+    pub id: CDPId,
+    /// CDP owner
+    pub owner: PublicKey,
+    /// Locked collateral
+    pub collateral: CollateralAmount,
+    /// Outstanding debt
+    pub debt: TokenAmount,
+    /// CDP status
+    pub status: CDPStatus,
+    /// Block the position was confirmed at
+    pub block_height: u64,
+}
+
+/// Verification-only client
+pub struct LightClient {
+    /// Verifier for the aggregated proofs
+    verifier: Box<dyn Verifier>,
+}
+
+impl Default for LightClient {
+    fn default() -> Self {
+        Self::new(NativeVerifier::new())
+    }
+}
+
+impl LightClient {
+    /// Create a client checking proofs with `verifier`
+    pub fn new(verifier: impl Verifier + 'static) -> Self {
+        Self {
+            verifier: Box::new(verifier),
+        }
+    }
+
+    /// Check that `batch` is the aggregated proof committed to by `header`
+    /// and that every proof in it verifies
+    pub fn verify_batch(&self, header: &StateHeader, batch: &ProofBatch) -> Result<()> {
+        if batch.is_empty() {
+            return Err(Error::InvalidStateProof("aggregated proof is empty".into()));
+        }
+        if ProofBatch::new(batch.proofs.clone()).batch_hash != batch.batch_hash {
+            return Err(Error::InvalidStateProof("aggregated proof hash mismatch".into()));
+        }
+        if batch.batch_hash != header.proofs_hash {
+            return Err(Error::InvalidStateProof("aggregated proof not committed to by header".into()));
+        }
+
+        for (index, proof) in batch.proofs.iter().enumerate() {
+            let result = self.verifier.verify(proof)?;
+            if !result.valid {
+                return Err(Error::InvalidStateProof(format!(
+                    "proof {} ({}) rejected: {}",
+                    index,
+                    proof.circuit_id,
+                    result.error.unwrap_or_default()
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that `inclusion` places its CDP under the header's state root
+    pub fn verify_inclusion(&self, header: &StateHeader, inclusion: &CdpInclusion) -> Result<()> {
+        if inclusion.proof.leaf != inclusion.cdp.state_hash() {
+            return Err(Error::InvalidStateProof("leaf does not match CDP state".into()));
+        }
+        if inclusion.proof.root != header.state_root {
+            return Err(Error::InvalidStateProof("proof is for a different state root".into()));
+        }
+        if !inclusion.proof.verify() {
+            return Err(Error::InvalidStateProof("Merkle path does not reach the root".into()));
+        }
+
+        Ok(())
+    }
+
+    /// Verify a CDP's collateral and debt as of `header`
+    pub fn verify_cdp(
+        &self,
+        header: &StateHeader,
+        batch: &ProofBatch,
+        inclusion: &CdpInclusion,
+    ) -> Result<VerifiedCdp> {
+        self.verify_batch(header, batch)?;
+        self.verify_inclusion(header, inclusion)?;
+
+        let cdp = &inclusion.cdp;
+        Ok(VerifiedCdp {
+            id: cdp.id,
+            owner: cdp.owner,
+            collateral: CollateralAmount::from_sats(cdp.collateral_sats),
+            debt: TokenAmount::from_cents(cdp.debt_cents),
+            status: cdp.status,
+            block_height: header.block_height,
+        })
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::crypto::KeyPair;
+    use crate::zkp::inputs::{PriceAttestationPublicInputs, PricePrivateInputs, SourcePrice};
+    use crate::zkp::prover::{NativeProver, Prover};
+
+    fn proof_batch() -> ProofBatch {
+        let oracle = KeyPair::generate();
+        let public = PriceAttestationPublicInputs {
+            price: 10_000_000,
+            timestamp: 1_000,
+            source_count: 1,
+            deviation_bps: 0,
+            oracle_pubkey: *oracle.public_key(),
+            signature: oracle.sign(&Hash::sha256(b"price")),
+        };
+        let private = PricePrivateInputs {
+            source_prices: vec![SourcePrice { source_id: 0, price: 10_000_000, timestamp: 1_000, weight: 1 }],
+            oracle_signature_data: Vec::new(),
+        };
+        ProofBatch::new(vec![NativeProver::new().prove_price_attestation(&public, &private).unwrap()])
+    }
+
+    #[test]
+    fn test_verify_cdp() {
+        let cdps: Vec<CDP> = (0..3)
+            .map(|i| CDP::with_collateral(*KeyPair::generate().public_key(), 100_000_000 + i, i, 100).unwrap())
+            .collect();
+        let refs: Vec<&CDP> = cdps.iter().collect();
+        let batch = proof_batch();
+        let header = StateHeader::new(100, 1_000, &refs, &batch);
+        let client = LightClient::default();
+
+        let inclusion = cdp_inclusion(&refs, &cdps[1].id).unwrap();
+        let verified = client.verify_cdp(&header, &batch, &inclusion).unwrap();
+        assert_eq!(verified.collateral.sats(), 100_000_001);
+        assert_eq!(verified.block_height, 100);
+
+        // Inflated collateral no longer matches the leaf
+        let mut forged = inclusion.clone();
+        forged.cdp.collateral_sats *= 2;
+        assert!(matches!(client.verify_cdp(&header, &batch, &forged), Err(Error::InvalidStateProof(_))));
+
+        // A batch the header does not commit to is rejected
+        assert!(client.verify_batch(&header, &proof_batch()).is_err());
+    }
+}
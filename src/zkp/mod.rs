@@ -9,6 +9,12 @@
 //! - **Native**: For testing, executes circuits without ZK
 //! - **SP1**: Production-grade zkVM from Succinct Labs
 //!
+//! Verification keys are versioned per circuit; [`vk_export`] turns them
+//! into BitcoinOS and EVM verifier artifacts.
+//!
+//! Wallets that only need to check CDP state against block headers can use
+//! [`light_client`], which has no storage or prover dependencies.
+//!
 //! ## Usage
 //!
 //! ```rust,ignore
@@ -24,12 +30,15 @@
 
 pub mod circuits;
 pub mod inputs;
+pub mod light_client;
 pub mod prover;
 pub mod sp1_prover;
 pub mod verifier;
+pub mod vk_export;
 
 pub use circuits::*;
 pub use inputs::*;
+pub use light_client::{CdpInclusion, LightClient, StateHeader, VerifiedCdp};
 pub use prover::*;
-pub use sp1_prover::{SP1Prover, SP1ProverConfig, SP1Verifier, ElfRegistry};
+pub use sp1_prover::{SP1Prover, SP1ProverConfig, SP1Verifier, ElfInfo, ElfRegistry, ElfSource};
 pub use verifier::*;
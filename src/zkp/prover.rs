@@ -57,7 +57,7 @@ impl ZKProof {
 }
 
 /// Proof metadata
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProofMetadata {
     /// Time taken to generate proof (milliseconds)
     pub generation_time_ms: u64,
@@ -65,6 +65,25 @@ pub struct ProofMetadata {
     pub constraint_count: usize,
     /// Prover version
     pub prover_version: String,
+    /// Version of the circuit the proof was generated against
+    #[serde(default = "default_circuit_version")]
+    pub circuit_version: u32,
+}
+
+impl Default for ProofMetadata {
+    fn default() -> Self {
+        Self {
+            generation_time_ms: 0,
+            constraint_count: 0,
+            prover_version: String::new(),
+            circuit_version: default_circuit_version(),
+        }
+    }
+}
+
+/// Proofs predating circuit versioning were all generated against version 1
+fn default_circuit_version() -> u32 {
+    1
 }
 
 /// Supported prover backends
@@ -189,6 +208,7 @@ impl NativeProver {
                 generation_time_ms: generation_time.as_millis() as u64,
                 constraint_count: 0, // Native doesn't count constraints
                 prover_version: self.version.clone(),
+                circuit_version: CircuitRegistry::new().version(circuit_id),
             },
         })
     }
@@ -744,6 +764,7 @@ mod tests {
                 generation_time_ms: 100,
                 constraint_count: 1000,
                 prover_version: "v1".to_string(),
+                circuit_version: 1,
             },
         };
 
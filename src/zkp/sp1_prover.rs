@@ -25,7 +25,7 @@
 //! ```
 
 #[cfg(feature = "sp1-prover")]
-use sp1_sdk::{ProverClient, SP1Stdin, SP1ProofWithPublicValues};
+use sp1_sdk::{HashableKey, ProverClient, SP1Stdin, SP1ProofWithPublicValues};
 
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -45,6 +45,7 @@ use crate::zkp::inputs::{
     ProofInputs, ProofType, OperationType,
 };
 use crate::zkp::prover::{Prover, ProverBackend, ProofMetadata, ZKProof};
+use crate::zkp::verifier::VerificationKey;
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // SP1 PROVER CONFIGURATION
@@ -108,11 +109,53 @@ impl SP1ProverConfig {
 // ELF REGISTRY
 // ═══════════════════════════════════════════════════════════════════════════════
 
+/// Guest ELFs compiled by `build.rs` and embedded in the binary
+/// (`guest-elfs` feature), keyed by circuit ID
+#[cfg(feature = "guest-elfs")]
+pub const EMBEDDED_ELFS: &[(&str, &[u8])] = &[
+    ("zkusd_deposit_v1", sp1_sdk::include_elf!("zkusd_deposit_v1")),
+    ("zkusd_withdraw_v1", sp1_sdk::include_elf!("zkusd_withdraw_v1")),
+    ("zkusd_mint_v1", sp1_sdk::include_elf!("zkusd_mint_v1")),
+    ("zkusd_repay_v1", sp1_sdk::include_elf!("zkusd_repay_v1")),
+    ("zkusd_liquidation_v1", sp1_sdk::include_elf!("zkusd_liquidation_v1")),
+    ("zkusd_price_attestation_v1", sp1_sdk::include_elf!("zkusd_price_attestation_v1")),
+];
+
+/// Guest ELFs embedded in the binary (none without `guest-elfs`)
+#[cfg(not(feature = "guest-elfs"))]
+pub const EMBEDDED_ELFS: &[(&str, &[u8])] = &[];
+
+/// Where a registered ELF came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ElfSource {
+    /// Compiled into the binary
+    Embedded,
+    /// Registered at runtime
+    Registered,
+    /// Loaded from the ELF directory
+    File(PathBuf),
+}
+
+/// Summary of a registered ELF
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ElfInfo {
+    /// Circuit identifier
+    pub circuit_id: String,
+    /// Where the ELF came from
+    pub source: ElfSource,
+    /// ELF size in bytes
+    pub size: usize,
+    /// SHA-256 of the ELF, identifying the guest program version
+    pub hash: Hash,
+}
+
 /// Registry for guest program ELF binaries
 #[derive(Debug)]
 pub struct ElfRegistry {
     /// Loaded ELF binaries by circuit ID
     elfs: HashMap<String, Vec<u8>>,
+    /// Origin of each loaded ELF
+    sources: HashMap<String, ElfSource>,
     /// ELF directory
     directory: PathBuf,
 }
@@ -122,10 +165,29 @@ impl ElfRegistry {
     pub fn new(directory: impl Into<PathBuf>) -> Self {
         Self {
             elfs: HashMap::new(),
+            sources: HashMap::new(),
             directory: directory.into(),
         }
     }
 
+    /// Create a registry preloaded with the embedded ELFs; the directory
+    /// is still searched for circuits that are not embedded
+    pub fn with_embedded(directory: impl Into<PathBuf>) -> Self {
+        let mut registry = Self::new(directory);
+        for (circuit_id, elf) in EMBEDDED_ELFS {
+            registry.elfs.insert(circuit_id.to_string(), elf.to_vec());
+            registry.sources.insert(circuit_id.to_string(), ElfSource::Embedded);
+        }
+        registry
+    }
+
+    /// Register an ELF for a circuit, replacing any previous one
+    pub fn register(&mut self, circuit_id: impl Into<String>, elf: Vec<u8>) {
+        let circuit_id = circuit_id.into();
+        self.sources.insert(circuit_id.clone(), ElfSource::Registered);
+        self.elfs.insert(circuit_id, elf);
+    }
+
     /// Load an ELF binary for a circuit
     pub fn load(&mut self, circuit_id: &str) -> Result<&[u8]> {
         if !self.elfs.contains_key(circuit_id) {
@@ -137,10 +199,36 @@ impl ElfRegistry {
                 }
             })?;
             self.elfs.insert(circuit_id.to_string(), elf_data);
+            self.sources.insert(circuit_id.to_string(), ElfSource::File(elf_path));
         }
         Ok(self.elfs.get(circuit_id).unwrap())
     }
 
+    /// Version hash of a circuit's ELF
+    pub fn elf_hash(&mut self, circuit_id: &str) -> Result<Hash> {
+        Ok(Hash::sha256(self.load(circuit_id)?))
+    }
+
+    /// Summary of a circuit's ELF
+    pub fn info(&mut self, circuit_id: &str) -> Result<ElfInfo> {
+        let elf = self.load(circuit_id)?;
+        let (size, hash) = (elf.len(), Hash::sha256(elf));
+
+        Ok(ElfInfo {
+            circuit_id: circuit_id.to_string(),
+            source: self.sources[circuit_id].clone(),
+            size,
+            hash,
+        })
+    }
+
+    /// Summaries of all available ELFs, sorted by circuit ID
+    pub fn entries(&mut self) -> Result<Vec<ElfInfo>> {
+        let mut circuits = self.available_circuits();
+        circuits.sort();
+        circuits.iter().map(|circuit_id| self.info(circuit_id)).collect()
+    }
+
     /// Check if ELF exists for circuit
     pub fn has_elf(&self, circuit_id: &str) -> bool {
         self.elfs.contains_key(circuit_id) ||
@@ -209,7 +297,7 @@ impl SP1Prover {
             ProverClient::builder().build()
         };
 
-        let elf_registry = ElfRegistry::new(&config.elf_directory);
+        let elf_registry = ElfRegistry::with_embedded(&config.elf_directory);
 
         // Create cache directory if needed
         if config.cache_proofs {
@@ -229,7 +317,7 @@ impl SP1Prover {
     /// Create a new SP1 prover (stub when feature is disabled)
     #[cfg(not(feature = "sp1-prover"))]
     pub fn new(config: SP1ProverConfig) -> Result<Self> {
-        let elf_registry = ElfRegistry::new(&config.elf_directory);
+        let elf_registry = ElfRegistry::with_embedded(&config.elf_directory);
 
         Ok(Self {
             config,
@@ -298,6 +386,7 @@ impl SP1Prover {
                 generation_time_ms: generation_time.as_millis() as u64,
                 constraint_count: 0, // SP1 doesn't expose this directly
                 prover_version: self.version.clone(),
+                circuit_version: CircuitRegistry::new().version(circuit_id),
             },
         })
     }
@@ -326,6 +415,39 @@ impl SP1Prover {
     pub fn available_circuits(&self) -> Vec<String> {
         self.elf_registry.available_circuits()
     }
+
+    /// Summaries of the ELFs this prover can use
+    pub fn elfs(&mut self) -> Result<Vec<ElfInfo>> {
+        self.elf_registry.entries()
+    }
+
+    /// Verification key hash (`bytes32`) of a circuit, as committed to by
+    /// on-chain SP1 verifiers
+    #[cfg(feature = "sp1-prover")]
+    pub fn verification_key(&mut self, circuit_id: &str) -> Result<String> {
+        let elf = self.elf_registry.load(circuit_id)?;
+        let (_pk, vk) = self.client.setup(elf);
+        Ok(vk.bytes32())
+    }
+
+    /// Verification key hash (stub when feature is disabled)
+    #[cfg(not(feature = "sp1-prover"))]
+    pub fn verification_key(&mut self, _circuit_id: &str) -> Result<String> {
+        Err(Error::InvalidParameter {
+            name: "sp1-prover".into(),
+            reason: "SP1 prover feature not enabled. Rebuild with --features sp1-prover".into(),
+        })
+    }
+
+    /// Program key of a circuit at its current version, for registration
+    /// and export
+    pub fn export_verification_key(&mut self, circuit_id: &str) -> Result<VerificationKey> {
+        let vkey = self.verification_key(circuit_id)?;
+        let key_data = hex::decode(vkey.trim_start_matches("0x"))
+            .map_err(|e| Error::Serialization(format!("Invalid verification key {}: {}", vkey, e)))?;
+
+        Ok(VerificationKey::new(circuit_id, CircuitRegistry::new().version(circuit_id), key_data))
+    }
 }
 
 #[cfg(feature = "sp1-prover")]
@@ -536,7 +658,7 @@ impl SP1Verifier {
     #[cfg(feature = "sp1-prover")]
     pub fn new(elf_directory: impl Into<PathBuf>) -> Self {
         Self {
-            elf_registry: ElfRegistry::new(elf_directory),
+            elf_registry: ElfRegistry::with_embedded(elf_directory),
             client: ProverClient::builder().build(),
         }
     }
@@ -544,7 +666,7 @@ impl SP1Verifier {
     #[cfg(not(feature = "sp1-prover"))]
     pub fn new(elf_directory: impl Into<PathBuf>) -> Self {
         Self {
-            elf_registry: ElfRegistry::new(elf_directory),
+            elf_registry: ElfRegistry::with_embedded(elf_directory),
         }
     }
 
@@ -624,6 +746,30 @@ mod tests {
         assert!(registry.available_circuits().is_empty());
     }
 
+    #[test]
+    fn test_elf_registry_register() {
+        let mut registry = ElfRegistry::new("./nonexistent");
+        registry.register("zkusd_deposit_v1", b"deposit elf".to_vec());
+
+        assert!(registry.has_elf("zkusd_deposit_v1"));
+        assert_eq!(registry.elf_hash("zkusd_deposit_v1").unwrap(), Hash::sha256(b"deposit elf"));
+
+        let entries = registry.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].source, ElfSource::Registered);
+        assert_eq!(entries[0].size, 11);
+        assert!(registry.elf_hash("zkusd_mint_v1").is_err());
+    }
+
+    #[test]
+    fn test_embedded_elfs_registered() {
+        let registry = ElfRegistry::with_embedded("./nonexistent");
+        assert_eq!(registry.available_circuits().len(), EMBEDDED_ELFS.len());
+        for (circuit_id, _) in EMBEDDED_ELFS {
+            assert!(CircuitRegistry::new().find(circuit_id).is_some());
+        }
+    }
+
     #[test]
     #[cfg(not(feature = "sp1-prover"))]
     fn test_sp1_prover_disabled() {
@@ -4,7 +4,7 @@
 //! check that proofs are valid without learning any private information.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::time::Instant;
 
 use crate::error::{Error, Result};
@@ -461,10 +461,10 @@ impl VerificationKey {
     }
 }
 
-/// Registry of verification keys
+/// Registry of verification keys, keeping every version of each circuit's key
 #[derive(Debug, Clone, Default)]
 pub struct VerificationKeyRegistry {
-    keys: HashMap<String, VerificationKey>,
+    keys: HashMap<String, BTreeMap<u32, VerificationKey>>,
 }
 
 impl VerificationKeyRegistry {
@@ -475,14 +475,31 @@ impl VerificationKeyRegistry {
         }
     }
 
-    /// Register a verification key
+    /// Register a verification key; the highest version of a circuit's
+    /// keys is the current one
     pub fn register(&mut self, key: VerificationKey) {
-        self.keys.insert(key.circuit_id.clone(), key);
+        self.keys
+            .entry(key.circuit_id.clone())
+            .or_default()
+            .insert(key.version, key);
     }
 
-    /// Get verification key for circuit
+    /// Get the current verification key for circuit
     pub fn get(&self, circuit_id: &str) -> Option<&VerificationKey> {
-        self.keys.get(circuit_id)
+        self.keys.get(circuit_id)?.values().next_back()
+    }
+
+    /// Get a specific version of a circuit's key
+    pub fn get_version(&self, circuit_id: &str, version: u32) -> Option<&VerificationKey> {
+        self.keys.get(circuit_id)?.get(&version)
+    }
+
+    /// Registered key versions of a circuit, oldest first
+    pub fn versions(&self, circuit_id: &str) -> Vec<u32> {
+        self.keys
+            .get(circuit_id)
+            .map(|keys| keys.keys().copied().collect())
+            .unwrap_or_default()
     }
 
     /// Check if circuit is registered
@@ -494,6 +511,94 @@ impl VerificationKeyRegistry {
     pub fn circuits(&self) -> impl Iterator<Item = &str> {
         self.keys.keys().map(|s| s.as_str())
     }
+
+    /// Current keys of all circuits, sorted by circuit ID
+    pub fn current_keys(&self) -> Vec<&VerificationKey> {
+        let mut keys: Vec<&VerificationKey> = self.keys.values().filter_map(|keys| keys.values().next_back()).collect();
+        keys.sort_by(|a, b| a.circuit_id.cmp(&b.circuit_id));
+        keys
+    }
+
+    /// Check that `proof` was generated against the current key of its circuit.
+    ///
+    /// Circuits without a registered key are not checked.
+    pub fn check_version(&self, proof: &ZKProof) -> Result<()> {
+        let Some(key) = self.get(&proof.circuit_id) else {
+            return Ok(());
+        };
+
+        if key.version != proof.metadata.circuit_version {
+            return Err(Error::CircuitVersionMismatch {
+                circuit_id: proof.circuit_id.clone(),
+                expected: key.version,
+                actual: proof.metadata.circuit_version,
+            });
+        }
+        if !key.verify_integrity() {
+            return Err(Error::Internal(format!(
+                "Verification key for {} v{} is corrupted",
+                key.circuit_id, key.version
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Verifier that refuses proofs generated against a circuit version other
+/// than the current registered key before delegating to `inner`
+pub struct VersionedVerifier<V: Verifier> {
+    /// Underlying verifier
+    inner: V,
+    /// Keys the proofs must match
+    keys: VerificationKeyRegistry,
+}
+
+impl<V: Verifier> VersionedVerifier<V> {
+    /// Wrap `inner`, checking proofs against `keys`
+    pub fn new(inner: V, keys: VerificationKeyRegistry) -> Self {
+        Self { inner, keys }
+    }
+
+    /// Registered keys
+    pub fn keys(&self) -> &VerificationKeyRegistry {
+        &self.keys
+    }
+
+    fn check(&self, proof: &ZKProof) -> Option<VerificationResult> {
+        match self.keys.check_version(proof) {
+            Ok(()) => None,
+            Err(e) => Some(VerificationResult::failure(e.to_string())),
+        }
+    }
+}
+
+impl<V: Verifier> Verifier for VersionedVerifier<V> {
+    fn verify(&self, proof: &ZKProof) -> Result<VerificationResult> {
+        match self.check(proof) {
+            Some(failure) => Ok(failure),
+            None => self.inner.verify(proof),
+        }
+    }
+
+    fn verify_with_inputs(
+        &self,
+        proof: &ZKProof,
+        public_inputs: &[u8],
+    ) -> Result<VerificationResult> {
+        match self.check(proof) {
+            Some(failure) => Ok(failure),
+            None => self.inner.verify_with_inputs(proof, public_inputs),
+        }
+    }
+
+    fn backend(&self) -> ProverBackend {
+        self.inner.backend()
+    }
+
+    fn supports_circuit(&self, circuit_id: &str) -> bool {
+        self.inner.supports_circuit(circuit_id)
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -646,6 +751,45 @@ mod tests {
         assert!(registry.get("nonexistent").is_none());
     }
 
+    #[test]
+    fn test_key_versions() {
+        let mut registry = VerificationKeyRegistry::new();
+        registry.register(VerificationKey::new("zkusd_deposit_v1", 2, vec![2]));
+        registry.register(VerificationKey::new("zkusd_deposit_v1", 1, vec![1]));
+
+        assert_eq!(registry.versions("zkusd_deposit_v1"), vec![1, 2]);
+        assert_eq!(registry.get("zkusd_deposit_v1").unwrap().version, 2);
+        assert_eq!(registry.get_version("zkusd_deposit_v1", 1).unwrap().key_data, vec![1]);
+    }
+
+    #[test]
+    fn test_versioned_verifier_rejects_mismatch() {
+        let proof = ZKProof {
+            proof_type: ProofType::CDPTransition,
+            circuit_id: DepositCircuit::circuit_id().to_string(),
+            proof_data: vec![1, 2, 3, 4],
+            public_inputs_hash: Hash::sha256(b"test"),
+            timestamp: 1234567890,
+            backend: ProverBackend::Native,
+            metadata: ProofMetadata::default(),
+        };
+        assert_eq!(proof.metadata.circuit_version, 1);
+
+        let mut keys = VerificationKeyRegistry::new();
+        keys.register(VerificationKey::new(proof.circuit_id.clone(), 1, vec![1]));
+        let verifier = VersionedVerifier::new(NativeVerifier::new(), keys.clone());
+        assert!(verifier.verify(&proof).unwrap().valid);
+
+        // Rotating the key to v2 refuses proofs against v1
+        keys.register(VerificationKey::new(proof.circuit_id.clone(), 2, vec![2]));
+        let verifier = VersionedVerifier::new(NativeVerifier::new(), keys.clone());
+        assert!(!verifier.verify(&proof).unwrap().valid);
+        assert!(matches!(
+            keys.check_version(&proof),
+            Err(Error::CircuitVersionMismatch { expected: 2, actual: 1, .. })
+        ));
+    }
+
     #[test]
     fn test_batch_verification_result() {
         let results = vec![
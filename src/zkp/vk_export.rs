@@ -0,0 +1,215 @@
+//! Verifier artifact export.
+//!
+//! Turns the current keys of a [`VerificationKeyRegistry`] into artifacts
+//! for on-chain verifiers:
+//!
+//! - **BitcoinOS**: a JSON key set listing each circuit's program key and
+//!   version, loaded by the BitcoinOS verifier
+//! - **EVM**: a Solidity contract pinning each circuit's SP1 program key,
+//!   for verifying zkUSD proofs on bridged chains through Succinct's
+//!   `ISP1Verifier` gateway
+//!
+//! Keys are expected to hold the 32-byte SP1 program key (see
+//! [`SP1Prover::export_verification_key`](crate::zkp::SP1Prover::export_verification_key)).
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use zkusd::zkp::vk_export::{export, VerifierTarget};
+//!
+//! let solidity = export(&registry, VerifierTarget::Evm)?;
+//! std::fs::write("ZkUsdVerifier.sol", solidity)?;
+//! ```
+
+use std::fmt::Write as _;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::zkp::verifier::{VerificationKey, VerificationKeyRegistry};
+
+/// Consumer of exported verifier artifacts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifierTarget {
+    /// JSON key set for BitcoinOS
+    BitcoinOs,
+    /// Solidity verifier contract
+    Evm,
+}
+
+impl FromStr for VerifierTarget {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "bitcoinos" | "bos" => Ok(Self::BitcoinOs),
+            "evm" | "solidity" => Ok(Self::Evm),
+            _ => Err(Error::InvalidParameter {
+                name: "target".into(),
+                reason: format!("Unknown verifier target '{}', expected bitcoinos or evm", s),
+            }),
+        }
+    }
+}
+
+/// A circuit's key as exported
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedKey {
+    /// Circuit identifier
+    pub circuit_id: String,
+    /// Key version proofs must be generated against
+    pub version: u32,
+    /// Program verification key (`0x`-prefixed hex)
+    pub program_vkey: String,
+    /// SHA-256 of the key data (hex)
+    pub key_hash: String,
+}
+
+impl From<&VerificationKey> for ExportedKey {
+    fn from(key: &VerificationKey) -> Self {
+        Self {
+            circuit_id: key.circuit_id.clone(),
+            version: key.version,
+            program_vkey: format!("0x{}", hex::encode(&key.key_data)),
+            key_hash: key.key_hash.to_hex(),
+        }
+    }
+}
+
+/// Key set consumed by the BitcoinOS verifier
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifierKeySet {
+    /// Protocol version the keys were exported from
+    pub protocol_version: String,
+    /// Current key of every circuit, sorted by circuit ID
+    pub keys: Vec<ExportedKey>,
+}
+
+impl VerifierKeySet {
+    /// Key set of the current keys in `registry`
+    pub fn from_registry(registry: &VerificationKeyRegistry) -> Self {
+        Self {
+            protocol_version: crate::VERSION.to_string(),
+            keys: registry.current_keys().into_iter().map(ExportedKey::from).collect(),
+        }
+    }
+}
+
+/// Export the current keys of `registry` for `target`
+pub fn export(registry: &VerificationKeyRegistry, target: VerifierTarget) -> Result<String> {
+    let keys = registry.current_keys();
+    if keys.is_empty() {
+        return Err(Error::InvalidParameter {
+            name: "registry".into(),
+            reason: "No verification keys registered".into(),
+        });
+    }
+    if let Some(key) = keys.iter().find(|key| !key.verify_integrity()) {
+        return Err(Error::Internal(format!(
+            "Verification key for {} v{} is corrupted",
+            key.circuit_id, key.version
+        )));
+    }
+
+    match target {
+        VerifierTarget::BitcoinOs => serde_json::to_string_pretty(&VerifierKeySet::from_registry(registry))
+            .map_err(|e| Error::Serialization(format!("Failed to encode key set: {}", e))),
+        VerifierTarget::Evm => solidity_verifier(&keys),
+    }
+}
+
+/// Solidity contract exposing one verify function per circuit
+fn solidity_verifier(keys: &[&VerificationKey]) -> Result<String> {
+    let mut out = String::new();
+    let _ = writeln!(out, "// SPDX-License-Identifier: MIT");
+    let _ = writeln!(out, "// Generated by zkusd {}. Do not edit.", crate::VERSION);
+    let _ = writeln!(out, "pragma solidity ^0.8.20;");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "interface ISP1Verifier {{");
+    let _ = writeln!(
+        out,
+        "    function verifyProof(bytes32 programVKey, bytes calldata publicValues, bytes calldata proofBytes) external view;"
+    );
+    let _ = writeln!(out, "}}");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "contract ZkUsdVerifier {{");
+    let _ = writeln!(out, "    ISP1Verifier public immutable verifier;");
+    let _ = writeln!(out);
+
+    for key in keys {
+        if key.key_data.len() != 32 {
+            return Err(Error::InvalidParameter {
+                name: "key_data".into(),
+                reason: format!(
+                    "{} key is {} bytes, EVM export needs a 32-byte SP1 program key",
+                    key.circuit_id,
+                    key.key_data.len()
+                ),
+            });
+        }
+        let name = key.circuit_id.to_ascii_uppercase();
+        let _ = writeln!(out, "    bytes32 public constant {}_VKEY = 0x{};", name, hex::encode(&key.key_data));
+        let _ = writeln!(out, "    uint32 public constant {}_VERSION = {};", name, key.version);
+    }
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "    constructor(address _verifier) {{");
+    let _ = writeln!(out, "        verifier = ISP1Verifier(_verifier);");
+    let _ = writeln!(out, "    }}");
+
+    for key in keys {
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "    function verify_{}(bytes calldata publicValues, bytes calldata proofBytes) external view {{",
+            key.circuit_id
+        );
+        let _ = writeln!(
+            out,
+            "        verifier.verifyProof({}_VKEY, publicValues, proofBytes);",
+            key.circuit_id.to_ascii_uppercase()
+        );
+        let _ = writeln!(out, "    }}");
+    }
+
+    let _ = writeln!(out, "}}");
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> VerificationKeyRegistry {
+        let mut registry = VerificationKeyRegistry::new();
+        registry.register(VerificationKey::new("zkusd_mint_v1", 1, vec![0x11; 32]));
+        registry.register(VerificationKey::new("zkusd_deposit_v1", 1, vec![0x22; 32]));
+        registry.register(VerificationKey::new("zkusd_deposit_v1", 2, vec![0x33; 32]));
+        registry
+    }
+
+    #[test]
+    fn test_export_bitcoinos() {
+        let json = export(&registry(), VerifierTarget::BitcoinOs).unwrap();
+        let set: VerifierKeySet = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(set.keys.len(), 2);
+        assert_eq!(set.keys[0].circuit_id, "zkusd_deposit_v1");
+        assert_eq!(set.keys[0].version, 2);
+        assert_eq!(set.keys[0].program_vkey, format!("0x{}", "33".repeat(32)));
+    }
+
+    #[test]
+    fn test_export_evm() {
+        let solidity = export(&registry(), VerifierTarget::Evm).unwrap();
+        assert!(solidity.contains(&format!("ZKUSD_DEPOSIT_V1_VKEY = 0x{};", "33".repeat(32))));
+        assert!(solidity.contains("ZKUSD_DEPOSIT_V1_VERSION = 2;"));
+        assert!(solidity.contains("function verify_zkusd_mint_v1("));
+
+        let mut short = VerificationKeyRegistry::new();
+        short.register(VerificationKey::new("zkusd_mint_v1", 1, vec![1, 2, 3]));
+        assert!(export(&short, VerifierTarget::Evm).is_err());
+        assert_eq!("bitcoinos".parse::<VerifierTarget>().unwrap(), VerifierTarget::BitcoinOs);
+    }
+}